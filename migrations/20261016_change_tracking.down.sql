@@ -0,0 +1,16 @@
+DROP INDEX IF EXISTS idx_deletions_deleted_at;
+DROP INDEX IF EXISTS idx_relations_updated_at;
+DROP INDEX IF EXISTS idx_entities_updated_at;
+DROP TRIGGER IF EXISTS relations_record_deletion ON relations;
+DROP TRIGGER IF EXISTS entities_record_deletion ON entities;
+DROP TRIGGER IF EXISTS relation_properties_touch ON relation_properties;
+DROP TRIGGER IF EXISTS entity_properties_touch ON entity_properties;
+DROP TRIGGER IF EXISTS relations_touch ON relations;
+DROP TRIGGER IF EXISTS entities_touch ON entities;
+DROP FUNCTION IF EXISTS record_relation_deletion();
+DROP FUNCTION IF EXISTS record_entity_deletion();
+DROP FUNCTION IF EXISTS touch_relation_of_property();
+DROP FUNCTION IF EXISTS touch_entity_of_property();
+DROP FUNCTION IF EXISTS touch_updated_at();
+DROP TABLE IF EXISTS deletions;
+ALTER TABLE relations DROP COLUMN IF EXISTS updated_at;
@@ -0,0 +1,82 @@
+-- Change tracking for delta exports (GET /api/data/export?since=)
+--
+-- updated_at is maintained by triggers so every write path counts: an entity
+-- is touched when its row or any of its properties change, a relation when
+-- its row or properties change. Deletions leave a tombstone.
+
+ALTER TABLE relations ADD COLUMN updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
+
+CREATE TABLE deletions (
+    id          BIGINT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    record_kind TEXT NOT NULL,          -- 'entity' or 'relation'
+    record_id   BIGINT NOT NULL,
+    reference   TEXT NOT NULL,          -- "type:name" or "relation_type source -> target"
+    deleted_at  TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_entities_updated_at ON entities(updated_at);
+CREATE INDEX idx_relations_updated_at ON relations(updated_at);
+CREATE INDEX idx_deletions_deleted_at ON deletions(deleted_at);
+
+CREATE FUNCTION touch_updated_at() RETURNS TRIGGER AS $$
+BEGIN
+    NEW.updated_at = NOW();
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER entities_touch BEFORE UPDATE ON entities
+    FOR EACH ROW EXECUTE FUNCTION touch_updated_at();
+CREATE TRIGGER relations_touch BEFORE UPDATE ON relations
+    FOR EACH ROW EXECUTE FUNCTION touch_updated_at();
+
+CREATE FUNCTION touch_entity_of_property() RETURNS TRIGGER AS $$
+BEGIN
+    UPDATE entities SET updated_at = NOW()
+     WHERE id = CASE WHEN TG_OP = 'DELETE' THEN OLD.entity_id ELSE NEW.entity_id END
+       AND updated_at <> NOW();
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER entity_properties_touch AFTER INSERT OR UPDATE OR DELETE ON entity_properties
+    FOR EACH ROW EXECUTE FUNCTION touch_entity_of_property();
+
+CREATE FUNCTION touch_relation_of_property() RETURNS TRIGGER AS $$
+BEGIN
+    UPDATE relations SET updated_at = NOW()
+     WHERE id = CASE WHEN TG_OP = 'DELETE' THEN OLD.relation_id ELSE NEW.relation_id END
+       AND updated_at <> NOW();
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER relation_properties_touch AFTER INSERT OR UPDATE OR DELETE ON relation_properties
+    FOR EACH ROW EXECUTE FUNCTION touch_relation_of_property();
+
+CREATE FUNCTION record_entity_deletion() RETURNS TRIGGER AS $$
+BEGIN
+    INSERT INTO deletions (record_kind, record_id, reference)
+    VALUES ('entity', OLD.id, OLD.entity_type || ':' || OLD.name);
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER entities_record_deletion AFTER DELETE ON entities
+    FOR EACH ROW EXECUTE FUNCTION record_entity_deletion();
+
+-- Relations removed by an entity's cascade are recorded too; the endpoints
+-- may already be gone, so the reference falls back to their ids.
+CREATE FUNCTION record_relation_deletion() RETURNS TRIGGER AS $$
+BEGIN
+    INSERT INTO deletions (record_kind, record_id, reference)
+    VALUES ('relation', OLD.id,
+        COALESCE((SELECT name FROM entities WHERE id = OLD.relation_type_id), OLD.relation_type_id::TEXT) || ' ' ||
+        COALESCE((SELECT entity_type || ':' || name FROM entities WHERE id = OLD.source_id), OLD.source_id::TEXT) || ' -> ' ||
+        COALESCE((SELECT entity_type || ':' || name FROM entities WHERE id = OLD.target_id), OLD.target_id::TEXT));
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER relations_record_deletion AFTER DELETE ON relations
+    FOR EACH ROW EXECUTE FUNCTION record_relation_deletion();
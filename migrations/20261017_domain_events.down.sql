@@ -0,0 +1,3 @@
+DROP TRIGGER IF EXISTS domain_events_append_only ON domain_events;
+DROP FUNCTION IF EXISTS reject_domain_event_change();
+DROP TABLE IF EXISTS domain_events;
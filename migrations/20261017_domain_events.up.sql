@@ -0,0 +1,28 @@
+-- Append-only stream of domain events (proposal.submitted, meeting.confirmed,
+-- decision.recorded, ...), written in the same transaction as the change they
+-- describe and read by integrations through GET /api/v1/events?after=.
+
+CREATE TABLE domain_events (
+    id             BIGINT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    event_type     TEXT NOT NULL,
+    aggregate_type TEXT NOT NULL,
+    aggregate_id   BIGINT NOT NULL,
+    actor_id       BIGINT NOT NULL DEFAULT 0,
+    payload        JSONB NOT NULL DEFAULT '{}',
+    occurred_at    TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    -- Writing transaction, so readers can hold back events whose id was
+    -- allocated by a transaction that has not committed yet
+    transaction_id XID8 NOT NULL DEFAULT pg_current_xact_id()
+);
+
+CREATE INDEX idx_domain_events_type ON domain_events(event_type);
+CREATE INDEX idx_domain_events_aggregate ON domain_events(aggregate_type, aggregate_id);
+
+CREATE FUNCTION reject_domain_event_change() RETURNS TRIGGER AS $$
+BEGIN
+    RAISE EXCEPTION 'domain_events is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER domain_events_append_only BEFORE UPDATE OR DELETE ON domain_events
+    FOR EACH ROW EXECUTE FUNCTION reject_domain_event_change();
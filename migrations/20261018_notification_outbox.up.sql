@@ -0,0 +1,19 @@
+-- Outbox of pending notification deliveries (live pushes now, e-mail and
+-- outbound webhooks as they are added). Rows are written in the same
+-- transaction as the notification itself and drained by a dispatcher, so a
+-- delivery that fails while a transport is down is retried, not lost.
+
+CREATE TABLE notification_outbox (
+    id           BIGINT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    channel      TEXT NOT NULL,
+    payload      JSONB NOT NULL DEFAULT '{}',
+    status       TEXT NOT NULL DEFAULT 'pending',   -- pending, sending, delivered, failed
+    attempts     INTEGER NOT NULL DEFAULT 0,
+    last_error   TEXT NOT NULL DEFAULT '',
+    available_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    claimed_at   TIMESTAMPTZ,
+    delivered_at TIMESTAMPTZ,
+    created_at   TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_notification_outbox_due ON notification_outbox(status, available_at);
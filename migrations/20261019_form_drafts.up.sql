@@ -0,0 +1,15 @@
+-- In-progress edits of long forms, snapshotted by the browser while the user
+-- types. One row per user and form; a successful submit of the form removes
+-- it. If the session expires mid-edit, the user is offered the snapshot when
+-- they open the form again after signing back in.
+
+CREATE TABLE form_drafts (
+    id         BIGINT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    user_id    BIGINT NOT NULL REFERENCES entities(id) ON DELETE CASCADE,
+    form_key   TEXT NOT NULL,                -- path the form posts to
+    page_path  TEXT NOT NULL,                -- page the form lives on
+    title      TEXT NOT NULL DEFAULT '',
+    fields     JSONB NOT NULL DEFAULT '{}',
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (user_id, form_key)
+);
@@ -0,0 +1,18 @@
+-- Server-held HMAC keys, one active key per purpose. Rotating a purpose
+-- issues a new active key and moves the old one to 'retiring': it no longer
+-- signs but still verifies until it is retired, by hand or once the grace
+-- period has passed. Signatures carry the kid of the key that made them.
+
+CREATE TABLE signing_keys (
+    id         BIGINT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    purpose    TEXT NOT NULL,
+    kid        TEXT NOT NULL UNIQUE,
+    secret     TEXT NOT NULL,
+    status     TEXT NOT NULL DEFAULT 'active'
+               CHECK (status IN ('active', 'retiring', 'retired')),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    rotated_at TIMESTAMPTZ,
+    retired_at TIMESTAMPTZ
+);
+
+CREATE UNIQUE INDEX signing_keys_one_active ON signing_keys (purpose) WHERE status = 'active';
@@ -0,0 +1,15 @@
+-- Nightly SLA compliance per ToR and metric, measured against the ToR's
+-- SLA policy pack over a trailing window. Rows are replaced wholesale on
+-- each run; computed_on is the UTC day of the run.
+
+CREATE TABLE sla_compliance (
+    tor_id      BIGINT NOT NULL REFERENCES entities(id) ON DELETE CASCADE,
+    metric      TEXT NOT NULL,
+    pack_id     BIGINT NOT NULL REFERENCES entities(id) ON DELETE CASCADE,
+    limit_days  BIGINT NOT NULL,
+    met         BIGINT NOT NULL DEFAULT 0,
+    breached    BIGINT NOT NULL DEFAULT 0,
+    pending     BIGINT NOT NULL DEFAULT 0,
+    computed_on DATE NOT NULL,
+    PRIMARY KEY (tor_id, metric)
+);
@@ -0,0 +1,15 @@
+-- Who opened which item of a classified ToR, recorded while read auditing
+-- (`audit.read_mode`) is on. The viewer's id and username are copied rather
+-- than referenced so the trail outlives deleted accounts.
+
+CREATE TABLE access_log (
+    id        BIGINT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    entity_id BIGINT NOT NULL REFERENCES entities(id) ON DELETE CASCADE,
+    tor_id    BIGINT NOT NULL REFERENCES entities(id) ON DELETE CASCADE,
+    user_id   BIGINT NOT NULL,
+    username  TEXT NOT NULL,
+    viewed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_access_log_entity ON access_log(entity_id, viewed_at DESC);
+CREATE INDEX idx_access_log_viewed ON access_log(viewed_at);
@@ -0,0 +1,15 @@
+-- Soft-deleted entities. The entity row, its properties and the relations
+-- touching it are kept as one JSON snapshot until restored or purged; the
+-- originals are removed, so queries over `entities` never see them.
+
+CREATE TABLE trash (
+    id          BIGINT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    entity_id   BIGINT NOT NULL,
+    entity_type TEXT NOT NULL,
+    name        TEXT NOT NULL,
+    label       TEXT NOT NULL,
+    snapshot    JSONB NOT NULL,
+    deleted_at  TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_trash_deleted_at ON trash(deleted_at);
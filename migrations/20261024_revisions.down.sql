@@ -0,0 +1,15 @@
+-- Revisions of proposals and COAs have no earlier form and are dropped.
+
+DELETE FROM entities rev
+WHERE rev.entity_type = 'revision'
+  AND NOT EXISTS (
+      SELECT 1 FROM relations r JOIN entities t ON t.id = r.target_id
+      WHERE r.source_id = rev.id AND t.entity_type = 'minutes_section'
+  );
+
+UPDATE entity_properties
+SET key = 'content', value = COALESCE(value::JSONB -> 0 ->> 1, '')
+WHERE key = 'snapshot'
+  AND entity_id IN (SELECT id FROM entities WHERE entity_type = 'revision');
+
+UPDATE entities SET entity_type = 'minutes_revision' WHERE entity_type = 'revision';
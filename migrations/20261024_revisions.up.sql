@@ -0,0 +1,10 @@
+-- Minutes section revisions become general `revision` entities, which keep
+-- every tracked field of an edited entity as a JSON list of [key, value]
+-- pairs in `snapshot` instead of a single `content` property.
+
+UPDATE entity_properties
+SET key = 'snapshot', value = jsonb_build_array(jsonb_build_array('content', value))::TEXT
+WHERE key = 'content'
+  AND entity_id IN (SELECT id FROM entities WHERE entity_type = 'minutes_revision');
+
+UPDATE entities SET entity_type = 'revision' WHERE entity_type = 'minutes_revision';
@@ -0,0 +1,14 @@
+-- Device and address pairs each user has signed in from, so a sign-in
+-- from one not seen before can be reported to the user.
+
+CREATE TABLE sign_in_devices (
+    id         BIGINT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    user_id    BIGINT NOT NULL REFERENCES entities(id) ON DELETE CASCADE,
+    device     TEXT NOT NULL,
+    ip         TEXT NOT NULL,
+    first_seen TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_seen  TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (user_id, device, ip)
+);
+
+CREATE INDEX idx_sign_in_devices_user ON sign_in_devices(user_id, last_seen DESC);
@@ -0,0 +1,12 @@
+-- Hashes of passwords users have replaced, so the password policy can
+-- refuse reusing recent ones. The newest row also dates the current
+-- password for the maximum-age rule.
+
+CREATE TABLE password_history (
+    id         BIGINT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    user_id    BIGINT NOT NULL REFERENCES entities(id) ON DELETE CASCADE,
+    hash       TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_password_history_user ON password_history(user_id, id DESC);
@@ -0,0 +1,11 @@
+-- Failed password sign-ins per account, counted over a sliding window to
+-- lock accounts under guessing from any number of addresses.
+
+CREATE TABLE login_failures (
+    id           BIGINT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    user_id      BIGINT NOT NULL REFERENCES entities(id) ON DELETE CASCADE,
+    ip           TEXT NOT NULL,
+    attempted_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_login_failures_user ON login_failures(user_id, attempted_at);
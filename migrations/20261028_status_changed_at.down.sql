@@ -0,0 +1,3 @@
+DROP TRIGGER IF EXISTS entity_status_changed ON entity_properties;
+DROP FUNCTION IF EXISTS record_status_change();
+DELETE FROM entity_properties WHERE key = 'status_changed_at';
@@ -0,0 +1,30 @@
+-- Time of the last status change, for timed workflow auto-transitions.
+--
+-- A trigger keeps a `status_changed_at` property next to every `status`
+-- property, so every write path counts. Rewriting the same status leaves
+-- the time alone.
+
+CREATE FUNCTION record_status_change() RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'INSERT' OR OLD.value IS DISTINCT FROM NEW.value THEN
+        INSERT INTO entity_properties (entity_id, key, value)
+        VALUES (NEW.entity_id, 'status_changed_at', NOW()::TEXT)
+        ON CONFLICT (entity_id, key) DO UPDATE SET value = excluded.value;
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER entity_status_changed AFTER INSERT OR UPDATE ON entity_properties
+    FOR EACH ROW WHEN (NEW.key = 'status') EXECUTE FUNCTION record_status_change();
+
+-- Existing entities count from their last update. The backfill must not
+-- look like activity, so it does not touch updated_at.
+ALTER TABLE entity_properties DISABLE TRIGGER entity_properties_touch;
+INSERT INTO entity_properties (entity_id, key, value)
+SELECT p.entity_id, 'status_changed_at', e.updated_at::TEXT
+FROM entity_properties p
+JOIN entities e ON e.id = p.entity_id
+WHERE p.key = 'status'
+ON CONFLICT (entity_id, key) DO NOTHING;
+ALTER TABLE entity_properties ENABLE TRIGGER entity_properties_touch;
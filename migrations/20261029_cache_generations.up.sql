@@ -0,0 +1,10 @@
+-- Generation counters for in-memory caches.
+--
+-- Writers bump a cache's row; every replica compares its cached generation
+-- against the row before serving, so a change made through one replica
+-- reaches the others on their next read.
+
+CREATE TABLE cache_generations (
+    name       TEXT PRIMARY KEY,
+    generation BIGINT NOT NULL DEFAULT 1
+);
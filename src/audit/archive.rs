@@ -0,0 +1,41 @@
+//! Audit archives: the audit trail for a period as one zip.
+//!
+//! The archive holds the database audit entries for the period
+//! (`entries.jsonl`), every daily audit log file in the period that exists
+//! on disk, and a `SHA256SUMS` manifest of them.
+
+use chrono::NaiveDate;
+use sqlx::PgPool;
+
+use super::AuditError;
+use crate::models::{audit, export_record};
+
+/// Longest period one archive may cover.
+pub const MAX_DAYS: i64 = 366;
+
+/// Archive file name, e.g. `audit-2026-01-01-to-2026-01-31.zip`.
+pub fn file_name(from: NaiveDate, to: NaiveDate) -> String {
+    format!("audit-{from}-to-{to}.zip")
+}
+
+/// Build the archive for `from..=to`.
+pub async fn build(pool: &PgPool, from: NaiveDate, to: NaiveDate) -> Result<Vec<u8>, AuditError> {
+    let mut entries = String::new();
+    for entry in audit::find_between(pool, from, to).await? {
+        entries.push_str(&serde_json::to_string(&entry)?);
+        entries.push('\n');
+    }
+    let mut files = vec![("entries.jsonl".to_string(), entries.into_bytes())];
+
+    let log_dir = super::log_dir(pool).await;
+    for day in from.iter_days().take_while(|d| *d <= to) {
+        let name = format!("audit-{day}.jsonl");
+        if let Ok(contents) = std::fs::read(log_dir.join(&name)) {
+            files.push((format!("logs/{name}"), contents));
+        }
+    }
+
+    let folder = file_name(from, to).trim_end_matches(".zip").to_string();
+    export_record::zip_with_manifest(&folder, &files)
+        .map_err(|e| AuditError::File(std::io::Error::other(e)))
+}
@@ -0,0 +1,208 @@
+//! Tamper-evident hash chains over the audit trail.
+//!
+//! The database entries and the daily log files each form one chain. Every
+//! link stores `prev_hash`, the hash of the entry before it, and `hash`, the
+//! SHA-256 of its own canonical JSON (keys sorted, `prev_hash` included,
+//! `hash` left out). Editing an entry breaks its own hash; deleting or
+//! reordering one breaks the next entry's `prev_hash`. The log file chain
+//! runs across days: the first line of a file links to the last line of
+//! the previous file.
+//!
+//! Entries written before the chain existed carry no hash and are counted
+//! as unchained while they precede the first link. Retention cleanup removes
+//! the oldest database entries, so the first remaining link is taken as the
+//! anchor rather than checked against the genesis hash.
+
+use std::sync::LazyLock;
+
+use serde::Serialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use tokio::sync::Mutex;
+
+use super::AuditError;
+
+/// `prev_hash` of the very first link.
+pub const GENESIS: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Serialises appends so two writers cannot link to the same predecessor.
+pub(crate) static APPEND_LOCK: LazyLock<Mutex<()>> = LazyLock::new(|| Mutex::new(()));
+
+/// Hash of an entry: SHA-256 of its canonical JSON without `hash`.
+pub fn entry_hash(entry: &Value) -> String {
+    let mut entry = entry.clone();
+    if let Some(map) = entry.as_object_mut() {
+        map.remove("hash");
+    }
+    hex::encode(Sha256::digest(entry.to_string().as_bytes()))
+}
+
+/// Where a chain first breaks.
+#[derive(Debug, Clone, Serialize)]
+pub struct BrokenLink {
+    /// The entry, e.g. `entry #42` or `audit-2026-03-01.jsonl line 7`.
+    pub location: String,
+    pub reason: String,
+}
+
+/// Outcome of walking one chain.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ChainReport {
+    /// Links checked before the first break, or all of them.
+    pub checked: usize,
+    /// Entries from before the chain started.
+    pub unchained: usize,
+    pub broken: Option<BrokenLink>,
+}
+
+impl ChainReport {
+    pub fn is_intact(&self) -> bool {
+        self.broken.is_none()
+    }
+}
+
+/// Walks entries in order, stopping at the first broken link.
+#[derive(Default)]
+struct Walker {
+    report: ChainReport,
+    previous: Option<String>,
+}
+
+impl Walker {
+    /// Check one entry. Returns false once the chain is broken.
+    fn check(&mut self, location: impl FnOnce() -> String, entry: &Value) -> bool {
+        let text = |key: &str| entry.get(key).and_then(Value::as_str).map(str::to_string);
+        let (Some(hash), Some(prev_hash)) = (text("hash"), text("prev_hash")) else {
+            if self.previous.is_none() {
+                self.report.unchained += 1;
+                return true;
+            }
+            return self.fail(location(), "Entry has no hash");
+        };
+        if self.previous.as_ref().is_some_and(|previous| previous != &prev_hash) {
+            return self.fail(location(), "Previous hash does not match: an entry before it was removed or changed");
+        }
+        if entry_hash(entry) != hash {
+            return self.fail(location(), "Hash does not match the entry: its content was changed");
+        }
+        self.previous = Some(hash);
+        self.report.checked += 1;
+        true
+    }
+
+    fn fail(&mut self, location: String, reason: &str) -> bool {
+        self.report.broken = Some(BrokenLink { location, reason: reason.to_string() });
+        false
+    }
+}
+
+/// The database entry fields that are hashed, with their property keys.
+const DB_FIELDS: &[&str] = &["user_id", "action", "target_type", "target_id", "summary", "prev_hash", "hash"];
+
+/// A database entry as the JSON its hash covers.
+pub fn db_entry(created_at: &str, properties: &[(String, String)]) -> Value {
+    let mut map = serde_json::Map::new();
+    map.insert("created_at".to_string(), Value::from(created_at));
+    for (key, value) in properties {
+        if DB_FIELDS.contains(&key.as_str()) {
+            map.insert(key.clone(), Value::from(value.as_str()));
+        }
+    }
+    Value::Object(map)
+}
+
+/// Hash of the newest chained database entry, or [`GENESIS`].
+pub(crate) async fn last_db_hash(pool: &PgPool) -> Result<String, sqlx::Error> {
+    let hash: Option<String> = sqlx::query_scalar(
+        "SELECT p.value FROM entities e \
+         JOIN entity_properties p ON p.entity_id = e.id AND p.key = 'hash' \
+         WHERE e.entity_type = 'audit_entry' \
+         ORDER BY e.id DESC LIMIT 1",
+    )
+    .fetch_optional(pool)
+    .await?;
+    Ok(hash.unwrap_or_else(|| GENESIS.to_string()))
+}
+
+/// Walk the database chain, oldest entry first.
+pub async fn verify_database(pool: &PgPool) -> Result<ChainReport, AuditError> {
+    let rows: Vec<(i64, String)> = sqlx::query_as(
+        "SELECT id, TO_CHAR(created_at AT TIME ZONE 'UTC', 'YYYY-MM-DD\"T\"HH24:MI:SS.US\"Z\"') \
+         FROM entities WHERE entity_type = 'audit_entry' ORDER BY id",
+    )
+    .fetch_all(pool)
+    .await?;
+    let properties: Vec<(i64, String, String)> = sqlx::query_as(
+        "SELECT p.entity_id, p.key, p.value FROM entity_properties p \
+         JOIN entities e ON e.id = p.entity_id AND e.entity_type = 'audit_entry' \
+         ORDER BY p.entity_id",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut walker = Walker::default();
+    let mut props = properties.into_iter().peekable();
+    for (id, created_at) in rows {
+        let mut own = Vec::new();
+        while let Some((_, key, value)) = props.next_if(|(entity_id, _, _)| *entity_id <= id) {
+            own.push((key, value));
+        }
+        if !walker.check(|| format!("Database entry #{id}"), &db_entry(&created_at, &own)) {
+            break;
+        }
+    }
+    Ok(walker.report)
+}
+
+/// The last line of a log file, if it has one.
+fn last_line(contents: &str) -> Option<&str> {
+    contents.lines().rev().find(|l| !l.trim().is_empty())
+}
+
+/// Daily log files in the directory, oldest first.
+fn log_files(dir: &std::path::Path) -> Vec<String> {
+    let mut names: Vec<String> = std::fs::read_dir(dir)
+        .map(|entries| entries
+            .filter_map(|e| e.ok()?.file_name().into_string().ok())
+            .filter(|n| n.starts_with("audit-") && n.ends_with(".jsonl"))
+            .collect())
+        .unwrap_or_default();
+    names.sort();
+    names
+}
+
+/// Hash of the newest chained line in the log files up to and including
+/// `file_name`, or [`GENESIS`].
+pub(crate) fn last_file_hash(dir: &std::path::Path, file_name: &str) -> String {
+    for name in log_files(dir).iter().rev().filter(|n| n.as_str() <= file_name) {
+        let Ok(contents) = std::fs::read_to_string(dir.join(name)) else { continue };
+        let hash = last_line(&contents)
+            .and_then(|line| serde_json::from_str::<Value>(line).ok())
+            .and_then(|entry| entry.get("hash").and_then(Value::as_str).map(str::to_string));
+        if let Some(hash) = hash {
+            return hash;
+        }
+    }
+    GENESIS.to_string()
+}
+
+/// Walk the log file chain across every daily file, oldest first.
+pub async fn verify_files(pool: &PgPool) -> Result<ChainReport, AuditError> {
+    let dir = super::log_dir(pool).await;
+    let mut walker = Walker::default();
+    'files: for name in log_files(&dir) {
+        let contents = std::fs::read_to_string(dir.join(&name))?;
+        for (n, line) in contents.lines().enumerate().filter(|(_, l)| !l.trim().is_empty()) {
+            let location = || format!("{} line {}", name, n + 1);
+            let Ok(entry) = serde_json::from_str::<Value>(line) else {
+                walker.fail(location(), "Line is not valid JSON");
+                break 'files;
+            };
+            if !walker.check(location, &entry) {
+                break 'files;
+            }
+        }
+    }
+    Ok(walker.report)
+}
@@ -0,0 +1,152 @@
+//! Audit exports: filtered audit records as CSV or JSON Lines.
+//!
+//! Records come from both stores: the database entries (high-value actions
+//! only) and the daily JSONL log files (every action, when file logging is
+//! enabled). Each record says which store it came from, so an action kept in
+//! both appears twice. Exports and the audit API always cover a date range
+//! of at most [`super::archive::MAX_DAYS`] days, the last 30 by default.
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use sqlx::PgPool;
+
+use super::AuditError;
+use crate::models::audit::{self, AuditFilter};
+
+pub const FORMATS: &[&str] = &["csv", "jsonl"];
+
+/// One audit record from either store.
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditRecord {
+    /// `database` or `file`.
+    pub source: &'static str,
+    /// RFC 3339, UTC.
+    pub timestamp: String,
+    pub user_id: i64,
+    pub username: String,
+    pub action: String,
+    pub target_type: String,
+    pub target_id: i64,
+    pub summary: String,
+    /// The logged details; null for database entries.
+    pub details: Value,
+}
+
+/// The date range a filter covers, filling in the defaults. Errors if the
+/// range is reversed or too long.
+pub fn period(filter: &AuditFilter, today: NaiveDate) -> Result<(NaiveDate, NaiveDate), String> {
+    let to = filter.to_date().unwrap_or(today);
+    let from = filter.from_date().unwrap_or(to - Duration::days(29));
+    if from > to || (to - from).num_days() >= super::archive::MAX_DAYS {
+        return Err(format!(
+            "Audit exports cover 1 to {} days, with the start before the end",
+            super::archive::MAX_DAYS
+        ));
+    }
+    Ok((from, to))
+}
+
+/// Parse a database `created_at::TEXT` into RFC 3339.
+fn normalize_timestamp(text: &str) -> String {
+    DateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S%.f%#z")
+        .map(|t| t.with_timezone(&Utc).to_rfc3339())
+        .unwrap_or_else(|_| text.to_string())
+}
+
+/// A log file line as a record, if it parses and matches the filter.
+fn from_log_line(line: &str, filter: &AuditFilter) -> Option<AuditRecord> {
+    let value: Value = serde_json::from_str(line).ok()?;
+    let text = |key: &str| value.get(key).and_then(Value::as_str).unwrap_or("").to_string();
+    let timestamp = DateTime::parse_from_rfc3339(&text("timestamp")).ok()?.with_timezone(&Utc);
+    let details = value.get("details").cloned().unwrap_or(Value::Null);
+    let record = AuditRecord {
+        source: "file",
+        timestamp: timestamp.to_rfc3339(),
+        user_id: value.get("user_id").and_then(Value::as_i64).unwrap_or(0),
+        username: text("username"),
+        action: text("action"),
+        target_type: text("target_type"),
+        target_id: value.get("target_id").and_then(Value::as_i64).unwrap_or(0),
+        summary: details.get("summary").and_then(Value::as_str).unwrap_or("").to_string(),
+        details,
+    };
+    filter.matches(&record.username, &record.action, &record.target_type, &record.summary, timestamp.date_naive())
+        .then_some(record)
+}
+
+/// Every record matching the filter within `from..=to`, oldest first.
+pub async fn collect(
+    pool: &PgPool,
+    filter: &AuditFilter,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Vec<AuditRecord>, AuditError> {
+    let bounded = AuditFilter { from: Some(from.to_string()), to: Some(to.to_string()), ..filter.clone() };
+    let mut records: Vec<AuditRecord> = audit::find_filtered(pool, &bounded).await?
+        .into_iter()
+        .map(|e| AuditRecord {
+            source: "database",
+            timestamp: normalize_timestamp(&e.created_at),
+            user_id: e.user_id,
+            username: e.username,
+            action: e.action,
+            target_type: e.target_type,
+            target_id: e.target_id,
+            summary: e.summary,
+            details: Value::Null,
+        })
+        .collect();
+
+    let log_dir = super::log_dir(pool).await;
+    for day in from.iter_days().take_while(|d| *d <= to) {
+        let Ok(contents) = std::fs::read_to_string(log_dir.join(format!("audit-{day}.jsonl"))) else {
+            continue;
+        };
+        records.extend(contents.lines().filter_map(|line| from_log_line(line, &bounded)));
+    }
+    records.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+    Ok(records)
+}
+
+fn escape_csv(s: &str) -> String {
+    if s.contains(',') || s.contains('"') || s.contains('\n') {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+pub fn to_csv(records: &[AuditRecord]) -> String {
+    let mut out = String::from("timestamp,source,user_id,username,action,target_type,target_id,summary,details\n");
+    for r in records {
+        let details = if r.details.is_null() { String::new() } else { r.details.to_string() };
+        out.push_str(&format!(
+            "{},{},{},{},{},{},{},{},{}\n",
+            r.timestamp,
+            r.source,
+            r.user_id,
+            escape_csv(&r.username),
+            escape_csv(&r.action),
+            escape_csv(&r.target_type),
+            r.target_id,
+            escape_csv(&r.summary),
+            escape_csv(&details),
+        ));
+    }
+    out
+}
+
+pub fn to_jsonl(records: &[AuditRecord]) -> Result<String, AuditError> {
+    let mut out = String::new();
+    for r in records {
+        out.push_str(&serde_json::to_string(r)?);
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+/// Export file name, e.g. `audit-2026-01-01-to-2026-01-31.csv`.
+pub fn file_name(from: NaiveDate, to: NaiveDate, format: &str) -> String {
+    format!("audit-{from}-to-{to}.{format}")
+}
@@ -1,3 +1,7 @@
+pub mod archive;
+pub mod chain;
+pub mod export;
+
 use sqlx::PgPool;
 use serde_json::Value;
 use std::fs::{self, OpenOptions};
@@ -43,7 +47,8 @@ fn is_important(action: &str) -> bool {
     matches!(action,
         "user.created" | "user.deleted" |
         "role.created" | "role.deleted" | "role.permissions_changed" |
-        "setting.critical_changed"
+        "setting.critical_changed" |
+        "ownership.transfer_requested" | "ownership.transferred"
     )
 }
 
@@ -76,6 +81,12 @@ async fn get_log_path(pool: &PgPool, date: &str) -> Result<String, AuditError> {
     Ok(full_path.to_string_lossy().to_string())
 }
 
+/// Directory holding the daily `audit-YYYY-MM-DD.jsonl` files.
+pub(crate) async fn log_dir(pool: &PgPool) -> std::path::PathBuf {
+    let default = crate::models::setting::definition("audit.log_path").map(|d| d.default).unwrap_or("data/audit/");
+    std::path::PathBuf::from(crate::models::setting::get_value(pool, "audit.log_path", default).await)
+}
+
 // Helper: Get username from user_id
 async fn get_username(pool: &PgPool, user_id: i64) -> String {
     sqlx::query_as::<_, (String,)>(
@@ -100,19 +111,7 @@ async fn write_to_file(
     details: &Value,
 ) -> Result<(), AuditError> {
     // Check if audit is enabled
-    let enabled: String = sqlx::query_as::<_, (String,)>(
-        "SELECT value FROM entity_properties
-         WHERE entity_id = (SELECT id FROM entities WHERE entity_type='setting' AND name='audit.enabled')
-           AND key='value'",
-    )
-    .fetch_optional(pool)
-    .await
-    .ok()
-    .flatten()
-    .map(|r| r.0)
-    .unwrap_or_else(|| "false".to_string());
-
-    if enabled != "true" {
+    if !crate::models::setting::get_bool(pool, "audit.enabled").await {
         return Ok(());
     }
 
@@ -120,11 +119,17 @@ async fn write_to_file(
     let log_path = get_log_path(pool, &date).await?;
     let username = get_username(pool, user_id).await;
 
+    // Link to the last line written, across days
+    let _guard = chain::APPEND_LOCK.lock().await;
+    let path = std::path::Path::new(&log_path);
+    let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+    let prev_hash = chain::last_file_hash(path.parent().unwrap_or(path), file_name);
+
     // Get current timestamp in ISO 8601 format
     let timestamp = chrono::Utc::now().to_rfc3339();
 
     // Build log entry
-    let entry = serde_json::json!({
+    let mut entry = serde_json::json!({
         "timestamp": timestamp,
         "user_id": user_id,
         "username": username,
@@ -132,9 +137,10 @@ async fn write_to_file(
         "target_type": target_type,
         "target_id": target_id,
         "details": details,
+        "prev_hash": prev_hash,
     });
+    entry["hash"] = Value::from(chain::entry_hash(&entry));
 
-    // Append to file
     // Append to file with secure permissions
     #[cfg(unix)]
     {
@@ -188,17 +194,7 @@ pub async fn log(
 
 pub async fn cleanup_old_entries(pool: &PgPool) {
     // Get retention_days setting
-    let retention_days: i64 = sqlx::query_as::<_, (String,)>(
-        "SELECT value FROM entity_properties
-         WHERE entity_id = (SELECT id FROM entities WHERE entity_type='setting' AND name='audit.retention_days')
-           AND key='value'",
-    )
-    .fetch_optional(pool)
-    .await
-    .ok()
-    .flatten()
-    .map(|r| r.0.parse().unwrap_or(90))
-    .unwrap_or(90);
+    let retention_days = crate::models::setting::get_i64(pool, "audit.retention_days").await;
 
     // Skip if retention is 0 (keep forever)
     if retention_days == 0 {
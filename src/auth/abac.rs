@@ -20,6 +20,10 @@
 //! `load_tor_capabilities` returns all six capability keys (using `LIKE 'can_%'`),
 //! not just the three used by Split 2 meeting handlers. This forward-compatibility
 //! allows future splits for suggestion/proposal ABAC without modifying this function.
+//!
+//! A position delegated to a user (`tor_function --(delegates_to)--> user`,
+//! see `models::tor::delegations`) counts as held by the delegate while the
+//! delegation window covers today and the delegator still fills the position.
 
 use crate::auth::session::{get_user_id, require_permission, Permissions};
 use crate::errors::AppError;
@@ -27,6 +31,42 @@ use crate::models::graph_sync::{self, GraphPool};
 use actix_session::Session;
 use sqlx::PgPool;
 
+/// SQL selecting the ids of positions the user (`user_param`) holds, either
+/// by filling them or by an active delegation on `day_param` (`YYYY-MM-DD`).
+fn held_positions_sql(user_param: &str, day_param: &str) -> String {
+    format!(
+        "SELECT r_fills.target_id FROM relations r_fills
+         WHERE r_fills.source_id = {user}
+           AND r_fills.relation_type_id = (
+               SELECT id FROM entities
+               WHERE entity_type = 'relation_type' AND name = 'fills_position'
+           )
+         UNION
+         SELECT r_del.source_id FROM relations r_del
+         JOIN relation_properties p_from ON p_from.relation_id = r_del.id AND p_from.key = 'delegator_id'
+         JOIN relation_properties p_start ON p_start.relation_id = r_del.id AND p_start.key = 'start_date'
+         JOIN relation_properties p_end ON p_end.relation_id = r_del.id AND p_end.key = 'end_date'
+         JOIN relations r_held ON r_held.target_id = r_del.source_id
+             AND r_held.source_id::TEXT = p_from.value
+             AND r_held.relation_type_id = (
+                 SELECT id FROM entities
+                 WHERE entity_type = 'relation_type' AND name = 'fills_position'
+             )
+         WHERE r_del.target_id = {user}
+           AND r_del.relation_type_id = (
+               SELECT id FROM entities
+               WHERE entity_type = 'relation_type' AND name = 'delegates_to'
+           )
+           AND p_start.value <= {day} AND p_end.value >= {day}",
+        user = user_param,
+        day = day_param,
+    )
+}
+
+fn today() -> String {
+    chrono::Local::now().format("%Y-%m-%d").to_string()
+}
+
 /// Check whether a user holds a specific capability in a given resource,
 /// by traversing the EAV graph:
 ///   user --(fills_position)--> tor_function --(belongs_to_rel)--> resource
@@ -59,13 +99,15 @@ pub async fn has_resource_capability_with_graph(
     capability: &str,
 ) -> Result<bool, AppError> {
     // Try Neo4j path for ToR capabilities
-    if belongs_to_rel == "belongs_to_tor" {
-        if let Some(g) = graph {
-            if let Some(result) = graph_sync::queries::has_tor_capability(g, user_id, resource_id, capability).await {
-                return Ok(result);
-            }
-            // Neo4j returned None (query failed) — fall through to Postgres
+    if belongs_to_rel == "belongs_to_tor"
+        && let Some(g) = graph
+    {
+        // The graph knows filled positions only; delegated ones are
+        // checked in Postgres
+        if graph_sync::queries::has_tor_capability(g, user_id, resource_id, capability).await == Some(true) {
+            return Ok(true);
         }
+        // Neo4j said no or returned None (query failed) — fall through to Postgres
     }
     has_resource_capability_pg(pool, user_id, resource_id, belongs_to_rel, capability).await
 }
@@ -78,19 +120,12 @@ async fn has_resource_capability_pg(
     belongs_to_rel: &str,
     capability: &str,
 ) -> Result<bool, AppError> {
-    let row: (i64,) = sqlx::query_as(
+    let row: (i64,) = sqlx::query_as(&format!(
         "SELECT COUNT(*)
          FROM entity_properties ep
          JOIN entities func
              ON ep.entity_id = func.id
              AND func.entity_type = 'tor_function'
-         JOIN relations r_fills
-             ON r_fills.target_id = func.id
-             AND r_fills.source_id = $1
-             AND r_fills.relation_type_id = (
-                 SELECT id FROM entities
-                 WHERE entity_type = 'relation_type' AND name = 'fills_position'
-             )
          JOIN relations r_belongs
              ON r_belongs.source_id = func.id
              AND r_belongs.target_id = $2
@@ -99,12 +134,15 @@ async fn has_resource_capability_pg(
                  WHERE entity_type = 'relation_type' AND name = $3
              )
          WHERE ep.key = $4
-           AND ep.value = 'true'",
-    )
+           AND ep.value = 'true'
+           AND func.id IN ({})",
+        held_positions_sql("$1", "$5"),
+    ))
     .bind(user_id)
     .bind(resource_id)
     .bind(belongs_to_rel)
     .bind(capability)
+    .bind(today())
     .fetch_one(pool)
     .await?;
     Ok(row.0 > 0)
@@ -123,19 +161,12 @@ pub async fn load_tor_capabilities(
     user_id: i64,
     tor_id: i64,
 ) -> Result<Permissions, AppError> {
-    let rows: Vec<(String,)> = sqlx::query_as(
+    let rows: Vec<(String,)> = sqlx::query_as(&format!(
         "SELECT DISTINCT ep.key
          FROM entity_properties ep
          JOIN entities func
              ON ep.entity_id = func.id
              AND func.entity_type = 'tor_function'
-         JOIN relations r_fills
-             ON r_fills.target_id = func.id
-             AND r_fills.source_id = $1
-             AND r_fills.relation_type_id = (
-                 SELECT id FROM entities
-                 WHERE entity_type = 'relation_type' AND name = 'fills_position'
-             )
          JOIN relations r_belongs
              ON r_belongs.source_id = func.id
              AND r_belongs.target_id = $2
@@ -144,10 +175,13 @@ pub async fn load_tor_capabilities(
                  WHERE entity_type = 'relation_type' AND name = 'belongs_to_tor'
              )
          WHERE ep.key LIKE 'can_%'
-           AND ep.value = 'true'",
-    )
+           AND ep.value = 'true'
+           AND func.id IN ({})",
+        held_positions_sql("$1", "$3"),
+    ))
     .bind(user_id)
     .bind(tor_id)
+    .bind(today())
     .fetch_all(pool)
     .await?;
     let keys: Vec<String> = rows.into_iter().map(|r| r.0).collect();
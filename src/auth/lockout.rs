@@ -0,0 +1,108 @@
+//! Account lockout after repeated failed sign-ins.
+//!
+//! The per-IP [`RateLimiter`](super::rate_limit::RateLimiter) slows one
+//! address down but not guessing spread over many. Failed password sign-ins
+//! are therefore also counted per account: `login.lockout_threshold`
+//! failures within `login.lockout_window_minutes` lock the account for
+//! `login.lockout_minutes` (0 keeps it locked until an administrator unlocks
+//! it from the user edit page), and the user administrators are warned. A
+//! successful sign-in or an unlock clears the count.
+
+use std::net::IpAddr;
+
+use sqlx::PgPool;
+
+use crate::models::{setting, user};
+use crate::warnings;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LockoutPolicy {
+    /// Failures that lock the account; 0 turns lockout off.
+    pub threshold: i64,
+    pub window_minutes: i64,
+    /// How long a lockout lasts; 0 until an administrator unlocks it.
+    pub lock_minutes: i64,
+}
+
+impl LockoutPolicy {
+    pub async fn load(pool: &PgPool) -> Self {
+        LockoutPolicy {
+            threshold: setting::get_i64(pool, "login.lockout_threshold").await.max(0),
+            window_minutes: setting::get_i64(pool, "login.lockout_window_minutes").await.max(1),
+            lock_minutes: setting::get_i64(pool, "login.lockout_minutes").await.max(0),
+        }
+    }
+}
+
+/// Failures within the window and how many addresses they came from.
+async fn recent_failures(pool: &PgPool, user_id: i64, window_minutes: i64) -> Result<(i64, i64), sqlx::Error> {
+    sqlx::query_as(
+        "SELECT COUNT(*), COUNT(DISTINCT ip) FROM login_failures \
+         WHERE user_id = $1 AND attempted_at > NOW() - make_interval(mins => $2::INT)",
+    )
+    .bind(user_id)
+    .bind(window_minutes)
+    .fetch_one(pool)
+    .await
+}
+
+/// Record a failed password sign-in to the account. Returns true when it
+/// locked the account.
+pub async fn record_failure(pool: &PgPool, user_id: i64, username: &str, ip: IpAddr) -> Result<bool, sqlx::Error> {
+    let policy = LockoutPolicy::load(pool).await;
+    if policy.threshold == 0 || user::is_locked(pool, user_id).await? {
+        return Ok(false);
+    }
+    sqlx::query("INSERT INTO login_failures (user_id, ip) VALUES ($1, $2)")
+        .bind(user_id)
+        .bind(ip.to_string())
+        .execute(pool)
+        .await?;
+    let (failures, addresses) = recent_failures(pool, user_id, policy.window_minutes).await?;
+    if failures < policy.threshold {
+        return Ok(false);
+    }
+
+    let reason = format!(
+        "{} failed sign-ins from {} address(es) within {} minutes",
+        failures, addresses, policy.window_minutes
+    );
+    let until = (policy.lock_minutes > 0)
+        .then(|| warnings::clock::now() + chrono::Duration::minutes(policy.lock_minutes));
+    user::lock(pool, user_id, &reason, until).await?;
+    clear(pool, user_id).await?;
+
+    let details = serde_json::json!({
+        "failures": failures,
+        "addresses": addresses,
+        "last_ip": ip.to_string(),
+        "locked_minutes": policy.lock_minutes,
+        "summary": format!("Account '{}' locked after {}", username, reason)
+    });
+    let _ = crate::audit::log(pool, user_id, "user.locked", "user", user_id, details.clone()).await;
+
+    let admins = warnings::get_users_with_permission(pool, "users.edit").await?;
+    if !admins.is_empty() {
+        let duration = if policy.lock_minutes > 0 {
+            format!("for {} minutes", policy.lock_minutes)
+        } else {
+            "until unlocked".to_string()
+        };
+        let msg = format!("Account '{}' locked {} after {}", username, duration, reason);
+        let wid = warnings::create_warning(
+            pool, "high", "security", "event.security.account_lockout", &msg, &details.to_string(), "system"
+        ).await?;
+        crate::models::entity::set_property(pool, wid, "link", &format!("/users/{}/edit", user_id)).await?;
+        warnings::create_receipts(pool, wid, &admins).await?;
+    }
+    Ok(true)
+}
+
+/// Forget the account's failed sign-ins.
+pub async fn clear(pool: &PgPool, user_id: i64) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM login_failures WHERE user_id = $1")
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
@@ -1,26 +1,196 @@
 use actix_session::SessionExt;
 use actix_web::{
-    Error, HttpResponse,
+    Error, HttpResponse, web,
     body::MessageBody,
     dev::{ServiceRequest, ServiceResponse},
     middleware::Next,
 };
 
+use sqlx::PgPool;
+
+use crate::models::{api_token, remember_token};
+use super::{network, password_policy, redirect, remember, timeout};
+use super::session::{self, sign_in};
+
 /// Middleware function that checks for an authenticated session.
-/// Redirects to /login if no session found.
+/// Redirects to /login if no session found; page requests (GET) carry the
+/// requested path as `?next=` so login can return the user there.
+/// Sessions past their idle timeout or absolute lifetime are purged. Without
+/// a live session, a valid remember-me cookie signs the user back in and is
+/// rotated. Until a user whose password expired changes it, every page but
+/// signing out redirects to the account page.
 pub async fn require_auth(
     req: ServiceRequest,
     next: Next<impl MessageBody + 'static>,
 ) -> Result<ServiceResponse<impl MessageBody>, Error> {
     let session = req.get_session();
-    let has_user = session.get::<i64>("user_id").unwrap_or(None).is_some();
+    let user_id = session.get::<i64>("user_id").unwrap_or(None);
+    let pool = req.app_data::<web::Data<PgPool>>().cloned();
+
+    let mut active = match (user_id, &pool) {
+        (Some(user_id), Some(pool)) => {
+            let now = chrono::Utc::now().timestamp();
+            let login_at = session.get::<i64>("login_at").unwrap_or(None).unwrap_or(now);
+            let last_seen = session.get::<i64>("last_seen").unwrap_or(None).unwrap_or(now);
+            let keep_signed_in = session.get::<bool>("keep_signed_in").unwrap_or(None).unwrap_or(false);
+            let limits = timeout::SessionLimits::load(pool, keep_signed_in).await;
+
+            let locked = crate::models::user::is_locked(pool, user_id).await.unwrap_or(false);
+            if !locked && limits.is_active(login_at, last_seen, now) {
+                let _ = session.insert("login_at", login_at);
+                let _ = session.insert("last_seen", now);
+                let via_token = session.get::<i64>("api_token_id").unwrap_or(None).is_some();
+                if let (false, Some(tracker)) = (via_token, req.app_data::<web::Data<timeout::SessionTracker>>()) {
+                    tracker.record(user_id, limits.expires_at(login_at, now));
+                }
+                if !via_token && let Err(e) = session::refresh_permissions(&session, pool, user_id).await {
+                    log::warn!("Failed to refresh permissions for user {}: {}", user_id, e);
+                }
+                true
+            } else {
+                // clear() rather than purge(): a remember-me resume below may refill it
+                session.clear();
+                false
+            }
+        }
+        (Some(_), None) => true,
+        (None, _) => false,
+    };
+
+    // No live session — try the remember-me cookie
+    let mut set_cookie = None;
+    let presented = req.cookie(remember::COOKIE_NAME).map(|c| c.value().to_string());
+    if let (false, Some(raw), Some(pool)) = (active, &presented, &pool) {
+        let lifetime = remember::lifetime(pool).await;
+        if let Ok(Some((user_id, fresh))) = remember_token::consume(pool, raw, lifetime).await {
+            let username: Option<(String,)> = sqlx::query_as(
+                "SELECT name FROM entities WHERE id = $1 AND entity_type = 'user'"
+            )
+            .bind(user_id)
+            .fetch_optional(pool.get_ref())
+            .await
+            .unwrap_or(None);
+            // Resuming is a sign-in too, so it answers to the network allowlist
+            let allowed = match req.peer_addr() {
+                Some(addr) => network::check(pool, addr.ip(), user_id, network::Scope::Login).await.unwrap_or(false),
+                None => true,
+            };
+            let signed_in = match username {
+                Some((username,)) if allowed => sign_in(&session, pool, user_id, &username, false).await.is_ok(),
+                _ => false,
+            };
+            if signed_in {
+                let secure = req.connection_info().scheme() == "https";
+                set_cookie = Some(remember::cookie(fresh, lifetime, secure));
+                active = true;
+            }
+        }
+    }
 
-    if !has_user {
-        let response = HttpResponse::SeeOther()
-            .insert_header(("Location", "/login"))
-            .finish();
+    if !active {
+        let location = if req.method() == actix_web::http::Method::GET {
+            let requested = req.uri().path_and_query().map(|pq| pq.as_str()).unwrap_or("/");
+            redirect::login_url(requested)
+        } else {
+            "/login".to_string()
+        };
+        let mut response = HttpResponse::SeeOther();
+        response.insert_header(("Location", location));
+        if presented.is_some() {
+            response.cookie(remember::removal_cookie());
+        }
+        return Ok(req.into_response(response.finish()).map_into_right_body());
+    }
+
+    // An expired password has to be changed before anything else
+    if session.get::<bool>(password_policy::EXPIRED_KEY).unwrap_or(None).unwrap_or(false)
+        && !password_policy::allowed_while_expired(req.path())
+    {
+        let response = HttpResponse::SeeOther().insert_header(("Location", "/account")).finish();
         return Ok(req.into_response(response).map_into_right_body());
     }
 
-    next.call(req).await.map(|res| res.map_into_left_body())
+    let mut res = next.call(req).await?;
+    if let Some(cookie) = set_cookie {
+        let _ = res.response_mut().add_cookie(&cookie);
+    }
+    Ok(res.map_into_left_body())
+}
+
+/// Middleware that authenticates `Authorization: Bearer` API tokens.
+///
+/// Wrapped outside [`require_auth`]: a valid token fills the request's session
+/// with its owner and the token's scoped permissions, so handlers and
+/// `require_auth` treat it like a signed-in user. The session is cleared again
+/// afterwards, so token requests never get a session cookie. Tokens are only
+/// accepted under `/api/v1/`, from addresses the network allowlist lets the
+/// owner use the API from; read-only tokens can only GET. Requests without
+/// the header pass through untouched.
+pub async fn bearer_auth(
+    req: ServiceRequest,
+    next: Next<impl MessageBody + 'static>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    let raw = req.headers()
+        .get(actix_web::http::header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(|v| v.trim().to_string());
+    let Some(raw) = raw else {
+        return next.call(req).await.map(|res| res.map_into_left_body());
+    };
+
+    let reject = |req: ServiceRequest, response: HttpResponse| {
+        Ok(req.into_response(response).map_into_right_body())
+    };
+    if !req.path().starts_with("/api/v1/") {
+        return reject(req, HttpResponse::Unauthorized()
+            .json(serde_json::json!({ "error": "API tokens are only accepted for /api/v1" })));
+    }
+    let Some(pool) = req.app_data::<web::Data<PgPool>>().cloned() else {
+        return reject(req, HttpResponse::InternalServerError().finish());
+    };
+
+    let today = chrono::Utc::now().format("%Y-%m-%d").to_string();
+    let identity = match api_token::identify(&pool, &raw, &today).await {
+        Ok(Some(identity)) => identity,
+        Ok(None) => {
+            return reject(req, HttpResponse::Unauthorized()
+                .insert_header(("WWW-Authenticate", "Bearer"))
+                .json(serde_json::json!({ "error": "Invalid, expired or revoked API token" })));
+        }
+        Err(e) => {
+            log::error!("Failed to resolve API token: {}", e);
+            return reject(req, HttpResponse::InternalServerError().finish());
+        }
+    };
+    // The network allowlist comes before the token counts as used
+    if let Some(addr) = req.peer_addr()
+        && let Some(response) = network::refuse_api(&pool, addr.ip(), identity.user_id, &identity.username).await
+    {
+        return reject(req, response);
+    }
+    let method = req.method();
+    if identity.read_only && method != actix_web::http::Method::GET && method != actix_web::http::Method::HEAD {
+        return reject(req, HttpResponse::Forbidden()
+            .json(serde_json::json!({ "error": "This API token is read-only" })));
+    }
+
+    if let Err(e) = api_token::record_use(&pool, identity.token_id).await {
+        log::error!("Failed to record API token use: {}", e);
+        return reject(req, HttpResponse::InternalServerError().finish());
+    }
+
+    let session = req.get_session();
+    let now = chrono::Utc::now().timestamp();
+    session.clear();
+    let _ = session.insert("user_id", identity.user_id);
+    let _ = session.insert("username", &identity.username);
+    let _ = session.insert("permissions", identity.permissions.join(","));
+    let _ = session.insert("login_at", now);
+    let _ = session.insert("last_seen", now);
+    let _ = session.insert("api_token_id", identity.token_id);
+
+    let res = next.call(req).await?;
+    session.clear();
+    Ok(res.map_into_left_body())
 }
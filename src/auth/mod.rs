@@ -1,7 +1,16 @@
 pub mod abac;
 pub mod csrf;
+pub mod lockout;
 pub mod middleware;
+pub mod network;
+pub mod new_device;
+pub mod oidc;
 pub mod password;
+pub mod password_policy;
 pub mod rate_limit;
+pub mod redirect;
+pub mod remember;
+pub mod timeout;
 pub mod session;
+pub mod session_keys;
 pub mod validate;
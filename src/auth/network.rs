@@ -0,0 +1,246 @@
+//! Network allowlist for sign-in and API access.
+//!
+//! `network.allowlist` lists the CIDR ranges (or single addresses) requests
+//! may come from; empty turns the restriction off. Geo restrictions are
+//! expressed the same way, as the ranges of the allowed regions or offices.
+//! `network.restrict_login` and `network.restrict_api` choose what the list
+//! guards, and users holding a role in `network.bypass_roles` are let
+//! through from anywhere. Blocked attempts are audited and raise one warning
+//! per address for the settings admins.
+
+use std::fmt;
+use std::net::IpAddr;
+
+use actix_session::SessionExt;
+use actix_web::{
+    Error, HttpResponse, web,
+    body::MessageBody,
+    dev::{ServiceRequest, ServiceResponse},
+    middleware::Next,
+};
+use sqlx::PgPool;
+
+use crate::models::{setting, user};
+
+/// What a request is trying to do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scope {
+    Login,
+    Api,
+}
+
+impl Scope {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Scope::Login => "login",
+            Scope::Api => "api",
+        }
+    }
+}
+
+/// An address range such as `10.0.0.0/8` or `2001:db8::/32`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cidr {
+    network: IpAddr,
+    prefix: u8,
+}
+
+impl Cidr {
+    /// Parse `addr/prefix`, or a bare address as a single-host range.
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let value = value.trim();
+        let (addr, prefix) = match value.split_once('/') {
+            Some((addr, prefix)) => (addr, Some(prefix)),
+            None => (value, None),
+        };
+        let network: IpAddr = addr.trim().parse()
+            .map_err(|_| format!("'{}' is not an IP address or CIDR range", value))?;
+        let max = if network.is_ipv4() { 32 } else { 128 };
+        let prefix = match prefix {
+            Some(p) => p.trim().parse::<u8>().ok().filter(|&p| p <= max)
+                .ok_or_else(|| format!("'{}' has a prefix length outside 0–{}", value, max))?,
+            None => max,
+        };
+        Ok(Cidr { network, prefix })
+    }
+
+    /// Whether the address falls in this range. IPv4-mapped IPv6 addresses
+    /// count as their IPv4 form.
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        match (self.network, ip.to_canonical()) {
+            (IpAddr::V4(net), IpAddr::V4(ip)) => {
+                let mask = u32::MAX.checked_shl(32 - u32::from(self.prefix)).unwrap_or(0);
+                u32::from(net) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(net), IpAddr::V6(ip)) => {
+                let mask = u128::MAX.checked_shl(128 - u32::from(self.prefix)).unwrap_or(0);
+                u128::from(net) & mask == u128::from(ip) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Cidr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.network, self.prefix)
+    }
+}
+
+/// Ranges listed in a `network.allowlist` value, separated by commas or
+/// whitespace.
+pub fn parse_allowlist(value: &str) -> Result<Vec<Cidr>, String> {
+    value.split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|v| !v.is_empty())
+        .map(Cidr::parse)
+        .collect()
+}
+
+/// The allowlist settings in force.
+#[derive(Debug, Clone, Default)]
+pub struct Policy {
+    pub allowlist: Vec<Cidr>,
+    pub restrict_login: bool,
+    pub restrict_api: bool,
+    pub bypass_roles: Vec<String>,
+}
+
+impl Policy {
+    pub async fn load(pool: &PgPool) -> Self {
+        let value = setting::get_value(pool, "network.allowlist", "").await;
+        let allowlist = value.split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|v| !v.is_empty())
+            .filter_map(|v| Cidr::parse(v).map_err(|e| log::warn!("Ignoring network.allowlist entry: {}", e)).ok())
+            .collect();
+        Policy {
+            allowlist,
+            restrict_login: setting::get_bool(pool, "network.restrict_login").await,
+            restrict_api: setting::get_bool(pool, "network.restrict_api").await,
+            bypass_roles: setting::get_value(pool, "network.bypass_roles", "").await
+                .split(',')
+                .map(|r| r.trim().to_lowercase())
+                .filter(|r| !r.is_empty())
+                .collect(),
+        }
+    }
+
+    /// Whether the allowlist guards this scope at all.
+    pub fn applies(&self, scope: Scope) -> bool {
+        !self.allowlist.is_empty() && match scope {
+            Scope::Login => self.restrict_login,
+            Scope::Api => self.restrict_api,
+        }
+    }
+
+    pub fn allows(&self, ip: IpAddr) -> bool {
+        self.allowlist.iter().any(|range| range.contains(ip))
+    }
+
+    /// Whether a user with these comma-separated role names is exempt.
+    pub fn bypasses(&self, role_names: &str) -> bool {
+        role_names.split(',')
+            .map(|r| r.trim().to_lowercase())
+            .any(|r| self.bypass_roles.contains(&r))
+    }
+}
+
+/// Whether a request from `ip` by the user may go ahead.
+pub async fn check(pool: &PgPool, ip: IpAddr, user_id: i64, scope: Scope) -> Result<bool, sqlx::Error> {
+    let policy = Policy::load(pool).await;
+    if !policy.applies(scope) || policy.allows(ip) {
+        return Ok(true);
+    }
+    if policy.bypass_roles.is_empty() {
+        return Ok(false);
+    }
+    let roles = user::find_display_by_id(pool, user_id).await?
+        .map(|u| u.role_names)
+        .unwrap_or_default();
+    Ok(policy.bypasses(&roles))
+}
+
+/// Audit a blocked attempt and warn the settings admins, once per address
+/// while the previous warning is still active.
+pub async fn report_blocked(pool: &PgPool, ip: IpAddr, user_id: i64, username: &str, scope: Scope) {
+    let details = serde_json::json!({
+        "ip": ip.to_string(),
+        "scope": scope.as_str(),
+        "username": username,
+        "summary": format!("Blocked {} from {} (not in the network allowlist)", scope.as_str(), ip)
+    });
+    let _ = crate::audit::log(pool, user_id, "security.network_blocked", "user", user_id, details.clone()).await;
+
+    let source_action = "event.security.network_blocked";
+    if crate::warnings::warning_exists(pool, source_action, &format!("\"ip\":\"{}\"", ip)).await {
+        return;
+    }
+    let admins = crate::warnings::get_users_with_permission(pool, "settings.manage").await.unwrap_or_default();
+    if admins.is_empty() {
+        return;
+    }
+    let what = match scope {
+        Scope::Login => "Sign-in",
+        Scope::Api => "API request",
+    };
+    let msg = format!("{} by '{}' blocked from {}, outside the network allowlist", what, username, ip);
+    if let Ok(wid) = crate::warnings::create_warning(
+        pool, "high", "security", source_action, &msg, &details.to_string(), "system"
+    ).await {
+        let _ = crate::warnings::create_receipts(pool, wid, &admins).await;
+    }
+}
+
+/// The 403 sent for a blocked request: JSON for the API, a page otherwise.
+pub fn blocked_response(scope: Scope) -> HttpResponse {
+    match scope {
+        Scope::Api => HttpResponse::Forbidden().json(serde_json::json!({
+            "error": "Access from this network is not allowed"
+        })),
+        Scope::Login => HttpResponse::Forbidden()
+            .content_type("text/html; charset=utf-8")
+            .body(include_str!("../../templates/errors/network_blocked.html")),
+    }
+}
+
+/// Check an API request from `ip` by the user. Returns the 403 to send when
+/// it is refused; a failed check refuses too.
+pub async fn refuse_api(pool: &PgPool, ip: IpAddr, user_id: i64, username: &str) -> Option<HttpResponse> {
+    match check(pool, ip, user_id, Scope::Api).await {
+        Ok(true) => None,
+        Ok(false) => {
+            report_blocked(pool, ip, user_id, username, Scope::Api).await;
+            Some(blocked_response(Scope::Api))
+        }
+        Err(e) => {
+            log::error!("Failed to check network allowlist for {}: {}", username, e);
+            Some(blocked_response(Scope::Api))
+        }
+    }
+}
+
+/// Middleware refusing `/api/` requests from outside the allowlist.
+/// Runs inside `require_auth`, so the session already names the user.
+/// Token requests were checked by `bearer_auth` before the token was used.
+pub async fn restrict_api(
+    req: ServiceRequest,
+    next: Next<impl MessageBody + 'static>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    if !req.path().starts_with("/api/") {
+        return next.call(req).await.map(|res| res.map_into_left_body());
+    }
+    let session = req.get_session();
+    if session.get::<i64>("api_token_id").ok().flatten().is_some() {
+        return next.call(req).await.map(|res| res.map_into_left_body());
+    }
+    let user = session.get::<i64>("user_id").ok().flatten()
+        .zip(session.get::<String>("username").ok().flatten());
+    let pool = req.app_data::<web::Data<PgPool>>().cloned();
+
+    if let (Some((user_id, username)), Some(pool), Some(addr)) = (user, pool, req.peer_addr())
+        && let Some(response) = refuse_api(&pool, addr.ip(), user_id, &username).await
+    {
+        return Ok(req.into_response(response).map_into_right_body());
+    }
+
+    next.call(req).await.map(|res| res.map_into_left_body())
+}
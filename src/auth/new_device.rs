@@ -0,0 +1,89 @@
+//! Alerts for sign-ins from new devices.
+//!
+//! Every successful sign-in records its device and address. One the user has
+//! not signed in from before raises a high warning addressed to them alone,
+//! which is e-mailed as well when they opted in to warning e-mails. It points
+//! to the sessions tab of their account page, where "This wasn't me" locks
+//! the account and alerts the user administrators. `login.new_device_alerts`
+//! turns the alerts off; sign-ins are still recorded.
+
+use std::net::IpAddr;
+
+use sqlx::PgPool;
+
+use crate::models::sign_in_device::{self, Sighting, SignInDevice};
+use crate::models::{remember_token, setting, user};
+use crate::warnings::{self, clock};
+
+/// Record a successful sign-in and alert the user if it came from a new
+/// device or address. Failures are logged: they never stop the sign-in.
+pub async fn notice(pool: &PgPool, user_id: i64, username: &str, device: &str, ip: IpAddr) {
+    let sighting = match sign_in_device::record(pool, user_id, device, &ip.to_string()).await {
+        Ok(s) => s,
+        Err(e) => {
+            log::error!("Failed to record sign-in for {}: {}", username, e);
+            return;
+        }
+    };
+    let Sighting::New(id) = sighting else { return };
+    if !setting::get_bool(pool, "login.new_device_alerts").await {
+        return;
+    }
+    if let Err(e) = alert(pool, user_id, id, device, ip).await {
+        log::error!("Failed to alert {} about a new sign-in: {}", username, e);
+    }
+}
+
+async fn alert(pool: &PgPool, user_id: i64, sign_in_id: i64, device: &str, ip: IpAddr) -> Result<(), sqlx::Error> {
+    let at = clock::now().format("%Y-%m-%d %H:%M UTC");
+    let msg = format!(
+        "New sign-in to your account from {} ({}) on {}. If this wasn't you, choose \"This wasn't me\" under Sessions on your account page.",
+        device, ip, at
+    );
+    let details = serde_json::json!({
+        "sign_in_id": sign_in_id,
+        "device": device,
+        "ip": ip.to_string(),
+    });
+    let wid = warnings::create_warning(
+        pool, "high", "security", "event.security.new_sign_in", &msg, &details.to_string(), "user"
+    ).await?;
+    crate::models::entity::set_property(pool, wid, "link", "/account#sessions").await?;
+    warnings::create_receipts(pool, wid, &[user_id]).await?;
+    Ok(())
+}
+
+/// "This wasn't me": lock the account, forget its remembered devices, audit
+/// it and alert the user administrators.
+pub async fn report_not_me(pool: &PgPool, user_id: i64, username: &str, sign_in: &SignInDevice) -> Result<(), sqlx::Error> {
+    let reason = format!("Owner did not recognise the sign-in from {} ({})", sign_in.device, sign_in.ip);
+    user::lock(pool, user_id, &reason, None).await?;
+    if let Err(e) = remember_token::revoke_all(pool, user_id).await {
+        log::error!("Failed to revoke remembered devices of {}: {}", username, e);
+    }
+
+    let details = serde_json::json!({
+        "sign_in_id": sign_in.id,
+        "device": sign_in.device,
+        "ip": sign_in.ip,
+        "summary": format!("Account '{}' locked by its owner after an unrecognised sign-in", username)
+    });
+    let _ = crate::audit::log(pool, user_id, "user.locked", "user", user_id, details.clone()).await;
+
+    let admins: Vec<i64> = warnings::get_users_with_permission(pool, "users.edit").await?
+        .into_iter()
+        .filter(|&id| id != user_id)
+        .collect();
+    if admins.is_empty() {
+        return Ok(());
+    }
+    let msg = format!(
+        "Account '{}' was locked by its owner, who did not recognise a sign-in from {} ({}) on {}",
+        username, sign_in.device, sign_in.ip, sign_in.first_seen
+    );
+    let wid = warnings::create_warning(
+        pool, "critical", "security", "event.security.account_locked", &msg, &details.to_string(), "system"
+    ).await?;
+    warnings::create_receipts(pool, wid, &admins).await?;
+    Ok(())
+}
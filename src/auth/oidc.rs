@@ -0,0 +1,378 @@
+//! OpenID Connect single sign-on: authorization code flow with PKCE.
+//!
+//! `/login/oidc` stores a [`PendingLogin`] in the session and redirects to
+//! the provider; `/login/oidc/callback` checks the returned state, exchanges
+//! the code at the token endpoint and validates the ID token claims. The ID
+//! token comes straight from the token endpoint over TLS, so its issuer is
+//! vouched for by the connection and the signature is not checked (OIDC Core
+//! 3.1.3.7). That only holds over https, so issuers and endpoints with any
+//! other scheme are refused. An identity is linked to a user through the `oidc_subject`
+//! property (`issuer|sub`); unknown identities are provisioned with the
+//! configured default role, unless their e-mail belongs to an existing user —
+//! that user links the identity from their account page instead.
+
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+
+use crate::models::{entity, setting, user};
+use crate::ticketing::http;
+
+/// Session key holding the [`PendingLogin`] between redirect and callback.
+pub const SESSION_KEY: &str = "oidc_pending";
+/// User property linking an account to an identity.
+pub const SUBJECT_PROPERTY: &str = "oidc_subject";
+/// Clock skew tolerated when checking `exp`.
+const LEEWAY_SECS: i64 = 60;
+
+/// Provider connection and provisioning settings.
+#[derive(Debug, Clone)]
+pub struct OidcConfig {
+    /// Issuer URL without trailing slash.
+    pub issuer: String,
+    pub client_id: String,
+    pub client_secret: String,
+    /// Registered callback; derived from the request when empty.
+    pub redirect_url: String,
+    pub button_label: String,
+    pub auto_provision: bool,
+    pub default_role: String,
+}
+
+impl OidcConfig {
+    /// Load from settings plus the `OIDC_CLIENT_SECRET` environment variable.
+    /// `None` when disabled or incomplete.
+    pub async fn load(pool: &PgPool) -> Option<Self> {
+        if !setting::get_bool(pool, "oidc.enabled").await {
+            return None;
+        }
+        let issuer = setting::get_value(pool, "oidc.issuer", "").await;
+        let client_id = setting::get_value(pool, "oidc.client_id", "").await;
+        let client_secret = std::env::var("OIDC_CLIENT_SECRET").unwrap_or_default();
+        if issuer.trim().is_empty() || client_id.trim().is_empty() || client_secret.is_empty() {
+            log::warn!("Single sign-on is enabled but issuer, client id or OIDC_CLIENT_SECRET is not set");
+            return None;
+        }
+        if !is_https(issuer.trim()) {
+            log::warn!("Single sign-on is enabled but the issuer {} is not an https URL", issuer.trim());
+            return None;
+        }
+        let button_label = setting::get_value(pool, "oidc.button_label", "").await;
+        Some(Self {
+            issuer: issuer.trim().trim_end_matches('/').to_string(),
+            client_id: client_id.trim().to_string(),
+            client_secret,
+            redirect_url: setting::get_value(pool, "oidc.redirect_url", "").await.trim().to_string(),
+            button_label: if button_label.trim().is_empty() { "Sign in with SSO".to_string() } else { button_label },
+            auto_provision: setting::get_bool(pool, "oidc.auto_provision").await,
+            default_role: setting::get_value(pool, "oidc.default_role", "viewer").await.trim().to_string(),
+        })
+    }
+
+    /// Callback URL sent to the provider: the configured one, else
+    /// `/login/oidc/callback` on the host the request came in on.
+    pub fn redirect_uri(&self, scheme: &str, host: &str) -> String {
+        if self.redirect_url.is_empty() {
+            format!("{}://{}/login/oidc/callback", scheme, host)
+        } else {
+            self.redirect_url.clone()
+        }
+    }
+
+    /// Link key of an identity issued by this provider.
+    pub fn subject_key(&self, sub: &str) -> String {
+        format!("{}|{}", self.issuer, sub)
+    }
+}
+
+/// Endpoints from the provider's discovery document.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProviderMetadata {
+    pub issuer: String,
+    pub authorization_endpoint: String,
+    pub token_endpoint: String,
+}
+
+/// Fetch `{issuer}/.well-known/openid-configuration`.
+pub async fn discover(issuer: &str) -> Result<ProviderMetadata, String> {
+    let url = format!("{}/.well-known/openid-configuration", issuer);
+    let response = http::send("GET", &url, &[], None).await?;
+    if !response.is_success() {
+        return Err(format!("Discovery returned HTTP {}", response.status));
+    }
+    let metadata: ProviderMetadata = serde_json::from_str(&response.body)
+        .map_err(|e| format!("Bad discovery document: {}", e))?;
+    check_metadata(issuer, &metadata)?;
+    Ok(metadata)
+}
+
+/// Whether a URL uses https, the only scheme the ID token can be trusted over.
+pub fn is_https(url: &str) -> bool {
+    url.get(..8).is_some_and(|scheme| scheme.eq_ignore_ascii_case("https://"))
+}
+
+/// Check a discovery document belongs to `issuer` and only names https
+/// endpoints.
+pub fn check_metadata(issuer: &str, metadata: &ProviderMetadata) -> Result<(), String> {
+    if !is_https(issuer) {
+        return Err(format!("Issuer {} is not an https URL", issuer));
+    }
+    if metadata.issuer.trim_end_matches('/') != issuer {
+        return Err(format!("Discovery document is for issuer {}", metadata.issuer));
+    }
+    for endpoint in [&metadata.authorization_endpoint, &metadata.token_endpoint] {
+        if !is_https(endpoint) {
+            return Err(format!("Provider endpoint {} is not an https URL", endpoint));
+        }
+    }
+    Ok(())
+}
+
+/// Flow state kept in the session while the user is at the provider.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingLogin {
+    pub state: String,
+    pub nonce: String,
+    /// PKCE code verifier.
+    pub verifier: String,
+    /// Local path to return to after sign-in.
+    pub next: String,
+    /// Set when a signed-in user is linking the identity to their account.
+    pub link_user_id: Option<i64>,
+}
+
+impl PendingLogin {
+    pub fn new(next: &str, link_user_id: Option<i64>) -> Self {
+        Self {
+            state: random_token(),
+            nonce: random_token(),
+            verifier: random_token(),
+            next: next.to_string(),
+            link_user_id,
+        }
+    }
+}
+
+fn random_token() -> String {
+    let mut rng = rand::rng();
+    let bytes: Vec<u8> = (0..32).map(|_| rng.random()).collect();
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+/// PKCE `S256` challenge for a verifier.
+pub fn pkce_challenge(verifier: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()))
+}
+
+/// Percent-encode for a query string or form body (RFC 3986 unreserved kept).
+fn encode(value: &str) -> String {
+    value.bytes().map(|b| match b {
+        b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => (b as char).to_string(),
+        _ => format!("%{:02X}", b),
+    }).collect()
+}
+
+fn encode_pairs(pairs: &[(&str, &str)]) -> String {
+    pairs.iter()
+        .map(|(k, v)| format!("{}={}", k, encode(v)))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+/// Provider URL the browser is sent to.
+pub fn authorization_url(
+    metadata: &ProviderMetadata,
+    config: &OidcConfig,
+    redirect_uri: &str,
+    pending: &PendingLogin,
+) -> String {
+    let challenge = pkce_challenge(&pending.verifier);
+    let query = encode_pairs(&[
+        ("response_type", "code"),
+        ("client_id", &config.client_id),
+        ("redirect_uri", redirect_uri),
+        ("scope", "openid email profile"),
+        ("state", &pending.state),
+        ("nonce", &pending.nonce),
+        ("code_challenge", &challenge),
+        ("code_challenge_method", "S256"),
+    ]);
+    let separator = if metadata.authorization_endpoint.contains('?') { '&' } else { '?' };
+    format!("{}{}{}", metadata.authorization_endpoint, separator, query)
+}
+
+/// Exchange an authorization code for the ID token.
+pub async fn exchange_code(
+    metadata: &ProviderMetadata,
+    config: &OidcConfig,
+    redirect_uri: &str,
+    code: &str,
+    verifier: &str,
+) -> Result<String, String> {
+    let body = encode_pairs(&[
+        ("grant_type", "authorization_code"),
+        ("code", code),
+        ("redirect_uri", redirect_uri),
+        ("client_id", &config.client_id),
+        ("client_secret", &config.client_secret),
+        ("code_verifier", verifier),
+    ]);
+    let headers = [("Content-Type", "application/x-www-form-urlencoded")];
+    let response = http::send("POST", &metadata.token_endpoint, &headers, Some(&body)).await?;
+    if !response.is_success() {
+        let excerpt: String = response.body.chars().take(200).collect();
+        return Err(format!("Token endpoint returned HTTP {}: {}", response.status, excerpt.trim()));
+    }
+    let tokens: serde_json::Value = serde_json::from_str(&response.body)
+        .map_err(|e| format!("Bad token response: {}", e))?;
+    tokens.get("id_token")
+        .and_then(|t| t.as_str())
+        .map(str::to_string)
+        .ok_or_else(|| "Token response has no id_token".to_string())
+}
+
+/// ID token claims used for sign-in and provisioning.
+#[derive(Debug, Clone, Deserialize)]
+pub struct IdClaims {
+    pub iss: String,
+    pub sub: String,
+    /// A single client id or a list of them.
+    pub aud: serde_json::Value,
+    pub exp: i64,
+    #[serde(default)]
+    pub nonce: Option<String>,
+    #[serde(default)]
+    pub email: Option<String>,
+    #[serde(default)]
+    pub email_verified: Option<bool>,
+    #[serde(default)]
+    pub preferred_username: Option<String>,
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+impl IdClaims {
+    /// E-mail address, if the provider says it is verified. A missing
+    /// `email_verified` claim counts as unverified.
+    pub fn verified_email(&self) -> Option<&str> {
+        self.email.as_deref()
+            .filter(|e| !e.trim().is_empty() && self.email_verified == Some(true))
+    }
+}
+
+/// Decode an ID token and check issuer, audience, expiry and nonce.
+pub fn validate_id_token(
+    id_token: &str,
+    issuer: &str,
+    client_id: &str,
+    nonce: &str,
+    now: i64,
+) -> Result<IdClaims, String> {
+    let mut parts = id_token.split('.');
+    let (Some(_header), Some(payload), Some(_signature), None) = (parts.next(), parts.next(), parts.next(), parts.next()) else {
+        return Err("ID token is not a JWT".to_string());
+    };
+    let bytes = URL_SAFE_NO_PAD.decode(payload.trim_end_matches('='))
+        .map_err(|_| "ID token payload is not base64url".to_string())?;
+    let claims: IdClaims = serde_json::from_slice(&bytes)
+        .map_err(|e| format!("Bad ID token claims: {}", e))?;
+
+    if claims.iss.trim_end_matches('/') != issuer {
+        return Err(format!("ID token issued by {}", claims.iss));
+    }
+    let audience_ok = match &claims.aud {
+        serde_json::Value::String(aud) => aud == client_id,
+        serde_json::Value::Array(auds) => auds.iter().any(|a| a.as_str() == Some(client_id)),
+        _ => false,
+    };
+    if !audience_ok {
+        return Err("ID token is not for this client".to_string());
+    }
+    if claims.exp + LEEWAY_SECS < now {
+        return Err("ID token has expired".to_string());
+    }
+    if claims.nonce.as_deref() != Some(nonce) {
+        return Err("ID token nonce does not match".to_string());
+    }
+    if claims.sub.is_empty() {
+        return Err("ID token has no subject".to_string());
+    }
+    Ok(claims)
+}
+
+/// How an identity maps onto a local account.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Resolution {
+    /// Already linked; sign in as (id, username).
+    Linked(i64, String),
+    /// A new account was created.
+    Provisioned(i64, String),
+    /// The e-mail belongs to an unlinked local account.
+    EmailInUse,
+    /// Unknown identity and provisioning is off.
+    Unknown,
+}
+
+/// Username for a new account: the preferred username, else the e-mail's
+/// local part, reduced to letters, digits and underscores.
+pub fn username_base(claims: &IdClaims) -> String {
+    let source = claims.preferred_username.as_deref()
+        .filter(|u| !u.trim().is_empty())
+        .or_else(|| claims.email.as_deref().and_then(|e| e.split('@').next()))
+        .unwrap_or("");
+    let base: String = source.trim().chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '_' })
+        .take(40)
+        .collect();
+    if base.trim_matches('_').len() < 2 { "sso_user".to_string() } else { base }
+}
+
+/// Find the account for an identity, creating one when allowed.
+pub async fn resolve_user(pool: &PgPool, config: &OidcConfig, claims: &IdClaims) -> Result<Resolution, sqlx::Error> {
+    let subject = config.subject_key(&claims.sub);
+    if let Some(u) = user::find_by_oidc_subject(pool, &subject).await? {
+        return Ok(Resolution::Linked(u.id, u.username));
+    }
+    let email = claims.verified_email().unwrap_or("");
+    if !email.is_empty() && user::find_id_by_email(pool, email).await?.is_some() {
+        return Ok(Resolution::EmailInUse);
+    }
+    if !config.auto_provision {
+        return Ok(Resolution::Unknown);
+    }
+
+    let username = entity::available_name(pool, "user", &username_base(claims)).await?;
+    let display_name = claims.name.as_deref()
+        .filter(|n| !n.trim().is_empty())
+        .unwrap_or(&username)
+        .trim()
+        .to_string();
+    // No password: the account signs in through the provider only
+    let id = user::create(pool, &user::NewUser {
+        username: username.clone(),
+        password: String::new(),
+        email: email.to_string(),
+        display_name,
+    }).await?;
+    entity::set_property(pool, id, SUBJECT_PROPERTY, &subject).await?;
+    if !user::assign_role_by_name(pool, id, &config.default_role).await? {
+        log::warn!("SSO default role '{}' does not exist; assigning viewer", config.default_role);
+        user::assign_default_role(pool, id).await?;
+    }
+    Ok(Resolution::Provisioned(id, username))
+}
+
+/// Link an identity to a user. Returns false if it belongs to someone else.
+pub async fn link(pool: &PgPool, config: &OidcConfig, user_id: i64, sub: &str) -> Result<bool, sqlx::Error> {
+    let subject = config.subject_key(sub);
+    if let Some(existing) = user::find_by_oidc_subject(pool, &subject).await?
+        && existing.id != user_id
+    {
+        return Ok(false);
+    }
+    entity::set_property(pool, user_id, SUBJECT_PROPERTY, &subject).await?;
+    Ok(true)
+}
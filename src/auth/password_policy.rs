@@ -0,0 +1,161 @@
+//! Password policy.
+//!
+//! Every new password — set on the user form, through the API or on the
+//! account page — must satisfy the policy configured in settings:
+//!
+//! - `auth.password_min_length`: minimum length in characters (at least 8)
+//! - `auth.password_min_classes`: how many of lowercase, uppercase, digits and
+//!   symbols it must mix
+//! - `auth.password_banned`: comma- or newline-separated passwords refused
+//!   regardless of case
+//! - `auth.password_history`: how many of the user's most recent passwords,
+//!   the current one included, cannot be reused (0 allows any)
+//! - `auth.password_max_age_days`: after how many days a password must be
+//!   changed at the next sign-in (0 never)
+
+use sqlx::PgPool;
+
+use crate::auth::{password, validate};
+use crate::models::{password_history, setting, user};
+
+/// Session key set at sign-in when the password has expired; the user is
+/// kept on the account page until they change it.
+pub const EXPIRED_KEY: &str = "password_expired";
+
+/// Paths reachable while the password is expired: the account page itself,
+/// signing out, static assets and the session keepalive.
+const OPEN_WHILE_EXPIRED: &[&str] = &["/account", "/logout", "/session/keepalive"];
+
+/// Whether a request path may go through while the password is expired.
+pub fn allowed_while_expired(path: &str) -> bool {
+    OPEN_WHILE_EXPIRED.contains(&path) || path.starts_with("/static/")
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PasswordPolicy {
+    pub min_length: usize,
+    pub min_classes: usize,
+    pub banned: Vec<String>,
+    pub history: i64,
+    pub max_age_days: i64,
+}
+
+impl Default for PasswordPolicy {
+    fn default() -> Self {
+        PasswordPolicy { min_length: 8, min_classes: 1, banned: vec![], history: 0, max_age_days: 0 }
+    }
+}
+
+/// Parse the banned-password list: comma- or newline-separated, lowercased.
+pub fn parse_banned(value: &str) -> Vec<String> {
+    value.split([',', '\n'])
+        .map(|p| p.trim().to_lowercase())
+        .filter(|p| !p.is_empty())
+        .collect()
+}
+
+/// How many character classes — lowercase, uppercase, digit, symbol — the
+/// password uses.
+pub fn classes(password: &str) -> usize {
+    [
+        password.chars().any(|c| c.is_lowercase()),
+        password.chars().any(|c| c.is_uppercase()),
+        password.chars().any(|c| c.is_ascii_digit()),
+        password.chars().any(|c| !c.is_alphanumeric()),
+    ].iter().filter(|&&used| used).count()
+}
+
+impl PasswordPolicy {
+    pub async fn load(pool: &PgPool) -> Self {
+        PasswordPolicy {
+            min_length: setting::get_i64(pool, "auth.password_min_length").await.max(8) as usize,
+            min_classes: setting::get_i64(pool, "auth.password_min_classes").await.clamp(1, 4) as usize,
+            banned: parse_banned(&setting::get_value(pool, "auth.password_banned", "").await),
+            history: setting::get_i64(pool, "auth.password_history").await.clamp(0, password_history::KEPT),
+            max_age_days: setting::get_i64(pool, "auth.password_max_age_days").await.max(0),
+        }
+    }
+
+    /// Problems with the password itself, for the user `username`.
+    pub fn check(&self, password: &str, username: &str) -> Vec<String> {
+        if let Some(error) = validate::validate_password(password) {
+            return vec![error];
+        }
+        let mut errors = vec![];
+        if password.chars().count() < self.min_length {
+            errors.push(format!("Password must be at least {} characters", self.min_length));
+        }
+        if classes(password) < self.min_classes {
+            errors.push(format!(
+                "Password must mix at least {} of lowercase letters, uppercase letters, digits and symbols",
+                self.min_classes
+            ));
+        }
+        let lowered = password.to_lowercase();
+        if self.banned.contains(&lowered) {
+            errors.push("This password is too common; choose another".to_string());
+        }
+        let username = username.trim().to_lowercase();
+        if username.chars().count() >= 3 && lowered.contains(&username) {
+            errors.push("Password must not contain the username".to_string());
+        }
+        errors
+    }
+
+    /// The rules in a sentence, for password forms.
+    pub fn describe(&self) -> String {
+        let mut rules = format!("At least {} characters", self.min_length);
+        if self.min_classes > 1 {
+            rules.push_str(&format!(
+                ", mixing {} of lowercase letters, uppercase letters, digits and symbols",
+                self.min_classes
+            ));
+        }
+        if self.history > 0 {
+            rules.push_str(&format!(", and not one of the last {} passwords", self.history));
+        }
+        rules.push('.');
+        rules
+    }
+
+    /// Whether `password` is the user's current one or among the recent
+    /// ones the policy forbids reusing.
+    pub async fn is_reused(&self, pool: &PgPool, user_id: i64, password: &str) -> Result<bool, sqlx::Error> {
+        if self.history == 0 {
+            return Ok(false);
+        }
+        let mut hashes: Vec<String> = user::find_password_hash_by_id(pool, user_id).await?
+            .into_iter()
+            .filter(|h| !h.is_empty())
+            .collect();
+        hashes.extend(password_history::recent(pool, user_id, self.history - 1).await?);
+        Ok(hashes.iter().any(|h| password::verify_password(password, h).unwrap_or(false)))
+    }
+
+    /// Every problem with a new password: [`check`](Self::check) plus reuse
+    /// for an existing user.
+    pub async fn validate(
+        &self,
+        pool: &PgPool,
+        password: &str,
+        username: &str,
+        user_id: Option<i64>,
+    ) -> Result<Vec<String>, sqlx::Error> {
+        let mut errors = self.check(password, username);
+        if let Some(id) = user_id
+            && errors.is_empty()
+            && self.is_reused(pool, id, password).await?
+        {
+            errors.push(format!("Password must not be one of the last {} passwords", self.history));
+        }
+        Ok(errors)
+    }
+
+    /// Whether the user's password is older than the maximum age.
+    pub async fn is_expired(&self, pool: &PgPool, user_id: i64) -> Result<bool, sqlx::Error> {
+        if self.max_age_days == 0 {
+            return Ok(false);
+        }
+        password_history::is_older_than(pool, user_id, self.max_age_days).await
+    }
+}
@@ -0,0 +1,43 @@
+//! Where to send a user after login: the deep link they were bounced from,
+//! else their role's landing page, else the dashboard.
+
+/// Landing page when none of the user's roles configures one.
+pub const DEFAULT_LANDING: &str = "/dashboard";
+
+/// Return `target` if it is a same-site path that is safe to redirect to.
+/// Rejects absolute and protocol-relative URLs, backslash tricks, control
+/// characters, and the login/logout endpoints themselves.
+pub fn safe_path(target: &str) -> Option<&str> {
+    let target = target.trim();
+    if !target.starts_with('/') || target.starts_with("//") {
+        return None;
+    }
+    if target.contains('\\') || target.chars().any(|c| c.is_control()) {
+        return None;
+    }
+    let path = target.split(['?', '#']).next().unwrap_or("");
+    if path == "/login" || path == "/logout" {
+        return None;
+    }
+    Some(target)
+}
+
+/// `/login` URL carrying the page the user originally asked for.
+pub fn login_url(next: &str) -> String {
+    match safe_path(next) {
+        Some(path) if path != "/" => format!("/login?next={}", encode(path)),
+        _ => "/login".to_string(),
+    }
+}
+
+/// Percent-encode a path for use as a query parameter value.
+fn encode(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for b in s.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => out.push(b as char),
+            _ => out.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    out
+}
@@ -0,0 +1,64 @@
+//! "Remember this device" cookie carrying a rotating login token.
+//! Token storage and rotation live in `models::remember_token`.
+
+use actix_web::cookie::{Cookie, SameSite, time};
+use actix_web::HttpRequest;
+use sqlx::PgPool;
+
+use crate::models::setting;
+
+/// Name of the persistent login cookie.
+pub const COOKIE_NAME: &str = "ahlt_remember";
+
+/// How long an issued token stays valid.
+pub async fn lifetime(pool: &PgPool) -> chrono::Duration {
+    setting::get_duration(pool, "session.remember_me_days").await
+}
+
+/// Cookie holding a raw token, expiring with it.
+pub fn cookie(token: String, lifetime: chrono::Duration, secure: bool) -> Cookie<'static> {
+    Cookie::build(COOKIE_NAME, token)
+        .path("/")
+        .http_only(true)
+        .secure(secure)
+        .same_site(SameSite::Lax)
+        .max_age(time::Duration::seconds(lifetime.num_seconds()))
+        .finish()
+}
+
+/// Cookie that clears the remember-me cookie in the browser.
+pub fn removal_cookie() -> Cookie<'static> {
+    let mut cookie = Cookie::build(COOKIE_NAME, "").path("/").finish();
+    cookie.make_removal();
+    cookie
+}
+
+/// Whether the request arrived over HTTPS (directly or via a proxy header).
+pub fn is_secure(req: &HttpRequest) -> bool {
+    req.connection_info().scheme() == "https"
+}
+
+/// Short label for a device, from its User-Agent header.
+pub fn device_label(user_agent: &str) -> String {
+    let browser = ["Edg/", "Firefox/", "Chrome/", "Safari/"]
+        .iter()
+        .find(|b| user_agent.contains(*b))
+        .map(|b| match *b {
+            "Edg/" => "Edge",
+            "Firefox/" => "Firefox",
+            "Chrome/" => "Chrome",
+            _ => "Safari",
+        });
+    let os = [("Windows", "Windows"), ("Android", "Android"), ("iPhone", "iOS"), ("iPad", "iOS"),
+              ("Mac OS X", "macOS"), ("Linux", "Linux")]
+        .iter()
+        .find(|(needle, _)| user_agent.contains(needle))
+        .map(|(_, name)| *name);
+    match (browser, os) {
+        (Some(b), Some(o)) => format!("{} on {}", b, o),
+        (Some(b), None) => b.to_string(),
+        (None, Some(o)) => o.to_string(),
+        (None, None) if user_agent.trim().is_empty() => "Unknown device".to_string(),
+        (None, None) => user_agent.chars().take(60).collect(),
+    }
+}
@@ -1,5 +1,9 @@
 use actix_session::Session;
+use sqlx::PgPool;
+
+use crate::auth::redirect;
 use crate::errors::AppError;
+use crate::models::{nav_item, permission, role};
 
 /// Wrapper around permission codes with a `has()` method for use in Askama templates.
 #[derive(Debug, Clone, Default)]
@@ -21,6 +25,46 @@ impl Permissions {
     }
 }
 
+/// Populate the session for a freshly authenticated user: identity,
+/// aggregated permissions, landing page and the timeout clock.
+pub async fn sign_in(
+    session: &Session,
+    pool: &PgPool,
+    user_id: i64,
+    username: &str,
+    keep_signed_in: bool,
+) -> Result<(), AppError> {
+    // Multi-role: aggregate permissions across all assigned roles
+    let perms = permission::find_codes_by_user_id(pool, user_id).await?;
+    let landing = role::find_landing_page_for_user(pool, user_id).await?
+        .filter(|p| redirect::safe_path(p).is_some())
+        .unwrap_or_else(|| redirect::DEFAULT_LANDING.to_string());
+    let now = chrono::Utc::now().timestamp();
+
+    let _ = session.insert("user_id", user_id);
+    let _ = session.insert("username", username);
+    let _ = session.insert("permissions", perms.join(","));
+    let _ = session.insert("nav_version", nav_item::version(pool).await?);
+    let _ = session.insert("landing_page", &landing);
+    let _ = session.insert("login_at", now);
+    let _ = session.insert("last_seen", now);
+    let _ = session.insert("keep_signed_in", keep_signed_in);
+    Ok(())
+}
+
+/// Reload the session's permissions when menus or role grants changed since
+/// they were loaded (see `nav_item::version`). Returns true when reloaded.
+pub async fn refresh_permissions(session: &Session, pool: &PgPool, user_id: i64) -> Result<bool, AppError> {
+    let version = nav_item::version(pool).await?;
+    if session.get::<i64>("nav_version").unwrap_or(None) == Some(version) {
+        return Ok(false);
+    }
+    let perms = permission::find_codes_by_user_id(pool, user_id).await?;
+    let _ = session.insert("permissions", perms.join(","));
+    let _ = session.insert("nav_version", version);
+    Ok(true)
+}
+
 pub fn get_user_id(session: &Session) -> Option<i64> {
     session.get::<i64>("user_id").unwrap_or(None)
 }
@@ -0,0 +1,128 @@
+//! Session cookie key ring, so `SESSION_KEY` can be rotated without signing
+//! everyone out.
+//!
+//! `SESSION_KEY` encrypts new cookies; `SESSION_KEY_PREVIOUS` lists older
+//! keys (comma-separated) that are still accepted. [`upgrade_cookie`] runs
+//! in front of the session middleware and re-encrypts a cookie sealed with
+//! a previous key under the current one. The session middleware then sees
+//! an ordinary cookie, and since every authenticated request touches the
+//! session, the browser gets the re-encrypted cookie back. Once sessions
+//! have cycled, drop the old key from `SESSION_KEY_PREVIOUS`.
+
+use actix_web::{
+    Error, web,
+    body::MessageBody,
+    cookie::{Cookie, CookieJar, Key},
+    dev::{ServiceRequest, ServiceResponse},
+    http::header::{self, HeaderValue},
+    middleware::Next,
+};
+
+/// Name of the session cookie (the session middleware's default).
+pub const COOKIE_NAME: &str = "id";
+
+/// Shortest key accepted; shorter values are ignored.
+pub const MIN_KEY_LEN: usize = 64;
+
+#[derive(Clone)]
+pub struct SessionKeys {
+    pub current: Key,
+    pub previous: Vec<Key>,
+}
+
+impl SessionKeys {
+    /// Build from the raw values of `SESSION_KEY` and `SESSION_KEY_PREVIOUS`.
+    /// Without a usable current key a random one is generated, and sessions
+    /// are lost on restart.
+    pub fn from_values(current: Option<&str>, previous: Option<&str>) -> Self {
+        let current = match current {
+            Some(val) if val.len() >= MIN_KEY_LEN => {
+                log::info!("Using SESSION_KEY from environment");
+                Key::from(val.as_bytes())
+            }
+            Some(val) => {
+                log::warn!("SESSION_KEY too short ({} bytes, need {}+) — generating random key", val.len(), MIN_KEY_LEN);
+                Key::generate()
+            }
+            None => {
+                log::warn!("No SESSION_KEY set — generating random key (sessions lost on restart)");
+                Key::generate()
+            }
+        };
+        let mut keys = Vec::new();
+        for val in previous.unwrap_or("").split(',').map(str::trim).filter(|v| !v.is_empty()) {
+            if val.len() >= MIN_KEY_LEN {
+                keys.push(Key::from(val.as_bytes()));
+            } else {
+                log::warn!("Ignoring a SESSION_KEY_PREVIOUS entry shorter than {} bytes", MIN_KEY_LEN);
+            }
+        }
+        if !keys.is_empty() {
+            log::info!("Accepting session cookies sealed with {} previous key(s)", keys.len());
+        }
+        SessionKeys { current, previous: keys }
+    }
+
+    /// Build from the environment.
+    pub fn from_env() -> Self {
+        Self::from_values(
+            std::env::var("SESSION_KEY").ok().as_deref(),
+            std::env::var("SESSION_KEY_PREVIOUS").ok().as_deref(),
+        )
+    }
+
+    /// Seal a session cookie value with the current key, as the session
+    /// middleware does.
+    pub fn seal(&self, value: &str) -> String {
+        let mut jar = CookieJar::new();
+        jar.private_mut(&self.current).add(Cookie::new(COOKIE_NAME, value.to_string()));
+        jar.get(COOKIE_NAME).map(|c| c.value().to_string()).unwrap_or_default()
+    }
+
+    /// A sealed value re-encrypted under the current key, if it was sealed
+    /// with a previous one. `None` if it is current already or not valid
+    /// under any key.
+    pub fn reseal(&self, sealed: &str) -> Option<String> {
+        let cookie = Cookie::new(COOKIE_NAME, sealed.to_string());
+        if CookieJar::new().private(&self.current).decrypt(cookie.clone()).is_some() {
+            return None;
+        }
+        let plain = self.previous.iter()
+            .find_map(|key| CookieJar::new().private(key).decrypt(cookie.clone()))?;
+        Some(self.seal(plain.value()))
+    }
+}
+
+/// Middleware: swap a session cookie sealed with a previous key for one
+/// sealed with the current key, before the session middleware reads it.
+/// Wrap it outside the session middleware.
+pub async fn upgrade_cookie(
+    mut req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    let keys = req.app_data::<web::Data<SessionKeys>>().cloned();
+    if let Some(keys) = keys.filter(|k| !k.previous.is_empty()) {
+        // Parse the header directly: `req.cookie()` caches what it parses,
+        // and the session middleware must see the rewritten value
+        let mut changed = false;
+        let mut pairs = Vec::new();
+        for value in req.headers().get_all(header::COOKIE) {
+            for pair in value.to_str().unwrap_or("").split(';').map(str::trim).filter(|p| !p.is_empty()) {
+                let resealed = Cookie::parse_encoded(pair).ok()
+                    .filter(|c| c.name() == COOKIE_NAME)
+                    .and_then(|c| keys.reseal(c.value()));
+                match resealed {
+                    Some(value) => {
+                        changed = true;
+                        pairs.push(Cookie::new(COOKIE_NAME, value).encoded().to_string());
+                    }
+                    None => pairs.push(pair.to_string()),
+                }
+            }
+        }
+        if changed && let Ok(value) = HeaderValue::from_str(&pairs.join("; ")) {
+            req.headers_mut().insert(header::COOKIE, value);
+        }
+    }
+    next.call(req).await
+}
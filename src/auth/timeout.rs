@@ -0,0 +1,78 @@
+//! Session idle timeout and absolute lifetime.
+//!
+//! `require_auth` stamps `login_at` / `last_seen` (unix seconds) into the
+//! session and rejects sessions past either limit. "Keep me signed in"
+//! lengthens only the idle timeout; the absolute lifetime always applies.
+//! The latest expiry per user is kept in a `SessionTracker` so the
+//! notifications WebSocket can warn before it is reached.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use sqlx::PgPool;
+
+use crate::models::setting;
+
+/// Idle and absolute limits for one session, in seconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SessionLimits {
+    pub idle_secs: i64,
+    pub absolute_secs: i64,
+}
+
+impl SessionLimits {
+    /// Read the limits from settings; `keep_signed_in` selects the longer idle timeout.
+    pub async fn load(pool: &PgPool, keep_signed_in: bool) -> Self {
+        let idle_minutes = if keep_signed_in {
+            setting::get_i64(pool, "session.keep_signed_in_idle_minutes").await
+        } else {
+            setting::get_i64(pool, "session.idle_timeout_minutes").await
+        };
+        let absolute_hours = setting::get_i64(pool, "session.absolute_lifetime_hours").await;
+        Self {
+            idle_secs: idle_minutes * 60,
+            absolute_secs: absolute_hours * 3600,
+        }
+    }
+
+    /// When a session that started at `login_at` and was last used at
+    /// `last_seen` expires: whichever limit is reached first.
+    pub fn expires_at(&self, login_at: i64, last_seen: i64) -> i64 {
+        (last_seen + self.idle_secs).min(login_at + self.absolute_secs)
+    }
+
+    /// Whether the session is still valid at `now`.
+    pub fn is_active(&self, login_at: i64, last_seen: i64, now: i64) -> bool {
+        now < self.expires_at(login_at, last_seen)
+    }
+}
+
+/// Latest known session expiry (unix seconds) per user.
+#[derive(Clone, Default)]
+pub struct SessionTracker {
+    expiries: Arc<Mutex<HashMap<i64, i64>>>,
+}
+
+impl SessionTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the expiry of the user's most recently used session.
+    pub fn record(&self, user_id: i64, expires_at: i64) {
+        let mut map = self.expiries.lock().unwrap_or_else(|e| e.into_inner());
+        map.insert(user_id, expires_at);
+    }
+
+    /// Forget the user's session (e.g. on logout).
+    pub fn forget(&self, user_id: i64) {
+        let mut map = self.expiries.lock().unwrap_or_else(|e| e.into_inner());
+        map.remove(&user_id);
+    }
+
+    /// Expiry of the user's most recently used session, if known.
+    pub fn expires_at(&self, user_id: i64) -> Option<i64> {
+        let map = self.expiries.lock().unwrap_or_else(|e| e.into_inner());
+        map.get(&user_id).copied()
+    }
+}
@@ -0,0 +1,186 @@
+//! Word (.docx) exports of minutes and meeting agenda packs.
+//!
+//! The body of each document is rendered from an Askama template under
+//! `templates/docx/` (`word/document.xml`); this module wraps it in a
+//! minimal WordprocessingML package with the shared styles the templates
+//! refer to (`Title`, `Subtitle`, `Heading1`–`Heading3`, `Meta`,
+//! `TableGrid`). Entries carry a fixed timestamp, so exporting unchanged
+//! content gives the same file and the same recorded checksum.
+
+use std::io::Write;
+
+use askama::Template;
+use sqlx::PgPool;
+use zip::write::SimpleFileOptions;
+
+use crate::errors::AppError;
+use crate::models::agenda_point::{self, AgendaPointDetail};
+use crate::models::coa::{self, CoaDetail};
+use crate::models::meeting::{self, MeetingDetail};
+use crate::models::minutes::{self, Minutes};
+use crate::models::proposal::{self, ProposalDetail};
+use crate::templates_structs::{AgendaDocxTemplate, MinutesDocxTemplate};
+
+pub const CONTENT_TYPE: &str = "application/vnd.openxmlformats-officedocument.wordprocessingml.document";
+
+/// One agenda point in the agenda pack, with the proposal it was scheduled
+/// from and the courses of action considered.
+#[derive(Debug, Clone)]
+pub struct AgendaPackItem {
+    pub number: usize,
+    pub point: AgendaPointDetail,
+    pub proposal: Option<ProposalDetail>,
+    pub coas: Vec<CoaDetail>,
+}
+
+impl AgendaPackItem {
+    pub fn is_decision(&self) -> bool {
+        self.point.item_type == "decision"
+    }
+}
+
+/// File name for exported minutes, e.g. `minutes-board_2026_03.docx`.
+pub fn minutes_file_name(minutes: &Minutes) -> String {
+    format!("minutes-{}.docx", minutes.meeting_name)
+}
+
+/// File name for a meeting's agenda pack, e.g. `agenda-board_2026_03.docx`.
+pub fn agenda_file_name(meeting: &MeetingDetail) -> String {
+    format!("agenda-{}.docx", meeting.name)
+}
+
+/// Escape text for XML content and attribute values.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+const CONTENT_TYPES_XML: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types">
+<Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/>
+<Default Extension="xml" ContentType="application/xml"/>
+<Override PartName="/word/document.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.document.main+xml"/>
+<Override PartName="/word/styles.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.styles+xml"/>
+<Override PartName="/docProps/core.xml" ContentType="application/vnd.openxmlformats-package.core-properties+xml"/>
+</Types>"#;
+
+const PACKAGE_RELS_XML: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+<Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="word/document.xml"/>
+<Relationship Id="rId2" Type="http://schemas.openxmlformats.org/package/2006/relationships/metadata/core-properties" Target="docProps/core.xml"/>
+</Relationships>"#;
+
+const DOCUMENT_RELS_XML: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+<Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/styles" Target="styles.xml"/>
+</Relationships>"#;
+
+const STYLES_XML: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:styles xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">
+<w:docDefaults>
+<w:rPrDefault><w:rPr><w:rFonts w:ascii="Calibri" w:hAnsi="Calibri" w:cs="Calibri"/><w:sz w:val="22"/><w:lang w:val="en-GB"/></w:rPr></w:rPrDefault>
+<w:pPrDefault><w:pPr><w:spacing w:after="120" w:line="264" w:lineRule="auto"/></w:pPr></w:pPrDefault>
+</w:docDefaults>
+<w:style w:type="paragraph" w:default="1" w:styleId="Normal"><w:name w:val="Normal"/><w:qFormat/></w:style>
+<w:style w:type="paragraph" w:styleId="Title"><w:name w:val="Title"/><w:basedOn w:val="Normal"/><w:next w:val="Normal"/><w:qFormat/>
+<w:pPr><w:spacing w:after="60"/></w:pPr><w:rPr><w:b/><w:sz w:val="48"/></w:rPr></w:style>
+<w:style w:type="paragraph" w:styleId="Subtitle"><w:name w:val="Subtitle"/><w:basedOn w:val="Normal"/><w:next w:val="Normal"/><w:qFormat/>
+<w:pPr><w:pBdr><w:bottom w:val="single" w:sz="12" w:space="4" w:color="1F4E79"/></w:pBdr><w:spacing w:after="240"/></w:pPr><w:rPr><w:color w:val="595959"/><w:sz w:val="26"/></w:rPr></w:style>
+<w:style w:type="paragraph" w:styleId="Heading1"><w:name w:val="heading 1"/><w:basedOn w:val="Normal"/><w:next w:val="Normal"/><w:qFormat/>
+<w:pPr><w:keepNext/><w:spacing w:before="360" w:after="120"/><w:outlineLvl w:val="0"/></w:pPr><w:rPr><w:b/><w:color w:val="1F4E79"/><w:sz w:val="32"/></w:rPr></w:style>
+<w:style w:type="paragraph" w:styleId="Heading2"><w:name w:val="heading 2"/><w:basedOn w:val="Normal"/><w:next w:val="Normal"/><w:qFormat/>
+<w:pPr><w:keepNext/><w:spacing w:before="240" w:after="80"/><w:outlineLvl w:val="1"/></w:pPr><w:rPr><w:b/><w:sz w:val="26"/></w:rPr></w:style>
+<w:style w:type="paragraph" w:styleId="Heading3"><w:name w:val="heading 3"/><w:basedOn w:val="Normal"/><w:next w:val="Normal"/><w:qFormat/>
+<w:pPr><w:keepNext/><w:spacing w:before="160" w:after="60"/><w:outlineLvl w:val="2"/></w:pPr><w:rPr><w:b/><w:i/><w:sz w:val="22"/></w:rPr></w:style>
+<w:style w:type="paragraph" w:styleId="Meta"><w:name w:val="Meta"/><w:basedOn w:val="Normal"/><w:qFormat/>
+<w:rPr><w:color w:val="595959"/><w:sz w:val="20"/></w:rPr></w:style>
+<w:style w:type="table" w:default="1" w:styleId="TableNormal"><w:name w:val="Normal Table"/>
+<w:tblPr><w:tblInd w:w="0" w:type="dxa"/><w:tblCellMar><w:top w:w="0" w:type="dxa"/><w:left w:w="108" w:type="dxa"/><w:bottom w:w="0" w:type="dxa"/><w:right w:w="108" w:type="dxa"/></w:tblCellMar></w:tblPr></w:style>
+<w:style w:type="table" w:styleId="TableGrid"><w:name w:val="Table Grid"/><w:basedOn w:val="TableNormal"/>
+<w:pPr><w:spacing w:after="0"/></w:pPr>
+<w:tblPr><w:tblBorders><w:top w:val="single" w:sz="4" w:space="0" w:color="A6A6A6"/><w:left w:val="single" w:sz="4" w:space="0" w:color="A6A6A6"/><w:bottom w:val="single" w:sz="4" w:space="0" w:color="A6A6A6"/><w:right w:val="single" w:sz="4" w:space="0" w:color="A6A6A6"/><w:insideH w:val="single" w:sz="4" w:space="0" w:color="A6A6A6"/><w:insideV w:val="single" w:sz="4" w:space="0" w:color="A6A6A6"/></w:tblBorders></w:tblPr></w:style>
+</w:styles>"#;
+
+fn core_xml(title: &str) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<cp:coreProperties xmlns:cp="http://schemas.openxmlformats.org/package/2006/metadata/core-properties" xmlns:dc="http://purl.org/dc/elements/1.1/">
+<dc:title>{}</dc:title>
+</cp:coreProperties>"#,
+        escape(title)
+    )
+}
+
+/// Package a rendered `word/document.xml` as a .docx file.
+pub fn package(title: &str, document_xml: &str) -> Result<Vec<u8>, zip::result::ZipError> {
+    let core = core_xml(title);
+    let parts: [(&str, &[u8]); 6] = [
+        ("[Content_Types].xml", CONTENT_TYPES_XML.as_bytes()),
+        ("_rels/.rels", PACKAGE_RELS_XML.as_bytes()),
+        ("docProps/core.xml", core.as_bytes()),
+        ("word/_rels/document.xml.rels", DOCUMENT_RELS_XML.as_bytes()),
+        ("word/styles.xml", STYLES_XML.as_bytes()),
+        ("word/document.xml", document_xml.as_bytes()),
+    ];
+
+    let options = SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated)
+        .last_modified_time(zip::DateTime::default());
+    let mut archive = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    for (name, contents) in parts {
+        archive.start_file(name, options)?;
+        archive.write_all(contents)?;
+    }
+    Ok(archive.finish()?.into_inner())
+}
+
+fn package_err(e: zip::result::ZipError) -> AppError {
+    AppError::Session(format!("Could not build Word document: {e}"))
+}
+
+/// Minutes as a Word document: sections, structured attendance and action
+/// items, distribution, and signature lines.
+pub async fn minutes(pool: &PgPool, minutes: &Minutes) -> Result<Vec<u8>, AppError> {
+    let sections = minutes::find_sections(pool, minutes.id).await?;
+    let meeting = meeting::find_by_id(pool, minutes.meeting_id).await?;
+    let xml = MinutesDocxTemplate {
+        minutes: minutes.clone(),
+        meeting,
+        sections,
+    }
+    .render()?;
+    package(&minutes.label, &xml).map_err(package_err)
+}
+
+/// The agenda points scheduled for a meeting with their proposals and
+/// courses of action, in agenda order.
+pub async fn agenda_pack_items(pool: &PgPool, meeting_id: i64) -> Result<Vec<AgendaPackItem>, AppError> {
+    let mut items = Vec::new();
+    for (i, scheduled) in meeting::find_agenda_points(pool, meeting_id).await?.iter().enumerate() {
+        let Some(point) = agenda_point::find_by_id(pool, scheduled.id).await? else { continue };
+        let proposal = proposal::find_for_agenda_point(pool, point.id).await?;
+        let mut listed = coa::find_all_for_agenda_point(pool, point.id).await?;
+        listed.sort_by_key(|c| c.id);
+        let mut coas = Vec::with_capacity(listed.len());
+        for c in listed {
+            coas.push(coa::find_by_id(pool, c.id).await?);
+        }
+        items.push(AgendaPackItem { number: i + 1, point, proposal, coas });
+    }
+    Ok(items)
+}
+
+/// The meeting agenda pack as a Word document: the agenda, then each point
+/// with its proposal and courses of action.
+pub async fn agenda_pack(pool: &PgPool, meeting: &MeetingDetail) -> Result<Vec<u8>, AppError> {
+    let items = agenda_pack_items(pool, meeting.id).await?;
+    let xml = AgendaDocxTemplate {
+        meeting: meeting.clone(),
+        items,
+    }
+    .render()?;
+    package(&format!("Agenda — {}", meeting.label), &xml).map_err(package_err)
+}
@@ -0,0 +1,10 @@
+//! Office document exports.
+//!
+//! The HTML exports (print views, minutes export) are rendered with the
+//! page templates; the formats here package the same content for tools
+//! outside the browser, for reading offline (a self-contained HTML meeting
+//! pack) or, for PDF/A, for long-term retention.
+
+pub mod docx;
+pub mod offline;
+pub mod pdf;
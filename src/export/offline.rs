@@ -0,0 +1,208 @@
+//! Offline meeting pack viewer: the agenda, each point with its proposal
+//! and courses of action, and the documents attached to those proposals,
+//! as one self-contained HTML file members can read without a connection.
+//!
+//! Each item is rendered on its own from a template under
+//! `templates/offline/` and hashed. The file embeds the manifest of item
+//! hashes; a client holding an earlier copy sends the hashes it has to the
+//! API and gets back the current manifest with only the items that changed.
+//! Item fragments carry no timestamps, so unchanged content keeps its hash.
+
+use std::collections::HashSet;
+
+use askama::Template;
+use serde::Serialize;
+use sqlx::PgPool;
+
+use crate::errors::AppError;
+use crate::models::export_record::sha256_hex;
+use crate::models::meeting::{self, MeetingDetail, MeetingPackDocument};
+use crate::models::{branding, document};
+use crate::templates_structs::{OfflineAgendaTemplate, OfflineDocumentTemplate, OfflinePackTemplate, OfflinePointTemplate};
+
+use super::docx::agenda_pack_items;
+
+pub const OFFLINE_FORMAT: &str = "ahlt.offline_pack";
+pub const OFFLINE_FORMAT_VERSION: u32 = 1;
+pub const CONTENT_TYPE: &str = "text/html; charset=utf-8";
+
+/// One rendered part of the pack: the agenda, an agenda point or a document.
+#[derive(Debug, Clone, Serialize)]
+pub struct OfflineItem {
+    /// Stable across downloads: `agenda`, `point-{id}` or `document-{id}`.
+    pub key: String,
+    /// "agenda", "agenda_point" or "document".
+    pub kind: &'static str,
+    pub title: String,
+    pub sha256: String,
+    /// HTML fragment shown in the viewer.
+    pub html: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ManifestEntry {
+    pub key: String,
+    pub kind: &'static str,
+    pub title: String,
+    pub sha256: String,
+}
+
+/// The items of a pack in viewer order, by hash.
+#[derive(Debug, Clone, Serialize)]
+pub struct OfflineManifest {
+    pub format: &'static str,
+    pub format_version: u32,
+    pub meeting_id: i64,
+    pub meeting_label: String,
+    /// RFC 3339 UTC.
+    pub generated_at: String,
+    pub items: Vec<ManifestEntry>,
+}
+
+/// Reply to a client refreshing its copy: the full manifest and the items
+/// whose hash the client did not have. Keys the client holds that are no
+/// longer in the manifest have been removed from the pack.
+#[derive(Debug, Clone, Serialize)]
+pub struct OfflineUpdate {
+    pub manifest: OfflineManifest,
+    pub items: Vec<OfflineItem>,
+    /// How many manifest items the client already had.
+    pub unchanged: usize,
+}
+
+/// A pack document ready to show inline: embedded files keep their data
+/// URI, anything else is text.
+#[derive(Debug, Clone)]
+pub struct OfflineDocument {
+    pub entry: MeetingPackDocument,
+    /// Agenda points the document is attached through, as (key, label).
+    pub agenda_points: Vec<(String, String)>,
+    /// MIME type of an embedded file; empty for text.
+    pub mime: String,
+    pub body: String,
+}
+
+impl OfflineDocument {
+    pub fn is_image(&self) -> bool {
+        self.mime.starts_with("image/")
+    }
+
+    pub fn is_pdf(&self) -> bool {
+        self.mime == "application/pdf"
+    }
+}
+
+/// File name of the viewer, e.g. `meeting-offline-board_2026_03.html`.
+pub fn file_name(meeting: &MeetingDetail) -> String {
+    format!("meeting-offline-{}.html", meeting.name)
+}
+
+pub fn point_key(agenda_point_id: i64) -> String {
+    format!("point-{agenda_point_id}")
+}
+
+pub fn document_key(document_id: i64) -> String {
+    format!("document-{document_id}")
+}
+
+/// The MIME type of a `data:...;base64,` body, if it is one.
+fn embedded_mime(body: &str) -> Option<&str> {
+    body.trim().strip_prefix("data:")
+        .and_then(|rest| rest.split_once(";base64,"))
+        .map(|(mime, _)| mime)
+}
+
+fn item(key: String, kind: &'static str, title: String, html: String) -> OfflineItem {
+    OfflineItem { key, kind, title, sha256: sha256_hex(html.as_bytes()), html }
+}
+
+/// Render every item of a meeting's pack, in viewer order.
+pub async fn items(pool: &PgPool, meeting: &MeetingDetail) -> Result<Vec<OfflineItem>, AppError> {
+    let points = agenda_pack_items(pool, meeting.id).await?;
+    let pack = meeting::find_pack_documents(pool, meeting.id).await?;
+
+    let mut documents: Vec<OfflineDocument> = Vec::new();
+    for entry in pack {
+        let attached = (point_key(entry.agenda_point_id), entry.agenda_label.clone());
+        if let Some(existing) = documents.iter_mut().find(|d| d.entry.document_id == entry.document_id) {
+            existing.agenda_points.push(attached);
+            continue;
+        }
+        let Some(doc) = document::find_by_id(pool, entry.document_id).await? else { continue };
+        let mime = embedded_mime(&doc.body).unwrap_or_default().to_string();
+        documents.push(OfflineDocument { entry, agenda_points: vec![attached], mime, body: doc.body });
+    }
+
+    let mut items = vec![item(
+        "agenda".to_string(),
+        "agenda",
+        "Agenda".to_string(),
+        OfflineAgendaTemplate { meeting: meeting.clone(), items: points.clone() }.render()?,
+    )];
+    for point in points {
+        let attached: Vec<MeetingPackDocument> = documents.iter()
+            .filter(|d| d.agenda_points.iter().any(|(key, _)| *key == point_key(point.point.id)))
+            .map(|d| d.entry.clone())
+            .collect();
+        let title = format!("{}. {}", point.number, point.point.title);
+        let key = point_key(point.point.id);
+        items.push(item(key, "agenda_point", title, OfflinePointTemplate { item: point, documents: attached }.render()?));
+    }
+    for doc in documents {
+        let (key, title) = (document_key(doc.entry.document_id), doc.entry.title.clone());
+        items.push(item(key, "document", title, OfflineDocumentTemplate { document: doc }.render()?));
+    }
+    Ok(items)
+}
+
+pub fn manifest(meeting: &MeetingDetail, items: &[OfflineItem]) -> OfflineManifest {
+    OfflineManifest {
+        format: OFFLINE_FORMAT,
+        format_version: OFFLINE_FORMAT_VERSION,
+        meeting_id: meeting.id,
+        meeting_label: meeting.label.clone(),
+        generated_at: crate::warnings::clock::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+        items: items.iter()
+            .map(|i| ManifestEntry { key: i.key.clone(), kind: i.kind, title: i.title.clone(), sha256: i.sha256.clone() })
+            .collect(),
+    }
+}
+
+/// The manifest and the items whose hash is not among `have`.
+pub fn update(meeting: &MeetingDetail, items: Vec<OfflineItem>, have: &HashSet<String>) -> OfflineUpdate {
+    let manifest = manifest(meeting, &items);
+    let (unchanged, changed): (Vec<_>, Vec<_>) = items.into_iter().partition(|i| have.contains(&i.sha256));
+    OfflineUpdate { manifest, items: changed, unchanged: unchanged.len() }
+}
+
+/// Parse the `have` parameter: comma-separated SHA-256 hex digests, as
+/// listed in the manifest of the copy the client holds.
+pub fn parse_have(have: &str) -> HashSet<String> {
+    have.split(',')
+        .map(|d| d.trim().to_lowercase())
+        .filter(|d| crate::models::export_record::is_sha256_hex(d))
+        .collect()
+}
+
+/// The viewer: every item inline, styled by the print stylesheet, with the
+/// manifest embedded for later refreshes.
+pub async fn bundle(pool: &PgPool, meeting: &MeetingDetail) -> Result<Vec<u8>, AppError> {
+    let items = items(pool, meeting).await?;
+    let manifest = manifest(meeting, &items);
+    let html = OfflinePackTemplate {
+        branding: branding::load(pool, "export").await,
+        meeting: meeting.clone(),
+        manifest_json: manifest_json(&manifest),
+        generated_at: manifest.generated_at.clone(),
+        items,
+    }
+    .render()?;
+    Ok(html.into_bytes())
+}
+
+/// The manifest as JSON safe to place inside a `<script>` element.
+pub fn manifest_json(manifest: &OfflineManifest) -> String {
+    serde_json::to_string(manifest)
+        .unwrap_or_else(|_| "{}".to_string())
+        .replace("</", "<\\/")
+}
@@ -0,0 +1,337 @@
+//! Just enough TrueType to embed a font in a PDF: character to glyph lookup
+//! and advance widths for layout, and a subset font program that keeps only
+//! the glyphs a document uses.
+//!
+//! The fonts are bundled with the binary (`static/fonts/`), so malformed
+//! data is a build problem rather than user input and is not recovered from.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::OnceLock;
+
+/// Tables copied into a subset. The rest (kerning, OpenType layout, device
+/// hinting) play no part in PDF text rendering, and `cmap` is not needed
+/// since text is shown by glyph id. `name` carries the font's copyright.
+const KEPT_TABLES: [&[u8; 4]; 12] = [
+    b"OS/2", b"cvt ", b"fpgm", b"glyf", b"head", b"hhea", b"hmtx", b"loca", b"maxp", b"name", b"post", b"prep",
+];
+
+pub struct Font {
+    data: &'static [u8],
+    /// PostScript name, used as the PDF `BaseFont`.
+    pub name: &'static str,
+    tables: HashMap<[u8; 4], (usize, usize)>,
+    pub units_per_em: u16,
+    /// xMin, yMin, xMax, yMax in font units.
+    pub bbox: [i16; 4],
+    pub ascent: i16,
+    pub descent: i16,
+    pub cap_height: i16,
+    pub bold: bool,
+    num_glyphs: u16,
+    num_h_metrics: u16,
+    long_loca: bool,
+    cmap: HashMap<char, u16>,
+}
+
+fn u16_at(data: &[u8], at: usize) -> u16 {
+    u16::from_be_bytes([data[at], data[at + 1]])
+}
+
+fn i16_at(data: &[u8], at: usize) -> i16 {
+    u16_at(data, at) as i16
+}
+
+fn u32_at(data: &[u8], at: usize) -> u32 {
+    u32::from_be_bytes([data[at], data[at + 1], data[at + 2], data[at + 3]])
+}
+
+pub fn regular() -> &'static Font {
+    static FONT: OnceLock<Font> = OnceLock::new();
+    FONT.get_or_init(|| Font::parse(include_bytes!("../../../static/fonts/DejaVuSans.ttf"), "DejaVuSans"))
+}
+
+pub fn bold() -> &'static Font {
+    static FONT: OnceLock<Font> = OnceLock::new();
+    FONT.get_or_init(|| Font::parse(include_bytes!("../../../static/fonts/DejaVuSans-Bold.ttf"), "DejaVuSans-Bold"))
+}
+
+impl Font {
+    fn parse(data: &'static [u8], name: &'static str) -> Font {
+        let mut tables = HashMap::new();
+        for i in 0..u16_at(data, 4) as usize {
+            let rec = 12 + i * 16;
+            let tag = [data[rec], data[rec + 1], data[rec + 2], data[rec + 3]];
+            tables.insert(tag, (u32_at(data, rec + 8) as usize, u32_at(data, rec + 12) as usize));
+        }
+        let table = |tag: &[u8; 4]| tables.get(tag).map(|t| t.0).unwrap_or_else(|| panic!("font {name} has no {tag:?} table"));
+
+        let head = table(b"head");
+        let hhea = table(b"hhea");
+        let os2 = tables.get(b"OS/2").map(|t| t.0);
+        let ascent = i16_at(data, hhea + 4);
+        let cap_height = match os2 {
+            Some(os2) if u16_at(data, os2) >= 2 => i16_at(data, os2 + 88),
+            _ => ascent,
+        };
+        let mut font = Font {
+            data,
+            name,
+            units_per_em: u16_at(data, head + 18),
+            bbox: [i16_at(data, head + 36), i16_at(data, head + 38), i16_at(data, head + 40), i16_at(data, head + 42)],
+            ascent,
+            descent: i16_at(data, hhea + 6),
+            cap_height,
+            bold: os2.is_some_and(|os2| u16_at(data, os2 + 4) >= 600),
+            num_glyphs: u16_at(data, table(b"maxp") + 4),
+            num_h_metrics: u16_at(data, hhea + 34),
+            long_loca: i16_at(data, head + 50) == 1,
+            cmap: HashMap::new(),
+            tables,
+        };
+        font.cmap = font.read_cmap();
+        font
+    }
+
+    fn table(&self, tag: &[u8; 4]) -> (usize, usize) {
+        self.tables[tag]
+    }
+
+    /// Unicode mappings from the (3,10) format 12 subtable when present,
+    /// else the (3,1) format 4 one.
+    fn read_cmap(&self) -> HashMap<char, u16> {
+        let data = self.data;
+        let (cmap, _) = self.table(b"cmap");
+        let mut bmp = None;
+        let mut full = None;
+        for i in 0..u16_at(data, cmap + 2) as usize {
+            let rec = cmap + 4 + i * 8;
+            let sub = cmap + u32_at(data, rec + 4) as usize;
+            match (u16_at(data, rec), u16_at(data, rec + 2), u16_at(data, sub)) {
+                (3, 10, 12) => full = Some(sub),
+                (3, 1, 4) => bmp = Some(sub),
+                _ => {}
+            }
+        }
+
+        let mut map = HashMap::new();
+        if let Some(sub) = full {
+            for g in 0..u32_at(data, sub + 12) as usize {
+                let group = sub + 16 + g * 12;
+                let (start, end, glyph) = (u32_at(data, group), u32_at(data, group + 4), u32_at(data, group + 8));
+                for code in start..=end {
+                    if let Some(c) = char::from_u32(code) {
+                        map.insert(c, (glyph + code - start) as u16);
+                    }
+                }
+            }
+        } else if let Some(sub) = bmp {
+            let seg_count = u16_at(data, sub + 6) as usize / 2;
+            let ends = sub + 14;
+            let starts = ends + seg_count * 2 + 2;
+            let deltas = starts + seg_count * 2;
+            let range_offsets = deltas + seg_count * 2;
+            for s in 0..seg_count {
+                let (start, end) = (u16_at(data, starts + s * 2), u16_at(data, ends + s * 2));
+                let delta = u16_at(data, deltas + s * 2);
+                let range_offset = u16_at(data, range_offsets + s * 2) as usize;
+                for code in start..=end {
+                    if code == 0xFFFF {
+                        break;
+                    }
+                    let glyph = if range_offset == 0 {
+                        code.wrapping_add(delta)
+                    } else {
+                        let at = range_offsets + s * 2 + range_offset + (code - start) as usize * 2;
+                        match u16_at(data, at) {
+                            0 => 0,
+                            g => g.wrapping_add(delta),
+                        }
+                    };
+                    if let (Some(c), true) = (char::from_u32(code as u32), glyph != 0) {
+                        map.insert(c, glyph);
+                    }
+                }
+            }
+        }
+        map
+    }
+
+    /// The glyph for a character, if the font has one.
+    pub fn glyph(&self, c: char) -> Option<u16> {
+        self.cmap.get(&c).copied()
+    }
+
+    /// Advance width of a glyph in font units.
+    pub fn advance(&self, glyph: u16) -> u16 {
+        let (hmtx, _) = self.table(b"hmtx");
+        let metric = glyph.min(self.num_h_metrics - 1) as usize;
+        u16_at(self.data, hmtx + metric * 4)
+    }
+
+    /// Advance width in thousandths of the font size, as PDF widths are given.
+    pub fn width_1000(&self, glyph: u16) -> i64 {
+        (self.advance(glyph) as f64 * 1000.0 / self.units_per_em as f64).round() as i64
+    }
+
+    /// Scale a font-unit value to thousandths of the font size.
+    pub fn scale_1000(&self, units: i16) -> i64 {
+        (units as f64 * 1000.0 / self.units_per_em as f64).round() as i64
+    }
+
+    fn glyph_data(&self, glyph: u16) -> &[u8] {
+        let (loca, _) = self.table(b"loca");
+        let (glyf, _) = self.table(b"glyf");
+        let g = glyph as usize;
+        let (start, end) = if self.long_loca {
+            (u32_at(self.data, loca + g * 4) as usize, u32_at(self.data, loca + g * 4 + 4) as usize)
+        } else {
+            (u16_at(self.data, loca + g * 2) as usize * 2, u16_at(self.data, loca + g * 2 + 2) as usize * 2)
+        };
+        &self.data[glyf + start..glyf + end]
+    }
+
+    /// Glyphs a composite glyph is built from.
+    fn components(&self, glyph: u16) -> Vec<u16> {
+        const ARGS_ARE_WORDS: u16 = 0x0001;
+        const HAVE_SCALE: u16 = 0x0008;
+        const MORE_COMPONENTS: u16 = 0x0020;
+        const HAVE_XY_SCALE: u16 = 0x0040;
+        const HAVE_2X2: u16 = 0x0080;
+
+        let data = self.glyph_data(glyph);
+        if data.len() < 10 || i16_at(data, 0) >= 0 {
+            return Vec::new();
+        }
+        let mut found = Vec::new();
+        let mut at = 10;
+        loop {
+            let flags = u16_at(data, at);
+            found.push(u16_at(data, at + 2));
+            at += 4 + if flags & ARGS_ARE_WORDS != 0 { 4 } else { 2 };
+            at += if flags & HAVE_SCALE != 0 {
+                2
+            } else if flags & HAVE_XY_SCALE != 0 {
+                4
+            } else if flags & HAVE_2X2 != 0 {
+                8
+            } else {
+                0
+            };
+            if flags & MORE_COMPONENTS == 0 {
+                break;
+            }
+        }
+        found
+    }
+
+    /// A font program with the outlines of `glyphs` (and the glyphs they are
+    /// composed of) and empty outlines for the rest, so glyph ids and widths
+    /// stay as in the full font. Glyphs after the last one kept are dropped.
+    pub fn subset(&self, glyphs: &BTreeSet<u16>) -> Vec<u8> {
+        let mut keep: BTreeSet<u16> = BTreeSet::from([0]);
+        let mut pending: Vec<u16> = glyphs.iter().copied().filter(|g| *g < self.num_glyphs).collect();
+        while let Some(g) = pending.pop() {
+            if keep.insert(g) {
+                pending.extend(self.components(g));
+            }
+        }
+
+        let num_glyphs = keep.last().copied().unwrap_or(0) + 1;
+        let num_h_metrics = self.num_h_metrics.min(num_glyphs);
+
+        let mut glyf = Vec::new();
+        let mut loca = Vec::with_capacity((num_glyphs as usize + 1) * 4);
+        for g in 0..num_glyphs {
+            loca.extend_from_slice(&(glyf.len() as u32).to_be_bytes());
+            if keep.contains(&g) {
+                glyf.extend_from_slice(self.glyph_data(g));
+                while glyf.len() % 4 != 0 {
+                    glyf.push(0);
+                }
+            }
+        }
+        loca.extend_from_slice(&(glyf.len() as u32).to_be_bytes());
+
+        let mut tables: BTreeMap<[u8; 4], Vec<u8>> = BTreeMap::new();
+        for tag in KEPT_TABLES {
+            let Some(&(offset, len)) = self.tables.get(tag) else { continue };
+            let original = &self.data[offset..offset + len];
+            let table = match tag {
+                b"glyf" => std::mem::take(&mut glyf),
+                b"loca" => std::mem::take(&mut loca),
+                b"head" => {
+                    let mut head = original.to_vec();
+                    head[8..12].copy_from_slice(&[0; 4]);
+                    head[50..52].copy_from_slice(&1i16.to_be_bytes());
+                    head
+                }
+                b"maxp" => {
+                    let mut maxp = original.to_vec();
+                    maxp[4..6].copy_from_slice(&num_glyphs.to_be_bytes());
+                    maxp
+                }
+                b"hhea" => {
+                    let mut hhea = original.to_vec();
+                    hhea[34..36].copy_from_slice(&num_h_metrics.to_be_bytes());
+                    hhea
+                }
+                b"hmtx" => {
+                    let len = num_h_metrics as usize * 4 + (num_glyphs - num_h_metrics) as usize * 2;
+                    original[..len].to_vec()
+                }
+                // Glyph names are not needed to render; keep just the header
+                b"post" => {
+                    let mut post = original[..32].to_vec();
+                    post[0..4].copy_from_slice(&0x0003_0000u32.to_be_bytes());
+                    post
+                }
+                _ => original.to_vec(),
+            };
+            tables.insert(*tag, table);
+        }
+        write_sfnt(&tables)
+    }
+}
+
+fn checksum(data: &[u8]) -> u32 {
+    data.chunks(4).fold(0u32, |sum, chunk| {
+        let mut word = [0u8; 4];
+        word[..chunk.len()].copy_from_slice(chunk);
+        sum.wrapping_add(u32::from_be_bytes(word))
+    })
+}
+
+/// Assemble an sfnt from its tables, filling in `head.checkSumAdjustment`.
+fn write_sfnt(tables: &BTreeMap<[u8; 4], Vec<u8>>) -> Vec<u8> {
+    let count = tables.len() as u16;
+    let entry_selector = 15 - count.leading_zeros() as u16;
+    let search_range = (1u16 << entry_selector) * 16;
+
+    let mut out = Vec::new();
+    out.extend_from_slice(&0x0001_0000u32.to_be_bytes());
+    out.extend_from_slice(&count.to_be_bytes());
+    out.extend_from_slice(&search_range.to_be_bytes());
+    out.extend_from_slice(&entry_selector.to_be_bytes());
+    out.extend_from_slice(&(count * 16 - search_range).to_be_bytes());
+
+    let mut offset = 12 + tables.len() * 16;
+    let mut head_offset = 0;
+    for (tag, table) in tables {
+        if tag == b"head" {
+            head_offset = offset;
+        }
+        out.extend_from_slice(tag);
+        out.extend_from_slice(&checksum(table).to_be_bytes());
+        out.extend_from_slice(&(offset as u32).to_be_bytes());
+        out.extend_from_slice(&(table.len() as u32).to_be_bytes());
+        offset += table.len().div_ceil(4) * 4;
+    }
+    for table in tables.values() {
+        out.extend_from_slice(table);
+        out.resize(out.len().div_ceil(4) * 4, 0);
+    }
+
+    let adjustment = 0xB1B0_AFBAu32.wrapping_sub(checksum(&out));
+    out[head_offset + 8..head_offset + 12].copy_from_slice(&adjustment.to_be_bytes());
+    out
+}
@@ -0,0 +1,288 @@
+//! Flowing text onto A4 pages: paragraphs wrapped to the text width,
+//! headings, label/value fields and ruled tables, with a footer carrying
+//! the page number on every page and, when set, a watermark line above
+//! the text.
+
+use std::collections::BTreeMap;
+
+use super::font::{self, Font};
+
+pub const PAGE_SIZE: (f32, f32) = (595.28, 841.89);
+const MARGIN: f32 = 56.69;
+const TEXT_WIDTH: f32 = PAGE_SIZE.0 - 2.0 * MARGIN;
+const FOOTER_BASELINE: f32 = 30.0;
+const HEADER_BASELINE: f32 = PAGE_SIZE.1 - 30.0;
+const LEADING: f32 = 1.35;
+const CELL_PADDING: f32 = 4.0;
+
+pub const BODY_SIZE: f32 = 10.0;
+pub const TABLE_SIZE: f32 = 9.0;
+
+#[derive(Clone, Copy)]
+pub enum Face {
+    Regular = 0,
+    Bold = 1,
+}
+
+pub struct Layout {
+    fonts: [&'static Font; 2],
+    /// Glyphs used per face, with the character each was used for.
+    used: [BTreeMap<u16, char>; 2],
+    pages: Vec<String>,
+    /// Top of the remaining space on the current page.
+    y: f32,
+    watermark: Option<String>,
+}
+
+/// The pages and fonts of a finished layout, ready for `writer::write`.
+pub struct Laidout {
+    pub pages: Vec<String>,
+    pub fonts: [(&'static Font, BTreeMap<u16, char>); 2],
+}
+
+impl Layout {
+    pub fn new() -> Self {
+        Layout {
+            fonts: [font::regular(), font::bold()],
+            used: [BTreeMap::new(), BTreeMap::new()],
+            pages: Vec::new(),
+            y: 0.0,
+            watermark: None,
+        }
+    }
+
+    fn new_page(&mut self) {
+        self.pages.push(String::new());
+        self.y = PAGE_SIZE.1 - MARGIN;
+    }
+
+    /// Start a new page unless `height` fits on the current one.
+    fn ensure(&mut self, height: f32) {
+        if self.pages.is_empty() || self.y - height < MARGIN {
+            self.new_page();
+        }
+    }
+
+    fn ops(&mut self, ops: &str) {
+        if self.pages.is_empty() {
+            self.new_page();
+        }
+        let page = self.pages.last_mut().expect("a page");
+        page.push_str(ops);
+        page.push('\n');
+    }
+
+    /// The glyph shown for a character. Characters the font lacks show as
+    /// '?': PDF/A does not allow the .notdef glyph to be drawn.
+    fn glyph(&self, face: Face, c: char) -> Option<(u16, char)> {
+        let font = self.fonts[face as usize];
+        let c = if c == '\t' { ' ' } else { c };
+        if c.is_control() {
+            return None;
+        }
+        font.glyph(c).map(|g| (g, c)).or_else(|| font.glyph('?').map(|g| (g, '?')))
+    }
+
+    pub fn measure(&self, face: Face, size: f32, text: &str) -> f32 {
+        let font = self.fonts[face as usize];
+        let units: u32 = text.chars().filter_map(|c| self.glyph(face, c)).map(|(g, _)| font.advance(g) as u32).sum();
+        units as f32 * size / font.units_per_em as f32
+    }
+
+    /// Break text into lines no wider than `width`, at spaces where possible.
+    pub fn wrap(&self, face: Face, size: f32, text: &str, width: f32) -> Vec<String> {
+        let mut lines = Vec::new();
+        let mut line = String::new();
+        for word in text.split_whitespace() {
+            let candidate = if line.is_empty() { word.to_string() } else { format!("{line} {word}") };
+            if self.measure(face, size, &candidate) <= width {
+                line = candidate;
+                continue;
+            }
+            if !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+            // A word wider than the line is broken between characters
+            for c in word.chars() {
+                line.push(c);
+                if self.measure(face, size, &line) > width && line.chars().count() > 1 {
+                    line.pop();
+                    lines.push(std::mem::take(&mut line));
+                    line.push(c);
+                }
+            }
+        }
+        if !line.is_empty() || lines.is_empty() {
+            lines.push(line);
+        }
+        lines
+    }
+
+    /// The operators drawing one line of text with its baseline at (x, y).
+    fn text_ops(&mut self, face: Face, size: f32, x: f32, y: f32, text: &str) -> Option<String> {
+        let mut hex = String::with_capacity(text.len() * 4);
+        for c in text.chars() {
+            if let Some((glyph, shown)) = self.glyph(face, c) {
+                self.used[face as usize].entry(glyph).or_insert(shown);
+                hex.push_str(&format!("{glyph:04X}"));
+            }
+        }
+        (!hex.is_empty()).then(|| format!("BT /F{} {size:.1} Tf {x:.2} {y:.2} Td <{hex}> Tj ET", face as usize + 1))
+    }
+
+    fn show(&mut self, face: Face, size: f32, x: f32, y: f32, text: &str) {
+        if let Some(ops) = self.text_ops(face, size, x, y, text) {
+            self.ops(&ops);
+        }
+    }
+
+    pub fn space(&mut self, height: f32) {
+        self.y -= height;
+    }
+
+    /// A wrapped paragraph across the text width.
+    pub fn paragraph(&mut self, face: Face, size: f32, text: &str) {
+        let leading = size * LEADING;
+        for line in self.wrap(face, size, text, TEXT_WIDTH) {
+            self.ensure(leading);
+            self.show(face, size, MARGIN, self.y - size, &line);
+            self.y -= leading;
+        }
+    }
+
+    /// Multi-line text: one paragraph per line, blank lines as gaps.
+    pub fn lines(&mut self, text: &str) {
+        for line in text.lines() {
+            if line.trim().is_empty() {
+                self.space(BODY_SIZE * 0.6);
+            } else {
+                self.paragraph(Face::Regular, BODY_SIZE, line);
+            }
+        }
+        self.space(BODY_SIZE * 0.4);
+    }
+
+    pub fn title(&mut self, text: &str) {
+        self.paragraph(Face::Bold, 18.0, text);
+        self.space(4.0);
+    }
+
+    pub fn subtitle(&mut self, text: &str) {
+        self.paragraph(Face::Regular, 13.0, text);
+        self.space(6.0);
+    }
+
+    /// A section heading, kept on the same page as the line after it.
+    pub fn heading(&mut self, text: &str) {
+        let size = 13.0;
+        self.space(8.0);
+        self.ensure(size * LEADING + BODY_SIZE * LEADING * 2.0);
+        self.paragraph(Face::Bold, size, text);
+        self.space(2.0);
+    }
+
+    /// "Label: value" on one line, the value wrapping under itself.
+    pub fn field(&mut self, label: &str, value: &str) {
+        let size = BODY_SIZE;
+        let leading = size * LEADING;
+        let label = format!("{label}: ");
+        let indent = self.measure(Face::Bold, size, &label);
+        let lines = self.wrap(Face::Regular, size, value, TEXT_WIDTH - indent);
+        self.ensure(leading);
+        self.show(Face::Bold, size, MARGIN, self.y - size, &label);
+        for (i, line) in lines.iter().enumerate() {
+            if i > 0 {
+                self.ensure(leading);
+            }
+            self.show(Face::Regular, size, MARGIN + indent, self.y - size, line);
+            self.y -= leading;
+        }
+    }
+
+    fn rule(&mut self, y: f32) {
+        self.ops(&format!("0.6 G 0.5 w {MARGIN:.2} {y:.2} m {:.2} {y:.2} l S", MARGIN + TEXT_WIDTH));
+    }
+
+    fn table_row(&mut self, columns: &[f32], cells: &[Vec<String>], face: Face, height: f32) {
+        let size = TABLE_SIZE;
+        let leading = size * LEADING;
+        let top = self.y;
+        if let Face::Bold = face {
+            self.ops(&format!("0.92 g {MARGIN:.2} {:.2} {TEXT_WIDTH:.2} {height:.2} re f 0 g", top - height));
+        }
+        let mut x = MARGIN;
+        for (width, lines) in columns.iter().zip(cells) {
+            let mut baseline = top - CELL_PADDING - size;
+            for line in lines {
+                self.show(face, size, x + CELL_PADDING, baseline, line);
+                baseline -= leading;
+            }
+            x += width;
+        }
+        self.rule(top - height);
+        self.y -= height;
+    }
+
+    /// A table with a shaded header row that repeats on each page. Columns
+    /// are (heading, share of the text width); rows are at least
+    /// `min_row_height` tall.
+    pub fn table(&mut self, columns: &[(&str, f32)], rows: &[Vec<String>], min_row_height: f32) {
+        let size = TABLE_SIZE;
+        let leading = size * LEADING;
+        let widths: Vec<f32> = columns.iter().map(|(_, share)| share * TEXT_WIDTH).collect();
+        let wrap_row = |layout: &Layout, face: Face, cells: &[String]| -> (Vec<Vec<String>>, f32) {
+            let wrapped: Vec<Vec<String>> = widths.iter().zip(cells)
+                .map(|(width, cell)| cell.lines().flat_map(|l| layout.wrap(face, size, l, width - 2.0 * CELL_PADDING)).collect())
+                .collect();
+            let tallest = wrapped.iter().map(Vec::len).max().unwrap_or(1).max(1);
+            (wrapped, (tallest as f32 * leading + 2.0 * CELL_PADDING).max(min_row_height))
+        };
+
+        let header: Vec<String> = columns.iter().map(|(heading, _)| heading.to_string()).collect();
+        let (header_cells, header_height) = wrap_row(self, Face::Bold, &header);
+        let mut header_drawn_on = None;
+        for row in rows {
+            let (cells, height) = wrap_row(self, Face::Regular, row);
+            self.ensure(height + if header_drawn_on == Some(self.pages.len()) { 0.0 } else { header_height });
+            if header_drawn_on != Some(self.pages.len()) {
+                self.table_row(&widths, &header_cells, Face::Bold, header_height);
+                header_drawn_on = Some(self.pages.len());
+            }
+            self.table_row(&widths, &cells, Face::Regular, height);
+        }
+        self.space(BODY_SIZE * 0.6);
+    }
+
+    /// Stamp every page with a line above the text, drawn on `finish`.
+    pub fn watermark(&mut self, text: &str) {
+        self.watermark = Some(text.to_string());
+    }
+
+    /// Add "`footer` … Page n of N", and the watermark, to every page.
+    pub fn finish(mut self, footer: &str) -> Laidout {
+        if self.pages.is_empty() {
+            self.new_page();
+        }
+        let count = self.pages.len();
+        let size = 8.0;
+        let footer_lines = self.wrap(Face::Regular, size, footer, TEXT_WIDTH * 0.75);
+        let footer = footer_lines.first().cloned().unwrap_or_default();
+        let watermark = self.watermark.take()
+            .and_then(|text| self.wrap(Face::Regular, size, &text, TEXT_WIDTH).into_iter().next());
+        for n in 0..count {
+            let number = format!("Page {} of {count}", n + 1);
+            let x = MARGIN + TEXT_WIDTH - self.measure(Face::Regular, size, &number);
+            let ops: Vec<String> = [
+                self.text_ops(Face::Regular, size, MARGIN, FOOTER_BASELINE, &footer),
+                self.text_ops(Face::Regular, size, x, FOOTER_BASELINE, &number),
+                watermark.as_deref().and_then(|w| self.text_ops(Face::Regular, size, MARGIN, HEADER_BASELINE, w)),
+            ].into_iter().flatten().collect();
+            self.pages[n].push_str(&format!("0.35 g\n{}\n0 g\n", ops.join("\n")));
+        }
+        let [regular, bold] = self.used;
+        Laidout {
+            pages: self.pages,
+            fonts: [(self.fonts[0], regular), (self.fonts[1], bold)],
+        }
+    }
+}
@@ -0,0 +1,237 @@
+//! PDF/A-2b exports of minutes and access reviews for long-term archiving.
+//!
+//! Documents are laid out directly as PDF content (`layout`) and written
+//! with everything PDF/A-2b asks of a file (`writer`): embedded subsets of
+//! the bundled DejaVu fonts (`font`), an XMP packet matching the document
+//! information dictionary, and a grey output intent. The ToR, meeting
+//! number and approval date travel in the XMP under a declared extension
+//! schema so records systems can index them.
+//!
+//! The creation date comes from `warnings::clock`; with it fixed, unchanged
+//! minutes export to identical bytes.
+
+mod font;
+mod layout;
+mod writer;
+
+use sqlx::PgPool;
+
+use crate::errors::AppError;
+use crate::models::access_review::{AccessEntry, AccessReview};
+use crate::models::export_policy::Watermark;
+use crate::models::meeting::{self, MeetingDetail};
+use crate::models::minutes::{self, Minutes, MinutesSection};
+
+use layout::{Face, Layout};
+pub use writer::{DocumentInfo, XMP_NAMESPACE};
+
+pub const CONTENT_TYPE: &str = "application/pdf";
+
+/// File name for archived minutes, e.g. `minutes-board_2026_03.pdf`.
+pub fn minutes_file_name(minutes: &Minutes) -> String {
+    format!("minutes-{}.pdf", minutes.meeting_name)
+}
+
+/// Archival metadata for a set of minutes.
+pub fn minutes_info(minutes: &Minutes, meeting: Option<&MeetingDetail>) -> DocumentInfo {
+    let tor = meeting.map(|m| m.tor_label.clone()).unwrap_or_default();
+    let number = meeting.map(|m| m.meeting_number.clone()).unwrap_or_default();
+    let date = meeting.map(|m| m.meeting_date.clone()).unwrap_or_default();
+
+    let mut subject = format!("Minutes of {}", meeting.map(|m| m.label.as_str()).unwrap_or(&minutes.meeting_name));
+    if !date.is_empty() {
+        subject.push_str(&format!(" held {date}"));
+    }
+    if !minutes.approved_date.is_empty() {
+        subject.push_str(&format!(", approved {}", minutes.approved_date));
+    }
+    let keywords = ["minutes", tor.as_str(), number.as_str()]
+        .into_iter()
+        .filter(|k| !k.is_empty())
+        .collect::<Vec<_>>()
+        .join("; ");
+
+    let properties = [
+        ("tor", "Terms of Reference the meeting was held under", tor.clone()),
+        ("meetingNumber", "Meeting number within the Terms of Reference", number),
+        ("meetingDate", "Date the meeting was held (YYYY-MM-DD)", date),
+        ("approvedBy", "Who approved the minutes", minutes.approved_by.clone()),
+        ("approvalDate", "Date the minutes were approved (YYYY-MM-DD)", minutes.approved_date.clone()),
+    ]
+    .into_iter()
+    .filter(|(_, _, value)| !value.is_empty())
+    .collect();
+
+    DocumentInfo {
+        title: minutes.label.clone(),
+        author: tor,
+        subject,
+        keywords,
+        created: crate::warnings::clock::now(),
+        properties,
+    }
+}
+
+/// Minutes as a PDF/A-2b document: the same content as the Word export.
+/// A watermark is drawn at the top of every page and kept in the XMP.
+pub fn render_minutes(
+    minutes: &Minutes,
+    meeting: Option<&MeetingDetail>,
+    sections: &[MinutesSection],
+    watermark: Option<&Watermark>,
+) -> Vec<u8> {
+    let mut doc = Layout::new();
+    if let Some(mark) = watermark {
+        doc.watermark(&mark.line());
+    }
+    doc.title(&minutes.label);
+    match meeting {
+        Some(m) => {
+            doc.subtitle(&m.tor_label);
+            doc.field("Meeting", &m.label);
+            if !m.meeting_number.is_empty() {
+                doc.field("Meeting number", &m.meeting_number);
+            }
+            doc.field("Date", &m.meeting_date);
+            if !m.location.is_empty() {
+                doc.field("Location", &m.location);
+            }
+            if !m.classification.is_empty() {
+                doc.field("Classification", &m.classification);
+            }
+        }
+        None => doc.subtitle(&minutes.meeting_name),
+    }
+    doc.field("Generated", &minutes.generated_date);
+    if !minutes.approved_by.is_empty() {
+        doc.field("Approved by", &minutes.approved_by);
+        doc.field("Approved on", &minutes.approved_date);
+    }
+
+    let attendance = minutes.attendance_list();
+    if !attendance.is_empty() {
+        doc.heading("Attendance");
+        let rows: Vec<Vec<String>> = attendance.into_iter().map(|a| vec![a.name, a.status, a.delegation_to]).collect();
+        doc.table(&[("Name", 0.45), ("Status", 0.2), ("Delegated to", 0.35)], &rows, 0.0);
+    }
+
+    for section in sections {
+        doc.heading(&section.label);
+        doc.lines(&section.content);
+    }
+
+    let action_items = minutes.action_items_list();
+    if !action_items.is_empty() {
+        doc.heading("Action Register");
+        let rows: Vec<Vec<String>> = action_items.into_iter()
+            .map(|a| vec![a.description, a.responsible, a.due_date, a.status])
+            .collect();
+        doc.table(&[("Action", 0.5), ("Responsible", 0.2), ("Due", 0.15), ("Status", 0.15)], &rows, 0.0);
+    }
+
+    let distribution = minutes.distribution_items();
+    if !distribution.is_empty() {
+        doc.heading("Distribution");
+        for recipient in &distribution {
+            doc.paragraph(Face::Regular, layout::BODY_SIZE, recipient);
+        }
+    }
+
+    doc.heading("Signatures");
+    let (chair, secretary) = meeting
+        .map(|m| (m.chair_user_id.clone(), m.secretary_user_id.clone()))
+        .unwrap_or_default();
+    let rows = vec![
+        vec!["Chair".to_string(), chair, "Signature:".to_string(), "Date:".to_string()],
+        vec!["Secretary".to_string(), secretary, "Signature:".to_string(), "Date:".to_string()],
+    ];
+    doc.table(&[("Role", 0.2), ("Name", 0.3), ("Signature", 0.3), ("Date", 0.2)], &rows, 36.0);
+
+    let mut info = minutes_info(minutes, meeting);
+    if let Some(mark) = watermark {
+        info.properties.push(("watermark", "Who exported the document, when, and from which deployment", mark.line()));
+    }
+    let footer = match meeting {
+        Some(m) => format!("{} — {}", m.tor_label, minutes.label),
+        None => minutes.label.clone(),
+    };
+    let laidout = doc.finish(&footer);
+    let fonts: Vec<_> = laidout.fonts.iter().map(|(font, glyphs)| (*font, glyphs)).collect();
+    writer::write(&info, &laidout.pages, &fonts, layout::PAGE_SIZE)
+}
+
+/// Approved minutes as a PDF/A-2b document.
+pub async fn minutes(pool: &PgPool, minutes: &Minutes, watermark: Option<&Watermark>) -> Result<Vec<u8>, AppError> {
+    let sections = minutes::find_sections(pool, minutes.id).await?;
+    let meeting = meeting::find_by_id(pool, minutes.meeting_id).await?;
+    Ok(render_minutes(minutes, meeting.as_ref(), &sections, watermark))
+}
+
+/// An access review as a PDF/A-2b document: the captured access of every
+/// user, and who signed the review off.
+pub fn render_access_review(
+    review: &AccessReview,
+    entries: &[AccessEntry],
+    watermark: Option<&Watermark>,
+) -> Vec<u8> {
+    let mut doc = Layout::new();
+    if let Some(mark) = watermark {
+        doc.watermark(&mark.line());
+    }
+    doc.title(&review.label);
+    doc.subtitle("Users, roles, permissions, ToR positions and capabilities");
+    doc.field("Generated", &review.generated_at);
+    doc.field("Generated by", &review.generated_by_name);
+    doc.field("Users", &review.user_count.to_string());
+
+    doc.heading("Access");
+    let rows: Vec<Vec<String>> = entries.iter()
+        .map(|e| {
+            let user = if e.is_active { e.display_name.clone() } else { format!("{} (inactive)", e.display_name) };
+            vec![
+                format!("{user}\n{}", e.username),
+                e.roles.join("\n"),
+                e.permissions.join("\n"),
+                e.positions.join("\n"),
+                e.capabilities.join("\n"),
+            ]
+        })
+        .collect();
+    doc.table(
+        &[("User", 0.18), ("Roles", 0.14), ("Permissions", 0.24), ("ToR positions", 0.22), ("Capabilities", 0.22)],
+        &rows,
+        0.0,
+    );
+
+    doc.heading("Sign-off");
+    if review.is_signed_off() {
+        doc.field("Reviewed by", &review.reviewed_by_name);
+        doc.field("Reviewed on", &review.reviewed_at);
+        if !review.notes.is_empty() {
+            doc.field("Notes", &review.notes);
+        }
+    } else {
+        let rows = vec![vec!["Reviewer".to_string(), String::new(), "Signature:".to_string(), "Date:".to_string()]];
+        doc.table(&[("Role", 0.2), ("Name", 0.3), ("Signature", 0.3), ("Date", 0.2)], &rows, 36.0);
+    }
+
+    let mut properties = vec![("generatedBy", "Who generated the access review", review.generated_by_name.clone())];
+    if review.is_signed_off() {
+        properties.push(("reviewedBy", "Who signed the access review off", review.reviewed_by_name.clone()));
+        properties.push(("reviewDate", "When the access review was signed off", review.reviewed_at.clone()));
+    }
+    if let Some(mark) = watermark {
+        properties.push(("watermark", "Who exported the document, when, and from which deployment", mark.line()));
+    }
+    let info = DocumentInfo {
+        title: review.label.clone(),
+        author: review.generated_by_name.clone(),
+        subject: format!("Access review of {} users generated {}", review.user_count, review.generated_at),
+        keywords: "access review; roles; permissions".to_string(),
+        created: crate::warnings::clock::now(),
+        properties,
+    };
+    let laidout = doc.finish(&review.label);
+    let fonts: Vec<_> = laidout.fonts.iter().map(|(font, glyphs)| (*font, glyphs)).collect();
+    writer::write(&info, &laidout.pages, &fonts, layout::PAGE_SIZE)
+}
@@ -0,0 +1,323 @@
+//! PDF file structure for PDF/A-2b: the object table, the document
+//! information dictionary mirrored in an XMP packet, a grey output intent,
+//! and Type 0 fonts embedding subsets of the bundled TrueType fonts.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+
+use super::font::Font;
+
+/// Namespace of the custom XMP properties (see `DocumentInfo::properties`).
+pub const XMP_NAMESPACE: &str = "urn:ahlt:xmp:archive:1/";
+const XMP_PREFIX: &str = "ahlt";
+
+/// Document metadata. The standard entries go both in the information
+/// dictionary and in XMP, where PDF/A requires them to agree.
+pub struct DocumentInfo {
+    pub title: String,
+    /// Left out when empty.
+    pub author: String,
+    pub subject: String,
+    pub keywords: String,
+    pub created: DateTime<Utc>,
+    /// Custom XMP properties as (name, description, value), declared in a
+    /// PDF/A extension schema.
+    pub properties: Vec<(&'static str, &'static str, String)>,
+}
+
+pub const PRODUCER: &str = concat!("ahlt ", env!("CARGO_PKG_VERSION"));
+
+struct Objects {
+    out: Vec<u8>,
+    offsets: Vec<usize>,
+}
+
+impl Objects {
+    fn reserve(&mut self) -> usize {
+        self.offsets.push(0);
+        self.offsets.len()
+    }
+
+    fn write(&mut self, id: usize, body: &str) {
+        self.offsets[id - 1] = self.out.len();
+        self.out.extend_from_slice(format!("{id} 0 obj\n{body}\nendobj\n").as_bytes());
+    }
+
+    fn write_stream(&mut self, id: usize, entries: &str, data: &[u8]) {
+        self.offsets[id - 1] = self.out.len();
+        self.out.extend_from_slice(format!("{id} 0 obj\n<< {entries} /Length {} >>\nstream\n", data.len()).as_bytes());
+        self.out.extend_from_slice(data);
+        self.out.extend_from_slice(b"\nendstream\nendobj\n");
+    }
+}
+
+/// A PDF text string: UTF-16BE with a byte order mark, hex encoded.
+fn text_string(text: &str) -> String {
+    let mut hex = String::from("<FEFF");
+    for unit in text.encode_utf16() {
+        hex.push_str(&format!("{unit:04X}"));
+    }
+    hex.push('>');
+    hex
+}
+
+fn pdf_date(at: &DateTime<Utc>) -> String {
+    at.format("D:%Y%m%d%H%M%S+00'00'").to_string()
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+fn xmp(info: &DocumentInfo) -> String {
+    let date = info.created.to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+    let mut schema_properties = String::new();
+    let mut values = String::new();
+    for (name, description, value) in &info.properties {
+        schema_properties.push_str(&format!(
+            "<rdf:li rdf:parseType=\"Resource\"><pdfaProperty:name>{name}</pdfaProperty:name>\
+             <pdfaProperty:valueType>Text</pdfaProperty:valueType>\
+             <pdfaProperty:category>external</pdfaProperty:category>\
+             <pdfaProperty:description>{}</pdfaProperty:description></rdf:li>\n",
+            xml_escape(description)
+        ));
+        values.push_str(&format!("<{XMP_PREFIX}:{name}>{}</{XMP_PREFIX}:{name}>\n", xml_escape(value)));
+    }
+
+    format!(
+        r#"<?xpacket begin="{bom}" id="W5M0MpCehiHzreSzNTczkc9d"?>
+<x:xmpmeta xmlns:x="adobe:ns:meta/">
+<rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+<rdf:Description rdf:about="" xmlns:pdfaid="http://www.aiim.org/pdfa/ns/id/">
+<pdfaid:part>2</pdfaid:part>
+<pdfaid:conformance>B</pdfaid:conformance>
+</rdf:Description>
+<rdf:Description rdf:about="" xmlns:dc="http://purl.org/dc/elements/1.1/">
+<dc:format>application/pdf</dc:format>
+<dc:title><rdf:Alt><rdf:li xml:lang="x-default">{title}</rdf:li></rdf:Alt></dc:title>
+{creator}<dc:description><rdf:Alt><rdf:li xml:lang="x-default">{subject}</rdf:li></rdf:Alt></dc:description>
+</rdf:Description>
+<rdf:Description rdf:about="" xmlns:pdf="http://ns.adobe.com/pdf/1.3/">
+<pdf:Producer>{producer}</pdf:Producer>
+<pdf:Keywords>{keywords}</pdf:Keywords>
+</rdf:Description>
+<rdf:Description rdf:about="" xmlns:xmp="http://ns.adobe.com/xap/1.0/">
+<xmp:CreatorTool>{producer}</xmp:CreatorTool>
+<xmp:CreateDate>{date}</xmp:CreateDate>
+<xmp:ModifyDate>{date}</xmp:ModifyDate>
+<xmp:MetadataDate>{date}</xmp:MetadataDate>
+</rdf:Description>
+<rdf:Description rdf:about="" xmlns:pdfaExtension="http://www.aiim.org/pdfa/ns/extension/" xmlns:pdfaSchema="http://www.aiim.org/pdfa/ns/schema#" xmlns:pdfaProperty="http://www.aiim.org/pdfa/ns/property#">
+<pdfaExtension:schemas><rdf:Bag><rdf:li rdf:parseType="Resource">
+<pdfaSchema:schema>Governance record metadata</pdfaSchema:schema>
+<pdfaSchema:namespaceURI>{XMP_NAMESPACE}</pdfaSchema:namespaceURI>
+<pdfaSchema:prefix>{XMP_PREFIX}</pdfaSchema:prefix>
+<pdfaSchema:property><rdf:Seq>
+{schema_properties}</rdf:Seq></pdfaSchema:property>
+</rdf:li></rdf:Bag></pdfaExtension:schemas>
+</rdf:Description>
+<rdf:Description rdf:about="" xmlns:{XMP_PREFIX}="{XMP_NAMESPACE}">
+{values}</rdf:Description>
+</rdf:RDF>
+</x:xmpmeta>
+<?xpacket end="w"?>"#,
+        bom = '\u{feff}',
+        title = xml_escape(&info.title),
+        creator = if info.author.is_empty() {
+            String::new()
+        } else {
+            format!("<dc:creator><rdf:Seq><rdf:li>{}</rdf:li></rdf:Seq></dc:creator>\n", xml_escape(&info.author))
+        },
+        subject = xml_escape(&info.subject),
+        keywords = xml_escape(&info.keywords),
+        producer = xml_escape(PRODUCER),
+    )
+}
+
+/// A minimal ICC v2 monitor profile for grey with gamma 2.2, the output
+/// intent PDF/A needs before device grey may be used.
+fn grey_icc_profile() -> Vec<u8> {
+    let description = "Grey gamma 2.2";
+    let mut desc = b"desc\0\0\0\0".to_vec();
+    desc.extend_from_slice(&(description.len() as u32 + 1).to_be_bytes());
+    desc.extend_from_slice(description.as_bytes());
+    desc.push(0);
+    desc.extend_from_slice(&[0; 8]); // no Unicode description
+    desc.extend_from_slice(&[0; 3]); // no ScriptCode description
+    desc.extend_from_slice(&[0; 67]);
+    let mut cprt = b"text\0\0\0\0".to_vec();
+    cprt.extend_from_slice(b"No copyright, use freely\0");
+    let mut wtpt = b"XYZ \0\0\0\0".to_vec();
+    for v in [0x0000_F6D6u32, 0x0001_0000, 0x0000_D32D] {
+        wtpt.extend_from_slice(&v.to_be_bytes());
+    }
+    let mut ktrc = b"curv\0\0\0\0".to_vec();
+    ktrc.extend_from_slice(&1u32.to_be_bytes());
+    ktrc.extend_from_slice(&0x0233u16.to_be_bytes());
+    let tags: [(&[u8; 4], Vec<u8>); 4] = [
+        (b"desc", desc),
+        (b"cprt", cprt),
+        (b"wtpt", wtpt),
+        (b"kTRC", ktrc),
+    ];
+
+    let mut header = vec![0u8; 128];
+    header[8..12].copy_from_slice(&0x0210_0000u32.to_be_bytes());
+    header[12..16].copy_from_slice(b"mntr");
+    header[16..20].copy_from_slice(b"GRAY");
+    header[20..24].copy_from_slice(b"XYZ ");
+    for (i, v) in [2024u16, 1, 1, 0, 0, 0].iter().enumerate() {
+        header[24 + i * 2..26 + i * 2].copy_from_slice(&v.to_be_bytes());
+    }
+    header[36..40].copy_from_slice(b"acsp");
+    // PCS illuminant: D50
+    header[68..72].copy_from_slice(&0x0000_F6D6u32.to_be_bytes());
+    header[72..76].copy_from_slice(&0x0001_0000u32.to_be_bytes());
+    header[76..80].copy_from_slice(&0x0000_D32Du32.to_be_bytes());
+
+    let mut table = (tags.len() as u32).to_be_bytes().to_vec();
+    let mut data = Vec::new();
+    let data_start = 128 + 4 + tags.len() * 12;
+    for (sig, tag) in &tags {
+        table.extend_from_slice(*sig);
+        table.extend_from_slice(&((data_start + data.len()) as u32).to_be_bytes());
+        table.extend_from_slice(&(tag.len() as u32).to_be_bytes());
+        data.extend_from_slice(tag);
+        data.resize(data.len().div_ceil(4) * 4, 0);
+    }
+    let mut profile = header;
+    profile.extend_from_slice(&table);
+    profile.extend_from_slice(&data);
+    let size = profile.len() as u32;
+    profile[0..4].copy_from_slice(&size.to_be_bytes());
+    profile
+}
+
+/// Six-letter subset tag, derived from the glyphs kept so the same subset
+/// always gets the same name.
+fn subset_tag(font: &Font, glyphs: &BTreeMap<u16, char>) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(font.name.as_bytes());
+    for g in glyphs.keys() {
+        hasher.update(g.to_be_bytes());
+    }
+    hasher.finalize().iter().take(6).map(|b| (b'A' + b % 26) as char).collect()
+}
+
+fn to_unicode_cmap(glyphs: &BTreeMap<u16, char>) -> String {
+    let mut cmap = String::from(
+        "/CIDInit /ProcSet findresource begin\n12 dict begin\nbegincmap\n\
+         /CIDSystemInfo << /Registry (Adobe) /Ordering (UCS) /Supplement 0 >> def\n\
+         /CMapName /Adobe-Identity-UCS def\n/CMapType 2 def\n\
+         1 begincodespacerange\n<0000> <FFFF>\nendcodespacerange\n",
+    );
+    let entries: Vec<(&u16, &char)> = glyphs.iter().collect();
+    for chunk in entries.chunks(100) {
+        cmap.push_str(&format!("{} beginbfchar\n", chunk.len()));
+        for (glyph, c) in chunk {
+            let mut units = [0u16; 2];
+            let hex: String = c.encode_utf16(&mut units).iter().map(|u| format!("{u:04X}")).collect();
+            cmap.push_str(&format!("<{glyph:04X}> <{hex}>\n"));
+        }
+        cmap.push_str("endbfchar\n");
+    }
+    cmap.push_str("endcmap\nCMapName currentdict /CMap defineresource pop\nend\nend\n");
+    cmap
+}
+
+/// Write the Type 0 font, its CID font, descriptor, program and ToUnicode
+/// map; returns the id of the Type 0 font.
+fn write_font(objects: &mut Objects, font: &Font, glyphs: &BTreeMap<u16, char>) -> usize {
+    let [type0, cid_font, descriptor, program, to_unicode] = [(); 5].map(|_| objects.reserve());
+    let base_font = format!("{}+{}", subset_tag(font, glyphs), font.name);
+
+    let subset = font.subset(&glyphs.keys().copied().collect());
+    objects.write_stream(program, &format!("/Length1 {}", subset.len()), &subset);
+    objects.write_stream(to_unicode, "", to_unicode_cmap(glyphs).as_bytes());
+
+    let [x_min, y_min, x_max, y_max] = font.bbox.map(|v| font.scale_1000(v));
+    objects.write(descriptor, &format!(
+        "<< /Type /FontDescriptor /FontName /{base_font} /Flags 32 /FontBBox [{x_min} {y_min} {x_max} {y_max}] \
+         /ItalicAngle 0 /Ascent {} /Descent {} /CapHeight {} /StemV {} /FontFile2 {program} 0 R >>",
+        font.scale_1000(font.ascent),
+        font.scale_1000(font.descent),
+        font.scale_1000(font.cap_height),
+        if font.bold { 120 } else { 80 },
+    ));
+
+    let widths: Vec<String> = glyphs.keys().map(|g| format!("{g} [{}]", font.width_1000(*g))).collect();
+    objects.write(cid_font, &format!(
+        "<< /Type /Font /Subtype /CIDFontType2 /BaseFont /{base_font} \
+         /CIDSystemInfo << /Registry (Adobe) /Ordering (Identity) /Supplement 0 >> \
+         /FontDescriptor {descriptor} 0 R /CIDToGIDMap /Identity /DW 1000 /W [{}] >>",
+        widths.join(" "),
+    ));
+    objects.write(type0, &format!(
+        "<< /Type /Font /Subtype /Type0 /BaseFont /{base_font} /Encoding /Identity-H \
+         /DescendantFonts [{cid_font} 0 R] /ToUnicode {to_unicode} 0 R >>"
+    ));
+    type0
+}
+
+/// Write a PDF/A-2b file of A4 pages. `pages` are content streams that
+/// refer to `fonts[i]` as `/F{i+1}`; each font comes with the glyphs used
+/// and the character each stands for.
+pub fn write(info: &DocumentInfo, pages: &[String], fonts: &[(&Font, &BTreeMap<u16, char>)], page_size: (f32, f32)) -> Vec<u8> {
+    let mut objects = Objects { out: Vec::new(), offsets: Vec::new() };
+    objects.out.extend_from_slice(b"%PDF-1.7\n%\xE2\xE3\xCF\xD3\n");
+    let [catalog, page_tree, info_dict, metadata, icc] = [(); 5].map(|_| objects.reserve());
+
+    let created = pdf_date(&info.created);
+    let author = if info.author.is_empty() { String::new() } else { format!("/Author {} ", text_string(&info.author)) };
+    objects.write(info_dict, &format!(
+        "<< /Title {} {author}/Subject {} /Keywords {} /Creator {producer} /Producer {producer} \
+         /CreationDate ({created}) /ModDate ({created}) >>",
+        text_string(&info.title),
+        text_string(&info.subject),
+        text_string(&info.keywords),
+        producer = text_string(PRODUCER),
+    ));
+    objects.write_stream(metadata, "/Type /Metadata /Subtype /XML", xmp(info).as_bytes());
+    objects.write_stream(icc, "/N 1", &grey_icc_profile());
+
+    let mut font_resources = String::new();
+    for (i, (font, glyphs)) in fonts.iter().enumerate() {
+        if !glyphs.is_empty() {
+            let id = write_font(&mut objects, font, glyphs);
+            font_resources.push_str(&format!("/F{} {id} 0 R ", i + 1));
+        }
+    }
+
+    let (width, height) = page_size;
+    let mut kids = Vec::with_capacity(pages.len());
+    for content in pages {
+        let [page, stream] = [(); 2].map(|_| objects.reserve());
+        objects.write_stream(stream, "", content.as_bytes());
+        objects.write(page, &format!(
+            "<< /Type /Page /Parent {page_tree} 0 R /MediaBox [0 0 {width:.2} {height:.2}] \
+             /Resources << /Font << {font_resources}>> >> /Contents {stream} 0 R >>"
+        ));
+        kids.push(format!("{page} 0 R"));
+    }
+    objects.write(page_tree, &format!("<< /Type /Pages /Kids [{}] /Count {} >>", kids.join(" "), kids.len()));
+    objects.write(catalog, &format!(
+        "<< /Type /Catalog /Pages {page_tree} 0 R /Metadata {metadata} 0 R /Lang (en) \
+         /OutputIntents [<< /Type /OutputIntent /S /GTS_PDFA1 /OutputConditionIdentifier (Grey) \
+         /Info (Grey gamma 2.2) /DestOutputProfile {icc} 0 R >>] >>"
+    ));
+
+    let id: String = Sha256::digest(&objects.out).iter().take(16).map(|b| format!("{b:02X}")).collect();
+    let xref = objects.out.len();
+    let mut table = format!("xref\n0 {}\n0000000000 65535 f\r\n", objects.offsets.len() + 1);
+    for offset in &objects.offsets {
+        table.push_str(&format!("{offset:010} 00000 n\r\n"));
+    }
+    table.push_str(&format!(
+        "trailer\n<< /Size {} /Root {catalog} 0 R /Info {info_dict} 0 R /ID [<{id}> <{id}>] >>\nstartxref\n{xref}\n%%EOF\n",
+        objects.offsets.len() + 1,
+    ));
+    objects.out.extend_from_slice(table.as_bytes());
+    objects.out
+}
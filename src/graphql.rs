@@ -0,0 +1,236 @@
+//! GraphQL API over the entity/relation graph.
+//!
+//! `POST /api/graphql` (when `api.graphql_enabled` is on) answers read-only
+//! queries over entities, their properties and typed relations, so nested
+//! governance data comes back in one request:
+//!
+//! ```graphql
+//! { entities(entityType: "tor") { label
+//!     incoming(relation: "belongs_to_tor") { label
+//!       incoming(relation: "fills_position") { name } } } }
+//! ```
+//!
+//! Permissions match the REST layer: `entities.list` sees every entity, and
+//! otherwise an entity is visible only with the permission its REST endpoint
+//! requires (see [`TYPE_PERMISSIONS`]) and within the same scope: ToR-owned
+//! entities to members of their ToR (see [`TOR_SCOPED`]), warnings to their
+//! recipients. Every node is checked, including those reached through
+//! relations. Invisible entities are left out of lists and resolve to null. Secrets in [`HIDDEN_PROPERTIES`] are never
+//! returned. Query depth and complexity are capped.
+
+use std::sync::LazyLock;
+
+use async_graphql::{Context, EmptyMutation, EmptySubscription, Object, Schema, SimpleObject};
+use sqlx::PgPool;
+
+use crate::auth::session::Permissions;
+use crate::errors::AppError;
+use crate::models::entity::{self, Entity};
+use crate::models::{relation, tor};
+use crate::warnings;
+
+/// Entity types readable without `entities.list`, with the permission their
+/// REST endpoint requires.
+pub const TYPE_PERMISSIONS: &[(&str, &str)] = &[
+    ("user", "users.list"),
+    ("tor", "tor.list"),
+    ("tor_function", "tor.list"),
+    ("proposal", "proposal.view"),
+    ("meeting", "meetings.view"),
+    ("warning", "warnings.view"),
+];
+
+/// ToR-owned entity types, with the relation to their ToR. Readable without
+/// `entities.list` only by members of that ToR.
+pub const TOR_SCOPED: &[(&str, &str)] = &[
+    ("proposal", "submitted_to"),
+    ("meeting", "belongs_to_tor"),
+];
+
+/// Property keys holding credentials or secrets.
+pub const HIDDEN_PROPERTIES: &[&str] = &[
+    "password", "secret", "previous_secret", "client_secret", "api_key", "plain_key",
+    "token_hash", "remember_token", "id_token",
+];
+
+const MAX_DEPTH: usize = 10;
+const MAX_COMPLEXITY: usize = 1000;
+const MAX_PAGE: i32 = 100;
+
+pub type GraphSchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+static SCHEMA: LazyLock<GraphSchema> = LazyLock::new(|| {
+    Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+        .limit_depth(MAX_DEPTH)
+        .limit_complexity(MAX_COMPLEXITY)
+        .finish()
+});
+
+/// The user a query runs as.
+struct Caller(i64);
+
+/// Run a query as `user_id`, a caller with `permissions`.
+pub async fn execute(
+    pool: &PgPool,
+    user_id: i64,
+    permissions: Permissions,
+    request: async_graphql::Request,
+) -> async_graphql::Response {
+    SCHEMA.execute(request.data(pool.clone()).data(Caller(user_id)).data(permissions)).await
+}
+
+/// Whether a caller with `permissions` may read entities of `entity_type`.
+pub fn can_read(permissions: &Permissions, entity_type: &str) -> bool {
+    permissions.has("entities.list")
+        || TYPE_PERMISSIONS.iter().any(|(t, code)| *t == entity_type && permissions.has(code))
+}
+
+/// Whether the caller may read `e`: its type, then its ToR or receipt scope.
+async fn can_see(ctx: &Context<'_>, e: &Entity) -> async_graphql::Result<bool> {
+    let permissions = ctx.data::<Permissions>()?;
+    if permissions.has("entities.list") {
+        return Ok(true);
+    }
+    if !can_read(permissions, &e.entity_type) {
+        return Ok(false);
+    }
+    let pool = ctx.data::<PgPool>()?;
+    let Caller(user_id) = *ctx.data::<Caller>()?;
+    if let Some((_, rel)) = TOR_SCOPED.iter().find(|(t, _)| *t == e.entity_type) {
+        for t in relation::find_targets(pool, e.id, rel).await? {
+            match tor::require_tor_membership(pool, user_id, t.id).await {
+                Ok(()) => return Ok(true),
+                Err(AppError::PermissionDenied(_)) => {}
+                Err(err) => return Err(err.into()),
+            }
+        }
+        return Ok(false);
+    }
+    if e.entity_type == "warning" {
+        return Ok(warnings::queries::find_receipt_for_user(pool, e.id, user_id).await?.is_some());
+    }
+    Ok(true)
+}
+
+async fn visible(ctx: &Context<'_>, entities: Vec<Entity>, entity_type: Option<&str>) -> async_graphql::Result<Vec<Node>> {
+    let mut nodes = Vec::new();
+    for e in entities {
+        if entity_type.is_none_or(|t| e.entity_type == t) && can_see(ctx, &e).await? {
+            nodes.push(Node(e));
+        }
+    }
+    Ok(nodes)
+}
+
+#[derive(SimpleObject)]
+pub struct Property {
+    pub key: String,
+    pub value: String,
+}
+
+/// An entity, from which properties and relations can be followed.
+pub struct Node(Entity);
+
+#[Object(name = "Entity")]
+impl Node {
+    async fn id(&self) -> i64 {
+        self.0.id
+    }
+
+    async fn entity_type(&self) -> &str {
+        &self.0.entity_type
+    }
+
+    async fn name(&self) -> &str {
+        &self.0.name
+    }
+
+    async fn label(&self) -> &str {
+        &self.0.label
+    }
+
+    async fn is_active(&self) -> bool {
+        self.0.is_active
+    }
+
+    async fn created_at(&self) -> &str {
+        &self.0.created_at
+    }
+
+    /// All properties, by key.
+    async fn properties(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<Property>> {
+        let pool = ctx.data::<PgPool>()?;
+        let mut properties: Vec<Property> = entity::get_properties(pool, self.0.id).await?
+            .into_iter()
+            .filter(|(key, _)| !HIDDEN_PROPERTIES.contains(&key.as_str()))
+            .map(|(key, value)| Property { key, value })
+            .collect();
+        properties.sort_by(|a, b| a.key.cmp(&b.key));
+        Ok(properties)
+    }
+
+    async fn property(&self, ctx: &Context<'_>, key: String) -> async_graphql::Result<Option<String>> {
+        if HIDDEN_PROPERTIES.contains(&key.as_str()) {
+            return Ok(None);
+        }
+        Ok(entity::get_property(ctx.data::<PgPool>()?, self.0.id, &key).await?)
+    }
+
+    /// Entities this one points to through `relation`, optionally of one type.
+    async fn outgoing(
+        &self,
+        ctx: &Context<'_>,
+        relation: String,
+        entity_type: Option<String>,
+    ) -> async_graphql::Result<Vec<Node>> {
+        let targets = relation::find_targets(ctx.data::<PgPool>()?, self.0.id, &relation).await?;
+        visible(ctx, targets, entity_type.as_deref()).await
+    }
+
+    /// Entities pointing to this one through `relation`, optionally of one type.
+    async fn incoming(
+        &self,
+        ctx: &Context<'_>,
+        relation: String,
+        entity_type: Option<String>,
+    ) -> async_graphql::Result<Vec<Node>> {
+        let sources = relation::find_sources(ctx.data::<PgPool>()?, self.0.id, &relation).await?;
+        visible(ctx, sources, entity_type.as_deref()).await
+    }
+}
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    async fn entity(&self, ctx: &Context<'_>, id: i64) -> async_graphql::Result<Option<Node>> {
+        let found = entity::find_by_id(ctx.data::<PgPool>()?, id).await?;
+        Ok(visible(ctx, found.into_iter().collect(), None).await?.pop())
+    }
+
+    /// Entities of one type, in sort order, `first` (at most 100) from `offset`.
+    async fn entities(
+        &self,
+        ctx: &Context<'_>,
+        entity_type: String,
+        #[graphql(default = 25)] first: i32,
+        #[graphql(default = 0)] offset: i32,
+    ) -> async_graphql::Result<Vec<Node>> {
+        let permissions = ctx.data::<Permissions>()?;
+        if !can_read(permissions, &entity_type) {
+            return Ok(vec![]);
+        }
+        let all = entity::find_by_type(ctx.data::<PgPool>()?, &entity_type).await?;
+        Ok(visible(ctx, all, None).await?
+            .into_iter()
+            .skip(offset.max(0) as usize)
+            .take(first.clamp(1, MAX_PAGE) as usize)
+            .collect())
+    }
+
+    /// Names of the relation types that can be followed.
+    async fn relation_types(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<String>> {
+        let types = entity::find_by_type(ctx.data::<PgPool>()?, "relation_type").await?;
+        Ok(types.into_iter().map(|t| t.name).collect())
+    }
+}
@@ -0,0 +1,145 @@
+use actix_session::Session;
+use actix_web::{web, HttpResponse};
+use serde::Deserialize;
+use sqlx::PgPool;
+
+use crate::auth::csrf;
+use crate::auth::session::{get_user_id, require_permission};
+use crate::errors::{AppError, render};
+use crate::handlers::auth_handlers::CsrfOnly;
+use crate::models::access_review;
+use crate::templates_structs::{AccessReviewTemplate, AccessReviewsTemplate, PageContext};
+
+#[derive(Deserialize)]
+pub struct ExportQuery {
+    #[serde(default)]
+    pub format: String,
+}
+
+#[derive(Deserialize)]
+pub struct SignOffForm {
+    pub csrf_token: String,
+    #[serde(default)]
+    pub notes: String,
+}
+
+fn redirect(location: &str) -> HttpResponse {
+    HttpResponse::SeeOther()
+        .insert_header(("Location", location.to_string()))
+        .finish()
+}
+
+/// GET /access-reviews — generated reviews and their sign-off status.
+pub async fn list(
+    pool: web::Data<PgPool>,
+    session: Session,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "audit.view")?;
+    let ctx = PageContext::build(&session, &pool, "/access-reviews").await?;
+    let reviews = access_review::find_all(&pool).await?;
+    render(AccessReviewsTemplate { ctx, reviews })
+}
+
+/// POST /access-reviews — snapshot everyone's access as a new review.
+pub async fn generate(
+    pool: web::Data<PgPool>,
+    session: Session,
+    form: web::Form<CsrfOnly>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "audit.view")?;
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+    let user_id = get_user_id(&session).ok_or(AppError::Session("User not logged in".to_string()))?;
+    let id = access_review::generate(&pool, user_id).await?;
+    let review = access_review::find_by_id(&pool, id).await?.ok_or(AppError::NotFound)?;
+
+    let details = serde_json::json!({
+        "user_count": review.user_count,
+        "summary": format!("Generated {} covering {} users", review.label, review.user_count)
+    });
+    let _ = crate::audit::log(&pool, user_id, "access_review.generated", "access_review", id, details).await;
+
+    let _ = session.insert("flash", format!("Generated {}", review.label));
+    Ok(redirect(&format!("/access-reviews/{id}")))
+}
+
+/// GET /access-reviews/{id} — the captured access and the sign-off form.
+pub async fn detail(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "audit.view")?;
+    let id = path.into_inner();
+    let review = access_review::find_by_id(&pool, id).await?.ok_or(AppError::NotFound)?;
+    let entries = access_review::find_entries(&pool, id).await?;
+    let ctx = PageContext::build(&session, &pool, "/access-reviews").await?;
+    render(AccessReviewTemplate { ctx, review, entries })
+}
+
+/// GET /access-reviews/{id}/export?format=csv|pdf — the review for the
+/// reviewer, with its sign-off once recorded.
+pub async fn export(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+    query: web::Query<ExportQuery>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "audit.view")?;
+    let id = path.into_inner();
+    let format = match query.format.as_str() {
+        "pdf" => "pdf",
+        _ => "csv",
+    };
+    let review = access_review::find_by_id(&pool, id).await?.ok_or(AppError::NotFound)?;
+    let entries = access_review::find_entries(&pool, id).await?;
+
+    let user_id = get_user_id(&session).unwrap_or(0);
+    let watermark = crate::handlers::export_handlers::authorize_export(&pool, user_id, format).await?;
+    let (bytes, content_type) = if format == "pdf" {
+        let pdf = crate::export::pdf::render_access_review(&review, &entries, watermark.as_ref());
+        (pdf, crate::export::pdf::CONTENT_TYPE)
+    } else {
+        let csv = access_review::to_table(&review, &entries).to_csv();
+        let csv = match watermark { Some(mark) => mark.stamp_csv(csv), None => csv };
+        (csv.into_bytes(), "text/csv; charset=utf-8")
+    };
+
+    let details = serde_json::json!({
+        "format": format,
+        "summary": format!("Exported {} as {}", review.label, format.to_uppercase())
+    });
+    let _ = crate::audit::log(&pool, user_id, "access_review.exported", "access_review", id, details).await;
+
+    crate::handlers::export_handlers::checksummed_download(
+        &pool, user_id, "access_review", &review.file_name(format), content_type, "attachment", bytes,
+    ).await
+}
+
+/// POST /access-reviews/{id}/sign-off — record who reviewed the snapshot.
+pub async fn sign_off(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+    form: web::Form<SignOffForm>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "audit.view")?;
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+    let user_id = get_user_id(&session).ok_or(AppError::Session("User not logged in".to_string()))?;
+    let id = path.into_inner();
+    let review = access_review::find_by_id(&pool, id).await?.ok_or(AppError::NotFound)?;
+    let location = format!("/access-reviews/{id}");
+
+    if !access_review::sign_off(&pool, id, user_id, &form.notes).await? {
+        let _ = session.insert("flash", format!("{} was already signed off", review.label));
+        return Ok(redirect(&location));
+    }
+
+    let details = serde_json::json!({
+        "notes": form.notes.trim(),
+        "summary": format!("Signed off {}", review.label)
+    });
+    let _ = crate::audit::log(&pool, user_id, "access_review.signed_off", "access_review", id, details).await;
+
+    let _ = session.insert("flash", format!("Signed off {}", review.label));
+    Ok(redirect(&location))
+}
@@ -1,11 +1,18 @@
 use actix_session::Session;
-use actix_web::{web, HttpResponse};
+use actix_web::{web, HttpRequest, HttpResponse};
 use serde::Deserialize;
 use sqlx::PgPool;
 
-use crate::models::{user, entity};
+use std::collections::HashMap;
+
+use crate::models::{user, api_token, delegation, entity, remember_token, setting, sign_in_device};
+use crate::warnings::notifications::{email, push};
 use crate::auth::{csrf, password, validate};
-use crate::auth::session::get_user_id;
+use crate::auth::new_device;
+use crate::auth::password_policy::{self, PasswordPolicy};
+use crate::auth::oidc::{self, OidcConfig, PendingLogin};
+use crate::auth::session::{get_user_id, get_username};
+use crate::handlers::auth_handlers::{self, CsrfOnly};
 use crate::errors::{AppError, render};
 use crate::templates_structs::{PageContext, AccountTemplate};
 
@@ -17,6 +24,17 @@ pub struct ChangePasswordForm {
     pub csrf_token: String,
 }
 
+#[derive(Deserialize)]
+pub struct OutOfOfficeForm {
+    pub csrf_token: String,
+    #[serde(default)]
+    pub delegate_id: i64,
+    pub start_date: String,
+    pub end_date: String,
+    #[serde(default)]
+    pub note: String,
+}
+
 #[derive(Deserialize)]
 pub struct ProfileUpdateForm {
     pub action: String, // "upload_avatar", "delete_avatar", "update_display_name"
@@ -27,13 +45,63 @@ pub struct ProfileUpdateForm {
     pub display_name: String,
 }
 
+/// Render the account page with the user's remembered devices and
+/// out-of-office window.
+async fn render_account(pool: &PgPool, session: &Session, errors: Vec<String>) -> Result<HttpResponse, AppError> {
+    render_account_with_tokens(pool, session, errors, Vec::new(), None).await
+}
+
+/// Render the account page, opening the API Tokens tab when there are token
+/// errors or a newly created token to show.
+async fn render_account_with_tokens(
+    pool: &PgPool,
+    session: &Session,
+    errors: Vec<String>,
+    token_errors: Vec<String>,
+    new_token: Option<String>,
+) -> Result<HttpResponse, AppError> {
+    let ctx = PageContext::build(session, pool, "/account").await?;
+    let user_id = get_user_id(session).unwrap_or(0);
+    let devices = remember_token::find_for_user(pool, user_id).await?;
+    let sign_ins = sign_in_device::find_for_user(pool, user_id).await?;
+    let today = chrono::Local::now().date_naive();
+    let out_of_office = delegation::find_for_user(pool, user_id).await?
+        .filter(|ooo| ooo.is_current_on(today));
+    let acting_for = delegation::find_active_delegators(pool, user_id, today).await?;
+    let delegates = entity::find_by_type(pool, "user").await?
+        .into_iter()
+        .filter(|u| u.id != user_id)
+        .collect();
+    let email_preferences = email::find_preferences(pool, user_id).await?;
+    let email_enabled = setting::get_bool(pool, "email.enabled").await;
+    let properties = entity::get_properties(pool, user_id).await?;
+    let has_email = properties.get("email").is_some_and(|e| !e.trim().is_empty());
+    let api_tokens = api_token::find_for_user(pool, user_id).await?;
+    let sso_enabled = OidcConfig::load(pool).await.is_some();
+    let sso_linked = properties.get(oidc::SUBJECT_PROPERTY).is_some_and(|s| !s.is_empty());
+    let has_password = properties.get("password").is_some_and(|p| !p.is_empty());
+    let push_public_key = push::load_vapid(pool).await.map(|v| v.public_key_b64());
+    let push_devices = push::find_for_user(pool, user_id).await?;
+    let password_rules = PasswordPolicy::load(pool).await.describe();
+    let password_expired = session.get::<bool>(password_policy::EXPIRED_KEY).unwrap_or(None).unwrap_or(false);
+    let utc_today = chrono::Utc::now().date_naive();
+    render(AccountTemplate {
+        ctx, errors, devices, sign_ins, out_of_office, acting_for, delegates,
+        email_preferences, email_enabled, has_email,
+        api_tokens, token_errors, new_token,
+        sso_enabled, sso_linked, has_password, password_rules, password_expired,
+        push_public_key, push_devices,
+        today: utc_today.format("%Y-%m-%d").to_string(),
+        max_token_expiry: (utc_today + chrono::Duration::days(api_token::MAX_LIFETIME_DAYS))
+            .format("%Y-%m-%d").to_string(),
+    })
+}
+
 pub async fn form(
     pool: web::Data<PgPool>,
     session: Session,
 ) -> Result<HttpResponse, AppError> {
-    let ctx = PageContext::build(&session, &pool, "/account").await?;
-    let tmpl = AccountTemplate { ctx, errors: vec![] };
-    render(tmpl)
+    render_account(&pool, &session, vec![]).await
 }
 
 pub async fn submit(
@@ -47,16 +115,15 @@ pub async fn submit(
         .ok_or_else(|| AppError::Session("User not logged in".to_string()))?;
 
     // Validate inputs
-    let mut errors: Vec<String> = vec![];
-    errors.extend(validate::validate_password(&form.new_password));
+    let username = get_username(&session).map_err(AppError::Session)?;
+    let policy = PasswordPolicy::load(&pool).await;
+    let mut errors = policy.validate(&pool, &form.new_password, &username, Some(user_id)).await?;
     if form.new_password != form.confirm_password {
         errors.push("New passwords do not match".to_string());
     }
 
     if !errors.is_empty() {
-        let ctx = PageContext::build(&session, &pool, "/account").await?;
-        let tmpl = AccountTemplate { ctx, errors };
-        return render(tmpl);
+        return render_account(&pool, &session, errors).await;
     }
 
     // Verify current password
@@ -66,9 +133,7 @@ pub async fn submit(
     match password::verify_password(&form.current_password, &stored_hash) {
         Ok(true) => {}
         _ => {
-            let ctx = PageContext::build(&session, &pool, "/account").await?;
-            let tmpl = AccountTemplate { ctx, errors: vec!["Current password is incorrect".to_string()] };
-            return render(tmpl);
+            return render_account(&pool, &session, vec!["Current password is incorrect".to_string()]).await;
         }
     }
 
@@ -77,12 +142,277 @@ pub async fn submit(
         .map_err(AppError::Hash)?;
     user::update_password(&pool, user_id, &new_hash).await?;
 
+    // A new password invalidates every remembered device
+    remember_token::revoke_all(&pool, user_id).await?;
+    session.remove(password_policy::EXPIRED_KEY);
+
     let _ = session.insert("flash", "Password changed successfully");
     Ok(HttpResponse::SeeOther()
         .insert_header(("Location", "/account"))
         .finish())
 }
 
+/// POST /account/sessions/{id}/revoke — forget one remembered device.
+pub async fn revoke_device(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+    form: web::Form<CsrfOnly>,
+) -> Result<HttpResponse, AppError> {
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+    let user_id = get_user_id(&session)
+        .ok_or_else(|| AppError::Session("User not logged in".to_string()))?;
+
+    if remember_token::revoke(&pool, user_id, path.into_inner()).await? {
+        let details = serde_json::json!({ "summary": "Remembered device revoked" });
+        let _ = crate::audit::log(&pool, user_id, "user.device_revoked", "user", user_id, details).await;
+        let _ = session.insert("flash", "Device revoked");
+    }
+    Ok(HttpResponse::SeeOther()
+        .insert_header(("Location", "/account#sessions"))
+        .finish())
+}
+
+/// POST /account/sessions/revoke-all — forget every remembered device.
+pub async fn revoke_all_devices(
+    pool: web::Data<PgPool>,
+    session: Session,
+    form: web::Form<CsrfOnly>,
+) -> Result<HttpResponse, AppError> {
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+    let user_id = get_user_id(&session)
+        .ok_or_else(|| AppError::Session("User not logged in".to_string()))?;
+
+    let count = remember_token::revoke_all(&pool, user_id).await?;
+    let details = serde_json::json!({
+        "count": count,
+        "summary": format!("Revoked {} remembered device(s)", count)
+    });
+    let _ = crate::audit::log(&pool, user_id, "user.devices_revoked", "user", user_id, details).await;
+
+    let _ = session.insert("flash", "All remembered devices revoked");
+    Ok(HttpResponse::SeeOther()
+        .insert_header(("Location", "/account#sessions"))
+        .finish())
+}
+
+/// POST /account/sign-ins/{id}/not-me — the user does not recognise a
+/// sign-in: lock the account, alert the administrators and sign out.
+pub async fn report_sign_in(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+    form: web::Form<CsrfOnly>,
+) -> Result<HttpResponse, AppError> {
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+    let user_id = get_user_id(&session)
+        .ok_or_else(|| AppError::Session("User not logged in".to_string()))?;
+    let sign_in = sign_in_device::find_by_id(&pool, path.into_inner()).await?
+        .filter(|s| s.user_id == user_id)
+        .ok_or(AppError::NotFound)?;
+    let username = get_username(&session).map_err(AppError::Session)?;
+
+    new_device::report_not_me(&pool, user_id, &username, &sign_in).await?;
+
+    session.purge();
+    let message = "Your account is locked and the administrators have been alerted. \
+                   Contact one of them to unlock it and change your password.";
+    auth_handlers::render_login(&pool, &session, Some(message.to_string()), String::new()).await
+}
+
+/// POST /account/sso/link — link a single sign-on identity to this account
+/// by signing in at the provider.
+pub async fn link_sso(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    session: Session,
+    form: web::Form<CsrfOnly>,
+) -> Result<HttpResponse, AppError> {
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+    let user_id = get_user_id(&session)
+        .ok_or_else(|| AppError::Session("User not logged in".to_string()))?;
+    let config = OidcConfig::load(&pool).await.ok_or(AppError::NotFound)?;
+    auth_handlers::start_oidc(&req, &pool, &session, &config, PendingLogin::new("/account", Some(user_id))).await
+}
+
+/// POST /account/sso/unlink — remove the linked identity. Refused for
+/// accounts without a password, which could then no longer sign in.
+pub async fn unlink_sso(
+    pool: web::Data<PgPool>,
+    session: Session,
+    form: web::Form<CsrfOnly>,
+) -> Result<HttpResponse, AppError> {
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+    let user_id = get_user_id(&session)
+        .ok_or_else(|| AppError::Session("User not logged in".to_string()))?;
+
+    let has_password = user::find_password_hash_by_id(&pool, user_id).await?
+        .is_some_and(|h| !h.is_empty());
+    if !has_password {
+        let _ = session.insert("flash", "Your account has no password, so single sign-on cannot be unlinked");
+    } else {
+        entity::delete_property(&pool, user_id, oidc::SUBJECT_PROPERTY).await?;
+        let details = serde_json::json!({ "summary": "Unlinked single sign-on identity" });
+        let _ = crate::audit::log(&pool, user_id, "user.sso_unlinked", "user", user_id, details).await;
+        let _ = session.insert("flash", "Single sign-on unlinked");
+    }
+    Ok(HttpResponse::SeeOther()
+        .insert_header(("Location", "/account#security"))
+        .finish())
+}
+
+/// POST /account/api-tokens — create an API token. The raw token is
+/// rendered once and never stored.
+pub async fn create_api_token(
+    pool: web::Data<PgPool>,
+    session: Session,
+    form: web::Form<HashMap<String, String>>,
+) -> Result<HttpResponse, AppError> {
+    csrf::validate_csrf(&session, form.get("csrf_token").map(String::as_str).unwrap_or(""))?;
+    let user_id = get_user_id(&session)
+        .ok_or_else(|| AppError::Session("User not logged in".to_string()))?;
+
+    let scopes: Vec<String> = api_token::SCOPES.iter()
+        .filter(|(key, _, _)| form.contains_key(&format!("scope_{}", key)))
+        .map(|(key, _, _)| key.to_string())
+        .collect();
+    let input = api_token::ApiTokenInput {
+        label: form.get("label").map(String::as_str).unwrap_or(""),
+        scopes: &scopes,
+        read_only: form.contains_key("read_only"),
+        expires_at: form.get("expires_at").map(String::as_str).unwrap_or(""),
+    };
+    let errors = api_token::validate(&input, chrono::Utc::now().date_naive());
+    if !errors.is_empty() {
+        return render_account_with_tokens(&pool, &session, vec![], errors, None).await;
+    }
+
+    let (token_id, raw) = api_token::create(&pool, user_id, &input).await?;
+    let details = serde_json::json!({
+        "token_id": token_id,
+        "scopes": scopes,
+        "read_only": input.read_only,
+        "expires_at": input.expires_at,
+        "summary": format!("Created API token '{}'", input.label.trim())
+    });
+    let _ = crate::audit::log(&pool, user_id, "user.api_token_created", "user", user_id, details).await;
+
+    render_account_with_tokens(&pool, &session, vec![], vec![], Some(raw)).await
+}
+
+/// POST /account/api-tokens/{id}/revoke — stop a token from working.
+pub async fn revoke_api_token(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+    form: web::Form<CsrfOnly>,
+) -> Result<HttpResponse, AppError> {
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+    let user_id = get_user_id(&session)
+        .ok_or_else(|| AppError::Session("User not logged in".to_string()))?;
+
+    let token_id = path.into_inner();
+    if api_token::revoke(&pool, user_id, token_id).await? {
+        let details = serde_json::json!({ "token_id": token_id, "summary": "API token revoked" });
+        let _ = crate::audit::log(&pool, user_id, "user.api_token_revoked", "user", user_id, details).await;
+        let _ = session.insert("flash", "API token revoked");
+    }
+    Ok(HttpResponse::SeeOther()
+        .insert_header(("Location", "/account#api-tokens"))
+        .finish())
+}
+
+/// POST /account/out-of-office — route reviews and decisions to a delegate
+/// for a date window.
+pub async fn set_out_of_office(
+    pool: web::Data<PgPool>,
+    session: Session,
+    form: web::Form<OutOfOfficeForm>,
+) -> Result<HttpResponse, AppError> {
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+    let user_id = get_user_id(&session)
+        .ok_or_else(|| AppError::Session("User not logged in".to_string()))?;
+
+    let errors = delegation::validate(user_id, form.delegate_id, &form.start_date, &form.end_date);
+    if !errors.is_empty() {
+        let _ = session.insert("flash", errors.join("; "));
+        return Ok(HttpResponse::SeeOther()
+            .insert_header(("Location", "/account#out-of-office"))
+            .finish());
+    }
+    let delegate = entity::find_by_id(&pool, form.delegate_id).await?
+        .filter(|e| e.entity_type == "user")
+        .ok_or(AppError::NotFound)?;
+
+    delegation::set_out_of_office(&pool, user_id, delegate.id, &form.start_date, &form.end_date, &form.note).await?;
+    let details = serde_json::json!({
+        "delegate_id": delegate.id,
+        "start_date": form.start_date.trim(),
+        "end_date": form.end_date.trim(),
+        "summary": format!(
+            "Out of office {} to {}, delegated to {}",
+            form.start_date.trim(), form.end_date.trim(), delegate.label
+        )
+    });
+    let _ = crate::audit::log(&pool, user_id, "user.out_of_office_set", "user", user_id, details).await;
+
+    let _ = session.insert("flash", format!("Out of office set; {} will act on your behalf", delegate.label));
+    Ok(HttpResponse::SeeOther()
+        .insert_header(("Location", "/account#out-of-office"))
+        .finish())
+}
+
+/// POST /account/out-of-office/clear — end the out-of-office window.
+pub async fn clear_out_of_office(
+    pool: web::Data<PgPool>,
+    session: Session,
+    form: web::Form<CsrfOnly>,
+) -> Result<HttpResponse, AppError> {
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+    let user_id = get_user_id(&session)
+        .ok_or_else(|| AppError::Session("User not logged in".to_string()))?;
+
+    delegation::clear_out_of_office(&pool, user_id).await?;
+    let details = serde_json::json!({ "summary": "Out of office cleared" });
+    let _ = crate::audit::log(&pool, user_id, "user.out_of_office_cleared", "user", user_id, details).await;
+
+    let _ = session.insert("flash", "Welcome back; out of office cleared");
+    Ok(HttpResponse::SeeOther()
+        .insert_header(("Location", "/account#out-of-office"))
+        .finish())
+}
+
+/// POST /account/email-preferences — choose which e-mail to receive.
+pub async fn set_email_preferences(
+    pool: web::Data<PgPool>,
+    session: Session,
+    form: web::Form<HashMap<String, String>>,
+) -> Result<HttpResponse, AppError> {
+    csrf::validate_csrf(&session, form.get("csrf_token").map(String::as_str).unwrap_or(""))?;
+    let user_id = get_user_id(&session)
+        .ok_or_else(|| AppError::Session("User not logged in".to_string()))?;
+
+    let enabled: Vec<String> = email::PREFERENCES.iter()
+        .filter(|(key, _, _)| form.contains_key(&format!("email_{}", key)))
+        .map(|(key, _, _)| key.to_string())
+        .collect();
+    email::set_preferences(&pool, user_id, &enabled).await?;
+    let details = serde_json::json!({
+        "enabled": enabled,
+        "summary": if enabled.is_empty() {
+            "Opted out of all e-mail notifications".to_string()
+        } else {
+            format!("E-mail notifications: {}", enabled.join(", "))
+        }
+    });
+    let _ = crate::audit::log(&pool, user_id, "user.email_preferences_updated", "user", user_id, details).await;
+
+    let _ = session.insert("flash", "E-mail preferences saved");
+    Ok(HttpResponse::SeeOther()
+        .insert_header(("Location", "/account#preferences"))
+        .finish())
+}
+
 /// Handle profile updates (avatar upload, delete, display name change)
 pub async fn update_profile(
     pool: web::Data<PgPool>,
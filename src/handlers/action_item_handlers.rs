@@ -0,0 +1,319 @@
+use std::collections::HashMap;
+
+use actix_session::Session;
+use actix_web::{web, HttpResponse};
+use serde::Deserialize;
+use sqlx::PgPool;
+
+use crate::auth::csrf;
+use crate::auth::session::{get_permissions, get_user_id, require_permission};
+use crate::errors::{AppError, render};
+use crate::handlers::auth_handlers::CsrfOnly;
+use crate::models::action_item::{self, ActionItem, ActionItemInput};
+use crate::models::{agenda_point, tor};
+use crate::templates_structs::{ActionItemFormTemplate, ActionItemsTemplate, MyActionItemsTemplate, PageContext};
+
+#[derive(Deserialize)]
+pub struct ActionsQuery {
+    /// Preselects the originating agenda point in the new item form.
+    pub agenda_point: Option<i64>,
+}
+
+fn today() -> String {
+    chrono::Local::now().format("%Y-%m-%d").to_string()
+}
+
+fn field<'a>(form: &'a HashMap<String, String>, key: &str) -> &'a str {
+    form.get(key).map(String::as_str).unwrap_or("")
+}
+
+fn input_from(form: &HashMap<String, String>) -> ActionItemInput<'_> {
+    ActionItemInput {
+        title: field(form, "title"),
+        description: field(form, "description"),
+        owner_id: field(form, "owner_id").parse().unwrap_or(0),
+        due_date: field(form, "due_date"),
+        status: form.get("status").map(String::as_str).unwrap_or(action_item::STATUSES[0].0),
+    }
+}
+
+fn redirect(tor_id: i64) -> HttpResponse {
+    HttpResponse::SeeOther()
+        .insert_header(("Location", format!("/tor/{tor_id}/actions")))
+        .finish()
+}
+
+/// The current user, after checking they belong to the ToR.
+async fn member(pool: &PgPool, session: &Session, tor_id: i64) -> Result<i64, AppError> {
+    let user_id = get_user_id(session).ok_or(AppError::Session("User not logged in".to_string()))?;
+    tor::require_tor_membership(pool, user_id, tor_id).await?;
+    Ok(user_id)
+}
+
+async fn find_in_tor(pool: &PgPool, tor_id: i64, id: i64) -> Result<ActionItem, AppError> {
+    action_item::find_by_id(pool, id).await?
+        .filter(|i| i.tor_id == tor_id)
+        .ok_or(AppError::NotFound)
+}
+
+/// Resolve the warning an item was converted from once the item is done.
+async fn resolve_linked_warning(pool: &PgPool, item: &ActionItem, status: &str, user_id: i64) -> Result<(), AppError> {
+    let Some(warning_id) = action_item::resolve_linked_warning(pool, item, status, user_id).await? else {
+        return Ok(());
+    };
+    let details = serde_json::json!({
+        "action_item_id": item.id,
+        "summary": format!("Resolved by completing action item '{}'", item.title)
+    });
+    let _ = crate::audit::log(pool, user_id, "warning.resolved", "warning", warning_id, details).await;
+    Ok(())
+}
+
+async fn render_list(
+    pool: &PgPool,
+    session: &Session,
+    tor_id: i64,
+    values: HashMap<String, String>,
+    errors: Vec<String>,
+) -> Result<HttpResponse, AppError> {
+    let tor_name = tor::get_tor_name(pool, tor_id).await?;
+    let ctx = PageContext::build(session, pool, "/workflow").await?
+        .with_tor(tor_id, &tor_name, "actions");
+    let user_id = get_user_id(session).unwrap_or(0);
+    let can_manage = get_permissions(session).map(|p| p.has("agenda.manage")).unwrap_or(false);
+    render(ActionItemsTemplate {
+        ctx,
+        tor_id,
+        tor_name,
+        items: action_item::find_for_tor(pool, tor_id).await?,
+        owners: action_item::find_owners(pool, tor_id).await?,
+        agenda_points: agenda_point::find_all_for_tor(pool, tor_id).await?
+            .into_iter()
+            .map(|p| (p.id, p.title))
+            .collect(),
+        values,
+        errors,
+        today: today(),
+        user_id,
+        can_manage,
+    })
+}
+
+/// GET /tor/{id}/actions — the ToR's action items and the form for a new one.
+pub async fn list(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+    query: web::Query<ActionsQuery>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "agenda.view")?;
+    let tor_id = path.into_inner();
+    member(&pool, &session, tor_id).await?;
+
+    let mut values = HashMap::new();
+    if let Some(ap) = query.agenda_point {
+        values.insert("agenda_point_id".to_string(), ap.to_string());
+    }
+    render_list(&pool, &session, tor_id, values, Vec::new()).await
+}
+
+/// POST /tor/{id}/actions — record an action item, optionally against the
+/// agenda point (and so the decision) it arises from.
+pub async fn create(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+    form: web::Form<HashMap<String, String>>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "agenda.manage")?;
+    csrf::validate_csrf(&session, field(&form, "csrf_token"))?;
+    let tor_id = path.into_inner();
+    let user_id = member(&pool, &session, tor_id).await?;
+
+    let input = input_from(&form);
+    let owners: Vec<i64> = action_item::find_owners(&pool, tor_id).await?.into_iter().map(|(id, _)| id).collect();
+    let mut errors = action_item::validate(&input, &owners);
+    let agenda_point_id = field(&form, "agenda_point_id").parse::<i64>().ok();
+    if let Some(ap) = agenda_point_id
+        && agenda_point::find_by_id(&pool, ap).await?.is_none_or(|p| p.tor_id != tor_id)
+    {
+        errors.push("The agenda point is not in this ToR".to_string());
+    }
+    if !errors.is_empty() {
+        return render_list(&pool, &session, tor_id, form.into_inner(), errors).await;
+    }
+
+    let id = action_item::create(&pool, tor_id, &input, agenda_point_id, user_id).await?;
+    let details = serde_json::json!({
+        "tor_id": tor_id,
+        "owner_id": input.owner_id,
+        "due_date": input.due_date.trim(),
+        "agenda_point_id": agenda_point_id,
+        "summary": format!("Added action item '{}'", input.title.trim())
+    });
+    let _ = crate::audit::log(&pool, user_id, "action_item.created", "action_item", id, details).await;
+
+    let _ = session.insert("flash", format!("Action item '{}' added", input.title.trim()));
+    Ok(redirect(tor_id))
+}
+
+/// GET /tor/{id}/actions/{aid} — edit form.
+pub async fn edit(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<(i64, i64)>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "agenda.manage")?;
+    let (tor_id, id) = path.into_inner();
+    member(&pool, &session, tor_id).await?;
+    let item = find_in_tor(&pool, tor_id, id).await?;
+    render_form(&pool, &session, item, HashMap::new(), Vec::new()).await
+}
+
+async fn render_form(
+    pool: &PgPool,
+    session: &Session,
+    item: ActionItem,
+    mut values: HashMap<String, String>,
+    errors: Vec<String>,
+) -> Result<HttpResponse, AppError> {
+    if values.is_empty() {
+        values = HashMap::from([
+            ("title".to_string(), item.title.clone()),
+            ("description".to_string(), item.description.clone()),
+            ("owner_id".to_string(), item.owner_id.to_string()),
+            ("due_date".to_string(), item.due_date.clone()),
+            ("status".to_string(), item.status.clone()),
+        ]);
+    }
+    let tor_name = tor::get_tor_name(pool, item.tor_id).await?;
+    let ctx = PageContext::build(session, pool, "/workflow").await?
+        .with_tor(item.tor_id, &tor_name, "actions");
+    render(ActionItemFormTemplate {
+        ctx,
+        owners: action_item::find_owners(pool, item.tor_id).await?,
+        item,
+        values,
+        errors,
+    })
+}
+
+/// POST /tor/{id}/actions/{aid} — update an action item.
+pub async fn update(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<(i64, i64)>,
+    form: web::Form<HashMap<String, String>>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "agenda.manage")?;
+    csrf::validate_csrf(&session, field(&form, "csrf_token"))?;
+    let (tor_id, id) = path.into_inner();
+    let user_id = member(&pool, &session, tor_id).await?;
+    let item = find_in_tor(&pool, tor_id, id).await?;
+
+    let input = input_from(&form);
+    let mut owners: Vec<i64> = action_item::find_owners(&pool, tor_id).await?.into_iter().map(|(id, _)| id).collect();
+    // An owner who has since left the ToR can stay on the item
+    owners.push(item.owner_id);
+    let errors = action_item::validate(&input, &owners);
+    if !errors.is_empty() {
+        return render_form(&pool, &session, item, form.into_inner(), errors).await;
+    }
+
+    action_item::update(&pool, &item, &input).await?;
+    resolve_linked_warning(&pool, &item, input.status, user_id).await?;
+    let details = serde_json::json!({
+        "tor_id": tor_id,
+        "old_owner_id": item.owner_id,
+        "owner_id": input.owner_id,
+        "old_status": item.status,
+        "status": input.status,
+        "due_date": input.due_date.trim(),
+        "summary": format!("Updated action item '{}'", input.title.trim())
+    });
+    let _ = crate::audit::log(&pool, user_id, "action_item.updated", "action_item", id, details).await;
+
+    let _ = session.insert("flash", format!("Action item '{}' updated", input.title.trim()));
+    Ok(redirect(tor_id))
+}
+
+/// POST /tor/{id}/actions/{aid}/status — change the status. Open to the
+/// item's owner as well as to agenda managers.
+pub async fn set_status(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<(i64, i64)>,
+    form: web::Form<HashMap<String, String>>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "agenda.view")?;
+    csrf::validate_csrf(&session, field(&form, "csrf_token"))?;
+    let (tor_id, id) = path.into_inner();
+    let user_id = member(&pool, &session, tor_id).await?;
+    let item = find_in_tor(&pool, tor_id, id).await?;
+
+    let can_manage = get_permissions(&session).map(|p| p.has("agenda.manage")).unwrap_or(false);
+    if item.owner_id != user_id && !can_manage {
+        return Err(AppError::PermissionDenied("Only the owner can update this action item".to_string()));
+    }
+    let status = field(&form, "status");
+    if !action_item::STATUSES.iter().any(|(key, _)| *key == status) {
+        return Err(AppError::PermissionDenied("Unknown action item status".to_string()));
+    }
+
+    action_item::set_status(&pool, id, status, &item.status).await?;
+    resolve_linked_warning(&pool, &item, status, user_id).await?;
+    let details = serde_json::json!({
+        "tor_id": tor_id,
+        "old_status": item.status,
+        "status": status,
+        "summary": format!("Marked action item '{}' {}", item.title, action_item::status_label(status).to_lowercase())
+    });
+    let _ = crate::audit::log(&pool, user_id, "action_item.status_changed", "action_item", id, details).await;
+
+    let _ = session.insert("flash", format!("Action item '{}' marked {}", item.title, action_item::status_label(status).to_lowercase()));
+    let back = match field(&form, "return_to") {
+        "mine" => "/actions".to_string(),
+        _ => format!("/tor/{tor_id}/actions"),
+    };
+    Ok(HttpResponse::SeeOther().insert_header(("Location", back)).finish())
+}
+
+/// POST /tor/{id}/actions/{aid}/delete
+pub async fn delete(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<(i64, i64)>,
+    form: web::Form<CsrfOnly>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "agenda.manage")?;
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+    let (tor_id, id) = path.into_inner();
+    let user_id = member(&pool, &session, tor_id).await?;
+    let item = find_in_tor(&pool, tor_id, id).await?;
+
+    action_item::delete(&pool, id).await?;
+    let details = serde_json::json!({
+        "tor_id": tor_id,
+        "owner_id": item.owner_id,
+        "summary": format!("Deleted action item '{}'", item.title)
+    });
+    let _ = crate::audit::log(&pool, user_id, "action_item.deleted", "action_item", id, details).await;
+
+    let _ = session.insert("flash", format!("Action item '{}' deleted", item.title));
+    Ok(redirect(tor_id))
+}
+
+/// GET /actions — the current user's action items across all ToRs.
+pub async fn mine(
+    pool: web::Data<PgPool>,
+    session: Session,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "agenda.view")?;
+    let user_id = get_user_id(&session).ok_or(AppError::Session("User not logged in".to_string()))?;
+    let ctx = PageContext::build(&session, &pool, "/actions").await?;
+    render(MyActionItemsTemplate {
+        ctx,
+        items: action_item::find_for_owner(&pool, user_id).await?,
+        today: today(),
+    })
+}
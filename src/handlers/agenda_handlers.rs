@@ -2,7 +2,6 @@ use actix_session::Session;
 use actix_web::{web, HttpResponse};
 use sqlx::PgPool;
 
-use std::collections::HashMap;
 use crate::auth::csrf;
 use crate::auth::session::{require_permission, get_user_id, get_permissions};
 use crate::errors::{AppError, render};
@@ -192,27 +191,42 @@ pub async fn detail(
             let permissions = crate::auth::session::get_permissions(&session)
                 .map_err(|e| AppError::Session(e))?;
 
-            // Get agenda point entity properties for transition validation
-            let mut entity_properties = std::collections::HashMap::new();
-            entity_properties.insert("status".to_string(), ap.status.clone());
-            entity_properties.insert("item_type".to_string(), ap.item_type.clone());
-
             // Get available transitions
             let available_transitions = workflow::find_available_transitions(
                 &pool,
                 "agenda_point",
                 &ap.status,
                 &permissions,
-                &entity_properties,
+                agenda_point_id,
             ).await?;
 
+            let opinion_versions = opinion::find_versions_for_agenda_point(&pool, agenda_point_id).await?;
+
+            // Decision authority configured for the ToR
+            let authority = tor::find_decision_authority(&pool, tor_id).await?;
+            let members = tor::find_members(&pool, tor_id).await?;
+            let decision_authority = authority.describe(&members);
+            let can_decide = tor::can_decide(&pool, &authority, user_id, &permissions).await?;
+            let sentiment = opinion::find_sentiment(&pool, agenda_point_id, user_id).await?;
+            let ballot_open = crate::models::ballot::find_for_agenda_point(&pool, agenda_point_id).await?
+                .iter()
+                .any(|b| b.is_open());
+            let action_items = crate::models::action_item::find_for_agenda_point(&pool, agenda_point_id).await?;
+
             let tmpl = AgendaPointDetailTemplate {
                 ctx,
                 tor_id,
                 agenda_point: ap,
                 coas,
                 opinions,
+                opinion_versions,
+                decision_authority,
+                can_decide,
                 available_transitions,
+                sentiment,
+                user_id,
+                ballot_open,
+                action_items,
             };
             render(tmpl)
         }
@@ -349,24 +363,17 @@ pub async fn transition(
     let permissions = get_permissions(&session)
         .map_err(|e| AppError::Session(format!("Failed to get permissions: {}", e)))?;
 
-    let mut entity_properties = HashMap::new();
-    entity_properties.insert("item_type".to_string(), ap.item_type.clone());
-
-    // Validate the transition via the workflow engine
-    workflow::validate_transition(
+    // Take the transition via the workflow engine
+    workflow::execute_transition(
         &pool,
         "agenda_point",
+        agenda_point_id,
         &ap.status,
         &form.to_status,
         &permissions,
-        &entity_properties,
+        user_id,
     ).await?;
 
-    // Update status
-    entity::set_property(&pool, agenda_point_id, "status", &form.to_status)
-        .await
-        .map_err(AppError::Db)?;
-
     // Audit log
     let details = serde_json::json!({
         "agenda_point_id": agenda_point_id,
@@ -0,0 +1,30 @@
+use actix_session::Session;
+use actix_web::{web, HttpResponse};
+use serde::Deserialize;
+use sqlx::PgPool;
+
+use crate::models::api_usage;
+use crate::auth::session::require_permission;
+use crate::errors::{AppError, render};
+use crate::templates_structs::{PageContext, ApiUsageTemplate};
+
+#[derive(Deserialize)]
+pub struct ApiUsageQuery {
+    hours: Option<i64>,
+}
+
+/// GET /api-usage — request counts, error rates and latency per caller and endpoint.
+pub async fn list(
+    pool: web::Data<PgPool>,
+    session: Session,
+    query: web::Query<ApiUsageQuery>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+
+    let ctx = PageContext::build(&session, &pool, "/api-usage").await?;
+    let hours = query.hours.unwrap_or(24).clamp(1, 24 * 30);
+    let since = chrono::Utc::now() - chrono::Duration::hours(hours);
+    let usage = api_usage::summarize(&pool, since).await?;
+
+    render(ApiUsageTemplate { ctx, usage, hours })
+}
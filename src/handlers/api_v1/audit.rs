@@ -0,0 +1,53 @@
+use actix_session::Session;
+use actix_web::{web, HttpResponse};
+use sqlx::PgPool;
+
+use crate::audit::export;
+use crate::auth::session::require_permission;
+use crate::errors::AppError;
+use crate::models::audit::AuditFilter;
+use crate::templates_structs::PaginatedResponse;
+
+/// GET /api/v1/audit - Audit records from the database and the daily log
+/// files, oldest first, for SIEM ingestion.
+/// Query params: q, user, action (prefix), target_type, from and to
+/// (YYYY-MM-DD, default the last 30 days), page (default 1), per_page
+/// (default 100, max 1000).
+pub async fn list(
+    pool: web::Data<PgPool>,
+    session: Session,
+    filter: web::Query<AuditFilter>,
+    query: web::Query<std::collections::HashMap<String, String>>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "audit.view")?;
+
+    let page = query
+        .get("page")
+        .and_then(|p| p.parse::<i64>().ok())
+        .unwrap_or(1)
+        .max(1);
+    let per_page = query
+        .get("per_page")
+        .and_then(|p| p.parse::<i64>().ok())
+        .unwrap_or(100)
+        .clamp(1, 1000);
+
+    let (from, to) = match export::period(&filter, chrono::Utc::now().date_naive()) {
+        Ok(period) => period,
+        Err(message) => return Ok(HttpResponse::BadRequest().json(serde_json::json!({ "error": message }))),
+    };
+    let records = export::collect(&pool, &filter, from, to).await
+        .map_err(|e| AppError::Session(format!("Could not read the audit log: {e}")))?;
+    let total = records.len() as i64;
+    let items: Vec<_> = records.into_iter()
+        .skip(((page - 1) * per_page) as usize)
+        .take(per_page as usize)
+        .collect();
+
+    Ok(HttpResponse::Ok().json(PaginatedResponse {
+        items,
+        page,
+        per_page,
+        total,
+    }))
+}
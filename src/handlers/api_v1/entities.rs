@@ -5,10 +5,14 @@ use sqlx::PgPool;
 use crate::models::entity;
 use crate::auth::session::{get_user_id, require_permission};
 use crate::errors::AppError;
+use crate::handlers::warning_handlers::ws::{self, ConnectionMap};
 use crate::templates_structs::{
     PaginatedResponse, ApiEntityResponse, ApiEntityRequest, ApiEntityProperty, ApiErrorResponse,
 };
 
+/// Entity types whose changes alter signed-in users' menus.
+const NAV_TYPES: &[&str] = &["nav_item", "role", "permission"];
+
 /// GET /api/v1/entities - List entities with optional type filter and pagination
 /// Query params: entity_type (filter), page (default 1), per_page (default 25)
 pub async fn list(
@@ -123,6 +127,7 @@ pub async fn create(
     pool: web::Data<PgPool>,
     session: Session,
     body: web::Json<ApiEntityRequest>,
+    conn_map: web::Data<ConnectionMap>,
 ) -> Result<HttpResponse, AppError> {
     require_permission(&session, "entities.create")?;
 
@@ -171,6 +176,9 @@ pub async fn create(
         "summary": "Entity created via API"
     });
     let _ = crate::audit::log(&pool, current_user_id, "entity.created", "entity", entity_id, details).await;
+    if NAV_TYPES.contains(&body.entity_type.as_str()) {
+        ws::notify_nav_updated(&pool, &conn_map).await?;
+    }
 
     // Fetch and return created entity
     let created_entity = entity::find_by_id(&pool, entity_id).await?.ok_or(AppError::NotFound)?;
@@ -200,13 +208,14 @@ pub async fn update(
     session: Session,
     path: web::Path<i64>,
     body: web::Json<ApiEntityRequest>,
+    conn_map: web::Data<ConnectionMap>,
 ) -> Result<HttpResponse, AppError> {
     require_permission(&session, "entities.edit")?;
 
     let entity_id = path.into_inner();
 
     // Check if entity exists
-    let _existing = entity::find_by_id(&pool, entity_id).await?.ok_or(AppError::NotFound)?;
+    let existing = entity::find_by_id(&pool, entity_id).await?.ok_or(AppError::NotFound)?;
 
     // Validate
     let mut errors = Vec::new();
@@ -251,6 +260,9 @@ pub async fn update(
         "summary": "Entity updated via API"
     });
     let _ = crate::audit::log(&pool, current_user_id, "entity.updated", "entity", entity_id, details).await;
+    if NAV_TYPES.contains(&existing.entity_type.as_str()) {
+        ws::notify_nav_updated(&pool, &conn_map).await?;
+    }
 
     // Fetch and return updated entity
     let updated_entity = entity::find_by_id(&pool, entity_id).await?.ok_or(AppError::NotFound)?;
@@ -279,16 +291,20 @@ pub async fn delete(
     pool: web::Data<PgPool>,
     session: Session,
     path: web::Path<i64>,
+    conn_map: web::Data<ConnectionMap>,
 ) -> Result<HttpResponse, AppError> {
     require_permission(&session, "entities.delete")?;
 
     let entity_id = path.into_inner();
 
     // Check if entity exists
-    entity::find_by_id(&pool, entity_id).await?.ok_or(AppError::NotFound)?;
+    let existing = entity::find_by_id(&pool, entity_id).await?.ok_or(AppError::NotFound)?;
 
     // Delete entity
     entity::delete(&pool, entity_id).await?;
+    if NAV_TYPES.contains(&existing.entity_type.as_str()) {
+        ws::notify_nav_updated(&pool, &conn_map).await?;
+    }
 
     // Audit log
     let current_user_id = get_user_id(&session).unwrap_or(0);
@@ -0,0 +1,45 @@
+use actix_session::Session;
+use actix_web::{web, HttpResponse};
+use serde::Serialize;
+use sqlx::PgPool;
+
+use crate::auth::session::require_permission;
+use crate::errors::AppError;
+use crate::models::domain_event::{self, DomainEvent};
+
+#[derive(Serialize)]
+pub struct ApiEventPage {
+    pub events: Vec<DomainEvent>,
+    /// Pass as `after` to read the next page; unchanged when nothing is new.
+    pub next_after: i64,
+    pub has_more: bool,
+}
+
+/// GET /api/v1/events - Domain events in order, for integrations to follow.
+/// Query params: after (last event id seen, default 0), type (event type
+/// prefix, e.g. `proposal.` or `meeting.confirmed`), limit (default 100, max 500).
+pub async fn list(
+    pool: web::Data<PgPool>,
+    session: Session,
+    query: web::Query<std::collections::HashMap<String, String>>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "audit.view")?;
+
+    let after = query
+        .get("after")
+        .and_then(|a| a.parse::<i64>().ok())
+        .unwrap_or(0)
+        .max(0);
+    let limit = query
+        .get("limit")
+        .and_then(|l| l.parse::<i64>().ok())
+        .unwrap_or(100)
+        .clamp(1, domain_event::MAX_LIMIT);
+    let type_prefix = query.get("type").map(|t| t.trim()).filter(|t| !t.is_empty());
+
+    let events = domain_event::find_after(&pool, after, type_prefix, limit).await?;
+    let next_after = events.last().map(|e| e.id).unwrap_or(after);
+    let has_more = events.len() as i64 == limit;
+
+    Ok(HttpResponse::Ok().json(ApiEventPage { events, next_after, has_more }))
+}
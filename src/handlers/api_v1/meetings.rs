@@ -0,0 +1,74 @@
+use actix_session::Session;
+use actix_web::{web, HttpResponse};
+use sqlx::PgPool;
+
+use crate::auth::session::{get_user_id, require_permission};
+use crate::errors::AppError;
+use crate::export::offline;
+use crate::models::{meeting, tor};
+
+/// GET /api/v1/meetings/{id}/archive - The meeting as a schema-versioned JSON
+/// record (agenda, COAs, opinions, decisions, attendance, minutes) for
+/// record-keeping systems. Recorded as an export with its checksum.
+pub async fn archive(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "meetings.view")?;
+    let user_id = get_user_id(&session)
+        .ok_or(AppError::Session("User not logged in".to_string()))?;
+
+    let m = meeting::find_by_id(&pool, path.into_inner()).await?
+        .ok_or(AppError::NotFound)?;
+    tor::require_tor_membership(&pool, user_id, m.tor_id).await?;
+
+    let record = meeting::archive::build(&pool, &m).await?;
+    let bytes = serde_json::to_vec_pretty(&record)
+        .map_err(|e| AppError::Session(format!("Could not serialise meeting archive: {e}")))?;
+
+    let details = serde_json::json!({
+        "meeting_id": m.id,
+        "tor_id": m.tor_id,
+        "schema_version": meeting::archive::ARCHIVE_SCHEMA_VERSION,
+        "summary": format!("Meeting archive exported for '{}'", m.label)
+    });
+    let _ = crate::audit::log(&pool, user_id, "meeting.archive_exported", "meeting", m.id, details).await;
+
+    crate::handlers::export_handlers::checksummed_download(
+        &pool, user_id, "meeting_archive", &meeting::archive::file_name(&m), "application/json", "inline", bytes,
+    ).await
+}
+
+/// GET /api/v1/meetings/{id}/offline?have=sha256,... - Refresh an offline
+/// meeting pack: the current manifest plus the items whose hash is not in
+/// `have`. Without `have` every item is returned.
+pub async fn offline(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+    query: web::Query<std::collections::HashMap<String, String>>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "meetings.view")?;
+    let user_id = get_user_id(&session)
+        .ok_or(AppError::Session("User not logged in".to_string()))?;
+
+    let m = meeting::find_by_id(&pool, path.into_inner()).await?
+        .ok_or(AppError::NotFound)?;
+    tor::require_tor_membership(&pool, user_id, m.tor_id).await?;
+
+    let have = offline::parse_have(query.get("have").map(String::as_str).unwrap_or(""));
+    let items = offline::items(&pool, &m).await?;
+    let update = offline::update(&m, items, &have);
+
+    let details = serde_json::json!({
+        "meeting_id": m.id,
+        "tor_id": m.tor_id,
+        "changed": update.items.len(),
+        "unchanged": update.unchanged,
+        "summary": format!("Offline meeting pack refreshed for '{}' ({} changed)", m.label, update.items.len())
+    });
+    let _ = crate::audit::log(&pool, user_id, "meeting.offline_synced", "meeting", m.id, details).await;
+
+    Ok(HttpResponse::Ok().json(update))
+}
@@ -0,0 +1,258 @@
+//! Quick actions for mobile shells and PWAs: the caller's next meetings,
+//! decision items awaiting their opinion (submitted with one request), and
+//! warnings to acknowledge. Payloads carry only what a small screen
+//! shows; the full records stay with the other `/api/v1` endpoints.
+
+use actix_session::Session;
+use actix_web::{web, HttpResponse};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+
+use crate::auth::session::{get_permissions, get_user_id, get_username, require_permission};
+use crate::errors::AppError;
+use crate::handlers::warning_handlers::ws::{send_count_update, ConnectionMap};
+use crate::models::{agenda_point, coa, dashboard, opinion, tor};
+use crate::templates_structs::ApiErrorResponse;
+use crate::warnings::{self, notifications, queries};
+
+/// How far ahead "next meetings" looks.
+const MEETING_DAYS: i64 = 30;
+/// Most unacknowledged warnings returned.
+const WARNING_LIMIT: i64 = 20;
+
+#[derive(Serialize)]
+pub struct MobileMeeting {
+    /// Absent for a meeting the calendar projects but nobody has created yet.
+    pub id: Option<i64>,
+    pub tor_id: i64,
+    pub tor: String,
+    pub date: String,
+    pub time: String,
+    pub minutes: i64,
+    pub location: String,
+}
+
+#[derive(Serialize)]
+pub struct MobileCoa {
+    pub id: i64,
+    pub title: String,
+}
+
+#[derive(Serialize)]
+pub struct MobileOpinionItem {
+    pub agenda_point_id: i64,
+    pub title: String,
+    pub date: String,
+    pub tor_id: i64,
+    pub tor: String,
+    pub coas: Vec<MobileCoa>,
+}
+
+#[derive(Serialize)]
+pub struct MobileWarning {
+    pub id: i64,
+    pub severity: String,
+    pub message: String,
+    pub created_at: String,
+}
+
+/// Everything the home screen shows. Lists the caller lacks the
+/// permission for are left out.
+#[derive(Serialize)]
+pub struct MobileHome {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub meetings: Option<Vec<MobileMeeting>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub opinions: Option<Vec<MobileOpinionItem>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub warnings: Option<Vec<MobileWarning>>,
+}
+
+#[derive(Deserialize)]
+pub struct MobileOpinionRequest {
+    pub coa_id: i64,
+    #[serde(default)]
+    pub commentary: String,
+}
+
+fn user(session: &Session) -> Result<i64, AppError> {
+    get_user_id(session).ok_or_else(|| AppError::Session("User not logged in".to_string()))
+}
+
+fn can(session: &Session, code: &str) -> bool {
+    get_permissions(session).map(|p| p.has(code)).unwrap_or(false)
+}
+
+async fn next_meetings(pool: &PgPool, user_id: i64) -> Vec<MobileMeeting> {
+    dashboard::find_upcoming_meetings(pool, user_id, MEETING_DAYS).await
+        .into_iter()
+        .map(|m| MobileMeeting {
+            id: m.meeting_id,
+            tor_id: m.tor_id,
+            tor: m.tor_label,
+            date: m.date,
+            time: m.start_time,
+            minutes: m.duration_minutes,
+            location: m.location,
+        })
+        .collect()
+}
+
+async fn pending_opinions(pool: &PgPool, user_id: i64) -> Result<Vec<MobileOpinionItem>, AppError> {
+    let tor_ids = tor::find_tor_ids_for_user(pool, user_id).await;
+    let mut items = Vec::new();
+    for p in opinion::find_pending_for_user(pool, user_id, &tor_ids).await? {
+        let mut coas = coa::find_all_for_agenda_point(pool, p.agenda_point_id).await?;
+        coas.sort_by_key(|c| c.id);
+        items.push(MobileOpinionItem {
+            agenda_point_id: p.agenda_point_id,
+            title: p.title,
+            date: p.scheduled_date,
+            tor_id: p.tor_id,
+            tor: p.tor_label,
+            coas: coas.into_iter().map(|c| MobileCoa { id: c.id, title: c.title }).collect(),
+        });
+    }
+    Ok(items)
+}
+
+async fn open_warnings(pool: &PgPool, user_id: i64) -> Result<Vec<MobileWarning>, AppError> {
+    let page = queries::find_for_user(pool, user_id, 1, WARNING_LIMIT, None, None, false, false).await?;
+    Ok(page.items.into_iter()
+        .map(|w| MobileWarning { id: w.warning_id, severity: w.severity, message: w.message, created_at: w.created_at })
+        .collect())
+}
+
+/// GET /api/v1/mobile - Next meetings, pending opinions and warnings to
+/// acknowledge in one round trip.
+pub async fn home(
+    pool: web::Data<PgPool>,
+    session: Session,
+) -> Result<HttpResponse, AppError> {
+    let user_id = user(&session)?;
+    let meetings = if can(&session, "meetings.view") { Some(next_meetings(&pool, user_id).await) } else { None };
+    let opinions = if can(&session, "agenda.participate") { Some(pending_opinions(&pool, user_id).await?) } else { None };
+    let warnings = if can(&session, "warnings.view") { Some(open_warnings(&pool, user_id).await?) } else { None };
+    Ok(HttpResponse::Ok().json(MobileHome { meetings, opinions, warnings }))
+}
+
+/// GET /api/v1/mobile/meetings - The caller's meetings in the next 30 days.
+pub async fn meetings(
+    pool: web::Data<PgPool>,
+    session: Session,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "meetings.view")?;
+    let user_id = user(&session)?;
+    Ok(HttpResponse::Ok().json(next_meetings(&pool, user_id).await))
+}
+
+/// GET /api/v1/mobile/opinions - Open decision items without the caller's
+/// opinion, each with the courses of action to choose from.
+pub async fn opinions(
+    pool: web::Data<PgPool>,
+    session: Session,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "agenda.participate")?;
+    let user_id = user(&session)?;
+    Ok(HttpResponse::Ok().json(pending_opinions(&pool, user_id).await?))
+}
+
+/// POST /api/v1/mobile/opinions/{agenda_point_id} - Record the caller's
+/// opinion, or revise it if they already gave one.
+/// Body: `{"coa_id": 12, "commentary": "optional"}`.
+pub async fn submit_opinion(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+    body: web::Json<MobileOpinionRequest>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "agenda.participate")?;
+    let user_id = user(&session)?;
+    let agenda_point_id = path.into_inner();
+
+    let point = agenda_point::find_by_id(&pool, agenda_point_id).await?
+        .ok_or(AppError::NotFound)?;
+    tor::require_tor_membership(&pool, user_id, point.tor_id).await?;
+
+    let rejected = |error: &str| HttpResponse::UnprocessableEntity().json(ApiErrorResponse {
+        error: error.to_string(),
+        details: None,
+    });
+    if point.item_type != "decision" {
+        return Ok(rejected("Opinions can only be recorded on decision items"));
+    }
+    if opinion::opinions_locked(&point.status) {
+        return Ok(rejected("A decision has been recorded; opinions can no longer change"));
+    }
+    let coas = coa::find_all_for_agenda_point(&pool, agenda_point_id).await?;
+    if !coas.iter().any(|c| c.id == body.coa_id) {
+        return Ok(rejected("The course of action is not one considered for this item"));
+    }
+
+    let commentary = body.commentary.trim();
+    let existing = opinion::find_opinion_by_user_and_agenda_point(&pool, user_id, agenda_point_id).await?;
+    let opinion_id = match existing {
+        Some(id) => {
+            opinion::update_opinion(&pool, id, body.coa_id, commentary).await?;
+            id
+        }
+        None => opinion::record_opinion(&pool, agenda_point_id, user_id, body.coa_id, commentary).await?,
+    };
+
+    let details = serde_json::json!({
+        "agenda_point_id": agenda_point_id,
+        "preferred_coa_id": body.coa_id,
+        "commentary_length": commentary.len(),
+        "via": "mobile",
+        "summary": format!("Recorded opinion on agenda point #{} preferring COA #{}", agenda_point_id, body.coa_id)
+    });
+    let _ = crate::audit::log(&pool, user_id, "opinion.recorded", "opinion", opinion_id, details).await;
+
+    let author = get_username(&session).unwrap_or_default();
+    let _ = notifications::notify_mentions(
+        &pool, user_id, &author, commentary,
+        &format!("an opinion on '{}'", point.title),
+        &format!("/tor/{}/workflow/agenda/{agenda_point_id}", point.tor_id),
+    ).await;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "opinion_id": opinion_id,
+        "revised": existing.is_some(),
+    })))
+}
+
+/// GET /api/v1/mobile/warnings - The caller's unacknowledged warnings,
+/// newest first.
+pub async fn warnings(
+    pool: web::Data<PgPool>,
+    session: Session,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "warnings.view")?;
+    let user_id = user(&session)?;
+    Ok(HttpResponse::Ok().json(open_warnings(&pool, user_id).await?))
+}
+
+/// POST /api/v1/mobile/warnings/{id}/ack - Acknowledge a warning: the
+/// caller's receipt is marked read, as when opening it in the browser.
+pub async fn acknowledge_warning(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+    conn_map: web::Data<ConnectionMap>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "warnings.view")?;
+    let user_id = user(&session)?;
+    let warning_id = path.into_inner();
+
+    let receipt_id = queries::find_receipt_for_user(&pool, warning_id, user_id).await?
+        .ok_or(AppError::NotFound)?;
+    if matches!(queries::get_receipt_status(&pool, receipt_id).await?.as_str(), "unread" | "forwarded") {
+        warnings::update_receipt_status(&pool, receipt_id, "read", user_id).await?;
+        send_count_update(&conn_map, &pool, user_id).await;
+    }
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "id": warning_id,
+        "status": "read",
+    })))
+}
@@ -1,15 +1,85 @@
+pub mod audit;
 pub mod entities;
+pub mod events;
+pub mod meetings;
+pub mod mobile;
 pub mod proposals;
 pub mod tors;
 pub mod users;
 pub mod warnings;
 
+use actix_session::SessionExt;
 use actix_web::{
     web, Error, HttpResponse,
     body::MessageBody,
     dev::{ServiceRequest, ServiceResponse},
     middleware::Next,
 };
+use sqlx::PgPool;
+
+use crate::models::{api_usage, quota};
+
+/// Record request count, errors and latency per caller and endpoint.
+///
+/// The endpoint is the matched route pattern (`GET /api/v1/users/{id}`) so ids
+/// don't fragment the statistics. Recording happens off the request path.
+pub async fn track_usage(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    let started = std::time::Instant::now();
+    let caller = req.get_session().get::<String>("username").ok().flatten()
+        .unwrap_or_else(|| "anonymous".to_string());
+    let pool = req.app_data::<web::Data<PgPool>>().cloned();
+
+    let res = next.call(req).await?;
+
+    if let Some(pool) = pool {
+        let latency_ms = started.elapsed().as_millis() as i64;
+        let endpoint = format!(
+            "{} {}",
+            res.request().method(),
+            res.request().match_pattern().unwrap_or_else(|| res.request().path().to_string()),
+        );
+        let is_error = res.status().is_client_error() || res.status().is_server_error();
+        actix_web::rt::spawn(async move {
+            let now = chrono::Utc::now();
+            if let Err(e) = api_usage::record(&pool, &caller, &endpoint, now, is_error, latency_ms).await {
+                log::warn!("Failed to record API usage for {}: {}", endpoint, e);
+            }
+        });
+    }
+
+    Ok(res)
+}
+
+/// Reject requests beyond the caller's daily API quota with 429.
+///
+/// Wrapped inside [`track_usage`], so rejected requests are recorded too.
+/// Usage is recorded asynchronously, so a burst can overshoot the limit by
+/// the requests still in flight.
+pub async fn enforce_quota(
+    req: ServiceRequest,
+    next: Next<impl MessageBody + 'static>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    let session = req.get_session();
+    let user = session.get::<i64>("user_id").ok().flatten()
+        .zip(session.get::<String>("username").ok().flatten());
+    let pool = req.app_data::<web::Data<PgPool>>().cloned();
+
+    if let (Some((user_id, username)), Some(pool)) = (user, pool) {
+        match quota::check_api_requests(&pool, user_id, &username, crate::warnings::clock::now()).await {
+            Ok(Some(message)) => {
+                let response = HttpResponse::TooManyRequests().json(serde_json::json!({ "error": message }));
+                return Ok(req.into_response(response).map_into_right_body());
+            }
+            Ok(None) => {}
+            Err(e) => log::warn!("Failed to check API quota for {}: {}", username, e),
+        }
+    }
+
+    next.call(req).await.map(|res| res.map_into_left_body())
+}
 
 /// CSRF protection for REST API mutation endpoints.
 ///
@@ -17,7 +87,7 @@ use actix_web::{
 /// Browsers cannot send cross-origin JSON with cookies via simple form POST —
 /// the Content-Type check acts as a CSRF guard without requiring tokens.
 /// GET requests are exempt (read-only, no state changes).
-async fn require_json_content_type(
+pub async fn require_json_content_type(
     req: ServiceRequest,
     next: Next<impl MessageBody + 'static>,
 ) -> Result<ServiceResponse<impl MessageBody>, Error> {
@@ -70,18 +140,47 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
             .wrap(actix_web::middleware::from_fn(require_json_content_type))
             .route("/theme", web::post().to(users::update_theme))
     );
-    // Read-only domain endpoints (no CSRF middleware needed — GET only)
     cfg.service(
         web::scope("/tors")
+            .wrap(actix_web::middleware::from_fn(require_json_content_type))
             .route("", web::get().to(tors::list))
+            .route("", web::post().to(tors::create))
             .route("/{id}", web::get().to(tors::detail))
+            .route("/{id}", web::put().to(tors::update))
+            .route("/{id}/members", web::get().to(tors::members))
+            .route("/{id}/members", web::post().to(tors::assign_member))
+            .route("/{id}/members/{position_id}", web::delete().to(tors::vacate_member))
+    );
+    cfg.service(
+        web::scope("/mobile")
+            .wrap(actix_web::middleware::from_fn(require_json_content_type))
+            .route("", web::get().to(mobile::home))
+            .route("/meetings", web::get().to(mobile::meetings))
+            .route("/opinions", web::get().to(mobile::opinions))
+            .route("/opinions/{agenda_point_id}", web::post().to(mobile::submit_opinion))
+            .route("/warnings", web::get().to(mobile::warnings))
+            .route("/warnings/{id}/ack", web::post().to(mobile::acknowledge_warning))
     );
+    // Read-only domain endpoints (no CSRF middleware needed — GET only)
     cfg.service(
         web::scope("/proposals")
             .route("", web::get().to(proposals::list))
     );
+    cfg.service(
+        web::scope("/meetings")
+            .route("/{id}/archive", web::get().to(meetings::archive))
+            .route("/{id}/offline", web::get().to(meetings::offline))
+    );
     cfg.service(
         web::scope("/warnings")
             .route("", web::get().to(warnings::list))
     );
+    cfg.service(
+        web::scope("/events")
+            .route("", web::get().to(events::list))
+    );
+    cfg.service(
+        web::scope("/audit")
+            .route("", web::get().to(audit::list))
+    );
 }
@@ -3,10 +3,11 @@ use actix_web::{web, HttpResponse};
 use serde::Serialize;
 use sqlx::PgPool;
 
-use crate::auth::session::require_permission;
+use crate::auth::session::{get_user_id, require_permission};
 use crate::errors::AppError;
-use crate::models::tor;
-use crate::templates_structs::PaginatedResponse;
+use crate::models::{protocol, tor, user};
+use crate::models::tor::TorMember;
+use crate::templates_structs::{ApiErrorResponse, ApiTorMemberRequest, ApiTorRequest, PaginatedResponse};
 
 #[derive(Serialize)]
 pub struct ApiTorListItem {
@@ -32,7 +33,72 @@ pub struct ApiTorDetail {
     pub cadence_time: String,
     pub cadence_duration_minutes: String,
     pub default_location: String,
+    pub remote_url: String,
+    pub background_repo_url: String,
+    pub tor_number: String,
+    pub classification: String,
+    pub version: String,
+    pub organization: String,
+    pub focus_scope: String,
+    pub objectives: Vec<String>,
+    pub inputs_required: Vec<String>,
+    pub outputs_expected: Vec<String>,
+    pub poc_contact: String,
+    pub phase_scheduling: String,
+    pub info_platform: String,
+    pub invite_policy: String,
     pub member_count: i64,
+    pub members: Vec<ApiTorMember>,
+    pub positions: Vec<ApiTorPosition>,
+    pub protocol_steps: Vec<ApiProtocolStep>,
+}
+
+/// A position with its current holder, if any.
+#[derive(Serialize)]
+pub struct ApiTorMember {
+    pub position_id: i64,
+    pub position_name: String,
+    pub position_label: String,
+    pub membership_type: String,
+    pub holder_id: Option<i64>,
+    pub holder_name: Option<String>,
+    pub holder_label: Option<String>,
+}
+
+impl From<TorMember> for ApiTorMember {
+    fn from(m: TorMember) -> Self {
+        ApiTorMember {
+            position_id: m.position_id,
+            position_name: m.position_name,
+            position_label: m.position_label,
+            membership_type: m.membership_type,
+            holder_id: m.holder_id,
+            holder_name: m.holder_name,
+            holder_label: m.holder_label,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct ApiTorPosition {
+    pub id: i64,
+    pub name: String,
+    pub label: String,
+    pub category: String,
+    pub assigned_to: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct ApiProtocolStep {
+    pub id: i64,
+    pub name: String,
+    pub label: String,
+    pub step_type: String,
+    pub sequence_order: i64,
+    pub default_duration_minutes: Option<i64>,
+    pub description: String,
+    pub is_required: bool,
+    pub responsible: String,
 }
 
 /// GET /api/v1/tors - List Terms of Reference with optional status filter and pagination.
@@ -92,22 +158,45 @@ pub async fn list(
     }))
 }
 
-/// GET /api/v1/tors/{id} - Get ToR detail with member count.
-pub async fn detail(
-    pool: web::Data<PgPool>,
-    session: Session,
-    path: web::Path<i64>,
-) -> Result<HttpResponse, AppError> {
-    require_permission(&session, "tor.list")?;
-
-    let tor_id = path.into_inner();
-    let tor = tor::find_detail_by_id(&pool, tor_id)
-        .await?
-        .ok_or(AppError::NotFound)?;
-
-    let member_count = tor::count_members(&pool, tor_id).await.unwrap_or(0);
+/// Full ToR detail with members, positions and protocol steps.
+async fn load_detail(pool: &PgPool, tor_id: i64) -> Result<Option<ApiTorDetail>, AppError> {
+    let Some(tor) = tor::find_detail_by_id(pool, tor_id).await? else {
+        return Ok(None);
+    };
+    let member_count = tor::count_members(pool, tor_id).await.unwrap_or(0);
+    let members = tor::find_members(pool, tor_id).await?
+        .into_iter()
+        .map(ApiTorMember::from)
+        .collect();
+    let positions = tor::find_functions(pool, tor_id).await?
+        .into_iter()
+        .map(|f| ApiTorPosition {
+            id: f.id,
+            name: f.name,
+            label: f.label,
+            category: f.category,
+            assigned_to: f.assigned_to,
+        })
+        .collect();
+    let protocol_steps = protocol::find_steps_for_tor(pool, tor_id).await?
+        .into_iter()
+        .map(|p| ApiProtocolStep {
+            id: p.id,
+            name: p.name,
+            label: p.label,
+            step_type: p.step_type,
+            sequence_order: p.sequence_order,
+            default_duration_minutes: p.default_duration_minutes,
+            description: p.description,
+            is_required: p.is_required,
+            responsible: p.responsible,
+        })
+        .collect();
 
-    Ok(HttpResponse::Ok().json(ApiTorDetail {
+    Ok(Some(ApiTorDetail {
+        objectives: tor.objectives_list(),
+        inputs_required: tor.inputs_required_list(),
+        outputs_expected: tor.outputs_expected_list(),
         id: tor.id,
         name: tor.name,
         label: tor.label,
@@ -118,6 +207,221 @@ pub async fn detail(
         cadence_time: tor.cadence_time,
         cadence_duration_minutes: tor.cadence_duration_minutes,
         default_location: tor.default_location,
+        remote_url: tor.remote_url,
+        background_repo_url: tor.background_repo_url,
+        tor_number: tor.tor_number,
+        classification: tor.classification,
+        version: tor.version,
+        organization: tor.organization,
+        focus_scope: tor.focus_scope,
+        poc_contact: tor.poc_contact,
+        phase_scheduling: tor.phase_scheduling,
+        info_platform: tor.info_platform,
+        invite_policy: tor.invite_policy,
         member_count,
+        members,
+        positions,
+        protocol_steps,
     }))
 }
+
+fn validation_failed(errors: Vec<String>) -> HttpResponse {
+    HttpResponse::BadRequest().json(ApiErrorResponse {
+        error: "Validation failed".to_string(),
+        details: Some(errors.join("; ")),
+    })
+}
+
+fn is_unique_violation(e: &sqlx::Error) -> bool {
+    e.as_database_error().is_some_and(|d| d.is_unique_violation())
+}
+
+fn name_taken() -> HttpResponse {
+    HttpResponse::Conflict().json(ApiErrorResponse {
+        error: "A ToR with this name already exists".to_string(),
+        details: None,
+    })
+}
+
+/// GET /api/v1/tors/{id} - Get ToR detail with members, positions and protocol steps.
+pub async fn detail(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "tor.list")?;
+
+    let tor_id = path.into_inner();
+    let detail = load_detail(&pool, tor_id).await?.ok_or(AppError::NotFound)?;
+    Ok(HttpResponse::Ok().json(detail))
+}
+
+/// POST /api/v1/tors - Create a ToR
+pub async fn create(
+    pool: web::Data<PgPool>,
+    session: Session,
+    body: web::Json<ApiTorRequest>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "tor.create")?;
+
+    let errors = body.validate(true);
+    if !errors.is_empty() {
+        return Ok(validation_failed(errors));
+    }
+
+    let name = body.name.as_deref().unwrap_or("").trim();
+    let label = body.label.as_deref().unwrap_or("").trim();
+    let mut props = body.props();
+    for (key, default) in [("status", "active"), ("meeting_cadence", "ad-hoc"), ("cadence_duration_minutes", "60")] {
+        if !props.iter().any(|(k, _)| *k == key) {
+            props.push((key, default.to_string()));
+        }
+    }
+    let props: Vec<(&str, &str)> = props.iter().map(|(k, v)| (*k, v.as_str())).collect();
+
+    let tor_id = match tor::create(&pool, name, label, &props).await {
+        Ok(id) => id,
+        Err(e) if is_unique_violation(&e) => return Ok(name_taken()),
+        Err(e) => return Err(e.into()),
+    };
+
+    let current_user_id = get_user_id(&session).unwrap_or(0);
+    let details = serde_json::json!({
+        "tor_name": name,
+        "summary": format!("Created Terms of Reference '{}' via API", label)
+    });
+    let _ = crate::audit::log(&pool, current_user_id, "tor.created", "tor", tor_id, details).await;
+
+    let detail = load_detail(&pool, tor_id).await?.ok_or(AppError::NotFound)?;
+    Ok(HttpResponse::Created().json(detail))
+}
+
+/// PUT /api/v1/tors/{id} - Update a ToR; omitted fields are left unchanged
+pub async fn update(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+    body: web::Json<ApiTorRequest>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "tor.edit")?;
+
+    let tor_id = path.into_inner();
+    let existing = tor::find_detail_by_id(&pool, tor_id).await?.ok_or(AppError::NotFound)?;
+
+    let errors = body.validate(false);
+    if !errors.is_empty() {
+        return Ok(validation_failed(errors));
+    }
+
+    let name = body.name.as_deref().map(str::trim).unwrap_or(&existing.name);
+    let label = body.label.as_deref().map(str::trim).unwrap_or(&existing.label);
+    let props = body.props();
+    let props: Vec<(&str, &str)> = props.iter().map(|(k, v)| (*k, v.as_str())).collect();
+
+    match tor::update(&pool, tor_id, name, label, &props).await {
+        Ok(()) => {}
+        Err(e) if is_unique_violation(&e) => return Ok(name_taken()),
+        Err(e) => return Err(e.into()),
+    }
+
+    let current_user_id = get_user_id(&session).unwrap_or(0);
+    let details = serde_json::json!({
+        "tor_name": name,
+        "fields": props.iter().map(|(k, _)| *k).collect::<Vec<_>>(),
+        "summary": format!("Updated Terms of Reference '{}' via API", label)
+    });
+    let _ = crate::audit::log(&pool, current_user_id, "tor.updated", "tor", tor_id, details).await;
+
+    let detail = load_detail(&pool, tor_id).await?.ok_or(AppError::NotFound)?;
+    Ok(HttpResponse::Ok().json(detail))
+}
+
+/// GET /api/v1/tors/{id}/members - Positions with their current holders
+pub async fn members(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "tor.list")?;
+
+    let tor_id = path.into_inner();
+    tor::find_detail_by_id(&pool, tor_id).await?.ok_or(AppError::NotFound)?;
+    let members: Vec<ApiTorMember> = tor::find_members(&pool, tor_id).await?
+        .into_iter()
+        .map(ApiTorMember::from)
+        .collect();
+    Ok(HttpResponse::Ok().json(members))
+}
+
+/// POST /api/v1/tors/{id}/members - Assign a user to a position
+pub async fn assign_member(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+    body: web::Json<ApiTorMemberRequest>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "tor.manage_members")?;
+
+    let tor_id = path.into_inner();
+    tor::find_detail_by_id(&pool, tor_id).await?.ok_or(AppError::NotFound)?;
+
+    let mut errors = Vec::new();
+    if !["mandatory", "optional"].contains(&body.membership_type.as_str()) {
+        errors.push("Membership type must be mandatory or optional".to_string());
+    }
+    let positions = tor::find_members(&pool, tor_id).await?;
+    if !positions.iter().any(|p| p.position_id == body.position_id) {
+        errors.push(format!("Position {} is not part of this ToR", body.position_id));
+    }
+    if user::find_display_by_id(&pool, body.user_id).await?.is_none() {
+        errors.push(format!("User {} not found", body.user_id));
+    }
+    if !errors.is_empty() {
+        return Ok(validation_failed(errors));
+    }
+
+    tor::assign_to_position(&pool, body.user_id, body.position_id, &body.membership_type).await?;
+    // The membership type decides which capability defaults apply
+    tor::apply_capabilities(&pool, tor_id).await?;
+
+    let current_user_id = get_user_id(&session).unwrap_or(0);
+    let details = serde_json::json!({
+        "user_id": body.user_id,
+        "position_id": body.position_id,
+        "membership_type": body.membership_type,
+        "summary": "Assigned user to position via API"
+    });
+    let _ = crate::audit::log(&pool, current_user_id, "tor.position_assigned", "tor", tor_id, details).await;
+
+    let members: Vec<ApiTorMember> = tor::find_members(&pool, tor_id).await?
+        .into_iter()
+        .map(ApiTorMember::from)
+        .collect();
+    Ok(HttpResponse::Ok().json(members))
+}
+
+/// DELETE /api/v1/tors/{id}/members/{position_id} - Vacate a position
+pub async fn vacate_member(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<(i64, i64)>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "tor.manage_members")?;
+
+    let (tor_id, position_id) = path.into_inner();
+    let positions = tor::find_members(&pool, tor_id).await?;
+    if !positions.iter().any(|p| p.position_id == position_id) {
+        return Err(AppError::NotFound);
+    }
+
+    tor::vacate_position(&pool, position_id).await?;
+
+    let current_user_id = get_user_id(&session).unwrap_or(0);
+    let details = serde_json::json!({
+        "position_id": position_id,
+        "summary": "Vacated position via API"
+    });
+    let _ = crate::audit::log(&pool, current_user_id, "tor.position_vacated", "tor", tor_id, details).await;
+
+    Ok(HttpResponse::NoContent().finish())
+}
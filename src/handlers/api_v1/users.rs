@@ -5,6 +5,7 @@ use sqlx::PgPool;
 
 use crate::models::user;
 use crate::auth::{password, validate};
+use crate::auth::password_policy::PasswordPolicy;
 use crate::auth::session::{get_user_id, require_permission};
 use crate::errors::AppError;
 use crate::templates_structs::{
@@ -76,7 +77,7 @@ pub async fn create(
     let mut errors = Vec::new();
     errors.extend(validate::validate_username(&body.username));
     if let Some(pwd) = &body.password {
-        errors.extend(validate::validate_password(pwd));
+        errors.extend(PasswordPolicy::load(&pool).await.validate(&pool, pwd, &body.username, None).await?);
     } else {
         errors.push("Password required for user creation".to_string());
     }
@@ -144,7 +145,7 @@ pub async fn update(
     let mut errors = Vec::new();
     errors.extend(validate::validate_username(&body.username));
     if let Some(pwd) = &body.password {
-        errors.extend(validate::validate_password(pwd));
+        errors.extend(PasswordPolicy::load(&pool).await.validate(&pool, pwd, &body.username, Some(user_id)).await?);
     }
     errors.extend(validate::validate_email(&body.email));
     errors.extend(validate::validate_optional(&body.display_name, "Display name", 100));
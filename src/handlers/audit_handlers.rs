@@ -3,50 +3,120 @@ use actix_web::{web, HttpResponse};
 use serde::Deserialize;
 use sqlx::PgPool;
 
-use crate::models::audit;
-use crate::auth::session::require_permission;
+use crate::audit::{chain, export};
+use crate::models::audit::{self, AuditFilter};
+use crate::auth::session::{get_user_id, require_permission};
 use crate::errors::{AppError, render};
-use crate::templates_structs::{PageContext, AuditListTemplate};
+use crate::templates_structs::{PageContext, AuditListTemplate, AuditVerifyTemplate};
 
 #[derive(Deserialize)]
 pub struct AuditQuery {
     page: Option<i64>,
     per_page: Option<i64>,
-    q: Option<String>,
-    action: Option<String>,
-    target_type: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct ExportQuery {
+    format: Option<String>,
 }
 
 pub async fn list(
     pool: web::Data<PgPool>,
     session: Session,
     query: web::Query<AuditQuery>,
+    filter: web::Query<AuditFilter>,
 ) -> Result<HttpResponse, AppError> {
     require_permission(&session, "audit.view")?;
 
     let ctx = PageContext::build(&session, &pool, "/audit").await?;
     let page = query.page.unwrap_or(1);
     let per_page = query.per_page.unwrap_or(25);
-    let search = query.q.as_deref();
-    let action_filter = query.action.as_deref();
-    let target_type_filter = query.target_type.as_deref();
-
-    let audit_page = audit::find_paginated(
-        &pool,
-        page,
-        per_page,
-        search,
-        action_filter,
-        target_type_filter,
-    ).await?;
+    let filter = filter.into_inner();
+
+    let audit_page = audit::find_paginated(&pool, page, per_page, &filter).await?;
 
     let tmpl = AuditListTemplate {
         ctx,
         audit_page,
-        search_query: query.q.clone(),
-        action_filter: query.action.clone(),
-        target_type_filter: query.target_type.clone(),
+        filter,
     };
 
     render(tmpl)
 }
+
+/// GET /audit/export?format=csv|jsonl — the filtered audit trail from the
+/// database and the daily log files. Dates default to the last 30 days.
+pub async fn export(
+    pool: web::Data<PgPool>,
+    session: Session,
+    query: web::Query<ExportQuery>,
+    filter: web::Query<AuditFilter>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "audit.view")?;
+    let format = query.format.as_deref().unwrap_or("csv");
+    if !export::FORMATS.contains(&format) {
+        return Err(AppError::NotFound);
+    }
+    let (from, to) = match export::period(&filter, chrono::Utc::now().date_naive()) {
+        Ok(period) => period,
+        Err(message) => {
+            let _ = session.insert("flash", message);
+            return Ok(HttpResponse::SeeOther().insert_header(("Location", "/audit")).finish());
+        }
+    };
+
+    let user_id = get_user_id(&session).unwrap_or(0);
+    let watermark = crate::handlers::export_handlers::authorize_export(&pool, user_id, format).await?;
+    let records = export::collect(&pool, &filter, from, to).await
+        .map_err(|e| AppError::Session(format!("Could not read the audit log: {e}")))?;
+    let (content, content_type) = if format == "csv" {
+        let csv = export::to_csv(&records);
+        (match watermark { Some(mark) => mark.stamp_csv(csv), None => csv }, "text/csv; charset=utf-8")
+    } else {
+        let jsonl = export::to_jsonl(&records)
+            .map_err(|e| AppError::Session(format!("Could not export the audit log: {e}")))?;
+        (jsonl, "application/x-ndjson")
+    };
+    let file_name = export::file_name(from, to, format);
+
+    let details = serde_json::json!({
+        "from": from.to_string(),
+        "to": to.to_string(),
+        "format": format,
+        "records": records.len(),
+        "summary": format!("Audit log exported for {} to {} ({} records)", from, to, records.len())
+    });
+    let _ = crate::audit::log(&pool, user_id, "audit.exported", "audit", 0, details).await;
+
+    crate::handlers::export_handlers::checksummed_download(
+        &pool, user_id, "audit_export", &file_name, content_type, "attachment", content.into_bytes(),
+    ).await
+}
+
+/// GET /audit/verify — re-walk the hash chains of the database entries and
+/// the log files and report the first broken link in each.
+pub async fn verify(
+    pool: web::Data<PgPool>,
+    session: Session,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "audit.view")?;
+    let failed = |e: crate::audit::AuditError| AppError::Session(format!("Could not read the audit log: {e}"));
+    let database = chain::verify_database(&pool).await.map_err(failed)?;
+    let files = chain::verify_files(&pool).await.map_err(failed)?;
+
+    let user_id = get_user_id(&session).unwrap_or(0);
+    let details = serde_json::json!({
+        "database": database,
+        "files": files,
+        "summary": format!(
+            "Audit chains verified: database {}, log files {}",
+            if database.is_intact() { "intact" } else { "broken" },
+            if files.is_intact() { "intact" } else { "broken" },
+        )
+    });
+    let _ = crate::audit::log(&pool, user_id, "audit.verified", "audit", 0, details).await;
+
+    let ctx = PageContext::build(&session, &pool, "/audit").await?;
+    let checked_at = chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    render(AuditVerifyTemplate { ctx, checked_at, database, files })
+}
@@ -3,8 +3,14 @@ use actix_web::{web, HttpRequest, HttpResponse};
 use serde::Deserialize;
 use sqlx::PgPool;
 
-use crate::models::{user, permission, setting};
-use crate::auth::{csrf, password, rate_limit::RateLimiter};
+use crate::models::{branding, user, setting, remember_token};
+use crate::auth::{csrf, password, rate_limit::RateLimiter, redirect, remember, timeout::SessionTracker};
+use crate::auth::oidc::{self, OidcConfig, PendingLogin, Resolution};
+use crate::auth::network::{self, Scope};
+use crate::auth::lockout;
+use crate::auth::new_device;
+use crate::auth::password_policy::{self, PasswordPolicy};
+use crate::auth::session::{get_user_id, sign_in};
 use crate::errors::{AppError, render};
 use crate::templates_structs::LoginTemplate;
 
@@ -13,6 +19,17 @@ pub struct LoginForm {
     pub username: String,
     pub password: String,
     pub csrf_token: String,
+    #[serde(default)]
+    pub next: String,
+    /// "Keep me signed in" checkbox — present only when ticked.
+    pub keep_signed_in: Option<String>,
+    /// "Remember this device" checkbox — issues a persistent login token.
+    pub remember_device: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct LoginQuery {
+    pub next: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -20,22 +37,66 @@ pub struct CsrfOnly {
     pub csrf_token: String,
 }
 
+/// Where to go after login: a safe `next` path, else the session's landing page.
+fn post_login_location(session: &Session, next: &str) -> String {
+    redirect::safe_path(next)
+        .map(|p| p.to_string())
+        .or_else(|| session.get::<String>("landing_page").unwrap_or(None))
+        .unwrap_or_else(|| redirect::DEFAULT_LANDING.to_string())
+}
+
+/// Render the login page, with the single sign-on button when configured.
+pub(crate) async fn render_login(pool: &PgPool, session: &Session, error: Option<String>, next: String) -> Result<HttpResponse, AppError> {
+    render(LoginTemplate {
+        error,
+        app_name: setting::get_value(pool, "app.name", "Ahlt").await,
+        csrf_token: csrf::get_or_create_token(session),
+        next,
+        branding: branding::load_for_page(pool, "portal").await,
+        sso_label: OidcConfig::load(pool).await.map(|c| c.button_label),
+    })
+}
+
 pub async fn login_page(
     pool: web::Data<PgPool>,
     session: Session,
+    query: web::Query<LoginQuery>,
 ) -> Result<HttpResponse, AppError> {
-    // If already logged in, redirect to dashboard
+    let next = query.into_inner().next
+        .and_then(|n| redirect::safe_path(&n).map(|p| p.to_string()))
+        .unwrap_or_default();
+
+    // If already logged in, go straight to the destination
     if session.get::<i64>("user_id").unwrap_or(None).is_some() {
         return Ok(HttpResponse::SeeOther()
-            .insert_header(("Location", "/dashboard"))
+            .insert_header(("Location", post_login_location(&session, &next)))
             .finish());
     }
 
-    let app_name = setting::get_value(&pool, "app.name", "Ahlt").await;
+    render_login(&pool, &session, None, next).await
+}
 
-    let csrf_token = csrf::get_or_create_token(&session);
-    let tmpl = LoginTemplate { error: None, app_name, csrf_token };
-    render(tmpl)
+/// GET / — send signed-in users to their landing page, everyone else to login.
+pub async fn root_redirect(session: Session) -> HttpResponse {
+    let location = if session.get::<i64>("user_id").unwrap_or(None).is_some() {
+        post_login_location(&session, "")
+    } else {
+        "/login".to_string()
+    };
+    HttpResponse::SeeOther()
+        .insert_header(("Location", location))
+        .finish()
+}
+
+/// Shown instead of signing in to a locked account.
+const LOCKED: &str = "This account is locked. Contact an administrator to unlock it.";
+
+/// Readable label of the browser and system a request came from.
+fn device_label(req: &HttpRequest) -> String {
+    req.headers().get(actix_web::http::header::USER_AGENT)
+        .and_then(|v| v.to_str().ok())
+        .map(remember::device_label)
+        .unwrap_or_else(|| "Unknown device".to_string())
 }
 
 pub async fn login_submit(
@@ -53,18 +114,10 @@ pub async fn login_submit(
         .unwrap_or_else(|| std::net::IpAddr::V4(std::net::Ipv4Addr::UNSPECIFIED));
 
     if limiter.is_blocked(ip) {
-        let app_name = setting::get_value(&pool, "app.name", "Ahlt").await;
-        let csrf_token = csrf::get_or_create_token(&session);
-        let tmpl = LoginTemplate {
-            error: Some("Too many failed login attempts. Please try again later.".to_string()),
-            app_name,
-            csrf_token,
-        };
-        return render(tmpl);
+        let error = "Too many failed login attempts. Please try again later.".to_string();
+        return render_login(&pool, &session, Some(error), form.next.clone()).await;
     }
 
-    let app_name = setting::get_value(&pool, "app.name", "Ahlt").await;
-
     // Look up user
     let found = user::find_by_username(&pool, &form.username).await?;
 
@@ -72,52 +125,230 @@ pub async fn login_submit(
         Some(u) => {
             match password::verify_password(&form.password, &u.password) {
                 Ok(true) => {
+                    // Only the right password learns the account is locked, so
+                    // the message does not give away which usernames exist.
+                    if user::is_locked(&pool, u.id).await? {
+                        limiter.record_failure(ip);
+                        return render_login(&pool, &session, Some(LOCKED.to_string()), form.next.clone()).await;
+                    }
+                    if !network::check(&pool, ip, u.id, Scope::Login).await? {
+                        network::report_blocked(&pool, ip, u.id, &u.username, Scope::Login).await;
+                        return Ok(network::blocked_response(Scope::Login));
+                    }
+
                     // Successful login — clear rate limit for this IP
                     limiter.clear(ip);
+                    lockout::clear(&pool, u.id).await?;
 
-                    // Multi-role: aggregate permissions across all assigned roles
-                    let perms = permission::find_codes_by_user_id(&pool, u.id).await?;
-                    let perms_csv = perms.join(",");
+                    sign_in(&session, &pool, u.id, &u.username, form.keep_signed_in.is_some()).await?;
+                    if PasswordPolicy::load(&pool).await.is_expired(&pool, u.id).await? {
+                        let _ = session.insert(password_policy::EXPIRED_KEY, true);
+                    }
+                    let device = device_label(&req);
+                    new_device::notice(&pool, u.id, &u.username, &device, ip).await;
 
-                    let _ = session.insert("user_id", u.id);
-                    let _ = session.insert("username", &u.username);
-                    let _ = session.insert("permissions", &perms_csv);
-                    Ok(HttpResponse::SeeOther()
-                        .insert_header(("Location", "/dashboard"))
+                    let mut response = HttpResponse::SeeOther();
+                    if form.remember_device.is_some() {
+                        let lifetime = remember::lifetime(&pool).await;
+                        let token = remember_token::issue(&pool, u.id, &device, lifetime, None).await?;
+                        response.cookie(remember::cookie(token, lifetime, remember::is_secure(&req)));
+                    }
+
+                    Ok(response
+                        .insert_header(("Location", post_login_location(&session, &form.next)))
                         .finish())
                 }
                 _ => {
+                    // Locked accounts are not counted again, see lockout::record_failure
                     limiter.record_failure(ip);
-                    let csrf_token = csrf::get_or_create_token(&session);
-                    let tmpl = LoginTemplate {
-                        error: Some("Invalid username or password".to_string()),
-                        app_name,
-                        csrf_token,
-                    };
-                    render(tmpl)
+                    lockout::record_failure(&pool, u.id, &u.username, ip).await?;
+                    let error = "Invalid username or password".to_string();
+                    render_login(&pool, &session, Some(error), form.next.clone()).await
                 }
             }
         }
         None => {
             limiter.record_failure(ip);
-            let csrf_token = csrf::get_or_create_token(&session);
-            let tmpl = LoginTemplate {
-                error: Some("Invalid username or password".to_string()),
-                app_name,
-                csrf_token,
-            };
-            render(tmpl)
+            let error = "Invalid username or password".to_string();
+            render_login(&pool, &session, Some(error), form.next.clone()).await
         }
     }
 }
 
+/// Store the flow state and send the browser to the identity provider.
+pub async fn start_oidc(
+    req: &HttpRequest,
+    pool: &PgPool,
+    session: &Session,
+    config: &OidcConfig,
+    pending: PendingLogin,
+) -> Result<HttpResponse, AppError> {
+    let metadata = match oidc::discover(&config.issuer).await {
+        Ok(m) => m,
+        Err(e) => {
+            log::error!("OIDC discovery failed: {}", e);
+            let error = "Single sign-on is unavailable right now. Please try again later.".to_string();
+            return render_login(pool, session, Some(error), pending.next).await;
+        }
+    };
+    let info = req.connection_info();
+    let redirect_uri = config.redirect_uri(info.scheme(), info.host());
+    let location = oidc::authorization_url(&metadata, config, &redirect_uri, &pending);
+    session.insert(oidc::SESSION_KEY, &pending)
+        .map_err(|e| AppError::Session(e.to_string()))?;
+    Ok(HttpResponse::SeeOther()
+        .insert_header(("Location", location))
+        .finish())
+}
+
+/// GET /login/oidc — sign in through the configured identity provider.
+pub async fn login_oidc(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    session: Session,
+    query: web::Query<LoginQuery>,
+) -> Result<HttpResponse, AppError> {
+    let config = OidcConfig::load(&pool).await.ok_or(AppError::NotFound)?;
+    let next = query.into_inner().next
+        .and_then(|n| redirect::safe_path(&n).map(|p| p.to_string()))
+        .unwrap_or_default();
+    start_oidc(&req, &pool, &session, &config, PendingLogin::new(&next, None)).await
+}
+
+#[derive(Deserialize)]
+pub struct OidcCallbackQuery {
+    pub code: Option<String>,
+    pub state: Option<String>,
+    pub error: Option<String>,
+}
+
+/// GET /login/oidc/callback — finish sign-in (or account linking) after the
+/// provider redirects back.
+pub async fn oidc_callback(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    session: Session,
+    query: web::Query<OidcCallbackQuery>,
+) -> Result<HttpResponse, AppError> {
+    let config = OidcConfig::load(&pool).await.ok_or(AppError::NotFound)?;
+    let pending = session.get::<PendingLogin>(oidc::SESSION_KEY).unwrap_or(None);
+    session.remove(oidc::SESSION_KEY);
+
+    let Some(pending) = pending.filter(|p| query.state.as_deref() == Some(p.state.as_str())) else {
+        let error = "Single sign-on session expired. Please try again.".to_string();
+        return render_login(&pool, &session, Some(error), String::new()).await;
+    };
+    let next = pending.next.clone();
+    let code = match (&query.error, &query.code) {
+        (None, Some(code)) => code.clone(),
+        (error, _) => {
+            log::warn!("OIDC provider refused sign-in: {}", error.as_deref().unwrap_or("no code"));
+            let error = "Sign-in was cancelled or refused by the identity provider.".to_string();
+            return render_login(&pool, &session, Some(error), next).await;
+        }
+    };
+
+    let info = req.connection_info().clone();
+    let redirect_uri = config.redirect_uri(info.scheme(), info.host());
+    let claims = match oidc::discover(&config.issuer).await {
+        Ok(metadata) => oidc::exchange_code(&metadata, &config, &redirect_uri, &code, &pending.verifier).await,
+        Err(e) => Err(e),
+    }
+    .and_then(|id_token| oidc::validate_id_token(
+        &id_token, &config.issuer, &config.client_id, &pending.nonce, chrono::Utc::now().timestamp(),
+    ));
+    let claims = match claims {
+        Ok(c) => c,
+        Err(e) => {
+            log::error!("OIDC sign-in failed: {}", e);
+            let error = "Single sign-on failed. Please try again or sign in with your password.".to_string();
+            return render_login(&pool, &session, Some(error), next).await;
+        }
+    };
+
+    // Linking from the account page: only for the user who started it
+    if let Some(link_user_id) = pending.link_user_id {
+        if get_user_id(&session) != Some(link_user_id) {
+            return render_login(&pool, &session, Some("Please sign in again.".to_string()), next).await;
+        }
+        let message = if oidc::link(&pool, &config, link_user_id, &claims.sub).await? {
+            let details = serde_json::json!({ "issuer": config.issuer, "summary": "Linked single sign-on identity" });
+            let _ = crate::audit::log(&pool, link_user_id, "user.sso_linked", "user", link_user_id, details).await;
+            "Single sign-on linked to your account"
+        } else {
+            "That identity is already linked to another account"
+        };
+        let _ = session.insert("flash", message);
+        return Ok(HttpResponse::SeeOther()
+            .insert_header(("Location", "/account#security"))
+            .finish());
+    }
+
+    let (user_id, username) = match oidc::resolve_user(&pool, &config, &claims).await? {
+        Resolution::Linked(id, username) => (id, username),
+        Resolution::Provisioned(id, username) => {
+            let details = serde_json::json!({
+                "username": username,
+                "role": config.default_role,
+                "summary": format!("Created account '{}' on first single sign-on", username)
+            });
+            let _ = crate::audit::log(&pool, id, "user.sso_provisioned", "user", id, details).await;
+            (id, username)
+        }
+        Resolution::EmailInUse => {
+            let error = "An account with this e-mail address already exists. Sign in with your password \
+                         and link single sign-on from your account page.".to_string();
+            return render_login(&pool, &session, Some(error), next).await;
+        }
+        Resolution::Unknown => {
+            let error = "No account is linked to this identity. Ask an administrator for access.".to_string();
+            return render_login(&pool, &session, Some(error), next).await;
+        }
+    };
+
+    if user::is_locked(&pool, user_id).await? {
+        return render_login(&pool, &session, Some(LOCKED.to_string()), next).await;
+    }
+    if let Some(addr) = req.peer_addr()
+        && !network::check(&pool, addr.ip(), user_id, Scope::Login).await?
+    {
+        network::report_blocked(&pool, addr.ip(), user_id, &username, Scope::Login).await;
+        return Ok(network::blocked_response(Scope::Login));
+    }
+
+    sign_in(&session, &pool, user_id, &username, false).await?;
+    if let Some(addr) = req.peer_addr() {
+        new_device::notice(&pool, user_id, &username, &device_label(&req), addr.ip()).await;
+    }
+    Ok(HttpResponse::SeeOther()
+        .insert_header(("Location", post_login_location(&session, &next)))
+        .finish())
+}
+
 pub async fn logout(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
     session: Session,
     form: web::Form<CsrfOnly>,
+    tracker: web::Data<SessionTracker>,
 ) -> Result<HttpResponse, AppError> {
     csrf::validate_csrf(&session, &form.csrf_token)?;
+    if let Some(user_id) = session.get::<i64>("user_id").unwrap_or(None) {
+        tracker.forget(user_id);
+    }
+    // Forget this device too, so logging out actually signs it out
+    if let Some(cookie) = req.cookie(remember::COOKIE_NAME) {
+        remember_token::revoke_raw(&pool, cookie.value()).await?;
+    }
     session.purge();
     Ok(HttpResponse::SeeOther()
+        .cookie(remember::removal_cookie())
         .insert_header(("Location", "/login"))
         .finish())
 }
+
+/// GET /session/keepalive — no-op request that lets `require_auth` refresh the
+/// idle timer when the user chooses to stay signed in.
+pub async fn keepalive() -> HttpResponse {
+    HttpResponse::NoContent().finish()
+}
@@ -0,0 +1,297 @@
+use std::collections::HashMap;
+
+use actix_session::Session;
+use actix_web::{web, HttpResponse};
+use sqlx::PgPool;
+
+use crate::auth::csrf;
+use crate::auth::session::{get_user_id, require_permission};
+use crate::errors::{AppError, render};
+use crate::handlers::auth_handlers::CsrfOnly;
+use crate::handlers::opinion_handlers::require_decision_authority;
+use crate::models::{agenda_point, ballot, coa, opinion, tor};
+use crate::templates_structs::{BallotTemplate, PageContext};
+
+fn redirect(tor_id: i64, agenda_point_id: i64) -> HttpResponse {
+    HttpResponse::SeeOther()
+        .insert_header(("Location", format!("/tor/{tor_id}/workflow/agenda/{agenda_point_id}/vote")))
+        .finish()
+}
+
+async fn load_coas(pool: &PgPool, agenda_point_id: i64) -> Result<Vec<coa::CoaDetail>, AppError> {
+    let mut coas = Vec::new();
+    for c in coa::find_all_for_agenda_point(pool, agenda_point_id).await? {
+        if let Ok(detail) = coa::find_by_id(pool, c.id).await {
+            coas.push(detail);
+        }
+    }
+    Ok(coas)
+}
+
+/// The decision agenda point at this path, checked to belong to the ToR.
+async fn find_decision_point(pool: &PgPool, tor_id: i64, agenda_point_id: i64) -> Result<agenda_point::AgendaPointDetail, AppError> {
+    let point = agenda_point::find_by_id(pool, agenda_point_id).await?
+        .filter(|ap| ap.tor_id == tor_id)
+        .ok_or(AppError::NotFound)?;
+    if point.item_type != "decision" {
+        return Err(AppError::PermissionDenied("Only decision agenda points can be voted on".to_string()));
+    }
+    Ok(point)
+}
+
+/// ToR members, and anyone acting for a member under an active delegation,
+/// may see the ballot.
+async fn require_ballot_access(pool: &PgPool, user_id: i64, tor_id: i64) -> Result<(), AppError> {
+    match tor::require_tor_membership(pool, user_id, tor_id).await {
+        Ok(()) => Ok(()),
+        Err(e) => {
+            let today = chrono::Local::now().date_naive();
+            if tor::find_active_delegations(pool, user_id, tor_id, today).await?.is_empty() {
+                Err(e)
+            } else {
+                Ok(())
+            }
+        }
+    }
+}
+
+/// The eligible voters the user may cast a proxy vote for: the delegators of
+/// the user's active delegations in the ToR.
+async fn proxy_principals(
+    pool: &PgPool,
+    user_id: i64,
+    tor_id: i64,
+    open: &ballot::Ballot,
+) -> Result<Vec<(i64, String)>, AppError> {
+    let today = chrono::Local::now().date_naive();
+    let mut principals: Vec<(i64, String)> = tor::find_active_delegations(pool, user_id, tor_id, today).await?
+        .into_iter()
+        .filter(|d| d.delegator_id != user_id && open.is_eligible(d.delegator_id))
+        .map(|d| (d.delegator_id, d.delegator_name))
+        .collect();
+    principals.sort();
+    principals.dedup_by_key(|(id, _)| *id);
+    Ok(principals)
+}
+
+async fn render_page(
+    pool: &PgPool,
+    session: &Session,
+    tor_id: i64,
+    agenda_point: agenda_point::AgendaPointDetail,
+    errors: Vec<String>,
+) -> Result<HttpResponse, AppError> {
+    let user_id = get_user_id(session).unwrap_or(0);
+    let can_manage = require_decision_authority(pool, session, tor_id, user_id).await.is_ok();
+    let coas = load_coas(pool, agenda_point.id).await?;
+    let mut ballots = ballot::find_for_agenda_point(pool, agenda_point.id).await?;
+    let current = ballots.first().filter(|b| b.is_open()).cloned();
+    if current.is_some() {
+        ballots.remove(0);
+    }
+    let (votes_cast, my_choice) = match &current {
+        Some(b) => {
+            let votes = ballot::find_votes(pool, b).await?;
+            let mine = votes.iter().find(|(voter, _)| *voter == user_id).map(|(_, choice)| choice.clone());
+            (votes.len(), mine)
+        }
+        None => (0, None),
+    };
+    let proxy_for = match &current {
+        Some(b) => proxy_principals(pool, user_id, tor_id, b).await?,
+        None => Vec::new(),
+    };
+
+    let tor_name = tor::get_tor_name(pool, tor_id).await?;
+    let ctx = PageContext::build(session, pool, "/workflow").await?
+        .with_tor(tor_id, &tor_name, "workflow");
+    render(BallotTemplate {
+        ctx,
+        tor_id,
+        agenda_point,
+        coas,
+        current,
+        closed: ballots,
+        votes_cast,
+        my_choice,
+        proxy_for,
+        user_id,
+        can_manage,
+        default_quorum_pct: ballot::default_quorum_pct(pool).await,
+        errors,
+    })
+}
+
+/// GET /tor/{id}/workflow/agenda/{aid}/vote — the ballot on an agenda point.
+pub async fn view(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<(i64, i64)>,
+) -> Result<HttpResponse, AppError> {
+    let (tor_id, agenda_point_id) = path.into_inner();
+    let user_id = get_user_id(&session).ok_or(AppError::Session("User not logged in".to_string()))?;
+    require_ballot_access(&pool, user_id, tor_id).await?;
+    let agenda_point = find_decision_point(&pool, tor_id, agenda_point_id).await?;
+    render_page(&pool, &session, tor_id, agenda_point, Vec::new()).await
+}
+
+/// POST /tor/{id}/workflow/agenda/{aid}/vote — cast or change a vote on the
+/// open ballot, either for the user or, with `on_behalf_of`, as proxy for a
+/// member who delegated their position to the user.
+pub async fn cast(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<(i64, i64)>,
+    form: web::Form<HashMap<String, String>>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "agenda.participate")?;
+    csrf::validate_csrf(&session, form.get("csrf_token").map(String::as_str).unwrap_or(""))?;
+    let (tor_id, agenda_point_id) = path.into_inner();
+    let user_id = get_user_id(&session).ok_or(AppError::Session("User not logged in".to_string()))?;
+    require_ballot_access(&pool, user_id, tor_id).await?;
+    let agenda_point = find_decision_point(&pool, tor_id, agenda_point_id).await?;
+
+    let open = ballot::find_for_agenda_point(&pool, agenda_point_id).await?
+        .into_iter()
+        .find(|b| b.is_open())
+        .ok_or_else(|| AppError::PermissionDenied("There is no open ballot on this agenda point".to_string()))?;
+    let voter_id: i64 = form.get("on_behalf_of").and_then(|v| v.parse().ok()).unwrap_or(user_id);
+    let principal = if voter_id == user_id {
+        None
+    } else {
+        let principal = proxy_principals(&pool, user_id, tor_id, &open).await?
+            .into_iter()
+            .find(|(id, _)| *id == voter_id)
+            .ok_or_else(|| AppError::PermissionDenied(
+                "You do not hold an active delegation from this member".to_string(),
+            ))?;
+        Some(principal)
+    };
+    if !open.is_eligible(voter_id) {
+        return Err(AppError::PermissionDenied(
+            "Only members who held a position when the ballot opened may vote".to_string(),
+        ));
+    }
+
+    let coa_ids: Vec<i64> = load_coas(&pool, agenda_point_id).await?.iter().map(|c| c.id).collect();
+    let choice = match ballot::parse_choice(&open.ballot_type, &form, &coa_ids) {
+        Ok(choice) => choice,
+        Err(e) => return render_page(&pool, &session, tor_id, agenda_point, vec![e]).await,
+    };
+    ballot::cast(&pool, open.id, voter_id, &choice).await?;
+
+    // The choice itself stays out of the audit log
+    let details = match &principal {
+        Some((principal_id, principal_name)) => serde_json::json!({
+            "agenda_point_id": agenda_point_id,
+            "on_behalf_of": principal_id,
+            "summary": format!("Voted on the ballot for '{}' as proxy for {}", agenda_point.title, principal_name)
+        }),
+        None => serde_json::json!({
+            "agenda_point_id": agenda_point_id,
+            "summary": format!("Voted on the ballot for '{}'", agenda_point.title)
+        }),
+    };
+    let _ = crate::audit::log(&pool, user_id, "ballot.vote_cast", "ballot", open.id, details).await;
+
+    let flash = match &principal {
+        Some((_, principal_name)) => format!("Your proxy vote for {} has been recorded", principal_name),
+        None => "Your vote has been recorded".to_string(),
+    };
+    let _ = session.insert("flash", flash);
+    Ok(redirect(tor_id, agenda_point_id))
+}
+
+/// POST /tor/{id}/workflow/agenda/{aid}/vote/open — open a ballot (decision authority only).
+pub async fn open(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<(i64, i64)>,
+    form: web::Form<HashMap<String, String>>,
+) -> Result<HttpResponse, AppError> {
+    csrf::validate_csrf(&session, form.get("csrf_token").map(String::as_str).unwrap_or(""))?;
+    let (tor_id, agenda_point_id) = path.into_inner();
+    let user_id = get_user_id(&session).ok_or(AppError::Session("User not logged in".to_string()))?;
+    require_decision_authority(&pool, &session, tor_id, user_id).await?;
+    let agenda_point = find_decision_point(&pool, tor_id, agenda_point_id).await?;
+
+    let ballot_type = form.get("ballot_type").map(|v| v.trim()).unwrap_or("");
+    let motion_coa_id: i64 = form.get("motion_coa_id").and_then(|v| v.parse().ok()).unwrap_or(0);
+    let quorum_pct: i64 = form.get("quorum_pct").and_then(|v| v.trim().parse().ok()).unwrap_or(0);
+    let eligible = ballot::eligible_voters(&pool, tor_id).await?;
+    let coa_ids: Vec<i64> = load_coas(&pool, agenda_point_id).await?.iter().map(|c| c.id).collect();
+
+    let mut errors = ballot::validate_open(ballot_type, motion_coa_id, quorum_pct, &coa_ids, eligible.len());
+    if opinion::opinions_locked(&agenda_point.status) {
+        errors.push("A decision has already been recorded for this agenda point".to_string());
+    }
+    if ballot::find_for_agenda_point(&pool, agenda_point_id).await?.iter().any(|b| b.is_open()) {
+        errors.push("A ballot is already open on this agenda point".to_string());
+    }
+    if !errors.is_empty() {
+        return render_page(&pool, &session, tor_id, agenda_point, errors).await;
+    }
+
+    let motion_coa_id = if ballot_type == "yes_no" { motion_coa_id } else { 0 };
+    let ballot_id = ballot::open(&pool, agenda_point_id, ballot_type, motion_coa_id, quorum_pct, &eligible, user_id).await?;
+
+    let details = serde_json::json!({
+        "agenda_point_id": agenda_point_id,
+        "ballot_type": ballot_type,
+        "quorum_pct": quorum_pct,
+        "eligible": eligible.len(),
+        "summary": format!("Opened a ballot on '{}'", agenda_point.title)
+    });
+    let _ = crate::audit::log(&pool, user_id, "ballot.opened", "ballot", ballot_id, details).await;
+
+    let _ = session.insert("flash", "Ballot opened");
+    Ok(redirect(tor_id, agenda_point_id))
+}
+
+/// POST /tor/{id}/workflow/agenda/{aid}/vote/close — close the open ballot
+/// and record the decision it carries (decision authority only).
+pub async fn close(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<(i64, i64)>,
+    form: web::Form<CsrfOnly>,
+) -> Result<HttpResponse, AppError> {
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+    let (tor_id, agenda_point_id) = path.into_inner();
+    let user_id = get_user_id(&session).ok_or(AppError::Session("User not logged in".to_string()))?;
+    let (_, on_behalf_of) = require_decision_authority(&pool, &session, tor_id, user_id).await?;
+    let agenda_point = find_decision_point(&pool, tor_id, agenda_point_id).await?;
+
+    let open = ballot::find_for_agenda_point(&pool, agenda_point_id).await?
+        .into_iter()
+        .find(|b| b.is_open())
+        .ok_or_else(|| AppError::PermissionDenied("There is no open ballot on this agenda point".to_string()))?;
+    let coas: Vec<(i64, String)> = load_coas(&pool, agenda_point_id).await?
+        .into_iter()
+        .map(|c| (c.id, c.title))
+        .collect();
+    let (tally, decision_id) = ballot::close(&pool, &open, &coas, user_id).await?;
+
+    if let (Some(decision_id), Some(principal)) = (decision_id, &on_behalf_of) {
+        opinion::record_on_behalf_of(&pool, decision_id, agenda_point_id, principal.user_id).await?;
+    }
+
+    let details = serde_json::json!({
+        "agenda_point_id": agenda_point_id,
+        "cast": tally.cast,
+        "eligible": tally.eligible,
+        "quorum_met": tally.quorum_met(),
+        "winner_coa_id": tally.winner,
+        "decision_id": decision_id,
+        "summary": format!("Closed the ballot on '{}': {}", agenda_point.title, tally.summary)
+    });
+    let _ = crate::audit::log(&pool, user_id, "ballot.closed", "ballot", open.id, details).await;
+
+    let flash = if decision_id.is_some() {
+        "Ballot closed and the decision recorded".to_string()
+    } else {
+        format!("Ballot closed without a decision. {}", tally.summary)
+    };
+    let _ = session.insert("flash", flash);
+    Ok(redirect(tor_id, agenda_point_id))
+}
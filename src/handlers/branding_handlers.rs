@@ -0,0 +1,43 @@
+use actix_web::{web, HttpRequest, HttpResponse};
+use serde::Deserialize;
+use sqlx::PgPool;
+
+use crate::errors::AppError;
+use crate::models::branding;
+
+#[derive(Deserialize)]
+pub struct LogoQuery {
+    #[serde(default)]
+    pub v: String,
+}
+
+/// GET /branding/logo/{surface}?v=<tag> — the published logo of a surface.
+/// Pages link it with the logo's current tag, so a request carrying that tag
+/// may be cached for good; any other is revalidated against the ETag.
+pub async fn logo(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    path: web::Path<String>,
+    query: web::Query<LogoQuery>,
+) -> Result<HttpResponse, AppError> {
+    let logo = branding::load_logo(&pool, &path.into_inner()).await
+        .ok_or(AppError::NotFound)?;
+    let etag = format!("\"{}\"", logo.tag);
+    let cache_control = if query.v == logo.tag { "public, max-age=31536000, immutable" } else { "public, no-cache" };
+    let unchanged = req.headers().get("If-None-Match")
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v == etag);
+    if unchanged {
+        return Ok(HttpResponse::NotModified()
+            .insert_header(("ETag", etag))
+            .insert_header(("Cache-Control", cache_control))
+            .finish());
+    }
+
+    Ok(HttpResponse::Ok()
+        .content_type(logo.media_type)
+        .insert_header(("ETag", etag))
+        .insert_header(("Cache-Control", cache_control))
+        .insert_header(("X-Content-Type-Options", "nosniff"))
+        .body(logo.bytes))
+}
@@ -0,0 +1,37 @@
+use actix_session::Session;
+use actix_web::{web, HttpResponse};
+use sqlx::PgPool;
+
+use crate::models::changelog;
+use crate::auth::csrf;
+use crate::auth::session::{get_user_id, require_permission};
+use crate::errors::{AppError, render};
+use crate::handlers::auth_handlers::CsrfOnly;
+use crate::templates_structs::{ChangelogTemplate, PageContext};
+
+/// GET /changelog — every recorded upgrade and enabled feature.
+pub async fn list(
+    pool: web::Data<PgPool>,
+    session: Session,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    let ctx = PageContext::build(&session, &pool, "/changelog").await?;
+    let entries = changelog::find_all(&pool).await?;
+    render(ChangelogTemplate { ctx, entries })
+}
+
+/// POST /changelog/seen — dismiss the dashboard's "what changed" panel.
+pub async fn mark_seen(
+    pool: web::Data<PgPool>,
+    session: Session,
+    form: web::Form<CsrfOnly>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+    let user_id = get_user_id(&session).unwrap_or(0);
+    changelog::mark_seen(&pool, user_id).await?;
+
+    Ok(HttpResponse::SeeOther()
+        .insert_header(("Location", "/dashboard"))
+        .finish())
+}
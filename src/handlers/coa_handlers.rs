@@ -5,14 +5,25 @@ use sqlx::PgPool;
 use crate::auth::csrf;
 use crate::auth::session::{require_permission, get_user_id};
 use crate::errors::{AppError, render};
-use crate::models::{tor, agenda_point, coa, relation};
-use crate::models::coa::CoaForm;
+use crate::handlers::auth_handlers::CsrfOnly;
+use crate::models::{tor, agenda_point, coa, relation, revision};
+use crate::models::coa::{CoaDetail, CoaForm};
+use crate::models::revision::RevisionDiff;
 use crate::templates_structs::{PageContext, CoaFormTemplate};
 
 // ---------------------------------------------------------------------------
 // COA CRUD handlers (Task 15)
 // ---------------------------------------------------------------------------
 
+/// Earlier versions of a COA's title and description, with what each edit changed.
+async fn history(pool: &PgPool, coa_detail: &CoaDetail) -> Result<Vec<RevisionDiff>, AppError> {
+    let revisions = revision::find_for(pool, &[coa_detail.id]).await?;
+    Ok(revision::diffs(revisions, &[
+        ("title", &coa_detail.title),
+        ("description", &coa_detail.description),
+    ]))
+}
+
 /// GET /tor/{id}/workflow/agenda/{agenda_id}/coa/new
 /// Renders the COA creation form.
 pub async fn new_form(
@@ -40,6 +51,7 @@ pub async fn new_form(
                 form_title: "New Course of Action".to_string(),
                 coa: None,
                 errors: vec![],
+                history: vec![],
             };
             render(tmpl)
         }
@@ -97,6 +109,7 @@ pub async fn create(
             form_title: "New Course of Action".to_string(),
             coa: None,
             errors,
+            history: vec![],
         };
         return render(tmpl);
     }
@@ -143,6 +156,7 @@ pub async fn edit_form(
 
     match coa::find_by_id(&pool, coa_id).await {
         Ok(coa_detail) => {
+            let history = history(&pool, &coa_detail).await?;
             let tor_name = tor::get_tor_name(&pool, tor_id).await?;
             let ctx = PageContext::build(&session, &pool, "/workflow").await?
                 .with_tor(tor_id, &tor_name, "workflow");
@@ -154,6 +168,7 @@ pub async fn edit_form(
                 form_title: format!("Edit: {}", &coa_detail.title),
                 coa: Some(coa_detail),
                 errors: vec![],
+                history,
             };
             render(tmpl)
         }
@@ -198,6 +213,10 @@ pub async fn update(
 
     if !errors.is_empty() {
         let coa_detail = coa::find_by_id(&pool, coa_id).await.ok();
+        let history = match &coa_detail {
+            Some(c) => history(&pool, c).await?,
+            None => vec![],
+        };
         let tor_name = tor::get_tor_name(&pool, tor_id).await.unwrap_or_default();
         let ctx = PageContext::build(&session, &pool, "/workflow").await?
             .with_tor(tor_id, &tor_name, "workflow");
@@ -208,12 +227,13 @@ pub async fn update(
             form_title: format!("Edit: {}", &coa_detail.as_ref().map(|c| &c.title).unwrap_or(&"COA".to_string())),
             coa: coa_detail,
             errors,
+            history,
         };
         return render(tmpl);
     }
 
     // Update COA
-    coa::update(&pool, coa_id, title, description).await?;
+    coa::update(&pool, coa_id, title, description, user_id).await?;
 
     // Audit log
     let details = serde_json::json!({
@@ -230,6 +250,45 @@ pub async fn update(
         .finish())
 }
 
+/// POST /tor/{id}/workflow/agenda/{agenda_id}/coa/{coa_id}/revisions/{revision_id}/restore
+/// Puts a COA's title and description back to an earlier revision.
+pub async fn restore_revision(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<(i64, i64, i64, i64)>,
+    form: web::Form<CsrfOnly>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "coa.edit")?;
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+
+    let (tor_id, agenda_point_id, coa_id, revision_id) = path.into_inner();
+    let user_id = get_user_id(&session).ok_or(AppError::Session("User not logged in".to_string()))?;
+    tor::require_tor_membership(&pool, user_id, tor_id).await?;
+
+    if coa::find_by_id(&pool, coa_id).await.is_err() {
+        return Err(AppError::NotFound);
+    }
+    let rev = revision::find_by_id(&pool, revision_id).await?
+        .filter(|r| r.target_id == coa_id)
+        .ok_or(AppError::NotFound)?;
+
+    let title = rev.field("title");
+    coa::update(&pool, coa_id, title, rev.field("description"), user_id).await?;
+
+    let details = serde_json::json!({
+        "tor_id": tor_id,
+        "agenda_point_id": agenda_point_id,
+        "revision_id": revision_id,
+        "summary": format!("Restored COA '{}' to the revision of {}", title, rev.edited_at)
+    });
+    let _ = crate::audit::log(&pool, user_id, "coa.restored", "coa", coa_id, details).await;
+
+    let _ = session.insert("flash", "Course of Action restored to the earlier version");
+    Ok(HttpResponse::SeeOther()
+        .insert_header(("Location", format!("/tor/{tor_id}/workflow/agenda/{agenda_point_id}/coa/{coa_id}/edit")))
+        .finish())
+}
+
 /// POST /tor/{id}/workflow/agenda/{agenda_id}/coa/{coa_id}/delete
 /// Deletes a COA.
 pub async fn delete(
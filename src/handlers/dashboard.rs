@@ -3,7 +3,7 @@ use actix_web::{web, HttpResponse};
 use chrono::{Local, Timelike};
 use sqlx::PgPool;
 
-use crate::models::{user, entity, audit, proposal, dashboard};
+use crate::models::{user, entity, audit, proposal, dashboard, changelog, form_draft};
 use crate::errors::{AppError, render};
 use crate::templates_structs::{PageContext, DashboardTemplate};
 
@@ -53,6 +53,13 @@ pub async fn index(
     let user_tors = dashboard::find_user_tors(&pool, user_id).await;
     let upcoming_meetings = dashboard::find_upcoming_meetings(&pool, user_id, 7).await;
     let pending_items = dashboard::find_pending_items(&pool, user_id).await;
+    let counts = dashboard::find_counts(&pool, user_id).await;
+    let drafts = form_draft::find_for_user(&pool, user_id).await.unwrap_or_default();
+    let changes = if ctx.permissions.has("settings.manage") {
+        changelog::find_unseen(&pool, user_id).await.unwrap_or_default()
+    } else {
+        Vec::new()
+    };
 
     let tmpl = DashboardTemplate {
         ctx,
@@ -67,6 +74,9 @@ pub async fn index(
         user_tors,
         upcoming_meetings,
         pending_items,
+        counts,
+        changes,
+        drafts,
     };
     render(tmpl)
 }
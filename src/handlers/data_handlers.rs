@@ -1,18 +1,51 @@
 use actix_session::Session;
-use actix_web::{web, HttpResponse};
+use base64::Engine;
+use actix_web::{web, HttpRequest, HttpResponse};
 use sqlx::PgPool;
 
 use crate::auth::csrf;
-use crate::auth::session::require_permission;
+use crate::auth::session::{get_user_id, require_permission};
 use crate::errors::{render, AppError};
-use crate::models::data_manager::{export, import, jsonld};
-use crate::templates_structs::{DataManagerTemplate, PageContext};
+use crate::models::data_manager::{archive, encryption, export, import, jsonld};
+use crate::models::data_manager::types::ConflictMode;
+use crate::handlers::warning_handlers::ws::{self, ConnectionMap};
+use crate::models::{attachment, setting};
+use crate::models::data_manager::spreadsheet::{self, SheetKind};
+use crate::templates_structs::{DataManagerTemplate, PageContext, SpreadsheetImportTemplate};
 
 /// Query params for the export endpoint.
 #[derive(serde::Deserialize)]
 pub struct ExportQuery {
     pub format: Option<String>,
     pub types: Option<String>,
+    /// Delta export: only changes after this timestamp (JSON format only).
+    pub since: Option<String>,
+    /// "passphrase" (sent in `X-Export-Passphrase`) or "key" (the configured
+    /// public key); omitted for a plain export.
+    pub encrypt: Option<String>,
+}
+
+/// Query params for the archive import.
+#[derive(serde::Deserialize)]
+pub struct ArchiveImportQuery {
+    #[serde(default)]
+    pub conflict_mode: ConflictMode,
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// Form for generating the export encryption key pair.
+#[derive(serde::Deserialize)]
+pub struct CsrfForm {
+    pub csrf_token: String,
+}
+
+fn header<'a>(req: &'a HttpRequest, name: &str) -> Option<&'a str> {
+    req.headers().get(name).and_then(|v| v.to_str().ok())
+}
+
+async fn encryption_public_key(pool: &PgPool) -> String {
+    setting::get_value(pool, "exports.encryption_public_key", "").await
 }
 
 /// GET /data-manager — admin page
@@ -32,14 +65,19 @@ pub async fn data_manager_page(
     .await
     .map_err(AppError::Db)?;
 
-    let tmpl = DataManagerTemplate { ctx, entity_types };
+    let key_fingerprint = encryption::fingerprint(&encryption_public_key(&pool).await);
+
+    let tmpl = DataManagerTemplate { ctx, entity_types, key_fingerprint };
     render(tmpl)
 }
 
-/// POST /api/data/import — import entities and relations
+/// POST /api/data/import — import entities and relations. With
+/// `"dry_run": true` the payload is validated and the report of what would be
+/// created, updated or skipped is returned without committing anything.
 pub async fn import_data(
     pool: web::Data<PgPool>,
     session: Session,
+    conn_map: web::Data<ConnectionMap>,
     body: web::Json<serde_json::Value>,
 ) -> Result<HttpResponse, AppError> {
     require_permission(&session, "settings.manage")?;
@@ -52,30 +90,44 @@ pub async fn import_data(
         return Err(AppError::Csrf("missing csrf_token in request body".to_string()));
     }
 
+    let dry_run = body.get("dry_run").and_then(|v| v.as_bool()).unwrap_or(false);
+
     // Auto-detect JSON-LD vs native format
     let payload = if body.get("@context").is_some() || body.get("@graph").is_some() {
         jsonld::parse_jsonld(&body)
             .map_err(|e| AppError::Session(format!("Invalid JSON-LD: {}", e)))?
     } else {
-        // Parse native format, stripping the csrf_token field
+        // Parse native format, stripping the csrf_token and dry_run fields
         let mut native = body.into_inner();
         if let Some(obj) = native.as_object_mut() {
             obj.remove("csrf_token");
+            obj.remove("dry_run");
         }
         serde_json::from_value(native)
             .map_err(|e| AppError::Session(format!("Invalid import payload: {}", e)))?
     };
 
-    let result = import::import_data(&pool, &payload).await
-        .map_err(|e| AppError::Session(format!("Import failed: {e}")))?;
+    let result = if dry_run {
+        import::preview_import(&pool, &payload).await
+    } else {
+        import::import_data(&pool, &payload).await
+    }
+    .map_err(|e| AppError::Session(format!("Import failed: {e}")))?;
+
+    // Imports can bring nav items, roles and grants
+    if !dry_run {
+        ws::notify_nav_updated(&pool, &conn_map).await?;
+    }
 
     Ok(HttpResponse::Ok().json(result))
 }
 
-/// GET /api/data/export — export entity graph
+/// GET /api/data/export — export entity graph, optionally encrypted. With
+/// `since=` returns only the changes after that time, for synchronisation.
 pub async fn export_data(
     pool: web::Data<PgPool>,
     session: Session,
+    req: HttpRequest,
     query: web::Query<ExportQuery>,
 ) -> Result<HttpResponse, AppError> {
     require_permission(&session, "settings.manage")?;
@@ -90,24 +142,242 @@ pub async fn export_data(
 
     let format = query.format.as_deref().unwrap_or("json");
 
-    match format {
+    if let Some(since) = query.since.as_deref().filter(|s| !s.trim().is_empty()) {
+        let Some(since) = export::parse_since(since) else {
+            return Ok(HttpResponse::BadRequest().body("since must be an RFC 3339 timestamp or a YYYY-MM-DD date"));
+        };
+        if format != "json" {
+            return Ok(HttpResponse::BadRequest().body("Delta exports (since=) are only available in JSON format"));
+        }
+        let data = export::export_changes(&pool, types_ref, &since).await?;
+        return Ok(HttpResponse::Ok().json(data));
+    }
+
+    let (bytes, content_type, ext) = match format {
+        "archive" => {
+            if types_ref.is_some_and(|t| !t.is_empty()) {
+                return Ok(HttpResponse::BadRequest().body("Archive exports always contain the whole environment"));
+            }
+            let bytes = archive::export_archive(&pool, &attachment::uploads_dir()).await
+                .map_err(|e| AppError::Session(format!("Export failed: {e}")))?;
+            (bytes, "application/zip", "zip")
+        }
         "jsonld" => {
             let data = jsonld::export_jsonld(&pool, types_ref).await?;
-            Ok(HttpResponse::Ok()
-                .content_type("application/ld+json")
-                .json(data))
+            (serde_json::to_vec(&data).unwrap_or_default(), "application/ld+json", "jsonld")
         }
         "sql" => {
             let sql = export::export_sql(&pool, types_ref).await?;
-            Ok(HttpResponse::Ok()
-                .content_type("text/plain; charset=utf-8")
-                .body(sql))
+            (sql.into_bytes(), "text/plain; charset=utf-8", "sql")
         }
         _ => {
             let data = export::export_entities(&pool, types_ref).await?;
-            Ok(HttpResponse::Ok().json(data))
+            (serde_json::to_vec(&data).unwrap_or_default(), "application/json", "json")
+        }
+    };
+    let user_id = get_user_id(&session).unwrap_or(0);
+
+    let public_key;
+    let recipient = match query.encrypt.as_deref() {
+        Some("passphrase") => encryption::Recipient::Passphrase(header(&req, "X-Export-Passphrase").unwrap_or("")),
+        Some("key") => {
+            public_key = encryption_public_key(&pool).await;
+            encryption::Recipient::PublicKey(&public_key)
         }
+        _ => {
+            return crate::handlers::export_handlers::checksummed_download(
+                &pool, user_id, "data_export", &format!("ahlt-export.{ext}"), content_type, "attachment", bytes,
+            ).await;
+        }
+    };
+    let method = query.encrypt.clone().unwrap_or_default();
+    let encrypted = match encryption::encrypt(&bytes, recipient) {
+        Ok(encrypted) => encrypted,
+        Err(e) => return Ok(HttpResponse::BadRequest().body(e.to_string())),
+    };
+
+    let details = serde_json::json!({
+        "format": ext,
+        "method": method,
+        "summary": format!("Encrypted {} export generated ({})", ext, method)
+    });
+    let _ = crate::audit::log(&pool, user_id, "data.export_encrypted", "data_manager", 0, details).await;
+
+    crate::handlers::export_handlers::checksummed_download(
+        &pool, user_id, "data_export", &format!("ahlt-export.{ext}.enc"), "application/octet-stream", "attachment", encrypted,
+    ).await
+}
+
+/// POST /api/data/import-archive — import a ZIP archive made with
+/// `format=archive`, remapping IDs to this environment. The raw archive is
+/// the body and the token comes in `X-CSRF-Token`; `dry_run=true` reports
+/// without committing.
+pub async fn import_archive(
+    pool: web::Data<PgPool>,
+    session: Session,
+    req: HttpRequest,
+    query: web::Query<ArchiveImportQuery>,
+    conn_map: web::Data<ConnectionMap>,
+    body: web::Bytes,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    csrf::validate_csrf(&session, header(&req, "X-CSRF-Token").unwrap_or(""))?;
+
+    let archive = match archive::read_archive(&pool, &body).await {
+        Ok(archive) => archive,
+        Err(e) => return Ok(HttpResponse::BadRequest().body(e)),
+    };
+    let result = archive::import_archive(&pool, &archive, &query.conflict_mode, &attachment::uploads_dir(), query.dry_run)
+        .await
+        .map_err(|e| AppError::Session(format!("Import failed: {e}")))?;
+
+    if !query.dry_run && result.result.errors.is_empty() {
+        ws::notify_nav_updated(&pool, &conn_map).await?;
+        let user_id = get_user_id(&session).unwrap_or(0);
+        let details = serde_json::json!({
+            "created_at": archive.manifest.created_at,
+            "created": result.result.created,
+            "updated": result.result.updated,
+            "files": result.files,
+            "summary": format!(
+                "Imported archive from {}: {} created, {} updated, {} file(s)",
+                archive.manifest.created_at, result.result.created, result.result.updated, result.files
+            )
+        });
+        let _ = crate::audit::log(&pool, user_id, "data.archive_imported", "data_manager", 0, details).await;
     }
+
+    Ok(HttpResponse::Ok().json(result))
+}
+
+/// POST /api/data/decrypt — decrypt an encrypted export so it can be fed to
+/// the import. The raw file is the body; the token comes in `X-CSRF-Token`
+/// and the secret in `X-Export-Passphrase` or `X-Export-Private-Key`.
+pub async fn decrypt_data(
+    session: Session,
+    req: HttpRequest,
+    body: web::Bytes,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    csrf::validate_csrf(&session, header(&req, "X-CSRF-Token").unwrap_or(""))?;
+
+    let secret = match (header(&req, "X-Export-Passphrase"), header(&req, "X-Export-Private-Key")) {
+        (_, Some(key)) if !key.is_empty() => encryption::Secret::PrivateKey(key),
+        (Some(passphrase), _) => encryption::Secret::Passphrase(passphrase),
+        _ => return Ok(HttpResponse::BadRequest().body("A passphrase or private key is required")),
+    };
+    match encryption::decrypt(&body, secret) {
+        Ok(plaintext) => Ok(HttpResponse::Ok().content_type("application/octet-stream").body(plaintext)),
+        Err(e) => Ok(HttpResponse::BadRequest().body(e.to_string())),
+    }
+}
+
+/// POST /data-manager/encryption-key — generate a new key pair for
+/// encrypted exports. The public key replaces the configured one; the
+/// private key is downloaded once and never stored.
+pub async fn generate_encryption_key(
+    pool: web::Data<PgPool>,
+    session: Session,
+    form: web::Form<CsrfForm>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+
+    let setting_entity = crate::models::entity::find_by_type_and_name(&pool, "setting", "exports.encryption_public_key").await?
+        .ok_or(AppError::NotFound)?;
+    let (private_key, public_key) = encryption::generate_key_pair();
+    let user_id = get_user_id(&session).unwrap_or(0);
+    setting::change_value(&pool, setting_entity.id, &public_key, user_id, "update").await?;
+
+    let fingerprint = encryption::fingerprint(&public_key).unwrap_or_default();
+    let details = serde_json::json!({
+        "fingerprint": fingerprint,
+        "summary": format!("Generated export encryption key {}", fingerprint)
+    });
+    let _ = crate::audit::log(&pool, user_id, "data.encryption_key_generated", "setting", setting_entity.id, details).await;
+
+    let contents = format!(
+        "# ahlt export private key, fingerprint {fingerprint}\n\
+         # Keep this file offline. It is needed to import exports encrypted for the key.\n\
+         {private_key}\n"
+    );
+    Ok(HttpResponse::Ok()
+        .content_type("text/plain; charset=utf-8")
+        .insert_header(("Content-Disposition", format!("attachment; filename=\"ahlt-export-key-{fingerprint}.txt\"")))
+        .insert_header(("Cache-Control", "no-store"))
+        .body(contents))
+}
+
+/// Upload form for the spreadsheet importer; `workbook` is a `data:` URI.
+#[derive(serde::Deserialize)]
+pub struct WorkbookForm {
+    pub csrf_token: String,
+    #[serde(default)]
+    pub workbook: String,
+}
+
+fn sheet_kind(slug: &str) -> Result<SheetKind, AppError> {
+    SheetKind::from_slug(slug).ok_or(AppError::NotFound)
+}
+
+/// GET /data-manager/spreadsheets — Excel templates and upload forms
+pub async fn spreadsheet_page(
+    pool: web::Data<PgPool>,
+    session: Session,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    let ctx = PageContext::build(&session, &pool, "/data-manager").await?;
+    render(SpreadsheetImportTemplate { ctx, report: None })
+}
+
+/// GET /data-manager/spreadsheets/{kind}/template — blank XLSX template
+pub async fn spreadsheet_template(
+    session: Session,
+    path: web::Path<String>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    let kind = sheet_kind(&path.into_inner())?;
+    let bytes = spreadsheet::build_template(kind)
+        .map_err(|e| AppError::Session(format!("Could not build template: {e}")))?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/vnd.openxmlformats-officedocument.spreadsheetml.sheet")
+        .insert_header((
+            "Content-Disposition",
+            format!("attachment; filename=\"{}-template.xlsx\"", kind.slug()),
+        ))
+        .body(bytes))
+}
+
+/// POST /data-manager/spreadsheets/{kind}/import — check and import a
+/// completed template; any row error imports nothing
+pub async fn spreadsheet_import(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<String>,
+    form: web::Form<WorkbookForm>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+    let kind = sheet_kind(&path.into_inner())?;
+
+    let encoded = form.workbook.split_once(";base64,").map(|(_, data)| data).unwrap_or("");
+    let bytes = base64::engine::general_purpose::STANDARD.decode(encoded).unwrap_or_default();
+    let report = spreadsheet::import_workbook(&pool, kind, &bytes).await
+        .map_err(|e| AppError::Session(format!("Import failed: {e}")))?;
+
+    if report.errors.is_empty() {
+        let user_id = get_user_id(&session).unwrap_or(0);
+        let details = serde_json::json!({
+            "kind": kind.slug(),
+            "created": report.created,
+            "summary": format!("Imported {} row(s) from the {} spreadsheet", report.created, kind.label())
+        });
+        let _ = crate::audit::log(&pool, user_id, "data.spreadsheet_imported", "data_manager", 0, details).await;
+    }
+
+    let ctx = PageContext::build(&session, &pool, "/data-manager").await?;
+    render(SpreadsheetImportTemplate { ctx, report: Some((kind, report)) })
 }
 
 /// GET /api/data/schema — return the JSON-LD @context
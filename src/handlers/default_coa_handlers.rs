@@ -0,0 +1,141 @@
+use std::collections::HashMap;
+
+use actix_session::Session;
+use actix_web::{web, HttpResponse};
+use sqlx::PgPool;
+
+use crate::auth::csrf;
+use crate::auth::session::{get_user_id, require_permission};
+use crate::errors::{AppError, render};
+use crate::handlers::auth_handlers::CsrfOnly;
+use crate::models::coa::defaults::{self, BoilerplateSection, DefaultCoaInput};
+use crate::templates_structs::{DefaultCoasTemplate, PageContext};
+
+/// The agenda item type whose default set this page manages; informative
+/// items have no courses of action.
+const ITEM_TYPE: &str = "decision";
+
+async fn render_list(pool: &PgPool, session: &Session, errors: Vec<String>) -> Result<HttpResponse, AppError> {
+    let ctx = PageContext::build(session, pool, "/default-coas").await?;
+    let coas = defaults::find_set(pool, ITEM_TYPE).await?;
+    render(DefaultCoasTemplate { ctx, coas, errors })
+}
+
+/// Form input: `title`, `description`, and for each section row `n`:
+/// `section_title_<n>` and `section_content_<n>`. Rows left blank are dropped.
+fn input_from(form: &HashMap<String, String>) -> DefaultCoaInput<'_> {
+    let field = |key: &str| form.get(key).map(String::as_str).unwrap_or("");
+    let mut sections = Vec::new();
+    for n in 0.. {
+        let (Some(title), content) = (form.get(&format!("section_title_{n}")), field(&format!("section_content_{n}"))) else { break };
+        if title.trim().is_empty() && content.trim().is_empty() {
+            continue;
+        }
+        sections.push(BoilerplateSection { title: title.trim().to_string(), content: content.trim().to_string() });
+    }
+    DefaultCoaInput {
+        title: field("title"),
+        description: field("description"),
+        sections,
+    }
+}
+
+fn redirect() -> HttpResponse {
+    HttpResponse::SeeOther()
+        .insert_header(("Location", "/default-coas"))
+        .finish()
+}
+
+/// GET /default-coas — the COAs generated for decision agenda points.
+pub async fn list(
+    pool: web::Data<PgPool>,
+    session: Session,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    render_list(&pool, &session, Vec::new()).await
+}
+
+/// POST /default-coas — add a COA to the set.
+pub async fn create(
+    pool: web::Data<PgPool>,
+    session: Session,
+    form: web::Form<HashMap<String, String>>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    csrf::validate_csrf(&session, form.get("csrf_token").map(String::as_str).unwrap_or(""))?;
+    let user_id = get_user_id(&session).unwrap_or(0);
+
+    let input = input_from(&form);
+    let set = defaults::find_set(&pool, ITEM_TYPE).await?;
+    let errors = defaults::validate(&input, &set, None);
+    if !errors.is_empty() {
+        return render_list(&pool, &session, errors).await;
+    }
+    let id = defaults::create(&pool, ITEM_TYPE, &input).await?;
+
+    let details = serde_json::json!({
+        "item_type": ITEM_TYPE,
+        "sections": input.sections.len(),
+        "summary": format!("Added default COA '{}'", input.title.trim())
+    });
+    let _ = crate::audit::log(&pool, user_id, "default_coa.created", "default_coa", id, details).await;
+
+    let _ = session.insert("flash", format!("Default COA '{}' added", input.title.trim()));
+    Ok(redirect())
+}
+
+/// POST /default-coas/{id} — update a default COA and its boilerplate sections.
+pub async fn update(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+    form: web::Form<HashMap<String, String>>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    csrf::validate_csrf(&session, form.get("csrf_token").map(String::as_str).unwrap_or(""))?;
+    let user_id = get_user_id(&session).unwrap_or(0);
+    let coa = defaults::find_by_id(&pool, path.into_inner()).await?.ok_or(AppError::NotFound)?;
+
+    let input = input_from(&form);
+    let set = defaults::find_set(&pool, &coa.item_type).await?;
+    let errors = defaults::validate(&input, &set, Some(coa.id));
+    if !errors.is_empty() {
+        return render_list(&pool, &session, errors).await;
+    }
+    defaults::update(&pool, &coa, &input).await?;
+
+    let details = serde_json::json!({
+        "old_title": coa.title,
+        "sections": input.sections.len(),
+        "summary": format!("Updated default COA '{}'", input.title.trim())
+    });
+    let _ = crate::audit::log(&pool, user_id, "default_coa.updated", "default_coa", coa.id, details).await;
+
+    let _ = session.insert("flash", format!("Default COA '{}' updated", input.title.trim()));
+    Ok(redirect())
+}
+
+/// POST /default-coas/{id}/delete — remove a COA from the set. COAs already
+/// generated from it are kept.
+pub async fn delete(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+    form: web::Form<CsrfOnly>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+    let user_id = get_user_id(&session).unwrap_or(0);
+    let coa = defaults::find_by_id(&pool, path.into_inner()).await?.ok_or(AppError::NotFound)?;
+
+    defaults::delete(&pool, coa.id).await?;
+
+    let details = serde_json::json!({
+        "item_type": coa.item_type,
+        "summary": format!("Deleted default COA '{}'", coa.title)
+    });
+    let _ = crate::audit::log(&pool, user_id, "default_coa.deleted", "default_coa", coa.id, details).await;
+
+    let _ = session.insert("flash", format!("Default COA '{}' deleted", coa.title));
+    Ok(redirect())
+}
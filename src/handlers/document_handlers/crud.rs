@@ -4,7 +4,8 @@ use sqlx::PgPool;
 
 use crate::auth::{csrf, session::{require_permission, get_user_id}};
 use crate::errors::{AppError, render};
-use crate::models::document;
+use crate::handlers::transfer_handlers;
+use crate::models::{document, quota};
 use crate::templates_structs::{PageContext, DocumentDetailTemplate, DocumentFormTemplate};
 
 /// GET /documents/new
@@ -54,6 +55,12 @@ pub async fn create(
     if doc_type.is_empty() {
         errors.push("Document type is required".to_string());
     }
+    let tor_id = form.tor_id.as_ref().and_then(|s| s.parse::<i64>().ok());
+    if let Some(tid) = tor_id
+        && let Some(message) = quota::check_tor_storage(&pool, tid, body.len() as i64, 0).await?
+    {
+        errors.push(message);
+    }
 
     if !errors.is_empty() {
         let ctx = PageContext::build(&session, &pool, "/documents").await?;
@@ -67,7 +74,6 @@ pub async fn create(
         return render(tmpl);
     }
 
-    let tor_id = form.tor_id.as_ref().and_then(|s| s.parse::<i64>().ok());
     let doc_id = document::create(&pool, title, doc_type, body, user_id, tor_id).await?;
 
     // Audit log
@@ -100,7 +106,8 @@ pub async fn detail(
     match document::find_by_id(&pool, doc_id).await? {
         Some(doc) => {
             let ctx = PageContext::build(&session, &pool, "/documents").await?;
-            let tmpl = DocumentDetailTemplate { ctx, document: doc };
+            let can_transfer = transfer_handlers::can_transfer(&pool, &session, doc_id).await?;
+            let tmpl = DocumentDetailTemplate { ctx, document: doc, can_transfer };
             render(tmpl)
         }
         None => Err(AppError::NotFound),
@@ -164,14 +171,23 @@ pub async fn update(
         errors.push("Document type is required".to_string());
     }
 
+    // Only growth counts against the ToR's storage quota
+    let existing = document::find_by_id(&pool, doc_id).await?.ok_or(AppError::NotFound)?;
+    if existing.tor_id != 0
+        && let Some(message) = quota::check_tor_storage(
+            &pool, existing.tor_id, body.len() as i64, existing.body.len() as i64,
+        ).await?
+    {
+        errors.push(message);
+    }
+
     if !errors.is_empty() {
-        let existing = document::find_by_id(&pool, doc_id).await.ok().flatten();
         let ctx = PageContext::build(&session, &pool, "/documents").await?;
         let tmpl = DocumentFormTemplate {
             ctx,
             form_title: "Edit Document".to_string(),
             form_action: format!("/documents/{}", doc_id),
-            document: existing,
+            document: Some(existing),
             errors,
         };
         return render(tmpl);
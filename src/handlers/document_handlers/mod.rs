@@ -1,5 +1,7 @@
 pub mod list;
 pub mod crud;
+pub mod preview;
 
 pub use list::list;
 pub use crud::{new_form, create, detail, edit_form, update, delete};
+pub use preview::preview;
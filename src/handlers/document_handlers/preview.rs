@@ -0,0 +1,42 @@
+use actix_session::Session;
+use actix_web::{web, HttpRequest, HttpResponse};
+use sqlx::PgPool;
+
+use crate::auth::session::require_permission;
+use crate::errors::AppError;
+use crate::models::document::{self, preview};
+
+/// GET /documents/{id}/preview
+/// First-page thumbnail of a document as SVG, for inline previews of
+/// attachments. Served from the disk cache when the document is unchanged.
+pub async fn preview(
+    req: HttpRequest,
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "document.view")?;
+
+    let doc = document::find_by_id(&pool, path.into_inner()).await?
+        .ok_or(AppError::NotFound)?;
+    let etag = format!("\"{}\"", preview::fingerprint(&doc));
+    let unchanged = req.headers().get("If-None-Match")
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v == etag);
+    if unchanged {
+        return Ok(HttpResponse::NotModified().insert_header(("ETag", etag)).finish());
+    }
+
+    let dir = preview::cache_dir(&pool).await;
+    let svg = preview::cached_thumbnail(&dir, &doc).unwrap_or_else(|e| {
+        log::warn!("Could not cache preview of document {} in {}: {}", doc.id, dir.display(), e);
+        preview::render_svg(&doc)
+    });
+
+    Ok(HttpResponse::Ok()
+        .content_type("image/svg+xml")
+        .insert_header(("ETag", etag))
+        .insert_header(("Cache-Control", "private, no-cache"))
+        .insert_header(("Content-Security-Policy", "default-src 'none'; img-src data:; style-src 'unsafe-inline'"))
+        .body(svg))
+}
@@ -0,0 +1,178 @@
+use actix_session::Session;
+use actix_web::{web, HttpResponse};
+use serde::Deserialize;
+use sqlx::PgPool;
+
+use crate::auth::csrf;
+use crate::auth::session::{get_user_id, require_permission};
+use crate::errors::{AppError, render};
+use crate::models::email_template::{self, BuiltIn, DEFAULT_LOCALE};
+use crate::templates_structs::{EmailTemplateFormTemplate, EmailTemplatesTemplate, PageContext};
+
+#[derive(Deserialize)]
+pub struct LocaleQuery {
+    locale: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct EmailTemplateForm {
+    pub csrf_token: String,
+    pub locale: String,
+    pub subject: String,
+    #[serde(default)]
+    pub body: String,
+    /// "preview" renders with sample data without saving.
+    #[serde(default)]
+    pub action: String,
+}
+
+#[derive(Deserialize)]
+pub struct ResetForm {
+    pub csrf_token: String,
+    pub locale: String,
+}
+
+fn redirect(location: &str) -> HttpResponse {
+    HttpResponse::SeeOther()
+        .insert_header(("Location", location.to_string()))
+        .finish()
+}
+
+fn find_built_in(kind: &str) -> Result<&'static BuiltIn, AppError> {
+    email_template::find_built_in(kind).ok_or(AppError::NotFound)
+}
+
+/// GET /email-templates — every outgoing email and its locale overrides.
+pub async fn list(
+    pool: web::Data<PgPool>,
+    session: Session,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    let ctx = PageContext::build(&session, &pool, "/email-templates").await?;
+    let overrides = email_template::find_all(&pool).await?;
+    render(EmailTemplatesTemplate { ctx, built_ins: email_template::BUILT_INS, overrides })
+}
+
+/// GET /email-templates/{kind}?locale= — edit one email for a locale. Shows
+/// the text that would be sent today, whichever variant it comes from.
+pub async fn edit(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<String>,
+    query: web::Query<LocaleQuery>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    let built_in = find_built_in(&path)?;
+    let locale = query.locale.as_deref()
+        .map(str::trim)
+        .filter(|l| email_template::is_valid_locale(l))
+        .unwrap_or(DEFAULT_LOCALE)
+        .to_string();
+
+    let existing = email_template::find(&pool, built_in.kind, &locale).await?;
+    let is_override = existing.is_some();
+    let (subject, body) = match existing {
+        Some(t) => (t.subject, t.body),
+        None => {
+            // Start from the variant currently used for this locale
+            let current = email_template::render(&pool, built_in.kind, &locale, &Default::default()).await?;
+            current.map(|c| (c.subject, c.body))
+                .unwrap_or_else(|| (built_in.subject.to_string(), built_in.body.to_string()))
+        }
+    };
+
+    let preview = email_template::RenderedEmail {
+        subject: email_template::substitute(&subject, &built_in.sample()),
+        body: email_template::substitute(&body, &built_in.sample()),
+        locale: locale.clone(),
+    };
+    let ctx = PageContext::build(&session, &pool, "/email-templates").await?;
+    let overrides = email_template::find_all(&pool).await?
+        .into_iter()
+        .filter(|t| t.kind == built_in.kind)
+        .collect();
+    render(EmailTemplateFormTemplate {
+        ctx, built_in, locale, subject, body, is_override, overrides,
+        preview: Some(preview),
+        errors: Vec::new(),
+    })
+}
+
+/// POST /email-templates/{kind} — preview with sample data, or save the
+/// override for the form's locale.
+pub async fn save(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<String>,
+    form: web::Form<EmailTemplateForm>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+    let built_in = find_built_in(&path)?;
+
+    let locale = form.locale.trim().to_string();
+    let body = form.body.replace("\r\n", "\n");
+    let errors = email_template::validate(built_in, &locale, &form.subject, &body);
+
+    if form.action == "preview" || !errors.is_empty() {
+        let sample = built_in.sample();
+        let preview = email_template::RenderedEmail {
+            subject: email_template::substitute(form.subject.trim(), &sample),
+            body: email_template::substitute(&body, &sample),
+            locale: locale.clone(),
+        };
+        let is_override = email_template::find(&pool, built_in.kind, &locale).await?.is_some();
+        let ctx = PageContext::build(&session, &pool, "/email-templates").await?;
+        let overrides = email_template::find_all(&pool).await?
+            .into_iter()
+            .filter(|t| t.kind == built_in.kind)
+            .collect();
+        return render(EmailTemplateFormTemplate {
+            ctx, built_in, locale,
+            subject: form.subject.clone(),
+            body,
+            is_override, overrides,
+            preview: Some(preview),
+            errors,
+        });
+    }
+
+    let id = email_template::save(&pool, built_in.kind, &locale, &form.subject, &body).await?;
+    let user_id = get_user_id(&session).unwrap_or(0);
+    let details = serde_json::json!({
+        "kind": built_in.kind,
+        "locale": locale,
+        "summary": format!("Updated email template '{}' ({})", built_in.label, locale)
+    });
+    let _ = crate::audit::log(&pool, user_id, "email_template.updated", "email_template", id, details).await;
+    let _ = session.insert("flash", format!("Saved '{}' for locale {}", built_in.label, locale));
+    Ok(redirect(&format!("/email-templates/{}?locale={}", built_in.kind, locale)))
+}
+
+/// POST /email-templates/{kind}/reset — drop the override for a locale so
+/// the next variant (or the built-in text) is used again.
+pub async fn reset(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<String>,
+    form: web::Form<ResetForm>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+    let built_in = find_built_in(&path)?;
+
+    let locale = form.locale.trim();
+    let existing = email_template::find(&pool, built_in.kind, locale).await?
+        .ok_or(AppError::NotFound)?;
+    email_template::reset(&pool, built_in.kind, locale).await?;
+
+    let user_id = get_user_id(&session).unwrap_or(0);
+    let details = serde_json::json!({
+        "kind": built_in.kind,
+        "locale": locale,
+        "summary": format!("Reset email template '{}' ({}) to default", built_in.label, locale)
+    });
+    let _ = crate::audit::log(&pool, user_id, "email_template.reset", "email_template", existing.id, details).await;
+    let _ = session.insert("flash", format!("'{}' for locale {} reset to default", built_in.label, locale));
+    Ok(redirect("/email-templates"))
+}
@@ -0,0 +1,192 @@
+use actix_session::Session;
+use actix_web::{web, HttpResponse};
+use serde::Deserialize;
+use sqlx::PgPool;
+
+use crate::auth::csrf;
+use crate::auth::session::{get_user_id, require_permission};
+use crate::errors::{AppError, render};
+use crate::models::export_policy::{self, Watermark};
+use crate::models::export_record::{self, ExportCheck, ExportRecord};
+use crate::templates_structs::{ExportsTemplate, PageContext};
+
+#[derive(Deserialize)]
+pub struct VerifyForm {
+    pub csrf_token: String,
+    /// A hex digest, or a whole `SHA256SUMS` manifest.
+    #[serde(default)]
+    pub checksums: String,
+}
+
+#[derive(Deserialize)]
+pub struct AuditArchiveQuery {
+    from: Option<String>,
+    to: Option<String>,
+}
+
+/// Check that the user may download a CSV or PDF export, and get the
+/// watermark to stamp it with. Refusals are audited.
+pub async fn authorize_export(pool: &PgPool, user_id: i64, format: &str) -> Result<Option<Watermark>, AppError> {
+    if !export_policy::FORMATS.contains(&format) {
+        return Ok(None);
+    }
+    if !export_policy::may_export(pool, user_id).await? {
+        let details = serde_json::json!({
+            "format": format,
+            "summary": format!("{} export refused: role not allowed to export", format.to_uppercase())
+        });
+        let _ = crate::audit::log(pool, user_id, "export.denied", "export", 0, details).await;
+        return Err(AppError::PermissionDenied(format!("{} exports are restricted to certain roles", format.to_uppercase())));
+    }
+    Ok(export_policy::watermark(pool, user_id).await?)
+}
+
+/// Record a generated export and send it with its checksum: a `Digest`
+/// header, the hex digest, and a link to its one-line manifest.
+pub async fn checksummed_download(
+    pool: &PgPool,
+    user_id: i64,
+    kind: &str,
+    file_name: &str,
+    content_type: &str,
+    disposition: &str,
+    bytes: Vec<u8>,
+) -> Result<HttpResponse, AppError> {
+    let record = export_record::record(pool, kind, file_name, &bytes, user_id).await?;
+    Ok(HttpResponse::Ok()
+        .content_type(content_type.to_string())
+        .insert_header(("Content-Disposition", format!("{disposition}; filename=\"{file_name}\"")))
+        .insert_header(("Digest", record.digest_header()))
+        .insert_header(("X-Checksum-SHA256", record.sha256.clone()))
+        .insert_header(("Link", format!("</exports/{}/manifest>; rel=\"describedby\"", record.id)))
+        .body(bytes))
+}
+
+/// Check each line of a pasted digest or manifest against recorded exports.
+async fn check(pool: &PgPool, text: &str) -> Result<Vec<ExportCheck>, AppError> {
+    let trimmed = text.trim().to_lowercase();
+    let lines = if export_record::is_sha256_hex(&trimmed) {
+        vec![(String::new(), trimmed)]
+    } else {
+        export_record::parse_manifest(text)
+    };
+    let mut checks = Vec::new();
+    for (file_name, sha256) in lines {
+        let matches = export_record::find_by_sha256(pool, &sha256).await?;
+        checks.push(ExportCheck { file_name, sha256, matches });
+    }
+    Ok(checks)
+}
+
+async fn render_exports(
+    pool: &PgPool,
+    session: &Session,
+    checksums: String,
+    checks: Option<Vec<ExportCheck>>,
+) -> Result<HttpResponse, AppError> {
+    let ctx = PageContext::build(session, pool, "/exports").await?;
+    let exports = export_record::find_recent(pool, 100).await?;
+    render(ExportsTemplate { ctx, exports, checksums, checks })
+}
+
+/// GET /exports — recently generated exports and a checksum verifier.
+pub async fn list(
+    pool: web::Data<PgPool>,
+    session: Session,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "audit.view")?;
+    render_exports(&pool, &session, String::new(), None).await
+}
+
+/// POST /exports/verify — check a digest or manifest against the record.
+pub async fn verify(
+    pool: web::Data<PgPool>,
+    session: Session,
+    form: web::Form<VerifyForm>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "audit.view")?;
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+    let checks = check(&pool, &form.checksums).await?;
+    render_exports(&pool, &session, form.checksums.clone(), Some(checks)).await
+}
+
+/// GET /exports/{id}/manifest — the export's `sha256sum` line, to circulate
+/// with the file.
+pub async fn manifest(
+    pool: web::Data<PgPool>,
+    path: web::Path<i64>,
+) -> Result<HttpResponse, AppError> {
+    let record = export_record::find_by_id(&pool, path.into_inner()).await?
+        .ok_or(AppError::NotFound)?;
+    Ok(HttpResponse::Ok()
+        .content_type("text/plain; charset=utf-8")
+        .insert_header((
+            "Content-Disposition",
+            format!("attachment; filename=\"{}.sha256\"", record.file_name),
+        ))
+        .body(record.manifest()))
+}
+
+/// GET /verify/{sha256} — public check whether a file was generated here.
+/// Recipients without an account can prove a circulated file is unaltered.
+pub async fn verify_public(
+    pool: web::Data<PgPool>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, AppError> {
+    let sha256 = path.into_inner().to_lowercase();
+    if !export_record::is_sha256_hex(&sha256) {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Expected a 64-character hex SHA-256 digest"
+        })));
+    }
+    let matches = export_record::find_by_sha256(&pool, &sha256).await?;
+    let exports: Vec<_> = matches.iter()
+        .map(|r: &ExportRecord| serde_json::json!({
+            "file_name": r.file_name,
+            "kind": r.kind,
+            "size_bytes": r.size_bytes,
+            "generated_at": r.generated_at,
+        }))
+        .collect();
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "sha256": sha256,
+        "verified": !exports.is_empty(),
+        "exports": exports,
+    })))
+}
+
+/// GET /audit/archive?from=&to= — the audit trail for a period as a zip
+/// with a `SHA256SUMS` manifest. Defaults to the last 30 days.
+pub async fn audit_archive(
+    pool: web::Data<PgPool>,
+    session: Session,
+    query: web::Query<AuditArchiveQuery>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "audit.view")?;
+    let parse = |v: &Option<String>| v.as_deref()
+        .and_then(|d| chrono::NaiveDate::parse_from_str(d, "%Y-%m-%d").ok());
+    let today = chrono::Utc::now().date_naive();
+    let to = parse(&query.to).unwrap_or(today);
+    let from = parse(&query.from).unwrap_or(to - chrono::Duration::days(29));
+    if from > to || (to - from).num_days() >= crate::audit::archive::MAX_DAYS {
+        let _ = session.insert("flash", format!(
+            "Audit archives cover 1 to {} days, with the start before the end",
+            crate::audit::archive::MAX_DAYS
+        ));
+        return Ok(HttpResponse::SeeOther().insert_header(("Location", "/exports")).finish());
+    }
+
+    let bytes = crate::audit::archive::build(&pool, from, to).await
+        .map_err(|e| AppError::Session(format!("Could not build audit archive: {e}")))?;
+    let file_name = crate::audit::archive::file_name(from, to);
+
+    let user_id = get_user_id(&session).unwrap_or(0);
+    let details = serde_json::json!({
+        "from": from.to_string(),
+        "to": to.to_string(),
+        "summary": format!("Audit archive exported for {} to {}", from, to)
+    });
+    let _ = crate::audit::log(&pool, user_id, "audit.archive_exported", "audit", 0, details).await;
+
+    checksummed_download(&pool, user_id, "audit_archive", &file_name, "application/zip", "attachment", bytes).await
+}
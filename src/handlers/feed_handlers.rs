@@ -0,0 +1,40 @@
+use actix_web::{web, HttpRequest, HttpResponse};
+use sqlx::PgPool;
+
+use crate::errors::AppError;
+use crate::models::{feed, setting, tor};
+use crate::models::feed::{FeedFormat, FeedKind};
+
+/// The application's origin as seen by the client, for absolute feed links.
+pub fn base_url(req: &HttpRequest) -> String {
+    let info = req.connection_info();
+    format!("{}://{}", info.scheme(), info.host())
+}
+
+/// Whether a ToR publishes its feeds without authentication.
+pub async fn is_public(pool: &PgPool, tor_id: i64) -> bool {
+    setting::get_value_for_tor(pool, tor_id, "feeds.public", "false").await == "true"
+}
+
+/// GET /feeds/tor/{id}/{decisions|minutes}.{atom|rss} — public feed of a
+/// ToR's decisions or approved minutes. Not found unless the ToR enables
+/// `feeds.public`.
+pub async fn public_feed(
+    pool: web::Data<PgPool>,
+    req: HttpRequest,
+    path: web::Path<(i64, String, String)>,
+) -> Result<HttpResponse, AppError> {
+    let (tor_id, kind, ext) = path.into_inner();
+    let kind = FeedKind::from_key(&kind).ok_or(AppError::NotFound)?;
+    let format = FeedFormat::from_extension(&ext).ok_or(AppError::NotFound)?;
+    let tor_label = tor::find_detail_by_id(&pool, tor_id).await?
+        .ok_or(AppError::NotFound)?
+        .label;
+    if !is_public(&pool, tor_id).await {
+        return Err(AppError::NotFound);
+    }
+    let feed = feed::build(&pool, kind, Some((tor_id, &tor_label)), &base_url(&req), req.path()).await?;
+    Ok(HttpResponse::Ok()
+        .content_type(format.content_type())
+        .body(feed.render(format)))
+}
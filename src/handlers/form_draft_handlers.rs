@@ -0,0 +1,116 @@
+use actix_session::{Session, SessionExt};
+use actix_web::{
+    Error, HttpResponse, web,
+    body::MessageBody,
+    dev::{ServiceRequest, ServiceResponse},
+    http::{header, Method},
+    middleware::Next,
+};
+use serde::Deserialize;
+use sqlx::PgPool;
+
+use crate::auth::csrf;
+use crate::auth::session::get_user_id;
+use crate::errors::AppError;
+use crate::models::form_draft;
+
+#[derive(Deserialize)]
+pub struct SaveDraftRequest {
+    pub csrf_token: String,
+    pub form_key: String,
+    pub page_path: String,
+    #[serde(default)]
+    pub title: String,
+    pub fields: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Deserialize)]
+pub struct DiscardDraftRequest {
+    pub csrf_token: String,
+    pub form_key: String,
+}
+
+#[derive(Deserialize)]
+pub struct DraftQuery {
+    pub key: String,
+}
+
+fn current_user(session: &Session) -> Result<i64, AppError> {
+    get_user_id(session).ok_or(AppError::Session("User not logged in".to_string()))
+}
+
+/// POST /form-drafts — snapshot a form being edited (JSON).
+pub async fn save(
+    pool: web::Data<PgPool>,
+    session: Session,
+    body: web::Json<SaveDraftRequest>,
+) -> Result<HttpResponse, AppError> {
+    let user_id = current_user(&session)?;
+    csrf::validate_csrf(&session, &body.csrf_token)?;
+
+    let fields = match form_draft::validate(&body.form_key, &body.page_path, &body.fields) {
+        Ok(fields) => fields,
+        Err(error) => return Ok(HttpResponse::BadRequest().json(serde_json::json!({ "error": error }))),
+    };
+    form_draft::save(&pool, user_id, &body.form_key, &body.page_path, body.title.trim(), &fields).await?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// GET /form-drafts?key= — the user's snapshot of a form, if any (JSON).
+pub async fn show(
+    pool: web::Data<PgPool>,
+    session: Session,
+    query: web::Query<DraftQuery>,
+) -> Result<HttpResponse, AppError> {
+    let user_id = current_user(&session)?;
+    let draft = form_draft::find(&pool, user_id, &query.key).await?
+        .ok_or(AppError::NotFound)?;
+    let fields: serde_json::Value = serde_json::from_str(&draft.fields).unwrap_or_default();
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "form_key": draft.form_key,
+        "fields": fields,
+        "updated_at": draft.updated_at,
+    })))
+}
+
+/// POST /form-drafts/discard — drop a snapshot the user declined to restore (JSON).
+pub async fn discard(
+    pool: web::Data<PgPool>,
+    session: Session,
+    body: web::Json<DiscardDraftRequest>,
+) -> Result<HttpResponse, AppError> {
+    let user_id = current_user(&session)?;
+    csrf::validate_csrf(&session, &body.csrf_token)?;
+    form_draft::discard(&pool, user_id, &body.form_key).await?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Middleware: a form post that succeeds (redirects, as every form handler
+/// does on success) makes the snapshot of that form obsolete. A post that
+/// re-renders the form with errors keeps it.
+pub async fn clear_on_submit(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    let submitted = req.method() == Method::POST
+        && req.headers().get(header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|ct| ct.starts_with("application/x-www-form-urlencoded") || ct.starts_with("multipart/form-data"));
+    let user_id = if submitted { get_user_id(&req.get_session()) } else { None };
+    let pool = req.app_data::<web::Data<PgPool>>().cloned();
+    let form_key = req.path().to_string();
+
+    let res = next.call(req).await?;
+
+    if let (Some(user_id), Some(pool)) = (user_id, pool) {
+        let to_login = res.headers().get(header::LOCATION)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|loc| loc.starts_with("/login"));
+        if res.status().is_redirection() && !to_login
+            && let Err(e) = form_draft::discard(&pool, user_id, &form_key).await
+        {
+            log::warn!("Failed to clear form draft for {}: {}", form_key, e);
+        }
+    }
+    Ok(res)
+}
@@ -0,0 +1,204 @@
+use std::collections::HashMap;
+
+use actix_session::Session;
+use actix_web::{web, HttpResponse};
+use sqlx::PgPool;
+
+use crate::auth::csrf;
+use crate::auth::session::{get_user_id, require_permission};
+use crate::errors::{AppError, render};
+use crate::handlers::auth_handlers::CsrfOnly;
+use crate::models::{glossary, tor};
+use crate::templates_structs::{GlossaryTemplate, PageContext};
+
+/// Permission needed to edit the glossary at this scope.
+fn edit_permission(tor_id: Option<i64>) -> &'static str {
+    if tor_id.is_some() { "tor.edit" } else { "settings.manage" }
+}
+
+fn redirect(tor_id: Option<i64>) -> HttpResponse {
+    let location = match tor_id {
+        Some(tor_id) => format!("/tor/{tor_id}/glossary"),
+        None => "/glossary".to_string(),
+    };
+    HttpResponse::SeeOther().insert_header(("Location", location)).finish()
+}
+
+async fn render_list(pool: &PgPool, session: &Session, tor_id: Option<i64>, errors: Vec<String>) -> Result<HttpResponse, AppError> {
+    let global = glossary::find_global(pool).await?;
+    let tmpl = match tor_id {
+        Some(tor_id) => {
+            let tor_label = tor::get_tor_name(pool, tor_id).await?;
+            let terms = glossary::find_tor_terms(pool, tor_id).await?;
+            let inherited = global.into_iter()
+                .filter(|g| !terms.iter().any(|t| t.term.to_lowercase() == g.term.to_lowercase()))
+                .collect();
+            let ctx = PageContext::build(session, pool, "/tor").await?
+                .with_tor(tor_id, &tor_label, "glossary");
+            GlossaryTemplate { ctx, tor_id: Some(tor_id), tor_label, terms, inherited, errors }
+        }
+        None => {
+            let ctx = PageContext::build(session, pool, "/glossary").await?;
+            GlossaryTemplate { ctx, tor_id: None, tor_label: String::new(), terms: global, inherited: vec![], errors }
+        }
+    };
+    render(tmpl)
+}
+
+/// Load a term and check it belongs to this glossary.
+async fn find_term(pool: &PgPool, tor_id: Option<i64>, term_id: i64) -> Result<glossary::GlossaryTerm, AppError> {
+    glossary::find_by_id(pool, term_id).await?
+        .filter(|t| t.tor_id == tor_id)
+        .ok_or(AppError::NotFound)
+}
+
+async fn create_term(pool: &PgPool, session: &Session, tor_id: Option<i64>, form: &HashMap<String, String>) -> Result<HttpResponse, AppError> {
+    require_permission(session, edit_permission(tor_id))?;
+    csrf::validate_csrf(session, form.get("csrf_token").map(String::as_str).unwrap_or(""))?;
+    let user_id = get_user_id(session).ok_or(AppError::Session("User not logged in".to_string()))?;
+
+    let term = form.get("term").map(String::as_str).unwrap_or("");
+    let definition = form.get("definition").map(String::as_str).unwrap_or("");
+    let errors = glossary::validate(pool, tor_id, term, definition, None).await?;
+    if !errors.is_empty() {
+        return render_list(pool, session, tor_id, errors).await;
+    }
+    let id = glossary::create(pool, tor_id, term, definition).await?;
+
+    let details = serde_json::json!({
+        "tor_id": tor_id,
+        "summary": format!("Added '{}' to the glossary", term.trim())
+    });
+    let _ = crate::audit::log(pool, user_id, "glossary.term_created", glossary::ENTITY_TYPE, id, details).await;
+
+    let _ = session.insert("flash", format!("'{}' added to the glossary", term.trim()));
+    Ok(redirect(tor_id))
+}
+
+async fn update_term(pool: &PgPool, session: &Session, tor_id: Option<i64>, term_id: i64, form: &HashMap<String, String>) -> Result<HttpResponse, AppError> {
+    require_permission(session, edit_permission(tor_id))?;
+    csrf::validate_csrf(session, form.get("csrf_token").map(String::as_str).unwrap_or(""))?;
+    let user_id = get_user_id(session).ok_or(AppError::Session("User not logged in".to_string()))?;
+    let existing = find_term(pool, tor_id, term_id).await?;
+
+    let term = form.get("term").map(String::as_str).unwrap_or("");
+    let definition = form.get("definition").map(String::as_str).unwrap_or("");
+    let errors = glossary::validate(pool, tor_id, term, definition, Some(existing.id)).await?;
+    if !errors.is_empty() {
+        return render_list(pool, session, tor_id, errors).await;
+    }
+    glossary::update(pool, &existing, term, definition).await?;
+
+    let details = serde_json::json!({
+        "tor_id": tor_id,
+        "old_term": existing.term,
+        "old_definition": existing.definition,
+        "summary": format!("Updated glossary term '{}'", term.trim())
+    });
+    let _ = crate::audit::log(pool, user_id, "glossary.term_updated", glossary::ENTITY_TYPE, existing.id, details).await;
+
+    let _ = session.insert("flash", format!("'{}' updated", term.trim()));
+    Ok(redirect(tor_id))
+}
+
+async fn delete_term(pool: &PgPool, session: &Session, tor_id: Option<i64>, term_id: i64, csrf_token: &str) -> Result<HttpResponse, AppError> {
+    require_permission(session, edit_permission(tor_id))?;
+    csrf::validate_csrf(session, csrf_token)?;
+    let user_id = get_user_id(session).ok_or(AppError::Session("User not logged in".to_string()))?;
+    let existing = find_term(pool, tor_id, term_id).await?;
+
+    glossary::delete(pool, existing.id).await?;
+
+    let details = serde_json::json!({
+        "tor_id": tor_id,
+        "term": existing.term,
+        "summary": format!("Removed '{}' from the glossary", existing.term)
+    });
+    let _ = crate::audit::log(pool, user_id, "glossary.term_deleted", glossary::ENTITY_TYPE, existing.id, details).await;
+
+    let _ = session.insert("flash", format!("'{}' removed from the glossary", existing.term));
+    Ok(redirect(tor_id))
+}
+
+/// GET /glossary — terms defined for every ToR.
+pub async fn list(
+    pool: web::Data<PgPool>,
+    session: Session,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    render_list(&pool, &session, None, Vec::new()).await
+}
+
+/// POST /glossary — add a global term.
+pub async fn create(
+    pool: web::Data<PgPool>,
+    session: Session,
+    form: web::Form<HashMap<String, String>>,
+) -> Result<HttpResponse, AppError> {
+    create_term(&pool, &session, None, &form).await
+}
+
+/// POST /glossary/{id} — update a global term.
+pub async fn update(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+    form: web::Form<HashMap<String, String>>,
+) -> Result<HttpResponse, AppError> {
+    update_term(&pool, &session, None, path.into_inner(), &form).await
+}
+
+/// POST /glossary/{id}/delete — remove a global term.
+pub async fn delete(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+    form: web::Form<CsrfOnly>,
+) -> Result<HttpResponse, AppError> {
+    delete_term(&pool, &session, None, path.into_inner(), &form.csrf_token).await
+}
+
+/// GET /tor/{id}/glossary — the ToR's own terms and the global terms it uses.
+pub async fn tor_list(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "tor.list")?;
+    let tor_id = path.into_inner();
+    let user_id = get_user_id(&session).ok_or(AppError::Session("User not logged in".to_string()))?;
+    tor::require_tor_membership(&pool, user_id, tor_id).await?;
+    render_list(&pool, &session, Some(tor_id), Vec::new()).await
+}
+
+/// POST /tor/{id}/glossary — add a term to the ToR's glossary.
+pub async fn tor_create(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+    form: web::Form<HashMap<String, String>>,
+) -> Result<HttpResponse, AppError> {
+    create_term(&pool, &session, Some(path.into_inner()), &form).await
+}
+
+/// POST /tor/{id}/glossary/{term_id} — update one of the ToR's terms.
+pub async fn tor_update(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<(i64, i64)>,
+    form: web::Form<HashMap<String, String>>,
+) -> Result<HttpResponse, AppError> {
+    let (tor_id, term_id) = path.into_inner();
+    update_term(&pool, &session, Some(tor_id), term_id, &form).await
+}
+
+/// POST /tor/{id}/glossary/{term_id}/delete — remove one of the ToR's terms.
+pub async fn tor_delete(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<(i64, i64)>,
+    form: web::Form<CsrfOnly>,
+) -> Result<HttpResponse, AppError> {
+    let (tor_id, term_id) = path.into_inner();
+    delete_term(&pool, &session, Some(tor_id), term_id, &form.csrf_token).await
+}
@@ -0,0 +1,229 @@
+use actix_session::Session;
+use actix_web::{web, HttpResponse};
+use serde::Deserialize;
+use sqlx::PgPool;
+
+use crate::models::distribution_group::{self, DistributionGroup, GroupRule};
+use crate::auth::csrf;
+use crate::auth::session::{get_user_id, require_permission};
+use crate::errors::{AppError, render};
+use crate::handlers::auth_handlers::CsrfOnly;
+use crate::handlers::warning_handlers::ws::ConnectionMap;
+use crate::templates_structs::{DistributionGroupTemplate, DistributionGroupsTemplate, PageContext};
+
+#[derive(Deserialize)]
+pub struct GroupForm {
+    pub csrf_token: String,
+    pub label: String,
+    #[serde(default)]
+    pub description: String,
+    /// ToR id, or empty for every ToR.
+    #[serde(default)]
+    pub tor_id: String,
+    #[serde(default)]
+    pub position_match: String,
+    #[serde(default)]
+    pub membership_type: String,
+}
+
+#[derive(Deserialize)]
+pub struct ExportQuery {
+    pub format: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct NotifyForm {
+    pub csrf_token: String,
+    pub message: String,
+}
+
+async fn render_list(pool: &PgPool, session: &Session, errors: Vec<String>) -> Result<HttpResponse, AppError> {
+    let ctx = PageContext::build(session, pool, "/distribution-groups").await?;
+    let mut groups = Vec::new();
+    for group in distribution_group::find_all(pool).await? {
+        let count = distribution_group::find_members(pool, &group.rule).await?.len();
+        groups.push((group, count));
+    }
+    render(DistributionGroupsTemplate { ctx, groups, errors })
+}
+
+async fn find_group(pool: &PgPool, key: &str) -> Result<DistributionGroup, AppError> {
+    distribution_group::find_by_key(pool, key).await?.ok_or(AppError::NotFound)
+}
+
+fn redirect(location: &str) -> HttpResponse {
+    HttpResponse::SeeOther()
+        .insert_header(("Location", location.to_string()))
+        .finish()
+}
+
+/// GET /distribution-groups — built-in and custom groups with member counts.
+pub async fn list(
+    pool: web::Data<PgPool>,
+    session: Session,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "tor.list")?;
+    render_list(&pool, &session, Vec::new()).await
+}
+
+/// POST /distribution-groups — define a group by position rule.
+pub async fn create(
+    pool: web::Data<PgPool>,
+    session: Session,
+    form: web::Form<GroupForm>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "tor.edit")?;
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+
+    let rule = GroupRule {
+        tor_id: form.tor_id.parse().ok(),
+        position_match: form.position_match.trim().to_string(),
+        membership_type: form.membership_type.clone(),
+    };
+    let errors = distribution_group::validate(&form.label, &rule);
+    if !errors.is_empty() {
+        return render_list(&pool, &session, errors).await;
+    }
+
+    let id = distribution_group::create(&pool, &form.label, &form.description, &rule).await?;
+    let user_id = get_user_id(&session).unwrap_or(0);
+    let details = serde_json::json!({
+        "tor_id": rule.tor_id,
+        "position_match": rule.position_match,
+        "membership_type": rule.membership_type,
+        "summary": format!("Created distribution group '{}'", form.label.trim())
+    });
+    let _ = crate::audit::log(&pool, user_id, "distribution_group.created", "distribution_group", id, details).await;
+
+    let _ = session.insert("flash", "Distribution group created");
+    Ok(redirect(&format!("/distribution-groups/{}", id)))
+}
+
+/// GET /distribution-groups/{key} — current members of a group.
+pub async fn detail(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<String>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "tor.list")?;
+    let group = find_group(&pool, &path.into_inner()).await?;
+    let members = distribution_group::find_members(&pool, &group.rule).await?;
+    let ctx = PageContext::build(&session, &pool, "/distribution-groups").await?;
+    render(DistributionGroupTemplate { ctx, group, members })
+}
+
+/// GET /distribution-groups/{key}/export?format=txt|csv — address list for
+/// external mail clients.
+pub async fn export(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<String>,
+    query: web::Query<ExportQuery>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "tor.list")?;
+    let group = find_group(&pool, &path.into_inner()).await?;
+    let members = distribution_group::find_members(&pool, &group.rule).await?;
+
+    let format = if query.format.as_deref() == Some("csv") { "csv" } else { "txt" };
+    let user_id = get_user_id(&session).unwrap_or(0);
+    let watermark = crate::handlers::export_handlers::authorize_export(&pool, user_id, format).await?;
+    let details = serde_json::json!({
+        "group": group.key,
+        "format": format,
+        "count": members.len(),
+        "summary": format!("Exported address list for '{}'", group.label)
+    });
+    let _ = crate::audit::log(&pool, user_id, "distribution_group.exported", "distribution_group", group.id.unwrap_or(0), details).await;
+
+    let (body, content_type) = if format == "csv" {
+        let csv = distribution_group::to_csv(&members);
+        (match watermark { Some(mark) => mark.stamp_csv(csv), None => csv }, "text/csv; charset=utf-8")
+    } else {
+        (distribution_group::to_address_list(&members), "text/plain; charset=utf-8")
+    };
+    crate::handlers::export_handlers::checksummed_download(
+        &pool,
+        user_id,
+        "address_list",
+        &format!("distribution-group-{}.{}", group.key, format),
+        content_type,
+        "attachment",
+        body.into_bytes(),
+    ).await
+}
+
+/// POST /distribution-groups/{key}/notify — send an in-app notification to
+/// every current member.
+pub async fn notify(
+    pool: web::Data<PgPool>,
+    conn_map: web::Data<ConnectionMap>,
+    session: Session,
+    path: web::Path<String>,
+    form: web::Form<NotifyForm>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "tor.edit")?;
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+    let group = find_group(&pool, &path.into_inner()).await?;
+    let location = format!("/distribution-groups/{}", group.key);
+
+    let message = form.message.trim();
+    if message.is_empty() {
+        let _ = session.insert("flash", "Enter a message to send");
+        return Ok(redirect(&location));
+    }
+    let recipients: Vec<i64> = distribution_group::find_members(&pool, &group.rule).await?
+        .iter()
+        .map(|m| m.user_id)
+        .collect();
+    if recipients.is_empty() {
+        let _ = session.insert("flash", "This group has no members");
+        return Ok(redirect(&location));
+    }
+
+    let user_id = get_user_id(&session).unwrap_or(0);
+    let details = serde_json::json!({
+        "group": group.key,
+        "group_label": group.label,
+        "sender_id": user_id,
+    });
+    let wid = crate::warnings::create_warning(
+        &pool, "info", "governance", "manual.distribution_group", message, &details.to_string(), "system",
+    ).await?;
+    crate::warnings::create_receipts(&pool, wid, &recipients).await?;
+    crate::warnings::outbox::drain(&pool, &conn_map).await;
+
+    let audit = serde_json::json!({
+        "group": group.key,
+        "recipients": recipients.len(),
+        "warning_id": wid,
+        "summary": format!("Notified {} member(s) of '{}'", recipients.len(), group.label)
+    });
+    let _ = crate::audit::log(&pool, user_id, "distribution_group.notified", "distribution_group", group.id.unwrap_or(0), audit).await;
+
+    let _ = session.insert("flash", format!("Notification sent to {} member(s)", recipients.len()));
+    Ok(redirect(&location))
+}
+
+/// POST /distribution-groups/{key}/delete — remove a custom group.
+pub async fn delete(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<String>,
+    form: web::Form<CsrfOnly>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "tor.edit")?;
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+    let group = find_group(&pool, &path.into_inner()).await?;
+    // Built-in groups follow their ToR and cannot be removed
+    let id = group.id.ok_or(AppError::NotFound)?;
+
+    distribution_group::delete(&pool, id).await?;
+    let user_id = get_user_id(&session).unwrap_or(0);
+    let details = serde_json::json!({
+        "summary": format!("Deleted distribution group '{}'", group.label)
+    });
+    let _ = crate::audit::log(&pool, user_id, "distribution_group.deleted", "distribution_group", id, details).await;
+
+    let _ = session.insert("flash", "Distribution group deleted");
+    Ok(redirect("/distribution-groups"))
+}
@@ -0,0 +1,84 @@
+use actix_session::Session;
+use actix_web::{web, HttpResponse};
+use sqlx::PgPool;
+
+use crate::models::{proposal, tor};
+use crate::auth::session::{get_user_id, require_permission};
+use crate::errors::{AppError, render};
+use crate::templates_structs::{PageContext, TorImpactTemplate};
+
+/// GET /tor/{id}/impact — what stalls downstream if this ToR is suspended.
+pub async fn tor_impact(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "tor.list")?;
+    let tor_id = path.into_inner();
+    let user_id = get_user_id(&session).ok_or(AppError::Session("User not logged in".to_string()))?;
+    tor::require_tor_membership(&pool, user_id, tor_id).await?;
+
+    let analysis = tor::analyse_impact(&pool, tor_id).await?.ok_or(AppError::NotFound)?;
+    let tor_name = tor::get_tor_name(&pool, tor_id).await?;
+    let ctx = PageContext::build(&session, &pool, "/tor").await?
+        .with_tor(tor_id, &tor_name, "impact");
+
+    render(TorImpactTemplate {
+        ctx,
+        tor_id,
+        subject: format!("Suspending {}", tor_name),
+        tor_name,
+        proposal: None,
+        spawned_items: Vec::new(),
+        analysis,
+    })
+}
+
+/// GET /tor/{id}/proposals/{proposal_id}/impact — what a rejection holds up:
+/// agenda points already spawned from the proposal, and the ToRs that depend
+/// on this ToR's output.
+pub async fn proposal_impact(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<(i64, i64)>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "proposal.view")?;
+    let (tor_id, proposal_id) = path.into_inner();
+    let user_id = get_user_id(&session).ok_or(AppError::Session("User not logged in".to_string()))?;
+    tor::require_tor_membership(&pool, user_id, tor_id).await?;
+
+    if !proposal::belongs_to_tor(&pool, proposal_id, tor_id).await? {
+        return Err(AppError::NotFound);
+    }
+    let p = proposal::find_by_id(&pool, proposal_id).await?.ok_or(AppError::NotFound)?;
+    let analysis = tor::analyse_impact(&pool, tor_id).await?.ok_or(AppError::NotFound)?;
+    let spawned_items = tor::find_open_agenda_points_for_proposal(&pool, proposal_id).await?;
+    let tor_name = tor::get_tor_name(&pool, tor_id).await?;
+    let ctx = PageContext::build(&session, &pool, "/workflow").await?
+        .with_tor(tor_id, &tor_name, "workflow");
+
+    render(TorImpactTemplate {
+        ctx,
+        tor_id,
+        subject: format!("Rejecting \u{201c}{}\u{201d}", p.title),
+        tor_name,
+        proposal: Some((p.id, p.title)),
+        spawned_items,
+        analysis,
+    })
+}
+
+/// GET /api/tor/{id}/impact — the impact subgraph for the governance map renderer.
+pub async fn impact_graph_api(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "tor.list")?;
+    let tor_id = path.into_inner();
+    let user_id = get_user_id(&session).ok_or(AppError::Session("User not logged in".to_string()))?;
+    tor::require_tor_membership(&pool, user_id, tor_id).await?;
+
+    let analysis = tor::analyse_impact(&pool, tor_id).await?.ok_or(AppError::NotFound)?;
+    Ok(HttpResponse::Ok().json(analysis))
+}
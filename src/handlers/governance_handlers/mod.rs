@@ -1,2 +1,5 @@
+pub mod distribution_groups;
+pub mod impact;
 pub mod map;
+pub use impact::*;
 pub use map::*;
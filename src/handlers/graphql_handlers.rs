@@ -0,0 +1,25 @@
+use actix_session::Session;
+use actix_web::{web, HttpResponse};
+use sqlx::PgPool;
+
+use crate::auth::session::{get_permissions, get_user_id};
+use crate::errors::AppError;
+use crate::graphql;
+use crate::models::setting;
+
+/// POST /api/graphql - Run a read-only GraphQL query over the entity graph
+/// as the signed-in caller. Not found unless `api.graphql_enabled` is on.
+pub async fn execute(
+    pool: web::Data<PgPool>,
+    session: Session,
+    body: web::Json<async_graphql::Request>,
+) -> Result<HttpResponse, AppError> {
+    if !setting::get_bool(&pool, "api.graphql_enabled").await {
+        return Err(AppError::NotFound);
+    }
+    let user_id = get_user_id(&session).ok_or(AppError::Session("User not logged in".to_string()))?;
+    let permissions = get_permissions(&session)
+        .map_err(|e| AppError::Session(format!("Failed to get permissions: {}", e)))?;
+    let response = graphql::execute(&pool, user_id, permissions, body.into_inner()).await;
+    Ok(HttpResponse::Ok().json(response))
+}
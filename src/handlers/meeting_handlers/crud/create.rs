@@ -12,6 +12,7 @@ use crate::auth::csrf;
 use crate::auth::session::get_user_id;
 use crate::errors::AppError;
 use crate::models::meeting;
+use crate::models::tor::standing_items;
 
 use super::forms::{ConfirmForm, CalendarConfirmForm};
 use super::helpers::parse_and_validate_date;
@@ -58,14 +59,16 @@ pub async fn confirm(
     ).await?;
 
     // Immediately transition to "confirmed" status.
-    meeting::update_status(&pool, meeting_id, "confirmed").await?;
+    let current_user_id = get_user_id(&session).unwrap_or(0);
+    meeting::transition(&pool, meeting_id, "confirmed", current_user_id).await?;
+    let standing = standing_items::instantiate(&pool, tor_id, meeting_id, &form.meeting_date, current_user_id).await?;
 
     // Audit
-    let current_user_id = get_user_id(&session).unwrap_or(0);
     let details = serde_json::json!({
         "meeting_id": meeting_id,
         "tor_id": tor_id,
         "meeting_date": &form.meeting_date,
+        "standing_items": standing.len(),
         "summary": format!("Meeting confirmed for {} on {}", &form.tor_name, &form.meeting_date),
     });
     let _ = crate::audit::log(
@@ -178,7 +181,7 @@ pub async fn confirm_calendar(
                 .content_type("application/json")
                 .body(serde_json::json!({"ok": false, "error": format!("Meeting is already '{}' and cannot be confirmed", existing.status)}).to_string()));
         }
-        match meeting::update_status(&pool, mid, "confirmed").await {
+        match meeting::transition(&pool, mid, "confirmed", current_user_id).await {
             Ok(_) => mid,
             Err(_) => {
                 return Ok(HttpResponse::InternalServerError()
@@ -196,7 +199,7 @@ pub async fn confirm_calendar(
                     .body(serde_json::json!({"ok": false, "error": "Failed to create meeting"}).to_string()));
             }
         };
-        match meeting::update_status(&pool, mid, "confirmed").await {
+        match meeting::transition(&pool, mid, "confirmed", current_user_id).await {
             Ok(_) => mid,
             Err(_) => {
                 return Ok(HttpResponse::InternalServerError()
@@ -205,11 +208,20 @@ pub async fn confirm_calendar(
             }
         }
     };
+    let standing = match standing_items::instantiate(&pool, tor_id, meeting_id, &form.meeting_date, current_user_id).await {
+        Ok(created) => created,
+        Err(_) => {
+            return Ok(HttpResponse::InternalServerError()
+                .content_type("application/json")
+                .body(serde_json::json!({"ok": false, "error": "Failed to add standing agenda items"}).to_string()));
+        }
+    };
 
     let details = serde_json::json!({
         "meeting_id": meeting_id,
         "tor_id": tor_id,
         "meeting_date": &form.meeting_date,
+        "standing_items": standing.len(),
         "summary": format!("Meeting confirmed for {} on {}", &form.tor_name, &form.meeting_date),
     });
     let _ = crate::audit::log(
@@ -47,3 +47,16 @@ pub struct RollCallForm {
     pub csrf_token: String,
     pub roll_call_data: String, // raw JSON string from hidden input
 }
+
+/// `?print=1` on the agenda, minutes and decision register pages selects
+/// their print layout.
+#[derive(serde::Deserialize, Default)]
+pub struct PrintQuery {
+    pub print: Option<String>,
+}
+
+impl PrintQuery {
+    pub fn is_print(&self) -> bool {
+        matches!(self.print.as_deref(), Some("1") | Some("true"))
+    }
+}
@@ -3,7 +3,7 @@
 /// This module organizes meeting handlers by operation type:
 /// - `read.rs`: GET detail view
 /// - `create.rs`: POST confirm, confirm_calendar
-/// - `update.rs`: POST transition, agenda management, minutes generation, roll call
+/// - `update.rs`: POST transition, agenda management, carry-over, minutes generation, roll call
 /// - `forms.rs`: Form structures for deserialization (shared across handlers)
 /// - `helpers.rs`: Shared validation and ToR boundary checks
 ///
@@ -20,8 +20,8 @@ pub mod update;
 pub use read::detail;
 pub use create::{confirm, confirm_calendar};
 pub use update::{
-    transition, assign_agenda, remove_agenda, generate_minutes, save_roll_call,
+    transition, assign_agenda, remove_agenda, carry_over, generate_minutes, save_roll_call,
 };
 pub use forms::{
-    ConfirmForm, CalendarConfirmForm, TransitionForm, AgendaForm, CsrfOnly, RollCallForm,
+    ConfirmForm, CalendarConfirmForm, TransitionForm, AgendaForm, CsrfOnly, RollCallForm, PrintQuery,
 };
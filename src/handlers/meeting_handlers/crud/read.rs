@@ -4,18 +4,20 @@
 
 use actix_session::Session;
 use actix_web::{web, HttpResponse};
-use std::collections::HashMap;
 use sqlx::PgPool;
 
 use crate::auth::abac;
 use crate::auth::session::{get_permissions, get_user_id, require_permission};
 use crate::errors::{render, AppError};
+use crate::handlers::tor_handlers;
 use crate::models::meeting;
 use crate::models::minutes;
 use crate::models::protocol;
 use crate::models::tor;
 use crate::models::workflow;
-use crate::templates_structs::{MeetingDetailTemplate, PageContext};
+use crate::templates_structs::{AgendaPrintTemplate, MeetingDetailTemplate, PageContext, PrintMeta};
+
+use super::forms::PrintQuery;
 
 // ---------------------------------------------------------------------------
 // GET — meeting detail
@@ -27,14 +29,18 @@ use crate::templates_structs::{MeetingDetailTemplate, PageContext};
 /// - Meeting metadata (date, status, location, etc.)
 /// - Agenda points assigned to this meeting
 /// - Unassigned agenda points available for this ToR
+/// - Undecided points that can be carried over (completed meetings)
 /// - Protocol steps
 /// - Available workflow transitions
 /// - Existing minutes (if any)
 /// - User capabilities (ABAC) for conditional UI rendering
+///
+/// With `?print=1`, renders the agenda alone in the print layout.
 pub async fn detail(
     pool: web::Data<PgPool>,
     session: Session,
     path: web::Path<(i64, i64)>,
+    query: web::Query<PrintQuery>,
 ) -> Result<HttpResponse, AppError> {
     require_permission(&session, "meetings.view")?;
     let (tor_id, mid) = path.into_inner();
@@ -50,12 +56,32 @@ pub async fn detail(
 
     tor::require_tor_membership(&pool, user_id, tor_id).await?;
 
+    if query.is_print() {
+        let agenda_points = meeting::find_agenda_points(&pool, mid).await?;
+        let meta = PrintMeta::build(&pool).await;
+        return render(AgendaPrintTemplate { meta, meeting, agenda_points });
+    }
+
     let tor_name = tor::get_tor_name(&pool, tor_id).await?;
-    let ctx = PageContext::build(&session, &pool, "/meetings").await?
+    let mut ctx = PageContext::build(&session, &pool, "/meetings").await?
         .with_tor(tor_id, &tor_name, "meetings");
+    if tor_handlers::access_log::track_view(&pool, &session, mid, tor_id).await? {
+        ctx = ctx.with_access_log(mid);
+    }
 
     let agenda_points = meeting::find_agenda_points(&pool, mid).await?;
     let unassigned_points = meeting::find_unassigned_agenda_points(&pool, tor_id).await?;
+    // Carry-over step: undecided points of a completed meeting not yet deferred
+    let (undecided_points, carry_over_targets) = if meeting.status == "completed" {
+        let undecided = agenda_points.iter()
+            .filter(|p| p.is_undecided() && !meeting.is_carried_over(p.id))
+            .cloned()
+            .collect();
+        let targets = meeting::find_carry_over_targets(&pool, tor_id, &meeting.meeting_date).await?;
+        (undecided, targets)
+    } else {
+        (Vec::new(), Vec::new())
+    };
     let protocol_steps = protocol::find_steps_for_tor(&pool, tor_id).await?;
     let permissions = get_permissions(&session)
         .map_err(|e| AppError::Session(format!("Failed to get permissions: {}", e)))?;
@@ -64,9 +90,10 @@ pub async fn detail(
         "meeting",
         &meeting.status,
         &permissions,
-        &HashMap::new(),
+        mid,
     ).await?;
     let existing_minutes = minutes::find_by_meeting(&pool, mid).await?;
+    let pack = meeting::find_pack_documents(&pool, mid).await?;
     let tor_capabilities = abac::load_tor_capabilities(&pool, user_id, tor_id)
         .await
         .unwrap_or_default();
@@ -76,9 +103,12 @@ pub async fn detail(
         meeting,
         agenda_points,
         unassigned_points,
+        undecided_points,
+        carry_over_targets,
         protocol_steps,
         transitions,
         minutes: existing_minutes,
+        pack,
         tor_id,
         tor_capabilities,
     };
@@ -1,7 +1,7 @@
-/// Meeting update/mutation operations.
-///
-/// Handles POST requests for lifecycle transitions, agenda management,
-/// minutes generation, and roll call data updates.
+//! Meeting update/mutation operations.
+//!
+//! Handles POST requests for lifecycle transitions, agenda management,
+//! carry-over of undecided points, minutes generation, and roll call data updates.
 
 use actix_session::Session;
 use actix_web::{web, HttpResponse};
@@ -45,20 +45,19 @@ pub async fn transition(
     let permissions = get_permissions(&session)
         .map_err(|e| AppError::Session(format!("Failed to get permissions: {}", e)))?;
 
-    // Validate the transition via the workflow engine (returns error if invalid).
-    workflow::validate_transition(
+    // Take the transition via the workflow engine (returns error if invalid).
+    let current_user_id = get_user_id(&session).unwrap_or(0);
+    workflow::execute_transition(
         &pool,
         "meeting",
+        mid,
         &meeting_detail.status,
         &form.new_status,
         &permissions,
-        &HashMap::new(),
+        current_user_id,
     ).await?;
 
-    meeting::update_status(&pool, mid, &form.new_status).await?;
-
     // Audit
-    let current_user_id = get_user_id(&session).unwrap_or(0);
     let details = serde_json::json!({
         "meeting_id": mid,
         "tor_id": tor_id,
@@ -75,10 +74,21 @@ pub async fn transition(
         details,
     ).await;
 
-    let _ = session.insert(
-        "flash",
-        format!("Meeting status changed to {}", &form.new_status),
-    );
+    // Completing a meeting with open decisions starts the carry-over step.
+    let mut flash = format!("Meeting status changed to {}", &form.new_status);
+    if form.new_status == "completed" {
+        let undecided = meeting::find_agenda_points(&pool, mid).await?
+            .iter()
+            .filter(|p| p.is_undecided())
+            .count();
+        if undecided > 0 {
+            flash.push_str(&format!(
+                ". {} undecided agenda point(s) can be carried over to the next meeting.",
+                undecided
+            ));
+        }
+    }
+    let _ = session.insert("flash", flash);
     Ok(HttpResponse::SeeOther()
         .insert_header(("Location", format!("/tor/{}/meetings/{}", tor_id, mid)))
         .finish())
@@ -174,6 +184,91 @@ pub async fn remove_agenda(
         .finish())
 }
 
+// ---------------------------------------------------------------------------
+// POST — carry over undecided agenda points
+// ---------------------------------------------------------------------------
+
+/// POST /tor/{id}/meetings/{mid}/carry-over — defer undecided points to a later meeting.
+///
+/// Only for completed meetings. The form names the target meeting
+/// (`target_meeting_id`) and one `carry_{agenda_point_id}` field per selected
+/// point. Existing minutes are annotated with the deferral.
+pub async fn carry_over(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<(i64, i64)>,
+    form: web::Form<HashMap<String, String>>,
+) -> Result<HttpResponse, AppError> {
+    csrf::validate_csrf(&session, form.get("csrf_token").map(|s| s.as_str()).unwrap_or(""))?;
+
+    let (tor_id, mid) = path.into_inner();
+    abac::require_tor_capability(&pool, &session, tor_id, "can_manage_agenda").await?;
+
+    validate_meeting_tor_ownership(&pool, mid, tor_id).await?;
+    let meeting_detail = meeting::find_by_id(&pool, mid).await?
+        .ok_or(AppError::NotFound)?;
+    let back = format!("/tor/{}/meetings/{}", tor_id, mid);
+
+    if meeting_detail.status != "completed" {
+        let _ = session.insert("flash", "Agenda points can only be carried over from completed meetings");
+        return Ok(HttpResponse::SeeOther().insert_header(("Location", back)).finish());
+    }
+
+    let target_id: i64 = form.get("target_meeting_id")
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(0);
+    let targets = meeting::find_carry_over_targets(&pool, tor_id, &meeting_detail.meeting_date).await?;
+    let Some(target) = targets.iter().find(|t| t.id == target_id) else {
+        let _ = session.insert("flash", "Select an upcoming meeting to carry the points over to");
+        return Ok(HttpResponse::SeeOther().insert_header(("Location", back)).finish());
+    };
+
+    let selected: Vec<meeting::MeetingAgendaPoint> = meeting::find_agenda_points(&pool, mid).await?
+        .into_iter()
+        .filter(|p| p.is_undecided() && form.contains_key(&format!("carry_{}", p.id)))
+        .collect();
+    if selected.is_empty() {
+        let _ = session.insert("flash", "No undecided agenda points selected");
+        return Ok(HttpResponse::SeeOther().insert_header(("Location", back)).finish());
+    }
+
+    let current_user_id = get_user_id(&session).unwrap_or(0);
+    let carried = meeting::carry_over(&pool, &meeting_detail, target, &selected).await?;
+
+    let mut annotated = false;
+    if !carried.is_empty()
+        && let Some(m) = minutes::find_by_meeting(&pool, mid).await?
+        && m.status != "approved"
+    {
+        minutes::annotate_deferrals(&pool, m.id, &carried, current_user_id).await?;
+        annotated = true;
+    }
+
+    // Audit
+    let details = serde_json::json!({
+        "meeting_id": mid,
+        "tor_id": tor_id,
+        "to_meeting_id": target.id,
+        "agenda_point_ids": carried.iter().map(|e| e.agenda_point_id).collect::<Vec<_>>(),
+        "minutes_annotated": annotated,
+        "summary": format!("{} agenda point(s) carried over to {}", carried.len(), target.label),
+    });
+    let _ = crate::audit::log(
+        &pool,
+        current_user_id,
+        "meeting.agenda_carried_over",
+        "meeting",
+        mid,
+        details,
+    ).await;
+
+    let _ = session.insert(
+        "flash",
+        format!("{} agenda point(s) carried over to {}", carried.len(), target.label),
+    );
+    Ok(HttpResponse::SeeOther().insert_header(("Location", back)).finish())
+}
+
 // ---------------------------------------------------------------------------
 // POST — generate minutes scaffold
 // ---------------------------------------------------------------------------
@@ -1,10 +1,13 @@
 use actix_session::Session;
 use actix_web::{web, HttpResponse};
+use askama::Template;
 use sqlx::PgPool;
 
-use crate::models::minutes;
+use crate::models::{branding, meeting, minutes, tor};
 use crate::auth::session::require_permission;
 use crate::errors::AppError;
+use crate::export::{docx, offline, pdf};
+use crate::templates_structs::MinutesExportTemplate;
 
 /// GET /meetings/{id}/export — Return print-friendly HTML export of approved minutes
 pub async fn export_minutes_html(
@@ -28,160 +31,15 @@ pub async fn export_minutes_html(
     // Fetch sections
     let sections = minutes::find_sections(&pool, minutes_id).await?;
 
-    // Build HTML content
-    let sections_html = sections
-        .into_iter()
-        .map(|s| {
-            let icon = match s.section_type.as_str() {
-                "attendance" => "👥",
-                "protocol" => "📋",
-                "agenda_items" => "📝",
-                "decisions" => "✅",
-                "action_items" => "🎯",
-                _ => "📄",
-            };
-
-            format!(
-                r#"<section class="minutes-section">
-                    <h2>{} {}</h2>
-                    <div class="section-content">{}</div>
-                </section>"#,
-                icon,
-                s.label,
-                s.content.replace("\n", "<br>")
-            )
-        })
-        .collect::<Vec<_>>()
-        .join("\n");
-
-    let html = format!(
-        r#"<!DOCTYPE html>
-<html lang="en">
-<head>
-    <meta charset="UTF-8">
-    <meta name="viewport" content="width=device-width, initial-scale=1.0">
-    <title>Meeting Minutes — {}</title>
-    <style>
-        * {{
-            margin: 0;
-            padding: 0;
-            box-sizing: border-box;
-        }}
-        body {{
-            font-family: -apple-system, system-ui, 'Segoe UI', Roboto, sans-serif;
-            line-height: 1.6;
-            color: #333;
-            background: #fff;
-        }}
-        .page {{
-            max-width: 900px;
-            margin: 0 auto;
-            padding: 2rem;
-        }}
-        header {{
-            border-bottom: 3px solid #333;
-            padding-bottom: 1.5rem;
-            margin-bottom: 2rem;
-        }}
-        h1 {{
-            font-size: 1.75rem;
-            margin-bottom: 0.5rem;
-        }}
-        .meta {{
-            display: grid;
-            grid-template-columns: 1fr 1fr 1fr;
-            gap: 1.5rem;
-            margin-top: 1rem;
-            font-size: 0.9rem;
-            color: #666;
-        }}
-        .meta-item label {{
-            font-weight: 600;
-            color: #333;
-            display: block;
-            margin-bottom: 0.25rem;
-        }}
-        .minutes-section {{
-            margin-bottom: 2rem;
-            page-break-inside: avoid;
-        }}
-        h2 {{
-            font-size: 1.25rem;
-            margin-bottom: 0.75rem;
-            color: #1c1917;
-            display: flex;
-            align-items: center;
-            gap: 0.5rem;
-        }}
-        .section-content {{
-            padding-left: 1rem;
-            border-left: 3px solid #ddd;
-            line-height: 1.8;
-        }}
-        .section-content br {{
-            margin-bottom: 0.5rem;
-        }}
-        footer {{
-            margin-top: 3rem;
-            padding-top: 1.5rem;
-            border-top: 1px solid #ddd;
-            font-size: 0.85rem;
-            color: #999;
-            text-align: center;
-        }}
-        @media print {{
-            body {{
-                background: none;
-                padding: 0;
-            }}
-            .page {{
-                max-width: none;
-                padding: 0;
-            }}
-            header, .minutes-section {{
-                page-break-inside: avoid;
-            }}
-            h1, h2 {{
-                page-break-after: avoid;
-            }}
-        }}
-    </style>
-</head>
-<body>
-    <div class="page">
-        <header>
-            <h1>Meeting Minutes</h1>
-            <div class="meta">
-                <div class="meta-item">
-                    <label>Meeting</label>
-                    <span>{}</span>
-                </div>
-                <div class="meta-item">
-                    <label>Generated</label>
-                    <span>{}</span>
-                </div>
-                <div class="meta-item">
-                    <label>Status</label>
-                    <span style="font-weight: 600; color: #15803d;">Approved</span>
-                </div>
-            </div>
-        </header>
-
-        <main>
-            {}
-        </main>
-
-        <footer>
-            <p>This is an approved record. Print this page to PDF for permanent archival.</p>
-        </footer>
-    </div>
-</body>
-</html>"#,
-        min.label,
-        min.meeting_name,
-        min.generated_date,
-        sections_html
-    );
+    let html = MinutesExportTemplate {
+        branding: branding::load(&pool, "export").await,
+        theme: "auto".to_string(),
+        title: min.label.clone(),
+        meeting_name: min.meeting_name.clone(),
+        generated_date: min.generated_date.clone(),
+        sections,
+    }
+    .render()?;
 
     // Audit log the export
     let current_user_id = crate::auth::session::get_user_id(&session).unwrap_or(0);
@@ -193,11 +51,168 @@ pub async fn export_minutes_html(
     });
     let _ = crate::audit::log(&pool, current_user_id, "minutes.exported", "minutes", minutes_id, details).await;
 
-    Ok(HttpResponse::Ok()
-        .content_type("text/html; charset=utf-8")
-        .insert_header((
-            "Content-Disposition",
-            format!("inline; filename=\"minutes-{}.html\"", minutes_id),
-        ))
-        .body(html))
+    crate::handlers::export_handlers::checksummed_download(
+        &pool,
+        current_user_id,
+        "minutes",
+        &format!("minutes-{}.html", minutes_id),
+        "text/html; charset=utf-8",
+        "inline",
+        html.into_bytes(),
+    ).await
+}
+
+/// GET /meetings/{id}/export.docx — download approved minutes as a Word document
+pub async fn export_minutes_docx(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "minutes.view")?;
+
+    let minutes_id = path.into_inner();
+    let min = minutes::find_by_id(&pool, minutes_id).await?
+        .ok_or(AppError::NotFound)?;
+    if min.status != "approved" {
+        return Err(AppError::PermissionDenied("Can only export approved minutes".to_string()));
+    }
+
+    let bytes = docx::minutes(&pool, &min).await?;
+
+    let current_user_id = crate::auth::session::get_user_id(&session).unwrap_or(0);
+    let details = serde_json::json!({
+        "minutes_id": minutes_id,
+        "minutes_label": min.label,
+        "format": "docx",
+        "summary": "Minutes exported to Word"
+    });
+    let _ = crate::audit::log(&pool, current_user_id, "minutes.exported", "minutes", minutes_id, details).await;
+
+    crate::handlers::export_handlers::checksummed_download(
+        &pool, current_user_id, "minutes", &docx::minutes_file_name(&min), docx::CONTENT_TYPE, "attachment", bytes,
+    ).await
+}
+
+/// GET /meetings/{id}/export.pdf — download approved minutes as a PDF/A-2b
+/// document for archival retention
+pub async fn export_minutes_pdf(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "minutes.view")?;
+
+    let minutes_id = path.into_inner();
+    let min = minutes::find_by_id(&pool, minutes_id).await?
+        .ok_or(AppError::NotFound)?;
+    if min.status != "approved" {
+        return Err(AppError::PermissionDenied("Can only export approved minutes".to_string()));
+    }
+
+    let current_user_id = crate::auth::session::get_user_id(&session).unwrap_or(0);
+    let watermark = crate::handlers::export_handlers::authorize_export(&pool, current_user_id, "pdf").await?;
+    let bytes = pdf::minutes(&pool, &min, watermark.as_ref()).await?;
+
+    let details = serde_json::json!({
+        "minutes_id": minutes_id,
+        "minutes_label": min.label,
+        "format": "pdf/a-2b",
+        "summary": "Minutes exported to PDF/A"
+    });
+    let _ = crate::audit::log(&pool, current_user_id, "minutes.exported", "minutes", minutes_id, details).await;
+
+    crate::handlers::export_handlers::checksummed_download(
+        &pool, current_user_id, "minutes", &pdf::minutes_file_name(&min), pdf::CONTENT_TYPE, "attachment", bytes,
+    ).await
+}
+
+/// GET /tor/{id}/meetings/{mid}/agenda.docx — download the agenda pack
+/// (agenda points, their proposals and COAs) as a Word document.
+pub async fn export_agenda_docx(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<(i64, i64)>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "meetings.view")?;
+    let (tor_id, mid) = path.into_inner();
+    let user_id = crate::auth::session::get_user_id(&session)
+        .ok_or(AppError::Session("User not logged in".to_string()))?;
+
+    let meeting = meeting::find_by_id(&pool, mid).await?
+        .filter(|m| m.tor_id == tor_id)
+        .ok_or(AppError::NotFound)?;
+    tor::require_tor_membership(&pool, user_id, tor_id).await?;
+
+    let bytes = docx::agenda_pack(&pool, &meeting).await?;
+    let details = serde_json::json!({
+        "meeting_id": mid,
+        "tor_id": tor_id,
+        "format": "docx",
+        "summary": format!("Agenda pack exported to Word for '{}'", meeting.label)
+    });
+    let _ = crate::audit::log(&pool, user_id, "meeting.agenda_exported", "meeting", mid, details).await;
+
+    crate::handlers::export_handlers::checksummed_download(
+        &pool, user_id, "meeting_agenda", &docx::agenda_file_name(&meeting), docx::CONTENT_TYPE, "attachment", bytes,
+    ).await
+}
+
+/// GET /tor/{id}/meetings/{mid}/offline — download the meeting pack as one
+/// self-contained HTML file for reading offline.
+pub async fn export_offline(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<(i64, i64)>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "meetings.view")?;
+    let (tor_id, mid) = path.into_inner();
+    let user_id = crate::auth::session::get_user_id(&session)
+        .ok_or(AppError::Session("User not logged in".to_string()))?;
+
+    let meeting = meeting::find_by_id(&pool, mid).await?
+        .filter(|m| m.tor_id == tor_id)
+        .ok_or(AppError::NotFound)?;
+    tor::require_tor_membership(&pool, user_id, tor_id).await?;
+
+    let bytes = offline::bundle(&pool, &meeting).await?;
+    let details = serde_json::json!({
+        "meeting_id": mid,
+        "tor_id": tor_id,
+        "format": "html",
+        "summary": format!("Offline meeting pack downloaded for '{}'", meeting.label)
+    });
+    let _ = crate::audit::log(&pool, user_id, "meeting.offline_exported", "meeting", mid, details).await;
+
+    crate::handlers::export_handlers::checksummed_download(
+        &pool, user_id, "meeting_offline", &offline::file_name(&meeting), offline::CONTENT_TYPE, "attachment", bytes,
+    ).await
+}
+
+/// GET /tor/{id}/meetings/{mid}/pack — download the meeting pack (zip).
+pub async fn export_pack(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<(i64, i64)>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "meetings.view")?;
+    let (tor_id, mid) = path.into_inner();
+    let user_id = crate::auth::session::get_user_id(&session)
+        .ok_or(AppError::Session("User not logged in".to_string()))?;
+
+    let meeting = meeting::find_by_id(&pool, mid).await?
+        .filter(|m| m.tor_id == tor_id)
+        .ok_or(AppError::NotFound)?;
+    tor::require_tor_membership(&pool, user_id, tor_id).await?;
+
+    let bytes = meeting::pack::build_pack(&pool, &meeting).await?;
+    let details = serde_json::json!({
+        "meeting_id": mid,
+        "tor_id": tor_id,
+        "summary": format!("Meeting pack exported for '{}'", meeting.label)
+    });
+    let _ = crate::audit::log(&pool, user_id, "meeting.pack_exported", "meeting", mid, details).await;
+
+    crate::handlers::export_handlers::checksummed_download(
+        &pool, user_id, "meeting_pack", &meeting::pack::file_name(&meeting), "application/zip", "attachment", bytes,
+    ).await
 }
@@ -9,6 +9,7 @@ use crate::auth::csrf;
 use crate::auth::session::{get_user_id, require_permission};
 use crate::errors::{render, AppError};
 use crate::handlers::role_handlers::helpers::parse_form_body;
+use crate::handlers::warning_handlers::ws::{self, ConnectionMap};
 use crate::models::{permission, role};
 use crate::templates_structs::{
     MatrixCell, MenuBuilderTemplate, PageContext, PageGroup, PermissionRow, RoleColumn,
@@ -93,6 +94,7 @@ pub async fn index(
 pub async fn save(
     session: Session,
     pool: web::Data<PgPool>,
+    conn_map: web::Data<ConnectionMap>,
     body: String,
 ) -> Result<HttpResponse, AppError> {
     require_permission(&session, "roles.manage")?;
@@ -134,8 +136,9 @@ pub async fn save(
         permission::revoke_permission(&pool, *role_id, *perm_id).await?;
     }
 
-    // Audit log
+    // Refresh menus of signed-in users, then audit
     if changes > 0 {
+        ws::notify_nav_updated(&pool, &conn_map).await?;
         let user_id = get_user_id(&session).unwrap_or(0);
         let summary = format!("{} granted, {} revoked via Menu Builder", to_grant.len(), to_revoke.len());
         if let Err(e) = audit::log(
@@ -4,11 +4,17 @@ use sqlx::PgPool;
 
 use crate::auth::abac;
 use crate::auth::csrf;
-use crate::auth::session::{get_user_id, require_permission};
+use crate::auth::session::{get_user_id, get_username, require_permission};
 use crate::errors::{AppError, render};
+use crate::handlers::auth_handlers::CsrfOnly;
+use crate::models::glossary;
 use crate::models::meeting;
 use crate::models::minutes;
-use crate::templates_structs::{PageContext, MinutesViewTemplate};
+use crate::models::revision;
+use crate::models::setting;
+use crate::ticketing;
+use crate::handlers::meeting_handlers::PrintQuery;
+use crate::templates_structs::{PageContext, MinutesViewTemplate, MinutesPrintTemplate, PrintMeta};
 
 /// Generate minutes scaffold for a meeting.
 pub async fn generate_minutes(
@@ -59,24 +65,43 @@ pub async fn generate_minutes(
         .finish())
 }
 
-/// View minutes with all sections.
+/// View minutes with all sections. `?print=1` renders the print layout with
+/// approval signatures.
 pub async fn view_minutes(
     pool: web::Data<PgPool>,
     session: Session,
     path: web::Path<i64>,
+    query: web::Query<PrintQuery>,
 ) -> Result<HttpResponse, AppError> {
     require_permission(&session, "minutes.edit")?;
 
     let minutes_id = path.into_inner();
 
     match minutes::find_by_id(&pool, minutes_id).await? {
+        Some(mins) if query.is_print() => {
+            let meeting = meeting::find_by_id(&pool, mins.meeting_id).await?;
+            let sections = minutes::find_sections(&pool, minutes_id).await?;
+            let meta = PrintMeta::build(&pool).await;
+            render(MinutesPrintTemplate { meta, minutes: mins, meeting, sections })
+        }
         Some(mins) => {
             let ctx = PageContext::build(&session, &pool, "/minutes").await?;
             let sections = minutes::find_sections(&pool, minutes_id).await?;
+            let revisions = minutes::find_revisions(&pool, minutes_id).await?;
+            let changed_sections = sections.iter()
+                .filter(|s| s.changes_since_circulation().is_some())
+                .count();
+            let glossary = match meeting::find_by_id(&pool, mins.meeting_id).await? {
+                Some(m) => glossary::find_for_tor(&pool, m.tor_id).await?,
+                None => vec![],
+            };
             let tmpl = MinutesViewTemplate {
                 ctx,
                 minutes: mins,
                 sections,
+                revisions,
+                changed_sections,
+                glossary,
             };
             render(tmpl)
         }
@@ -96,22 +121,23 @@ pub async fn update_section(
 
     let (minutes_id, section_id) = path.into_inner();
 
+    let mins = minutes::find_by_id(&pool, minutes_id).await?.ok_or(AppError::NotFound)?;
+
     // Check if minutes are approved (read-only)
-    if let Some(mins) = minutes::find_by_id(&pool, minutes_id).await?
-        && mins.status == "approved"
-    {
+    if mins.status == "approved" {
         let _ = session.insert("flash", "Cannot edit approved minutes");
         return Ok(HttpResponse::SeeOther()
             .insert_header(("Location", format!("/minutes/{minutes_id}")))
             .finish());
     }
 
+    let current_user_id = get_user_id(&session).unwrap_or(0);
     let content = form.get("content").map(|s| s.as_str()).unwrap_or("");
-    minutes::update_section_content(&pool, section_id, content).await?;
+    minutes::update_section_content(&pool, section_id, content, current_user_id).await?;
 
-    let current_user_id = get_user_id(&session).unwrap_or(0);
     let details = serde_json::json!({
         "section_id": section_id,
+        "after_circulation": !mins.circulated_at.is_empty(),
         "summary": "Updated minutes section"
     });
     let _ = crate::audit::log(&pool, current_user_id, "minutes.section_edited", "minutes", minutes_id, details).await;
@@ -122,6 +148,44 @@ pub async fn update_section(
         .finish())
 }
 
+/// POST /minutes/{id}/sections/{section_id}/revisions/{revision_id}/restore —
+/// put a section back to an earlier revision.
+pub async fn restore_section_revision(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<(i64, i64, i64)>,
+    form: web::Form<CsrfOnly>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "minutes.edit")?;
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+    let user_id = get_user_id(&session).ok_or(AppError::Session("User not logged in".to_string()))?;
+
+    let (minutes_id, section_id, revision_id) = path.into_inner();
+    let mins = minutes::find_by_id(&pool, minutes_id).await?.ok_or(AppError::NotFound)?;
+    let rev = revision::find_by_id(&pool, revision_id).await?
+        .filter(|r| r.target_id == section_id)
+        .ok_or(AppError::NotFound)?;
+    if !minutes::find_sections(&pool, minutes_id).await?.iter().any(|s| s.id == section_id) {
+        return Err(AppError::NotFound);
+    }
+
+    if mins.status == "approved" {
+        let _ = session.insert("flash", "Cannot edit approved minutes");
+    } else {
+        minutes::update_section_content(&pool, section_id, rev.field("content"), user_id).await?;
+        let details = serde_json::json!({
+            "section_id": section_id,
+            "revision_id": revision_id,
+            "summary": format!("Restored minutes section to the revision of {}", rev.edited_at)
+        });
+        let _ = crate::audit::log(&pool, user_id, "minutes.section_restored", "minutes", minutes_id, details).await;
+        let _ = session.insert("flash", "Section restored");
+    }
+    Ok(HttpResponse::SeeOther()
+        .insert_header(("Location", format!("/minutes/{minutes_id}")))
+        .finish())
+}
+
 /// Update minutes status (draft -> pending_approval -> approved).
 pub async fn update_minutes_status(
     pool: web::Data<PgPool>,
@@ -161,6 +225,13 @@ pub async fn update_minutes_status(
     }
 
     minutes::update_status(&pool, minutes_id, new_status).await?;
+    if new_status == "pending_approval" {
+        minutes::mark_circulated(&pool, minutes_id).await?;
+    } else if new_status == "approved" {
+        let approver = get_username(&session).unwrap_or_default();
+        let today = crate::warnings::clock::now().format("%Y-%m-%d").to_string();
+        minutes::mark_approved(&pool, minutes_id, &approver, &today).await?;
+    }
 
     let current_user_id = get_user_id(&session).unwrap_or(0);
     let details = serde_json::json!({
@@ -290,7 +361,22 @@ pub async fn save_action_items(
             .finish());
     }
 
-    minutes::update_structured_action_items(&pool, minutes_id, &form.structured_action_items).await?;
+    let track = setting::get_bool(&pool, "ticketing.enabled").await;
+    let items = minutes::action_items::prepare_for_save(
+        &form.structured_action_items, &mins.structured_action_items, track,
+    );
+    minutes::update_structured_action_items(&pool, minutes_id, &items).await?;
+
+    // Push new and edited items to the ticketing system without delaying the response
+    if let Some(cfg) = ticketing::TicketingConfig::load(&pool).await {
+        let pool = pool.get_ref().clone();
+        let label = mins.label.clone();
+        actix_web::rt::spawn(async move {
+            if let Err(e) = ticketing::sync_minutes(&pool, &cfg, minutes_id, &label).await {
+                log::error!("Ticket sync for minutes {} failed: {}", minutes_id, e);
+            }
+        });
+    }
 
     let user_id = get_user_id(&session).unwrap_or(0);
     let _ = crate::audit::log(&pool, user_id, "minutes.action_items_saved", "minutes", minutes_id,
@@ -1,26 +1,57 @@
+pub mod access_review_handlers;
 pub mod account_handlers;
+pub mod action_item_handlers;
 pub mod agenda_handlers;
+pub mod api_usage_handlers;
 pub mod api_v1;
 pub mod audit_handlers;
 pub mod auth_handlers;
+pub mod ballot_handlers;
+pub mod branding_handlers;
+pub mod changelog_handlers;
 pub mod coa_handlers;
 pub mod dashboard;
+pub mod default_coa_handlers;
 pub mod data_handlers;
 pub mod document_handlers;
+pub mod email_template_handlers;
+pub mod export_handlers;
+pub mod feed_handlers;
+pub mod form_draft_handlers;
+pub mod glossary_handlers;
 pub mod governance_handlers;
+pub mod graphql_handlers;
 pub mod meeting_handlers;
+pub mod multipart;
+pub mod nav_handlers;
 pub mod menu_builder_handlers;
 pub mod minutes_handlers;
+pub mod notification_handlers;
 pub mod ontology_handlers;
 pub mod opinion_handlers;
+pub mod outbound_webhook_handlers;
 pub mod workflow_handlers;
 pub mod workflow_builder_handlers;
 pub mod proposal_handlers;
+pub mod pwa_handlers;
 pub mod queue_handlers;
+pub mod quota_handlers;
+pub mod recertification_handlers;
+pub mod rejection_reason_handlers;
+pub mod report_definition_handlers;
+pub mod report_handlers;
 pub mod role_handlers;
 pub mod role_builder_handlers;
+pub mod scheduler_handlers;
 pub mod settings_handlers;
+pub mod share_handlers;
+pub mod signing_key_handlers;
+pub mod sla_pack_handlers;
 pub mod suggestion_handlers;
+pub mod survey_handlers;
 pub mod tor_handlers;
+pub mod transfer_handlers;
+pub mod trash_handlers;
 pub mod user_handlers;
 pub mod warning_handlers;
+pub mod webhook_handlers;
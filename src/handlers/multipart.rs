@@ -0,0 +1,114 @@
+//! Minimal `multipart/form-data` parsing for file uploads.
+//!
+//! Bodies are read whole (bounded by the route's `PayloadConfig`) and split
+//! on the boundary from the `Content-Type` header. Only what browsers send
+//! for `<form enctype="multipart/form-data">` is supported: one level of
+//! parts with `Content-Disposition: form-data` and an optional
+//! `Content-Type`.
+
+/// One field of a multipart body.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Part {
+    pub name: String,
+    /// Set for file inputs; the browser-supplied name, path stripped.
+    pub filename: Option<String>,
+    /// Declared type of the part; `text/plain` when absent.
+    pub content_type: String,
+    pub data: Vec<u8>,
+}
+
+impl Part {
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.data).into_owned()
+    }
+}
+
+/// Whether a `Content-Type` header value announces a multipart form.
+pub fn is_multipart(content_type: &str) -> bool {
+    content_type.trim_start().to_ascii_lowercase().starts_with("multipart/form-data")
+}
+
+/// Split a multipart body into its parts.
+pub fn parse(content_type: &str, body: &[u8]) -> Result<Vec<Part>, String> {
+    if !is_multipart(content_type) {
+        return Err("Expected a multipart/form-data request".to_string());
+    }
+    let boundary = header_param(content_type, "boundary")
+        .filter(|b| !b.is_empty())
+        .ok_or("Missing multipart boundary")?;
+    let delimiter = format!("--{}", boundary).into_bytes();
+
+    let mut parts = Vec::new();
+    let mut pos = find(body, &delimiter, 0).ok_or("Malformed multipart body")? + delimiter.len();
+    loop {
+        // "--" after a delimiter closes the body
+        if body[pos..].starts_with(b"--") {
+            return Ok(parts);
+        }
+        pos = skip_line_break(body, pos).ok_or("Malformed multipart body")?;
+        let header_end = find(body, b"\r\n\r\n", pos).ok_or("Malformed multipart part headers")?;
+        let headers = std::str::from_utf8(&body[pos..header_end]).map_err(|_| "Malformed multipart part headers")?;
+        let data_start = header_end + 4;
+        let mut closing = b"\r\n".to_vec();
+        closing.extend_from_slice(&delimiter);
+        let data_end = find(body, &closing, data_start).ok_or("Unterminated multipart part")?;
+
+        let mut name = None;
+        let mut filename = None;
+        let mut part_type = "text/plain".to_string();
+        for line in headers.split("\r\n") {
+            let Some((key, value)) = line.split_once(':') else { continue };
+            match key.trim().to_ascii_lowercase().as_str() {
+                "content-disposition" => {
+                    name = header_param(value, "name");
+                    filename = header_param(value, "filename").map(|f| base_name(&f).to_string());
+                }
+                "content-type" => part_type = value.trim().to_string(),
+                _ => {}
+            }
+        }
+        parts.push(Part {
+            name: name.ok_or("Multipart part without a name")?,
+            filename,
+            content_type: part_type,
+            data: body[data_start..data_end].to_vec(),
+        });
+        pos = data_end + closing.len();
+    }
+}
+
+/// Value of a `; key=value` parameter in a header, unquoted.
+fn header_param(header: &str, key: &str) -> Option<String> {
+    header.split(';').skip(1).find_map(|param| {
+        let (k, v) = param.split_once('=')?;
+        if !k.trim().eq_ignore_ascii_case(key) {
+            return None;
+        }
+        let v = v.trim();
+        let v = v.strip_prefix('"').and_then(|v| v.strip_suffix('"')).unwrap_or(v);
+        Some(v.replace("\\\"", "\""))
+    })
+}
+
+/// Older browsers send the full client path as the file name.
+fn base_name(filename: &str) -> &str {
+    filename.rsplit(['/', '\\']).next().unwrap_or(filename)
+}
+
+fn skip_line_break(body: &[u8], pos: usize) -> Option<usize> {
+    if body[pos..].starts_with(b"\r\n") {
+        Some(pos + 2)
+    } else {
+        None
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8], from: usize) -> Option<usize> {
+    if from > haystack.len() || needle.is_empty() {
+        return None;
+    }
+    haystack[from..]
+        .windows(needle.len())
+        .position(|w| w == needle)
+        .map(|i| i + from)
+}
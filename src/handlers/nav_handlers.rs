@@ -0,0 +1,39 @@
+use actix_session::Session;
+use actix_web::{web, HttpResponse};
+use serde::Deserialize;
+use sqlx::PgPool;
+
+use crate::auth::session::get_permissions;
+use crate::errors::AppError;
+use crate::models::nav_item;
+
+#[derive(Deserialize)]
+pub struct NavQuery {
+    #[serde(default)]
+    pub path: String,
+}
+
+/// GET /api/nav?path=/current/page — the caller's header modules and sidebar
+/// for a page, fetched by nav.js on a `nav_updated` push to refresh menus
+/// without reloading. The session's permissions are already current: the auth
+/// middleware reloads them when the navigation version moved.
+pub async fn current(
+    pool: web::Data<PgPool>,
+    session: Session,
+    query: web::Query<NavQuery>,
+) -> Result<HttpResponse, AppError> {
+    let permissions = get_permissions(&session)
+        .map_err(|e| AppError::Session(format!("Failed to get permissions: {}", e)))?;
+    let (modules, sidebar_items) = nav_item::find_navigation(&pool, &permissions, &query.path).await;
+
+    let link = |label: &str, url: &str, is_active: bool| serde_json::json!({
+        "label": label,
+        "url": url,
+        "is_active": is_active,
+    });
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "version": nav_item::version(&pool).await?,
+        "modules": modules.iter().map(|m| link(&m.label, &m.url, m.is_active)).collect::<Vec<_>>(),
+        "sidebar": sidebar_items.iter().map(|i| link(&i.label, &i.url, i.is_active)).collect::<Vec<_>>(),
+    })))
+}
@@ -0,0 +1,133 @@
+use actix_session::Session;
+use actix_web::{web, HttpResponse};
+use serde::Deserialize;
+use sqlx::PgPool;
+
+use crate::auth::{csrf, session::get_user_id};
+use crate::errors::{AppError, render};
+use crate::handlers::auth_handlers::CsrfOnly;
+use crate::handlers::warning_handlers::ws::{ConnectionMap, send_count_update};
+use crate::templates_structs::{NotificationsTemplate, PageContext};
+use crate::warnings::notifications;
+
+#[derive(Deserialize)]
+pub struct NotificationQuery {
+    channel: Option<String>,
+    show_read: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct MarkAllForm {
+    pub csrf_token: String,
+    #[serde(default)]
+    pub channel: String,
+}
+
+/// The known channel named in a query or form, if any.
+fn known_channel(channel: &str) -> Option<&'static str> {
+    notifications::CHANNELS.iter()
+        .find(|(key, _)| *key == channel)
+        .map(|(key, _)| *key)
+}
+
+/// GET /notifications — the user's inbox across all channels.
+pub async fn list(
+    pool: web::Data<PgPool>,
+    session: Session,
+    query: web::Query<NotificationQuery>,
+) -> Result<HttpResponse, AppError> {
+    let user_id = get_user_id(&session).ok_or_else(|| AppError::Session("No user".into()))?;
+    let ctx = PageContext::build(&session, &pool, "/notifications").await?;
+
+    let channel = query.channel.as_deref().and_then(known_channel);
+    let show_read = query.show_read.as_deref() == Some("true");
+
+    let all = notifications::find_for_user(&pool, user_id, show_read).await?;
+    let channels = notifications::CHANNELS.iter()
+        .map(|(key, label)| {
+            let unread = all.iter().filter(|n| n.is_unread() && n.channel() == *key).count();
+            (key.to_string(), label.to_string(), unread)
+        })
+        .collect();
+    let unread = all.iter().filter(|n| n.is_unread()).count();
+    let items = all.into_iter()
+        .filter(|n| channel.is_none_or(|c| n.channel() == c))
+        .collect();
+
+    render(NotificationsTemplate {
+        ctx,
+        items,
+        channels,
+        channel: channel.unwrap_or("").to_string(),
+        show_read,
+        unread,
+    })
+}
+
+/// GET /notifications/{receipt_id} — mark read and open what it points to.
+pub async fn open(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+    conn_map: web::Data<ConnectionMap>,
+) -> Result<HttpResponse, AppError> {
+    let user_id = get_user_id(&session).ok_or_else(|| AppError::Session("No user".into()))?;
+    let notification = notifications::mark_read(&pool, user_id, path.into_inner()).await?
+        .ok_or(AppError::NotFound)?;
+    send_count_update(&conn_map, &pool, user_id).await;
+
+    Ok(HttpResponse::SeeOther()
+        .insert_header(("Location", notification.href()))
+        .finish())
+}
+
+/// POST /notifications/{receipt_id}/read — mark one notification read.
+pub async fn mark_read(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+    form: web::Form<CsrfOnly>,
+    conn_map: web::Data<ConnectionMap>,
+) -> Result<HttpResponse, AppError> {
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+    let user_id = get_user_id(&session).ok_or_else(|| AppError::Session("No user".into()))?;
+    notifications::mark_read(&pool, user_id, path.into_inner()).await?
+        .ok_or(AppError::NotFound)?;
+    send_count_update(&conn_map, &pool, user_id).await;
+
+    Ok(HttpResponse::SeeOther()
+        .insert_header(("Location", "/notifications"))
+        .finish())
+}
+
+/// POST /notifications/read-all — mark every unread notification read,
+/// optionally in one channel.
+pub async fn mark_all_read(
+    pool: web::Data<PgPool>,
+    session: Session,
+    form: web::Form<MarkAllForm>,
+    conn_map: web::Data<ConnectionMap>,
+) -> Result<HttpResponse, AppError> {
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+    let user_id = get_user_id(&session).ok_or_else(|| AppError::Session("No user".into()))?;
+
+    let channel = known_channel(&form.channel);
+    let count = notifications::mark_all_read(&pool, user_id, channel).await?;
+    send_count_update(&conn_map, &pool, user_id).await;
+
+    let details = serde_json::json!({
+        "channel": channel,
+        "count": count,
+        "summary": format!("Marked {} notification(s) read", count)
+    });
+    let _ = crate::audit::log(&pool, user_id, "notification.all_read", "user", user_id, details).await;
+
+    let _ = session.insert("flash", format!("Marked {} notification(s) read", count));
+    let location = match channel {
+        Some(c) => format!("/notifications?channel={c}"),
+        None => "/notifications".to_string(),
+    };
+    Ok(HttpResponse::SeeOther()
+        .insert_header(("Location", location))
+        .finish())
+}
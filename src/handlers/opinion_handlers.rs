@@ -1,13 +1,19 @@
 use actix_session::Session;
-use actix_web::{web, HttpResponse};
+use actix_web::{web, HttpRequest, HttpResponse};
 use sqlx::PgPool;
 
 use crate::auth::csrf;
-use crate::auth::session::{require_permission, get_user_id};
+use crate::auth::session::{require_permission, get_permissions, get_user_id, get_username};
 use crate::errors::{AppError, render};
-use crate::models::{tor, agenda_point, coa, opinion};
+use crate::models::{action_item, tor, agenda_point, coa, decision_citation, delegation, opinion};
 use crate::models::opinion::{OpinionForm, DecisionForm};
-use crate::templates_structs::{PageContext, OpinionFormTemplate, DecisionFormTemplate};
+use crate::handlers::meeting_handlers::PrintQuery;
+use crate::handlers::feed_handlers;
+use crate::warnings::notifications;
+use crate::templates_structs::{
+    PageContext, OpinionFormTemplate, DecisionFormTemplate, DecisionRegisterTemplate,
+    DecisionRegisterPrintTemplate, PrintMeta,
+};
 
 // ---------------------------------------------------------------------------
 // Opinion Recording Handlers (Task 16)
@@ -37,6 +43,10 @@ pub async fn form(
         ));
     }
 
+    if opinion::opinions_locked(&agenda_point.status) {
+        return Ok(opinions_locked_redirect(&session, tor_id, agenda_point_id));
+    }
+
     // Check if user has already recorded an opinion
     let existing_opinion = opinion::find_opinion_by_user_and_agenda_point(&pool, user_id, agenda_point_id).await?;
 
@@ -80,6 +90,12 @@ pub async fn submit(
     let user_id = get_user_id(&session).ok_or(AppError::Session("User not logged in".to_string()))?;
     tor::require_tor_membership(&pool, user_id, tor_id).await?;
 
+    let agenda_point = agenda_point::find_by_id(&pool, agenda_point_id).await?
+        .ok_or(AppError::NotFound)?;
+    if opinion::opinions_locked(&agenda_point.status) {
+        return Ok(opinions_locked_redirect(&session, tor_id, agenda_point_id));
+    }
+
     // Validate form input
     let preferred_coa_id = form.preferred_coa_id;
     let commentary = form.commentary.trim();
@@ -90,8 +106,6 @@ pub async fn submit(
     }
 
     if !errors.is_empty() {
-        let _agenda_point = agenda_point::find_by_id(&pool, agenda_point_id).await?
-            .ok_or(AppError::NotFound)?;
         let coas = coa::find_all_for_agenda_point(&pool, agenda_point_id).await?;
         let tor_name = tor::get_tor_name(&pool, tor_id).await.unwrap_or_default();
         let ctx = PageContext::build(&session, &pool, "/workflow").await?
@@ -136,16 +150,118 @@ pub async fn submit(
     });
     let _ = crate::audit::log(&pool, user_id, "opinion.recorded", "opinion", opinion_id, details).await;
 
+    let author = get_username(&session).unwrap_or_default();
+    let _ = notifications::notify_mentions(
+        &pool, user_id, &author, commentary,
+        &format!("an opinion on '{}'", agenda_point.title),
+        &format!("/tor/{tor_id}/workflow/agenda/{agenda_point_id}"),
+    ).await;
+
     let _ = session.insert("flash", "Opinion recorded successfully");
     Ok(HttpResponse::SeeOther()
         .insert_header(("Location", format!("/tor/{tor_id}/workflow/agenda/{agenda_point_id}")))
         .finish())
 }
 
+#[derive(serde::Deserialize)]
+pub struct ReactionForm {
+    pub csrf_token: String,
+    pub kind: String,
+}
+
+/// POST /tor/{id}/workflow/agenda/{aid}/opinions/{opinion_id}/react
+/// Sets, changes or withdraws the user's reaction to another member's opinion.
+pub async fn react(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<(i64, i64, i64)>,
+    form: web::Form<ReactionForm>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "agenda.participate")?;
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+
+    let (tor_id, agenda_point_id, opinion_id) = path.into_inner();
+    let user_id = get_user_id(&session).ok_or(AppError::Session("User not logged in".to_string()))?;
+    tor::require_tor_membership(&pool, user_id, tor_id).await?;
+
+    let agenda_point = agenda_point::find_by_id(&pool, agenda_point_id).await?
+        .ok_or(AppError::NotFound)?;
+    if opinion::opinions_locked(&agenda_point.status) {
+        return Ok(opinions_locked_redirect(&session, tor_id, agenda_point_id));
+    }
+    let target = opinion::find_opinion_by_id(&pool, opinion_id).await?
+        .filter(|o| o.agenda_point_id == agenda_point_id)
+        .ok_or(AppError::NotFound)?;
+    let redirect = HttpResponse::SeeOther()
+        .insert_header(("Location", format!("/tor/{tor_id}/workflow/agenda/{agenda_point_id}")))
+        .finish();
+    if target.recorded_by == user_id {
+        let _ = session.insert("flash", "You cannot react to your own opinion");
+        return Ok(redirect);
+    }
+    if !opinion::is_reaction(&form.kind) {
+        return Err(AppError::PermissionDenied(format!("Unknown reaction '{}'", form.kind)));
+    }
+
+    let reaction = opinion::react(&pool, opinion_id, user_id, &form.kind).await?;
+
+    let details = serde_json::json!({
+        "agenda_point_id": agenda_point_id,
+        "reaction": reaction,
+        "summary": match &reaction {
+            Some(kind) => format!("Reacted '{}' to {}'s opinion", kind, target.recorded_by_name),
+            None => format!("Withdrew reaction to {}'s opinion", target.recorded_by_name),
+        }
+    });
+    let _ = crate::audit::log(&pool, user_id, "opinion.reacted", "opinion", opinion_id, details).await;
+
+    Ok(redirect)
+}
+
+/// Redirect back to the agenda point when opinions are frozen by a decision.
+fn opinions_locked_redirect(session: &Session, tor_id: i64, agenda_point_id: i64) -> HttpResponse {
+    let _ = session.insert("flash", "Opinions are locked because a decision has been recorded");
+    HttpResponse::SeeOther()
+        .insert_header(("Location", format!("/tor/{tor_id}/workflow/agenda/{agenda_point_id}")))
+        .finish()
+}
+
 // ---------------------------------------------------------------------------
 // Decision Recording Handlers (Task 16)
 // ---------------------------------------------------------------------------
 
+/// Enforce ToR membership and the configured decision authority for the
+/// current user. A delegate may decide for an absent member who holds the
+/// authority; that member is returned so the decision can be attributed.
+pub(crate) async fn require_decision_authority(
+    pool: &PgPool,
+    session: &Session,
+    tor_id: i64,
+    user_id: i64,
+) -> Result<(tor::DecisionAuthority, Option<delegation::OutOfOffice>), AppError> {
+    let authority = tor::find_decision_authority(pool, tor_id).await?;
+    let permissions = get_permissions(session)
+        .map_err(|e| AppError::Session(format!("Failed to get permissions: {}", e)))?;
+    let membership = tor::require_tor_membership(pool, user_id, tor_id).await;
+    if membership.is_ok() && tor::can_decide(pool, &authority, user_id, &permissions).await? {
+        return Ok((authority, None));
+    }
+
+    let today = chrono::Local::now().date_naive();
+    for (principal, principal_permissions) in delegation::find_principals_in_tor(pool, user_id, tor_id, today).await? {
+        if tor::can_decide(pool, &authority, principal.user_id, &principal_permissions).await? {
+            return Ok((authority, Some(principal)));
+        }
+    }
+
+    membership?;
+    let members = tor::find_members(pool, tor_id).await?;
+    Err(AppError::PermissionDenied(format!(
+        "Decisions in this ToR are recorded by: {}",
+        authority.describe(&members)
+    )))
+}
+
 /// GET /tor/{id}/workflow/agenda/{aid}/decide
 /// Renders the decision recording form for an agenda point.
 pub async fn decision_form(
@@ -153,11 +269,9 @@ pub async fn decision_form(
     session: Session,
     path: web::Path<(i64, i64)>,
 ) -> Result<HttpResponse, AppError> {
-    require_permission(&session, "agenda.decide")?;
-
     let (tor_id, agenda_point_id) = path.into_inner();
     let user_id = get_user_id(&session).ok_or(AppError::Session("User not logged in".to_string()))?;
-    tor::require_tor_membership(&pool, user_id, tor_id).await?;
+    let (_, on_behalf_of) = require_decision_authority(&pool, &session, tor_id, user_id).await?;
 
     // Fetch agenda point
     let agenda_point = agenda_point::find_by_id(&pool, agenda_point_id).await?
@@ -215,12 +329,24 @@ pub async fn decision_form(
     let ctx = PageContext::build(&session, &pool, "/workflow").await?
         .with_tor(tor_id, &tor_name, "workflow");
 
+    let opinion_versions = opinion::version_stamp(
+        &opinion::find_opinions_for_agenda_point(&pool, agenda_point_id).await?,
+    );
+    let sentiment = opinion::find_sentiment(&pool, agenda_point_id, user_id).await?;
+
     let tmpl = DecisionFormTemplate {
         ctx,
         tor_id,
         agenda_point,
         coas,
         opinions,
+        opinion_versions,
+        sentiment,
+        on_behalf_of,
+        owners: action_item::find_owners(&pool, tor_id).await?,
+        follow_up_actions: String::new(),
+        follow_up_owner_id: String::new(),
+        follow_up_due: String::new(),
         errors: vec![],
     };
     render(tmpl)
@@ -234,12 +360,11 @@ pub async fn record_decision(
     path: web::Path<(i64, i64)>,
     form: web::Form<DecisionForm>,
 ) -> Result<HttpResponse, AppError> {
-    require_permission(&session, "agenda.decide")?;
     csrf::validate_csrf(&session, &form.csrf_token)?;
 
     let (tor_id, agenda_point_id) = path.into_inner();
     let user_id = get_user_id(&session).ok_or(AppError::Session("User not logged in".to_string()))?;
-    tor::require_tor_membership(&pool, user_id, tor_id).await?;
+    let (authority, on_behalf_of) = require_decision_authority(&pool, &session, tor_id, user_id).await?;
 
     // Validate form input
     let selected_coa_id = form.selected_coa_id;
@@ -250,6 +375,45 @@ pub async fn record_decision(
         errors.push("Please select a course of action".to_string());
     }
 
+    // The decision must be based on the opinions the decider saw
+    let current_opinions = opinion::find_opinions_for_agenda_point(&pool, agenda_point_id).await?;
+    let current_versions = opinion::version_stamp(&current_opinions);
+    if form.opinion_versions != current_versions {
+        errors.push("Opinions were revised while you were reviewing. Check the updated opinions before deciding.".to_string());
+    }
+
+    // Vote-based authority: the decision follows the majority of opinions
+    if authority == tor::DecisionAuthority::Vote && selected_coa_id > 0 {
+        match tor::vote_winner(&current_opinions) {
+            Some(winner) if winner == selected_coa_id => {}
+            Some(_) => errors.push("This ToR decides by vote: select the course of action preferred by the majority of member opinions".to_string()),
+            None => errors.push("This ToR decides by vote, and no course of action has a majority of member opinions yet".to_string()),
+        }
+    }
+
+    // Follow-up actions share one owner and due date
+    let owners = action_item::find_owners(&pool, tor_id).await?;
+    let follow_ups = action_item::follow_up_titles(&form.follow_up_actions);
+    let follow_up_owner_id = form.follow_up_owner_id.parse::<i64>().unwrap_or(0);
+    if !follow_ups.is_empty() {
+        let owner_ids: Vec<i64> = owners.iter().map(|(id, _)| *id).collect();
+        for title in &follow_ups {
+            let input = action_item::ActionItemInput {
+                title,
+                description: "",
+                owner_id: follow_up_owner_id,
+                due_date: &form.follow_up_due,
+                status: action_item::STATUSES[0].0,
+            };
+            for err in action_item::validate(&input, &owner_ids) {
+                let err = format!("Follow-up actions: {err}");
+                if !errors.contains(&err) {
+                    errors.push(err);
+                }
+            }
+        }
+    }
+
     if !errors.is_empty() {
         let agenda_point = agenda_point::find_by_id(&pool, agenda_point_id).await?
             .ok_or(AppError::NotFound)?;
@@ -292,6 +456,7 @@ pub async fn record_decision(
         let tor_name = tor::get_tor_name(&pool, tor_id).await.unwrap_or_default();
         let ctx = PageContext::build(&session, &pool, "/workflow").await?
             .with_tor(tor_id, &tor_name, "workflow");
+        let sentiment = opinion::find_sentiment(&pool, agenda_point_id, user_id).await?;
 
         let tmpl = DecisionFormTemplate {
             ctx,
@@ -299,6 +464,13 @@ pub async fn record_decision(
             agenda_point,
             coas,
             opinions,
+            opinion_versions: current_versions,
+            sentiment,
+            on_behalf_of,
+            owners,
+            follow_up_actions: form.follow_up_actions.clone(),
+            follow_up_owner_id: form.follow_up_owner_id.clone(),
+            follow_up_due: form.follow_up_due.clone(),
             errors,
         };
         return render(tmpl);
@@ -312,13 +484,37 @@ pub async fn record_decision(
     let now = chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
     let _ = crate::models::entity::set_property(&pool, agenda_point_id, "decided_date", &now).await;
     let _ = crate::models::entity::set_property(&pool, agenda_point_id, "selected_coa_id", &selected_coa_id.to_string()).await;
+    let mut summary = format!("Recorded decision on agenda point #{} selecting COA #{}", agenda_point_id, selected_coa_id);
+    if let Some(principal) = &on_behalf_of {
+        opinion::record_on_behalf_of(&pool, decision_id, agenda_point_id, principal.user_id).await?;
+        summary.push_str(&format!(" on behalf of {}", principal.user_name));
+    }
+
+    // Follow-up actions, linked to the agenda point and so to this decision
+    let mut action_item_ids = Vec::with_capacity(follow_ups.len());
+    for title in &follow_ups {
+        let input = action_item::ActionItemInput {
+            title,
+            description: "",
+            owner_id: follow_up_owner_id,
+            due_date: &form.follow_up_due,
+            status: action_item::STATUSES[0].0,
+        };
+        action_item_ids.push(action_item::create(&pool, tor_id, &input, Some(agenda_point_id), user_id).await?);
+    }
+    if !action_item_ids.is_empty() {
+        summary.push_str(&format!(" with {} follow-up action(s)", action_item_ids.len()));
+    }
 
     // Audit log the decision
     let details = serde_json::json!({
         "agenda_point_id": agenda_point_id,
         "selected_coa_id": selected_coa_id,
         "rationale_length": decision_rationale.len(),
-        "summary": format!("Recorded decision on agenda point #{} selecting COA #{}", agenda_point_id, selected_coa_id)
+        "decision_authority": authority.mode(),
+        "on_behalf_of": on_behalf_of.as_ref().map(|p| p.user_id),
+        "action_item_ids": action_item_ids,
+        "summary": summary
     });
     let _ = crate::audit::log(&pool, user_id, "decision.finalized", "decision", decision_id, details).await;
 
@@ -327,3 +523,62 @@ pub async fn record_decision(
         .insert_header(("Location", format!("/tor/{tor_id}/workflow/agenda/{agenda_point_id}")))
         .finish())
 }
+
+/// GET /tor/{id}/decisions — decisions recorded in this ToR, newest first.
+/// `?print=1` renders the register in the print layout.
+pub async fn decision_register(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+    query: web::Query<PrintQuery>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "meetings.view")?;
+    let tor_id = path.into_inner();
+    let user_id = get_user_id(&session).ok_or(AppError::Session("User not logged in".to_string()))?;
+    tor::require_tor_membership(&pool, user_id, tor_id).await?;
+
+    let tor_name = tor::get_tor_name(&pool, tor_id).await?;
+    let decisions = opinion::find_decision_register(&pool, Some(tor_id)).await?;
+
+    if query.is_print() {
+        let meta = PrintMeta::build(&pool).await;
+        return render(DecisionRegisterPrintTemplate { meta, tor_id, tor_label: tor_name, decisions });
+    }
+
+    let ctx = PageContext::build(&session, &pool, "/meetings").await?
+        .with_tor(tor_id, &tor_name, "decisions");
+    let public_feeds = feed_handlers::is_public(&pool, tor_id).await;
+    render(DecisionRegisterTemplate { ctx, tor_id, tor_name, decisions, public_feeds })
+}
+
+/// GET /cite/{citation_id} and GET /decisions/sha256/{hash} — resolve a
+/// decision citation. Redirects to the decision in its ToR's register, or
+/// returns JSON-LD for `Accept: application/ld+json` or a `.jsonld` suffix.
+pub async fn resolve_citation(
+    pool: web::Data<PgPool>,
+    session: Session,
+    req: HttpRequest,
+    path: web::Path<String>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "meetings.view")?;
+    let reference = path.into_inner();
+    let (reference, suffix_json_ld) = match reference.strip_suffix(".jsonld") {
+        Some(stem) => (stem.to_string(), true),
+        None => (reference, false),
+    };
+    let entry = decision_citation::resolve(&pool, &reference).await?
+        .ok_or(AppError::NotFound)?;
+    let user_id = get_user_id(&session).ok_or(AppError::Session("User not logged in".to_string()))?;
+    tor::require_tor_membership(&pool, user_id, entry.tor_id).await?;
+
+    let wants_json_ld = suffix_json_ld || req.headers()
+        .get(actix_web::http::header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.contains("application/ld+json"));
+    if wants_json_ld {
+        let document = decision_citation::to_json_ld(&entry, &feed_handlers::base_url(&req));
+        return Ok(HttpResponse::Ok().content_type("application/ld+json").json(document));
+    }
+    let location = format!("/tor/{}/decisions#{}", entry.tor_id, entry.citation_id);
+    Ok(HttpResponse::SeeOther().insert_header(("Location", location)).finish())
+}
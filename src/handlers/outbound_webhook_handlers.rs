@@ -0,0 +1,216 @@
+use actix_session::Session;
+use actix_web::{web, HttpResponse};
+use serde::Deserialize;
+use sqlx::PgPool;
+
+use crate::auth::csrf;
+use crate::auth::session::{get_user_id, require_permission};
+use crate::errors::{AppError, render};
+use crate::handlers::auth_handlers::CsrfOnly;
+use crate::models::outbound_webhook::{self, OutboundWebhookInput};
+use crate::templates_structs::{OutboundWebhooksTemplate, PageContext, WebhookDeliveriesTemplate};
+
+const STATUSES: &[&str] = &["pending", "delivered", "failed"];
+
+#[derive(Deserialize)]
+pub struct OutboundWebhookForm {
+    pub csrf_token: String,
+    pub slug: String,
+    pub label: String,
+    pub url: String,
+    #[serde(default)]
+    pub events: String,
+}
+
+async fn render_list(pool: &PgPool, session: &Session, errors: Vec<String>) -> Result<HttpResponse, AppError> {
+    let ctx = PageContext::build(session, pool, "/outbound-webhooks").await?;
+    let webhooks = outbound_webhook::find_all(pool).await?;
+    render(OutboundWebhooksTemplate { ctx, webhooks, errors })
+}
+
+fn back_to_list() -> HttpResponse {
+    HttpResponse::SeeOther()
+        .insert_header(("Location", "/outbound-webhooks"))
+        .finish()
+}
+
+/// GET /outbound-webhooks — systems notified of domain events.
+pub async fn list(
+    pool: web::Data<PgPool>,
+    session: Session,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    render_list(&pool, &session, Vec::new()).await
+}
+
+/// POST /outbound-webhooks — add an outbound webhook.
+pub async fn create(
+    pool: web::Data<PgPool>,
+    session: Session,
+    form: web::Form<OutboundWebhookForm>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+    let user_id = get_user_id(&session).unwrap_or(0);
+
+    let input = OutboundWebhookInput {
+        slug: form.slug.trim(),
+        label: &form.label,
+        url: &form.url,
+        events: &form.events,
+    };
+    let mut errors = outbound_webhook::validate(&input);
+    if errors.is_empty() && outbound_webhook::find_by_slug(&pool, input.slug).await?.is_some() {
+        errors.push(format!("An outbound webhook with slug '{}' already exists", input.slug));
+    }
+    if !errors.is_empty() {
+        return render_list(&pool, &session, errors).await;
+    }
+
+    let id = outbound_webhook::create(&pool, &input, user_id).await?;
+    let details = serde_json::json!({
+        "slug": input.slug,
+        "url": input.url.trim(),
+        "events": outbound_webhook::normalize_filter(input.events),
+        "summary": format!("Created outbound webhook '{}'", input.slug)
+    });
+    let _ = crate::audit::log(&pool, user_id, "outbound_webhook.created", "outbound_webhook", id, details).await;
+
+    let _ = session.insert("flash", "Outbound webhook created");
+    Ok(back_to_list())
+}
+
+/// POST /outbound-webhooks/{id}/secret — issue a new signing secret.
+pub async fn regenerate_secret(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+    form: web::Form<CsrfOnly>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+    let id = path.into_inner();
+    let user_id = get_user_id(&session).unwrap_or(0);
+
+    if outbound_webhook::find_by_id(&pool, id).await?.is_none() {
+        return Err(AppError::NotFound);
+    }
+    outbound_webhook::regenerate_secret(&pool, id).await?;
+    let details = serde_json::json!({ "summary": "Regenerated outbound webhook secret" });
+    let _ = crate::audit::log(&pool, user_id, "outbound_webhook.secret_rotated", "outbound_webhook", id, details).await;
+
+    let _ = session.insert("flash", "Secret regenerated — update the receiving system");
+    Ok(back_to_list())
+}
+
+#[derive(Deserialize)]
+pub struct ToggleForm {
+    pub csrf_token: String,
+    pub active: bool,
+}
+
+/// POST /outbound-webhooks/{id}/toggle — pause or resume deliveries.
+pub async fn toggle(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+    form: web::Form<ToggleForm>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+    let id = path.into_inner();
+    let user_id = get_user_id(&session).unwrap_or(0);
+
+    if !outbound_webhook::set_active(&pool, id, form.active).await? {
+        return Err(AppError::NotFound);
+    }
+    let action = if form.active { "outbound_webhook.enabled" } else { "outbound_webhook.disabled" };
+    let details = serde_json::json!({
+        "summary": if form.active { "Resumed outbound webhook" } else { "Paused outbound webhook" }
+    });
+    let _ = crate::audit::log(&pool, user_id, action, "outbound_webhook", id, details).await;
+
+    Ok(back_to_list())
+}
+
+/// POST /outbound-webhooks/{id}/delete — remove a webhook and its delivery log.
+pub async fn delete(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+    form: web::Form<CsrfOnly>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+    let id = path.into_inner();
+    let user_id = get_user_id(&session).unwrap_or(0);
+
+    if outbound_webhook::delete(&pool, id).await? {
+        let details = serde_json::json!({ "summary": "Deleted outbound webhook" });
+        let _ = crate::audit::log(&pool, user_id, "outbound_webhook.deleted", "outbound_webhook", id, details).await;
+        let _ = session.insert("flash", "Outbound webhook deleted");
+    }
+    Ok(back_to_list())
+}
+
+#[derive(Deserialize)]
+pub struct DeliveryQuery {
+    webhook: Option<i64>,
+    status: Option<String>,
+}
+
+/// GET /outbound-webhooks/deliveries — delivery log, newest first.
+pub async fn deliveries(
+    pool: web::Data<PgPool>,
+    session: Session,
+    query: web::Query<DeliveryQuery>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+
+    let ctx = PageContext::build(&session, &pool, "/outbound-webhooks").await?;
+    let status = query.status.clone().filter(|s| STATUSES.contains(&s.as_str()));
+    let deliveries = outbound_webhook::find_deliveries(&pool, query.webhook, status.as_deref(), 200).await?;
+    let webhooks = outbound_webhook::find_all(&pool).await?;
+
+    render(WebhookDeliveriesTemplate {
+        ctx,
+        deliveries,
+        webhooks,
+        webhook_id: query.webhook.unwrap_or(0),
+        status: status.unwrap_or_default(),
+    })
+}
+
+/// POST /outbound-webhooks/deliveries/{id}/retry — send a pending or failed
+/// delivery now, with a fresh set of attempts.
+pub async fn retry(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+    form: web::Form<CsrfOnly>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+    let id = path.into_inner();
+    let user_id = get_user_id(&session).unwrap_or(0);
+
+    if outbound_webhook::requeue(&pool, id).await? {
+        let delivery = outbound_webhook::find_delivery(&pool, id).await?.ok_or(AppError::NotFound)?;
+        let delivered = outbound_webhook::attempt(&pool, &delivery).await?;
+        let details = serde_json::json!({
+            "webhook_id": delivery.webhook_id,
+            "event_id": delivery.event_id,
+            "delivered": delivered,
+            "summary": format!("Retried webhook delivery {} of {}", id, delivery.event_type)
+        });
+        let _ = crate::audit::log(&pool, user_id, "outbound_webhook.retried", "webhook_delivery", id, details).await;
+        let _ = session.insert("flash", if delivered {
+            "Delivered"
+        } else {
+            "Delivery failed again — it will be retried automatically"
+        });
+    }
+    Ok(HttpResponse::SeeOther()
+        .insert_header(("Location", "/outbound-webhooks/deliveries"))
+        .finish())
+}
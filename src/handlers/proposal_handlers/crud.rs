@@ -5,9 +5,13 @@ use sqlx::PgPool;
 use crate::auth::csrf;
 use crate::auth::session::{require_permission, get_user_id};
 use crate::errors::{AppError, render};
-use crate::models::{tor, proposal};
+use crate::handlers::auth_handlers::CsrfOnly;
+use crate::handlers::{tor_handlers, transfer_handlers};
+use crate::models::{attachment, document, glossary, tor, proposal, quota, revision, setting};
+use crate::models::fiscal::FiscalCalendar;
 use crate::models::proposal::ProposalForm;
 use crate::templates_structs::{PageContext, ProposalFormTemplate, ProposalDetailTemplate};
+use crate::warnings::notifications;
 
 /// GET /tor/{tor_id}/proposals/{id}
 /// Renders the proposal detail page.
@@ -25,12 +29,48 @@ pub async fn detail(
     match proposal::find_by_id(&pool, proposal_id).await? {
         Some(p) => {
             let tor_name = tor::get_tor_name(&pool, tor_id).await?;
-            let ctx = PageContext::build(&session, &pool, "/workflow").await?
+            let related = proposal::find_related(&pool, proposal_id).await?;
+            let intake = proposal::find_intake(&pool, tor_id).await?;
+            let answers = proposal::find_section_answers(&pool, proposal_id).await?;
+            let attachments = proposal::find_attachments(&pool, proposal_id).await?;
+            let documents = document::find_all(&pool, Some(tor_id), None).await?
+                .into_iter()
+                .filter(|d| !attachments.iter().any(|a| a.id == d.id))
+                .collect();
+            let files = attachment::find_for_proposal(&pool, proposal_id).await?;
+            let max_upload_mb = setting::get_i64(&pool, attachment::MAX_SIZE_MB).await;
+            let watching = notifications::is_watching(&pool, user_id, proposal_id).await?;
+            let missing = if p.status == "draft" || p.status == "rejected" {
+                intake.missing(&answers, attachments.len() + files.len())
+            } else {
+                vec![]
+            };
+            let mut ctx = PageContext::build(&session, &pool, "/workflow").await?
                 .with_tor(tor_id, &tor_name, "workflow");
+            if tor_handlers::access_log::track_view(&pool, &session, proposal_id, tor_id).await? {
+                ctx = ctx.with_access_log(proposal_id);
+            }
+            let history = revision::diffs(revision::find_for(&pool, &[proposal_id]).await?, &[
+                ("title", &p.title),
+                ("description", &p.description),
+                ("rationale", &p.rationale),
+            ]);
             let tmpl = ProposalDetailTemplate {
                 ctx,
                 tor_id,
                 proposal: p,
+                related,
+                intake,
+                answers,
+                attachments,
+                documents,
+                files,
+                max_upload_mb,
+                missing,
+                watching,
+                can_transfer: transfer_handlers::can_transfer(&pool, &session, proposal_id).await?,
+                glossary: glossary::find_for_tor(&pool, tor_id).await?,
+                history,
             };
             render(tmpl)
         }
@@ -38,12 +78,20 @@ pub async fn detail(
     }
 }
 
+#[derive(serde::Deserialize)]
+pub struct NewProposalQuery {
+    /// Key of the ToR proposal template to start from.
+    pub template: Option<String>,
+}
+
 /// GET /tor/{tor_id}/proposals/new
-/// Renders the proposal creation form.
+/// Renders the proposal creation form, pre-filled from a proposal template
+/// when `?template=<key>` is given.
 pub async fn new_form(
     pool: web::Data<PgPool>,
     session: Session,
     path: web::Path<i64>,
+    query: web::Query<NewProposalQuery>,
 ) -> Result<HttpResponse, AppError> {
     require_permission(&session, "proposal.create")?;
 
@@ -52,6 +100,22 @@ pub async fn new_form(
     tor::require_tor_membership(&pool, user_id, tor_id).await?;
 
     let tor_name = tor::get_tor_name(&pool, tor_id).await?;
+    let intake = proposal::find_intake(&pool, tor_id).await?;
+    let templates = proposal::find_templates(&pool, tor_id).await?;
+    let template = query.template.as_deref()
+        .and_then(|key| templates.iter().find(|t| t.key == key));
+    let fiscal = FiscalCalendar::load(&pool).await;
+    let draft = template.map(|t| ProposalForm {
+        title: proposal::expand_title(&t.title_pattern, &tor_name, chrono::Local::now().date_naive(), &fiscal),
+        description: t.description.clone(),
+        rationale: t.rationale.clone(),
+        related_suggestion_id: None,
+        override_duplicates: None,
+        related_proposal_id: None,
+        csrf_token: String::new(),
+        fields: t.sections.iter().map(|(key, v)| (format!("section_{key}"), v.clone())).collect(),
+    });
+    let template_key = template.map(|t| t.key.clone()).unwrap_or_default();
     let ctx = PageContext::build(&session, &pool, "/workflow").await?
         .with_tor(tor_id, &tor_name, "workflow");
 
@@ -62,13 +126,21 @@ pub async fn new_form(
         form_action: format!("/tor/{tor_id}/proposals"),
         form_title: "New Proposal".to_string(),
         proposal: None,
+        draft,
         errors: vec![],
+        similar: vec![],
+        intake,
+        answers: Default::default(),
+        templates,
+        template_key,
     };
     render(tmpl)
 }
 
 /// POST /tor/{tor_id}/proposals
-/// Creates a new proposal linked to the ToR.
+/// Creates a new proposal linked to the ToR. When existing proposals in the
+/// ToR look similar, the form is shown again listing them; the user can then
+/// create anyway or create and link to one of them.
 pub async fn create(
     pool: web::Data<PgPool>,
     session: Session,
@@ -94,8 +166,34 @@ pub async fn create(
     if description.is_empty() {
         errors.push("Description is required".to_string());
     }
+    if let Some(message) = quota::check_open_proposals(&pool, user_id).await? {
+        errors.push(message);
+    }
 
-    if !errors.is_empty() {
+    let related_id = match form.related_proposal_id.as_deref().map(str::trim).filter(|v| !v.is_empty()) {
+        Some(v) => match v.parse::<i64>() {
+            Ok(id) if proposal::belongs_to_tor(&pool, id, tor_id).await? => Some(id),
+            _ => {
+                errors.push("The proposal to link to was not found in this ToR".to_string());
+                None
+            }
+        },
+        None => None,
+    };
+
+    // Duplicate check, unless the user already confirmed
+    let similar = if errors.is_empty() && related_id.is_none() && form.override_duplicates.is_none() {
+        proposal::find_similar(&pool, tor_id, title, description).await?
+    } else {
+        vec![]
+    };
+
+    let intake = proposal::find_intake(&pool, tor_id).await?;
+    let templates = proposal::find_templates(&pool, tor_id).await?;
+    let template = form.fields.get("template_key")
+        .and_then(|key| templates.iter().find(|t| &t.key == key))
+        .cloned();
+    if !errors.is_empty() || !similar.is_empty() {
         let tor_name = tor::get_tor_name(&pool, tor_id).await?;
         let ctx = PageContext::build(&session, &pool, "/workflow").await?
             .with_tor(tor_id, &tor_name, "workflow");
@@ -106,7 +204,13 @@ pub async fn create(
             form_action: format!("/tor/{tor_id}/proposals"),
             form_title: "New Proposal".to_string(),
             proposal: None,
+            draft: Some(form.clone()),
             errors,
+            similar,
+            intake,
+            answers: Default::default(),
+            template_key: template.map(|t| t.key).unwrap_or_default(),
+            templates,
         };
         return render(tmpl);
     }
@@ -119,16 +223,32 @@ pub async fn create(
     let proposal_id = proposal::create(
         &pool, tor_id, title, description, rationale, user_id, &today, None,
     ).await?;
+    proposal::save_section_answers(&pool, proposal_id, &intake, &form.section_answers()).await?;
+    if let Some(template) = &template {
+        proposal::apply_to_proposal(&pool, proposal_id, template).await?;
+    }
+    if let Some(related_id) = related_id {
+        proposal::link_related(&pool, proposal_id, related_id).await?;
+    }
+    // Authors follow their own proposals
+    notifications::watch(&pool, user_id, proposal_id).await?;
 
     // Audit log
     let details = serde_json::json!({
         "tor_id": tor_id,
         "title": title,
+        "related_proposal_id": related_id,
+        "template": template.as_ref().map(|t| t.key.as_str()),
         "summary": format!("Created proposal '{}'", title)
     });
     let _ = crate::audit::log(&pool, user_id, "proposal.created", "proposal", proposal_id, details).await;
 
-    let _ = session.insert("flash", "Proposal created successfully");
+    let flash = if related_id.is_some() {
+        "Proposal created and linked to the similar proposal"
+    } else {
+        "Proposal created successfully"
+    };
+    let _ = session.insert("flash", flash);
     Ok(HttpResponse::SeeOther()
         .insert_header(("Location", format!("/tor/{tor_id}/proposals/{proposal_id}")))
         .finish())
@@ -159,6 +279,8 @@ pub async fn edit_form(
             }
 
             let tor_name = tor::get_tor_name(&pool, tor_id).await?;
+            let intake = proposal::find_intake(&pool, tor_id).await?;
+            let answers = proposal::find_section_answers(&pool, proposal_id).await?;
             let ctx = PageContext::build(&session, &pool, "/workflow").await?
                 .with_tor(tor_id, &tor_name, "workflow");
             let tmpl = ProposalFormTemplate {
@@ -168,7 +290,13 @@ pub async fn edit_form(
                 form_action: format!("/tor/{tor_id}/proposals/{proposal_id}"),
                 form_title: "Edit Proposal".to_string(),
                 proposal: Some(p),
+                draft: None,
                 errors: vec![],
+                similar: vec![],
+                intake,
+                answers,
+                templates: vec![],
+                template_key: String::new(),
             };
             render(tmpl)
         }
@@ -177,7 +305,8 @@ pub async fn edit_form(
 }
 
 /// POST /tor/{tor_id}/proposals/{id}
-/// Updates an existing proposal's title, description, and rationale.
+/// Updates an existing proposal's title, description, rationale and intake
+/// section answers.
 pub async fn update(
     pool: web::Data<PgPool>,
     session: Session,
@@ -204,6 +333,7 @@ pub async fn update(
         errors.push("Description is required".to_string());
     }
 
+    let intake = proposal::find_intake(&pool, tor_id).await?;
     if !errors.is_empty() {
         let existing = proposal::find_by_id(&pool, proposal_id).await.ok().flatten();
         let tor_name = tor::get_tor_name(&pool, tor_id).await?;
@@ -216,12 +346,19 @@ pub async fn update(
             form_action: format!("/tor/{tor_id}/proposals/{proposal_id}"),
             form_title: "Edit Proposal".to_string(),
             proposal: existing,
+            draft: Some(form.clone()),
             errors,
+            similar: vec![],
+            intake,
+            answers: Default::default(),
+            templates: vec![],
+            template_key: String::new(),
         };
         return render(tmpl);
     }
 
-    proposal::update(&pool, proposal_id, title, description, rationale).await?;
+    proposal::update(&pool, proposal_id, title, description, rationale, user_id).await?;
+    proposal::save_section_answers(&pool, proposal_id, &intake, &form.section_answers()).await?;
 
     // Audit log
     let details = serde_json::json!({
@@ -236,3 +373,162 @@ pub async fn update(
         .insert_header(("Location", format!("/tor/{tor_id}/proposals/{proposal_id}")))
         .finish())
 }
+
+/// POST /tor/{tor_id}/proposals/{id}/revisions/{revision_id}/restore
+/// Puts the title, description and rationale back to an earlier revision.
+pub async fn restore_revision(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<(i64, i64, i64)>,
+    form: web::Form<CsrfOnly>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "proposal.edit")?;
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+
+    let (tor_id, proposal_id, revision_id) = path.into_inner();
+    let user_id = get_user_id(&session).ok_or(AppError::Session("User not logged in".to_string()))?;
+    tor::require_tor_membership(&pool, user_id, tor_id).await?;
+
+    let p = proposal::find_by_id(&pool, proposal_id).await?.ok_or(AppError::NotFound)?;
+    let rev = revision::find_by_id(&pool, revision_id).await?
+        .filter(|r| r.target_id == proposal_id)
+        .ok_or(AppError::NotFound)?;
+    let location = format!("/tor/{tor_id}/proposals/{proposal_id}");
+    if p.status != "draft" && p.status != "rejected" {
+        let _ = session.insert("flash", "Only draft and rejected proposals can be edited");
+        return Ok(HttpResponse::SeeOther().insert_header(("Location", location)).finish());
+    }
+
+    let title = rev.field("title");
+    proposal::update(&pool, proposal_id, title, rev.field("description"), rev.field("rationale"), user_id).await?;
+
+    let details = serde_json::json!({
+        "proposal_id": proposal_id,
+        "revision_id": revision_id,
+        "title": title,
+        "summary": format!("Restored proposal '{}' to the revision of {}", title, rev.edited_at)
+    });
+    let _ = crate::audit::log(&pool, user_id, "proposal.restored", "proposal", proposal_id, details).await;
+
+    let _ = session.insert("flash", "Proposal restored to the earlier version");
+    Ok(HttpResponse::SeeOther().insert_header(("Location", location)).finish())
+}
+
+#[derive(serde::Deserialize)]
+pub struct AttachForm {
+    pub csrf_token: String,
+    pub document_id: i64,
+}
+
+/// POST /tor/{tor_id}/proposals/{id}/attachments
+/// Attaches a document from the ToR's library to a draft or rejected proposal.
+pub async fn attach_document(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<(i64, i64)>,
+    form: web::Form<AttachForm>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "proposal.edit")?;
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+
+    let (tor_id, proposal_id) = path.into_inner();
+    let user_id = get_user_id(&session).ok_or(AppError::Session("User not logged in".to_string()))?;
+    tor::require_tor_membership(&pool, user_id, tor_id).await?;
+
+    let p = proposal::find_by_id(&pool, proposal_id).await?
+        .ok_or(AppError::NotFound)?;
+    let redirect = HttpResponse::SeeOther()
+        .insert_header(("Location", format!("/tor/{tor_id}/proposals/{proposal_id}")))
+        .finish();
+    if p.status != "draft" && p.status != "rejected" {
+        let _ = session.insert("flash", "Only draft or rejected proposals can be changed");
+        return Ok(redirect);
+    }
+    let doc = document::find_by_id(&pool, form.document_id).await?
+        .filter(|d| d.tor_id == tor_id)
+        .ok_or(AppError::NotFound)?;
+
+    proposal::attach_document(&pool, proposal_id, doc.id).await?;
+
+    let details = serde_json::json!({
+        "document_id": doc.id,
+        "summary": format!("Attached '{}' to proposal '{}'", doc.title, p.title)
+    });
+    let _ = crate::audit::log(&pool, user_id, "proposal.document_attached", "proposal", proposal_id, details).await;
+
+    let _ = session.insert("flash", "Document attached");
+    Ok(redirect)
+}
+
+/// POST /tor/{tor_id}/proposals/{id}/attachments/{document_id}/remove
+/// Detaches a document from a draft or rejected proposal.
+pub async fn detach_document(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<(i64, i64, i64)>,
+    form: web::Form<crate::handlers::auth_handlers::CsrfOnly>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "proposal.edit")?;
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+
+    let (tor_id, proposal_id, document_id) = path.into_inner();
+    let user_id = get_user_id(&session).ok_or(AppError::Session("User not logged in".to_string()))?;
+    tor::require_tor_membership(&pool, user_id, tor_id).await?;
+
+    let p = proposal::find_by_id(&pool, proposal_id).await?
+        .ok_or(AppError::NotFound)?;
+    let redirect = HttpResponse::SeeOther()
+        .insert_header(("Location", format!("/tor/{tor_id}/proposals/{proposal_id}")))
+        .finish();
+    if p.status != "draft" && p.status != "rejected" {
+        let _ = session.insert("flash", "Only draft or rejected proposals can be changed");
+        return Ok(redirect);
+    }
+
+    proposal::detach_document(&pool, proposal_id, document_id).await?;
+
+    let details = serde_json::json!({
+        "document_id": document_id,
+        "summary": format!("Removed attachment #{} from proposal '{}'", document_id, p.title)
+    });
+    let _ = crate::audit::log(&pool, user_id, "proposal.document_detached", "proposal", proposal_id, details).await;
+
+    let _ = session.insert("flash", "Attachment removed");
+    Ok(redirect)
+}
+
+#[derive(serde::Deserialize)]
+pub struct WatchForm {
+    pub csrf_token: String,
+    /// `watch` or `unwatch`.
+    pub action: String,
+}
+
+/// POST /tor/{tor_id}/proposals/{id}/watch
+/// Follows or stops following a proposal's status changes.
+pub async fn watch(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<(i64, i64)>,
+    form: web::Form<WatchForm>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "proposal.view")?;
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+
+    let (tor_id, proposal_id) = path.into_inner();
+    let user_id = get_user_id(&session).ok_or(AppError::Session("User not logged in".to_string()))?;
+    tor::require_tor_membership(&pool, user_id, tor_id).await?;
+    proposal::find_by_id(&pool, proposal_id).await?
+        .ok_or(AppError::NotFound)?;
+
+    if form.action == "unwatch" {
+        notifications::unwatch(&pool, user_id, proposal_id).await?;
+        let _ = session.insert("flash", "You no longer follow this proposal");
+    } else {
+        notifications::watch(&pool, user_id, proposal_id).await?;
+        let _ = session.insert("flash", "You will be notified when this proposal changes");
+    }
+    Ok(HttpResponse::SeeOther()
+        .insert_header(("Location", format!("/tor/{tor_id}/proposals/{proposal_id}")))
+        .finish())
+}
@@ -0,0 +1,178 @@
+use actix_session::Session;
+use actix_web::{web, HttpRequest, HttpResponse};
+use sqlx::PgPool;
+
+use crate::auth::csrf;
+use crate::auth::session::{require_permission, get_user_id};
+use crate::errors::AppError;
+use crate::handlers::multipart;
+use crate::models::{attachment, proposal, quota, tor};
+use crate::models::proposal::ProposalDetail;
+
+/// Largest request body the upload route reads: the highest allowed
+/// `uploads.max_size_mb` plus room for the other form fields.
+pub const UPLOAD_PAYLOAD_LIMIT: usize = 101 * 1024 * 1024;
+
+/// Route guard: the request carries a multipart form.
+pub fn is_multipart_request(ctx: &actix_web::guard::GuardContext) -> bool {
+    ctx.head().headers().get("content-type")
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(multipart::is_multipart)
+}
+
+/// Quoted-string safe file name for `Content-Disposition`.
+fn disposition_name(file_name: &str) -> String {
+    file_name.chars()
+        .map(|c| if c.is_ascii_graphic() && c != '"' && c != '\\' || c == ' ' { c } else { '_' })
+        .collect()
+}
+
+/// The proposal, if it was submitted to this ToR.
+async fn find_proposal(pool: &PgPool, tor_id: i64, proposal_id: i64) -> Result<ProposalDetail, AppError> {
+    if !proposal::belongs_to_tor(pool, proposal_id, tor_id).await? {
+        return Err(AppError::NotFound);
+    }
+    proposal::find_by_id(pool, proposal_id).await?.ok_or(AppError::NotFound)
+}
+
+/// POST /tor/{tor_id}/proposals/{id}/attachments (multipart/form-data)
+/// Uploads a file to a draft or rejected proposal.
+pub async fn upload_attachment(
+    pool: web::Data<PgPool>,
+    session: Session,
+    req: HttpRequest,
+    path: web::Path<(i64, i64)>,
+    body: web::Bytes,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "proposal.edit")?;
+    let content_type = req.headers().get("content-type")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("");
+    // A body that cannot be parsed has no CSRF token either
+    let parts = multipart::parse(content_type, &body).unwrap_or_default();
+    let token = parts.iter().find(|p| p.name == "csrf_token").map(|p| p.text()).unwrap_or_default();
+    csrf::validate_csrf(&session, &token)?;
+
+    let (tor_id, proposal_id) = path.into_inner();
+    let user_id = get_user_id(&session).ok_or(AppError::Session("User not logged in".to_string()))?;
+    tor::require_tor_membership(&pool, user_id, tor_id).await?;
+
+    let p = find_proposal(&pool, tor_id, proposal_id).await?;
+    let redirect = HttpResponse::SeeOther()
+        .insert_header(("Location", format!("/tor/{tor_id}/proposals/{proposal_id}")))
+        .finish();
+    if p.status != "draft" && p.status != "rejected" {
+        let _ = session.insert("flash", "Only draft or rejected proposals can be changed");
+        return Ok(redirect);
+    }
+
+    let file = parts.iter().find(|p| p.name == "file" && p.filename.is_some());
+    let file_name = file.and_then(|f| f.filename.as_deref()).unwrap_or("");
+    let data = file.map(|f| f.data.as_slice()).unwrap_or(&[]);
+    let max_bytes = attachment::max_bytes(&pool).await;
+    let stored_type = match attachment::validate(file_name, file.map(|f| f.content_type.as_str()).unwrap_or(""), data, max_bytes) {
+        Ok(t) => t,
+        Err(msg) => {
+            let _ = session.insert("flash", msg);
+            return Ok(redirect);
+        }
+    };
+    if let Some(msg) = quota::check_tor_storage(&pool, tor_id, data.len() as i64, 0).await? {
+        let _ = session.insert("flash", msg);
+        return Ok(redirect);
+    }
+
+    let dir = attachment::uploads_dir();
+    let key = match attachment::save_file(&dir, data) {
+        Ok(key) => key,
+        Err(e) => {
+            log::error!("Could not store upload in {}: {}", dir.display(), e);
+            let _ = session.insert("flash", "The file could not be stored; try again later");
+            return Ok(redirect);
+        }
+    };
+    let id = attachment::create(&pool, proposal_id, &key, file_name, stored_type, data, user_id).await?;
+
+    let details = serde_json::json!({
+        "attachment_id": id,
+        "file_name": file_name,
+        "content_type": stored_type,
+        "size_bytes": data.len(),
+        "summary": format!("Uploaded '{}' to proposal '{}'", file_name, p.title)
+    });
+    let _ = crate::audit::log(&pool, user_id, "proposal.file_uploaded", "proposal", proposal_id, details).await;
+
+    let _ = session.insert("flash", format!("'{}' uploaded", file_name));
+    Ok(redirect)
+}
+
+/// GET /tor/{tor_id}/proposals/{id}/attachments/{attachment_id}
+/// Downloads a file uploaded to a proposal.
+pub async fn download_attachment(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<(i64, i64, i64)>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "proposal.view")?;
+
+    let (tor_id, proposal_id, attachment_id) = path.into_inner();
+    let user_id = get_user_id(&session).ok_or(AppError::Session("User not logged in".to_string()))?;
+    tor::require_tor_membership(&pool, user_id, tor_id).await?;
+
+    find_proposal(&pool, tor_id, proposal_id).await?;
+    let file = attachment::find_by_id(&pool, attachment_id).await?
+        .filter(|a| a.proposal_id == proposal_id)
+        .ok_or(AppError::NotFound)?;
+    let bytes = attachment::read_file(&attachment::uploads_dir(), &file).map_err(|e| {
+        log::warn!("Upload {} of attachment #{} is unreadable: {}", file.storage_key, file.id, e);
+        AppError::NotFound
+    })?;
+
+    Ok(HttpResponse::Ok()
+        .content_type(file.content_type.clone())
+        .insert_header(("Content-Disposition", format!("attachment; filename=\"{}\"", disposition_name(&file.file_name))))
+        .insert_header(("X-Content-Type-Options", "nosniff"))
+        .insert_header(("X-Checksum-SHA256", file.sha256.clone()))
+        .body(bytes))
+}
+
+/// POST /tor/{tor_id}/proposals/{id}/attachments/{attachment_id}/delete
+/// Deletes a file uploaded to a draft or rejected proposal.
+pub async fn delete_attachment(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<(i64, i64, i64)>,
+    form: web::Form<crate::handlers::auth_handlers::CsrfOnly>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "proposal.edit")?;
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+
+    let (tor_id, proposal_id, attachment_id) = path.into_inner();
+    let user_id = get_user_id(&session).ok_or(AppError::Session("User not logged in".to_string()))?;
+    tor::require_tor_membership(&pool, user_id, tor_id).await?;
+
+    let p = find_proposal(&pool, tor_id, proposal_id).await?;
+    let redirect = HttpResponse::SeeOther()
+        .insert_header(("Location", format!("/tor/{tor_id}/proposals/{proposal_id}")))
+        .finish();
+    if p.status != "draft" && p.status != "rejected" {
+        let _ = session.insert("flash", "Only draft or rejected proposals can be changed");
+        return Ok(redirect);
+    }
+    let file = attachment::find_by_id(&pool, attachment_id).await?
+        .filter(|a| a.proposal_id == proposal_id)
+        .ok_or(AppError::NotFound)?;
+
+    attachment::delete(&pool, &attachment::uploads_dir(), &file).await?;
+
+    let details = serde_json::json!({
+        "attachment_id": file.id,
+        "file_name": file.file_name,
+        "sha256": file.sha256,
+        "summary": format!("Deleted '{}' from proposal '{}'", file.file_name, p.title)
+    });
+    let _ = crate::audit::log(&pool, user_id, "proposal.file_deleted", "proposal", proposal_id, details).await;
+
+    let _ = session.insert("flash", format!("'{}' deleted", file.file_name));
+    Ok(redirect)
+}
@@ -1,5 +1,7 @@
 mod crud;
+mod files;
 mod workflow;
 
 pub use crud::*;
+pub use files::*;
 pub use workflow::*;
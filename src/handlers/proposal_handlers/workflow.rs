@@ -4,12 +4,14 @@ use std::collections::HashMap;
 use sqlx::PgPool;
 
 use crate::auth::csrf;
-use crate::auth::session::{require_permission, get_user_id, get_permissions};
+use crate::auth::session::{require_permission, get_user_id, get_permissions, Permissions};
 use crate::errors::AppError;
-use crate::models::{tor, proposal, workflow};
+use crate::models::{delegation, rejection_reason, tor, proposal, workflow};
+use crate::warnings::notifications;
 
 /// POST /tor/{tor_id}/proposals/{id}/submit
-/// Submits a draft proposal for review.
+/// Submits a draft proposal for review, once the ToR's required intake
+/// sections and attachments are provided.
 pub async fn submit(
     pool: web::Data<PgPool>,
     session: Session,
@@ -27,21 +29,28 @@ pub async fn submit(
     let current_proposal = proposal::find_by_id(&pool, proposal_id).await?
         .ok_or(AppError::NotFound)?;
     let user_permissions = get_permissions(&session)
-        .map_err(|e| AppError::Session(e))?;
-    let entity_props = HashMap::new();
+        .map_err(AppError::Session)?;
 
-    // Validate workflow transition via workflow engine
-    workflow::validate_transition(
+    // The ToR's intake form must be complete
+    let missing = proposal::missing_for_submit(&pool, tor_id, proposal_id).await?;
+    if !missing.is_empty() {
+        let _ = session.insert("flash", format!("Cannot submit yet: {}", missing.join("; ")));
+        return Ok(HttpResponse::SeeOther()
+            .insert_header(("Location", format!("/tor/{tor_id}/proposals/{proposal_id}")))
+            .finish());
+    }
+
+    // Take the transition via the workflow engine
+    workflow::execute_transition(
         &pool,
         "proposal",
+        proposal_id,
         &current_proposal.status,
         "submitted",
         &user_permissions,
-        &entity_props,
+        user_id,
     ).await?;
 
-    proposal::update_status(&pool, proposal_id, "submitted", None).await?;
-
     // Audit log
     let details = serde_json::json!({
         "proposal_id": proposal_id,
@@ -49,6 +58,11 @@ pub async fn submit(
         "summary": format!("Submitted proposal #{} for review", proposal_id)
     });
     let _ = crate::audit::log(&pool, user_id, "proposal.submitted", "proposal", proposal_id, details).await;
+    let _ = notifications::notify_watchers(
+        &pool, proposal_id, user_id,
+        &format!("Proposal '{}' was submitted for review", current_proposal.title),
+        &format!("/tor/{tor_id}/proposals/{proposal_id}"),
+    ).await;
 
     let _ = session.insert("flash", "Proposal submitted for review");
     Ok(HttpResponse::SeeOther()
@@ -56,6 +70,42 @@ pub async fn submit(
         .finish())
 }
 
+/// Permissions to act with on a ToR's proposal: the user's own when they sit
+/// on the ToR and hold `permission`, otherwise those of an absent member who
+/// delegated to them (returned so the action is attributed to both).
+async fn acting_permissions(
+    pool: &PgPool,
+    session: &Session,
+    user_id: i64,
+    tor_id: i64,
+    permission: &str,
+) -> Result<(Permissions, Option<delegation::OutOfOffice>), AppError> {
+    let own = get_permissions(session)
+        .map_err(AppError::Session)?;
+    let membership = tor::require_tor_membership(pool, user_id, tor_id).await;
+    if own.has(permission) && membership.is_ok() {
+        return Ok((own, None));
+    }
+
+    let today = chrono::Local::now().date_naive();
+    for (principal, permissions) in delegation::find_principals_in_tor(pool, user_id, tor_id, today).await? {
+        if permissions.has(permission) {
+            return Ok((permissions, Some(principal)));
+        }
+    }
+
+    require_permission(session, permission)?;
+    membership?;
+    Err(AppError::PermissionDenied(permission.to_string()))
+}
+
+/// Audit summary suffix naming the absent member a delegate acted for.
+fn on_behalf_suffix(on_behalf_of: &Option<delegation::OutOfOffice>) -> String {
+    on_behalf_of.as_ref()
+        .map(|p| format!(" on behalf of {}", p.user_name))
+        .unwrap_or_default()
+}
+
 /// POST /tor/{tor_id}/proposals/{id}/review
 /// Starts review of a submitted proposal.
 pub async fn review(
@@ -64,39 +114,40 @@ pub async fn review(
     path: web::Path<(i64, i64)>,
     form: web::Form<crate::handlers::auth_handlers::CsrfOnly>,
 ) -> Result<HttpResponse, AppError> {
-    require_permission(&session, "proposal.review")?;
     csrf::validate_csrf(&session, &form.csrf_token)?;
 
     let (tor_id, proposal_id) = path.into_inner();
     let user_id = get_user_id(&session).ok_or(AppError::Session("User not logged in".to_string()))?;
-    tor::require_tor_membership(&pool, user_id, tor_id).await?;
+    let (user_permissions, on_behalf_of) = acting_permissions(&pool, &session, user_id, tor_id, "proposal.review").await?;
 
     // Get current status for workflow validation
     let current_proposal = proposal::find_by_id(&pool, proposal_id).await?
         .ok_or(AppError::NotFound)?;
-    let user_permissions = get_permissions(&session)
-        .map_err(|e| AppError::Session(e))?;
-    let entity_props = HashMap::new();
 
-    // Validate workflow transition via workflow engine
-    workflow::validate_transition(
+    // Take the transition via the workflow engine
+    workflow::execute_transition(
         &pool,
         "proposal",
+        proposal_id,
         &current_proposal.status,
         "under_review",
         &user_permissions,
-        &entity_props,
+        user_id,
     ).await?;
 
-    proposal::update_status(&pool, proposal_id, "under_review", None).await?;
-
     // Audit log
     let details = serde_json::json!({
         "proposal_id": proposal_id,
         "new_status": "under_review",
-        "summary": format!("Started review of proposal #{}", proposal_id)
+        "on_behalf_of": on_behalf_of.as_ref().map(|p| p.user_id),
+        "summary": format!("Started review of proposal #{}{}", proposal_id, on_behalf_suffix(&on_behalf_of))
     });
     let _ = crate::audit::log(&pool, user_id, "proposal.review_started", "proposal", proposal_id, details).await;
+    let _ = notifications::notify_watchers(
+        &pool, proposal_id, user_id,
+        &format!("Proposal '{}' was taken under review", current_proposal.title),
+        &format!("/tor/{tor_id}/proposals/{proposal_id}"),
+    ).await;
 
     let _ = session.insert("flash", "Proposal is now under review");
     Ok(HttpResponse::SeeOther()
@@ -112,39 +163,40 @@ pub async fn approve(
     path: web::Path<(i64, i64)>,
     form: web::Form<crate::handlers::auth_handlers::CsrfOnly>,
 ) -> Result<HttpResponse, AppError> {
-    require_permission(&session, "proposal.approve")?;
     csrf::validate_csrf(&session, &form.csrf_token)?;
 
     let (tor_id, proposal_id) = path.into_inner();
     let user_id = get_user_id(&session).ok_or(AppError::Session("User not logged in".to_string()))?;
-    tor::require_tor_membership(&pool, user_id, tor_id).await?;
+    let (user_permissions, on_behalf_of) = acting_permissions(&pool, &session, user_id, tor_id, "proposal.approve").await?;
 
     // Get current status for workflow validation
     let current_proposal = proposal::find_by_id(&pool, proposal_id).await?
         .ok_or(AppError::NotFound)?;
-    let user_permissions = get_permissions(&session)
-        .map_err(|e| AppError::Session(e))?;
-    let entity_props = HashMap::new();
 
-    // Validate workflow transition via workflow engine
-    workflow::validate_transition(
+    // Take the transition via the workflow engine
+    workflow::execute_transition(
         &pool,
         "proposal",
+        proposal_id,
         &current_proposal.status,
         "approved",
         &user_permissions,
-        &entity_props,
+        user_id,
     ).await?;
 
-    proposal::update_status(&pool, proposal_id, "approved", None).await?;
-
     // Audit log
     let details = serde_json::json!({
         "proposal_id": proposal_id,
         "new_status": "approved",
-        "summary": format!("Approved proposal #{}", proposal_id)
+        "on_behalf_of": on_behalf_of.as_ref().map(|p| p.user_id),
+        "summary": format!("Approved proposal #{}{}", proposal_id, on_behalf_suffix(&on_behalf_of))
     });
     let _ = crate::audit::log(&pool, user_id, "proposal.approved", "proposal", proposal_id, details).await;
+    let _ = notifications::notify_watchers(
+        &pool, proposal_id, user_id,
+        &format!("Proposal '{}' was approved", current_proposal.title),
+        &format!("/tor/{tor_id}/proposals/{proposal_id}"),
+    ).await;
 
     let _ = session.insert("flash", "Proposal approved");
     Ok(HttpResponse::SeeOther()
@@ -153,56 +205,77 @@ pub async fn approve(
 }
 
 /// POST /tor/{tor_id}/proposals/{id}/reject
-/// Rejects a proposal with a required reason.
+/// Rejects a proposal under a reason from the rejection taxonomy, with an
+/// optional note. While no reasons are configured the note is required instead.
 pub async fn reject(
     pool: web::Data<PgPool>,
     session: Session,
     path: web::Path<(i64, i64)>,
     form: web::Form<HashMap<String, String>>,
 ) -> Result<HttpResponse, AppError> {
-    require_permission(&session, "proposal.approve")?;
     let csrf_token = form.get("csrf_token").map(|s| s.as_str()).unwrap_or("");
     csrf::validate_csrf(&session, csrf_token)?;
 
     let (tor_id, proposal_id) = path.into_inner();
     let user_id = get_user_id(&session).ok_or(AppError::Session("User not logged in".to_string()))?;
-    tor::require_tor_membership(&pool, user_id, tor_id).await?;
+    let (user_permissions, on_behalf_of) = acting_permissions(&pool, &session, user_id, tor_id, "proposal.approve").await?;
 
-    let rejection_reason = form.get("rejection_reason").map(|s| s.trim().to_string()).unwrap_or_default();
-    if rejection_reason.is_empty() {
-        let _ = session.insert("flash", "Rejection reason is required");
+    let note = form.get("rejection_reason").map(|s| s.trim()).unwrap_or("");
+    let reasons = rejection_reason::find_active(&pool).await?;
+    let category = form.get("reason_id")
+        .and_then(|v| v.parse::<i64>().ok())
+        .and_then(|id| reasons.iter().find(|r| r.id == id));
+    let missing = match category {
+        None if !reasons.is_empty() => Some("Choose a rejection reason"),
+        None if note.is_empty() => Some("Rejection reason is required"),
+        _ => None,
+    };
+    if let Some(msg) = missing {
+        let _ = session.insert("flash", msg);
         return Ok(HttpResponse::SeeOther()
             .insert_header(("Location", format!("/tor/{tor_id}/proposals/{proposal_id}")))
             .finish());
     }
+    let rejection_reason = match category {
+        Some(reason) => rejection_reason::reason_text(&reason.label, note),
+        None => note.to_string(),
+    };
 
     // Get current status for workflow validation
     let current_proposal = proposal::find_by_id(&pool, proposal_id).await?
         .ok_or(AppError::NotFound)?;
-    let user_permissions = get_permissions(&session)
-        .map_err(|e| AppError::Session(e))?;
-    let entity_props = HashMap::new();
 
     // Validate workflow transition via workflow engine
-    workflow::validate_transition(
+    let transition = workflow::validate_transition(
         &pool,
         "proposal",
         &current_proposal.status,
         "rejected",
         &user_permissions,
-        &entity_props,
+        proposal_id,
     ).await?;
 
-    proposal::update_status(&pool, proposal_id, "rejected", Some(&rejection_reason)).await?;
+    match category {
+        Some(reason) => proposal::reject(&pool, proposal_id, &reason.name, &rejection_reason, user_id).await?,
+        None => proposal::transition(&pool, proposal_id, "rejected", Some(&rejection_reason), user_id).await?,
+    }
+    workflow::run_transition_hooks(&pool, "proposal", proposal_id, &current_proposal.status, &transition, user_id).await;
 
     // Audit log
     let details = serde_json::json!({
         "proposal_id": proposal_id,
         "new_status": "rejected",
         "rejection_reason": &rejection_reason,
-        "summary": format!("Rejected proposal #{}", proposal_id)
+        "rejection_category": category.map(|r| &r.name),
+        "on_behalf_of": on_behalf_of.as_ref().map(|p| p.user_id),
+        "summary": format!("Rejected proposal #{}{}", proposal_id, on_behalf_suffix(&on_behalf_of))
     });
     let _ = crate::audit::log(&pool, user_id, "proposal.rejected", "proposal", proposal_id, details).await;
+    let _ = notifications::notify_watchers(
+        &pool, proposal_id, user_id,
+        &format!("Proposal '{}' was rejected", current_proposal.title),
+        &format!("/tor/{tor_id}/proposals/{proposal_id}"),
+    ).await;
 
     let _ = session.insert("flash", "Proposal rejected");
     Ok(HttpResponse::SeeOther()
@@ -0,0 +1,110 @@
+use actix_session::Session;
+use actix_web::{web, HttpResponse};
+use serde::Deserialize;
+use sqlx::PgPool;
+
+use crate::auth::csrf;
+use crate::auth::session::get_user_id;
+use crate::errors::{AppError, render};
+use crate::models::{branding, setting};
+use crate::templates_structs::OfflineTemplate;
+use crate::warnings::notifications::push;
+
+/// The service worker, served from the root so its scope is the whole site.
+const SERVICE_WORKER: &str = include_str!("../../static/js/sw.js");
+/// Theme colour when the portal branding sets none.
+const DEFAULT_THEME_COLOR: &str = "#b45309";
+
+#[derive(Deserialize)]
+pub struct SubscriptionKeys {
+    pub p256dh: String,
+    pub auth: String,
+}
+
+#[derive(Deserialize)]
+pub struct SubscribeRequest {
+    pub csrf_token: String,
+    pub endpoint: String,
+    pub keys: SubscriptionKeys,
+    #[serde(default)]
+    pub device: String,
+}
+
+#[derive(Deserialize)]
+pub struct UnsubscribeRequest {
+    pub csrf_token: String,
+    pub endpoint: String,
+}
+
+fn current_user(session: &Session) -> Result<i64, AppError> {
+    get_user_id(session).ok_or(AppError::Session("User not logged in".to_string()))
+}
+
+/// GET /manifest.webmanifest — makes the app installable.
+pub async fn manifest(pool: web::Data<PgPool>) -> Result<HttpResponse, AppError> {
+    let app_name = setting::get_value(&pool, "app.name", "Ahlt").await;
+    let brand = branding::load(&pool, "app").await;
+    let theme_color = if brand.primary_color.is_empty() { DEFAULT_THEME_COLOR.to_string() } else { brand.primary_color };
+    let short_name: String = app_name.chars().take(12).collect();
+    let manifest = serde_json::json!({
+        "name": app_name,
+        "short_name": short_name,
+        "start_url": "/dashboard",
+        "scope": "/",
+        "display": "standalone",
+        "background_color": "#ffffff",
+        "theme_color": theme_color,
+        "icons": [
+            { "src": "/static/img/icon.svg", "sizes": "any", "type": "image/svg+xml", "purpose": "any maskable" }
+        ],
+    });
+    Ok(HttpResponse::Ok()
+        .content_type("application/manifest+json")
+        .body(manifest.to_string()))
+}
+
+/// GET /sw.js — the service worker; revalidated on every load so updates
+/// reach installed apps.
+pub async fn service_worker() -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("application/javascript")
+        .insert_header(("Cache-Control", "no-cache"))
+        .body(SERVICE_WORKER)
+}
+
+/// GET /offline — shown by the service worker for pages not kept offline.
+pub async fn offline(pool: web::Data<PgPool>) -> Result<HttpResponse, AppError> {
+    render(OfflineTemplate {
+        app_name: setting::get_value(&pool, "app.name", "Ahlt").await,
+        branding: branding::load(&pool, "portal").await,
+    })
+}
+
+/// POST /account/push/subscribe — register this browser for push (JSON).
+pub async fn subscribe(
+    pool: web::Data<PgPool>,
+    session: Session,
+    body: web::Json<SubscribeRequest>,
+) -> Result<HttpResponse, AppError> {
+    let user_id = current_user(&session)?;
+    csrf::validate_csrf(&session, &body.csrf_token)?;
+    if push::load_vapid(&pool).await.is_none() {
+        return Ok(HttpResponse::Conflict().json(serde_json::json!({ "error": "Push notifications are not enabled" })));
+    }
+    match push::subscribe(&pool, user_id, body.endpoint.trim(), &body.keys.p256dh, &body.keys.auth, body.device.trim()).await {
+        Ok(id) => Ok(HttpResponse::Created().json(serde_json::json!({ "id": id }))),
+        Err(error) => Ok(HttpResponse::BadRequest().json(serde_json::json!({ "error": error }))),
+    }
+}
+
+/// POST /account/push/unsubscribe — stop pushing to this browser (JSON).
+pub async fn unsubscribe(
+    pool: web::Data<PgPool>,
+    session: Session,
+    body: web::Json<UnsubscribeRequest>,
+) -> Result<HttpResponse, AppError> {
+    let user_id = current_user(&session)?;
+    csrf::validate_csrf(&session, &body.csrf_token)?;
+    push::unsubscribe(&pool, user_id, body.endpoint.trim()).await?;
+    Ok(HttpResponse::NoContent().finish())
+}
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use actix_session::Session;
 use actix_web::{web, HttpResponse};
 use serde::Deserialize;
@@ -6,7 +8,8 @@ use sqlx::PgPool;
 use crate::auth::csrf;
 use crate::auth::session::{require_permission, get_user_id};
 use crate::errors::{AppError, render};
-use crate::models::{tor, proposal, agenda_point, relation};
+use crate::models::{tor, proposal, agenda_point, coa, meeting, relation};
+use crate::models::meeting::capacity::{self, QueueItem, SchedulePlan};
 use crate::templates_structs::{PageContext, QueueTemplate};
 
 // ---------------------------------------------------------------------------
@@ -24,13 +27,71 @@ pub struct UnqueueForm {
     pub csrf_token: String,
 }
 
-#[derive(Debug, Deserialize)]
-pub struct BulkScheduleForm {
-    pub csrf_token: String,
-    #[serde(default)]
-    pub proposal_ids: Vec<i64>,
-    pub scheduled_date: String,
-    pub time_allocation_minutes: String,
+/// Scheduling choices carried between the form, the preview and the commit.
+struct ScheduleState {
+    errors: Vec<String>,
+    plan: Option<SchedulePlan>,
+    mode: String,
+    scheduled_date: String,
+    default_minutes: i32,
+    selected: Vec<i64>,
+    item_minutes: HashMap<i64, i32>,
+    /// Proposals to schedule as decision items.
+    decisions: Vec<i64>,
+}
+
+impl Default for ScheduleState {
+    fn default() -> Self {
+        Self {
+            errors: vec![],
+            plan: None,
+            mode: "date".to_string(),
+            scheduled_date: String::new(),
+            default_minutes: 15,
+            selected: vec![],
+            item_minutes: HashMap::new(),
+            decisions: vec![],
+        }
+    }
+}
+
+async fn current_date(pool: &PgPool) -> Result<String, AppError> {
+    sqlx::query_scalar("SELECT CURRENT_DATE::text")
+        .fetch_one(pool)
+        .await
+        .map_err(AppError::Db)
+}
+
+/// Render the queue page with the scheduling form in the given state.
+async fn render_queue(
+    pool: &PgPool,
+    session: &Session,
+    tor_id: i64,
+    state: ScheduleState,
+) -> Result<HttpResponse, AppError> {
+    let queued_proposals = proposal::find_queued_proposals(pool, tor_id).await?;
+    let upcoming = capacity::find_upcoming_capacity(pool, tor_id, &current_date(pool).await?).await?;
+
+    let tor_name = tor::get_tor_name(pool, tor_id).await?;
+    let ctx = PageContext::build(session, pool, "/workflow").await?
+        .with_tor(tor_id, &tor_name, "workflow");
+
+    let tmpl = QueueTemplate {
+        ctx,
+        tor_id,
+        tor_name,
+        queued_proposals,
+        errors: state.errors,
+        upcoming,
+        plan: state.plan,
+        mode: state.mode,
+        scheduled_date: state.scheduled_date,
+        default_minutes: state.default_minutes,
+        selected: state.selected,
+        item_minutes: state.item_minutes,
+        decisions: state.decisions,
+    };
+    render(tmpl)
 }
 
 // ---------------------------------------------------------------------------
@@ -51,21 +112,7 @@ pub async fn view_queue(
     let user_id = get_user_id(&session).ok_or(AppError::Session("User not logged in".to_string()))?;
     tor::require_tor_membership(&pool, user_id, tor_id).await?;
 
-    // Fetch queued proposals
-    let queued_proposals = proposal::find_queued_proposals(&pool, tor_id).await?;
-
-    let tor_name = tor::get_tor_name(&pool, tor_id).await?;
-    let ctx = PageContext::build(&session, &pool, "/workflow").await?
-        .with_tor(tor_id, &tor_name, "workflow");
-
-    let tmpl = QueueTemplate {
-        ctx,
-        tor_id,
-        tor_name,
-        queued_proposals,
-    };
-
-    render(tmpl)
+    render_queue(&pool, &session, tor_id, ScheduleState::default()).await
 }
 
 /// POST /tor/{id}/proposals/{pid}/ready-for-agenda
@@ -164,128 +211,196 @@ pub async fn schedule_form(
     let user_id = get_user_id(&session).ok_or(AppError::Session("User not logged in".to_string()))?;
     tor::require_tor_membership(&pool, user_id, tor_id).await?;
 
-    // Fetch queued proposals
-    let queued_proposals = proposal::find_queued_proposals(&pool, tor_id).await?;
-
-    let tor_name = tor::get_tor_name(&pool, tor_id).await?;
-    let ctx = PageContext::build(&session, &pool, "/workflow").await?
-        .with_tor(tor_id, &tor_name, "workflow");
-
-    let tmpl = QueueTemplate {
-        ctx,
-        tor_id,
-        tor_name,
-        queued_proposals,
-    };
-
-    render(tmpl)
+    render_queue(&pool, &session, tor_id, ScheduleState::default()).await
 }
 
 /// POST /tor/{id}/workflow/queue/schedule
-/// Bulk schedule selected proposals into agenda points for a specific date/time.
+/// Bulk schedule selected proposals (`proposal_<id>`, optional per-item
+/// `minutes_<id>`) into agenda points.
+///
+/// `mode=date` places everything on `scheduled_date`. `mode=distribute`
+/// spreads the items across upcoming meetings by remaining time: the first
+/// submit renders a preview; `action=commit` with `assign_<id>=<meeting id>`
+/// applies it if the meetings still have room.
 /// Requires: agenda.manage permission
 pub async fn bulk_schedule(
     pool: web::Data<PgPool>,
     session: Session,
     path: web::Path<i64>,
-    form: web::Form<BulkScheduleForm>,
+    form: web::Form<HashMap<String, String>>,
 ) -> Result<HttpResponse, AppError> {
     require_permission(&session, "agenda.manage")?;
-    csrf::validate_csrf(&session, &form.csrf_token)?;
+    csrf::validate_csrf(&session, form.get("csrf_token").map(|s| s.as_str()).unwrap_or(""))?;
 
     let tor_id = path.into_inner();
     let user_id = get_user_id(&session).ok_or(AppError::Session("User not logged in".to_string()))?;
     tor::require_tor_membership(&pool, user_id, tor_id).await?;
 
-    // Validation
-    let mut errors = vec![];
+    let mut state = ScheduleState {
+        mode: if form.get("mode").map(|s| s.as_str()) == Some("distribute") { "distribute" } else { "date" }.to_string(),
+        scheduled_date: form.get("scheduled_date").map(|s| s.trim().to_string()).unwrap_or_default(),
+        default_minutes: form.get("time_allocation_minutes").and_then(|s| s.trim().parse().ok()).unwrap_or(0),
+        ..Default::default()
+    };
 
-    if form.proposal_ids.is_empty() {
-        errors.push("Please select at least one proposal to schedule".to_string());
+    // Selected items in queue order, each with its time allocation
+    let mut items = Vec::new();
+    for p in proposal::find_queued_proposals(&pool, tor_id).await? {
+        if !form.contains_key(&format!("proposal_{}", p.id)) {
+            continue;
+        }
+        let minutes = match form.get(&format!("minutes_{}", p.id)).map(|s| s.trim()).filter(|s| !s.is_empty()) {
+            Some(value) => {
+                let minutes = value.parse().unwrap_or(0);
+                state.item_minutes.insert(p.id, minutes);
+                minutes
+            }
+            None => state.default_minutes,
+        };
+        state.selected.push(p.id);
+        if form.contains_key(&format!("decision_{}", p.id)) {
+            state.decisions.push(p.id);
+        }
+        items.push(QueueItem { proposal_id: p.id, title: p.title, minutes });
     }
 
-    if form.scheduled_date.trim().is_empty() {
-        errors.push("Scheduled date is required".to_string());
+    // Validation
+    if items.is_empty() {
+        state.errors.push("Please select at least one proposal to schedule".to_string());
     }
-
-    // Parse time allocation
-    let time_allocation_minutes: i32 = form.time_allocation_minutes
-        .trim()
-        .parse()
-        .unwrap_or(0);
-
-    if time_allocation_minutes <= 0 {
-        errors.push("Time allocation must be greater than 0 minutes".to_string());
+    if state.default_minutes <= 0 || items.iter().any(|i| i.minutes <= 0) {
+        state.errors.push("Time allocation must be greater than 0 minutes".to_string());
     }
-
-    // Validate that scheduled_date is not in the past
-    let today: String = sqlx::query_scalar("SELECT CURRENT_DATE::text")
-        .fetch_one(pool.get_ref())
-        .await
-        .map_err(AppError::Db)?;
-    if form.scheduled_date < today {
-        errors.push("Scheduled date cannot be in the past".to_string());
+    let today = current_date(&pool).await?;
+    if state.mode == "date" {
+        if state.scheduled_date.is_empty() {
+            state.errors.push("Scheduled date is required".to_string());
+        } else if state.scheduled_date < today {
+            state.errors.push("Scheduled date cannot be in the past".to_string());
+        }
+    }
+    if !state.errors.is_empty() {
+        return render_queue(&pool, &session, tor_id, state).await;
     }
 
-    if !errors.is_empty() {
-        let queued_proposals = proposal::find_queued_proposals(&pool, tor_id).await?;
-        let tor_name = tor::get_tor_name(&pool, tor_id).await?;
-        let ctx = PageContext::build(&session, &pool, "/workflow").await?
-            .with_tor(tor_id, &tor_name, "workflow");
-
-        let tmpl = QueueTemplate {
-            ctx,
-            tor_id,
-            tor_name,
-            queued_proposals,
-        };
+    if state.mode == "date" {
+        for item in &items {
+            let decision = state.decisions.contains(&item.proposal_id);
+            schedule_item(&pool, tor_id, user_id, item, decision, &state.scheduled_date, None).await?;
+        }
+
+        // Audit log
+        let details = serde_json::json!({
+            "scheduled_date": state.scheduled_date,
+            "count": items.len(),
+            "time_allocation_minutes": state.default_minutes,
+            "summary": format!("Scheduled {} proposals for {}", items.len(), state.scheduled_date)
+        });
+        let _ = crate::audit::log(&pool, user_id, "queue.bulk_scheduled", "agenda_point", tor_id, details).await;
+
+        let _ = session.insert("flash", format!("Scheduled {} proposals", items.len()));
+        return Ok(HttpResponse::SeeOther()
+            .insert_header(("Location", format!("/tor/{tor_id}/workflow?tab=agenda")))
+            .finish());
+    }
 
-        // For now, return the form with template (errors will need to be added to QueueTemplate in future)
-        return render(tmpl);
+    let upcoming = capacity::find_upcoming_capacity(&pool, tor_id, &today).await?;
+    if upcoming.is_empty() {
+        state.errors.push("No upcoming meetings to distribute across. Confirm meetings first or schedule on a single date.".to_string());
+        return render_queue(&pool, &session, tor_id, state).await;
     }
 
-    // Bulk schedule: create agenda points for each proposal
-    let mut scheduled_count = 0;
-    for proposal_id in &form.proposal_ids {
-        // Get the proposal to copy metadata
-        let proposal = proposal::find_by_id(&pool, *proposal_id).await?
-            .ok_or(AppError::NotFound)?;
-
-        // Create agenda point entity
-        let agenda_point_id = agenda_point::create(
-            &pool,
-            tor_id,
-            &proposal.title,
-            &format!("From proposal: {}", proposal.title),
-            "informative", // Default type; can be customized per proposal in future
-            &form.scheduled_date,
-            time_allocation_minutes,
-            user_id,
-            "", // presenter
-            "", // priority
-            "", // pre_read_url
-        ).await?;
-
-        // Create spawns_agenda_point relation: proposal → agenda_point
-        relation::create(&pool, "spawns_agenda_point", *proposal_id, agenda_point_id).await?;
-
-        // Remove proposal from queue (set ready_for_agenda=false)
-        proposal::unqueue_proposal(&pool, *proposal_id).await?;
-
-        scheduled_count += 1;
+    let assignments: Vec<(QueueItem, i64)> = items.iter()
+        .filter_map(|item| {
+            form.get(&format!("assign_{}", item.proposal_id))
+                .and_then(|s| s.parse().ok())
+                .map(|meeting_id| (item.clone(), meeting_id))
+        })
+        .collect();
+    let committing = form.get("action").map(|s| s.as_str()) == Some("commit") && assignments.len() == items.len();
+    let plan = if committing {
+        capacity::apply_assignments(upcoming.clone(), assignments)
+    } else {
+        None
+    };
+    let Some(plan) = plan else {
+        if committing {
+            state.errors.push("Meeting capacity changed since the preview. Review the updated distribution.".to_string());
+        }
+        state.plan = Some(capacity::plan_schedule(upcoming, items));
+        return render_queue(&pool, &session, tor_id, state).await;
+    };
+
+    let mut per_meeting = Vec::new();
+    for slot in plan.meetings.iter().filter(|m| !m.items.is_empty()) {
+        for item in &slot.items {
+            let decision = state.decisions.contains(&item.proposal_id);
+            schedule_item(&pool, tor_id, user_id, item, decision, &slot.meeting.meeting_date, Some(slot.meeting.meeting_id)).await?;
+        }
+        per_meeting.push(serde_json::json!({
+            "meeting_id": slot.meeting.meeting_id,
+            "meeting_date": slot.meeting.meeting_date,
+            "count": slot.items.len(),
+            "minutes": slot.planned_minutes(),
+        }));
     }
 
     // Audit log
     let details = serde_json::json!({
-        "scheduled_date": form.scheduled_date,
-        "count": scheduled_count,
-        "time_allocation_minutes": time_allocation_minutes,
-        "summary": format!("Scheduled {} proposals for {}", scheduled_count, form.scheduled_date)
+        "count": plan.placed_count(),
+        "meetings": per_meeting,
+        "summary": format!("Distributed {} proposals across {} meetings", plan.placed_count(), per_meeting.len())
     });
     let _ = crate::audit::log(&pool, user_id, "queue.bulk_scheduled", "agenda_point", tor_id, details).await;
 
-    let _ = session.insert("flash", format!("Scheduled {} proposals", scheduled_count));
+    let _ = session.insert("flash", format!("Scheduled {} proposals across {} meetings", plan.placed_count(), per_meeting.len()));
     Ok(HttpResponse::SeeOther()
         .insert_header(("Location", format!("/tor/{tor_id}/workflow?tab=agenda")))
         .finish())
 }
+
+/// Create the agenda point for a queued proposal and take it off the queue.
+/// Decision items start with the COAs of the proposal's template, or else
+/// with the default COA set.
+async fn schedule_item(
+    pool: &PgPool,
+    tor_id: i64,
+    user_id: i64,
+    item: &QueueItem,
+    decision: bool,
+    scheduled_date: &str,
+    meeting_id: Option<i64>,
+) -> Result<(), AppError> {
+    // Proposals from a template with default COAs are always decision items
+    let template_coas = proposal::find_default_coas(pool, item.proposal_id).await?;
+    let item_type = if decision || !template_coas.is_empty() { "decision" } else { "informative" };
+    let agenda_point_id = agenda_point::create(
+        pool,
+        tor_id,
+        &item.title,
+        &format!("From proposal: {}", item.title),
+        item_type,
+        scheduled_date,
+        item.minutes,
+        user_id,
+        "", // presenter
+        "", // priority
+        "", // pre_read_url
+    ).await?;
+
+    // Create spawns_agenda_point relation: proposal → agenda_point
+    relation::create(pool, "spawns_agenda_point", item.proposal_id, agenda_point_id).await?;
+    if !template_coas.is_empty() {
+        proposal::create_default_coas(pool, agenda_point_id, &template_coas, user_id).await?;
+    } else if item_type == "decision" {
+        coa::defaults::generate(pool, agenda_point_id, item_type, user_id).await?;
+    }
+
+    if let Some(meeting_id) = meeting_id {
+        meeting::assign_agenda(pool, meeting_id, agenda_point_id).await?;
+    }
+
+    // Remove proposal from queue (set ready_for_agenda=false)
+    proposal::unqueue_proposal(pool, item.proposal_id).await?;
+    Ok(())
+}
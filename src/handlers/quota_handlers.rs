@@ -0,0 +1,86 @@
+use std::collections::HashMap;
+
+use actix_session::Session;
+use actix_web::{web, HttpResponse};
+use sqlx::PgPool;
+
+use crate::auth::csrf;
+use crate::auth::session::{get_user_id, require_permission};
+use crate::errors::{AppError, render};
+use crate::models::{quota, role, setting};
+use crate::templates_structs::{PageContext, QuotasTemplate};
+use crate::warnings::clock;
+
+async fn render_report(pool: &PgPool, session: &Session, errors: Vec<String>) -> Result<HttpResponse, AppError> {
+    let ctx = PageContext::build(session, pool, "/quotas").await?;
+    let mut limits = Vec::new();
+    for (name, label) in [
+        (quota::OPEN_PROPOSALS, "Open proposals per user"),
+        (quota::API_REQUESTS_PER_DAY, "API requests per user per day"),
+        (quota::TOR_STORAGE_MB, "Storage per ToR (MB)"),
+    ] {
+        limits.push((label.to_string(), setting::get_i64(pool, name).await));
+    }
+    let roles = quota::find_role_quotas(pool).await?;
+    let users = quota::user_usage(pool, clock::now()).await?;
+    let tors = quota::tor_usage(pool).await?;
+    render(QuotasTemplate { ctx, limits, roles, users, tors, errors })
+}
+
+/// GET /quotas — quota limits, role overrides, and usage per user and ToR.
+pub async fn report(
+    pool: web::Data<PgPool>,
+    session: Session,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    render_report(&pool, &session, Vec::new()).await
+}
+
+/// POST /quotas/roles/{id} — set or clear a role's per-user quota overrides.
+/// Each quota is submitted under its setting name; empty clears the override.
+pub async fn update_role(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+    form: web::Form<HashMap<String, String>>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    csrf::validate_csrf(&session, form.get("csrf_token").map(String::as_str).unwrap_or(""))?;
+    let user_id = get_user_id(&session).unwrap_or(0);
+    let role_id = path.into_inner();
+    let role = role::find_by_id(&pool, role_id).await?.ok_or(AppError::NotFound)?;
+
+    // Validate everything before saving anything
+    let mut limits = Vec::new();
+    let mut errors = Vec::new();
+    for (name, label) in quota::USER_QUOTAS {
+        let value = form.get(*name).map(|v| v.trim()).unwrap_or("");
+        if value.is_empty() {
+            limits.push((*name, None));
+            continue;
+        }
+        match setting::validate(name, "number", value) {
+            Ok(()) => limits.push((*name, value.parse().ok())),
+            Err(e) => errors.push(format!("{} ({}): {}", label, role.label, e)),
+        }
+    }
+    if !errors.is_empty() {
+        return render_report(&pool, &session, errors).await;
+    }
+
+    for (name, limit) in &limits {
+        quota::set_role_limit(&pool, role_id, name, *limit).await?;
+    }
+
+    let details = serde_json::json!({
+        "role_id": role_id,
+        "limits": limits.iter().map(|(name, limit)| (name.to_string(), *limit)).collect::<HashMap<_, _>>(),
+        "summary": format!("Updated quota overrides for role '{}'", role.label)
+    });
+    let _ = crate::audit::log(&pool, user_id, "quota.role_updated", "role", role_id, details).await;
+
+    let _ = session.insert("flash", format!("Quotas updated for {}", role.label));
+    Ok(HttpResponse::SeeOther()
+        .insert_header(("Location", "/quotas"))
+        .finish())
+}
@@ -0,0 +1,224 @@
+use std::collections::HashMap;
+
+use actix_session::Session;
+use actix_web::{web, HttpResponse};
+use serde::Deserialize;
+use sqlx::PgPool;
+
+use crate::auth::csrf;
+use crate::auth::session::{get_permissions, get_user_id, require_permission};
+use crate::errors::{AppError, render};
+use crate::handlers::auth_handlers::CsrfOnly;
+use crate::handlers::warning_handlers::ws::ConnectionMap;
+use crate::models::recertification::{self, FALLBACK_PERMISSION};
+use crate::templates_structs::{
+    PageContext, RecertificationCampaignTemplate, RecertificationCampaignsTemplate, RecertificationTasksTemplate,
+};
+use crate::warnings::{clock, notifications};
+
+#[derive(Deserialize)]
+pub struct DecisionForm {
+    pub csrf_token: String,
+    pub decision: String,
+}
+
+fn redirect(location: &str) -> HttpResponse {
+    HttpResponse::SeeOther()
+        .insert_header(("Location", location.to_string()))
+        .finish()
+}
+
+async fn render_list(pool: &PgPool, session: &Session, errors: Vec<String>) -> Result<HttpResponse, AppError> {
+    let ctx = PageContext::build(session, pool, "/recertifications").await?;
+    let campaigns = recertification::find_all(pool).await?;
+    let default_deadline = (clock::now().date_naive() + chrono::Duration::days(14)).format("%Y-%m-%d").to_string();
+    render(RecertificationCampaignsTemplate { ctx, campaigns, default_deadline, errors })
+}
+
+/// Notify each reviewer of their share of a new campaign.
+/// Items without a chair go to every holder of `tor.manage_members`.
+async fn notify_reviewers(
+    pool: &PgPool,
+    conn_map: &ConnectionMap,
+    campaign_id: i64,
+    label: &str,
+    deadline: &str,
+    reviewers: &[(i64, usize)],
+) {
+    let mut notified = false;
+    for (reviewer_id, count) in reviewers {
+        let targets = if *reviewer_id == 0 {
+            crate::warnings::get_users_with_permission(pool, FALLBACK_PERMISSION).await.unwrap_or_default()
+        } else {
+            vec![*reviewer_id]
+        };
+        let message = format!(
+            "Access recertification '{}': confirm or revoke access for {} member{} by {}",
+            label, count, if *count == 1 { "" } else { "s" }, deadline
+        );
+        match notifications::send(pool, "event.recertification.task", &message, "/recertifications/tasks", &targets).await {
+            Ok(sent) => notified |= sent.is_some(),
+            Err(e) => log::error!("Failed to notify reviewer {} of campaign {}: {}", reviewer_id, campaign_id, e),
+        }
+    }
+    if notified {
+        crate::warnings::outbox::drain(pool, conn_map).await;
+    }
+}
+
+/// GET /recertifications — campaigns with their progress, and a launch form.
+pub async fn list(
+    pool: web::Data<PgPool>,
+    session: Session,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "roles.assign")?;
+    render_list(&pool, &session, Vec::new()).await
+}
+
+/// POST /recertifications — launch a campaign over every filled ToR position.
+pub async fn launch(
+    pool: web::Data<PgPool>,
+    conn_map: web::Data<ConnectionMap>,
+    session: Session,
+    form: web::Form<HashMap<String, String>>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "roles.assign")?;
+    csrf::validate_csrf(&session, form.get("csrf_token").map(String::as_str).unwrap_or(""))?;
+    let user_id = get_user_id(&session).ok_or(AppError::Session("User not logged in".to_string()))?;
+
+    let field = |key: &str| form.get(key).map(|v| v.trim()).unwrap_or("");
+    let (label, deadline, on_deadline) = (field("label"), field("deadline"), field("on_deadline"));
+    let errors = recertification::validate_launch(label, deadline, on_deadline, clock::now().date_naive());
+    if !errors.is_empty() {
+        return render_list(&pool, &session, errors).await;
+    }
+
+    let (id, reviewers) = recertification::launch(&pool, label, deadline, on_deadline, user_id).await?;
+    notify_reviewers(&pool, &conn_map, id, label, deadline, &reviewers).await;
+    let items: usize = reviewers.iter().map(|(_, count)| count).sum();
+
+    let details = serde_json::json!({
+        "deadline": deadline,
+        "on_deadline": on_deadline,
+        "items": items,
+        "reviewers": reviewers.len(),
+        "summary": format!("Launched access recertification '{}' with {} items due {}", label, items, deadline)
+    });
+    let _ = crate::audit::log(&pool, user_id, "recertification.launched", "recert_campaign", id, details).await;
+
+    let _ = session.insert("flash", format!("Campaign '{}' launched with {} items", label, items));
+    Ok(redirect(&format!("/recertifications/{id}")))
+}
+
+/// GET /recertifications/{id} — a campaign's items and their decisions.
+pub async fn detail(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "roles.assign")?;
+    let id = path.into_inner();
+    let campaign = recertification::find_by_id(&pool, id).await?.ok_or(AppError::NotFound)?;
+    let items = recertification::find_items(&pool, id).await?;
+    let ctx = PageContext::build(&session, &pool, "/recertifications").await?;
+    render(RecertificationCampaignTemplate { ctx, campaign, items })
+}
+
+/// POST /recertifications/{id}/close — end a campaign before its deadline,
+/// applying its deadline action to the pending items.
+pub async fn close(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+    form: web::Form<CsrfOnly>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "roles.assign")?;
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+    let user_id = get_user_id(&session).ok_or(AppError::Session("User not logged in".to_string()))?;
+    let id = path.into_inner();
+    let campaign = recertification::find_by_id(&pool, id).await?.ok_or(AppError::NotFound)?;
+    if !campaign.is_open() {
+        let _ = session.insert("flash", format!("Campaign '{}' is already closed", campaign.label));
+        return Ok(redirect(&format!("/recertifications/{id}")));
+    }
+
+    let (campaign, affected) = recertification::close(&pool, &campaign).await?;
+    if campaign.on_deadline == "revoke" {
+        for item in &affected {
+            let details = serde_json::json!({
+                "campaign_id": id,
+                "user_id": item.user_id,
+                "position_id": item.position_id,
+                "summary": format!("Revoked unconfirmed access of {} as {} in {}", item.user_label, item.position_label, item.tor_label)
+            });
+            let _ = crate::audit::log(&pool, user_id, "recertification.auto_revoked", "tor", item.tor_id, details).await;
+        }
+    }
+    let details = serde_json::json!({
+        "on_deadline": campaign.on_deadline,
+        "unconfirmed": affected.len(),
+        "summary": format!("Closed access recertification '{}' with {} unconfirmed items", campaign.label, affected.len())
+    });
+    let _ = crate::audit::log(&pool, user_id, "recertification.closed", "recert_campaign", id, details).await;
+
+    let _ = session.insert("flash", format!("Campaign '{}' closed", campaign.label));
+    Ok(redirect(&format!("/recertifications/{id}")))
+}
+
+/// GET /recertifications/tasks — the current user's members to confirm or revoke.
+pub async fn tasks(
+    pool: web::Data<PgPool>,
+    session: Session,
+) -> Result<HttpResponse, AppError> {
+    let user_id = get_user_id(&session).ok_or(AppError::Session("User not logged in".to_string()))?;
+    let permissions = get_permissions(&session)
+        .map_err(|e| AppError::Session(format!("Failed to get permissions: {}", e)))?;
+    let items = recertification::find_tasks(&pool, user_id, permissions.has(FALLBACK_PERMISSION)).await?;
+    let ctx = PageContext::build(&session, &pool, "/recertifications/tasks").await?;
+    render(RecertificationTasksTemplate { ctx, items })
+}
+
+/// POST /recertifications/items/{id} — confirm or revoke one member's access.
+pub async fn decide(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+    form: web::Form<DecisionForm>,
+) -> Result<HttpResponse, AppError> {
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+    let user_id = get_user_id(&session).ok_or(AppError::Session("User not logged in".to_string()))?;
+    let permissions = get_permissions(&session)
+        .map_err(|e| AppError::Session(format!("Failed to get permissions: {}", e)))?;
+    let item = recertification::find_item(&pool, path.into_inner()).await?.ok_or(AppError::NotFound)?;
+    let campaign = recertification::find_by_id(&pool, item.campaign_id).await?.ok_or(AppError::NotFound)?;
+    if !campaign.is_open() || !item.can_decide(user_id, permissions.has(FALLBACK_PERMISSION)) {
+        return Err(AppError::PermissionDenied("This access is not yours to recertify".to_string()));
+    }
+
+    let decision = match form.decision.as_str() {
+        "confirm" => recertification::CONFIRMED,
+        "revoke" => recertification::REVOKED,
+        _ => {
+            let _ = session.insert("flash", "Choose confirm or revoke");
+            return Ok(redirect("/recertifications/tasks"));
+        }
+    };
+    if recertification::decide(&pool, &item, decision, user_id).await? {
+        let details = serde_json::json!({
+            "campaign_id": item.campaign_id,
+            "user_id": item.user_id,
+            "position_id": item.position_id,
+            "summary": format!("{} access of {} as {} in {}",
+                if decision == recertification::CONFIRMED { "Confirmed" } else { "Revoked" },
+                item.user_label, item.position_label, item.tor_label)
+        });
+        let action = format!("recertification.{decision}");
+        let _ = crate::audit::log(&pool, user_id, &action, "tor", item.tor_id, details).await;
+        let _ = session.insert("flash", format!(
+            "{} {} as {}",
+            if decision == recertification::CONFIRMED { "Confirmed" } else { "Revoked" },
+            item.user_label, item.position_label
+        ));
+    }
+    Ok(redirect("/recertifications/tasks"))
+}
@@ -0,0 +1,156 @@
+use std::collections::HashMap;
+
+use actix_session::Session;
+use actix_web::{web, HttpResponse};
+use sqlx::PgPool;
+
+use crate::auth::csrf;
+use crate::auth::session::{get_user_id, require_permission};
+use crate::errors::{AppError, render};
+use crate::handlers::auth_handlers::CsrfOnly;
+use crate::models::rejection_reason::{self, RejectionReasonInput};
+use crate::templates_structs::{PageContext, RejectionReasonsTemplate};
+
+async fn render_list(pool: &PgPool, session: &Session, errors: Vec<String>) -> Result<HttpResponse, AppError> {
+    let ctx = PageContext::build(session, pool, "/rejection-reasons").await?;
+    let reasons = rejection_reason::find_all(pool).await?;
+    render(RejectionReasonsTemplate { ctx, reasons, errors })
+}
+
+fn input_from(form: &HashMap<String, String>) -> RejectionReasonInput<'_> {
+    let field = |key: &str| form.get(key).map(String::as_str).unwrap_or("");
+    RejectionReasonInput {
+        name: field("name"),
+        label: field("label"),
+        description: field("description"),
+    }
+}
+
+fn redirect() -> HttpResponse {
+    HttpResponse::SeeOther()
+        .insert_header(("Location", "/rejection-reasons"))
+        .finish()
+}
+
+/// GET /rejection-reasons — the reasons proposals can be rejected for.
+pub async fn list(
+    pool: web::Data<PgPool>,
+    session: Session,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    render_list(&pool, &session, Vec::new()).await
+}
+
+/// POST /rejection-reasons — add a reason.
+pub async fn create(
+    pool: web::Data<PgPool>,
+    session: Session,
+    form: web::Form<HashMap<String, String>>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    csrf::validate_csrf(&session, form.get("csrf_token").map(String::as_str).unwrap_or(""))?;
+    let user_id = get_user_id(&session).unwrap_or(0);
+
+    let input = input_from(&form);
+    let errors = rejection_reason::validate(&pool, &input, None).await?;
+    if !errors.is_empty() {
+        return render_list(&pool, &session, errors).await;
+    }
+    let id = rejection_reason::create(&pool, &input).await?;
+
+    let details = serde_json::json!({
+        "name": input.name.trim(),
+        "summary": format!("Created rejection reason '{}'", input.label.trim())
+    });
+    let _ = crate::audit::log(&pool, user_id, "rejection_reason.created", "rejection_reason", id, details).await;
+
+    let _ = session.insert("flash", format!("Rejection reason '{}' created", input.label.trim()));
+    Ok(redirect())
+}
+
+/// POST /rejection-reasons/{id} — update a reason's label and description.
+pub async fn update(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+    form: web::Form<HashMap<String, String>>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    csrf::validate_csrf(&session, form.get("csrf_token").map(String::as_str).unwrap_or(""))?;
+    let user_id = get_user_id(&session).unwrap_or(0);
+    let reason = rejection_reason::find_by_id(&pool, path.into_inner()).await?.ok_or(AppError::NotFound)?;
+
+    let input = input_from(&form);
+    let errors = rejection_reason::validate(&pool, &input, Some(reason.id)).await?;
+    if !errors.is_empty() {
+        return render_list(&pool, &session, errors).await;
+    }
+    rejection_reason::update(&pool, &reason, &input).await?;
+
+    let details = serde_json::json!({
+        "name": reason.name,
+        "old_label": reason.label,
+        "summary": format!("Updated rejection reason '{}'", input.label.trim())
+    });
+    let _ = crate::audit::log(&pool, user_id, "rejection_reason.updated", "rejection_reason", reason.id, details).await;
+
+    let _ = session.insert("flash", format!("Rejection reason '{}' updated", input.label.trim()));
+    Ok(redirect())
+}
+
+/// POST /rejection-reasons/{id}/toggle — retire a reason or bring it back.
+pub async fn toggle(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+    form: web::Form<CsrfOnly>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+    let user_id = get_user_id(&session).unwrap_or(0);
+    let reason = rejection_reason::find_by_id(&pool, path.into_inner()).await?.ok_or(AppError::NotFound)?;
+
+    let active = !reason.is_active;
+    rejection_reason::set_active(&pool, reason.id, active).await?;
+    let summary = if active {
+        format!("Restored rejection reason '{}'", reason.label)
+    } else {
+        format!("Retired rejection reason '{}'", reason.label)
+    };
+    let details = serde_json::json!({ "name": reason.name, "active": active, "summary": summary });
+    let _ = crate::audit::log(&pool, user_id, "rejection_reason.toggled", "rejection_reason", reason.id, details).await;
+
+    let _ = session.insert("flash", summary);
+    Ok(redirect())
+}
+
+/// POST /rejection-reasons/{id}/delete — delete a reason no rejection uses.
+pub async fn delete(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+    form: web::Form<CsrfOnly>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+    let user_id = get_user_id(&session).unwrap_or(0);
+    let reason = rejection_reason::find_by_id(&pool, path.into_inner()).await?.ok_or(AppError::NotFound)?;
+
+    if reason.usage > 0 {
+        let _ = session.insert("flash", format!(
+            "'{}' has been used for {} rejection(s); retire it instead so they keep their reason",
+            reason.label, reason.usage
+        ));
+        return Ok(redirect());
+    }
+    rejection_reason::delete(&pool, reason.id).await?;
+
+    let details = serde_json::json!({
+        "name": reason.name,
+        "summary": format!("Deleted rejection reason '{}'", reason.label)
+    });
+    let _ = crate::audit::log(&pool, user_id, "rejection_reason.deleted", "rejection_reason", reason.id, details).await;
+
+    let _ = session.insert("flash", format!("Rejection reason '{}' deleted", reason.label));
+    Ok(redirect())
+}
@@ -0,0 +1,199 @@
+use std::collections::HashMap;
+
+use actix_session::Session;
+use actix_web::{web, HttpResponse};
+use sqlx::PgPool;
+
+use crate::auth::csrf;
+use crate::auth::session::{get_user_id, require_permission};
+use crate::errors::{AppError, render};
+use crate::handlers::auth_handlers::CsrfOnly;
+use crate::models::report_definition::{self, ReportDefinition, ReportDefinitionInput};
+use crate::models::{distribution_group, tor};
+use crate::templates_structs::{PageContext, ReportDefinitionsTemplate};
+use crate::warnings::clock;
+
+async fn render_page(
+    pool: &PgPool,
+    session: &Session,
+    editing: Option<ReportDefinition>,
+    errors: Vec<String>,
+) -> Result<HttpResponse, AppError> {
+    let ctx = PageContext::build(session, pool, "/report-definitions").await?;
+    let definitions = report_definition::find_all(pool).await?;
+    let groups = distribution_group::find_all(pool).await?
+        .into_iter()
+        .map(|g| (g.key, g.label))
+        .collect();
+    let tors = tor::find_all_tors(pool).await?
+        .into_iter()
+        .map(|(id, _, label)| (id, label))
+        .collect();
+    render(ReportDefinitionsTemplate { ctx, definitions, groups, tors, editing, errors })
+}
+
+fn input_from(form: &HashMap<String, String>) -> ReportDefinitionInput<'_> {
+    let field = |key: &str| form.get(key).map(String::as_str).unwrap_or("");
+    ReportDefinitionInput {
+        label: field("label"),
+        report: field("report"),
+        parameter: field("parameter"),
+        tor_id: field("tor_id"),
+        schedule: field("schedule"),
+        format: field("format"),
+        group_key: field("group_key"),
+        delivery: field("delivery"),
+    }
+}
+
+fn redirect() -> HttpResponse {
+    HttpResponse::SeeOther()
+        .insert_header(("Location", "/report-definitions"))
+        .finish()
+}
+
+/// GET /report-definitions — scheduled reports sent to distribution groups.
+pub async fn list(
+    pool: web::Data<PgPool>,
+    session: Session,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    render_page(&pool, &session, None, Vec::new()).await
+}
+
+/// POST /report-definitions — add a scheduled report.
+pub async fn create(
+    pool: web::Data<PgPool>,
+    session: Session,
+    form: web::Form<HashMap<String, String>>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    csrf::validate_csrf(&session, form.get("csrf_token").map(String::as_str).unwrap_or(""))?;
+    let user_id = get_user_id(&session).ok_or(AppError::Session("User not logged in".to_string()))?;
+
+    let input = input_from(&form);
+    let errors = report_definition::validate(&pool, &input).await?;
+    if !errors.is_empty() {
+        return render_page(&pool, &session, None, errors).await;
+    }
+    let id = report_definition::create(&pool, &input, clock::now()).await?;
+
+    let details = serde_json::json!({
+        "report": input.report,
+        "schedule": input.schedule.trim(),
+        "group_key": input.group_key,
+        "summary": format!("Scheduled report '{}'", input.label.trim())
+    });
+    let _ = crate::audit::log(&pool, user_id, "report_definition.created", report_definition::ENTITY_TYPE, id, details).await;
+
+    let _ = session.insert("flash", format!("Scheduled report '{}' created", input.label.trim()));
+    Ok(redirect())
+}
+
+/// GET /report-definitions/{id} — edit a scheduled report.
+pub async fn edit(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    let def = report_definition::find_by_id(&pool, path.into_inner()).await?.ok_or(AppError::NotFound)?;
+    render_page(&pool, &session, Some(def), Vec::new()).await
+}
+
+/// POST /report-definitions/{id} — update a scheduled report.
+pub async fn update(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+    form: web::Form<HashMap<String, String>>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    csrf::validate_csrf(&session, form.get("csrf_token").map(String::as_str).unwrap_or(""))?;
+    let user_id = get_user_id(&session).ok_or(AppError::Session("User not logged in".to_string()))?;
+    let def = report_definition::find_by_id(&pool, path.into_inner()).await?.ok_or(AppError::NotFound)?;
+
+    let input = input_from(&form);
+    let errors = report_definition::validate(&pool, &input).await?;
+    if !errors.is_empty() {
+        return render_page(&pool, &session, Some(def), errors).await;
+    }
+    report_definition::update(&pool, def.id, &input, clock::now()).await?;
+
+    let details = serde_json::json!({
+        "old_schedule": def.schedule,
+        "schedule": input.schedule.trim(),
+        "group_key": input.group_key,
+        "summary": format!("Updated scheduled report '{}'", input.label.trim())
+    });
+    let _ = crate::audit::log(&pool, user_id, "report_definition.updated", report_definition::ENTITY_TYPE, def.id, details).await;
+
+    let _ = session.insert("flash", format!("Scheduled report '{}' updated", input.label.trim()));
+    Ok(redirect())
+}
+
+/// POST /report-definitions/{id}/toggle — pause or resume a scheduled report.
+pub async fn toggle(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+    form: web::Form<CsrfOnly>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+    let user_id = get_user_id(&session).ok_or(AppError::Session("User not logged in".to_string()))?;
+    let def = report_definition::find_by_id(&pool, path.into_inner()).await?.ok_or(AppError::NotFound)?;
+
+    let active = !def.is_active;
+    report_definition::set_active(&pool, &def, active, clock::now()).await?;
+    let summary = if active {
+        format!("Resumed scheduled report '{}'", def.label)
+    } else {
+        format!("Paused scheduled report '{}'", def.label)
+    };
+    let action = if active { "report_definition.resumed" } else { "report_definition.paused" };
+    let details = serde_json::json!({ "summary": summary });
+    let _ = crate::audit::log(&pool, user_id, action, report_definition::ENTITY_TYPE, def.id, details).await;
+
+    let _ = session.insert("flash", summary);
+    Ok(redirect())
+}
+
+/// POST /report-definitions/{id}/delete — delete a scheduled report and its deliveries.
+pub async fn delete(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+    form: web::Form<CsrfOnly>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+    let user_id = get_user_id(&session).ok_or(AppError::Session("User not logged in".to_string()))?;
+    let def = report_definition::find_by_id(&pool, path.into_inner()).await?.ok_or(AppError::NotFound)?;
+
+    report_definition::delete(&pool, def.id).await?;
+    let details = serde_json::json!({
+        "report": def.report,
+        "summary": format!("Deleted scheduled report '{}'", def.label)
+    });
+    let _ = crate::audit::log(&pool, user_id, "report_definition.deleted", report_definition::ENTITY_TYPE, def.id, details).await;
+
+    let _ = session.insert("flash", format!("Scheduled report '{}' deleted", def.label));
+    Ok(redirect())
+}
+
+/// GET /report-definitions/{id}/preview — the report as it would be sent now.
+pub async fn preview(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    let def = report_definition::find_by_id(&pool, path.into_inner()).await?.ok_or(AppError::NotFound)?;
+
+    let today = clock::now().with_timezone(&chrono::Local).date_naive();
+    let (report, _) = report_definition::build_report(&pool, &def, today).await?;
+    let content = crate::warnings::generators::render_report(&pool, &report, &def.format).await?;
+    let content_type = if def.format == "csv" { "text/csv; charset=utf-8" } else { "text/html; charset=utf-8" };
+    Ok(HttpResponse::Ok().content_type(content_type).body(content))
+}
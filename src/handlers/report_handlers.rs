@@ -0,0 +1,210 @@
+use actix_session::Session;
+use actix_web::{web, HttpResponse};
+use chrono::Local;
+use serde::Deserialize;
+use sqlx::PgPool;
+
+use crate::models::report_subscription::{self, ReportSubscription};
+use crate::models::{rejection_reason, sla_pack, survey, tor};
+use crate::auth::csrf;
+use crate::auth::session::{get_user_id, require_permission};
+use crate::errors::{AppError, render};
+use crate::handlers::auth_handlers::CsrfOnly;
+use crate::templates_structs::{PageContext, ReportSubscriptionsAdminTemplate, ReportSubscriptionsTemplate};
+
+#[derive(Deserialize)]
+pub struct SubscriptionForm {
+    pub csrf_token: String,
+    pub report: String,
+    pub format: String,
+    pub frequency: String,
+}
+
+#[derive(Deserialize)]
+pub struct ToggleForm {
+    pub csrf_token: String,
+    pub active: bool,
+}
+
+async fn render_list(
+    pool: &PgPool,
+    session: &Session,
+    user_id: i64,
+    errors: Vec<String>,
+) -> Result<HttpResponse, AppError> {
+    let ctx = PageContext::build(session, pool, "/reports/subscriptions").await?;
+    let subscriptions = report_subscription::find_for_user(pool, user_id).await?;
+    let deliveries = report_subscription::find_deliveries_for_user(pool, user_id).await?;
+    let mut health = Vec::new();
+    let mut sla = Vec::new();
+    for t in tor::find_user_tors(pool, user_id).await {
+        // A member with several positions appears once per position
+        if health.iter().any(|(id, _, _)| *id == t.tor_id) {
+            continue;
+        }
+        let score = survey::find_health_score(pool, t.tor_id).await?;
+        health.push((t.tor_id, t.tor_label, score));
+        sla.extend(sla_pack::find_compliance(pool, t.tor_id).await?);
+    }
+    let tor_ids: Vec<i64> = health.iter().map(|(id, _, _)| *id).collect();
+    let rejections = rejection_reason::find_summaries(pool, &tor_ids).await?;
+    render(ReportSubscriptionsTemplate { ctx, subscriptions, deliveries, errors, health, sla, rejections })
+}
+
+/// The signed-in user's subscription, or NotFound for anyone else's.
+async fn find_own(pool: &PgPool, id: i64, user_id: i64) -> Result<ReportSubscription, AppError> {
+    report_subscription::find_by_id(pool, id).await?
+        .filter(|s| s.user_id == user_id)
+        .ok_or(AppError::NotFound)
+}
+
+/// GET /reports/subscriptions — the user's report subscriptions and deliveries.
+pub async fn list(
+    pool: web::Data<PgPool>,
+    session: Session,
+) -> Result<HttpResponse, AppError> {
+    let user_id = get_user_id(&session).unwrap_or(0);
+    render_list(&pool, &session, user_id, Vec::new()).await
+}
+
+/// POST /reports/subscriptions — subscribe to a report.
+pub async fn create(
+    pool: web::Data<PgPool>,
+    session: Session,
+    form: web::Form<SubscriptionForm>,
+) -> Result<HttpResponse, AppError> {
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+    let user_id = get_user_id(&session).unwrap_or(0);
+
+    let errors = report_subscription::validate(&form.report, &form.format, &form.frequency);
+    if !errors.is_empty() {
+        return render_list(&pool, &session, user_id, errors).await;
+    }
+
+    let id = report_subscription::create(
+        &pool, user_id, &form.report, &form.format, &form.frequency, Local::now().date_naive(),
+    ).await?;
+    let details = serde_json::json!({
+        "report": form.report,
+        "format": form.format,
+        "frequency": form.frequency,
+        "summary": format!("Subscribed to the {} {} report", form.frequency, form.report)
+    });
+    let _ = crate::audit::log(&pool, user_id, "report_subscription.created", "report_subscription", id, details).await;
+
+    let _ = session.insert("flash", "Subscribed — you will be notified when the first report is ready");
+    Ok(HttpResponse::SeeOther()
+        .insert_header(("Location", "/reports/subscriptions"))
+        .finish())
+}
+
+/// POST /reports/subscriptions/{id}/toggle — pause or resume a subscription.
+pub async fn toggle(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+    form: web::Form<ToggleForm>,
+) -> Result<HttpResponse, AppError> {
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+    let user_id = get_user_id(&session).unwrap_or(0);
+    let sub = find_own(&pool, path.into_inner(), user_id).await?;
+
+    report_subscription::set_active(&pool, sub.id, form.active).await?;
+    let action = if form.active { "report_subscription.resumed" } else { "report_subscription.paused" };
+    let details = serde_json::json!({
+        "summary": format!("{} report subscription '{}'", if form.active { "Resumed" } else { "Paused" }, sub.report_label())
+    });
+    let _ = crate::audit::log(&pool, user_id, action, "report_subscription", sub.id, details).await;
+
+    Ok(HttpResponse::SeeOther()
+        .insert_header(("Location", "/reports/subscriptions"))
+        .finish())
+}
+
+/// POST /reports/subscriptions/{id}/delete — unsubscribe.
+pub async fn delete(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+    form: web::Form<CsrfOnly>,
+) -> Result<HttpResponse, AppError> {
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+    let user_id = get_user_id(&session).unwrap_or(0);
+    let sub = find_own(&pool, path.into_inner(), user_id).await?;
+
+    report_subscription::delete(&pool, sub.id).await?;
+    let details = serde_json::json!({
+        "summary": format!("Unsubscribed from '{}'", sub.report_label())
+    });
+    let _ = crate::audit::log(&pool, user_id, "report_subscription.deleted", "report_subscription", sub.id, details).await;
+
+    let _ = session.insert("flash", "Unsubscribed");
+    Ok(HttpResponse::SeeOther()
+        .insert_header(("Location", "/reports/subscriptions"))
+        .finish())
+}
+
+/// GET /reports/deliveries/{id} — download a delivered report (owner only).
+pub async fn download(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+) -> Result<HttpResponse, AppError> {
+    let user_id = get_user_id(&session).unwrap_or(0);
+    let delivery = report_subscription::find_delivery(&pool, path.into_inner()).await?
+        .filter(|d| d.user_id == user_id)
+        .ok_or(AppError::NotFound)?;
+
+    let watermark = crate::handlers::export_handlers::authorize_export(&pool, user_id, &delivery.format).await?;
+    let content = match &watermark {
+        Some(mark) if delivery.format == "csv" => mark.stamp_csv(delivery.content.clone()),
+        _ => delivery.content.clone(),
+    };
+    let disposition = if delivery.format == "csv" { "attachment" } else { "inline" };
+    crate::handlers::export_handlers::checksummed_download(
+        &pool,
+        user_id,
+        "report",
+        &delivery.filename(),
+        delivery.content_type(),
+        disposition,
+        content.into_bytes(),
+    ).await
+}
+
+/// GET /report-subscriptions — admin view of all active subscriptions.
+pub async fn admin_list(
+    pool: web::Data<PgPool>,
+    session: Session,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    let ctx = PageContext::build(&session, &pool, "/report-subscriptions").await?;
+    let subscriptions = report_subscription::find_all_active(&pool).await?;
+    render(ReportSubscriptionsAdminTemplate { ctx, subscriptions })
+}
+
+/// POST /report-subscriptions/{id}/suspend — pause another user's subscription.
+pub async fn admin_suspend(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+    form: web::Form<CsrfOnly>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+    let id = path.into_inner();
+    let user_id = get_user_id(&session).unwrap_or(0);
+
+    let sub = report_subscription::find_by_id(&pool, id).await?.ok_or(AppError::NotFound)?;
+    report_subscription::set_active(&pool, id, false).await?;
+    let details = serde_json::json!({
+        "owner_id": sub.user_id,
+        "summary": format!("Paused {}'s report subscription '{}'", sub.user_name, sub.report_label())
+    });
+    let _ = crate::audit::log(&pool, user_id, "report_subscription.paused", "report_subscription", id, details).await;
+
+    let _ = session.insert("flash", "Subscription paused");
+    Ok(HttpResponse::SeeOther()
+        .insert_header(("Location", "/report-subscriptions"))
+        .finish())
+}
@@ -11,7 +11,7 @@ use crate::templates_structs::{
     PermissionGroup,
 };
 use crate::audit;
-use crate::handlers::warning_handlers::ws::ConnectionMap;
+use crate::handlers::warning_handlers::ws::{self, ConnectionMap};
 
 pub async fn wizard_form(
     pool: web::Data<PgPool>,
@@ -116,6 +116,7 @@ pub async fn create_role(
 
     validate_role_name(&form.name)?;
     validate_role_label(&form.label)?;
+    let landing_page = validate_landing_page(&form.landing_page)?;
     ensure_unique_role_name(&pool, &form.name).await?;
 
     let permission_ids: Vec<i64> = serde_json::from_str(&form.permission_ids)
@@ -143,6 +144,8 @@ pub async fn create_role(
         .await?;
     }
 
+    role::set_landing_page(&pool, role_id, &landing_page).await?;
+
     let rt_id: i64 = sqlx::query_scalar(
         "SELECT id FROM entities WHERE entity_type='relation_type' AND name='has_permission'",
     )
@@ -189,6 +192,7 @@ pub async fn update_role(
 
     validate_role_name(&form.name)?;
     validate_role_label(&form.label)?;
+    let landing_page = validate_landing_page(&form.landing_page)?;
     ensure_unique_role_name_excluding(&pool, &form.name, role_id).await?;
 
     let permission_ids: Vec<i64> = serde_json::from_str(&form.permission_ids)
@@ -200,6 +204,8 @@ pub async fn update_role(
 
     role::update(&pool, role_id, form.name.trim(), form.label.trim(),
                  form.description.trim(), &permission_ids).await?;
+    role::set_landing_page(&pool, role_id, &landing_page).await?;
+    ws::notify_nav_updated(&pool, &conn_map).await?;
 
     // Audit log
     let user_id = get_user_id(&session).unwrap_or(0);
@@ -220,9 +226,7 @@ pub async fn update_role(
             .unwrap_or_default();
         if !admins.is_empty() {
             let _ = crate::warnings::create_receipts(&pool, wid, &admins).await;
-            crate::handlers::warning_handlers::ws::notify_users(
-                &conn_map, &pool, &admins, wid, "info", &msg,
-            ).await;
+            crate::warnings::outbox::drain(&pool, &conn_map).await;
         }
     }
 
@@ -232,6 +236,17 @@ pub async fn update_role(
         .finish())
 }
 
+/// Landing page must be empty (dashboard) or a same-site path.
+fn validate_landing_page(path: &str) -> Result<String, AppError> {
+    let path = path.trim();
+    if path.is_empty() {
+        return Ok(String::new());
+    }
+    crate::auth::redirect::safe_path(path)
+        .map(|p| p.to_string())
+        .ok_or_else(|| AppError::Session("Landing page must be a path starting with /".into()))
+}
+
 fn validate_role_name(name: &str) -> Result<(), AppError> {
     if name.is_empty() {
         return Err(AppError::Session("Role name required".into()));
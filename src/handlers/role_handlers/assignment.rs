@@ -7,6 +7,7 @@ use crate::models::relation;
 use crate::auth::csrf;
 use crate::auth::session::require_permission;
 use crate::errors::AppError;
+use crate::handlers::warning_handlers::ws::{self, ConnectionMap};
 
 #[derive(Deserialize)]
 pub struct AssignForm {
@@ -21,12 +22,14 @@ pub async fn assign(
     pool: web::Data<PgPool>,
     session: Session,
     form: web::Form<AssignForm>,
+    conn_map: web::Data<ConnectionMap>,
 ) -> Result<HttpResponse, AppError> {
     require_permission(&session, "roles.assign")?;
     csrf::validate_csrf(&session, &form.csrf_token)?;
 
     // relation::create uses INSERT OR IGNORE — safe against duplicates
     relation::create(&pool, "has_role", form.user_id, form.role_id).await?;
+    ws::notify_nav_updated(&pool, &conn_map).await?;
 
     // Audit
     let current_user_id = crate::auth::session::get_user_id(&session).unwrap_or(0);
@@ -56,6 +59,7 @@ pub async fn unassign(
     pool: web::Data<PgPool>,
     session: Session,
     form: web::Form<UnassignForm>,
+    conn_map: web::Data<ConnectionMap>,
 ) -> Result<HttpResponse, AppError> {
     require_permission(&session, "roles.assign")?;
     csrf::validate_csrf(&session, &form.csrf_token)?;
@@ -87,6 +91,7 @@ pub async fn unassign(
     }
 
     relation::delete(&pool, "has_role", form.user_id, form.role_id).await?;
+    ws::notify_nav_updated(&pool, &conn_map).await?;
 
     // Audit
     let current_user_id = crate::auth::session::get_user_id(&session).unwrap_or(0);
@@ -0,0 +1,112 @@
+use actix_session::Session;
+use actix_web::{web, HttpResponse};
+use serde::Deserialize;
+use sqlx::PgPool;
+
+use crate::auth::csrf;
+use crate::auth::session::{get_user_id, require_permission};
+use crate::errors::{AppError, render};
+use crate::models::scheduler_job;
+use crate::templates_structs::{PageContext, SchedulerTemplate};
+use crate::warnings::scheduler;
+
+#[derive(Deserialize)]
+pub struct JobForm {
+    pub csrf_token: String,
+}
+
+fn back_to_list() -> HttpResponse {
+    HttpResponse::SeeOther()
+        .insert_header(("Location", "/scheduler"))
+        .finish()
+}
+
+/// GET /scheduler — background jobs with their schedule, last run and failures.
+pub async fn list(
+    pool: web::Data<PgPool>,
+    session: Session,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+
+    let ctx = PageContext::build(&session, &pool, "/scheduler").await?;
+    let jobs = scheduler_job::find_all(&pool).await?;
+
+    render(SchedulerTemplate { ctx, jobs, tick_secs: scheduler::TICK_SECS })
+}
+
+/// Look up the job a control was sent for, by key.
+async fn find_job(pool: &PgPool, key: &str) -> Result<scheduler_job::JobState, AppError> {
+    if scheduler::find_job(key).is_none() {
+        return Err(AppError::NotFound);
+    }
+    scheduler_job::find_by_key(pool, key).await?.ok_or(AppError::NotFound)
+}
+
+/// POST /scheduler/{key}/pause — stop running a job on its schedule.
+pub async fn pause(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<String>,
+    form: web::Form<JobForm>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+    let job = find_job(&pool, &path.into_inner()).await?;
+
+    scheduler_job::set_paused(&pool, job.id, true).await?;
+
+    let user_id = get_user_id(&session).unwrap_or(0);
+    let details = serde_json::json!({ "job": job.key, "summary": format!("Paused scheduler job '{}'", job.label) });
+    let _ = crate::audit::log(&pool, user_id, "scheduler.job_paused", "scheduler_job", job.id, details).await;
+
+    let _ = session.insert("flash", format!("'{}' paused", job.label));
+    Ok(back_to_list())
+}
+
+/// POST /scheduler/{key}/resume — run a paused job on its schedule again.
+pub async fn resume(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<String>,
+    form: web::Form<JobForm>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+    let job = find_job(&pool, &path.into_inner()).await?;
+
+    scheduler_job::set_paused(&pool, job.id, false).await?;
+    scheduler::wake();
+
+    let user_id = get_user_id(&session).unwrap_or(0);
+    let details = serde_json::json!({ "job": job.key, "summary": format!("Resumed scheduler job '{}'", job.label) });
+    let _ = crate::audit::log(&pool, user_id, "scheduler.job_resumed", "scheduler_job", job.id, details).await;
+
+    let _ = session.insert("flash", format!("'{}' resumed", job.label));
+    Ok(back_to_list())
+}
+
+/// POST /scheduler/{key}/run — run a job now, even when paused.
+pub async fn run_now(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<String>,
+    form: web::Form<JobForm>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+    let job = find_job(&pool, &path.into_inner()).await?;
+
+    if job.running {
+        let _ = session.insert("flash", format!("'{}' is already running", job.label));
+        return Ok(back_to_list());
+    }
+    scheduler_job::request_run(&pool, job.id).await?;
+    scheduler::wake();
+
+    let user_id = get_user_id(&session).unwrap_or(0);
+    let details = serde_json::json!({ "job": job.key, "summary": format!("Requested a run of scheduler job '{}'", job.label) });
+    let _ = crate::audit::log(&pool, user_id, "scheduler.job_run_requested", "scheduler_job", job.id, details).await;
+
+    let _ = session.insert("flash", format!("'{}' queued to run now; refresh to see the result", job.label));
+    Ok(back_to_list())
+}
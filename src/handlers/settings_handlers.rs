@@ -1,13 +1,23 @@
+use std::collections::HashMap;
+
 use actix_session::Session;
 use actix_web::{web, HttpResponse};
 use sqlx::PgPool;
 
-use crate::models::setting;
+use crate::models::{branding, changelog, setting};
 use crate::audit;
 use crate::auth::csrf;
 use crate::auth::session::{get_user_id, require_permission};
 use crate::errors::{AppError, render};
-use crate::templates_structs::{PageContext, SettingsTemplate};
+use crate::handlers::auth_handlers::CsrfOnly;
+use crate::handlers::warning_handlers::ws::ConnectionMap;
+use crate::models::minutes::MinutesSection;
+use crate::templates_structs::{
+    BrandingPreviewTemplate, BrandingTemplate, LoginTemplate, MinutesExportTemplate, PageContext, SettingsTemplate,
+};
+
+/// How many recent changes the history drawer shows.
+const HISTORY_LIMIT: i64 = 50;
 
 /// Decode a URL-encoded string (form data): `+` → space, `%HH` → byte.
 fn url_decode(s: &str) -> String {
@@ -48,8 +58,9 @@ pub async fn list(
 
     let ctx = PageContext::build(&session, &pool, "/settings").await?;
     let settings = setting::find_all(&pool).await?;
+    let history = setting::find_history(&pool, HISTORY_LIMIT).await?;
 
-    let tmpl = SettingsTemplate { ctx, settings };
+    let tmpl = SettingsTemplate { ctx, settings, history, errors: Vec::new() };
     render(tmpl)
 }
 
@@ -62,6 +73,7 @@ fn get_field<'a>(params: &'a [(String, String)], key: &str) -> &'a str {
 
 pub async fn save(
     pool: web::Data<PgPool>,
+    conn_map: web::Data<ConnectionMap>,
     session: Session,
     body: String,
 ) -> Result<HttpResponse, AppError> {
@@ -73,27 +85,327 @@ pub async fn save(
     let current_user_id = get_user_id(&session).unwrap_or(0);
 
     // Each setting is submitted as setting_<id>=<value>
-    let mut changed = Vec::new();
-    for (key, value) in &params {
-        if let Some(id_str) = key.strip_prefix("setting_") {
-            if let Ok(id) = id_str.parse::<i64>() {
-                setting::update_value(&pool, id, value.trim()).await?;
-                changed.push(id);
+    let submitted: Vec<(i64, &str)> = params.iter()
+        .filter_map(|(key, value)| {
+            let id = key.strip_prefix("setting_")?.parse::<i64>().ok()?;
+            Some((id, value.trim()))
+        })
+        .collect();
+
+    // Validate everything before saving anything
+    let mut settings = setting::find_all(&pool).await?;
+    let mut errors = Vec::new();
+    for s in settings.iter_mut() {
+        if let Some(&(_, value)) = submitted.iter().find(|(id, _)| *id == s.id) {
+            if let Err(e) = setting::validate(&s.name, &s.setting_type, value) {
+                errors.push(format!("{}: {}", s.label, e));
             }
+            s.value = value.to_string();
+        }
+    }
+    if !errors.is_empty() {
+        let ctx = PageContext::build(&session, &pool, "/settings").await?;
+        let history = setting::find_history(&pool, HISTORY_LIMIT).await?;
+        return render(SettingsTemplate { ctx, settings, history, errors });
+    }
+
+    // Unchanged values are not recorded
+    let mut changed = Vec::new();
+    for (id, value) in submitted {
+        if let Some(change) = setting::change_value(&pool, id, value, current_user_id, "update").await? {
+            changed.push(change);
         }
     }
 
     if !changed.is_empty() {
         let details = serde_json::json!({
-            "setting_ids": changed,
+            "setting_ids": changed.iter().map(|c| c.setting_id).collect::<Vec<_>>(),
+            "changes": changed.iter().map(|c| serde_json::json!({
+                "name": c.name,
+                "old_value": c.old_value,
+                "new_value": c.new_value,
+            })).collect::<Vec<_>>(),
             "count": changed.len(),
             "summary": format!("Updated {} setting(s)", changed.len())
         });
         let _ = audit::log(&pool, current_user_id, "settings.update", "setting", 0, details).await;
     }
 
+    for change in &changed {
+        if change.critical {
+            notify_critical_change(&pool, &conn_map, current_user_id, change).await;
+        }
+        changelog::record_setting_change(&pool, change).await?;
+    }
+
     let _ = session.insert("flash", "Settings saved successfully");
     Ok(HttpResponse::SeeOther()
         .insert_header(("Location", "/settings"))
         .finish())
 }
+
+/// POST /settings/history/{change_id}/rollback — restore the value from before a change.
+pub async fn rollback(
+    pool: web::Data<PgPool>,
+    conn_map: web::Data<ConnectionMap>,
+    session: Session,
+    path: web::Path<i64>,
+    form: web::Form<CsrfOnly>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+
+    let change_id = path.into_inner();
+    let current_user_id = get_user_id(&session).unwrap_or(0);
+
+    match setting::rollback(&pool, change_id, current_user_id).await? {
+        Some(change) => {
+            let details = serde_json::json!({
+                "name": change.name,
+                "old_value": change.old_value,
+                "new_value": change.new_value,
+                "rolled_back_change": change_id,
+                "summary": format!("Rolled back '{}' to '{}'", change.label, change.new_value)
+            });
+            let _ = audit::log(&pool, current_user_id, "settings.rollback", "setting", change.setting_id, details).await;
+
+            if change.critical {
+                notify_critical_change(&pool, &conn_map, current_user_id, &change).await;
+            }
+            changelog::record_setting_change(&pool, &change).await?;
+            let _ = session.insert("flash", format!("Rolled back '{}'", change.label));
+        }
+        None => {
+            let _ = session.insert("flash", "Setting already has that value");
+        }
+    }
+
+    Ok(HttpResponse::SeeOther()
+        .insert_header(("Location", "/settings"))
+        .finish())
+}
+
+/// GET /settings/branding — logo, colours and footer per surface.
+pub async fn branding_page(
+    pool: web::Data<PgPool>,
+    session: Session,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+
+    let ctx = PageContext::build(&session, &pool, "/settings/branding").await?;
+    let surfaces = branding::load_all(&pool).await;
+    let has_draft = branding::has_draft(&pool).await;
+    render(BrandingTemplate { ctx, surfaces, has_draft, errors: Vec::new() })
+}
+
+/// POST /settings/branding — save a draft for preview. Fields are submitted as
+/// `<surface>_<field>`; an empty logo field keeps the current logo unless
+/// `<surface>_remove_logo` is set.
+pub async fn save_branding(
+    pool: web::Data<PgPool>,
+    session: Session,
+    form: web::Form<HashMap<String, String>>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    csrf::validate_csrf(&session, form.get("csrf_token").map(String::as_str).unwrap_or(""))?;
+    let current_user_id = get_user_id(&session).unwrap_or(0);
+
+    let field = |surface: &str, name: &str| {
+        form.get(&format!("{}_{}", surface, name)).map(|v| v.trim().to_string()).unwrap_or_default()
+    };
+
+    let mut surfaces = branding::load_all(&pool).await;
+    let mut errors = Vec::new();
+    for (key, _, current) in surfaces.iter_mut() {
+        let logo = match field(key, "logo") {
+            uploaded if !uploaded.is_empty() => uploaded,
+            _ if !field(key, "remove_logo").is_empty() => String::new(),
+            _ => std::mem::take(&mut current.logo),
+        };
+        *current = branding::Branding {
+            logo,
+            primary_color: field(key, "primary_color"),
+            accent_color: field(key, "accent_color"),
+            footer_text: field(key, "footer_text"),
+        };
+        errors.extend(branding::validate(key, current));
+    }
+    if !errors.is_empty() {
+        let ctx = PageContext::build(&session, &pool, "/settings/branding").await?;
+        let has_draft = branding::has_draft(&pool).await;
+        return render(BrandingTemplate { ctx, surfaces, has_draft, errors });
+    }
+
+    for (key, _, values) in &surfaces {
+        branding::save_draft(&pool, key, values).await?;
+    }
+
+    let details = serde_json::json!({
+        "surfaces": surfaces.iter().map(|(key, _, b)| serde_json::json!({
+            "surface": key,
+            "has_logo": !b.logo.is_empty(),
+            "primary_color": b.primary_color,
+            "accent_color": b.accent_color,
+            "footer_text": b.footer_text,
+        })).collect::<Vec<_>>(),
+        "summary": "Saved branding draft"
+    });
+    let _ = audit::log(&pool, current_user_id, "settings.branding_drafted", "setting", 0, details).await;
+
+    let _ = session.insert("flash", "Draft saved. Check the previews, then publish.");
+    Ok(HttpResponse::SeeOther()
+        .insert_header(("Location", "/settings/branding"))
+        .finish())
+}
+
+/// POST /settings/branding/publish — make the draft live everywhere.
+pub async fn publish_branding(
+    pool: web::Data<PgPool>,
+    session: Session,
+    form: web::Form<CsrfOnly>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+    let current_user_id = get_user_id(&session).unwrap_or(0);
+
+    if branding::publish(&pool).await? {
+        let details = serde_json::json!({ "summary": "Published branding" });
+        let _ = audit::log(&pool, current_user_id, "settings.branding_published", "setting", 0, details).await;
+        let _ = session.insert("flash", "Branding published");
+    } else {
+        let _ = session.insert("flash", "No draft to publish");
+    }
+    Ok(HttpResponse::SeeOther()
+        .insert_header(("Location", "/settings/branding"))
+        .finish())
+}
+
+/// POST /settings/branding/discard — drop the draft, keeping the published branding.
+pub async fn discard_branding(
+    pool: web::Data<PgPool>,
+    session: Session,
+    form: web::Form<CsrfOnly>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+    let current_user_id = get_user_id(&session).unwrap_or(0);
+
+    branding::discard_draft(&pool).await?;
+    let details = serde_json::json!({ "summary": "Discarded branding draft" });
+    let _ = audit::log(&pool, current_user_id, "settings.branding_discarded", "setting", 0, details).await;
+
+    let _ = session.insert("flash", "Draft discarded");
+    Ok(HttpResponse::SeeOther()
+        .insert_header(("Location", "/settings/branding"))
+        .finish())
+}
+
+#[derive(serde::Deserialize)]
+pub struct PreviewQuery {
+    #[serde(default)]
+    pub theme: String,
+}
+
+/// GET /settings/branding/preview/{surface}?theme=light|dark — a sample page
+/// rendered with the draft branding.
+pub async fn preview_branding(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<String>,
+    query: web::Query<PreviewQuery>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+
+    let surface = path.into_inner();
+    let theme = match query.theme.as_str() {
+        "light" | "dark" => query.theme.clone(),
+        _ => "auto".to_string(),
+    };
+    let draft = branding::load_draft(&pool, &surface).await;
+
+    match surface.as_str() {
+        "app" => {
+            let mut ctx = PageContext::build(&session, &pool, "/settings/branding").await?;
+            ctx.branding = draft;
+            if theme != "auto" {
+                ctx.theme = theme;
+            }
+            render(BrandingPreviewTemplate { ctx })
+        }
+        "portal" => render(LoginTemplate {
+            error: None,
+            app_name: setting::get_value(&pool, "app.name", "Ahlt").await,
+            csrf_token: String::new(),
+            next: String::new(),
+            branding: draft,
+            sso_label: None,
+        }),
+        "export" => render(MinutesExportTemplate {
+            branding: draft,
+            theme,
+            title: "Preview".to_string(),
+            meeting_name: "Board Meeting".to_string(),
+            generated_date: chrono::Local::now().format("%Y-%m-%d").to_string(),
+            sections: preview_sections(),
+        }),
+        _ => Err(AppError::NotFound),
+    }
+}
+
+/// Sample minutes shown in the export preview.
+fn preview_sections() -> Vec<MinutesSection> {
+    [
+        ("attendance", "Attendance", "Chair: A. Example\nSecretary: B. Example\nMembers present: 6 of 7"),
+        ("decisions", "Decisions", "1. The revised budget was approved.\n2. The rota review was deferred."),
+        ("action_items", "Action Items", "- Circulate the approved budget (Secretary, next week)"),
+    ]
+    .into_iter()
+    .enumerate()
+    .map(|(i, (section_type, label, content))| MinutesSection {
+        id: 0,
+        name: section_type.to_string(),
+        label: label.to_string(),
+        section_type: section_type.to_string(),
+        sequence_order: i as i64,
+        content: content.to_string(),
+        is_auto_generated: true,
+        circulated_content: None,
+    })
+    .collect()
+}
+
+/// Audit a critical setting change and warn every other settings admin.
+async fn notify_critical_change(
+    pool: &PgPool,
+    conn_map: &ConnectionMap,
+    actor_id: i64,
+    change: &setting::AppliedChange,
+) {
+    let details = serde_json::json!({
+        "name": change.name,
+        "old_value": change.old_value,
+        "new_value": change.new_value,
+        "summary": format!("Critical setting '{}' changed", change.label)
+    });
+    let _ = audit::log(pool, actor_id, "setting.critical_changed", "setting", change.setting_id, details.clone()).await;
+
+    let admins: Vec<i64> = crate::warnings::get_users_with_permission(pool, "settings.manage").await
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|&id| id != actor_id)
+        .collect();
+    if admins.is_empty() {
+        return;
+    }
+
+    let msg = format!(
+        "Critical setting '{}' changed from '{}' to '{}'",
+        change.label, change.old_value, change.new_value
+    );
+    if let Ok(wid) = crate::warnings::create_warning(
+        pool, "high", "security", "event.setting.critical_changed", &msg, &details.to_string(), "system"
+    ).await {
+        let _ = crate::warnings::create_receipts(pool, wid, &admins).await;
+        crate::warnings::outbox::drain(pool, conn_map).await;
+    }
+}
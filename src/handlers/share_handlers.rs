@@ -0,0 +1,257 @@
+use actix_session::Session;
+use actix_web::{web, HttpRequest, HttpResponse};
+use chrono::Local;
+use serde::Deserialize;
+use sqlx::PgPool;
+
+use crate::models::{branding, feed, minutes, opinion, share_token, tor};
+use crate::models::feed::{FeedFormat, FeedKind};
+use crate::models::share_token::{ShareToken, ShareTokenInput};
+use crate::auth::csrf;
+use crate::auth::session::{get_user_id, require_permission};
+use crate::errors::{AppError, render};
+use crate::handlers::auth_handlers::CsrfOnly;
+use crate::handlers::feed_handlers;
+use crate::templates_structs::{PageContext, ShareEmbedTemplate, ShareTokensTemplate};
+
+/// Days of upcoming meetings included in a shared calendar.
+const CALENDAR_DAYS: i64 = 60;
+
+#[derive(Deserialize)]
+pub struct ShareTokenForm {
+    pub csrf_token: String,
+    pub label: String,
+    pub view: String,
+    /// Empty for all ToRs.
+    #[serde(default)]
+    pub tor_id: String,
+    pub expires_at: String,
+}
+
+async fn render_list(
+    pool: &PgPool,
+    session: &Session,
+    errors: Vec<String>,
+    new_token: Option<String>,
+) -> Result<HttpResponse, AppError> {
+    let ctx = PageContext::build(session, pool, "/share-tokens").await?;
+    let tokens = share_token::find_all(pool).await?;
+    let tors = tor::find_all_tors(pool).await?;
+    let today = Local::now().date_naive().format("%Y-%m-%d").to_string();
+    render(ShareTokensTemplate { ctx, tokens, tors, errors, new_token, today })
+}
+
+/// GET /share-tokens — read-only share tokens for embedding.
+pub async fn list(
+    pool: web::Data<PgPool>,
+    session: Session,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    render_list(&pool, &session, Vec::new(), None).await
+}
+
+/// POST /share-tokens — issue a token. The raw token is shown once.
+pub async fn create(
+    pool: web::Data<PgPool>,
+    session: Session,
+    form: web::Form<ShareTokenForm>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+    let user_id = get_user_id(&session).unwrap_or(0);
+
+    let input = ShareTokenInput {
+        label: &form.label,
+        view: &form.view,
+        tor_id: form.tor_id.trim().parse().ok(),
+        expires_at: form.expires_at.trim(),
+    };
+    let errors = share_token::validate(&input, Local::now().date_naive());
+    if !errors.is_empty() {
+        return render_list(&pool, &session, errors, None).await;
+    }
+
+    let (id, raw) = share_token::create(&pool, &input, user_id).await?;
+    let details = serde_json::json!({
+        "view": input.view,
+        "tor_id": input.tor_id,
+        "expires_at": input.expires_at,
+        "summary": format!("Issued share token '{}' for the {} view", input.label.trim(), input.view)
+    });
+    let _ = crate::audit::log(&pool, user_id, "share_token.created", "share_token", id, details).await;
+
+    render_list(&pool, &session, Vec::new(), Some(raw)).await
+}
+
+#[derive(Deserialize)]
+pub struct ToggleForm {
+    pub csrf_token: String,
+    pub active: bool,
+}
+
+/// POST /share-tokens/{id}/toggle — suspend or reinstate a token.
+pub async fn toggle(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+    form: web::Form<ToggleForm>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+    let id = path.into_inner();
+    let user_id = get_user_id(&session).unwrap_or(0);
+
+    if !share_token::set_active(&pool, id, form.active).await? {
+        return Err(AppError::NotFound);
+    }
+    let action = if form.active { "share_token.enabled" } else { "share_token.disabled" };
+    let details = serde_json::json!({
+        "summary": if form.active { "Reinstated share token" } else { "Suspended share token" }
+    });
+    let _ = crate::audit::log(&pool, user_id, action, "share_token", id, details).await;
+
+    Ok(HttpResponse::SeeOther()
+        .insert_header(("Location", "/share-tokens"))
+        .finish())
+}
+
+/// POST /share-tokens/{id}/delete
+pub async fn delete(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+    form: web::Form<CsrfOnly>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+    let id = path.into_inner();
+    let user_id = get_user_id(&session).unwrap_or(0);
+
+    if !share_token::delete(&pool, id).await? {
+        return Err(AppError::NotFound);
+    }
+    let details = serde_json::json!({ "summary": "Deleted share token" });
+    let _ = crate::audit::log(&pool, user_id, "share_token.deleted", "share_token", id, details).await;
+
+    let _ = session.insert("flash", "Share token deleted");
+    Ok(HttpResponse::SeeOther()
+        .insert_header(("Location", "/share-tokens"))
+        .finish())
+}
+
+/// Data behind a shared view, limited to the token's ToR when set.
+async fn shared_data(pool: &PgPool, token: &ShareToken) -> Result<ShareEmbedTemplate, AppError> {
+    let mut tmpl = ShareEmbedTemplate {
+        branding: branding::Branding::default(),
+        title: token.label.clone(),
+        view: token.view.clone(),
+        tor_label: token.tor_label.clone(),
+        tors: Vec::new(),
+        dependencies: Vec::new(),
+        events: Vec::new(),
+        decisions: Vec::new(),
+        minutes: Vec::new(),
+    };
+    match token.view.as_str() {
+        "governance_map" => {
+            tmpl.tors = tor::find_all_tors(pool).await?;
+            tmpl.dependencies = tor::find_all_dependencies(pool).await?;
+        }
+        "tor_calendar" => {
+            let today = Local::now().date_naive();
+            tmpl.events = tor::compute_meetings(pool, today, today + chrono::Duration::days(CALENDAR_DAYS)).await?
+                .into_iter()
+                .filter(|e| token.tor_id.is_none_or(|id| e.tor_id == id))
+                .collect();
+        }
+        "decision_register" => {
+            tmpl.decisions = opinion::find_decision_register(pool, token.tor_id).await?;
+        }
+        "published_minutes" => {
+            tmpl.minutes = minutes::find_published(pool, token.tor_id, feed::MAX_ENTRIES as i64).await?;
+        }
+        _ => return Err(AppError::NotFound),
+    }
+    Ok(tmpl)
+}
+
+async fn resolve(pool: &PgPool, raw: &str) -> Result<ShareToken, AppError> {
+    let today = Local::now().date_naive().format("%Y-%m-%d").to_string();
+    share_token::resolve(pool, raw, &today).await?
+        .ok_or(AppError::NotFound)
+}
+
+/// GET /share/{token} — embeddable, read-only HTML view (no login).
+pub async fn embed(
+    pool: web::Data<PgPool>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, AppError> {
+    let token = resolve(&pool, &path.into_inner()).await?;
+    let mut tmpl = shared_data(&pool, &token).await?;
+    tmpl.branding = branding::load_for_page(&pool, "portal").await;
+    render(tmpl)
+}
+
+/// GET /share/{token}/data — the same view as JSON (no login).
+pub async fn embed_data(
+    pool: web::Data<PgPool>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, AppError> {
+    let token = resolve(&pool, &path.into_inner()).await?;
+    let data = shared_data(&pool, &token).await?;
+    let body = match token.view.as_str() {
+        "governance_map" => serde_json::json!({
+            "tors": data.tors.iter()
+                .map(|(id, name, label)| serde_json::json!({ "id": id, "name": name, "label": label }))
+                .collect::<Vec<_>>(),
+            "dependencies": data.dependencies.iter()
+                .map(|d| serde_json::json!({
+                    "source_tor_id": d.source_tor_id,
+                    "target_tor_id": d.target_tor_id,
+                    "relation_type": d.relation_type,
+                    "is_blocking": d.is_blocking,
+                }))
+                .collect::<Vec<_>>(),
+        }),
+        "tor_calendar" => serde_json::json!({ "meetings": data.events }),
+        "published_minutes" => serde_json::json!({
+            "minutes": data.minutes.iter()
+                .map(|m| serde_json::json!({
+                    "id": m.id,
+                    "tor_id": m.tor_id,
+                    "tor_label": m.tor_label,
+                    "meeting_date": m.meeting_date,
+                    "updated_at": m.updated_at,
+                }))
+                .collect::<Vec<_>>(),
+        }),
+        _ => serde_json::json!({ "decisions": data.decisions }),
+    };
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "view": token.view,
+        "tor": token.tor_label,
+        "data": body,
+    })))
+}
+
+/// GET /share/{token}/feed.{atom|rss} — the decision register or published
+/// minutes behind a share token as a feed (no login).
+pub async fn embed_feed(
+    pool: web::Data<PgPool>,
+    req: HttpRequest,
+    path: web::Path<(String, String)>,
+) -> Result<HttpResponse, AppError> {
+    let (raw, ext) = path.into_inner();
+    let format = FeedFormat::from_extension(&ext).ok_or(AppError::NotFound)?;
+    let token = resolve(&pool, &raw).await?;
+    let kind = match token.view.as_str() {
+        "decision_register" => FeedKind::Decisions,
+        "published_minutes" => FeedKind::Minutes,
+        _ => return Err(AppError::NotFound),
+    };
+    let tor = token.tor_id.zip(token.tor_label.as_deref());
+    let feed = feed::build(&pool, kind, tor, &feed_handlers::base_url(&req), req.path()).await?;
+    Ok(HttpResponse::Ok()
+        .content_type(format.content_type())
+        .body(feed.render(format)))
+}
@@ -0,0 +1,88 @@
+use actix_session::Session;
+use actix_web::{web, HttpResponse};
+use serde::Deserialize;
+use sqlx::PgPool;
+
+use crate::models::{setting, signing_key};
+use crate::auth::csrf;
+use crate::auth::session::{get_user_id, require_permission};
+use crate::auth::session_keys::SessionKeys;
+use crate::errors::{AppError, render};
+use crate::handlers::auth_handlers::CsrfOnly;
+use crate::templates_structs::{PageContext, SigningKeysTemplate};
+
+#[derive(Deserialize)]
+pub struct RotateForm {
+    pub csrf_token: String,
+    pub purpose: String,
+}
+
+/// GET /signing-keys — signing keys by purpose, and the session key ring.
+pub async fn list(
+    pool: web::Data<PgPool>,
+    session: Session,
+    session_keys: web::Data<SessionKeys>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    let ctx = PageContext::build(&session, &pool, "/signing-keys").await?;
+    let keys = signing_key::find_all(&pool).await?;
+    let grace_days = setting::get_i64(&pool, "security.signing_key_grace_days").await;
+    render(SigningKeysTemplate {
+        ctx,
+        keys,
+        grace_days,
+        previous_session_keys: session_keys.previous.len(),
+    })
+}
+
+/// POST /signing-keys/rotate — issue a new key for a purpose; the old one
+/// keeps verifying until retired.
+pub async fn rotate(
+    pool: web::Data<PgPool>,
+    session: Session,
+    form: web::Form<RotateForm>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+    let user_id = get_user_id(&session).unwrap_or(0);
+
+    let Some(kid) = signing_key::rotate(&pool, &form.purpose).await? else {
+        return Err(AppError::NotFound);
+    };
+    let label = signing_key::purpose_label(&form.purpose);
+    let details = serde_json::json!({
+        "purpose": form.purpose,
+        "kid": kid,
+        "summary": format!("Rotated the {} signing key", label)
+    });
+    let _ = crate::audit::log(&pool, user_id, "signing_key.rotated", "signing_key", 0, details).await;
+
+    let _ = session.insert("flash", format!("{} key rotated — the previous key keeps verifying until retired", label));
+    Ok(HttpResponse::SeeOther()
+        .insert_header(("Location", "/signing-keys"))
+        .finish())
+}
+
+/// POST /signing-keys/{id}/retire — stop accepting a rotated-out key now.
+pub async fn retire(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+    form: web::Form<CsrfOnly>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+    let id = path.into_inner();
+    let user_id = get_user_id(&session).unwrap_or(0);
+
+    if !signing_key::retire(&pool, id).await? {
+        return Err(AppError::NotFound);
+    }
+    let details = serde_json::json!({ "summary": "Retired a signing key" });
+    let _ = crate::audit::log(&pool, user_id, "signing_key.retired", "signing_key", id, details).await;
+
+    let _ = session.insert("flash", "Key retired");
+    Ok(HttpResponse::SeeOther()
+        .insert_header(("Location", "/signing-keys"))
+        .finish())
+}
@@ -0,0 +1,181 @@
+use std::collections::HashMap;
+
+use actix_session::Session;
+use actix_web::{web, HttpResponse};
+use sqlx::PgPool;
+
+use crate::auth::csrf;
+use crate::auth::session::{get_user_id, require_permission};
+use crate::errors::{AppError, render};
+use crate::handlers::auth_handlers::CsrfOnly;
+use crate::models::sla_pack::{self, SlaPackInput};
+use crate::models::tor;
+use crate::templates_structs::{PageContext, SlaPacksTemplate};
+use crate::warnings::clock;
+
+async fn render_list(pool: &PgPool, session: &Session, errors: Vec<String>) -> Result<HttpResponse, AppError> {
+    let ctx = PageContext::build(session, pool, "/sla-packs").await?;
+    let packs = sla_pack::find_all(pool).await?;
+    let assignments = sla_pack::find_assignments(pool).await?;
+    let computed_on = sqlx::query_scalar::<_, Option<String>>(
+        "SELECT TO_CHAR(MAX(computed_on), 'YYYY-MM-DD') FROM sla_compliance",
+    )
+    .fetch_one(pool)
+    .await?;
+    render(SlaPacksTemplate { ctx, packs, assignments, computed_on, errors })
+}
+
+fn input_from(form: &HashMap<String, String>) -> SlaPackInput<'_> {
+    let field = |key: &str| form.get(key).map(String::as_str).unwrap_or("");
+    SlaPackInput {
+        name: field("name"),
+        label: field("label"),
+        description: field("description"),
+        limits: sla_pack::METRICS.iter().map(|(key, _, _)| (*key, field(key))).collect(),
+    }
+}
+
+fn redirect() -> HttpResponse {
+    HttpResponse::SeeOther()
+        .insert_header(("Location", "/sla-packs"))
+        .finish()
+}
+
+/// GET /sla-packs — SLA policy packs and which ToR uses which.
+pub async fn list(
+    pool: web::Data<PgPool>,
+    session: Session,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    render_list(&pool, &session, Vec::new()).await
+}
+
+/// POST /sla-packs — create a pack.
+pub async fn create(
+    pool: web::Data<PgPool>,
+    session: Session,
+    form: web::Form<HashMap<String, String>>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    csrf::validate_csrf(&session, form.get("csrf_token").map(String::as_str).unwrap_or(""))?;
+    let user_id = get_user_id(&session).unwrap_or(0);
+
+    let input = input_from(&form);
+    let errors = sla_pack::validate(&pool, &input, None).await?;
+    if !errors.is_empty() {
+        return render_list(&pool, &session, errors).await;
+    }
+    let id = sla_pack::create(&pool, &input).await?;
+
+    let details = serde_json::json!({
+        "name": input.name.trim(),
+        "limits": input.limits.iter().map(|(k, v)| (k.to_string(), v.trim().to_string())).collect::<HashMap<_, _>>(),
+        "summary": format!("Created SLA pack '{}'", input.label.trim())
+    });
+    let _ = crate::audit::log(&pool, user_id, "sla_pack.created", "sla_pack", id, details).await;
+
+    let _ = session.insert("flash", format!("SLA pack '{}' created", input.label.trim()));
+    Ok(redirect())
+}
+
+/// POST /sla-packs/{id} — update a pack's name, description and limits.
+pub async fn update(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+    form: web::Form<HashMap<String, String>>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    csrf::validate_csrf(&session, form.get("csrf_token").map(String::as_str).unwrap_or(""))?;
+    let user_id = get_user_id(&session).unwrap_or(0);
+    let id = path.into_inner();
+    sla_pack::find_by_id(&pool, id).await?.ok_or(AppError::NotFound)?;
+
+    let input = input_from(&form);
+    let errors = sla_pack::validate(&pool, &input, Some(id)).await?;
+    if !errors.is_empty() {
+        return render_list(&pool, &session, errors).await;
+    }
+    sla_pack::update(&pool, id, &input).await?;
+
+    let details = serde_json::json!({
+        "name": input.name.trim(),
+        "limits": input.limits.iter().map(|(k, v)| (k.to_string(), v.trim().to_string())).collect::<HashMap<_, _>>(),
+        "summary": format!("Updated SLA pack '{}'", input.label.trim())
+    });
+    let _ = crate::audit::log(&pool, user_id, "sla_pack.updated", "sla_pack", id, details).await;
+
+    let _ = session.insert("flash", format!("SLA pack '{}' updated; compliance is re-measured tonight", input.label.trim()));
+    Ok(redirect())
+}
+
+/// POST /sla-packs/{id}/delete — delete a pack; its ToRs are left without one.
+pub async fn delete(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+    form: web::Form<CsrfOnly>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+    let user_id = get_user_id(&session).unwrap_or(0);
+    let id = path.into_inner();
+    let pack = sla_pack::find_by_id(&pool, id).await?.ok_or(AppError::NotFound)?;
+
+    sla_pack::delete(&pool, id).await?;
+    let details = serde_json::json!({
+        "name": pack.name,
+        "tor_count": pack.tor_count,
+        "summary": format!("Deleted SLA pack '{}'", pack.label)
+    });
+    let _ = crate::audit::log(&pool, user_id, "sla_pack.deleted", "sla_pack", id, details).await;
+
+    let _ = session.insert("flash", format!("SLA pack '{}' deleted", pack.label));
+    Ok(redirect())
+}
+
+/// POST /sla-packs/assign — assign a pack to a ToR; an empty `pack_id` clears it.
+pub async fn assign(
+    pool: web::Data<PgPool>,
+    session: Session,
+    form: web::Form<HashMap<String, String>>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    csrf::validate_csrf(&session, form.get("csrf_token").map(String::as_str).unwrap_or(""))?;
+    let user_id = get_user_id(&session).unwrap_or(0);
+
+    let tor_id: i64 = form.get("tor_id").and_then(|v| v.parse().ok()).ok_or(AppError::NotFound)?;
+    let tor_detail = tor::find_detail_by_id(&pool, tor_id).await?.ok_or(AppError::NotFound)?;
+    let pack = match form.get("pack_id").and_then(|v| v.parse::<i64>().ok()) {
+        Some(pack_id) => Some(sla_pack::find_by_id(&pool, pack_id).await?.ok_or(AppError::NotFound)?),
+        None => None,
+    };
+
+    sla_pack::assign(&pool, tor_id, pack.as_ref().map(|p| p.id)).await?;
+    let summary = match &pack {
+        Some(p) => format!("Assigned SLA pack '{}' to ToR '{}'", p.label, tor_detail.label),
+        None => format!("Cleared the SLA pack of ToR '{}'", tor_detail.label),
+    };
+    let details = serde_json::json!({
+        "pack_id": pack.as_ref().map(|p| p.id),
+        "summary": summary
+    });
+    let _ = crate::audit::log(&pool, user_id, "sla_pack.assigned", "tor", tor_id, details).await;
+
+    let _ = session.insert("flash", summary);
+    Ok(redirect())
+}
+
+/// POST /sla-packs/recompute — measure compliance now rather than tonight.
+pub async fn recompute(
+    pool: web::Data<PgPool>,
+    session: Session,
+    form: web::Form<CsrfOnly>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+
+    let measured = sla_pack::recompute(&pool, clock::now().date_naive()).await?;
+    let _ = session.insert("flash", format!("SLA compliance measured for {} ToR(s)", measured));
+    Ok(redirect())
+}
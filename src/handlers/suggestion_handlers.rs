@@ -4,11 +4,12 @@ use std::collections::HashMap;
 use sqlx::PgPool;
 
 use crate::auth::csrf;
-use crate::auth::session::{require_permission, get_user_id, get_permissions};
+use crate::auth::session::{require_permission, get_user_id, get_permissions, get_username};
 use crate::errors::{AppError, render};
 use crate::models::{tor, suggestion, proposal, workflow};
 use crate::models::suggestion::SuggestionForm;
 use crate::templates_structs::{PageContext, SuggestionFormTemplate};
+use crate::warnings::notifications;
 
 /// GET /tor/{tor_id}/suggestions/new
 /// Renders the suggestion creation form.
@@ -81,6 +82,12 @@ pub async fn create(
     });
     let _ = crate::audit::log(&pool, user_id, "suggestion.created", "suggestion", suggestion_id, details).await;
 
+    let author = get_username(&session).unwrap_or_default();
+    let _ = notifications::notify_mentions(
+        &pool, user_id, &author, description, "a suggestion",
+        &format!("/tor/{tor_id}/workflow?tab=suggestions"),
+    ).await;
+
     let _ = session.insert("flash", "Suggestion submitted successfully");
     Ok(HttpResponse::SeeOther()
         .insert_header(("Location", format!("/tor/{tor_id}/workflow?tab=suggestions")))
@@ -107,19 +114,17 @@ pub async fn accept(
         .ok_or(AppError::NotFound)?;
     let user_permissions = get_permissions(&session)
         .map_err(|e| AppError::Session(e))?;
-    let entity_props = HashMap::new();
 
-    // Validate workflow transition via workflow engine
-    workflow::validate_transition(
+    // Take the transition via the workflow engine
+    workflow::execute_transition(
         &pool,
         "suggestion",
+        suggestion_id,
         &current_suggestion.status,
         "accepted",
         &user_permissions,
-        &entity_props,
+        user_id,
     ).await?;
-
-    suggestion::update_status(&pool, suggestion_id, "accepted", None).await?;
     let proposal_id = proposal::auto_create_from_suggestion(&pool, suggestion_id, tor_id).await?;
 
     // Audit log
@@ -165,19 +170,19 @@ pub async fn reject(
         .ok_or(AppError::NotFound)?;
     let user_permissions = get_permissions(&session)
         .map_err(|e| AppError::Session(e))?;
-    let entity_props = HashMap::new();
 
     // Validate workflow transition via workflow engine
-    workflow::validate_transition(
+    let transition = workflow::validate_transition(
         &pool,
         "suggestion",
         &current_suggestion.status,
         "rejected",
         &user_permissions,
-        &entity_props,
+        suggestion_id,
     ).await?;
 
     suggestion::update_status(&pool, suggestion_id, "rejected", Some(&rejection_reason)).await?;
+    workflow::run_transition_hooks(&pool, "suggestion", suggestion_id, &current_suggestion.status, &transition, user_id).await;
 
     // Audit log
     let details = serde_json::json!({
@@ -192,3 +197,132 @@ pub async fn reject(
         .insert_header(("Location", format!("/tor/{tor_id}/workflow?tab=suggestions")))
         .finish())
 }
+
+/// POST /tor/{tor_id}/suggestions/{id}/reopen
+/// Reopens a suggestion closed for inactivity, within the grace period.
+/// Open to its submitter and to reviewers.
+pub async fn reopen(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<(i64, i64)>,
+    form: web::Form<crate::handlers::auth_handlers::CsrfOnly>,
+) -> Result<HttpResponse, AppError> {
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+
+    let (tor_id, suggestion_id) = path.into_inner();
+    let user_id = get_user_id(&session).ok_or(AppError::Session("User not logged in".to_string()))?;
+    tor::require_tor_membership(&pool, user_id, tor_id).await?;
+
+    let current_suggestion = suggestion::find_by_id(&pool, suggestion_id).await?
+        .ok_or(AppError::NotFound)?;
+    let reviewer = get_permissions(&session).is_ok_and(|p| p.has("suggestion.review"));
+    if current_suggestion.submitted_by_id != user_id && !reviewer {
+        return Err(AppError::PermissionDenied("suggestion.review".to_string()));
+    }
+    if !suggestion::can_reopen(&pool, suggestion_id, crate::warnings::clock::now()).await? {
+        let _ = session.insert("flash", "This suggestion can no longer be reopened");
+        return Ok(HttpResponse::SeeOther()
+            .insert_header(("Location", format!("/tor/{tor_id}/workflow?tab=suggestions")))
+            .finish());
+    }
+
+    suggestion::reopen(&pool, suggestion_id).await?;
+
+    // Audit log
+    let details = serde_json::json!({
+        "suggestion_id": suggestion_id,
+        "summary": format!("Reopened suggestion #{} after it was closed for inactivity", suggestion_id)
+    });
+    let _ = crate::audit::log(&pool, user_id, "suggestion.reopened", "suggestion", suggestion_id, details).await;
+
+    let _ = session.insert("flash", "Suggestion reopened");
+    Ok(HttpResponse::SeeOther()
+        .insert_header(("Location", format!("/tor/{tor_id}/workflow?tab=suggestions")))
+        .finish())
+}
+
+/// Form for turning a suggestion cluster into a proposal.
+#[derive(serde::Deserialize)]
+pub struct ClusterConvertForm {
+    pub csrf_token: String,
+    #[serde(default)]
+    pub title: String,
+}
+
+/// Load a proposed cluster of the ToR, or 404.
+async fn proposed_cluster(pool: &PgPool, tor_id: i64, cluster_id: i64) -> Result<suggestion::SuggestionCluster, AppError> {
+    suggestion::find_cluster(pool, cluster_id).await?
+        .filter(|c| c.tor_id == tor_id && c.status == "proposed")
+        .ok_or(AppError::NotFound)
+}
+
+/// POST /tor/{tor_id}/suggestion-clusters/{id}/convert
+/// Consolidates a cluster's open suggestions into one draft proposal.
+pub async fn convert_cluster(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<(i64, i64)>,
+    form: web::Form<ClusterConvertForm>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "suggestion.review")?;
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+
+    let (tor_id, cluster_id) = path.into_inner();
+    let user_id = get_user_id(&session).ok_or(AppError::Session("User not logged in".to_string()))?;
+    tor::require_tor_membership(&pool, user_id, tor_id).await?;
+    let cluster = proposed_cluster(&pool, tor_id, cluster_id).await?;
+
+    let title = form.title.trim();
+    let open = cluster.members.iter().filter(|m| m.status == "open").count();
+    if title.is_empty() || open == 0 {
+        let message = if title.is_empty() { "A proposal title is required" } else { "None of these suggestions is still open" };
+        let _ = session.insert("flash", message);
+        return Ok(HttpResponse::SeeOther()
+            .insert_header(("Location", format!("/tor/{tor_id}/workflow?tab=suggestions")))
+            .finish());
+    }
+
+    let today: String = sqlx::query_scalar("SELECT CURRENT_DATE::text")
+        .fetch_one(pool.get_ref())
+        .await?;
+    let proposal_id = suggestion::convert_cluster(&pool, &cluster, title, user_id, &today).await?;
+
+    // Audit log
+    let suggestion_ids: Vec<i64> = cluster.members.iter().filter(|m| m.status == "open").map(|m| m.id).collect();
+    let details = serde_json::json!({
+        "tor_id": tor_id,
+        "suggestion_ids": suggestion_ids,
+        "spawned_proposal_id": proposal_id,
+        "summary": format!("Consolidated {} suggestions into draft proposal #{}", suggestion_ids.len(), proposal_id)
+    });
+    let _ = crate::audit::log(&pool, user_id, "suggestion.cluster_converted", "suggestion_cluster", cluster_id, details).await;
+
+    let _ = session.insert("flash", format!("{} suggestions consolidated into a draft proposal", suggestion_ids.len()));
+    Ok(HttpResponse::SeeOther()
+        .insert_header(("Location", format!("/tor/{tor_id}/proposals/{proposal_id}")))
+        .finish())
+}
+
+/// POST /tor/{tor_id}/suggestion-clusters/{id}/dismiss
+/// Sets a proposed cluster aside; the same group is not proposed again.
+pub async fn dismiss_cluster(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<(i64, i64)>,
+    form: web::Form<crate::handlers::auth_handlers::CsrfOnly>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "suggestion.review")?;
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+
+    let (tor_id, cluster_id) = path.into_inner();
+    let user_id = get_user_id(&session).ok_or(AppError::Session("User not logged in".to_string()))?;
+    tor::require_tor_membership(&pool, user_id, tor_id).await?;
+    proposed_cluster(&pool, tor_id, cluster_id).await?;
+
+    suggestion::dismiss_cluster(&pool, cluster_id).await?;
+
+    let _ = session.insert("flash", "Cluster dismissed");
+    Ok(HttpResponse::SeeOther()
+        .insert_header(("Location", format!("/tor/{tor_id}/workflow?tab=suggestions")))
+        .finish())
+}
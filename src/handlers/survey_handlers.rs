@@ -0,0 +1,279 @@
+use std::collections::HashMap;
+
+use actix_session::Session;
+use actix_web::{web, HttpResponse};
+use serde::Deserialize;
+use sqlx::PgPool;
+
+use crate::models::{survey, tor};
+use crate::auth::csrf;
+use crate::auth::session::{get_user_id, require_permission};
+use crate::errors::{AppError, render};
+use crate::handlers::auth_handlers::CsrfOnly;
+use crate::templates_structs::{PageContext, SurveyTemplatesTemplate, TorAssessmentTemplate, TorAssessmentsTemplate};
+
+#[derive(Deserialize)]
+pub struct SurveyTemplateForm {
+    pub csrf_token: String,
+    pub label: String,
+    #[serde(default)]
+    pub description: String,
+    /// Rating questions, one per line.
+    #[serde(default)]
+    pub rating_questions: String,
+    /// Open questions, one per line.
+    #[serde(default)]
+    pub open_questions: String,
+}
+
+#[derive(Deserialize)]
+pub struct StartAssessmentForm {
+    pub csrf_token: String,
+    pub template_id: i64,
+    pub period: String,
+    #[serde(default)]
+    pub closes_on: String,
+}
+
+fn redirect(location: &str) -> HttpResponse {
+    HttpResponse::SeeOther()
+        .insert_header(("Location", location.to_string()))
+        .finish()
+}
+
+async fn render_templates(pool: &PgPool, session: &Session, errors: Vec<String>) -> Result<HttpResponse, AppError> {
+    let ctx = PageContext::build(session, pool, "/surveys").await?;
+    let templates = survey::find_templates(pool).await?;
+    render(SurveyTemplatesTemplate { ctx, templates, errors })
+}
+
+/// GET /surveys — questionnaire templates for ToR self-assessments.
+pub async fn templates(
+    pool: web::Data<PgPool>,
+    session: Session,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    render_templates(&pool, &session, Vec::new()).await
+}
+
+/// POST /surveys — define a questionnaire template.
+pub async fn create_template(
+    pool: web::Data<PgPool>,
+    session: Session,
+    form: web::Form<SurveyTemplateForm>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+
+    let questions = survey::parse_questions(&form.rating_questions, &form.open_questions);
+    let errors = survey::validate_template(&form.label, &questions);
+    if !errors.is_empty() {
+        return render_templates(&pool, &session, errors).await;
+    }
+
+    let id = survey::create_template(&pool, &form.label, &form.description, &questions).await?;
+    let user_id = get_user_id(&session).unwrap_or(0);
+    let details = serde_json::json!({
+        "label": form.label.trim(),
+        "questions": questions.len(),
+        "summary": format!("Created survey template '{}'", form.label.trim())
+    });
+    let _ = crate::audit::log(&pool, user_id, "survey_template.created", "survey_template", id, details).await;
+    let _ = session.insert("flash", "Survey template created");
+    Ok(redirect("/surveys"))
+}
+
+/// POST /surveys/{id}/delete — remove a questionnaire template.
+pub async fn delete_template(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+    form: web::Form<CsrfOnly>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+
+    let id = path.into_inner();
+    let template = survey::find_template(&pool, id).await?.ok_or(AppError::NotFound)?;
+    survey::delete_template(&pool, id).await?;
+
+    let user_id = get_user_id(&session).unwrap_or(0);
+    let details = serde_json::json!({
+        "label": template.label,
+        "summary": format!("Deleted survey template '{}'", template.label)
+    });
+    let _ = crate::audit::log(&pool, user_id, "survey_template.deleted", "survey_template", id, details).await;
+    let _ = session.insert("flash", "Survey template deleted");
+    Ok(redirect("/surveys"))
+}
+
+/// GET /tor/{id}/assessments — the ToR's self-assessments and health score.
+pub async fn assessments(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "tor.list")?;
+
+    let tor_id = path.into_inner();
+    let tor_detail = tor::find_detail_by_id(&pool, tor_id).await?
+        .ok_or(AppError::NotFound)?;
+
+    let assessments = survey::find_assessments(&pool, tor_id).await?;
+    let templates = survey::find_templates(&pool).await?;
+    let health = survey::find_health_score(&pool, tor_id).await?;
+
+    let tor_label = tor_detail.label;
+    let ctx = PageContext::build(&session, &pool, "/tor").await?
+        .with_tor(tor_id, &tor_label, "overview");
+    render(TorAssessmentsTemplate { ctx, tor_id, tor_label, assessments, templates, health })
+}
+
+/// POST /tor/{id}/assessments — start a self-assessment from a template.
+pub async fn start_assessment(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+    form: web::Form<StartAssessmentForm>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "tor.edit")?;
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+
+    let tor_id = path.into_inner();
+    tor::find_detail_by_id(&pool, tor_id).await?
+        .ok_or(AppError::NotFound)?;
+    let list = format!("/tor/{tor_id}/assessments");
+
+    let Some(template) = survey::find_template(&pool, form.template_id).await? else {
+        let _ = session.insert("flash", "Select a survey template");
+        return Ok(redirect(&list));
+    };
+    if form.period.trim().is_empty() {
+        let _ = session.insert("flash", "Name the period being assessed");
+        return Ok(redirect(&list));
+    }
+    let closes_on = form.closes_on.trim();
+    if !closes_on.is_empty() && chrono::NaiveDate::parse_from_str(closes_on, "%Y-%m-%d").is_err() {
+        let _ = session.insert("flash", "Invalid closing date");
+        return Ok(redirect(&list));
+    }
+
+    let id = survey::start_assessment(&pool, tor_id, &template, &form.period, closes_on).await?;
+    let user_id = get_user_id(&session).unwrap_or(0);
+    let details = serde_json::json!({
+        "assessment_id": id,
+        "template": template.label,
+        "period": form.period.trim(),
+        "summary": format!("Started self-assessment '{}' for {}", template.label, form.period.trim())
+    });
+    let _ = crate::audit::log(&pool, user_id, "tor.assessment_started", "tor", tor_id, details).await;
+    let _ = session.insert("flash", "Self-assessment started; members can now respond");
+    Ok(redirect(&format!("/tor/{tor_id}/assessments/{id}")))
+}
+
+/// The assessment, or NotFound when it belongs to another ToR.
+async fn find_for_tor(pool: &PgPool, tor_id: i64, assessment_id: i64) -> Result<survey::Assessment, AppError> {
+    survey::find_assessment(pool, assessment_id).await?
+        .filter(|a| a.tor_id == tor_id)
+        .ok_or(AppError::NotFound)
+}
+
+async fn render_assessment(
+    pool: &PgPool,
+    session: &Session,
+    tor_id: i64,
+    assessment: survey::Assessment,
+    answers: Option<HashMap<String, String>>,
+    errors: Vec<String>,
+) -> Result<HttpResponse, AppError> {
+    let tor_label = tor::get_tor_name(pool, tor_id).await?;
+    let user_id = get_user_id(session).unwrap_or(0);
+    let is_member = tor::require_tor_membership(pool, user_id, tor_id).await.is_ok();
+    let answers = match answers {
+        Some(answers) => answers,
+        None => survey::find_response(pool, assessment.id, user_id).await?.unwrap_or_default(),
+    };
+    let results = survey::find_results(pool, &assessment).await?;
+
+    let ctx = PageContext::build(session, pool, "/tor").await?
+        .with_tor(tor_id, &tor_label, "overview");
+    render(TorAssessmentTemplate { ctx, tor_id, tor_label, assessment, results, answers, is_member, errors })
+}
+
+/// GET /tor/{id}/assessments/{assessment_id} — answer form and aggregated results.
+pub async fn assessment_detail(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<(i64, i64)>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "tor.list")?;
+    let (tor_id, assessment_id) = path.into_inner();
+    let assessment = find_for_tor(&pool, tor_id, assessment_id).await?;
+    render_assessment(&pool, &session, tor_id, assessment, None, Vec::new()).await
+}
+
+/// POST /tor/{id}/assessments/{assessment_id}/respond — a member's answers.
+/// Fields are `answer_<key>`; answering again replaces earlier answers.
+pub async fn respond(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<(i64, i64)>,
+    form: web::Form<HashMap<String, String>>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "tor.list")?;
+    csrf::validate_csrf(&session, form.get("csrf_token").map(|s| s.as_str()).unwrap_or(""))?;
+
+    let (tor_id, assessment_id) = path.into_inner();
+    let user_id = get_user_id(&session).ok_or(AppError::Session("User not logged in".to_string()))?;
+    tor::require_tor_membership(&pool, user_id, tor_id).await?;
+    let assessment = find_for_tor(&pool, tor_id, assessment_id).await?;
+    let detail = format!("/tor/{tor_id}/assessments/{assessment_id}");
+    if !assessment.is_open() {
+        let _ = session.insert("flash", "This self-assessment is closed");
+        return Ok(redirect(&detail));
+    }
+
+    let answers = match assessment.check_answers(&form) {
+        Ok(answers) => answers,
+        Err(errors) => {
+            let submitted = assessment.questions_list().into_iter()
+                .filter_map(|q| form.get(&q.field()).map(|a| (q.key, a.clone())))
+                .collect();
+            return render_assessment(&pool, &session, tor_id, assessment, Some(submitted), errors).await;
+        }
+    };
+    survey::submit_response(&pool, assessment_id, user_id, &answers).await?;
+
+    let details = serde_json::json!({
+        "assessment_id": assessment_id,
+        "summary": format!("Responded to self-assessment '{}'", assessment.label)
+    });
+    let _ = crate::audit::log(&pool, user_id, "tor.assessment_answered", "tor", tor_id, details).await;
+    let _ = session.insert("flash", "Thank you, your answers were recorded");
+    Ok(redirect(&detail))
+}
+
+/// POST /tor/{id}/assessments/{assessment_id}/close — stop accepting answers.
+pub async fn close_assessment(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<(i64, i64)>,
+    form: web::Form<CsrfOnly>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "tor.edit")?;
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+
+    let (tor_id, assessment_id) = path.into_inner();
+    let assessment = find_for_tor(&pool, tor_id, assessment_id).await?;
+    survey::close_assessment(&pool, assessment_id).await?;
+
+    let user_id = get_user_id(&session).unwrap_or(0);
+    let details = serde_json::json!({
+        "assessment_id": assessment_id,
+        "responses": assessment.responses,
+        "summary": format!("Closed self-assessment '{}'", assessment.label)
+    });
+    let _ = crate::audit::log(&pool, user_id, "tor.assessment_closed", "tor", tor_id, details).await;
+    let _ = session.insert("flash", "Self-assessment closed");
+    Ok(redirect(&format!("/tor/{tor_id}/assessments/{assessment_id}")))
+}
@@ -0,0 +1,67 @@
+use actix_session::Session;
+use actix_web::{web, HttpResponse};
+use sqlx::PgPool;
+
+use crate::auth::session::{get_permissions, get_user_id, get_username, require_permission};
+use crate::errors::{AppError, render};
+use crate::models::{access_log, entity, tor};
+use crate::templates_structs::{PageContext, TorAccessLogTemplate};
+
+/// Record that the signed-in user opened an item of a ToR (see
+/// `models::access_log`). Returns whether the page shows the Access log tab.
+pub(crate) async fn track_view(
+    pool: &PgPool,
+    session: &Session,
+    entity_id: i64,
+    tor_id: i64,
+) -> Result<bool, AppError> {
+    let user_id = get_user_id(session).ok_or(AppError::Session("User not logged in".to_string()))?;
+    let username = get_username(session).unwrap_or_default();
+    let can_audit = get_permissions(session).is_ok_and(|p| p.has("audit.view"));
+    Ok(access_log::track(pool, entity_id, tor_id, user_id, &username, can_audit).await?)
+}
+
+/// GET /tor/{id}/access-log/{entity_id} — who viewed an item of a
+/// classified ToR. For the ToR's chairs and auditors.
+pub async fn access_log_tab(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<(i64, i64)>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "tor.list")?;
+    let (tor_id, entity_id) = path.into_inner();
+    let user_id = get_user_id(&session).ok_or(AppError::Session("User not logged in".to_string()))?;
+
+    let tor_detail = tor::find_detail_by_id(&pool, tor_id).await?
+        .ok_or(AppError::NotFound)?;
+    let item = entity::find_by_id(&pool, entity_id).await?
+        .ok_or(AppError::NotFound)?;
+    let item_path = match item.entity_type.as_str() {
+        "tor" if item.id == tor_id => format!("/tor/{}", tor_id),
+        "meeting" => format!("/tor/{}/meetings/{}", tor_id, entity_id),
+        "proposal" => format!("/tor/{}/proposals/{}", tor_id, entity_id),
+        _ => return Err(AppError::NotFound),
+    };
+
+    let can_audit = get_permissions(&session).is_ok_and(|p| p.has("audit.view"));
+    if !access_log::is_classified(&pool, tor_id).await?
+        || !access_log::can_view(&pool, user_id, can_audit, tor_id).await?
+    {
+        return Err(AppError::PermissionDenied("access log".to_string()));
+    }
+
+    let entries = access_log::find_for_entity(&pool, tor_id, entity_id).await?;
+    let ctx = PageContext::build(&session, &pool, "/tor").await?
+        .with_tor(tor_id, &tor_detail.label, "access_log")
+        .with_access_log(entity_id);
+    render(TorAccessLogTemplate {
+        ctx,
+        tor_id,
+        classification: tor_detail.classification,
+        item_label: item.label,
+        item_type: item.entity_type,
+        item_path,
+        entries,
+        read_mode: crate::models::setting::get_bool(&pool, "audit.read_mode").await,
+    })
+}
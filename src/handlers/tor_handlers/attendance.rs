@@ -0,0 +1,29 @@
+use actix_session::Session;
+use actix_web::{web, HttpResponse};
+use sqlx::PgPool;
+
+use crate::models::meeting::attendance;
+use crate::models::tor;
+use crate::auth::session::require_permission;
+use crate::errors::{AppError, render};
+use crate::templates_structs::{PageContext, TorAttendanceTemplate};
+
+/// GET /tor/{id}/attendance — quorum history of the ToR's meetings and each
+/// member's attendance rate, from the recorded roll calls.
+pub async fn attendance_page(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "tor.list")?;
+
+    let tor_id = path.into_inner();
+    let tor_detail = tor::find_detail_by_id(&pool, tor_id).await?
+        .ok_or(AppError::NotFound)?;
+    let stats = attendance::find_for_tor(&pool, tor_id).await?;
+
+    let tor_label = tor_detail.label;
+    let ctx = PageContext::build(&session, &pool, "/tor").await?
+        .with_tor(tor_id, &tor_label, "meetings");
+    render(TorAttendanceTemplate { ctx, tor_id, tor_label, stats })
+}
@@ -0,0 +1,173 @@
+use actix_session::Session;
+use actix_web::{web, HttpResponse};
+use chrono::{Local, NaiveDate, Weekday};
+use serde::Deserialize;
+use sqlx::PgPool;
+
+use crate::models::{entity, tor};
+use crate::models::tor::availability;
+use crate::auth::csrf;
+use crate::auth::session::{require_permission, get_user_id, get_permissions};
+use crate::errors::{AppError, render};
+use crate::templates_structs::{PageContext, TorAvailabilityTemplate};
+
+/// Number of alternative dates offered by the scheduling assistant.
+const SUGGESTION_LIMIT: usize = 3;
+
+/// GET /tor/{id}/availability — members' recurring unavailability and absences.
+pub async fn availability_page(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "tor.list")?;
+
+    let tor_id = path.into_inner();
+    let tor_detail = tor::find_detail_by_id(&pool, tor_id).await?
+        .ok_or(AppError::NotFound)?;
+
+    let user_id = get_user_id(&session).unwrap_or(0);
+    let is_member = tor::require_tor_membership(&pool, user_id, tor_id).await.is_ok();
+    let entries = availability::find_for_tor(&pool, tor_id).await?;
+
+    let tor_label = tor_detail.label;
+    let ctx = PageContext::build(&session, &pool, "/tor").await?
+        .with_tor(tor_id, &tor_label, "meetings");
+    let tmpl = TorAvailabilityTemplate {
+        ctx,
+        tor_id,
+        tor_label,
+        entries,
+        user_id,
+        is_member,
+    };
+    render(tmpl)
+}
+
+/// POST /tor/{id}/availability — record unavailability for the current user.
+/// `kind=recurring` takes `weekday`; `kind=absence` takes `start_date` and
+/// optional `end_date` (defaults to the start date).
+pub async fn add_availability(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+    form: web::Form<std::collections::HashMap<String, String>>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "tor.list")?;
+    csrf::validate_csrf(&session, form.get("csrf_token").map(|s| s.as_str()).unwrap_or(""))?;
+
+    let tor_id = path.into_inner();
+    let user_id = get_user_id(&session).ok_or(AppError::Session("User not logged in".to_string()))?;
+    tor::require_tor_membership(&pool, user_id, tor_id).await?;
+
+    let redirect = HttpResponse::SeeOther()
+        .insert_header(("Location", format!("/tor/{tor_id}/availability")))
+        .finish();
+    let note = form.get("note").map(|s| s.trim()).unwrap_or("");
+    let parse_date = |key: &str| form.get(key)
+        .and_then(|s| NaiveDate::parse_from_str(s, "%Y-%m-%d").ok());
+
+    let (entry_id, summary) = match form.get("kind").map(|s| s.as_str()) {
+        Some("recurring") => {
+            let Some(weekday) = form.get("weekday").and_then(|s| s.parse::<Weekday>().ok()) else {
+                let _ = session.insert("flash", "Select a weekday");
+                return Ok(redirect);
+            };
+            let id = availability::add_recurring(&pool, tor_id, user_id, weekday, note).await?;
+            (id, format!("Recorded recurring unavailability on {}", availability::weekday_name(weekday)))
+        }
+        Some("absence") => {
+            let Some(start) = parse_date("start_date") else {
+                let _ = session.insert("flash", "Enter the first day of the absence");
+                return Ok(redirect);
+            };
+            let end = parse_date("end_date").unwrap_or(start);
+            if end < start {
+                let _ = session.insert("flash", "The absence cannot end before it starts");
+                return Ok(redirect);
+            }
+            let id = availability::add_absence(&pool, tor_id, user_id, start, end, note).await?;
+            (id, format!("Recorded absence {} to {}", start, end))
+        }
+        _ => {
+            let _ = session.insert("flash", "Invalid availability entry");
+            return Ok(redirect);
+        }
+    };
+
+    let details = serde_json::json!({
+        "unavailability_id": entry_id,
+        "summary": summary
+    });
+    let _ = crate::audit::log(&pool, user_id, "tor.availability_recorded", "tor", tor_id, details).await;
+    let _ = session.insert("flash", "Availability recorded");
+    Ok(redirect)
+}
+
+/// POST /tor/{id}/availability/{entry_id}/delete — remove an entry.
+/// Members may remove their own entries; `tor.edit` may remove any.
+pub async fn delete_availability(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<(i64, i64)>,
+    form: web::Form<std::collections::HashMap<String, String>>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "tor.list")?;
+    csrf::validate_csrf(&session, form.get("csrf_token").map(|s| s.as_str()).unwrap_or(""))?;
+
+    let (tor_id, entry_id) = path.into_inner();
+    let user_id = get_user_id(&session).unwrap_or(0);
+    let entry = availability::find_for_tor(&pool, tor_id).await?
+        .into_iter()
+        .find(|e| e.id == entry_id)
+        .ok_or(AppError::NotFound)?;
+
+    let permissions = get_permissions(&session)
+        .map_err(|e| AppError::Session(format!("Failed to get permissions: {}", e)))?;
+    if entry.user_id != user_id && !permissions.has("tor.edit") {
+        return Err(AppError::PermissionDenied("You can only remove your own availability".into()));
+    }
+
+    entity::delete(&pool, entry_id).await?;
+    let details = serde_json::json!({
+        "unavailability_id": entry_id,
+        "user_id": entry.user_id,
+        "summary": format!("Removed unavailability for {}: {}", entry.user_label, entry.describe())
+    });
+    let _ = crate::audit::log(&pool, user_id, "tor.availability_removed", "tor", tor_id, details).await;
+    let _ = session.insert("flash", "Availability entry removed");
+    Ok(HttpResponse::SeeOther()
+        .insert_header(("Location", format!("/tor/{tor_id}/availability")))
+        .finish())
+}
+
+#[derive(Deserialize)]
+pub struct AttendanceQuery {
+    pub date: String,
+}
+
+/// GET /api/tor/{id}/availability?date=YYYY-MM-DD — projected attendance on
+/// a date plus better alternatives, for the meeting scheduling forms.
+pub async fn attendance_api(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+    query: web::Query<AttendanceQuery>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "tor.list")?;
+
+    let tor_id = path.into_inner();
+    let Ok(date) = NaiveDate::parse_from_str(&query.date, "%Y-%m-%d") else {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Invalid date"
+        })));
+    };
+
+    let members = tor::find_members(&pool, tor_id).await?;
+    let entries = availability::find_for_tor(&pool, tor_id).await?;
+    let today = Local::now().date_naive();
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "attendance": availability::project_attendance(&members, &entries, date),
+        "suggestions": availability::suggest_dates(&members, &entries, date, today, SUGGESTION_LIMIT),
+    })))
+}
@@ -2,9 +2,11 @@ use actix_session::Session;
 use actix_web::{web, HttpResponse};
 use sqlx::PgPool;
 
-use crate::models::tor;
+use crate::models::{entity, tor};
 use crate::models::protocol;
 use crate::models::meeting;
+use crate::models::survey;
+use crate::models::sla_pack;
 use crate::auth::{csrf, validate};
 use crate::auth::session::require_permission;
 use crate::errors::{AppError, render};
@@ -157,11 +159,15 @@ pub async fn detail(
 
     match tor::find_detail_by_id(&pool, id).await? {
         Some(tor_detail) => {
-            let ctx = PageContext::build(&session, &pool, "/tor").await?
+            let mut ctx = PageContext::build(&session, &pool, "/tor").await?
                 .with_tor(id, &tor_detail.label, "overview");
+            if super::access_log::track_view(&pool, &session, id, id).await? {
+                ctx = ctx.with_access_log(id);
+            }
             let members = tor::find_members(&pool, id).await?;
             let functions = tor::find_functions(&pool, id).await?;
             let protocol_steps = protocol::find_steps_for_tor(&pool, id).await?;
+            let standing_items = tor::standing_items::find_for_tor(&pool, id).await?;
             let non_members = tor::find_non_members(&pool, id).await?;
             let available_users = non_members.into_iter()
                 .map(|(id, name, label)| UserOption { id, name, label })
@@ -170,6 +176,21 @@ pub async fn detail(
             let downstream_deps = tor::find_downstream(&pool, id).await?;
             let other_tors = tor::find_other_tors(&pool, id).await?;
             let meetings = meeting::find_by_tor(&pool, id).await?;
+            let health = survey::find_health_score(&pool, id).await?;
+            let sla = sla_pack::find_compliance(&pool, id).await?;
+            let user_id = crate::auth::session::get_user_id(&session).unwrap_or(0);
+            let today = chrono::Local::now().date_naive();
+            let delegations = tor::find_delegations(&pool, id).await?;
+            let delegable_positions = super::delegations::delegable_positions(&session, user_id, &members);
+            let delegate_users = if delegable_positions.is_empty() {
+                Vec::new()
+            } else {
+                entity::find_by_type(&pool, "user").await?
+                    .into_iter()
+                    .filter(|u| u.is_active)
+                    .map(|u| UserOption { id: u.id, name: u.name, label: u.label })
+                    .collect()
+            };
 
             let tmpl = TorDetailTemplate {
                 ctx,
@@ -177,11 +198,20 @@ pub async fn detail(
                 members,
                 functions,
                 protocol_steps,
+                standing_items,
                 available_users,
                 upstream_deps,
                 downstream_deps,
                 other_tors,
                 meetings,
+                health,
+                sla,
+                can_transfer: crate::handlers::transfer_handlers::can_transfer(&pool, &session, id).await?,
+                delegations,
+                delegable_positions,
+                delegate_users,
+                user_id,
+                today,
             };
             render(tmpl)
         }
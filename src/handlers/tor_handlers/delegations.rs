@@ -0,0 +1,129 @@
+use actix_session::Session;
+use actix_web::{web, HttpResponse};
+use serde::Deserialize;
+use sqlx::PgPool;
+
+use crate::auth::csrf;
+use crate::auth::session::{get_permissions, get_user_id};
+use crate::errors::AppError;
+use crate::handlers::auth_handlers::CsrfOnly;
+use crate::models::{entity, tor};
+
+#[derive(Deserialize)]
+pub struct DelegationForm {
+    pub csrf_token: String,
+    #[serde(default)]
+    pub position_id: i64,
+    #[serde(default)]
+    pub delegate_id: i64,
+    pub start_date: String,
+    pub end_date: String,
+    #[serde(default)]
+    pub note: String,
+}
+
+fn back_to_tor(tor_id: i64) -> HttpResponse {
+    HttpResponse::SeeOther()
+        .insert_header(("Location", format!("/tor/{tor_id}#delegations")))
+        .finish()
+}
+
+/// Positions of the ToR the user may delegate: every filled one for
+/// `tor.manage_members`, otherwise those the user fills.
+pub(crate) fn delegable_positions(
+    session: &Session,
+    user_id: i64,
+    members: &[tor::TorMember],
+) -> Vec<tor::TorMember> {
+    let manages = get_permissions(session).is_ok_and(|p| p.has("tor.manage_members"));
+    members.iter()
+        .filter(|m| m.holder_id.is_some_and(|holder| manages || holder == user_id))
+        .cloned()
+        .collect()
+}
+
+/// POST /tor/{id}/delegations — delegate a position for a date window.
+pub async fn create_delegation(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+    form: web::Form<DelegationForm>,
+) -> Result<HttpResponse, AppError> {
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+    let tor_id = path.into_inner();
+    let user_id = get_user_id(&session).ok_or(AppError::Session("User not logged in".to_string()))?;
+
+    let members = tor::find_members(&pool, tor_id).await?;
+    let position = delegable_positions(&session, user_id, &members)
+        .into_iter()
+        .find(|m| m.position_id == form.position_id)
+        .ok_or(AppError::PermissionDenied("tor.manage_members".to_string()))?;
+    let delegator_id = position.holder_id.unwrap_or(0);
+
+    let errors = tor::validate_delegation(delegator_id, form.delegate_id, &form.start_date, &form.end_date);
+    if !errors.is_empty() {
+        let _ = session.insert("flash", errors.join("; "));
+        return Ok(back_to_tor(tor_id));
+    }
+    let delegate = entity::find_by_id(&pool, form.delegate_id).await?
+        .filter(|e| e.entity_type == "user" && e.is_active)
+        .ok_or(AppError::NotFound)?;
+
+    let id = tor::delegate_position(
+        &pool, position.position_id, delegator_id, delegate.id, &form.start_date, &form.end_date, &form.note,
+    ).await?;
+    let delegator = position.holder_label.unwrap_or_default();
+    let details = serde_json::json!({
+        "delegation_id": id,
+        "position_id": position.position_id,
+        "delegator_id": delegator_id,
+        "delegate_id": delegate.id,
+        "start_date": form.start_date.trim(),
+        "end_date": form.end_date.trim(),
+        "summary": format!(
+            "Delegated {} ({}) to {} from {} to {}",
+            position.position_label, delegator, delegate.label, form.start_date.trim(), form.end_date.trim()
+        )
+    });
+    let _ = crate::audit::log(&pool, user_id, "tor.position_delegated", "tor", tor_id, details).await;
+
+    let _ = session.insert("flash", format!("{} will act as {} during the delegation", delegate.label, position.position_label));
+    Ok(back_to_tor(tor_id))
+}
+
+/// POST /tor/{id}/delegations/{delegation_id}/revoke — end a delegation early.
+pub async fn revoke_delegation(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<(i64, i64)>,
+    form: web::Form<CsrfOnly>,
+) -> Result<HttpResponse, AppError> {
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+    let (tor_id, delegation_id) = path.into_inner();
+    let user_id = get_user_id(&session).ok_or(AppError::Session("User not logged in".to_string()))?;
+
+    let delegation = tor::find_delegations(&pool, tor_id).await?
+        .into_iter()
+        .find(|d| d.id == delegation_id)
+        .ok_or(AppError::NotFound)?;
+    let manages = get_permissions(&session).is_ok_and(|p| p.has("tor.manage_members"));
+    if !manages && delegation.delegator_id != user_id && delegation.delegate_id != user_id {
+        return Err(AppError::PermissionDenied("tor.manage_members".to_string()));
+    }
+
+    tor::revoke_delegation(&pool, delegation.id).await?;
+    let details = serde_json::json!({
+        "delegation_id": delegation.id,
+        "position_id": delegation.position_id,
+        "delegator_id": delegation.delegator_id,
+        "delegate_id": delegation.delegate_id,
+        "summary": format!(
+            "Revoked delegation of {} ({}) to {}",
+            delegation.position_label, delegation.delegator_name, delegation.delegate_name
+        )
+    });
+    let _ = crate::audit::log(&pool, user_id, "tor.delegation_revoked", "tor", tor_id, details).await;
+
+    let _ = session.insert("flash", "Delegation revoked");
+    Ok(back_to_tor(tor_id))
+}
@@ -0,0 +1,36 @@
+use actix_session::Session;
+use actix_web::{web, HttpResponse};
+use sqlx::PgPool;
+
+use crate::models::tor;
+use crate::auth::session::{get_user_id, require_permission};
+use crate::errors::AppError;
+
+/// GET /tor/{id}/handover — download the secretariat handover package (zip).
+pub async fn handover_package(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "tor.edit")?;
+
+    let tor_id = path.into_inner();
+    let package = tor::build_handover(&pool, tor_id).await?;
+    let bytes = package.to_archive()
+        .map_err(|e| AppError::Session(format!("Could not build handover package: {e}")))?;
+
+    let user_id = get_user_id(&session).unwrap_or(0);
+    let details = serde_json::json!({
+        "tor_id": tor_id,
+        "members": package.members.len(),
+        "open_items": package.open_items.len(),
+        "upcoming_meetings": package.upcoming_meetings.len(),
+        "pending_minutes": package.pending_minutes.len(),
+        "summary": format!("Handover package exported for {}", package.tor.label),
+    });
+    let _ = crate::audit::log(&pool, user_id, "tor.handover_exported", "tor", tor_id, details).await;
+
+    crate::handlers::export_handlers::checksummed_download(
+        &pool, user_id, "handover_package", &package.file_name(), "application/zip", "attachment", bytes,
+    ).await
+}
@@ -41,6 +41,8 @@ pub async fn manage_members(
             }
 
             tor::assign_to_position(&pool, user_id, position_id, membership_type).await?;
+            // The membership type decides which capability defaults apply
+            tor::apply_capabilities(&pool, tor_id).await?;
             let details = serde_json::json!({
                 "user_id": user_id,
                 "position_id": position_id,
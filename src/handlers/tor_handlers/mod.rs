@@ -5,6 +5,14 @@ pub mod protocol;
 pub mod dependencies;
 pub mod presentation;
 pub mod calendar;
+pub mod settings;
+pub mod availability;
+pub mod attendance;
+pub mod terms;
+pub mod handover;
+pub mod access_log;
+pub mod delegations;
+pub mod standing_items;
 
 pub use list::*;
 pub use crud::*;
@@ -13,3 +21,11 @@ pub use protocol::*;
 pub use dependencies::*;
 pub use presentation::*;
 pub use calendar::*;
+pub use settings::*;
+pub use availability::*;
+pub use attendance::*;
+pub use terms::*;
+pub use handover::*;
+pub use access_log::*;
+pub use delegations::*;
+pub use standing_items::*;
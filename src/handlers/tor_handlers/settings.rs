@@ -0,0 +1,440 @@
+use actix_session::Session;
+use actix_web::{web, HttpResponse};
+use sqlx::PgPool;
+
+use crate::models::{proposal, setting, tor};
+use crate::auth::csrf;
+use crate::auth::session::{require_permission, get_user_id};
+use crate::errors::{AppError, render};
+use crate::templates_structs::{PageContext, TorSettingsTemplate};
+
+/// GET /tor/{id}/settings — overridable settings with global and ToR values.
+pub async fn settings_tab(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "tor.edit")?;
+
+    let tor_id = path.into_inner();
+    let settings = setting::find_for_tor(&pool, tor_id).await?;
+    render_settings(&pool, &session, tor_id, settings, Vec::new()).await
+}
+
+/// Render the settings tab with the given override values and form errors.
+async fn render_settings(
+    pool: &PgPool,
+    session: &Session,
+    tor_id: i64,
+    settings: Vec<setting::TorSettingDisplay>,
+    errors: Vec<String>,
+) -> Result<HttpResponse, AppError> {
+    let tor_detail = tor::find_detail_by_id(pool, tor_id).await?
+        .ok_or(AppError::NotFound)?;
+
+    let decision_authority = tor::find_decision_authority(pool, tor_id).await?;
+    let positions = tor::find_members(pool, tor_id).await?;
+    let capabilities = tor::find_capability_matrix(pool, tor_id).await?;
+    let intake = proposal::find_intake(pool, tor_id).await?;
+    let proposal_templates = proposal::find_templates(pool, tor_id).await?;
+
+    let tor_label = tor_detail.label;
+    let ctx = PageContext::build(session, pool, "/tor").await?
+        .with_tor(tor_id, &tor_label, "settings");
+    let tmpl = TorSettingsTemplate {
+        ctx,
+        tor_id,
+        tor_label,
+        settings,
+        errors,
+        decision_authority,
+        positions,
+        capabilities,
+        intake,
+        proposal_templates,
+    };
+    render(tmpl)
+}
+
+/// POST /tor/{id}/settings — save overrides.
+/// Each setting submits `override_<name>=on` when overridden and `value_<name>`;
+/// unchecked settings have their override cleared so the global value applies.
+pub async fn save_settings(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+    form: web::Form<std::collections::HashMap<String, String>>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "tor.edit")?;
+    csrf::validate_csrf(&session, form.get("csrf_token").map(|s| s.as_str()).unwrap_or(""))?;
+
+    let tor_id = path.into_inner();
+    tor::find_detail_by_id(&pool, tor_id).await?
+        .ok_or(AppError::NotFound)?;
+
+    // Validate everything before saving anything
+    let settings = setting::find_for_tor(&pool, tor_id).await?;
+    let mut submitted = settings.clone();
+    let mut errors = Vec::new();
+    for s in submitted.iter_mut() {
+        if form.contains_key(&format!("override_{}", s.name)) {
+            let value = form.get(&format!("value_{}", s.name)).map(|v| v.trim()).unwrap_or("");
+            if let Err(e) = setting::validate(&s.name, &s.setting_type, value) {
+                errors.push(format!("{}: {}", s.label, e));
+            }
+            s.override_value = Some(value.to_string());
+        } else {
+            s.override_value = None;
+        }
+    }
+    if !errors.is_empty() {
+        return render_settings(&pool, &session, tor_id, submitted, errors).await;
+    }
+
+    let mut overridden = Vec::new();
+    let mut cleared = Vec::new();
+    for (s, new) in settings.into_iter().zip(submitted) {
+        match new.override_value {
+            Some(value) if s.override_value.as_deref() != Some(value.as_str()) => {
+                setting::set_tor_override(&pool, tor_id, &s.name, &value).await?;
+                overridden.push(s.name);
+            }
+            None if s.override_value.is_some() => {
+                setting::clear_tor_override(&pool, tor_id, &s.name).await?;
+                cleared.push(s.name);
+            }
+            _ => {}
+        }
+    }
+
+    if !overridden.is_empty() || !cleared.is_empty() {
+        let user_id = get_user_id(&session).unwrap_or(0);
+        let details = serde_json::json!({
+            "overridden": overridden,
+            "cleared": cleared,
+            "summary": format!("Updated {} ToR setting override(s)", overridden.len() + cleared.len())
+        });
+        let _ = crate::audit::log(&pool, user_id, "tor.settings_updated", "tor", tor_id, details).await;
+    }
+
+    let _ = session.insert("flash", "ToR settings saved");
+    Ok(HttpResponse::SeeOther()
+        .insert_header(("Location", format!("/tor/{tor_id}/settings")))
+        .finish())
+}
+
+/// POST /tor/{id}/decision-authority — configure who records decisions.
+/// Form: `mode` (`permission`, `positions` or `vote`) and one
+/// `position_<id>` field per selected position.
+pub async fn save_decision_authority(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+    form: web::Form<std::collections::HashMap<String, String>>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "tor.edit")?;
+    csrf::validate_csrf(&session, form.get("csrf_token").map(|s| s.as_str()).unwrap_or(""))?;
+
+    let tor_id = path.into_inner();
+    tor::find_detail_by_id(&pool, tor_id).await?
+        .ok_or(AppError::NotFound)?;
+    let redirect = HttpResponse::SeeOther()
+        .insert_header(("Location", format!("/tor/{tor_id}/settings")))
+        .finish();
+
+    let authority = match form.get("mode").map(|s| s.as_str()).unwrap_or("") {
+        "permission" => tor::DecisionAuthority::Permission,
+        "vote" => tor::DecisionAuthority::Vote,
+        "positions" => {
+            let ids: Vec<i64> = tor::find_members(&pool, tor_id).await?
+                .into_iter()
+                .map(|m| m.position_id)
+                .filter(|id| form.contains_key(&format!("position_{}", id)))
+                .collect();
+            if ids.is_empty() {
+                let _ = session.insert("flash", "Select at least one position with decision authority");
+                return Ok(redirect);
+            }
+            tor::DecisionAuthority::Positions(ids)
+        }
+        _ => {
+            let _ = session.insert("flash", "Invalid decision authority");
+            return Ok(redirect);
+        }
+    };
+
+    let previous = tor::find_decision_authority(&pool, tor_id).await?;
+    if previous != authority {
+        tor::save_decision_authority(&pool, tor_id, &authority).await?;
+
+        let user_id = get_user_id(&session).unwrap_or(0);
+        let members = tor::find_members(&pool, tor_id).await?;
+        let details = serde_json::json!({
+            "from": previous.mode(),
+            "to": authority.mode(),
+            "summary": format!("Decision authority set to: {}", authority.describe(&members))
+        });
+        let _ = crate::audit::log(&pool, user_id, "tor.decision_authority_updated", "tor", tor_id, details).await;
+    }
+
+    let _ = session.insert("flash", "Decision authority saved");
+    Ok(redirect)
+}
+
+/// POST /tor/{id}/capabilities — save the position capability matrix.
+/// Form: `default_<membership_type>_<capability>=on` for each ToR default
+/// and `cap_<position_id>_<capability>` set to `inherit`, `grant` or `deny`.
+pub async fn save_capabilities(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+    form: web::Form<std::collections::HashMap<String, String>>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "tor.edit")?;
+    csrf::validate_csrf(&session, form.get("csrf_token").map(|s| s.as_str()).unwrap_or(""))?;
+
+    let tor_id = path.into_inner();
+    tor::find_detail_by_id(&pool, tor_id).await?
+        .ok_or(AppError::NotFound)?;
+
+    let granted = |membership_type: &str| -> Vec<String> {
+        tor::CAPABILITIES.iter()
+            .map(|(key, _)| *key)
+            .filter(|key| form.contains_key(&format!("default_{}_{}", membership_type, key)))
+            .map(String::from)
+            .collect()
+    };
+    let defaults = tor::CapabilityDefaults {
+        mandatory: granted("mandatory"),
+        optional: granted("optional"),
+    };
+
+    let previous = tor::find_capability_matrix(&pool, tor_id).await?;
+    let mut overrides = std::collections::HashMap::new();
+    let mut changed_positions = Vec::new();
+    for p in &previous.positions {
+        let mut caps = std::collections::BTreeMap::new();
+        for (key, _) in tor::CAPABILITIES {
+            match form.get(&format!("cap_{}_{}", p.position_id, key)).map(|s| s.as_str()) {
+                Some("grant") => { caps.insert(key.to_string(), true); }
+                Some("deny") => { caps.insert(key.to_string(), false); }
+                Some("inherit") => {}
+                // Not submitted: keep what the position had
+                _ => if let Some(v) = p.overrides.get(*key) { caps.insert(key.to_string(), *v); },
+            }
+        }
+        if caps != p.overrides {
+            changed_positions.push(p.position_label.clone());
+        }
+        overrides.insert(p.position_id, caps);
+    }
+
+    tor::save_capabilities(&pool, tor_id, &defaults, &overrides).await?;
+
+    if defaults != previous.defaults || !changed_positions.is_empty() {
+        let user_id = get_user_id(&session).unwrap_or(0);
+        let details = serde_json::json!({
+            "mandatory_defaults": defaults.mandatory,
+            "optional_defaults": defaults.optional,
+            "positions_changed": changed_positions,
+            "summary": format!(
+                "Updated position capabilities ({} position override(s) changed)",
+                changed_positions.len()
+            )
+        });
+        let _ = crate::audit::log(&pool, user_id, "tor.capabilities_updated", "tor", tor_id, details).await;
+    }
+
+    let _ = session.insert("flash", "Position capabilities saved");
+    Ok(HttpResponse::SeeOther()
+        .insert_header(("Location", format!("/tor/{tor_id}/settings")))
+        .finish())
+}
+
+/// POST /tor/{id}/proposal-intake — configure the ToR's proposal form.
+/// Form: `guidance`, `min_attachments`, `attachment_help`, and for each
+/// section row `n`: `section_label_<n>`, `section_help_<n>`,
+/// `section_required_<n>=on` and, for existing sections, `section_key_<n>`.
+/// Rows with a blank label are dropped.
+pub async fn save_proposal_intake(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+    form: web::Form<std::collections::HashMap<String, String>>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "tor.edit")?;
+    csrf::validate_csrf(&session, form.get("csrf_token").map(|s| s.as_str()).unwrap_or(""))?;
+
+    let tor_id = path.into_inner();
+    tor::find_detail_by_id(&pool, tor_id).await?
+        .ok_or(AppError::NotFound)?;
+    let redirect = HttpResponse::SeeOther()
+        .insert_header(("Location", format!("/tor/{tor_id}/settings")))
+        .finish();
+
+    let text = |key: &str| form.get(key).map(|v| v.trim().to_string()).unwrap_or_default();
+    let min_attachments = match text("min_attachments").as_str() {
+        "" => 0,
+        v => match v.parse::<usize>() {
+            Ok(n) => n,
+            Err(_) => {
+                let _ = session.insert("flash", "Minimum attachments must be a whole number");
+                return Ok(redirect);
+            }
+        },
+    };
+
+    let mut sections: Vec<proposal::IntakeSection> = Vec::new();
+    for n in 0.. {
+        let Some(label) = form.get(&format!("section_label_{n}")) else { break };
+        let label = label.trim();
+        if label.is_empty() {
+            continue;
+        }
+        let mut key = form.get(&format!("section_key_{n}"))
+            .map(|k| k.trim().to_string())
+            .filter(|k| !k.is_empty())
+            .unwrap_or_else(|| proposal::section_key(label));
+        if key.is_empty() {
+            let _ = session.insert("flash", format!("Section '{label}' needs a name with letters or digits"));
+            return Ok(redirect);
+        }
+        if sections.iter().any(|s| s.key == key) {
+            key = format!("{}_{}", key, n + 1);
+        }
+        sections.push(proposal::IntakeSection {
+            key,
+            label: label.to_string(),
+            help: text(&format!("section_help_{n}")),
+            required: form.contains_key(&format!("section_required_{n}")),
+        });
+    }
+
+    let intake = proposal::ProposalIntake {
+        guidance: text("guidance"),
+        sections,
+        min_attachments,
+        attachment_help: text("attachment_help"),
+    };
+    let previous = proposal::find_intake(&pool, tor_id).await?;
+    if intake != previous {
+        proposal::save_intake(&pool, tor_id, &intake).await?;
+        let user_id = get_user_id(&session).unwrap_or(0);
+        let details = serde_json::json!({
+            "sections": intake.sections.iter().map(|s| s.label.as_str()).collect::<Vec<_>>(),
+            "required": intake.sections.iter().filter(|s| s.required).map(|s| s.label.as_str()).collect::<Vec<_>>(),
+            "min_attachments": intake.min_attachments,
+            "summary": format!("Updated proposal intake form ({} extra section(s))", intake.sections.len())
+        });
+        let _ = crate::audit::log(&pool, user_id, "tor.proposal_intake_updated", "tor", tor_id, details).await;
+    }
+
+    let _ = session.insert("flash", "Proposal intake form saved");
+    Ok(redirect)
+}
+
+/// POST /tor/{id}/proposal-templates — add a proposal template, or update
+/// the one named by `key`. Form: `name`, `title_pattern`, `description`,
+/// `rationale`, `default_coas` (one per line) and `section_<key>` answers
+/// for the ToR's intake sections.
+pub async fn save_proposal_template(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+    form: web::Form<std::collections::HashMap<String, String>>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "tor.edit")?;
+    csrf::validate_csrf(&session, form.get("csrf_token").map(|s| s.as_str()).unwrap_or(""))?;
+
+    let tor_id = path.into_inner();
+    tor::find_detail_by_id(&pool, tor_id).await?
+        .ok_or(AppError::NotFound)?;
+    let redirect = HttpResponse::SeeOther()
+        .insert_header(("Location", format!("/tor/{tor_id}/settings")))
+        .finish();
+
+    let text = |key: &str| form.get(key).map(|v| v.trim().to_string()).unwrap_or_default();
+    let mut templates = proposal::find_templates(&pool, tor_id).await?;
+    let editing = templates.iter().position(|t| t.key == text("key"));
+    let name = text("name");
+    let intake = proposal::find_intake(&pool, tor_id).await?;
+    let template = proposal::ProposalTemplate {
+        key: match editing {
+            Some(i) => templates[i].key.clone(),
+            None => proposal::section_key(&name),
+        },
+        title_pattern: text("title_pattern"),
+        description: text("description"),
+        rationale: text("rationale"),
+        sections: intake.sections.iter()
+            .map(|s| (s.key.clone(), text(&s.field())))
+            .filter(|(_, v)| !v.is_empty())
+            .collect(),
+        default_coas: proposal::parse_coa_lines(form.get("default_coas").map(|v| v.as_str()).unwrap_or("")),
+        name,
+    };
+
+    let errors = proposal::validate_template(&template, &templates, editing.is_some());
+    if !errors.is_empty() {
+        let _ = session.insert("flash", errors.join(". "));
+        return Ok(redirect);
+    }
+
+    let action = match editing {
+        Some(i) if templates[i] == template => {
+            let _ = session.insert("flash", "No changes to the proposal template");
+            return Ok(redirect);
+        }
+        Some(i) => {
+            templates[i] = template.clone();
+            "updated"
+        }
+        None => {
+            templates.push(template.clone());
+            "added"
+        }
+    };
+    proposal::save_templates(&pool, tor_id, &templates).await?;
+
+    let user_id = get_user_id(&session).unwrap_or(0);
+    let details = serde_json::json!({
+        "key": template.key,
+        "default_coas": template.default_coas,
+        "summary": format!("Proposal template '{}' {}", template.name, action)
+    });
+    let _ = crate::audit::log(&pool, user_id, "tor.proposal_template_saved", "tor", tor_id, details).await;
+
+    let _ = session.insert("flash", format!("Proposal template '{}' {}", template.name, action));
+    Ok(redirect)
+}
+
+/// POST /tor/{id}/proposal-templates/{key}/delete — remove a proposal
+/// template. Proposals created from it keep their content and default COAs.
+pub async fn delete_proposal_template(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<(i64, String)>,
+    form: web::Form<crate::handlers::auth_handlers::CsrfOnly>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "tor.edit")?;
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+
+    let (tor_id, key) = path.into_inner();
+    let mut templates = proposal::find_templates(&pool, tor_id).await?;
+    let removed = templates.iter().position(|t| t.key == key)
+        .map(|i| templates.remove(i))
+        .ok_or(AppError::NotFound)?;
+    proposal::save_templates(&pool, tor_id, &templates).await?;
+
+    let user_id = get_user_id(&session).unwrap_or(0);
+    let details = serde_json::json!({
+        "key": removed.key,
+        "summary": format!("Proposal template '{}' deleted", removed.name)
+    });
+    let _ = crate::audit::log(&pool, user_id, "tor.proposal_template_deleted", "tor", tor_id, details).await;
+
+    let _ = session.insert("flash", format!("Proposal template '{}' deleted", removed.name));
+    Ok(HttpResponse::SeeOther()
+        .insert_header(("Location", format!("/tor/{tor_id}/settings")))
+        .finish())
+}
@@ -0,0 +1,92 @@
+use actix_session::Session;
+use actix_web::{web, HttpResponse};
+use sqlx::PgPool;
+
+use crate::models::tor::standing_items::{self, RECURRENCES};
+use crate::auth::csrf;
+use crate::auth::session::{require_permission, get_user_id};
+use crate::errors::AppError;
+
+fn back_to_tor(tor_id: i64) -> HttpResponse {
+    HttpResponse::SeeOther()
+        .insert_header(("Location", format!("/tor/{tor_id}")))
+        .finish()
+}
+
+/// POST /tor/{id}/standing-items — add a standing agenda item.
+pub async fn add_standing_item(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+    form: web::Form<std::collections::HashMap<String, String>>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "tor.edit")?;
+    csrf::validate_csrf(&session, form.get("csrf_token").map(|s| s.as_str()).unwrap_or(""))?;
+
+    let tor_id = path.into_inner();
+
+    let field = |key: &str| form.get(key).map(|s| s.trim()).unwrap_or("");
+    let title = field("title");
+    let item_type = if field("item_type") == "decision" { "decision" } else { "informative" };
+    let recurrence = field("recurrence");
+    let minutes: i32 = field("time_allocation_minutes").parse().unwrap_or(0);
+    let protocol_step_id: Option<i64> = field("protocol_step_id").parse().ok().filter(|id| *id > 0);
+
+    if title.is_empty() {
+        let _ = session.insert("flash", "A standing item needs a title");
+        return Ok(back_to_tor(tor_id));
+    }
+    if !RECURRENCES.iter().any(|(key, _)| *key == recurrence) {
+        let _ = session.insert("flash", "Choose how often the standing item recurs");
+        return Ok(back_to_tor(tor_id));
+    }
+    if !(1..=480).contains(&minutes) {
+        let _ = session.insert("flash", "Time allocation must be between 1 and 480 minutes");
+        return Ok(back_to_tor(tor_id));
+    }
+
+    let id = standing_items::create(
+        &pool, tor_id, title, field("description"), item_type, minutes,
+        field("presenter"), recurrence, protocol_step_id,
+    ).await?;
+
+    let current_user_id = get_user_id(&session).unwrap_or(0);
+    let details = serde_json::json!({
+        "standing_item_id": id,
+        "title": title,
+        "recurrence": recurrence,
+        "summary": format!("Added standing agenda item '{}'", title)
+    });
+    let _ = crate::audit::log(&pool, current_user_id, "tor.standing_item_added", "tor", tor_id, details).await;
+
+    let _ = session.insert("flash", "Standing item added");
+    Ok(back_to_tor(tor_id))
+}
+
+/// POST /tor/{id}/standing-items/{item_id}/delete — stop adding a standing item
+/// to new meetings.
+pub async fn delete_standing_item(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<(i64, i64)>,
+    form: web::Form<std::collections::HashMap<String, String>>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "tor.edit")?;
+    csrf::validate_csrf(&session, form.get("csrf_token").map(|s| s.as_str()).unwrap_or(""))?;
+
+    let (tor_id, item_id) = path.into_inner();
+
+    if !standing_items::delete(&pool, tor_id, item_id).await? {
+        return Err(AppError::NotFound);
+    }
+
+    let current_user_id = get_user_id(&session).unwrap_or(0);
+    let details = serde_json::json!({
+        "standing_item_id": item_id,
+        "summary": "Removed standing agenda item"
+    });
+    let _ = crate::audit::log(&pool, current_user_id, "tor.standing_item_removed", "tor", tor_id, details).await;
+
+    let _ = session.insert("flash", "Standing item removed");
+    Ok(back_to_tor(tor_id))
+}
@@ -0,0 +1,209 @@
+use std::collections::HashMap;
+
+use actix_session::Session;
+use actix_web::{web, HttpResponse};
+use chrono::{Local, NaiveDate};
+use sqlx::PgPool;
+
+use crate::models::tor::{self, NewHolder, PositionRotation};
+use crate::auth::csrf;
+use crate::auth::session::{get_user_id, require_permission};
+use crate::errors::{AppError, render};
+use crate::handlers::warning_handlers::ws::ConnectionMap;
+use crate::templates_structs::{NewTermRow, PageContext, TorNewTermTemplate, UserOption};
+
+/// Wizard rows from the submitted form; positions not in the form keep
+/// their holder and membership type.
+fn rows_from_form(members: Vec<tor::TermMember>, form: &HashMap<String, String>) -> Vec<NewTermRow> {
+    members.into_iter()
+        .map(|member| {
+            let holder = form.get(&format!("holder_{}", member.position_id))
+                .cloned()
+                .unwrap_or_else(|| "keep".to_string());
+            let membership_type = form.get(&format!("membership_{}", member.position_id))
+                .cloned()
+                .unwrap_or_else(|| member.membership_type.clone());
+            NewTermRow { member, holder, membership_type }
+        })
+        .collect()
+}
+
+fn rotations(rows: &[NewTermRow]) -> Vec<PositionRotation> {
+    rows.iter()
+        .map(|row| PositionRotation {
+            position_id: row.member.position_id,
+            holder: match row.holder.as_str() {
+                "vacant" => NewHolder::Vacant,
+                value => value.parse().map(NewHolder::User).unwrap_or(NewHolder::Keep),
+            },
+            membership_type: row.membership_type.clone(),
+        })
+        .collect()
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn render_wizard(
+    pool: &PgPool,
+    session: &Session,
+    tor_id: i64,
+    step: &str,
+    term_date: String,
+    note: String,
+    rows: Vec<NewTermRow>,
+    errors: Vec<String>,
+) -> Result<HttpResponse, AppError> {
+    let tor_label = tor::get_tor_name(pool, tor_id).await?;
+    let ctx = PageContext::build(session, pool, "/tor").await?
+        .with_tor(tor_id, &tor_label, "overview");
+    let users: Vec<UserOption> = sqlx::query_as(
+        "SELECT id, name, label FROM entities WHERE entity_type = 'user' AND is_active = true ORDER BY label",
+    )
+    .fetch_all(pool)
+    .await?;
+    let handovers = if step == "review" {
+        tor::plan_handovers(pool, tor_id, &rotations(&rows)).await?
+    } else {
+        Vec::new()
+    };
+    let terms = tor::find_terms(pool, tor_id).await?;
+
+    render(TorNewTermTemplate {
+        ctx,
+        tor_id,
+        tor_label,
+        step: step.to_string(),
+        term_date,
+        note,
+        rows,
+        users,
+        handovers,
+        terms,
+        errors,
+    })
+}
+
+/// Parse and check the wizard form. Returns the rows, the start date and
+/// any problems.
+async fn check_form(
+    pool: &PgPool,
+    tor_id: i64,
+    form: &HashMap<String, String>,
+) -> Result<(Vec<NewTermRow>, Option<NaiveDate>, Vec<String>), AppError> {
+    let rows = rows_from_form(tor::find_current_members(pool, tor_id).await?, form);
+    let term_date = form.get("term_date")
+        .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok());
+    let terms = tor::find_terms(pool, tor_id).await?;
+    let errors = tor::validate_rotation(term_date, Local::now().date_naive(), terms.first(), &rotations(&rows));
+    Ok((rows, term_date, errors))
+}
+
+/// GET /tor/{id}/new-term — step 1: choose the holders for the new term.
+pub async fn new_term_form(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "tor.manage_members")?;
+    let tor_id = path.into_inner();
+    tor::find_detail_by_id(&pool, tor_id).await?.ok_or(AppError::NotFound)?;
+
+    let rows = rows_from_form(tor::find_current_members(&pool, tor_id).await?, &HashMap::new());
+    let today = Local::now().date_naive().format("%Y-%m-%d").to_string();
+    render_wizard(&pool, &session, tor_id, "edit", today, String::new(), rows, Vec::new()).await
+}
+
+/// POST /tor/{id}/new-term/review — step 2: review the handovers, or go
+/// back to step 1 when `action=edit`.
+pub async fn review_new_term(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+    form: web::Form<HashMap<String, String>>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "tor.manage_members")?;
+    csrf::validate_csrf(&session, form.get("csrf_token").map(|s| s.as_str()).unwrap_or(""))?;
+    let tor_id = path.into_inner();
+    tor::find_detail_by_id(&pool, tor_id).await?.ok_or(AppError::NotFound)?;
+
+    let (rows, _, errors) = check_form(&pool, tor_id, &form).await?;
+    let term_date = form.get("term_date").cloned().unwrap_or_default();
+    let note = form.get("note").cloned().unwrap_or_default();
+    let step = if errors.is_empty() && form.get("action").map(|s| s.as_str()) != Some("edit") {
+        "review"
+    } else {
+        "edit"
+    };
+    let errors = if form.get("action").map(|s| s.as_str()) == Some("edit") { Vec::new() } else { errors };
+    render_wizard(&pool, &session, tor_id, step, term_date, note, rows, errors).await
+}
+
+/// POST /tor/{id}/new-term — step 3: archive the current membership, apply
+/// the new holders and send handover notifications.
+pub async fn start_new_term(
+    pool: web::Data<PgPool>,
+    conn_map: web::Data<ConnectionMap>,
+    session: Session,
+    path: web::Path<i64>,
+    form: web::Form<HashMap<String, String>>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "tor.manage_members")?;
+    csrf::validate_csrf(&session, form.get("csrf_token").map(|s| s.as_str()).unwrap_or(""))?;
+    let tor_id = path.into_inner();
+    let tor_detail = tor::find_detail_by_id(&pool, tor_id).await?.ok_or(AppError::NotFound)?;
+
+    let (rows, term_date, errors) = check_form(&pool, tor_id, &form).await?;
+    let note = form.get("note").map(|s| s.trim().to_string()).unwrap_or_default();
+    let Some(term_date) = term_date.filter(|_| errors.is_empty()) else {
+        let date = form.get("term_date").cloned().unwrap_or_default();
+        return render_wizard(&pool, &session, tor_id, "edit", date, note, rows, errors).await;
+    };
+
+    let rotations = rotations(&rows);
+    let handovers = tor::plan_handovers(&pool, tor_id, &rotations).await?;
+    let term_id = tor::start_new_term(&pool, tor_id, term_date, &rotations).await?;
+
+    for h in &handovers {
+        let message = match (&h.outgoing, &h.incoming) {
+            (Some((_, from)), Some((_, to))) => format!(
+                "Handover in {}: {} passes the {} position to {} from {}",
+                tor_detail.label, from, h.position_label, to, term_date
+            ),
+            (None, Some((_, to))) => format!(
+                "{} takes up the {} position in {} from {}",
+                to, h.position_label, tor_detail.label, term_date
+            ),
+            (Some((_, from)), None) => format!(
+                "{} leaves the {} position in {} from {}; the position is vacant",
+                from, h.position_label, tor_detail.label, term_date
+            ),
+            (None, None) => continue,
+        };
+        let targets: Vec<i64> = h.outgoing.iter().chain(h.incoming.iter()).map(|(id, _)| *id).collect();
+        let details = serde_json::json!({
+            "tor_id": tor_id,
+            "position_id": h.position_id,
+            "term_id": term_id,
+            "note": note,
+        });
+        if let Ok(wid) = crate::warnings::create_warning(
+            &pool, "info", "governance", "event.tor.term_rotation", &message, &details.to_string(), "system",
+        ).await {
+            let _ = crate::warnings::create_receipts(&pool, wid, &targets).await;
+            crate::warnings::outbox::drain(&pool, &conn_map).await;
+        }
+    }
+
+    let user_id = get_user_id(&session).unwrap_or(0);
+    let details = serde_json::json!({
+        "term_id": term_id,
+        "term_date": term_date.to_string(),
+        "handovers": handovers.len(),
+        "summary": format!("Started a new term on {} with {} handover(s)", term_date, handovers.len())
+    });
+    let _ = crate::audit::log(&pool, user_id, "tor.term_started", "tor", tor_id, details).await;
+
+    let _ = session.insert("flash", format!("New term started — {} handover(s) notified", handovers.len()));
+    Ok(HttpResponse::SeeOther()
+        .insert_header(("Location", format!("/tor/{tor_id}")))
+        .finish())
+}
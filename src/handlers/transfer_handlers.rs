@@ -0,0 +1,260 @@
+use actix_session::Session;
+use actix_web::{web, HttpResponse};
+use serde::Deserialize;
+use sqlx::PgPool;
+
+use crate::auth::csrf;
+use crate::auth::session::{get_permissions, get_user_id};
+use crate::errors::{AppError, render};
+use crate::handlers::auth_handlers::CsrfOnly;
+use crate::models::ownership_transfer::{self, OwnershipTransfer, TransferItem};
+use crate::templates_structs::{OwnershipTransfersTemplate, TransferFormTemplate, PageContext, UserOption};
+use crate::warnings::notifications;
+
+#[derive(Deserialize)]
+pub struct TransferForm {
+    pub csrf_token: String,
+    #[serde(default)]
+    pub to_user_id: i64,
+    #[serde(default)]
+    pub note: String,
+}
+
+fn current_user(session: &Session) -> Result<i64, AppError> {
+    get_user_id(session).ok_or(AppError::Session("User not logged in".to_string()))
+}
+
+/// Whether the signed-in user may offer the item to someone else: its owner,
+/// or anyone with the item's manage permission.
+fn may_transfer(session: &Session, user_id: i64, item: &TransferItem) -> bool {
+    (item.owner_id != 0 && item.owner_id == user_id)
+        || get_permissions(session).is_ok_and(|p| p.has(item.manage_permission()))
+}
+
+/// Whether detail pages show the "Transfer ownership" action for an item.
+pub(crate) async fn can_transfer(pool: &PgPool, session: &Session, item_id: i64) -> Result<bool, AppError> {
+    let user_id = current_user(session)?;
+    Ok(ownership_transfer::find_item(pool, item_id).await?
+        .is_some_and(|item| may_transfer(session, user_id, &item)))
+}
+
+async fn find_transfer(pool: &PgPool, id: i64) -> Result<OwnershipTransfer, AppError> {
+    ownership_transfer::find_by_id(pool, id).await?
+        .filter(|t| t.is_pending())
+        .ok_or(AppError::NotFound)
+}
+
+/// Tell everyone involved in a transfer except the actor.
+async fn notify(pool: &PgPool, transfer: &OwnershipTransfer, actor_id: i64, message: &str, link: &str) {
+    let mut users: Vec<i64> = [transfer.from_user_id, transfer.requested_by_id, transfer.to_user_id]
+        .into_iter()
+        .filter(|&id| id != 0 && id != actor_id)
+        .collect();
+    users.sort_unstable();
+    users.dedup();
+    if let Err(e) = notifications::send(pool, "transfer.update", message, link, &users).await {
+        log::error!("Failed to notify ownership transfer {}: {}", transfer.id, e);
+    }
+}
+
+fn audit_details(transfer: &OwnershipTransfer, summary: String) -> serde_json::Value {
+    serde_json::json!({
+        "item_type": transfer.item_type,
+        "item_id": transfer.item_id,
+        "from_user_id": transfer.from_user_id,
+        "from_user": transfer.from_name,
+        "to_user_id": transfer.to_user_id,
+        "to_user": transfer.to_name,
+        "requested_by_id": transfer.requested_by_id,
+        "summary": summary,
+    })
+}
+
+async fn render_form(
+    pool: &PgPool,
+    session: &Session,
+    user_id: i64,
+    item: TransferItem,
+    form: Option<&TransferForm>,
+    errors: Vec<String>,
+) -> Result<HttpResponse, AppError> {
+    let ctx = PageContext::build(session, pool, "/transfers").await?;
+    let pending = ownership_transfer::find_pending_for_item(pool, item.id).await?;
+    let users = sqlx::query_as::<_, UserOption>(
+        "SELECT id, name, label FROM entities \
+         WHERE entity_type = 'user' AND is_active = true AND id != $1 ORDER BY label",
+    )
+    .bind(item.owner_id)
+    .fetch_all(pool)
+    .await?;
+    render(TransferFormTemplate {
+        ctx,
+        user_id,
+        item,
+        pending,
+        users,
+        to_user_id: form.map(|f| f.to_user_id).unwrap_or(0),
+        note: form.map(|f| f.note.clone()).unwrap_or_default(),
+        errors,
+    })
+}
+
+/// GET /transfers — ownership transfers offered to and by the user.
+pub async fn list(
+    pool: web::Data<PgPool>,
+    session: Session,
+) -> Result<HttpResponse, AppError> {
+    let user_id = current_user(&session)?;
+    let ctx = PageContext::build(&session, &pool, "/transfers").await?;
+    let incoming = ownership_transfer::find_incoming(&pool, user_id).await?;
+    let outgoing = ownership_transfer::find_outgoing(&pool, user_id).await?;
+    render(OwnershipTransfersTemplate { ctx, user_id, incoming, outgoing })
+}
+
+/// GET /transfers/new/{item_id} — offer a proposal, ToR chair or document
+/// to another user.
+pub async fn new_form(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+) -> Result<HttpResponse, AppError> {
+    let user_id = current_user(&session)?;
+    let item = ownership_transfer::find_item(&pool, path.into_inner()).await?
+        .ok_or(AppError::NotFound)?;
+    if !may_transfer(&session, user_id, &item) {
+        return Err(AppError::PermissionDenied(item.manage_permission().to_string()));
+    }
+    render_form(&pool, &session, user_id, item, None, Vec::new()).await
+}
+
+/// POST /transfers/new/{item_id} — create the pending transfer and notify
+/// the new owner.
+pub async fn create(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+    form: web::Form<TransferForm>,
+) -> Result<HttpResponse, AppError> {
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+    let user_id = current_user(&session)?;
+    let item = ownership_transfer::find_item(&pool, path.into_inner()).await?
+        .ok_or(AppError::NotFound)?;
+    if !may_transfer(&session, user_id, &item) {
+        return Err(AppError::PermissionDenied(item.manage_permission().to_string()));
+    }
+
+    let errors = ownership_transfer::validate(&pool, &item, form.to_user_id).await?;
+    if !errors.is_empty() {
+        return render_form(&pool, &session, user_id, item, Some(&form), errors).await;
+    }
+
+    let id = ownership_transfer::request(&pool, &item, form.to_user_id, user_id, &form.note).await?;
+    let transfer = ownership_transfer::find_by_id(&pool, id).await?.ok_or(AppError::NotFound)?;
+    let summary = format!(
+        "{} asked {} to take over {} from {}",
+        transfer.requested_by_name, transfer.to_name, item.describe(),
+        if transfer.from_name.is_empty() { "nobody" } else { transfer.from_name.as_str() },
+    );
+    let details = audit_details(&transfer, summary);
+    let _ = crate::audit::log(&pool, user_id, "ownership.transfer_requested", &item.kind, item.id, details).await;
+
+    let message = format!("{} asks you to take over {}", transfer.requested_by_name, item.describe());
+    notify(&pool, &transfer, user_id, &message, "/transfers").await;
+
+    let _ = session.insert("flash", format!("Transfer offered to {} — it takes effect once they accept", transfer.to_name));
+    Ok(HttpResponse::SeeOther()
+        .insert_header(("Location", item.path()))
+        .finish())
+}
+
+/// POST /transfers/{id}/accept — the new owner takes over the item.
+pub async fn accept(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+    form: web::Form<CsrfOnly>,
+) -> Result<HttpResponse, AppError> {
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+    let user_id = current_user(&session)?;
+    let transfer = find_transfer(&pool, path.into_inner()).await?;
+    if transfer.to_user_id != user_id {
+        return Err(AppError::NotFound);
+    }
+
+    if !ownership_transfer::accept(&pool, &transfer).await? {
+        ownership_transfer::cancel(&pool, transfer.id).await?;
+        let _ = session.insert("flash", "The item no longer exists; the transfer was withdrawn");
+        return Ok(HttpResponse::SeeOther()
+            .insert_header(("Location", "/transfers"))
+            .finish());
+    }
+    let what = transfer.describe();
+    let summary = format!(
+        "{} took over {} from {}",
+        transfer.to_name, what,
+        if transfer.from_name.is_empty() { "nobody" } else { transfer.from_name.as_str() },
+    );
+    let details = audit_details(&transfer, summary);
+    let _ = crate::audit::log(&pool, user_id, "ownership.transferred", &transfer.item_type, transfer.item_id, details).await;
+
+    let message = format!("{} accepted ownership of {}", transfer.to_name, what);
+    notify(&pool, &transfer, user_id, &message, &transfer.item_path()).await;
+
+    let _ = session.insert("flash", format!("You now own {}", what));
+    Ok(HttpResponse::SeeOther()
+        .insert_header(("Location", transfer.item_path()))
+        .finish())
+}
+
+/// POST /transfers/{id}/decline — the offered owner turns it down.
+pub async fn decline(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+    form: web::Form<CsrfOnly>,
+) -> Result<HttpResponse, AppError> {
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+    let user_id = current_user(&session)?;
+    let transfer = find_transfer(&pool, path.into_inner()).await?;
+    if transfer.to_user_id != user_id {
+        return Err(AppError::NotFound);
+    }
+
+    ownership_transfer::decline(&pool, transfer.id).await?;
+    let what = transfer.describe();
+    let details = audit_details(&transfer, format!("{} declined to take over {}", transfer.to_name, what));
+    let _ = crate::audit::log(&pool, user_id, "ownership.transfer_declined", &transfer.item_type, transfer.item_id, details).await;
+    notify(&pool, &transfer, user_id, &format!("{} declined ownership of {}", transfer.to_name, what), &transfer.item_path()).await;
+
+    let _ = session.insert("flash", "Transfer declined");
+    Ok(HttpResponse::SeeOther()
+        .insert_header(("Location", "/transfers"))
+        .finish())
+}
+
+/// POST /transfers/{id}/cancel — the requester or current owner withdraws
+/// the offer.
+pub async fn cancel(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+    form: web::Form<CsrfOnly>,
+) -> Result<HttpResponse, AppError> {
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+    let user_id = current_user(&session)?;
+    let transfer = find_transfer(&pool, path.into_inner()).await?;
+    if !transfer.can_cancel(user_id) {
+        return Err(AppError::NotFound);
+    }
+
+    ownership_transfer::cancel(&pool, transfer.id).await?;
+    let what = transfer.describe();
+    let details = audit_details(&transfer, format!("Withdrew the transfer of {} to {}", what, transfer.to_name));
+    let _ = crate::audit::log(&pool, user_id, "ownership.transfer_cancelled", &transfer.item_type, transfer.item_id, details).await;
+    notify(&pool, &transfer, user_id, &format!("The transfer of {} to {} was withdrawn", what, transfer.to_name), "/transfers").await;
+
+    let _ = session.insert("flash", "Transfer withdrawn");
+    Ok(HttpResponse::SeeOther()
+        .insert_header(("Location", "/transfers"))
+        .finish())
+}
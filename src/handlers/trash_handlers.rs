@@ -0,0 +1,92 @@
+use actix_session::Session;
+use actix_web::{web, HttpResponse};
+use sqlx::PgPool;
+
+use crate::auth::csrf;
+use crate::auth::session::{get_user_id, require_permission};
+use crate::errors::{AppError, render};
+use crate::handlers::auth_handlers::CsrfOnly;
+use crate::models::{setting, trash};
+use crate::templates_structs::{PageContext, TrashTemplate};
+
+fn redirect() -> HttpResponse {
+    HttpResponse::SeeOther()
+        .insert_header(("Location", "/admin/trash"))
+        .finish()
+}
+
+/// GET /admin/trash — deleted users, proposals and ToRs awaiting restore or purge.
+pub async fn list(
+    pool: web::Data<PgPool>,
+    session: Session,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    let ctx = PageContext::build(&session, &pool, "/admin/trash").await?;
+    let entries = trash::find_all(&pool).await?;
+    let retention_days = setting::get_i64(&pool, "trash.retention_days").await;
+    render(TrashTemplate { ctx, entries, retention_days })
+}
+
+/// POST /admin/trash/{id}/restore — put a deleted entity back.
+pub async fn restore(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+    form: web::Form<CsrfOnly>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+    let user_id = get_user_id(&session).ok_or(AppError::Session("User not logged in".to_string()))?;
+    let entry = trash::find_by_id(&pool, path.into_inner()).await?.ok_or(AppError::NotFound)?;
+
+    if trash::name_taken(&pool, &entry).await? {
+        let _ = session.insert("flash", format!(
+            "Cannot restore '{}': another {} named '{}' exists now",
+            entry.label, entry.entity_type, entry.name
+        ));
+        return Ok(redirect());
+    }
+    let Some(restored) = trash::restore(&pool, entry.id).await? else {
+        return Err(AppError::NotFound);
+    };
+
+    let details = serde_json::json!({
+        "relations": restored.relations,
+        "dropped_relations": restored.dropped,
+        "summary": format!("Restored {} '{}' from the trash", entry.entity_type, entry.label)
+    });
+    let _ = crate::audit::log(&pool, user_id, "trash.restored", &entry.entity_type, restored.entity_id, details).await;
+
+    let mut message = format!("Restored '{}'", entry.label);
+    if restored.dropped > 0 {
+        message.push_str(&format!(
+            "; {} link{} to items no longer present could not be restored",
+            restored.dropped, if restored.dropped == 1 { "" } else { "s" }
+        ));
+    }
+    let _ = session.insert("flash", message);
+    Ok(redirect())
+}
+
+/// POST /admin/trash/{id}/purge — delete a trashed entity for good.
+pub async fn purge(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+    form: web::Form<CsrfOnly>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+    let user_id = get_user_id(&session).ok_or(AppError::Session("User not logged in".to_string()))?;
+    let entry = trash::find_by_id(&pool, path.into_inner()).await?.ok_or(AppError::NotFound)?;
+
+    if trash::purge(&pool, entry.id).await? {
+        let details = serde_json::json!({
+            "name": entry.name,
+            "summary": format!("Purged {} '{}' from the trash", entry.entity_type, entry.label)
+        });
+        let _ = crate::audit::log(&pool, user_id, "trash.purged", &entry.entity_type, entry.entity_id, details).await;
+        let _ = session.insert("flash", format!("'{}' deleted permanently", entry.label));
+    }
+    Ok(redirect())
+}
@@ -24,6 +24,7 @@ pub async fn new_form(
         form_title: "Create User".to_string(),
         user: None,
         errors: vec![],
+        locked_reason: None,
     };
     render(tmpl)
 }
@@ -37,7 +38,7 @@ pub async fn create(
     require_permission(&session, "users.create")?;
     csrf::validate_csrf(&session, &form.csrf_token)?;
 
-    let errors = helpers::validate_user_form(&form, true);
+    let errors = helpers::validate_user_form(&pool, &form, None).await?;
 
     if !errors.is_empty() {
         let ctx = PageContext::build(&session, &pool, "/users").await?;
@@ -47,6 +48,7 @@ pub async fn create(
             form_title: "Create User".to_string(),
             user: None,
             errors,
+            locked_reason: None,
         };
         return render(tmpl);
     }
@@ -82,9 +84,7 @@ pub async fn create(
                 let admins = crate::warnings::get_users_with_permission(&pool, "admin.settings").await.unwrap_or_default();
                 if !admins.is_empty() {
                     let _ = crate::warnings::create_receipts(&pool, wid, &admins).await;
-                    crate::handlers::warning_handlers::ws::notify_users(
-                        &conn_map, &pool, &admins, wid, "info", &msg,
-                    ).await;
+                    crate::warnings::outbox::drain(&pool, &conn_map).await;
                 }
             }
 
@@ -106,6 +106,7 @@ pub async fn create(
                 form_title: "Create User".to_string(),
                 user: None,
                 errors: vec![msg],
+                locked_reason: None,
             };
             render(tmpl)
         }
@@ -61,9 +61,7 @@ pub async fn delete(
                     let admins = crate::warnings::get_users_with_permission(&pool, "admin.settings").await.unwrap_or_default();
                     if !admins.is_empty() {
                         let _ = crate::warnings::create_receipts(&pool, wid, &admins).await;
-                        crate::handlers::warning_handlers::ws::notify_users(
-                            &conn_map, &pool, &admins, wid, "medium", &msg,
-                        ).await;
+                        crate::warnings::outbox::drain(&pool, &conn_map).await;
                     }
                 }
             }
@@ -156,9 +154,7 @@ pub async fn bulk_delete(
                     let admins = crate::warnings::get_users_with_permission(&pool, "admin.settings").await.unwrap_or_default();
                     if !admins.is_empty() {
                         let _ = crate::warnings::create_receipts(&pool, wid, &admins).await;
-                        crate::handlers::warning_handlers::ws::notify_users(
-                            &conn_map, &pool, &admins, wid, "medium", &msg,
-                        ).await;
+                        crate::warnings::outbox::drain(&pool, &conn_map).await;
                     }
                 }
             }
@@ -3,18 +3,22 @@ use crate::auth::session::get_user_id;
 use crate::models::user::{self, UserForm};
 use crate::errors::AppError;
 use crate::auth::validate;
+use crate::auth::password_policy::PasswordPolicy;
 use actix_session::Session;
 
-/// Validate user form data (used in both create and update flows)
-pub fn validate_user_form(form: &UserForm, require_password: bool) -> Vec<String> {
+/// Validate user form data (used in both create and update flows).
+/// `user_id` is `None` when creating, which requires a password; a new
+/// password must satisfy the password policy.
+pub async fn validate_user_form(pool: &PgPool, form: &UserForm, user_id: Option<i64>) -> Result<Vec<String>, AppError> {
     let mut errors = vec![];
     errors.extend(validate::validate_username(&form.username));
     errors.extend(validate::validate_email(&form.email));
     errors.extend(validate::validate_optional(&form.display_name, "Display name", 100));
-    if require_password || !form.password.is_empty() {
-        errors.extend(validate::validate_password(&form.password));
+    if user_id.is_none() || !form.password.is_empty() {
+        let policy = PasswordPolicy::load(pool).await;
+        errors.extend(policy.validate(pool, &form.password, &form.username, user_id).await?);
     }
-    errors
+    Ok(errors)
 }
 
 /// Check if a user is the last admin in the system
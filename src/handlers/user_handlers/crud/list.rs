@@ -5,32 +5,24 @@ use sqlx::PgPool;
 
 use crate::auth::session::{require_permission, get_user_id};
 use crate::errors::AppError;
-
-#[derive(Deserialize)]
-pub struct ExportQuery {
-    filter: Option<String>,
-    sort: Option<String>,
-    dir: Option<String>,
-}
+use crate::models::table_filter::TableQuery;
 
 pub async fn export_csv(
     pool: web::Data<PgPool>,
     session: Session,
-    query: web::Query<ExportQuery>,
+    query: web::Query<TableQuery>,
 ) -> Result<HttpResponse, AppError> {
     require_permission(&session, "users.list")?;
 
-    let filter = query.filter.as_deref()
-        .and_then(|s| crate::models::table_filter::FilterTree::from_json(s).ok())
-        .unwrap_or_default();
-    let sort = crate::models::table_filter::SortSpec::from_params(
-        query.sort.as_deref(), query.dir.as_deref()
-    );
+    let filter = query.filter_tree();
+    let sort = query.sort_spec(&crate::models::user::filter::default_columns());
 
     let users = crate::models::user::find_all_filtered(&pool, &filter, &sort).await?;
 
-    // Audit log
     let uid = crate::auth::session::get_user_id(&session).unwrap_or(0);
+    let watermark = crate::handlers::export_handlers::authorize_export(&pool, uid, "csv").await?;
+
+    // Audit log
     let _ = crate::audit::log(&pool, uid, "users.export", "user", 0,
         serde_json::json!({ "count": users.len(), "format": "csv" })).await;
 
@@ -61,11 +53,13 @@ pub async fn export_csv(
         ));
     }
 
-    Ok(HttpResponse::Ok()
-        .content_type("text/csv; charset=utf-8")
-        .insert_header(("Content-Disposition",
-            format!("attachment; filename=\"users-{today}.csv\"")))
-        .body(csv))
+    if let Some(mark) = &watermark {
+        csv = mark.stamp_csv(csv);
+    }
+
+    crate::handlers::export_handlers::checksummed_download(
+        &pool, uid, "user_list", &format!("users-{today}.csv"), "text/csv; charset=utf-8", "attachment", csv.into_bytes(),
+    ).await
 }
 
 #[derive(Deserialize)]
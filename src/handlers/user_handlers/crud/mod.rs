@@ -8,6 +8,6 @@ pub mod list;
 // Re-export all handlers for backwards compatibility
 pub use self::create::{new_form, create};
 pub use self::read::edit_form;
-pub use self::update::update;
+pub use self::update::{update, unlock};
 pub use self::delete::{delete, bulk_delete, BulkDeleteForm};
-pub use self::list::{export_csv, save_columns, ExportQuery, SaveColumnsForm};
+pub use self::list::{export_csv, save_columns, SaveColumnsForm};
@@ -2,7 +2,7 @@ use actix_session::Session;
 use actix_web::{web, HttpResponse};
 use sqlx::PgPool;
 
-use crate::models::user;
+use crate::models::{entity, user};
 use crate::auth::session::require_permission;
 use crate::errors::{AppError, render};
 use crate::templates_structs::{PageContext, UserFormTemplate};
@@ -18,6 +18,11 @@ pub async fn edit_form(
 
     match user::find_display_by_id(&pool, id).await {
         Ok(Some(u)) => {
+            let locked_reason = if user::is_locked(&pool, id).await? {
+                Some(entity::get_property(&pool, id, "locked_reason").await?.unwrap_or_default())
+            } else {
+                None
+            };
             let ctx = PageContext::build(&session, &pool, "/users").await?;
             let tmpl = UserFormTemplate {
                 ctx,
@@ -25,6 +30,7 @@ pub async fn edit_form(
                 form_title: "Edit User".to_string(),
                 user: Some(u),
                 errors: vec![],
+                locked_reason,
             };
             render(tmpl)
         }
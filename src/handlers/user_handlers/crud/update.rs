@@ -3,9 +3,10 @@ use actix_web::{web, HttpResponse};
 use sqlx::PgPool;
 
 use crate::models::user::{self, UserForm};
-use crate::auth::{csrf, password};
-use crate::auth::session::require_permission;
+use crate::auth::{csrf, lockout, password};
+use crate::auth::session::{get_user_id, require_permission};
 use crate::errors::{AppError, render};
+use crate::handlers::auth_handlers::CsrfOnly;
 use crate::templates_structs::{PageContext, UserFormTemplate};
 use super::helpers;
 
@@ -21,7 +22,7 @@ pub async fn update(
     let id = path.into_inner();
 
     // Validate (password is optional on update)
-    let errors = helpers::validate_user_form(&form, false);
+    let errors = helpers::validate_user_form(&pool, &form, Some(id)).await?;
 
     if !errors.is_empty() {
         let existing = user::find_display_by_id(&pool, id).await.ok().flatten();
@@ -32,6 +33,7 @@ pub async fn update(
             form_title: "Edit User".to_string(),
             user: existing,
             errors,
+            locked_reason: None,
         };
         return render(tmpl);
     }
@@ -85,8 +87,38 @@ pub async fn update(
                 form_title: "Edit User".to_string(),
                 user: existing,
                 errors: vec![msg],
+                locked_reason: None,
             };
             render(tmpl)
         }
     }
 }
+
+/// POST /users/{id}/unlock — let a locked account sign in again.
+pub async fn unlock(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+    form: web::Form<CsrfOnly>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "users.edit")?;
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+    let current_user_id = get_user_id(&session)
+        .ok_or_else(|| AppError::Session("User not logged in".to_string()))?;
+
+    let id = path.into_inner();
+    let target = user::find_display_by_id(&pool, id).await?.ok_or(AppError::NotFound)?;
+    if user::is_locked(&pool, id).await? {
+        user::unlock(&pool, id).await?;
+        lockout::clear(&pool, id).await?;
+        let details = serde_json::json!({
+            "username": target.username,
+            "summary": format!("Unlocked user '{}'", target.username)
+        });
+        let _ = crate::audit::log(&pool, current_user_id, "user.unlocked", "user", id, details).await;
+        let _ = session.insert("flash", "User unlocked");
+    }
+    Ok(HttpResponse::SeeOther()
+        .insert_header(("Location", format!("/users/{id}/edit")))
+        .finish())
+}
@@ -1,5 +1,7 @@
 pub mod list;
 pub mod crud;
+pub mod reassign;
 
 pub use list::*;
 pub use crud::*;
+pub use reassign::*;
@@ -0,0 +1,119 @@
+use actix_session::Session;
+use actix_web::{web, HttpResponse};
+use chrono::Local;
+use serde::Deserialize;
+use sqlx::PgPool;
+
+use crate::auth::csrf;
+use crate::auth::session::{get_user_id, require_permission};
+use crate::errors::{AppError, render};
+use crate::models::{entity, reassignment};
+use crate::templates_structs::{PageContext, UserOption, UserReassignTemplate};
+
+#[derive(Deserialize)]
+pub struct ReassignQuery {
+    pub to: Option<i64>,
+}
+
+#[derive(Deserialize)]
+pub struct ReassignForm {
+    pub csrf_token: String,
+    pub to_user_id: i64,
+}
+
+async fn render_page(
+    pool: &PgPool,
+    session: &Session,
+    from_user_id: i64,
+    to_user_id: i64,
+    errors: Vec<String>,
+) -> Result<HttpResponse, AppError> {
+    let from = entity::find_by_id(pool, from_user_id).await?
+        .filter(|u| u.entity_type == "user")
+        .ok_or(AppError::NotFound)?;
+    let ctx = PageContext::build(session, pool, "/users").await?;
+    let users: Vec<UserOption> = sqlx::query_as(
+        "SELECT id, name, label FROM entities WHERE entity_type = 'user' AND is_active = true ORDER BY label",
+    )
+    .fetch_all(pool)
+    .await?;
+    let to_label = users.iter().find(|u| u.id == to_user_id).map(|u| u.label.clone()).unwrap_or_default();
+    let previewed = to_user_id > 0 && errors.is_empty();
+    let items = if previewed {
+        reassignment::plan(pool, from_user_id, to_user_id, Local::now().date_naive()).await?
+    } else {
+        Vec::new()
+    };
+    render(UserReassignTemplate {
+        ctx,
+        from_user_id,
+        from_label: from.label,
+        users,
+        to_user_id,
+        to_label,
+        previewed,
+        kinds: reassignment::KINDS,
+        items,
+        errors,
+    })
+}
+
+/// GET /users/{id}/reassign?to= — choose who receives the user's pending
+/// work, then preview what moves.
+pub async fn reassign_form(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+    query: web::Query<ReassignQuery>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "users.edit")?;
+    let from_user_id = path.into_inner();
+    let to_user_id = query.to.unwrap_or(0);
+    let errors = if to_user_id > 0 {
+        reassignment::validate(&pool, from_user_id, to_user_id).await?
+    } else {
+        Vec::new()
+    };
+    render_page(&pool, &session, from_user_id, to_user_id, errors).await
+}
+
+/// POST /users/{id}/reassign — move everything in the preview.
+pub async fn reassign(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+    form: web::Form<ReassignForm>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "users.edit")?;
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+    let from_user_id = path.into_inner();
+    let errors = reassignment::validate(&pool, from_user_id, form.to_user_id).await?;
+    if !errors.is_empty() {
+        return render_page(&pool, &session, from_user_id, form.to_user_id, errors).await;
+    }
+
+    let from_label = entity::find_by_id(&pool, from_user_id).await?
+        .filter(|u| u.entity_type == "user")
+        .ok_or(AppError::NotFound)?
+        .label;
+    let to_label = entity::find_by_id(&pool, form.to_user_id).await?.map(|u| u.label).unwrap_or_default();
+    let items = reassignment::apply(&pool, from_user_id, form.to_user_id, Local::now().date_naive()).await?;
+    let moved: Vec<_> = items.iter().filter(|i| i.moves()).collect();
+
+    let user_id = get_user_id(&session).ok_or(AppError::Session("User not logged in".to_string()))?;
+    let counts: serde_json::Map<String, serde_json::Value> = reassignment::KINDS.iter()
+        .map(|(kind, _)| (kind.to_string(), moved.iter().filter(|i| i.kind == *kind).count().into()))
+        .collect();
+    let details = serde_json::json!({
+        "to_user_id": form.to_user_id,
+        "moved": counts,
+        "skipped": items.len() - moved.len(),
+        "summary": format!("Reassigned {} item(s) from {} to {}", moved.len(), from_label, to_label)
+    });
+    let _ = crate::audit::log(&pool, user_id, "user.work_reassigned", "user", from_user_id, details).await;
+
+    let _ = session.insert("flash", format!("Reassigned {} item(s) from {} to {}", moved.len(), from_label, to_label));
+    Ok(HttpResponse::SeeOther()
+        .insert_header(("Location", format!("/users/{}/edit", from_user_id)))
+        .finish())
+}
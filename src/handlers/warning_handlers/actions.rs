@@ -3,8 +3,9 @@ use actix_web::{web, HttpResponse};
 use serde::Deserialize;
 use sqlx::PgPool;
 
-use crate::auth::{csrf, session::get_user_id};
+use crate::auth::{csrf, session::{get_user_id, require_permission}};
 use crate::errors::AppError;
+use crate::models::action_item::{self, ActionItemInput};
 use crate::warnings::{self, queries};
 use crate::handlers::warning_handlers::ws::{ConnectionMap, send_count_update};
 
@@ -20,6 +21,16 @@ pub struct ForwardForm {
     pub target_user_id: i64,
 }
 
+#[derive(Deserialize)]
+pub struct ConvertForm {
+    pub csrf_token: String,
+    pub title: String,
+    /// `{tor_id}:{user_id}` — the ToR the item is tracked in and its owner.
+    pub assignee: String,
+    #[serde(default)]
+    pub due_date: String,
+}
+
 pub async fn mark_deleted(
     pool: web::Data<PgPool>,
     session: Session,
@@ -70,12 +81,7 @@ pub async fn forward(
     warnings::create_receipts(&pool, warning_id, &[form.target_user_id]).await?;
 
     // Notify target user via WS
-    if let Some(w) = queries::get_warning_detail(&pool, warning_id).await? {
-        crate::handlers::warning_handlers::ws::notify_users(
-            &conn_map, &pool, &[form.target_user_id],
-            warning_id, &w.severity, &w.message,
-        ).await;
-    }
+    warnings::outbox::drain(&pool, &conn_map).await;
 
     let details = serde_json::json!({
         "warning_id": warning_id,
@@ -91,3 +97,57 @@ pub async fn forward(
         .insert_header(("Location", location.as_str()))
         .finish())
 }
+
+/// POST /warnings/{id}/action-item — track the follow-up of a warning as an
+/// action item. Completing the item resolves the warning.
+pub async fn convert_to_action_item(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+    form: web::Form<ConvertForm>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "agenda.manage")?;
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+    let warning_id = path.into_inner();
+    let user_id = get_user_id(&session).ok_or_else(|| AppError::Session("No user".into()))?;
+    let warning = queries::get_warning_detail(&pool, warning_id).await?
+        .ok_or(AppError::NotFound)?;
+    let back = HttpResponse::SeeOther()
+        .insert_header(("Location", format!("/warnings/{}", warning_id)))
+        .finish();
+
+    let (tor_id, owner_id) = form.assignee.split_once(':')
+        .and_then(|(t, u)| Some((t.parse::<i64>().ok()?, u.parse::<i64>().ok()?)))
+        .unwrap_or((0, 0));
+    let input = ActionItemInput {
+        title: &form.title,
+        description: &warning.message,
+        owner_id,
+        due_date: &form.due_date,
+        status: action_item::STATUSES[0].0,
+    };
+    let owners: Vec<i64> = action_item::find_owners(&pool, tor_id).await?.into_iter().map(|(id, _)| id).collect();
+    let errors = action_item::validate(&input, &owners);
+    if !errors.is_empty() {
+        let _ = session.insert("flash", errors.join(". "));
+        return Ok(back);
+    }
+
+    let id = action_item::create(&pool, tor_id, &input, None, user_id).await?;
+    action_item::link_warning(&pool, id, warning_id).await?;
+    if let Some(receipt_id) = queries::find_receipt_for_user(&pool, warning_id, user_id).await? {
+        warnings::create_event(&pool, receipt_id, "converted", user_id, Some(input.title.trim())).await?;
+    }
+
+    let details = serde_json::json!({
+        "tor_id": tor_id,
+        "owner_id": owner_id,
+        "due_date": input.due_date.trim(),
+        "warning_id": warning_id,
+        "summary": format!("Created action item '{}' from warning #{}", input.title.trim(), warning_id)
+    });
+    let _ = crate::audit::log(&pool, user_id, "action_item.created", "action_item", id, details).await;
+
+    let _ = session.insert("flash", format!("Action item '{}' created", input.title.trim()));
+    Ok(back)
+}
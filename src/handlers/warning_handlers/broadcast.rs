@@ -0,0 +1,126 @@
+use actix_session::Session;
+use actix_web::{web, HttpResponse};
+use serde::Deserialize;
+use sqlx::PgPool;
+
+use crate::auth::csrf;
+use crate::auth::session::{get_user_id, require_permission};
+use crate::errors::{AppError, render};
+use crate::handlers::auth_handlers::CsrfOnly;
+use crate::models::{role, tor};
+use crate::templates_structs::{BroadcastsTemplate, PageContext};
+use crate::warnings::{broadcast, clock};
+use crate::warnings::broadcast::BroadcastInput;
+
+#[derive(Deserialize)]
+pub struct BroadcastForm {
+    pub csrf_token: String,
+    pub severity: String,
+    pub message: String,
+    #[serde(default)]
+    pub details: String,
+    pub audience: String,
+    #[serde(default)]
+    pub role_id: String,
+    #[serde(default)]
+    pub tor_id: String,
+    /// `datetime-local`; empty to send now.
+    #[serde(default)]
+    pub send_at: String,
+    /// `datetime-local`; empty for no expiry.
+    #[serde(default)]
+    pub expires_at: String,
+}
+
+async fn render_list(pool: &PgPool, session: &Session, errors: Vec<String>) -> Result<HttpResponse, AppError> {
+    let ctx = PageContext::build(session, pool, "/broadcasts").await?;
+    let broadcasts = broadcast::find_all(pool).await?;
+    let roles = role::find_all_display(pool).await?;
+    let tors = tor::find_all_tors(pool).await?;
+    render(BroadcastsTemplate { ctx, broadcasts, roles, tors, errors })
+}
+
+/// GET /broadcasts — scheduled and past broadcast notices.
+pub async fn list(
+    pool: web::Data<PgPool>,
+    session: Session,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    render_list(&pool, &session, Vec::new()).await
+}
+
+/// POST /broadcasts — schedule a notice; one without a send time goes out now.
+pub async fn create(
+    pool: web::Data<PgPool>,
+    session: Session,
+    form: web::Form<BroadcastForm>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+    let user_id = get_user_id(&session).unwrap_or(0);
+
+    let mut errors = Vec::new();
+    let send_at = broadcast::parse_local(&form.send_at).unwrap_or_else(|e| { errors.push(e); None });
+    let expires_at = broadcast::parse_local(&form.expires_at).unwrap_or_else(|e| { errors.push(e); None });
+    let audience_id = match form.audience.as_str() {
+        "role" => form.role_id.trim().parse().ok(),
+        "tor" => form.tor_id.trim().parse().ok(),
+        _ => None,
+    };
+    let input = BroadcastInput {
+        severity: &form.severity,
+        message: &form.message,
+        details: &form.details,
+        audience: &form.audience,
+        audience_id,
+        send_at,
+        expires_at,
+    };
+    errors.extend(broadcast::validate(&input, clock::now()));
+    if !errors.is_empty() {
+        return render_list(&pool, &session, errors).await;
+    }
+
+    let id = broadcast::create(&pool, &input, user_id).await?;
+    let details = serde_json::json!({
+        "audience": input.audience,
+        "audience_id": input.audience_id,
+        "severity": input.severity,
+        "send_at": input.send_at.map(|t| t.to_rfc3339()),
+        "expires_at": input.expires_at.map(|t| t.to_rfc3339()),
+        "summary": format!("Scheduled broadcast: {}", input.message.trim())
+    });
+    let _ = crate::audit::log(&pool, user_id, "broadcast.created", "warning_broadcast", id, details).await;
+
+    // Due now unless scheduled for later
+    let (sent, _) = broadcast::run_due(&pool).await?;
+    let flash = if sent > 0 { "Broadcast sent" } else { "Broadcast scheduled" };
+    let _ = session.insert("flash", flash);
+    Ok(HttpResponse::SeeOther()
+        .insert_header(("Location", "/broadcasts"))
+        .finish())
+}
+
+/// POST /broadcasts/{id}/cancel — cancel a scheduled broadcast or withdraw a sent one.
+pub async fn cancel(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+    form: web::Form<CsrfOnly>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+    let id = path.into_inner();
+    let user_id = get_user_id(&session).unwrap_or(0);
+
+    if !broadcast::cancel(&pool, id, user_id).await? {
+        return Err(AppError::NotFound);
+    }
+    let details = serde_json::json!({ "summary": "Cancelled broadcast" });
+    let _ = crate::audit::log(&pool, user_id, "broadcast.cancelled", "warning_broadcast", id, details).await;
+
+    let _ = session.insert("flash", "Broadcast cancelled");
+    Ok(HttpResponse::SeeOther()
+        .insert_header(("Location", "/broadcasts"))
+        .finish())
+}
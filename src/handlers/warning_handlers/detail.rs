@@ -2,9 +2,10 @@ use actix_session::Session;
 use actix_web::{web, HttpResponse};
 use sqlx::PgPool;
 
-use crate::auth::session::get_user_id;
+use crate::auth::session::{get_permissions, get_user_id};
 use crate::errors::{AppError, render};
-use crate::templates_structs::{PageContext, WarningDetailTemplate, UserOption};
+use crate::models::{action_item, entity};
+use crate::templates_structs::{AssigneeGroup, PageContext, WarningDetailTemplate, UserOption};
 use crate::warnings::queries;
 
 pub async fn detail(
@@ -54,6 +55,19 @@ pub async fn detail(
         }
     }
 
+    let action_items = action_item::find_for_warning(&pool, warning_id).await?;
+    let can_convert = warning.status != "resolved"
+        && get_permissions(&session).map(|p| p.has("agenda.manage")).unwrap_or(false);
+    let mut assignees = Vec::new();
+    if can_convert {
+        for tor in entity::find_by_type(&pool, "tor").await? {
+            let holders = action_item::find_owners(&pool, tor.id).await?;
+            if !holders.is_empty() {
+                assignees.push(AssigneeGroup { tor_id: tor.id, tor_label: tor.label, holders });
+            }
+        }
+    }
+
     let tmpl = WarningDetailTemplate {
         ctx,
         warning,
@@ -61,6 +75,9 @@ pub async fn detail(
         timeline,
         user_receipt_id: receipt_id,
         users,
+        action_items,
+        assignees,
+        can_convert,
     };
 
     render(tmpl)
@@ -2,3 +2,5 @@ pub mod ws;
 pub mod list;
 pub mod detail;
 pub mod actions;
+pub mod outbox;
+pub mod broadcast;
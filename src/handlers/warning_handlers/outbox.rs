@@ -0,0 +1,86 @@
+use actix_session::Session;
+use actix_web::{web, HttpResponse};
+use serde::Deserialize;
+use sqlx::PgPool;
+
+use crate::auth::csrf;
+use crate::auth::session::{get_user_id, require_permission};
+use crate::errors::{AppError, render};
+use crate::handlers::warning_handlers::ws::ConnectionMap;
+use crate::templates_structs::{OutboxTemplate, PageContext};
+use crate::warnings::outbox;
+
+const STATUSES: &[&str] = &["pending", "sending", "delivered", "failed"];
+
+#[derive(Deserialize)]
+pub struct OutboxQuery {
+    status: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct ReplayForm {
+    pub csrf_token: String,
+}
+
+/// GET /outbox — queued notification deliveries and their status.
+pub async fn list(
+    pool: web::Data<PgPool>,
+    session: Session,
+    query: web::Query<OutboxQuery>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+
+    let ctx = PageContext::build(&session, &pool, "/outbox").await?;
+    let status = query.status.clone().filter(|s| STATUSES.contains(&s.as_str()));
+    let entries = outbox::find_recent(&pool, status.as_deref(), 200).await?;
+    let counts = outbox::count_by_status(&pool).await?;
+
+    render(OutboxTemplate { ctx, entries, counts, status: status.unwrap_or_default() })
+}
+
+/// POST /outbox/{id}/replay — requeue one delivery and dispatch it now.
+pub async fn replay(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+    form: web::Form<ReplayForm>,
+    conn_map: web::Data<ConnectionMap>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+    let user_id = get_user_id(&session).unwrap_or(0);
+    let id = path.into_inner();
+
+    if outbox::replay(&pool, id).await? {
+        let details = serde_json::json!({ "summary": format!("Replayed notification delivery {}", id) });
+        let _ = crate::audit::log(&pool, user_id, "outbox.replayed", "notification_outbox", id, details).await;
+        outbox::drain(&pool, &conn_map).await;
+        let _ = session.insert("flash", "Delivery requeued");
+    }
+    Ok(HttpResponse::SeeOther()
+        .insert_header(("Location", "/outbox"))
+        .finish())
+}
+
+/// POST /outbox/replay-failed — requeue every parked delivery.
+pub async fn replay_failed(
+    pool: web::Data<PgPool>,
+    session: Session,
+    form: web::Form<ReplayForm>,
+    conn_map: web::Data<ConnectionMap>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+    let user_id = get_user_id(&session).unwrap_or(0);
+
+    let count = outbox::replay_failed(&pool).await?;
+    if count > 0 {
+        let details = serde_json::json!({ "count": count, "summary": format!("Replayed {} failed notification deliveries", count) });
+        let _ = crate::audit::log(&pool, user_id, "outbox.replayed", "notification_outbox", 0, details).await;
+        outbox::drain(&pool, &conn_map).await;
+    }
+    let _ = session.insert("flash", format!("{} failed deliveries requeued", count));
+    Ok(HttpResponse::SeeOther()
+        .insert_header(("Location", "/outbox"))
+        .finish())
+}
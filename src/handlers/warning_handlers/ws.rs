@@ -7,8 +7,13 @@ use tokio::sync::mpsc;
 use sqlx::PgPool;
 
 use crate::auth::session::get_user_id;
+use crate::auth::timeout::SessionTracker;
+use crate::models::{nav_item, setting};
 use crate::warnings::queries;
 
+/// How often each socket checks whether its user's session is about to expire.
+const EXPIRY_CHECK_SECS: u64 = 15;
+
 pub type ConnectionMap = std::sync::Arc<RwLock<HashMap<i64, Vec<mpsc::UnboundedSender<String>>>>>;
 
 pub fn new_connection_map() -> ConnectionMap {
@@ -65,12 +70,55 @@ pub async fn send_count_update(conn_map: &ConnectionMap, pool: &PgPool, user_id:
     }
 }
 
+/// Send a user's current dashboard counters.
+pub async fn send_dashboard_counts(conn_map: &ConnectionMap, pool: &PgPool, user_id: i64) {
+    let counts = crate::models::dashboard::find_counts(pool, user_id).await;
+    let msg = serde_json::json!({
+        "type": "dashboard_counts",
+        "counts": counts,
+    });
+    let msg_str = msg.to_string();
+    let map = match conn_map.read() {
+        Ok(m) => m,
+        Err(_) => return,
+    };
+    if let Some(senders) = map.get(&user_id) {
+        for sender in senders {
+            let _ = sender.send(msg_str.clone());
+        }
+    }
+}
+
+/// Invalidate cached menus and permissions (see `nav_item::bump`) and tell
+/// every user connected to this replica to refresh their navigation; users
+/// on other replicas pick the new version up on their next request.
+pub async fn notify_nav_updated(pool: &PgPool, conn_map: &ConnectionMap) -> Result<(), sqlx::Error> {
+    let version = nav_item::bump(pool).await?;
+    let msg = serde_json::json!({
+        "type": "nav_updated",
+        "version": version,
+    });
+    let msg_str = msg.to_string();
+    let map = match conn_map.read() {
+        Ok(m) => m,
+        Err(_) => return Ok(()),
+    };
+    for senders in map.values() {
+        for sender in senders {
+            let _ = sender.send(msg_str.clone());
+        }
+    }
+    Ok(())
+}
+
 /// WebSocket upgrade handler.
 pub async fn ws_connect(
     req: HttpRequest,
     body: web::Payload,
     session: Session,
     conn_map: web::Data<ConnectionMap>,
+    tracker: web::Data<SessionTracker>,
+    pool: web::Data<PgPool>,
 ) -> Result<HttpResponse, actix_web::Error> {
     let user_id = match get_user_id(&session) {
         Some(id) => id,
@@ -90,10 +138,29 @@ pub async fn ws_connect(
     }
 
     let conn_map_clone = conn_map.into_inner().clone();
+    let tracker = tracker.into_inner();
+    let warn_secs = setting::get_i64(&pool, "session.expiry_warning_seconds").await;
 
     actix_web::rt::spawn(async move {
+        let mut expiry_tick = tokio::time::interval(std::time::Duration::from_secs(EXPIRY_CHECK_SECS));
+        let mut warned_for: Option<i64> = None;
         loop {
             tokio::select! {
+                _ = expiry_tick.tick() => {
+                    // Warn once per expiry time; activity pushes the expiry out and re-arms it
+                    let Some(expires_at) = tracker.expires_at(user_id) else { continue };
+                    let seconds_left = expires_at - chrono::Utc::now().timestamp();
+                    if seconds_left <= warn_secs && warned_for != Some(expires_at) {
+                        warned_for = Some(expires_at);
+                        let msg = serde_json::json!({
+                            "type": "session_expiring",
+                            "seconds_left": seconds_left.max(0),
+                        });
+                        if ws_session.text(msg.to_string()).await.is_err() {
+                            break;
+                        }
+                    }
+                }
                 Some(msg) = rx.recv() => {
                     if ws_session.text(msg).await.is_err() {
                         break;
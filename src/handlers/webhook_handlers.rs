@@ -0,0 +1,227 @@
+use actix_session::Session;
+use actix_web::{web, HttpRequest, HttpResponse};
+use serde::Deserialize;
+use sqlx::PgPool;
+
+use crate::models::{setting, tor, webhook};
+use crate::models::webhook::{Delivery, WebhookInput};
+use crate::auth::csrf;
+use crate::auth::session::{get_user_id, require_permission};
+use crate::errors::{AppError, render};
+use crate::handlers::auth_handlers::CsrfOnly;
+use crate::templates_structs::{PageContext, WebhooksTemplate};
+
+#[derive(Deserialize)]
+pub struct WebhookForm {
+    pub csrf_token: String,
+    pub slug: String,
+    pub label: String,
+    pub target: String,
+    pub tor_id: i64,
+    #[serde(default)]
+    pub title_field: String,
+    #[serde(default)]
+    pub description_field: String,
+    #[serde(default)]
+    pub rationale_field: String,
+    #[serde(default)]
+    pub external_id_field: String,
+}
+
+async fn render_list(pool: &PgPool, session: &Session, errors: Vec<String>) -> Result<HttpResponse, AppError> {
+    let ctx = PageContext::build(session, pool, "/webhooks").await?;
+    let webhooks = webhook::find_all(pool).await?;
+    let tors = tor::find_all_tors(pool).await?;
+    render(WebhooksTemplate { ctx, webhooks, tors, errors })
+}
+
+/// GET /webhooks — configured inbound webhooks.
+pub async fn list(
+    pool: web::Data<PgPool>,
+    session: Session,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    render_list(&pool, &session, Vec::new()).await
+}
+
+/// POST /webhooks — add an inbound webhook.
+pub async fn create(
+    pool: web::Data<PgPool>,
+    session: Session,
+    form: web::Form<WebhookForm>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+    let user_id = get_user_id(&session).unwrap_or(0);
+
+    let input = WebhookInput {
+        slug: form.slug.trim(),
+        label: &form.label,
+        target: &form.target,
+        tor_id: form.tor_id,
+        title_field: &form.title_field,
+        description_field: &form.description_field,
+        rationale_field: &form.rationale_field,
+        external_id_field: &form.external_id_field,
+    };
+    let mut errors = webhook::validate(&input);
+    if errors.is_empty() && webhook::find_by_slug(&pool, input.slug).await?.is_some() {
+        errors.push(format!("A webhook with slug '{}' already exists", input.slug));
+    }
+    if !errors.is_empty() {
+        return render_list(&pool, &session, errors).await;
+    }
+
+    let id = webhook::create(&pool, &input, user_id).await?;
+    let details = serde_json::json!({
+        "slug": input.slug,
+        "target": input.target,
+        "tor_id": input.tor_id,
+        "summary": format!("Created inbound webhook '{}'", input.slug)
+    });
+    let _ = crate::audit::log(&pool, user_id, "webhook.created", "webhook", id, details).await;
+
+    let _ = session.insert("flash", "Webhook created");
+    Ok(HttpResponse::SeeOther()
+        .insert_header(("Location", "/webhooks"))
+        .finish())
+}
+
+/// POST /webhooks/{id}/rotate — issue a new shared secret; the old one is
+/// accepted for the signing key grace period.
+pub async fn rotate_secret(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+    form: web::Form<CsrfOnly>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+    let id = path.into_inner();
+    let user_id = get_user_id(&session).unwrap_or(0);
+
+    let grace = setting::get_duration(&pool, "security.signing_key_grace_days").await;
+    let grace_days = grace.num_days();
+    webhook::rotate_secret(&pool, id, grace).await?;
+    let details = serde_json::json!({
+        "grace_days": grace_days,
+        "summary": format!("Rotated webhook secret; the old one is accepted for {} days", grace_days)
+    });
+    let _ = crate::audit::log(&pool, user_id, "webhook.secret_rotated", "webhook", id, details).await;
+
+    let _ = session.insert("flash", format!(
+        "Secret rotated — update the sending system within {} days", grace_days));
+    Ok(HttpResponse::SeeOther()
+        .insert_header(("Location", "/webhooks"))
+        .finish())
+}
+
+#[derive(Deserialize)]
+pub struct ToggleForm {
+    pub csrf_token: String,
+    pub active: bool,
+}
+
+/// POST /webhooks/{id}/toggle — pause or resume deliveries.
+pub async fn toggle(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+    form: web::Form<ToggleForm>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+    let id = path.into_inner();
+    let user_id = get_user_id(&session).unwrap_or(0);
+
+    if !webhook::set_active(&pool, id, form.active).await? {
+        return Err(AppError::NotFound);
+    }
+    let action = if form.active { "webhook.enabled" } else { "webhook.disabled" };
+    let details = serde_json::json!({
+        "summary": if form.active { "Enabled webhook" } else { "Disabled webhook" }
+    });
+    let _ = crate::audit::log(&pool, user_id, action, "webhook", id, details).await;
+
+    Ok(HttpResponse::SeeOther()
+        .insert_header(("Location", "/webhooks"))
+        .finish())
+}
+
+/// POST /webhooks/{id}/delete
+pub async fn delete(
+    pool: web::Data<PgPool>,
+    session: Session,
+    path: web::Path<i64>,
+    form: web::Form<CsrfOnly>,
+) -> Result<HttpResponse, AppError> {
+    require_permission(&session, "settings.manage")?;
+    csrf::validate_csrf(&session, &form.csrf_token)?;
+    let id = path.into_inner();
+    let user_id = get_user_id(&session).unwrap_or(0);
+
+    if webhook::delete(&pool, id).await? {
+        let details = serde_json::json!({ "summary": "Deleted webhook" });
+        let _ = crate::audit::log(&pool, user_id, "webhook.deleted", "webhook", id, details).await;
+        let _ = session.insert("flash", "Webhook deleted");
+    }
+    Ok(HttpResponse::SeeOther()
+        .insert_header(("Location", "/webhooks"))
+        .finish())
+}
+
+fn json_error(status: actix_web::http::StatusCode, message: &str) -> HttpResponse {
+    HttpResponse::build(status).json(serde_json::json!({ "error": message }))
+}
+
+/// POST /hooks/{slug} — public receiver, authenticated by HMAC signature.
+pub async fn receive(
+    pool: web::Data<PgPool>,
+    req: HttpRequest,
+    path: web::Path<String>,
+    body: web::Bytes,
+) -> Result<HttpResponse, AppError> {
+    use actix_web::http::StatusCode;
+
+    // Unknown, paused and badly signed hooks look the same to the caller
+    let hook = match webhook::find_by_slug(&pool, &path.into_inner()).await? {
+        Some(h) if h.is_active => h,
+        _ => return Ok(json_error(StatusCode::NOT_FOUND, "Unknown webhook")),
+    };
+    let signature = req.headers()
+        .get(webhook::SIGNATURE_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("");
+    if !hook.accepts(&body, signature, chrono::Utc::now()) {
+        log::warn!("Rejected webhook delivery for '{}': bad signature", hook.slug);
+        return Ok(json_error(StatusCode::NOT_FOUND, "Unknown webhook"));
+    }
+
+    let payload: serde_json::Value = match serde_json::from_slice(&body) {
+        Ok(v) => v,
+        Err(_) => return Ok(json_error(StatusCode::BAD_REQUEST, "Body must be JSON")),
+    };
+
+    match webhook::deliver(&pool, &hook, &payload).await? {
+        Delivery::Created { entity_type, id } => {
+            let details = serde_json::json!({
+                "webhook": hook.slug,
+                "tor_id": hook.tor_id,
+                "summary": format!("Webhook '{}' created {} #{}", hook.slug, entity_type, id)
+            });
+            let _ = crate::audit::log(
+                &pool, hook.submitted_by_id, "webhook.delivered", &entity_type, id, details,
+            ).await;
+            Ok(HttpResponse::Created().json(serde_json::json!({
+                "status": "created", "type": entity_type, "id": id
+            })))
+        }
+        Delivery::Duplicate { id } => Ok(HttpResponse::Ok().json(serde_json::json!({
+            "status": "duplicate", "id": id
+        }))),
+        Delivery::MissingField(field) => Ok(json_error(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            &format!("Payload is missing mapped field '{}'", field),
+        )),
+    }
+}
@@ -10,6 +10,49 @@ use crate::models::{entity, workflow};
 use crate::handlers::role_handlers::helpers::{parse_form_body, get_field};
 use crate::templates_structs::{PageContext, WorkflowBuilderListTemplate, WorkflowBuilderDetailTemplate};
 
+/// Longest auto-transition delay the builder accepts, in days.
+const MAX_AUTO_AFTER_DAYS: i64 = 3650;
+
+/// Check a transition's guard condition and auto-transition delay as sent by
+/// the builder. Returns the delay in days (0 for none), or a message saying
+/// what is wrong.
+async fn check_transition_rules(pool: &PgPool, condition: &str, auto_after_days: &str) -> Result<i64, String> {
+    let guards = workflow::guards::parse(condition)?;
+    let relation_types = entity::find_by_type(pool, "relation_type").await.map_err(|e| e.to_string())?;
+    for guard in &guards {
+        if let workflow::guards::Guard::Relation { relation_type, .. } = guard
+            && !relation_types.iter().any(|rt| rt.name == *relation_type)
+        {
+            return Err(format!("Unknown relation type '{}' in condition", relation_type));
+        }
+    }
+
+    let auto_after_days = auto_after_days.trim();
+    if auto_after_days.is_empty() {
+        return Ok(0);
+    }
+    match auto_after_days.parse::<i64>() {
+        Ok(days) if (0..=MAX_AUTO_AFTER_DAYS).contains(&days) => Ok(days),
+        _ => Err(format!("Auto-transition delay must be a whole number of days between 0 and {}", MAX_AUTO_AFTER_DAYS)),
+    }
+}
+
+/// Check a transition's hooks as sent by the builder. Returns them as
+/// compact JSON (empty for none), or a message saying what is wrong.
+async fn check_transition_hooks(pool: &PgPool, hooks: &str) -> Result<String, String> {
+    let hooks = workflow::hooks::parse(hooks)?;
+    if hooks.is_empty() {
+        return Ok(String::new());
+    }
+    let relation_types = entity::find_by_type(pool, "relation_type").await.map_err(|e| e.to_string())?;
+    for relation_type in workflow::hooks::relation_types(&hooks) {
+        if !relation_types.iter().any(|rt| rt.name == relation_type) {
+            return Err(format!("Unknown relation type '{}' in hooks", relation_type));
+        }
+    }
+    serde_json::to_string(&hooks).map_err(|e| e.to_string())
+}
+
 /// GET /workflow/builder
 pub async fn list(
     pool: web::Data<PgPool>,
@@ -163,7 +206,7 @@ pub async fn create_transition(
     let label = get_field(&params, "label");
     let required_permission = get_field(&params, "required_permission");
     let requires_outcome = get_field(&params, "requires_outcome") == "true";
-    let condition = get_field(&params, "condition");
+    let condition = get_field(&params, "condition").trim();
 
     if from_status_id == 0 || to_status_id == 0 || label.is_empty() {
         session.insert("flash", "From status, to status, and label are required.".to_string()).ok();
@@ -171,15 +214,28 @@ pub async fn create_transition(
             .insert_header(("Location", format!("/workflow/builder/{}", scope)))
             .finish());
     }
+    let rules = match check_transition_rules(&pool, condition, get_field(&params, "auto_after_days")).await {
+        Ok(days) => check_transition_hooks(&pool, get_field(&params, "hooks")).await.map(|hooks| (days, hooks)),
+        Err(msg) => Err(msg),
+    };
+    let (auto_after_days, hooks) = match rules {
+        Ok(rules) => rules,
+        Err(msg) => {
+            session.insert("flash", msg).ok();
+            return Ok(HttpResponse::SeeOther()
+                .insert_header(("Location", format!("/workflow/builder/{}", scope)))
+                .finish());
+        }
+    };
 
-    let id = workflow::create_transition(
-        &pool, &scope, from_status_id, to_status_id,
-        label, required_permission, requires_outcome, condition,
-    ).await?;
+    let input = workflow::TransitionInput {
+        label, required_permission, requires_outcome, condition, auto_after_days, hooks: &hooks,
+    };
+    let id = workflow::create_transition(&pool, &scope, from_status_id, to_status_id, &input).await?;
 
     let user_id = get_user_id(&session).unwrap_or(0);
     let details = serde_json::json!({
-        "scope": scope, "label": label,
+        "scope": scope, "label": label, "condition": condition, "auto_after_days": auto_after_days, "hooks": hooks,
         "summary": format!("Created workflow transition '{}'", label)
     });
     let _ = audit::log(&pool, user_id, "workflow_transition.create", "workflow_transition", id, details).await;
@@ -206,13 +262,36 @@ pub async fn update_transition(
     let label = get_field(&params, "label").to_string();
     let required_permission = get_field(&params, "required_permission").to_string();
     let requires_outcome = get_field(&params, "requires_outcome") == "true";
-    let condition = get_field(&params, "condition").to_string();
-
-    workflow::update_transition(&pool, transition_id, &label, &required_permission, requires_outcome, &condition).await?;
+    let condition = get_field(&params, "condition").trim().to_string();
+
+    let rules = match check_transition_rules(&pool, &condition, get_field(&params, "auto_after_days")).await {
+        Ok(days) => check_transition_hooks(&pool, get_field(&params, "hooks")).await.map(|hooks| (days, hooks)),
+        Err(msg) => Err(msg),
+    };
+    let (auto_after_days, hooks) = match rules {
+        Ok(rules) => rules,
+        Err(msg) => {
+            session.insert("flash", msg).ok();
+            return Ok(HttpResponse::SeeOther()
+                .insert_header(("Location", format!("/workflow/builder/{}", scope)))
+                .finish());
+        }
+    };
+
+    let input = workflow::TransitionInput {
+        label: &label,
+        required_permission: &required_permission,
+        requires_outcome,
+        condition: &condition,
+        auto_after_days,
+        hooks: &hooks,
+    };
+    workflow::update_transition(&pool, transition_id, &input).await?;
 
     let user_id = get_user_id(&session).unwrap_or(0);
     let details = serde_json::json!({
         "scope": scope, "transition_id": transition_id, "label": label,
+        "condition": condition, "auto_after_days": auto_after_days, "hooks": hooks,
         "summary": format!("Updated workflow transition '{}'", label)
     });
     let _ = audit::log(&pool, user_id, "workflow_transition.update", "workflow_transition", transition_id, details).await;
@@ -5,7 +5,7 @@ use sqlx::PgPool;
 
 use crate::auth::session::{require_permission, get_user_id, get_permissions};
 use crate::errors::{AppError, render};
-use crate::models::{tor, suggestion, proposal, agenda_point};
+use crate::models::{tor, delegation, suggestion, proposal, agenda_point, rejection_reason};
 use crate::templates_structs::{PageContext, WorkflowTemplate, WorkflowIndexTemplate};
 
 /// GET /tor/{tor_id}/workflow
@@ -20,7 +20,18 @@ pub async fn view(
 
     let tor_id = path.into_inner();
     let user_id = get_user_id(&session).ok_or(AppError::Session("User not logged in".to_string()))?;
-    tor::require_tor_membership(&pool, user_id, tor_id).await?;
+    // Delegates of absent members see the workflow to act for them
+    let today = chrono::Local::now().date_naive();
+    let principals = delegation::find_principals_in_tor(&pool, user_id, tor_id, today).await?;
+    if principals.is_empty() {
+        tor::require_tor_membership(&pool, user_id, tor_id).await?;
+    }
+    let mut delegated = Vec::new();
+    let mut acting_for = Vec::new();
+    for (principal, permissions) in principals {
+        delegated.extend(permissions.0);
+        acting_for.push(principal);
+    }
 
     let tor_name = tor::get_tor_name(&pool, tor_id).await?;
     let active_tab = query
@@ -29,8 +40,22 @@ pub async fn view(
         .unwrap_or_else(|| "suggestions".to_string());
 
     let suggestions = suggestion::find_all_for_tor(&pool, tor_id).await?;
+    let reviewer = get_permissions(&session).is_ok_and(|p| p.has("suggestion.review"));
+    let now = crate::warnings::clock::now();
+    let mut reopenable = Vec::new();
+    for s in suggestions.iter().filter(|s| s.status == "closed" && (reviewer || s.submitted_by_id == user_id)) {
+        if suggestion::can_reopen(&pool, s.id, now).await? {
+            reopenable.push(s.id);
+        }
+    }
+    let clusters = if reviewer {
+        suggestion::find_proposed_clusters(&pool, tor_id).await?
+    } else {
+        Vec::new()
+    };
     let proposals = proposal::find_all_for_tor(&pool, tor_id).await?;
     let agenda_points = agenda_point::find_all_for_tor(&pool, tor_id).await?;
+    let rejection_reasons = rejection_reason::find_active(&pool).await?;
 
     let ctx = PageContext::build(&session, &pool, "/workflow").await?
         .with_tor(tor_id, &tor_name, "workflow");
@@ -43,6 +68,11 @@ pub async fn view(
         suggestions,
         proposals,
         agenda_points,
+        rejection_reasons,
+        acting_for,
+        delegated,
+        reopenable,
+        clusters,
     };
     render(tmpl)
 }
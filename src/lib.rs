@@ -2,7 +2,10 @@ pub mod audit;
 pub mod auth;
 pub mod db;
 pub mod errors;
+pub mod export;
+pub mod graphql;
 pub mod handlers;
 pub mod models;
 pub mod templates_structs;
+pub mod ticketing;
 pub mod warnings;
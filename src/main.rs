@@ -1,7 +1,7 @@
 use actix_session::{SessionMiddleware, storage::CookieSessionStore};
-use actix_web::{App, HttpServer, cookie::Key, middleware, web};
+use actix_web::{App, HttpServer, guard, middleware, web};
 
-use ahlt::{audit, auth, db, handlers, warnings};
+use ahlt::{audit, auth, db, handlers, ticketing, warnings};
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
@@ -30,6 +30,16 @@ async fn main() -> std::io::Result<()> {
         _ => db::seed_ontology(&pool, &admin_hash).await,
     }
 
+    // Release notes for migrations applied since the last start
+    match ahlt::models::changelog::record_migrations(&pool, ahlt::models::changelog::MIGRATION_NOTES).await {
+        Ok(entries) if !entries.is_empty() => log::info!("Changelog: recorded {} migration(s)", entries.len()),
+        Ok(_) => {}
+        Err(e) => log::error!("Changelog: failed to record migrations: {}", e),
+    }
+
+    // Keep branding in memory; every page shows it
+    ahlt::models::branding::enable_cache();
+
     // Initialize Neo4j graph connection (optional — app works without it)
     let neo4j_graph = match std::env::var("NEO4J_URI") {
         Ok(uri) => {
@@ -46,21 +56,22 @@ async fn main() -> std::io::Result<()> {
     // Clean up old audit entries based on retention policy
     audit::cleanup_old_entries(&pool).await;
 
-    // Session encryption key — load from SESSION_KEY env var for persistent sessions across restarts
-    let secret_key = match std::env::var("SESSION_KEY") {
-        Ok(val) if val.len() >= 64 => {
-            log::info!("Using SESSION_KEY from environment");
-            Key::from(val.as_bytes())
-        }
-        Ok(val) => {
-            log::warn!("SESSION_KEY too short ({} bytes, need 64+) — generating random key", val.len());
-            Key::generate()
-        }
-        Err(_) => {
-            log::warn!("No SESSION_KEY set — generating random key (sessions lost on restart)");
-            Key::generate()
-        }
-    };
+    // Citation IDs for decisions recorded before citations existed
+    match ahlt::models::decision_citation::backfill(&pool).await {
+        Ok(n) if n > 0 => log::info!("Decision citations: assigned {} citation(s)", n),
+        Ok(_) => {}
+        Err(e) => log::error!("Decision citations: backfill failed: {}", e),
+    }
+
+    // Session encryption keys — SESSION_KEY seals cookies, SESSION_KEY_PREVIOUS
+    // keeps cookies from before a rotation valid
+    let session_keys = auth::session_keys::SessionKeys::from_env();
+    let secret_key = session_keys.current.clone();
+
+    // One active signing key per purpose (share tokens, API keys, webhooks)
+    if let Err(e) = ahlt::models::signing_key::ensure_all(&pool).await {
+        log::error!("Failed to initialise signing keys: {}", e);
+    }
 
     // Server binding configuration
     let host = std::env::var("HOST").unwrap_or_else(|_| "127.0.0.1".to_string());
@@ -83,12 +94,27 @@ async fn main() -> std::io::Result<()> {
     // Login rate limiter (per-IP, in-memory)
     let rate_limiter = auth::rate_limit::RateLimiter::new();
 
+    // Latest session expiry per user, for the WebSocket expiry warning
+    let session_tracker = auth::timeout::SessionTracker::new();
+
     // WebSocket connection map for real-time warning notifications
     let conn_map = handlers::warning_handlers::ws::new_connection_map();
 
     // Spawn background scheduler for warning generators and cleanup
     warnings::scheduler::spawn_scheduler(pool.clone(), conn_map.clone());
 
+    // Drain queued notification deliveries (and retry failed ones)
+    warnings::outbox::spawn_dispatcher(pool.clone(), conn_map.clone());
+
+    // Send queued e-mail over SMTP (nothing is queued while disabled)
+    warnings::notifications::email::spawn_sender(pool.clone());
+
+    // Send queued Web Push messages (nothing is queued while disabled)
+    warnings::notifications::push::spawn_sender(pool.clone());
+
+    // Poll ticketing system for action item status (no-op while disabled)
+    ticketing::spawn_sync(pool.clone());
+
     HttpServer::new(move || {
         let session_mw = SessionMiddleware::builder(
             CookieSessionStore::default(),
@@ -100,10 +126,13 @@ async fn main() -> std::io::Result<()> {
 
         App::new()
             .wrap(session_mw)
+            .wrap(middleware::from_fn(auth::session_keys::upgrade_cookie))
             .wrap(middleware::Logger::default())
             .app_data(web::Data::new(pool.clone()))
             .app_data(web::Data::new(conn_map.clone()))
             .app_data(web::Data::new(rate_limiter.clone()))
+            .app_data(web::Data::new(session_tracker.clone()))
+            .app_data(web::Data::new(session_keys.clone()))
             .app_data(web::Data::new(neo4j_graph.clone()))
             // Static files
             .service(actix_files::Files::new("/static", "./static"))
@@ -112,25 +141,65 @@ async fn main() -> std::io::Result<()> {
             // Public routes
             .route("/login", web::get().to(handlers::auth_handlers::login_page))
             .route("/login", web::post().to(handlers::auth_handlers::login_submit))
+            .route("/login/oidc", web::get().to(handlers::auth_handlers::login_oidc))
+            .route("/login/oidc/callback", web::get().to(handlers::auth_handlers::oidc_callback))
+            // Installable app: manifest, service worker and offline fallback
+            .route("/manifest.webmanifest", web::get().to(handlers::pwa_handlers::manifest))
+            .route("/sw.js", web::get().to(handlers::pwa_handlers::service_worker))
+            .route("/offline", web::get().to(handlers::pwa_handlers::offline))
+            // Inbound webhooks (HMAC-authenticated, no session)
+            .route("/hooks/{slug}", web::post().to(handlers::webhook_handlers::receive))
+            // Read-only shared views (share token, no session)
+            .route("/share/{token}", web::get().to(handlers::share_handlers::embed))
+            .route("/share/{token}/data", web::get().to(handlers::share_handlers::embed_data))
+            .route("/share/{token}/feed.{format}", web::get().to(handlers::share_handlers::embed_feed))
+            // Public Atom/RSS feeds for ToRs that enable feeds.public
+            .route("/feeds/tor/{id}/{kind}.{format}", web::get().to(handlers::feed_handlers::public_feed))
+            // Published branding logo, cached by the browser until it changes
+            .route("/branding/logo/{surface}", web::get().to(handlers::branding_handlers::logo))
+            // Public checksum verification of generated exports
+            .route("/verify/{sha256}", web::get().to(handlers::export_handlers::verify_public))
             // Root redirect
-            .route("/", web::get().to(|| async {
-                actix_web::HttpResponse::SeeOther()
-                    .insert_header(("Location", "/dashboard"))
-                    .finish()
-            }))
+            .route("/", web::get().to(handlers::auth_handlers::root_redirect))
             // Protected routes
             .service(
                 web::scope("")
+                    .wrap(actix_web::middleware::from_fn(auth::network::restrict_api))
+                    .wrap(actix_web::middleware::from_fn(handlers::form_draft_handlers::clear_on_submit))
                     .wrap(actix_web::middleware::from_fn(auth::middleware::require_auth))
+                    .wrap(actix_web::middleware::from_fn(auth::middleware::bearer_auth))
                     .route("/dashboard", web::get().to(handlers::dashboard::index))
                     .route("/logout", web::post().to(handlers::auth_handlers::logout))
+                    .route("/session/keepalive", web::get().to(handlers::auth_handlers::keepalive))
+                    // Snapshots of forms being edited (JSON)
+                    .route("/form-drafts", web::get().to(handlers::form_draft_handlers::show))
+                    .route("/form-drafts", web::post().to(handlers::form_draft_handlers::save))
+                    .route("/form-drafts/discard", web::post().to(handlers::form_draft_handlers::discard))
                     // Data Manager — import/export API + admin page
                     .route("/data-manager", web::get().to(handlers::data_handlers::data_manager_page))
+                    .route("/data-manager/encryption-key", web::post().to(handlers::data_handlers::generate_encryption_key))
+                    .route("/data-manager/spreadsheets", web::get().to(handlers::data_handlers::spreadsheet_page))
+                    .route("/data-manager/spreadsheets/{kind}/template", web::get().to(handlers::data_handlers::spreadsheet_template))
+                    .service(
+                        web::resource("/data-manager/spreadsheets/{kind}/import")
+                            .app_data(web::FormConfig::default().limit(4 * 1024 * 1024))
+                            .route(web::post().to(handlers::data_handlers::spreadsheet_import))
+                    )
                     .service(
                         web::scope("/api/data")
                             .app_data(web::JsonConfig::default().limit(50 * 1024 * 1024))
                             .route("/import", web::post().to(handlers::data_handlers::import_data))
                             .route("/export", web::get().to(handlers::data_handlers::export_data))
+                            .service(
+                                web::resource("/import-archive")
+                                    .app_data(web::PayloadConfig::new(512 * 1024 * 1024))
+                                    .route(web::post().to(handlers::data_handlers::import_archive))
+                            )
+                            .service(
+                                web::resource("/decrypt")
+                                    .app_data(web::PayloadConfig::new(50 * 1024 * 1024))
+                                    .route(web::post().to(handlers::data_handlers::decrypt_data))
+                            )
                             .route("/schema", web::get().to(handlers::data_handlers::schema))
                     )
                     // Documents CRUD — /documents/new BEFORE /documents/{id}
@@ -139,10 +208,24 @@ async fn main() -> std::io::Result<()> {
                     .route("/documents", web::post().to(handlers::document_handlers::create))
                     .route("/documents/{id}", web::get().to(handlers::document_handlers::detail))
                     .route("/documents/{id}/edit", web::get().to(handlers::document_handlers::edit_form))
+                    .route("/documents/{id}/preview", web::get().to(handlers::document_handlers::preview))
                     .route("/documents/{id}", web::post().to(handlers::document_handlers::update))
                     .route("/documents/{id}/delete", web::post().to(handlers::document_handlers::delete))
                     // API v1 — REST endpoints for external integrations
-                    .service(web::scope("/api/v1").configure(handlers::api_v1::configure))
+                    .service(
+                        web::scope("/api/v1")
+                            .wrap(actix_web::middleware::from_fn(handlers::api_v1::enforce_quota))
+                            .wrap(actix_web::middleware::from_fn(handlers::api_v1::track_usage))
+                            .configure(handlers::api_v1::configure)
+                    )
+                    // GraphQL — read-only queries over the entity graph
+                    .service(
+                        web::scope("/api/graphql")
+                            .wrap(actix_web::middleware::from_fn(handlers::api_v1::require_json_content_type))
+                            .wrap(actix_web::middleware::from_fn(handlers::api_v1::enforce_quota))
+                            .wrap(actix_web::middleware::from_fn(handlers::api_v1::track_usage))
+                            .route("", web::post().to(handlers::graphql_handlers::execute))
+                    )
                     // User CRUD — /users/new BEFORE /users/{id} to avoid routing conflict
                     .route("/users", web::get().to(handlers::user_handlers::list))
                     .route("/users/new", web::get().to(handlers::user_handlers::new_form))
@@ -152,12 +235,16 @@ async fn main() -> std::io::Result<()> {
                     .route("/users/{id}/edit", web::get().to(handlers::user_handlers::edit_form))
                     .route("/users/{id}", web::post().to(handlers::user_handlers::update))
                     .route("/users/{id}/delete", web::post().to(handlers::user_handlers::delete))
+                    .route("/users/{id}/unlock", web::post().to(handlers::user_handlers::unlock))
+                    .route("/users/{id}/reassign", web::get().to(handlers::user_handlers::reassign_form))
+                    .route("/users/{id}/reassign", web::post().to(handlers::user_handlers::reassign))
                     .route("/users/bulk-delete", web::post().to(handlers::user_handlers::bulk_delete))
                     // Role assignment
                     .route("/roles", web::get().to(handlers::role_handlers::list))
                     .route("/roles/assign", web::post().to(handlers::role_handlers::assignment::assign))
                     .route("/roles/unassign", web::post().to(handlers::role_handlers::assignment::unassign))
                     .route("/api/roles/preview", web::get().to(handlers::role_handlers::assignment::menu_preview))
+                    .route("/api/nav", web::get().to(handlers::nav_handlers::current))
                     // Role Builder — specific routes BEFORE parameterized /roles/{id}
                     .route("/roles/builder", web::get().to(handlers::role_builder_handlers::wizard_form))
                     .route("/roles/builder/preview", web::post().to(handlers::role_builder_handlers::preview_menu))
@@ -168,6 +255,13 @@ async fn main() -> std::io::Result<()> {
                     // Governance map — before parameterized /tor/{id} routes
                     .route("/governance/map", web::get().to(handlers::governance_handlers::governance_map))
                     .route("/api/governance/graph", web::get().to(handlers::governance_handlers::governance_graph_api))
+                    // Distribution groups derived from positions
+                    .route("/distribution-groups", web::get().to(handlers::governance_handlers::distribution_groups::list))
+                    .route("/distribution-groups", web::post().to(handlers::governance_handlers::distribution_groups::create))
+                    .route("/distribution-groups/{key}", web::get().to(handlers::governance_handlers::distribution_groups::detail))
+                    .route("/distribution-groups/{key}/export", web::get().to(handlers::governance_handlers::distribution_groups::export))
+                    .route("/distribution-groups/{key}/notify", web::post().to(handlers::governance_handlers::distribution_groups::notify))
+                    .route("/distribution-groups/{key}/delete", web::post().to(handlers::governance_handlers::distribution_groups::delete))
                     // Workflow builder — BEFORE /workflow to avoid path conflict
                     .route("/workflow/builder", web::get().to(handlers::workflow_builder_handlers::list))
                     .route("/workflow/builder/{scope}", web::get().to(handlers::workflow_builder_handlers::detail))
@@ -184,6 +278,7 @@ async fn main() -> std::io::Result<()> {
                     .route("/tor/new", web::get().to(handlers::tor_handlers::new_form))
                     .route("/tor/outlook", web::get().to(handlers::tor_handlers::outlook))
                     .route("/api/tor/calendar", web::get().to(handlers::tor_handlers::calendar_api))
+                    .route("/api/tor/{id}/availability", web::get().to(handlers::tor_handlers::attendance_api))
                     .route("/api/tor/{id}/meetings/confirm-calendar", web::post().to(handlers::meeting_handlers::confirm_calendar))
                     .route("/tor", web::post().to(handlers::tor_handlers::create))
                     .route("/tor/{id}", web::get().to(handlers::tor_handlers::detail))
@@ -192,13 +287,24 @@ async fn main() -> std::io::Result<()> {
                     .route("/tor/{id}/delete", web::post().to(handlers::tor_handlers::delete))
                     // ToR member management
                     .route("/tor/{id}/members", web::post().to(handlers::tor_handlers::manage_members))
+                    .route("/tor/{id}/delegations", web::post().to(handlers::tor_handlers::create_delegation))
+                    .route("/tor/{id}/delegations/{delegation_id}/revoke", web::post().to(handlers::tor_handlers::revoke_delegation))
+                    // Member availability
+                    .route("/tor/{id}/availability", web::get().to(handlers::tor_handlers::availability_page))
+                    .route("/tor/{id}/availability", web::post().to(handlers::tor_handlers::add_availability))
+                    .route("/tor/{id}/availability/{entry_id}/delete", web::post().to(handlers::tor_handlers::delete_availability))
+                    .route("/tor/{id}/attendance", web::get().to(handlers::tor_handlers::attendance_page))
                     // ToR protocol management
                     .route("/tor/{id}/protocol", web::post().to(handlers::tor_handlers::add_step))
                     .route("/tor/{id}/protocol/{step_id}/delete", web::post().to(handlers::tor_handlers::delete_step))
                     .route("/tor/{id}/protocol/{step_id}/move", web::post().to(handlers::tor_handlers::move_step))
+                    .route("/tor/{id}/standing-items", web::post().to(handlers::tor_handlers::add_standing_item))
+                    .route("/tor/{id}/standing-items/{item_id}/delete", web::post().to(handlers::tor_handlers::delete_standing_item))
                     // ToR dependency management
                     .route("/tor/{id}/dependencies", web::post().to(handlers::tor_handlers::handle_add_dependency))
                     .route("/tor/{id}/dependencies/{relation_id}/delete", web::post().to(handlers::tor_handlers::handle_remove_dependency))
+                    .route("/tor/{id}/impact", web::get().to(handlers::governance_handlers::tor_impact))
+                    .route("/api/tor/{id}/impact", web::get().to(handlers::governance_handlers::impact_graph_api))
                     // Presentation template management
                     .route("/tor/{id}/templates", web::get().to(handlers::tor_handlers::list_templates))
                     .route("/tor/{id}/templates", web::post().to(handlers::tor_handlers::create_template))
@@ -206,6 +312,30 @@ async fn main() -> std::io::Result<()> {
                     .route("/tor/{id}/templates/{template_id}/slides", web::post().to(handlers::tor_handlers::handle_add_slide))
                     .route("/tor/{id}/templates/{template_id}/slides/{slide_id}/delete", web::post().to(handlers::tor_handlers::handle_delete_slide))
                     .route("/tor/{id}/templates/{template_id}/slides/{slide_id}/move", web::post().to(handlers::tor_handlers::handle_move_slide))
+                    // Per-ToR glossary
+                    .route("/tor/{id}/glossary", web::get().to(handlers::glossary_handlers::tor_list))
+                    .route("/tor/{id}/glossary", web::post().to(handlers::glossary_handlers::tor_create))
+                    .route("/tor/{id}/glossary/{term_id}", web::post().to(handlers::glossary_handlers::tor_update))
+                    .route("/tor/{id}/glossary/{term_id}/delete", web::post().to(handlers::glossary_handlers::tor_delete))
+                    // Per-ToR settings overrides
+                    .route("/tor/{id}/settings", web::get().to(handlers::tor_handlers::settings_tab))
+                    .route("/tor/{id}/settings", web::post().to(handlers::tor_handlers::save_settings))
+                    .route("/tor/{id}/decision-authority", web::post().to(handlers::tor_handlers::save_decision_authority))
+                    .route("/tor/{id}/proposal-intake", web::post().to(handlers::tor_handlers::save_proposal_intake))
+                    .route("/tor/{id}/proposal-templates", web::post().to(handlers::tor_handlers::save_proposal_template))
+                    .route("/tor/{id}/proposal-templates/{key}/delete", web::post().to(handlers::tor_handlers::delete_proposal_template))
+                    .route("/tor/{id}/capabilities", web::post().to(handlers::tor_handlers::save_capabilities))
+                    // New term wizard
+                    .route("/tor/{id}/handover", web::get().to(handlers::tor_handlers::handover_package))
+                    .route("/tor/{id}/access-log/{entity_id}", web::get().to(handlers::tor_handlers::access_log_tab))
+                    .route("/tor/{id}/assessments", web::get().to(handlers::survey_handlers::assessments))
+                    .route("/tor/{id}/assessments", web::post().to(handlers::survey_handlers::start_assessment))
+                    .route("/tor/{id}/assessments/{assessment_id}", web::get().to(handlers::survey_handlers::assessment_detail))
+                    .route("/tor/{id}/assessments/{assessment_id}/respond", web::post().to(handlers::survey_handlers::respond))
+                    .route("/tor/{id}/assessments/{assessment_id}/close", web::post().to(handlers::survey_handlers::close_assessment))
+                    .route("/tor/{id}/new-term", web::get().to(handlers::tor_handlers::new_term_form))
+                    .route("/tor/{id}/new-term/review", web::post().to(handlers::tor_handlers::review_new_term))
+                    .route("/tor/{id}/new-term", web::post().to(handlers::tor_handlers::start_new_term))
                     // Workflow view
                     .route("/tor/{id}/workflow", web::get().to(handlers::workflow_handlers::view))
                     // Suggestion workflow
@@ -213,6 +343,9 @@ async fn main() -> std::io::Result<()> {
                     .route("/tor/{id}/suggestions", web::post().to(handlers::suggestion_handlers::create))
                     .route("/tor/{id}/suggestions/{suggestion_id}/accept", web::post().to(handlers::suggestion_handlers::accept))
                     .route("/tor/{id}/suggestions/{suggestion_id}/reject", web::post().to(handlers::suggestion_handlers::reject))
+                    .route("/tor/{id}/suggestions/{suggestion_id}/reopen", web::post().to(handlers::suggestion_handlers::reopen))
+                    .route("/tor/{id}/suggestion-clusters/{cluster_id}/convert", web::post().to(handlers::suggestion_handlers::convert_cluster))
+                    .route("/tor/{id}/suggestion-clusters/{cluster_id}/dismiss", web::post().to(handlers::suggestion_handlers::dismiss_cluster))
                     // Proposal workflow
                     .route("/tor/{id}/proposals/new", web::get().to(handlers::proposal_handlers::new_form))
                     .route("/tor/{id}/proposals", web::post().to(handlers::proposal_handlers::create))
@@ -220,9 +353,22 @@ async fn main() -> std::io::Result<()> {
                     .route("/tor/{id}/proposals/{proposal_id}/edit", web::get().to(handlers::proposal_handlers::edit_form))
                     .route("/tor/{id}/proposals/{proposal_id}", web::post().to(handlers::proposal_handlers::update))
                     .route("/tor/{id}/proposals/{proposal_id}/submit", web::post().to(handlers::proposal_handlers::submit))
+                    .route("/tor/{id}/proposals/{proposal_id}/watch", web::post().to(handlers::proposal_handlers::watch))
+                    .route("/tor/{id}/proposals/{proposal_id}/revisions/{revision_id}/restore", web::post().to(handlers::proposal_handlers::restore_revision))
+                    // Multipart posts upload a file; plain forms attach a library document
+                    .service(
+                        web::resource("/tor/{id}/proposals/{proposal_id}/attachments")
+                            .app_data(web::PayloadConfig::new(handlers::proposal_handlers::UPLOAD_PAYLOAD_LIMIT))
+                            .route(web::post().guard(guard::fn_guard(handlers::proposal_handlers::is_multipart_request)).to(handlers::proposal_handlers::upload_attachment))
+                            .route(web::post().to(handlers::proposal_handlers::attach_document))
+                    )
+                    .route("/tor/{id}/proposals/{proposal_id}/attachments/{attachment_id}", web::get().to(handlers::proposal_handlers::download_attachment))
+                    .route("/tor/{id}/proposals/{proposal_id}/attachments/{attachment_id}/delete", web::post().to(handlers::proposal_handlers::delete_attachment))
+                    .route("/tor/{id}/proposals/{proposal_id}/attachments/{document_id}/remove", web::post().to(handlers::proposal_handlers::detach_document))
                     .route("/tor/{id}/proposals/{proposal_id}/review", web::post().to(handlers::proposal_handlers::review))
                     .route("/tor/{id}/proposals/{proposal_id}/approve", web::post().to(handlers::proposal_handlers::approve))
                     .route("/tor/{id}/proposals/{proposal_id}/reject", web::post().to(handlers::proposal_handlers::reject))
+                    .route("/tor/{id}/proposals/{proposal_id}/impact", web::get().to(handlers::governance_handlers::proposal_impact))
                     // Workflow queue
                     .route("/tor/{id}/workflow/queue", web::get().to(handlers::queue_handlers::view_queue))
                     .route("/tor/{id}/workflow/queue/schedule-form", web::get().to(handlers::queue_handlers::schedule_form))
@@ -243,18 +389,37 @@ async fn main() -> std::io::Result<()> {
                     .route("/tor/{id}/workflow/agenda/{agenda_id}/coa/{coa_id}/edit", web::get().to(handlers::coa_handlers::edit_form))
                     .route("/tor/{id}/workflow/agenda/{agenda_id}/coa/{coa_id}", web::post().to(handlers::coa_handlers::update))
                     .route("/tor/{id}/workflow/agenda/{agenda_id}/coa/{coa_id}/delete", web::post().to(handlers::coa_handlers::delete))
+                    .route("/tor/{id}/workflow/agenda/{agenda_id}/coa/{coa_id}/revisions/{revision_id}/restore", web::post().to(handlers::coa_handlers::restore_revision))
                     .route("/tor/{id}/workflow/agenda/{agenda_id}/coa/{coa_id}/sections", web::post().to(handlers::coa_handlers::add_section))
                     .route("/tor/{id}/workflow/agenda/{agenda_id}/coa/{coa_id}/sections/{section_id}", web::post().to(handlers::coa_handlers::update_section))
                     .route("/tor/{id}/workflow/agenda/{agenda_id}/coa/{coa_id}/sections/{section_id}/delete", web::post().to(handlers::coa_handlers::delete_section))
                     // Opinions + Decisions
                     .route("/tor/{id}/workflow/agenda/{agenda_id}/input", web::get().to(handlers::opinion_handlers::form))
                     .route("/tor/{id}/workflow/agenda/{agenda_id}/input", web::post().to(handlers::opinion_handlers::submit))
+                    .route("/tor/{id}/workflow/agenda/{agenda_id}/opinions/{opinion_id}/react", web::post().to(handlers::opinion_handlers::react))
+                    .route("/tor/{id}/workflow/agenda/{agenda_id}/vote", web::get().to(handlers::ballot_handlers::view))
+                    .route("/tor/{id}/workflow/agenda/{agenda_id}/vote", web::post().to(handlers::ballot_handlers::cast))
+                    .route("/tor/{id}/workflow/agenda/{agenda_id}/vote/open", web::post().to(handlers::ballot_handlers::open))
+                    .route("/tor/{id}/workflow/agenda/{agenda_id}/vote/close", web::post().to(handlers::ballot_handlers::close))
                     .route("/tor/{id}/workflow/agenda/{agenda_id}/decide", web::get().to(handlers::opinion_handlers::decision_form))
                     .route("/tor/{id}/workflow/agenda/{agenda_id}/decide", web::post().to(handlers::opinion_handlers::record_decision))
+                    .route("/tor/{id}/decisions", web::get().to(handlers::opinion_handlers::decision_register))
+                    // Decision citations: resolver and content-addressed permalink
+                    .route("/cite/{reference}", web::get().to(handlers::opinion_handlers::resolve_citation))
+                    .route("/decisions/sha256/{hash}", web::get().to(handlers::opinion_handlers::resolve_citation))
+                    // Action items arising from decisions
+                    .route("/actions", web::get().to(handlers::action_item_handlers::mine))
+                    .route("/tor/{id}/actions", web::get().to(handlers::action_item_handlers::list))
+                    .route("/tor/{id}/actions", web::post().to(handlers::action_item_handlers::create))
+                    .route("/tor/{id}/actions/{action_id}", web::get().to(handlers::action_item_handlers::edit))
+                    .route("/tor/{id}/actions/{action_id}", web::post().to(handlers::action_item_handlers::update))
+                    .route("/tor/{id}/actions/{action_id}/status", web::post().to(handlers::action_item_handlers::set_status))
+                    .route("/tor/{id}/actions/{action_id}/delete", web::post().to(handlers::action_item_handlers::delete))
                     // Minutes management
                     .route("/minutes/generate", web::post().to(handlers::minutes_handlers::generate_minutes))
                     .route("/minutes/{id}", web::get().to(handlers::minutes_handlers::view_minutes))
                     .route("/minutes/{id}/sections/{section_id}", web::post().to(handlers::minutes_handlers::update_section))
+                    .route("/minutes/{id}/sections/{section_id}/revisions/{revision_id}/restore", web::post().to(handlers::minutes_handlers::restore_section_revision))
                     .route("/minutes/{id}/status", web::post().to(handlers::minutes_handlers::update_minutes_status))
                     .route("/minutes/{id}/distribution", web::post().to(handlers::minutes_handlers::save_distribution))
                     .route("/minutes/{id}/attendance", web::post().to(handlers::minutes_handlers::save_attendance))
@@ -264,29 +429,176 @@ async fn main() -> std::io::Result<()> {
                     .route("/tor/{id}/meetings/confirm", web::post().to(handlers::meeting_handlers::confirm))
                     .route("/tor/{id}/meetings", web::get().to(handlers::meeting_handlers::list_for_tor))
                     .route("/tor/{id}/meetings/{mid}", web::get().to(handlers::meeting_handlers::detail))
+                    .route("/tor/{id}/meetings/{mid}/pack", web::get().to(handlers::meeting_handlers::export_pack))
+                    .route("/tor/{id}/meetings/{mid}/agenda.docx", web::get().to(handlers::meeting_handlers::export_agenda_docx))
+                    .route("/tor/{id}/meetings/{mid}/offline", web::get().to(handlers::meeting_handlers::export_offline))
                     .route("/tor/{id}/meetings/{mid}/transition", web::post().to(handlers::meeting_handlers::transition))
                     .route("/tor/{id}/meetings/{mid}/agenda/assign", web::post().to(handlers::meeting_handlers::assign_agenda))
                     .route("/tor/{id}/meetings/{mid}/agenda/remove", web::post().to(handlers::meeting_handlers::remove_agenda))
+                    .route("/tor/{id}/meetings/{mid}/carry-over", web::post().to(handlers::meeting_handlers::carry_over))
                     .route("/tor/{id}/meetings/{mid}/minutes/generate", web::post().to(handlers::meeting_handlers::generate_minutes))
                     .route("/tor/{id}/meetings/{mid}/roll-call", web::post().to(handlers::meeting_handlers::save_roll_call))
                     .route("/meetings/{id}/export", web::get().to(handlers::meeting_handlers::export_minutes_html))
+                    .route("/meetings/{id}/export.docx", web::get().to(handlers::meeting_handlers::export_minutes_docx))
+                    .route("/meetings/{id}/export.pdf", web::get().to(handlers::meeting_handlers::export_minutes_pdf))
+                    // Notification center — /notifications/read-all before /notifications/{id}
+                    .route("/notifications", web::get().to(handlers::notification_handlers::list))
+                    .route("/notifications/read-all", web::post().to(handlers::notification_handlers::mark_all_read))
+                    .route("/notifications/{id}", web::get().to(handlers::notification_handlers::open))
+                    .route("/notifications/{id}/read", web::post().to(handlers::notification_handlers::mark_read))
                     // Warnings — /warnings before /warnings/{id}
                     .route("/warnings", web::get().to(handlers::warning_handlers::list::list))
                     .route("/warnings/{id}", web::get().to(handlers::warning_handlers::detail::detail))
                     .route("/warnings/{id}/delete", web::post().to(handlers::warning_handlers::actions::mark_deleted))
                     .route("/warnings/{id}/forward", web::post().to(handlers::warning_handlers::actions::forward))
+                    .route("/warnings/{id}/action-item", web::post().to(handlers::warning_handlers::actions::convert_to_action_item))
+                    // Notification outbox — /outbox/replay-failed before /outbox/{id}
+                    .route("/outbox", web::get().to(handlers::warning_handlers::outbox::list))
+                    .route("/outbox/replay-failed", web::post().to(handlers::warning_handlers::outbox::replay_failed))
+                    .route("/outbox/{id}/replay", web::post().to(handlers::warning_handlers::outbox::replay))
+                    // Trash — soft-deleted entities
+                    .route("/admin/trash", web::get().to(handlers::trash_handlers::list))
+                    .route("/admin/trash/{id}/restore", web::post().to(handlers::trash_handlers::restore))
+                    .route("/admin/trash/{id}/purge", web::post().to(handlers::trash_handlers::purge))
+                    // Broadcast notices
+                    .route("/broadcasts", web::get().to(handlers::warning_handlers::broadcast::list))
+                    .route("/broadcasts", web::post().to(handlers::warning_handlers::broadcast::create))
+                    .route("/broadcasts/{id}/cancel", web::post().to(handlers::warning_handlers::broadcast::cancel))
+                    .route("/quotas", web::get().to(handlers::quota_handlers::report))
+                    .route("/quotas/roles/{id}", web::post().to(handlers::quota_handlers::update_role))
+                    .route("/sla-packs", web::get().to(handlers::sla_pack_handlers::list))
+                    .route("/sla-packs", web::post().to(handlers::sla_pack_handlers::create))
+                    .route("/sla-packs/assign", web::post().to(handlers::sla_pack_handlers::assign))
+                    .route("/sla-packs/recompute", web::post().to(handlers::sla_pack_handlers::recompute))
+                    .route("/sla-packs/{id}", web::post().to(handlers::sla_pack_handlers::update))
+                    .route("/sla-packs/{id}/delete", web::post().to(handlers::sla_pack_handlers::delete))
+                    // Rejection reason taxonomy
+                    .route("/rejection-reasons", web::get().to(handlers::rejection_reason_handlers::list))
+                    .route("/rejection-reasons", web::post().to(handlers::rejection_reason_handlers::create))
+                    .route("/rejection-reasons/{id}", web::post().to(handlers::rejection_reason_handlers::update))
+                    .route("/rejection-reasons/{id}/toggle", web::post().to(handlers::rejection_reason_handlers::toggle))
+                    .route("/rejection-reasons/{id}/delete", web::post().to(handlers::rejection_reason_handlers::delete))
+                    .route("/glossary", web::get().to(handlers::glossary_handlers::list))
+                    .route("/glossary", web::post().to(handlers::glossary_handlers::create))
+                    .route("/glossary/{id}", web::post().to(handlers::glossary_handlers::update))
+                    .route("/glossary/{id}/delete", web::post().to(handlers::glossary_handlers::delete))
+                    .route("/default-coas", web::get().to(handlers::default_coa_handlers::list))
+                    .route("/default-coas", web::post().to(handlers::default_coa_handlers::create))
+                    .route("/default-coas/{id}", web::post().to(handlers::default_coa_handlers::update))
+                    .route("/default-coas/{id}/delete", web::post().to(handlers::default_coa_handlers::delete))
                     // Account
                     .route("/account", web::get().to(handlers::account_handlers::form))
                     .route("/account", web::post().to(handlers::account_handlers::submit))
                     .route("/account/profile", web::post().to(handlers::account_handlers::update_profile))
+                    .route("/account/email-preferences", web::post().to(handlers::account_handlers::set_email_preferences))
+                    .route("/account/sso/link", web::post().to(handlers::account_handlers::link_sso))
+                    .route("/account/sso/unlink", web::post().to(handlers::account_handlers::unlink_sso))
+                    .route("/account/push/subscribe", web::post().to(handlers::pwa_handlers::subscribe))
+                    .route("/account/push/unsubscribe", web::post().to(handlers::pwa_handlers::unsubscribe))
+                    .route("/account/out-of-office", web::post().to(handlers::account_handlers::set_out_of_office))
+                    .route("/account/out-of-office/clear", web::post().to(handlers::account_handlers::clear_out_of_office))
+                    .route("/account/sessions/revoke-all", web::post().to(handlers::account_handlers::revoke_all_devices))
+                    .route("/account/sessions/{id}/revoke", web::post().to(handlers::account_handlers::revoke_device))
+                    .route("/account/sign-ins/{id}/not-me", web::post().to(handlers::account_handlers::report_sign_in))
+                    .route("/account/api-tokens", web::post().to(handlers::account_handlers::create_api_token))
+                    .route("/account/api-tokens/{id}/revoke", web::post().to(handlers::account_handlers::revoke_api_token))
+                    // Report subscriptions
+                    .route("/reports/subscriptions", web::get().to(handlers::report_handlers::list))
+                    .route("/reports/subscriptions", web::post().to(handlers::report_handlers::create))
+                    .route("/reports/subscriptions/{id}/toggle", web::post().to(handlers::report_handlers::toggle))
+                    .route("/reports/subscriptions/{id}/delete", web::post().to(handlers::report_handlers::delete))
+                    .route("/transfers", web::get().to(handlers::transfer_handlers::list))
+                    .route("/transfers/new/{item_id}", web::get().to(handlers::transfer_handlers::new_form))
+                    .route("/transfers/new/{item_id}", web::post().to(handlers::transfer_handlers::create))
+                    .route("/transfers/{id}/accept", web::post().to(handlers::transfer_handlers::accept))
+                    .route("/transfers/{id}/decline", web::post().to(handlers::transfer_handlers::decline))
+                    .route("/transfers/{id}/cancel", web::post().to(handlers::transfer_handlers::cancel))
+                    .route("/reports/deliveries/{id}", web::get().to(handlers::report_handlers::download))
                     // Settings
                     .route("/settings", web::get().to(handlers::settings_handlers::list))
                     .route("/settings", web::post().to(handlers::settings_handlers::save))
+                    .route("/settings/history/{change_id}/rollback", web::post().to(handlers::settings_handlers::rollback))
+                    // Release notes
+                    .route("/changelog", web::get().to(handlers::changelog_handlers::list))
+                    .route("/changelog/seen", web::post().to(handlers::changelog_handlers::mark_seen))
+                    .route("/surveys", web::get().to(handlers::survey_handlers::templates))
+                    .route("/surveys", web::post().to(handlers::survey_handlers::create_template))
+                    .route("/surveys/{id}/delete", web::post().to(handlers::survey_handlers::delete_template))
+                    // Outgoing email texts
+                    .route("/email-templates", web::get().to(handlers::email_template_handlers::list))
+                    .route("/email-templates/{kind}", web::get().to(handlers::email_template_handlers::edit))
+                    .route("/email-templates/{kind}", web::post().to(handlers::email_template_handlers::save))
+                    .route("/email-templates/{kind}/reset", web::post().to(handlers::email_template_handlers::reset))
+                    .service(
+                        web::resource("/settings/branding")
+                            .app_data(web::FormConfig::default().limit(2 * 1024 * 1024))
+                            .route(web::get().to(handlers::settings_handlers::branding_page))
+                            .route(web::post().to(handlers::settings_handlers::save_branding))
+                    )
+                    .route("/settings/branding/publish", web::post().to(handlers::settings_handlers::publish_branding))
+                    .route("/settings/branding/discard", web::post().to(handlers::settings_handlers::discard_branding))
+                    .route("/settings/branding/preview/{surface}", web::get().to(handlers::settings_handlers::preview_branding))
                     // Menu Builder
                     .route("/menu-builder", web::get().to(handlers::menu_builder_handlers::index))
                     .route("/menu-builder", web::post().to(handlers::menu_builder_handlers::save))
+                    // API usage
+                    .route("/api-usage", web::get().to(handlers::api_usage_handlers::list))
+                    // Inbound webhooks
+                    .route("/webhooks", web::get().to(handlers::webhook_handlers::list))
+                    .route("/webhooks", web::post().to(handlers::webhook_handlers::create))
+                    .route("/webhooks/{id}/rotate", web::post().to(handlers::webhook_handlers::rotate_secret))
+                    .route("/webhooks/{id}/toggle", web::post().to(handlers::webhook_handlers::toggle))
+                    .route("/webhooks/{id}/delete", web::post().to(handlers::webhook_handlers::delete))
+                    // Outbound webhooks — /outbound-webhooks/deliveries before /outbound-webhooks/{id}
+                    .route("/outbound-webhooks", web::get().to(handlers::outbound_webhook_handlers::list))
+                    .route("/outbound-webhooks", web::post().to(handlers::outbound_webhook_handlers::create))
+                    .route("/outbound-webhooks/deliveries", web::get().to(handlers::outbound_webhook_handlers::deliveries))
+                    .route("/outbound-webhooks/deliveries/{id}/retry", web::post().to(handlers::outbound_webhook_handlers::retry))
+                    .route("/outbound-webhooks/{id}/secret", web::post().to(handlers::outbound_webhook_handlers::regenerate_secret))
+                    .route("/outbound-webhooks/{id}/toggle", web::post().to(handlers::outbound_webhook_handlers::toggle))
+                    .route("/outbound-webhooks/{id}/delete", web::post().to(handlers::outbound_webhook_handlers::delete))
+                    // Share tokens for embedded views
+                    .route("/share-tokens", web::get().to(handlers::share_handlers::list))
+                    .route("/share-tokens", web::post().to(handlers::share_handlers::create))
+                    .route("/share-tokens/{id}/toggle", web::post().to(handlers::share_handlers::toggle))
+                    .route("/share-tokens/{id}/delete", web::post().to(handlers::share_handlers::delete))
+                    // Signing key registry
+                    // Scheduler jobs
+                    .route("/scheduler", web::get().to(handlers::scheduler_handlers::list))
+                    .route("/scheduler/{key}/pause", web::post().to(handlers::scheduler_handlers::pause))
+                    .route("/scheduler/{key}/resume", web::post().to(handlers::scheduler_handlers::resume))
+                    .route("/scheduler/{key}/run", web::post().to(handlers::scheduler_handlers::run_now))
+                    .route("/signing-keys", web::get().to(handlers::signing_key_handlers::list))
+                    .route("/signing-keys/rotate", web::post().to(handlers::signing_key_handlers::rotate))
+                    .route("/signing-keys/{id}/retire", web::post().to(handlers::signing_key_handlers::retire))
+                    .route("/report-subscriptions", web::get().to(handlers::report_handlers::admin_list))
+                    .route("/report-subscriptions/{id}/suspend", web::post().to(handlers::report_handlers::admin_suspend))
+                    .route("/report-definitions", web::get().to(handlers::report_definition_handlers::list))
+                    .route("/report-definitions", web::post().to(handlers::report_definition_handlers::create))
+                    .route("/report-definitions/{id}", web::get().to(handlers::report_definition_handlers::edit))
+                    .route("/report-definitions/{id}", web::post().to(handlers::report_definition_handlers::update))
+                    .route("/report-definitions/{id}/preview", web::get().to(handlers::report_definition_handlers::preview))
+                    .route("/report-definitions/{id}/toggle", web::post().to(handlers::report_definition_handlers::toggle))
+                    .route("/report-definitions/{id}/delete", web::post().to(handlers::report_definition_handlers::delete))
                     // Audit log
                     .route("/audit", web::get().to(handlers::audit_handlers::list))
+                    .route("/audit/export", web::get().to(handlers::audit_handlers::export))
+                    .route("/audit/verify", web::get().to(handlers::audit_handlers::verify))
+                    .route("/audit/archive", web::get().to(handlers::export_handlers::audit_archive))
+                    .route("/exports", web::get().to(handlers::export_handlers::list))
+                    .route("/exports/verify", web::post().to(handlers::export_handlers::verify))
+                    .route("/exports/{id}/manifest", web::get().to(handlers::export_handlers::manifest))
+                    .route("/access-reviews", web::get().to(handlers::access_review_handlers::list))
+                    .route("/access-reviews", web::post().to(handlers::access_review_handlers::generate))
+                    .route("/access-reviews/{id}", web::get().to(handlers::access_review_handlers::detail))
+                    .route("/access-reviews/{id}/export", web::get().to(handlers::access_review_handlers::export))
+                    .route("/access-reviews/{id}/sign-off", web::post().to(handlers::access_review_handlers::sign_off))
+                    .route("/recertifications", web::get().to(handlers::recertification_handlers::list))
+                    .route("/recertifications", web::post().to(handlers::recertification_handlers::launch))
+                    .route("/recertifications/tasks", web::get().to(handlers::recertification_handlers::tasks))
+                    .route("/recertifications/items/{id}", web::post().to(handlers::recertification_handlers::decide))
+                    .route("/recertifications/{id}", web::get().to(handlers::recertification_handlers::detail))
+                    .route("/recertifications/{id}/close", web::post().to(handlers::recertification_handlers::close))
                     // Ontology explorer — Concepts (schema graph) is the landing page
                     .route("/ontology", web::get().to(handlers::ontology_handlers::graph))
                     .route("/ontology/data", web::get().to(handlers::ontology_handlers::data))
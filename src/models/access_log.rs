@@ -0,0 +1,126 @@
+//! Read access log for classified ToRs.
+//!
+//! While read auditing is on (`audit.read_mode`), opening the overview of a
+//! ToR that has a classification, or one of its meetings or proposals,
+//! records who viewed it and when. Reloads by the same user within a few
+//! minutes are recorded once. The ToR's chairs and users with `audit.view`
+//! see an item's log in its Access log tab.
+
+use serde::Serialize;
+use sqlx::PgPool;
+
+use crate::models::distribution_group::{self, GroupRule};
+use crate::models::setting;
+
+/// Views of the same item by the same user within this window count once.
+pub const REPEAT_WINDOW_SECS: i64 = 600;
+/// Most entries shown for an item.
+pub const LIMIT: i64 = 500;
+/// Position text that marks a ToR's chair, as in the built-in `tor_chairs` group.
+const CHAIR_POSITION: &str = "chair";
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct AccessLogEntry {
+    pub user_id: i64,
+    pub username: String,
+    /// Current display name; empty when the account was deleted.
+    pub user_label: String,
+    pub viewed_at: String,
+}
+
+/// Whether the ToR carries a classification.
+pub async fn is_classified(pool: &PgPool, tor_id: i64) -> Result<bool, sqlx::Error> {
+    let row: Option<(String,)> = sqlx::query_as(
+        "SELECT value FROM entity_properties WHERE entity_id = $1 AND key = 'classification'",
+    )
+    .bind(tor_id)
+    .fetch_optional(pool)
+    .await?;
+    Ok(row.is_some_and(|(value,)| !value.trim().is_empty()))
+}
+
+/// Whether the user currently holds a chair position in the ToR.
+pub async fn is_chair(pool: &PgPool, user_id: i64, tor_id: i64) -> Result<bool, sqlx::Error> {
+    let rule = GroupRule {
+        tor_id: Some(tor_id),
+        position_match: CHAIR_POSITION.to_string(),
+        membership_type: String::new(),
+    };
+    Ok(distribution_group::find_members(pool, &rule).await?
+        .iter()
+        .any(|m| m.user_id == user_id))
+}
+
+/// Whether the user may read the ToR's access logs: auditors (`can_audit`,
+/// i.e. `audit.view`) and the ToR's chairs.
+pub async fn can_view(pool: &PgPool, user_id: i64, can_audit: bool, tor_id: i64) -> Result<bool, sqlx::Error> {
+    Ok(can_audit || is_chair(pool, user_id, tor_id).await?)
+}
+
+/// Record a view, unless the user already viewed the item within
+/// [`REPEAT_WINDOW_SECS`]. Returns whether a row was added.
+pub async fn record(pool: &PgPool, entity_id: i64, tor_id: i64, user_id: i64, username: &str) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "INSERT INTO access_log (entity_id, tor_id, user_id, username) \
+         SELECT $1, $2, $3, $4 \
+         WHERE NOT EXISTS ( \
+             SELECT 1 FROM access_log \
+             WHERE entity_id = $1 AND user_id = $3 \
+               AND viewed_at > NOW() - make_interval(secs => $5))",
+    )
+    .bind(entity_id)
+    .bind(tor_id)
+    .bind(user_id)
+    .bind(username)
+    .bind(REPEAT_WINDOW_SECS as f64)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Called by detail pages: record the view when read auditing is on and the
+/// ToR is classified. Returns whether the viewer may open the item's access
+/// log, i.e. whether the page shows the Access log tab.
+pub async fn track(
+    pool: &PgPool,
+    entity_id: i64,
+    tor_id: i64,
+    user_id: i64,
+    username: &str,
+    can_audit: bool,
+) -> Result<bool, sqlx::Error> {
+    if !is_classified(pool, tor_id).await? {
+        return Ok(false);
+    }
+    if setting::get_bool(pool, "audit.read_mode").await {
+        record(pool, entity_id, tor_id, user_id, username).await?;
+    }
+    can_view(pool, user_id, can_audit, tor_id).await
+}
+
+/// Views of an item recorded under a ToR, newest first.
+pub async fn find_for_entity(pool: &PgPool, tor_id: i64, entity_id: i64) -> Result<Vec<AccessLogEntry>, sqlx::Error> {
+    sqlx::query_as::<_, AccessLogEntry>(
+        "SELECT a.user_id, a.username, COALESCE(u.label, '') AS user_label, \
+                to_char(a.viewed_at, 'YYYY-MM-DD HH24:MI:SS') AS viewed_at \
+         FROM access_log a \
+         LEFT JOIN entities u ON u.id = a.user_id AND u.entity_type = 'user' \
+         WHERE a.tor_id = $1 AND a.entity_id = $2 \
+         ORDER BY a.viewed_at DESC, a.id DESC \
+         LIMIT $3",
+    )
+    .bind(tor_id)
+    .bind(entity_id)
+    .bind(LIMIT)
+    .fetch_all(pool)
+    .await
+}
+
+/// Delete entries older than `before`. Returns how many were removed.
+pub async fn cleanup(pool: &PgPool, before: chrono::DateTime<chrono::Utc>) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM access_log WHERE viewed_at < $1::TIMESTAMPTZ")
+        .bind(before.to_rfc3339())
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected())
+}
@@ -0,0 +1,223 @@
+//! Access reviews: periodic sign-off of who can do what.
+//!
+//! Generating a review snapshots every user with their roles, effective
+//! permissions, ToR positions and the capabilities those positions carry.
+//! The snapshot is stored on an `access_review` entity as JSON, so the CSV
+//! and PDF exports show exactly what was reviewed even after roles change.
+//! A reviewer then signs the review off with optional notes.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+
+use super::entity;
+use super::report_subscription::ReportTable;
+use super::tor::capabilities::CAPABILITIES;
+
+/// One user's access at the time of the review.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AccessEntry {
+    pub user_id: i64,
+    pub username: String,
+    pub display_name: String,
+    pub is_active: bool,
+    pub roles: Vec<String>,
+    pub permissions: Vec<String>,
+    /// "ToR — Position" for each position held.
+    pub positions: Vec<String>,
+    /// "ToR: Capability" for each capability the positions grant.
+    pub capabilities: Vec<String>,
+}
+
+/// A generated review and its sign-off.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct AccessReview {
+    pub id: i64,
+    pub label: String,
+    pub user_count: i64,
+    pub generated_by_name: String,
+    pub generated_at: String,
+    /// `pending` or `signed_off`.
+    pub status: String,
+    pub reviewed_by_name: String,
+    pub reviewed_at: String,
+    pub notes: String,
+}
+
+impl AccessReview {
+    pub fn is_signed_off(&self) -> bool {
+        self.status == "signed_off"
+    }
+
+    /// File name for an export, e.g. `access-review-2026-10-17.csv`.
+    pub fn file_name(&self, format: &str) -> String {
+        format!("access-review-{}.{format}", &self.generated_at[..10.min(self.generated_at.len())])
+    }
+}
+
+/// Every user's current roles, permissions, positions and capabilities,
+/// ordered by display name.
+pub async fn collect(pool: &PgPool) -> Result<Vec<AccessEntry>, sqlx::Error> {
+    let users: Vec<(i64, String, String, bool)> = sqlx::query_as(
+        "SELECT id, name, label, is_active FROM entities WHERE entity_type = 'user' ORDER BY label, name"
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let roles: Vec<(i64, String)> = sqlx::query_as(
+        "SELECT r.source_id, role_e.label \
+         FROM relations r \
+         JOIN entities role_e ON r.target_id = role_e.id AND role_e.entity_type = 'role' \
+         WHERE r.relation_type_id = (SELECT id FROM entities WHERE entity_type = 'relation_type' AND name = 'has_role') \
+         ORDER BY role_e.label"
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let positions: Vec<(i64, i64, String, String)> = sqlx::query_as(
+        "SELECT r_fill.source_id, pos.id, pos.label, tor.label \
+         FROM relations r_fill \
+         JOIN entities pos ON pos.id = r_fill.target_id AND pos.entity_type = 'tor_function' \
+         JOIN relations r_tor ON r_tor.source_id = pos.id \
+             AND r_tor.relation_type_id = (SELECT id FROM entities WHERE entity_type = 'relation_type' AND name = 'belongs_to_tor') \
+         JOIN entities tor ON tor.id = r_tor.target_id AND tor.entity_type = 'tor' \
+         WHERE r_fill.relation_type_id = (SELECT id FROM entities WHERE entity_type = 'relation_type' AND name = 'fills_position') \
+         ORDER BY tor.label, pos.sort_order, pos.label"
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let keys: Vec<String> = CAPABILITIES.iter().map(|(k, _)| k.to_string()).collect();
+    let granted: Vec<(i64, String)> = sqlx::query_as(
+        "SELECT entity_id, key FROM entity_properties WHERE key = ANY($1) AND value = 'true'"
+    )
+    .bind(&keys)
+    .fetch_all(pool)
+    .await?;
+    let mut granted_by_position: HashMap<i64, Vec<&str>> = HashMap::new();
+    for (position_id, key) in &granted {
+        granted_by_position.entry(*position_id).or_default().push(key);
+    }
+
+    let mut entries = Vec::with_capacity(users.len());
+    for (user_id, username, display_name, is_active) in users {
+        let mut entry = AccessEntry {
+            user_id,
+            username,
+            display_name,
+            is_active,
+            roles: roles.iter().filter(|(u, _)| *u == user_id).map(|(_, r)| r.clone()).collect(),
+            permissions: super::permission::find_codes_by_user_id(pool, user_id).await?,
+            positions: Vec::new(),
+            capabilities: Vec::new(),
+        };
+        for (_, position_id, position, tor) in positions.iter().filter(|(u, ..)| *u == user_id) {
+            entry.positions.push(format!("{tor} — {position}"));
+            let held = granted_by_position.get(position_id);
+            for (key, label) in CAPABILITIES {
+                let capability = format!("{tor}: {label}");
+                if held.is_some_and(|keys| keys.contains(key)) && !entry.capabilities.contains(&capability) {
+                    entry.capabilities.push(capability);
+                }
+            }
+        }
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+/// The snapshot as a table, one row per user with lists joined by "; ".
+pub fn to_table(review: &AccessReview, entries: &[AccessEntry]) -> ReportTable {
+    ReportTable {
+        title: review.label.clone(),
+        columns: vec!["User", "Username", "Active", "Roles", "Permissions", "ToR positions", "Capabilities"],
+        rows: entries.iter()
+            .map(|e| vec![
+                e.display_name.clone(),
+                e.username.clone(),
+                if e.is_active { "yes" } else { "no" }.to_string(),
+                e.roles.join("; "),
+                e.permissions.join("; "),
+                e.positions.join("; "),
+                e.capabilities.join("; "),
+            ])
+            .collect(),
+    }
+}
+
+/// Snapshot everyone's access now as a pending review.
+pub async fn generate(pool: &PgPool, user_id: i64) -> Result<i64, sqlx::Error> {
+    let entries = collect(pool).await?;
+    let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+    let name = entity::available_name(pool, "access_review", &format!("access_review_{today}")).await?;
+    let id = entity::create(pool, "access_review", &name, &format!("Access review {today}")).await?;
+    entity::set_properties(pool, id, &[
+        ("generated_by", &user_id.to_string()),
+        ("user_count", &entries.len().to_string()),
+        ("snapshot", &serde_json::to_string(&entries).unwrap_or_else(|_| "[]".to_string())),
+        ("status", "pending"),
+    ]).await?;
+    Ok(id)
+}
+
+const SELECT: &str =
+    "SELECT e.id, e.label, COALESCE(p_count.value, '0')::BIGINT AS user_count, \
+            COALESCE(gen.label, 'system') AS generated_by_name, \
+            to_char(e.created_at, 'YYYY-MM-DD HH24:MI') AS generated_at, \
+            COALESCE(p_status.value, 'pending') AS status, \
+            COALESCE(rev.label, '') AS reviewed_by_name, \
+            COALESCE(p_rev_at.value, '') AS reviewed_at, \
+            COALESCE(p_notes.value, '') AS notes \
+     FROM entities e \
+     LEFT JOIN entity_properties p_count ON e.id = p_count.entity_id AND p_count.key = 'user_count' \
+     LEFT JOIN entity_properties p_gen ON e.id = p_gen.entity_id AND p_gen.key = 'generated_by' \
+     LEFT JOIN entities gen ON gen.id = CAST(p_gen.value AS BIGINT) AND gen.entity_type = 'user' \
+     LEFT JOIN entity_properties p_status ON e.id = p_status.entity_id AND p_status.key = 'status' \
+     LEFT JOIN entity_properties p_rev ON e.id = p_rev.entity_id AND p_rev.key = 'reviewed_by' \
+     LEFT JOIN entities rev ON rev.id = CAST(p_rev.value AS BIGINT) AND rev.entity_type = 'user' \
+     LEFT JOIN entity_properties p_rev_at ON e.id = p_rev_at.entity_id AND p_rev_at.key = 'reviewed_at' \
+     LEFT JOIN entity_properties p_notes ON e.id = p_notes.entity_id AND p_notes.key = 'notes' \
+     WHERE e.entity_type = 'access_review'";
+
+/// All reviews, newest first.
+pub async fn find_all(pool: &PgPool) -> Result<Vec<AccessReview>, sqlx::Error> {
+    sqlx::query_as::<_, AccessReview>(&format!("{SELECT} ORDER BY e.id DESC"))
+        .fetch_all(pool)
+        .await
+}
+
+pub async fn find_by_id(pool: &PgPool, id: i64) -> Result<Option<AccessReview>, sqlx::Error> {
+    sqlx::query_as::<_, AccessReview>(&format!("{SELECT} AND e.id = $1"))
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+}
+
+/// The users and access captured when the review was generated.
+pub async fn find_entries(pool: &PgPool, id: i64) -> Result<Vec<AccessEntry>, sqlx::Error> {
+    let snapshot = entity::get_property(pool, id, "snapshot").await?.unwrap_or_default();
+    Ok(serde_json::from_str(&snapshot).unwrap_or_default())
+}
+
+/// Record that `user_id` reviewed the snapshot. Returns false when the
+/// review was already signed off.
+pub async fn sign_off(pool: &PgPool, id: i64, user_id: i64, notes: &str) -> Result<bool, sqlx::Error> {
+    let updated = sqlx::query(
+        "UPDATE entity_properties SET value = 'signed_off' \
+         WHERE entity_id = $1 AND key = 'status' AND value = 'pending'"
+    )
+    .bind(id)
+    .execute(pool)
+    .await?;
+    if updated.rows_affected() == 0 {
+        return Ok(false);
+    }
+    let now = chrono::Local::now().format("%Y-%m-%d %H:%M").to_string();
+    entity::set_properties(pool, id, &[
+        ("reviewed_by", &user_id.to_string()),
+        ("reviewed_at", &now),
+        ("notes", notes.trim()),
+    ]).await?;
+    Ok(true)
+}
@@ -0,0 +1,294 @@
+//! Action items: follow-up work arising from a ToR's decisions.
+//!
+//! An `action_item` entity belongs to a ToR (`tor_id`) and has an owner, an
+//! optional due date and a status. Items recorded with a decision, or added
+//! against an agenda point afterwards, keep the originating `agenda_point_id`
+//! and the `decision_id` taken on it. Items converted from a warning keep its
+//! `warning_id`; finishing such an item resolves the warning. Open items past
+//! their due date raise an overdue warning for their owner (see
+//! `warnings::generators`).
+
+use serde::Serialize;
+use sqlx::PgPool;
+
+use super::{entity, tor};
+
+pub const ENTITY_TYPE: &str = "action_item";
+
+/// Statuses as (key, label); the last one closes the item.
+pub const STATUSES: &[(&str, &str)] = &[
+    ("open", "Open"),
+    ("in_progress", "In progress"),
+    ("done", "Done"),
+];
+
+pub const DONE: &str = "done";
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct ActionItem {
+    pub id: i64,
+    pub title: String,
+    pub description: String,
+    pub tor_id: i64,
+    pub tor_label: String,
+    pub owner_id: i64,
+    pub owner_name: String,
+    /// `YYYY-MM-DD`; empty when there is no deadline.
+    pub due_date: String,
+    pub status: String,
+    pub agenda_point_id: Option<i64>,
+    pub agenda_point_title: String,
+    pub decision_id: Option<i64>,
+    /// The warning the item was converted from.
+    pub warning_id: Option<i64>,
+    pub created_by_id: i64,
+    pub created_date: String,
+    pub completed_date: String,
+}
+
+impl ActionItem {
+    pub fn is_open(&self) -> bool {
+        self.status != DONE
+    }
+
+    /// Open with a due date before `today` (`YYYY-MM-DD`).
+    pub fn is_overdue(&self, today: &str) -> bool {
+        self.is_open() && !self.due_date.is_empty() && self.due_date.as_str() < today
+    }
+
+    pub fn status_label(&self) -> &str {
+        status_label(&self.status)
+    }
+}
+
+pub fn status_label(status: &str) -> &str {
+    STATUSES.iter().find(|(key, _)| *key == status).map(|(_, label)| *label).unwrap_or(status)
+}
+
+/// Fields submitted from the action item form.
+pub struct ActionItemInput<'a> {
+    pub title: &'a str,
+    pub description: &'a str,
+    pub owner_id: i64,
+    pub due_date: &'a str,
+    pub status: &'a str,
+}
+
+const SELECT: &str =
+    "SELECT e.id, e.label AS title, \
+            COALESCE(p_desc.value, '') AS description, \
+            COALESCE(t.id, 0) AS tor_id, COALESCE(t.label, '') AS tor_label, \
+            COALESCE(u.id, 0) AS owner_id, COALESCE(u.label, '') AS owner_name, \
+            COALESCE(p_due.value, '') AS due_date, \
+            COALESCE(p_status.value, 'open') AS status, \
+            ap.id AS agenda_point_id, COALESCE(p_ap_title.value, ap.label, '') AS agenda_point_title, \
+            d.id AS decision_id, w.id AS warning_id, \
+            COALESCE(p_by.value, '0')::BIGINT AS created_by_id, \
+            COALESCE(p_created.value, '') AS created_date, \
+            COALESCE(p_done.value, '') AS completed_date \
+     FROM entities e \
+     LEFT JOIN entity_properties p_desc ON e.id = p_desc.entity_id AND p_desc.key = 'description' \
+     LEFT JOIN entity_properties p_tor ON e.id = p_tor.entity_id AND p_tor.key = 'tor_id' \
+     LEFT JOIN entities t ON t.id::TEXT = p_tor.value AND t.entity_type = 'tor' \
+     LEFT JOIN entity_properties p_owner ON e.id = p_owner.entity_id AND p_owner.key = 'owner_id' \
+     LEFT JOIN entities u ON u.id::TEXT = p_owner.value AND u.entity_type = 'user' \
+     LEFT JOIN entity_properties p_due ON e.id = p_due.entity_id AND p_due.key = 'due_date' \
+     LEFT JOIN entity_properties p_status ON e.id = p_status.entity_id AND p_status.key = 'status' \
+     LEFT JOIN entity_properties p_ap ON e.id = p_ap.entity_id AND p_ap.key = 'agenda_point_id' \
+     LEFT JOIN entities ap ON ap.id::TEXT = p_ap.value AND ap.entity_type = 'agenda_point' \
+     LEFT JOIN entity_properties p_ap_title ON ap.id = p_ap_title.entity_id AND p_ap_title.key = 'title' \
+     LEFT JOIN entity_properties p_dec ON e.id = p_dec.entity_id AND p_dec.key = 'decision_id' \
+     LEFT JOIN entities d ON d.id::TEXT = p_dec.value AND d.entity_type = 'decision' \
+     LEFT JOIN entity_properties p_warn ON e.id = p_warn.entity_id AND p_warn.key = 'warning_id' \
+     LEFT JOIN entities w ON w.id::TEXT = p_warn.value AND w.entity_type = 'warning' \
+     LEFT JOIN entity_properties p_by ON e.id = p_by.entity_id AND p_by.key = 'created_by_id' \
+     LEFT JOIN entity_properties p_created ON e.id = p_created.entity_id AND p_created.key = 'created_date' \
+     LEFT JOIN entity_properties p_done ON e.id = p_done.entity_id AND p_done.key = 'completed_date' \
+     WHERE e.entity_type = 'action_item'";
+
+/// Open items first, then by due date (items without one last).
+const ORDER: &str =
+    " ORDER BY (COALESCE(p_status.value, 'open') = 'done'), NULLIF(p_due.value, '') ASC NULLS LAST, e.id";
+
+pub async fn find_by_id(pool: &PgPool, id: i64) -> Result<Option<ActionItem>, sqlx::Error> {
+    sqlx::query_as::<_, ActionItem>(&format!("{SELECT} AND e.id = $1"))
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+}
+
+pub async fn find_for_tor(pool: &PgPool, tor_id: i64) -> Result<Vec<ActionItem>, sqlx::Error> {
+    sqlx::query_as::<_, ActionItem>(&format!("{SELECT} AND t.id = $1{ORDER}"))
+        .bind(tor_id)
+        .fetch_all(pool)
+        .await
+}
+
+/// Items owned by a user across all ToRs.
+pub async fn find_for_owner(pool: &PgPool, user_id: i64) -> Result<Vec<ActionItem>, sqlx::Error> {
+    sqlx::query_as::<_, ActionItem>(&format!("{SELECT} AND u.id = $1{ORDER}"))
+        .bind(user_id)
+        .fetch_all(pool)
+        .await
+}
+
+/// Items arising from an agenda point.
+pub async fn find_for_agenda_point(pool: &PgPool, agenda_point_id: i64) -> Result<Vec<ActionItem>, sqlx::Error> {
+    sqlx::query_as::<_, ActionItem>(&format!("{SELECT} AND ap.id = $1{ORDER}"))
+        .bind(agenda_point_id)
+        .fetch_all(pool)
+        .await
+}
+
+/// Items converted from a warning.
+pub async fn find_for_warning(pool: &PgPool, warning_id: i64) -> Result<Vec<ActionItem>, sqlx::Error> {
+    sqlx::query_as::<_, ActionItem>(&format!("{SELECT} AND w.id = $1{ORDER}"))
+        .bind(warning_id)
+        .fetch_all(pool)
+        .await
+}
+
+/// Open items due before `today` (`YYYY-MM-DD`), across all ToRs.
+pub async fn find_overdue(pool: &PgPool, today: &str) -> Result<Vec<ActionItem>, sqlx::Error> {
+    sqlx::query_as::<_, ActionItem>(&format!(
+        "{SELECT} AND COALESCE(p_status.value, 'open') <> 'done' \
+           AND NULLIF(p_due.value, '') IS NOT NULL AND p_due.value < $1{ORDER}"
+    ))
+    .bind(today)
+    .fetch_all(pool)
+    .await
+}
+
+/// Users holding a position in the ToR, as (id, label): who can own its items.
+pub async fn find_owners(pool: &PgPool, tor_id: i64) -> Result<Vec<(i64, String)>, sqlx::Error> {
+    let mut owners: Vec<(i64, String)> = tor::find_members(pool, tor_id).await?
+        .into_iter()
+        .filter_map(|m| Some((m.holder_id?, m.holder_label.unwrap_or_default())))
+        .collect();
+    owners.sort_by(|a, b| a.1.cmp(&b.1).then(a.0.cmp(&b.0)));
+    owners.dedup_by_key(|o| o.0);
+    Ok(owners)
+}
+
+/// The decision recorded on an agenda point, if any (the latest one).
+pub async fn find_decision_id(pool: &PgPool, agenda_point_id: i64) -> Result<Option<i64>, sqlx::Error> {
+    sqlx::query_scalar(
+        "SELECT e.id FROM entities e \
+         JOIN entity_properties p ON p.entity_id = e.id AND p.key = 'agenda_point_id' \
+         WHERE e.entity_type = 'decision' AND p.value = $1 \
+         ORDER BY e.id DESC LIMIT 1",
+    )
+    .bind(agenda_point_id.to_string())
+    .fetch_optional(pool)
+    .await
+}
+
+/// Validate form input; `owner_ids` are the ToR's current position holders.
+pub fn validate(input: &ActionItemInput<'_>, owner_ids: &[i64]) -> Vec<String> {
+    let mut errors = Vec::new();
+    let title = input.title.trim();
+    if title.is_empty() {
+        errors.push("Title is required".to_string());
+    } else if title.chars().count() > 200 {
+        errors.push("Title must be at most 200 characters".to_string());
+    }
+    if !owner_ids.contains(&input.owner_id) {
+        errors.push("Choose an owner who holds a position in this ToR".to_string());
+    }
+    let due = input.due_date.trim();
+    if !due.is_empty() && chrono::NaiveDate::parse_from_str(due, "%Y-%m-%d").is_err() {
+        errors.push("Due date must be a date (YYYY-MM-DD)".to_string());
+    }
+    if !STATUSES.iter().any(|(key, _)| *key == input.status) {
+        errors.push("Choose a status".to_string());
+    }
+    errors
+}
+
+/// Titles of the follow-up actions entered with a decision, one per line.
+pub fn follow_up_titles(text: &str) -> Vec<&str> {
+    text.lines().map(str::trim).filter(|l| !l.is_empty()).collect()
+}
+
+fn today() -> String {
+    chrono::Local::now().format("%Y-%m-%d").to_string()
+}
+
+/// Record a new action item, linked to the agenda point it arose from and
+/// the decision taken on it. Returns the new id.
+pub async fn create(
+    pool: &PgPool,
+    tor_id: i64,
+    input: &ActionItemInput<'_>,
+    agenda_point_id: Option<i64>,
+    created_by_id: i64,
+) -> Result<i64, sqlx::Error> {
+    let name = entity::available_name(pool, ENTITY_TYPE, &format!("action_tor{tor_id}")).await?;
+    let id = entity::create(pool, ENTITY_TYPE, &name, input.title.trim()).await?;
+    entity::set_properties(pool, id, &[
+        ("tor_id", &tor_id.to_string()),
+        ("created_by_id", &created_by_id.to_string()),
+        ("created_date", &today()),
+    ]).await?;
+    if let Some(ap) = agenda_point_id {
+        entity::set_property(pool, id, "agenda_point_id", &ap.to_string()).await?;
+        if let Some(decision_id) = find_decision_id(pool, ap).await? {
+            entity::set_property(pool, id, "decision_id", &decision_id.to_string()).await?;
+        }
+    }
+    save_fields(pool, id, input, "").await?;
+    Ok(id)
+}
+
+/// Link an item to the warning it was converted from.
+pub async fn link_warning(pool: &PgPool, id: i64, warning_id: i64) -> Result<(), sqlx::Error> {
+    entity::set_property(pool, id, "warning_id", &warning_id.to_string()).await
+}
+
+/// Once `item` moves to done, resolve the warning it was converted from and
+/// close the warning's receipts. Returns the resolved warning's id.
+pub async fn resolve_linked_warning(
+    pool: &PgPool,
+    item: &ActionItem,
+    status: &str,
+    user_id: i64,
+) -> Result<Option<i64>, sqlx::Error> {
+    match item.warning_id {
+        Some(warning_id) if status == DONE && item.status != DONE => {
+            crate::warnings::resolve_warning(pool, warning_id, user_id).await?;
+            Ok(Some(warning_id))
+        }
+        _ => Ok(None),
+    }
+}
+
+async fn save_fields(pool: &PgPool, id: i64, input: &ActionItemInput<'_>, previous_status: &str) -> Result<(), sqlx::Error> {
+    entity::set_properties(pool, id, &[
+        ("description", input.description.trim()),
+        ("owner_id", &input.owner_id.to_string()),
+        ("due_date", input.due_date.trim()),
+    ]).await?;
+    set_status(pool, id, input.status, previous_status).await
+}
+
+/// Change the status, stamping or clearing `completed_date` as the item
+/// is closed or reopened.
+pub async fn set_status(pool: &PgPool, id: i64, status: &str, previous_status: &str) -> Result<(), sqlx::Error> {
+    entity::set_property(pool, id, "status", status).await?;
+    if status == DONE && previous_status != DONE {
+        entity::set_property(pool, id, "completed_date", &today()).await?;
+    } else if status != DONE {
+        entity::delete_property(pool, id, "completed_date").await?;
+    }
+    Ok(())
+}
+
+pub async fn update(pool: &PgPool, item: &ActionItem, input: &ActionItemInput<'_>) -> Result<(), sqlx::Error> {
+    let name = entity::find_by_id(pool, item.id).await?.map(|e| e.name).unwrap_or_default();
+    entity::update(pool, item.id, &name, input.title.trim()).await?;
+    save_fields(pool, item.id, input, &item.status).await
+}
+
+pub async fn delete(pool: &PgPool, id: i64) -> Result<(), sqlx::Error> {
+    entity::delete(pool, id).await
+}
@@ -236,7 +236,7 @@ pub async fn create(
     priority: &str,
     pre_read_url: &str,
 ) -> Result<i64, AppError> {
-    let name = format!("agenda_{}_{}", scheduled_date.replace('-', "_"), tor_id);
+    let name = entity::available_name(pool, "agenda_point", &format!("agenda_{}_{}", scheduled_date.replace('-', "_"), tor_id)).await?;
     let label = if title.len() > 50 {
         format!("{}...", &title[..50])
     } else {
@@ -0,0 +1,250 @@
+//! Personal API tokens for scripting against `/api/v1`.
+//!
+//! The token is `<selector>.<secret>`: the selector is the entity name and is
+//! looked up directly; only an HMAC of the secret under the `api_key`
+//! signing key is stored, so retiring that key revokes every token issued
+//! with it. A token acts as its owner, limited to the permissions of its
+//! scopes — and to reading, if it is read-only — until it expires or is
+//! revoked. Permissions are taken from the owner's roles at each request,
+//! so a token never outlives a revoked role.
+
+use rand::Rng;
+use sqlx::PgPool;
+
+use super::{entity, permission, relation, signing_key};
+
+/// API areas a token can be scoped to, as (scope, label, permission prefix).
+pub const SCOPES: &[(&str, &str, &str)] = &[
+    ("users", "Users", "users."),
+    ("entities", "Entities", "entities."),
+    ("tors", "Terms of Reference", "tor."),
+    ("proposals", "Proposals", "proposal."),
+    ("warnings", "Warnings", "warnings."),
+    ("events", "Event log", "audit."),
+    ("meetings", "Meetings", "meetings."),
+    ("agenda", "Agenda & opinions", "agenda."),
+];
+
+/// Longest allowed lifetime of an API token.
+pub const MAX_LIFETIME_DAYS: i64 = 365;
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct ApiToken {
+    pub id: i64,
+    pub selector: String,
+    pub label: String,
+    pub token_hash: String,
+    pub user_id: i64,
+    /// Comma-separated scope keys.
+    pub scopes: String,
+    pub read_only: bool,
+    /// Last valid day, `YYYY-MM-DD`.
+    pub expires_at: String,
+    pub created_at: String,
+    pub last_used_at: String,
+    pub revoked_at: String,
+}
+
+impl ApiToken {
+    pub fn scope_list(&self) -> Vec<&str> {
+        self.scopes.split(',').map(str::trim).filter(|s| !s.is_empty()).collect()
+    }
+
+    /// Scope labels for display.
+    pub fn scope_labels(&self) -> String {
+        self.scope_list().iter()
+            .map(|s| SCOPES.iter().find(|(key, _, _)| key == s).map(|(_, label, _)| *label).unwrap_or(s))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    pub fn is_expired(&self, today: &str) -> bool {
+        self.expires_at.as_str() < today
+    }
+
+    pub fn is_revoked(&self) -> bool {
+        !self.revoked_at.is_empty()
+    }
+}
+
+/// Fields submitted from the account API token form.
+pub struct ApiTokenInput<'a> {
+    pub label: &'a str,
+    pub scopes: &'a [String],
+    pub read_only: bool,
+    pub expires_at: &'a str,
+}
+
+/// A token resolved for a request: who it acts as and with which permissions.
+#[derive(Debug, Clone)]
+pub struct TokenIdentity {
+    pub token_id: i64,
+    pub user_id: i64,
+    pub username: String,
+    pub permissions: Vec<String>,
+    pub read_only: bool,
+}
+
+const SELECT_TOKEN: &str =
+    "SELECT e.id, e.name AS selector, e.label, \
+            COALESCE(p_hash.value, '') AS token_hash, \
+            COALESCE(r_user.target_id, 0) AS user_id, \
+            COALESCE(p_scopes.value, '') AS scopes, \
+            COALESCE(p_ro.value, 'false') = 'true' AS read_only, \
+            COALESCE(p_exp.value, '') AS expires_at, \
+            TO_CHAR(e.created_at, 'YYYY-MM-DD HH24:MI') AS created_at, \
+            COALESCE(p_used.value, '') AS last_used_at, \
+            COALESCE(p_rev.value, '') AS revoked_at \
+     FROM entities e \
+     LEFT JOIN relations r_user ON r_user.source_id = e.id \
+         AND r_user.relation_type_id = ( \
+             SELECT id FROM entities WHERE entity_type = 'relation_type' AND name = 'for_user') \
+     LEFT JOIN entity_properties p_hash ON e.id = p_hash.entity_id AND p_hash.key = 'token_hash' \
+     LEFT JOIN entity_properties p_scopes ON e.id = p_scopes.entity_id AND p_scopes.key = 'scopes' \
+     LEFT JOIN entity_properties p_ro ON e.id = p_ro.entity_id AND p_ro.key = 'read_only' \
+     LEFT JOIN entity_properties p_exp ON e.id = p_exp.entity_id AND p_exp.key = 'expires_at' \
+     LEFT JOIN entity_properties p_used ON e.id = p_used.entity_id AND p_used.key = 'last_used_at' \
+     LEFT JOIN entity_properties p_rev ON e.id = p_rev.entity_id AND p_rev.key = 'revoked_at' \
+     WHERE e.entity_type = 'api_token'";
+
+/// A user's tokens, newest first.
+pub async fn find_for_user(pool: &PgPool, user_id: i64) -> Result<Vec<ApiToken>, sqlx::Error> {
+    sqlx::query_as::<_, ApiToken>(&format!("{} AND r_user.target_id = $1 ORDER BY e.id DESC", SELECT_TOKEN))
+        .bind(user_id)
+        .fetch_all(pool)
+        .await
+}
+
+/// Validate form input against today's date. Returns a list of problems.
+pub fn validate(input: &ApiTokenInput<'_>, today: chrono::NaiveDate) -> Vec<String> {
+    let mut errors = Vec::new();
+    if input.label.trim().is_empty() {
+        errors.push("Name is required".to_string());
+    }
+    if input.scopes.is_empty() {
+        errors.push("Choose at least one scope".to_string());
+    }
+    for scope in input.scopes {
+        if !SCOPES.iter().any(|(key, _, _)| key == scope) {
+            errors.push(format!("Unknown scope '{}'", scope));
+        }
+    }
+    match chrono::NaiveDate::parse_from_str(input.expires_at, "%Y-%m-%d") {
+        Ok(date) if date < today => errors.push("Expiry date cannot be in the past".to_string()),
+        Ok(date) if date > today + chrono::Duration::days(MAX_LIFETIME_DAYS) => {
+            errors.push(format!("API tokens can be valid for at most {} days", MAX_LIFETIME_DAYS));
+        }
+        Ok(_) => {}
+        Err(_) => errors.push("Expiry date is required".to_string()),
+    }
+    errors
+}
+
+/// Create a token for a user. Returns its id and the raw token, which is shown once.
+pub async fn create(pool: &PgPool, user_id: i64, input: &ApiTokenInput<'_>) -> Result<(i64, String), sqlx::Error> {
+    let selector = random_hex(12);
+    let secret = random_hex(32);
+
+    let token_hash = signing_key::sign(pool, "api_key", secret.as_bytes()).await?;
+    let id = entity::create(pool, "api_token", &selector, input.label.trim()).await?;
+    entity::set_properties(pool, id, &[
+        ("token_hash", &token_hash),
+        ("scopes", &input.scopes.join(",")),
+        ("read_only", if input.read_only { "true" } else { "false" }),
+        ("expires_at", input.expires_at),
+    ]).await?;
+    relation::create(pool, "for_user", id, user_id).await?;
+    Ok((id, format!("{}.{}", selector, secret)))
+}
+
+/// Revoke one of the user's tokens. Returns false if the user has no such
+/// live token.
+pub async fn revoke(pool: &PgPool, user_id: i64, id: i64) -> Result<bool, sqlx::Error> {
+    let token = sqlx::query_as::<_, ApiToken>(&format!("{} AND e.id = $1 AND r_user.target_id = $2", SELECT_TOKEN))
+        .bind(id)
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?;
+    match token {
+        Some(token) if !token.is_revoked() => {
+            let now = chrono::Utc::now().format("%Y-%m-%d %H:%M").to_string();
+            entity::set_property(pool, id, "revoked_at", &now).await?;
+            Ok(true)
+        }
+        _ => Ok(false),
+    }
+}
+
+/// The owner's permissions that fall within the token's scopes.
+pub fn scoped_permissions(owner_permissions: &[String], scopes: &[&str]) -> Vec<String> {
+    let prefixes: Vec<&str> = SCOPES.iter()
+        .filter(|(key, _, _)| scopes.contains(key))
+        .map(|(_, _, prefix)| *prefix)
+        .collect();
+    owner_permissions.iter()
+        .filter(|code| prefixes.iter().any(|p| code.starts_with(p)))
+        .cloned()
+        .collect()
+}
+
+/// Look up the token behind a raw `<selector>.<secret>` value and record the
+/// use. Returns who it acts as only while it is unrevoked, unexpired and its
+/// owner is active.
+pub async fn resolve(pool: &PgPool, raw: &str, today: &str) -> Result<Option<TokenIdentity>, sqlx::Error> {
+    let identity = identify(pool, raw, today).await?;
+    if let Some(identity) = &identity {
+        record_use(pool, identity.token_id).await?;
+    }
+    Ok(identity)
+}
+
+/// [`resolve`] without recording the use, for checks that may still refuse
+/// the request.
+pub async fn identify(pool: &PgPool, raw: &str, today: &str) -> Result<Option<TokenIdentity>, sqlx::Error> {
+    let Some((selector, secret)) = raw.split_once('.') else {
+        return Ok(None);
+    };
+    let token = sqlx::query_as::<_, ApiToken>(&format!("{} AND e.name = $1", SELECT_TOKEN))
+        .bind(selector)
+        .fetch_optional(pool)
+        .await?;
+    let Some(token) = token else {
+        return Ok(None);
+    };
+    if token.is_revoked() || token.is_expired(today) {
+        return Ok(None);
+    }
+    if !signing_key::verify(pool, "api_key", secret.as_bytes(), &token.token_hash).await? {
+        return Ok(None);
+    }
+    let username = sqlx::query_scalar::<_, String>(
+        "SELECT name FROM entities WHERE id = $1 AND entity_type = 'user' AND is_active = true"
+    )
+    .bind(token.user_id)
+    .fetch_optional(pool)
+    .await?;
+    let Some(username) = username else {
+        return Ok(None);
+    };
+
+    let owner_permissions = permission::find_codes_by_user_id(pool, token.user_id).await?;
+    Ok(Some(TokenIdentity {
+        token_id: token.id,
+        user_id: token.user_id,
+        username,
+        permissions: scoped_permissions(&owner_permissions, &token.scope_list()),
+        read_only: token.read_only,
+    }))
+}
+
+/// Record that a token was used just now.
+pub async fn record_use(pool: &PgPool, token_id: i64) -> Result<(), sqlx::Error> {
+    let now = chrono::Utc::now().format("%Y-%m-%d %H:%M").to_string();
+    entity::set_property(pool, token_id, "last_used_at", &now).await
+}
+
+fn random_hex(len: usize) -> String {
+    let mut rng = rand::rng();
+    let bytes: Vec<u8> = (0..len).map(|_| rng.random()).collect();
+    hex::encode(bytes)
+}
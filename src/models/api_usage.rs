@@ -0,0 +1,124 @@
+//! Per-caller, per-endpoint API usage, aggregated into hourly `api_usage` entities.
+//!
+//! Each bucket is one entity named `<hour>|<caller>|<endpoint>` whose counter
+//! properties are incremented in place, so concurrent requests never lose counts.
+
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+
+/// Usage of one endpoint by one caller over the reporting window.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct ApiUsageSummary {
+    pub caller: String,
+    pub endpoint: String,
+    pub request_count: i64,
+    pub error_count: i64,
+    pub avg_latency_ms: i64,
+    pub max_latency_ms: i64,
+}
+
+impl ApiUsageSummary {
+    /// Share of requests that returned a 4xx/5xx status, in whole percent.
+    pub fn error_rate_pct(&self) -> i64 {
+        if self.request_count == 0 {
+            return 0;
+        }
+        self.error_count * 100 / self.request_count
+    }
+
+    /// Enough traffic with enough failures to suggest a broken integration.
+    pub fn is_failing(&self) -> bool {
+        self.request_count >= 10 && self.error_rate_pct() >= 25
+    }
+}
+
+/// Hour bucket key, e.g. `2026-03-01T14`. Sorts chronologically as text.
+pub fn hour_bucket(at: DateTime<Utc>) -> String {
+    at.format("%Y-%m-%dT%H").to_string()
+}
+
+/// Add one request to its hourly bucket.
+pub async fn record(
+    pool: &PgPool,
+    caller: &str,
+    endpoint: &str,
+    at: DateTime<Utc>,
+    is_error: bool,
+    latency_ms: i64,
+) -> Result<(), sqlx::Error> {
+    let hour = hour_bucket(at);
+    let name = format!("{}|{}|{}", hour, caller, endpoint);
+
+    let (id,): (i64,) = sqlx::query_as(
+        "INSERT INTO entities (entity_type, name, label) VALUES ('api_usage', $1, $2) \
+         ON CONFLICT (entity_type, name) DO UPDATE SET updated_at = NOW() \
+         RETURNING id"
+    )
+    .bind(&name)
+    .bind(endpoint)
+    .fetch_one(pool)
+    .await?;
+
+    sqlx::query(
+        "INSERT INTO entity_properties (entity_id, key, value) VALUES \
+             ($1, 'caller', $2), ($1, 'endpoint', $3), ($1, 'hour', $4), \
+             ($1, 'request_count', '1'), ($1, 'error_count', $5), \
+             ($1, 'total_latency_ms', $6), ($1, 'max_latency_ms', $6) \
+         ON CONFLICT (entity_id, key) DO UPDATE SET value = CASE entity_properties.key \
+             WHEN 'request_count' THEN (entity_properties.value::BIGINT + 1)::TEXT \
+             WHEN 'error_count' THEN (entity_properties.value::BIGINT + excluded.value::BIGINT)::TEXT \
+             WHEN 'total_latency_ms' THEN (entity_properties.value::BIGINT + excluded.value::BIGINT)::TEXT \
+             WHEN 'max_latency_ms' THEN GREATEST(entity_properties.value::BIGINT, excluded.value::BIGINT)::TEXT \
+             ELSE entity_properties.value END"
+    )
+    .bind(id)
+    .bind(caller)
+    .bind(endpoint)
+    .bind(&hour)
+    .bind(if is_error { "1" } else { "0" })
+    .bind(latency_ms.to_string())
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Usage per caller and endpoint since `since`, busiest first.
+pub async fn summarize(pool: &PgPool, since: DateTime<Utc>) -> Result<Vec<ApiUsageSummary>, sqlx::Error> {
+    let rows = sqlx::query_as::<_, ApiUsageSummary>(
+        "SELECT p_caller.value AS caller, p_ep.value AS endpoint, \
+                SUM(p_req.value::BIGINT)::BIGINT AS request_count, \
+                SUM(p_err.value::BIGINT)::BIGINT AS error_count, \
+                (SUM(p_lat.value::BIGINT) / GREATEST(SUM(p_req.value::BIGINT), 1))::BIGINT AS avg_latency_ms, \
+                MAX(p_max.value::BIGINT) AS max_latency_ms \
+         FROM entities e \
+         JOIN entity_properties p_hour ON e.id = p_hour.entity_id AND p_hour.key = 'hour' \
+         JOIN entity_properties p_caller ON e.id = p_caller.entity_id AND p_caller.key = 'caller' \
+         JOIN entity_properties p_ep ON e.id = p_ep.entity_id AND p_ep.key = 'endpoint' \
+         JOIN entity_properties p_req ON e.id = p_req.entity_id AND p_req.key = 'request_count' \
+         JOIN entity_properties p_err ON e.id = p_err.entity_id AND p_err.key = 'error_count' \
+         JOIN entity_properties p_lat ON e.id = p_lat.entity_id AND p_lat.key = 'total_latency_ms' \
+         JOIN entity_properties p_max ON e.id = p_max.entity_id AND p_max.key = 'max_latency_ms' \
+         WHERE e.entity_type = 'api_usage' AND p_hour.value >= $1 \
+         GROUP BY p_caller.value, p_ep.value \
+         ORDER BY request_count DESC, caller, endpoint"
+    )
+    .bind(hour_bucket(since))
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
+/// Delete buckets older than `before`. Returns the number removed.
+pub async fn cleanup(pool: &PgPool, before: DateTime<Utc>) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        "DELETE FROM entities e \
+         USING entity_properties p \
+         WHERE p.entity_id = e.id AND p.key = 'hour' \
+           AND e.entity_type = 'api_usage' AND p.value < $1"
+    )
+    .bind(hour_bucket(before))
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
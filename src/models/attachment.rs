@@ -0,0 +1,208 @@
+//! Files uploaded to proposals.
+//!
+//! An upload is stored on disk under `data/{APP_ENV}/uploads/` with a random
+//! name, and recorded as a `document_attachment` entity linked to its proposal
+//! with `attached_to_proposal` (attachment → proposal), the same relation that
+//! links library documents. Only the types in [`ALLOWED_TYPES`] are accepted:
+//! the file's extension picks the type and its first bytes must match it.
+//! Uploads are limited to `uploads.max_size_mb` and count towards the ToR's
+//! storage quota.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use rand::Rng;
+use serde::Serialize;
+use sqlx::PgPool;
+
+use super::export_record::sha256_hex;
+use super::{entity, relation, setting};
+
+pub const ENTITY_TYPE: &str = "document_attachment";
+
+/// Setting holding the upload size limit in megabytes.
+pub const MAX_SIZE_MB: &str = "uploads.max_size_mb";
+
+/// Accepted uploads: content type, file extensions and how the file must start.
+/// Text types have no signature but must be valid UTF-8.
+pub const ALLOWED_TYPES: &[(&str, &[&str], &[u8])] = &[
+    ("application/pdf", &["pdf"], b"%PDF-"),
+    ("image/png", &["png"], b"\x89PNG\r\n\x1a\n"),
+    ("image/jpeg", &["jpg", "jpeg"], b"\xff\xd8\xff"),
+    ("image/gif", &["gif"], b"GIF8"),
+    ("text/plain", &["txt"], b""),
+    ("text/csv", &["csv"], b""),
+    ("application/vnd.openxmlformats-officedocument.wordprocessingml.document", &["docx"], b"PK\x03\x04"),
+    ("application/vnd.openxmlformats-officedocument.spreadsheetml.sheet", &["xlsx"], b"PK\x03\x04"),
+    ("application/vnd.openxmlformats-officedocument.presentationml.presentation", &["pptx"], b"PK\x03\x04"),
+];
+
+/// File extensions accepted, for the upload form's `accept` attribute.
+pub fn accept_list() -> String {
+    ALLOWED_TYPES.iter()
+        .flat_map(|(_, exts, _)| exts.iter().map(|e| format!(".{e}")))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// A file uploaded to a proposal.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct Attachment {
+    pub id: i64,
+    pub proposal_id: i64,
+    /// Name of the file on disk inside the uploads directory.
+    pub storage_key: String,
+    pub file_name: String,
+    pub content_type: String,
+    pub size_bytes: i64,
+    pub sha256: String,
+    pub uploaded_by: i64,
+    pub uploaded_by_name: String,
+    pub uploaded_at: String,
+}
+
+impl Attachment {
+    pub fn size_display(&self) -> String {
+        match self.size_bytes {
+            b if b < 1024 => format!("{b} B"),
+            b if b < 1024 * 1024 => format!("{:.1} KB", b as f64 / 1024.0),
+            b => format!("{:.1} MB", b as f64 / (1024.0 * 1024.0)),
+        }
+    }
+}
+
+/// Directory uploads are stored in for the running environment.
+pub fn uploads_dir() -> PathBuf {
+    let env = std::env::var("APP_ENV").unwrap_or_else(|_| "dev".to_string());
+    PathBuf::from("data").join(env).join("uploads")
+}
+
+/// Largest upload allowed, in bytes.
+pub async fn max_bytes(pool: &PgPool) -> usize {
+    setting::get_i64(pool, MAX_SIZE_MB).await.max(1) as usize * 1024 * 1024
+}
+
+/// Check an upload against the allowed types and the size limit. Returns the
+/// content type to store it under. Browsers often send a generic type, so the
+/// declared type must match or be `application/octet-stream`.
+pub fn validate(file_name: &str, declared_type: &str, data: &[u8], max_bytes: usize) -> Result<&'static str, String> {
+    let file_name = file_name.trim();
+    if file_name.is_empty() {
+        return Err("Choose a file to upload".to_string());
+    }
+    if data.is_empty() {
+        return Err(format!("'{}' is empty", file_name));
+    }
+    if data.len() > max_bytes {
+        return Err(format!(
+            "'{}' is {:.1} MB; uploads may be at most {} MB",
+            file_name,
+            data.len() as f64 / (1024.0 * 1024.0),
+            max_bytes / (1024 * 1024)
+        ));
+    }
+
+    let extension = Path::new(file_name).extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let Some((content_type, _, signature)) = ALLOWED_TYPES.iter()
+        .find(|(_, exts, _)| exts.contains(&extension.as_str()))
+    else {
+        return Err(format!("'{}' is not an accepted file type (accepted: {})", file_name, accept_list()));
+    };
+
+    let declared = declared_type.split(';').next().unwrap_or("").trim().to_lowercase();
+    let declared_ok = declared.is_empty()
+        || declared == "application/octet-stream"
+        || declared == *content_type
+        || (content_type.starts_with("text/") && declared.starts_with("text/"))
+        || (*content_type == "text/csv" && declared == "application/vnd.ms-excel");
+    let content_ok = if signature.is_empty() {
+        std::str::from_utf8(data).is_ok()
+    } else {
+        data.starts_with(signature)
+    };
+    if !declared_ok || !content_ok {
+        return Err(format!("The content of '{}' does not match its file type", file_name));
+    }
+    Ok(content_type)
+}
+
+/// Write an upload to the uploads directory under a new random name.
+pub fn save_file(dir: &Path, data: &[u8]) -> io::Result<String> {
+    fs::create_dir_all(dir)?;
+    let bytes: [u8; 16] = rand::rng().random();
+    let key = hex::encode(bytes);
+    fs::write(dir.join(&key), data)?;
+    Ok(key)
+}
+
+/// Contents of a stored upload.
+pub fn read_file(dir: &Path, attachment: &Attachment) -> io::Result<Vec<u8>> {
+    fs::read(dir.join(&attachment.storage_key))
+}
+
+/// Record a saved upload against a proposal.
+pub async fn create(
+    pool: &PgPool,
+    proposal_id: i64,
+    storage_key: &str,
+    file_name: &str,
+    content_type: &str,
+    data: &[u8],
+    user_id: i64,
+) -> Result<i64, sqlx::Error> {
+    let id = entity::create(pool, ENTITY_TYPE, storage_key, file_name).await?;
+    entity::set_properties(pool, id, &[
+        ("file_name", file_name),
+        ("content_type", content_type),
+        ("size_bytes", &data.len().to_string()),
+        ("sha256", &sha256_hex(data)),
+        ("uploaded_by", &user_id.to_string()),
+    ]).await?;
+    relation::create(pool, "attached_to_proposal", id, proposal_id).await?;
+    Ok(id)
+}
+
+const SELECT: &str =
+    "SELECT e.id, r.target_id AS proposal_id, e.name AS storage_key, \
+            COALESCE(p_file.value, e.label) AS file_name, \
+            COALESCE(p_type.value, 'application/octet-stream') AS content_type, \
+            COALESCE(p_size.value, '0')::BIGINT AS size_bytes, COALESCE(p_sha.value, '') AS sha256, \
+            COALESCE(p_by.value, '0')::BIGINT AS uploaded_by, COALESCE(u.label, 'unknown') AS uploaded_by_name, \
+            to_char(e.created_at, 'YYYY-MM-DD HH24:MI') AS uploaded_at \
+     FROM entities e \
+     JOIN relations r ON r.source_id = e.id \
+     JOIN entities rt ON rt.id = r.relation_type_id AND rt.name = 'attached_to_proposal' \
+     LEFT JOIN entity_properties p_file ON e.id = p_file.entity_id AND p_file.key = 'file_name' \
+     LEFT JOIN entity_properties p_type ON e.id = p_type.entity_id AND p_type.key = 'content_type' \
+     LEFT JOIN entity_properties p_size ON e.id = p_size.entity_id AND p_size.key = 'size_bytes' \
+     LEFT JOIN entity_properties p_sha ON e.id = p_sha.entity_id AND p_sha.key = 'sha256' \
+     LEFT JOIN entity_properties p_by ON e.id = p_by.entity_id AND p_by.key = 'uploaded_by' \
+     LEFT JOIN entities u ON u.id = CAST(p_by.value AS BIGINT) AND u.entity_type = 'user' \
+     WHERE e.entity_type = 'document_attachment'";
+
+/// Files uploaded to a proposal, oldest first.
+pub async fn find_for_proposal(pool: &PgPool, proposal_id: i64) -> Result<Vec<Attachment>, sqlx::Error> {
+    sqlx::query_as::<_, Attachment>(&format!("{SELECT} AND r.target_id = $1 ORDER BY e.id"))
+        .bind(proposal_id)
+        .fetch_all(pool)
+        .await
+}
+
+pub async fn find_by_id(pool: &PgPool, id: i64) -> Result<Option<Attachment>, sqlx::Error> {
+    sqlx::query_as::<_, Attachment>(&format!("{SELECT} AND e.id = $1"))
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+}
+
+/// Remove an upload's record and its file.
+pub async fn delete(pool: &PgPool, dir: &Path, attachment: &Attachment) -> Result<(), sqlx::Error> {
+    entity::delete(pool, attachment.id).await?;
+    if let Err(e) = fs::remove_file(dir.join(&attachment.storage_key)) {
+        log::warn!("Could not remove upload {}: {}", attachment.storage_key, e);
+    }
+    Ok(())
+}
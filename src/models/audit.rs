@@ -1,6 +1,9 @@
+use chrono::NaiveDate;
 use sqlx::PgPool;
 use serde::Serialize;
 
+use crate::audit::chain;
+
 #[derive(Debug, Clone, Serialize, sqlx::FromRow)]
 pub struct AuditEntry {
     pub id: i64,
@@ -40,66 +43,129 @@ const SELECT_AUDIT_DISPLAY: &str = "\
     LEFT JOIN entities u ON CAST(p_user_id.value AS BIGINT) = u.id AND u.entity_type = 'user' \
     WHERE e.entity_type = 'audit_entry'";
 
+/// Filters for the audit log, from the query string. Empty values and
+/// `all` mean no filter; dates are inclusive.
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+pub struct AuditFilter {
+    /// Substring of the username or summary.
+    pub q: Option<String>,
+    /// Exact username.
+    pub user: Option<String>,
+    /// Action prefix, e.g. `role.` or `user.created`.
+    pub action: Option<String>,
+    pub target_type: Option<String>,
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+impl AuditFilter {
+    fn value(v: &Option<String>) -> Option<&str> {
+        v.as_deref().map(str::trim).filter(|s| !s.is_empty() && *s != "all")
+    }
+
+    pub fn search(&self) -> Option<&str> {
+        Self::value(&self.q)
+    }
+
+    pub fn user(&self) -> Option<&str> {
+        Self::value(&self.user)
+    }
+
+    pub fn action(&self) -> Option<&str> {
+        Self::value(&self.action)
+    }
+
+    pub fn target_type(&self) -> Option<&str> {
+        Self::value(&self.target_type)
+    }
+
+    pub fn from_date(&self) -> Option<NaiveDate> {
+        Self::value(&self.from).and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+    }
+
+    pub fn to_date(&self) -> Option<NaiveDate> {
+        Self::value(&self.to).and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.search().is_none() && self.user().is_none() && self.action().is_none()
+            && self.target_type().is_none() && self.from_date().is_none() && self.to_date().is_none()
+    }
+
+    /// The filter as query-string pairs (`&key=value`), for links that keep it.
+    pub fn query_string(&self) -> String {
+        let pairs = [
+            ("q", self.search().map(str::to_string)),
+            ("user", self.user().map(str::to_string)),
+            ("action", self.action().map(str::to_string)),
+            ("target_type", self.target_type().map(str::to_string)),
+            ("from", self.from_date().map(|d| d.to_string())),
+            ("to", self.to_date().map(|d| d.to_string())),
+        ];
+        pairs.into_iter()
+            .filter_map(|(key, value)| value.map(|v| format!("&{}={}", key, super::table_filter::encode(&v))))
+            .collect()
+    }
+
+    /// Whether a record matches, for entries read from the log files.
+    pub fn matches(&self, username: &str, action: &str, target_type: &str, summary: &str, date: NaiveDate) -> bool {
+        self.search().is_none_or(|q| username.contains(q) || summary.contains(q))
+            && self.user().is_none_or(|u| username == u)
+            && self.action().is_none_or(|a| action.starts_with(a))
+            && self.target_type().is_none_or(|t| target_type == t)
+            && self.from_date().is_none_or(|from| date >= from)
+            && self.to_date().is_none_or(|to| date <= to)
+    }
+
+    /// SQL conditions (each starting with ` AND `) over the columns of
+    /// [`SELECT_AUDIT_DISPLAY`], with their values for `$1..`.
+    fn sql(&self) -> (String, Vec<String>) {
+        let mut clause = String::new();
+        let mut params: Vec<String> = Vec::new();
+        if let Some(q) = self.search() {
+            let pattern = format!("%{}%", q);
+            params.push(pattern.clone());
+            params.push(pattern);
+            clause.push_str(&format!(" AND (u.name LIKE ${} OR p_summary.value LIKE ${})", params.len() - 1, params.len()));
+        }
+        if let Some(user) = self.user() {
+            params.push(user.to_string());
+            clause.push_str(&format!(" AND u.name = ${}", params.len()));
+        }
+        if let Some(action) = self.action() {
+            params.push(format!("{}%", action));
+            clause.push_str(&format!(" AND p_action.value LIKE ${}", params.len()));
+        }
+        if let Some(target) = self.target_type() {
+            params.push(target.to_string());
+            clause.push_str(&format!(" AND p_target_type.value = ${}", params.len()));
+        }
+        if let Some(from) = self.from_date() {
+            params.push(from.to_string());
+            clause.push_str(&format!(" AND e.created_at >= ${}::DATE", params.len()));
+        }
+        if let Some(to) = self.to_date() {
+            params.push(to.to_string());
+            clause.push_str(&format!(" AND e.created_at < ${}::DATE + 1", params.len()));
+        }
+        (clause, params)
+    }
+}
+
 /// Find audit entries with pagination and optional filters
 pub async fn find_paginated(
     pool: &PgPool,
     page: i64,
     per_page: i64,
-    search: Option<&str>,
-    action_filter: Option<&str>,
-    target_type_filter: Option<&str>,
+    filter: &AuditFilter,
 ) -> Result<AuditEntryPage, sqlx::Error> {
     let page = page.max(1);
     let per_page = per_page.clamp(1, 100);
     let offset = (page - 1) * per_page;
-
-    // Build filter clauses with sequential $N parameters
-    let mut filters = Vec::new();
-    let mut param_index: usize = 0;
-    // We'll collect the string values to bind later
-    let mut string_params: Vec<String> = Vec::new();
-
-    if let Some(q) = search.filter(|s| !s.trim().is_empty()) {
-        let pattern = format!("%{}%", q.trim());
-        param_index += 1;
-        let p1 = param_index;
-        param_index += 1;
-        let p2 = param_index;
-        filters.push(format!("(u.name LIKE ${} OR p_summary.value LIKE ${})", p1, p2));
-        string_params.push(pattern.clone());
-        string_params.push(pattern);
-    }
-
-    if let Some(action) = action_filter.filter(|a| a != &"all") {
-        param_index += 1;
-        filters.push(format!("p_action.value LIKE ${}", param_index));
-        string_params.push(format!("{}%", action));
-    }
-
-    if let Some(target) = target_type_filter.filter(|t| t != &"all") {
-        param_index += 1;
-        filters.push(format!("p_target_type.value = ${}", param_index));
-        string_params.push(target.to_string());
-    }
-
-    let filter_clause = if filters.is_empty() {
-        String::new()
-    } else {
-        format!(" AND {}", filters.join(" AND "))
-    };
+    let (filter_clause, string_params) = filter.sql();
 
     // Get total count
-    let count_sql = format!(
-        "SELECT COUNT(*) FROM entities e \
-         LEFT JOIN entity_properties p_user_id ON e.id = p_user_id.entity_id AND p_user_id.key = 'user_id' \
-         LEFT JOIN entity_properties p_action ON e.id = p_action.entity_id AND p_action.key = 'action' \
-         LEFT JOIN entity_properties p_target_type ON e.id = p_target_type.entity_id AND p_target_type.key = 'target_type' \
-         LEFT JOIN entity_properties p_summary ON e.id = p_summary.entity_id AND p_summary.key = 'summary' \
-         LEFT JOIN entities u ON CAST(p_user_id.value AS BIGINT) = u.id AND u.entity_type = 'user' \
-         WHERE e.entity_type = 'audit_entry'{}",
-        filter_clause
-    );
-
+    let count_sql = format!("SELECT COUNT(*) FROM ({}{}) filtered", SELECT_AUDIT_DISPLAY, filter_clause);
     let mut count_query = sqlx::query_as::<_, (i64,)>(&count_sql);
     for p in &string_params {
         count_query = count_query.bind(p);
@@ -108,8 +174,8 @@ pub async fn find_paginated(
     let total_pages = (total_count as f64 / per_page as f64).ceil() as i64;
 
     // Get paginated results
-    let limit_param = param_index + 1;
-    let offset_param = param_index + 2;
+    let limit_param = string_params.len() + 1;
+    let offset_param = string_params.len() + 2;
     let sql = format!(
         "{}{} ORDER BY e.created_at DESC LIMIT ${} OFFSET ${}",
         SELECT_AUDIT_DISPLAY,
@@ -135,6 +201,17 @@ pub async fn find_paginated(
     })
 }
 
+/// Every audit entry matching the filter, oldest first (for exports).
+pub async fn find_filtered(pool: &PgPool, filter: &AuditFilter) -> Result<Vec<AuditEntry>, sqlx::Error> {
+    let (filter_clause, params) = filter.sql();
+    let sql = format!("{}{} ORDER BY e.created_at, e.id", SELECT_AUDIT_DISPLAY, filter_clause);
+    let mut query = sqlx::query_as::<_, AuditEntry>(&sql);
+    for p in &params {
+        query = query.bind(p);
+    }
+    query.fetch_all(pool).await
+}
+
 /// Fetch the N most recent audit entries (for dashboard activity feed).
 pub async fn find_recent(pool: &PgPool, limit: i64) -> Result<Vec<AuditEntry>, sqlx::Error> {
     let sql = format!(
@@ -148,6 +225,23 @@ pub async fn find_recent(pool: &PgPool, limit: i64) -> Result<Vec<AuditEntry>, s
     Ok(entries)
 }
 
+/// Audit entries recorded on the given days (inclusive), oldest first.
+pub async fn find_between(
+    pool: &PgPool,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Vec<AuditEntry>, sqlx::Error> {
+    let sql = format!(
+        "{} AND e.created_at::DATE BETWEEN $1::DATE AND $2::DATE ORDER BY e.created_at, e.id",
+        SELECT_AUDIT_DISPLAY
+    );
+    sqlx::query_as::<_, AuditEntry>(&sql)
+        .bind(from.to_string())
+        .bind(to.to_string())
+        .fetch_all(pool)
+        .await
+}
+
 /// Create an audit entry in the database
 pub async fn create(
     pool: &PgPool,
@@ -157,51 +251,40 @@ pub async fn create(
     target_id: i64,
     summary: &str,
 ) -> Result<i64, sqlx::Error> {
-    // Insert audit_entry entity with RETURNING id
+    // Link to the newest entry; the timestamp is part of the hashed content
+    let _guard = chain::APPEND_LOCK.lock().await;
+    let prev_hash = chain::last_db_hash(pool).await?;
+    let created_at = chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Micros, true);
+    let mut properties = vec![
+        ("user_id".to_string(), user_id.to_string()),
+        ("action".to_string(), action.to_string()),
+        ("target_type".to_string(), target_type.to_string()),
+        ("target_id".to_string(), target_id.to_string()),
+        ("summary".to_string(), summary.to_string()),
+        ("prev_hash".to_string(), prev_hash),
+    ];
+    let hash = chain::entry_hash(&chain::db_entry(&created_at, &properties));
+    properties.push(("hash".to_string(), hash));
+
+    let mut tx = pool.begin().await?;
     let (entry_id,) = sqlx::query_as::<_, (i64,)>(
-        "INSERT INTO entities (entity_type, name, label) VALUES ('audit_entry', $1, $2) RETURNING id"
+        "INSERT INTO entities (entity_type, name, label, created_at) \
+         VALUES ('audit_entry', $1, $2, $3::TIMESTAMPTZ) RETURNING id"
     )
     .bind(action)
     .bind(summary)
-    .fetch_one(pool)
-    .await?;
-
-    // Set properties
-    sqlx::query(
-        "INSERT INTO entity_properties (entity_id, key, value) VALUES ($1, 'user_id', $2)"
-    )
-    .bind(entry_id)
-    .bind(user_id.to_string())
-    .execute(pool)
-    .await?;
-    sqlx::query(
-        "INSERT INTO entity_properties (entity_id, key, value) VALUES ($1, 'action', $2)"
-    )
-    .bind(entry_id)
-    .bind(action)
-    .execute(pool)
-    .await?;
-    sqlx::query(
-        "INSERT INTO entity_properties (entity_id, key, value) VALUES ($1, 'target_type', $2)"
-    )
-    .bind(entry_id)
-    .bind(target_type)
-    .execute(pool)
-    .await?;
-    sqlx::query(
-        "INSERT INTO entity_properties (entity_id, key, value) VALUES ($1, 'target_id', $2)"
-    )
-    .bind(entry_id)
-    .bind(target_id.to_string())
-    .execute(pool)
-    .await?;
-    sqlx::query(
-        "INSERT INTO entity_properties (entity_id, key, value) VALUES ($1, 'summary', $2)"
-    )
-    .bind(entry_id)
-    .bind(summary)
-    .execute(pool)
+    .bind(&created_at)
+    .fetch_one(&mut *tx)
     .await?;
+    for (key, value) in &properties {
+        sqlx::query("INSERT INTO entity_properties (entity_id, key, value) VALUES ($1, $2, $3)")
+            .bind(entry_id)
+            .bind(key)
+            .bind(value)
+            .execute(&mut *tx)
+            .await?;
+    }
+    tx.commit().await?;
 
     Ok(entry_id)
 }
@@ -0,0 +1,464 @@
+//! Formal ballots on decision agenda points.
+//!
+//! Opinions record what members prefer; a ballot is the structured vote.
+//! Whoever records decisions in the ToR (see `tor::DecisionAuthority`)
+//! opens a ballot of one of the [`BALLOT_TYPES`] and closes it. The members
+//! holding a position in the ToR when it opens are its eligible voters, and
+//! the quorum is the share of them that must vote (abstaining counts) for
+//! the result to stand. Closing tallies the `vote` entities; when the ballot
+//! carries a course of action, the decision is recorded with the tally as
+//! its rationale and in its `ballot_tally` property.
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+use sqlx::PgPool;
+
+use crate::errors::AppError;
+use super::{entity, opinion, setting, tor};
+
+/// Ballot types as (key, label, how it is decided).
+pub const BALLOT_TYPES: &[(&str, &str, &str)] = &[
+    ("yes_no", "Yes / No / Abstain", "Members vote for or against one course of action; carried when yes outnumbers no"),
+    ("ranked", "Ranked courses of action", "Members rank the courses of action; decided by instant runoff"),
+    ("weighted", "Weighted points", "Members spread their points across the courses of action; most points wins"),
+];
+
+/// Points each voter spreads in a weighted ballot.
+pub const WEIGHTED_POINTS: i64 = 10;
+
+/// Setting holding the default quorum, in percent of eligible voters.
+pub const QUORUM_SETTING: &str = "governance.ballot_quorum_pct";
+
+/// Choice recorded for a voter who abstains, in any ballot type.
+pub const ABSTAIN: &str = "abstain";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Ballot {
+    pub id: i64,
+    pub agenda_point_id: i64,
+    pub ballot_type: String,
+    /// `open` or `closed`.
+    pub status: String,
+    /// The course of action voted on in a yes/no ballot; 0 otherwise.
+    pub motion_coa_id: i64,
+    pub quorum_pct: i64,
+    /// Users entitled to vote, fixed when the ballot opened.
+    pub eligible: Vec<i64>,
+    pub opened_by_id: i64,
+    pub opened_at: String,
+    pub closed_at: String,
+    /// Result as stored when the ballot closed.
+    pub tally: Option<Tally>,
+}
+
+impl Ballot {
+    pub fn is_open(&self) -> bool {
+        self.status == "open"
+    }
+
+    pub fn type_label(&self) -> &'static str {
+        BALLOT_TYPES.iter()
+            .find(|(key, _, _)| *key == self.ballot_type)
+            .map(|(_, label, _)| *label)
+            .unwrap_or("")
+    }
+
+    pub fn is_eligible(&self, user_id: i64) -> bool {
+        self.eligible.contains(&user_id)
+    }
+
+    /// Votes needed for the result to stand.
+    pub fn quorum(&self) -> usize {
+        quorum(self.eligible.len(), self.quorum_pct)
+    }
+}
+
+/// Votes needed out of `eligible` for a quorum of `pct` percent, rounded up.
+pub fn quorum(eligible: usize, pct: i64) -> usize {
+    (eligible * pct.clamp(0, 100) as usize).div_ceil(100)
+}
+
+/// Result of a ballot.
+#[derive(Debug, Clone, PartialEq, Serialize, serde::Deserialize)]
+pub struct Tally {
+    pub eligible: usize,
+    pub cast: usize,
+    pub abstained: usize,
+    pub quorum: usize,
+    /// (label, count): yes/no counts, final-round first preferences, or points.
+    pub counts: Vec<(String, i64)>,
+    /// First preferences per course of action in each instant-runoff round.
+    #[serde(default)]
+    pub rounds: Vec<Vec<(String, i64)>>,
+    /// The course of action the ballot carried, if any.
+    pub winner: Option<i64>,
+    pub summary: String,
+}
+
+impl Tally {
+    pub fn quorum_met(&self) -> bool {
+        self.cast >= self.quorum && self.cast > 0
+    }
+}
+
+/// Instant runoff over rankings (most preferred first). Each round counts
+/// every ranking for its highest remaining candidate; a candidate with more
+/// than half of those wins, otherwise the candidates with the fewest are
+/// all eliminated. Returns the winner, if any, and the counts of each round.
+pub fn instant_runoff(rankings: &[Vec<i64>], candidates: &[i64]) -> (Option<i64>, Vec<Vec<(i64, i64)>>) {
+    let mut remaining = candidates.to_vec();
+    let mut rounds = Vec::new();
+    while !remaining.is_empty() {
+        let mut counts: Vec<(i64, i64)> = remaining.iter().map(|c| (*c, 0)).collect();
+        let mut active = 0;
+        for ranking in rankings {
+            if let Some(top) = ranking.iter().find(|c| remaining.contains(c)) {
+                active += 1;
+                if let Some(entry) = counts.iter_mut().find(|(c, _)| c == top) {
+                    entry.1 += 1;
+                }
+            }
+        }
+        rounds.push(counts.clone());
+        if active == 0 {
+            return (None, rounds);
+        }
+        if let Some((winner, _)) = counts.iter().find(|(_, n)| n * 2 > active) {
+            return (Some(*winner), rounds);
+        }
+        let fewest = counts.iter().map(|(_, n)| *n).min().unwrap_or(0);
+        let eliminated: Vec<i64> = counts.iter().filter(|(_, n)| *n == fewest).map(|(c, _)| *c).collect();
+        if eliminated.len() == remaining.len() {
+            return (None, rounds);
+        }
+        remaining.retain(|c| !eliminated.contains(c));
+    }
+    (None, rounds)
+}
+
+/// Parse a vote from the ballot form into the stored choice: `yes`, `no`,
+/// a comma-separated ranking of COA ids, `coa:points` pairs, or `abstain`.
+pub fn parse_choice(ballot_type: &str, form: &HashMap<String, String>, coa_ids: &[i64]) -> Result<String, String> {
+    if form.get("abstain").is_some_and(|v| !v.is_empty()) {
+        return Ok(ABSTAIN.to_string());
+    }
+    let field = |prefix: &str, coa_id: i64| form.get(&format!("{prefix}_{coa_id}")).map(|v| v.trim()).unwrap_or("");
+    match ballot_type {
+        "yes_no" => match form.get("choice").map(|v| v.as_str()) {
+            Some(choice @ ("yes" | "no" | "abstain")) => Ok(choice.to_string()),
+            _ => Err("Choose yes, no or abstain".to_string()),
+        },
+        "ranked" => {
+            let mut ranked = Vec::new();
+            for coa_id in coa_ids {
+                match field("rank", *coa_id) {
+                    "" => {}
+                    v => match v.parse::<usize>() {
+                        Ok(rank) if (1..=coa_ids.len()).contains(&rank) => ranked.push((rank, *coa_id)),
+                        _ => return Err(format!("Ranks must be between 1 and {}", coa_ids.len())),
+                    },
+                }
+            }
+            if ranked.is_empty() {
+                return Err("Rank at least one course of action".to_string());
+            }
+            ranked.sort();
+            if ranked.windows(2).any(|w| w[0].0 == w[1].0) {
+                return Err("Give each course of action a different rank".to_string());
+            }
+            Ok(ranked.iter().map(|(_, id)| id.to_string()).collect::<Vec<_>>().join(","))
+        }
+        "weighted" => {
+            let mut points = Vec::new();
+            for coa_id in coa_ids {
+                match field("points", *coa_id) {
+                    "" => {}
+                    v => match v.parse::<i64>() {
+                        Ok(p) if p >= 0 => points.push((*coa_id, p)),
+                        _ => return Err("Points must be whole numbers of 0 or more".to_string()),
+                    },
+                }
+            }
+            let total: i64 = points.iter().map(|(_, p)| p).sum();
+            if total != WEIGHTED_POINTS {
+                return Err(format!("Spread exactly {} points ({} given)", WEIGHTED_POINTS, total));
+            }
+            Ok(points.iter().filter(|(_, p)| *p > 0).map(|(id, p)| format!("{id}:{p}")).collect::<Vec<_>>().join(","))
+        }
+        _ => Err("Unknown ballot type".to_string()),
+    }
+}
+
+/// Tally the votes on a ballot. `votes` are stored choices of eligible
+/// voters; `coas` are (id, title) of the agenda point's courses of action.
+pub fn tally(ballot: &Ballot, votes: &[String], coas: &[(i64, String)]) -> Tally {
+    let title = |id: i64| coas.iter().find(|(c, _)| *c == id).map(|(_, t)| t.clone()).unwrap_or_else(|| format!("COA #{id}"));
+    let abstained = votes.iter().filter(|v| *v == ABSTAIN).count();
+    let cast_votes: Vec<&String> = votes.iter().filter(|v| *v != ABSTAIN).collect();
+    let mut tally = Tally {
+        eligible: ballot.eligible.len(),
+        cast: votes.len(),
+        abstained,
+        quorum: ballot.quorum(),
+        counts: Vec::new(),
+        rounds: Vec::new(),
+        winner: None,
+        summary: String::new(),
+    };
+
+    let (leader, detail) = match ballot.ballot_type.as_str() {
+        "yes_no" => {
+            let yes = cast_votes.iter().filter(|v| v.as_str() == "yes").count() as i64;
+            let no = cast_votes.iter().filter(|v| v.as_str() == "no").count() as i64;
+            tally.counts = vec![("Yes".to_string(), yes), ("No".to_string(), no), ("Abstain".to_string(), abstained as i64)];
+            let detail = format!("on '{}': {} yes, {} no, {} abstain", title(ballot.motion_coa_id), yes, no, abstained);
+            ((yes > no).then_some(ballot.motion_coa_id), detail)
+        }
+        "ranked" => {
+            let rankings: Vec<Vec<i64>> = cast_votes.iter()
+                .map(|v| v.split(',').filter_map(|id| id.parse().ok()).collect())
+                .collect();
+            let candidates: Vec<i64> = coas.iter().map(|(id, _)| *id).collect();
+            let (winner, rounds) = instant_runoff(&rankings, &candidates);
+            tally.rounds = rounds.iter()
+                .map(|round| round.iter().map(|(id, n)| (title(*id), *n)).collect())
+                .collect();
+            tally.counts = tally.rounds.last().cloned().unwrap_or_default();
+            let detail = format!(
+                "by instant runoff over {} round(s): {}",
+                rounds.len(),
+                tally.counts.iter().map(|(t, n)| format!("'{}' {}", t, n)).collect::<Vec<_>>().join(", ")
+            );
+            (winner, detail)
+        }
+        _ => {
+            let mut points: Vec<(i64, i64)> = coas.iter().map(|(id, _)| (*id, 0)).collect();
+            for vote in &cast_votes {
+                for pair in vote.split(',') {
+                    if let Some((id, p)) = pair.split_once(':')
+                        && let (Ok(id), Ok(p)) = (id.parse::<i64>(), p.parse::<i64>())
+                        && let Some(entry) = points.iter_mut().find(|(c, _)| *c == id)
+                    {
+                        entry.1 += p;
+                    }
+                }
+            }
+            tally.counts = points.iter().map(|(id, p)| (title(*id), *p)).collect();
+            let best = points.iter().map(|(_, p)| *p).max().unwrap_or(0);
+            let leaders: Vec<i64> = points.iter().filter(|(_, p)| *p == best).map(|(id, _)| *id).collect();
+            let detail = format!(
+                "by weighted points: {}",
+                tally.counts.iter().map(|(t, n)| format!("'{}' {}", t, n)).collect::<Vec<_>>().join(", ")
+            );
+            ((best > 0 && leaders.len() == 1).then(|| leaders[0]), detail)
+        }
+    };
+
+    let turnout = format!("{} of {} eligible voted, quorum {}", tally.cast, tally.eligible, tally.quorum);
+    let outcome = if !tally.quorum_met() {
+        "Quorum not met; no decision.".to_string()
+    } else if let Some(winner) = leader {
+        tally.winner = Some(winner);
+        format!("Carried: '{}'.", title(winner))
+    } else {
+        "Not carried; no decision.".to_string()
+    };
+    tally.summary = format!("{} ballot {} ({}). {}", ballot.type_label(), detail, turnout, outcome);
+    tally
+}
+
+async fn load(pool: &PgPool, id: i64) -> Result<Option<Ballot>, sqlx::Error> {
+    let Some(e) = entity::find_by_id(pool, id).await?.filter(|e| e.entity_type == "ballot") else {
+        return Ok(None);
+    };
+    let props = entity::get_properties(pool, e.id).await?;
+    let get = |key: &str| props.get(key).cloned().unwrap_or_default();
+    let int = |key: &str| props.get(key).and_then(|v| v.parse().ok()).unwrap_or(0);
+    Ok(Some(Ballot {
+        id: e.id,
+        agenda_point_id: int("agenda_point_id"),
+        ballot_type: get("ballot_type"),
+        status: get("status"),
+        motion_coa_id: int("motion_coa_id"),
+        quorum_pct: int("quorum_pct"),
+        eligible: serde_json::from_str(&get("eligible_voters")).unwrap_or_default(),
+        opened_by_id: int("opened_by_id"),
+        opened_at: get("opened_at"),
+        closed_at: get("closed_at"),
+        tally: props.get("tally").and_then(|json| serde_json::from_str(json).ok()),
+    }))
+}
+
+pub async fn find_by_id(pool: &PgPool, id: i64) -> Result<Option<Ballot>, sqlx::Error> {
+    load(pool, id).await
+}
+
+/// Ballots held on an agenda point, newest first.
+pub async fn find_for_agenda_point(pool: &PgPool, agenda_point_id: i64) -> Result<Vec<Ballot>, sqlx::Error> {
+    let ids: Vec<i64> = sqlx::query_scalar(
+        "SELECT e.id FROM entities e \
+         JOIN entity_properties p ON p.entity_id = e.id AND p.key = 'agenda_point_id' \
+         WHERE e.entity_type = 'ballot' AND p.value = $1 \
+         ORDER BY e.id DESC",
+    )
+    .bind(agenda_point_id.to_string())
+    .fetch_all(pool)
+    .await?;
+    let mut ballots = Vec::new();
+    for id in ids {
+        if let Some(ballot) = load(pool, id).await? {
+            ballots.push(ballot);
+        }
+    }
+    Ok(ballots)
+}
+
+/// Users holding a position in the ToR, who may vote on its ballots.
+pub async fn eligible_voters(pool: &PgPool, tor_id: i64) -> Result<Vec<i64>, sqlx::Error> {
+    let mut voters: Vec<i64> = tor::find_members(pool, tor_id).await?
+        .into_iter()
+        .filter_map(|m| m.holder_id)
+        .collect();
+    voters.sort();
+    voters.dedup();
+    Ok(voters)
+}
+
+/// The quorum percentage new ballots start with.
+pub async fn default_quorum_pct(pool: &PgPool) -> i64 {
+    setting::get_i64(pool, QUORUM_SETTING).await
+}
+
+/// Why a ballot of this type cannot be opened, if it cannot.
+pub fn validate_open(ballot_type: &str, motion_coa_id: i64, quorum_pct: i64, coa_ids: &[i64], eligible: usize) -> Vec<String> {
+    let mut errors = Vec::new();
+    if !BALLOT_TYPES.iter().any(|(key, _, _)| *key == ballot_type) {
+        errors.push("Choose a ballot type".to_string());
+    }
+    if coa_ids.is_empty() {
+        errors.push("Add courses of action before opening a ballot".to_string());
+    } else if ballot_type == "yes_no" && !coa_ids.contains(&motion_coa_id) {
+        errors.push("Choose the course of action to vote on".to_string());
+    } else if ballot_type != "yes_no" && coa_ids.len() < 2 {
+        errors.push("Ranked and weighted ballots need at least two courses of action".to_string());
+    }
+    if !(1..=100).contains(&quorum_pct) {
+        errors.push("Quorum must be between 1 and 100%".to_string());
+    }
+    if eligible == 0 {
+        errors.push("No members hold a position in this ToR, so nobody could vote".to_string());
+    }
+    errors
+}
+
+/// Open a ballot on an agenda point for the given voters.
+pub async fn open(
+    pool: &PgPool,
+    agenda_point_id: i64,
+    ballot_type: &str,
+    motion_coa_id: i64,
+    quorum_pct: i64,
+    eligible: &[i64],
+    opened_by: i64,
+) -> Result<i64, sqlx::Error> {
+    let name = entity::available_name(pool, "ballot", &format!("ballot_ap{}", agenda_point_id)).await?;
+    let id = entity::create(pool, "ballot", &name, &name).await?;
+    let now = chrono::Local::now().format("%Y-%m-%d %H:%M").to_string();
+    entity::set_properties(pool, id, &[
+        ("agenda_point_id", &agenda_point_id.to_string()),
+        ("ballot_type", ballot_type),
+        ("status", "open"),
+        ("motion_coa_id", &motion_coa_id.to_string()),
+        ("quorum_pct", &quorum_pct.to_string()),
+        ("eligible_voters", &serde_json::to_string(eligible).unwrap_or_else(|_| "[]".to_string())),
+        ("opened_by_id", &opened_by.to_string()),
+        ("opened_at", &now),
+    ]).await?;
+    Ok(id)
+}
+
+/// Record or replace a voter's choice on an open ballot.
+pub async fn cast(pool: &PgPool, ballot_id: i64, voter_id: i64, choice: &str) -> Result<(), sqlx::Error> {
+    let name = format!("ballot{}_voter{}", ballot_id, voter_id);
+    // One insert, so two quick submits cannot both create the vote
+    let id = match entity::create_if_absent(pool, "vote", &name, &name).await? {
+        Some(id) => id,
+        None => entity::find_by_type_and_name(pool, "vote", &name).await?
+            .ok_or(sqlx::Error::RowNotFound)?.id,
+    };
+    let now = chrono::Local::now().format("%Y-%m-%d %H:%M").to_string();
+    entity::set_properties(pool, id, &[
+        ("ballot_id", &ballot_id.to_string()),
+        ("voter_id", &voter_id.to_string()),
+        ("choice", choice),
+        ("cast_at", &now),
+    ]).await
+}
+
+/// Stored choices on a ballot as (voter id, choice), counting eligible voters only.
+pub async fn find_votes(pool: &PgPool, ballot: &Ballot) -> Result<Vec<(i64, String)>, sqlx::Error> {
+    let rows: Vec<(String, String)> = sqlx::query_as(
+        "SELECT p_voter.value, p_choice.value \
+         FROM entities e \
+         JOIN entity_properties p_ballot ON p_ballot.entity_id = e.id AND p_ballot.key = 'ballot_id' \
+         JOIN entity_properties p_voter ON p_voter.entity_id = e.id AND p_voter.key = 'voter_id' \
+         JOIN entity_properties p_choice ON p_choice.entity_id = e.id AND p_choice.key = 'choice' \
+         WHERE e.entity_type = 'vote' AND p_ballot.value = $1 \
+         ORDER BY e.id",
+    )
+    .bind(ballot.id.to_string())
+    .fetch_all(pool)
+    .await?;
+    Ok(rows.into_iter()
+        .filter_map(|(voter, choice)| voter.parse().ok().map(|v| (v, choice)))
+        .filter(|(voter, _)| ballot.is_eligible(*voter))
+        .collect())
+}
+
+/// Close a ballot and tally it. When it carries a course of action the
+/// decision is recorded by `closed_by` with the tally as its rationale.
+/// Returns the tally and the decision id, if one was recorded. A ballot that
+/// is already closed is refused, so its decision is recorded only once.
+pub async fn close(
+    pool: &PgPool,
+    ballot: &Ballot,
+    coas: &[(i64, String)],
+    closed_by: i64,
+) -> Result<(Tally, Option<i64>), AppError> {
+    let claimed = sqlx::query(
+        "UPDATE entity_properties SET value = 'closed' \
+         WHERE entity_id = $1 AND key = 'status' AND value = 'open'",
+    )
+    .bind(ballot.id)
+    .execute(pool)
+    .await?;
+    if claimed.rows_affected() == 0 {
+        return Err(AppError::PermissionDenied("This ballot is already closed".to_string()));
+    }
+
+    let votes: Vec<String> = find_votes(pool, ballot).await?.into_iter().map(|(_, choice)| choice).collect();
+    let result = tally(ballot, &votes, coas);
+    let tally_json = serde_json::to_string(&result).unwrap_or_else(|_| "{}".to_string());
+    let now = chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    entity::set_properties(pool, ballot.id, &[
+        ("closed_at", &now[..16]),
+        ("closed_by_id", &closed_by.to_string()),
+        ("tally", &tally_json),
+    ]).await?;
+
+    let Some(winner) = result.winner else {
+        return Ok((result, None));
+    };
+    let decision_id = opinion::record_decision(pool, ballot.agenda_point_id, closed_by, winner, &result.summary).await?;
+    entity::set_properties(pool, decision_id, &[
+        ("ballot_id", &ballot.id.to_string()),
+        ("ballot_tally", &tally_json),
+    ]).await?;
+    entity::set_properties(pool, ballot.agenda_point_id, &[
+        ("decided_by_id", &closed_by.to_string()),
+        ("decided_date", &now),
+        ("selected_coa_id", &winner.to_string()),
+    ]).await?;
+    Ok((result, Some(decision_id)))
+}
@@ -0,0 +1,456 @@
+//! Per-deployment branding: logo, colour palette and footer text.
+//!
+//! Stored as `setting` entities named `branding.<surface>.<field>`, kept off
+//! the generic settings page. The application surface is the base; the public
+//! portal (login page and shared views) and exports fall back to it field by
+//! field. Edits are saved as a draft (`branding.draft.<surface>.<field>`) that
+//! can be previewed, then published or discarded.
+//!
+//! The server caches stored values in memory and checks the `branding`
+//! generation in the database before serving them, so a write through any
+//! replica reaches every other one on its next read. Pages link the logo as `/branding/logo/<surface>` rather than
+//! inlining it, so browsers fetch it once per change.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+
+use super::{cache_generation, entity, setting};
+
+/// Surfaces that can be branded, as (key, label).
+pub const SURFACES: &[(&str, &str)] = &[
+    ("app", "Application"),
+    ("portal", "Public portal"),
+    ("export", "Exports"),
+];
+
+/// Largest accepted logo, as decoded image bytes.
+pub const MAX_LOGO_BYTES: usize = 256 * 1024;
+
+const LOGO_TYPES: &[&str] = &["image/png", "image/jpeg", "image/gif", "image/webp"];
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Branding {
+    /// Image as a `data:` URI, or its `/branding/logo` URL when loaded by
+    /// [`load_for_page`]; empty for none.
+    pub logo: String,
+    /// `#rgb` or `#rrggbb`; empty keeps the theme colour.
+    pub primary_color: String,
+    pub accent_color: String,
+    pub footer_text: String,
+}
+
+impl Branding {
+    /// These values, with empty fields taken from `base`.
+    pub fn or(self, base: &Branding) -> Branding {
+        let pick = |own: String, fallback: &String| if own.is_empty() { fallback.clone() } else { own };
+        Branding {
+            logo: pick(self.logo, &base.logo),
+            primary_color: pick(self.primary_color, &base.primary_color),
+            accent_color: pick(self.accent_color, &base.accent_color),
+            footer_text: pick(self.footer_text, &base.footer_text),
+        }
+    }
+
+    /// `<style>` overriding the theme's colour variables; empty when no colour
+    /// is set. Dark mode keeps its own primary for contrast and gets a
+    /// lightened accent.
+    pub fn style_tag(&self) -> String {
+        let mut light = String::new();
+        let mut dark = String::new();
+        if parse_color(&self.primary_color).is_some() {
+            light.push_str(&format!("--primary: {0}; --primary-hover: {0}; --nav-bg: {0}; ", self.primary_color));
+        }
+        if let Some((r, g, b)) = parse_color(&self.accent_color) {
+            light.push_str(&format!("--accent: {0}; --accent-hover: {0}; ", self.accent_color));
+            light.push_str(&format!("--accent-subtle: rgba({}, {}, {}, 0.08); ", r, g, b));
+            let (r, g, b) = lighten((r, g, b), DARK_LIGHTEN);
+            dark.push_str(&format!("--accent: {0}; --accent-hover: {0}; ", to_hex((r, g, b))));
+            dark.push_str(&format!("--accent-subtle: rgba({}, {}, {}, 0.12); ", r, g, b));
+        }
+        if light.is_empty() {
+            return String::new();
+        }
+        let mut style = format!("<style>:root:not(.dark) {{ {}}}", light);
+        if !dark.is_empty() {
+            style.push_str(&format!(" :root.dark {{ {}}}", dark));
+        }
+        style.push_str("</style>");
+        style
+    }
+
+    /// Colour of headings and rules in exported documents, for a light or
+    /// dark page.
+    pub fn document_color(&self, dark: bool) -> String {
+        match (parse_color(&self.primary_color), dark) {
+            (Some(_), false) => self.primary_color.clone(),
+            (Some(rgb), true) => to_hex(lighten(rgb, DARK_LIGHTEN)),
+            (None, false) => "#1c1917".to_string(),
+            (None, true) => "#e7e5e4".to_string(),
+        }
+    }
+}
+
+/// WCAG AA minimum contrast for body text.
+pub const MIN_CONTRAST: f32 = 4.5;
+
+/// A text/background pair from the brand palette, checked for readability.
+#[derive(Debug, Clone)]
+pub struct ContrastCheck {
+    pub label: &'static str,
+    pub foreground: String,
+    pub background: String,
+    pub ratio: f32,
+}
+
+impl ContrastCheck {
+    pub fn passes(&self) -> bool {
+        self.ratio >= MIN_CONTRAST
+    }
+
+    pub fn ratio_text(&self) -> String {
+        format!("{:.2}:1", self.ratio)
+    }
+}
+
+impl Branding {
+    /// Contrast of the theme's text colours against the brand colours, for
+    /// each colour that is set. Dark mode is checked with the lightened accent.
+    pub fn contrast_checks(&self) -> Vec<ContrastCheck> {
+        let mut pairs: Vec<(&'static str, String, String)> = Vec::new();
+        if let Some(primary) = parse_color(&self.primary_color) {
+            let primary = to_hex(primary);
+            pairs.push(("Navigation text on primary", "#a8a29e".to_string(), primary.clone()));
+            pairs.push(("Button text on primary", "#ffffff".to_string(), primary));
+        }
+        if let Some(accent) = parse_color(&self.accent_color) {
+            pairs.push(("Links on page surface", to_hex(accent), "#ffffff".to_string()));
+            pairs.push(("Links on dark surface", to_hex(lighten(accent, DARK_LIGHTEN)), "#2d2723".to_string()));
+        }
+        pairs.into_iter()
+            .filter_map(|(label, foreground, background)| {
+                let ratio = contrast_ratio(&foreground, &background)?;
+                Some(ContrastCheck { label, foreground, background, ratio })
+            })
+            .collect()
+    }
+}
+
+/// WCAG contrast ratio of two hex colours, from 1 to 21.
+pub fn contrast_ratio(foreground: &str, background: &str) -> Option<f32> {
+    let a = luminance(parse_color(foreground)?);
+    let b = luminance(parse_color(background)?);
+    let (light, dark) = if a > b { (a, b) } else { (b, a) };
+    Some((light + 0.05) / (dark + 0.05))
+}
+
+fn luminance((r, g, b): (u8, u8, u8)) -> f32 {
+    let linear = |c: u8| {
+        let c = c as f32 / 255.0;
+        if c <= 0.039_28 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) }
+    };
+    0.2126 * linear(r) + 0.7152 * linear(g) + 0.0722 * linear(b)
+}
+
+/// How far brand colours are mixed toward white for dark backgrounds.
+const DARK_LIGHTEN: f32 = 0.45;
+
+fn lighten((r, g, b): (u8, u8, u8), amount: f32) -> (u8, u8, u8) {
+    let mix = |c: u8| (c as f32 + (255.0 - c as f32) * amount).round() as u8;
+    (mix(r), mix(g), mix(b))
+}
+
+fn to_hex((r, g, b): (u8, u8, u8)) -> String {
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+/// Split `#rgb` / `#rrggbb` into its components.
+fn parse_color(value: &str) -> Option<(u8, u8, u8)> {
+    let hex = value.strip_prefix('#')?;
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let channel = |s: &str| u8::from_str_radix(s, 16).ok();
+    match hex.len() {
+        3 => {
+            let double = |i: usize| channel(&hex[i..i + 1].repeat(2));
+            Some((double(0)?, double(1)?, double(2)?))
+        }
+        6 => Some((channel(&hex[0..2])?, channel(&hex[2..4])?, channel(&hex[4..6])?)),
+        _ => None,
+    }
+}
+
+const DRAFT_PREFIX: &str = "branding.draft.";
+
+fn setting_name(surface: &str, field: &str, draft: bool) -> String {
+    if draft {
+        format!("{}{}.{}", DRAFT_PREFIX, surface, field)
+    } else {
+        format!("branding.{}.{}", surface, field)
+    }
+}
+
+fn surface_label(surface: &str) -> &str {
+    SURFACES.iter()
+        .find(|(key, _)| *key == surface)
+        .map(|(_, label)| *label)
+        .unwrap_or(surface)
+}
+
+/// Stored branding values keyed by setting name, with a fingerprint of each
+/// non-empty logo.
+struct Stored {
+    values: HashMap<String, String>,
+    logo_tags: HashMap<String, String>,
+}
+
+/// Name of the branding row in `cache_generations`, bumped on every write.
+const GENERATION: &str = "branding";
+
+static CACHE_ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Stored values, tagged with the generation they were read at.
+static CACHE: RwLock<Option<(i64, Arc<Stored>)>> = RwLock::new(None);
+
+/// Keep stored values in memory between requests. The server turns this on
+/// at startup; each read still checks the generation in the database.
+pub fn enable_cache() {
+    CACHE_ENABLED.store(true, Ordering::SeqCst);
+}
+
+/// Drop the cached values on every replica; called after every write.
+async fn invalidate(pool: &PgPool) {
+    if let Err(e) = cache_generation::bump(pool, GENERATION).await {
+        log::error!("Failed to bump branding generation: {}", e);
+    }
+}
+
+fn logo_tag(logo: &str) -> String {
+    hex::encode(&Sha256::digest(logo.as_bytes())[..8])
+}
+
+/// Stored branding values, from the cache when it is current.
+async fn load_values(pool: &PgPool) -> Arc<Stored> {
+    let enabled = CACHE_ENABLED.load(Ordering::SeqCst);
+    // Read before the values, so a read that raced a write is cached under
+    // the older generation and reloaded next time.
+    let generation = if enabled { cache_generation::current(pool, GENERATION).await.ok() } else { None };
+    if let Some(generation) = generation
+        && let Ok(cache) = CACHE.read()
+        && let Some((cached, stored)) = cache.as_ref()
+        && *cached == generation
+    {
+        return stored.clone();
+    }
+
+    let rows: Vec<(String, String)> = sqlx::query_as(
+        "SELECT e.name, COALESCE(p.value, '') \
+         FROM entities e \
+         LEFT JOIN entity_properties p ON e.id = p.entity_id AND p.key = 'value' \
+         WHERE e.entity_type = 'setting' AND e.name LIKE 'branding.%'"
+    )
+    .fetch_all(pool)
+    .await
+    .unwrap_or_default();
+    let values: HashMap<String, String> = rows.into_iter().collect();
+    let logo_tags = values.iter()
+        .filter(|(name, value)| name.ends_with(".logo") && !value.is_empty())
+        .map(|(name, value)| (name.clone(), logo_tag(value)))
+        .collect();
+    let stored = Arc::new(Stored { values, logo_tags });
+
+    if let Some(generation) = generation
+        && let Ok(mut cache) = CACHE.write()
+    {
+        *cache = Some((generation, stored.clone()));
+    }
+    stored
+}
+
+fn from_values(values: &HashMap<String, String>, surface: &str, draft: bool) -> Branding {
+    let get = |field: &str| values.get(&setting_name(surface, field, draft)).cloned().unwrap_or_default();
+    Branding {
+        logo: get("logo"),
+        primary_color: get("primary_color"),
+        accent_color: get("accent_color"),
+        footer_text: get("footer_text"),
+    }
+}
+
+fn has_draft_values(values: &HashMap<String, String>) -> bool {
+    values.keys().any(|name| name.starts_with(DRAFT_PREFIX))
+}
+
+fn resolve(values: &HashMap<String, String>, surface: &str, draft: bool) -> Branding {
+    let own = from_values(values, surface, draft);
+    if surface == "app" {
+        return own;
+    }
+    own.or(&from_values(values, "app", draft))
+}
+
+/// Published branding in effect on a surface, falling back to the application's.
+pub async fn load(pool: &PgPool, surface: &str) -> Branding {
+    resolve(&load_values(pool).await.values, surface, false)
+}
+
+/// Fingerprint of the published logo in effect on a surface.
+fn published_logo_tag(stored: &Stored, surface: &str) -> Option<String> {
+    [surface, "app"].iter()
+        .find_map(|s| stored.logo_tags.get(&setting_name(s, "logo", false)))
+        .cloned()
+}
+
+/// Published branding for a rendered page: as [`load`], with the logo given
+/// as its versioned `/branding/logo/<surface>` URL.
+pub async fn load_for_page(pool: &PgPool, surface: &str) -> Branding {
+    let stored = load_values(pool).await;
+    let mut branding = resolve(&stored.values, surface, false);
+    if let Some(tag) = published_logo_tag(&stored, surface) {
+        branding.logo = format!("/branding/logo/{}?v={}", surface, tag);
+    }
+    branding
+}
+
+/// A published logo, decoded for serving.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Logo {
+    pub media_type: String,
+    pub bytes: Vec<u8>,
+    /// Changes whenever the image does; used as ETag and URL version.
+    pub tag: String,
+}
+
+/// Published logo in effect on a surface; None for an unknown surface or
+/// when no logo is set.
+pub async fn load_logo(pool: &PgPool, surface: &str) -> Option<Logo> {
+    if !SURFACES.iter().any(|(key, _)| *key == surface) {
+        return None;
+    }
+    let stored = load_values(pool).await;
+    let tag = published_logo_tag(&stored, surface)?;
+    let logo = resolve(&stored.values, surface, false).logo;
+    let (media_type, data) = logo.strip_prefix("data:")?.split_once(";base64,")?;
+    let bytes = STANDARD.decode(data).ok()?;
+    Some(Logo { media_type: media_type.to_string(), bytes, tag })
+}
+
+/// Branding a surface would have once the draft is published; the published
+/// branding when there is no draft.
+pub async fn load_draft(pool: &PgPool, surface: &str) -> Branding {
+    let stored = load_values(pool).await;
+    resolve(&stored.values, surface, has_draft_values(&stored.values))
+}
+
+/// Whether there is an unpublished draft.
+pub async fn has_draft(pool: &PgPool) -> bool {
+    has_draft_values(&load_values(pool).await.values)
+}
+
+/// Values being edited on each surface (the draft if any, else the published
+/// values), without fallback, in `SURFACES` order.
+pub async fn load_all(pool: &PgPool) -> Vec<(&'static str, &'static str, Branding)> {
+    let stored = load_values(pool).await;
+    let draft = has_draft_values(&stored.values);
+    SURFACES.iter()
+        .map(|(key, label)| (*key, *label, from_values(&stored.values, key, draft)))
+        .collect()
+}
+
+/// Check colours and logo of one surface. Returns a list of problems.
+pub fn validate(surface: &str, branding: &Branding) -> Vec<String> {
+    let label = surface_label(surface);
+    let mut errors = Vec::new();
+    for (name, value) in [("Primary colour", &branding.primary_color), ("Accent colour", &branding.accent_color)] {
+        if !value.is_empty() && parse_color(value).is_none() {
+            errors.push(format!("{}: {} must be a hex colour such as #b45309", label, name));
+        }
+    }
+    if !branding.logo.is_empty() {
+        let media_type = branding.logo.strip_prefix("data:")
+            .and_then(|rest| rest.split_once(";base64,"))
+            .map(|(media_type, _)| media_type);
+        match media_type {
+            Some(t) if LOGO_TYPES.contains(&t) => {
+                if branding.logo.len() > MAX_LOGO_BYTES * 4 / 3 + 64 {
+                    errors.push(format!("{}: logo must be at most {} KB", label, MAX_LOGO_BYTES / 1024));
+                }
+            }
+            _ => errors.push(format!("{}: logo must be a PNG, JPEG, GIF or WebP image", label)),
+        }
+    }
+    if branding.footer_text.chars().count() > 500 {
+        errors.push(format!("{}: footer text must be at most 500 characters", label));
+    }
+    errors
+}
+
+/// Publish one surface's branding, creating its settings on first use.
+pub async fn save(pool: &PgPool, surface: &str, branding: &Branding) -> Result<(), sqlx::Error> {
+    store(pool, surface, branding, false).await
+}
+
+/// Save one surface's branding as a draft for preview.
+pub async fn save_draft(pool: &PgPool, surface: &str, branding: &Branding) -> Result<(), sqlx::Error> {
+    store(pool, surface, branding, true).await
+}
+
+/// Publish the draft of every surface and drop it. Returns false when there
+/// was no draft.
+pub async fn publish(pool: &PgPool) -> Result<bool, sqlx::Error> {
+    let stored = load_values(pool).await;
+    if !has_draft_values(&stored.values) {
+        return Ok(false);
+    }
+    for (key, _) in SURFACES {
+        save(pool, key, &from_values(&stored.values, key, true)).await?;
+    }
+    discard_draft(pool).await?;
+    Ok(true)
+}
+
+/// Drop the draft, keeping the published branding.
+pub async fn discard_draft(pool: &PgPool) -> Result<(), sqlx::Error> {
+    let result = sqlx::query("DELETE FROM entities WHERE entity_type = 'setting' AND name LIKE 'branding.draft.%'")
+        .execute(pool)
+        .await;
+    invalidate(pool).await;
+    result.map(|_| ())
+}
+
+/// Write one surface's values. The cache is dropped even when a write fails
+/// part way, since earlier fields may already have changed.
+async fn store(pool: &PgPool, surface: &str, branding: &Branding, draft: bool) -> Result<(), sqlx::Error> {
+    let result = store_fields(pool, surface, branding, draft).await;
+    invalidate(pool).await;
+    result
+}
+
+async fn store_fields(pool: &PgPool, surface: &str, branding: &Branding, draft: bool) -> Result<(), sqlx::Error> {
+    let label = surface_label(surface);
+    for (field, field_label, value) in [
+        ("logo", "logo", &branding.logo),
+        ("primary_color", "primary colour", &branding.primary_color),
+        ("accent_color", "accent colour", &branding.accent_color),
+        ("footer_text", "footer text", &branding.footer_text),
+    ] {
+        let name = setting_name(surface, field, draft);
+        let id = match entity::find_by_type_and_name(pool, "setting", &name).await? {
+            Some(existing) => existing.id,
+            None => {
+                let suffix = if draft { " (draft)" } else { "" };
+                let id = entity::create(pool, "setting", &name, &format!("{} {}{}", label, field_label, suffix)).await?;
+                entity::set_property(pool, id, "setting_type", "text").await?;
+                id
+            }
+        };
+        setting::update_value(pool, id, value).await?;
+    }
+    Ok(())
+}
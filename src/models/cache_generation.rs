@@ -0,0 +1,31 @@
+//! Database-held generation counters for in-memory caches.
+//!
+//! The server runs as several replicas against one database, so a cache
+//! cannot be invalidated by touching process memory alone. Writers [`bump`]
+//! the cache's counter; readers compare their cached generation against
+//! [`current`] before serving and reload when it moved.
+
+use sqlx::PgPool;
+
+/// The generation of the named cache; 1 until it is first bumped.
+pub async fn current(pool: &PgPool, name: &str) -> Result<i64, sqlx::Error> {
+    let generation: Option<i64> = sqlx::query_scalar(
+        "SELECT generation FROM cache_generations WHERE name = $1",
+    )
+    .bind(name)
+    .fetch_optional(pool)
+    .await?;
+    Ok(generation.unwrap_or(1))
+}
+
+/// Advance the named cache's generation. Returns the new generation.
+pub async fn bump(pool: &PgPool, name: &str) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar(
+        "INSERT INTO cache_generations (name, generation) VALUES ($1, 2) \
+         ON CONFLICT (name) DO UPDATE SET generation = cache_generations.generation + 1 \
+         RETURNING generation",
+    )
+    .bind(name)
+    .fetch_one(pool)
+    .await
+}
@@ -0,0 +1,324 @@
+//! Release notes for upgrades.
+//!
+//! Every applied database migration and every feature setting switched on
+//! is recorded once as a `changelog_entry`. Admins see entries newer than
+//! the last one they dismissed in a "what changed" panel on the dashboard,
+//! and the full history on `/changelog`. Migrations whose note is flagged
+//! `governance` also raise a warning, since they change how decisions,
+//! minutes or memberships behave.
+//!
+//! Entries recorded when the changelog is first populated describe the
+//! installation rather than an upgrade; they are kept as `baseline` and not
+//! shown in the panel.
+
+use chrono::Local;
+use sqlx::PgPool;
+
+use super::{entity, setting};
+
+/// Release note for a migration, matched on its version.
+#[derive(Debug, Clone, Copy)]
+pub struct ReleaseNote {
+    pub version: i64,
+    pub title: &'static str,
+    pub description: &'static str,
+    /// The migration changes governance behaviour, not just storage.
+    pub governance: bool,
+}
+
+/// Notes for the migrations in `migrations/`. Add one with every migration
+/// that users or admins would notice.
+pub const MIGRATION_NOTES: &[ReleaseNote] = &[
+    ReleaseNote {
+        version: 20260221,
+        title: "Initial schema",
+        description: "Entities, properties and relations, with the audit log and warning tables.",
+        governance: false,
+    },
+    ReleaseNote {
+        version: 20261016,
+        title: "Change tracking",
+        description: "Entities and relations record when they last changed and deletions leave tombstones, so data exports can deliver only what changed.",
+        governance: false,
+    },
+    ReleaseNote {
+        version: 20261017,
+        title: "Domain events",
+        description: "Proposal, meeting and decision changes are recorded as events that integrations can read from the API.",
+        governance: false,
+    },
+    ReleaseNote {
+        version: 20261018,
+        title: "Notification outbox",
+        description: "Notifications are queued with the change that raised them and retried until delivered.",
+        governance: false,
+    },
+    ReleaseNote {
+        version: 20261019,
+        title: "Form drafts",
+        description: "Long forms are saved as drafts while being edited and offered again after the session expires.",
+        governance: false,
+    },
+    ReleaseNote {
+        version: 20261020,
+        title: "Signing keys",
+        description: "Signatures use server-held keys that can be rotated with a grace period.",
+        governance: false,
+    },
+    ReleaseNote {
+        version: 20261021,
+        title: "SLA compliance",
+        description: "ToRs are measured nightly against their SLA policy pack for review, decision and minutes turnaround.",
+        governance: true,
+    },
+    ReleaseNote {
+        version: 20261022,
+        title: "Read audit log",
+        description: "Views of classified ToR items are recorded while read auditing is on.",
+        governance: false,
+    },
+    ReleaseNote {
+        version: 20261023,
+        title: "Trash",
+        description: "Deleted users, proposals and ToRs go to the trash and can be restored until they are purged.",
+        governance: true,
+    },
+    ReleaseNote {
+        version: 20261024,
+        title: "Revisions",
+        description: "Minutes section revisions become general revisions that keep every tracked field of an edit.",
+        governance: true,
+    },
+    ReleaseNote {
+        version: 20261025,
+        title: "Sign-in devices",
+        description: "Users are told when they sign in from a device or address not seen before.",
+        governance: false,
+    },
+    ReleaseNote {
+        version: 20261026,
+        title: "Password history",
+        description: "The password policy can refuse recently used passwords and expire passwords after a maximum age.",
+        governance: true,
+    },
+    ReleaseNote {
+        version: 20261027,
+        title: "Account lockout",
+        description: "Accounts are locked after repeated failed sign-ins until the lock expires or an admin unlocks them.",
+        governance: true,
+    },
+    ReleaseNote {
+        version: 20261028,
+        title: "Status change times",
+        description: "The time of each status change is recorded, so workflow transitions can be taken automatically after a number of days.",
+        governance: true,
+    },
+    ReleaseNote {
+        version: 20261029,
+        title: "Shared cache generations",
+        description: "Branding, menu and permission changes reach every server replica on the next request, not only the one that made them.",
+        governance: false,
+    },
+];
+
+/// Descriptions of feature settings, as (setting name, description).
+/// Boolean settings without one are recorded with their label only.
+pub const FEATURE_NOTES: &[(&str, &str)] = &[
+    ("audit.enabled", "Changes are written to the audit log and its files."),
+    ("ticketing.enabled", "Minutes action items can be linked to issues in the external ticketing system."),
+    ("a11y.debug", "Pages show accessibility debugging outlines."),
+];
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct ChangelogEntry {
+    pub id: i64,
+    pub title: String,
+    /// `migration` or `feature`.
+    pub kind: String,
+    /// Migration version or setting name.
+    pub reference: String,
+    pub description: String,
+    pub governance: bool,
+    pub baseline: bool,
+    pub recorded_at: String,
+}
+
+impl ChangelogEntry {
+    pub fn kind_label(&self) -> &'static str {
+        match self.kind.as_str() {
+            "migration" => "Upgrade",
+            _ => "Feature enabled",
+        }
+    }
+}
+
+const SELECT_ENTRIES: &str =
+    "SELECT e.id, e.label AS title, \
+            COALESCE(p_kind.value, '') AS kind, \
+            COALESCE(p_ref.value, '') AS reference, \
+            COALESCE(p_desc.value, '') AS description, \
+            COALESCE(p_gov.value = 'true', false) AS governance, \
+            COALESCE(p_base.value = 'true', false) AS baseline, \
+            COALESCE(p_at.value, '') AS recorded_at \
+     FROM entities e \
+     LEFT JOIN entity_properties p_kind ON e.id = p_kind.entity_id AND p_kind.key = 'kind' \
+     LEFT JOIN entity_properties p_ref ON e.id = p_ref.entity_id AND p_ref.key = 'reference' \
+     LEFT JOIN entity_properties p_desc ON e.id = p_desc.entity_id AND p_desc.key = 'description' \
+     LEFT JOIN entity_properties p_gov ON e.id = p_gov.entity_id AND p_gov.key = 'governance' \
+     LEFT JOIN entity_properties p_base ON e.id = p_base.entity_id AND p_base.key = 'baseline' \
+     LEFT JOIN entity_properties p_at ON e.id = p_at.entity_id AND p_at.key = 'recorded_at' \
+     WHERE e.entity_type = 'changelog_entry'";
+
+#[allow(clippy::too_many_arguments)]
+async fn create_entry(
+    pool: &PgPool,
+    name: &str,
+    title: &str,
+    kind: &str,
+    reference: &str,
+    description: &str,
+    governance: bool,
+    baseline: bool,
+) -> Result<i64, sqlx::Error> {
+    let name = entity::available_name(pool, "changelog_entry", name).await?;
+    let id = entity::create(pool, "changelog_entry", &name, title).await?;
+    entity::set_properties(pool, id, &[
+        ("kind", kind),
+        ("reference", reference),
+        ("description", description),
+        ("governance", if governance { "true" } else { "false" }),
+        ("baseline", if baseline { "true" } else { "false" }),
+        ("recorded_at", &Local::now().format("%Y-%m-%d %H:%M").to_string()),
+    ]).await?;
+    Ok(id)
+}
+
+async fn find_by_id(pool: &PgPool, id: i64) -> Result<ChangelogEntry, sqlx::Error> {
+    sqlx::query_as::<_, ChangelogEntry>(&format!("{SELECT_ENTRIES} AND e.id = $1"))
+        .bind(id)
+        .fetch_one(pool)
+        .await
+}
+
+/// Record successfully applied migrations that have no entry yet, titled
+/// from `notes` or the migration's own description. Returns the new
+/// entries; governance-relevant ones also raise a warning for admins.
+pub async fn record_migrations(pool: &PgPool, notes: &[ReleaseNote]) -> Result<Vec<ChangelogEntry>, sqlx::Error> {
+    let applied: Vec<(i64, String)> = sqlx::query_as(
+        "SELECT version, description FROM _sqlx_migrations WHERE success ORDER BY version",
+    )
+    .fetch_all(pool)
+    .await?;
+    let recorded: Vec<String> = sqlx::query_scalar(
+        "SELECT p.value FROM entities e \
+         JOIN entity_properties p ON e.id = p.entity_id AND p.key = 'reference' \
+         WHERE e.entity_type = 'changelog_entry'",
+    )
+    .fetch_all(pool)
+    .await?;
+    let baseline = recorded.is_empty();
+
+    let mut entries = Vec::new();
+    for (version, description) in applied {
+        let reference = version.to_string();
+        if recorded.contains(&reference) {
+            continue;
+        }
+        let note = notes.iter().find(|n| n.version == version);
+        let title = note.map(|n| n.title.to_string()).unwrap_or_else(|| description.replace('_', " "));
+        let id = create_entry(
+            pool,
+            &format!("migration.{}", version),
+            &title,
+            "migration",
+            &reference,
+            note.map(|n| n.description).unwrap_or(""),
+            note.is_some_and(|n| n.governance),
+            baseline,
+        ).await?;
+        let entry = find_by_id(pool, id).await?;
+        if entry.governance && !baseline {
+            warn_admins(pool, &entry).await?;
+        }
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+async fn warn_admins(pool: &PgPool, entry: &ChangelogEntry) -> Result<(), sqlx::Error> {
+    let admins = crate::warnings::get_users_with_permission(pool, "settings.manage").await?;
+    if admins.is_empty() {
+        return Ok(());
+    }
+    let message = format!("Upgrade changed governance behaviour: {}", entry.title);
+    let details = serde_json::json!({
+        "changelog_entry_id": entry.id,
+        "migration": entry.reference,
+        "description": entry.description,
+    });
+    let wid = crate::warnings::create_warning(
+        pool, "medium", "governance", "event.system.migration", &message, &details.to_string(), "system",
+    ).await?;
+    crate::warnings::create_receipts(pool, wid, &admins).await?;
+    Ok(())
+}
+
+/// Record a boolean setting switched on. Other changes are ignored.
+pub async fn record_setting_change(
+    pool: &PgPool,
+    change: &setting::AppliedChange,
+) -> Result<Option<ChangelogEntry>, sqlx::Error> {
+    let is_flag = setting::definition(&change.name).is_some_and(|d| d.kind == setting::SettingKind::Bool);
+    if !is_flag || change.new_value != "true" || change.old_value == "true" {
+        return Ok(None);
+    }
+    let description = FEATURE_NOTES.iter()
+        .find(|(name, _)| *name == change.name)
+        .map(|(_, d)| *d)
+        .unwrap_or("");
+    let id = create_entry(
+        pool,
+        &format!("feature.{}", change.name),
+        &change.label,
+        "feature",
+        &change.name,
+        description,
+        false,
+        false,
+    ).await?;
+    Ok(Some(find_by_id(pool, id).await?))
+}
+
+/// All entries, newest first.
+pub async fn find_all(pool: &PgPool) -> Result<Vec<ChangelogEntry>, sqlx::Error> {
+    sqlx::query_as::<_, ChangelogEntry>(&format!("{SELECT_ENTRIES} ORDER BY e.id DESC"))
+        .fetch_all(pool)
+        .await
+}
+
+/// Entries newer than the last one the user dismissed, newest first.
+/// Baseline entries are never unseen.
+pub async fn find_unseen(pool: &PgPool, user_id: i64) -> Result<Vec<ChangelogEntry>, sqlx::Error> {
+    let seen: i64 = entity::get_property(pool, user_id, "changelog_seen").await?
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0);
+    sqlx::query_as::<_, ChangelogEntry>(&format!(
+        "{SELECT_ENTRIES} AND e.id > $1 AND COALESCE(p_base.value, 'false') <> 'true' ORDER BY e.id DESC"
+    ))
+    .bind(seen)
+    .fetch_all(pool)
+    .await
+}
+
+/// Dismiss every current entry for the user.
+pub async fn mark_seen(pool: &PgPool, user_id: i64) -> Result<(), sqlx::Error> {
+    let latest: Option<i64> = sqlx::query_scalar(
+        "SELECT MAX(id) FROM entities WHERE entity_type = 'changelog_entry'",
+    )
+    .fetch_one(pool)
+    .await?;
+    if let Some(latest) = latest {
+        entity::set_property(pool, user_id, "changelog_seen", &latest.to_string()).await?;
+    }
+    Ok(())
+}
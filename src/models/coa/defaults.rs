@@ -0,0 +1,153 @@
+//! Default COA sets.
+//!
+//! Admins keep a set of `default_coa` entities per agenda item type (e.g.
+//! Approve / Approve with conditions / Reject / Defer for decisions), each
+//! with boilerplate sections stored as JSON in `sections`. When a proposal is
+//! scheduled as a decision agenda point, the set is copied into real COAs
+//! linked with `considers_coa`; the copies are ordinary COAs whose sections
+//! can be edited per agenda point. A proposal template's own default COAs
+//! take precedence over the set.
+
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+
+use crate::errors::AppError;
+use crate::models::{entity, proposal, relation};
+
+pub const ENTITY_TYPE: &str = "default_coa";
+
+/// A section a generated COA starts with.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BoilerplateSection {
+    pub title: String,
+    #[serde(default)]
+    pub content: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct DefaultCoa {
+    pub id: i64,
+    pub item_type: String,
+    pub title: String,
+    pub description: String,
+    pub sections: Vec<BoilerplateSection>,
+}
+
+/// Fields submitted from the default COA form.
+pub struct DefaultCoaInput<'a> {
+    pub title: &'a str,
+    pub description: &'a str,
+    pub sections: Vec<BoilerplateSection>,
+}
+
+#[derive(sqlx::FromRow)]
+struct DefaultCoaRow {
+    id: i64,
+    label: String,
+    item_type: String,
+    description: String,
+    sections: String,
+}
+
+impl From<DefaultCoaRow> for DefaultCoa {
+    fn from(row: DefaultCoaRow) -> Self {
+        DefaultCoa {
+            id: row.id,
+            item_type: row.item_type,
+            title: row.label,
+            description: row.description,
+            sections: serde_json::from_str(&row.sections).unwrap_or_default(),
+        }
+    }
+}
+
+const SELECT: &str =
+    "SELECT e.id, e.label, \
+            COALESCE(p_type.value, '') AS item_type, \
+            COALESCE(p_desc.value, '') AS description, \
+            COALESCE(p_sections.value, '[]') AS sections \
+     FROM entities e \
+     LEFT JOIN entity_properties p_type ON e.id = p_type.entity_id AND p_type.key = 'item_type' \
+     LEFT JOIN entity_properties p_desc ON e.id = p_desc.entity_id AND p_desc.key = 'description' \
+     LEFT JOIN entity_properties p_sections ON e.id = p_sections.entity_id AND p_sections.key = 'sections' \
+     WHERE e.entity_type = 'default_coa'";
+
+/// The default COAs for an agenda item type, in order.
+pub async fn find_set(pool: &PgPool, item_type: &str) -> Result<Vec<DefaultCoa>, sqlx::Error> {
+    let rows = sqlx::query_as::<_, DefaultCoaRow>(&format!("{SELECT} AND p_type.value = $1 ORDER BY e.sort_order, e.id"))
+        .bind(item_type)
+        .fetch_all(pool)
+        .await?;
+    Ok(rows.into_iter().map(DefaultCoa::from).collect())
+}
+
+pub async fn find_by_id(pool: &PgPool, id: i64) -> Result<Option<DefaultCoa>, sqlx::Error> {
+    let row = sqlx::query_as::<_, DefaultCoaRow>(&format!("{SELECT} AND e.id = $1"))
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+    Ok(row.map(DefaultCoa::from))
+}
+
+/// Validate form input against the rest of the set; `existing_id` is the
+/// default COA being edited, if any.
+pub fn validate(input: &DefaultCoaInput<'_>, set: &[DefaultCoa], existing_id: Option<i64>) -> Vec<String> {
+    let mut errors = Vec::new();
+    let title = input.title.trim();
+    if title.is_empty() {
+        errors.push("Title is required".to_string());
+    } else if title.chars().count() > 200 {
+        errors.push("Title must be at most 200 characters".to_string());
+    } else if set.iter().any(|c| Some(c.id) != existing_id && c.title.eq_ignore_ascii_case(title)) {
+        errors.push(format!("The set already has a course of action titled '{}'", title));
+    }
+    if input.sections.iter().any(|s| s.title.trim().is_empty()) {
+        errors.push("Every boilerplate section needs a title".to_string());
+    }
+    errors
+}
+
+async fn save_fields(pool: &PgPool, id: i64, input: &DefaultCoaInput<'_>) -> Result<(), sqlx::Error> {
+    let sections = serde_json::to_string(&input.sections).unwrap_or_else(|_| "[]".to_string());
+    entity::set_properties(pool, id, &[
+        ("description", input.description.trim()),
+        ("sections", &sections),
+    ]).await
+}
+
+/// Add a COA at the end of an item type's set.
+pub async fn create(pool: &PgPool, item_type: &str, input: &DefaultCoaInput<'_>) -> Result<i64, sqlx::Error> {
+    let base = format!("{}_{}", item_type, proposal::section_key(input.title));
+    let name = entity::available_name(pool, ENTITY_TYPE, &base).await?;
+    let sort_order = entity::count_by_type(pool, ENTITY_TYPE).await? + 1;
+    let id = entity::create_with_sort(pool, ENTITY_TYPE, &name, input.title.trim(), sort_order).await?;
+    entity::set_property(pool, id, "item_type", item_type).await?;
+    save_fields(pool, id, input).await?;
+    Ok(id)
+}
+
+pub async fn update(pool: &PgPool, coa: &DefaultCoa, input: &DefaultCoaInput<'_>) -> Result<(), sqlx::Error> {
+    let current = entity::find_by_id(pool, coa.id).await?.map(|e| e.name).unwrap_or_default();
+    entity::update(pool, coa.id, &current, input.title.trim()).await?;
+    save_fields(pool, coa.id, input).await
+}
+
+pub async fn delete(pool: &PgPool, id: i64) -> Result<(), sqlx::Error> {
+    entity::delete(pool, id).await
+}
+
+/// Create COAs for an agenda point from its item type's default set.
+/// COAs with boilerplate sections are created as complex COAs.
+pub async fn generate(pool: &PgPool, agenda_point_id: i64, item_type: &str, user_id: i64) -> Result<Vec<i64>, AppError> {
+    let mut ids = Vec::new();
+    for default in find_set(pool, item_type).await? {
+        let coa_type = if default.sections.is_empty() { "simple" } else { "complex" };
+        let coa_id = super::create(pool, &default.title, &default.description, coa_type, user_id).await?;
+        for (i, section) in default.sections.iter().enumerate() {
+            super::add_section(pool, coa_id, &section.title, &section.content, i as i32 + 1).await?;
+        }
+        relation::create(pool, "considers_coa", agenda_point_id, coa_id).await?;
+        ids.push(coa_id);
+    }
+    Ok(ids)
+}
@@ -1,6 +1,7 @@
 pub mod types;
 pub mod queries;
 pub mod sections;
+pub mod defaults;
 
 pub use types::*;
 pub use queries::*;
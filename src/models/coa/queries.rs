@@ -1,6 +1,6 @@
 use sqlx::PgPool;
 use crate::errors::AppError;
-use crate::models::{entity, relation};
+use crate::models::{entity, relation, revision};
 use super::types::*;
 use super::sections;
 
@@ -132,13 +132,16 @@ pub async fn create(
     Ok(coa_id)
 }
 
-/// Update COA title and description.
+/// Update COA title and description, keeping the previous ones as a revision.
 pub async fn update(
     pool: &PgPool,
     id: i64,
     title: &str,
     description: &str,
+    edited_by: i64,
 ) -> Result<(), AppError> {
+    revision::record(pool, id, &[("title", title), ("description", description)], edited_by)
+        .await.map_err(AppError::Db)?;
     entity::set_property(pool, id, "title", title).await.map_err(AppError::Db)?;
     entity::set_property(pool, id, "description", description).await.map_err(AppError::Db)?;
     Ok(())
@@ -1,5 +1,5 @@
 use sqlx::PgPool;
-use crate::models::tor;
+use crate::models::{delegation, tor};
 
 // Re-export UserTorMembership as the canonical type for dashboard consumers
 pub use crate::models::tor::types::UserTorMembership;
@@ -24,6 +24,19 @@ pub struct PendingItems {
     pub unread_warnings: Vec<PendingWarning>,
     pub pending_proposals: Vec<PendingProposal>,
     pub open_suggestions: Vec<PendingSuggestion>,
+    /// Absent members whose reviews and decisions are routed to the user.
+    pub acting_for: Vec<delegation::OutOfOffice>,
+}
+
+/// Headline counters, pushed to open dashboards as they change.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize)]
+pub struct DashboardCounts {
+    /// Proposals submitted or under review in the user's ToRs, or those of
+    /// members the user stands in for.
+    pub pending_reviews: i64,
+    pub unread_warnings: i64,
+    /// Meetings in the next 7 days.
+    pub upcoming_meetings: i64,
 }
 
 #[derive(Debug, Clone, Default, sqlx::FromRow)]
@@ -60,9 +73,17 @@ pub async fn find_user_tors(pool: &PgPool, user_id: i64) -> Vec<UserTorMembershi
     tor::find_user_tors(pool, user_id).await
 }
 
-/// Find upcoming meetings (next N days) for ToRs the user belongs to.
-/// Uses the calendar computation engine, then filters to user's ToRs.
+/// Find upcoming meetings (next N days) for ToRs the user belongs to,
+/// the first 8 by date and time.
 pub async fn find_upcoming_meetings(pool: &PgPool, user_id: i64, days: i64) -> Vec<UpcomingMeeting> {
+    let mut meetings = find_all_upcoming_meetings(pool, user_id, days).await;
+    meetings.truncate(8);
+    meetings
+}
+
+/// All upcoming meetings (next N days) for ToRs the user belongs to, by date
+/// and time. Uses the calendar computation engine, then filters to user's ToRs.
+async fn find_all_upcoming_meetings(pool: &PgPool, user_id: i64, days: i64) -> Vec<UpcomingMeeting> {
     use chrono::{Local, Duration};
     use crate::models::tor::calendar;
 
@@ -95,9 +116,8 @@ pub async fn find_upcoming_meetings(pool: &PgPool, user_id: i64, days: i64) -> V
         })
         .collect();
 
-    // Sort by date then time, limit to 8
+    // Sort by date then time
     meetings.sort_by(|a, b| (&a.date, &a.start_time).cmp(&(&b.date, &b.start_time)));
-    meetings.truncate(8);
     meetings
 }
 
@@ -105,18 +125,51 @@ pub async fn find_upcoming_meetings(pool: &PgPool, user_id: i64, days: i64) -> V
 pub async fn find_pending_items(pool: &PgPool, user_id: i64) -> PendingItems {
     // Pre-fetch user's ToR IDs once, shared by proposals and suggestions queries
     let tor_ids = tor::find_tor_ids_for_user(pool, user_id).await;
+    let (review_tor_ids, acting_for) = find_review_tor_ids(pool, user_id, &tor_ids).await;
 
     let unread_warnings = find_unread_warnings(pool, user_id).await;
-    let pending_proposals = find_pending_proposals_for_tors(pool, &tor_ids).await;
+    let pending_proposals = find_pending_proposals_for_tors(pool, &review_tor_ids).await;
     let open_suggestions = find_open_suggestions_for_tors(pool, &tor_ids).await;
 
     PendingItems {
         unread_warnings,
         pending_proposals,
         open_suggestions,
+        acting_for,
+    }
+}
+
+/// Headline counters for the user's dashboard.
+pub async fn find_counts(pool: &PgPool, user_id: i64) -> DashboardCounts {
+    let tor_ids = tor::find_tor_ids_for_user(pool, user_id).await;
+    let (review_tor_ids, _) = find_review_tor_ids(pool, user_id, &tor_ids).await;
+    DashboardCounts {
+        pending_reviews: count_pending_proposals_for_tors(pool, &review_tor_ids).await,
+        unread_warnings: crate::warnings::queries::count_unread(pool, user_id).await,
+        upcoming_meetings: find_all_upcoming_meetings(pool, user_id, 7).await.len() as i64,
     }
 }
 
+/// ToRs whose proposals the user reviews: their own, plus those of members
+/// the user stands in for.
+async fn find_review_tor_ids(
+    pool: &PgPool,
+    user_id: i64,
+    tor_ids: &[i64],
+) -> (Vec<i64>, Vec<delegation::OutOfOffice>) {
+    let today = chrono::Local::now().date_naive();
+    let acting_for = delegation::find_active_delegators(pool, user_id, today).await.unwrap_or_default();
+    let mut review_tor_ids = tor_ids.to_vec();
+    for principal in &acting_for {
+        for id in tor::find_tor_ids_for_user(pool, principal.user_id).await {
+            if !review_tor_ids.contains(&id) {
+                review_tor_ids.push(id);
+            }
+        }
+    }
+    (review_tor_ids, acting_for)
+}
+
 /// Top 5 unread warnings for the user.
 async fn find_unread_warnings(pool: &PgPool, user_id: i64) -> Vec<PendingWarning> {
     sqlx::query_as::<_, PendingWarning>(
@@ -183,6 +236,27 @@ async fn find_pending_proposals_for_tors(pool: &PgPool, tor_ids: &[i64]) -> Vec<
     query.fetch_all(pool).await.unwrap_or_default()
 }
 
+/// Number of pending proposals (submitted or under_review) across given ToR IDs.
+async fn count_pending_proposals_for_tors(pool: &PgPool, tor_ids: &[i64]) -> i64 {
+    if tor_ids.is_empty() {
+        return 0;
+    }
+    sqlx::query_scalar(
+        "SELECT COUNT(*) \
+         FROM entities p \
+         JOIN entity_properties p_status ON p.id = p_status.entity_id AND p_status.key = 'status' \
+         JOIN relations r_tor ON p.id = r_tor.source_id \
+            AND r_tor.relation_type_id = (SELECT id FROM entities WHERE entity_type = 'relation_type' AND name = 'submitted_to') \
+         WHERE p.entity_type = 'proposal' \
+           AND p_status.value IN ('submitted', 'under_review') \
+           AND r_tor.target_id = ANY($1)",
+    )
+    .bind(tor_ids)
+    .fetch_one(pool)
+    .await
+    .unwrap_or(0)
+}
+
 /// Open suggestions across given ToR IDs.
 async fn find_open_suggestions_for_tors(pool: &PgPool, tor_ids: &[i64]) -> Vec<PendingSuggestion> {
     if tor_ids.is_empty() {
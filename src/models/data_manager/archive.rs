@@ -0,0 +1,405 @@
+//! Versioned archive export/import, for moving a whole environment (say dev
+//! to staging) in one file.
+//!
+//! An archive is a zip holding:
+//! - `manifest.json`: the archive format version, the payload
+//!   `schema_version`, the last database migration of the source, and counts
+//! - `entities.json`, `properties.json`, `relations.json`: every row, keyed
+//!   by the source environment's IDs
+//! - `files/{storage_key}`: the uploaded attachments
+//!
+//! The JSON export refers to entities by "type:name" and leaves IDs stored in
+//! properties untouched, so relations to filtered-out entities and any
+//! `*_id` property break on the way across. The archive keeps the source IDs
+//! and the importer remaps them: every entity is matched by type and name or
+//! created, then relations, ID-valued properties (keys ending in `_id`, `_by`
+//! or, as comma-separated lists, `_ids`) and ToR-scoped setting names
+//! (`name@tor:{id}`) are rewritten to the target's IDs.
+
+use std::collections::{HashMap, HashSet};
+use std::io::{Cursor, Read, Write};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use zip::write::SimpleFileOptions;
+
+use super::import::{find_existing, insert_entity, process_relation, upsert_entity};
+use super::types::{ConflictMode, EntityImport, ImportItem, ImportResult, RelationImport, SCHEMA_VERSION};
+use crate::models::attachment;
+
+/// Version of the archive layout. Bump it when the files inside change shape.
+pub const ARCHIVE_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Manifest {
+    pub archive_version: u32,
+    pub schema_version: u32,
+    /// Version of the last database migration applied in the source.
+    pub migration: i64,
+    pub created_at: String,
+    pub entities: usize,
+    pub relations: usize,
+    pub files: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ArchivedEntity {
+    pub id: i64,
+    pub entity_type: String,
+    pub name: String,
+    pub label: String,
+    pub sort_order: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ArchivedProperty {
+    pub entity_id: i64,
+    pub key: String,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedRelation {
+    pub id: i64,
+    pub relation_type_id: i64,
+    pub source_id: i64,
+    pub target_id: i64,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub properties: HashMap<String, String>,
+}
+
+/// Outcome of an archive import: the usual import report plus the files
+/// restored and the IDs that changed on the way in.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveImportResult {
+    #[serde(flatten)]
+    pub result: ImportResult,
+    pub files: usize,
+    pub remapped_ids: usize,
+}
+
+fn zip_err(e: zip::result::ZipError) -> String {
+    format!("archive error: {}", e)
+}
+
+fn db_err(e: sqlx::Error) -> String {
+    format!("DB error: {}", e)
+}
+
+/// Version of the last migration applied to the database.
+async fn migration_version(pool: &PgPool) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar("SELECT COALESCE(MAX(version), 0) FROM _sqlx_migrations WHERE success")
+        .fetch_one(pool)
+        .await
+}
+
+/// Build an archive of the whole environment. Attachments are read from
+/// `uploads`; a missing file is left out and logged.
+pub async fn export_archive(pool: &PgPool, uploads: &Path) -> Result<Vec<u8>, String> {
+    let entities: Vec<ArchivedEntity> = sqlx::query_as(
+        "SELECT id, entity_type, name, label, sort_order::BIGINT AS sort_order FROM entities ORDER BY id",
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(db_err)?;
+    let properties: Vec<ArchivedProperty> = sqlx::query_as(
+        "SELECT entity_id, key, value FROM entity_properties ORDER BY entity_id, key",
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(db_err)?;
+
+    let rel_rows: Vec<(i64, i64, i64, i64)> = sqlx::query_as(
+        "SELECT id, relation_type_id, source_id, target_id FROM relations ORDER BY id",
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(db_err)?;
+    let rp_rows: Vec<(i64, String, String)> = sqlx::query_as(
+        "SELECT relation_id, key, value FROM relation_properties ORDER BY relation_id",
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(db_err)?;
+    let mut rel_props: HashMap<i64, HashMap<String, String>> = HashMap::new();
+    for (relation_id, key, value) in rp_rows {
+        rel_props.entry(relation_id).or_default().insert(key, value);
+    }
+    let relations: Vec<ArchivedRelation> = rel_rows
+        .into_iter()
+        .map(|(id, relation_type_id, source_id, target_id)| ArchivedRelation {
+            id,
+            relation_type_id,
+            source_id,
+            target_id,
+            properties: rel_props.remove(&id).unwrap_or_default(),
+        })
+        .collect();
+
+    let mut files = Vec::new();
+    for e in entities.iter().filter(|e| e.entity_type == attachment::ENTITY_TYPE) {
+        match std::fs::read(uploads.join(&e.name)) {
+            Ok(data) => files.push((e.name.clone(), data)),
+            Err(err) => log::warn!("Archive export: upload {} not included: {}", e.name, err),
+        }
+    }
+
+    let manifest = Manifest {
+        archive_version: ARCHIVE_VERSION,
+        schema_version: SCHEMA_VERSION,
+        migration: migration_version(pool).await.map_err(db_err)?,
+        created_at: chrono::Utc::now().to_rfc3339(),
+        entities: entities.len(),
+        relations: relations.len(),
+        files: files.len(),
+    };
+
+    let options = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    let mut archive = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    let json_files = [
+        ("manifest.json", serde_json::to_vec_pretty(&manifest)),
+        ("entities.json", serde_json::to_vec(&entities)),
+        ("properties.json", serde_json::to_vec(&properties)),
+        ("relations.json", serde_json::to_vec(&relations)),
+    ];
+    for (name, data) in json_files {
+        let data = data.map_err(|e| format!("could not encode {}: {}", name, e))?;
+        archive.start_file(name, options).map_err(zip_err)?;
+        archive.write_all(&data).map_err(|e| format!("archive error: {}", e))?;
+    }
+    for (key, data) in &files {
+        archive.start_file(format!("files/{}", key), options).map_err(zip_err)?;
+        archive.write_all(data).map_err(|e| format!("archive error: {}", e))?;
+    }
+    Ok(archive.finish().map_err(zip_err)?.into_inner())
+}
+
+/// Contents of an archive, read and checked but not yet imported.
+pub struct Archive {
+    pub manifest: Manifest,
+    pub entities: Vec<ArchivedEntity>,
+    pub properties: Vec<ArchivedProperty>,
+    pub relations: Vec<ArchivedRelation>,
+    pub files: HashMap<String, Vec<u8>>,
+}
+
+fn read_entry<T: serde::de::DeserializeOwned>(
+    zip: &mut zip::ZipArchive<Cursor<&[u8]>>,
+    name: &str,
+) -> Result<T, String> {
+    let mut file = zip.by_name(name).map_err(|_| format!("archive has no {}", name))?;
+    let mut data = Vec::new();
+    file.read_to_end(&mut data).map_err(|e| format!("could not read {}: {}", name, e))?;
+    serde_json::from_slice(&data).map_err(|e| format!("invalid {}: {}", name, e))
+}
+
+/// Open an archive, rejecting ones written by a newer version of the format
+/// or of the database schema than this server has.
+pub async fn read_archive(pool: &PgPool, bytes: &[u8]) -> Result<Archive, String> {
+    let mut zip = zip::ZipArchive::new(Cursor::new(bytes)).map_err(zip_err)?;
+    let manifest: Manifest = read_entry(&mut zip, "manifest.json")?;
+    if manifest.archive_version == 0 || manifest.archive_version > ARCHIVE_VERSION {
+        return Err(format!(
+            "unsupported archive_version {} — this server supports versions 1 to {}",
+            manifest.archive_version, ARCHIVE_VERSION
+        ));
+    }
+    if manifest.schema_version == 0 || manifest.schema_version > SCHEMA_VERSION {
+        return Err(format!(
+            "unsupported schema_version {} — this server supports versions 1 to {}",
+            manifest.schema_version, SCHEMA_VERSION
+        ));
+    }
+    let migration = migration_version(pool).await.map_err(db_err)?;
+    if manifest.migration > migration {
+        return Err(format!(
+            "the archive was made by a newer database (migration {}); migrate this server (at {}) first",
+            manifest.migration, migration
+        ));
+    }
+
+    let entities = read_entry(&mut zip, "entities.json")?;
+    let properties = read_entry(&mut zip, "properties.json")?;
+    let relations = read_entry(&mut zip, "relations.json")?;
+    let mut files = HashMap::new();
+    for i in 0..zip.len() {
+        let mut file = zip.by_index(i).map_err(zip_err)?;
+        let Some(key) = file.name().strip_prefix("files/").map(str::to_string) else {
+            continue;
+        };
+        // Storage keys are plain names; anything else could escape the uploads directory
+        if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+            return Err(format!("invalid file name in archive: {}", file.name()));
+        }
+        let mut data = Vec::new();
+        file.read_to_end(&mut data).map_err(|e| format!("could not read {}: {}", key, e))?;
+        files.insert(key, data);
+    }
+    Ok(Archive { manifest, entities, properties, relations, files })
+}
+
+/// Whether a property holds entity IDs and should be remapped.
+fn holds_ids(key: &str) -> bool {
+    key.ends_with("_id") || key.ends_with("_by") || key.ends_with("_ids")
+}
+
+/// Rewrite the source IDs in an ID-valued property. Values that are not IDs
+/// of archived entities are kept as they are.
+fn remap_value(key: &str, value: &str, ids: &HashMap<i64, i64>) -> String {
+    if !holds_ids(key) {
+        return value.to_string();
+    }
+    let remap = |part: &str| match part.trim().parse::<i64>().ok().and_then(|id| ids.get(&id)) {
+        Some(new_id) => new_id.to_string(),
+        None => part.to_string(),
+    };
+    if key.ends_with("_ids") {
+        value.split(',').map(remap).collect::<Vec<_>>().join(",")
+    } else {
+        remap(value)
+    }
+}
+
+/// The ToR a scoped setting name (`name@tor:{id}`) points at.
+fn scoped_tor(name: &str) -> Option<(&str, i64)> {
+    let (base, id) = name.rsplit_once("@tor:")?;
+    Some((base, id.parse().ok()?))
+}
+
+/// Import an archive. Entities are matched by type and name and handled per
+/// `mode` like the JSON import; all IDs are then remapped. In fail mode the
+/// first conflict imports nothing. Attachments are written to `uploads`
+/// after the commit, unless a file of that name is already there.
+pub async fn import_archive(
+    pool: &PgPool,
+    archive: &Archive,
+    mode: &ConflictMode,
+    uploads: &Path,
+    dry_run: bool,
+) -> Result<ArchiveImportResult, String> {
+    let mut result = ImportResult {
+        dry_run,
+        created: 0,
+        updated: 0,
+        skipped: 0,
+        errors: Vec::new(),
+        items: Vec::new(),
+    };
+    let mut properties: HashMap<i64, HashMap<String, String>> = HashMap::new();
+    for p in &archive.properties {
+        properties.entry(p.entity_id).or_default().insert(p.key.clone(), p.value.clone());
+    }
+
+    let mut tx = pool.begin().await.map_err(|e| format!("failed to begin transaction: {}", e))?;
+
+    // Phase 1: match or create every entity, so all IDs are known before any
+    // reference is rewritten. Scoped names need their ToR's new ID first.
+    let (plain, scoped): (Vec<_>, Vec<_>) = archive.entities.iter().partition(|e| scoped_tor(&e.name).is_none());
+    let mut ids: HashMap<i64, i64> = HashMap::new();
+    let mut names: HashMap<i64, String> = HashMap::new();
+    let mut to_write: Vec<(i64, EntityImport)> = Vec::new();
+    for e in plain.into_iter().chain(scoped) {
+        let name = match scoped_tor(&e.name) {
+            Some((base, tor_id)) => format!("{}@tor:{}", base, ids.get(&tor_id).copied().unwrap_or(tor_id)),
+            None => e.name.clone(),
+        };
+        let entity = EntityImport {
+            entity_type: e.entity_type.clone(),
+            name,
+            label: e.label.clone(),
+            sort_order: e.sort_order,
+            properties: HashMap::new(),
+        };
+        let reference = format!("{}:{}", entity.entity_type, entity.name);
+        let (new_id, action) = match find_existing(&mut tx, &entity).await? {
+            Some(existing) => match mode {
+                ConflictMode::Skip => (existing, "skip"),
+                ConflictMode::Upsert => (existing, "update"),
+                ConflictMode::Fail => {
+                    let reason = format!("entity already exists: {}", reference);
+                    result.reject("entity", reference, serde_json::to_value(&entity), reason);
+                    tx.rollback().await.map_err(|e| format!("rollback failed: {}", e))?;
+                    return Ok(ArchiveImportResult { result, files: 0, remapped_ids: 0 });
+                }
+            },
+            None => (insert_entity(&mut tx, &entity).await?, "create"),
+        };
+        match action {
+            "create" => result.created += 1,
+            "update" => result.updated += 1,
+            _ => result.skipped += 1,
+        }
+        result.items.push(ImportItem { kind: "entity".to_string(), reference, action: action.to_string(), reason: None });
+        ids.insert(e.id, new_id);
+        names.insert(e.id, format!("{}:{}", entity.entity_type, entity.name));
+        if action != "skip" {
+            to_write.push((e.id, entity));
+        }
+    }
+
+    // Phase 2: properties of created and updated entities, with IDs remapped
+    for (old_id, mut entity) in to_write {
+        entity.properties = properties
+            .remove(&old_id)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(k, v)| {
+                let v = remap_value(&k, &v, &ids);
+                (k, v)
+            })
+            .collect();
+        upsert_entity(&mut tx, ids[&old_id], &entity).await?;
+    }
+
+    // Phase 3: relations, by the remapped endpoints
+    for r in &archive.relations {
+        let endpoints = (names.get(&r.relation_type_id), names.get(&r.source_id), names.get(&r.target_id));
+        let (Some(relation_type), Some(source), Some(target)) = endpoints else {
+            let reference = format!("relation #{}", r.id);
+            let reason = "refers to an entity missing from the archive".to_string();
+            result.reject("relation", reference, serde_json::to_value(r), reason);
+            continue;
+        };
+        let rel = RelationImport {
+            relation_type: relation_type.strip_prefix("relation_type:").unwrap_or(relation_type).to_string(),
+            source: source.clone(),
+            target: target.clone(),
+            properties: r.properties.iter().map(|(k, v)| (k.clone(), remap_value(k, v, &ids))).collect(),
+        };
+        let reference = format!("{} {} -> {}", rel.relation_type, rel.source, rel.target);
+        match process_relation(&mut tx, &rel).await {
+            Ok(created) => result.items.push(ImportItem {
+                kind: "relation".to_string(),
+                reference,
+                action: if created { "create" } else { "skip" }.to_string(),
+                reason: (!created).then(|| "relation already exists".to_string()),
+            }),
+            Err(reason) => result.reject("relation", reference, serde_json::to_value(&rel), reason),
+        }
+    }
+
+    let remapped_ids = ids.iter().filter(|(old, new)| old != new).count();
+    if dry_run {
+        tx.rollback().await.map_err(|e| format!("rollback failed: {}", e))?;
+        return Ok(ArchiveImportResult { result, files: archive.files.len(), remapped_ids });
+    }
+    tx.commit().await.map_err(|e| format!("commit failed: {}", e))?;
+
+    let mut files = 0;
+    let referenced: HashSet<&str> = archive.entities.iter()
+        .filter(|e| e.entity_type == attachment::ENTITY_TYPE)
+        .map(|e| e.name.as_str())
+        .collect();
+    for (key, data) in archive.files.iter().filter(|(key, _)| referenced.contains(key.as_str())) {
+        let path = uploads.join(key);
+        if path.exists() {
+            continue;
+        }
+        std::fs::create_dir_all(uploads)
+            .and_then(|_| std::fs::write(&path, data))
+            .map_err(|e| format!("could not restore upload {}: {}", key, e))?;
+        files += 1;
+    }
+    Ok(ArchiveImportResult { result, files, remapped_ids })
+}
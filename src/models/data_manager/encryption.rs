@@ -0,0 +1,188 @@
+//! Encrypted exports.
+//!
+//! Exports holding personal data can be encrypted with AES-256-GCM, either
+//! under a passphrase (key derived with Argon2id) or for a configured X25519
+//! public key (ephemeral key agreement, key derived with HKDF-SHA256). Only
+//! the public key is stored on the server; importing a key-encrypted file
+//! needs the private key supplied with the upload.
+//!
+//! File layout: `AHLTENC1`, a mode byte, the mode's header (salt or
+//! ephemeral public key), a 12-byte nonce, then the ciphertext with its tag.
+//! Everything before the nonce is authenticated as associated data.
+
+use std::fmt;
+
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use argon2::Argon2;
+use base64::Engine;
+use hkdf::Hkdf;
+use sha2::{Digest, Sha256};
+use x25519_dalek::{PublicKey, StaticSecret};
+
+pub const MAGIC: &[u8; 8] = b"AHLTENC1";
+const MODE_PASSPHRASE: u8 = 1;
+const MODE_PUBLIC_KEY: u8 = 2;
+const SALT_LEN: usize = 16;
+const KEY_LEN: usize = 32;
+const NONCE_LEN: usize = 12;
+const HKDF_INFO: &[u8] = b"ahlt-export-v1";
+
+/// Shortest passphrase accepted for encrypting.
+pub const MIN_PASSPHRASE_LEN: usize = 12;
+
+/// What an export is encrypted for.
+pub enum Recipient<'a> {
+    Passphrase(&'a str),
+    /// Base64 X25519 public key.
+    PublicKey(&'a str),
+}
+
+/// What an encrypted export is opened with.
+pub enum Secret<'a> {
+    Passphrase(&'a str),
+    /// Base64 X25519 private key.
+    PrivateKey(&'a str),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EncryptionError {
+    WeakPassphrase,
+    InvalidKey,
+    /// Not an encrypted export, or truncated.
+    Malformed,
+    /// Encrypted for a passphrase but opened with a key, or the reverse.
+    WrongMode,
+    /// Wrong passphrase or key, or the file was altered.
+    Failed,
+}
+
+impl fmt::Display for EncryptionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EncryptionError::WeakPassphrase => write!(f, "Passphrase must be at least {MIN_PASSPHRASE_LEN} characters"),
+            EncryptionError::InvalidKey => write!(f, "Key must be 32 bytes, base64-encoded"),
+            EncryptionError::Malformed => write!(f, "Not an encrypted export"),
+            EncryptionError::WrongMode => write!(f, "This export was encrypted with the other method (passphrase or key)"),
+            EncryptionError::Failed => write!(f, "Wrong passphrase or key, or the file was altered"),
+        }
+    }
+}
+
+/// Whether bytes are an encrypted export.
+pub fn is_encrypted(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+fn decode_key(b64: &str) -> Result<[u8; KEY_LEN], EncryptionError> {
+    base64::engine::general_purpose::STANDARD
+        .decode(b64.trim())
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or(EncryptionError::InvalidKey)
+}
+
+fn encode_key(bytes: &[u8]) -> String {
+    base64::engine::general_purpose::STANDARD.encode(bytes)
+}
+
+/// A new key pair as (private, public), both base64.
+pub fn generate_key_pair() -> (String, String) {
+    let secret = StaticSecret::from(rand::random::<[u8; KEY_LEN]>());
+    let public = PublicKey::from(&secret);
+    (encode_key(secret.as_bytes()), encode_key(public.as_bytes()))
+}
+
+/// Short fingerprint of a public key for display, or None if it is invalid.
+pub fn fingerprint(public_key: &str) -> Option<String> {
+    let key = decode_key(public_key).ok()?;
+    Some(hex::encode(&Sha256::digest(key)[..8]))
+}
+
+fn passphrase_key(passphrase: &str, salt: &[u8]) -> Result<[u8; KEY_LEN], EncryptionError> {
+    let mut key = [0u8; KEY_LEN];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|_| EncryptionError::Failed)?;
+    Ok(key)
+}
+
+fn agreed_key(shared: &[u8], ephemeral: &[u8], recipient: &[u8]) -> [u8; KEY_LEN] {
+    let salt = [ephemeral, recipient].concat();
+    let mut key = [0u8; KEY_LEN];
+    Hkdf::<Sha256>::new(Some(&salt), shared)
+        .expand(HKDF_INFO, &mut key)
+        .expect("32 bytes is a valid HKDF-SHA256 output length");
+    key
+}
+
+/// Encrypt an export.
+pub fn encrypt(plaintext: &[u8], recipient: Recipient) -> Result<Vec<u8>, EncryptionError> {
+    let mut header = MAGIC.to_vec();
+    let key = match recipient {
+        Recipient::Passphrase(passphrase) => {
+            if passphrase.chars().count() < MIN_PASSPHRASE_LEN {
+                return Err(EncryptionError::WeakPassphrase);
+            }
+            let salt = rand::random::<[u8; SALT_LEN]>();
+            header.push(MODE_PASSPHRASE);
+            header.extend_from_slice(&salt);
+            passphrase_key(passphrase, &salt)?
+        }
+        Recipient::PublicKey(public_key) => {
+            let recipient = PublicKey::from(decode_key(public_key)?);
+            let ephemeral = StaticSecret::from(rand::random::<[u8; KEY_LEN]>());
+            let ephemeral_public = PublicKey::from(&ephemeral);
+            let shared = ephemeral.diffie_hellman(&recipient);
+            header.push(MODE_PUBLIC_KEY);
+            header.extend_from_slice(ephemeral_public.as_bytes());
+            agreed_key(shared.as_bytes(), ephemeral_public.as_bytes(), recipient.as_bytes())
+        }
+    };
+
+    let nonce = rand::random::<[u8; NONCE_LEN]>();
+    let cipher = Aes256Gcm::new_from_slice(&key).map_err(|_| EncryptionError::Failed)?;
+    let ciphertext = cipher
+        .encrypt(Nonce::from_slice(&nonce), Payload { msg: plaintext, aad: &header })
+        .map_err(|_| EncryptionError::Failed)?;
+
+    let mut out = header;
+    out.extend_from_slice(&nonce);
+    out.extend_from_slice(&ciphertext);
+    Ok(out)
+}
+
+/// Decrypt an export.
+pub fn decrypt(data: &[u8], secret: Secret) -> Result<Vec<u8>, EncryptionError> {
+    if !is_encrypted(data) || data.len() < MAGIC.len() + 1 {
+        return Err(EncryptionError::Malformed);
+    }
+    let mode = data[MAGIC.len()];
+    let header_len = MAGIC.len() + 1 + match mode {
+        MODE_PASSPHRASE => SALT_LEN,
+        MODE_PUBLIC_KEY => KEY_LEN,
+        _ => return Err(EncryptionError::Malformed),
+    };
+    if data.len() < header_len + NONCE_LEN {
+        return Err(EncryptionError::Malformed);
+    }
+    let (header, rest) = data.split_at(header_len);
+    let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+    let mode_data = &header[MAGIC.len() + 1..];
+
+    let key = match (mode, secret) {
+        (MODE_PASSPHRASE, Secret::Passphrase(passphrase)) => passphrase_key(passphrase, mode_data)?,
+        (MODE_PUBLIC_KEY, Secret::PrivateKey(private_key)) => {
+            let secret = StaticSecret::from(decode_key(private_key)?);
+            let ephemeral: [u8; KEY_LEN] = mode_data.try_into().map_err(|_| EncryptionError::Malformed)?;
+            let shared = secret.diffie_hellman(&PublicKey::from(ephemeral));
+            agreed_key(shared.as_bytes(), &ephemeral, PublicKey::from(&secret).as_bytes())
+        }
+        _ => return Err(EncryptionError::WrongMode),
+    };
+
+    let cipher = Aes256Gcm::new_from_slice(&key).map_err(|_| EncryptionError::Failed)?;
+    cipher
+        .decrypt(Nonce::from_slice(nonce), Payload { msg: ciphertext, aad: header })
+        .map_err(|_| EncryptionError::Failed)
+}
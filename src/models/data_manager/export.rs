@@ -1,7 +1,7 @@
 use sqlx::PgPool;
 use std::collections::HashMap;
 
-use super::types::{EntityExport, ExportPayload, RelationExport};
+use super::types::{DeletedRecord, EntityExport, ExportPayload, RelationExport, SCHEMA_VERSION};
 
 /// Build a lookup map of entity ID -> "entity_type:name" for resolving relations.
 async fn build_entity_ref_map(pool: &PgPool) -> Result<HashMap<i64, String>, sqlx::Error> {
@@ -18,64 +18,123 @@ async fn build_entity_ref_map(pool: &PgPool) -> Result<HashMap<i64, String>, sql
     Ok(map)
 }
 
-/// Query entities into a vec, with optional entity_type filter.
-async fn query_entities(pool: &PgPool, types: Option<&[String]>) -> Result<Vec<EntityExport>, sqlx::Error> {
+/// Query entities into a vec, with optional entity_type filter and, for
+/// delta exports, only those changed after `since`.
+async fn query_entities(
+    pool: &PgPool,
+    types: Option<&[String]>,
+    since: Option<&str>,
+) -> Result<Vec<EntityExport>, sqlx::Error> {
+    let rows: Vec<(i64, String, String, String, i64)> = sqlx::query_as(
+        "SELECT id, entity_type, name, label, sort_order::BIGINT FROM entities \
+         WHERE ($1::TEXT[] IS NULL OR entity_type = ANY($1)) \
+           AND ($2::TEXT IS NULL OR updated_at > $2::TIMESTAMPTZ) \
+         ORDER BY id",
+    )
+    .bind(types.filter(|ts| !ts.is_empty()))
+    .bind(since)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(id, entity_type, name, label, sort_order)| EntityExport {
+            id,
+            entity_type,
+            name,
+            label,
+            sort_order,
+            properties: HashMap::new(),
+        })
+        .collect())
+}
+
+/// Export entities with their properties, optionally filtered by entity type.
+pub async fn export_entities(
+    pool: &PgPool,
+    types: Option<&[String]>,
+) -> Result<ExportPayload, sqlx::Error> {
+    export_graph(pool, types, None).await
+}
+
+/// Export only what changed after `since` (an RFC 3339 timestamp, see
+/// [`parse_since`]): entities and relations created or updated since then,
+/// and tombstones of those deleted. The payload's `until` is the `since` for
+/// the next call.
+///
+/// Writes are stamped with their transaction's start time but only show once
+/// committed, so `until` stops short of the oldest transaction still open in
+/// this database: whatever it writes is in the next delta. Rows after `until`
+/// that are already visible come again next time; importers upsert them.
+pub async fn export_changes(
+    pool: &PgPool,
+    types: Option<&[String]>,
+    since: &str,
+) -> Result<ExportPayload, sqlx::Error> {
+    let (until,): (String,) = sqlx::query_as(
+        "SELECT to_char(LEAST(NOW(), ( \
+                    SELECT MIN(xact_start) - INTERVAL '1 microsecond' FROM pg_stat_activity \
+                    WHERE datname = current_database() AND pid <> pg_backend_pid() AND xact_start IS NOT NULL \
+                )) AT TIME ZONE 'UTC', 'YYYY-MM-DD\"T\"HH24:MI:SS.US\"Z\"')",
+    )
+    .fetch_one(pool)
+    .await?;
+
+    let mut payload = export_graph(pool, types, Some(since)).await?;
+
+    let deleted: Vec<DeletedRecord> = sqlx::query_as(
+        "SELECT record_kind AS kind, record_id AS id, reference, \
+                to_char(deleted_at AT TIME ZONE 'UTC', 'YYYY-MM-DD\"T\"HH24:MI:SS.US\"Z\"') AS deleted_at \
+         FROM deletions WHERE deleted_at > $1::TIMESTAMPTZ ORDER BY id",
+    )
+    .bind(since)
+    .fetch_all(pool)
+    .await?;
+    payload.deleted = deleted
+        .into_iter()
+        .filter(|d| d.kind != "entity" || ref_in_types(&d.reference, types))
+        .collect();
+    payload.since = Some(since.to_string());
+    payload.until = Some(until);
+    Ok(payload)
+}
+
+/// Normalise a `since` value to an RFC 3339 UTC timestamp. Accepts RFC 3339,
+/// `YYYY-MM-DDTHH:MM:SS` or `YYYY-MM-DD HH:MM:SS` (read as UTC), or a date.
+pub fn parse_since(value: &str) -> Option<String> {
+    let value = value.trim();
+    let utc = if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(value) {
+        dt.with_timezone(&chrono::Utc)
+    } else if let Ok(dt) = chrono::NaiveDateTime::parse_from_str(&value.replace(' ', "T"), "%Y-%m-%dT%H:%M:%S%.f") {
+        dt.and_utc()
+    } else {
+        chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()?.and_hms_opt(0, 0, 0)?.and_utc()
+    };
+    Some(utc.format("%Y-%m-%dT%H:%M:%S%.6fZ").to_string())
+}
+
+/// Whether a "type:name" reference is of one of the filtered types.
+fn ref_in_types(reference: &str, types: Option<&[String]>) -> bool {
     match types {
         Some(ts) if !ts.is_empty() => {
-            // Use ANY($1) with a slice instead of individual placeholders
-            let rows: Vec<(i64, String, String, String, i64)> = sqlx::query_as(
-                "SELECT id, entity_type, name, label, sort_order::BIGINT FROM entities WHERE entity_type = ANY($1) ORDER BY id",
-            )
-                .bind(ts)
-                .fetch_all(pool)
-                .await?;
-
-            Ok(rows
-                .into_iter()
-                .map(|(id, entity_type, name, label, sort_order)| EntityExport {
-                    id,
-                    entity_type,
-                    name,
-                    label,
-                    sort_order,
-                    properties: HashMap::new(),
-                })
-                .collect())
-        }
-        _ => {
-            let rows: Vec<(i64, String, String, String, i64)> = sqlx::query_as(
-                "SELECT id, entity_type, name, label, sort_order::BIGINT FROM entities ORDER BY id",
-            )
-            .fetch_all(pool)
-            .await?;
-
-            Ok(rows
-                .into_iter()
-                .map(|(id, entity_type, name, label, sort_order)| EntityExport {
-                    id,
-                    entity_type,
-                    name,
-                    label,
-                    sort_order,
-                    properties: HashMap::new(),
-                })
-                .collect())
+            let (entity_type, _) = split_ref(reference);
+            ts.iter().any(|t| t == entity_type)
         }
+        _ => true,
     }
 }
 
-/// Export entities with their properties, optionally filtered by entity type.
-pub async fn export_entities(
+async fn export_graph(
     pool: &PgPool,
     types: Option<&[String]>,
+    since: Option<&str>,
 ) -> Result<ExportPayload, sqlx::Error> {
     let ref_map = build_entity_ref_map(pool).await?;
 
-    let mut entities = query_entities(pool, types).await?;
-    let entity_ids: Vec<i64> = entities.iter().map(|e| e.id).collect();
+    let mut entities = query_entities(pool, types, since).await?;
 
     // Batch-load all properties for matched entities (avoid N+1)
-    if !entity_ids.is_empty() {
+    if !entities.is_empty() {
         let prop_rows: Vec<(i64, String, String)> = sqlx::query_as(
             "SELECT entity_id, key, value FROM entity_properties ORDER BY entity_id",
         )
@@ -97,14 +156,13 @@ pub async fn export_entities(
     // Query relations, resolving IDs to type:name via ref_map
     let mut relations: Vec<RelationExport> = Vec::new();
     let rel_rows: Vec<(i64, i64, i64, i64)> = sqlx::query_as(
-        "SELECT id, relation_type_id, source_id, target_id FROM relations ORDER BY id",
+        "SELECT id, relation_type_id, source_id, target_id FROM relations \
+         WHERE ($1::TEXT IS NULL OR updated_at > $1::TIMESTAMPTZ) ORDER BY id",
     )
+    .bind(since)
     .fetch_all(pool)
     .await?;
 
-    // Build a set of entity IDs for filtering relations when type filter is active
-    let entity_id_set: std::collections::HashSet<i64> = entity_ids.iter().cloned().collect();
-
     // Batch-load all relation_properties
     let rp_rows: Vec<(i64, String, String)> = sqlx::query_as(
         "SELECT relation_id, key, value FROM relation_properties ORDER BY relation_id",
@@ -117,13 +175,6 @@ pub async fn export_entities(
     }
 
     for (id, rel_type_id, source_id, target_id) in rel_rows {
-        // When filtering by type, only include relations where both source and target are in the set
-        if types.is_some() && !types.unwrap().is_empty() {
-            if !entity_id_set.contains(&source_id) || !entity_id_set.contains(&target_id) {
-                continue;
-            }
-        }
-
         let relation_type = ref_map
             .get(&rel_type_id)
             .map(|r| {
@@ -140,6 +191,11 @@ pub async fn export_entities(
             .cloned()
             .unwrap_or_else(|| format!("unknown:{}", target_id));
 
+        // When filtering by type, only include relations where both source and target are of those types
+        if !ref_in_types(&source, types) || !ref_in_types(&target, types) {
+            continue;
+        }
+
         let properties = rel_props_map.remove(&id).unwrap_or_default();
 
         relations.push(RelationExport {
@@ -152,8 +208,12 @@ pub async fn export_entities(
     }
 
     Ok(ExportPayload {
+        schema_version: SCHEMA_VERSION,
+        since: None,
+        until: None,
         entities,
         relations,
+        deleted: Vec::new(),
     })
 }
 
@@ -166,7 +226,8 @@ pub async fn export_sql(
     let mut sql = String::new();
 
     sql.push_str("-- Data Manager export\n");
-    sql.push_str("-- Generated by ahlt\n\n");
+    sql.push_str("-- Generated by ahlt\n");
+    sql.push_str(&format!("-- schema_version: {}\n\n", payload.schema_version));
 
     // Entities
     sql.push_str("-- Entities\n");
@@ -1,6 +1,17 @@
+use std::collections::{HashMap, HashSet};
+
 use sqlx::{Acquire, PgPool, Postgres};
 
-use super::types::{ConflictMode, EntityImport, ImportError, ImportPayload, ImportResult, RelationImport};
+use super::types::{
+    ConflictMode, EntityImport, ImportError, ImportItem, ImportPayload, ImportResult, RelationImport, SCHEMA_VERSION,
+};
+
+/// A payload item that fails validation, by its position in the payload.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PayloadIssue {
+    Entity(usize, String),
+    Relation(usize, String),
+}
 
 /// Outcome of inserting/upserting a single entity.
 enum EntityOutcome {
@@ -9,6 +20,24 @@ enum EntityOutcome {
     Skipped,
 }
 
+impl EntityOutcome {
+    fn action(&self) -> &'static str {
+        match self {
+            EntityOutcome::Created => "create",
+            EntityOutcome::Updated => "update",
+            EntityOutcome::Skipped => "skip",
+        }
+    }
+}
+
+fn entity_ref(entity: &EntityImport) -> String {
+    format!("{}:{}", entity.entity_type, entity.name)
+}
+
+fn relation_ref(rel: &RelationImport) -> String {
+    format!("{} {} -> {}", rel.relation_type, rel.source, rel.target)
+}
+
 /// Parse a "type:name" reference string into (entity_type, name).
 fn parse_ref(ref_str: &str) -> Result<(&str, &str), String> {
     ref_str
@@ -34,7 +63,7 @@ async fn resolve_entity_ref(
 }
 
 /// Check if an entity with the given type+name already exists. Returns Some(id) if it does.
-async fn find_existing(
+pub(super) async fn find_existing(
     tx: &mut sqlx::Transaction<'_, Postgres>,
     entity: &EntityImport,
 ) -> Result<Option<i64>, String> {
@@ -50,7 +79,7 @@ async fn find_existing(
 }
 
 /// Insert a new entity with its properties.
-async fn insert_entity(
+pub(super) async fn insert_entity(
     tx: &mut sqlx::Transaction<'_, Postgres>,
     entity: &EntityImport,
 ) -> Result<i64, String> {
@@ -83,7 +112,7 @@ async fn insert_entity(
 }
 
 /// Upsert an existing entity: update label, sort_order, and replace all properties.
-async fn upsert_entity(
+pub(super) async fn upsert_entity(
     tx: &mut sqlx::Transaction<'_, Postgres>,
     existing_id: i64,
     entity: &EntityImport,
@@ -147,11 +176,12 @@ async fn process_entity(
     }
 }
 
-/// Process a single relation import.
-async fn process_relation(
+/// Process a single relation import. Returns false if the relation already
+/// existed and was left alone.
+pub(super) async fn process_relation(
     tx: &mut sqlx::Transaction<'_, Postgres>,
     rel: &RelationImport,
-) -> Result<(), String> {
+) -> Result<bool, String> {
     let row: (i64,) = sqlx::query_as(
         "SELECT id FROM entities WHERE entity_type = 'relation_type' AND name = $1",
     )
@@ -201,63 +231,207 @@ async fn process_relation(
         }
     }
 
-    Ok(())
+    Ok(!exists)
+}
+
+/// Reject payloads written by a newer format version than this server understands.
+/// A missing `schema_version` is a legacy payload and is accepted as version 1.
+pub fn check_schema_version(payload: &ImportPayload) -> Result<u32, String> {
+    let version = payload.schema_version.unwrap_or(1);
+    if version == 0 || version > SCHEMA_VERSION {
+        return Err(format!(
+            "unsupported schema_version {} — this server supports versions 1 to {}",
+            version, SCHEMA_VERSION
+        ));
+    }
+    Ok(version)
+}
+
+/// Check the whole payload before anything is written: entities need a type
+/// and name and may appear only once, relations need a known relation type
+/// and references to entities in the payload or the database.
+pub async fn validate_payload(pool: &PgPool, payload: &ImportPayload) -> Result<Vec<PayloadIssue>, String> {
+    let mut issues = Vec::new();
+    let mut in_payload: HashSet<(&str, &str)> = HashSet::new();
+    for (i, entity) in payload.entities.iter().enumerate() {
+        if entity.entity_type.trim().is_empty() || entity.name.trim().is_empty() {
+            issues.push(PayloadIssue::Entity(i, "entity_type and name are required".to_string()));
+        } else if !in_payload.insert((&entity.entity_type, &entity.name)) {
+            issues.push(PayloadIssue::Entity(i, format!("duplicate entity in payload: {}", entity_ref(entity))));
+        }
+    }
+
+    let mut in_database: HashMap<(String, String), bool> = HashMap::new();
+    let mut exists = async |entity_type: &str, name: &str| -> Result<bool, String> {
+        if in_payload.contains(&(entity_type, name)) {
+            return Ok(true);
+        }
+        let key = (entity_type.to_string(), name.to_string());
+        if let Some(&found) = in_database.get(&key) {
+            return Ok(found);
+        }
+        let found: bool = sqlx::query_scalar(
+            "SELECT EXISTS (SELECT 1 FROM entities WHERE entity_type = $1 AND name = $2)",
+        )
+        .bind(entity_type)
+        .bind(name)
+        .fetch_one(pool)
+        .await
+        .map_err(|e| format!("DB error checking references: {}", e))?;
+        in_database.insert(key, found);
+        Ok(found)
+    };
+
+    for (i, rel) in payload.relations.iter().enumerate() {
+        if !exists("relation_type", &rel.relation_type).await? {
+            issues.push(PayloadIssue::Relation(i, format!("unknown relation type: {}", rel.relation_type)));
+            continue;
+        }
+        for reference in [&rel.source, &rel.target] {
+            let reason = match parse_ref(reference) {
+                Err(reason) => reason,
+                Ok((entity_type, name)) if !exists(entity_type, name).await? => {
+                    format!("dangling reference: {} is neither in the payload nor in the database", reference)
+                }
+                Ok(_) => continue,
+            };
+            issues.push(PayloadIssue::Relation(i, reason));
+            break;
+        }
+    }
+    Ok(issues)
 }
 
 /// Import data from an ImportPayload, applying conflict resolution.
 /// The entire operation runs in a single transaction.
 pub async fn import_data(pool: &PgPool, payload: &ImportPayload) -> Result<ImportResult, String> {
-    let mut tx = pool
-        .begin()
-        .await
-        .map_err(|e| format!("failed to begin transaction: {}", e))?;
+    run_import(pool, payload, false).await
+}
+
+/// Dry run of [`import_data`]: reports what would be created, updated,
+/// skipped or rejected, then rolls the transaction back.
+pub async fn preview_import(pool: &PgPool, payload: &ImportPayload) -> Result<ImportResult, String> {
+    run_import(pool, payload, true).await
+}
+
+async fn run_import(pool: &PgPool, payload: &ImportPayload, dry_run: bool) -> Result<ImportResult, String> {
+    check_schema_version(payload)?;
 
     let mut result = ImportResult {
+        dry_run,
         created: 0,
         updated: 0,
         skipped: 0,
         errors: Vec::new(),
+        items: Vec::new(),
     };
+    let mut entity_issues = HashMap::new();
+    let mut relation_issues = HashMap::new();
+    for issue in validate_payload(pool, payload).await? {
+        match issue {
+            PayloadIssue::Entity(i, reason) => entity_issues.insert(i, reason),
+            PayloadIssue::Relation(i, reason) => relation_issues.insert(i, reason),
+        };
+    }
+
+    // In fail mode an invalid payload imports nothing
+    if payload.conflict_mode == ConflictMode::Fail && !(entity_issues.is_empty() && relation_issues.is_empty()) {
+        for (i, entity) in payload.entities.iter().enumerate() {
+            if let Some(reason) = entity_issues.remove(&i) {
+                result.reject("entity", entity_ref(entity), serde_json::to_value(entity), reason);
+            }
+        }
+        for (i, rel) in payload.relations.iter().enumerate() {
+            if let Some(reason) = relation_issues.remove(&i) {
+                result.reject("relation", relation_ref(rel), serde_json::to_value(rel), reason);
+            }
+        }
+        return Ok(result);
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| format!("failed to begin transaction: {}", e))?;
 
     // Phase 1: Process entities
-    for entity in &payload.entities {
-        match process_entity(&mut tx, entity, &payload.conflict_mode).await {
-            Ok(EntityOutcome::Created) => result.created += 1,
-            Ok(EntityOutcome::Updated) => result.updated += 1,
-            Ok(EntityOutcome::Skipped) => result.skipped += 1,
+    for (i, entity) in payload.entities.iter().enumerate() {
+        let outcome = match entity_issues.remove(&i) {
+            Some(reason) => Err(reason),
+            None => process_entity(&mut tx, entity, &payload.conflict_mode).await,
+        };
+        match outcome {
+            Ok(outcome) => {
+                match outcome {
+                    EntityOutcome::Created => result.created += 1,
+                    EntityOutcome::Updated => result.updated += 1,
+                    EntityOutcome::Skipped => result.skipped += 1,
+                }
+                result.items.push(ImportItem {
+                    kind: "entity".to_string(),
+                    reference: entity_ref(entity),
+                    action: outcome.action().to_string(),
+                    reason: None,
+                });
+            }
             Err(reason) => {
+                result.reject("entity", entity_ref(entity), serde_json::to_value(entity), reason);
                 if payload.conflict_mode == ConflictMode::Fail {
                     // Rollback on first error in fail mode
                     tx.rollback()
                         .await
                         .map_err(|e| format!("rollback failed: {}", e))?;
-                    result.errors.push(ImportError {
-                        item: serde_json::to_value(entity).unwrap_or_default(),
-                        reason,
-                    });
                     return Ok(result);
                 }
-                result.errors.push(ImportError {
-                    item: serde_json::to_value(entity).unwrap_or_default(),
-                    reason,
-                });
             }
         }
     }
 
     // Phase 2: Process relations (after all entities exist)
-    for rel in &payload.relations {
-        if let Err(reason) = process_relation(&mut tx, rel).await {
-            result.errors.push(ImportError {
-                item: serde_json::to_value(rel).unwrap_or_default(),
-                reason,
-            });
+    for (i, rel) in payload.relations.iter().enumerate() {
+        let outcome = match relation_issues.remove(&i) {
+            Some(reason) => Err(reason),
+            None => process_relation(&mut tx, rel).await,
+        };
+        match outcome {
+            Ok(created) => result.items.push(ImportItem {
+                kind: "relation".to_string(),
+                reference: relation_ref(rel),
+                action: if created { "create" } else { "skip" }.to_string(),
+                reason: (!created).then(|| "relation already exists".to_string()),
+            }),
+            Err(reason) => result.reject("relation", relation_ref(rel), serde_json::to_value(rel), reason),
         }
     }
 
-    tx.commit()
-        .await
-        .map_err(|e| format!("commit failed: {}", e))?;
+    if dry_run {
+        tx.rollback()
+            .await
+            .map_err(|e| format!("rollback failed: {}", e))?;
+    } else {
+        tx.commit()
+            .await
+            .map_err(|e| format!("commit failed: {}", e))?;
+    }
 
     Ok(result)
 }
+
+impl ImportResult {
+    /// Record an item that was not imported.
+    pub(super) fn reject(
+        &mut self,
+        kind: &str,
+        reference: String,
+        item: Result<serde_json::Value, serde_json::Error>,
+        reason: String,
+    ) {
+        self.items.push(ImportItem {
+            kind: kind.to_string(),
+            reference,
+            action: "error".to_string(),
+            reason: Some(reason.clone()),
+        });
+        self.errors.push(ImportError { item: item.unwrap_or_default(), reason });
+    }
+}
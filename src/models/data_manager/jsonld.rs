@@ -3,7 +3,7 @@ use serde_json::{json, Map, Value};
 use std::collections::{HashMap, HashSet};
 
 use super::export::export_entities;
-use super::types::{ConflictMode, EntityImport, ImportPayload, RelationImport};
+use super::types::{ConflictMode, EntityImport, ImportPayload, RelationImport, SCHEMA_VERSION};
 
 const AHLT_NS: &str = "http://ahlt.local/ontology/";
 
@@ -100,6 +100,7 @@ pub async fn export_jsonld(
 
     Ok(json!({
         "@context": context,
+        "ahlt:schema_version": SCHEMA_VERSION,
         "@graph": graph
     }))
 }
@@ -119,6 +120,11 @@ pub fn parse_jsonld(value: &Value) -> Result<ImportPayload, String> {
         })
         .unwrap_or_default();
 
+    let schema_version = obj
+        .get("ahlt:schema_version")
+        .and_then(|v| v.as_u64())
+        .map(|v| v as u32);
+
     let graph = obj
         .get("@graph")
         .and_then(|v| v.as_array())
@@ -238,6 +244,7 @@ pub fn parse_jsonld(value: &Value) -> Result<ImportPayload, String> {
     }
 
     Ok(ImportPayload {
+        schema_version,
         conflict_mode,
         entities,
         relations,
@@ -1,4 +1,7 @@
+pub mod archive;
+pub mod encryption;
 pub mod export;
 pub mod import;
 pub mod jsonld;
+pub mod spreadsheet;
 pub mod types;
@@ -0,0 +1,474 @@
+//! Excel templates and import for initial population.
+//!
+//! Each [`SheetKind`] has a downloadable XLSX template whose header row
+//! names the columns. An uploaded workbook is read from its first sheet and
+//! every row is checked before anything is written: a file with any row
+//! error imports nothing, so administrators can fix the listed rows and
+//! upload the same file again. Valid rows become a native [`ImportPayload`]
+//! and go through [`import::import_data`] in one transaction.
+
+use std::collections::{HashMap, HashSet};
+use std::io::Cursor;
+
+use calamine::{Data, Reader, Xlsx};
+use rust_xlsxwriter::{DataValidation, Format, Workbook, XlsxError};
+use sqlx::PgPool;
+
+use super::import;
+use super::types::{ConflictMode, EntityImport, ImportPayload, RelationImport};
+
+/// What a template populates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SheetKind {
+    Tors,
+    Positions,
+    /// Standing items of every meeting, stored as protocol steps.
+    AgendaItems,
+}
+
+pub const SHEET_KINDS: [SheetKind; 3] = [SheetKind::Tors, SheetKind::Positions, SheetKind::AgendaItems];
+
+/// A template column. `key` is the entity property it fills, or one of the
+/// structural keys `name`, `label`, `tor` and `holder`.
+#[derive(Debug, Clone, Copy)]
+pub struct Column {
+    pub key: &'static str,
+    pub header: &'static str,
+    pub required: bool,
+    /// Accepted values; empty for free text.
+    pub allowed: &'static [&'static str],
+    pub hint: &'static str,
+}
+
+const fn col(key: &'static str, header: &'static str, required: bool, hint: &'static str) -> Column {
+    Column { key, header, required, allowed: &[], hint }
+}
+
+const fn choice(key: &'static str, header: &'static str, allowed: &'static [&'static str], hint: &'static str) -> Column {
+    Column { key, header, required: false, allowed, hint }
+}
+
+const TOR_COLUMNS: &[Column] = &[
+    col("name", "Name", true, "Unique identifier, e.g. finance_board"),
+    col("label", "Label", true, "Display name"),
+    col("description", "Description", false, "Up to 500 characters"),
+    choice("status", "Status", &["active", "archived"], "Defaults to active"),
+    choice("meeting_cadence", "Meeting cadence", &["ad-hoc", "weekly", "biweekly", "monthly", "daily", "working_days"], "Defaults to ad-hoc"),
+    choice("cadence_day", "Cadence day", &["monday", "tuesday", "wednesday", "thursday", "friday"], "Day of the week for weekly cadences"),
+    col("cadence_time", "Cadence time", false, "Start time as HH:MM"),
+    col("cadence_duration_minutes", "Duration (minutes)", false, "Defaults to 60"),
+    col("default_location", "Default location", false, "Room or address"),
+];
+
+const POSITION_COLUMNS: &[Column] = &[
+    col("tor", "ToR", true, "Name of an existing ToR"),
+    col("name", "Name", true, "Unique identifier, e.g. finance_board_chair"),
+    col("label", "Label", true, "Position title, e.g. Chair"),
+    choice("membership_type", "Membership type", &["mandatory", "optional"], "Defaults to optional"),
+    col("category", "Category", false, "Free text grouping"),
+    col("holder", "Holder", false, "Username of the current holder; empty for vacant"),
+];
+
+const AGENDA_ITEM_COLUMNS: &[Column] = &[
+    col("tor", "ToR", true, "Name of an existing ToR"),
+    col("name", "Name", true, "Unique identifier, e.g. finance_board_approve_minutes"),
+    col("label", "Label", true, "Item title as shown on agendas"),
+    choice("step_type", "Type", &["procedural", "agenda_slot", "fixed"], "Defaults to procedural"),
+    col("sequence_order", "Order", false, "Position in the meeting; defaults to the row order"),
+    col("default_duration_minutes", "Duration (minutes)", false, "Time allotted"),
+    choice("is_required", "Required", &["yes", "no"], "Defaults to yes"),
+    col("responsible", "Responsible", false, "Role or position leading the item"),
+    col("description", "Description", false, "Up to 500 characters"),
+];
+
+impl SheetKind {
+    pub fn slug(&self) -> &'static str {
+        match self {
+            SheetKind::Tors => "tors",
+            SheetKind::Positions => "positions",
+            SheetKind::AgendaItems => "agenda-items",
+        }
+    }
+
+    pub fn from_slug(slug: &str) -> Option<Self> {
+        SHEET_KINDS.into_iter().find(|k| k.slug() == slug)
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SheetKind::Tors => "Terms of Reference",
+            SheetKind::Positions => "Positions",
+            SheetKind::AgendaItems => "Recurring agenda items",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            SheetKind::Tors => "One row per governance body, with its meeting cadence.",
+            SheetKind::Positions => "Positions within existing ToRs and, optionally, who holds them.",
+            SheetKind::AgendaItems => "Standing items added to every meeting of an existing ToR.",
+        }
+    }
+
+    pub fn columns(&self) -> &'static [Column] {
+        match self {
+            SheetKind::Tors => TOR_COLUMNS,
+            SheetKind::Positions => POSITION_COLUMNS,
+            SheetKind::AgendaItems => AGENDA_ITEM_COLUMNS,
+        }
+    }
+
+    fn entity_type(&self) -> &'static str {
+        match self {
+            SheetKind::Tors => "tor",
+            SheetKind::Positions => "tor_function",
+            SheetKind::AgendaItems => "protocol_step",
+        }
+    }
+}
+
+/// A problem with one row. `row` is the spreadsheet row number, with the
+/// header on row 1; 0 means the file as a whole.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RowError {
+    pub row: usize,
+    pub column: String,
+    pub message: String,
+}
+
+impl RowError {
+    fn new(row: usize, column: &str, message: impl Into<String>) -> Self {
+        RowError { row, column: column.to_string(), message: message.into() }
+    }
+}
+
+/// A data row with its values keyed by column key. Empty cells are absent.
+#[derive(Debug, Clone)]
+pub struct SheetRow {
+    pub row: usize,
+    pub values: HashMap<&'static str, String>,
+}
+
+impl SheetRow {
+    pub fn get(&self, key: &str) -> &str {
+        self.values.get(key).map(|s| s.as_str()).unwrap_or("")
+    }
+}
+
+/// Outcome of an upload: the rows written, or the errors that stopped it.
+#[derive(Debug, Clone, Default)]
+pub struct SheetImportReport {
+    pub created: usize,
+    pub errors: Vec<RowError>,
+}
+
+/// Build the XLSX template for a kind: a header row with list validation on
+/// the choice columns, and an instructions sheet describing every column.
+pub fn build_template(kind: SheetKind) -> Result<Vec<u8>, XlsxError> {
+    let mut workbook = Workbook::new();
+    let header = Format::new().set_bold();
+
+    let sheet = workbook.add_worksheet();
+    sheet.set_name(kind.label())?;
+    sheet.set_freeze_panes(1, 0)?;
+    for (i, c) in kind.columns().iter().enumerate() {
+        let col = i as u16;
+        let title = if c.required { format!("{} *", c.header) } else { c.header.to_string() };
+        sheet.write_string_with_format(0, col, &title, &header)?;
+        sheet.set_column_width(col, (c.header.len() + 6).max(16) as f64)?;
+        if !c.allowed.is_empty() {
+            let validation = DataValidation::new()
+                .allow_list_strings(c.allowed)?
+                .set_input_message(c.hint)?;
+            sheet.add_data_validation(1, col, 1000, col, &validation)?;
+        }
+    }
+
+    let help = workbook.add_worksheet();
+    help.set_name("Instructions")?;
+    for (col, title) in ["Column", "Required", "Accepted values", "Notes"].into_iter().enumerate() {
+        help.write_string_with_format(0, col as u16, title, &header)?;
+    }
+    for (i, c) in kind.columns().iter().enumerate() {
+        let row = i as u32 + 1;
+        help.write_string(row, 0, c.header)?;
+        help.write_string(row, 1, if c.required { "Yes" } else { "No" })?;
+        help.write_string(row, 2, c.allowed.join(", "))?;
+        help.write_string(row, 3, c.hint)?;
+    }
+    help.set_column_width(0, 22)?;
+    help.set_column_width(2, 40)?;
+    help.set_column_width(3, 60)?;
+
+    workbook.save_to_buffer()
+}
+
+/// Text of a cell as typed: whole numbers without a fraction, dates as
+/// `YYYY-MM-DD` and times as `HH:MM`.
+fn cell_text(cell: &Data) -> String {
+    match cell {
+        Data::DateTime(dt) => match dt.as_datetime() {
+            Some(d) if dt.as_f64() < 1.0 => d.format("%H:%M").to_string(),
+            Some(d) => d.format("%Y-%m-%d").to_string(),
+            None => dt.to_string(),
+        },
+        Data::Bool(b) => if *b { "yes".to_string() } else { "no".to_string() },
+        other => other.to_string().trim().to_string(),
+    }
+}
+
+/// Cells of the first worksheet, one vector per row from row 1.
+pub fn read_workbook(bytes: &[u8]) -> Result<Vec<Vec<String>>, String> {
+    let mut workbook: Xlsx<_> = calamine::open_workbook_from_rs(Cursor::new(bytes))
+        .map_err(|_| "The file is not an Excel workbook (.xlsx)".to_string())?;
+    let range = workbook.worksheet_range_at(0)
+        .ok_or_else(|| "The workbook has no sheets".to_string())?
+        .map_err(|e| format!("The first sheet could not be read: {e}"))?;
+
+    // Rows before the range start are empty; keep the numbering intact
+    let skipped = range.start().map(|(row, _)| row as usize).unwrap_or(0);
+    let mut rows = vec![Vec::new(); skipped];
+    let first_col = range.start().map(|(_, col)| col as usize).unwrap_or(0);
+    for cells in range.rows() {
+        let mut row = vec![String::new(); first_col];
+        row.extend(cells.iter().map(cell_text));
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+fn is_number(value: &str) -> bool {
+    value.parse::<u32>().is_ok()
+}
+
+fn is_time(value: &str) -> bool {
+    chrono::NaiveTime::parse_from_str(value, "%H:%M").is_ok()
+}
+
+/// Map the header row onto the kind's columns and check each data row on
+/// its own: required cells, lengths, accepted values, number and time
+/// formats, and names repeated within the file. Blank rows are skipped.
+pub fn parse_rows(kind: SheetKind, cells: &[Vec<String>]) -> (Vec<SheetRow>, Vec<RowError>) {
+    let mut errors = Vec::new();
+    let Some(header) = cells.first() else {
+        return (Vec::new(), vec![RowError::new(0, "", "The sheet is empty")]);
+    };
+
+    // Column index by key; headers match case-insensitively, ignoring the
+    // required marker
+    let mut positions: Vec<(usize, &Column)> = Vec::new();
+    for (i, title) in header.iter().enumerate() {
+        let title = title.trim_end_matches('*').trim();
+        if let Some(c) = kind.columns().iter().find(|c| c.header.eq_ignore_ascii_case(title)) {
+            positions.push((i, c));
+        } else if !title.is_empty() {
+            errors.push(RowError::new(1, title, "Unknown column; download the template for the expected headers"));
+        }
+    }
+    for c in kind.columns().iter().filter(|c| c.required) {
+        if !positions.iter().any(|(_, p)| p.key == c.key) {
+            errors.push(RowError::new(1, c.header, "Required column is missing"));
+        }
+    }
+    if !errors.is_empty() {
+        return (Vec::new(), errors);
+    }
+
+    let mut rows = Vec::new();
+    let mut names: HashMap<String, usize> = HashMap::new();
+    for (i, raw) in cells.iter().enumerate().skip(1) {
+        let row = i + 1;
+        let mut values = HashMap::new();
+        for (idx, c) in &positions {
+            let value = raw.get(*idx).map(|s| s.trim()).unwrap_or("");
+            if value.is_empty() {
+                continue;
+            }
+            // Choices are stored lowercase whatever the sheet's casing
+            let value = if c.allowed.is_empty() { value.to_string() } else { value.to_lowercase() };
+            values.insert(c.key, value);
+        }
+        if values.is_empty() {
+            continue;
+        }
+
+        for (_, c) in &positions {
+            let value = values.get(c.key).map(|s| s.as_str()).unwrap_or("");
+            let max_len = match c.key {
+                "name" => 50,
+                "description" => 500,
+                _ => 100,
+            };
+            if value.is_empty() {
+                if c.required {
+                    errors.push(RowError::new(row, c.header, format!("{} is required", c.header)));
+                }
+            } else if value.chars().count() > max_len {
+                errors.push(RowError::new(row, c.header, format!("{} must be at most {} characters", c.header, max_len)));
+            } else if !c.allowed.is_empty() && !c.allowed.contains(&value) {
+                errors.push(RowError::new(row, c.header, format!("Use one of: {}", c.allowed.join(", "))));
+            } else if c.key.ends_with("_minutes") || c.key == "sequence_order" {
+                if !is_number(value) {
+                    errors.push(RowError::new(row, c.header, "Enter a whole number"));
+                }
+            } else if c.key == "cadence_time" && !is_time(value) {
+                errors.push(RowError::new(row, c.header, "Enter a time as HH:MM"));
+            }
+        }
+
+        if let Some(name) = values.get("name") {
+            if let Some(first) = names.get(name) {
+                errors.push(RowError::new(row, "Name", format!("Name '{}' is already used on row {}", name, first)));
+            } else {
+                names.insert(name.clone(), row);
+            }
+        }
+        rows.push(SheetRow { row, values });
+    }
+    if rows.is_empty() && errors.is_empty() {
+        errors.push(RowError::new(0, "", "The sheet has no data rows"));
+    }
+    (rows, errors)
+}
+
+/// Names of existing entities of a type among `names`.
+async fn existing_names(pool: &PgPool, entity_type: &str, names: &[String]) -> Result<HashSet<String>, sqlx::Error> {
+    let found: Vec<String> = sqlx::query_scalar(
+        "SELECT name FROM entities WHERE entity_type = $1 AND name = ANY($2)",
+    )
+    .bind(entity_type)
+    .bind(names)
+    .fetch_all(pool)
+    .await?;
+    Ok(found.into_iter().collect())
+}
+
+/// Check rows against the database: new names must be unused, and ToRs
+/// and holders must exist.
+pub async fn check_references(pool: &PgPool, kind: SheetKind, rows: &[SheetRow]) -> Result<Vec<RowError>, sqlx::Error> {
+    let collect = |key: &str| -> Vec<String> {
+        rows.iter().map(|r| r.get(key).to_string()).filter(|v| !v.is_empty()).collect()
+    };
+    let taken = existing_names(pool, kind.entity_type(), &collect("name")).await?;
+    let tors = existing_names(pool, "tor", &collect("tor")).await?;
+    let users = existing_names(pool, "user", &collect("holder")).await?;
+
+    let mut errors = Vec::new();
+    let mut held: HashMap<&str, usize> = HashMap::new();
+    for r in rows {
+        if taken.contains(r.get("name")) {
+            errors.push(RowError::new(r.row, "Name", format!("'{}' already exists", r.get("name"))));
+        }
+        let tor = r.get("tor");
+        if !tor.is_empty() && !tors.contains(tor) {
+            errors.push(RowError::new(r.row, "ToR", format!("No ToR named '{}'", tor)));
+        }
+        let holder = r.get("holder");
+        if !holder.is_empty() {
+            if !users.contains(holder) {
+                errors.push(RowError::new(r.row, "Holder", format!("No user named '{}'", holder)));
+            } else if let Some(first) = held.get(holder) {
+                errors.push(RowError::new(r.row, "Holder", format!("'{}' already holds the position on row {}", holder, first)));
+            } else {
+                held.insert(holder, r.row);
+            }
+        }
+    }
+    Ok(errors)
+}
+
+/// Native import payload for checked rows.
+pub fn to_payload(kind: SheetKind, rows: &[SheetRow]) -> ImportPayload {
+    let entity_type = kind.entity_type();
+    let mut entities = Vec::new();
+    let mut relations = Vec::new();
+    for (i, r) in rows.iter().enumerate() {
+        let mut properties: HashMap<String, String> = r.values.iter()
+            .filter(|(k, _)| !matches!(**k, "name" | "label" | "tor" | "holder"))
+            .map(|(k, v)| (k.to_string(), v.clone()))
+            .collect();
+        let reference = format!("{}:{}", entity_type, r.get("name"));
+        match kind {
+            SheetKind::Tors => {
+                properties.entry("status".to_string()).or_insert_with(|| "active".to_string());
+                properties.entry("meeting_cadence".to_string()).or_insert_with(|| "ad-hoc".to_string());
+                properties.entry("cadence_duration_minutes".to_string()).or_insert_with(|| "60".to_string());
+            }
+            SheetKind::Positions => {
+                properties.entry("membership_type".to_string()).or_insert_with(|| "optional".to_string());
+                relations.push(RelationImport {
+                    relation_type: "belongs_to_tor".to_string(),
+                    source: reference.clone(),
+                    target: format!("tor:{}", r.get("tor")),
+                    properties: HashMap::new(),
+                });
+                if !r.get("holder").is_empty() {
+                    relations.push(RelationImport {
+                        relation_type: "fills_position".to_string(),
+                        source: format!("user:{}", r.get("holder")),
+                        target: reference.clone(),
+                        properties: HashMap::new(),
+                    });
+                }
+            }
+            SheetKind::AgendaItems => {
+                properties.entry("step_type".to_string()).or_insert_with(|| "procedural".to_string());
+                properties.entry("sequence_order".to_string()).or_insert_with(|| (i + 1).to_string());
+                let required = properties.get("is_required").map(|v| v != "no").unwrap_or(true);
+                properties.insert("is_required".to_string(), required.to_string());
+                relations.push(RelationImport {
+                    relation_type: "protocol_of".to_string(),
+                    source: reference.clone(),
+                    target: format!("tor:{}", r.get("tor")),
+                    properties: HashMap::new(),
+                });
+            }
+        }
+        entities.push(EntityImport {
+            entity_type: entity_type.to_string(),
+            name: r.get("name").to_string(),
+            label: r.get("label").to_string(),
+            sort_order: 0,
+            properties,
+        });
+    }
+    ImportPayload {
+        schema_version: None,
+        conflict_mode: ConflictMode::Fail,
+        entities,
+        relations,
+    }
+}
+
+/// Read, check and import an uploaded workbook. Nothing is written unless
+/// every row passes.
+pub async fn import_workbook(pool: &PgPool, kind: SheetKind, bytes: &[u8]) -> Result<SheetImportReport, String> {
+    let cells = match read_workbook(bytes) {
+        Ok(cells) => cells,
+        Err(message) => return Ok(SheetImportReport { created: 0, errors: vec![RowError::new(0, "", message)] }),
+    };
+    let (rows, mut errors) = parse_rows(kind, &cells);
+    if !rows.is_empty() {
+        errors.extend(check_references(pool, kind, &rows).await.map_err(|e| e.to_string())?);
+    }
+    if !errors.is_empty() {
+        errors.sort_by_key(|e| e.row);
+        return Ok(SheetImportReport { created: 0, errors });
+    }
+
+    let result = import::import_data(pool, &to_payload(kind, &rows)).await?;
+    if let Some(err) = result.errors.first() {
+        return Err(err.reason.clone());
+    }
+
+    // Holders pick up their capabilities from the ToR defaults
+    if kind == SheetKind::Positions {
+        let tors: HashSet<&str> = rows.iter().map(|r| r.get("tor")).collect();
+        for name in tors {
+            if let Some(tor) = crate::models::entity::find_by_type_and_name(pool, "tor", name).await.map_err(|e| e.to_string())? {
+                crate::models::tor::apply_capabilities(pool, tor.id).await.map_err(|e| e.to_string())?;
+            }
+        }
+    }
+    Ok(SheetImportReport { created: result.created, errors: Vec::new() })
+}
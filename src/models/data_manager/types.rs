@@ -1,6 +1,11 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+/// Version of the native import/export format, embedded in every export as
+/// `schema_version`. Bump this whenever the payload shape changes and update
+/// the golden fixtures in `tests/fixtures/data_manager/` to match.
+pub const SCHEMA_VERSION: u32 = 1;
+
 /// How to handle conflicts when an entity with the same type+name already exists.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
@@ -20,6 +25,10 @@ impl Default for ConflictMode {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ImportPayload {
+    /// Format version the payload was produced with. Payloads written before
+    /// versioning was introduced omit it and are treated as version 1.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema_version: Option<u32>,
     #[serde(default)]
     pub conflict_mode: ConflictMode,
     #[serde(default)]
@@ -49,12 +58,31 @@ pub struct RelationImport {
     pub properties: HashMap<String, String>,
 }
 
+/// Counts cover entities; `items` reports every entity and relation.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ImportResult {
+    /// Set when the import was a dry run and nothing was committed.
+    #[serde(default)]
+    pub dry_run: bool,
     pub created: usize,
     pub updated: usize,
     pub skipped: usize,
     pub errors: Vec<ImportError>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub items: Vec<ImportItem>,
+}
+
+/// What the import did, or on a dry run would do, with one payload item.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportItem {
+    /// "entity" or "relation".
+    pub kind: String,
+    /// "type:name" for entities, "relation_type source -> target" for relations.
+    pub reference: String,
+    /// "create", "update", "skip" or "error".
+    pub action: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -67,8 +95,29 @@ pub struct ImportError {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExportPayload {
+    pub schema_version: u32,
+    /// Set on delta exports: only records changed after this time are included.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub since: Option<String>,
+    /// Set on delta exports: pass as the next `since` to continue the feed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub until: Option<String>,
     pub entities: Vec<EntityExport>,
     pub relations: Vec<RelationExport>,
+    /// Records deleted after `since`, on delta exports.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub deleted: Vec<DeletedRecord>,
+}
+
+/// A deleted entity or relation, identified by its id and reference.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct DeletedRecord {
+    /// "entity" or "relation".
+    pub kind: String,
+    pub id: i64,
+    /// "type:name" for entities, "relation_type source -> target" for relations.
+    pub reference: String,
+    pub deleted_at: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
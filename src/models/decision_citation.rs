@@ -0,0 +1,207 @@
+//! Decision citations: a stable citation ID and a content-addressed
+//! permalink for every recorded decision.
+//!
+//! The citation ID (e.g. `BOARD-2026-003`) is the ToR name, the year of the
+//! decision and a per-ToR, per-year sequence number. It is assigned once,
+//! when the decision is recorded, and never changes. The content hash is the
+//! SHA-256 of the decision's canonical JSON: its citation ID, ToR, agenda
+//! point, chosen course of action, decider, rationale and date. Only IDs and
+//! the decision's own text go in, so renaming a ToR or user leaves the hash
+//! intact. `/cite/{ref}` resolves either form, as does the permalink
+//! `/decisions/sha256/{hash}`; both serve JSON-LD on request.
+
+use serde_json::{json, Map, Value};
+use sha2::{Digest, Sha256};
+use sqlx::{PgPool, Postgres, Transaction};
+
+use crate::errors::AppError;
+use crate::models::opinion::{self, DecisionRegisterEntry};
+
+/// Properties hashed into the content address, besides the citation ID.
+const HASHED_PROPERTIES: &[&str] = &[
+    "agenda_point_id",
+    "selected_coa_id",
+    "decided_by_id",
+    "decision_rationale",
+    "decided_date",
+];
+
+/// Citation ID prefix for a ToR name: upper case, with runs of anything
+/// other than letters and digits as single dashes (`ops_board` -> `OPS-BOARD`).
+pub fn prefix(tor_name: &str) -> String {
+    let mut out = String::new();
+    for c in tor_name.chars() {
+        if c.is_ascii_alphanumeric() {
+            out.push(c.to_ascii_uppercase());
+        } else if !out.is_empty() && !out.ends_with('-') {
+            out.push('-');
+        }
+    }
+    let out = out.trim_end_matches('-');
+    if out.is_empty() { "TOR".to_string() } else { out.to_string() }
+}
+
+/// Canonical JSON of a decision: keys sorted, no whitespace.
+pub fn canonical(citation_id: &str, tor_id: i64, properties: &[(&str, String)]) -> String {
+    let mut map = Map::new();
+    map.insert("citation_id".to_string(), Value::from(citation_id));
+    map.insert("tor_id".to_string(), Value::from(tor_id));
+    for (key, value) in properties {
+        map.insert(key.to_string(), Value::from(value.as_str()));
+    }
+    Value::Object(map).to_string()
+}
+
+pub fn content_hash(canonical: &str) -> String {
+    hex::encode(Sha256::digest(canonical.as_bytes()))
+}
+
+/// Assign the citation ID and content hash of a decision within the
+/// transaction recording it. Locks the ToR so concurrent decisions get
+/// distinct sequence numbers. Returns the citation ID; a decision that
+/// already has one keeps it.
+pub async fn assign_in_tx(
+    tx: &mut Transaction<'_, Postgres>,
+    decision_id: i64,
+) -> Result<String, sqlx::Error> {
+    let existing: Option<String> = sqlx::query_scalar(
+        "SELECT value FROM entity_properties WHERE entity_id = $1 AND key = 'citation_id'",
+    )
+    .bind(decision_id)
+    .fetch_optional(&mut **tx)
+    .await?;
+    if let Some(citation_id) = existing {
+        return Ok(citation_id);
+    }
+
+    let properties: Vec<(String, String)> = sqlx::query_as(
+        "SELECT key, value FROM entity_properties WHERE entity_id = $1",
+    )
+    .bind(decision_id)
+    .fetch_all(&mut **tx)
+    .await?;
+    let property = |key: &str| properties.iter()
+        .find(|(k, _)| k == key)
+        .map(|(_, v)| v.clone())
+        .unwrap_or_default();
+
+    let (tor_id, tor_name): (i64, String) = sqlx::query_as(
+        "SELECT t.id, t.name FROM entities t \
+         JOIN relations r ON r.target_id = t.id \
+             AND r.relation_type_id = (SELECT id FROM entities WHERE entity_type = 'relation_type' AND name = 'belongs_to_tor') \
+         WHERE r.source_id = $1::BIGINT AND t.entity_type = 'tor' \
+         ORDER BY t.id LIMIT 1 \
+         FOR UPDATE OF t",
+    )
+    .bind(property("agenda_point_id").parse::<i64>().unwrap_or(0))
+    .fetch_optional(&mut **tx)
+    .await?
+    .unwrap_or((0, String::new()));
+
+    let date = property("decided_date");
+    let year = date.get(..4).filter(|y| y.chars().all(|c| c.is_ascii_digit())).unwrap_or("0000");
+    let stem = format!("{}-{}-", prefix(&tor_name), year);
+    let last: i32 = sqlx::query_scalar(
+        "SELECT COALESCE(MAX(SUBSTRING(value FROM LENGTH($1) + 1)::INT), 0) \
+         FROM entity_properties \
+         WHERE key = 'citation_id' AND value ~ ('^' || $1 || '[0-9]+$')",
+    )
+    .bind(&stem)
+    .fetch_one(&mut **tx)
+    .await?;
+    let citation_id = format!("{}{:03}", stem, last + 1);
+
+    let hashed: Vec<(&str, String)> = HASHED_PROPERTIES.iter().map(|k| (*k, property(k))).collect();
+    let hash = content_hash(&canonical(&citation_id, tor_id, &hashed));
+    crate::models::entity::set_property_in_tx(tx, decision_id, "citation_id", &citation_id).await?;
+    crate::models::entity::set_property_in_tx(tx, decision_id, "content_hash", &hash).await?;
+    Ok(citation_id)
+}
+
+/// Give citations to decisions recorded before citations existed, oldest
+/// first. Run at startup; returns how many were assigned.
+pub async fn backfill(pool: &PgPool) -> Result<usize, sqlx::Error> {
+    let ids: Vec<i64> = sqlx::query_scalar(
+        "SELECT d.id FROM entities d \
+         LEFT JOIN entity_properties p_date ON d.id = p_date.entity_id AND p_date.key = 'decided_date' \
+         WHERE d.entity_type = 'decision' \
+           AND NOT EXISTS (SELECT 1 FROM entity_properties c WHERE c.entity_id = d.id AND c.key = 'citation_id') \
+         ORDER BY p_date.value NULLS LAST, d.id",
+    )
+    .fetch_all(pool)
+    .await?;
+    for &id in &ids {
+        let mut tx = pool.begin().await?;
+        assign_in_tx(&mut tx, id).await?;
+        tx.commit().await?;
+    }
+    Ok(ids.len())
+}
+
+/// Whether the decision still hashes to its recorded content address.
+pub async fn verify(pool: &PgPool, entry: &DecisionRegisterEntry) -> Result<bool, sqlx::Error> {
+    let properties = crate::models::entity::get_properties(pool, entry.id).await?;
+    let hashed: Vec<(&str, String)> = HASHED_PROPERTIES.iter()
+        .map(|k| (*k, properties.get(*k).cloned().unwrap_or_default()))
+        .collect();
+    Ok(content_hash(&canonical(&entry.citation_id, entry.tor_id, &hashed)) == entry.content_hash)
+}
+
+/// The decision a citation ID (any case) or a SHA-256 content hash refers to.
+pub async fn resolve(pool: &PgPool, reference: &str) -> Result<Option<DecisionRegisterEntry>, AppError> {
+    let reference = reference.trim();
+    let (key, value) = if crate::models::export_record::is_sha256_hex(&reference.to_lowercase()) {
+        ("content_hash", reference.to_lowercase())
+    } else {
+        ("citation_id", reference.to_uppercase())
+    };
+    let found: Option<(i64, i64)> = sqlx::query_as(
+        "SELECT d.id, r.target_id FROM entities d \
+         JOIN entity_properties p ON d.id = p.entity_id AND p.key = $1 AND p.value = $2 \
+         JOIN entity_properties p_ap ON d.id = p_ap.entity_id AND p_ap.key = 'agenda_point_id' \
+         JOIN relations r ON r.source_id::TEXT = p_ap.value \
+             AND r.relation_type_id = (SELECT id FROM entities WHERE entity_type = 'relation_type' AND name = 'belongs_to_tor') \
+         WHERE d.entity_type = 'decision' \
+         LIMIT 1",
+    )
+    .bind(key)
+    .bind(&value)
+    .fetch_optional(pool)
+    .await?;
+    let Some((decision_id, tor_id)) = found else {
+        return Ok(None);
+    };
+    Ok(opinion::find_decision_register(pool, Some(tor_id)).await?
+        .into_iter()
+        .find(|d| d.id == decision_id))
+}
+
+/// A decision as a schema.org `ChooseAction` in JSON-LD. `base_url` is the
+/// application's origin.
+pub fn to_json_ld(entry: &DecisionRegisterEntry, base_url: &str) -> Value {
+    json!({
+        "@context": "https://schema.org",
+        "@type": "ChooseAction",
+        "@id": format!("{base_url}{}", entry.permalink()),
+        "url": format!("{base_url}{}", entry.citation_url()),
+        "identifier": [
+            { "@type": "PropertyValue", "propertyID": "citation", "value": entry.citation_id },
+            { "@type": "PropertyValue", "propertyID": "sha256", "value": entry.content_hash },
+        ],
+        "name": format!("{}: {}", entry.citation_id, entry.agenda_point_title),
+        "object": {
+            "@type": "Thing",
+            "name": entry.agenda_point_title,
+            "url": format!("{base_url}/tor/{}/workflow/agenda/{}", entry.tor_id, entry.agenda_point_id),
+        },
+        "actionOption": entry.selected_coa_title,
+        "description": entry.decision_rationale,
+        "agent": { "@type": "Person", "name": entry.decided_by() },
+        "participant": {
+            "@type": "Organization",
+            "name": entry.tor_label,
+            "url": format!("{base_url}/tor/{}", entry.tor_id),
+        },
+        "endTime": crate::models::feed::local_to_rfc3339(&entry.decided_date),
+    })
+}
@@ -0,0 +1,151 @@
+//! Out-of-office delegation: while a user is away, a named delegate may
+//! review proposals and record decisions on their behalf.
+//!
+//! The window is stored on the user entity as `ooo_delegate_id`,
+//! `ooo_start` and `ooo_end` (inclusive `YYYY-MM-DD` dates) plus an optional
+//! `ooo_note`. Actions taken by the delegate are attributed to both users.
+
+use chrono::NaiveDate;
+use sqlx::PgPool;
+
+use crate::auth::session::Permissions;
+use super::{entity, permission, tor};
+
+const KEYS: &[&str] = &["ooo_delegate_id", "ooo_start", "ooo_end", "ooo_note"];
+
+#[derive(Debug, Clone, PartialEq, sqlx::FromRow)]
+pub struct OutOfOffice {
+    pub user_id: i64,
+    pub user_name: String,
+    pub delegate_id: i64,
+    pub delegate_name: String,
+    pub start_date: String,
+    pub end_date: String,
+    pub note: String,
+}
+
+impl OutOfOffice {
+    /// Whether the window covers the given day.
+    pub fn is_active_on(&self, day: NaiveDate) -> bool {
+        let day = day.format("%Y-%m-%d").to_string();
+        self.start_date <= day && day <= self.end_date
+    }
+
+    /// Whether the window has not yet ended on the given day.
+    pub fn is_current_on(&self, day: NaiveDate) -> bool {
+        day.format("%Y-%m-%d").to_string() <= self.end_date
+    }
+
+    /// Attribution for audit entries and minutes, e.g. "Bob on behalf of Alice".
+    pub fn attribution(&self) -> String {
+        format!("{} on behalf of {}", self.delegate_name, self.user_name)
+    }
+}
+
+/// Check an out-of-office window before saving it.
+pub fn validate(user_id: i64, delegate_id: i64, start: &str, end: &str) -> Vec<String> {
+    let mut errors = Vec::new();
+    if delegate_id <= 0 {
+        errors.push("Choose a delegate".to_string());
+    } else if delegate_id == user_id {
+        errors.push("You cannot delegate to yourself".to_string());
+    }
+    let start = NaiveDate::parse_from_str(start.trim(), "%Y-%m-%d");
+    let end = NaiveDate::parse_from_str(end.trim(), "%Y-%m-%d");
+    match (start, end) {
+        (Ok(start), Ok(end)) if end < start => errors.push("The end date must not be before the start date".to_string()),
+        (Ok(_), Ok(_)) => {}
+        _ => errors.push("Enter a start and end date".to_string()),
+    }
+    errors
+}
+
+const SELECT: &str =
+    "SELECT u.id AS user_id, u.label AS user_name, \
+            d.id AS delegate_id, d.label AS delegate_name, \
+            p_start.value AS start_date, p_end.value AS end_date, \
+            COALESCE(p_note.value, '') AS note \
+     FROM entities u \
+     JOIN entity_properties p_del ON u.id = p_del.entity_id AND p_del.key = 'ooo_delegate_id' \
+     JOIN entities d ON d.id::TEXT = p_del.value AND d.entity_type = 'user' \
+     JOIN entity_properties p_start ON u.id = p_start.entity_id AND p_start.key = 'ooo_start' \
+     JOIN entity_properties p_end ON u.id = p_end.entity_id AND p_end.key = 'ooo_end' \
+     LEFT JOIN entity_properties p_note ON u.id = p_note.entity_id AND p_note.key = 'ooo_note' \
+     WHERE u.entity_type = 'user'";
+
+/// The user's out-of-office window, if one is set.
+pub async fn find_for_user(pool: &PgPool, user_id: i64) -> Result<Option<OutOfOffice>, sqlx::Error> {
+    sqlx::query_as::<_, OutOfOffice>(&format!("{SELECT} AND u.id = $1"))
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await
+}
+
+/// Set (or replace) the user's out-of-office window.
+pub async fn set_out_of_office(
+    pool: &PgPool,
+    user_id: i64,
+    delegate_id: i64,
+    start: &str,
+    end: &str,
+    note: &str,
+) -> Result<(), sqlx::Error> {
+    entity::set_properties(pool, user_id, &[
+        ("ooo_delegate_id", &delegate_id.to_string()),
+        ("ooo_start", start.trim()),
+        ("ooo_end", end.trim()),
+        ("ooo_note", note.trim()),
+    ]).await
+}
+
+/// Remove the user's out-of-office window.
+pub async fn clear_out_of_office(pool: &PgPool, user_id: i64) -> Result<(), sqlx::Error> {
+    for key in KEYS {
+        entity::delete_property(pool, user_id, key).await?;
+    }
+    Ok(())
+}
+
+/// Every out-of-office window naming `delegate_id` as delegate, past ones
+/// included, by user.
+pub async fn find_covered_by(pool: &PgPool, delegate_id: i64) -> Result<Vec<OutOfOffice>, sqlx::Error> {
+    sqlx::query_as::<_, OutOfOffice>(&format!("{SELECT} AND d.id = $1 ORDER BY u.label"))
+        .bind(delegate_id)
+        .fetch_all(pool)
+        .await
+}
+
+/// Users who are away on `day` and have delegated to `delegate_id`.
+pub async fn find_active_delegators(
+    pool: &PgPool,
+    delegate_id: i64,
+    day: NaiveDate,
+) -> Result<Vec<OutOfOffice>, sqlx::Error> {
+    let day = day.format("%Y-%m-%d").to_string();
+    sqlx::query_as::<_, OutOfOffice>(&format!(
+        "{SELECT} AND d.id = $1 AND p_start.value <= $2 AND p_end.value >= $2 ORDER BY u.label"
+    ))
+    .bind(delegate_id)
+    .bind(&day)
+    .fetch_all(pool)
+    .await
+}
+
+/// Absent members of a ToR the delegate may act for on `day`, with the
+/// permissions each of them holds.
+pub async fn find_principals_in_tor(
+    pool: &PgPool,
+    delegate_id: i64,
+    tor_id: i64,
+    day: NaiveDate,
+) -> Result<Vec<(OutOfOffice, Permissions)>, sqlx::Error> {
+    let mut principals = Vec::new();
+    for ooo in find_active_delegators(pool, delegate_id, day).await? {
+        if !tor::find_tor_ids_for_user(pool, ooo.user_id).await.contains(&tor_id) {
+            continue;
+        }
+        let permissions = Permissions(permission::find_codes_by_user_id(pool, ooo.user_id).await?);
+        principals.push((ooo, permissions));
+    }
+    Ok(principals)
+}
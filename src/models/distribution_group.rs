@@ -0,0 +1,280 @@
+//! Distribution groups derived from ToR positions.
+//!
+//! A group is a rule over positions, not a stored member list: members are
+//! whoever currently fills a matching position, so groups follow rotations
+//! and vacancies without upkeep. Every active ToR has a built-in
+//! "members of" group (key `tor-<id>`); further groups are stored as
+//! `distribution_group` entities (key `<id>`) with the properties
+//! `tor_id`, `position_match` and `membership_type`, each empty for "any".
+
+use sqlx::PgPool;
+
+use super::entity;
+
+/// Membership type filters, as (key, label).
+pub const MEMBERSHIP_FILTERS: &[(&str, &str)] = &[
+    ("", "Any membership"),
+    ("mandatory", "Mandatory members"),
+    ("optional", "Optional members"),
+];
+
+/// Which positions a group covers.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GroupRule {
+    /// Limit to one ToR; `None` covers every active ToR.
+    pub tor_id: Option<i64>,
+    /// Case-insensitive text the position label or name must contain.
+    pub position_match: String,
+    /// `mandatory`, `optional` or empty for any.
+    pub membership_type: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct DistributionGroup {
+    /// `tor-<id>` for built-in groups, the entity id for stored ones.
+    pub key: String,
+    /// Entity id of a stored group; `None` for built-in groups.
+    pub id: Option<i64>,
+    pub label: String,
+    pub description: String,
+    pub rule: GroupRule,
+    /// ToR label when the rule is limited to one ToR.
+    pub tor_label: Option<String>,
+}
+
+impl DistributionGroup {
+    pub fn is_builtin(&self) -> bool {
+        self.id.is_none()
+    }
+
+    /// Human-readable summary of the rule.
+    pub fn describe(&self) -> String {
+        let positions = if self.rule.position_match.is_empty() {
+            "All positions".to_string()
+        } else {
+            format!("Positions matching \u{201c}{}\u{201d}", self.rule.position_match)
+        };
+        let membership = match self.rule.membership_type.as_str() {
+            "mandatory" => " held by mandatory members",
+            "optional" => " held by optional members",
+            _ => "",
+        };
+        let scope = match &self.tor_label {
+            Some(label) => format!(" in {}", label),
+            None => " in every ToR".to_string(),
+        };
+        format!("{}{}{}", positions, membership, scope)
+    }
+}
+
+/// A user in a group, with the positions that put them there.
+#[derive(Debug, Clone)]
+pub struct GroupMember {
+    pub user_id: i64,
+    pub user_label: String,
+    pub email: String,
+    /// "Position (ToR)" entries.
+    pub positions: Vec<String>,
+}
+
+impl GroupMember {
+    /// `Name <address>` for a mail client's address field.
+    pub fn address(&self) -> String {
+        if self.user_label.chars().any(|c| "\",;<>@()".contains(c)) {
+            format!("\"{}\" <{}>", self.user_label.replace('"', "'"), self.email)
+        } else {
+            format!("{} <{}>", self.user_label, self.email)
+        }
+    }
+}
+
+/// Check a group definition. Returns a list of problems.
+pub fn validate(label: &str, rule: &GroupRule) -> Vec<String> {
+    let mut errors = Vec::new();
+    if label.trim().is_empty() {
+        errors.push("Enter a name for the group".to_string());
+    }
+    if !MEMBERSHIP_FILTERS.iter().any(|(k, _)| *k == rule.membership_type) {
+        errors.push("Choose a membership type".to_string());
+    }
+    if rule.tor_id.is_none() && rule.position_match.trim().is_empty() && rule.membership_type.is_empty() {
+        errors.push("Limit the group to a ToR, a position or a membership type".to_string());
+    }
+    errors
+}
+
+/// Built-in groups first (by ToR label), then stored groups by label.
+pub async fn find_all(pool: &PgPool) -> Result<Vec<DistributionGroup>, sqlx::Error> {
+    let tors: Vec<(i64, String)> = sqlx::query_as(
+        "SELECT id, label FROM entities WHERE entity_type = 'tor' AND is_active = true ORDER BY label",
+    )
+    .fetch_all(pool)
+    .await?;
+    let mut groups: Vec<DistributionGroup> = tors.into_iter().map(|(id, label)| builtin(id, label)).collect();
+
+    let ids: Vec<(i64,)> = sqlx::query_as(
+        "SELECT id FROM entities WHERE entity_type = 'distribution_group' ORDER BY label, id",
+    )
+    .fetch_all(pool)
+    .await?;
+    for (id,) in ids {
+        if let Some(group) = find_stored(pool, id).await? {
+            groups.push(group);
+        }
+    }
+    Ok(groups)
+}
+
+/// Look up a group by its key (`tor-<id>` or a stored group id).
+pub async fn find_by_key(pool: &PgPool, key: &str) -> Result<Option<DistributionGroup>, sqlx::Error> {
+    if let Some(tor_id) = key.strip_prefix("tor-").and_then(|id| id.parse::<i64>().ok()) {
+        let tor: Option<(String,)> = sqlx::query_as(
+            "SELECT label FROM entities WHERE id = $1 AND entity_type = 'tor' AND is_active = true",
+        )
+        .bind(tor_id)
+        .fetch_optional(pool)
+        .await?;
+        return Ok(tor.map(|(label,)| builtin(tor_id, label)));
+    }
+    match key.parse::<i64>() {
+        Ok(id) => find_stored(pool, id).await,
+        Err(_) => Ok(None),
+    }
+}
+
+fn builtin(tor_id: i64, tor_label: String) -> DistributionGroup {
+    DistributionGroup {
+        key: format!("tor-{}", tor_id),
+        id: None,
+        label: format!("Members of {}", tor_label),
+        description: String::new(),
+        rule: GroupRule { tor_id: Some(tor_id), ..GroupRule::default() },
+        tor_label: Some(tor_label),
+    }
+}
+
+async fn find_stored(pool: &PgPool, id: i64) -> Result<Option<DistributionGroup>, sqlx::Error> {
+    let Some(e) = entity::find_by_id(pool, id).await? else {
+        return Ok(None);
+    };
+    if e.entity_type != "distribution_group" {
+        return Ok(None);
+    }
+    let props = entity::get_properties(pool, id).await?;
+    let prop = |key: &str| props.get(key).cloned().unwrap_or_default();
+    let tor_id = prop("tor_id").parse::<i64>().ok();
+    let tor_label = match tor_id {
+        Some(tor_id) => entity::find_by_id(pool, tor_id).await?.map(|t| t.label),
+        None => None,
+    };
+    Ok(Some(DistributionGroup {
+        key: id.to_string(),
+        id: Some(id),
+        label: e.label,
+        description: prop("description"),
+        rule: GroupRule {
+            tor_id,
+            position_match: prop("position_match"),
+            membership_type: prop("membership_type"),
+        },
+        tor_label,
+    }))
+}
+
+pub async fn create(pool: &PgPool, label: &str, description: &str, rule: &GroupRule) -> Result<i64, sqlx::Error> {
+    let name = entity::available_name(pool, "distribution_group", &slug(label)).await?;
+    let id = entity::create(pool, "distribution_group", &name, label.trim()).await?;
+    let tor_id = rule.tor_id.map(|id| id.to_string()).unwrap_or_default();
+    entity::set_properties(pool, id, &[
+        ("description", description.trim()),
+        ("tor_id", &tor_id),
+        ("position_match", rule.position_match.trim()),
+        ("membership_type", &rule.membership_type),
+    ]).await?;
+    Ok(id)
+}
+
+pub async fn delete(pool: &PgPool, id: i64) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM entities WHERE id = $1 AND entity_type = 'distribution_group'")
+        .bind(id)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+fn slug(label: &str) -> String {
+    let s: String = label.trim().to_lowercase().chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if s.is_empty() { "group".to_string() } else { s }
+}
+
+/// Current holders of the positions a rule covers, one entry per user.
+/// Inactive users and ToRs are left out.
+pub async fn find_members(pool: &PgPool, rule: &GroupRule) -> Result<Vec<GroupMember>, sqlx::Error> {
+    let rows: Vec<(i64, String, String, String, String)> = sqlx::query_as(
+        "SELECT u.id, u.label, COALESCE(p_email.value, ''), f.label, t.label \
+         FROM entities f \
+         JOIN relations r_tor ON r_tor.source_id = f.id \
+             AND r_tor.relation_type_id = ( \
+                 SELECT id FROM entities WHERE entity_type = 'relation_type' AND name = 'belongs_to_tor') \
+         JOIN entities t ON t.id = r_tor.target_id AND t.entity_type = 'tor' AND t.is_active = true \
+         JOIN relations r_fills ON r_fills.target_id = f.id \
+             AND r_fills.relation_type_id = ( \
+                 SELECT id FROM entities WHERE entity_type = 'relation_type' AND name = 'fills_position') \
+         JOIN entities u ON u.id = r_fills.source_id AND u.entity_type = 'user' AND u.is_active = true \
+         LEFT JOIN entity_properties p_email ON u.id = p_email.entity_id AND p_email.key = 'email' \
+         LEFT JOIN entity_properties p_mt ON f.id = p_mt.entity_id AND p_mt.key = 'membership_type' \
+         WHERE f.entity_type = 'tor_function' \
+           AND ($1::BIGINT IS NULL OR t.id = $1) \
+           AND ($2 = '' OR strpos(lower(f.label), lower($2)) > 0 OR strpos(lower(f.name), lower($2)) > 0) \
+           AND ($3 = '' OR COALESCE(p_mt.value, 'optional') = $3) \
+         ORDER BY u.label, u.id, t.label, f.label",
+    )
+    .bind(rule.tor_id)
+    .bind(rule.position_match.trim())
+    .bind(&rule.membership_type)
+    .fetch_all(pool)
+    .await?;
+
+    let mut members: Vec<GroupMember> = Vec::new();
+    for (user_id, user_label, email, position, tor) in rows {
+        let entry = format!("{} ({})", position, tor);
+        match members.last_mut() {
+            Some(m) if m.user_id == user_id => m.positions.push(entry),
+            _ => members.push(GroupMember { user_id, user_label, email, positions: vec![entry] }),
+        }
+    }
+    Ok(members)
+}
+
+/// Addresses for pasting into a mail client, one per line. Members without
+/// an email address are skipped.
+pub fn to_address_list(members: &[GroupMember]) -> String {
+    members.iter()
+        .filter(|m| !m.email.is_empty())
+        .map(|m| format!("{};\n", m.address()))
+        .collect()
+}
+
+/// Name, Email and Positions columns, for importing as a contact group.
+pub fn to_csv(members: &[GroupMember]) -> String {
+    let mut out = String::from("Name,Email,Positions\n");
+    for m in members {
+        out.push_str(&format!(
+            "{},{},{}\n",
+            escape_csv(&m.user_label),
+            escape_csv(&m.email),
+            escape_csv(&m.positions.join("; ")),
+        ));
+    }
+    out
+}
+
+fn escape_csv(s: &str) -> String {
+    if s.contains(',') || s.contains('"') || s.contains('\n') {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
@@ -1,3 +1,4 @@
+pub mod preview;
 pub mod queries;
 pub mod types;
 
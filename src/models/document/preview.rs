@@ -0,0 +1,197 @@
+//! First-page thumbnails of documents, so proposal pages and meeting packs
+//! can show what an attachment looks like without downloading it.
+//!
+//! A document body holding a `data:image/...;base64,` URI is shown as the
+//! image, one holding a `data:application/pdf;base64,` URI as a PDF cover
+//! with its page count, anything else as the first lines of text. Thumbnails
+//! are SVG, cached on disk under the `previews.cache_path` setting and keyed
+//! by a fingerprint of the content so an edited document gets a new one.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use base64::Engine;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+
+use super::types::DocumentDetail;
+use crate::models::setting;
+
+pub const WIDTH: u32 = 160;
+pub const HEIGHT: u32 = 220;
+
+/// Text lines shown on a text thumbnail, and their width in characters.
+const TEXT_LINES: usize = 16;
+const TEXT_COLUMNS: usize = 30;
+
+/// Image types embedded as-is. SVG is excluded: it may carry script.
+const IMAGE_TYPES: &[&str] = &["image/png", "image/jpeg", "image/gif", "image/webp"];
+
+/// What a document's first page is made of.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PreviewKind {
+    /// Embedded image: its MIME type and base64 data.
+    Image { mime: String, data: String },
+    /// Embedded PDF and its page count.
+    Pdf { pages: usize },
+    Text,
+}
+
+/// Split a `data:<mime>;base64,<data>` URI.
+fn parse_data_uri(body: &str) -> Option<(&str, &str)> {
+    let rest = body.trim().strip_prefix("data:")?;
+    let (mime, data) = rest.split_once(";base64,")?;
+    Some((mime, data))
+}
+
+/// Classify a document body.
+pub fn detect(body: &str) -> PreviewKind {
+    let Some((mime, data)) = parse_data_uri(body) else {
+        return PreviewKind::Text;
+    };
+    let engine = base64::engine::general_purpose::STANDARD;
+    if IMAGE_TYPES.contains(&mime) && engine.decode(data).is_ok() {
+        return PreviewKind::Image { mime: mime.to_string(), data: data.to_string() };
+    }
+    if mime == "application/pdf"
+        && let Ok(bytes) = engine.decode(data)
+        && bytes.starts_with(b"%PDF")
+    {
+        return PreviewKind::Pdf { pages: count_pdf_pages(&bytes) };
+    }
+    PreviewKind::Text
+}
+
+/// Count `/Type /Page` objects (not `/Type /Pages`) in a PDF.
+pub fn count_pdf_pages(bytes: &[u8]) -> usize {
+    let mut pages = 0;
+    let mut i = 0;
+    while let Some(pos) = find(&bytes[i..], b"/Type") {
+        let mut j = i + pos + 5;
+        while j < bytes.len() && bytes[j].is_ascii_whitespace() {
+            j += 1;
+        }
+        if bytes[j..].starts_with(b"/Page") && bytes.get(j + 5) != Some(&b's') {
+            pages += 1;
+        }
+        i = j;
+    }
+    pages.max(1)
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+/// Identifies a version of a document's preview.
+pub fn fingerprint(doc: &DocumentDetail) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(doc.title.as_bytes());
+    hasher.update([0]);
+    hasher.update(doc.doc_type.as_bytes());
+    hasher.update([0]);
+    hasher.update(doc.body.as_bytes());
+    hex::encode(&hasher.finalize()[..8])
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Wrap text into at most `TEXT_LINES` lines of `TEXT_COLUMNS` characters.
+fn wrap(text: &str) -> Vec<String> {
+    let mut lines = Vec::new();
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            let word: String = word.chars().take(TEXT_COLUMNS).collect();
+            if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > TEXT_COLUMNS {
+                lines.push(std::mem::take(&mut line));
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(&word);
+        }
+        lines.push(line);
+        if lines.len() >= TEXT_LINES {
+            break;
+        }
+    }
+    lines.truncate(TEXT_LINES);
+    lines
+}
+
+/// Render the thumbnail of a document's first page as SVG.
+pub fn render_svg(doc: &DocumentDetail) -> String {
+    let title: String = doc.title.chars().take(TEXT_COLUMNS - 4).collect();
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{WIDTH}\" height=\"{HEIGHT}\" viewBox=\"0 0 {WIDTH} {HEIGHT}\">\
+         <rect x=\"0.5\" y=\"0.5\" width=\"{}\" height=\"{}\" fill=\"#fff\" stroke=\"#d0d5dd\"/>",
+        WIDTH - 1, HEIGHT - 1
+    );
+    match detect(&doc.body) {
+        PreviewKind::Image { mime, data } => {
+            svg.push_str(&format!(
+                "<image x=\"4\" y=\"4\" width=\"{}\" height=\"{}\" preserveAspectRatio=\"xMidYMid meet\" href=\"data:{mime};base64,{data}\"/>",
+                WIDTH - 8, HEIGHT - 8
+            ));
+        }
+        PreviewKind::Pdf { pages } => {
+            svg.push_str(&format!(
+                "<rect x=\"12\" y=\"14\" width=\"40\" height=\"18\" rx=\"3\" fill=\"#b42318\"/>\
+                 <text x=\"32\" y=\"27\" text-anchor=\"middle\" font-family=\"sans-serif\" font-size=\"10\" font-weight=\"bold\" fill=\"#fff\">PDF</text>\
+                 <text x=\"12\" y=\"54\" font-family=\"sans-serif\" font-size=\"11\" font-weight=\"bold\" fill=\"#101828\">{}</text>\
+                 <text x=\"12\" y=\"{}\" font-family=\"sans-serif\" font-size=\"9\" fill=\"#667085\">{pages} page{}</text>",
+                escape(&title),
+                HEIGHT - 14,
+                if pages == 1 { "" } else { "s" }
+            ));
+        }
+        PreviewKind::Text => {
+            svg.push_str(&format!(
+                "<text x=\"10\" y=\"20\" font-family=\"sans-serif\" font-size=\"10\" font-weight=\"bold\" fill=\"#101828\">{}</text>",
+                escape(&title)
+            ));
+            for (i, line) in wrap(&doc.body).iter().enumerate() {
+                svg.push_str(&format!(
+                    "<text x=\"10\" y=\"{}\" font-family=\"sans-serif\" font-size=\"7\" fill=\"#475467\">{}</text>",
+                    36 + i * 11,
+                    escape(line)
+                ));
+            }
+        }
+    }
+    svg.push_str("</svg>");
+    svg
+}
+
+/// Directory thumbnails are cached in.
+pub async fn cache_dir(pool: &PgPool) -> PathBuf {
+    let default = setting::definition("previews.cache_path").map(|d| d.default).unwrap_or("data/previews/");
+    PathBuf::from(setting::get_value(pool, "previews.cache_path", default).await)
+}
+
+/// The document's thumbnail, from the cache when its content is unchanged.
+/// Writing a new version removes the document's older ones.
+pub fn cached_thumbnail(dir: &Path, doc: &DocumentDetail) -> io::Result<String> {
+    let prefix = format!("document-{}-", doc.id);
+    let path = dir.join(format!("{prefix}{}.svg", fingerprint(doc)));
+    if let Ok(svg) = fs::read_to_string(&path) {
+        return Ok(svg);
+    }
+
+    fs::create_dir_all(dir)?;
+    for entry in fs::read_dir(dir)?.flatten() {
+        if entry.file_name().to_string_lossy().starts_with(&prefix) {
+            let _ = fs::remove_file(entry.path());
+        }
+    }
+    let svg = render_svg(doc);
+    fs::write(&path, &svg)?;
+    Ok(svg)
+}
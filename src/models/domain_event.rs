@@ -0,0 +1,123 @@
+//! Domain events: an append-only record of what happened to proposals,
+//! meetings and decisions, for integrations to consume in order.
+//!
+//! Events are appended inside the transaction that makes the change, so an
+//! event exists exactly when its change was committed. Readers page through
+//! the stream by id with [`find_after`]; events from transactions still in
+//! flight are held back so a consumer's cursor never skips one.
+
+use serde::Serialize;
+use sqlx::{PgPool, Postgres, Transaction};
+
+use crate::warnings::outbox;
+
+/// A recorded domain event.
+#[derive(Debug, Clone, Serialize)]
+pub struct DomainEvent {
+    pub id: i64,
+    pub event_type: String,
+    pub aggregate_type: String,
+    pub aggregate_id: i64,
+    pub actor_id: i64,
+    pub payload: serde_json::Value,
+    pub occurred_at: String,
+}
+
+#[derive(sqlx::FromRow)]
+struct DomainEventRow {
+    id: i64,
+    event_type: String,
+    aggregate_type: String,
+    aggregate_id: i64,
+    actor_id: i64,
+    payload: String,
+    occurred_at: String,
+}
+
+impl From<DomainEventRow> for DomainEvent {
+    fn from(row: DomainEventRow) -> Self {
+        DomainEvent {
+            id: row.id,
+            event_type: row.event_type,
+            aggregate_type: row.aggregate_type,
+            aggregate_id: row.aggregate_id,
+            actor_id: row.actor_id,
+            payload: serde_json::from_str(&row.payload).unwrap_or_default(),
+            occurred_at: row.occurred_at,
+        }
+    }
+}
+
+/// Most events returned by one read.
+pub const MAX_LIMIT: i64 = 500;
+
+/// Append an event within the transaction making the change it describes.
+/// The event type is `{aggregate_type}.{what happened}`, e.g. `proposal.submitted`.
+///
+/// Also queues a refresh of open dashboards; wake the outbox dispatcher once
+/// the transaction commits.
+pub async fn append(
+    tx: &mut Transaction<'_, Postgres>,
+    event_type: &str,
+    aggregate_type: &str,
+    aggregate_id: i64,
+    actor_id: i64,
+    payload: serde_json::Value,
+) -> Result<i64, sqlx::Error> {
+    let (id,): (i64,) = sqlx::query_as(
+        "INSERT INTO domain_events (event_type, aggregate_type, aggregate_id, actor_id, payload) \
+         VALUES ($1, $2, $3, $4, $5::JSONB) RETURNING id",
+    )
+    .bind(event_type)
+    .bind(aggregate_type)
+    .bind(aggregate_id)
+    .bind(actor_id)
+    .bind(payload.to_string())
+    .fetch_one(&mut **tx)
+    .await?;
+    outbox::enqueue(tx, outbox::CHANNEL_DASHBOARD, serde_json::json!({ "event_type": event_type })).await?;
+    Ok(id)
+}
+
+/// Committed events after `after` in id order, optionally only those whose
+/// type starts with `type_prefix` (`proposal.` or `meeting.confirmed`).
+pub async fn find_after(
+    pool: &PgPool,
+    after: i64,
+    type_prefix: Option<&str>,
+    limit: i64,
+) -> Result<Vec<DomainEvent>, sqlx::Error> {
+    let rows = sqlx::query_as::<_, DomainEventRow>(
+        "SELECT id, event_type, aggregate_type, aggregate_id, actor_id, payload::TEXT AS payload, \
+                to_char(occurred_at AT TIME ZONE 'UTC', 'YYYY-MM-DD\"T\"HH24:MI:SS.US\"Z\"') AS occurred_at \
+         FROM domain_events \
+         WHERE id > $1 \
+           AND transaction_id < pg_snapshot_xmin(pg_current_snapshot()) \
+           AND ($2::TEXT IS NULL OR starts_with(event_type, $2)) \
+         ORDER BY id LIMIT $3",
+    )
+    .bind(after)
+    .bind(type_prefix)
+    .bind(limit.clamp(1, MAX_LIMIT))
+    .fetch_all(pool)
+    .await?;
+    Ok(rows.into_iter().map(DomainEvent::from).collect())
+}
+
+/// Events about one proposal, meeting or decision, oldest first.
+pub async fn find_for_aggregate(
+    pool: &PgPool,
+    aggregate_type: &str,
+    aggregate_id: i64,
+) -> Result<Vec<DomainEvent>, sqlx::Error> {
+    let rows = sqlx::query_as::<_, DomainEventRow>(
+        "SELECT id, event_type, aggregate_type, aggregate_id, actor_id, payload::TEXT AS payload, \
+                to_char(occurred_at AT TIME ZONE 'UTC', 'YYYY-MM-DD\"T\"HH24:MI:SS.US\"Z\"') AS occurred_at \
+         FROM domain_events WHERE aggregate_type = $1 AND aggregate_id = $2 ORDER BY id",
+    )
+    .bind(aggregate_type)
+    .bind(aggregate_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows.into_iter().map(DomainEvent::from).collect())
+}
@@ -0,0 +1,333 @@
+//! Templates for outgoing emails.
+//!
+//! Every email the application sends has a built-in default (`BUILT_INS`).
+//! Admins may override the subject and body per locale; an override is an
+//! `email_template` entity named `{kind}.{locale}`. Placeholders are written
+//! `{{meeting.date}}` and replaced with the values passed when rendering.
+//!
+//! Lookup order when rendering for a locale such as `nb-NO`: the `nb-NO`
+//! override, the `nb` override, the default-locale override, the built-in.
+
+use std::collections::HashMap;
+
+use sqlx::PgPool;
+
+use super::entity;
+
+/// Locale of the built-in texts, used when no better variant exists.
+pub const DEFAULT_LOCALE: &str = "en";
+
+/// A kind of outgoing email with its default text.
+#[derive(Debug, Clone, Copy)]
+pub struct BuiltIn {
+    pub kind: &'static str,
+    pub label: &'static str,
+    pub description: &'static str,
+    pub subject: &'static str,
+    pub body: &'static str,
+    /// Placeholders available to this email, with sample values for previews.
+    pub variables: &'static [(&'static str, &'static str)],
+}
+
+pub const BUILT_INS: &[BuiltIn] = &[
+    BuiltIn {
+        kind: "meeting_invitation",
+        label: "Meeting invitation",
+        description: "Sent to ToR members when a meeting is scheduled.",
+        subject: "Invitation: {{tor.name}} meeting on {{meeting.date}}",
+        body: "Dear {{recipient.name}},\n\nYou are invited to the {{tor.name}} meeting on {{meeting.date}} at {{meeting.time}} in {{meeting.location}}.\n\nThe agenda is available at {{meeting.url}}.\n\n{{app.name}}",
+        variables: &[
+            ("recipient.name", "Alice Andersen"),
+            ("tor.name", "Finance Board"),
+            ("meeting.date", "2026-03-12"),
+            ("meeting.time", "09:00"),
+            ("meeting.location", "Room 4"),
+            ("meeting.url", "https://ahlt.example.org/meetings/42"),
+            ("app.name", "AHLT"),
+        ],
+    },
+    BuiltIn {
+        kind: "meeting_reminder",
+        label: "Meeting reminder",
+        description: "Sent to ToR members shortly before a meeting.",
+        subject: "Reminder: {{tor.name}} meets on {{meeting.date}}",
+        body: "Dear {{recipient.name}},\n\nA reminder that {{tor.name}} meets on {{meeting.date}} at {{meeting.time}} in {{meeting.location}}.\n\n{{meeting.url}}\n\n{{app.name}}",
+        variables: &[
+            ("recipient.name", "Alice Andersen"),
+            ("tor.name", "Finance Board"),
+            ("meeting.date", "2026-03-12"),
+            ("meeting.time", "09:00"),
+            ("meeting.location", "Room 4"),
+            ("meeting.url", "https://ahlt.example.org/meetings/42"),
+            ("app.name", "AHLT"),
+        ],
+    },
+    BuiltIn {
+        kind: "minutes_circulated",
+        label: "Minutes circulated",
+        description: "Sent to attendees when draft minutes are circulated for comment.",
+        subject: "Draft minutes: {{tor.name}} {{meeting.date}}",
+        body: "Dear {{recipient.name}},\n\nThe draft minutes of the {{tor.name}} meeting on {{meeting.date}} are ready for your comments until {{minutes.comment_deadline}}.\n\n{{minutes.url}}\n\n{{app.name}}",
+        variables: &[
+            ("recipient.name", "Alice Andersen"),
+            ("tor.name", "Finance Board"),
+            ("meeting.date", "2026-03-12"),
+            ("minutes.comment_deadline", "2026-03-19"),
+            ("minutes.url", "https://ahlt.example.org/minutes/7"),
+            ("app.name", "AHLT"),
+        ],
+    },
+    BuiltIn {
+        kind: "report_delivery",
+        label: "Report delivery",
+        description: "Sent when a subscribed report is generated.",
+        subject: "{{report.title}} ({{report.period}})",
+        body: "Dear {{recipient.name}},\n\nYour {{report.frequency}} report \"{{report.title}}\" for {{report.period}} is ready.\n\n{{report.url}}\n\n{{app.name}}",
+        variables: &[
+            ("recipient.name", "Alice Andersen"),
+            ("report.title", "Open proposals in my ToRs"),
+            ("report.period", "2026-02-01 to 2026-03-01"),
+            ("report.frequency", "monthly"),
+            ("report.url", "https://ahlt.example.org/reports/deliveries/3"),
+            ("app.name", "AHLT"),
+        ],
+    },
+    BuiltIn {
+        kind: "proposal_status",
+        label: "Proposal status",
+        description: "Sent to the author when someone else moves their proposal to a new status.",
+        subject: "Your proposal \"{{proposal.title}}\" is now {{proposal.status}}",
+        body: "Dear {{recipient.name}},\n\nYour proposal \"{{proposal.title}}\" to {{tor.name}} is now {{proposal.status}}.\n\n{{proposal.url}}\n\n{{app.name}}",
+        variables: &[
+            ("recipient.name", "Alice Andersen"),
+            ("proposal.title", "Extend the audit budget"),
+            ("proposal.status", "approved"),
+            ("tor.name", "Finance Board"),
+            ("proposal.url", "https://ahlt.example.org/tor/3/proposals/12"),
+            ("app.name", "AHLT"),
+        ],
+    },
+    BuiltIn {
+        kind: "notification",
+        label: "Notification",
+        description: "Sent for warnings, mentions and watch updates in the notification center.",
+        subject: "[{{app.name}}] {{notification.channel}}: {{notification.message}}",
+        body: "Dear {{recipient.name}},\n\n{{notification.message}}\n\n{{notification.url}}\n\n{{app.name}}",
+        variables: &[
+            ("recipient.name", "Alice Andersen"),
+            ("notification.channel", "Mentions"),
+            ("notification.message", "bob mentioned you in a suggestion"),
+            ("notification.url", "https://ahlt.example.org/notifications"),
+            ("app.name", "AHLT"),
+        ],
+    },
+];
+
+/// The built-in email of a kind.
+pub fn find_built_in(kind: &str) -> Option<&'static BuiltIn> {
+    BUILT_INS.iter().find(|b| b.kind == kind)
+}
+
+impl BuiltIn {
+    /// Sample values for previews.
+    pub fn sample(&self) -> HashMap<String, String> {
+        self.variables.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    pub fn variable_names(&self) -> Vec<&'static str> {
+        self.variables.iter().map(|(k, _)| *k).collect()
+    }
+}
+
+/// An admin's override of an email for one locale.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct EmailTemplate {
+    pub id: i64,
+    pub kind: String,
+    pub locale: String,
+    pub subject: String,
+    pub body: String,
+    pub updated_at: String,
+}
+
+/// An email ready to send.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderedEmail {
+    pub subject: String,
+    pub body: String,
+    /// Locale of the override used, or empty for the built-in text.
+    pub locale: String,
+}
+
+/// Placeholder names used in a text, in order of first use.
+pub fn placeholders(text: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else { break };
+        let name = after[..end].trim().to_string();
+        if !name.is_empty() && !names.contains(&name) {
+            names.push(name);
+        }
+        rest = &after[end + 2..];
+    }
+    names
+}
+
+/// Replace `{{name}}` placeholders with their values. Unknown placeholders
+/// are left as written so a mistake is visible rather than silently blank.
+pub fn substitute(text: &str, vars: &HashMap<String, String>) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            rest = &rest[start..];
+            break;
+        };
+        match vars.get(after[..end].trim()) {
+            Some(value) => out.push_str(value),
+            None => out.push_str(&rest[start..start + 2 + end + 2]),
+        }
+        rest = &after[end + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Whether a locale code looks like `en` or `nb-NO`.
+pub fn is_valid_locale(locale: &str) -> bool {
+    let mut parts = locale.split('-');
+    let lang = parts.next().unwrap_or("");
+    let region = parts.next();
+    parts.next().is_none()
+        && lang.len() == 2 && lang.chars().all(|c| c.is_ascii_lowercase())
+        && region.is_none_or(|r| r.len() == 2 && r.chars().all(|c| c.is_ascii_uppercase()))
+}
+
+/// Check an override before saving it.
+pub fn validate(built_in: &BuiltIn, locale: &str, subject: &str, body: &str) -> Vec<String> {
+    let mut errors = Vec::new();
+    if !is_valid_locale(locale) {
+        errors.push("Locale must be a language code such as 'en' or 'nb-NO'".to_string());
+    }
+    if subject.trim().is_empty() {
+        errors.push("Subject is required".to_string());
+    }
+    if subject.contains('\n') {
+        errors.push("Subject must be a single line".to_string());
+    }
+    if body.trim().is_empty() {
+        errors.push("Body is required".to_string());
+    }
+    let known = built_in.variable_names();
+    for name in placeholders(&format!("{subject}\n{body}")) {
+        if !known.contains(&name.as_str()) {
+            errors.push(format!("Unknown placeholder {{{{{name}}}}}"));
+        }
+    }
+    errors
+}
+
+const SELECT: &str =
+    "SELECT e.id, COALESCE(p_kind.value, '') AS kind, COALESCE(p_loc.value, '') AS locale, \
+            COALESCE(p_sub.value, '') AS subject, COALESCE(p_body.value, '') AS body, \
+            e.updated_at::TEXT AS updated_at \
+     FROM entities e \
+     LEFT JOIN entity_properties p_kind ON e.id = p_kind.entity_id AND p_kind.key = 'kind' \
+     LEFT JOIN entity_properties p_loc ON e.id = p_loc.entity_id AND p_loc.key = 'locale' \
+     LEFT JOIN entity_properties p_sub ON e.id = p_sub.entity_id AND p_sub.key = 'subject' \
+     LEFT JOIN entity_properties p_body ON e.id = p_body.entity_id AND p_body.key = 'body' \
+     WHERE e.entity_type = 'email_template'";
+
+/// All overrides, by kind then locale.
+pub async fn find_all(pool: &PgPool) -> Result<Vec<EmailTemplate>, sqlx::Error> {
+    sqlx::query_as::<_, EmailTemplate>(&format!("{SELECT} ORDER BY kind, locale"))
+        .fetch_all(pool)
+        .await
+}
+
+/// The override of an email for exactly this locale.
+pub async fn find(pool: &PgPool, kind: &str, locale: &str) -> Result<Option<EmailTemplate>, sqlx::Error> {
+    sqlx::query_as::<_, EmailTemplate>(&format!("{SELECT} AND e.name = $1"))
+        .bind(format!("{kind}.{locale}"))
+        .fetch_optional(pool)
+        .await
+}
+
+/// Create or replace the override of an email for a locale.
+pub async fn save(
+    pool: &PgPool,
+    kind: &str,
+    locale: &str,
+    subject: &str,
+    body: &str,
+) -> Result<i64, sqlx::Error> {
+    let id = match find(pool, kind, locale).await? {
+        Some(existing) => {
+            sqlx::query("UPDATE entities SET updated_at = NOW() WHERE id = $1")
+                .bind(existing.id)
+                .execute(pool)
+                .await?;
+            existing.id
+        }
+        None => {
+            let label = find_built_in(kind).map(|b| b.label).unwrap_or(kind);
+            entity::create(pool, "email_template", &format!("{kind}.{locale}"), &format!("{label} ({locale})")).await?
+        }
+    };
+    entity::set_properties(pool, id, &[
+        ("kind", kind),
+        ("locale", locale),
+        ("subject", subject.trim()),
+        ("body", body),
+    ]).await?;
+    Ok(id)
+}
+
+/// Remove an override, falling back to the next variant. Returns whether
+/// there was one.
+pub async fn reset(pool: &PgPool, kind: &str, locale: &str) -> Result<bool, sqlx::Error> {
+    match find(pool, kind, locale).await? {
+        Some(existing) => {
+            entity::delete(pool, existing.id).await?;
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+/// Render an email for a locale, falling back through the lookup order.
+/// Returns None for an unknown kind.
+pub async fn render(
+    pool: &PgPool,
+    kind: &str,
+    locale: &str,
+    vars: &HashMap<String, String>,
+) -> Result<Option<RenderedEmail>, sqlx::Error> {
+    let Some(built_in) = find_built_in(kind) else {
+        return Ok(None);
+    };
+    let mut candidates = vec![locale.to_string()];
+    if let Some((lang, _)) = locale.split_once('-') {
+        candidates.push(lang.to_string());
+    }
+    candidates.push(DEFAULT_LOCALE.to_string());
+
+    for candidate in candidates.iter().filter(|c| !c.is_empty()) {
+        if let Some(t) = find(pool, kind, candidate).await? {
+            return Ok(Some(RenderedEmail {
+                subject: substitute(&t.subject, vars),
+                body: substitute(&t.body, vars),
+                locale: t.locale,
+            }));
+        }
+    }
+    Ok(Some(RenderedEmail {
+        subject: substitute(built_in.subject, vars),
+        body: substitute(built_in.body, vars),
+        locale: String::new(),
+    }))
+}
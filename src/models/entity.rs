@@ -1,5 +1,5 @@
 #![allow(dead_code)]
-use sqlx::PgPool;
+use sqlx::{PgPool, Postgres, Transaction};
 use std::collections::HashMap;
 
 #[derive(Debug, Clone, sqlx::FromRow)]
@@ -64,6 +64,57 @@ pub async fn create(pool: &PgPool, entity_type: &str, name: &str, label: &str) -
     Ok(row.0)
 }
 
+/// Create a new entity within a transaction, returning its id.
+pub async fn create_in_tx(
+    tx: &mut Transaction<'_, Postgres>,
+    entity_type: &str,
+    name: &str,
+    label: &str,
+) -> Result<i64, sqlx::Error> {
+    let row: (i64,) = sqlx::query_as(
+        "INSERT INTO entities (entity_type, name, label) VALUES ($1, $2, $3) RETURNING id",
+    )
+    .bind(entity_type)
+    .bind(name)
+    .bind(label)
+    .fetch_one(&mut **tx)
+    .await?;
+    Ok(row.0)
+}
+
+/// Create a new entity unless the name is already taken for the type, returning
+/// its id, or `None` on conflict. The check and insert are one statement.
+pub async fn create_if_absent(pool: &PgPool, entity_type: &str, name: &str, label: &str) -> Result<Option<i64>, sqlx::Error> {
+    sqlx::query_scalar(
+        "INSERT INTO entities (entity_type, name, label) VALUES ($1, $2, $3) \
+         ON CONFLICT (entity_type, name) DO NOTHING RETURNING id",
+    )
+    .bind(entity_type)
+    .bind(name)
+    .bind(label)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Return `base`, or `base_2`, `base_3`, … if that name is already taken for the type.
+pub async fn available_name(pool: &PgPool, entity_type: &str, base: &str) -> Result<String, sqlx::Error> {
+    let taken: Vec<String> = sqlx::query_scalar(
+        "SELECT name FROM entities WHERE entity_type = $1 AND (name = $2 OR name LIKE $2 || '\\_%')",
+    )
+    .bind(entity_type)
+    .bind(base)
+    .fetch_all(pool)
+    .await?;
+    if !taken.iter().any(|n| n == base) {
+        return Ok(base.to_string());
+    }
+    let mut n = 2;
+    while taken.iter().any(|t| *t == format!("{}_{}", base, n)) {
+        n += 1;
+    }
+    Ok(format!("{}_{}", base, n))
+}
+
 /// Create a new entity with sort_order, returning its id.
 pub async fn create_with_sort(pool: &PgPool, entity_type: &str, name: &str, label: &str, sort_order: i64) -> Result<i64, sqlx::Error> {
     let row: (i64,) = sqlx::query_as(
@@ -91,8 +142,18 @@ pub async fn update(pool: &PgPool, id: i64, name: &str, label: &str) -> Result<(
     Ok(())
 }
 
-/// Delete an entity (cascades to properties and relations).
+/// Delete an entity. Types listed in `trash.entity_types` go to the trash
+/// and can be restored (see `trash`); anything else is removed for good.
 pub async fn delete(pool: &PgPool, id: i64) -> Result<(), sqlx::Error> {
+    if super::trash::move_to_trash(pool, id).await? {
+        return Ok(());
+    }
+    purge(pool, id).await
+}
+
+/// Delete an entity for good, bypassing the trash (cascades to properties
+/// and relations).
+pub async fn purge(pool: &PgPool, id: i64) -> Result<(), sqlx::Error> {
     sqlx::query("DELETE FROM entities WHERE id = $1")
         .bind(id)
         .execute(pool)
@@ -150,6 +211,25 @@ pub async fn set_property(pool: &PgPool, entity_id: i64, key: &str, value: &str)
     Ok(())
 }
 
+/// Set a property (upsert) within a transaction.
+pub async fn set_property_in_tx(
+    tx: &mut Transaction<'_, Postgres>,
+    entity_id: i64,
+    key: &str,
+    value: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO entity_properties (entity_id, key, value) VALUES ($1, $2, $3) \
+         ON CONFLICT(entity_id, key) DO UPDATE SET value = EXCLUDED.value",
+    )
+    .bind(entity_id)
+    .bind(key)
+    .bind(value)
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
 /// Delete a property.
 pub async fn delete_property(pool: &PgPool, entity_id: i64, key: &str) -> Result<(), sqlx::Error> {
     sqlx::query(
@@ -0,0 +1,180 @@
+//! Checksums of generated exports.
+//!
+//! Every download the application generates (data manager exports, handover
+//! packages, minutes, audit archives, address lists) is recorded with its
+//! SHA-256 digest. Downloads carry the digest in their headers and a manifest
+//! in `sha256sum` format can be fetched alongside, so a recipient can check a
+//! circulated file against `/verify/{sha256}` or with `sha256sum -c`.
+
+use std::io::Write;
+
+use base64::Engine;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use zip::write::SimpleFileOptions;
+
+use super::entity;
+
+/// A generated export and its digest.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct ExportRecord {
+    pub id: i64,
+    pub kind: String,
+    pub file_name: String,
+    pub sha256: String,
+    pub size_bytes: i64,
+    pub generated_by: i64,
+    pub generated_by_name: String,
+    pub generated_at: String,
+}
+
+impl ExportRecord {
+    /// One-line manifest for the file, checkable with `sha256sum -c`.
+    pub fn manifest(&self) -> String {
+        manifest(&[(self.file_name.as_str(), self.sha256.as_str())])
+    }
+
+    /// `Digest` header value (RFC 3230): the base64 of the raw digest.
+    pub fn digest_header(&self) -> String {
+        let raw = hex::decode(&self.sha256).unwrap_or_default();
+        format!("sha-256={}", base64::engine::general_purpose::STANDARD.encode(raw))
+    }
+}
+
+/// One line of a checked manifest and the exports it matches.
+#[derive(Debug, Clone)]
+pub struct ExportCheck {
+    /// File name from the manifest; empty when a bare digest was checked.
+    pub file_name: String,
+    pub sha256: String,
+    pub matches: Vec<ExportRecord>,
+}
+
+impl ExportCheck {
+    pub fn verified(&self) -> bool {
+        !self.matches.is_empty()
+    }
+
+    /// Whether a match was recorded under the file name being checked.
+    pub fn name_matches(&self) -> bool {
+        self.file_name.is_empty() || self.matches.iter().any(|r| r.file_name == self.file_name)
+    }
+}
+
+/// Hex SHA-256 of some bytes.
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    hex::encode(Sha256::digest(bytes))
+}
+
+/// Whether a string is a 64-character hex SHA-256.
+pub fn is_sha256_hex(value: &str) -> bool {
+    value.len() == 64 && value.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// A `sha256sum`-format manifest of (file name, hex digest) pairs.
+pub fn manifest(files: &[(&str, &str)]) -> String {
+    files.iter()
+        .map(|(name, sha)| format!("{sha}  {name}\n"))
+        .collect()
+}
+
+/// Parse a `sha256sum`-format manifest into (file name, hex digest) pairs.
+/// Lines that are not a digest followed by a file name are skipped.
+pub fn parse_manifest(text: &str) -> Vec<(String, String)> {
+    text.lines()
+        .filter_map(|line| {
+            let (sha, name) = line.trim().split_once(char::is_whitespace)?;
+            let name = name.trim_start().trim_start_matches('*');
+            (is_sha256_hex(sha) && !name.is_empty()).then(|| (name.to_string(), sha.to_lowercase()))
+        })
+        .collect()
+}
+
+/// Name of the manifest inside generated archives.
+pub const MANIFEST_FILE: &str = "SHA256SUMS";
+
+/// Zip files into one folder together with a `SHA256SUMS` manifest of them,
+/// so `sha256sum -c SHA256SUMS` inside the folder checks every file.
+pub fn zip_with_manifest(folder: &str, files: &[(String, Vec<u8>)]) -> Result<Vec<u8>, zip::result::ZipError> {
+    let digests: Vec<(&str, String)> = files.iter()
+        .map(|(name, contents)| (name.as_str(), sha256_hex(contents)))
+        .collect();
+    let sums = manifest(&digests.iter().map(|(n, d)| (*n, d.as_str())).collect::<Vec<_>>());
+
+    let options = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    let mut archive = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    for (name, contents) in files {
+        archive.start_file(format!("{folder}/{name}"), options)?;
+        archive.write_all(contents)?;
+    }
+    archive.start_file(format!("{folder}/{MANIFEST_FILE}"), options)?;
+    archive.write_all(sums.as_bytes())?;
+    Ok(archive.finish()?.into_inner())
+}
+
+const SELECT: &str =
+    "SELECT e.id, COALESCE(p_kind.value, '') AS kind, COALESCE(p_file.value, '') AS file_name, \
+            COALESCE(p_sha.value, '') AS sha256, COALESCE(p_size.value, '0')::BIGINT AS size_bytes, \
+            COALESCE(p_by.value, '0')::BIGINT AS generated_by, COALESCE(u.label, 'system') AS generated_by_name, \
+            to_char(e.created_at, 'YYYY-MM-DD HH24:MI:SS') AS generated_at \
+     FROM entities e \
+     LEFT JOIN entity_properties p_kind ON e.id = p_kind.entity_id AND p_kind.key = 'kind' \
+     LEFT JOIN entity_properties p_file ON e.id = p_file.entity_id AND p_file.key = 'file_name' \
+     LEFT JOIN entity_properties p_sha ON e.id = p_sha.entity_id AND p_sha.key = 'sha256' \
+     LEFT JOIN entity_properties p_size ON e.id = p_size.entity_id AND p_size.key = 'size_bytes' \
+     LEFT JOIN entity_properties p_by ON e.id = p_by.entity_id AND p_by.key = 'generated_by' \
+     LEFT JOIN entities u ON u.id = CAST(p_by.value AS BIGINT) AND u.entity_type = 'user' \
+     WHERE e.entity_type = 'export_record'";
+
+/// Record a generated export. The same file with the same content is
+/// recorded once, however often it is downloaded.
+pub async fn record(
+    pool: &PgPool,
+    kind: &str,
+    file_name: &str,
+    bytes: &[u8],
+    user_id: i64,
+) -> Result<ExportRecord, sqlx::Error> {
+    let sha = sha256_hex(bytes);
+    if let Some(existing) = find_by_sha256(pool, &sha).await?
+        .into_iter()
+        .find(|r| r.file_name == file_name)
+    {
+        return Ok(existing);
+    }
+
+    let name = entity::available_name(pool, "export_record", &sha[..16]).await?;
+    let id = entity::create(pool, "export_record", &name, file_name).await?;
+    entity::set_properties(pool, id, &[
+        ("kind", kind),
+        ("file_name", file_name),
+        ("sha256", &sha),
+        ("size_bytes", &bytes.len().to_string()),
+        ("generated_by", &user_id.to_string()),
+    ]).await?;
+    find_by_id(pool, id).await?.ok_or(sqlx::Error::RowNotFound)
+}
+
+pub async fn find_by_id(pool: &PgPool, id: i64) -> Result<Option<ExportRecord>, sqlx::Error> {
+    sqlx::query_as::<_, ExportRecord>(&format!("{SELECT} AND e.id = $1"))
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+}
+
+/// Exports with this digest, oldest first.
+pub async fn find_by_sha256(pool: &PgPool, sha256: &str) -> Result<Vec<ExportRecord>, sqlx::Error> {
+    sqlx::query_as::<_, ExportRecord>(&format!("{SELECT} AND p_sha.value = $1 ORDER BY e.id"))
+        .bind(sha256.to_lowercase())
+        .fetch_all(pool)
+        .await
+}
+
+/// The most recent exports, newest first.
+pub async fn find_recent(pool: &PgPool, limit: i64) -> Result<Vec<ExportRecord>, sqlx::Error> {
+    sqlx::query_as::<_, ExportRecord>(&format!("{SELECT} ORDER BY e.id DESC LIMIT $1"))
+        .bind(limit)
+        .fetch_all(pool)
+        .await
+}
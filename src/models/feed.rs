@@ -0,0 +1,267 @@
+//! Syndication feeds of governance output.
+//!
+//! The decision register and approved minutes are offered as Atom 1.0 and
+//! RSS 2.0, across all ToRs or for one. Feeds are served either behind a
+//! share token (`/share/{token}/feed.atom`) or, for ToRs that enable the
+//! `feeds.public` setting, without authentication under `/feeds/`. Entry links
+//! point into the application, so reading the full record still needs a login.
+
+use chrono::{DateTime, Local, NaiveDateTime, Utc};
+use sqlx::PgPool;
+
+use crate::errors::AppError;
+use super::{minutes, opinion};
+
+/// Entries per feed.
+pub const MAX_ENTRIES: usize = 50;
+
+/// What a feed syndicates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FeedKind {
+    Decisions,
+    Minutes,
+}
+
+impl FeedKind {
+    pub fn from_key(key: &str) -> Option<Self> {
+        match key {
+            "decisions" => Some(FeedKind::Decisions),
+            "minutes" => Some(FeedKind::Minutes),
+            _ => None,
+        }
+    }
+
+    pub fn key(self) -> &'static str {
+        match self {
+            FeedKind::Decisions => "decisions",
+            FeedKind::Minutes => "minutes",
+        }
+    }
+
+    fn title(self) -> &'static str {
+        match self {
+            FeedKind::Decisions => "Decisions",
+            FeedKind::Minutes => "Published minutes",
+        }
+    }
+}
+
+/// Serialization of a feed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FeedFormat {
+    Atom,
+    Rss,
+}
+
+impl FeedFormat {
+    /// Format from a URL extension (`atom` or `rss`).
+    pub fn from_extension(ext: &str) -> Option<Self> {
+        match ext {
+            "atom" => Some(FeedFormat::Atom),
+            "rss" => Some(FeedFormat::Rss),
+            _ => None,
+        }
+    }
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            FeedFormat::Atom => "application/atom+xml; charset=utf-8",
+            FeedFormat::Rss => "application/rss+xml; charset=utf-8",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FeedEntry {
+    /// Stable id, unique across feeds: `{base_url}/{kind}/{id}`.
+    pub id: String,
+    pub title: String,
+    pub link: String,
+    /// RFC 3339.
+    pub updated: String,
+    pub author: String,
+    pub summary: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct Feed {
+    pub title: String,
+    /// HTML page the feed mirrors.
+    pub link: String,
+    /// URL the feed itself is served from.
+    pub self_link: String,
+    pub updated: String,
+    pub entries: Vec<FeedEntry>,
+}
+
+impl Feed {
+    pub fn render(&self, format: FeedFormat) -> String {
+        match format {
+            FeedFormat::Atom => self.to_atom(),
+            FeedFormat::Rss => self.to_rss(),
+        }
+    }
+
+    pub fn to_atom(&self) -> String {
+        let mut out = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+        out.push_str("<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+        out.push_str(&format!("  <id>{}</id>\n", escape(&self.self_link)));
+        out.push_str(&format!("  <title>{}</title>\n", escape(&self.title)));
+        out.push_str(&format!("  <updated>{}</updated>\n", escape(&self.updated)));
+        out.push_str(&format!("  <link rel=\"self\" href=\"{}\"/>\n", escape(&self.self_link)));
+        out.push_str(&format!("  <link rel=\"alternate\" type=\"text/html\" href=\"{}\"/>\n", escape(&self.link)));
+        for e in &self.entries {
+            out.push_str("  <entry>\n");
+            out.push_str(&format!("    <id>{}</id>\n", escape(&e.id)));
+            out.push_str(&format!("    <title>{}</title>\n", escape(&e.title)));
+            out.push_str(&format!("    <updated>{}</updated>\n", escape(&e.updated)));
+            out.push_str(&format!("    <link rel=\"alternate\" type=\"text/html\" href=\"{}\"/>\n", escape(&e.link)));
+            if !e.author.is_empty() {
+                out.push_str(&format!("    <author><name>{}</name></author>\n", escape(&e.author)));
+            }
+            out.push_str(&format!("    <summary type=\"text\">{}</summary>\n", escape(&e.summary)));
+            out.push_str("  </entry>\n");
+        }
+        out.push_str("</feed>\n");
+        out
+    }
+
+    pub fn to_rss(&self) -> String {
+        let mut out = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+        out.push_str("<rss version=\"2.0\" xmlns:atom=\"http://www.w3.org/2005/Atom\" xmlns:dc=\"http://purl.org/dc/elements/1.1/\">\n");
+        out.push_str("<channel>\n");
+        out.push_str(&format!("  <title>{}</title>\n", escape(&self.title)));
+        out.push_str(&format!("  <link>{}</link>\n", escape(&self.link)));
+        out.push_str(&format!("  <description>{}</description>\n", escape(&self.title)));
+        out.push_str(&format!("  <atom:link rel=\"self\" type=\"application/rss+xml\" href=\"{}\"/>\n", escape(&self.self_link)));
+        out.push_str(&format!("  <lastBuildDate>{}</lastBuildDate>\n", rfc2822(&self.updated)));
+        for e in &self.entries {
+            out.push_str("  <item>\n");
+            out.push_str(&format!("    <title>{}</title>\n", escape(&e.title)));
+            out.push_str(&format!("    <link>{}</link>\n", escape(&e.link)));
+            out.push_str(&format!("    <guid isPermaLink=\"false\">{}</guid>\n", escape(&e.id)));
+            out.push_str(&format!("    <pubDate>{}</pubDate>\n", rfc2822(&e.updated)));
+            if !e.author.is_empty() {
+                out.push_str(&format!("    <dc:creator>{}</dc:creator>\n", escape(&e.author)));
+            }
+            out.push_str(&format!("    <description>{}</description>\n", escape(&e.summary)));
+            out.push_str("  </item>\n");
+        }
+        out.push_str("</channel>\n</rss>\n");
+        out
+    }
+}
+
+/// Escape text for XML element content and attribute values.
+pub fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            // Control characters other than tab and newlines are not allowed in XML 1.0
+            c if c.is_control() && !matches!(c, '\t' | '\n' | '\r') => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn rfc2822(rfc3339: &str) -> String {
+    DateTime::parse_from_rfc3339(rfc3339)
+        .map(|d| d.to_rfc2822())
+        .unwrap_or_default()
+}
+
+/// A decision's `decided_date` (local time, `YYYY-MM-DD HH:MM:SS`) as RFC 3339.
+pub(crate) fn local_to_rfc3339(value: &str) -> String {
+    NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S")
+        .ok()
+        .and_then(|naive| naive.and_local_timezone(Local).earliest())
+        .map(|local| local.with_timezone(&Utc).to_rfc3339())
+        .unwrap_or_else(|| Utc::now().to_rfc3339())
+}
+
+/// Build a feed. `base_url` is the application's origin (`https://host`);
+/// `self_path` is the path the feed is served from.
+pub async fn build(
+    pool: &PgPool,
+    kind: FeedKind,
+    tor: Option<(i64, &str)>,
+    base_url: &str,
+    self_path: &str,
+) -> Result<Feed, AppError> {
+    let tor_id = tor.map(|(id, _)| id);
+    let title = match tor {
+        Some((_, label)) => format!("{} — {}", kind.title(), label),
+        None => kind.title().to_string(),
+    };
+
+    let (link, mut entries) = match kind {
+        FeedKind::Decisions => {
+            let link = match tor_id {
+                Some(id) => format!("{base_url}/tor/{id}/decisions"),
+                None => format!("{base_url}/tor"),
+            };
+            let entries = opinion::find_decision_register(pool, tor_id).await?
+                .into_iter()
+                .take(MAX_ENTRIES)
+                .map(|d| {
+                    let mut summary = format!("Decision: {}", d.selected_coa_title);
+                    if !d.decision_rationale.is_empty() {
+                        summary.push_str(&format!("\nRationale: {}", d.decision_rationale));
+                    }
+                    FeedEntry {
+                        id: format!("{base_url}/decisions/{}", d.id),
+                        title: format!("{}: {}", d.tor_label, d.agenda_point_title),
+                        link: format!("{base_url}/tor/{}/workflow/agenda/{}", d.tor_id, d.agenda_point_id),
+                        updated: local_to_rfc3339(&d.decided_date),
+                        author: d.decided_by(),
+                        summary,
+                    }
+                })
+                .collect::<Vec<_>>();
+            (link, entries)
+        }
+        FeedKind::Minutes => {
+            let link = match tor_id {
+                Some(id) => format!("{base_url}/tor/{id}/meetings"),
+                None => format!("{base_url}/meetings"),
+            };
+            let mut entries = Vec::new();
+            for m in minutes::find_published(pool, tor_id, MAX_ENTRIES as i64).await? {
+                let sections = minutes::find_sections(pool, m.id).await?;
+                let summary = sections.iter()
+                    .filter(|s| !s.content.trim().is_empty())
+                    .map(|s| format!("{}\n{}", s.label, s.content.trim()))
+                    .collect::<Vec<_>>()
+                    .join("\n\n");
+                entries.push(FeedEntry {
+                    id: format!("{base_url}/minutes/{}", m.id),
+                    title: format!("{}: minutes of {}", m.tor_label, m.meeting_date),
+                    link: format!("{base_url}/minutes/{}", m.id),
+                    updated: m.updated_at,
+                    author: String::new(),
+                    summary,
+                });
+            }
+            (link, entries)
+        }
+    };
+
+    // Newest first by timestamp
+    entries.sort_by(|a, b| b.updated.cmp(&a.updated));
+    let updated = entries.first()
+        .map(|e| e.updated.clone())
+        .unwrap_or_else(|| Utc::now().to_rfc3339());
+    Ok(Feed {
+        title,
+        link,
+        self_link: format!("{base_url}{self_path}"),
+        updated,
+        entries,
+    })
+}
@@ -0,0 +1,96 @@
+//! Fiscal calendar.
+//!
+//! Reporting periods follow the deployment's fiscal year rather than the
+//! calendar year. `fiscal.year_start_month` is the month (1–12) the fiscal
+//! year begins, and `fiscal.quarter_label` names its quarters with these
+//! placeholders:
+//!
+//! - `{fy}`: the year the fiscal year ends in (`FY2027` runs July 2026 to
+//!   June 2027 when it starts in July)
+//! - `{start_year}`: the year it begins in
+//! - `{q}`: the quarter, 1 to 4
+
+use chrono::{Datelike, Months, NaiveDate};
+use sqlx::PgPool;
+
+use super::setting;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FiscalCalendar {
+    /// Month the fiscal year starts in, 1 to 12.
+    pub start_month: u32,
+    pub quarter_label: String,
+}
+
+impl Default for FiscalCalendar {
+    fn default() -> Self {
+        FiscalCalendar { start_month: 1, quarter_label: "{fy} Q{q}".to_string() }
+    }
+}
+
+/// Problems with a quarter label pattern.
+pub fn validate_label(pattern: &str) -> Result<(), String> {
+    if pattern.contains("{q}") {
+        Ok(())
+    } else {
+        Err("must contain {q}".to_string())
+    }
+}
+
+impl FiscalCalendar {
+    pub async fn load(pool: &PgPool) -> Self {
+        let quarter_label = setting::get_value(pool, "fiscal.quarter_label", "").await;
+        FiscalCalendar {
+            start_month: setting::get_i64(pool, "fiscal.year_start_month").await.clamp(1, 12) as u32,
+            quarter_label: if validate_label(&quarter_label).is_ok() {
+                quarter_label
+            } else {
+                FiscalCalendar::default().quarter_label
+            },
+        }
+    }
+
+    /// Months from the start of the calendar year to the start of the fiscal
+    /// year.
+    pub fn offset_months(&self) -> u32 {
+        self.start_month - 1
+    }
+
+    /// First day of the fiscal year `date` is in.
+    pub fn year_start(&self, date: NaiveDate) -> NaiveDate {
+        let year = if date.month() >= self.start_month { date.year() } else { date.year() - 1 };
+        NaiveDate::from_ymd_opt(year, self.start_month, 1).unwrap_or(date)
+    }
+
+    /// The year the fiscal year `date` is in ends in.
+    pub fn fiscal_year(&self, date: NaiveDate) -> i32 {
+        let start = self.year_start(date).year();
+        if self.start_month == 1 { start } else { start + 1 }
+    }
+
+    /// Fiscal quarter of `date`, 1 to 4.
+    pub fn quarter(&self, date: NaiveDate) -> u32 {
+        (date.month0() + 12 - self.offset_months()) % 12 / 3 + 1
+    }
+
+    /// First day of the fiscal quarter `back` quarters before the one `date`
+    /// is in; a negative `back` counts forward.
+    pub fn quarter_start(&self, date: NaiveDate, back: i64) -> NaiveDate {
+        let current = self.year_start(date) + Months::new((self.quarter(date) - 1) * 3);
+        let months = Months::new((back.unsigned_abs() * 3).min(u32::MAX as u64) as u32);
+        if back >= 0 {
+            current.checked_sub_months(months)
+        } else {
+            current.checked_add_months(months)
+        }
+        .unwrap_or(current)
+    }
+
+    /// Name of the fiscal quarter `date` is in, e.g. `2027 Q1`.
+    pub fn label(&self, date: NaiveDate) -> String {
+        self.quarter_label
+            .replace("{fy}", &self.fiscal_year(date).to_string())
+            .replace("{start_year}", &self.year_start(date).year().to_string())
+            .replace("{q}", &self.quarter(date).to_string())
+    }
+}
@@ -0,0 +1,131 @@
+//! Server-side snapshots of forms being edited.
+//!
+//! Long forms (`<form data-autosave>`) post their field values here while the
+//! user types. A snapshot is keyed by the path the form posts to, so every
+//! form on a page — each minutes section, say — keeps its own. Submitting the
+//! form successfully removes the snapshot; if the session expires first, the
+//! form offers to restore it once the user has signed back in.
+
+use serde::Serialize;
+use sqlx::PgPool;
+
+use crate::auth::redirect;
+
+/// Largest snapshot accepted, in bytes of JSON.
+pub const MAX_FIELDS_BYTES: usize = 256 * 1024;
+/// Snapshots not touched for this long are abandoned and removed.
+pub const RETENTION_DAYS: i64 = 30;
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct FormDraft {
+    pub id: i64,
+    pub form_key: String,
+    pub page_path: String,
+    pub title: String,
+    /// JSON object of field name to value (a string, or a list for
+    /// multi-value fields).
+    pub fields: String,
+    pub updated_at: String,
+}
+
+/// Check a snapshot before it is stored. Values must be strings or lists of
+/// strings; the CSRF token is never stored.
+pub fn validate(
+    form_key: &str,
+    page_path: &str,
+    fields: &serde_json::Map<String, serde_json::Value>,
+) -> Result<serde_json::Value, String> {
+    if !form_key.starts_with('/') || form_key.starts_with("//") || form_key.len() > 500 {
+        return Err("Invalid form key".to_string());
+    }
+    if redirect::safe_path(page_path).is_none() || page_path.len() > 500 {
+        return Err("Invalid page path".to_string());
+    }
+    let mut kept = serde_json::Map::new();
+    for (name, value) in fields {
+        if name == "csrf_token" {
+            continue;
+        }
+        let valid = match value {
+            serde_json::Value::String(_) => true,
+            serde_json::Value::Array(items) => items.iter().all(|v| v.is_string()),
+            _ => false,
+        };
+        if !valid {
+            return Err(format!("Field '{}' must be text", name));
+        }
+        kept.insert(name.clone(), value.clone());
+    }
+    let kept = serde_json::Value::Object(kept);
+    if kept.to_string().len() > MAX_FIELDS_BYTES {
+        return Err("Form is too large to save".to_string());
+    }
+    Ok(kept)
+}
+
+/// Store the latest snapshot of a form, replacing any earlier one.
+pub async fn save(
+    pool: &PgPool,
+    user_id: i64,
+    form_key: &str,
+    page_path: &str,
+    title: &str,
+    fields: &serde_json::Value,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO form_drafts (user_id, form_key, page_path, title, fields) \
+         VALUES ($1, $2, $3, $4, $5::JSONB) \
+         ON CONFLICT (user_id, form_key) DO UPDATE \
+         SET page_path = EXCLUDED.page_path, title = EXCLUDED.title, \
+             fields = EXCLUDED.fields, updated_at = NOW()",
+    )
+    .bind(user_id)
+    .bind(form_key)
+    .bind(page_path)
+    .bind(title.chars().take(200).collect::<String>())
+    .bind(fields.to_string())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+const SELECT_DRAFT: &str =
+    "SELECT id, form_key, page_path, title, fields::TEXT AS fields, \
+            to_char(updated_at, 'YYYY-MM-DD HH24:MI') AS updated_at \
+     FROM form_drafts";
+
+/// The user's snapshot of one form.
+pub async fn find(pool: &PgPool, user_id: i64, form_key: &str) -> Result<Option<FormDraft>, sqlx::Error> {
+    sqlx::query_as::<_, FormDraft>(&format!("{} WHERE user_id = $1 AND form_key = $2", SELECT_DRAFT))
+        .bind(user_id)
+        .bind(form_key)
+        .fetch_optional(pool)
+        .await
+}
+
+/// All of the user's unsaved edits, most recent first.
+pub async fn find_for_user(pool: &PgPool, user_id: i64) -> Result<Vec<FormDraft>, sqlx::Error> {
+    sqlx::query_as::<_, FormDraft>(&format!("{} WHERE user_id = $1 ORDER BY updated_at DESC", SELECT_DRAFT))
+        .bind(user_id)
+        .fetch_all(pool)
+        .await
+}
+
+/// Drop the user's snapshot of a form. Returns whether there was one.
+pub async fn discard(pool: &PgPool, user_id: i64, form_key: &str) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM form_drafts WHERE user_id = $1 AND form_key = $2")
+        .bind(user_id)
+        .bind(form_key)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Delete snapshots last updated before `before`. Returns how many were removed.
+pub async fn cleanup(pool: &PgPool, before: chrono::DateTime<chrono::Utc>) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM form_drafts WHERE updated_at < $1::TIMESTAMPTZ")
+        .bind(before.to_rfc3339())
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected())
+}
@@ -0,0 +1,195 @@
+//! Glossary of defined terms.
+//!
+//! A `glossary_term` entity's label is the term and its `definition`
+//! property says what it means. Terms linked to a ToR with `scoped_to_tor`
+//! belong to that ToR; unlinked terms are global. When a ToR defines a term
+//! the global glossary also has, the ToR's definition is used. Proposals
+//! and approved minutes show defined terms with their definition on hover,
+//! and meeting packs carry the ToR's glossary as `glossary.txt`.
+
+use sqlx::PgPool;
+
+use super::{entity, relation};
+
+pub const ENTITY_TYPE: &str = "glossary_term";
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct GlossaryTerm {
+    pub id: i64,
+    pub name: String,
+    pub term: String,
+    pub definition: String,
+    /// The ToR the term belongs to; None for global terms.
+    pub tor_id: Option<i64>,
+}
+
+impl GlossaryTerm {
+    pub fn is_global(&self) -> bool {
+        self.tor_id.is_none()
+    }
+}
+
+const SELECT: &str =
+    "SELECT e.id, e.name, e.label AS term, COALESCE(p_def.value, '') AS definition, r.target_id AS tor_id \
+     FROM entities e \
+     LEFT JOIN entity_properties p_def ON p_def.entity_id = e.id AND p_def.key = 'definition' \
+     LEFT JOIN relations r ON r.source_id = e.id \
+         AND r.relation_type_id = (SELECT id FROM entities WHERE entity_type = 'relation_type' AND name = 'scoped_to_tor') \
+     WHERE e.entity_type = 'glossary_term'";
+
+/// Global terms, alphabetically.
+pub async fn find_global(pool: &PgPool) -> Result<Vec<GlossaryTerm>, sqlx::Error> {
+    sqlx::query_as::<_, GlossaryTerm>(&format!("{SELECT} AND r.target_id IS NULL ORDER BY LOWER(e.label)"))
+        .fetch_all(pool)
+        .await
+}
+
+/// Terms a ToR defines itself, alphabetically.
+pub async fn find_tor_terms(pool: &PgPool, tor_id: i64) -> Result<Vec<GlossaryTerm>, sqlx::Error> {
+    sqlx::query_as::<_, GlossaryTerm>(&format!("{SELECT} AND r.target_id = $1 ORDER BY LOWER(e.label)"))
+        .bind(tor_id)
+        .fetch_all(pool)
+        .await
+}
+
+/// The glossary in effect for a ToR: its own terms plus the global terms it
+/// does not redefine, alphabetically.
+pub async fn find_for_tor(pool: &PgPool, tor_id: i64) -> Result<Vec<GlossaryTerm>, sqlx::Error> {
+    let terms = sqlx::query_as::<_, GlossaryTerm>(&format!(
+        "{SELECT} AND (r.target_id IS NULL OR r.target_id = $1) \
+         ORDER BY LOWER(e.label), r.target_id IS NULL"
+    ))
+    .bind(tor_id)
+    .fetch_all(pool)
+    .await?;
+
+    let mut effective: Vec<GlossaryTerm> = Vec::with_capacity(terms.len());
+    for term in terms {
+        if effective.last().is_none_or(|t| t.term.to_lowercase() != term.term.to_lowercase()) {
+            effective.push(term);
+        }
+    }
+    Ok(effective)
+}
+
+pub async fn find_by_id(pool: &PgPool, id: i64) -> Result<Option<GlossaryTerm>, sqlx::Error> {
+    sqlx::query_as::<_, GlossaryTerm>(&format!("{SELECT} AND e.id = $1"))
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+}
+
+/// Validate form input for a term in a ToR's glossary (or the global one
+/// when `tor_id` is None). `existing_id` is the term being edited, if any.
+pub async fn validate(
+    pool: &PgPool,
+    tor_id: Option<i64>,
+    term: &str,
+    definition: &str,
+    existing_id: Option<i64>,
+) -> Result<Vec<String>, sqlx::Error> {
+    let mut errors = Vec::new();
+    let term = term.trim();
+    if term.is_empty() {
+        errors.push("Term is required".to_string());
+    } else if !term.chars().any(char::is_alphanumeric) {
+        errors.push("Term must contain a letter or digit".to_string());
+    } else {
+        let siblings = match tor_id {
+            Some(tor_id) => find_tor_terms(pool, tor_id).await?,
+            None => find_global(pool).await?,
+        };
+        if siblings.iter().any(|t| Some(t.id) != existing_id && t.term.to_lowercase() == term.to_lowercase()) {
+            errors.push(format!("'{}' is already defined in this glossary", term));
+        }
+    }
+    if definition.trim().is_empty() {
+        errors.push("Definition is required".to_string());
+    }
+    Ok(errors)
+}
+
+/// Entity name for a term: lowercase words joined by '_'.
+fn base_name(term: &str) -> String {
+    term.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_lowercase())
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+pub async fn create(pool: &PgPool, tor_id: Option<i64>, term: &str, definition: &str) -> Result<i64, sqlx::Error> {
+    let name = entity::available_name(pool, ENTITY_TYPE, &base_name(term)).await?;
+    let id = entity::create(pool, ENTITY_TYPE, &name, term.trim()).await?;
+    entity::set_property(pool, id, "definition", definition.trim()).await?;
+    if let Some(tor_id) = tor_id {
+        relation::create(pool, "scoped_to_tor", id, tor_id).await?;
+    }
+    Ok(id)
+}
+
+pub async fn update(pool: &PgPool, term: &GlossaryTerm, new_term: &str, definition: &str) -> Result<(), sqlx::Error> {
+    entity::update(pool, term.id, &term.name, new_term.trim()).await?;
+    entity::set_property(pool, term.id, "definition", definition.trim()).await
+}
+
+pub async fn delete(pool: &PgPool, id: i64) -> Result<(), sqlx::Error> {
+    entity::delete(pool, id).await
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// HTML for `text` with every whole-word, case-insensitive occurrence of a
+/// defined term wrapped in an `<abbr>` carrying its definition. Longer terms
+/// win where terms overlap. Everything else is escaped, so the result can
+/// be rendered unescaped.
+pub fn annotate(text: &str, terms: &[GlossaryTerm]) -> String {
+    let mut sorted: Vec<(String, &GlossaryTerm)> = terms.iter()
+        .filter(|t| !t.term.trim().is_empty())
+        .map(|t| (t.term.trim().to_lowercase(), t))
+        .collect();
+    sorted.sort_by_key(|(lower, _)| std::cmp::Reverse(lower.len()));
+
+    let is_word = |c: Option<char>| c.is_some_and(char::is_alphanumeric);
+    let mut out = String::with_capacity(text.len());
+    let mut plain_from = 0;
+    let mut i = 0;
+    while i < text.len() {
+        let at_boundary = !is_word(text[..i].chars().next_back());
+        let matched = at_boundary.then(|| sorted.iter().find(|(lower, _)| {
+            text.get(i..i + lower.len()).is_some_and(|s| {
+                s.to_lowercase() == *lower && !is_word(text[i + lower.len()..].chars().next())
+            })
+        })).flatten();
+        match matched {
+            Some((lower, term)) => {
+                let end = i + lower.len();
+                out.push_str(&escape(&text[plain_from..i]));
+                out.push_str(&format!(
+                    "<abbr class=\"glossary-term\" title=\"{}\">{}</abbr>",
+                    escape(&term.definition),
+                    escape(&text[i..end]),
+                ));
+                plain_from = end;
+                i = end;
+            }
+            None => i += text[i..].chars().next().map_or(1, char::len_utf8),
+        }
+    }
+    out.push_str(&escape(&text[plain_from..]));
+    out
+}
+
+/// The glossary as plain text for meeting packs, one term per paragraph.
+pub fn to_text(title: &str, terms: &[GlossaryTerm]) -> String {
+    let mut out = format!("{}\nGlossary\n", title);
+    for term in terms {
+        out.push_str(&format!("\n{}\n    {}\n", term.term, term.definition));
+    }
+    out
+}
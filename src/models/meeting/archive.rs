@@ -0,0 +1,326 @@
+//! Meeting archive record: the whole meeting as one schema-versioned JSON
+//! document for institutional record-keeping systems.
+//!
+//! The record has its own types rather than serialising the view models, so
+//! the shape only changes deliberately. Bump `ARCHIVE_SCHEMA_VERSION` whenever
+//! it does.
+
+use serde::Serialize;
+use sqlx::PgPool;
+
+use crate::errors::AppError;
+use crate::models::{agenda_point, coa, minutes, opinion, proposal};
+
+use super::queries::find_agenda_points;
+use super::types::{CarryOverEntry, MeetingDetail};
+
+pub const ARCHIVE_SCHEMA: &str = "ahlt.meeting_archive";
+pub const ARCHIVE_SCHEMA_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MeetingArchive {
+    pub schema: &'static str,
+    pub schema_version: u32,
+    /// RFC 3339 UTC.
+    pub generated_at: String,
+    pub meeting: ArchivedMeeting,
+    /// Agenda points in agenda order.
+    pub agenda: Vec<ArchivedAgendaPoint>,
+    /// From the approved minutes' attendance when recorded, else the roll call.
+    pub attendance: Vec<ArchivedAttendance>,
+    pub minutes: Option<ArchivedMinutes>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ArchivedTor {
+    pub id: i64,
+    pub name: String,
+    pub label: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ArchivedMeeting {
+    pub id: i64,
+    pub name: String,
+    pub label: String,
+    pub meeting_date: String,
+    pub status: String,
+    pub location: String,
+    pub meeting_number: String,
+    pub classification: String,
+    pub vtc_details: String,
+    pub chair: String,
+    pub secretary: String,
+    pub notes: String,
+    pub tor: ArchivedTor,
+    pub carried_over: Vec<CarryOverEntry>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ArchivedAgendaPoint {
+    pub id: i64,
+    pub title: String,
+    pub description: String,
+    /// "decision" or "informative".
+    pub item_type: String,
+    pub status: String,
+    pub priority: String,
+    pub presenter: String,
+    pub time_allocation_minutes: i32,
+    pub proposal: Option<ArchivedProposal>,
+    pub coas: Vec<ArchivedCoa>,
+    pub opinions: Vec<ArchivedOpinion>,
+    pub decision: Option<ArchivedDecision>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ArchivedProposal {
+    pub id: i64,
+    pub title: String,
+    pub description: String,
+    pub rationale: String,
+    pub submitted_by: String,
+    pub submitted_date: String,
+    pub status: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ArchivedCoa {
+    pub id: i64,
+    pub title: String,
+    pub description: String,
+    /// "simple" or "complex".
+    pub coa_type: String,
+    pub sections: Vec<ArchivedCoaSection>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ArchivedCoaSection {
+    pub title: String,
+    pub content: String,
+    pub subsections: Vec<ArchivedCoaSection>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ArchivedOpinion {
+    pub id: i64,
+    pub recorded_by_id: i64,
+    pub recorded_by: String,
+    pub preferred_coa_id: i64,
+    pub commentary: String,
+    pub recorded_date: String,
+    pub version: i32,
+    pub revised_date: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ArchivedDecision {
+    pub id: i64,
+    pub selected_coa: String,
+    pub rationale: String,
+    pub decided_by_id: i64,
+    /// Decider with the positions held at the time, e.g. "Alice (Chair)".
+    pub decided_by: String,
+    pub decided_date: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ArchivedAttendance {
+    pub name: String,
+    /// "present", "absent" or "excused".
+    pub status: String,
+    pub delegation_to: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ArchivedMinutes {
+    pub id: i64,
+    pub label: String,
+    pub status: String,
+    pub generated_date: String,
+    pub circulated_at: String,
+    pub approved_by: String,
+    pub approved_date: String,
+    pub sections: Vec<ArchivedMinutesSection>,
+    pub action_items: Vec<ArchivedActionItem>,
+    pub distribution: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ArchivedMinutesSection {
+    pub section_type: String,
+    pub label: String,
+    pub content: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ArchivedActionItem {
+    pub description: String,
+    pub responsible: String,
+    pub due_date: String,
+    pub status: String,
+}
+
+/// Archive file name, e.g. `meeting-archive-board_2026_03.json`.
+pub fn file_name(meeting: &MeetingDetail) -> String {
+    format!("meeting-archive-{}.json", meeting.name)
+}
+
+fn archived_coa(detail: coa::CoaDetail) -> ArchivedCoa {
+    ArchivedCoa {
+        id: detail.id,
+        title: detail.title,
+        description: detail.description,
+        coa_type: detail.coa_type,
+        sections: detail.sections.into_iter().map(|s| ArchivedCoaSection {
+            title: s.title,
+            content: s.content,
+            subsections: s.subsections.into_iter().map(|sub| ArchivedCoaSection {
+                title: sub.title,
+                content: sub.content,
+                subsections: Vec::new(),
+            }).collect(),
+        }).collect(),
+    }
+}
+
+/// Build the archive record for a meeting.
+pub async fn build(pool: &PgPool, meeting: &MeetingDetail) -> Result<MeetingArchive, AppError> {
+    let decisions = opinion::find_decision_register(pool, Some(meeting.tor_id)).await?;
+
+    let mut agenda = Vec::new();
+    for scheduled in find_agenda_points(pool, meeting.id).await? {
+        let Some(point) = agenda_point::find_by_id(pool, scheduled.id).await? else { continue };
+
+        let mut listed = coa::find_all_for_agenda_point(pool, point.id).await?;
+        listed.sort_by_key(|c| c.id);
+        let mut coas = Vec::with_capacity(listed.len());
+        for c in listed {
+            coas.push(archived_coa(coa::find_by_id(pool, c.id).await?));
+        }
+
+        let opinions = opinion::find_opinions_for_agenda_point(pool, point.id).await?
+            .into_iter()
+            .map(|o| ArchivedOpinion {
+                id: o.id,
+                recorded_by_id: o.recorded_by,
+                recorded_by: o.recorded_by_name,
+                preferred_coa_id: o.preferred_coa_id,
+                commentary: o.commentary,
+                recorded_date: o.created_date,
+                version: o.version,
+                revised_date: o.revised_date,
+            })
+            .collect();
+
+        // The register is newest first, so this is the decision that stands.
+        let decision = decisions.iter()
+            .find(|d| d.agenda_point_id == point.id)
+            .map(|d| ArchivedDecision {
+                id: d.id,
+                selected_coa: d.selected_coa_title.clone(),
+                rationale: d.decision_rationale.clone(),
+                decided_by_id: d.decided_by_id,
+                decided_by: d.decided_by(),
+                decided_date: d.decided_date.clone(),
+            });
+
+        let proposal = proposal::find_for_agenda_point(pool, point.id).await?
+            .map(|p| ArchivedProposal {
+                id: p.id,
+                title: p.title,
+                description: p.description,
+                rationale: p.rationale,
+                submitted_by: p.submitted_by_name,
+                submitted_date: p.submitted_date,
+                status: p.status,
+            });
+
+        agenda.push(ArchivedAgendaPoint {
+            id: point.id,
+            title: point.title,
+            description: point.description,
+            item_type: point.item_type,
+            status: point.status,
+            priority: point.priority,
+            presenter: point.presenter,
+            time_allocation_minutes: point.time_allocation_minutes,
+            proposal,
+            coas,
+            opinions,
+            decision,
+        });
+    }
+
+    let min = minutes::find_by_meeting(pool, meeting.id).await?;
+    let mut attendance: Vec<ArchivedAttendance> = min.as_ref()
+        .map(|m| m.attendance_list())
+        .unwrap_or_default()
+        .into_iter()
+        .map(|a| ArchivedAttendance { name: a.name, status: a.status, delegation_to: a.delegation_to })
+        .collect();
+    if attendance.is_empty() {
+        attendance = meeting.roll_call_list().into_iter()
+            .map(|r| ArchivedAttendance { name: r.username, status: r.status, delegation_to: String::new() })
+            .collect();
+    }
+
+    let minutes = match min {
+        Some(m) => {
+            let sections = minutes::find_sections(pool, m.id).await?
+                .into_iter()
+                .map(|s| ArchivedMinutesSection { section_type: s.section_type, label: s.label, content: s.content })
+                .collect();
+            let action_items = m.action_items_list().into_iter()
+                .map(|a| ArchivedActionItem {
+                    description: a.description,
+                    responsible: a.responsible,
+                    due_date: a.due_date,
+                    status: a.status,
+                })
+                .collect();
+            Some(ArchivedMinutes {
+                distribution: m.distribution_items(),
+                id: m.id,
+                label: m.label,
+                status: m.status,
+                generated_date: m.generated_date,
+                circulated_at: m.circulated_at,
+                approved_by: m.approved_by,
+                approved_date: m.approved_date,
+                sections,
+                action_items,
+            })
+        }
+        None => None,
+    };
+
+    Ok(MeetingArchive {
+        schema: ARCHIVE_SCHEMA,
+        schema_version: ARCHIVE_SCHEMA_VERSION,
+        generated_at: crate::warnings::clock::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+        meeting: ArchivedMeeting {
+            id: meeting.id,
+            name: meeting.name.clone(),
+            label: meeting.label.clone(),
+            meeting_date: meeting.meeting_date.clone(),
+            status: meeting.status.clone(),
+            location: meeting.location.clone(),
+            meeting_number: meeting.meeting_number.clone(),
+            classification: meeting.classification.clone(),
+            vtc_details: meeting.vtc_details.clone(),
+            chair: meeting.chair_user_id.clone(),
+            secretary: meeting.secretary_user_id.clone(),
+            notes: meeting.notes.clone(),
+            tor: ArchivedTor {
+                id: meeting.tor_id,
+                name: meeting.tor_name.clone(),
+                label: meeting.tor_label.clone(),
+            },
+            carried_over: meeting.carried_over_list(),
+        },
+        agenda,
+        attendance,
+        minutes,
+    })
+}
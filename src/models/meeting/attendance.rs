@@ -0,0 +1,253 @@
+//! Attendance statistics from meeting roll calls.
+//!
+//! Each meeting's roll call (`roll_call_data`) lists who was expected and
+//! whether they were present, excused or absent. A meeting is quorate when
+//! at least `governance.meeting_quorum_pct` percent of its roll call was
+//! present. From the roll calls of a ToR's meetings this module derives the
+//! quorum history and each member's attendance rate, and finds ToRs whose
+//! latest meetings repeatedly missed quorum.
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+use sqlx::PgPool;
+
+use crate::models::{ballot, setting};
+
+use super::types::{MeetingDetail, RollCallEntry};
+
+/// Setting holding the meeting quorum, in percent of the roll call.
+pub const QUORUM_SETTING: &str = "governance.meeting_quorum_pct";
+/// Setting holding how many missed quorums in a row raise a warning.
+pub const MISS_THRESHOLD_SETTING: &str = "warnings.quorum_miss_threshold";
+
+/// One meeting's roll call and whether it was quorate.
+#[derive(Debug, Clone)]
+pub struct MeetingAttendance {
+    pub meeting_id: i64,
+    pub tor_id: i64,
+    pub tor_label: String,
+    pub label: String,
+    pub meeting_date: String,
+    pub roll_call: Vec<RollCallEntry>,
+    /// Members who had to be present for a quorum.
+    pub quorum: usize,
+}
+
+impl MeetingAttendance {
+    fn count(&self, status: &str) -> usize {
+        self.roll_call.iter().filter(|e| e.status == status).count()
+    }
+
+    pub fn present(&self) -> usize {
+        self.count("present")
+    }
+
+    pub fn excused(&self) -> usize {
+        self.count("excused")
+    }
+
+    pub fn absent(&self) -> usize {
+        self.count("absent")
+    }
+
+    pub fn expected(&self) -> usize {
+        self.roll_call.len()
+    }
+
+    pub fn quorum_met(&self) -> bool {
+        self.present() >= self.quorum
+    }
+
+    /// Share of the roll call present, in whole percent.
+    pub fn present_pct(&self) -> usize {
+        percent(self.present(), self.expected())
+    }
+}
+
+/// One member's attendance across a ToR's meetings.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MemberAttendance {
+    pub username: String,
+    /// The user's label, or the roll call name when it is not a user.
+    pub display_name: String,
+    pub present: usize,
+    pub excused: usize,
+    pub absent: usize,
+}
+
+impl MemberAttendance {
+    /// Meetings the member was on the roll call for.
+    pub fn expected(&self) -> usize {
+        self.present + self.excused + self.absent
+    }
+
+    /// Share of those meetings attended, in whole percent.
+    pub fn rate_pct(&self) -> usize {
+        percent(self.present, self.expected())
+    }
+}
+
+/// A ToR's quorum history, oldest meeting first, and its members' rates.
+#[derive(Debug, Clone)]
+pub struct TorAttendance {
+    pub quorum_pct: i64,
+    pub meetings: Vec<MeetingAttendance>,
+    pub members: Vec<MemberAttendance>,
+}
+
+impl TorAttendance {
+    pub fn quorate_count(&self) -> usize {
+        self.meetings.iter().filter(|m| m.quorum_met()).count()
+    }
+
+    /// Average attendance across the meetings, in whole percent.
+    pub fn average_pct(&self) -> usize {
+        let present = self.meetings.iter().map(MeetingAttendance::present).sum();
+        let expected = self.meetings.iter().map(MeetingAttendance::expected).sum();
+        percent(present, expected)
+    }
+
+    pub fn trailing_misses(&self) -> usize {
+        trailing_misses(&self.meetings)
+    }
+
+    /// Series for the attendance charts, as JSON safe to embed in a
+    /// `<script type="application/json">` element.
+    pub fn chart_json(&self) -> String {
+        #[derive(Serialize)]
+        struct Point<'a> {
+            label: &'a str,
+            date: &'a str,
+            present: usize,
+            expected: usize,
+            quorum: usize,
+            quorum_met: bool,
+        }
+        #[derive(Serialize)]
+        struct Rate<'a> {
+            name: &'a str,
+            rate_pct: usize,
+        }
+        serde_json::json!({
+            "quorum_pct": self.quorum_pct,
+            "meetings": self.meetings.iter().map(|m| Point {
+                label: &m.label,
+                date: &m.meeting_date,
+                present: m.present(),
+                expected: m.expected(),
+                quorum: m.quorum,
+                quorum_met: m.quorum_met(),
+            }).collect::<Vec<_>>(),
+            "members": self.members.iter().map(|m| Rate { name: &m.display_name, rate_pct: m.rate_pct() }).collect::<Vec<_>>(),
+        })
+        .to_string()
+        .replace("</", "<\\/")
+    }
+}
+
+fn percent(part: usize, whole: usize) -> usize {
+    (part * 100 + whole / 2).checked_div(whole).unwrap_or(0)
+}
+
+/// Meetings at the end of `meetings` (oldest first) that missed quorum in a row.
+pub fn trailing_misses(meetings: &[MeetingAttendance]) -> usize {
+    meetings.iter().rev().take_while(|m| !m.quorum_met()).count()
+}
+
+/// Per-member totals over the roll calls, ordered by name.
+pub fn member_totals(meetings: &[MeetingAttendance]) -> Vec<MemberAttendance> {
+    let mut totals: BTreeMap<&str, MemberAttendance> = BTreeMap::new();
+    for entry in meetings.iter().flat_map(|m| &m.roll_call) {
+        let member = totals.entry(&entry.username).or_insert_with(|| MemberAttendance {
+            username: entry.username.clone(),
+            display_name: entry.username.clone(),
+            present: 0,
+            excused: 0,
+            absent: 0,
+        });
+        match entry.status.as_str() {
+            "present" => member.present += 1,
+            "excused" => member.excused += 1,
+            _ => member.absent += 1,
+        }
+    }
+    totals.into_values().collect()
+}
+
+/// Meetings with a recorded roll call, by ToR and then date; one ToR when
+/// `tor_id` is given. Cancelled meetings are left out.
+pub async fn find_meetings(
+    pool: &PgPool,
+    tor_id: Option<i64>,
+    quorum_pct: i64,
+) -> Result<Vec<MeetingAttendance>, sqlx::Error> {
+    let rows: Vec<(i64, i64, String, String, String, String)> = sqlx::query_as(
+        "SELECT e.id, tor.id, tor.label, e.label, COALESCE(p_date.value, ''), p_rc.value \
+         FROM entities e \
+         JOIN entity_properties p_rc ON p_rc.entity_id = e.id AND p_rc.key = 'roll_call_data' \
+         JOIN relations r_tor ON r_tor.source_id = e.id \
+             AND r_tor.relation_type_id = (SELECT id FROM entities WHERE entity_type = 'relation_type' AND name = 'belongs_to_tor') \
+         JOIN entities tor ON tor.id = r_tor.target_id AND tor.entity_type = 'tor' \
+         LEFT JOIN entity_properties p_date ON p_date.entity_id = e.id AND p_date.key = 'meeting_date' \
+         LEFT JOIN entity_properties p_status ON p_status.entity_id = e.id AND p_status.key = 'status' \
+         WHERE e.entity_type = 'meeting' \
+           AND COALESCE(p_status.value, 'projected') <> 'cancelled' \
+           AND ($1::BIGINT IS NULL OR tor.id = $1) \
+         ORDER BY tor.id, p_date.value, e.id"
+    )
+    .bind(tor_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter()
+        .filter_map(|(meeting_id, tor_id, tor_label, label, meeting_date, json)| {
+            let roll_call = MeetingDetail::parse_roll_call(&json);
+            (!roll_call.is_empty()).then(|| MeetingAttendance {
+                meeting_id,
+                tor_id,
+                tor_label,
+                label,
+                meeting_date,
+                quorum: ballot::quorum(roll_call.len(), quorum_pct),
+                roll_call,
+            })
+        })
+        .collect())
+}
+
+/// A ToR's quorum history and member attendance rates.
+pub async fn find_for_tor(pool: &PgPool, tor_id: i64) -> Result<TorAttendance, sqlx::Error> {
+    let quorum_pct = setting::get_i64(pool, QUORUM_SETTING).await;
+    let meetings = find_meetings(pool, Some(tor_id), quorum_pct).await?;
+    let mut members = member_totals(&meetings);
+
+    let usernames: Vec<String> = members.iter().map(|m| m.username.clone()).collect();
+    let labels: Vec<(String, String)> = sqlx::query_as(
+        "SELECT name, label FROM entities WHERE entity_type = 'user' AND name = ANY($1)"
+    )
+    .bind(&usernames)
+    .fetch_all(pool)
+    .await?;
+    for member in &mut members {
+        if let Some((_, label)) = labels.iter().find(|(name, _)| *name == member.username) {
+            member.display_name = label.clone();
+        }
+    }
+    members.sort_by(|a, b| a.display_name.cmp(&b.display_name));
+
+    Ok(TorAttendance { quorum_pct, meetings, members })
+}
+
+/// ToRs whose latest `threshold` or more meetings all missed quorum, as
+/// (ToR id, label, meetings missed in a row).
+pub async fn find_repeated_misses(pool: &PgPool, threshold: usize) -> Result<Vec<(i64, String, usize)>, sqlx::Error> {
+    let quorum_pct = setting::get_i64(pool, QUORUM_SETTING).await;
+    let meetings = find_meetings(pool, None, quorum_pct).await?;
+    Ok(meetings.chunk_by(|a, b| a.tor_id == b.tor_id)
+        .filter_map(|tor_meetings| {
+            let misses = trailing_misses(tor_meetings);
+            (misses >= threshold).then(|| (tor_meetings[0].tor_id, tor_meetings[0].tor_label.clone(), misses))
+        })
+        .collect())
+}
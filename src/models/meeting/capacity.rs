@@ -0,0 +1,149 @@
+use sqlx::PgPool;
+
+/// Meeting length used when neither the meeting nor its ToR sets one.
+const DEFAULT_CAPACITY_MINUTES: i32 = 60;
+
+/// Agenda time available at an upcoming meeting.
+///
+/// Capacity is the meeting's `duration_minutes`, falling back to the ToR's
+/// `cadence_duration_minutes`; allocated time is the sum of
+/// `time_allocation_minutes` over agenda points already scheduled for it.
+#[derive(Debug, Clone)]
+pub struct MeetingCapacity {
+    pub meeting_id: i64,
+    pub label: String,
+    pub meeting_date: String,
+    pub capacity_minutes: i32,
+    pub allocated_minutes: i32,
+}
+
+impl MeetingCapacity {
+    pub fn remaining(&self) -> i32 {
+        (self.capacity_minutes - self.allocated_minutes).max(0)
+    }
+}
+
+/// A queued proposal waiting to be placed on an agenda.
+#[derive(Debug, Clone)]
+pub struct QueueItem {
+    pub proposal_id: i64,
+    pub title: String,
+    pub minutes: i32,
+}
+
+/// One meeting's share of a schedule plan.
+#[derive(Debug, Clone)]
+pub struct PlannedMeeting {
+    pub meeting: MeetingCapacity,
+    pub items: Vec<QueueItem>,
+}
+
+impl PlannedMeeting {
+    pub fn planned_minutes(&self) -> i32 {
+        self.items.iter().map(|i| i.minutes).sum()
+    }
+
+    /// Time left after the planned items are added.
+    pub fn remaining_after(&self) -> i32 {
+        self.meeting.remaining() - self.planned_minutes()
+    }
+}
+
+/// Result of distributing queued items across meetings.
+#[derive(Debug, Clone)]
+pub struct SchedulePlan {
+    /// Every upcoming meeting, soonest first, with the items placed on it.
+    pub meetings: Vec<PlannedMeeting>,
+    /// Items that did not fit into any meeting.
+    pub unplaced: Vec<QueueItem>,
+}
+
+impl SchedulePlan {
+    pub fn placed_count(&self) -> usize {
+        self.meetings.iter().map(|m| m.items.len()).sum()
+    }
+}
+
+/// Upcoming meetings (projected or confirmed, on or after `from_date`) of a
+/// ToR with their agenda capacity, soonest first.
+pub async fn find_upcoming_capacity(
+    pool: &PgPool,
+    tor_id: i64,
+    from_date: &str,
+) -> Result<Vec<MeetingCapacity>, sqlx::Error> {
+    let rows: Vec<(i64, String, String, String, i64)> = sqlx::query_as(
+        "SELECT e.id, e.label, p_date.value, \
+                COALESCE(NULLIF(p_dur.value, ''), NULLIF(p_cadence.value, ''), '') AS duration, \
+                COALESCE(( \
+                    SELECT SUM(CASE WHEN p_alloc.value ~ '^[0-9]+$' THEN p_alloc.value::bigint ELSE 0 END) \
+                    FROM relations r_sched \
+                    JOIN entity_properties p_alloc ON p_alloc.entity_id = r_sched.source_id \
+                        AND p_alloc.key = 'time_allocation_minutes' \
+                    WHERE r_sched.target_id = e.id \
+                      AND r_sched.relation_type_id = ( \
+                          SELECT id FROM entities WHERE entity_type = 'relation_type' AND name = 'scheduled_for_meeting') \
+                ), 0)::bigint AS allocated \
+         FROM entities e \
+         JOIN entity_properties p_date ON p_date.entity_id = e.id AND p_date.key = 'meeting_date' \
+         LEFT JOIN entity_properties p_status ON p_status.entity_id = e.id AND p_status.key = 'status' \
+         LEFT JOIN entity_properties p_dur ON p_dur.entity_id = e.id AND p_dur.key = 'duration_minutes' \
+         JOIN relations r_tor ON r_tor.source_id = e.id AND r_tor.target_id = $1 \
+             AND r_tor.relation_type_id = ( \
+                 SELECT id FROM entities WHERE entity_type = 'relation_type' AND name = 'belongs_to_tor') \
+         LEFT JOIN entity_properties p_cadence ON p_cadence.entity_id = r_tor.target_id \
+             AND p_cadence.key = 'cadence_duration_minutes' \
+         WHERE e.entity_type = 'meeting' \
+           AND p_date.value >= $2 \
+           AND COALESCE(p_status.value, 'projected') IN ('projected', 'confirmed') \
+         ORDER BY p_date.value ASC, e.id ASC",
+    )
+    .bind(tor_id)
+    .bind(from_date)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter()
+        .map(|(meeting_id, label, meeting_date, duration, allocated)| MeetingCapacity {
+            meeting_id,
+            label,
+            meeting_date,
+            capacity_minutes: duration.parse().unwrap_or(DEFAULT_CAPACITY_MINUTES),
+            allocated_minutes: allocated as i32,
+        })
+        .collect())
+}
+
+/// Place items, in queue order, on the soonest meeting with enough time left.
+pub fn plan_schedule(meetings: Vec<MeetingCapacity>, items: Vec<QueueItem>) -> SchedulePlan {
+    let mut planned: Vec<PlannedMeeting> = meetings.into_iter()
+        .map(|meeting| PlannedMeeting { meeting, items: Vec::new() })
+        .collect();
+    let mut unplaced = Vec::new();
+
+    for item in items {
+        match planned.iter_mut().find(|m| m.remaining_after() >= item.minutes) {
+            Some(slot) => slot.items.push(item),
+            None => unplaced.push(item),
+        }
+    }
+    SchedulePlan { meetings: planned, unplaced }
+}
+
+/// Rebuild a previewed plan from explicit (item, meeting id) assignments.
+/// `None` when a meeting is no longer upcoming or would be over capacity.
+pub fn apply_assignments(meetings: Vec<MeetingCapacity>, assignments: Vec<(QueueItem, i64)>) -> Option<SchedulePlan> {
+    let mut planned: Vec<PlannedMeeting> = meetings.into_iter()
+        .map(|meeting| PlannedMeeting { meeting, items: Vec::new() })
+        .collect();
+
+    for (item, meeting_id) in assignments {
+        planned.iter_mut()
+            .find(|m| m.meeting.meeting_id == meeting_id)?
+            .items
+            .push(item);
+    }
+    if planned.iter().any(|m| m.remaining_after() < 0) {
+        return None;
+    }
+    Some(SchedulePlan { meetings: planned, unplaced: Vec::new() })
+}
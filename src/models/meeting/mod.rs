@@ -1,5 +1,9 @@
 pub mod types;
 pub mod queries;
+pub mod capacity;
+pub mod pack;
+pub mod archive;
+pub mod attendance;
 
 pub use types::*;
 pub use queries::*;
@@ -0,0 +1,80 @@
+//! Meeting pack download: the agenda, every document attached to the
+//! proposals on it and the ToR's glossary, as one zip with a `SHA256SUMS`
+//! manifest.
+
+use base64::Engine;
+use sqlx::PgPool;
+
+use crate::errors::AppError;
+use crate::models::{document, export_record, glossary};
+
+use super::queries::{find_agenda_points, find_pack_documents};
+use super::types::MeetingDetail;
+
+/// Archive file name, e.g. `meeting-pack-board_2026_03.zip`.
+pub fn file_name(meeting: &MeetingDetail) -> String {
+    format!("meeting-pack-{}.zip", meeting.name)
+}
+
+/// File name safe for an archive entry: lowercase words joined by '-'.
+fn slug(title: &str) -> String {
+    let words: Vec<String> = title
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_lowercase())
+        .collect();
+    if words.is_empty() { "document".to_string() } else { words.join("-") }
+}
+
+/// A document body as file contents and extension: embedded images and
+/// PDFs are decoded, anything else is text.
+fn document_file(body: &str) -> (Vec<u8>, &'static str) {
+    let embedded = body.trim().strip_prefix("data:")
+        .and_then(|rest| rest.split_once(";base64,"))
+        .and_then(|(mime, data)| {
+            let ext = match mime {
+                "application/pdf" => "pdf",
+                "image/png" => "png",
+                "image/jpeg" => "jpg",
+                "image/gif" => "gif",
+                "image/webp" => "webp",
+                _ => return None,
+            };
+            let bytes = base64::engine::general_purpose::STANDARD.decode(data).ok()?;
+            Some((bytes, ext))
+        });
+    embedded.unwrap_or_else(|| (body.as_bytes().to_vec(), "txt"))
+}
+
+/// Build the pack for a meeting.
+pub async fn build_pack(pool: &PgPool, meeting: &MeetingDetail) -> Result<Vec<u8>, AppError> {
+    let agenda_points = find_agenda_points(pool, meeting.id).await?;
+    let pack = find_pack_documents(pool, meeting.id).await?;
+
+    let mut agenda = format!("{}\n{}\n\nAgenda\n", meeting.label, meeting.meeting_date);
+    for (i, point) in agenda_points.iter().enumerate() {
+        agenda.push_str(&format!("{}. {} ({})\n", i + 1, point.label, point.item_type));
+    }
+    if !pack.is_empty() {
+        agenda.push_str("\nDocuments\n");
+    }
+
+    let mut files = Vec::new();
+    for (i, entry) in pack.iter().enumerate() {
+        let Some(doc) = document::find_by_id(pool, entry.document_id).await? else { continue };
+        let (contents, ext) = document_file(&doc.body);
+        let name = format!("documents/{:02}-{}.{}", i + 1, slug(&entry.title), ext);
+        agenda.push_str(&format!("- {} ({}): {}\n", entry.title, entry.agenda_label, name));
+        files.push((name, contents));
+    }
+    let terms = glossary::find_for_tor(pool, meeting.tor_id).await?;
+    if !terms.is_empty() {
+        agenda.push_str(&format!("\nGlossary ({} terms): glossary.txt\n", terms.len()));
+        files.push(("glossary.txt".to_string(), glossary::to_text(&meeting.label, &terms).into_bytes()));
+    }
+    files.insert(0, ("agenda.txt".to_string(), agenda.into_bytes()));
+
+    let folder = file_name(meeting).trim_end_matches(".zip").to_string();
+    export_record::zip_with_manifest(&folder, &files)
+        .map_err(|e| AppError::Session(format!("Could not build meeting pack: {e}")))
+}
@@ -158,7 +158,8 @@ pub async fn find_by_id(pool: &PgPool, id: i64) -> Result<Option<MeetingDetail>,
                 COALESCE(p_vtc.value, '') AS vtc_details, \
                 COALESCE(p_chair.value, '') AS chair_user_id, \
                 COALESCE(p_secretary.value, '') AS secretary_user_id, \
-                COALESCE(p_roll.value, '[]') AS roll_call_data \
+                COALESCE(p_roll.value, '[]') AS roll_call_data, \
+                COALESCE(p_carry.value, '[]') AS carried_over_data \
          FROM entities e \
          LEFT JOIN entity_properties p_date ON e.id = p_date.entity_id AND p_date.key = 'meeting_date' \
          LEFT JOIN entity_properties p_status ON e.id = p_status.entity_id AND p_status.key = 'status' \
@@ -170,6 +171,7 @@ pub async fn find_by_id(pool: &PgPool, id: i64) -> Result<Option<MeetingDetail>,
          LEFT JOIN entity_properties p_chair ON e.id = p_chair.entity_id AND p_chair.key = 'chair_user_id' \
          LEFT JOIN entity_properties p_secretary ON e.id = p_secretary.entity_id AND p_secretary.key = 'secretary_user_id' \
          LEFT JOIN entity_properties p_roll ON e.id = p_roll.entity_id AND p_roll.key = 'roll_call_data' \
+         LEFT JOIN entity_properties p_carry ON e.id = p_carry.entity_id AND p_carry.key = 'carried_over' \
          LEFT JOIN relations r_tor ON e.id = r_tor.source_id \
              AND r_tor.relation_type_id = (SELECT id FROM entities WHERE entity_type = 'relation_type' AND name = 'belongs_to_tor') \
          LEFT JOIN entities tor ON r_tor.target_id = tor.id \
@@ -192,6 +194,13 @@ pub struct MeetingAgendaPoint {
     pub status: String,
 }
 
+impl MeetingAgendaPoint {
+    /// Decision items that have not been decided yet.
+    pub fn is_undecided(&self) -> bool {
+        self.item_type == "decision" && !matches!(self.status.as_str(), "decided" | "voted")
+    }
+}
+
 /// Assign an agenda point to a meeting (idempotent -- ignores duplicates).
 ///
 /// Creates a `scheduled_for_meeting` relation: source = agenda_point, target = meeting.
@@ -236,6 +245,8 @@ pub async fn remove_agenda(
 }
 
 /// Find all agenda points assigned to a meeting via `scheduled_for_meeting`.
+///
+/// Points created from standing items come first, in protocol order.
 pub async fn find_agenda_points(
     pool: &PgPool,
     meeting_id: i64,
@@ -250,8 +261,9 @@ pub async fn find_agenda_points(
              AND r.target_id = $1 \
          LEFT JOIN entity_properties p_type ON e.id = p_type.entity_id AND p_type.key = 'item_type' \
          LEFT JOIN entity_properties p_status ON e.id = p_status.entity_id AND p_status.key = 'status' \
+         LEFT JOIN entity_properties p_order ON e.id = p_order.entity_id AND p_order.key = 'protocol_order' \
          WHERE e.entity_type = 'agenda_point' \
-         ORDER BY e.label ASC",
+         ORDER BY CAST(p_order.value AS BIGINT) ASC NULLS LAST, e.label ASC",
     )
     .bind(meeting_id)
     .fetch_all(pool)
@@ -259,6 +271,44 @@ pub async fn find_agenda_points(
     Ok(rows)
 }
 
+/// Document attached to the proposal behind one of a meeting's agenda points.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct MeetingPackDocument {
+    pub agenda_point_id: i64,
+    pub agenda_label: String,
+    pub document_id: i64,
+    pub title: String,
+    pub doc_type: String,
+}
+
+/// The meeting pack: documents attached to the proposals that spawned the
+/// meeting's agenda points, in agenda order.
+pub async fn find_pack_documents(
+    pool: &PgPool,
+    meeting_id: i64,
+) -> Result<Vec<MeetingPackDocument>, sqlx::Error> {
+    sqlx::query_as::<_, MeetingPackDocument>(
+        "SELECT ap.id AS agenda_point_id, ap.label AS agenda_label, d.id AS document_id, \
+                COALESCE(p_title.value, d.label) AS title, \
+                COALESCE(p_type.value, 'ad_hoc') AS doc_type \
+         FROM entities ap \
+         JOIN relations sched ON sched.source_id = ap.id AND sched.target_id = $1 \
+             AND sched.relation_type_id = (SELECT id FROM entities WHERE entity_type = 'relation_type' AND name = 'scheduled_for_meeting') \
+         JOIN relations spawn ON spawn.target_id = ap.id \
+             AND spawn.relation_type_id = (SELECT id FROM entities WHERE entity_type = 'relation_type' AND name = 'spawns_agenda_point') \
+         JOIN relations att ON att.target_id = spawn.source_id \
+             AND att.relation_type_id = (SELECT id FROM entities WHERE entity_type = 'relation_type' AND name = 'attached_to_proposal') \
+         JOIN entities d ON d.id = att.source_id AND d.entity_type = 'document' \
+         LEFT JOIN entity_properties p_title ON d.id = p_title.entity_id AND p_title.key = 'title' \
+         LEFT JOIN entity_properties p_type ON d.id = p_type.entity_id AND p_type.key = 'doc_type' \
+         WHERE ap.entity_type = 'agenda_point' \
+         ORDER BY ap.label ASC, d.id ASC",
+    )
+    .bind(meeting_id)
+    .fetch_all(pool)
+    .await
+}
+
 /// Find agenda points belonging to a ToR that are NOT assigned to ANY meeting.
 pub async fn find_unassigned_agenda_points(
     pool: &PgPool,
@@ -305,6 +355,26 @@ pub async fn update_status(
     Ok(())
 }
 
+/// Move a meeting to a new status on behalf of `actor_id`, appending a
+/// `meeting.{status}` domain event in the same transaction.
+pub async fn transition(
+    pool: &PgPool,
+    meeting_id: i64,
+    status: &str,
+    actor_id: i64,
+) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    crate::models::entity::set_property_in_tx(&mut tx, meeting_id, "status", status).await?;
+    let payload = serde_json::json!({ "status": status });
+    crate::models::domain_event::append(&mut tx, &format!("meeting.{status}"), "meeting", meeting_id, actor_id, payload).await?;
+    if status == "confirmed" {
+        crate::warnings::notifications::email::queue_meeting_invitation(pool, &mut tx, meeting_id).await?;
+    }
+    tx.commit().await?;
+    crate::warnings::outbox::wake();
+    Ok(())
+}
+
 /// Upsert roll_call_data JSON string for a meeting.
 pub async fn update_roll_call(pool: &PgPool, meeting_id: i64, json: &str) -> Result<(), sqlx::Error> {
     sqlx::query(
@@ -317,3 +387,64 @@ pub async fn update_roll_call(pool: &PgPool, meeting_id: i64, json: &str) -> Res
     .await?;
     Ok(())
 }
+
+/// Later meetings of a ToR that undecided points can be carried over to:
+/// dated after `after_date` and not yet held, soonest first.
+pub async fn find_carry_over_targets(
+    pool: &PgPool,
+    tor_id: i64,
+    after_date: &str,
+) -> Result<Vec<MeetingListItem>, sqlx::Error> {
+    let sql = format!(
+        "{} AND tor.id = $1 AND p_date.value > $2 \
+         AND COALESCE(p_status.value, 'projected') IN ('projected', 'confirmed') \
+         ORDER BY p_date.value ASC",
+        MEETING_LIST_SELECT
+    );
+    let rows = sqlx::query_as::<_, MeetingListItem>(&sql)
+        .bind(tor_id)
+        .bind(after_date)
+        .fetch_all(pool)
+        .await?;
+    Ok(rows)
+}
+
+/// Carry undecided agenda points over to a later meeting.
+///
+/// Each point stays on the original agenda and is also scheduled for the
+/// target meeting. Opinions reference the agenda point, so they carry over
+/// unchanged. The deferral is recorded on the original meeting
+/// (`carried_over`) and the new entries are returned.
+pub async fn carry_over(
+    pool: &PgPool,
+    from: &MeetingDetail,
+    to: &MeetingListItem,
+    points: &[MeetingAgendaPoint],
+) -> Result<Vec<CarryOverEntry>, sqlx::Error> {
+    let mut entries = from.carried_over_list();
+    let mut added = Vec::new();
+    for point in points {
+        if from.is_carried_over(point.id) {
+            continue;
+        }
+        assign_agenda(pool, to.id, point.id).await?;
+        crate::models::entity::set_properties(pool, point.id, &[
+            ("status", "scheduled"),
+            ("scheduled_date", &to.meeting_date),
+            ("carried_over_from", &from.id.to_string()),
+        ]).await?;
+        added.push(CarryOverEntry {
+            agenda_point_id: point.id,
+            label: point.label.clone(),
+            to_meeting_id: to.id,
+            to_meeting_label: to.label.clone(),
+        });
+    }
+
+    if !added.is_empty() {
+        entries.extend(added.iter().cloned());
+        let json = serde_json::to_string(&entries).unwrap_or_else(|_| "[]".to_string());
+        crate::models::entity::set_property(pool, from.id, "carried_over", &json).await?;
+    }
+    Ok(added)
+}
@@ -32,6 +32,16 @@ pub struct MeetingDetail {
     pub chair_user_id: String,
     pub secretary_user_id: String,
     pub roll_call_data: String,    // JSON: [{username, status}]
+    pub carried_over_data: String, // JSON: [{agenda_point_id, label, to_meeting_id, to_meeting_label}]
+}
+
+/// An undecided agenda point deferred from a meeting to a later one.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct CarryOverEntry {
+    pub agenda_point_id: i64,
+    pub label: String,
+    pub to_meeting_id: i64,
+    pub to_meeting_label: String,
 }
 
 /// A single roll call entry parsed from roll_call_data JSON.
@@ -42,7 +52,7 @@ pub struct RollCallEntry {
 }
 
 impl MeetingDetail {
-    fn parse_roll_call(json: &str) -> Vec<RollCallEntry> {
+    pub fn parse_roll_call(json: &str) -> Vec<RollCallEntry> {
         let raw: Vec<serde_json::Value> = serde_json::from_str(json).unwrap_or_default();
         raw.into_iter().filter_map(|v| {
             Some(RollCallEntry {
@@ -55,4 +65,13 @@ impl MeetingDetail {
     pub fn roll_call_list(&self) -> Vec<RollCallEntry> {
         Self::parse_roll_call(&self.roll_call_data)
     }
+
+    pub fn carried_over_list(&self) -> Vec<CarryOverEntry> {
+        serde_json::from_str(&self.carried_over_data).unwrap_or_default()
+    }
+
+    /// Whether an agenda point has already been carried over from this meeting.
+    pub fn is_carried_over(&self, agenda_point_id: i64) -> bool {
+        self.carried_over_list().iter().any(|e| e.agenda_point_id == agenda_point_id)
+    }
 }
@@ -0,0 +1,133 @@
+//! Action items are stored as a JSON array on the minutes entity
+//! (`structured_action_items`). Besides the user-edited fields each item
+//! carries a stable `ref` and, once linked to the ticketing system,
+//! `external_key`, `external_url` and `external_status`. `sync: "pending"`
+//! marks items whose ticket must be created or updated.
+
+use rand::Rng;
+use serde_json::{Map, Value};
+
+/// Fields the user edits in the minutes view.
+const USER_FIELDS: &[&str] = &["description", "responsible", "due_date", "status"];
+/// Fields owned by the ticket sync; never taken from the form.
+const TICKET_FIELDS: &[&str] = &["external_key", "external_url", "external_status", "sync"];
+
+/// Result of syncing one item, applied by `ref`.
+#[derive(Debug, Default)]
+pub struct TicketUpdate {
+    pub ref_id: String,
+    pub external_key: Option<String>,
+    pub external_url: Option<String>,
+    pub external_status: Option<String>,
+    /// Local status derived from the ticket's status.
+    pub status: Option<String>,
+    /// Fingerprint of the content that was pushed; clears `sync` if unchanged since.
+    pub pushed: Option<String>,
+}
+
+fn new_ref() -> String {
+    let mut rng = rand::rng();
+    let bytes: [u8; 6] = rng.random();
+    format!("ai-{}", hex::encode(bytes))
+}
+
+fn str_field<'a>(item: &'a Value, key: &str) -> &'a str {
+    item.get(key).and_then(|v| v.as_str()).unwrap_or("")
+}
+
+/// Identifies the pushed content of an item (what the ticket mirrors).
+pub fn fingerprint(item: &Value) -> String {
+    ["description", "responsible", "due_date"]
+        .iter()
+        .map(|k| str_field(item, k))
+        .collect::<Vec<_>>()
+        .join("\u{1f}")
+}
+
+fn parse(json: &str) -> Vec<Value> {
+    serde_json::from_str::<Vec<Value>>(json)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|v| v.is_object())
+        .collect()
+}
+
+/// Merge items submitted from the form with the stored ones.
+///
+/// Ticket fields are carried over from the stored item with the same `ref`;
+/// the status of a linked item belongs to the ticket. With `track` set, new
+/// or edited items are marked for sync.
+pub fn prepare_for_save(submitted: &str, stored: &str, track: bool) -> String {
+    let stored = parse(stored);
+    let mut out = Vec::new();
+
+    for item in parse(submitted) {
+        let previous = stored.iter()
+            .find(|s| !str_field(&item, "ref").is_empty() && str_field(s, "ref") == str_field(&item, "ref"));
+
+        let mut merged = Map::new();
+        for key in USER_FIELDS {
+            if let Some(v) = item.get(*key) {
+                merged.insert(key.to_string(), v.clone());
+            }
+        }
+
+        match previous {
+            Some(prev) => {
+                merged.insert("ref".to_string(), Value::from(str_field(prev, "ref")));
+                for key in TICKET_FIELDS {
+                    if let Some(v) = prev.get(*key) {
+                        merged.insert(key.to_string(), v.clone());
+                    }
+                }
+                if !str_field(prev, "external_key").is_empty()
+                    && let Some(v) = prev.get("status")
+                {
+                    merged.insert("status".to_string(), v.clone());
+                }
+                if track && fingerprint(prev) != fingerprint(&item) {
+                    merged.insert("sync".to_string(), Value::from("pending"));
+                }
+            }
+            None => {
+                merged.insert("ref".to_string(), Value::from(new_ref()));
+                if track {
+                    merged.insert("sync".to_string(), Value::from("pending"));
+                }
+            }
+        }
+        out.push(Value::Object(merged));
+    }
+
+    Value::Array(out).to_string()
+}
+
+/// Apply sync results to the currently stored items. Items deleted in the
+/// meantime are skipped; `sync` is only cleared when the content is still
+/// what was pushed.
+pub fn apply_ticket_updates(stored: &str, updates: &[TicketUpdate]) -> String {
+    let mut items = parse(stored);
+    for update in updates {
+        let Some(Value::Object(item)) = items.iter_mut()
+            .find(|i| str_field(i, "ref") == update.ref_id)
+        else {
+            continue;
+        };
+        let fields = [
+            ("external_key", &update.external_key),
+            ("external_url", &update.external_url),
+            ("external_status", &update.external_status),
+            ("status", &update.status),
+        ];
+        for (key, value) in fields {
+            if let Some(v) = value {
+                item.insert(key.to_string(), Value::from(v.as_str()));
+            }
+        }
+        let current = fingerprint(&Value::Object(item.clone()));
+        if update.pushed.as_deref() == Some(current.as_str()) {
+            item.remove("sync");
+        }
+    }
+    Value::Array(items).to_string()
+}
@@ -0,0 +1,72 @@
+//! Line-based diff for showing how a minutes section changed.
+
+/// One line of a diff.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiffLine {
+    pub kind: DiffKind,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DiffKind {
+    Same,
+    Added,
+    Removed,
+}
+
+impl DiffLine {
+    /// CSS modifier for the line (`diff-line--added`, …).
+    pub fn css_class(&self) -> &'static str {
+        match self.kind {
+            DiffKind::Same => "diff-line",
+            DiffKind::Added => "diff-line diff-line--added",
+            DiffKind::Removed => "diff-line diff-line--removed",
+        }
+    }
+
+    pub fn marker(&self) -> &'static str {
+        match self.kind {
+            DiffKind::Same => " ",
+            DiffKind::Added => "+",
+            DiffKind::Removed => "-",
+        }
+    }
+}
+
+/// Diff two texts line by line (longest common subsequence).
+pub fn diff_lines(old: &str, new: &str) -> Vec<DiffLine> {
+    let a: Vec<&str> = old.lines().collect();
+    let b: Vec<&str> = new.lines().collect();
+
+    // lcs[i][j] = LCS length of a[i..] and b[j..]
+    let mut lcs = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let line = |kind, text: &str| DiffLine { kind, text: text.to_string() };
+    let mut out = Vec::with_capacity(a.len().max(b.len()));
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if a[i] == b[j] {
+            out.push(line(DiffKind::Same, a[i]));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            out.push(line(DiffKind::Removed, a[i]));
+            i += 1;
+        } else {
+            out.push(line(DiffKind::Added, b[j]));
+            j += 1;
+        }
+    }
+    out.extend(a[i..].iter().map(|t| line(DiffKind::Removed, t)));
+    out.extend(b[j..].iter().map(|t| line(DiffKind::Added, t)));
+    out
+}
@@ -1,5 +1,7 @@
 pub mod types;
 pub mod queries;
+pub mod action_items;
+pub mod diff;
 
 pub use types::*;
 pub use queries::*;
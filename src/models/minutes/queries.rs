@@ -1,5 +1,10 @@
 use sqlx::PgPool;
 use super::types::*;
+use crate::models::meeting::CarryOverEntry;
+use crate::models::revision::{self, Revision};
+
+/// Placeholder content of a freshly generated Decisions section.
+const NO_DECISIONS: &str = "No decisions recorded.";
 
 /// Intermediate row struct for MinutesSection with is_auto_generated as String from DB.
 #[derive(sqlx::FromRow)]
@@ -11,6 +16,7 @@ struct MinutesSectionRow {
     sequence_order: i64,
     content: String,
     is_auto_generated: String,
+    circulated_content: Option<String>,
 }
 
 /// Find minutes for a specific meeting.
@@ -25,7 +31,8 @@ pub async fn find_by_meeting(pool: &PgPool, meeting_id: i64) -> Result<Option<Mi
                 COALESCE(p_appr_date.value, '') AS approved_date, \
                 COALESCE(p_dist.value, '[]') AS distribution_list, \
                 COALESCE(p_att.value, '[]') AS structured_attendance, \
-                COALESCE(p_ai.value, '[]') AS structured_action_items \
+                COALESCE(p_ai.value, '[]') AS structured_action_items, \
+                COALESCE(p_circ.value, '') AS circulated_at \
          FROM entities m \
          JOIN relations r ON m.id = r.target_id \
          JOIN entities mtg ON r.source_id = mtg.id \
@@ -36,6 +43,7 @@ pub async fn find_by_meeting(pool: &PgPool, meeting_id: i64) -> Result<Option<Mi
          LEFT JOIN entity_properties p_dist ON m.id = p_dist.entity_id AND p_dist.key = 'distribution_list' \
          LEFT JOIN entity_properties p_att ON m.id = p_att.entity_id AND p_att.key = 'structured_attendance' \
          LEFT JOIN entity_properties p_ai ON m.id = p_ai.entity_id AND p_ai.key = 'structured_action_items' \
+         LEFT JOIN entity_properties p_circ ON m.id = p_circ.entity_id AND p_circ.key = 'circulated_at' \
          WHERE r.source_id = $1 \
            AND r.relation_type_id = ( \
                SELECT id FROM entities WHERE entity_type = 'relation_type' AND name = 'minutes_of') \
@@ -60,7 +68,8 @@ pub async fn find_by_id(pool: &PgPool, minutes_id: i64) -> Result<Option<Minutes
                 COALESCE(p_appr_date.value, '') AS approved_date, \
                 COALESCE(p_dist.value, '[]') AS distribution_list, \
                 COALESCE(p_att.value, '[]') AS structured_attendance, \
-                COALESCE(p_ai.value, '[]') AS structured_action_items \
+                COALESCE(p_ai.value, '[]') AS structured_action_items, \
+                COALESCE(p_circ.value, '') AS circulated_at \
          FROM entities m \
          JOIN relations r ON m.id = r.target_id \
          JOIN entities mtg ON r.source_id = mtg.id \
@@ -71,6 +80,7 @@ pub async fn find_by_id(pool: &PgPool, minutes_id: i64) -> Result<Option<Minutes
          LEFT JOIN entity_properties p_dist ON m.id = p_dist.entity_id AND p_dist.key = 'distribution_list' \
          LEFT JOIN entity_properties p_att ON m.id = p_att.entity_id AND p_att.key = 'structured_attendance' \
          LEFT JOIN entity_properties p_ai ON m.id = p_ai.entity_id AND p_ai.key = 'structured_action_items' \
+         LEFT JOIN entity_properties p_circ ON m.id = p_circ.entity_id AND p_circ.key = 'circulated_at' \
          WHERE m.id = $1 \
            AND r.relation_type_id = ( \
                SELECT id FROM entities WHERE entity_type = 'relation_type' AND name = 'minutes_of') \
@@ -83,6 +93,35 @@ pub async fn find_by_id(pool: &PgPool, minutes_id: i64) -> Result<Option<Minutes
     Ok(row)
 }
 
+/// Approved minutes, most recently approved first, optionally for one ToR only.
+pub async fn find_published(pool: &PgPool, tor_id: Option<i64>, limit: i64) -> Result<Vec<PublishedMinutes>, sqlx::Error> {
+    sqlx::query_as::<_, PublishedMinutes>(
+        "SELECT m.id, m.label, mtg.id AS meeting_id, \
+                COALESCE(p_date.value, '') AS meeting_date, \
+                t.id AS tor_id, t.label AS tor_label, \
+                to_char(m.updated_at AT TIME ZONE 'UTC', 'YYYY-MM-DD\"T\"HH24:MI:SS\"Z\"') AS updated_at \
+         FROM entities m \
+         JOIN entity_properties p_status ON m.id = p_status.entity_id AND p_status.key = 'status' \
+         JOIN relations r_min ON r_min.target_id = m.id \
+             AND r_min.relation_type_id = ( \
+                 SELECT id FROM entities WHERE entity_type = 'relation_type' AND name = 'minutes_of') \
+         JOIN entities mtg ON mtg.id = r_min.source_id \
+         JOIN relations r_tor ON r_tor.source_id = mtg.id \
+             AND r_tor.relation_type_id = ( \
+                 SELECT id FROM entities WHERE entity_type = 'relation_type' AND name = 'belongs_to_tor') \
+         JOIN entities t ON t.id = r_tor.target_id AND t.entity_type = 'tor' \
+         LEFT JOIN entity_properties p_date ON mtg.id = p_date.entity_id AND p_date.key = 'meeting_date' \
+         WHERE m.entity_type = 'minutes' AND p_status.value = 'approved' \
+           AND ($1::BIGINT IS NULL OR t.id = $1) \
+         ORDER BY m.updated_at DESC, m.id DESC \
+         LIMIT $2",
+    )
+    .bind(tor_id)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+}
+
 /// Find all sections of a minutes document, ordered by sequence.
 pub async fn find_sections(pool: &PgPool, minutes_id: i64) -> Result<Vec<MinutesSection>, sqlx::Error> {
     let rows = sqlx::query_as::<_, MinutesSectionRow>(
@@ -90,13 +129,15 @@ pub async fn find_sections(pool: &PgPool, minutes_id: i64) -> Result<Vec<Minutes
                 COALESCE(p_type.value, '') AS section_type, \
                 CAST(COALESCE(p_order.value, '0') AS BIGINT) AS sequence_order, \
                 COALESCE(p_content.value, '') AS content, \
-                COALESCE(p_auto.value, 'false') AS is_auto_generated \
+                COALESCE(p_auto.value, 'false') AS is_auto_generated, \
+                p_circ.value AS circulated_content \
          FROM entities s \
          JOIN relations r ON s.id = r.source_id \
          LEFT JOIN entity_properties p_type ON s.id = p_type.entity_id AND p_type.key = 'section_type' \
          LEFT JOIN entity_properties p_order ON s.id = p_order.entity_id AND p_order.key = 'sequence_order' \
          LEFT JOIN entity_properties p_content ON s.id = p_content.entity_id AND p_content.key = 'content' \
          LEFT JOIN entity_properties p_auto ON s.id = p_auto.entity_id AND p_auto.key = 'is_auto_generated' \
+         LEFT JOIN entity_properties p_circ ON s.id = p_circ.entity_id AND p_circ.key = 'circulated_content' \
          WHERE r.target_id = $1 \
            AND r.relation_type_id = ( \
                SELECT id FROM entities WHERE entity_type = 'relation_type' AND name = 'section_of') \
@@ -116,6 +157,7 @@ pub async fn find_sections(pool: &PgPool, minutes_id: i64) -> Result<Vec<Minutes
             sequence_order: r.sequence_order,
             content: r.content,
             is_auto_generated: r.is_auto_generated == "true",
+            circulated_content: r.circulated_content,
         }
     }).collect();
 
@@ -143,10 +185,19 @@ pub async fn generate_scaffold(
     .fetch_one(pool)
     .await?;
 
+    // Attendance reflects who held each position on the meeting date,
+    // not whoever holds it when the minutes are generated.
+    let meeting_date = crate::models::entity::get_property(pool, meeting_id, "meeting_date")
+        .await?
+        .unwrap_or_default();
+    let members = crate::models::tor::find_members_at(pool, tor_id, &meeting_date).await?;
+    let attendance = expected_attendance(&members);
+
     // Set properties
     let props = vec![
         ("status", "draft"),
         ("generated_date", &today),
+        ("structured_attendance", &attendance),
     ];
     for (key, value) in props {
         sqlx::query(
@@ -171,10 +222,10 @@ pub async fn generate_scaffold(
 
     // Generate sections
     let sections = [
-        ("attendance", "Attendance", generate_attendance_content(pool, tor_id).await?),
+        ("attendance", "Attendance", generate_attendance_content(&members)),
         ("protocol", "Meeting Protocol", generate_protocol_content(pool, tor_id).await?),
         ("agenda_items", "Agenda Items", "No agenda items recorded.".to_string()),
-        ("decisions", "Decisions", "No decisions recorded.".to_string()),
+        ("decisions", "Decisions", generate_decisions_content(pool, meeting_id).await?),
         ("action_items", "Action Items", "No action items recorded.".to_string()),
     ];
 
@@ -220,13 +271,11 @@ pub async fn generate_scaffold(
 }
 
 /// Generate attendance content showing positions and their holders.
-async fn generate_attendance_content(pool: &PgPool, tor_id: i64) -> Result<String, sqlx::Error> {
-    use crate::models::tor;
-    let members = tor::find_members(pool, tor_id).await?;
+fn generate_attendance_content(members: &[crate::models::tor::TermMember]) -> String {
     let mut lines = Vec::new();
     lines.push("## Attendance\n".to_string());
 
-    for m in &members {
+    for m in members {
         let holder = match &m.holder_label {
             Some(label) => format!("**{}**", label),
             None => {
@@ -241,7 +290,24 @@ async fn generate_attendance_content(pool: &PgPool, tor_id: i64) -> Result<Strin
         lines.push(format!("- {} \u{2014} {}{}", m.position_label, holder, mt_badge));
     }
 
-    Ok(lines.join("\n"))
+    lines.join("\n")
+}
+
+/// Structured attendance pre-filled with each position holder, marked
+/// present for the secretary to correct.
+fn expected_attendance(members: &[crate::models::tor::TermMember]) -> String {
+    let entries: Vec<serde_json::Value> = members.iter()
+        .filter_map(|m| {
+            let (user_id, label) = (m.holder_id?, m.holder_label.as_ref()?);
+            Some(serde_json::json!({
+                "user_id": user_id,
+                "name": format!("{} ({})", label, m.position_label),
+                "status": "present",
+                "delegation_to": "",
+            }))
+        })
+        .collect();
+    serde_json::Value::Array(entries).to_string()
 }
 
 /// Generate protocol content from ToR protocol steps.
@@ -263,8 +329,97 @@ async fn generate_protocol_content(pool: &PgPool, tor_id: i64) -> Result<String,
     Ok(lines.join("\n"))
 }
 
-/// Update a section's content.
-pub async fn update_section_content(pool: &PgPool, section_id: i64, content: &str) -> Result<(), sqlx::Error> {
+/// Generate decisions content: decisions recorded on the meeting's agenda
+/// points (naming who decided, and for whom when a delegate stood in),
+/// followed by points already carried over from the meeting.
+async fn generate_decisions_content(pool: &PgPool, meeting_id: i64) -> Result<String, sqlx::Error> {
+    let decided: Vec<(String, String, String, String)> = sqlx::query_as(
+        "SELECT COALESCE(p_title.value, ap.label), COALESCE(coa.label, ''), \
+                COALESCE(u.label, ''), COALESCE(principal.label, '') \
+         FROM entities d \
+         JOIN entity_properties p_ap ON d.id = p_ap.entity_id AND p_ap.key = 'agenda_point_id' \
+         JOIN entities ap ON ap.id::TEXT = p_ap.value \
+         JOIN relations r ON r.source_id = ap.id AND r.target_id = $1 \
+             AND r.relation_type_id = ( \
+                 SELECT id FROM entities WHERE entity_type = 'relation_type' AND name = 'scheduled_for_meeting') \
+         LEFT JOIN entity_properties p_title ON ap.id = p_title.entity_id AND p_title.key = 'title' \
+         LEFT JOIN entity_properties p_coa ON d.id = p_coa.entity_id AND p_coa.key = 'selected_coa_id' \
+         LEFT JOIN entities coa ON coa.id::TEXT = p_coa.value \
+         LEFT JOIN entity_properties p_by ON d.id = p_by.entity_id AND p_by.key = 'decided_by_id' \
+         LEFT JOIN entities u ON u.id::TEXT = p_by.value \
+         LEFT JOIN entity_properties p_obo ON d.id = p_obo.entity_id AND p_obo.key = 'on_behalf_of_id' \
+         LEFT JOIN entities principal ON principal.id::TEXT = p_obo.value \
+         WHERE d.entity_type = 'decision' \
+         ORDER BY d.id",
+    )
+    .bind(meeting_id)
+    .fetch_all(pool)
+    .await?;
+    let carried: Vec<CarryOverEntry> = crate::models::entity::get_property(pool, meeting_id, "carried_over")
+        .await?
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default();
+
+    let mut parts = Vec::new();
+    if !decided.is_empty() {
+        let lines: Vec<String> = decided.iter()
+            .map(|(title, coa, by, principal)| {
+                let by = if principal.is_empty() { by.clone() } else { format!("{by} on behalf of {principal}") };
+                format!("- {title}: {coa} (decided by {by})")
+            })
+            .collect();
+        parts.push(lines.join("\n"));
+    }
+    if !carried.is_empty() {
+        parts.push(deferral_note(&carried));
+    }
+    if parts.is_empty() {
+        Ok(NO_DECISIONS.to_string())
+    } else {
+        Ok(parts.join("\n\n"))
+    }
+}
+
+/// Minutes annotation for agenda points deferred to a later meeting.
+pub fn deferral_note(entries: &[CarryOverEntry]) -> String {
+    let mut lines = vec!["Deferred (undecided, carried over):".to_string()];
+    for e in entries {
+        lines.push(format!("- {} \u{2014} deferred to {}", e.label, e.to_meeting_label));
+    }
+    lines.join("\n")
+}
+
+/// Append a deferral note to the Decisions section of a minutes document,
+/// keeping the previous content as a revision.
+pub async fn annotate_deferrals(
+    pool: &PgPool,
+    minutes_id: i64,
+    entries: &[CarryOverEntry],
+    edited_by: i64,
+) -> Result<(), sqlx::Error> {
+    let Some(section) = find_sections(pool, minutes_id).await?
+        .into_iter()
+        .find(|s| s.section_type == "decisions")
+    else {
+        return Ok(());
+    };
+    let note = deferral_note(entries);
+    let content = if section.content.trim().is_empty() || section.content == NO_DECISIONS {
+        note
+    } else {
+        format!("{}\n\n{}", section.content, note)
+    };
+    update_section_content(pool, section.id, &content, edited_by).await
+}
+
+/// Update a section's content, keeping the previous content as a revision.
+pub async fn update_section_content(
+    pool: &PgPool,
+    section_id: i64,
+    content: &str,
+    edited_by: i64,
+) -> Result<(), sqlx::Error> {
+    record_revision(pool, section_id, content, edited_by).await?;
     sqlx::query(
         "UPDATE entity_properties SET value = $1 WHERE entity_id = $2 AND key = 'content'",
     )
@@ -275,6 +430,50 @@ pub async fn update_section_content(pool: &PgPool, section_id: i64, content: &st
     Ok(())
 }
 
+/// Keep the current content as a revision before a section is edited.
+/// Does nothing if the content is unchanged.
+pub async fn record_revision(
+    pool: &PgPool,
+    section_id: i64,
+    new_content: &str,
+    edited_by: i64,
+) -> Result<bool, sqlx::Error> {
+    revision::record(pool, section_id, &[("content", new_content)], edited_by).await
+}
+
+/// Earlier revisions of all sections of a minutes document, newest first.
+pub async fn find_revisions(pool: &PgPool, minutes_id: i64) -> Result<Vec<Revision>, sqlx::Error> {
+    let section_ids: Vec<i64> = find_sections(pool, minutes_id).await?.iter().map(|s| s.id).collect();
+    revision::find_for(pool, &section_ids).await
+}
+
+/// Snapshot every section's content when the minutes are circulated for approval,
+/// so later edits can be shown as changes since circulation.
+pub async fn mark_circulated(pool: &PgPool, minutes_id: i64) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO entity_properties (entity_id, key, value) \
+         SELECT s.id, 'circulated_content', COALESCE(p.value, '') \
+         FROM entities s \
+         JOIN relations r ON r.source_id = s.id AND r.target_id = $1 \
+         JOIN entities rt ON rt.id = r.relation_type_id AND rt.name = 'section_of' \
+         LEFT JOIN entity_properties p ON p.entity_id = s.id AND p.key = 'content' \
+         WHERE s.entity_type = 'minutes_section' \
+         ON CONFLICT (entity_id, key) DO UPDATE SET value = EXCLUDED.value",
+    )
+    .bind(minutes_id)
+    .execute(pool)
+    .await?;
+    sqlx::query(
+        "INSERT INTO entity_properties (entity_id, key, value) \
+         VALUES ($1, 'circulated_at', TO_CHAR(NOW(), 'YYYY-MM-DD HH24:MI')) \
+         ON CONFLICT (entity_id, key) DO UPDATE SET value = EXCLUDED.value",
+    )
+    .bind(minutes_id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
 /// Update minutes status.
 pub async fn update_status(pool: &PgPool, minutes_id: i64, new_status: &str) -> Result<(), sqlx::Error> {
     sqlx::query(
@@ -287,6 +486,14 @@ pub async fn update_status(pool: &PgPool, minutes_id: i64, new_status: &str) ->
     Ok(())
 }
 
+/// Record who approved the minutes and on which day (`YYYY-MM-DD`).
+pub async fn mark_approved(pool: &PgPool, minutes_id: i64, approved_by: &str, approved_date: &str) -> Result<(), sqlx::Error> {
+    crate::models::entity::set_properties(pool, minutes_id, &[
+        ("approved_by", approved_by),
+        ("approved_date", approved_date),
+    ]).await
+}
+
 /// Upsert the distribution list (JSON string) for a minutes entity.
 pub async fn update_distribution_list(pool: &PgPool, minutes_id: i64, json: &str) -> Result<(), sqlx::Error> {
     sqlx::query(
@@ -325,3 +532,27 @@ pub async fn update_structured_action_items(pool: &PgPool, minutes_id: i64, json
     .await?;
     Ok(())
 }
+
+/// Current action items JSON of a minutes entity.
+pub async fn find_structured_action_items(pool: &PgPool, minutes_id: i64) -> Result<String, sqlx::Error> {
+    let json: Option<String> = sqlx::query_scalar(
+        "SELECT value FROM entity_properties WHERE entity_id = $1 AND key = 'structured_action_items'",
+    )
+    .bind(minutes_id)
+    .fetch_optional(pool)
+    .await?;
+    Ok(json.unwrap_or_else(|| "[]".to_string()))
+}
+
+/// Minutes (id, label) with action items linked to or awaiting a ticket.
+pub async fn find_with_ticketed_action_items(pool: &PgPool) -> Result<Vec<(i64, String)>, sqlx::Error> {
+    sqlx::query_as(
+        "SELECT m.id, m.label FROM entities m \
+         JOIN entity_properties p ON p.entity_id = m.id AND p.key = 'structured_action_items' \
+         WHERE m.entity_type = 'minutes' \
+           AND (p.value LIKE '%\"external_key\"%' OR p.value LIKE '%\"sync\"%') \
+         ORDER BY m.id",
+    )
+    .fetch_all(pool)
+    .await
+}
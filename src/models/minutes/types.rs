@@ -11,7 +11,21 @@ pub struct Minutes {
     pub approved_date: String,
     pub distribution_list: String,       // JSON: ["name/email"]
     pub structured_attendance: String,   // JSON: [{user_id, name, status, delegation_to}]
-    pub structured_action_items: String, // JSON: [{description, responsible, due_date, status}]
+    pub structured_action_items: String, // JSON: [{ref, description, responsible, due_date, status, external_key?, ...}]
+    pub circulated_at: String,  // set when submitted for approval; empty before
+}
+
+/// Approved minutes with their meeting and ToR, for feeds and embeds.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct PublishedMinutes {
+    pub id: i64,
+    pub label: String,
+    pub meeting_id: i64,
+    pub meeting_date: String,
+    pub tor_id: i64,
+    pub tor_label: String,
+    /// When the minutes were last changed (their approval), RFC 3339 UTC.
+    pub updated_at: String,
 }
 
 #[derive(Debug, Clone)]
@@ -70,4 +84,16 @@ pub struct MinutesSection {
     pub sequence_order: i64,
     pub content: String,
     pub is_auto_generated: bool,
+    /// Content as it was when the minutes were circulated for approval.
+    pub circulated_content: Option<String>,
+}
+
+impl MinutesSection {
+    /// Inline diff against the circulated content, if the section changed since.
+    pub fn changes_since_circulation(&self) -> Option<Vec<super::diff::DiffLine>> {
+        match &self.circulated_content {
+            Some(before) if *before != self.content => Some(super::diff::diff_lines(before, &self.content)),
+            _ => None,
+        }
+    }
 }
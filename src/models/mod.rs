@@ -1,25 +1,65 @@
+pub mod access_review;
+pub mod access_log;
+pub mod action_item;
 pub mod agenda_point;
+pub mod api_token;
+pub mod api_usage;
+pub mod attachment;
+pub mod ballot;
 pub mod audit;
+pub mod branding;
+pub mod cache_generation;
+pub mod changelog;
 pub mod dashboard;
+pub mod decision_citation;
+pub mod delegation;
 pub mod coa;
 pub mod data_manager;
+pub mod distribution_group;
 pub mod document;
+pub mod domain_event;
+pub mod email_template;
 pub mod entity;
+pub mod export_policy;
+pub mod export_record;
+pub mod feed;
+pub mod fiscal;
+pub mod form_draft;
+pub mod glossary;
 pub mod graph_sync;
 pub mod meeting;
 pub mod minutes;
 pub mod nav_item;
 pub mod ontology;
 pub mod opinion;
+pub mod outbound_webhook;
+pub mod ownership_transfer;
+pub mod password_history;
 pub mod presentation_template;
 pub mod relation;
+pub mod remember_token;
+pub mod report_definition;
+pub mod report_subscription;
 pub mod permission;
 pub mod protocol;
 pub mod proposal;
+pub mod quota;
+pub mod reassignment;
+pub mod recertification;
+pub mod rejection_reason;
+pub mod revision;
 pub mod role;
+pub mod scheduler_job;
 pub mod setting;
+pub mod share_token;
+pub mod sign_in_device;
+pub mod signing_key;
+pub mod sla_pack;
 pub mod suggestion;
+pub mod survey;
 pub mod table_filter;
 pub mod tor;
+pub mod trash;
 pub mod user;
+pub mod webhook;
 pub mod workflow;
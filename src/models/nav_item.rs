@@ -1,6 +1,31 @@
+use std::sync::{Arc, RwLock};
+
 use sqlx::PgPool;
 
 use crate::auth::session::Permissions;
+use crate::models::cache_generation;
+
+/// Navigation version, held in the database as the `nav` cache generation so
+/// every replica sees the same value. [`bump`] advances it whenever nav items
+/// or the role permissions that filter them change. The nav rows are cached
+/// per version, and sessions whose permissions were loaded at an older
+/// version reload them (see `auth::session::refresh_permissions`), so menu
+/// changes reach signed-in users without a new sign-in.
+const VERSION: &str = "nav";
+
+type NavRows = Arc<Vec<(String, RawNavItem)>>;
+
+static CACHE: RwLock<Option<(i64, NavRows)>> = RwLock::new(None);
+
+pub async fn version(pool: &PgPool) -> Result<i64, sqlx::Error> {
+    cache_generation::current(pool, VERSION).await
+}
+
+/// Invalidate cached navigation and session permissions on every replica.
+/// Returns the new version.
+pub async fn bump(pool: &PgPool) -> Result<i64, sqlx::Error> {
+    cache_generation::bump(pool, VERSION).await
+}
 
 pub struct NavModule {
     pub label: String,
@@ -23,12 +48,17 @@ struct RawNavRow {
     parent: String,
 }
 
-/// Returns (header_modules, sidebar_items) for the current user and path.
-pub async fn find_navigation(
-    pool: &PgPool,
-    permissions: &Permissions,
-    current_path: &str,
-) -> (Vec<NavModule>, Vec<NavSidebarItem>) {
+/// All active nav items in sort order, from the cache when it is current.
+async fn load_rows(pool: &PgPool) -> NavRows {
+    let version = version(pool).await.ok();
+    if let Some(version) = version
+        && let Ok(cache) = CACHE.read()
+        && let Some((cached, rows)) = cache.as_ref()
+        && *cached == version
+    {
+        return rows.clone();
+    }
+
     let raw_rows = sqlx::query_as::<_, RawNavRow>(
         "SELECT e.name AS nav_name, e.label, \
                 COALESCE(p_url.value, '') AS url, \
@@ -51,10 +81,14 @@ pub async fn find_navigation(
          ORDER BY e.sort_order, e.id"
     )
     .fetch_all(pool)
-    .await
-    .unwrap_or_default();
+    .await;
+    let raw_rows = match raw_rows {
+        Ok(rows) => rows,
+        // Not cached, so the next request retries
+        Err(_) => return Arc::new(vec![]),
+    };
 
-    let rows: Vec<(String, RawNavItem)> = raw_rows
+    let rows: NavRows = Arc::new(raw_rows
         .into_iter()
         .map(|r| (r.nav_name, RawNavItem {
             label: r.label,
@@ -62,7 +96,22 @@ pub async fn find_navigation(
             permission_code: r.permission_code,
             parent: r.parent,
         }))
-        .collect();
+        .collect());
+    if let Some(version) = version
+        && let Ok(mut cache) = CACHE.write()
+    {
+        *cache = Some((version, rows.clone()));
+    }
+    rows
+}
+
+/// Returns (header_modules, sidebar_items) for the current user and path.
+pub async fn find_navigation(
+    pool: &PgPool,
+    permissions: &Permissions,
+    current_path: &str,
+) -> (Vec<NavModule>, Vec<NavSidebarItem>) {
+    let rows = load_rows(pool).await;
 
     // Partition: top-level (no parent) vs children
     let top_level: Vec<&(String, RawNavItem)> = rows.iter()
@@ -1,5 +1,7 @@
 pub mod types;
 pub mod queries;
+pub mod reactions;
 
 pub use types::*;
 pub use queries::*;
+pub use reactions::*;
@@ -1,6 +1,8 @@
-use sqlx::PgPool;
+use std::collections::{HashMap, hash_map::Entry};
+
+use sqlx::{PgPool, Postgres, Transaction};
 use crate::errors::AppError;
-use crate::models::{entity, relation};
+use crate::models::{decision_citation, domain_event, entity, relation, tor};
 use super::types::*;
 
 /// Intermediate row for find_opinions_for_agenda_point query.
@@ -12,6 +14,9 @@ struct OpinionListRow {
     preferred_coa_id: String,
     commentary: String,
     created_date: String,
+    version: String,
+    revised_date: String,
+    considered_version: Option<String>,
 }
 
 /// Intermediate row for find_opinion_by_id query.
@@ -25,6 +30,26 @@ struct OpinionDetailRow {
     coa_title: String,
     commentary: String,
     created_date: String,
+    version: String,
+    revised_date: String,
+}
+
+/// Intermediate row for find_versions_for_agenda_point query.
+#[derive(sqlx::FromRow)]
+struct OpinionVersionRow {
+    id: i64,
+    opinion_id: i64,
+    version: String,
+    preferred_coa_id: String,
+    coa_title: String,
+    commentary: String,
+    recorded_date: String,
+}
+
+/// Whether opinions on an agenda point in this status are frozen.
+/// Members may revise their opinion until the decision is recorded.
+pub fn opinions_locked(agenda_point_status: &str) -> bool {
+    matches!(agenda_point_status, "decided" | "voted")
 }
 
 /// Record a new opinion on an agenda point.
@@ -91,7 +116,10 @@ pub async fn find_opinions_for_agenda_point(
                     CAST(r_pref.target_id AS TEXT), \
                     '0') AS preferred_coa_id, \
                 COALESCE(p_comment.value, p_rationale.value, '') AS commentary, \
-                COALESCE(p_date.value, '') AS created_date \
+                COALESCE(p_date.value, '') AS created_date, \
+                COALESCE(p_ver.value, '1') AS version, \
+                COALESCE(p_rev.value, '') AS revised_date, \
+                p_cons.value AS considered_version \
          FROM entities e \
          JOIN relations r ON e.id = r.source_id \
          JOIN entities rt ON r.relation_type_id = rt.id AND rt.name = 'opinion_on' \
@@ -117,6 +145,12 @@ pub async fn find_opinions_for_agenda_point(
              ON e.id = p_rationale.entity_id AND p_rationale.key = 'rationale' \
          LEFT JOIN entity_properties p_date \
              ON e.id = p_date.entity_id AND p_date.key = 'created_date' \
+         LEFT JOIN entity_properties p_ver \
+             ON e.id = p_ver.entity_id AND p_ver.key = 'version' \
+         LEFT JOIN entity_properties p_rev \
+             ON e.id = p_rev.entity_id AND p_rev.key = 'revised_date' \
+         LEFT JOIN entity_properties p_cons \
+             ON e.id = p_cons.entity_id AND p_cons.key = 'considered_version' \
          WHERE e.entity_type = 'opinion' AND r.target_id = $1 \
          ORDER BY COALESCE(p_date.value, '') ASC",
     )
@@ -135,6 +169,9 @@ pub async fn find_opinions_for_agenda_point(
             preferred_coa_id,
             commentary: row.commentary,
             created_date: row.created_date,
+            version: row.version.parse().unwrap_or(1),
+            revised_date: row.revised_date,
+            considered_version: row.considered_version.and_then(|v| v.parse().ok()),
         }
     }).collect();
 
@@ -154,7 +191,9 @@ pub async fn find_opinion_by_id(
                 COALESCE(p_coa.value, '0') AS preferred_coa_id, \
                 COALESCE(coa.label, '') AS coa_title, \
                 COALESCE(p_comment.value, '') AS commentary, \
-                COALESCE(p_date.value, '') AS created_date \
+                COALESCE(p_date.value, '') AS created_date, \
+                COALESCE(p_ver.value, '1') AS version, \
+                COALESCE(p_rev.value, '') AS revised_date \
          FROM entities e \
          LEFT JOIN entity_properties p_ap \
              ON e.id = p_ap.entity_id AND p_ap.key = 'agenda_point_id' \
@@ -170,6 +209,10 @@ pub async fn find_opinion_by_id(
              ON e.id = p_comment.entity_id AND p_comment.key = 'commentary' \
          LEFT JOIN entity_properties p_date \
              ON e.id = p_date.entity_id AND p_date.key = 'created_date' \
+         LEFT JOIN entity_properties p_ver \
+             ON e.id = p_ver.entity_id AND p_ver.key = 'version' \
+         LEFT JOIN entity_properties p_rev \
+             ON e.id = p_rev.entity_id AND p_rev.key = 'revised_date' \
          WHERE e.id = $1 AND e.entity_type = 'opinion'",
     )
     .bind(id)
@@ -191,6 +234,8 @@ pub async fn find_opinion_by_id(
                 coa_title: r.coa_title,
                 commentary: r.commentary,
                 created_date: r.created_date,
+                version: r.version.parse().unwrap_or(1),
+                revised_date: r.revised_date,
             }))
         }
         None => Ok(None),
@@ -198,12 +243,40 @@ pub async fn find_opinion_by_id(
 }
 
 /// Update an existing opinion's preferred COA and commentary.
+///
+/// The previous content is kept as an `opinion_version` entity
+/// (`revision_of` the opinion) and the version number is bumped.
+/// Resubmitting unchanged content does nothing.
 pub async fn update_opinion(
     pool: &PgPool,
     id: i64,
     preferred_coa_id: i64,
     commentary: &str,
 ) -> Result<(), AppError> {
+    let current = find_opinion_by_id(pool, id).await?.ok_or(AppError::NotFound)?;
+    if current.preferred_coa_id == preferred_coa_id && current.commentary == commentary {
+        return Ok(());
+    }
+
+    let recorded_date = if current.revised_date.is_empty() { &current.created_date } else { &current.revised_date };
+    let name = format!("opinion_{}_v{}", id, current.version);
+    let version_id = entity::create(pool, "opinion_version", &name, &name).await
+        .map_err(AppError::Db)?;
+    entity::set_properties(pool, version_id, &[
+        ("opinion_id", &id.to_string()),
+        ("version", &current.version.to_string()),
+        ("preferred_coa_id", &current.preferred_coa_id.to_string()),
+        ("commentary", &current.commentary),
+        ("recorded_date", recorded_date),
+    ]).await.map_err(AppError::Db)?;
+    relation::create(pool, "revision_of", version_id, id).await
+        .map_err(AppError::Db)?;
+
+    let now = chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    entity::set_property(pool, id, "version", &(current.version + 1).to_string()).await
+        .map_err(AppError::Db)?;
+    entity::set_property(pool, id, "revised_date", &now).await
+        .map_err(AppError::Db)?;
     entity::set_property(pool, id, "preferred_coa_id", &preferred_coa_id.to_string()).await
         .map_err(|e| AppError::Db(e))?;
     entity::set_property(pool, id, "commentary", commentary).await
@@ -253,30 +326,117 @@ pub async fn record_decision(
     decision_rationale: &str,
 ) -> Result<i64, AppError> {
     let name = format!("decision_ap{}_by{}", agenda_point_id, decided_by_id);
-
-    let decision_id = entity::create(pool, "decision", &name, &name).await
-        .map_err(|e| AppError::Db(e))?;
-
+    let opinions = find_opinions_for_agenda_point(pool, agenda_point_id).await?;
     let now = chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
 
-    entity::set_property(pool, decision_id, "agenda_point_id", &agenda_point_id.to_string()).await
-        .map_err(|e| AppError::Db(e))?;
-    entity::set_property(pool, decision_id, "decided_by_id", &decided_by_id.to_string()).await
-        .map_err(|e| AppError::Db(e))?;
-    entity::set_property(pool, decision_id, "selected_coa_id", &selected_coa_id.to_string()).await
-        .map_err(|e| AppError::Db(e))?;
-    entity::set_property(pool, decision_id, "decision_rationale", decision_rationale).await
-        .map_err(|e| AppError::Db(e))?;
-    entity::set_property(pool, decision_id, "decided_date", &now).await
-        .map_err(|e| AppError::Db(e))?;
+    let mut tx = pool.begin().await?;
+    let decision_id = entity::create_in_tx(&mut tx, "decision", &name, &name).await?;
+    for (key, value) in [
+        ("agenda_point_id", agenda_point_id.to_string()),
+        ("decided_by_id", decided_by_id.to_string()),
+        ("selected_coa_id", selected_coa_id.to_string()),
+        ("decision_rationale", decision_rationale.to_string()),
+        ("decided_date", now),
+    ] {
+        entity::set_property_in_tx(&mut tx, decision_id, key, &value).await?;
+    }
 
     // Update agenda point status to "voted"
-    entity::set_property(pool, agenda_point_id, "status", "voted").await
-        .map_err(|e| AppError::Db(e))?;
+    entity::set_property_in_tx(&mut tx, agenda_point_id, "status", "voted").await?;
+
+    freeze_opinions(&mut tx, decision_id, &opinions).await?;
+    let citation_id = decision_citation::assign_in_tx(&mut tx, decision_id).await?;
+
+    let payload = serde_json::json!({
+        "agenda_point_id": agenda_point_id,
+        "selected_coa_id": selected_coa_id,
+        "citation_id": citation_id,
+    });
+    domain_event::append(&mut tx, "decision.recorded", "decision", decision_id, decided_by_id, payload).await?;
+    tx.commit().await?;
+    crate::warnings::outbox::wake();
 
     Ok(decision_id)
 }
 
+/// Mark a decision (and its agenda point) as taken by a delegate on behalf
+/// of an absent member.
+pub async fn record_on_behalf_of(
+    pool: &PgPool,
+    decision_id: i64,
+    agenda_point_id: i64,
+    principal_id: i64,
+) -> Result<(), AppError> {
+    let principal = principal_id.to_string();
+    entity::set_property(pool, decision_id, "on_behalf_of_id", &principal).await?;
+    entity::set_property(pool, agenda_point_id, "on_behalf_of_id", &principal).await?;
+    Ok(())
+}
+
+/// Record which version of each opinion the decision considered, on the
+/// opinions (`considered_version`) and on the decision (`considered_opinions`).
+async fn freeze_opinions(
+    tx: &mut Transaction<'_, Postgres>,
+    decision_id: i64,
+    opinions: &[OpinionListItem],
+) -> Result<(), sqlx::Error> {
+    let mut considered = Vec::new();
+    for op in opinions {
+        entity::set_property_in_tx(tx, op.id, "considered_version", &op.version.to_string()).await?;
+        considered.push(serde_json::json!({ "opinion_id": op.id, "version": op.version }));
+    }
+    entity::set_property_in_tx(tx, decision_id, "considered_opinions", &serde_json::Value::from(considered).to_string()).await
+}
+
+/// Current opinion versions as `opinion_id:version` pairs, comma-separated.
+/// Embedded in the decision form to detect revisions made while it was open.
+pub fn version_stamp(opinions: &[OpinionListItem]) -> String {
+    let mut pairs: Vec<(i64, i32)> = opinions.iter().map(|o| (o.id, o.version)).collect();
+    pairs.sort();
+    pairs.iter().map(|(id, v)| format!("{}:{}", id, v)).collect::<Vec<_>>().join(",")
+}
+
+/// Earlier versions of all opinions on an agenda point, newest first per opinion.
+pub async fn find_versions_for_agenda_point(
+    pool: &PgPool,
+    agenda_point_id: i64,
+) -> Result<Vec<OpinionVersion>, AppError> {
+    let rows = sqlx::query_as::<_, OpinionVersionRow>(
+        "SELECT v.id, op.id AS opinion_id, \
+                COALESCE(p_ver.value, '1') AS version, \
+                COALESCE(p_coa.value, '0') AS preferred_coa_id, \
+                COALESCE(coa.label, '') AS coa_title, \
+                COALESCE(p_comment.value, '') AS commentary, \
+                COALESCE(p_date.value, '') AS recorded_date \
+         FROM entities v \
+         JOIN relations r_v ON r_v.source_id = v.id \
+         JOIN entities rt_v ON rt_v.id = r_v.relation_type_id AND rt_v.name = 'revision_of' \
+         JOIN entities op ON op.id = r_v.target_id AND op.entity_type = 'opinion' \
+         JOIN entity_properties p_ap ON op.id = p_ap.entity_id AND p_ap.key = 'agenda_point_id' \
+         LEFT JOIN entity_properties p_ver ON v.id = p_ver.entity_id AND p_ver.key = 'version' \
+         LEFT JOIN entity_properties p_coa ON v.id = p_coa.entity_id AND p_coa.key = 'preferred_coa_id' \
+         LEFT JOIN entities coa ON coa.id::TEXT = p_coa.value \
+         LEFT JOIN entity_properties p_comment ON v.id = p_comment.entity_id AND p_comment.key = 'commentary' \
+         LEFT JOIN entity_properties p_date ON v.id = p_date.entity_id AND p_date.key = 'recorded_date' \
+         WHERE v.entity_type = 'opinion_version' AND p_ap.value = $1 \
+         ORDER BY op.id, v.id DESC",
+    )
+    .bind(agenda_point_id.to_string())
+    .fetch_all(pool)
+    .await
+    .map_err(AppError::Db)?;
+
+    Ok(rows.into_iter().map(|r| OpinionVersion {
+        id: r.id,
+        opinion_id: r.opinion_id,
+        version: r.version.parse().unwrap_or(1),
+        preferred_coa_id: r.preferred_coa_id.parse().unwrap_or(0),
+        coa_title: r.coa_title,
+        commentary: r.commentary,
+        recorded_date: r.recorded_date,
+    }).collect())
+}
+
 /// Get a summary of opinions grouped by preferred COA for an agenda point.
 /// Returns a list of (coa_id, count) tuples showing how many people prefer each COA.
 pub async fn get_opinions_summary(
@@ -300,3 +460,96 @@ pub async fn get_opinions_summary(
 
     Ok(results.into_iter().map(|(coa_id, count)| (coa_id, count as i32)).collect())
 }
+
+/// Recorded decisions, newest first, optionally for one ToR only.
+pub async fn find_decision_register(
+    pool: &PgPool,
+    tor_id: Option<i64>,
+) -> Result<Vec<DecisionRegisterEntry>, AppError> {
+    let mut entries = sqlx::query_as::<_, DecisionRegisterEntry>(
+        "SELECT d.id, ap.id AS agenda_point_id, \
+                COALESCE(p_title.value, ap.label) AS agenda_point_title, \
+                t.id AS tor_id, t.label AS tor_label, \
+                COALESCE(coa.label, '') AS selected_coa_title, \
+                COALESCE(p_rat.value, '') AS decision_rationale, \
+                COALESCE(u.id, 0) AS decided_by_id, \
+                COALESCE(u.label, '') AS decided_by_name, \
+                COALESCE(principal.label, '') AS on_behalf_of_name, \
+                COALESCE(p_date.value, '') AS decided_date, \
+                COALESCE(p_cite.value, '') AS citation_id, \
+                COALESCE(p_hash.value, '') AS content_hash \
+         FROM entities d \
+         JOIN entity_properties p_ap ON d.id = p_ap.entity_id AND p_ap.key = 'agenda_point_id' \
+         JOIN entities ap ON ap.id::TEXT = p_ap.value AND ap.entity_type = 'agenda_point' \
+         JOIN relations r_tor ON r_tor.source_id = ap.id \
+             AND r_tor.relation_type_id = ( \
+                 SELECT id FROM entities WHERE entity_type = 'relation_type' AND name = 'belongs_to_tor') \
+         JOIN entities t ON t.id = r_tor.target_id AND t.entity_type = 'tor' \
+         LEFT JOIN entity_properties p_title ON ap.id = p_title.entity_id AND p_title.key = 'title' \
+         LEFT JOIN entity_properties p_coa ON d.id = p_coa.entity_id AND p_coa.key = 'selected_coa_id' \
+         LEFT JOIN entities coa ON coa.id::TEXT = p_coa.value \
+         LEFT JOIN entity_properties p_rat ON d.id = p_rat.entity_id AND p_rat.key = 'decision_rationale' \
+         LEFT JOIN entity_properties p_by ON d.id = p_by.entity_id AND p_by.key = 'decided_by_id' \
+         LEFT JOIN entities u ON u.id::TEXT = p_by.value \
+         LEFT JOIN entity_properties p_obo ON d.id = p_obo.entity_id AND p_obo.key = 'on_behalf_of_id' \
+         LEFT JOIN entities principal ON principal.id::TEXT = p_obo.value \
+         LEFT JOIN entity_properties p_date ON d.id = p_date.entity_id AND p_date.key = 'decided_date' \
+         LEFT JOIN entity_properties p_cite ON d.id = p_cite.entity_id AND p_cite.key = 'citation_id' \
+         LEFT JOIN entity_properties p_hash ON d.id = p_hash.entity_id AND p_hash.key = 'content_hash' \
+         WHERE d.entity_type = 'decision' AND ($1::BIGINT IS NULL OR t.id = $1) \
+         ORDER BY p_date.value DESC NULLS LAST, d.id DESC",
+    )
+    .bind(tor_id)
+    .fetch_all(pool)
+    .await
+    .map_err(AppError::Db)?;
+
+    // Attribute each decision to the positions held when it was made
+    let mut histories: HashMap<i64, tor::MembershipHistory> = HashMap::new();
+    for entry in &mut entries {
+        if let Entry::Vacant(slot) = histories.entry(entry.tor_id) {
+            slot.insert(tor::find_membership_history(pool, entry.tor_id).await?);
+        }
+        entry.decided_by_position = histories[&entry.tor_id]
+            .positions_of(entry.decided_by_id, &entry.decided_date)
+            .join(", ");
+    }
+    Ok(entries)
+}
+
+/// Decision items in the given ToRs that still take opinions, have courses
+/// of action to choose from, and have no opinion from the user yet; soonest
+/// first.
+pub async fn find_pending_for_user(
+    pool: &PgPool,
+    user_id: i64,
+    tor_ids: &[i64],
+) -> Result<Vec<PendingOpinion>, AppError> {
+    sqlx::query_as::<_, PendingOpinion>(
+        "SELECT ap.id AS agenda_point_id, COALESCE(p_title.value, ap.label) AS title, \
+                COALESCE(p_sched.value, '') AS scheduled_date, t.id AS tor_id, t.label AS tor_label \
+         FROM entities ap \
+         JOIN relations r_tor ON r_tor.source_id = ap.id \
+             AND r_tor.relation_type_id = (SELECT id FROM entities WHERE entity_type = 'relation_type' AND name = 'belongs_to_tor') \
+         JOIN entities t ON t.id = r_tor.target_id AND t.entity_type = 'tor' \
+         JOIN entity_properties p_type ON ap.id = p_type.entity_id AND p_type.key = 'item_type' AND p_type.value = 'decision' \
+         LEFT JOIN entity_properties p_status ON ap.id = p_status.entity_id AND p_status.key = 'status' \
+         LEFT JOIN entity_properties p_title ON ap.id = p_title.entity_id AND p_title.key = 'title' \
+         LEFT JOIN entity_properties p_sched ON ap.id = p_sched.entity_id AND p_sched.key = 'scheduled_date' \
+         WHERE ap.entity_type = 'agenda_point' AND t.id = ANY($2) \
+           AND COALESCE(p_status.value, '') NOT IN ('decided', 'voted') \
+           AND EXISTS (SELECT 1 FROM relations r_coa \
+                       WHERE r_coa.source_id = ap.id \
+                         AND r_coa.relation_type_id = (SELECT id FROM entities WHERE entity_type = 'relation_type' AND name = 'considers_coa')) \
+           AND NOT EXISTS (SELECT 1 FROM entities o \
+                           JOIN entity_properties p_by ON o.id = p_by.entity_id AND p_by.key = 'recorded_by_id' \
+                           JOIN entity_properties p_ap ON o.id = p_ap.entity_id AND p_ap.key = 'agenda_point_id' \
+                           WHERE o.entity_type = 'opinion' AND p_by.value = $1::TEXT AND p_ap.value = ap.id::TEXT) \
+         ORDER BY NULLIF(p_sched.value, '') ASC NULLS LAST, ap.id ASC",
+    )
+    .bind(user_id)
+    .bind(tor_ids)
+    .fetch_all(pool)
+    .await
+    .map_err(AppError::Db)
+}
@@ -0,0 +1,145 @@
+//! Reactions on member opinions.
+//!
+//! Members react to each other's opinions with agree, disagree or unsure;
+//! one reaction per member per opinion, stored as an `opinion_reaction`
+//! entity named `reaction_op<opinion>_by<user>` with `opinion_id`,
+//! `user_id`, `kind` and `reacted_at` properties. Reacting again with the
+//! same kind withdraws the reaction.
+//!
+//! The reactions on an agenda point's opinions are tallied into a
+//! supportive / opposed / neutral summary for the decision form.
+
+use std::collections::HashMap;
+
+use sqlx::PgPool;
+
+use crate::errors::AppError;
+use crate::models::entity;
+
+/// Reaction kinds with their button labels, in display order.
+pub const REACTIONS: &[(&str, &str)] = &[
+    ("support", "Agree"),
+    ("oppose", "Disagree"),
+    ("neutral", "Unsure"),
+];
+
+pub fn is_reaction(kind: &str) -> bool {
+    REACTIONS.iter().any(|(k, _)| *k == kind)
+}
+
+/// Reaction counts by kind.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ReactionCounts {
+    pub support: i64,
+    pub oppose: i64,
+    pub neutral: i64,
+}
+
+impl ReactionCounts {
+    pub fn get(&self, kind: &str) -> i64 {
+        match kind {
+            "support" => self.support,
+            "oppose" => self.oppose,
+            _ => self.neutral,
+        }
+    }
+
+    pub fn total(&self) -> i64 {
+        self.support + self.oppose + self.neutral
+    }
+
+    /// Share of the reactions with this kind, 0-100.
+    pub fn percent(&self, kind: &str) -> i64 {
+        if self.total() == 0 { 0 } else { self.get(kind) * 100 / self.total() }
+    }
+
+    fn add(&mut self, kind: &str) {
+        match kind {
+            "support" => self.support += 1,
+            "oppose" => self.oppose += 1,
+            _ => self.neutral += 1,
+        }
+    }
+}
+
+/// Reactions on the opinions of one agenda point.
+#[derive(Debug, Clone, Default)]
+pub struct Sentiment {
+    /// All reactions on the agenda point's opinions.
+    pub overall: ReactionCounts,
+    pub by_opinion: HashMap<i64, ReactionCounts>,
+    /// The viewing user's reaction per opinion.
+    pub mine: HashMap<i64, String>,
+    /// Members who reacted to at least one opinion.
+    pub members: i64,
+}
+
+impl Sentiment {
+    pub fn for_opinion(&self, opinion_id: i64) -> ReactionCounts {
+        self.by_opinion.get(&opinion_id).copied().unwrap_or_default()
+    }
+
+    pub fn is_mine(&self, opinion_id: i64, kind: &str) -> bool {
+        self.mine.get(&opinion_id).is_some_and(|k| k == kind)
+    }
+}
+
+/// Tally the reactions on an agenda point's opinions, marking those by `user_id`.
+pub async fn find_sentiment(pool: &PgPool, agenda_point_id: i64, user_id: i64) -> Result<Sentiment, AppError> {
+    let opinion_ids: Vec<i64> = super::find_opinions_for_agenda_point(pool, agenda_point_id).await?
+        .into_iter()
+        .map(|o| o.id)
+        .collect();
+    let rows: Vec<(String, String, String)> = sqlx::query_as(
+        "SELECT p_op.value, p_user.value, p_kind.value \
+         FROM entities e \
+         JOIN entity_properties p_op ON e.id = p_op.entity_id AND p_op.key = 'opinion_id' \
+         JOIN entity_properties p_user ON e.id = p_user.entity_id AND p_user.key = 'user_id' \
+         JOIN entity_properties p_kind ON e.id = p_kind.entity_id AND p_kind.key = 'kind' \
+         WHERE e.entity_type = 'opinion_reaction' \
+           AND p_op.value = ANY($1)",
+    )
+    .bind(opinion_ids.iter().map(|id| id.to_string()).collect::<Vec<_>>())
+    .fetch_all(pool)
+    .await?;
+
+    let mut sentiment = Sentiment::default();
+    let mut members = std::collections::HashSet::new();
+    for (opinion_id, reactor, kind) in rows {
+        let Ok(opinion_id) = opinion_id.parse::<i64>() else { continue };
+        sentiment.overall.add(&kind);
+        sentiment.by_opinion.entry(opinion_id).or_default().add(&kind);
+        if reactor == user_id.to_string() {
+            sentiment.mine.insert(opinion_id, kind);
+        }
+        members.insert(reactor);
+    }
+    sentiment.members = members.len() as i64;
+    Ok(sentiment)
+}
+
+/// Set the user's reaction to an opinion, or withdraw it when it already
+/// has this kind. Returns the reaction now in place.
+pub async fn react(pool: &PgPool, opinion_id: i64, user_id: i64, kind: &str) -> Result<Option<String>, AppError> {
+    let name = format!("reaction_op{}_by{}", opinion_id, user_id);
+    if let Some(existing) = entity::find_by_type_and_name(pool, "opinion_reaction", &name).await? {
+        if entity::get_property(pool, existing.id, "kind").await?.as_deref() == Some(kind) {
+            entity::delete(pool, existing.id).await?;
+            return Ok(None);
+        }
+        entity::set_properties(pool, existing.id, &[
+            ("kind", kind),
+            ("reacted_at", &chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string()),
+        ]).await?;
+        return Ok(Some(kind.to_string()));
+    }
+
+    let id = entity::create(pool, "opinion_reaction", &name, &name).await?;
+    entity::set_properties(pool, id, &[
+        ("opinion_id", &opinion_id.to_string()),
+        ("user_id", &user_id.to_string()),
+        ("kind", kind),
+        ("reacted_at", &chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string()),
+    ]).await?;
+    Ok(Some(kind.to_string()))
+}
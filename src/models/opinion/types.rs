@@ -9,6 +9,11 @@ pub struct OpinionListItem {
     pub preferred_coa_id: i64,
     pub commentary: String,
     pub created_date: String,
+    /// Starts at 1; bumped on every revision.
+    pub version: i32,
+    pub revised_date: String,
+    /// Version the decider saw, set when the decision is recorded.
+    pub considered_version: Option<i32>,
 }
 
 /// Full opinion detail.
@@ -22,6 +27,20 @@ pub struct OpinionDetail {
     pub coa_title: String,
     pub commentary: String,
     pub created_date: String,
+    pub version: i32,
+    pub revised_date: String,
+}
+
+/// An earlier version of an opinion, kept when the member revises it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpinionVersion {
+    pub id: i64,
+    pub opinion_id: i64,
+    pub version: i32,
+    pub preferred_coa_id: i64,
+    pub coa_title: String,
+    pub commentary: String,
+    pub recorded_date: String,
 }
 
 /// Form input for recording an opinion on an agenda item.
@@ -38,6 +57,17 @@ pub struct DecisionForm {
     pub selected_coa_id: i64,
     pub decision_rationale: String,
     pub csrf_token: String,
+    /// Opinion versions shown on the form (`opinion_id:version,...`).
+    #[serde(default)]
+    pub opinion_versions: String,
+    /// Follow-up actions, one per line, recorded as action items.
+    #[serde(default)]
+    pub follow_up_actions: String,
+    /// Owner of the follow-up actions (empty when there are none).
+    #[serde(default)]
+    pub follow_up_owner_id: String,
+    #[serde(default)]
+    pub follow_up_due: String,
 }
 
 /// Summary of opinions grouped by COA preference for an agenda point.
@@ -64,3 +94,65 @@ pub struct DecisionRecord {
     pub opinion_count: i32,
     pub opinions_summary: String,  // e.g., "3 preferred COA#1, 2 preferred COA#2"
 }
+
+/// A decision item in one of the user's ToRs that is still open and on
+/// which the user has not recorded an opinion.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct PendingOpinion {
+    pub agenda_point_id: i64,
+    pub title: String,
+    pub scheduled_date: String,
+    pub tor_id: i64,
+    pub tor_label: String,
+}
+
+/// One row of the decision register: a recorded decision with its context.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct DecisionRegisterEntry {
+    pub id: i64,
+    pub agenda_point_id: i64,
+    pub agenda_point_title: String,
+    pub tor_id: i64,
+    pub tor_label: String,
+    pub selected_coa_title: String,
+    pub decision_rationale: String,
+    pub decided_by_id: i64,
+    pub decided_by_name: String,
+    /// Positions the decider held in the ToR on the decision date.
+    #[sqlx(default)]
+    pub decided_by_position: String,
+    /// The absent member a delegate decided for; empty otherwise.
+    pub on_behalf_of_name: String,
+    pub decided_date: String,
+    /// Stable citation ID, e.g. `BOARD-2026-003`.
+    pub citation_id: String,
+    /// SHA-256 of the decision's canonical content.
+    pub content_hash: String,
+}
+
+impl DecisionRegisterEntry {
+    /// Resolver URL for the citation ID.
+    pub fn citation_url(&self) -> String {
+        format!("/cite/{}", self.citation_id)
+    }
+
+    /// Content-addressed permalink.
+    pub fn permalink(&self) -> String {
+        format!("/decisions/sha256/{}", self.content_hash)
+    }
+
+    /// The decider with the positions they held at the time, e.g. "Alice (Chair)",
+    /// or "Bob on behalf of Alice" for a delegated decision.
+    pub fn decided_by(&self) -> String {
+        let decider = if self.decided_by_position.is_empty() {
+            self.decided_by_name.clone()
+        } else {
+            format!("{} ({})", self.decided_by_name, self.decided_by_position)
+        };
+        if self.on_behalf_of_name.is_empty() {
+            decider
+        } else {
+            format!("{decider} on behalf of {}", self.on_behalf_of_name)
+        }
+    }
+}
@@ -0,0 +1,466 @@
+//! Outbound webhooks: domain events (approvals, meeting confirmations,
+//! decisions) are POSTed to external systems as they happen.
+//!
+//! An outbound webhook is an `outbound_webhook` entity (name = slug) with a
+//! target URL, a shared secret and an event filter. It keeps a cursor into
+//! the domain event stream; [`run_due`], called by the warning scheduler,
+//! turns events past the cursor into `webhook_delivery` entities and sends
+//! the ones that are due. Each body is signed like inbound deliveries
+//! (`X-Ahlt-Signature: sha256=<hex HMAC>`), so one verifier serves both
+//! directions. Failed sends are retried with backoff and parked as `failed`
+//! after [`MAX_ATTEMPTS`]; the deliveries double as the on-screen log.
+
+use serde::Serialize;
+use sqlx::PgPool;
+
+use crate::ticketing::http;
+use crate::warnings::clock;
+use super::domain_event::{self, DomainEvent};
+use super::{entity, webhook};
+
+/// Header naming the event type of a delivery.
+pub const EVENT_HEADER: &str = "X-Ahlt-Event";
+/// Header carrying the delivery id; unchanged across retries.
+pub const DELIVERY_HEADER: &str = "X-Ahlt-Delivery";
+
+/// Event filters offered on the admin form, as (filter, label).
+pub const EVENT_SUGGESTIONS: &[(&str, &str)] = &[
+    ("proposal.approved", "Proposal approved"),
+    ("meeting.confirmed", "Meeting confirmed"),
+    ("decision.recorded", "Decision recorded"),
+    ("proposal.*", "Any proposal status change"),
+    ("meeting.*", "Any meeting status change"),
+];
+
+/// Sends before a delivery is parked as failed.
+pub const MAX_ATTEMPTS: i64 = 8;
+/// Wait after the first failed send; doubles with each further failure.
+const BASE_BACKOFF_SECS: i64 = 60;
+/// Longest wait between sends.
+const MAX_BACKOFF_SECS: i64 = 6 * 3600;
+/// Deliveries sent per run, oldest first.
+const BATCH_SIZE: i64 = 100;
+/// Response text kept with a failed send.
+const MAX_ERROR_LEN: usize = 300;
+
+/// Timestamps are stored as UTC in this format, so they sort as text.
+const TIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%SZ";
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct OutboundWebhook {
+    pub id: i64,
+    pub slug: String,
+    pub label: String,
+    pub is_active: bool,
+    pub url: String,
+    pub secret: String,
+    /// Comma-separated event filters; empty for every event.
+    pub events: String,
+    /// Last domain event id turned into deliveries.
+    pub last_event_id: i64,
+}
+
+impl OutboundWebhook {
+    /// Whether an event type passes this webhook's filter.
+    pub fn wants(&self, event_type: &str) -> bool {
+        matches_filter(&self.events, event_type)
+    }
+}
+
+/// One event queued for one webhook, with the outcome of its latest send.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct WebhookDelivery {
+    pub id: i64,
+    pub webhook_id: i64,
+    pub webhook_label: String,
+    pub event_id: i64,
+    pub event_type: String,
+    /// Exact bytes sent and signed; identical on every retry.
+    pub body: String,
+    /// `pending`, `delivered` or `failed`.
+    pub status: String,
+    pub attempts: i64,
+    /// UTC, `YYYY-MM-DDTHH:MM:SSZ`.
+    pub next_attempt_at: String,
+    /// HTTP status of the latest response; 0 when none was received.
+    pub response_status: i64,
+    pub last_error: String,
+    pub created_at: String,
+    pub delivered_at: String,
+}
+
+impl WebhookDelivery {
+    /// Delivered rows are not resent.
+    pub fn can_retry(&self) -> bool {
+        self.status != "delivered"
+    }
+}
+
+/// Fields submitted from the outbound webhook admin form.
+pub struct OutboundWebhookInput<'a> {
+    pub slug: &'a str,
+    pub label: &'a str,
+    pub url: &'a str,
+    pub events: &'a str,
+}
+
+/// Whether `event_type` passes a comma-separated filter. Entries match
+/// exactly, or by prefix when they end in `.*`; an empty filter passes all.
+pub fn matches_filter(filter: &str, event_type: &str) -> bool {
+    let mut entries = filter.split(',').map(str::trim).filter(|e| !e.is_empty()).peekable();
+    if entries.peek().is_none() {
+        return true;
+    }
+    entries.any(|entry| match entry.strip_suffix('*') {
+        Some(prefix) => event_type.starts_with(prefix),
+        None => entry == event_type,
+    })
+}
+
+/// Normalise a filter for storage: trimmed, de-duplicated entries.
+pub fn normalize_filter(filter: &str) -> String {
+    let mut entries: Vec<&str> = Vec::new();
+    for entry in filter.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        if !entries.contains(&entry) {
+            entries.push(entry);
+        }
+    }
+    entries.join(", ")
+}
+
+/// Validate admin input. Returns a list of problems (empty when valid).
+pub fn validate(input: &OutboundWebhookInput<'_>) -> Vec<String> {
+    let mut errors = Vec::new();
+    if input.slug.is_empty()
+        || !input.slug.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+    {
+        errors.push("Slug must be lowercase letters, digits and dashes".to_string());
+    }
+    if input.label.trim().is_empty() {
+        errors.push("Name is required".to_string());
+    }
+    let url = input.url.trim();
+    let host = url.strip_prefix("https://").or_else(|| url.strip_prefix("http://")).unwrap_or("");
+    if host.is_empty() || host.starts_with('/') || url.chars().any(char::is_whitespace) {
+        errors.push("URL must be an absolute http:// or https:// address".to_string());
+    }
+    for entry in input.events.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let pattern = entry.strip_suffix(".*").unwrap_or(entry);
+        let valid = !pattern.is_empty()
+            && pattern.split('.').all(|part| {
+                !part.is_empty() && part.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+            });
+        if !valid {
+            errors.push(format!("'{}' is not an event type or a `prefix.*` filter", entry));
+        }
+    }
+    errors
+}
+
+const SELECT_WEBHOOK: &str =
+    "SELECT e.id, e.name AS slug, e.label, e.is_active, \
+            COALESCE(p_url.value, '') AS url, \
+            COALESCE(p_secret.value, '') AS secret, \
+            COALESCE(p_events.value, '') AS events, \
+            COALESCE(NULLIF(p_cursor.value, '')::BIGINT, 0) AS last_event_id \
+     FROM entities e \
+     LEFT JOIN entity_properties p_url ON e.id = p_url.entity_id AND p_url.key = 'url' \
+     LEFT JOIN entity_properties p_secret ON e.id = p_secret.entity_id AND p_secret.key = 'secret' \
+     LEFT JOIN entity_properties p_events ON e.id = p_events.entity_id AND p_events.key = 'events' \
+     LEFT JOIN entity_properties p_cursor ON e.id = p_cursor.entity_id AND p_cursor.key = 'last_event_id' \
+     WHERE e.entity_type = 'outbound_webhook'";
+
+/// All outbound webhooks, by label.
+pub async fn find_all(pool: &PgPool) -> Result<Vec<OutboundWebhook>, sqlx::Error> {
+    sqlx::query_as::<_, OutboundWebhook>(&format!("{} ORDER BY e.label", SELECT_WEBHOOK))
+        .fetch_all(pool)
+        .await
+}
+
+pub async fn find_by_id(pool: &PgPool, id: i64) -> Result<Option<OutboundWebhook>, sqlx::Error> {
+    sqlx::query_as::<_, OutboundWebhook>(&format!("{} AND e.id = $1", SELECT_WEBHOOK))
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+}
+
+pub async fn find_by_slug(pool: &PgPool, slug: &str) -> Result<Option<OutboundWebhook>, sqlx::Error> {
+    sqlx::query_as::<_, OutboundWebhook>(&format!("{} AND e.name = $1", SELECT_WEBHOOK))
+        .bind(slug)
+        .fetch_optional(pool)
+        .await
+}
+
+/// Create an outbound webhook with a fresh secret. It receives events
+/// recorded from now on, not the history. Returns its id.
+pub async fn create(pool: &PgPool, input: &OutboundWebhookInput<'_>, created_by: i64) -> Result<i64, sqlx::Error> {
+    let latest: i64 = sqlx::query_scalar("SELECT COALESCE(MAX(id), 0) FROM domain_events")
+        .fetch_one(pool)
+        .await?;
+    let id = entity::create(pool, "outbound_webhook", input.slug, input.label.trim()).await?;
+    entity::set_properties(pool, id, &[
+        ("url", input.url.trim()),
+        ("secret", &webhook::generate_secret()),
+        ("events", &normalize_filter(input.events)),
+        ("last_event_id", &latest.to_string()),
+        ("created_by_id", &created_by.to_string()),
+    ]).await?;
+    Ok(id)
+}
+
+/// Replace the shared secret. Deliveries still pending are signed with the
+/// new one when next sent.
+pub async fn regenerate_secret(pool: &PgPool, id: i64) -> Result<(), sqlx::Error> {
+    entity::set_property(pool, id, "secret", &webhook::generate_secret()).await
+}
+
+/// Pause or resume. Events recorded while paused are delivered on resume.
+/// Returns false if no such webhook.
+pub async fn set_active(pool: &PgPool, id: i64, active: bool) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "UPDATE entities SET is_active = $2, updated_at = NOW() WHERE id = $1 AND entity_type = 'outbound_webhook'"
+    )
+    .bind(id)
+    .bind(active)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Delete a webhook together with its delivery log.
+pub async fn delete(pool: &PgPool, id: i64) -> Result<bool, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    sqlx::query(
+        "DELETE FROM entities WHERE entity_type = 'webhook_delivery' AND id IN ( \
+             SELECT entity_id FROM entity_properties WHERE key = 'webhook_id' AND value = $1)"
+    )
+    .bind(id.to_string())
+    .execute(&mut *tx)
+    .await?;
+    let result = sqlx::query("DELETE FROM entities WHERE id = $1 AND entity_type = 'outbound_webhook'")
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Seconds to wait before the next send after `attempts` failed ones.
+pub fn backoff_secs(attempts: i64) -> i64 {
+    BASE_BACKOFF_SECS
+        .saturating_mul(2_i64.saturating_pow((attempts - 1).clamp(0, 20) as u32))
+        .min(MAX_BACKOFF_SECS)
+}
+
+/// Request body for an event: the event as the events API returns it.
+pub fn body_for(event: &DomainEvent) -> String {
+    serde_json::to_string(event).unwrap_or_default()
+}
+
+/// Queue deliveries for events recorded since each active webhook's cursor
+/// and advance the cursors. Returns how many deliveries were queued.
+pub async fn enqueue_new_events(pool: &PgPool) -> Result<usize, sqlx::Error> {
+    let now = clock::now().format(TIME_FORMAT).to_string();
+    let mut queued = 0;
+    for hook in find_all(pool).await?.into_iter().filter(|h| h.is_active) {
+        let mut cursor = hook.last_event_id;
+        loop {
+            let events = domain_event::find_after(pool, cursor, None, domain_event::MAX_LIMIT).await?;
+            let Some(last) = events.last() else { break };
+            cursor = last.id;
+            for event in events.iter().filter(|e| hook.wants(&e.event_type)) {
+                let name = format!("delivery.{}.{}", hook.id, event.id);
+                let id = entity::create(pool, "webhook_delivery", &name, &event.event_type).await?;
+                entity::set_properties(pool, id, &[
+                    ("webhook_id", &hook.id.to_string()),
+                    ("event_id", &event.id.to_string()),
+                    ("event_type", &event.event_type),
+                    ("body", &body_for(event)),
+                    ("status", "pending"),
+                    ("attempts", "0"),
+                    ("next_attempt_at", &now),
+                ]).await?;
+                queued += 1;
+            }
+            entity::set_property(pool, hook.id, "last_event_id", &cursor.to_string()).await?;
+        }
+    }
+    Ok(queued)
+}
+
+const SELECT_DELIVERY: &str =
+    "SELECT e.id, \
+            COALESCE(NULLIF(p_hook.value, '')::BIGINT, 0) AS webhook_id, \
+            COALESCE(h.label, '') AS webhook_label, \
+            COALESCE(NULLIF(p_event.value, '')::BIGINT, 0) AS event_id, \
+            e.label AS event_type, \
+            COALESCE(p_body.value, '') AS body, \
+            COALESCE(p_status.value, 'pending') AS status, \
+            COALESCE(NULLIF(p_attempts.value, '')::BIGINT, 0) AS attempts, \
+            COALESCE(p_next.value, '') AS next_attempt_at, \
+            COALESCE(NULLIF(p_code.value, '')::BIGINT, 0) AS response_status, \
+            COALESCE(p_error.value, '') AS last_error, \
+            to_char(e.created_at, 'YYYY-MM-DD HH24:MI:SS') AS created_at, \
+            COALESCE(p_done.value, '') AS delivered_at \
+     FROM entities e \
+     LEFT JOIN entity_properties p_hook ON e.id = p_hook.entity_id AND p_hook.key = 'webhook_id' \
+     LEFT JOIN entities h ON h.id::TEXT = p_hook.value \
+     LEFT JOIN entity_properties p_event ON e.id = p_event.entity_id AND p_event.key = 'event_id' \
+     LEFT JOIN entity_properties p_body ON e.id = p_body.entity_id AND p_body.key = 'body' \
+     LEFT JOIN entity_properties p_status ON e.id = p_status.entity_id AND p_status.key = 'status' \
+     LEFT JOIN entity_properties p_attempts ON e.id = p_attempts.entity_id AND p_attempts.key = 'attempts' \
+     LEFT JOIN entity_properties p_next ON e.id = p_next.entity_id AND p_next.key = 'next_attempt_at' \
+     LEFT JOIN entity_properties p_code ON e.id = p_code.entity_id AND p_code.key = 'response_status' \
+     LEFT JOIN entity_properties p_error ON e.id = p_error.entity_id AND p_error.key = 'last_error' \
+     LEFT JOIN entity_properties p_done ON e.id = p_done.entity_id AND p_done.key = 'delivered_at' \
+     WHERE e.entity_type = 'webhook_delivery'";
+
+/// Most recent deliveries, newest first, optionally for one webhook or with one status.
+pub async fn find_deliveries(
+    pool: &PgPool,
+    webhook_id: Option<i64>,
+    status: Option<&str>,
+    limit: i64,
+) -> Result<Vec<WebhookDelivery>, sqlx::Error> {
+    sqlx::query_as::<_, WebhookDelivery>(&format!(
+        "SELECT * FROM ({}) d \
+         WHERE ($1::BIGINT IS NULL OR d.webhook_id = $1) AND ($2::TEXT IS NULL OR d.status = $2) \
+         ORDER BY d.id DESC LIMIT $3",
+        SELECT_DELIVERY
+    ))
+    .bind(webhook_id)
+    .bind(status)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn find_delivery(pool: &PgPool, id: i64) -> Result<Option<WebhookDelivery>, sqlx::Error> {
+    sqlx::query_as::<_, WebhookDelivery>(&format!("{} AND e.id = $1", SELECT_DELIVERY))
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+}
+
+/// Pending deliveries whose next send is due, oldest first.
+async fn find_due(pool: &PgPool, now: &str) -> Result<Vec<WebhookDelivery>, sqlx::Error> {
+    sqlx::query_as::<_, WebhookDelivery>(&format!(
+        "SELECT * FROM ({}) d WHERE d.status = 'pending' AND d.next_attempt_at <= $1 ORDER BY d.id LIMIT $2",
+        SELECT_DELIVERY
+    ))
+    .bind(now)
+    .bind(BATCH_SIZE)
+    .fetch_all(pool)
+    .await
+}
+
+/// POST a delivery's body, signed with the webhook's current secret.
+/// Returns the response status, or why no response was received.
+async fn send(hook: &OutboundWebhook, delivery: &WebhookDelivery) -> Result<u16, String> {
+    let signature = webhook::sign(&hook.secret, delivery.body.as_bytes());
+    let delivery_id = delivery.id.to_string();
+    let headers = [
+        (webhook::SIGNATURE_HEADER, signature.as_str()),
+        (EVENT_HEADER, delivery.event_type.as_str()),
+        (DELIVERY_HEADER, delivery_id.as_str()),
+    ];
+    let response = http::send("POST", &hook.url, &headers, Some(&delivery.body)).await?;
+    if response.is_success() {
+        return Ok(response.status);
+    }
+    let excerpt: String = response.body.chars().take(MAX_ERROR_LEN).collect();
+    Err(format!("HTTP {}: {}", response.status, excerpt.trim()))
+}
+
+/// Store the outcome of one send: delivered, due again after backoff, or
+/// parked as failed once the attempts run out.
+async fn record_attempt(
+    pool: &PgPool,
+    delivery: &WebhookDelivery,
+    outcome: &Result<u16, String>,
+) -> Result<(), sqlx::Error> {
+    let now = clock::now();
+    let attempts = delivery.attempts + 1;
+    let attempts_text = attempts.to_string();
+    match outcome {
+        Ok(code) => {
+            entity::set_properties(pool, delivery.id, &[
+                ("status", "delivered"),
+                ("attempts", &attempts_text),
+                ("response_status", &code.to_string()),
+                ("last_error", ""),
+                ("delivered_at", &now.format(TIME_FORMAT).to_string()),
+            ]).await
+        }
+        Err(error) => {
+            // "HTTP 503: ..." carries the status; transport errors have none
+            let code = error.strip_prefix("HTTP ")
+                .and_then(|rest| rest.split(':').next())
+                .unwrap_or("0");
+            let status = if attempts >= MAX_ATTEMPTS { "failed" } else { "pending" };
+            let next = now + chrono::Duration::seconds(backoff_secs(attempts));
+            entity::set_properties(pool, delivery.id, &[
+                ("status", status),
+                ("attempts", &attempts_text),
+                ("response_status", code),
+                ("last_error", error),
+                ("next_attempt_at", &next.format(TIME_FORMAT).to_string()),
+            ]).await
+        }
+    }
+}
+
+/// Send one delivery now, whatever its schedule. Returns whether the
+/// receiver accepted it.
+pub async fn attempt(pool: &PgPool, delivery: &WebhookDelivery) -> Result<bool, sqlx::Error> {
+    let outcome = match find_by_id(pool, delivery.webhook_id).await? {
+        Some(hook) => send(&hook, delivery).await,
+        None => Err("Webhook no longer exists".to_string()),
+    };
+    record_attempt(pool, delivery, &outcome).await?;
+    if let Err(e) = &outcome {
+        log::warn!("Webhook delivery {} ({}) failed (attempt {}): {}",
+            delivery.id, delivery.event_type, delivery.attempts + 1, e);
+    }
+    Ok(outcome.is_ok())
+}
+
+/// Queue deliveries for new events, then send those that are due.
+/// Deliveries of paused webhooks wait until they are resumed.
+/// Returns (delivered, failed sends).
+pub async fn run_due(pool: &PgPool) -> Result<(usize, usize), sqlx::Error> {
+    enqueue_new_events(pool).await?;
+    let now = clock::now().format(TIME_FORMAT).to_string();
+    let active: Vec<i64> = find_all(pool).await?.into_iter()
+        .filter(|h| h.is_active)
+        .map(|h| h.id)
+        .collect();
+    let mut delivered = 0;
+    let mut failed = 0;
+    for delivery in find_due(pool, &now).await?.iter().filter(|d| active.contains(&d.webhook_id)) {
+        if attempt(pool, delivery).await? {
+            delivered += 1;
+        } else {
+            failed += 1;
+        }
+    }
+    Ok((delivered, failed))
+}
+
+/// Reset a pending or failed delivery so it is sent on the next run with a
+/// fresh set of attempts. Returns false if it was already delivered.
+pub async fn requeue(pool: &PgPool, id: i64) -> Result<bool, sqlx::Error> {
+    let Some(delivery) = find_delivery(pool, id).await? else {
+        return Ok(false);
+    };
+    if !delivery.can_retry() {
+        return Ok(false);
+    }
+    let now = clock::now().format(TIME_FORMAT).to_string();
+    entity::set_properties(pool, id, &[
+        ("status", "pending"),
+        ("attempts", "0"),
+        ("next_attempt_at", &now),
+    ]).await?;
+    Ok(true)
+}
@@ -0,0 +1,357 @@
+//! Ownership transfers.
+//!
+//! A proposal is owned by its submitter, a ToR by the holder of its chair
+//! position and a document by its creator. The owner, or anyone allowed to
+//! manage the item, can offer it to another user. The offer waits as a
+//! pending `ownership_transfer` until the new owner accepts it, which moves
+//! ownership, or declines it; the requester may cancel it meanwhile. Only one
+//! transfer per item can be pending.
+
+use sqlx::{PgPool, Postgres, Transaction};
+
+use super::{entity, relation, tor};
+
+/// Items that can change owner, as (entity type, label).
+pub const KINDS: &[(&str, &str)] = &[
+    ("proposal", "proposal"),
+    ("tor", "ToR"),
+    ("document", "document"),
+];
+
+pub const STATUS_PENDING: &str = "pending";
+pub const STATUS_ACCEPTED: &str = "accepted";
+pub const STATUS_DECLINED: &str = "declined";
+pub const STATUS_CANCELLED: &str = "cancelled";
+
+/// Position text that marks a ToR's chair, as in the built-in `tor_chairs` group.
+const CHAIR_POSITION: &str = "chair";
+
+/// An item that can be transferred, with its current owner.
+#[derive(Debug, Clone)]
+pub struct TransferItem {
+    pub id: i64,
+    /// `proposal`, `tor` or `document`.
+    pub kind: String,
+    pub label: String,
+    /// 0 when the item has no owner (e.g. a vacant chair).
+    pub owner_id: i64,
+    pub owner_name: String,
+    /// ToR the item belongs to; 0 for documents not scoped to one.
+    pub tor_id: i64,
+    /// The ToR's chair position; 0 for other kinds.
+    pub position_id: i64,
+}
+
+impl TransferItem {
+    pub fn kind_label(&self) -> &'static str {
+        kind_label(&self.kind)
+    }
+
+    /// Page of the item.
+    pub fn path(&self) -> String {
+        item_path(&self.kind, self.id, self.tor_id)
+    }
+
+    /// Permission that lets users other than the owner transfer the item.
+    pub fn manage_permission(&self) -> &'static str {
+        match self.kind.as_str() {
+            "proposal" => "proposal.edit",
+            "tor" => "tor.manage_members",
+            _ => "document.edit",
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        describe(&self.kind, &self.label)
+    }
+}
+
+/// What changes hands, for messages: "the chair of ToR 'Board'".
+fn describe(kind: &str, label: &str) -> String {
+    match kind {
+        "tor" => format!("the chair of ToR \u{2018}{}\u{2019}", label),
+        _ => format!("{} \u{2018}{}\u{2019}", kind_label(kind), label),
+    }
+}
+
+fn kind_label(kind: &str) -> &'static str {
+    KINDS.iter().find(|(k, _)| *k == kind).map(|(_, label)| *label).unwrap_or("item")
+}
+
+fn item_path(kind: &str, id: i64, tor_id: i64) -> String {
+    match kind {
+        "proposal" => format!("/tor/{}/proposals/{}", tor_id, id),
+        "tor" => format!("/tor/{}", id),
+        _ => format!("/documents/{}", id),
+    }
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct OwnershipTransfer {
+    pub id: i64,
+    pub item_id: i64,
+    pub item_type: String,
+    pub item_label: String,
+    pub tor_id: i64,
+    pub from_user_id: i64,
+    pub from_name: String,
+    pub to_user_id: i64,
+    pub to_name: String,
+    pub requested_by_id: i64,
+    pub requested_by_name: String,
+    pub status: String,
+    pub note: String,
+    pub created_at: String,
+    /// When it was accepted, declined or cancelled; empty while pending.
+    pub decided_at: String,
+}
+
+impl OwnershipTransfer {
+    pub fn is_pending(&self) -> bool {
+        self.status == STATUS_PENDING
+    }
+
+    pub fn kind_label(&self) -> &'static str {
+        kind_label(&self.item_type)
+    }
+
+    pub fn item_path(&self) -> String {
+        item_path(&self.item_type, self.item_id, self.tor_id)
+    }
+
+    pub fn describe(&self) -> String {
+        describe(&self.item_type, &self.item_label)
+    }
+
+    /// Whether the user may withdraw the offer.
+    pub fn can_cancel(&self, user_id: i64) -> bool {
+        self.is_pending() && (self.requested_by_id == user_id || self.from_user_id == user_id)
+    }
+}
+
+/// Chair position of a ToR: the first position named like a chair that is
+/// not a vice chair, mandatory positions first.
+pub async fn find_chair_position(pool: &PgPool, tor_id: i64) -> Result<Option<tor::TorMember>, sqlx::Error> {
+    Ok(tor::find_members(pool, tor_id).await?
+        .into_iter()
+        .find(|m| {
+            let text = format!("{} {}", m.position_name, m.position_label).to_lowercase();
+            text.contains(CHAIR_POSITION) && !text.contains("vice")
+        }))
+}
+
+async fn user_label(pool: &PgPool, user_id: i64) -> Result<String, sqlx::Error> {
+    Ok(entity::find_by_id(pool, user_id).await?
+        .filter(|u| u.entity_type == "user")
+        .map(|u| u.label)
+        .unwrap_or_default())
+}
+
+fn id_property(value: Option<String>) -> i64 {
+    value.and_then(|v| v.parse().ok()).unwrap_or(0)
+}
+
+/// Look up a proposal, ToR or document with its current owner.
+pub async fn find_item(pool: &PgPool, id: i64) -> Result<Option<TransferItem>, sqlx::Error> {
+    let Some(e) = entity::find_by_id(pool, id).await? else {
+        return Ok(None);
+    };
+    let (owner_id, tor_id, position_id) = match e.entity_type.as_str() {
+        "proposal" => {
+            let owner = id_property(entity::get_property(pool, id, "submitted_by_id").await?);
+            let tor_id = relation::find_targets(pool, id, "submitted_to").await?
+                .first().map(|t| t.id).unwrap_or(0);
+            (owner, tor_id, 0)
+        }
+        "document" => {
+            let owner = id_property(entity::get_property(pool, id, "created_by_id").await?);
+            let tor_id = relation::find_targets(pool, id, "scoped_to_tor").await?
+                .first().map(|t| t.id).unwrap_or(0);
+            (owner, tor_id, 0)
+        }
+        "tor" => match find_chair_position(pool, id).await? {
+            Some(chair) => (chair.holder_id.unwrap_or(0), id, chair.position_id),
+            None => (0, id, 0),
+        },
+        _ => return Ok(None),
+    };
+    Ok(Some(TransferItem {
+        id,
+        kind: e.entity_type,
+        label: e.label,
+        owner_id,
+        owner_name: user_label(pool, owner_id).await?,
+        tor_id,
+        position_id,
+    }))
+}
+
+/// Check a transfer request before creating it.
+pub async fn validate(pool: &PgPool, item: &TransferItem, to_user_id: i64) -> Result<Vec<String>, sqlx::Error> {
+    let mut errors = Vec::new();
+    if item.kind == "tor" && item.position_id == 0 {
+        errors.push("This ToR has no chair position to transfer".to_string());
+    }
+    let to_user = entity::find_by_id(pool, to_user_id).await?
+        .filter(|u| u.entity_type == "user" && u.is_active);
+    if to_user.is_none() {
+        errors.push("Choose the new owner".to_string());
+    } else if to_user_id == item.owner_id {
+        errors.push(format!("{} already owns this {}", item.owner_name, item.kind_label()));
+    }
+    if find_pending_for_item(pool, item.id).await?.is_some() {
+        errors.push(format!("A transfer of this {} is already waiting for acceptance", item.kind_label()));
+    }
+    Ok(errors)
+}
+
+/// Offer an item to a new owner. Returns the pending transfer's id.
+pub async fn request(
+    pool: &PgPool,
+    item: &TransferItem,
+    to_user_id: i64,
+    requested_by_id: i64,
+    note: &str,
+) -> Result<i64, sqlx::Error> {
+    let name = entity::available_name(pool, "ownership_transfer", &format!("{}_{}_to_{}", item.kind, item.id, to_user_id)).await?;
+    let id = entity::create(pool, "ownership_transfer", &name, &item.label).await?;
+    entity::set_properties(pool, id, &[
+        ("item_id", &item.id.to_string()),
+        ("item_type", &item.kind),
+        ("tor_id", &item.tor_id.to_string()),
+        ("from_user_id", &item.owner_id.to_string()),
+        ("to_user_id", &to_user_id.to_string()),
+        ("requested_by_id", &requested_by_id.to_string()),
+        ("status", STATUS_PENDING),
+        ("note", note.trim()),
+    ]).await?;
+    Ok(id)
+}
+
+const SELECT_TRANSFER: &str =
+    "SELECT e.id, \
+            COALESCE(NULLIF(p_item.value, '')::BIGINT, 0) AS item_id, \
+            COALESCE(p_type.value, '') AS item_type, \
+            COALESCE(i.label, e.label) AS item_label, \
+            COALESCE(NULLIF(p_tor.value, '')::BIGINT, 0) AS tor_id, \
+            COALESCE(NULLIF(p_from.value, '')::BIGINT, 0) AS from_user_id, \
+            COALESCE(uf.label, '') AS from_name, \
+            COALESCE(NULLIF(p_to.value, '')::BIGINT, 0) AS to_user_id, \
+            COALESCE(ut.label, '') AS to_name, \
+            COALESCE(NULLIF(p_by.value, '')::BIGINT, 0) AS requested_by_id, \
+            COALESCE(ub.label, '') AS requested_by_name, \
+            COALESCE(p_status.value, '') AS status, \
+            COALESCE(p_note.value, '') AS note, \
+            to_char(e.created_at, 'YYYY-MM-DD HH24:MI') AS created_at, \
+            COALESCE(p_decided.value, '') AS decided_at \
+     FROM entities e \
+     LEFT JOIN entity_properties p_item ON e.id = p_item.entity_id AND p_item.key = 'item_id' \
+     LEFT JOIN entities i ON i.id::TEXT = p_item.value \
+     LEFT JOIN entity_properties p_type ON e.id = p_type.entity_id AND p_type.key = 'item_type' \
+     LEFT JOIN entity_properties p_tor ON e.id = p_tor.entity_id AND p_tor.key = 'tor_id' \
+     LEFT JOIN entity_properties p_from ON e.id = p_from.entity_id AND p_from.key = 'from_user_id' \
+     LEFT JOIN entities uf ON uf.id::TEXT = p_from.value \
+     LEFT JOIN entity_properties p_to ON e.id = p_to.entity_id AND p_to.key = 'to_user_id' \
+     LEFT JOIN entities ut ON ut.id::TEXT = p_to.value \
+     LEFT JOIN entity_properties p_by ON e.id = p_by.entity_id AND p_by.key = 'requested_by_id' \
+     LEFT JOIN entities ub ON ub.id::TEXT = p_by.value \
+     LEFT JOIN entity_properties p_status ON e.id = p_status.entity_id AND p_status.key = 'status' \
+     LEFT JOIN entity_properties p_note ON e.id = p_note.entity_id AND p_note.key = 'note' \
+     LEFT JOIN entity_properties p_decided ON e.id = p_decided.entity_id AND p_decided.key = 'decided_at' \
+     WHERE e.entity_type = 'ownership_transfer'";
+
+pub async fn find_by_id(pool: &PgPool, id: i64) -> Result<Option<OwnershipTransfer>, sqlx::Error> {
+    sqlx::query_as::<_, OwnershipTransfer>(&format!("{} AND e.id = $1", SELECT_TRANSFER))
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+}
+
+/// The transfer of an item waiting for acceptance, if any.
+pub async fn find_pending_for_item(pool: &PgPool, item_id: i64) -> Result<Option<OwnershipTransfer>, sqlx::Error> {
+    sqlx::query_as::<_, OwnershipTransfer>(
+        &format!("{} AND p_item.value = $1 AND p_status.value = $2", SELECT_TRANSFER),
+    )
+    .bind(item_id.to_string())
+    .bind(STATUS_PENDING)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Transfers offered to the user, pending first then newest first.
+pub async fn find_incoming(pool: &PgPool, user_id: i64) -> Result<Vec<OwnershipTransfer>, sqlx::Error> {
+    sqlx::query_as::<_, OwnershipTransfer>(&format!(
+        "{} AND p_to.value = $1 \
+         ORDER BY CASE WHEN p_status.value = 'pending' THEN 0 ELSE 1 END, e.id DESC LIMIT 50",
+        SELECT_TRANSFER,
+    ))
+    .bind(user_id.to_string())
+    .fetch_all(pool)
+    .await
+}
+
+/// Transfers the user requested or whose items they gave away, pending
+/// first then newest first.
+pub async fn find_outgoing(pool: &PgPool, user_id: i64) -> Result<Vec<OwnershipTransfer>, sqlx::Error> {
+    sqlx::query_as::<_, OwnershipTransfer>(&format!(
+        "{} AND (p_by.value = $1 OR p_from.value = $1) AND p_to.value != $1 \
+         ORDER BY CASE WHEN p_status.value = 'pending' THEN 0 ELSE 1 END, e.id DESC LIMIT 50",
+        SELECT_TRANSFER,
+    ))
+    .bind(user_id.to_string())
+    .fetch_all(pool)
+    .await
+}
+
+async fn close(pool: &PgPool, id: i64, status: &str) -> Result<(), sqlx::Error> {
+    let now = chrono::Utc::now().format("%Y-%m-%d %H:%M").to_string();
+    entity::set_properties(pool, id, &[("status", status), ("decided_at", &now)]).await
+}
+
+async fn close_in_tx(tx: &mut Transaction<'_, Postgres>, id: i64, status: &str) -> Result<(), sqlx::Error> {
+    let now = chrono::Utc::now().format("%Y-%m-%d %H:%M").to_string();
+    entity::set_property_in_tx(tx, id, "status", status).await?;
+    entity::set_property_in_tx(tx, id, "decided_at", &now).await
+}
+
+/// Hand the item to the new owner and close the transfer as accepted, in one
+/// transaction. Returns false when the item no longer exists.
+pub async fn accept(pool: &PgPool, transfer: &OwnershipTransfer) -> Result<bool, sqlx::Error> {
+    let Some(item) = find_item(pool, transfer.item_id).await? else {
+        return Ok(false);
+    };
+    let chair = if item.kind == "tor" {
+        let Some(chair) = find_chair_position(pool, item.id).await? else {
+            return Ok(false);
+        };
+        // Archive the membership before the handover; it only reads the current state
+        tor::record_position_change(pool, chair.position_id).await?;
+        Some(chair)
+    } else {
+        None
+    };
+
+    let new_owner = transfer.to_user_id.to_string();
+    let mut tx = pool.begin().await?;
+    match (item.kind.as_str(), chair) {
+        ("proposal", _) => entity::set_property_in_tx(&mut tx, item.id, "submitted_by_id", &new_owner).await?,
+        ("document", _) => entity::set_property_in_tx(&mut tx, item.id, "created_by_id", &new_owner).await?,
+        (_, Some(chair)) => {
+            tor::vacate_position_in_tx(&mut tx, chair.position_id).await?;
+            tor::assign_to_position_in_tx(&mut tx, transfer.to_user_id, chair.position_id, &chair.membership_type).await?;
+        }
+        _ => return Ok(false),
+    }
+    close_in_tx(&mut tx, transfer.id, STATUS_ACCEPTED).await?;
+    tx.commit().await?;
+    Ok(true)
+}
+
+/// Close a pending transfer without moving ownership.
+pub async fn decline(pool: &PgPool, id: i64) -> Result<(), sqlx::Error> {
+    close(pool, id, STATUS_DECLINED).await
+}
+
+pub async fn cancel(pool: &PgPool, id: i64) -> Result<(), sqlx::Error> {
+    close(pool, id, STATUS_CANCELLED).await
+}
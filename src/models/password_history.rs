@@ -0,0 +1,59 @@
+//! Passwords users have replaced.
+//!
+//! Changing a password keeps the hash it replaces (see `user::update_password`
+//! and `user::update`), so the password policy can refuse recent passwords
+//! and tell when the current one was set. Only the newest [`KEPT`] hashes of
+//! each user are kept.
+
+use sqlx::PgPool;
+
+/// How many replaced hashes are kept per user; the upper bound of
+/// `auth.password_history`.
+pub const KEPT: i64 = 24;
+
+/// Keep the user's current password hash before it is replaced. Accounts
+/// without a password (single sign-on) record nothing.
+pub async fn push_current(pool: &PgPool, user_id: i64) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO password_history (user_id, hash) \
+         SELECT entity_id, value FROM entity_properties \
+         WHERE entity_id = $1 AND key = 'password' AND value <> ''",
+    )
+    .bind(user_id)
+    .execute(pool)
+    .await?;
+    sqlx::query(
+        "DELETE FROM password_history WHERE user_id = $1 AND id NOT IN \
+         (SELECT id FROM password_history WHERE user_id = $1 ORDER BY id DESC LIMIT $2)",
+    )
+    .bind(user_id)
+    .bind(KEPT)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// The user's `limit` most recently replaced hashes, newest first.
+pub async fn recent(pool: &PgPool, user_id: i64, limit: i64) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar("SELECT hash FROM password_history WHERE user_id = $1 ORDER BY id DESC LIMIT $2")
+        .bind(user_id)
+        .bind(limit)
+        .fetch_all(pool)
+        .await
+}
+
+/// Whether the user's password was set more than `max_age_days` ago: at the
+/// last change or, if it was never changed, when the account was created.
+pub async fn is_older_than(pool: &PgPool, user_id: i64, max_age_days: i64) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar(
+        "SELECT COALESCE( \
+             (SELECT MAX(created_at) FROM password_history WHERE user_id = $1), \
+             (SELECT created_at FROM entities WHERE id = $1) \
+         ) < NOW() - make_interval(days => $2::INT)",
+    )
+    .bind(user_id)
+    .bind(max_age_days)
+    .fetch_one(pool)
+    .await
+    .map(|older: Option<bool>| older.unwrap_or(false))
+}
@@ -0,0 +1,174 @@
+//! Per-ToR proposal intake forms.
+//!
+//! A ToR can add its own sections to the proposal form (financial impact,
+//! legal review, ...), guidance shown above the form, and a minimum number
+//! of attached documents. The configuration is stored on the ToR entity as
+//! `proposal_intake` (JSON). Section answers are stored on the proposal as
+//! `section_<key>` properties; attachments are ToR documents or uploaded
+//! files linked with `attached_to_proposal` (document → proposal).
+//!
+//! Drafts may be saved incomplete: required sections and attachments are
+//! checked when the proposal is submitted.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+
+use crate::errors::AppError;
+use crate::models::{attachment, entity, relation};
+
+/// An extra section on a ToR's proposal form.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct IntakeSection {
+    pub key: String,
+    pub label: String,
+    #[serde(default)]
+    pub help: String,
+    #[serde(default)]
+    pub required: bool,
+}
+
+impl IntakeSection {
+    /// Form field and property name for the section's answer.
+    pub fn field(&self) -> String {
+        format!("section_{}", self.key)
+    }
+}
+
+/// How a ToR's proposal form differs from the standard one.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProposalIntake {
+    /// Shown above the proposal form.
+    #[serde(default)]
+    pub guidance: String,
+    #[serde(default)]
+    pub sections: Vec<IntakeSection>,
+    /// Documents that must be attached before submitting.
+    #[serde(default)]
+    pub min_attachments: usize,
+    /// What the attachments should be, shown with the attachment list.
+    #[serde(default)]
+    pub attachment_help: String,
+}
+
+impl ProposalIntake {
+    pub fn is_standard(&self) -> bool {
+        self == &ProposalIntake::default()
+    }
+
+    /// What still blocks submitting a proposal with these answers and this
+    /// many attachments. Empty when it may be submitted.
+    pub fn missing(&self, answers: &HashMap<String, String>, attachments: usize) -> Vec<String> {
+        let mut missing: Vec<String> = self.sections.iter()
+            .filter(|s| s.required)
+            .filter(|s| answers.get(&s.key).is_none_or(|a| a.trim().is_empty()))
+            .map(|s| format!("{} is required", s.label))
+            .collect();
+        if attachments < self.min_attachments {
+            missing.push(format!(
+                "At least {} attached document(s) required ({} attached)",
+                self.min_attachments, attachments
+            ));
+        }
+        missing
+    }
+}
+
+/// Section key from its label: lowercase words joined by underscores.
+pub fn section_key(label: &str) -> String {
+    label.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+/// Load a ToR's intake configuration; the standard form if none is set.
+pub async fn find_intake(pool: &PgPool, tor_id: i64) -> Result<ProposalIntake, AppError> {
+    Ok(entity::get_property(pool, tor_id, "proposal_intake").await?
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default())
+}
+
+/// Store a ToR's intake configuration.
+pub async fn save_intake(pool: &PgPool, tor_id: i64, intake: &ProposalIntake) -> Result<(), AppError> {
+    let json = serde_json::to_string(intake).unwrap_or_else(|_| "{}".to_string());
+    entity::set_property(pool, tor_id, "proposal_intake", &json).await?;
+    Ok(())
+}
+
+/// Section answers on a proposal, keyed by section key.
+pub async fn find_section_answers(pool: &PgPool, proposal_id: i64) -> Result<HashMap<String, String>, AppError> {
+    Ok(entity::get_properties(pool, proposal_id).await?
+        .into_iter()
+        .filter_map(|(k, v)| k.strip_prefix("section_").map(|key| (key.to_string(), v)))
+        .collect())
+}
+
+/// Store answers for the configured sections; blank answers are removed.
+pub async fn save_section_answers(
+    pool: &PgPool,
+    proposal_id: i64,
+    intake: &ProposalIntake,
+    answers: &HashMap<String, String>,
+) -> Result<(), AppError> {
+    for section in &intake.sections {
+        match answers.get(&section.key).map(|a| a.trim()).filter(|a| !a.is_empty()) {
+            Some(answer) => entity::set_property(pool, proposal_id, &section.field(), answer).await?,
+            None => entity::delete_property(pool, proposal_id, &section.field()).await?,
+        }
+    }
+    Ok(())
+}
+
+/// Document attached to a proposal.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ProposalAttachment {
+    pub id: i64,
+    pub title: String,
+    pub doc_type: String,
+}
+
+pub async fn find_attachments(pool: &PgPool, proposal_id: i64) -> Result<Vec<ProposalAttachment>, AppError> {
+    let attachments = sqlx::query_as::<_, ProposalAttachment>(
+        "SELECT d.id, \
+                COALESCE(p_title.value, d.label) AS title, \
+                COALESCE(p_type.value, 'ad_hoc') AS doc_type \
+         FROM relations r \
+         JOIN entities rt ON r.relation_type_id = rt.id AND rt.name = 'attached_to_proposal' \
+         JOIN entities d ON d.id = r.source_id AND d.entity_type = 'document' \
+         LEFT JOIN entity_properties p_title \
+             ON d.id = p_title.entity_id AND p_title.key = 'title' \
+         LEFT JOIN entity_properties p_type \
+             ON d.id = p_type.entity_id AND p_type.key = 'doc_type' \
+         WHERE r.target_id = $1 \
+         ORDER BY d.id",
+    )
+    .bind(proposal_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(attachments)
+}
+
+pub async fn attach_document(pool: &PgPool, proposal_id: i64, document_id: i64) -> Result<(), AppError> {
+    relation::create(pool, "attached_to_proposal", document_id, proposal_id).await?;
+    Ok(())
+}
+
+pub async fn detach_document(pool: &PgPool, proposal_id: i64, document_id: i64) -> Result<(), AppError> {
+    relation::delete(pool, "attached_to_proposal", document_id, proposal_id).await?;
+    Ok(())
+}
+
+/// What blocks submitting the proposal under its ToR's intake form.
+pub async fn missing_for_submit(pool: &PgPool, tor_id: i64, proposal_id: i64) -> Result<Vec<String>, AppError> {
+    let intake = find_intake(pool, tor_id).await?;
+    if intake.is_standard() {
+        return Ok(vec![]);
+    }
+    let answers = find_section_answers(pool, proposal_id).await?;
+    let attachments = find_attachments(pool, proposal_id).await?;
+    let files = attachment::find_for_proposal(pool, proposal_id).await?;
+    Ok(intake.missing(&answers, attachments.len() + files.len()))
+}
@@ -1,5 +1,9 @@
 pub mod types;
 pub mod queries;
+pub mod intake;
+pub mod template;
 
 pub use types::*;
 pub use queries::*;
+pub use intake::*;
+pub use template::*;
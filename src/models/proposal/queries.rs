@@ -1,6 +1,6 @@
-use sqlx::PgPool;
+use sqlx::{PgPool, Postgres, Transaction};
 use crate::errors::AppError;
-use crate::models::{entity, relation};
+use crate::models::{domain_event, entity, relation, revision};
 use super::types::*;
 
 /// Count proposals with a given status (e.g. "draft", "submitted", "approved").
@@ -282,7 +282,7 @@ pub async fn create(
     submitted_date: &str,
     related_suggestion_id: Option<i64>,
 ) -> Result<i64, AppError> {
-    let name = name_from_title(title);
+    let name = entity::available_name(pool, "proposal", &name_from_title(title)).await?;
 
     let proposal_id = entity::create(pool, "proposal", &name, title).await?;
 
@@ -302,14 +302,22 @@ pub async fn create(
     Ok(proposal_id)
 }
 
-/// Update an existing proposal's title, description, and rationale.
+/// Update an existing proposal's title, description, and rationale, keeping the
+/// previous ones as a revision.
 pub async fn update(
     pool: &PgPool,
     proposal_id: i64,
     title: &str,
     description: &str,
     rationale: &str,
+    edited_by: i64,
 ) -> Result<(), AppError> {
+    revision::record(pool, proposal_id, &[
+        ("title", title),
+        ("description", description),
+        ("rationale", rationale),
+    ], edited_by).await?;
+
     sqlx::query(
         "UPDATE entities SET label = $1, updated_at = NOW() WHERE id = $2",
     )
@@ -334,16 +342,85 @@ pub async fn update_status(
     new_status: &str,
     rejection_reason: Option<&str>,
 ) -> Result<(), AppError> {
-    entity::set_property(pool, proposal_id, "status", new_status).await?;
+    let mut tx = pool.begin().await?;
+    write_status(&mut tx, proposal_id, new_status, rejection_reason).await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Move a proposal to a new status on behalf of `actor_id`, appending a
+/// `proposal.{status}` domain event in the same transaction.
+pub async fn transition(
+    pool: &PgPool,
+    proposal_id: i64,
+    new_status: &str,
+    rejection_reason: Option<&str>,
+    actor_id: i64,
+) -> Result<(), AppError> {
+    transition_with(pool, proposal_id, new_status, rejection_reason, None, actor_id).await
+}
+
+/// Reject a proposal under a reason from the rejection taxonomy. `category`
+/// is the reason's name, `reason_text` the readable reason shown for it.
+pub async fn reject(
+    pool: &PgPool,
+    proposal_id: i64,
+    category: &str,
+    reason_text: &str,
+    actor_id: i64,
+) -> Result<(), AppError> {
+    transition_with(pool, proposal_id, "rejected", Some(reason_text), Some(category), actor_id).await
+}
 
+async fn transition_with(
+    pool: &PgPool,
+    proposal_id: i64,
+    new_status: &str,
+    rejection_reason: Option<&str>,
+    rejection_category: Option<&str>,
+    actor_id: i64,
+) -> Result<(), AppError> {
+    let mut tx = pool.begin().await?;
+    write_status(&mut tx, proposal_id, new_status, rejection_reason).await?;
+    match rejection_category {
+        Some(category) => entity::set_property_in_tx(&mut tx, proposal_id, "rejection_category", category).await?,
+        None => {
+            sqlx::query("DELETE FROM entity_properties WHERE entity_id = $1 AND key = 'rejection_category'")
+                .bind(proposal_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+    }
+    let mut payload = serde_json::json!({ "status": new_status });
     if let Some(reason) = rejection_reason {
-        entity::set_property(pool, proposal_id, "rejection_reason", reason).await?;
+        payload["rejection_reason"] = reason.into();
+    }
+    if let Some(category) = rejection_category {
+        payload["rejection_category"] = category.into();
+    }
+    domain_event::append(&mut tx, &format!("proposal.{new_status}"), "proposal", proposal_id, actor_id, payload).await?;
+    crate::warnings::notifications::email::queue_proposal_status(pool, &mut tx, proposal_id, new_status, actor_id).await?;
+    tx.commit().await?;
+    crate::warnings::outbox::wake();
+    Ok(())
+}
+
+async fn write_status(
+    tx: &mut Transaction<'_, Postgres>,
+    proposal_id: i64,
+    new_status: &str,
+    rejection_reason: Option<&str>,
+) -> Result<(), sqlx::Error> {
+    entity::set_property_in_tx(tx, proposal_id, "status", new_status).await?;
+
+    if let Some(reason) = rejection_reason {
+        entity::set_property_in_tx(tx, proposal_id, "rejection_reason", reason).await?;
     } else if new_status != "rejected" {
         sqlx::query(
             "DELETE FROM entity_properties WHERE entity_id = $1 AND key = 'rejection_reason'",
         )
         .bind(proposal_id)
-        .execute(pool)
+        .execute(&mut **tx)
         .await?;
     }
 
@@ -483,3 +560,146 @@ pub async fn unqueue_proposal(
     entity::set_property(pool, proposal_id, "ready_for_agenda", "false").await?;
     Ok(())
 }
+
+/// Score at which an existing proposal is shown as a likely duplicate.
+pub const SIMILARITY_THRESHOLD: f64 = 0.35;
+
+/// Find proposals in the ToR whose title and description share terms with
+/// the given ones, best match first. Terms are Postgres full-text lexemes,
+/// so stop words are dropped and word forms are stemmed; the score weighs
+/// title overlap above description overlap.
+pub async fn find_similar(
+    pool: &PgPool,
+    tor_id: i64,
+    title: &str,
+    description: &str,
+) -> Result<Vec<SimilarProposal>, AppError> {
+    #[derive(sqlx::FromRow)]
+    struct Row {
+        id: i64,
+        title: String,
+        status: String,
+        submitted_date: String,
+        title_terms: Vec<String>,
+        description_terms: Vec<String>,
+    }
+
+    let (title_terms, description_terms): (Vec<String>, Vec<String>) = sqlx::query_as(
+        "SELECT tsvector_to_array(to_tsvector('english', $1)), \
+                tsvector_to_array(to_tsvector('english', $2))",
+    )
+    .bind(title)
+    .bind(description)
+    .fetch_one(pool)
+    .await?;
+
+    let rows = sqlx::query_as::<_, Row>(
+        "SELECT e.id, \
+                COALESCE(p_title.value, '') AS title, \
+                COALESCE(p_status.value, 'draft') AS status, \
+                COALESCE(p_date.value, '') AS submitted_date, \
+                tsvector_to_array(to_tsvector('english', COALESCE(p_title.value, ''))) AS title_terms, \
+                tsvector_to_array(to_tsvector('english', COALESCE(p_desc.value, ''))) AS description_terms \
+         FROM entities e \
+         JOIN relations r ON e.id = r.source_id \
+         JOIN entities rt ON r.relation_type_id = rt.id AND rt.name = 'submitted_to' \
+         LEFT JOIN entity_properties p_title \
+             ON e.id = p_title.entity_id AND p_title.key = 'title' \
+         LEFT JOIN entity_properties p_status \
+             ON e.id = p_status.entity_id AND p_status.key = 'status' \
+         LEFT JOIN entity_properties p_date \
+             ON e.id = p_date.entity_id AND p_date.key = 'submitted_date' \
+         LEFT JOIN entity_properties p_desc \
+             ON e.id = p_desc.entity_id AND p_desc.key = 'description' \
+         WHERE e.entity_type = 'proposal' AND r.target_id = $1",
+    )
+    .bind(tor_id)
+    .fetch_all(pool)
+    .await?;
+
+    let mut similar: Vec<SimilarProposal> = rows
+        .into_iter()
+        .filter_map(|row| {
+            let score = 0.6 * term_overlap(&title_terms, &row.title_terms)
+                + 0.4 * term_overlap(&description_terms, &row.description_terms);
+            (score >= SIMILARITY_THRESHOLD).then_some(SimilarProposal {
+                id: row.id,
+                title: row.title,
+                status: row.status,
+                submitted_date: row.submitted_date,
+                score,
+            })
+        })
+        .collect();
+    similar.sort_by(|a, b| b.score.total_cmp(&a.score));
+    similar.truncate(5);
+    Ok(similar)
+}
+
+/// Dice coefficient of two lexeme sets (each already deduplicated).
+pub fn term_overlap(a: &[String], b: &[String]) -> f64 {
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    let shared = a.iter().filter(|t| b.contains(t)).count();
+    2.0 * shared as f64 / (a.len() + b.len()) as f64
+}
+
+/// Whether the proposal was submitted to the ToR.
+pub async fn belongs_to_tor(pool: &PgPool, proposal_id: i64, tor_id: i64) -> Result<bool, AppError> {
+    let exists: bool = sqlx::query_scalar(
+        "SELECT EXISTS (SELECT 1 FROM relations r \
+                        JOIN entities rt ON r.relation_type_id = rt.id AND rt.name = 'submitted_to' \
+                        WHERE r.source_id = $1 AND r.target_id = $2)",
+    )
+    .bind(proposal_id)
+    .bind(tor_id)
+    .fetch_one(pool)
+    .await?;
+    Ok(exists)
+}
+
+/// The proposal an agenda point was scheduled from, if any.
+pub async fn find_for_agenda_point(pool: &PgPool, agenda_point_id: i64) -> Result<Option<ProposalDetail>, AppError> {
+    let proposal_id: Option<i64> = sqlx::query_scalar(
+        "SELECT r.source_id FROM relations r \
+         JOIN entities rt ON r.relation_type_id = rt.id AND rt.name = 'spawns_agenda_point' \
+         WHERE r.target_id = $1 \
+         ORDER BY r.id LIMIT 1",
+    )
+    .bind(agenda_point_id)
+    .fetch_optional(pool)
+    .await?;
+    match proposal_id {
+        Some(id) => find_by_id(pool, id).await,
+        None => Ok(None),
+    }
+}
+
+/// Record that a new proposal was created knowing it resembles an existing one.
+pub async fn link_related(pool: &PgPool, proposal_id: i64, related_id: i64) -> Result<(), AppError> {
+    relation::create(pool, "related_to_proposal", proposal_id, related_id).await?;
+    Ok(())
+}
+
+/// Proposals linked to this one in either direction, oldest first.
+pub async fn find_related(pool: &PgPool, proposal_id: i64) -> Result<Vec<RelatedProposal>, AppError> {
+    let related = sqlx::query_as::<_, RelatedProposal>(
+        "SELECT e.id, \
+                COALESCE(p_title.value, e.label) AS title, \
+                COALESCE(p_status.value, 'draft') AS status \
+         FROM relations r \
+         JOIN entities rt ON r.relation_type_id = rt.id AND rt.name = 'related_to_proposal' \
+         JOIN entities e ON e.id = CASE WHEN r.source_id = $1 THEN r.target_id ELSE r.source_id END \
+         LEFT JOIN entity_properties p_title \
+             ON e.id = p_title.entity_id AND p_title.key = 'title' \
+         LEFT JOIN entity_properties p_status \
+             ON e.id = p_status.entity_id AND p_status.key = 'status' \
+         WHERE r.source_id = $1 OR r.target_id = $1 \
+         ORDER BY e.id",
+    )
+    .bind(proposal_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(related)
+}
@@ -0,0 +1,152 @@
+//! Per-ToR proposal templates.
+//!
+//! A ToR can keep templates for proposals it sees again and again (budget
+//! reallocations, membership changes, ...). Picking one on the new proposal
+//! form pre-fills the title from a pattern, the description, rationale and
+//! intake section answers. A template's default courses of action are copied
+//! onto the proposal as `default_coas`; when the proposal is scheduled, its
+//! agenda point becomes a decision item with those COAs. Templates are
+//! stored on the ToR entity as `proposal_templates` (JSON), like the intake
+//! form.
+
+use std::collections::BTreeMap;
+
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+
+use crate::errors::AppError;
+use crate::models::fiscal::FiscalCalendar;
+use crate::models::{coa, entity, relation};
+
+/// Placeholders a title pattern may use, as (placeholder, meaning).
+pub const TITLE_PLACEHOLDERS: &[(&str, &str)] = &[
+    ("{date}", "today's date"),
+    ("{year}", "the current year"),
+    ("{month}", "the current month"),
+    ("{quarter}", "the current fiscal quarter, e.g. Q3"),
+    ("{fiscal_year}", "the year the current fiscal year ends in"),
+    ("{tor}", "the ToR's name"),
+];
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProposalTemplate {
+    pub key: String,
+    pub name: String,
+    #[serde(default)]
+    pub title_pattern: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub rationale: String,
+    /// Pre-filled intake section answers, keyed by section key.
+    #[serde(default)]
+    pub sections: BTreeMap<String, String>,
+    /// Titles of the courses of action the agenda point starts with.
+    #[serde(default)]
+    pub default_coas: Vec<String>,
+}
+
+impl ProposalTemplate {
+    pub fn section(&self, key: &str) -> &str {
+        self.sections.get(key).map(|v| v.as_str()).unwrap_or("")
+    }
+
+    /// Default COAs one per line, as edited in the settings form.
+    pub fn coa_lines(&self) -> String {
+        self.default_coas.join("\n")
+    }
+}
+
+/// Fill in a title pattern's placeholders; quarters follow `fiscal`.
+pub fn expand_title(pattern: &str, tor_label: &str, today: NaiveDate, fiscal: &FiscalCalendar) -> String {
+    pattern
+        .replace("{date}", &today.format("%Y-%m-%d").to_string())
+        .replace("{year}", &today.year().to_string())
+        .replace("{month}", &today.format("%B").to_string())
+        .replace("{quarter}", &format!("Q{}", fiscal.quarter(today)))
+        .replace("{fiscal_year}", &fiscal.fiscal_year(today).to_string())
+        .replace("{tor}", tor_label)
+}
+
+/// Split the default COAs field into titles, one per non-blank line.
+pub fn parse_coa_lines(text: &str) -> Vec<String> {
+    text.lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// Problems with a template about to be saved among `existing` ones.
+pub fn validate_template(template: &ProposalTemplate, existing: &[ProposalTemplate], editing: bool) -> Vec<String> {
+    let mut errors = Vec::new();
+    if template.name.trim().is_empty() {
+        errors.push("Template name is required".to_string());
+    } else if template.key.is_empty() {
+        errors.push("Template name needs letters or digits".to_string());
+    } else if !editing && existing.iter().any(|t| t.key == template.key) {
+        errors.push(format!("A template named '{}' already exists", template.name.trim()));
+    }
+    if template.title_pattern.chars().count() > 200 {
+        errors.push("Title pattern must be at most 200 characters".to_string());
+    }
+    if let Some(title) = template.default_coas.iter().find(|t| t.chars().count() > 200) {
+        errors.push(format!("Course of action '{}…' is longer than 200 characters", title.chars().take(40).collect::<String>()));
+    }
+    if template.default_coas.iter().enumerate().any(|(i, t)| template.default_coas[..i].contains(t)) {
+        errors.push("Each default course of action needs a different title".to_string());
+    }
+    errors
+}
+
+/// A ToR's proposal templates, in the order they were added.
+pub async fn find_templates(pool: &PgPool, tor_id: i64) -> Result<Vec<ProposalTemplate>, AppError> {
+    Ok(entity::get_property(pool, tor_id, "proposal_templates").await?
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default())
+}
+
+pub async fn find_template(pool: &PgPool, tor_id: i64, key: &str) -> Result<Option<ProposalTemplate>, AppError> {
+    Ok(find_templates(pool, tor_id).await?.into_iter().find(|t| t.key == key))
+}
+
+/// Store a ToR's proposal templates; an empty list removes the property.
+pub async fn save_templates(pool: &PgPool, tor_id: i64, templates: &[ProposalTemplate]) -> Result<(), AppError> {
+    if templates.is_empty() {
+        entity::delete_property(pool, tor_id, "proposal_templates").await?;
+    } else {
+        let json = serde_json::to_string(templates).unwrap_or_else(|_| "[]".to_string());
+        entity::set_property(pool, tor_id, "proposal_templates", &json).await?;
+    }
+    Ok(())
+}
+
+/// Record on a new proposal which template it came from and the COAs its
+/// agenda point should start with.
+pub async fn apply_to_proposal(pool: &PgPool, proposal_id: i64, template: &ProposalTemplate) -> Result<(), AppError> {
+    entity::set_property(pool, proposal_id, "template_key", &template.key).await?;
+    if !template.default_coas.is_empty() {
+        let json = serde_json::to_string(&template.default_coas).unwrap_or_else(|_| "[]".to_string());
+        entity::set_property(pool, proposal_id, "default_coas", &json).await?;
+    }
+    Ok(())
+}
+
+/// Default COA titles recorded on a proposal.
+pub async fn find_default_coas(pool: &PgPool, proposal_id: i64) -> Result<Vec<String>, AppError> {
+    Ok(entity::get_property(pool, proposal_id, "default_coas").await?
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default())
+}
+
+/// Create the default COAs for an agenda point and link them to it.
+pub async fn create_default_coas(pool: &PgPool, agenda_point_id: i64, titles: &[String], user_id: i64) -> Result<Vec<i64>, AppError> {
+    let mut ids = Vec::new();
+    for title in titles {
+        let coa_id = coa::create(pool, title, "", "simple", user_id).await?;
+        relation::create(pool, "considers_coa", agenda_point_id, coa_id).await?;
+        ids.push(coa_id);
+    }
+    Ok(ids)
+}
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 
 /// Proposal as shown in the workflow list view.
@@ -43,6 +45,31 @@ pub struct ProposalDetail {
     pub related_suggestion_id: Option<i64>,
 }
 
+/// Existing proposal in the same ToR that looks like a new one.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimilarProposal {
+    pub id: i64,
+    pub title: String,
+    pub status: String,
+    pub submitted_date: String,
+    /// 0.0 (nothing shared) to 1.0 (same terms).
+    pub score: f64,
+}
+
+impl SimilarProposal {
+    pub fn score_percent(&self) -> i64 {
+        (self.score * 100.0).round() as i64
+    }
+}
+
+/// Proposal linked to another through `related_to_proposal`.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct RelatedProposal {
+    pub id: i64,
+    pub title: String,
+    pub status: String,
+}
+
 /// Form input for creating/editing a proposal.
 #[derive(Debug, Clone, Deserialize)]
 pub struct ProposalForm {
@@ -51,5 +78,21 @@ pub struct ProposalForm {
     pub rationale: String,
     #[allow(dead_code)]
     pub related_suggestion_id: Option<String>,
+    /// Create even though similar proposals were found.
+    pub override_duplicates: Option<String>,
+    /// Create and link to this similar proposal.
+    pub related_proposal_id: Option<String>,
     pub csrf_token: String,
+    /// Answers to the ToR's intake sections, as `section_<key>` fields.
+    #[serde(flatten)]
+    pub fields: HashMap<String, String>,
+}
+
+impl ProposalForm {
+    /// Intake section answers keyed by section key.
+    pub fn section_answers(&self) -> HashMap<String, String> {
+        self.fields.iter()
+            .filter_map(|(k, v)| k.strip_prefix("section_").map(|key| (key.to_string(), v.clone())))
+            .collect()
+    }
 }
@@ -0,0 +1,374 @@
+//! Usage quotas: open proposals per user, stored content per ToR, and API
+//! requests per user per day.
+//!
+//! Limits are `quota.*` settings where 0 means unlimited. The ToR storage
+//! limit can be overridden per ToR like any `tor_overridable` setting. Roles
+//! may override the per-user limits with a property named after the setting;
+//! a user whose roles override a limit gets the most generous of them.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+
+use super::setting;
+
+pub const OPEN_PROPOSALS: &str = "quota.max_open_proposals";
+pub const TOR_STORAGE_MB: &str = "quota.tor_storage_mb";
+pub const API_REQUESTS_PER_DAY: &str = "quota.api_requests_per_day";
+
+/// Per-user quotas that roles can override, as (setting, label).
+pub const USER_QUOTAS: &[(&str, &str)] = &[
+    (OPEN_PROPOSALS, "Open proposals"),
+    (API_REQUESTS_PER_DAY, "API requests per day"),
+];
+
+/// Proposal statuses that no longer count as open.
+const CLOSED_STATUSES: &[&str] = &["approved", "rejected"];
+
+const BYTES_PER_MB: i64 = 1024 * 1024;
+
+/// The most generous of several limits: unlimited (0) beats any number.
+/// `None` when there are none.
+pub fn most_generous(limits: &[i64]) -> Option<i64> {
+    if limits.contains(&0) {
+        return Some(0);
+    }
+    limits.iter().copied().max()
+}
+
+/// A size in bytes as megabytes with one decimal, e.g. `2.5 MB`.
+pub fn format_mb(bytes: i64) -> String {
+    format!("{:.1} MB", bytes as f64 / BYTES_PER_MB as f64)
+}
+
+/// Usage against a limit, as shown in the report.
+#[derive(Debug, Clone)]
+pub struct Usage {
+    pub used: i64,
+    /// 0 = unlimited.
+    pub limit: i64,
+}
+
+impl Usage {
+    pub fn is_unlimited(&self) -> bool {
+        self.limit == 0
+    }
+
+    pub fn is_exceeded(&self) -> bool {
+        self.limit > 0 && self.used >= self.limit
+    }
+
+    /// Share of the limit used, in whole percent (0 when unlimited).
+    pub fn pct(&self) -> i64 {
+        if self.limit == 0 {
+            return 0;
+        }
+        (self.used * 100 / self.limit).min(100)
+    }
+}
+
+/// A role with its per-user quota overrides (empty = no override), in
+/// `USER_QUOTAS` order.
+#[derive(Debug, Clone)]
+pub struct RoleQuota {
+    pub id: i64,
+    pub label: String,
+    pub overrides: Vec<String>,
+}
+
+/// A user's usage of the per-user quotas.
+#[derive(Debug, Clone)]
+pub struct UserQuotaUsage {
+    pub id: i64,
+    pub username: String,
+    pub display_name: String,
+    pub open_proposals: Usage,
+    pub api_requests_today: Usage,
+}
+
+/// A ToR's stored content against its storage limit, in bytes.
+#[derive(Debug, Clone)]
+pub struct TorQuotaUsage {
+    pub id: i64,
+    pub label: String,
+    pub storage: Usage,
+}
+
+impl TorQuotaUsage {
+    pub fn used_display(&self) -> String {
+        format_mb(self.storage.used)
+    }
+
+    pub fn limit_display(&self) -> String {
+        format_mb(self.storage.limit)
+    }
+}
+
+/// Role overrides of per-user limits, as (user_id, setting, limit).
+async fn role_overrides(pool: &PgPool, user_id: Option<i64>) -> Result<Vec<(i64, String, i64)>, sqlx::Error> {
+    let names: Vec<&str> = USER_QUOTAS.iter().map(|(name, _)| *name).collect();
+    let rows = sqlx::query_as::<_, (i64, String, String)>(
+        "SELECT r.source_id, p.key, p.value \
+         FROM relations r \
+         JOIN entities rt ON rt.id = r.relation_type_id AND rt.name = 'has_role' \
+         JOIN entity_properties p ON p.entity_id = r.target_id AND p.key = ANY($1) \
+         WHERE ($2::BIGINT IS NULL OR r.source_id = $2)"
+    )
+    .bind(&names)
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows.into_iter()
+        .filter_map(|(uid, key, value)| value.trim().parse().ok().map(|limit| (uid, key, limit)))
+        .collect())
+}
+
+/// A user's limit for a per-user quota: the most generous override among
+/// their roles, else the global setting. 0 = unlimited.
+pub async fn user_limit(pool: &PgPool, user_id: i64, name: &str) -> Result<i64, sqlx::Error> {
+    let limits: Vec<i64> = role_overrides(pool, Some(user_id)).await?
+        .into_iter()
+        .filter(|(_, key, _)| key == name)
+        .map(|(_, _, limit)| limit)
+        .collect();
+    match most_generous(&limits) {
+        Some(limit) => Ok(limit),
+        None => Ok(setting::get_i64(pool, name).await),
+    }
+}
+
+/// A ToR's storage limit in bytes, after its override. 0 = unlimited.
+pub async fn tor_storage_limit(pool: &PgPool, tor_id: i64) -> i64 {
+    let default = setting::get_i64(pool, TOR_STORAGE_MB).await;
+    let mb = setting::get_value_for_tor(pool, tor_id, TOR_STORAGE_MB, &default.to_string()).await
+        .trim()
+        .parse()
+        .unwrap_or(default);
+    mb * BYTES_PER_MB
+}
+
+/// Per-user quota overrides of every role.
+pub async fn find_role_quotas(pool: &PgPool) -> Result<Vec<RoleQuota>, sqlx::Error> {
+    let roles = super::role::find_all_display(pool).await?;
+    let names: Vec<&str> = USER_QUOTAS.iter().map(|(name, _)| *name).collect();
+    let rows = sqlx::query_as::<_, (i64, String, String)>(
+        "SELECT p.entity_id, p.key, p.value FROM entity_properties p \
+         JOIN entities e ON e.id = p.entity_id AND e.entity_type = 'role' \
+         WHERE p.key = ANY($1)"
+    )
+    .bind(&names)
+    .fetch_all(pool)
+    .await?;
+    Ok(roles.into_iter().map(|role| {
+        let overrides = USER_QUOTAS.iter().map(|(name, _)| {
+            rows.iter()
+                .find(|(id, key, _)| *id == role.id && key == name)
+                .map(|(_, _, value)| value.clone())
+                .unwrap_or_default()
+        }).collect();
+        RoleQuota { id: role.id, label: role.label, overrides }
+    }).collect())
+}
+
+/// Set a role's override of a per-user quota, or clear it with `None`.
+pub async fn set_role_limit(pool: &PgPool, role_id: i64, name: &str, limit: Option<i64>) -> Result<(), sqlx::Error> {
+    match limit {
+        Some(limit) => {
+            sqlx::query(
+                "INSERT INTO entity_properties (entity_id, key, value) VALUES ($1, $2, $3) \
+                 ON CONFLICT(entity_id, key) DO UPDATE SET value = excluded.value"
+            )
+            .bind(role_id)
+            .bind(name)
+            .bind(limit.to_string())
+            .execute(pool)
+            .await?;
+        }
+        None => {
+            sqlx::query("DELETE FROM entity_properties WHERE entity_id = $1 AND key = $2")
+                .bind(role_id)
+                .bind(name)
+                .execute(pool)
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+/// Open (not approved or rejected) proposals per author.
+async fn open_proposal_counts(pool: &PgPool, user_id: Option<i64>) -> Result<HashMap<i64, i64>, sqlx::Error> {
+    let rows = sqlx::query_as::<_, (String, i64)>(
+        "SELECT p_by.value, COUNT(*) \
+         FROM entities e \
+         JOIN entity_properties p_by ON e.id = p_by.entity_id AND p_by.key = 'submitted_by_id' \
+         LEFT JOIN entity_properties p_status ON e.id = p_status.entity_id AND p_status.key = 'status' \
+         WHERE e.entity_type = 'proposal' \
+           AND COALESCE(p_status.value, 'draft') <> ALL($1) \
+           AND ($2::BIGINT IS NULL OR p_by.value = ($2::BIGINT)::TEXT) \
+         GROUP BY p_by.value"
+    )
+    .bind(CLOSED_STATUSES)
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows.into_iter().filter_map(|(id, n)| id.parse().ok().map(|id| (id, n))).collect())
+}
+
+/// Proposals the user authored that are not yet approved or rejected.
+pub async fn open_proposals(pool: &PgPool, user_id: i64) -> Result<i64, sqlx::Error> {
+    Ok(open_proposal_counts(pool, Some(user_id)).await?.get(&user_id).copied().unwrap_or(0))
+}
+
+/// API requests per caller on the UTC day of `at`.
+async fn api_request_counts(pool: &PgPool, caller: Option<&str>, at: DateTime<Utc>) -> Result<HashMap<String, i64>, sqlx::Error> {
+    let rows = sqlx::query_as::<_, (String, i64)>(
+        "SELECT p_caller.value, SUM(p_req.value::BIGINT)::BIGINT \
+         FROM entities e \
+         JOIN entity_properties p_hour ON e.id = p_hour.entity_id AND p_hour.key = 'hour' \
+         JOIN entity_properties p_caller ON e.id = p_caller.entity_id AND p_caller.key = 'caller' \
+         JOIN entity_properties p_req ON e.id = p_req.entity_id AND p_req.key = 'request_count' \
+         WHERE e.entity_type = 'api_usage' AND p_hour.value LIKE $1 || '%' \
+           AND ($2::TEXT IS NULL OR p_caller.value = $2) \
+         GROUP BY p_caller.value"
+    )
+    .bind(at.format("%Y-%m-%d").to_string())
+    .bind(caller)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows.into_iter().collect())
+}
+
+/// API requests made by a caller (username) on the UTC day of `at`.
+pub async fn api_requests_on(pool: &PgPool, username: &str, at: DateTime<Utc>) -> Result<i64, sqlx::Error> {
+    Ok(api_request_counts(pool, Some(username), at).await?.get(username).copied().unwrap_or(0))
+}
+
+/// Bytes of content stored in a ToR's documents and in files uploaded to
+/// its proposals.
+pub async fn tor_storage_bytes(pool: &PgPool, tor_id: i64) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar::<_, i64>(
+        "SELECT (SELECT COALESCE(SUM(OCTET_LENGTH(p.value)), 0) \
+                 FROM relations r \
+                 JOIN entities rt ON rt.id = r.relation_type_id AND rt.name = 'scoped_to_tor' \
+                 JOIN entities d ON d.id = r.source_id AND d.entity_type = 'document' \
+                 JOIN entity_properties p ON p.entity_id = d.id AND p.key = 'body' \
+                 WHERE r.target_id = $1) \
+              + (SELECT COALESCE(SUM(p.value::BIGINT), 0) \
+                 FROM relations rs \
+                 JOIN entities rst ON rst.id = rs.relation_type_id AND rst.name = 'submitted_to' \
+                 JOIN relations ra ON ra.target_id = rs.source_id \
+                 JOIN entities rat ON rat.id = ra.relation_type_id AND rat.name = 'attached_to_proposal' \
+                 JOIN entities a ON a.id = ra.source_id AND a.entity_type = 'document_attachment' \
+                 JOIN entity_properties p ON p.entity_id = a.id AND p.key = 'size_bytes' \
+                 WHERE rs.target_id = $1)::BIGINT"
+    )
+    .bind(tor_id)
+    .fetch_one(pool)
+    .await
+}
+
+/// Why the user may not open another proposal, if they are at their limit.
+pub async fn check_open_proposals(pool: &PgPool, user_id: i64) -> Result<Option<String>, sqlx::Error> {
+    let limit = user_limit(pool, user_id, OPEN_PROPOSALS).await?;
+    if limit == 0 {
+        return Ok(None);
+    }
+    let open = open_proposals(pool, user_id).await?;
+    Ok((open >= limit).then(|| format!(
+        "You have {} open proposals, the most allowed is {}. \
+         Wait until some are approved or rejected before creating another.",
+        open, limit
+    )))
+}
+
+/// Why a ToR cannot take `new_bytes` more content (after `freed_bytes` are
+/// released, e.g. by replacing a document body), if it would exceed its
+/// storage limit.
+pub async fn check_tor_storage(pool: &PgPool, tor_id: i64, new_bytes: i64, freed_bytes: i64) -> Result<Option<String>, sqlx::Error> {
+    let limit = tor_storage_limit(pool, tor_id).await;
+    if limit == 0 {
+        return Ok(None);
+    }
+    let used = tor_storage_bytes(pool, tor_id).await?;
+    let total = used - freed_bytes + new_bytes;
+    Ok((new_bytes > freed_bytes && total > limit).then(|| format!(
+        "This ToR's storage quota is {}; {} is in use and this change would bring it to {}.",
+        format_mb(limit), format_mb(used), format_mb(total)
+    )))
+}
+
+/// Why the user may not make another API request today, if they are at
+/// their daily limit.
+pub async fn check_api_requests(pool: &PgPool, user_id: i64, username: &str, at: DateTime<Utc>) -> Result<Option<String>, sqlx::Error> {
+    let limit = user_limit(pool, user_id, API_REQUESTS_PER_DAY).await?;
+    if limit == 0 {
+        return Ok(None);
+    }
+    let made = api_requests_on(pool, username, at).await?;
+    Ok((made >= limit).then(|| format!(
+        "Daily API quota of {} requests reached; it resets at midnight UTC.",
+        limit
+    )))
+}
+
+/// Per-user quota usage of every active user, most used first.
+pub async fn user_usage(pool: &PgPool, at: DateTime<Utc>) -> Result<Vec<UserQuotaUsage>, sqlx::Error> {
+    let users = sqlx::query_as::<_, (i64, String, String)>(
+        "SELECT id, name, label FROM entities \
+         WHERE entity_type = 'user' AND is_active = true \
+         ORDER BY label, id"
+    )
+    .fetch_all(pool)
+    .await?;
+    let overrides = role_overrides(pool, None).await?;
+    let global_proposals = setting::get_i64(pool, OPEN_PROPOSALS).await;
+    let global_requests = setting::get_i64(pool, API_REQUESTS_PER_DAY).await;
+    let proposals = open_proposal_counts(pool, None).await?;
+    let requests = api_request_counts(pool, None, at).await?;
+
+    let limit_for = |user_id: i64, name: &str, global: i64| {
+        let limits: Vec<i64> = overrides.iter()
+            .filter(|(uid, key, _)| *uid == user_id && key == name)
+            .map(|(_, _, limit)| *limit)
+            .collect();
+        most_generous(&limits).unwrap_or(global)
+    };
+
+    let mut usage: Vec<UserQuotaUsage> = users.into_iter().map(|(id, username, display_name)| {
+        UserQuotaUsage {
+            open_proposals: Usage {
+                used: proposals.get(&id).copied().unwrap_or(0),
+                limit: limit_for(id, OPEN_PROPOSALS, global_proposals),
+            },
+            api_requests_today: Usage {
+                used: requests.get(&username).copied().unwrap_or(0),
+                limit: limit_for(id, API_REQUESTS_PER_DAY, global_requests),
+            },
+            id,
+            username,
+            display_name,
+        }
+    }).collect();
+    usage.sort_by_key(|u| std::cmp::Reverse(u.open_proposals.pct().max(u.api_requests_today.pct())));
+    Ok(usage)
+}
+
+/// Storage usage of every active ToR, most used first.
+pub async fn tor_usage(pool: &PgPool) -> Result<Vec<TorQuotaUsage>, sqlx::Error> {
+    let tors = sqlx::query_as::<_, (i64, String)>(
+        "SELECT id, label FROM entities WHERE entity_type = 'tor' AND is_active = true ORDER BY label, id"
+    )
+    .fetch_all(pool)
+    .await?;
+    let mut usage = Vec::with_capacity(tors.len());
+    for (id, label) in tors {
+        let storage = Usage {
+            used: tor_storage_bytes(pool, id).await?,
+            limit: tor_storage_limit(pool, id).await,
+        };
+        usage.push(TorQuotaUsage { id, label, storage });
+    }
+    usage.sort_by_key(|t| std::cmp::Reverse(t.storage.pct()));
+    Ok(usage)
+}
@@ -0,0 +1,236 @@
+//! Bulk reassignment of a user's pending work to another user.
+//!
+//! Used when someone leaves or changes role. What moves: review cover
+//! (out-of-office windows and position delegations naming the user as
+//! delegate, unless already ended), unfinished minutes (minutes not yet
+//! approved for meetings the user is secretary of), open action items and
+//! the positions the user fills. [`plan`] lists what would move, with
+//! the reason for anything that cannot; [`apply`] recomputes the plan and
+//! moves everything not skipped.
+
+use chrono::NaiveDate;
+use sqlx::PgPool;
+
+use super::{action_item, delegation, entity, tor};
+
+/// Kinds of work, as (key, heading), in the order they are listed.
+pub const KINDS: &[(&str, &str)] = &[
+    ("cover", "Out-of-office cover"),
+    ("delegation", "Delegated positions"),
+    ("minutes", "Unfinished minutes"),
+    ("action_item", "Open action items"),
+    ("position", "Positions"),
+];
+
+/// One piece of work that moves to the new user.
+#[derive(Debug, Clone)]
+pub struct ReassignItem {
+    /// A key from [`KINDS`].
+    pub kind: &'static str,
+    /// What changes: the principal for out-of-office cover, the
+    /// `delegates_to` relation, the meeting, the action item or the position.
+    pub id: i64,
+    pub label: String,
+    /// ToR, dates or other context for the preview.
+    pub context: String,
+    pub path: String,
+    /// Why the item stays with the current user; empty when it moves.
+    pub skipped: String,
+}
+
+impl ReassignItem {
+    pub fn moves(&self) -> bool {
+        self.skipped.is_empty()
+    }
+}
+
+/// Check the two users before planning.
+pub async fn validate(pool: &PgPool, from_user_id: i64, to_user_id: i64) -> Result<Vec<String>, sqlx::Error> {
+    let mut errors = Vec::new();
+    let to_user = entity::find_by_id(pool, to_user_id).await?
+        .filter(|u| u.entity_type == "user" && u.is_active);
+    if to_user.is_none() {
+        errors.push("Choose the user to receive the work".to_string());
+    } else if to_user_id == from_user_id {
+        errors.push("Choose a different user to receive the work".to_string());
+    }
+    Ok(errors)
+}
+
+/// Meetings with unapproved minutes whose secretary is the user, by id or
+/// username, as (meeting id, secretary value, minutes id, minutes label, ToR label).
+async fn find_secretary_minutes(
+    pool: &PgPool,
+    user_id: i64,
+    username: &str,
+) -> Result<Vec<(i64, String, i64, String, String)>, sqlx::Error> {
+    sqlx::query_as(
+        "SELECT m.id, p_sec.value, mi.id, mi.label, COALESCE(t.label, '') \
+         FROM entities mi \
+         JOIN relations r_min ON r_min.target_id = mi.id \
+             AND r_min.relation_type_id = (SELECT id FROM entities WHERE entity_type = 'relation_type' AND name = 'minutes_of') \
+         JOIN entities m ON m.id = r_min.source_id AND m.entity_type = 'meeting' \
+         JOIN entity_properties p_sec ON m.id = p_sec.entity_id AND p_sec.key = 'secretary_user_id' \
+         LEFT JOIN entity_properties p_status ON mi.id = p_status.entity_id AND p_status.key = 'status' \
+         LEFT JOIN relations r_tor ON m.id = r_tor.source_id \
+             AND r_tor.relation_type_id = (SELECT id FROM entities WHERE entity_type = 'relation_type' AND name = 'belongs_to_tor') \
+         LEFT JOIN entities t ON t.id = r_tor.target_id \
+         WHERE mi.entity_type = 'minutes' \
+           AND COALESCE(p_status.value, 'draft') != 'approved' \
+           AND (p_sec.value = $1 OR p_sec.value = $2) \
+         ORDER BY mi.id",
+    )
+    .bind(user_id.to_string())
+    .bind(username)
+    .fetch_all(pool)
+    .await
+}
+
+/// Positions the user fills, as (position id, position label, membership
+/// type, ToR id, ToR label).
+async fn find_positions(pool: &PgPool, user_id: i64) -> Result<Vec<(i64, String, String, i64, String)>, sqlx::Error> {
+    sqlx::query_as(
+        "SELECT f.id, f.label, COALESCE(p_type.value, 'optional'), t.id, t.label \
+         FROM entities f \
+         JOIN relations r_fills ON r_fills.target_id = f.id AND r_fills.source_id = $1 \
+             AND r_fills.relation_type_id = (SELECT id FROM entities WHERE entity_type = 'relation_type' AND name = 'fills_position') \
+         JOIN relations r_tor ON r_tor.source_id = f.id \
+             AND r_tor.relation_type_id = (SELECT id FROM entities WHERE entity_type = 'relation_type' AND name = 'belongs_to_tor') \
+         JOIN entities t ON t.id = r_tor.target_id AND t.entity_type = 'tor' \
+         LEFT JOIN entity_properties p_type ON f.id = p_type.entity_id AND p_type.key = 'membership_type' \
+         WHERE f.entity_type = 'tor_function' \
+         ORDER BY t.label, f.label",
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// Everything that would move from one user to the other on `today`.
+pub async fn plan(
+    pool: &PgPool,
+    from_user_id: i64,
+    to_user_id: i64,
+    today: NaiveDate,
+) -> Result<Vec<ReassignItem>, sqlx::Error> {
+    let day = today.format("%Y-%m-%d").to_string();
+    let mut items = Vec::new();
+
+    for ooo in delegation::find_covered_by(pool, from_user_id).await? {
+        if ooo.end_date < day {
+            continue;
+        }
+        items.push(ReassignItem {
+            kind: "cover",
+            id: ooo.user_id,
+            label: format!("Out-of-office cover for {}", ooo.user_name),
+            context: format!("{} to {}", ooo.start_date, ooo.end_date),
+            path: format!("/users/{}/edit", ooo.user_id),
+            skipped: if ooo.user_id == to_user_id { "Would cover for themselves".to_string() } else { String::new() },
+        });
+    }
+    for d in tor::find_delegations_to(pool, from_user_id).await? {
+        if d.end_date < day {
+            continue;
+        }
+        items.push(ReassignItem {
+            kind: "delegation",
+            id: d.id,
+            label: format!("{} for {}", d.position_label, d.delegator_name),
+            context: format!("{} to {}", d.start_date, d.end_date),
+            path: format!("/tor/{}", d.tor_id),
+            skipped: if d.delegator_id == to_user_id { "Holds the delegated position".to_string() } else { String::new() },
+        });
+    }
+
+    let username = entity::find_by_id(pool, from_user_id).await?.map(|u| u.name).unwrap_or_default();
+    for (meeting_id, _, minutes_id, label, tor_label) in find_secretary_minutes(pool, from_user_id, &username).await? {
+        items.push(ReassignItem {
+            kind: "minutes",
+            id: meeting_id,
+            label,
+            context: tor_label,
+            path: format!("/minutes/{}", minutes_id),
+            skipped: String::new(),
+        });
+    }
+
+    for a in action_item::find_for_owner(pool, from_user_id).await? {
+        if !a.is_open() {
+            continue;
+        }
+        items.push(ReassignItem {
+            kind: "action_item",
+            id: a.id,
+            label: a.title,
+            context: if a.due_date.is_empty() { a.tor_label } else { format!("{}, due {}", a.tor_label, a.due_date) },
+            path: format!("/tor/{}/actions", a.tor_id),
+            skipped: String::new(),
+        });
+    }
+
+    let held: Vec<i64> = find_positions(pool, to_user_id).await?.into_iter().map(|p| p.0).collect();
+    for (position_id, label, _, tor_id, tor_label) in find_positions(pool, from_user_id).await? {
+        items.push(ReassignItem {
+            kind: "position",
+            id: position_id,
+            label,
+            context: tor_label,
+            path: format!("/tor/{}", tor_id),
+            skipped: if held.contains(&position_id) { "Already holds it".to_string() } else { String::new() },
+        });
+    }
+    Ok(items)
+}
+
+/// Move everything in the plan that is not skipped. Returns the plan.
+pub async fn apply(
+    pool: &PgPool,
+    from_user_id: i64,
+    to_user_id: i64,
+    today: NaiveDate,
+) -> Result<Vec<ReassignItem>, sqlx::Error> {
+    let items = plan(pool, from_user_id, to_user_id, today).await?;
+    let to = to_user_id.to_string();
+    let (from_name, to_name) = (
+        entity::find_by_id(pool, from_user_id).await?.map(|u| u.name).unwrap_or_default(),
+        entity::find_by_id(pool, to_user_id).await?.map(|u| u.name).unwrap_or_default(),
+    );
+    let secretaries = find_secretary_minutes(pool, from_user_id, &from_name).await?;
+    let positions = find_positions(pool, from_user_id).await?;
+    let mut tor_ids = Vec::new();
+
+    for item in items.iter().filter(|i| i.moves()) {
+        match item.kind {
+            "cover" => {
+                entity::set_property(pool, item.id, "ooo_delegate_id", &to).await?;
+            }
+            "delegation" => {
+                let Some(d) = tor::find_delegation(pool, item.id).await? else { continue };
+                tor::delegate_position(pool, d.position_id, d.delegator_id, to_user_id, &d.start_date, &d.end_date, &d.note).await?;
+                tor::revoke_delegation(pool, d.id).await?;
+            }
+            "minutes" => {
+                // Keep the form the meeting stores its secretary in
+                let by_id = secretaries.iter().any(|s| s.0 == item.id && s.1 == from_user_id.to_string());
+                let value = if by_id { to.as_str() } else { to_name.as_str() };
+                entity::set_property(pool, item.id, "secretary_user_id", value).await?;
+            }
+            "action_item" => {
+                entity::set_property(pool, item.id, "owner_id", &to).await?;
+            }
+            _ => {
+                let Some((_, _, membership_type, tor_id, _)) = positions.iter().find(|p| p.0 == item.id) else { continue };
+                tor::remove_from_position(pool, from_user_id, item.id).await?;
+                tor::assign_to_position(pool, to_user_id, item.id, membership_type).await?;
+                if !tor_ids.contains(tor_id) {
+                    tor_ids.push(*tor_id);
+                }
+            }
+        }
+    }
+    for tor_id in tor_ids {
+        tor::apply_capabilities(pool, tor_id).await?;
+    }
+    Ok(items)
+}
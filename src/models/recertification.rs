@@ -0,0 +1,308 @@
+//! Access recertification campaigns.
+//!
+//! An administrator launches a campaign with a deadline. Every filled ToR
+//! position becomes an item for the ToR's chair to confirm or revoke; the
+//! chair's own position, and positions in ToRs without a chair, go to the
+//! holders of `tor.manage_members` instead. Revoking removes the member from
+//! the position straight away.
+//!
+//! Once the deadline has passed the scheduler closes the campaign: items
+//! still pending are flagged, or revoked when the campaign was launched with
+//! `on_deadline = revoke`.
+
+use serde::Serialize;
+use sqlx::PgPool;
+
+use super::{entity, ownership_transfer, tor};
+
+/// Permission of the fallback reviewers for items without a chair.
+pub const FALLBACK_PERMISSION: &str = "tor.manage_members";
+
+/// What happens to items still pending at the deadline, as (key, label).
+pub const DEADLINE_ACTIONS: &[(&str, &str)] = &[
+    ("flag", "Flag unconfirmed access"),
+    ("revoke", "Revoke unconfirmed access"),
+];
+
+pub const PENDING: &str = "pending";
+pub const CONFIRMED: &str = "confirmed";
+pub const REVOKED: &str = "revoked";
+pub const FLAGGED: &str = "flagged";
+pub const AUTO_REVOKED: &str = "auto_revoked";
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct Campaign {
+    pub id: i64,
+    pub label: String,
+    pub deadline: String,
+    /// `flag` or `revoke`.
+    pub on_deadline: String,
+    /// `open` or `closed`.
+    pub status: String,
+    pub launched_by_name: String,
+    pub launched_at: String,
+    pub total: i64,
+    pub pending: i64,
+    pub confirmed: i64,
+    pub revoked: i64,
+    pub flagged: i64,
+}
+
+impl Campaign {
+    pub fn is_open(&self) -> bool {
+        self.status == "open"
+    }
+
+    /// Share of items decided, in whole percent.
+    pub fn progress_pct(&self) -> i64 {
+        if self.total == 0 { 100 } else { (self.total - self.pending) * 100 / self.total }
+    }
+
+    pub fn on_deadline_label(&self) -> &'static str {
+        DEADLINE_ACTIONS.iter()
+            .find(|(key, _)| *key == self.on_deadline)
+            .map_or("", |(_, label)| *label)
+    }
+}
+
+/// One member's position in a campaign, awaiting or holding a decision.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct Item {
+    pub id: i64,
+    pub campaign_id: i64,
+    pub campaign_label: String,
+    pub deadline: String,
+    pub tor_id: i64,
+    pub tor_label: String,
+    pub position_id: i64,
+    pub position_label: String,
+    pub user_id: i64,
+    pub user_label: String,
+    /// The chair who reviews the item; 0 for the fallback reviewers.
+    pub reviewer_id: i64,
+    pub decision: String,
+    pub decided_by_name: String,
+    pub decided_at: String,
+}
+
+impl Item {
+    pub fn is_pending(&self) -> bool {
+        self.decision == PENDING
+    }
+
+    /// Whether `user_id` may decide the item: its chair, or a fallback
+    /// reviewer when it has none.
+    pub fn can_decide(&self, user_id: i64, is_fallback_reviewer: bool) -> bool {
+        self.is_pending() && if self.reviewer_id == 0 { is_fallback_reviewer } else { self.reviewer_id == user_id }
+    }
+}
+
+/// Errors from validating a new campaign.
+pub fn validate_launch(label: &str, deadline: &str, on_deadline: &str, today: chrono::NaiveDate) -> Vec<String> {
+    let mut errors = Vec::new();
+    if label.trim().is_empty() {
+        errors.push("Name the campaign".to_string());
+    }
+    match chrono::NaiveDate::parse_from_str(deadline, "%Y-%m-%d") {
+        Ok(date) if date < today => errors.push("The deadline cannot be in the past".to_string()),
+        Ok(_) => {}
+        Err(_) => errors.push("Deadline must be a date (YYYY-MM-DD)".to_string()),
+    }
+    if !DEADLINE_ACTIONS.iter().any(|(key, _)| *key == on_deadline) {
+        errors.push("Choose what happens to unconfirmed access".to_string());
+    }
+    errors
+}
+
+/// Launch a campaign covering every filled position in every ToR. Returns
+/// the campaign id and the reviewers who received items (0 for the fallback
+/// reviewers), each with their item count.
+pub async fn launch(
+    pool: &PgPool,
+    label: &str,
+    deadline: &str,
+    on_deadline: &str,
+    user_id: i64,
+) -> Result<(i64, Vec<(i64, usize)>), sqlx::Error> {
+    let name = entity::available_name(pool, "recert_campaign", &format!("recert_{deadline}")).await?;
+    let campaign_id = entity::create(pool, "recert_campaign", &name, label.trim()).await?;
+    entity::set_properties(pool, campaign_id, &[
+        ("deadline", deadline),
+        ("on_deadline", on_deadline),
+        ("status", "open"),
+        ("launched_by", &user_id.to_string()),
+    ]).await?;
+
+    let mut reviewers: Vec<(i64, usize)> = Vec::new();
+    for t in entity::find_by_type(pool, "tor").await? {
+        let chair = ownership_transfer::find_chair_position(pool, t.id).await?;
+        let chair_holder = chair.as_ref().and_then(|c| c.holder_id).unwrap_or(0);
+        for member in tor::find_members(pool, t.id).await? {
+            let (Some(holder_id), Some(holder_label)) = (member.holder_id, member.holder_label) else {
+                continue;
+            };
+            let reviewer_id = if holder_id == chair_holder { 0 } else { chair_holder };
+            let item_name = entity::available_name(pool, "recert_item", &format!("{name}_{}", member.position_id)).await?;
+            let item_id = entity::create(pool, "recert_item", &item_name, &holder_label).await?;
+            entity::set_properties(pool, item_id, &[
+                ("campaign_id", &campaign_id.to_string()),
+                ("tor_id", &t.id.to_string()),
+                ("tor_label", &t.label),
+                ("position_id", &member.position_id.to_string()),
+                ("position_label", &member.position_label),
+                ("user_id", &holder_id.to_string()),
+                ("reviewer_id", &reviewer_id.to_string()),
+                ("decision", PENDING),
+            ]).await?;
+            match reviewers.iter_mut().find(|(id, _)| *id == reviewer_id) {
+                Some((_, count)) => *count += 1,
+                None => reviewers.push((reviewer_id, 1)),
+            }
+        }
+    }
+    Ok((campaign_id, reviewers))
+}
+
+const CAMPAIGN_SELECT: &str =
+    "SELECT e.id, e.label, COALESCE(p_dl.value, '') AS deadline, \
+            COALESCE(p_od.value, 'flag') AS on_deadline, COALESCE(p_st.value, 'open') AS status, \
+            COALESCE(u.label, 'system') AS launched_by_name, \
+            to_char(e.created_at, 'YYYY-MM-DD HH24:MI') AS launched_at, \
+            COUNT(i.id) AS total, \
+            COUNT(i.id) FILTER (WHERE p_dec.value = 'pending') AS pending, \
+            COUNT(i.id) FILTER (WHERE p_dec.value = 'confirmed') AS confirmed, \
+            COUNT(i.id) FILTER (WHERE p_dec.value IN ('revoked', 'auto_revoked')) AS revoked, \
+            COUNT(i.id) FILTER (WHERE p_dec.value = 'flagged') AS flagged \
+     FROM entities e \
+     LEFT JOIN entity_properties p_dl ON e.id = p_dl.entity_id AND p_dl.key = 'deadline' \
+     LEFT JOIN entity_properties p_od ON e.id = p_od.entity_id AND p_od.key = 'on_deadline' \
+     LEFT JOIN entity_properties p_st ON e.id = p_st.entity_id AND p_st.key = 'status' \
+     LEFT JOIN entity_properties p_by ON e.id = p_by.entity_id AND p_by.key = 'launched_by' \
+     LEFT JOIN entities u ON u.id = CAST(p_by.value AS BIGINT) AND u.entity_type = 'user' \
+     LEFT JOIN entity_properties p_cid ON p_cid.key = 'campaign_id' AND p_cid.value = e.id::TEXT \
+     LEFT JOIN entities i ON i.id = p_cid.entity_id AND i.entity_type = 'recert_item' \
+     LEFT JOIN entity_properties p_dec ON p_dec.entity_id = i.id AND p_dec.key = 'decision' \
+     WHERE e.entity_type = 'recert_campaign'";
+
+const CAMPAIGN_GROUP: &str = "GROUP BY e.id, e.label, p_dl.value, p_od.value, p_st.value, u.label";
+
+/// All campaigns, newest first.
+pub async fn find_all(pool: &PgPool) -> Result<Vec<Campaign>, sqlx::Error> {
+    sqlx::query_as::<_, Campaign>(&format!("{CAMPAIGN_SELECT} {CAMPAIGN_GROUP} ORDER BY e.id DESC"))
+        .fetch_all(pool)
+        .await
+}
+
+pub async fn find_by_id(pool: &PgPool, id: i64) -> Result<Option<Campaign>, sqlx::Error> {
+    sqlx::query_as::<_, Campaign>(&format!("{CAMPAIGN_SELECT} AND e.id = $1 {CAMPAIGN_GROUP}"))
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+}
+
+const ITEM_SELECT: &str =
+    "SELECT i.id, c.id AS campaign_id, c.label AS campaign_label, COALESCE(p_dl.value, '') AS deadline, \
+            COALESCE(p_tor.value, '0')::BIGINT AS tor_id, COALESCE(p_torl.value, '') AS tor_label, \
+            COALESCE(p_pos.value, '0')::BIGINT AS position_id, COALESCE(p_posl.value, '') AS position_label, \
+            COALESCE(p_user.value, '0')::BIGINT AS user_id, i.label AS user_label, \
+            COALESCE(p_rev.value, '0')::BIGINT AS reviewer_id, COALESCE(p_dec.value, 'pending') AS decision, \
+            COALESCE(dec_u.label, CASE WHEN p_dec_by.value = '0' THEN 'system' ELSE '' END) AS decided_by_name, \
+            COALESCE(p_dec_at.value, '') AS decided_at \
+     FROM entities i \
+     JOIN entity_properties p_cid ON p_cid.entity_id = i.id AND p_cid.key = 'campaign_id' \
+     JOIN entities c ON c.id = CAST(p_cid.value AS BIGINT) AND c.entity_type = 'recert_campaign' \
+     LEFT JOIN entity_properties p_dl ON p_dl.entity_id = c.id AND p_dl.key = 'deadline' \
+     LEFT JOIN entity_properties p_st ON p_st.entity_id = c.id AND p_st.key = 'status' \
+     LEFT JOIN entity_properties p_tor ON p_tor.entity_id = i.id AND p_tor.key = 'tor_id' \
+     LEFT JOIN entity_properties p_torl ON p_torl.entity_id = i.id AND p_torl.key = 'tor_label' \
+     LEFT JOIN entity_properties p_pos ON p_pos.entity_id = i.id AND p_pos.key = 'position_id' \
+     LEFT JOIN entity_properties p_posl ON p_posl.entity_id = i.id AND p_posl.key = 'position_label' \
+     LEFT JOIN entity_properties p_user ON p_user.entity_id = i.id AND p_user.key = 'user_id' \
+     LEFT JOIN entity_properties p_rev ON p_rev.entity_id = i.id AND p_rev.key = 'reviewer_id' \
+     LEFT JOIN entity_properties p_dec ON p_dec.entity_id = i.id AND p_dec.key = 'decision' \
+     LEFT JOIN entity_properties p_dec_by ON p_dec_by.entity_id = i.id AND p_dec_by.key = 'decided_by' \
+     LEFT JOIN entities dec_u ON dec_u.id = CAST(p_dec_by.value AS BIGINT) AND dec_u.entity_type = 'user' \
+     LEFT JOIN entity_properties p_dec_at ON p_dec_at.entity_id = i.id AND p_dec_at.key = 'decided_at' \
+     WHERE i.entity_type = 'recert_item'";
+
+/// A campaign's items by ToR and position.
+pub async fn find_items(pool: &PgPool, campaign_id: i64) -> Result<Vec<Item>, sqlx::Error> {
+    sqlx::query_as::<_, Item>(&format!("{ITEM_SELECT} AND c.id = $1 ORDER BY p_torl.value, p_posl.value, i.id"))
+        .bind(campaign_id)
+        .fetch_all(pool)
+        .await
+}
+
+pub async fn find_item(pool: &PgPool, id: i64) -> Result<Option<Item>, sqlx::Error> {
+    sqlx::query_as::<_, Item>(&format!("{ITEM_SELECT} AND i.id = $1"))
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+}
+
+/// Pending items of open campaigns that `user_id` reviews, including the
+/// chairless ones when they are a fallback reviewer.
+pub async fn find_tasks(pool: &PgPool, user_id: i64, is_fallback_reviewer: bool) -> Result<Vec<Item>, sqlx::Error> {
+    sqlx::query_as::<_, Item>(&format!(
+        "{ITEM_SELECT} AND COALESCE(p_st.value, 'open') = 'open' AND p_dec.value = 'pending' \
+         AND (p_rev.value = $1 OR ($2 AND p_rev.value = '0')) \
+         ORDER BY p_dl.value, p_torl.value, p_posl.value, i.id"
+    ))
+    .bind(user_id.to_string())
+    .bind(is_fallback_reviewer)
+    .fetch_all(pool)
+    .await
+}
+
+/// Set the decision on a pending item; revoking removes the member from the
+/// position. Returns false when the item was already decided.
+pub async fn decide(pool: &PgPool, item: &Item, decision: &str, user_id: i64) -> Result<bool, sqlx::Error> {
+    let updated = sqlx::query(
+        "UPDATE entity_properties SET value = $2 \
+         WHERE entity_id = $1 AND key = 'decision' AND value = 'pending'"
+    )
+    .bind(item.id)
+    .bind(decision)
+    .execute(pool)
+    .await?;
+    if updated.rows_affected() == 0 {
+        return Ok(false);
+    }
+    if decision == REVOKED || decision == AUTO_REVOKED {
+        tor::remove_from_position(pool, item.user_id, item.position_id).await?;
+    }
+    let now = chrono::Local::now().format("%Y-%m-%d %H:%M").to_string();
+    entity::set_properties(pool, item.id, &[
+        ("decided_by", &user_id.to_string()),
+        ("decided_at", &now),
+    ]).await?;
+    Ok(true)
+}
+
+/// Close open campaigns whose deadline is before `today`, flagging or
+/// revoking their pending items. Returns each closed campaign with the
+/// items it flagged or revoked.
+pub async fn close_overdue(pool: &PgPool, today: &str) -> Result<Vec<(Campaign, Vec<Item>)>, sqlx::Error> {
+    let mut closed = Vec::new();
+    for campaign in find_all(pool).await? {
+        if !campaign.is_open() || campaign.deadline.as_str() >= today {
+            continue;
+        }
+        closed.push(close(pool, &campaign).await?);
+    }
+    Ok(closed)
+}
+
+/// Close a campaign now, applying its deadline action to pending items.
+pub async fn close(pool: &PgPool, campaign: &Campaign) -> Result<(Campaign, Vec<Item>), sqlx::Error> {
+    let outcome = if campaign.on_deadline == "revoke" { AUTO_REVOKED } else { FLAGGED };
+    let mut affected = Vec::new();
+    for item in find_items(pool, campaign.id).await? {
+        if item.is_pending() && decide(pool, &item, outcome, 0).await? {
+            affected.push(item);
+        }
+    }
+    entity::set_property(pool, campaign.id, "status", "closed").await?;
+    let campaign = find_by_id(pool, campaign.id).await?.ok_or(sqlx::Error::RowNotFound)?;
+    Ok((campaign, affected))
+}
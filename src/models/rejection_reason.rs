@@ -0,0 +1,183 @@
+//! Rejection reason taxonomy.
+//!
+//! Admins maintain a list of `rejection_reason` entities; rejecting a
+//! proposal picks one of them and may add a note. The proposal keeps the
+//! reason's name in `rejection_category` and a readable "Label — note" text
+//! in `rejection_reason`, so lists, emails and the API show it unchanged.
+//! The `proposal.rejected` domain event carries the category too, which is
+//! what the per-ToR analytics count. Reasons that have been used can be
+//! retired (inactive) but not deleted, so past rejections keep their label.
+
+use sqlx::PgPool;
+
+use super::entity;
+
+pub const ENTITY_TYPE: &str = "rejection_reason";
+
+/// Label shown for rejections made before a category was required.
+pub const UNCLASSIFIED: &str = "Unclassified";
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct RejectionReason {
+    pub id: i64,
+    pub name: String,
+    pub label: String,
+    pub description: String,
+    pub is_active: bool,
+    /// Rejections recorded under this reason.
+    pub usage: i64,
+}
+
+/// Fields submitted from the reason form.
+pub struct RejectionReasonInput<'a> {
+    pub name: &'a str,
+    pub label: &'a str,
+    pub description: &'a str,
+}
+
+/// Rejections of one ToR's proposals grouped by reason, most common first.
+#[derive(Debug, Clone)]
+pub struct RejectionSummary {
+    pub tor_id: i64,
+    pub tor_label: String,
+    pub total: i64,
+    /// (reason label, count), most common first.
+    pub reasons: Vec<(String, i64)>,
+}
+
+impl RejectionSummary {
+    /// The most common reason, ignoring unclassified rejections.
+    pub fn top_reason(&self) -> Option<&str> {
+        self.reasons.iter()
+            .find(|(label, _)| label != UNCLASSIFIED)
+            .map(|(label, _)| label.as_str())
+    }
+
+    /// Share of the ToR's rejections, 0-100.
+    pub fn pct(&self, count: &i64) -> i64 {
+        if self.total == 0 { 0 } else { count * 100 / self.total }
+    }
+}
+
+/// The text stored as a proposal's `rejection_reason`.
+pub fn reason_text(label: &str, note: &str) -> String {
+    match note.trim() {
+        "" => label.to_string(),
+        note => format!("{} — {}", label, note),
+    }
+}
+
+const SELECT: &str =
+    "SELECT e.id, e.name, e.label, COALESCE(p_desc.value, '') AS description, e.is_active, \
+            (SELECT COUNT(*) FROM domain_events ev \
+             WHERE ev.event_type = 'proposal.rejected' AND ev.payload->>'rejection_category' = e.name) AS usage \
+     FROM entities e \
+     LEFT JOIN entity_properties p_desc ON e.id = p_desc.entity_id AND p_desc.key = 'description' \
+     WHERE e.entity_type = 'rejection_reason'";
+
+/// All reasons, active first, in their sort order.
+pub async fn find_all(pool: &PgPool) -> Result<Vec<RejectionReason>, sqlx::Error> {
+    sqlx::query_as::<_, RejectionReason>(&format!("{SELECT} ORDER BY e.is_active DESC, e.sort_order, e.label"))
+        .fetch_all(pool)
+        .await
+}
+
+/// Reasons that may be picked when rejecting.
+pub async fn find_active(pool: &PgPool) -> Result<Vec<RejectionReason>, sqlx::Error> {
+    sqlx::query_as::<_, RejectionReason>(&format!("{SELECT} AND e.is_active = true ORDER BY e.sort_order, e.label"))
+        .fetch_all(pool)
+        .await
+}
+
+pub async fn find_by_id(pool: &PgPool, id: i64) -> Result<Option<RejectionReason>, sqlx::Error> {
+    sqlx::query_as::<_, RejectionReason>(&format!("{SELECT} AND e.id = $1"))
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+}
+
+/// Validate form input. `existing_id` is the reason being edited, if any;
+/// a reason's name is fixed once created.
+pub async fn validate(pool: &PgPool, input: &RejectionReasonInput<'_>, existing_id: Option<i64>) -> Result<Vec<String>, sqlx::Error> {
+    let mut errors = Vec::new();
+    if existing_id.is_none() {
+        let name = input.name.trim();
+        if name.is_empty() {
+            errors.push("Name is required".to_string());
+        } else if !name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-') {
+            errors.push("Name may only contain lowercase letters, digits, '-' and '_'".to_string());
+        } else if entity::find_by_type_and_name(pool, ENTITY_TYPE, name).await?.is_some() {
+            errors.push(format!("A rejection reason named '{}' already exists", name));
+        }
+    }
+    let label = input.label.trim();
+    if label.is_empty() {
+        errors.push("Label is required".to_string());
+    } else if label == UNCLASSIFIED {
+        errors.push(format!("'{}' is reserved for rejections without a reason", UNCLASSIFIED));
+    }
+    Ok(errors)
+}
+
+pub async fn create(pool: &PgPool, input: &RejectionReasonInput<'_>) -> Result<i64, sqlx::Error> {
+    let sort_order = entity::count_by_type(pool, ENTITY_TYPE).await? + 1;
+    let id = entity::create_with_sort(pool, ENTITY_TYPE, input.name.trim(), input.label.trim(), sort_order).await?;
+    entity::set_property(pool, id, "description", input.description.trim()).await?;
+    Ok(id)
+}
+
+/// Update a reason's label and description; its name is kept.
+pub async fn update(pool: &PgPool, reason: &RejectionReason, input: &RejectionReasonInput<'_>) -> Result<(), sqlx::Error> {
+    entity::update(pool, reason.id, &reason.name, input.label.trim()).await?;
+    entity::set_property(pool, reason.id, "description", input.description.trim()).await
+}
+
+/// Retire a reason (it can no longer be picked) or bring it back.
+pub async fn set_active(pool: &PgPool, id: i64, active: bool) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE entities SET is_active = $2, updated_at = NOW() WHERE id = $1 AND entity_type = 'rejection_reason'")
+        .bind(id)
+        .bind(active)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Delete a reason. Only unused reasons should be deleted; used ones are retired.
+pub async fn delete(pool: &PgPool, id: i64) -> Result<(), sqlx::Error> {
+    entity::delete(pool, id).await
+}
+
+/// Rejections of proposals in these ToRs grouped by reason, one summary per
+/// ToR that has any, ordered by ToR label. Every rejection counts, including
+/// earlier rejections of proposals that were resubmitted.
+pub async fn find_summaries(pool: &PgPool, tor_ids: &[i64]) -> Result<Vec<RejectionSummary>, sqlx::Error> {
+    let rows: Vec<(i64, String, String, i64)> = sqlx::query_as(
+        "SELECT t.id, t.label, \
+                COALESCE(rr.label, NULLIF(ev.payload->>'rejection_category', ''), $2) AS reason, \
+                COUNT(*) AS count \
+         FROM domain_events ev \
+         JOIN relations r ON r.source_id = ev.aggregate_id \
+         JOIN entities rt ON rt.id = r.relation_type_id AND rt.name = 'submitted_to' \
+         JOIN entities t ON t.id = r.target_id \
+         LEFT JOIN entities rr ON rr.entity_type = 'rejection_reason' AND rr.name = ev.payload->>'rejection_category' \
+         WHERE ev.event_type = 'proposal.rejected' AND ev.aggregate_type = 'proposal' AND t.id = ANY($1) \
+         GROUP BY t.id, t.label, reason \
+         ORDER BY t.label, t.id, count DESC, reason",
+    )
+    .bind(tor_ids)
+    .bind(UNCLASSIFIED)
+    .fetch_all(pool)
+    .await?;
+
+    let mut summaries: Vec<RejectionSummary> = Vec::new();
+    for (tor_id, tor_label, reason, count) in rows {
+        match summaries.last_mut() {
+            Some(s) if s.tor_id == tor_id => {
+                s.total += count;
+                s.reasons.push((reason, count));
+            }
+            _ => summaries.push(RejectionSummary { tor_id, tor_label, total: count, reasons: vec![(reason, count)] }),
+        }
+    }
+    Ok(summaries)
+}
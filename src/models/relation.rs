@@ -1,5 +1,5 @@
 #![allow(dead_code)]
-use sqlx::PgPool;
+use sqlx::{PgPool, Postgres, Transaction};
 use super::entity::Entity;
 
 /// Find all target entities related to source via a named relation type.
@@ -53,6 +53,26 @@ pub async fn create(pool: &PgPool, relation_type_name: &str, source_id: i64, tar
     Ok(())
 }
 
+/// Create a relation within a transaction.
+pub async fn create_in_tx(
+    tx: &mut Transaction<'_, Postgres>,
+    relation_type_name: &str,
+    source_id: i64,
+    target_id: i64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO relations (relation_type_id, source_id, target_id) \
+         VALUES ((SELECT id FROM entities WHERE entity_type = 'relation_type' AND name = $1), $2, $3) \
+         ON CONFLICT DO NOTHING",
+    )
+    .bind(relation_type_name)
+    .bind(source_id)
+    .bind(target_id)
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
 /// Delete a specific relation.
 pub async fn delete(pool: &PgPool, relation_type_name: &str, source_id: i64, target_id: i64) -> Result<(), sqlx::Error> {
     sqlx::query(
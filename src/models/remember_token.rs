@@ -0,0 +1,183 @@
+//! Persistent "remember this device" login tokens.
+//!
+//! The cookie value is `<selector>.<validator>`. The selector is the entity
+//! name and is looked up directly; only an argon2 hash of the validator is
+//! stored. Tokens are single-use: `consume` deletes the presented token and
+//! issues a replacement. A known selector with a wrong validator means the
+//! token was copied and already used, so every token of that user is revoked.
+
+use rand::Rng;
+use sqlx::PgPool;
+
+use crate::auth::password;
+use crate::errors::AppError;
+use super::{entity, relation};
+
+/// A remembered device, for the account sessions tab.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct RememberTokenDisplay {
+    pub id: i64,
+    pub label: String,
+    pub device_since: String,
+    pub last_used_at: String,
+    pub expires_at: String,
+}
+
+fn random_hex(len: usize) -> String {
+    let mut rng = rand::rng();
+    let bytes: Vec<u8> = (0..len).map(|_| rng.random()).collect();
+    hex::encode(bytes)
+}
+
+/// Issue a new token for `user_id` valid for `lifetime`. Returns the raw cookie value.
+/// `device_since` carries the original sign-in time across rotations.
+pub async fn issue(
+    pool: &PgPool,
+    user_id: i64,
+    label: &str,
+    lifetime: chrono::Duration,
+    device_since: Option<&str>,
+) -> Result<String, AppError> {
+    let selector = random_hex(12);
+    let validator = random_hex(32);
+    let token_hash = password::hash_password(&validator).map_err(AppError::Hash)?;
+
+    let now = chrono::Utc::now();
+    let now_str = now.format("%Y-%m-%d %H:%M").to_string();
+    let expires_at = (now + lifetime).timestamp().to_string();
+
+    let id = entity::create(pool, "remember_token", &selector, label).await?;
+    entity::set_properties(pool, id, &[
+        ("token_hash", &token_hash),
+        ("expires_at", &expires_at),
+        ("device_since", device_since.unwrap_or(&now_str)),
+        ("last_used_at", &now_str),
+    ]).await?;
+    relation::create(pool, "for_user", id, user_id).await?;
+
+    Ok(format!("{}.{}", selector, validator))
+}
+
+/// Row used while redeeming a token.
+#[derive(sqlx::FromRow)]
+struct StoredToken {
+    id: i64,
+    label: String,
+    user_id: i64,
+    token_hash: String,
+    expires_at: String,
+    device_since: String,
+}
+
+/// Redeem a raw token. On success the token is replaced by a fresh one and
+/// `(user_id, new_raw_token)` is returned; otherwise `None`.
+pub async fn consume(
+    pool: &PgPool,
+    raw: &str,
+    lifetime: chrono::Duration,
+) -> Result<Option<(i64, String)>, AppError> {
+    let Some((selector, validator)) = raw.split_once('.') else {
+        return Ok(None);
+    };
+
+    let stored = sqlx::query_as::<_, StoredToken>(
+        "SELECT t.id, t.label, r.target_id AS user_id, \
+                COALESCE(p_hash.value, '') AS token_hash, \
+                COALESCE(p_exp.value, '0') AS expires_at, \
+                COALESCE(p_since.value, '') AS device_since \
+         FROM entities t \
+         JOIN relations r ON r.source_id = t.id \
+         JOIN entities rt ON rt.id = r.relation_type_id AND rt.name = 'for_user' \
+         JOIN entities u ON u.id = r.target_id AND u.entity_type = 'user' AND u.is_active = true \
+         LEFT JOIN entity_properties p_hash ON t.id = p_hash.entity_id AND p_hash.key = 'token_hash' \
+         LEFT JOIN entity_properties p_exp ON t.id = p_exp.entity_id AND p_exp.key = 'expires_at' \
+         LEFT JOIN entity_properties p_since ON t.id = p_since.entity_id AND p_since.key = 'device_since' \
+         WHERE t.entity_type = 'remember_token' AND t.name = $1"
+    )
+    .bind(selector)
+    .fetch_optional(pool)
+    .await?;
+
+    let Some(stored) = stored else {
+        return Ok(None);
+    };
+
+    if !password::verify_password(validator, &stored.token_hash).unwrap_or(false) {
+        revoke_all(pool, stored.user_id).await?;
+        return Ok(None);
+    }
+
+    // Single use: the presented token is gone whether or not it was still valid
+    entity::delete(pool, stored.id).await?;
+    let expires_at: i64 = stored.expires_at.parse().unwrap_or(0);
+    if expires_at <= chrono::Utc::now().timestamp() {
+        return Ok(None);
+    }
+
+    let fresh = issue(pool, stored.user_id, &stored.label, lifetime, Some(&stored.device_since)).await?;
+    Ok(Some((stored.user_id, fresh)))
+}
+
+/// Remembered devices of a user, most recently used first.
+pub async fn find_for_user(pool: &PgPool, user_id: i64) -> Result<Vec<RememberTokenDisplay>, AppError> {
+    let tokens = sqlx::query_as::<_, RememberTokenDisplay>(
+        "SELECT t.id, t.label, \
+                COALESCE(p_since.value, '') AS device_since, \
+                COALESCE(p_used.value, '') AS last_used_at, \
+                COALESCE(TO_CHAR(TO_TIMESTAMP(p_exp.value::BIGINT), 'YYYY-MM-DD HH24:MI'), '') AS expires_at \
+         FROM entities t \
+         JOIN relations r ON r.source_id = t.id AND r.target_id = $1 \
+         JOIN entities rt ON rt.id = r.relation_type_id AND rt.name = 'for_user' \
+         LEFT JOIN entity_properties p_since ON t.id = p_since.entity_id AND p_since.key = 'device_since' \
+         LEFT JOIN entity_properties p_used ON t.id = p_used.entity_id AND p_used.key = 'last_used_at' \
+         LEFT JOIN entity_properties p_exp ON t.id = p_exp.entity_id AND p_exp.key = 'expires_at' \
+         WHERE t.entity_type = 'remember_token' \
+         ORDER BY t.id DESC"
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(tokens)
+}
+
+/// Revoke one of the user's tokens. Returns false if it isn't theirs.
+pub async fn revoke(pool: &PgPool, user_id: i64, token_id: i64) -> Result<bool, AppError> {
+    let result = sqlx::query(
+        "DELETE FROM entities t \
+         WHERE t.id = $1 AND t.entity_type = 'remember_token' \
+           AND EXISTS (SELECT 1 FROM relations r \
+                       JOIN entities rt ON rt.id = r.relation_type_id AND rt.name = 'for_user' \
+                       WHERE r.source_id = t.id AND r.target_id = $2)"
+    )
+    .bind(token_id)
+    .bind(user_id)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Revoke every token of a user ("sign out everywhere", password change, theft).
+pub async fn revoke_all(pool: &PgPool, user_id: i64) -> Result<u64, AppError> {
+    let result = sqlx::query(
+        "DELETE FROM entities t \
+         WHERE t.entity_type = 'remember_token' \
+           AND EXISTS (SELECT 1 FROM relations r \
+                       JOIN entities rt ON rt.id = r.relation_type_id AND rt.name = 'for_user' \
+                       WHERE r.source_id = t.id AND r.target_id = $1)"
+    )
+    .bind(user_id)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+/// Revoke the token presented as a raw cookie value (logout on this device).
+pub async fn revoke_raw(pool: &PgPool, raw: &str) -> Result<(), AppError> {
+    if let Some((selector, _)) = raw.split_once('.') {
+        sqlx::query("DELETE FROM entities WHERE entity_type = 'remember_token' AND name = $1")
+            .bind(selector)
+            .execute(pool)
+            .await?;
+    }
+    Ok(())
+}
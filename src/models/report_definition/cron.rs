@@ -0,0 +1,139 @@
+//! Cron-style schedules: `minute hour day-of-month month day-of-week`.
+//!
+//! Each field is `*`, a number, a range `a-b`, a step `*/n` or `a-b/n`, or a
+//! comma-separated list of those. Day-of-week runs 0-6 from Sunday (7 is
+//! Sunday too). As in cron, when both day fields are restricted a day
+//! matching either one qualifies. `@hourly`, `@daily`, `@weekly` and
+//! `@monthly` are accepted as shorthands.
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, TimeZone, Timelike};
+
+/// Allowed values of one field, as a bit set.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Field {
+    bits: u64,
+    /// False for `*`, which matters for the day-of-month/day-of-week rule.
+    restricted: bool,
+}
+
+impl Field {
+    fn parse(text: &str, name: &str, min: u32, max: u32) -> Result<Field, String> {
+        let mut bits = 0u64;
+        for part in text.split(',') {
+            let (range, step) = match part.split_once('/') {
+                Some((range, step)) => {
+                    let step: u32 = step.parse().map_err(|_| format!("Invalid step '{}' in {}", step, name))?;
+                    if step == 0 {
+                        return Err(format!("Step must be at least 1 in {}", name));
+                    }
+                    (range, step)
+                }
+                None => (part, 1),
+            };
+            let (from, to) = if range == "*" {
+                (min, max)
+            } else {
+                let number = |s: &str| s.parse::<u32>().map_err(|_| format!("Invalid value '{}' in {}", s, name));
+                let (from, to) = match range.split_once('-') {
+                    Some((a, b)) => (number(a)?, number(b)?),
+                    // `5/15` means every 15 from 5
+                    None if part.contains('/') => (number(range)?, max),
+                    None => {
+                        let n = number(range)?;
+                        (n, n)
+                    }
+                };
+                if from < min || to > max || from > to {
+                    return Err(format!("{} must be between {} and {}", name, min, max));
+                }
+                (from, to)
+            };
+            for v in (from..=to).step_by(step as usize) {
+                bits |= 1 << v;
+            }
+        }
+        Ok(Field { bits, restricted: text != "*" })
+    }
+
+    fn contains(&self, v: u32) -> bool {
+        self.bits & (1 << v) != 0
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Schedule {
+    minute: Field,
+    hour: Field,
+    day: Field,
+    month: Field,
+    weekday: Field,
+}
+
+impl Schedule {
+    pub fn parse(expression: &str) -> Result<Schedule, String> {
+        let expression = match expression.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            other => other,
+        };
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            return Err("A schedule has five fields: minute hour day-of-month month day-of-week".to_string());
+        };
+        let mut weekday = Field::parse(weekday, "day-of-week", 0, 7)?;
+        if weekday.contains(7) {
+            weekday.bits |= 1;
+        }
+        Ok(Schedule {
+            minute: Field::parse(minute, "minute", 0, 59)?,
+            hour: Field::parse(hour, "hour", 0, 23)?,
+            day: Field::parse(day, "day-of-month", 1, 31)?,
+            month: Field::parse(month, "month", 1, 12)?,
+            weekday,
+        })
+    }
+
+    fn day_matches(&self, date: NaiveDate) -> bool {
+        let day = self.day.contains(date.day());
+        let weekday = self.weekday.contains(date.weekday().num_days_from_sunday());
+        match (self.day.restricted, self.weekday.restricted) {
+            (true, true) => day || weekday,
+            (true, false) => day,
+            (false, true) => weekday,
+            (false, false) => true,
+        }
+    }
+
+    /// The first matching minute strictly after `after`, in its time zone.
+    /// Times skipped by a daylight-saving change are passed over. None if
+    /// nothing matches within five years (e.g. `0 0 31 2 *`).
+    pub fn next_after<Tz: TimeZone>(&self, after: &DateTime<Tz>) -> Option<DateTime<Tz>> {
+        let start = after.naive_local().with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        let limit = start + Duration::days(5 * 366);
+        let mut t = start;
+        while t <= limit {
+            let date = t.date();
+            if !self.month.contains(date.month()) {
+                let (y, m) = if date.month() == 12 { (date.year() + 1, 1) } else { (date.year(), date.month() + 1) };
+                t = midnight(NaiveDate::from_ymd_opt(y, m, 1)?);
+            } else if !self.day_matches(date) {
+                t = midnight(date.succ_opt()?);
+            } else if !self.hour.contains(t.hour()) {
+                t = t.with_minute(0)? + Duration::hours(1);
+            } else if !self.minute.contains(t.minute()) {
+                t += Duration::minutes(1);
+            } else if let Some(found) = after.timezone().from_local_datetime(&t).earliest() {
+                return Some(found);
+            } else {
+                t += Duration::minutes(1);
+            }
+        }
+        None
+    }
+}
+
+fn midnight(date: NaiveDate) -> NaiveDateTime {
+    date.and_hms_opt(0, 0, 0).unwrap_or_default()
+}
@@ -0,0 +1,429 @@
+//! Scheduled report definitions.
+//!
+//! Report subscriptions let a user pick a report over their own ToRs; a
+//! `report_definition` is set up by an admin for a distribution group. It
+//! names one of [`REPORTS`] with its parameter, optionally limited to one
+//! ToR, a cron-style `schedule` (see [`cron`], in server local time), a
+//! format and how it is delivered. When it comes due the warning scheduler
+//! builds the report once, stores a `report_delivery` (with a
+//! `definition_id`) in every group member's report inbox and notifies them;
+//! with e-mail delivery, members who opted in to report e-mails also get
+//! the `report_delivery` e-mail linking to it. `next_run_at` and
+//! `last_run_at` are RFC 3339 instants.
+
+pub mod cron;
+
+use chrono::{DateTime, Duration, Local, NaiveDate, Utc};
+use sqlx::PgPool;
+
+use super::report_subscription::{self, ReportTable, KEEP_DELIVERIES};
+use super::fiscal::FiscalCalendar;
+use super::{distribution_group, entity, proposal};
+use crate::errors::AppError;
+use cron::Schedule;
+
+pub const ENTITY_TYPE: &str = "report_definition";
+
+/// A report a definition can run, with the meaning of its parameter.
+pub struct ReportKind {
+    pub key: &'static str,
+    pub label: &'static str,
+    pub parameter: &'static str,
+    pub default: i64,
+}
+
+pub const REPORTS: &[ReportKind] = &[
+    ReportKind {
+        key: "pending_proposals",
+        label: "Proposals pending longer than N days",
+        parameter: "Days pending",
+        default: 14,
+    },
+    ReportKind {
+        key: "meeting_attendance",
+        label: "Meeting attendance by quarter",
+        parameter: "Quarters",
+        default: 4,
+    },
+];
+
+/// How deliveries reach the group, as (key, label).
+pub const DELIVERIES: &[(&str, &str)] = &[
+    ("inbox", "Report inbox"),
+    ("email", "Report inbox and e-mail"),
+];
+
+pub fn find_kind(key: &str) -> Option<&'static ReportKind> {
+    REPORTS.iter().find(|k| k.key == key)
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct ReportDefinition {
+    pub id: i64,
+    pub label: String,
+    pub is_active: bool,
+    pub report: String,
+    pub parameter: i64,
+    /// Limit to one ToR; None covers every ToR.
+    pub tor_id: Option<i64>,
+    pub tor_label: String,
+    pub schedule: String,
+    pub format: String,
+    /// Distribution group key (see `models::distribution_group`).
+    pub group_key: String,
+    pub delivery: String,
+    pub next_run_at: String,
+    pub last_run_at: String,
+}
+
+impl ReportDefinition {
+    pub fn report_label(&self) -> &str {
+        find_kind(&self.report).map_or(self.report.as_str(), |k| k.label)
+    }
+
+    pub fn parameter_label(&self) -> &str {
+        find_kind(&self.report).map_or("", |k| k.parameter)
+    }
+
+    pub fn delivery_label(&self) -> &str {
+        DELIVERIES.iter().find(|(k, _)| *k == self.delivery).map_or(self.delivery.as_str(), |(_, label)| *label)
+    }
+
+    pub fn emails(&self) -> bool {
+        self.delivery == "email"
+    }
+
+    /// `next_run_at` in local time, for display.
+    pub fn next_run_display(&self) -> String {
+        local_display(&self.next_run_at)
+    }
+
+    pub fn last_run_display(&self) -> String {
+        local_display(&self.last_run_at)
+    }
+}
+
+fn local_display(instant: &str) -> String {
+    DateTime::parse_from_rfc3339(instant)
+        .map(|t| t.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_default()
+}
+
+/// Fields submitted from the definition form.
+pub struct ReportDefinitionInput<'a> {
+    pub label: &'a str,
+    pub report: &'a str,
+    pub parameter: &'a str,
+    /// A ToR id, or empty for every ToR.
+    pub tor_id: &'a str,
+    pub schedule: &'a str,
+    pub format: &'a str,
+    pub group_key: &'a str,
+    pub delivery: &'a str,
+}
+
+const SELECT: &str =
+    "SELECT e.id, e.label, e.is_active, \
+            COALESCE(p_report.value, '') AS report, \
+            COALESCE(NULLIF(p_param.value, '')::BIGINT, 0) AS parameter, \
+            t.id AS tor_id, COALESCE(t.label, '') AS tor_label, \
+            COALESCE(p_schedule.value, '') AS schedule, \
+            COALESCE(p_format.value, '') AS format, \
+            COALESCE(p_group.value, '') AS group_key, \
+            COALESCE(p_delivery.value, '') AS delivery, \
+            COALESCE(p_next.value, '') AS next_run_at, \
+            COALESCE(p_last.value, '') AS last_run_at \
+     FROM entities e \
+     LEFT JOIN entity_properties p_report ON e.id = p_report.entity_id AND p_report.key = 'report' \
+     LEFT JOIN entity_properties p_param ON e.id = p_param.entity_id AND p_param.key = 'parameter' \
+     LEFT JOIN entity_properties p_tor ON e.id = p_tor.entity_id AND p_tor.key = 'tor_id' \
+     LEFT JOIN entities t ON t.id::TEXT = p_tor.value AND t.entity_type = 'tor' \
+     LEFT JOIN entity_properties p_schedule ON e.id = p_schedule.entity_id AND p_schedule.key = 'schedule' \
+     LEFT JOIN entity_properties p_format ON e.id = p_format.entity_id AND p_format.key = 'format' \
+     LEFT JOIN entity_properties p_group ON e.id = p_group.entity_id AND p_group.key = 'group_key' \
+     LEFT JOIN entity_properties p_delivery ON e.id = p_delivery.entity_id AND p_delivery.key = 'delivery' \
+     LEFT JOIN entity_properties p_next ON e.id = p_next.entity_id AND p_next.key = 'next_run_at' \
+     LEFT JOIN entity_properties p_last ON e.id = p_last.entity_id AND p_last.key = 'last_run_at' \
+     WHERE e.entity_type = 'report_definition'";
+
+pub async fn find_all(pool: &PgPool) -> Result<Vec<ReportDefinition>, sqlx::Error> {
+    sqlx::query_as::<_, ReportDefinition>(&format!("{SELECT} ORDER BY e.is_active DESC, e.label"))
+        .fetch_all(pool)
+        .await
+}
+
+pub async fn find_by_id(pool: &PgPool, id: i64) -> Result<Option<ReportDefinition>, sqlx::Error> {
+    sqlx::query_as::<_, ReportDefinition>(&format!("{SELECT} AND e.id = $1"))
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+}
+
+/// Active definitions whose next run is at or before `now`.
+pub async fn find_due(pool: &PgPool, now: DateTime<Utc>) -> Result<Vec<ReportDefinition>, sqlx::Error> {
+    sqlx::query_as::<_, ReportDefinition>(&format!(
+        "{SELECT} AND e.is_active = true AND p_next.value <> '' \
+         AND p_next.value::TIMESTAMPTZ <= $1::TIMESTAMPTZ ORDER BY e.id"
+    ))
+    .bind(now.to_rfc3339())
+    .fetch_all(pool)
+    .await
+}
+
+/// The next run after `now` for a schedule, as stored in `next_run_at`;
+/// empty if the schedule never fires.
+pub fn next_run(schedule: &str, now: DateTime<Utc>) -> String {
+    Schedule::parse(schedule).ok()
+        .and_then(|s| s.next_after(&now.with_timezone(&Local)))
+        .map(|t| t.with_timezone(&Utc).to_rfc3339())
+        .unwrap_or_default()
+}
+
+/// Validate form input. Returns a list of problems.
+pub async fn validate(pool: &PgPool, input: &ReportDefinitionInput<'_>) -> Result<Vec<String>, sqlx::Error> {
+    let mut errors = Vec::new();
+    if input.label.trim().is_empty() {
+        errors.push("Name is required".to_string());
+    }
+    if find_kind(input.report).is_none() {
+        errors.push("Choose a report".to_string());
+    }
+    if !input.parameter.trim().parse::<i64>().is_ok_and(|n| n >= 1) {
+        errors.push("The report parameter must be a whole number of at least 1".to_string());
+    }
+    if !input.tor_id.is_empty() && input.tor_id.parse::<i64>().is_err() {
+        errors.push("Choose a ToR".to_string());
+    }
+    match Schedule::parse(input.schedule) {
+        Ok(s) if s.next_after(&Local::now()).is_none() => errors.push("The schedule never runs".to_string()),
+        Ok(_) => {}
+        Err(e) => errors.push(format!("Schedule: {}", e)),
+    }
+    if !report_subscription::FORMATS.iter().any(|(k, _)| *k == input.format) {
+        errors.push("Choose a format".to_string());
+    }
+    if !DELIVERIES.iter().any(|(k, _)| *k == input.delivery) {
+        errors.push("Choose how the report is delivered".to_string());
+    }
+    if distribution_group::find_by_key(pool, input.group_key).await?.is_none() {
+        errors.push("Choose a distribution group to receive the report".to_string());
+    }
+    Ok(errors)
+}
+
+fn properties<'a>(input: &'a ReportDefinitionInput<'a>, next_run_at: &'a str) -> [(&'a str, &'a str); 8] {
+    [
+        ("report", input.report),
+        ("parameter", input.parameter.trim()),
+        ("tor_id", input.tor_id),
+        ("schedule", input.schedule.trim()),
+        ("format", input.format),
+        ("group_key", input.group_key),
+        ("delivery", input.delivery),
+        ("next_run_at", next_run_at),
+    ]
+}
+
+pub async fn create(pool: &PgPool, input: &ReportDefinitionInput<'_>, now: DateTime<Utc>) -> Result<i64, sqlx::Error> {
+    let base: String = input.label.trim().to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect::<Vec<_>>()
+        .join("_");
+    let name = entity::available_name(pool, ENTITY_TYPE, &base).await?;
+    let id = entity::create(pool, ENTITY_TYPE, &name, input.label.trim()).await?;
+    let next_run_at = next_run(input.schedule, now);
+    entity::set_properties(pool, id, &properties(input, &next_run_at)).await?;
+    Ok(id)
+}
+
+/// Update a definition; its next run follows the (possibly new) schedule.
+pub async fn update(pool: &PgPool, id: i64, input: &ReportDefinitionInput<'_>, now: DateTime<Utc>) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE entities SET label = $2, updated_at = NOW() WHERE id = $1 AND entity_type = 'report_definition'")
+        .bind(id)
+        .bind(input.label.trim())
+        .execute(pool)
+        .await?;
+    let next_run_at = next_run(input.schedule, now);
+    entity::set_properties(pool, id, &properties(input, &next_run_at)).await
+}
+
+/// Pause or resume a definition. Resuming schedules the next run from
+/// `now`, so runs missed while paused are not made up.
+pub async fn set_active(pool: &PgPool, def: &ReportDefinition, active: bool, now: DateTime<Utc>) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE entities SET is_active = $2, updated_at = NOW() WHERE id = $1 AND entity_type = 'report_definition'")
+        .bind(def.id)
+        .bind(active)
+        .execute(pool)
+        .await?;
+    if active {
+        entity::set_property(pool, def.id, "next_run_at", &next_run(&def.schedule, now)).await?;
+    }
+    Ok(())
+}
+
+/// Delete a definition together with the deliveries it made.
+pub async fn delete(pool: &PgPool, id: i64) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "DELETE FROM entities WHERE entity_type = 'report_delivery' AND id IN ( \
+             SELECT entity_id FROM entity_properties WHERE key = 'definition_id' AND value = $1)",
+    )
+    .bind(id.to_string())
+    .execute(pool)
+    .await?;
+    entity::delete(pool, id).await
+}
+
+/// The users a definition delivers to: the current members of its group.
+pub async fn find_recipients(pool: &PgPool, def: &ReportDefinition) -> Result<Vec<i64>, sqlx::Error> {
+    let Some(group) = distribution_group::find_by_key(pool, &def.group_key).await? else {
+        return Ok(Vec::new());
+    };
+    let mut ids: Vec<i64> = distribution_group::find_members(pool, &group.rule).await?
+        .into_iter()
+        .map(|m| m.user_id)
+        .collect();
+    ids.sort_unstable();
+    ids.dedup();
+    Ok(ids)
+}
+
+/// Build a definition's report as of `today`. Returns the table and the
+/// period it covers (end exclusive).
+pub async fn build_report(
+    pool: &PgPool,
+    def: &ReportDefinition,
+    today: NaiveDate,
+) -> Result<(ReportTable, (NaiveDate, NaiveDate)), AppError> {
+    let end = today + Duration::days(1);
+    match def.report.as_str() {
+        "pending_proposals" => {
+            let mut pending: Vec<_> = proposal::find_all_cross_tor(pool, None).await?.into_iter()
+                .filter(|p| matches!(p.status.as_str(), "submitted" | "under_review"))
+                .filter(|p| def.tor_id.is_none_or(|tor_id| p.tor_id == tor_id))
+                .filter_map(|p| {
+                    let submitted = NaiveDate::parse_from_str(p.submitted_date.get(..10)?, "%Y-%m-%d").ok()?;
+                    let days = (today - submitted).num_days();
+                    (days > def.parameter).then_some((days, p))
+                })
+                .collect();
+            // Longest waiting first
+            pending.sort_by_key(|(days, _)| std::cmp::Reverse(*days));
+            let table = ReportTable {
+                title: format!("{} (as of {})", def.label, today),
+                columns: vec!["ToR", "Proposal", "Status", "Submitted", "Days pending"],
+                rows: pending.into_iter()
+                    .map(|(days, p)| vec![p.tor_name, p.title, p.status.replace('_', " "), p.submitted_date, days.to_string()])
+                    .collect(),
+            };
+            Ok((table, (today, end)))
+        }
+        "meeting_attendance" => {
+            let fiscal = FiscalCalendar::load(pool).await;
+            let start = fiscal.quarter_start(today, def.parameter - 1);
+            // Fiscal quarters are calendar quarters shifted by the start month
+            let rows: Vec<(String, String, i64, i64, i64, i64)> = sqlx::query_as(
+                "SELECT t.label, \
+                        to_char(date_trunc('quarter', p_date.value::DATE - make_interval(months => $4)) \
+                            + make_interval(months => $4), 'YYYY-MM-DD') AS quarter, \
+                        COUNT(DISTINCT mtg.id) AS meetings, \
+                        COUNT(*) FILTER (WHERE a->>'status' = 'present') AS present, \
+                        COUNT(*) FILTER (WHERE a->>'status' = 'excused') AS excused, \
+                        COUNT(*) FILTER (WHERE a->>'status' = 'absent') AS absent \
+                 FROM entities m \
+                 JOIN entity_properties p_att ON p_att.entity_id = m.id AND p_att.key = 'structured_attendance' \
+                     AND p_att.value LIKE '[%' \
+                 CROSS JOIN LATERAL jsonb_array_elements(p_att.value::JSONB) a \
+                 JOIN relations r_min ON r_min.target_id = m.id \
+                     AND r_min.relation_type_id = ( \
+                         SELECT id FROM entities WHERE entity_type = 'relation_type' AND name = 'minutes_of') \
+                 JOIN entities mtg ON mtg.id = r_min.source_id \
+                 JOIN relations r_tor ON r_tor.source_id = mtg.id \
+                     AND r_tor.relation_type_id = ( \
+                         SELECT id FROM entities WHERE entity_type = 'relation_type' AND name = 'belongs_to_tor') \
+                 JOIN entities t ON t.id = r_tor.target_id AND t.entity_type = 'tor' \
+                 JOIN entity_properties p_date ON p_date.entity_id = mtg.id AND p_date.key = 'meeting_date' \
+                 WHERE m.entity_type = 'minutes' \
+                   AND p_date.value >= $1 AND p_date.value < $2 \
+                   AND ($3::BIGINT IS NULL OR t.id = $3) \
+                 GROUP BY t.id, t.label, quarter \
+                 ORDER BY t.label, t.id, quarter",
+            )
+            .bind(start.format("%Y-%m-%d").to_string())
+            .bind(end.format("%Y-%m-%d").to_string())
+            .bind(def.tor_id)
+            .bind(fiscal.offset_months() as i32)
+            .fetch_all(pool)
+            .await?;
+            let rows = rows.into_iter()
+                .map(|(tor, quarter, meetings, present, excused, absent)| {
+                    let recorded = present + excused + absent;
+                    let rate = if recorded == 0 { 0 } else { present * 100 / recorded };
+                    vec![
+                        tor,
+                        NaiveDate::parse_from_str(&quarter, "%Y-%m-%d").map_or(quarter, |d| fiscal.label(d)),
+                        meetings.to_string(),
+                        present.to_string(),
+                        excused.to_string(),
+                        absent.to_string(),
+                        format!("{}%", rate),
+                    ]
+                })
+                .collect();
+            let table = ReportTable {
+                title: format!("{} ({} to {})", def.label, start, today),
+                columns: vec!["ToR", "Quarter", "Meetings", "Present", "Excused", "Absent", "Attendance"],
+                rows,
+            };
+            Ok((table, (start, end)))
+        }
+        _ => Err(AppError::NotFound),
+    }
+}
+
+/// Store a run's report in a recipient's inbox and prune their older
+/// deliveries of this definition. Returns the delivery id.
+pub async fn record_delivery(
+    pool: &PgPool,
+    def: &ReportDefinition,
+    user_id: i64,
+    period: (NaiveDate, NaiveDate),
+    title: &str,
+    content: &str,
+) -> Result<i64, sqlx::Error> {
+    let start = period.0.format("%Y-%m-%d").to_string();
+    let name = entity::available_name(pool, "report_delivery", &format!("def{}_u{}_{}", def.id, user_id, start)).await?;
+    let id = entity::create(pool, "report_delivery", &name, title).await?;
+    entity::set_properties(pool, id, &[
+        ("definition_id", &def.id.to_string()),
+        ("user_id", &user_id.to_string()),
+        ("format", &def.format),
+        ("period_start", &start),
+        ("period_end", &period.1.format("%Y-%m-%d").to_string()),
+        ("generated_at", &Local::now().format("%Y-%m-%d %H:%M").to_string()),
+        ("content", content),
+    ]).await?;
+
+    sqlx::query(
+        "DELETE FROM entities WHERE entity_type = 'report_delivery' AND id IN ( \
+             SELECT d.entity_id FROM entity_properties d \
+             JOIN entity_properties u ON u.entity_id = d.entity_id AND u.key = 'user_id' AND u.value = $2 \
+             WHERE d.key = 'definition_id' AND d.value = $1 \
+             ORDER BY d.entity_id DESC OFFSET $3)",
+    )
+    .bind(def.id.to_string())
+    .bind(user_id.to_string())
+    .bind(KEEP_DELIVERIES)
+    .execute(pool)
+    .await?;
+    Ok(id)
+}
+
+/// Record a run at `now` and schedule the next one.
+pub async fn mark_run(pool: &PgPool, def: &ReportDefinition, now: DateTime<Utc>) -> Result<(), sqlx::Error> {
+    let next_run_at = next_run(&def.schedule, now);
+    entity::set_properties(pool, def.id, &[
+        ("last_run_at", &now.to_rfc3339()),
+        ("next_run_at", &next_run_at),
+    ]).await
+}
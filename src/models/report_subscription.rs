@@ -0,0 +1,451 @@
+//! Recurring report subscriptions.
+//!
+//! A user subscribes to a report over their own ToRs with a format and a
+//! frequency. The warning scheduler generates due reports, stores each one as
+//! a `report_delivery` and notifies the owner, who downloads it from their
+//! subscriptions page. Only the latest [`KEEP_DELIVERIES`] are kept.
+
+use chrono::{Duration, Months, NaiveDate};
+use sqlx::PgPool;
+
+use super::fiscal::FiscalCalendar;
+use super::{entity, opinion, proposal, survey, tor};
+use crate::errors::AppError;
+
+/// Reports that can be subscribed to, as (key, label).
+pub const REPORTS: &[(&str, &str)] = &[
+    ("proposal_throughput", "Proposal throughput for my ToRs"),
+    ("decision_register", "Decisions in my ToRs"),
+    ("governance_health", "Governance health of my ToRs"),
+];
+
+/// Delivery formats, as (key, label). HTML deliveries are print-ready.
+pub const FORMATS: &[(&str, &str)] = &[
+    ("csv", "CSV"),
+    ("html", "Printable HTML"),
+];
+
+/// Delivery frequencies, as (key, label).
+pub const FREQUENCIES: &[(&str, &str)] = &[
+    ("daily", "Daily"),
+    ("weekly", "Weekly"),
+    ("monthly", "Monthly"),
+    ("quarterly", "Quarterly (fiscal quarters)"),
+];
+
+/// Deliveries kept per subscription; older ones are pruned.
+pub const KEEP_DELIVERIES: i64 = 12;
+
+fn label_of(options: &'static [(&'static str, &'static str)], key: &str) -> Option<&'static str> {
+    options.iter().find(|(k, _)| *k == key).map(|(_, label)| *label)
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct ReportSubscription {
+    pub id: i64,
+    pub is_active: bool,
+    pub user_id: i64,
+    pub user_name: String,
+    pub report: String,
+    pub format: String,
+    pub frequency: String,
+    /// Next delivery date, `YYYY-MM-DD`.
+    pub next_run: String,
+    pub last_run: String,
+}
+
+impl ReportSubscription {
+    pub fn report_label(&self) -> &str {
+        label_of(REPORTS, &self.report).unwrap_or(self.report.as_str())
+    }
+
+    pub fn format_label(&self) -> &str {
+        label_of(FORMATS, &self.format).unwrap_or(self.format.as_str())
+    }
+
+    pub fn frequency_label(&self) -> &str {
+        label_of(FREQUENCIES, &self.frequency).unwrap_or(self.frequency.as_str())
+    }
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct ReportDelivery {
+    pub id: i64,
+    /// The subscription that made it, or 0 for a scheduled report definition.
+    pub subscription_id: i64,
+    /// The report definition that made it, or 0 for a subscription.
+    pub definition_id: i64,
+    pub user_id: i64,
+    pub title: String,
+    pub format: String,
+    /// First day of the period, inclusive.
+    pub period_start: String,
+    /// Last day of the period, exclusive.
+    pub period_end: String,
+    pub generated_at: String,
+    pub content: String,
+}
+
+impl ReportDelivery {
+    pub fn content_type(&self) -> &'static str {
+        if self.format == "csv" { "text/csv; charset=utf-8" } else { "text/html; charset=utf-8" }
+    }
+
+    pub fn filename(&self) -> String {
+        if self.definition_id != 0 {
+            format!("scheduled-{}-{}.{}", self.definition_id, self.period_start, self.format)
+        } else {
+            format!("report-{}-{}.{}", self.subscription_id, self.period_start, self.format)
+        }
+    }
+}
+
+/// A generated report: a title and a table of text cells.
+#[derive(Debug, Clone)]
+pub struct ReportTable {
+    pub title: String,
+    pub columns: Vec<&'static str>,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl ReportTable {
+    pub fn to_csv(&self) -> String {
+        let mut out = String::new();
+        let header: Vec<String> = self.columns.iter().map(|c| escape_csv(c)).collect();
+        out.push_str(&header.join(","));
+        out.push('\n');
+        for row in &self.rows {
+            let cells: Vec<String> = row.iter().map(|c| escape_csv(c)).collect();
+            out.push_str(&cells.join(","));
+            out.push('\n');
+        }
+        out
+    }
+}
+
+fn escape_csv(s: &str) -> String {
+    if s.contains(',') || s.contains('"') || s.contains('\n') {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+/// Validate a subscription request. Returns a list of problems.
+pub fn validate(report: &str, format: &str, frequency: &str) -> Vec<String> {
+    let mut errors = Vec::new();
+    if label_of(REPORTS, report).is_none() {
+        errors.push("Choose a report".to_string());
+    }
+    if label_of(FORMATS, format).is_none() {
+        errors.push("Choose a format".to_string());
+    }
+    if label_of(FREQUENCIES, frequency).is_none() {
+        errors.push("Choose how often to receive the report".to_string());
+    }
+    errors
+}
+
+/// The delivery date after `date` for a frequency. Quarterly deliveries fall
+/// on the first day of each fiscal quarter.
+pub fn next_after(frequency: &str, date: NaiveDate, fiscal: &FiscalCalendar) -> NaiveDate {
+    match frequency {
+        "daily" => date + Duration::days(1),
+        "quarterly" => fiscal.quarter_start(date, -1),
+        "monthly" => date.checked_add_months(Months::new(1)).unwrap_or(date + Duration::days(30)),
+        _ => date + Duration::days(7),
+    }
+}
+
+/// The period a delivery on `date` covers: one frequency back, up to `date`.
+/// A quarterly delivery covers the fiscal quarter before it.
+pub fn period_before(frequency: &str, date: NaiveDate, fiscal: &FiscalCalendar) -> (NaiveDate, NaiveDate) {
+    let start = match frequency {
+        "daily" => date - Duration::days(1),
+        "quarterly" => fiscal.quarter_start(date - Duration::days(1), 0),
+        "monthly" => date.checked_sub_months(Months::new(1)).unwrap_or(date - Duration::days(30)),
+        _ => date - Duration::days(7),
+    };
+    (start, date)
+}
+
+const SELECT_SUBSCRIPTION: &str =
+    "SELECT e.id, e.is_active, \
+            COALESCE(NULLIF(p_user.value, '')::BIGINT, 0) AS user_id, \
+            COALESCE(u.label, '') AS user_name, \
+            COALESCE(p_report.value, '') AS report, \
+            COALESCE(p_format.value, '') AS format, \
+            COALESCE(p_freq.value, '') AS frequency, \
+            COALESCE(p_next.value, '') AS next_run, \
+            COALESCE(p_last.value, '') AS last_run \
+     FROM entities e \
+     LEFT JOIN entity_properties p_user ON e.id = p_user.entity_id AND p_user.key = 'user_id' \
+     LEFT JOIN entities u ON u.id::TEXT = p_user.value \
+     LEFT JOIN entity_properties p_report ON e.id = p_report.entity_id AND p_report.key = 'report' \
+     LEFT JOIN entity_properties p_format ON e.id = p_format.entity_id AND p_format.key = 'format' \
+     LEFT JOIN entity_properties p_freq ON e.id = p_freq.entity_id AND p_freq.key = 'frequency' \
+     LEFT JOIN entity_properties p_next ON e.id = p_next.entity_id AND p_next.key = 'next_run' \
+     LEFT JOIN entity_properties p_last ON e.id = p_last.entity_id AND p_last.key = 'last_run' \
+     WHERE e.entity_type = 'report_subscription'";
+
+const SELECT_DELIVERY: &str =
+    "SELECT e.id, \
+            COALESCE(NULLIF(p_sub.value, '')::BIGINT, 0) AS subscription_id, \
+            COALESCE(NULLIF(p_def.value, '')::BIGINT, 0) AS definition_id, \
+            COALESCE(NULLIF(p_user.value, '')::BIGINT, 0) AS user_id, \
+            e.label AS title, \
+            COALESCE(p_format.value, '') AS format, \
+            COALESCE(p_start.value, '') AS period_start, \
+            COALESCE(p_end.value, '') AS period_end, \
+            COALESCE(p_gen.value, '') AS generated_at, \
+            COALESCE(p_content.value, '') AS content \
+     FROM entities e \
+     LEFT JOIN entity_properties p_sub ON e.id = p_sub.entity_id AND p_sub.key = 'subscription_id' \
+     LEFT JOIN entity_properties p_def ON e.id = p_def.entity_id AND p_def.key = 'definition_id' \
+     LEFT JOIN entity_properties p_user ON e.id = p_user.entity_id AND p_user.key = 'user_id' \
+     LEFT JOIN entity_properties p_format ON e.id = p_format.entity_id AND p_format.key = 'format' \
+     LEFT JOIN entity_properties p_start ON e.id = p_start.entity_id AND p_start.key = 'period_start' \
+     LEFT JOIN entity_properties p_end ON e.id = p_end.entity_id AND p_end.key = 'period_end' \
+     LEFT JOIN entity_properties p_gen ON e.id = p_gen.entity_id AND p_gen.key = 'generated_at' \
+     LEFT JOIN entity_properties p_content ON e.id = p_content.entity_id AND p_content.key = 'content' \
+     WHERE e.entity_type = 'report_delivery'";
+
+/// Subscribe a user to a report. The first delivery is one period from `today`.
+pub async fn create(
+    pool: &PgPool,
+    user_id: i64,
+    report: &str,
+    format: &str,
+    frequency: &str,
+    today: NaiveDate,
+) -> Result<i64, sqlx::Error> {
+    let name = entity::available_name(
+        pool, "report_subscription", &format!("u{}_{}_{}", user_id, report, frequency),
+    ).await?;
+    let label = label_of(REPORTS, report).unwrap_or(report);
+    let id = entity::create(pool, "report_subscription", &name, label).await?;
+    let fiscal = FiscalCalendar::load(pool).await;
+    entity::set_properties(pool, id, &[
+        ("user_id", &user_id.to_string()),
+        ("report", report),
+        ("format", format),
+        ("frequency", frequency),
+        ("next_run", &next_after(frequency, today, &fiscal).format("%Y-%m-%d").to_string()),
+    ]).await?;
+    Ok(id)
+}
+
+pub async fn find_by_id(pool: &PgPool, id: i64) -> Result<Option<ReportSubscription>, sqlx::Error> {
+    sqlx::query_as::<_, ReportSubscription>(&format!("{} AND e.id = $1", SELECT_SUBSCRIPTION))
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+}
+
+/// A user's subscriptions, in creation order.
+pub async fn find_for_user(pool: &PgPool, user_id: i64) -> Result<Vec<ReportSubscription>, sqlx::Error> {
+    sqlx::query_as::<_, ReportSubscription>(&format!("{} AND p_user.value = $1 ORDER BY e.id", SELECT_SUBSCRIPTION))
+        .bind(user_id.to_string())
+        .fetch_all(pool)
+        .await
+}
+
+/// All active subscriptions, soonest delivery first.
+pub async fn find_all_active(pool: &PgPool) -> Result<Vec<ReportSubscription>, sqlx::Error> {
+    sqlx::query_as::<_, ReportSubscription>(
+        &format!("{} AND e.is_active = true ORDER BY p_next.value, u.label", SELECT_SUBSCRIPTION),
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Active subscriptions whose next delivery is on or before `today`.
+pub async fn find_due(pool: &PgPool, today: &str) -> Result<Vec<ReportSubscription>, sqlx::Error> {
+    sqlx::query_as::<_, ReportSubscription>(
+        &format!("{} AND e.is_active = true AND p_next.value <= $1 ORDER BY e.id", SELECT_SUBSCRIPTION),
+    )
+    .bind(today)
+    .fetch_all(pool)
+    .await
+}
+
+/// Pause or resume a subscription. Returns false if no such subscription.
+pub async fn set_active(pool: &PgPool, id: i64, active: bool) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "UPDATE entities SET is_active = $2, updated_at = NOW() WHERE id = $1 AND entity_type = 'report_subscription'"
+    )
+    .bind(id)
+    .bind(active)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Delete a subscription together with its stored deliveries.
+pub async fn delete(pool: &PgPool, id: i64) -> Result<bool, sqlx::Error> {
+    sqlx::query(
+        "DELETE FROM entities WHERE entity_type = 'report_delivery' AND id IN ( \
+             SELECT entity_id FROM entity_properties WHERE key = 'subscription_id' AND value = $1)",
+    )
+    .bind(id.to_string())
+    .execute(pool)
+    .await?;
+    let result = sqlx::query("DELETE FROM entities WHERE id = $1 AND entity_type = 'report_subscription'")
+        .bind(id)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// A user's stored deliveries, newest first, without their content.
+pub async fn find_deliveries_for_user(pool: &PgPool, user_id: i64) -> Result<Vec<ReportDelivery>, sqlx::Error> {
+    sqlx::query_as::<_, ReportDelivery>(&format!(
+        "SELECT id, subscription_id, definition_id, user_id, title, format, period_start, period_end, generated_at, \
+                '' AS content \
+         FROM ({}) d WHERE user_id = $1 ORDER BY generated_at DESC, id DESC",
+        SELECT_DELIVERY
+    ))
+    .bind(user_id)
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn find_delivery(pool: &PgPool, id: i64) -> Result<Option<ReportDelivery>, sqlx::Error> {
+    sqlx::query_as::<_, ReportDelivery>(&format!("{} AND e.id = $1", SELECT_DELIVERY))
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+}
+
+/// Store a generated report for a subscription, advance its schedule past
+/// `today` and prune old deliveries. Returns the delivery id.
+pub async fn record_delivery(
+    pool: &PgPool,
+    sub: &ReportSubscription,
+    period: (NaiveDate, NaiveDate),
+    title: &str,
+    content: &str,
+    today: NaiveDate,
+) -> Result<i64, sqlx::Error> {
+    let start = period.0.format("%Y-%m-%d").to_string();
+    let name = entity::available_name(pool, "report_delivery", &format!("sub{}_{}", sub.id, start)).await?;
+    let id = entity::create(pool, "report_delivery", &name, title).await?;
+    entity::set_properties(pool, id, &[
+        ("subscription_id", &sub.id.to_string()),
+        ("user_id", &sub.user_id.to_string()),
+        ("format", &sub.format),
+        ("period_start", &start),
+        ("period_end", &period.1.format("%Y-%m-%d").to_string()),
+        ("generated_at", &chrono::Local::now().format("%Y-%m-%d %H:%M").to_string()),
+        ("content", content),
+    ]).await?;
+
+    // Catch up without backfilling: skip any periods missed while down.
+    let fiscal = FiscalCalendar::load(pool).await;
+    let mut next = next_after(&sub.frequency, today, &fiscal);
+    while next <= today {
+        next = next_after(&sub.frequency, next, &fiscal);
+    }
+    entity::set_properties(pool, sub.id, &[
+        ("next_run", &next.format("%Y-%m-%d").to_string()),
+        ("last_run", &today.format("%Y-%m-%d").to_string()),
+    ]).await?;
+
+    sqlx::query(
+        "DELETE FROM entities WHERE entity_type = 'report_delivery' AND id IN ( \
+             SELECT entity_id FROM entity_properties WHERE key = 'subscription_id' AND value = $1 \
+             ORDER BY entity_id DESC OFFSET $2)",
+    )
+    .bind(sub.id.to_string())
+    .bind(KEEP_DELIVERIES)
+    .execute(pool)
+    .await?;
+    Ok(id)
+}
+
+/// Build a subscription's report for `[start, end)`, limited to the owner's ToRs.
+pub async fn build_report(
+    pool: &PgPool,
+    sub: &ReportSubscription,
+    period: (NaiveDate, NaiveDate),
+) -> Result<ReportTable, AppError> {
+    let start = period.0.format("%Y-%m-%d").to_string();
+    let end = period.1.format("%Y-%m-%d").to_string();
+    let in_period = |date: &str| {
+        let day = date.get(..10).unwrap_or(date);
+        day >= start.as_str() && day < end.as_str()
+    };
+    let title = format!("{} ({} to {})", sub.report_label(), start, period.1 - Duration::days(1));
+
+    match sub.report.as_str() {
+        "proposal_throughput" => {
+            let proposals = proposal::find_all_cross_tor(pool, Some(sub.user_id)).await?;
+            let rows = tor::find_user_tors(pool, sub.user_id).await.into_iter()
+                .map(|t| {
+                    let submitted: Vec<_> = proposals.iter()
+                        .filter(|p| p.tor_id == t.tor_id && p.status != "draft" && in_period(&p.submitted_date))
+                        .collect();
+                    let count = |status: &str| submitted.iter().filter(|p| p.status == status).count();
+                    let (approved, rejected) = (count("approved"), count("rejected"));
+                    vec![
+                        t.tor_label,
+                        submitted.len().to_string(),
+                        approved.to_string(),
+                        rejected.to_string(),
+                        (submitted.len() - approved - rejected).to_string(),
+                    ]
+                })
+                .collect();
+            Ok(ReportTable {
+                title,
+                columns: vec!["ToR", "Submitted", "Approved", "Rejected", "Open"],
+                rows,
+            })
+        }
+        "decision_register" => {
+            let tor_ids = tor::find_tor_ids_for_user(pool, sub.user_id).await;
+            let rows = opinion::find_decision_register(pool, None).await?.into_iter()
+                .filter(|d| tor_ids.contains(&d.tor_id) && in_period(&d.decided_date))
+                .map(|d| {
+                    let decided_by = d.decided_by();
+                    vec![
+                        d.decided_date,
+                        d.tor_label,
+                        d.agenda_point_title,
+                        d.selected_coa_title,
+                        d.decision_rationale,
+                        decided_by,
+                    ]
+                })
+                .collect();
+            Ok(ReportTable {
+                title,
+                columns: vec!["Date", "ToR", "Agenda point", "Decision", "Rationale", "Decided by"],
+                rows,
+            })
+        }
+        "governance_health" => {
+            // The latest scores, whatever the period
+            let mut rows = Vec::new();
+            for t in tor::find_user_tors(pool, sub.user_id).await {
+                let row = match survey::find_health_score(pool, t.tor_id).await? {
+                    Some(h) => vec![
+                        t.tor_label,
+                        h.period.clone(),
+                        h.score.to_string(),
+                        h.band().to_string(),
+                        format!("{} of {}", h.responses, h.members),
+                    ],
+                    None => vec![t.tor_label, "Not assessed".to_string(), String::new(), String::new(), String::new()],
+                };
+                rows.push(row);
+            }
+            Ok(ReportTable {
+                title,
+                columns: vec!["ToR", "Assessment", "Score", "Health", "Responses"],
+                rows,
+            })
+        }
+        _ => Err(AppError::NotFound),
+    }
+}
@@ -0,0 +1,159 @@
+//! Revision history for edited text.
+//!
+//! Updating a proposal, a COA or a minutes section first keeps the fields
+//! about to be overwritten as a `revision` entity, linked to what it is a
+//! revision of by `revision_of`. A revision holds every tracked field as it
+//! was before that edit, so detail pages can show what each edit changed and
+//! an older version can be put back; restoring is itself an edit, so it can
+//! be undone the same way.
+
+use sqlx::PgPool;
+
+use super::minutes::diff::{diff_lines, DiffLine};
+use super::{entity, relation};
+
+/// The tracked fields of an entity as they were before one edit.
+#[derive(Debug, Clone)]
+pub struct Revision {
+    pub id: i64,
+    /// The proposal, COA or minutes section this is a revision of.
+    pub target_id: i64,
+    pub fields: Vec<(String, String)>,
+    pub edited_by: String,
+    pub edited_at: String,
+}
+
+impl Revision {
+    /// A field's value in this revision; empty if it was not tracked.
+    pub fn field(&self, key: &str) -> &str {
+        self.fields.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str()).unwrap_or("")
+    }
+}
+
+/// How one field changed in an edit.
+#[derive(Debug, Clone)]
+pub struct FieldChange {
+    pub field: String,
+    pub lines: Vec<DiffLine>,
+}
+
+impl FieldChange {
+    /// The field name for headings ("rationale" → "Rationale").
+    pub fn label(&self) -> String {
+        let mut chars = self.field.chars();
+        match chars.next() {
+            Some(first) => first.to_uppercase().chain(chars).collect(),
+            None => String::new(),
+        }
+    }
+}
+
+/// A revision and what the edit that replaced it changed.
+#[derive(Debug, Clone)]
+pub struct RevisionDiff {
+    pub revision: Revision,
+    pub changes: Vec<FieldChange>,
+}
+
+#[derive(sqlx::FromRow)]
+struct RevisionRow {
+    id: i64,
+    target_id: i64,
+    snapshot: String,
+    edited_by: String,
+    edited_at: String,
+}
+
+impl From<RevisionRow> for Revision {
+    fn from(row: RevisionRow) -> Self {
+        Revision {
+            id: row.id,
+            target_id: row.target_id,
+            fields: serde_json::from_str(&row.snapshot).unwrap_or_default(),
+            edited_by: row.edited_by,
+            edited_at: row.edited_at,
+        }
+    }
+}
+
+/// Keep the current values of the fields as a revision before they are
+/// replaced by `new_values`. Does nothing, returning false, when no field
+/// changes or the target does not exist.
+pub async fn record(
+    pool: &PgPool,
+    target_id: i64,
+    new_values: &[(&str, &str)],
+    edited_by: i64,
+) -> Result<bool, sqlx::Error> {
+    if entity::find_by_id(pool, target_id).await?.is_none() {
+        return Ok(false);
+    }
+    let mut previous = Vec::with_capacity(new_values.len());
+    for (key, _) in new_values {
+        let value = entity::get_property(pool, target_id, key).await?.unwrap_or_default();
+        previous.push((key.to_string(), value));
+    }
+    if previous.iter().zip(new_values).all(|((_, old), (_, new))| old == new) {
+        return Ok(false);
+    }
+
+    let base = format!("revision_{}", target_id);
+    let name = entity::available_name(pool, "revision", &base).await?;
+    let revision_id = entity::create(pool, "revision", &name, &name).await?;
+    let snapshot = serde_json::to_string(&previous).unwrap_or_default();
+    entity::set_properties(pool, revision_id, &[
+        ("snapshot", &snapshot),
+        ("edited_by", &edited_by.to_string()),
+    ]).await?;
+    relation::create(pool, "revision_of", revision_id, target_id).await?;
+    Ok(true)
+}
+
+const SELECT_REVISION: &str =
+    "SELECT rev.id, r.target_id, \
+            COALESCE(p_snap.value, '[]') AS snapshot, \
+            COALESCE(u.label, 'system') AS edited_by, \
+            TO_CHAR(rev.created_at, 'YYYY-MM-DD HH24:MI') AS edited_at \
+     FROM entities rev \
+     JOIN relations r ON r.source_id = rev.id \
+     JOIN entities rt ON rt.id = r.relation_type_id AND rt.name = 'revision_of' \
+     LEFT JOIN entity_properties p_snap ON rev.id = p_snap.entity_id AND p_snap.key = 'snapshot' \
+     LEFT JOIN entity_properties p_by ON rev.id = p_by.entity_id AND p_by.key = 'edited_by' \
+     LEFT JOIN entities u ON u.entity_type = 'user' AND u.id::TEXT = p_by.value \
+     WHERE rev.entity_type = 'revision'";
+
+/// Revisions of the given entities, newest first.
+pub async fn find_for(pool: &PgPool, target_ids: &[i64]) -> Result<Vec<Revision>, sqlx::Error> {
+    let rows: Vec<RevisionRow> = sqlx::query_as(
+        &format!("{SELECT_REVISION} AND r.target_id = ANY($1) ORDER BY rev.id DESC"),
+    )
+    .bind(target_ids)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows.into_iter().map(Revision::from).collect())
+}
+
+pub async fn find_by_id(pool: &PgPool, id: i64) -> Result<Option<Revision>, sqlx::Error> {
+    let row: Option<RevisionRow> = sqlx::query_as(&format!("{SELECT_REVISION} AND rev.id = $1"))
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+    Ok(row.map(Revision::from))
+}
+
+/// Pair each revision of one entity, newest first, with what the following
+/// edit changed: the diff from it to the next newer revision, or to the
+/// current values for the newest.
+pub fn diffs(revisions: Vec<Revision>, current: &[(&str, &str)]) -> Vec<RevisionDiff> {
+    let mut newer: Vec<(String, String)> = current.iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+    revisions.into_iter().map(|revision| {
+        let changes = revision.fields.iter().filter_map(|(key, old)| {
+            let new = newer.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str()).unwrap_or("");
+            (old != new).then(|| FieldChange { field: key.clone(), lines: diff_lines(old, new) })
+        }).collect();
+        newer = revision.fields.clone();
+        RevisionDiff { revision, changes }
+    }).collect()
+}
@@ -45,12 +45,14 @@ pub async fn find_by_id(pool: &PgPool, id: i64) -> Result<Option<RoleDisplay>, s
     Ok(role)
 }
 
-/// Find a role with its description for editing.
+/// Find a role with its description and landing page for editing.
 pub async fn find_detail_by_id(pool: &PgPool, id: i64) -> Result<Option<RoleDetail>, sqlx::Error> {
     let role = sqlx::query_as::<_, RoleDetail>(
-        "SELECT e.id, e.name, e.label, COALESCE(p_desc.value, '') AS description \
+        "SELECT e.id, e.name, e.label, COALESCE(p_desc.value, '') AS description, \
+                COALESCE(p_land.value, '') AS landing_page \
          FROM entities e \
          LEFT JOIN entity_properties p_desc ON e.id = p_desc.entity_id AND p_desc.key = 'description' \
+         LEFT JOIN entity_properties p_land ON e.id = p_land.entity_id AND p_land.key = 'landing_page' \
          WHERE e.id = $1 AND e.entity_type = 'role'"
     )
     .bind(id)
@@ -59,6 +61,44 @@ pub async fn find_detail_by_id(pool: &PgPool, id: i64) -> Result<Option<RoleDeta
     Ok(role)
 }
 
+/// Set or clear (empty path) the page users with this role land on after login.
+pub async fn set_landing_page(pool: &PgPool, role_id: i64, path: &str) -> Result<(), sqlx::Error> {
+    if path.is_empty() {
+        sqlx::query("DELETE FROM entity_properties WHERE entity_id = $1 AND key = 'landing_page'")
+            .bind(role_id)
+            .execute(pool)
+            .await?;
+    } else {
+        sqlx::query(
+            "INSERT INTO entity_properties (entity_id, key, value) VALUES ($1, 'landing_page', $2) \
+             ON CONFLICT(entity_id, key) DO UPDATE SET value = excluded.value"
+        )
+        .bind(role_id)
+        .bind(path)
+        .execute(pool)
+        .await?;
+    }
+    Ok(())
+}
+
+/// Landing page of the first of the user's roles (by role sort order) that sets one.
+pub async fn find_landing_page_for_user(pool: &PgPool, user_id: i64) -> Result<Option<String>, sqlx::Error> {
+    let row = sqlx::query_as::<_, (String,)>(
+        "SELECT p.value \
+         FROM relations r \
+         JOIN entities rt ON rt.id = r.relation_type_id AND rt.name = 'has_role' \
+         JOIN entities role ON role.id = r.target_id \
+         JOIN entity_properties p ON p.entity_id = role.id AND p.key = 'landing_page' \
+         WHERE r.source_id = $1 AND p.value <> '' \
+         ORDER BY role.sort_order, role.id \
+         LIMIT 1"
+    )
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?;
+    Ok(row.map(|r| r.0))
+}
+
 /// Helper struct for reading permission checkbox rows from the DB.
 /// The `checked` column comes as an integer (0 or 1) from the CASE expression.
 #[derive(sqlx::FromRow)]
@@ -26,6 +26,7 @@ pub struct RoleDetail {
     pub name: String,
     pub label: String,
     pub description: String,
+    pub landing_page: String,
 }
 
 /// A permission with its checked state for the role form.
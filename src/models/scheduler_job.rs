@@ -0,0 +1,247 @@
+//! Persisted state of the background scheduler's jobs.
+//!
+//! Each job in [`crate::warnings::scheduler::JOBS`] has a `scheduler_job`
+//! entity named after its key. Its properties hold the controls an admin
+//! sets (`paused`, `run_requested`) and what the scheduler recorded: when the
+//! job runs next, how its last run went and how long it took, and its last
+//! failure with the context it was raised in. Times are RFC 3339 UTC.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+
+use crate::models::entity;
+
+/// How a job run ended.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RunOutcome {
+    /// The run finished; the message sums up what it did, or is empty.
+    Succeeded(String),
+    /// The run returned an error or panicked.
+    Failed { error: String, context: String },
+}
+
+#[derive(Debug, Clone)]
+pub struct JobState {
+    pub id: i64,
+    pub key: String,
+    pub label: String,
+    pub every_secs: i64,
+    pub paused: bool,
+    pub run_requested: bool,
+    pub running: bool,
+    pub next_run_at: Option<DateTime<Utc>>,
+    pub last_started_at: Option<DateTime<Utc>>,
+    pub last_duration_ms: Option<i64>,
+    /// `ok` or `failed`; empty before the first run.
+    pub last_outcome: String,
+    pub last_message: String,
+    pub last_failed_at: Option<DateTime<Utc>>,
+    pub last_error: String,
+    pub last_error_context: String,
+    pub run_count: i64,
+    pub failure_count: i64,
+}
+
+fn parse_time(value: Option<&String>) -> Option<DateTime<Utc>> {
+    value.and_then(|v| DateTime::parse_from_rfc3339(v).ok()).map(|t| t.with_timezone(&Utc))
+}
+
+fn format_time(at: DateTime<Utc>) -> String {
+    at.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+}
+
+impl JobState {
+    fn from_properties(id: i64, key: String, label: String, props: &HashMap<String, String>) -> Self {
+        let text = |k: &str| props.get(k).cloned().unwrap_or_default();
+        let number = |k: &str| props.get(k).and_then(|v| v.parse::<i64>().ok());
+        JobState {
+            id,
+            key,
+            label,
+            every_secs: number("every_secs").unwrap_or(0),
+            paused: props.get("paused").is_some_and(|v| v == "true"),
+            run_requested: props.get("run_requested").is_some_and(|v| v == "true"),
+            running: props.get("running").is_some_and(|v| v == "true"),
+            next_run_at: parse_time(props.get("next_run_at")),
+            last_started_at: parse_time(props.get("last_started_at")),
+            last_duration_ms: number("last_duration_ms"),
+            last_outcome: text("last_outcome"),
+            last_message: text("last_message"),
+            last_failed_at: parse_time(props.get("last_failed_at")),
+            last_error: text("last_error"),
+            last_error_context: text("last_error_context"),
+            run_count: number("run_count").unwrap_or(0),
+            failure_count: number("failure_count").unwrap_or(0),
+        }
+    }
+
+    /// Whether the scheduler should run the job at `now`: an admin asked
+    /// for a run, or it is active and its next run time has come.
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        !self.running
+            && (self.run_requested || (!self.paused && self.next_run_at.is_none_or(|at| at <= now)))
+    }
+
+    /// Interval for display, e.g. "5 min".
+    pub fn every_label(&self) -> String {
+        match self.every_secs {
+            s if s > 0 && s % 3600 == 0 => format!("{} h", s / 3600),
+            s if s > 0 && s % 60 == 0 => format!("{} min", s / 60),
+            s => format!("{} s", s),
+        }
+    }
+
+    pub fn next_run_label(&self) -> String {
+        self.next_run_at.map(format_time).unwrap_or_default()
+    }
+
+    pub fn last_started_label(&self) -> String {
+        self.last_started_at.map(format_time).unwrap_or_default()
+    }
+
+    pub fn last_failed_label(&self) -> String {
+        self.last_failed_at.map(format_time).unwrap_or_default()
+    }
+}
+
+/// Create or refresh the state entity of each job, as (key, label, interval
+/// in seconds). New jobs are due at once. A run left marked as running by a
+/// stopped process is recorded as interrupted.
+pub async fn register(pool: &PgPool, jobs: &[(&str, &str, i64)], now: DateTime<Utc>) -> Result<(), sqlx::Error> {
+    for (sort_order, (key, label, every_secs)) in jobs.iter().enumerate() {
+        let id = match entity::find_by_type_and_name(pool, "scheduler_job", key).await? {
+            Some(existing) => {
+                entity::update(pool, existing.id, key, label).await?;
+                existing.id
+            }
+            None => {
+                let id = entity::create_with_sort(pool, "scheduler_job", key, label, sort_order as i64).await?;
+                entity::set_property(pool, id, "next_run_at", &format_time(now)).await?;
+                id
+            }
+        };
+        entity::set_property(pool, id, "every_secs", &every_secs.to_string()).await?;
+        if entity::get_property(pool, id, "running").await?.as_deref() == Some("true") {
+            entity::set_properties(pool, id, &[
+                ("running", "false"),
+                ("last_outcome", "failed"),
+                ("last_failed_at", &format_time(now)),
+                ("last_error", "Interrupted: the application stopped during the run"),
+                ("last_error_context", ""),
+                ("next_run_at", &format_time(now)),
+            ]).await?;
+        }
+    }
+    Ok(())
+}
+
+/// All jobs' state in registration order.
+pub async fn find_all(pool: &PgPool) -> Result<Vec<JobState>, sqlx::Error> {
+    #[derive(sqlx::FromRow)]
+    struct Row {
+        id: i64,
+        name: String,
+        label: String,
+        key: Option<String>,
+        value: Option<String>,
+    }
+
+    let rows: Vec<Row> = sqlx::query_as(
+        "SELECT e.id, e.name, e.label, p.key, p.value \
+         FROM entities e \
+         LEFT JOIN entity_properties p ON p.entity_id = e.id \
+         WHERE e.entity_type = 'scheduler_job' \
+         ORDER BY e.sort_order, e.id",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut jobs: Vec<(i64, String, String, HashMap<String, String>)> = Vec::new();
+    for row in rows {
+        if jobs.last().is_none_or(|(last_id, ..)| *last_id != row.id) {
+            jobs.push((row.id, row.name, row.label, HashMap::new()));
+        }
+        if let (Some(key), Some(value), Some((.., props))) = (row.key, row.value, jobs.last_mut()) {
+            props.insert(key, value);
+        }
+    }
+    Ok(jobs.into_iter()
+        .map(|(id, key, label, props)| JobState::from_properties(id, key, label, &props))
+        .collect())
+}
+
+/// One job's state by key.
+pub async fn find_by_key(pool: &PgPool, key: &str) -> Result<Option<JobState>, sqlx::Error> {
+    let Some(e) = entity::find_by_type_and_name(pool, "scheduler_job", key).await? else {
+        return Ok(None);
+    };
+    let props = entity::get_properties(pool, e.id).await?;
+    Ok(Some(JobState::from_properties(e.id, e.name, e.label, &props)))
+}
+
+/// Pause or resume a job. Resuming does not run missed runs; the job next
+/// runs at its scheduled time, or at once when that has passed.
+pub async fn set_paused(pool: &PgPool, id: i64, paused: bool) -> Result<(), sqlx::Error> {
+    if paused {
+        entity::set_property(pool, id, "paused", "true").await
+    } else {
+        entity::delete_property(pool, id, "paused").await
+    }
+}
+
+/// Ask the scheduler to run a job on its next check, paused or not.
+pub async fn request_run(pool: &PgPool, id: i64) -> Result<(), sqlx::Error> {
+    entity::set_property(pool, id, "run_requested", "true").await
+}
+
+/// Record that a run started.
+pub async fn record_start(pool: &PgPool, id: i64, started_at: DateTime<Utc>) -> Result<(), sqlx::Error> {
+    entity::set_properties(pool, id, &[
+        ("running", "true"),
+        ("last_started_at", &format_time(started_at)),
+    ]).await?;
+    entity::delete_property(pool, id, "run_requested").await
+}
+
+/// Record how a run ended and when the job runs next.
+pub async fn record_finish(
+    pool: &PgPool,
+    id: i64,
+    duration_ms: i64,
+    outcome: &RunOutcome,
+    finished_at: DateTime<Utc>,
+    next_run_at: DateTime<Utc>,
+) -> Result<(), sqlx::Error> {
+    let props = entity::get_properties(pool, id).await?;
+    let count = |k: &str| props.get(k).and_then(|v| v.parse::<i64>().ok()).unwrap_or(0);
+    let run_count = (count("run_count") + 1).to_string();
+    let duration_ms = duration_ms.to_string();
+    let next_run_at = format_time(next_run_at);
+    let mut updates: Vec<(&str, &str)> = vec![
+        ("running", "false"),
+        ("run_count", &run_count),
+        ("last_duration_ms", &duration_ms),
+        ("next_run_at", &next_run_at),
+    ];
+    let failure_count;
+    let failed_at;
+    match outcome {
+        RunOutcome::Succeeded(message) => {
+            updates.push(("last_outcome", "ok"));
+            updates.push(("last_message", message));
+        }
+        RunOutcome::Failed { error, context } => {
+            failure_count = (count("failure_count") + 1).to_string();
+            failed_at = format_time(finished_at);
+            updates.push(("last_outcome", "failed"));
+            updates.push(("last_message", ""));
+            updates.push(("failure_count", &failure_count));
+            updates.push(("last_failed_at", &failed_at));
+            updates.push(("last_error", error));
+            updates.push(("last_error_context", context));
+        }
+    }
+    entity::set_properties(pool, id, &updates).await
+}
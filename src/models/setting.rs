@@ -12,7 +12,32 @@ pub struct SettingDisplay {
     pub setting_type: String, // "text", "number", "boolean"
 }
 
-/// Find all active settings, ordered by sort_order.
+/// A ToR-overridable setting with its global value and the ToR's override, if any.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct TorSettingDisplay {
+    pub name: String,
+    pub label: String,
+    pub description: String,
+    pub setting_type: String,
+    pub global_value: String,
+    pub override_value: Option<String>,
+}
+
+impl TorSettingDisplay {
+    /// The value in effect for the ToR: its override, else the global value.
+    pub fn effective_value(&self) -> &str {
+        self.override_value.as_deref().unwrap_or(&self.global_value)
+    }
+}
+
+/// Entity name of a ToR-scoped override for a global setting.
+fn override_name(name: &str, tor_id: i64) -> String {
+    format!("{}@tor:{}", name, tor_id)
+}
+
+/// Find all active global settings, ordered by sort_order.
+/// ToR-scoped overrides are excluded — see `find_for_tor` — as is branding,
+/// which has its own page (see `branding`).
 pub async fn find_all(pool: &PgPool) -> Result<Vec<SettingDisplay>, sqlx::Error> {
     let settings = sqlx::query_as::<_, SettingDisplay>(
         "SELECT e.id, e.name, e.label, \
@@ -24,6 +49,8 @@ pub async fn find_all(pool: &PgPool) -> Result<Vec<SettingDisplay>, sqlx::Error>
          LEFT JOIN entity_properties p_desc ON e.id = p_desc.entity_id AND p_desc.key = 'description' \
          LEFT JOIN entity_properties p_type ON e.id = p_type.entity_id AND p_type.key = 'setting_type' \
          WHERE e.entity_type = 'setting' AND e.is_active = true \
+           AND NOT EXISTS (SELECT 1 FROM entity_properties o WHERE o.entity_id = e.id AND o.key = 'overrides') \
+           AND e.name NOT LIKE 'branding.%' \
          ORDER BY e.sort_order, e.id"
     )
     .fetch_all(pool)
@@ -31,6 +58,164 @@ pub async fn find_all(pool: &PgPool) -> Result<Vec<SettingDisplay>, sqlx::Error>
     Ok(settings)
 }
 
+/// Declared value type of a known setting, with its allowed range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettingKind {
+    Text,
+    Bool,
+    Int { min: i64, max: i64 },
+    /// Whole number of days, read back as a `chrono::Duration`.
+    Days { min: i64, max: i64 },
+}
+
+/// Declaration of a known setting: its type, allowed range and default value.
+#[derive(Debug, Clone, Copy)]
+pub struct SettingDef {
+    pub name: &'static str,
+    pub kind: SettingKind,
+    pub default: &'static str,
+}
+
+/// All settings the application reads. Values saved from `/settings` are
+/// validated against these; typed getters fall back to `default`.
+pub const DEFINITIONS: &[SettingDef] = &[
+    SettingDef { name: "app.name", kind: SettingKind::Text, default: "Ahlt" },
+    SettingDef { name: "app.description", kind: SettingKind::Text, default: "" },
+    SettingDef { name: "audit.enabled", kind: SettingKind::Bool, default: "false" },
+    SettingDef { name: "audit.log_path", kind: SettingKind::Text, default: "data/audit/" },
+    SettingDef { name: "audit.retention_days", kind: SettingKind::Days { min: 0, max: 3650 }, default: "90" },
+    SettingDef { name: "audit.read_mode", kind: SettingKind::Bool, default: "true" },
+    SettingDef { name: "audit.read_retention_days", kind: SettingKind::Days { min: 1, max: 3650 }, default: "365" },
+    SettingDef { name: "suggestions.stale_after_days", kind: SettingKind::Days { min: 0, max: 3650 }, default: "0" },
+    SettingDef { name: "suggestions.stale_warning_days", kind: SettingKind::Days { min: 1, max: 365 }, default: "7" },
+    SettingDef { name: "suggestions.reopen_grace_days", kind: SettingKind::Days { min: 0, max: 365 }, default: "14" },
+    SettingDef { name: "previews.cache_path", kind: SettingKind::Text, default: "data/previews/" },
+    SettingDef { name: "uploads.max_size_mb", kind: SettingKind::Int { min: 1, max: 100 }, default: "10" },
+    SettingDef { name: "governance.ballot_quorum_pct", kind: SettingKind::Int { min: 1, max: 100 }, default: "50" },
+    SettingDef { name: "governance.meeting_quorum_pct", kind: SettingKind::Int { min: 1, max: 100 }, default: "50" },
+    SettingDef { name: "exports.encryption_public_key", kind: SettingKind::Text, default: "" },
+    SettingDef { name: "exports.allowed_roles", kind: SettingKind::Text, default: "" },
+    SettingDef { name: "exports.watermark", kind: SettingKind::Bool, default: "false" },
+    SettingDef { name: "exports.watermark_text", kind: SettingKind::Text, default: "" },
+    SettingDef { name: "trash.entity_types", kind: SettingKind::Text, default: "user,proposal,tor" },
+    SettingDef { name: "trash.retention_days", kind: SettingKind::Days { min: 1, max: 3650 }, default: "30" },
+    SettingDef { name: "network.allowlist", kind: SettingKind::Text, default: "" },
+    SettingDef { name: "network.restrict_login", kind: SettingKind::Bool, default: "true" },
+    SettingDef { name: "network.restrict_api", kind: SettingKind::Bool, default: "true" },
+    SettingDef { name: "network.bypass_roles", kind: SettingKind::Text, default: "" },
+    SettingDef { name: "login.new_device_alerts", kind: SettingKind::Bool, default: "true" },
+    SettingDef { name: "auth.password_min_length", kind: SettingKind::Int { min: 8, max: 128 }, default: "8" },
+    SettingDef { name: "auth.password_min_classes", kind: SettingKind::Int { min: 1, max: 4 }, default: "1" },
+    SettingDef { name: "auth.password_banned", kind: SettingKind::Text, default: "" },
+    SettingDef { name: "auth.password_history", kind: SettingKind::Int { min: 0, max: 24 }, default: "0" },
+    SettingDef { name: "auth.password_max_age_days", kind: SettingKind::Days { min: 0, max: 3650 }, default: "0" },
+    SettingDef { name: "login.lockout_threshold", kind: SettingKind::Int { min: 0, max: 100 }, default: "5" },
+    SettingDef { name: "login.lockout_window_minutes", kind: SettingKind::Int { min: 1, max: 1440 }, default: "15" },
+    SettingDef { name: "login.lockout_minutes", kind: SettingKind::Int { min: 0, max: 10_080 }, default: "30" },
+    SettingDef { name: "fiscal.year_start_month", kind: SettingKind::Int { min: 1, max: 12 }, default: "1" },
+    SettingDef { name: "fiscal.quarter_label", kind: SettingKind::Text, default: "{fy} Q{q}" },
+    SettingDef { name: "api.graphql_enabled", kind: SettingKind::Bool, default: "false" },
+    SettingDef { name: "feeds.public", kind: SettingKind::Bool, default: "false" },
+    SettingDef { name: "email.enabled", kind: SettingKind::Bool, default: "false" },
+    SettingDef { name: "email.smtp_host", kind: SettingKind::Text, default: "" },
+    SettingDef { name: "email.smtp_port", kind: SettingKind::Int { min: 1, max: 65_535 }, default: "587" },
+    SettingDef { name: "email.smtp_security", kind: SettingKind::Text, default: "starttls" },
+    SettingDef { name: "email.from_address", kind: SettingKind::Text, default: "" },
+    SettingDef { name: "email.base_url", kind: SettingKind::Text, default: "" },
+    SettingDef { name: "security.signing_key_grace_days", kind: SettingKind::Days { min: 0, max: 365 }, default: "7" },
+    SettingDef { name: "warnings.retention_resolved_days", kind: SettingKind::Days { min: 1, max: 3650 }, default: "30" },
+    SettingDef { name: "warnings.retention_info_days", kind: SettingKind::Days { min: 1, max: 3650 }, default: "90" },
+    SettingDef { name: "warnings.retention_deleted_days", kind: SettingKind::Days { min: 1, max: 3650 }, default: "7" },
+    SettingDef { name: "warnings.quorum_miss_threshold", kind: SettingKind::Int { min: 0, max: 50 }, default: "3" },
+    SettingDef { name: "session.idle_timeout_minutes", kind: SettingKind::Int { min: 1, max: 1440 }, default: "30" },
+    SettingDef { name: "session.keep_signed_in_idle_minutes", kind: SettingKind::Int { min: 1, max: 43_200 }, default: "1440" },
+    SettingDef { name: "session.absolute_lifetime_hours", kind: SettingKind::Int { min: 1, max: 720 }, default: "12" },
+    SettingDef { name: "session.expiry_warning_seconds", kind: SettingKind::Int { min: 10, max: 3600 }, default: "120" },
+    SettingDef { name: "session.remember_me_days", kind: SettingKind::Days { min: 1, max: 365 }, default: "30" },
+    SettingDef { name: "quota.max_open_proposals", kind: SettingKind::Int { min: 0, max: 10_000 }, default: "0" },
+    SettingDef { name: "quota.tor_storage_mb", kind: SettingKind::Int { min: 0, max: 1_000_000 }, default: "0" },
+    SettingDef { name: "quota.api_requests_per_day", kind: SettingKind::Int { min: 0, max: 10_000_000 }, default: "0" },
+    SettingDef { name: "api.usage_retention_days", kind: SettingKind::Days { min: 1, max: 365 }, default: "30" },
+    SettingDef { name: "ticketing.enabled", kind: SettingKind::Bool, default: "false" },
+    SettingDef { name: "ticketing.base_url", kind: SettingKind::Text, default: "" },
+    SettingDef { name: "ticketing.project_key", kind: SettingKind::Text, default: "" },
+    SettingDef { name: "ticketing.issue_type", kind: SettingKind::Text, default: "Task" },
+    SettingDef { name: "a11y.debug", kind: SettingKind::Bool, default: "false" },
+    SettingDef { name: "oidc.enabled", kind: SettingKind::Bool, default: "false" },
+    SettingDef { name: "oidc.issuer", kind: SettingKind::Text, default: "" },
+    SettingDef { name: "oidc.client_id", kind: SettingKind::Text, default: "" },
+    SettingDef { name: "oidc.redirect_url", kind: SettingKind::Text, default: "" },
+    SettingDef { name: "oidc.button_label", kind: SettingKind::Text, default: "Sign in with SSO" },
+    SettingDef { name: "oidc.auto_provision", kind: SettingKind::Bool, default: "true" },
+    SettingDef { name: "oidc.default_role", kind: SettingKind::Text, default: "viewer" },
+    SettingDef { name: "push.enabled", kind: SettingKind::Bool, default: "false" },
+    SettingDef { name: "push.vapid_subject", kind: SettingKind::Text, default: "" },
+    SettingDef { name: "push.vapid_public_key", kind: SettingKind::Text, default: "" },
+];
+
+/// Look up the declaration for a setting name.
+pub fn definition(name: &str) -> Option<&'static SettingDef> {
+    DEFINITIONS.iter().find(|d| d.name == name)
+}
+
+/// Validate a value for a setting. Declared settings are checked against their
+/// definition; undeclared ones only against their stored `setting_type`.
+pub fn validate(name: &str, setting_type: &str, value: &str) -> Result<(), String> {
+    let kind = match definition(name) {
+        Some(def) => def.kind,
+        None => match setting_type {
+            "boolean" => SettingKind::Bool,
+            "number" => SettingKind::Int { min: i64::MIN, max: i64::MAX },
+            _ => SettingKind::Text,
+        },
+    };
+    match kind {
+        SettingKind::Text if name == "network.allowlist" => {
+            crate::auth::network::parse_allowlist(value).map(|_| ())
+        }
+        SettingKind::Text if name == "fiscal.quarter_label" => super::fiscal::validate_label(value),
+        SettingKind::Text => Ok(()),
+        SettingKind::Bool => match value {
+            "true" | "false" => Ok(()),
+            _ => Err("must be true or false".to_string()),
+        },
+        SettingKind::Int { min, max } | SettingKind::Days { min, max } => {
+            let n: i64 = value.parse().map_err(|_| "must be a whole number".to_string())?;
+            if n < min || n > max {
+                return Err(format!("must be between {} and {}", min, max));
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Stored value of a setting, or its declared default when missing.
+async fn value_or_default(pool: &PgPool, name: &str) -> String {
+    let default = definition(name).map(|d| d.default).unwrap_or("");
+    get_value(pool, name, default).await
+}
+
+/// Read a boolean setting. Unparseable values fall back to the declared default.
+pub async fn get_bool(pool: &PgPool, name: &str) -> bool {
+    let default = definition(name).map(|d| d.default == "true").unwrap_or(false);
+    match value_or_default(pool, name).await.as_str() {
+        "true" => true,
+        "false" => false,
+        _ => default,
+    }
+}
+
+/// Read an integer setting. Unparseable values fall back to the declared default.
+pub async fn get_i64(pool: &PgPool, name: &str) -> i64 {
+    let default = definition(name).and_then(|d| d.default.parse().ok()).unwrap_or(0);
+    value_or_default(pool, name).await.trim().parse().unwrap_or(default)
+}
+
+/// Read a day-count setting as a duration.
+pub async fn get_duration(pool: &PgPool, name: &str) -> chrono::Duration {
+    chrono::Duration::days(get_i64(pool, name).await)
+}
+
 /// Get a single setting's value by name, returning a default if not found.
 pub async fn get_value(pool: &PgPool, name: &str, default: &str) -> String {
     let result = sqlx::query_as::<_, (String,)>(
@@ -64,3 +249,204 @@ pub async fn update_value(pool: &PgPool, id: i64, value: &str) -> Result<(), sql
     .await?;
     Ok(())
 }
+
+/// Find the settings a ToR may override (global settings flagged `tor_overridable`),
+/// each paired with the ToR's override value when one is set.
+pub async fn find_for_tor(pool: &PgPool, tor_id: i64) -> Result<Vec<TorSettingDisplay>, sqlx::Error> {
+    let settings = sqlx::query_as::<_, TorSettingDisplay>(
+        "SELECT e.name, e.label, \
+                COALESCE(p_desc.value, '') AS description, \
+                COALESCE(p_type.value, 'text') AS setting_type, \
+                COALESCE(p_val.value, '') AS global_value, \
+                o_val.value AS override_value \
+         FROM entities e \
+         JOIN entity_properties p_scope ON e.id = p_scope.entity_id \
+             AND p_scope.key = 'tor_overridable' AND p_scope.value = 'true' \
+         LEFT JOIN entity_properties p_val ON e.id = p_val.entity_id AND p_val.key = 'value' \
+         LEFT JOIN entity_properties p_desc ON e.id = p_desc.entity_id AND p_desc.key = 'description' \
+         LEFT JOIN entity_properties p_type ON e.id = p_type.entity_id AND p_type.key = 'setting_type' \
+         LEFT JOIN entities o ON o.entity_type = 'setting' AND o.name = e.name || '@tor:' || $1::TEXT \
+         LEFT JOIN entity_properties o_val ON o.id = o_val.entity_id AND o_val.key = 'value' \
+         WHERE e.entity_type = 'setting' AND e.is_active = true \
+         ORDER BY e.sort_order, e.id"
+    )
+    .bind(tor_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(settings)
+}
+
+/// Resolve a setting for a ToR: the ToR's override, then the global value, then `default`.
+pub async fn get_value_for_tor(pool: &PgPool, tor_id: i64, name: &str, default: &str) -> String {
+    let result = sqlx::query_as::<_, (String,)>(
+        "SELECT p.value \
+         FROM entities e \
+         JOIN entity_properties p ON e.id = p.entity_id AND p.key = 'value' \
+         WHERE e.entity_type = 'setting' AND e.name = $1"
+    )
+    .bind(override_name(name, tor_id))
+    .fetch_optional(pool)
+    .await;
+    match result {
+        Ok(Some((value,))) => value,
+        _ => get_value(pool, name, default).await,
+    }
+}
+
+/// Set (or replace) a ToR's override of a global setting.
+/// The override is a `setting` entity linked to the ToR via `scoped_to_tor`.
+pub async fn set_tor_override(pool: &PgPool, tor_id: i64, name: &str, value: &str) -> Result<(), sqlx::Error> {
+    let scoped_name = override_name(name, tor_id);
+    let id = match super::entity::find_by_type_and_name(pool, "setting", &scoped_name).await? {
+        Some(existing) => existing.id,
+        None => {
+            let label = super::entity::find_by_type_and_name(pool, "setting", name).await?
+                .map(|global| global.label)
+                .unwrap_or_else(|| name.to_string());
+            let id = super::entity::create(pool, "setting", &scoped_name, &label).await?;
+            super::entity::set_property(pool, id, "overrides", name).await?;
+            super::relation::create(pool, "scoped_to_tor", id, tor_id).await?;
+            id
+        }
+    };
+    update_value(pool, id, value).await
+}
+
+/// Remove a ToR's override so the global value applies again.
+pub async fn clear_tor_override(pool: &PgPool, tor_id: i64, name: &str) -> Result<(), sqlx::Error> {
+    if let Some(existing) = super::entity::find_by_type_and_name(pool, "setting", &override_name(name, tor_id)).await? {
+        super::entity::delete(pool, existing.id).await?;
+    }
+    Ok(())
+}
+
+/// One recorded change to a global setting, for the settings history drawer.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct SettingChange {
+    pub id: i64,
+    pub setting_id: i64,
+    pub setting_label: String,
+    pub old_value: String,
+    pub new_value: String,
+    pub kind: String, // "update", "rollback"
+    pub changed_by: String,
+    pub created_at: String,
+}
+
+/// A setting value change that was applied and recorded in the history.
+#[derive(Debug, Clone)]
+pub struct AppliedChange {
+    pub change_id: i64,
+    pub setting_id: i64,
+    pub name: String,
+    pub label: String,
+    pub old_value: String,
+    pub new_value: String,
+    pub critical: bool,
+}
+
+/// Change a setting's value and record the old/new pair as a `setting_change`
+/// entity linked to the setting via `change_of`.
+/// Returns `None` when the setting does not exist or the value is unchanged.
+pub async fn change_value(
+    pool: &PgPool,
+    id: i64,
+    value: &str,
+    user_id: i64,
+    kind: &str,
+) -> Result<Option<AppliedChange>, sqlx::Error> {
+    let current = sqlx::query_as::<_, (String, String, String, bool)>(
+        "SELECT e.name, e.label, COALESCE(p_val.value, ''), \
+                COALESCE(p_crit.value = 'true', false) \
+         FROM entities e \
+         LEFT JOIN entity_properties p_val ON e.id = p_val.entity_id AND p_val.key = 'value' \
+         LEFT JOIN entity_properties p_crit ON e.id = p_crit.entity_id AND p_crit.key = 'critical' \
+         WHERE e.id = $1 AND e.entity_type = 'setting'"
+    )
+    .bind(id)
+    .fetch_optional(pool)
+    .await?;
+
+    let Some((name, label, old_value, critical)) = current else {
+        return Ok(None);
+    };
+    if old_value == value {
+        return Ok(None);
+    }
+
+    update_value(pool, id, value).await?;
+
+    let (seq,): (i64,) = sqlx::query_as(
+        "SELECT COUNT(*) + 1 FROM entities WHERE entity_type = 'setting_change' AND name LIKE $1"
+    )
+    .bind(format!("sc.{}.%", id))
+    .fetch_one(pool)
+    .await?;
+    let change_id = super::entity::create(pool, "setting_change", &format!("sc.{}.{}", id, seq), &label).await?;
+    super::entity::set_properties(pool, change_id, &[
+        ("old_value", &old_value),
+        ("new_value", value),
+        ("kind", kind),
+        ("changed_by", &user_id.to_string()),
+    ]).await?;
+    super::relation::create(pool, "change_of", change_id, id).await?;
+
+    Ok(Some(AppliedChange {
+        change_id,
+        setting_id: id,
+        name,
+        label,
+        old_value,
+        new_value: value.to_string(),
+        critical,
+    }))
+}
+
+/// Most recent changes across all global settings, newest first.
+pub async fn find_history(pool: &PgPool, limit: i64) -> Result<Vec<SettingChange>, sqlx::Error> {
+    let changes = sqlx::query_as::<_, SettingChange>(
+        "SELECT c.id, s.id AS setting_id, s.label AS setting_label, \
+                COALESCE(p_old.value, '') AS old_value, \
+                COALESCE(p_new.value, '') AS new_value, \
+                COALESCE(p_kind.value, 'update') AS kind, \
+                COALESCE(u.name, 'system') AS changed_by, \
+                TO_CHAR(c.created_at, 'YYYY-MM-DD HH24:MI') AS created_at \
+         FROM entities c \
+         JOIN relations r ON r.source_id = c.id \
+         JOIN entities rt ON rt.id = r.relation_type_id AND rt.name = 'change_of' \
+         JOIN entities s ON s.id = r.target_id \
+         LEFT JOIN entity_properties p_old ON c.id = p_old.entity_id AND p_old.key = 'old_value' \
+         LEFT JOIN entity_properties p_new ON c.id = p_new.entity_id AND p_new.key = 'new_value' \
+         LEFT JOIN entity_properties p_kind ON c.id = p_kind.entity_id AND p_kind.key = 'kind' \
+         LEFT JOIN entity_properties p_by ON c.id = p_by.entity_id AND p_by.key = 'changed_by' \
+         LEFT JOIN entities u ON u.entity_type = 'user' AND u.id::TEXT = p_by.value \
+         WHERE c.entity_type = 'setting_change' \
+         ORDER BY c.created_at DESC, c.id DESC \
+         LIMIT $1"
+    )
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+    Ok(changes)
+}
+
+/// Restore the value a setting had before the given change.
+/// The rollback is itself recorded as a change of kind `rollback`.
+pub async fn rollback(pool: &PgPool, change_id: i64, user_id: i64) -> Result<Option<AppliedChange>, sqlx::Error> {
+    let target = sqlx::query_as::<_, (i64, String)>(
+        "SELECT r.target_id, COALESCE(p_old.value, '') \
+         FROM entities c \
+         JOIN relations r ON r.source_id = c.id \
+         JOIN entities rt ON rt.id = r.relation_type_id AND rt.name = 'change_of' \
+         LEFT JOIN entity_properties p_old ON c.id = p_old.entity_id AND p_old.key = 'old_value' \
+         WHERE c.id = $1 AND c.entity_type = 'setting_change'"
+    )
+    .bind(change_id)
+    .fetch_optional(pool)
+    .await?;
+
+    match target {
+        Some((setting_id, old_value)) => change_value(pool, setting_id, &old_value, user_id, "rollback").await,
+        None => Ok(None),
+    }
+}
@@ -0,0 +1,193 @@
+//! Read-only share tokens for embedding views in other portals.
+//!
+//! The token is `<selector>.<secret>`: the selector is the entity name and is
+//! looked up directly; only an HMAC of the secret under the `share_token`
+//! signing key is stored, so retiring that key revokes every token issued
+//! with it. Tokens from before the key registry store a plain SHA-256 hash.
+//! A token grants one view, optionally limited to one ToR, until its expiry
+//! date.
+
+use rand::Rng;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+
+use super::{entity, relation, signing_key};
+
+/// Views that can be shared, as (key, label).
+pub const VIEWS: &[(&str, &str)] = &[
+    ("governance_map", "Governance map"),
+    ("tor_calendar", "ToR calendar"),
+    ("decision_register", "Decision register"),
+    ("published_minutes", "Published minutes"),
+];
+
+/// Longest allowed lifetime of a share token.
+pub const MAX_LIFETIME_DAYS: i64 = 365;
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct ShareToken {
+    pub id: i64,
+    pub selector: String,
+    pub label: String,
+    pub is_active: bool,
+    pub view: String,
+    pub token_hash: String,
+    pub tor_id: Option<i64>,
+    pub tor_label: Option<String>,
+    /// Last valid day, `YYYY-MM-DD`.
+    pub expires_at: String,
+    pub created_by_name: String,
+    pub last_used_at: String,
+}
+
+impl ShareToken {
+    pub fn view_label(&self) -> &str {
+        VIEWS.iter()
+            .find(|(key, _)| *key == self.view)
+            .map(|(_, label)| *label)
+            .unwrap_or(self.view.as_str())
+    }
+
+    pub fn is_expired(&self, today: &str) -> bool {
+        self.expires_at.as_str() < today
+    }
+}
+
+/// Fields submitted from the share token admin form.
+pub struct ShareTokenInput<'a> {
+    pub label: &'a str,
+    pub view: &'a str,
+    pub tor_id: Option<i64>,
+    pub expires_at: &'a str,
+}
+
+const SELECT_TOKEN: &str =
+    "SELECT e.id, e.name AS selector, e.label, e.is_active, \
+            COALESCE(p_view.value, '') AS view, \
+            COALESCE(p_hash.value, '') AS token_hash, \
+            t.id AS tor_id, t.label AS tor_label, \
+            COALESCE(p_exp.value, '') AS expires_at, \
+            COALESCE(u.label, '') AS created_by_name, \
+            COALESCE(p_used.value, '') AS last_used_at \
+     FROM entities e \
+     LEFT JOIN relations r_tor ON r_tor.source_id = e.id \
+         AND r_tor.relation_type_id = ( \
+             SELECT id FROM entities WHERE entity_type = 'relation_type' AND name = 'scoped_to_tor') \
+     LEFT JOIN entities t ON t.id = r_tor.target_id \
+     LEFT JOIN entity_properties p_view ON e.id = p_view.entity_id AND p_view.key = 'view' \
+     LEFT JOIN entity_properties p_hash ON e.id = p_hash.entity_id AND p_hash.key = 'token_hash' \
+     LEFT JOIN entity_properties p_exp ON e.id = p_exp.entity_id AND p_exp.key = 'expires_at' \
+     LEFT JOIN entity_properties p_by ON e.id = p_by.entity_id AND p_by.key = 'created_by_id' \
+     LEFT JOIN entities u ON u.id::TEXT = p_by.value \
+     LEFT JOIN entity_properties p_used ON e.id = p_used.entity_id AND p_used.key = 'last_used_at' \
+     WHERE e.entity_type = 'share_token'";
+
+/// All share tokens, soonest expiry first.
+pub async fn find_all(pool: &PgPool) -> Result<Vec<ShareToken>, sqlx::Error> {
+    sqlx::query_as::<_, ShareToken>(&format!("{} ORDER BY p_exp.value, e.label", SELECT_TOKEN))
+        .fetch_all(pool)
+        .await
+}
+
+/// Validate admin input against today's date. Returns a list of problems.
+pub fn validate(input: &ShareTokenInput<'_>, today: chrono::NaiveDate) -> Vec<String> {
+    let mut errors = Vec::new();
+    if input.label.trim().is_empty() {
+        errors.push("Name is required".to_string());
+    }
+    if !VIEWS.iter().any(|(key, _)| *key == input.view) {
+        errors.push("Choose a view to share".to_string());
+    }
+    if input.view == "governance_map" && input.tor_id.is_some() {
+        errors.push("The governance map cannot be limited to one ToR".to_string());
+    }
+    match chrono::NaiveDate::parse_from_str(input.expires_at, "%Y-%m-%d") {
+        Ok(date) if date < today => errors.push("Expiry date cannot be in the past".to_string()),
+        Ok(date) if date > today + chrono::Duration::days(MAX_LIFETIME_DAYS) => {
+            errors.push(format!("Share tokens can be valid for at most {} days", MAX_LIFETIME_DAYS));
+        }
+        Ok(_) => {}
+        Err(_) => errors.push("Expiry date is required".to_string()),
+    }
+    errors
+}
+
+/// Create a share token. Returns its id and the raw token, which is shown once.
+pub async fn create(pool: &PgPool, input: &ShareTokenInput<'_>, created_by: i64) -> Result<(i64, String), sqlx::Error> {
+    let selector = random_hex(12);
+    let secret = random_hex(32);
+
+    let token_hash = signing_key::sign(pool, "share_token", secret.as_bytes()).await?;
+    let id = entity::create(pool, "share_token", &selector, input.label.trim()).await?;
+    entity::set_properties(pool, id, &[
+        ("view", input.view),
+        ("token_hash", &token_hash),
+        ("expires_at", input.expires_at),
+        ("created_by_id", &created_by.to_string()),
+    ]).await?;
+    if let Some(tor_id) = input.tor_id {
+        relation::create(pool, "scoped_to_tor", id, tor_id).await?;
+    }
+    Ok((id, format!("{}.{}", selector, secret)))
+}
+
+/// Look up the token behind a raw `<selector>.<secret>` value. Returns it only
+/// while active and unexpired, and records the use.
+pub async fn resolve(pool: &PgPool, raw: &str, today: &str) -> Result<Option<ShareToken>, sqlx::Error> {
+    let Some((selector, secret)) = raw.split_once('.') else {
+        return Ok(None);
+    };
+    let token = sqlx::query_as::<_, ShareToken>(&format!("{} AND e.name = $1", SELECT_TOKEN))
+        .bind(selector)
+        .fetch_optional(pool)
+        .await?;
+    let Some(mut token) = token else {
+        return Ok(None);
+    };
+    if !token.is_active || token.is_expired(today) {
+        return Ok(None);
+    }
+    let valid = if token.token_hash.contains('.') {
+        signing_key::verify(pool, "share_token", secret.as_bytes(), &token.token_hash).await?
+    } else {
+        token.token_hash == legacy_hash(secret)
+    };
+    if !valid {
+        return Ok(None);
+    }
+    let now = chrono::Utc::now().format("%Y-%m-%d %H:%M").to_string();
+    entity::set_property(pool, token.id, "last_used_at", &now).await?;
+    token.last_used_at = now;
+    Ok(Some(token))
+}
+
+/// Suspend or reinstate a token. Returns false if no such token.
+pub async fn set_active(pool: &PgPool, id: i64, active: bool) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "UPDATE entities SET is_active = $2, updated_at = NOW() WHERE id = $1 AND entity_type = 'share_token'"
+    )
+    .bind(id)
+    .bind(active)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Delete a token; embeds using it stop working.
+pub async fn delete(pool: &PgPool, id: i64) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM entities WHERE id = $1 AND entity_type = 'share_token'")
+        .bind(id)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+fn legacy_hash(secret: &str) -> String {
+    hex::encode(Sha256::digest(secret.as_bytes()))
+}
+
+fn random_hex(len: usize) -> String {
+    let mut rng = rand::rng();
+    let bytes: Vec<u8> = (0..len).map(|_| rng.random()).collect();
+    hex::encode(bytes)
+}
@@ -0,0 +1,84 @@
+//! Devices and addresses users have signed in from.
+//!
+//! Every successful sign-in records its device label and IP address. A pair
+//! the user has not signed in from before is reported to them (see
+//! `auth::new_device`); the very first sign-in of an account only sets the
+//! baseline.
+
+use sqlx::PgPool;
+
+/// One device and address pair, for the account sessions tab.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct SignInDevice {
+    pub id: i64,
+    pub user_id: i64,
+    pub device: String,
+    pub ip: String,
+    pub first_seen: String,
+    pub last_seen: String,
+}
+
+/// What a recorded sign-in was.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sighting {
+    /// The account's first recorded sign-in.
+    First,
+    /// From a device and address signed in from before.
+    Known,
+    /// From a device or address not seen before; carries the new row's id.
+    New(i64),
+}
+
+/// Record a sign-in by the user from `device` at `ip`.
+pub async fn record(pool: &PgPool, user_id: i64, device: &str, ip: &str) -> Result<Sighting, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let seen_before: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM sign_in_devices WHERE user_id = $1)")
+        .bind(user_id)
+        .fetch_one(&mut *tx)
+        .await?;
+    let inserted: Option<i64> = sqlx::query_scalar(
+        "INSERT INTO sign_in_devices (user_id, device, ip) VALUES ($1, $2, $3) \
+         ON CONFLICT (user_id, device, ip) DO NOTHING RETURNING id",
+    )
+    .bind(user_id)
+    .bind(device)
+    .bind(ip)
+    .fetch_optional(&mut *tx)
+    .await?;
+    let sighting = match inserted {
+        Some(id) if seen_before => Sighting::New(id),
+        Some(_) => Sighting::First,
+        None => {
+            sqlx::query("UPDATE sign_in_devices SET last_seen = NOW() WHERE user_id = $1 AND device = $2 AND ip = $3")
+                .bind(user_id)
+                .bind(device)
+                .bind(ip)
+                .execute(&mut *tx)
+                .await?;
+            Sighting::Known
+        }
+    };
+    tx.commit().await?;
+    Ok(sighting)
+}
+
+const SELECT_DEVICE: &str =
+    "SELECT id, user_id, device, ip, \
+            TO_CHAR(first_seen, 'YYYY-MM-DD HH24:MI') AS first_seen, \
+            TO_CHAR(last_seen, 'YYYY-MM-DD HH24:MI') AS last_seen \
+     FROM sign_in_devices";
+
+/// The user's sign-in devices, most recently used first.
+pub async fn find_for_user(pool: &PgPool, user_id: i64) -> Result<Vec<SignInDevice>, sqlx::Error> {
+    sqlx::query_as(&format!("{SELECT_DEVICE} WHERE user_id = $1 ORDER BY sign_in_devices.last_seen DESC, id DESC"))
+        .bind(user_id)
+        .fetch_all(pool)
+        .await
+}
+
+pub async fn find_by_id(pool: &PgPool, id: i64) -> Result<Option<SignInDevice>, sqlx::Error> {
+    sqlx::query_as(&format!("{SELECT_DEVICE} WHERE id = $1"))
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+}
@@ -0,0 +1,177 @@
+//! Registry of server-held signing keys.
+//!
+//! Each purpose (share tokens, API keys, outbound webhook signatures) has
+//! one active HMAC-SHA256 key. A signature is `<kid>.<hex mac>`, so it can
+//! be checked against the key that made it. Rotating a purpose issues a new
+//! active key; the old one stops signing but keeps verifying while it is
+//! `retiring`, and stops verifying once retired — by an admin, or by the
+//! scheduler after `security.signing_key_grace_days`.
+
+use hmac::{Hmac, Mac};
+use rand::Rng;
+use serde::Serialize;
+use sha2::Sha256;
+use sqlx::PgPool;
+
+/// What keys are used for, as (purpose, label, what breaks when retired).
+pub const PURPOSES: &[(&str, &str, &str)] = &[
+    ("share_token", "Share tokens", "Share links and feed URLs issued with the key stop working"),
+    ("api_key", "API keys", "API keys issued with the key stop working"),
+    ("webhook", "Webhook signatures", "Receivers reject deliveries signed with the key"),
+];
+
+/// A key as listed on the admin page; the secret is never exposed.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct SigningKey {
+    pub id: i64,
+    pub purpose: String,
+    pub kid: String,
+    pub status: String,
+    pub created_at: String,
+    pub rotated_at: String,
+    pub retired_at: String,
+}
+
+impl SigningKey {
+    pub fn purpose_label(&self) -> &str {
+        purpose_label(&self.purpose)
+    }
+}
+
+pub fn purpose_label(purpose: &str) -> &str {
+    PURPOSES.iter()
+        .find(|(key, _, _)| *key == purpose)
+        .map(|(_, label, _)| *label)
+        .unwrap_or(purpose)
+}
+
+fn random_hex(bytes: usize) -> String {
+    let mut rng = rand::rng();
+    hex::encode((0..bytes).map(|_| rng.random::<u8>()).collect::<Vec<u8>>())
+}
+
+fn mac(secret: &str, data: &[u8]) -> Hmac<Sha256> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .expect("HMAC accepts keys of any length");
+    mac.update(data);
+    mac
+}
+
+/// Issue an active key for a purpose that has none.
+async fn ensure(pool: &PgPool, purpose: &str) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO signing_keys (purpose, kid, secret) VALUES ($1, $2, $3) \
+         ON CONFLICT (purpose) WHERE status = 'active' DO NOTHING",
+    )
+    .bind(purpose)
+    .bind(random_hex(8))
+    .bind(random_hex(32))
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Issue an active key for every purpose that has none. Run at startup.
+pub async fn ensure_all(pool: &PgPool) -> Result<(), sqlx::Error> {
+    for (purpose, _, _) in PURPOSES {
+        ensure(pool, purpose).await?;
+    }
+    Ok(())
+}
+
+/// All keys, newest first within each purpose.
+pub async fn find_all(pool: &PgPool) -> Result<Vec<SigningKey>, sqlx::Error> {
+    sqlx::query_as::<_, SigningKey>(
+        "SELECT id, purpose, kid, status, \
+                TO_CHAR(created_at, 'YYYY-MM-DD HH24:MI') AS created_at, \
+                COALESCE(TO_CHAR(rotated_at, 'YYYY-MM-DD HH24:MI'), '') AS rotated_at, \
+                COALESCE(TO_CHAR(retired_at, 'YYYY-MM-DD HH24:MI'), '') AS retired_at \
+         FROM signing_keys \
+         ORDER BY purpose, id DESC",
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Issue a new active key for a purpose; the previous one moves to
+/// `retiring`. Returns the new kid, or `None` for an unknown purpose.
+pub async fn rotate(pool: &PgPool, purpose: &str) -> Result<Option<String>, sqlx::Error> {
+    if !PURPOSES.iter().any(|(key, _, _)| *key == purpose) {
+        return Ok(None);
+    }
+    let kid = random_hex(8);
+    let mut tx = pool.begin().await?;
+    sqlx::query(
+        "UPDATE signing_keys SET status = 'retiring', rotated_at = NOW() \
+         WHERE purpose = $1 AND status = 'active'",
+    )
+    .bind(purpose)
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query("INSERT INTO signing_keys (purpose, kid, secret) VALUES ($1, $2, $3)")
+        .bind(purpose)
+        .bind(&kid)
+        .bind(random_hex(32))
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+    Ok(Some(kid))
+}
+
+/// Retire a retiring key now; signatures made with it stop verifying.
+/// Active keys are never retired — rotate first. Returns false otherwise.
+pub async fn retire(pool: &PgPool, id: i64) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "UPDATE signing_keys SET status = 'retired', retired_at = NOW(), secret = '' \
+         WHERE id = $1 AND status = 'retiring'",
+    )
+    .bind(id)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Retire keys rotated out before `before`. Returns how many.
+pub async fn retire_expired(pool: &PgPool, before: chrono::DateTime<chrono::Utc>) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        "UPDATE signing_keys SET status = 'retired', retired_at = NOW(), secret = '' \
+         WHERE status = 'retiring' AND rotated_at < $1::TIMESTAMPTZ",
+    )
+    .bind(before.to_rfc3339())
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+/// Sign data with the purpose's active key: `<kid>.<hex mac>`. A key is
+/// issued first if the purpose has none.
+pub async fn sign(pool: &PgPool, purpose: &str, data: &[u8]) -> Result<String, sqlx::Error> {
+    ensure(pool, purpose).await?;
+    let (kid, secret) = sqlx::query_as::<_, (String, String)>(
+        "SELECT kid, secret FROM signing_keys WHERE purpose = $1 AND status = 'active'",
+    )
+    .bind(purpose)
+    .fetch_one(pool)
+    .await?;
+    Ok(format!("{}.{}", kid, hex::encode(mac(&secret, data).finalize().into_bytes())))
+}
+
+/// Check a signature made by [`sign`] in constant time. Keys that are active
+/// or retiring verify; retired keys and other purposes' keys do not.
+pub async fn verify(pool: &PgPool, purpose: &str, data: &[u8], signature: &str) -> Result<bool, sqlx::Error> {
+    let Some((kid, hex_mac)) = signature.split_once('.') else {
+        return Ok(false);
+    };
+    let Ok(expected) = hex::decode(hex_mac) else {
+        return Ok(false);
+    };
+    let secret = sqlx::query_scalar::<_, String>(
+        "SELECT secret FROM signing_keys \
+         WHERE purpose = $1 AND kid = $2 AND status IN ('active', 'retiring')",
+    )
+    .bind(purpose)
+    .bind(kid)
+    .fetch_optional(pool)
+    .await?;
+    Ok(secret.is_some_and(|secret| mac(&secret, data).verify_slice(&expected).is_ok()))
+}
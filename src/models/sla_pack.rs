@@ -0,0 +1,431 @@
+//! Governance SLA policy packs.
+//!
+//! A pack bundles day limits for proposal review, proposal decision and
+//! minutes publication under a name, and is assigned to ToRs through
+//! `uses_sla_pack`. Once a day the scheduler measures each assigned ToR
+//! against its pack over the last [`WINDOW_DAYS`] and replaces the rows of
+//! `sla_compliance`; the reports page and ToR detail read those rows.
+
+use chrono::NaiveDate;
+use sqlx::PgPool;
+
+use super::{entity, relation};
+
+/// SLA metrics as (property, label, what is measured).
+pub const METRICS: &[(&str, &str, &str)] = &[
+    ("review_days", "Review", "Days from a proposal's submission until its review starts"),
+    ("decision_days", "Decision", "Days from a proposal's submission until it is approved or rejected"),
+    ("minutes_days", "Minutes", "Days from a completed meeting until its minutes are approved"),
+];
+
+/// Trailing window compliance is measured over.
+pub const WINDOW_DAYS: i64 = 90;
+
+/// Longest limit a pack may set for one metric.
+pub const MAX_LIMIT_DAYS: i64 = 365;
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct SlaPack {
+    pub id: i64,
+    pub name: String,
+    pub label: String,
+    pub description: String,
+    pub review_days: i64,
+    pub decision_days: i64,
+    pub minutes_days: i64,
+    /// ToRs the pack is assigned to.
+    pub tor_count: i64,
+}
+
+impl SlaPack {
+    /// Limit for a metric in days; 0 means the metric is not tracked.
+    pub fn limit(&self, metric: &str) -> i64 {
+        match metric {
+            "review_days" => self.review_days,
+            "decision_days" => self.decision_days,
+            "minutes_days" => self.minutes_days,
+            _ => 0,
+        }
+    }
+}
+
+/// Fields submitted from the SLA pack form; limits are raw form values.
+pub struct SlaPackInput<'a> {
+    pub name: &'a str,
+    pub label: &'a str,
+    pub description: &'a str,
+    pub limits: Vec<(&'static str, &'a str)>,
+}
+
+impl SlaPackInput<'_> {
+    fn limit_props(&self) -> Vec<(&'static str, String)> {
+        self.limits.iter()
+            .map(|(metric, value)| {
+                let days = value.trim().parse::<i64>().unwrap_or(0);
+                (*metric, days.to_string())
+            })
+            .collect()
+    }
+}
+
+/// Compliance of one ToR with one metric of its pack, as of the last run.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct SlaCompliance {
+    pub tor_id: i64,
+    pub tor_label: String,
+    pub pack_label: String,
+    pub metric: String,
+    pub limit_days: i64,
+    pub met: i64,
+    pub breached: i64,
+    /// Still open and not yet due; not counted either way.
+    pub pending: i64,
+    pub computed_on: String,
+}
+
+impl SlaCompliance {
+    pub fn metric_label(&self) -> &'static str {
+        METRICS.iter()
+            .find(|(key, _, _)| *key == self.metric)
+            .map(|(_, label, _)| *label)
+            .unwrap_or("")
+    }
+
+    /// Share of due items that met the limit, 0-100; `None` when nothing was due.
+    pub fn pct(&self) -> Option<i64> {
+        let due = self.met + self.breached;
+        (due > 0).then(|| self.met * 100 / due)
+    }
+
+    pub fn badge_class(&self) -> &'static str {
+        match self.pct() {
+            Some(90..) => "badge-success",
+            Some(70..=89) => "badge-warning",
+            Some(_) => "badge-danger",
+            None => "badge-muted",
+        }
+    }
+}
+
+/// How one item fared against a limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Met,
+    Breached,
+    /// Not finished and not yet overdue.
+    Pending,
+}
+
+/// Classify an item started on `start` and finished on `end` (if it has
+/// finished) against a limit of `limit_days`, as seen on `today`.
+pub fn classify(start: NaiveDate, end: Option<NaiveDate>, limit_days: i64, today: NaiveDate) -> Outcome {
+    let deadline = start + chrono::Duration::days(limit_days);
+    match end {
+        Some(end) if end <= deadline => Outcome::Met,
+        Some(_) => Outcome::Breached,
+        None if today > deadline => Outcome::Breached,
+        None => Outcome::Pending,
+    }
+}
+
+const SELECT_PACK: &str =
+    "SELECT e.id, e.name, e.label, \
+            COALESCE(p_desc.value, '') AS description, \
+            CAST(COALESCE(NULLIF(p_review.value, ''), '0') AS BIGINT) AS review_days, \
+            CAST(COALESCE(NULLIF(p_decision.value, ''), '0') AS BIGINT) AS decision_days, \
+            CAST(COALESCE(NULLIF(p_minutes.value, ''), '0') AS BIGINT) AS minutes_days, \
+            (SELECT COUNT(*) FROM relations r \
+             JOIN entities rt ON r.relation_type_id = rt.id AND rt.name = 'uses_sla_pack' \
+             WHERE r.target_id = e.id) AS tor_count \
+     FROM entities e \
+     LEFT JOIN entity_properties p_desc ON e.id = p_desc.entity_id AND p_desc.key = 'description' \
+     LEFT JOIN entity_properties p_review ON e.id = p_review.entity_id AND p_review.key = 'review_days' \
+     LEFT JOIN entity_properties p_decision ON e.id = p_decision.entity_id AND p_decision.key = 'decision_days' \
+     LEFT JOIN entity_properties p_minutes ON e.id = p_minutes.entity_id AND p_minutes.key = 'minutes_days' \
+     WHERE e.entity_type = 'sla_pack'";
+
+pub async fn find_all(pool: &PgPool) -> Result<Vec<SlaPack>, sqlx::Error> {
+    sqlx::query_as::<_, SlaPack>(&format!("{} ORDER BY e.label", SELECT_PACK))
+        .fetch_all(pool)
+        .await
+}
+
+pub async fn find_by_id(pool: &PgPool, id: i64) -> Result<Option<SlaPack>, sqlx::Error> {
+    sqlx::query_as::<_, SlaPack>(&format!("{} AND e.id = $1", SELECT_PACK))
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+}
+
+/// The pack assigned to a ToR, if any.
+pub async fn find_for_tor(pool: &PgPool, tor_id: i64) -> Result<Option<SlaPack>, sqlx::Error> {
+    sqlx::query_as::<_, SlaPack>(&format!(
+        "{} AND e.id = (SELECT r.target_id FROM relations r \
+                        JOIN entities rt ON r.relation_type_id = rt.id AND rt.name = 'uses_sla_pack' \
+                        WHERE r.source_id = $1 LIMIT 1)",
+        SELECT_PACK,
+    ))
+    .bind(tor_id)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Every active ToR with the id of its pack, if any, ordered by label.
+pub async fn find_assignments(pool: &PgPool) -> Result<Vec<(i64, String, Option<i64>)>, sqlx::Error> {
+    sqlx::query_as(
+        "SELECT t.id, t.label, \
+                (SELECT r.target_id FROM relations r \
+                 JOIN entities rt ON r.relation_type_id = rt.id AND rt.name = 'uses_sla_pack' \
+                 WHERE r.source_id = t.id LIMIT 1) AS pack_id \
+         FROM entities t \
+         WHERE t.entity_type = 'tor' AND t.is_active = true \
+         ORDER BY t.label",
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Validate form input. `existing_id` is the pack being edited, if any.
+pub async fn validate(pool: &PgPool, input: &SlaPackInput<'_>, existing_id: Option<i64>) -> Result<Vec<String>, sqlx::Error> {
+    let mut errors = Vec::new();
+    let name = input.name.trim();
+    if name.is_empty() {
+        errors.push("Name is required".to_string());
+    } else if !name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-') {
+        errors.push("Name may only contain lowercase letters, digits, '-' and '_'".to_string());
+    } else if let Some(other) = entity::find_by_type_and_name(pool, "sla_pack", name).await?
+        && Some(other.id) != existing_id
+    {
+        errors.push(format!("An SLA pack named '{}' already exists", name));
+    }
+    if input.label.trim().is_empty() {
+        errors.push("Label is required".to_string());
+    }
+    let mut tracked = 0;
+    let mut invalid = false;
+    for (metric, value) in &input.limits {
+        let label = METRICS.iter().find(|(key, _, _)| key == metric).map(|(_, l, _)| *l).unwrap_or(metric);
+        let value = value.trim();
+        if value.is_empty() {
+            continue;
+        }
+        match value.parse::<i64>() {
+            Ok(days) if (0..=MAX_LIMIT_DAYS).contains(&days) => {
+                if days > 0 {
+                    tracked += 1;
+                }
+            }
+            _ => {
+                invalid = true;
+                errors.push(format!("{} limit must be between 0 and {} days", label, MAX_LIMIT_DAYS));
+            }
+        }
+    }
+    if tracked == 0 && !invalid {
+        errors.push("Set a limit for at least one metric".to_string());
+    }
+    Ok(errors)
+}
+
+pub async fn create(pool: &PgPool, input: &SlaPackInput<'_>) -> Result<i64, sqlx::Error> {
+    let id = entity::create(pool, "sla_pack", input.name.trim(), input.label.trim()).await?;
+    write_props(pool, id, input).await?;
+    Ok(id)
+}
+
+pub async fn update(pool: &PgPool, id: i64, input: &SlaPackInput<'_>) -> Result<(), sqlx::Error> {
+    entity::update(pool, id, input.name.trim(), input.label.trim()).await?;
+    write_props(pool, id, input).await
+}
+
+async fn write_props(pool: &PgPool, id: i64, input: &SlaPackInput<'_>) -> Result<(), sqlx::Error> {
+    entity::set_property(pool, id, "description", input.description.trim()).await?;
+    for (key, value) in input.limit_props() {
+        entity::set_property(pool, id, key, &value).await?;
+    }
+    Ok(())
+}
+
+/// Delete a pack; its ToRs are left without one and lose their compliance rows.
+pub async fn delete(pool: &PgPool, id: i64) -> Result<(), sqlx::Error> {
+    entity::delete(pool, id).await
+}
+
+/// Assign a pack to a ToR, or clear its pack with `None`. Compliance rows
+/// for the old pack are dropped; the new pack is measured on the next run.
+pub async fn assign(pool: &PgPool, tor_id: i64, pack_id: Option<i64>) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "DELETE FROM relations WHERE source_id = $1 AND relation_type_id = ( \
+             SELECT id FROM entities WHERE entity_type = 'relation_type' AND name = 'uses_sla_pack')",
+    )
+    .bind(tor_id)
+    .execute(pool)
+    .await?;
+    sqlx::query("DELETE FROM sla_compliance WHERE tor_id = $1")
+        .bind(tor_id)
+        .execute(pool)
+        .await?;
+    if let Some(pack_id) = pack_id {
+        relation::create(pool, "uses_sla_pack", tor_id, pack_id).await?;
+    }
+    Ok(())
+}
+
+/// (start, end) dates of the items measured by a metric for one ToR, for
+/// items started within the window ending on `today`.
+async fn find_spans(
+    pool: &PgPool,
+    tor_id: i64,
+    metric: &str,
+    today: NaiveDate,
+) -> Result<Vec<(NaiveDate, Option<NaiveDate>)>, sqlx::Error> {
+    let since = (today - chrono::Duration::days(WINDOW_DAYS)).format("%Y-%m-%d").to_string();
+    let rows: Vec<(String, String)> = match metric {
+        "review_days" | "decision_days" => {
+            let end_events: &[&str] = if metric == "review_days" {
+                &["proposal.under_review"]
+            } else {
+                &["proposal.approved", "proposal.rejected"]
+            };
+            sqlx::query_as(
+                "SELECT TO_CHAR(s.started, 'YYYY-MM-DD'), \
+                        COALESCE(TO_CHAR(( \
+                            SELECT MIN(d.occurred_at) FROM domain_events d \
+                            WHERE d.aggregate_type = 'proposal' AND d.aggregate_id = s.id \
+                              AND d.event_type = ANY($3) AND d.occurred_at >= s.started), 'YYYY-MM-DD'), '') \
+                 FROM ( \
+                     SELECT p.id, MIN(d.occurred_at) AS started \
+                     FROM entities p \
+                     JOIN relations r ON r.source_id = p.id AND r.target_id = $1 \
+                     JOIN entities rt ON r.relation_type_id = rt.id AND rt.name = 'submitted_to' \
+                     JOIN domain_events d ON d.aggregate_type = 'proposal' AND d.aggregate_id = p.id \
+                         AND d.event_type = 'proposal.submitted' \
+                     WHERE p.entity_type = 'proposal' \
+                     GROUP BY p.id \
+                 ) s \
+                 WHERE s.started >= $2::DATE",
+            )
+            .bind(tor_id)
+            .bind(&since)
+            .bind(end_events)
+            .fetch_all(pool)
+            .await?
+        }
+        "minutes_days" => {
+            sqlx::query_as(
+                "SELECT p_date.value, \
+                        COALESCE(( \
+                            SELECT COALESCE(NULLIF(p_appr.value, ''), TO_CHAR(m.updated_at, 'YYYY-MM-DD')) \
+                            FROM relations r_min \
+                            JOIN entities rt ON r_min.relation_type_id = rt.id AND rt.name = 'minutes_of' \
+                            JOIN entities m ON m.id = r_min.target_id \
+                            JOIN entity_properties p_ms ON m.id = p_ms.entity_id AND p_ms.key = 'status' \
+                            LEFT JOIN entity_properties p_appr ON m.id = p_appr.entity_id AND p_appr.key = 'approved_date' \
+                            WHERE r_min.source_id = mtg.id AND p_ms.value = 'approved' \
+                            LIMIT 1), '') \
+                 FROM entities mtg \
+                 JOIN relations r ON r.source_id = mtg.id AND r.target_id = $1 \
+                 JOIN entities rt ON r.relation_type_id = rt.id AND rt.name = 'belongs_to_tor' \
+                 JOIN entity_properties p_date ON mtg.id = p_date.entity_id AND p_date.key = 'meeting_date' \
+                 JOIN entity_properties p_status ON mtg.id = p_status.entity_id AND p_status.key = 'status' \
+                 WHERE mtg.entity_type = 'meeting' AND p_status.value = 'completed' \
+                   AND p_date.value >= $2 AND p_date.value <= $3",
+            )
+            .bind(tor_id)
+            .bind(&since)
+            .bind(today.format("%Y-%m-%d").to_string())
+            .fetch_all(pool)
+            .await?
+        }
+        _ => Vec::new(),
+    };
+    let parse = |s: &str| NaiveDate::parse_from_str(s.get(..10).unwrap_or(s), "%Y-%m-%d").ok();
+    Ok(rows.iter()
+        .filter_map(|(start, end)| parse(start).map(|start| (start, parse(end))))
+        .collect())
+}
+
+/// Measure every ToR with a pack and replace all compliance rows.
+/// Returns the number of ToRs measured.
+pub async fn recompute(pool: &PgPool, today: NaiveDate) -> Result<usize, sqlx::Error> {
+    let mut rows = Vec::new();
+    let assigned: Vec<(i64, Option<i64>)> = find_assignments(pool).await?
+        .into_iter()
+        .map(|(tor_id, _, pack_id)| (tor_id, pack_id))
+        .collect();
+    let mut measured = 0;
+    for (tor_id, pack_id) in assigned {
+        let Some(pack) = (match pack_id {
+            Some(id) => find_by_id(pool, id).await?,
+            None => None,
+        }) else {
+            continue;
+        };
+        measured += 1;
+        for (metric, _, _) in METRICS {
+            let limit = pack.limit(metric);
+            if limit <= 0 {
+                continue;
+            }
+            let (mut met, mut breached, mut pending) = (0, 0, 0);
+            for (start, end) in find_spans(pool, tor_id, metric, today).await? {
+                match classify(start, end, limit, today) {
+                    Outcome::Met => met += 1,
+                    Outcome::Breached => breached += 1,
+                    Outcome::Pending => pending += 1,
+                }
+            }
+            rows.push((tor_id, *metric, pack.id, limit, met, breached, pending));
+        }
+    }
+
+    let mut tx = pool.begin().await?;
+    sqlx::query("DELETE FROM sla_compliance").execute(&mut *tx).await?;
+    for (tor_id, metric, pack_id, limit, met, breached, pending) in rows {
+        sqlx::query(
+            "INSERT INTO sla_compliance (tor_id, metric, pack_id, limit_days, met, breached, pending, computed_on) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8::DATE)",
+        )
+        .bind(tor_id)
+        .bind(metric)
+        .bind(pack_id)
+        .bind(limit)
+        .bind(met)
+        .bind(breached)
+        .bind(pending)
+        .bind(today.format("%Y-%m-%d").to_string())
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+    Ok(measured)
+}
+
+/// Recompute unless compliance was already computed today. Called by the
+/// scheduler on every tick, so it runs on the first tick of each day.
+pub async fn run_nightly(pool: &PgPool, today: NaiveDate) -> Result<Option<usize>, sqlx::Error> {
+    let last: Option<String> = sqlx::query_scalar("SELECT TO_CHAR(MAX(computed_on), 'YYYY-MM-DD') FROM sla_compliance")
+        .fetch_one(pool)
+        .await?;
+    if last.as_deref() == Some(today.format("%Y-%m-%d").to_string().as_str()) {
+        return Ok(None);
+    }
+    recompute(pool, today).await.map(Some)
+}
+
+const SELECT_COMPLIANCE: &str =
+    "SELECT c.tor_id, t.label AS tor_label, p.label AS pack_label, c.metric, c.limit_days, \
+            c.met, c.breached, c.pending, TO_CHAR(c.computed_on, 'YYYY-MM-DD') AS computed_on \
+     FROM sla_compliance c \
+     JOIN entities t ON t.id = c.tor_id \
+     JOIN entities p ON p.id = c.pack_id";
+
+/// A ToR's compliance rows in metric order.
+pub async fn find_compliance(pool: &PgPool, tor_id: i64) -> Result<Vec<SlaCompliance>, sqlx::Error> {
+    let mut rows = sqlx::query_as::<_, SlaCompliance>(&format!("{} WHERE c.tor_id = $1", SELECT_COMPLIANCE))
+        .bind(tor_id)
+        .fetch_all(pool)
+        .await?;
+    rows.sort_by_key(|c| METRICS.iter().position(|(key, _, _)| *key == c.metric));
+    Ok(rows)
+}
@@ -0,0 +1,311 @@
+//! Topic clusters of open suggestions.
+//!
+//! A background job groups each ToR's open suggestions whose descriptions
+//! share enough terms (Postgres full-text lexemes, scored like
+//! [`proposal::find_similar`]) and stores each group as a
+//! `suggestion_cluster` linked to its ToR with `scoped_to_tor` and to its
+//! members with `clusters_suggestion`. A cluster is named after its member
+//! set, so a group the triager dismissed is not proposed again unless its
+//! membership changes. Converting a cluster creates one draft proposal and
+//! accepts the open members, linking each to it with `consolidated_into`.
+
+use std::collections::{BTreeSet, HashMap};
+
+use serde::Serialize;
+use sqlx::PgPool;
+
+use crate::errors::AppError;
+use crate::models::{entity, proposal, relation};
+
+pub const CLUSTER_ENTITY_TYPE: &str = "suggestion_cluster";
+
+/// Score from which two suggestions land in the same cluster.
+pub const CLUSTER_THRESHOLD: f64 = 0.3;
+
+/// Shared terms shown as a cluster's themes.
+const MAX_THEMES: usize = 4;
+
+/// A group of similar suggestions, found by the clustering job.
+#[derive(Debug, Clone, Serialize)]
+pub struct SuggestionCluster {
+    pub id: i64,
+    pub tor_id: i64,
+    /// Terms most members share, comma-separated.
+    pub themes: String,
+    /// Mean similarity of the linked members, 0 to 1.
+    pub score: f64,
+    /// "proposed", "dismissed" or "converted".
+    pub status: String,
+    pub proposal_id: Option<i64>,
+    pub members: Vec<ClusterMember>,
+}
+
+impl SuggestionCluster {
+    pub fn score_percent(&self) -> i64 {
+        (self.score * 100.0).round() as i64
+    }
+
+    /// Title offered when converting the cluster to a proposal.
+    pub fn suggested_title(&self) -> String {
+        format!("Consolidated: {}", self.themes)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct ClusterMember {
+    pub id: i64,
+    pub description: String,
+    pub status: String,
+    pub submitted_by_name: String,
+}
+
+/// A group found by [`group_similar`]: member IDs (ascending), mean score
+/// and shared terms.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Grouping {
+    pub members: Vec<i64>,
+    pub score: f64,
+    pub themes: Vec<String>,
+}
+
+/// Group items whose terms overlap at least `threshold`. Items join a group
+/// through any member (single linkage); groups of one are dropped.
+pub fn group_similar(items: &[(i64, Vec<String>)], threshold: f64) -> Vec<Grouping> {
+    let mut parent: Vec<usize> = (0..items.len()).collect();
+    fn root(parent: &mut [usize], mut i: usize) -> usize {
+        while parent[i] != i {
+            parent[i] = parent[parent[i]];
+            i = parent[i];
+        }
+        i
+    }
+
+    let mut edges = Vec::new();
+    for i in 0..items.len() {
+        for j in i + 1..items.len() {
+            let score = proposal::term_overlap(&items[i].1, &items[j].1);
+            if score >= threshold {
+                edges.push((i, j, score));
+                let (a, b) = (root(&mut parent, i), root(&mut parent, j));
+                parent[a] = b;
+            }
+        }
+    }
+
+    let mut groups: HashMap<usize, (Vec<usize>, Vec<f64>)> = HashMap::new();
+    for i in 0..items.len() {
+        let r = root(&mut parent, i);
+        groups.entry(r).or_default().0.push(i);
+    }
+    for (i, _, score) in edges {
+        let r = root(&mut parent, i);
+        groups.entry(r).or_default().1.push(score);
+    }
+
+    let mut result: Vec<Grouping> = groups
+        .into_values()
+        .filter(|(members, _)| members.len() > 1)
+        .map(|(members, scores)| {
+            let mut counts: HashMap<&str, usize> = HashMap::new();
+            for &m in &members {
+                for term in items[m].1.iter().collect::<BTreeSet<_>>() {
+                    *counts.entry(term.as_str()).or_default() += 1;
+                }
+            }
+            let mut shared: Vec<(&str, usize)> = counts.into_iter().filter(|(_, n)| *n > 1).collect();
+            shared.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+            let mut ids: Vec<i64> = members.iter().map(|&m| items[m].0).collect();
+            ids.sort_unstable();
+            Grouping {
+                members: ids,
+                score: scores.iter().sum::<f64>() / scores.len() as f64,
+                themes: shared.into_iter().take(MAX_THEMES).map(|(t, _)| t.to_string()).collect(),
+            }
+        })
+        .collect();
+    result.sort_by(|a, b| a.members.cmp(&b.members));
+    result
+}
+
+fn cluster_name(tor_id: i64, members: &[i64]) -> String {
+    let ids: Vec<String> = members.iter().map(|id| id.to_string()).collect();
+    format!("{}-{}", tor_id, ids.join("-"))
+}
+
+/// Re-cluster the open suggestions of every ToR. New groups are stored as
+/// proposed clusters; proposed clusters that no longer match a group are
+/// removed. Dismissed and converted clusters are kept. Returns the number
+/// of new clusters.
+pub async fn refresh_clusters(pool: &PgPool) -> Result<usize, AppError> {
+    let rows: Vec<(i64, i64, Vec<String>)> = sqlx::query_as(
+        "SELECT r.target_id, e.id, tsvector_to_array(to_tsvector('english', COALESCE(p_desc.value, ''))) \
+         FROM entities e \
+         JOIN relations r ON e.id = r.source_id \
+         JOIN entities rt ON r.relation_type_id = rt.id AND rt.name = 'suggested_to' \
+         LEFT JOIN entity_properties p_status ON e.id = p_status.entity_id AND p_status.key = 'status' \
+         LEFT JOIN entity_properties p_desc ON e.id = p_desc.entity_id AND p_desc.key = 'description' \
+         WHERE e.entity_type = 'suggestion' AND COALESCE(p_status.value, 'open') = 'open' \
+         ORDER BY r.target_id, e.id",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut by_tor: HashMap<i64, Vec<(i64, Vec<String>)>> = HashMap::new();
+    for (tor_id, id, terms) in rows {
+        by_tor.entry(tor_id).or_default().push((id, terms));
+    }
+
+    let mut current = BTreeSet::new();
+    let mut created = 0;
+    for (tor_id, items) in by_tor {
+        for group in group_similar(&items, CLUSTER_THRESHOLD) {
+            let name = cluster_name(tor_id, &group.members);
+            current.insert(name.clone());
+            if entity::find_by_type_and_name(pool, CLUSTER_ENTITY_TYPE, &name).await?.is_some() {
+                continue;
+            }
+            let themes = group.themes.join(", ");
+            let id = entity::create(pool, CLUSTER_ENTITY_TYPE, &name, &themes).await?;
+            entity::set_properties(pool, id, &[
+                ("status", "proposed"),
+                ("themes", &themes),
+                ("score", &format!("{:.3}", group.score)),
+            ]).await?;
+            relation::create(pool, "scoped_to_tor", id, tor_id).await?;
+            for member in &group.members {
+                relation::create(pool, "clusters_suggestion", id, *member).await?;
+            }
+            created += 1;
+        }
+    }
+
+    let proposed: Vec<(i64, String)> = sqlx::query_as(
+        "SELECT e.id, e.name FROM entities e \
+         JOIN entity_properties p ON p.entity_id = e.id AND p.key = 'status' AND p.value = 'proposed' \
+         WHERE e.entity_type = $1",
+    )
+    .bind(CLUSTER_ENTITY_TYPE)
+    .fetch_all(pool)
+    .await?;
+    for (id, name) in proposed {
+        if !current.contains(&name) {
+            entity::delete(pool, id).await?;
+        }
+    }
+    Ok(created)
+}
+
+const SELECT_CLUSTER: &str =
+    "SELECT e.id, r.target_id AS tor_id, COALESCE(p_themes.value, e.label) AS themes, \
+            COALESCE(NULLIF(p_score.value, ''), '0')::FLOAT8 AS score, \
+            COALESCE(p_status.value, 'proposed') AS status, \
+            NULLIF(p_proposal.value, '')::BIGINT AS proposal_id \
+     FROM entities e \
+     JOIN relations r ON r.source_id = e.id \
+     JOIN entities rt ON rt.id = r.relation_type_id AND rt.name = 'scoped_to_tor' \
+     LEFT JOIN entity_properties p_themes ON e.id = p_themes.entity_id AND p_themes.key = 'themes' \
+     LEFT JOIN entity_properties p_score ON e.id = p_score.entity_id AND p_score.key = 'score' \
+     LEFT JOIN entity_properties p_status ON e.id = p_status.entity_id AND p_status.key = 'status' \
+     LEFT JOIN entity_properties p_proposal ON e.id = p_proposal.entity_id AND p_proposal.key = 'proposal_id' \
+     WHERE e.entity_type = 'suggestion_cluster'";
+
+#[derive(sqlx::FromRow)]
+struct ClusterRow {
+    id: i64,
+    tor_id: i64,
+    themes: String,
+    score: f64,
+    status: String,
+    proposal_id: Option<i64>,
+}
+
+async fn with_members(pool: &PgPool, row: ClusterRow) -> Result<SuggestionCluster, AppError> {
+    let members = sqlx::query_as::<_, ClusterMember>(
+        "SELECT s.id, COALESCE(p_desc.value, '') AS description, \
+                COALESCE(p_status.value, 'open') AS status, COALESCE(u.label, '') AS submitted_by_name \
+         FROM relations r \
+         JOIN entities rt ON rt.id = r.relation_type_id AND rt.name = 'clusters_suggestion' \
+         JOIN entities s ON s.id = r.target_id \
+         LEFT JOIN entity_properties p_desc ON s.id = p_desc.entity_id AND p_desc.key = 'description' \
+         LEFT JOIN entity_properties p_status ON s.id = p_status.entity_id AND p_status.key = 'status' \
+         LEFT JOIN entity_properties p_by ON s.id = p_by.entity_id AND p_by.key = 'submitted_by_id' \
+         LEFT JOIN entities u ON u.id = CAST(NULLIF(p_by.value, '') AS BIGINT) \
+         WHERE r.source_id = $1 \
+         ORDER BY s.id",
+    )
+    .bind(row.id)
+    .fetch_all(pool)
+    .await?;
+    Ok(SuggestionCluster {
+        id: row.id,
+        tor_id: row.tor_id,
+        themes: row.themes,
+        score: row.score,
+        status: row.status,
+        proposal_id: row.proposal_id,
+        members,
+    })
+}
+
+/// Proposed clusters of a ToR, best first, for the triager.
+pub async fn find_proposed_clusters(pool: &PgPool, tor_id: i64) -> Result<Vec<SuggestionCluster>, AppError> {
+    let rows = sqlx::query_as::<_, ClusterRow>(&format!(
+        "{} AND r.target_id = $1 AND COALESCE(p_status.value, 'proposed') = 'proposed' ORDER BY score DESC, e.id",
+        SELECT_CLUSTER
+    ))
+    .bind(tor_id)
+    .fetch_all(pool)
+    .await?;
+    let mut clusters = Vec::with_capacity(rows.len());
+    for row in rows {
+        clusters.push(with_members(pool, row).await?);
+    }
+    Ok(clusters)
+}
+
+pub async fn find_cluster(pool: &PgPool, id: i64) -> Result<Option<SuggestionCluster>, AppError> {
+    let row = sqlx::query_as::<_, ClusterRow>(&format!("{} AND e.id = $1", SELECT_CLUSTER))
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+    match row {
+        Some(row) => Ok(Some(with_members(pool, row).await?)),
+        None => Ok(None),
+    }
+}
+
+/// Set a proposed cluster aside; it is not proposed again.
+pub async fn dismiss_cluster(pool: &PgPool, id: i64) -> Result<(), AppError> {
+    entity::set_property(pool, id, "status", "dismissed").await?;
+    Ok(())
+}
+
+/// Turn a cluster into one draft proposal. The open members are accepted
+/// and linked to it; members decided in the meantime are left alone. Returns
+/// the proposal ID.
+pub async fn convert_cluster(
+    pool: &PgPool,
+    cluster: &SuggestionCluster,
+    title: &str,
+    user_id: i64,
+    today: &str,
+) -> Result<i64, AppError> {
+    let open: Vec<&ClusterMember> = cluster.members.iter().filter(|m| m.status == "open").collect();
+    let mut description = format!("Consolidated from {} suggestions:\n", open.len());
+    for m in &open {
+        description.push_str(&format!("\n- #{} ({}): {}", m.id, m.submitted_by_name, m.description));
+    }
+    let ids: Vec<String> = open.iter().map(|m| format!("#{}", m.id)).collect();
+    let rationale = format!("Raised in suggestions {}", ids.join(", "));
+
+    let proposal_id = proposal::create(pool, cluster.tor_id, title, &description, &rationale, user_id, today, None).await?;
+    for m in open {
+        super::update_status(pool, m.id, "accepted", None).await?;
+        relation::create(pool, "consolidated_into", m.id, proposal_id).await?;
+    }
+    entity::set_properties(pool, cluster.id, &[
+        ("status", "converted"),
+        ("proposal_id", &proposal_id.to_string()),
+    ]).await?;
+    Ok(proposal_id)
+}
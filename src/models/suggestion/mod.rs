@@ -1,5 +1,9 @@
 pub mod types;
 pub mod queries;
+pub mod sunset;
+pub mod clusters;
 
 pub use types::*;
 pub use queries::*;
+pub use sunset::*;
+pub use clusters::*;
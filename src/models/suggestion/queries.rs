@@ -24,6 +24,8 @@ pub async fn find_all_for_tor(pool: &PgPool, tor_id: i64) -> Result<Vec<Suggesti
         submitted_by_name: String,
         rejection_reason: Option<String>,
         spawned_proposal_id: Option<i64>,
+        closed_at: Option<String>,
+        close_reason: Option<String>,
     }
 
     let rows = sqlx::query_as::<_, Row>(
@@ -34,7 +36,9 @@ pub async fn find_all_for_tor(pool: &PgPool, tor_id: i64) -> Result<Vec<Suggesti
                 COALESCE(p_by.value, '0') AS submitted_by_id, \
                 COALESCE(u.label, '') AS submitted_by_name, \
                 p_reason.value AS rejection_reason, \
-                r_spawn.target_id AS spawned_proposal_id \
+                r_spawn.target_id AS spawned_proposal_id, \
+                p_closed.value AS closed_at, \
+                p_close_reason.value AS close_reason \
          FROM entities e \
          JOIN relations r ON e.id = r.source_id \
          JOIN entities rt ON r.relation_type_id = rt.id AND rt.name = 'suggested_to' \
@@ -52,9 +56,13 @@ pub async fn find_all_for_tor(pool: &PgPool, tor_id: i64) -> Result<Vec<Suggesti
              ON e.id = p_reason.entity_id AND p_reason.key = 'rejection_reason' \
          LEFT JOIN relations r_spawn \
              ON e.id = r_spawn.source_id \
-            AND r_spawn.relation_type_id = ( \
+            AND r_spawn.relation_type_id IN ( \
                 SELECT id FROM entities \
-                WHERE entity_type = 'relation_type' AND name = 'spawns_proposal') \
+                WHERE entity_type = 'relation_type' AND name IN ('spawns_proposal', 'consolidated_into')) \
+         LEFT JOIN entity_properties p_closed \
+             ON e.id = p_closed.entity_id AND p_closed.key = 'closed_at' \
+         LEFT JOIN entity_properties p_close_reason \
+             ON e.id = p_close_reason.entity_id AND p_close_reason.key = 'close_reason' \
          WHERE e.entity_type = 'suggestion' AND r.target_id = $1 \
          ORDER BY submitted_date DESC",
     )
@@ -76,6 +84,8 @@ pub async fn find_all_for_tor(pool: &PgPool, tor_id: i64) -> Result<Vec<Suggesti
                 status: row.status,
                 rejection_reason: row.rejection_reason,
                 spawned_proposal_id: row.spawned_proposal_id,
+                closed_at: row.closed_at,
+                close_reason: row.close_reason,
             }
         })
         .collect();
@@ -128,9 +138,9 @@ pub async fn find_all_cross_tor(pool: &PgPool, user_id: Option<i64>) -> Result<V
                         ON e.id = p_reason.entity_id AND p_reason.key = 'rejection_reason' \
                     LEFT JOIN relations r_spawn \
                         ON e.id = r_spawn.source_id \
-                       AND r_spawn.relation_type_id = ( \
+                       AND r_spawn.relation_type_id IN ( \
                            SELECT id FROM entities \
-                           WHERE entity_type = 'relation_type' AND name = 'spawns_proposal') \
+                           WHERE entity_type = 'relation_type' AND name IN ('spawns_proposal', 'consolidated_into')) \
                     WHERE e.entity_type = 'suggestion'";
 
     let rows = if let Some(uid) = user_id {
@@ -221,9 +231,9 @@ pub async fn find_by_id(pool: &PgPool, id: i64) -> Result<Option<SuggestionDetai
              ON e.id = p_reason.entity_id AND p_reason.key = 'rejection_reason' \
          LEFT JOIN relations r_spawn \
              ON e.id = r_spawn.source_id \
-            AND r_spawn.relation_type_id = ( \
+            AND r_spawn.relation_type_id IN ( \
                 SELECT id FROM entities \
-                WHERE entity_type = 'relation_type' AND name = 'spawns_proposal') \
+                WHERE entity_type = 'relation_type' AND name IN ('spawns_proposal', 'consolidated_into')) \
          WHERE e.id = $1 AND e.entity_type = 'suggestion'",
     )
     .bind(id)
@@ -254,7 +264,8 @@ pub async fn create(
     submitted_by_id: i64,
     submitted_date: &str,
 ) -> Result<i64, AppError> {
-    let name = format!("suggestion_{}_{}", submitted_date.replace('-', "_"), tor_id);
+    let base = format!("suggestion_{}_{}", submitted_date.replace('-', "_"), tor_id);
+    let name = entity::available_name(pool, "suggestion", &base).await?;
     let label = make_preview(description, 50);
 
     let suggestion_id = entity::create(pool, "suggestion", &name, &label).await?;
@@ -0,0 +1,194 @@
+//! Sunset of stale suggestions.
+//!
+//! An open suggestion nobody has touched for `suggestions.stale_after_days`
+//! (overridable per ToR; 0 turns the policy off) gets a warning: its
+//! submitter is told it will close, and `stale_warned_at` records when. If it
+//! is still untouched `suggestions.stale_warning_days` later it is closed
+//! with status `closed`. The submitter, or a reviewer, may reopen it within
+//! `suggestions.reopen_grace_days` of closing.
+//!
+//! "Touched" means the entity's `updated_at` moved. `stale_warned_at` holds
+//! the database time of the warning write, which bumps `updated_at` to the
+//! same instant, so any later change shows as `updated_at > stale_warned_at`.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+
+use crate::models::{entity, setting};
+
+pub const STALE_AFTER_DAYS: &str = "suggestions.stale_after_days";
+pub const STALE_WARNING_DAYS: &str = "suggestions.stale_warning_days";
+pub const REOPEN_GRACE_DAYS: &str = "suggestions.reopen_grace_days";
+
+/// An open suggestion due for a warning or for closing.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct StaleSuggestion {
+    pub id: i64,
+    pub tor_id: i64,
+    pub tor_name: String,
+    pub label: String,
+    pub submitted_by_id: i64,
+    /// The ToR's stale period in days.
+    pub stale_days: i64,
+    /// The ToR's days between warning and closing.
+    pub warning_days: i64,
+}
+
+impl StaleSuggestion {
+    pub fn link(&self) -> String {
+        format!("/tor/{}/workflow?tab=suggestions", self.tor_id)
+    }
+}
+
+/// An open suggestion with the times its sunset is measured from, in
+/// microseconds since the epoch so equal instants compare equal.
+#[derive(sqlx::FromRow)]
+struct OpenRow {
+    id: i64,
+    tor_id: i64,
+    tor_name: String,
+    label: String,
+    submitted_by_id: i64,
+    updated_at: i64,
+    stale_warned_at: Option<i64>,
+}
+
+/// `days` before `now`, in microseconds since the epoch.
+fn micros_before(now: DateTime<Utc>, days: i64) -> i64 {
+    (now - chrono::Duration::days(days)).timestamp_micros()
+}
+
+/// A ToR's sunset period: its override, else the global value. A value that
+/// is not a whole number falls back to the global one rather than failing
+/// the job for every ToR.
+async fn period_for_tor(pool: &PgPool, tor_id: i64, name: &str, global: i64) -> i64 {
+    setting::get_value_for_tor(pool, tor_id, name, &global.to_string()).await
+        .trim()
+        .parse()
+        .unwrap_or(global)
+}
+
+/// Open suggestions with each ToR's sunset periods resolved, kept when
+/// `due` holds for them. ToRs with the policy off are left out.
+async fn find_open(
+    pool: &PgPool,
+    due: impl Fn(&OpenRow, i64, i64) -> bool,
+) -> Result<Vec<StaleSuggestion>, sqlx::Error> {
+    let rows = sqlx::query_as::<_, OpenRow>(
+        "SELECT e.id, t.id AS tor_id, t.label AS tor_name, e.label, \
+                COALESCE(NULLIF(p_by.value, '')::BIGINT, 0) AS submitted_by_id, \
+                (EXTRACT(EPOCH FROM e.updated_at) * 1000000)::BIGINT AS updated_at, \
+                (EXTRACT(EPOCH FROM p_warn.value::TIMESTAMPTZ) * 1000000)::BIGINT AS stale_warned_at \
+         FROM entities e \
+         JOIN entity_properties p_status ON p_status.entity_id = e.id AND p_status.key = 'status' AND p_status.value = 'open' \
+         JOIN relations r ON r.source_id = e.id \
+             AND r.relation_type_id = (SELECT id FROM entities WHERE entity_type = 'relation_type' AND name = 'suggested_to') \
+         JOIN entities t ON t.id = r.target_id \
+         LEFT JOIN entity_properties p_by ON p_by.entity_id = e.id AND p_by.key = 'submitted_by_id' \
+         LEFT JOIN entity_properties p_warn ON p_warn.entity_id = e.id AND p_warn.key = 'stale_warned_at' \
+         WHERE e.entity_type = 'suggestion' \
+         ORDER BY e.id",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let global_stale = setting::get_i64(pool, STALE_AFTER_DAYS).await;
+    let global_warning = setting::get_i64(pool, STALE_WARNING_DAYS).await;
+    let mut periods: HashMap<i64, (i64, i64)> = HashMap::new();
+    let mut found = Vec::new();
+    for row in rows {
+        let (stale_days, warning_days) = match periods.get(&row.tor_id) {
+            Some(p) => *p,
+            None => {
+                let p = (
+                    period_for_tor(pool, row.tor_id, STALE_AFTER_DAYS, global_stale).await,
+                    period_for_tor(pool, row.tor_id, STALE_WARNING_DAYS, global_warning).await,
+                );
+                periods.insert(row.tor_id, p);
+                p
+            }
+        };
+        if stale_days <= 0 || !due(&row, stale_days, warning_days) {
+            continue;
+        }
+        found.push(StaleSuggestion {
+            id: row.id,
+            tor_id: row.tor_id,
+            tor_name: row.tor_name,
+            label: row.label,
+            submitted_by_id: row.submitted_by_id,
+            stale_days,
+            warning_days,
+        });
+    }
+    Ok(found)
+}
+
+/// Open suggestions untouched for their ToR's stale period that have not
+/// been warned since they were last touched.
+pub async fn find_to_warn(pool: &PgPool, now: DateTime<Utc>) -> Result<Vec<StaleSuggestion>, sqlx::Error> {
+    find_open(pool, |row, stale_days, _| {
+        row.updated_at < micros_before(now, stale_days)
+            && row.stale_warned_at.is_none_or(|warned| row.updated_at > warned)
+    })
+    .await
+}
+
+/// Warned suggestions still untouched once the warning period has passed.
+pub async fn find_to_close(pool: &PgPool, now: DateTime<Utc>) -> Result<Vec<StaleSuggestion>, sqlx::Error> {
+    find_open(pool, |row, _, warning_days| {
+        row.stale_warned_at.is_some_and(|warned| {
+            row.updated_at <= warned && warned < micros_before(now, warning_days)
+        })
+    })
+    .await
+}
+
+/// Record that the submitter was warned. Returns the date the suggestion
+/// closes if it stays untouched.
+pub async fn mark_warned(pool: &PgPool, suggestion: &StaleSuggestion, now: DateTime<Utc>) -> Result<String, sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO entity_properties (entity_id, key, value) VALUES ($1, 'stale_warned_at', NOW()::TEXT) \
+         ON CONFLICT (entity_id, key) DO UPDATE SET value = excluded.value",
+    )
+    .bind(suggestion.id)
+    .execute(pool)
+    .await?;
+    Ok((now + chrono::Duration::days(suggestion.warning_days)).format("%Y-%m-%d").to_string())
+}
+
+/// Close a stale suggestion. Returns the last day it may be reopened.
+pub async fn auto_close(pool: &PgPool, suggestion: &StaleSuggestion, now: DateTime<Utc>) -> Result<String, sqlx::Error> {
+    let reason = format!("Closed automatically after {} days without activity", suggestion.stale_days + suggestion.warning_days);
+    let closed_at = now.to_rfc3339();
+    entity::set_properties(pool, suggestion.id, &[
+        ("status", "closed"),
+        ("closed_at", &closed_at),
+        ("close_reason", &reason),
+    ]).await?;
+    entity::delete_property(pool, suggestion.id, "stale_warned_at").await?;
+    let grace = setting::get_duration(pool, REOPEN_GRACE_DAYS).await;
+    Ok((now + grace).format("%Y-%m-%d").to_string())
+}
+
+/// Whether a closed suggestion may still be reopened.
+pub async fn can_reopen(pool: &PgPool, suggestion_id: i64, now: DateTime<Utc>) -> Result<bool, sqlx::Error> {
+    if entity::get_property(pool, suggestion_id, "status").await?.as_deref() != Some("closed") {
+        return Ok(false);
+    }
+    let closed_at = entity::get_property(pool, suggestion_id, "closed_at").await?
+        .and_then(|v| DateTime::parse_from_rfc3339(&v).ok());
+    let grace = setting::get_duration(pool, REOPEN_GRACE_DAYS).await;
+    Ok(closed_at.is_some_and(|at| now <= at.with_timezone(&Utc) + grace))
+}
+
+/// Reopen an auto-closed suggestion; reopening counts as activity.
+pub async fn reopen(pool: &PgPool, suggestion_id: i64) -> Result<(), sqlx::Error> {
+    entity::set_property(pool, suggestion_id, "status", "open").await?;
+    for key in ["closed_at", "close_reason", "stale_warned_at"] {
+        entity::delete_property(pool, suggestion_id, key).await?;
+    }
+    Ok(())
+}
@@ -12,6 +12,9 @@ pub struct SuggestionListItem {
     pub status: String,
     pub rejection_reason: Option<String>,
     pub spawned_proposal_id: Option<i64>,
+    /// When a stale suggestion was closed automatically (RFC 3339).
+    pub closed_at: Option<String>,
+    pub close_reason: Option<String>,
 }
 
 /// Suggestion as shown in the cross-ToR workflow index view.
@@ -0,0 +1,426 @@
+//! ToR self-assessment surveys.
+//!
+//! Admins define questionnaire templates (`survey_template`) of rating
+//! questions, answered 1-5, and open questions. A ToR runs a template as a
+//! `self_assessment` for a period ("2026 Q4"), linked to it with
+//! `scoped_to_tor`; the questions are copied onto the assessment so editing
+//! or removing the template leaves past results intact. Members answer once
+//! per assessment (`survey_response`, replaced if they answer again) while
+//! it is open.
+//!
+//! The ToR's governance health score is the average rating of its latest
+//! assessment with responses, scaled to 0-100.
+
+use std::collections::HashMap;
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+
+use super::{entity, relation, tor};
+
+/// Highest answer to a rating question; the lowest is 1.
+pub const RATING_MAX: i64 = 5;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SurveyQuestion {
+    pub key: String,
+    pub text: String,
+    /// `rating` (1-5) or `text`.
+    pub kind: String,
+}
+
+impl SurveyQuestion {
+    pub fn is_rating(&self) -> bool {
+        self.kind == "rating"
+    }
+
+    /// Form field for the question's answer.
+    pub fn field(&self) -> String {
+        format!("answer_{}", self.key)
+    }
+}
+
+/// Questions from one-per-line rating and open question lists, keyed in order.
+pub fn parse_questions(ratings: &str, open: &str) -> Vec<SurveyQuestion> {
+    let lines = |text: &str, kind: &'static str| -> Vec<(String, &'static str)> {
+        text.lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+            .map(|l| (l.to_string(), kind))
+            .collect()
+    };
+    lines(ratings, "rating").into_iter()
+        .chain(lines(open, "text"))
+        .enumerate()
+        .map(|(i, (text, kind))| SurveyQuestion { key: format!("q{}", i + 1), text, kind: kind.to_string() })
+        .collect()
+}
+
+fn questions_from_json(json: &str) -> Vec<SurveyQuestion> {
+    serde_json::from_str(json).unwrap_or_default()
+}
+
+/// Validate a template. Returns a list of problems.
+pub fn validate_template(label: &str, questions: &[SurveyQuestion]) -> Vec<String> {
+    let mut errors = Vec::new();
+    if label.trim().is_empty() {
+        errors.push("Title is required".to_string());
+    }
+    if !questions.iter().any(|q| q.is_rating()) {
+        errors.push("Add at least one rating question".to_string());
+    }
+    errors
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct SurveyTemplate {
+    pub id: i64,
+    pub label: String,
+    pub description: String,
+    pub questions: String, // JSON: [{key, text, kind}]
+}
+
+impl SurveyTemplate {
+    pub fn questions_list(&self) -> Vec<SurveyQuestion> {
+        questions_from_json(&self.questions)
+    }
+}
+
+fn slug(label: &str) -> String {
+    let s: String = label.trim().to_lowercase().chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if s.is_empty() { "survey".to_string() } else { s }
+}
+
+const SELECT_TEMPLATES: &str =
+    "SELECT e.id, e.label, \
+            COALESCE(p_desc.value, '') AS description, \
+            COALESCE(p_q.value, '[]') AS questions \
+     FROM entities e \
+     LEFT JOIN entity_properties p_desc ON e.id = p_desc.entity_id AND p_desc.key = 'description' \
+     LEFT JOIN entity_properties p_q ON e.id = p_q.entity_id AND p_q.key = 'questions' \
+     WHERE e.entity_type = 'survey_template'";
+
+pub async fn create_template(
+    pool: &PgPool,
+    label: &str,
+    description: &str,
+    questions: &[SurveyQuestion],
+) -> Result<i64, sqlx::Error> {
+    let name = entity::available_name(pool, "survey_template", &slug(label)).await?;
+    let id = entity::create(pool, "survey_template", &name, label.trim()).await?;
+    let json = serde_json::to_string(questions).unwrap_or_else(|_| "[]".to_string());
+    entity::set_properties(pool, id, &[("description", description.trim()), ("questions", &json)]).await?;
+    Ok(id)
+}
+
+pub async fn find_templates(pool: &PgPool) -> Result<Vec<SurveyTemplate>, sqlx::Error> {
+    sqlx::query_as::<_, SurveyTemplate>(&format!("{SELECT_TEMPLATES} ORDER BY e.label"))
+        .fetch_all(pool)
+        .await
+}
+
+pub async fn find_template(pool: &PgPool, id: i64) -> Result<Option<SurveyTemplate>, sqlx::Error> {
+    sqlx::query_as::<_, SurveyTemplate>(&format!("{SELECT_TEMPLATES} AND e.id = $1"))
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+}
+
+/// Remove a template. Assessments already run from it keep their questions.
+pub async fn delete_template(pool: &PgPool, id: i64) -> Result<(), sqlx::Error> {
+    entity::delete(pool, id).await
+}
+
+/// A template run by a ToR for one period.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct Assessment {
+    pub id: i64,
+    pub tor_id: i64,
+    pub label: String,
+    pub period: String,
+    /// `open` or `closed`.
+    pub status: String,
+    pub opened_at: String,
+    /// Last day answers are accepted, `YYYY-MM-DD`; empty when open-ended.
+    pub closes_on: String,
+    pub questions: String, // JSON: [{key, text, kind}]
+    pub responses: i64,
+}
+
+impl Assessment {
+    pub fn questions_list(&self) -> Vec<SurveyQuestion> {
+        questions_from_json(&self.questions)
+    }
+
+    /// Accepting answers: not closed, and not past its closing day.
+    pub fn is_open(&self) -> bool {
+        let today = Local::now().format("%Y-%m-%d").to_string();
+        self.status == "open" && (self.closes_on.is_empty() || today <= self.closes_on)
+    }
+
+    /// Check submitted answers, keyed by form field. Ratings are required
+    /// and must be 1-5; open answers are optional. Returns the answers by
+    /// question key, or the problems found.
+    pub fn check_answers(&self, form: &HashMap<String, String>) -> Result<HashMap<String, String>, Vec<String>> {
+        let mut answers = HashMap::new();
+        let mut errors = Vec::new();
+        for q in self.questions_list() {
+            let answer = form.get(&q.field()).map(|a| a.trim()).unwrap_or("");
+            if q.is_rating() {
+                match answer.parse::<i64>() {
+                    Ok(v) if (1..=RATING_MAX).contains(&v) => {
+                        answers.insert(q.key, v.to_string());
+                    }
+                    _ => errors.push(format!("Rate \u{201c}{}\u{201d} from 1 to {}", q.text, RATING_MAX)),
+                }
+            } else if !answer.is_empty() {
+                answers.insert(q.key, answer.to_string());
+            }
+        }
+        if errors.is_empty() { Ok(answers) } else { Err(errors) }
+    }
+}
+
+const SELECT_ASSESSMENTS: &str =
+    "SELECT e.id, r_tor.target_id AS tor_id, e.label, \
+            COALESCE(p_period.value, '') AS period, \
+            COALESCE(p_status.value, 'open') AS status, \
+            COALESCE(p_opened.value, '') AS opened_at, \
+            COALESCE(p_closes.value, '') AS closes_on, \
+            COALESCE(p_q.value, '[]') AS questions, \
+            (SELECT COUNT(*) FROM entities s \
+             JOIN entity_properties p_a ON s.id = p_a.entity_id AND p_a.key = 'assessment_id' \
+             WHERE s.entity_type = 'survey_response' AND p_a.value = e.id::text) AS responses \
+     FROM entities e \
+     JOIN relations r_tor ON r_tor.source_id = e.id \
+         AND r_tor.relation_type_id = ( \
+             SELECT id FROM entities WHERE entity_type = 'relation_type' AND name = 'scoped_to_tor') \
+     LEFT JOIN entity_properties p_period ON e.id = p_period.entity_id AND p_period.key = 'period' \
+     LEFT JOIN entity_properties p_status ON e.id = p_status.entity_id AND p_status.key = 'status' \
+     LEFT JOIN entity_properties p_opened ON e.id = p_opened.entity_id AND p_opened.key = 'opened_at' \
+     LEFT JOIN entity_properties p_closes ON e.id = p_closes.entity_id AND p_closes.key = 'closes_on' \
+     LEFT JOIN entity_properties p_q ON e.id = p_q.entity_id AND p_q.key = 'questions' \
+     WHERE e.entity_type = 'self_assessment'";
+
+/// Start a self-assessment of a ToR from a template.
+pub async fn start_assessment(
+    pool: &PgPool,
+    tor_id: i64,
+    template: &SurveyTemplate,
+    period: &str,
+    closes_on: &str,
+) -> Result<i64, sqlx::Error> {
+    let name = entity::available_name(pool, "self_assessment", &format!("assessment_{}", tor_id)).await?;
+    let label = format!("{} \u{2014} {}", template.label, period.trim());
+    let id = entity::create(pool, "self_assessment", &name, &label).await?;
+    entity::set_properties(pool, id, &[
+        ("template_id", &template.id.to_string()),
+        ("period", period.trim()),
+        ("status", "open"),
+        ("opened_at", &Local::now().format("%Y-%m-%d").to_string()),
+        ("closes_on", closes_on),
+        ("questions", &template.questions),
+    ]).await?;
+    relation::create(pool, "scoped_to_tor", id, tor_id).await?;
+    Ok(id)
+}
+
+/// A ToR's assessments, newest first.
+pub async fn find_assessments(pool: &PgPool, tor_id: i64) -> Result<Vec<Assessment>, sqlx::Error> {
+    sqlx::query_as::<_, Assessment>(&format!("{SELECT_ASSESSMENTS} AND r_tor.target_id = $1 ORDER BY e.id DESC"))
+        .bind(tor_id)
+        .fetch_all(pool)
+        .await
+}
+
+pub async fn find_assessment(pool: &PgPool, id: i64) -> Result<Option<Assessment>, sqlx::Error> {
+    sqlx::query_as::<_, Assessment>(&format!("{SELECT_ASSESSMENTS} AND e.id = $1"))
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+}
+
+pub async fn close_assessment(pool: &PgPool, id: i64) -> Result<(), sqlx::Error> {
+    entity::set_property(pool, id, "status", "closed").await
+}
+
+/// Store a member's answers, replacing any earlier ones.
+pub async fn submit_response(
+    pool: &PgPool,
+    assessment_id: i64,
+    user_id: i64,
+    answers: &HashMap<String, String>,
+) -> Result<i64, sqlx::Error> {
+    let name = format!("response_a{}_by{}", assessment_id, user_id);
+    let json = serde_json::to_string(answers).unwrap_or_else(|_| "{}".to_string());
+    let submitted_at = Local::now().format("%Y-%m-%d %H:%M").to_string();
+    if let Some(existing) = entity::find_by_type_and_name(pool, "survey_response", &name).await? {
+        entity::set_properties(pool, existing.id, &[("answers", &json), ("submitted_at", &submitted_at)]).await?;
+        return Ok(existing.id);
+    }
+    let id = entity::create(pool, "survey_response", &name, &name).await?;
+    entity::set_properties(pool, id, &[
+        ("assessment_id", &assessment_id.to_string()),
+        ("user_id", &user_id.to_string()),
+        ("answers", &json),
+        ("submitted_at", &submitted_at),
+    ]).await?;
+    Ok(id)
+}
+
+/// The member's answers to an assessment, if they responded.
+pub async fn find_response(
+    pool: &PgPool,
+    assessment_id: i64,
+    user_id: i64,
+) -> Result<Option<HashMap<String, String>>, sqlx::Error> {
+    let name = format!("response_a{}_by{}", assessment_id, user_id);
+    let Some(response) = entity::find_by_type_and_name(pool, "survey_response", &name).await? else {
+        return Ok(None);
+    };
+    Ok(entity::get_property(pool, response.id, "answers").await?
+        .and_then(|json| serde_json::from_str(&json).ok()))
+}
+
+async fn find_all_answers(pool: &PgPool, assessment_id: i64) -> Result<Vec<HashMap<String, String>>, sqlx::Error> {
+    let rows: Vec<(String,)> = sqlx::query_as(
+        "SELECT COALESCE(p_ans.value, '{}') \
+         FROM entities e \
+         JOIN entity_properties p_a ON e.id = p_a.entity_id AND p_a.key = 'assessment_id' \
+         LEFT JOIN entity_properties p_ans ON e.id = p_ans.entity_id AND p_ans.key = 'answers' \
+         WHERE e.entity_type = 'survey_response' AND p_a.value = $1 \
+         ORDER BY e.id",
+    )
+    .bind(assessment_id.to_string())
+    .fetch_all(pool)
+    .await?;
+    Ok(rows.into_iter().filter_map(|(json,)| serde_json::from_str(&json).ok()).collect())
+}
+
+/// Answers to one question across all responses.
+#[derive(Debug, Clone)]
+pub struct QuestionResult {
+    pub question: SurveyQuestion,
+    /// Answers per rating, 1 to 5; empty for open questions.
+    pub distribution: Vec<i64>,
+    /// Open answers; empty for rating questions.
+    pub comments: Vec<String>,
+}
+
+impl QuestionResult {
+    pub fn answered(&self) -> i64 {
+        self.distribution.iter().sum()
+    }
+
+    /// Mean rating, or None without answers.
+    pub fn average(&self) -> Option<f64> {
+        let total: i64 = self.distribution.iter().enumerate().map(|(i, n)| (i as i64 + 1) * n).sum();
+        (self.answered() > 0).then(|| total as f64 / self.answered() as f64)
+    }
+
+    /// Mean rating to one decimal, or "—".
+    pub fn average_label(&self) -> String {
+        self.average().map(|a| format!("{a:.1}")).unwrap_or_else(|| "\u{2014}".to_string())
+    }
+
+    /// Share of the answers with this rating, 0-100.
+    pub fn percent(&self, rating: &i64) -> i64 {
+        let answered = self.answered();
+        let count = self.distribution.get((*rating - 1) as usize).copied().unwrap_or(0);
+        if answered == 0 { 0 } else { count * 100 / answered }
+    }
+}
+
+/// Aggregated answers to an assessment.
+#[derive(Debug, Clone)]
+pub struct SurveyResults {
+    pub questions: Vec<QuestionResult>,
+    pub responses: i64,
+    /// 0-100 from all ratings; None without responses.
+    pub score: Option<i64>,
+}
+
+/// Map a mean rating (1-5) onto 0-100.
+fn score_from_average(average: f64) -> i64 {
+    ((average - 1.0) * 100.0 / (RATING_MAX - 1) as f64).round() as i64
+}
+
+pub async fn find_results(pool: &PgPool, assessment: &Assessment) -> Result<SurveyResults, sqlx::Error> {
+    let responses = find_all_answers(pool, assessment.id).await?;
+    let questions: Vec<QuestionResult> = assessment.questions_list().into_iter()
+        .map(|question| {
+            let answers = responses.iter().filter_map(|r| r.get(&question.key));
+            let (mut distribution, mut comments) = (Vec::new(), Vec::new());
+            if question.is_rating() {
+                distribution = vec![0; RATING_MAX as usize];
+                for v in answers.filter_map(|a| a.parse::<i64>().ok()).filter(|v| (1..=RATING_MAX).contains(v)) {
+                    distribution[(v - 1) as usize] += 1;
+                }
+            } else {
+                comments = answers.cloned().collect();
+            }
+            QuestionResult { question, distribution, comments }
+        })
+        .collect();
+
+    let (sum, count) = questions.iter()
+        .filter(|q| q.question.is_rating())
+        .fold((0i64, 0i64), |(sum, count), q| {
+            let total: i64 = q.distribution.iter().enumerate().map(|(i, n)| (i as i64 + 1) * n).sum();
+            (sum + total, count + q.answered())
+        });
+    let score = (count > 0).then(|| score_from_average(sum as f64 / count as f64));
+    Ok(SurveyResults { questions, responses: responses.len() as i64, score })
+}
+
+/// A ToR's governance health from its latest assessment with responses.
+#[derive(Debug, Clone)]
+pub struct HealthScore {
+    pub assessment_id: i64,
+    pub period: String,
+    /// 0-100.
+    pub score: i64,
+    pub responses: i64,
+    pub members: i64,
+}
+
+impl HealthScore {
+    pub fn band(&self) -> &'static str {
+        match self.score {
+            75.. => "Healthy",
+            50..=74 => "Needs attention",
+            _ => "At risk",
+        }
+    }
+
+    pub fn badge_class(&self) -> &'static str {
+        match self.score {
+            75.. => "badge-success",
+            50..=74 => "badge-warning",
+            _ => "badge-danger",
+        }
+    }
+
+    /// Share of members who responded, 0-100.
+    pub fn participation(&self) -> i64 {
+        if self.members == 0 { 0 } else { (self.responses * 100 / self.members).min(100) }
+    }
+}
+
+pub async fn find_health_score(pool: &PgPool, tor_id: i64) -> Result<Option<HealthScore>, sqlx::Error> {
+    let Some(latest) = find_assessments(pool, tor_id).await?.into_iter().find(|a| a.responses > 0) else {
+        return Ok(None);
+    };
+    let results = find_results(pool, &latest).await?;
+    let members = tor::count_members(pool, tor_id).await?;
+    Ok(results.score.map(|score| HealthScore {
+        assessment_id: latest.id,
+        period: latest.period,
+        score,
+        responses: results.responses,
+        members,
+    }))
+}
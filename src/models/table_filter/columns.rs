@@ -55,7 +55,7 @@ pub async fn resolve_columns(
 
 /// Apply a comma-separated ordered column string to the full column list.
 /// Columns in the string appear first (in order), rest appended hidden at end.
-fn apply_pref(all_columns: &[ColumnDef], pref: &str) -> Vec<ColumnDef> {
+pub(crate) fn apply_pref(all_columns: &[ColumnDef], pref: &str) -> Vec<ColumnDef> {
     let ordered_keys: Vec<&str> = pref.split(',').map(str::trim).filter(|s| !s.is_empty()).collect();
     let mut result: Vec<ColumnDef> = vec![];
 
@@ -68,6 +68,76 @@ impl SortSpec {
     }
 }
 
+/// Rows-per-page choices offered by the controls bar.
+pub const PER_PAGE_OPTIONS: &[i64] = &[10, 25, 50, 100];
+
+/// Table state as it appears in a list page's query string, so a view can be
+/// bookmarked or shared: `page`, `per_page`, `filter` (a [`FilterTree`] as
+/// JSON), `sort`, `dir` and `columns` (visible column keys, comma-separated).
+/// Every value is re-checked before use; anything unknown falls back to the
+/// default.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TableQuery {
+    pub page: Option<i64>,
+    pub per_page: Option<i64>,
+    pub filter: Option<String>,
+    pub sort: Option<String>,
+    pub dir: Option<String>,
+    pub columns: Option<String>,
+}
+
+impl TableQuery {
+    pub fn page(&self) -> i64 {
+        self.page.unwrap_or(1).max(1)
+    }
+
+    /// Rows per page; only [`PER_PAGE_OPTIONS`] are accepted.
+    pub fn per_page(&self) -> i64 {
+        self.per_page.filter(|n| PER_PAGE_OPTIONS.contains(n)).unwrap_or(25)
+    }
+
+    /// The filter; empty when missing or malformed.
+    pub fn filter_tree(&self) -> FilterTree {
+        self.filter.as_deref()
+            .and_then(|s| FilterTree::from_json(s).ok())
+            .unwrap_or_default()
+    }
+
+    /// The sort, limited to the sort keys of sortable `columns`.
+    pub fn sort_spec(&self, columns: &[ColumnDef]) -> SortSpec {
+        let sort = self.sort.as_deref()
+            .filter(|key| columns.iter().any(|c| c.sortable && c.sort_key == *key));
+        SortSpec::from_params(sort, self.dir.as_deref())
+    }
+
+    /// Columns named in the URL, in order, when it names any known one;
+    /// `None` leaves the saved preference in charge.
+    pub fn columns(&self, all_columns: &[ColumnDef]) -> Option<Vec<ColumnDef>> {
+        let keys = self.columns.as_deref()?;
+        keys.split(',')
+            .any(|k| all_columns.iter().any(|c| c.key == k.trim()))
+            .then(|| columns::apply_pref(all_columns, keys))
+    }
+}
+
+/// JSON made safe to embed in a `<script type="application/json">` block:
+/// `<`, `>` and `&` are written as `\u` escapes, so values such as
+/// `</script>` cannot close the block. `JSON.parse` reads it back unchanged.
+pub fn script_json(json: &str) -> String {
+    let mut out = String::with_capacity(json.len());
+    for c in json.chars() {
+        match c {
+            '<' => out.push_str("\\u003c"),
+            '>' => out.push_str("\\u003e"),
+            '&' => out.push_str("\\u0026"),
+            '\u{2028}' => out.push_str("\\u2028"),
+            '\u{2029}' => out.push_str("\\u2029"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
 /// Ordered column definition passed to templates.
 #[derive(Debug, Clone)]
 pub struct ColumnDef {
@@ -79,6 +149,117 @@ pub struct ColumnDef {
     pub sort_key: String,
 }
 
+/// Paging, sorting and filter state of a list page, shared by the table
+/// partials (filter builder, controls bar, column picker, sortable headers and
+/// pagination) so they build the same links for every list.
+#[derive(Debug, Clone)]
+pub struct TableView {
+    /// List page path, e.g. `/users`.
+    pub base_url: String,
+    /// Plural noun for the result summary, e.g. "users".
+    pub noun: String,
+    pub filter_json: String,
+    pub sort: SortSpec,
+    pub page: i64,
+    pub per_page: i64,
+    pub total_pages: i64,
+    pub total_count: i64,
+    /// Rows on the current page.
+    pub shown: usize,
+    /// Visible column keys in order, comma-separated.
+    pub columns: String,
+    /// Whether the columns came from the URL rather than the saved
+    /// preference; links then keep them.
+    pub columns_pinned: bool,
+}
+
+impl TableView {
+    pub fn sort_column(&self) -> &str {
+        &self.sort.column
+    }
+
+    pub fn sort_dir(&self) -> &'static str {
+        self.sort.dir_str()
+    }
+
+    fn is_sorted(&self, key: &str) -> bool {
+        !key.is_empty() && self.sort.column == key
+    }
+
+    fn query(&self, page: i64, sort: &str, dir: &str, pin_columns: bool) -> String {
+        let mut query = format!(
+            "?page={}&per_page={}&sort={}&dir={}&filter={}",
+            page, self.per_page, encode(sort), dir, encode(&self.filter_json)
+        );
+        if pin_columns {
+            query.push_str(&format!("&columns={}", encode(&self.columns)));
+        }
+        query
+    }
+
+    /// Link that sorts by `key`: ascending, or the other way round when the
+    /// table is already sorted by it. Starts again from the first page.
+    pub fn sort_href(&self, key: &str) -> String {
+        let dir = if self.is_sorted(key) { self.sort.toggle_dir() } else { "asc" };
+        format!("{}{}", self.base_url, self.query(1, key, dir, self.columns_pinned))
+    }
+
+    /// Link to another page, keeping sort and filter.
+    pub fn page_href(&self, page: i64) -> String {
+        format!("{}{}", self.base_url, self.query(page, &self.sort.column, self.sort.dir_str(), self.columns_pinned))
+    }
+
+    /// Link to exactly this view — filter, sort, page and visible columns —
+    /// for bookmarking or sharing.
+    pub fn share_href(&self) -> String {
+        format!("{}{}", self.base_url, self.query(self.page, &self.sort.column, self.sort.dir_str(), true))
+    }
+
+    /// CSV export of the whole filtered, sorted list.
+    pub fn export_href(&self) -> String {
+        format!(
+            "{}/export.csv?filter={}&sort={}&dir={}",
+            self.base_url, encode(&self.filter_json), encode(&self.sort.column), self.sort.dir_str()
+        )
+    }
+
+    /// `aria-sort` value of a sortable column header.
+    pub fn aria_sort(&self, key: &str) -> &'static str {
+        match (self.is_sorted(key), &self.sort.dir) {
+            (false, _) => "none",
+            (true, SortDir::Asc) => "ascending",
+            (true, SortDir::Desc) => "descending",
+        }
+    }
+
+    /// Accessible name of a sort link, saying what activating it does.
+    pub fn sort_label(&self, label: &str, key: &str) -> String {
+        let next = if self.is_sorted(key) && self.sort.dir == SortDir::Asc { "descending" } else { "ascending" };
+        format!("{}: sort {}", label, next)
+    }
+
+    /// Arrow showing the current sort direction of a column; empty when unsorted.
+    pub fn sort_indicator(&self, key: &str) -> &'static str {
+        match self.aria_sort(key) {
+            "ascending" => "\u{25B2}",
+            "descending" => "\u{25BC}",
+            _ => "",
+        }
+    }
+}
+
+/// Percent-encode a query parameter value.
+pub(crate) fn encode(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for b in s.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => out.push(b as char),
+            _ => out.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    out
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -140,4 +321,107 @@ mod tests {
         assert_eq!(s.column, "email");
         assert_eq!(s.dir, SortDir::Asc);
     }
+
+    fn view(sort: &str, dir: &str) -> TableView {
+        TableView {
+            base_url: "/users".into(),
+            noun: "users".into(),
+            filter_json: r#"{"logic":"and"}"#.into(),
+            sort: SortSpec::from_params(Some(sort), Some(dir)),
+            page: 2,
+            per_page: 25,
+            total_pages: 3,
+            total_count: 60,
+            shown: 25,
+            columns: "user,email,actions".into(),
+            columns_pinned: false,
+        }
+    }
+
+    #[test]
+    fn table_view_sort_links_toggle_and_encode() {
+        let v = view("username", "asc");
+        assert_eq!(
+            v.sort_href("username"),
+            "/users?page=1&per_page=25&sort=username&dir=desc&filter=%7B%22logic%22%3A%22and%22%7D"
+        );
+        assert!(v.sort_href("email").contains("&sort=email&dir=asc&"));
+        assert!(v.page_href(3).starts_with("/users?page=3&per_page=25&sort=username&dir=asc&"));
+        assert!(v.export_href().starts_with("/users/export.csv?filter=%7B"));
+    }
+
+    #[test]
+    fn table_view_aria_sort_states() {
+        let v = view("username", "desc");
+        assert_eq!(v.aria_sort("username"), "descending");
+        assert_eq!(v.aria_sort("email"), "none");
+        assert_eq!(v.sort_label("Name", "username"), "Name: sort ascending");
+        assert_eq!(view("username", "asc").sort_label("Name", "username"), "Name: sort descending");
+        assert_eq!(view("", "asc").aria_sort(""), "none");
+    }
+
+    #[test]
+    fn table_view_share_link_pins_columns() {
+        let mut v = view("email", "desc");
+        assert!(!v.page_href(1).contains("columns="));
+        assert!(v.share_href().starts_with("/users?page=2&per_page=25&sort=email&dir=desc&"));
+        assert!(v.share_href().ends_with("&columns=user%2Cemail%2Cactions"));
+        v.columns_pinned = true;
+        assert!(v.sort_href("username").ends_with("&columns=user%2Cemail%2Cactions"));
+    }
+
+    fn column(key: &str, sortable: bool, always_visible: bool) -> ColumnDef {
+        ColumnDef {
+            key: key.into(),
+            label: key.into(),
+            visible: true,
+            always_visible,
+            sortable,
+            sort_key: if sortable { key.into() } else { String::new() },
+        }
+    }
+
+    #[test]
+    fn table_query_rechecks_every_value() {
+        let cols = vec![column("user", true, true), column("email", true, false), column("status", false, false)];
+        let q = TableQuery {
+            page: Some(-3),
+            per_page: Some(5000),
+            filter: Some("not json".into()),
+            sort: Some("password".into()),
+            dir: Some("desc".into()),
+            columns: Some("secret,<b>".into()),
+        };
+        assert_eq!((q.page(), q.per_page()), (1, 25));
+        assert!(q.filter_tree().is_empty());
+        assert_eq!(q.sort_spec(&cols).column, "");
+        assert!(q.columns(&cols).is_none());
+
+        let q = TableQuery {
+            page: Some(4),
+            per_page: Some(50),
+            filter: Some(r#"{"conditions":[{"field":"email","op":"contains","value":"@acme"}]}"#.into()),
+            sort: Some("email".into()),
+            dir: Some("desc".into()),
+            columns: Some("status,email".into()),
+        };
+        assert_eq!((q.page(), q.per_page()), (4, 50));
+        assert_eq!(q.filter_tree().conditions[0].value, "@acme");
+        assert_eq!((q.sort_spec(&cols).column.as_str(), q.sort_spec(&cols).dir), ("email", SortDir::Desc));
+        assert_eq!(TableQuery { sort: Some("status".into()), ..Default::default() }.sort_spec(&cols).column, "");
+        let shown: Vec<(String, bool)> = q.columns(&cols).unwrap().into_iter().map(|c| (c.key, c.visible)).collect();
+        assert_eq!(shown, vec![("status".into(), true), ("email".into(), true), ("user".into(), true)]);
+    }
+
+    #[test]
+    fn script_json_cannot_close_the_script_block() {
+        let tree = FilterTree {
+            conditions: vec![Condition { field: "username".into(), op: "contains".into(), value: "</script><b>&".into() }],
+            ..Default::default()
+        };
+        let safe = script_json(&tree.to_json());
+        assert!(!safe.contains('<') && !safe.contains('>') && !safe.contains('&'));
+        let back = FilterTree::from_json(&safe).unwrap();
+        assert_eq!(back.conditions[0].value, "</script><b>&");
+    }
 }
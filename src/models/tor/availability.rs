@@ -0,0 +1,222 @@
+use chrono::{Datelike, NaiveDate, Weekday};
+use serde::Serialize;
+use sqlx::PgPool;
+
+use crate::models::{entity, relation};
+
+use super::types::TorMember;
+
+/// How far either side of a requested date alternatives are searched.
+const SUGGESTION_WINDOW_DAYS: i64 = 14;
+
+/// A period in which a member cannot attend the ToR's meetings.
+///
+/// Stored as an `unavailability` entity linked to the user (`for_user`) and
+/// the ToR (`scoped_to_tor`). `kind` is `recurring` (every `weekday`) or
+/// `absence` (`start_date`..=`end_date`).
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct Unavailability {
+    pub id: i64,
+    pub user_id: i64,
+    pub user_label: String,
+    pub kind: String,
+    pub weekday: String,
+    pub start_date: String,
+    pub end_date: String,
+    pub note: String,
+}
+
+impl Unavailability {
+    pub fn covers(&self, date: NaiveDate) -> bool {
+        match self.kind.as_str() {
+            "recurring" => self.weekday.parse::<Weekday>().is_ok_and(|w| w == date.weekday()),
+            _ => {
+                let parse = |s: &str| NaiveDate::parse_from_str(s, "%Y-%m-%d").ok();
+                match (parse(&self.start_date), parse(&self.end_date)) {
+                    (Some(start), Some(end)) => start <= date && date <= end,
+                    _ => false,
+                }
+            }
+        }
+    }
+
+    /// "Every Tuesday" or "2026-03-02 – 2026-03-06".
+    pub fn describe(&self) -> String {
+        if self.kind == "recurring" {
+            format!("Every {}", self.weekday)
+        } else if self.start_date == self.end_date {
+            self.start_date.clone()
+        } else {
+            format!("{} – {}", self.start_date, self.end_date)
+        }
+    }
+}
+
+/// All recorded unavailability for a ToR's members.
+pub async fn find_for_tor(pool: &PgPool, tor_id: i64) -> Result<Vec<Unavailability>, sqlx::Error> {
+    sqlx::query_as::<_, Unavailability>(
+        "SELECT e.id, u.id AS user_id, u.label AS user_label, \
+                COALESCE(p_kind.value, 'absence') AS kind, \
+                COALESCE(p_wd.value, '') AS weekday, \
+                COALESCE(p_start.value, '') AS start_date, \
+                COALESCE(p_end.value, '') AS end_date, \
+                COALESCE(p_note.value, '') AS note \
+         FROM entities e \
+         JOIN relations r_tor ON r_tor.source_id = e.id AND r_tor.target_id = $1 \
+             AND r_tor.relation_type_id = ( \
+                 SELECT id FROM entities WHERE entity_type = 'relation_type' AND name = 'scoped_to_tor') \
+         JOIN relations r_user ON r_user.source_id = e.id \
+             AND r_user.relation_type_id = ( \
+                 SELECT id FROM entities WHERE entity_type = 'relation_type' AND name = 'for_user') \
+         JOIN entities u ON u.id = r_user.target_id \
+         LEFT JOIN entity_properties p_kind ON p_kind.entity_id = e.id AND p_kind.key = 'kind' \
+         LEFT JOIN entity_properties p_wd ON p_wd.entity_id = e.id AND p_wd.key = 'weekday' \
+         LEFT JOIN entity_properties p_start ON p_start.entity_id = e.id AND p_start.key = 'start_date' \
+         LEFT JOIN entity_properties p_end ON p_end.entity_id = e.id AND p_end.key = 'end_date' \
+         LEFT JOIN entity_properties p_note ON p_note.entity_id = e.id AND p_note.key = 'note' \
+         WHERE e.entity_type = 'unavailability' \
+         ORDER BY u.label, p_kind.value DESC, p_start.value, e.id",
+    )
+    .bind(tor_id)
+    .fetch_all(pool)
+    .await
+}
+
+async fn create_entry(
+    pool: &PgPool,
+    tor_id: i64,
+    user_id: i64,
+    props: &[(&str, &str)],
+) -> Result<i64, sqlx::Error> {
+    let name = entity::available_name(pool, "unavailability", &format!("unavailability_{}_{}", tor_id, user_id)).await?;
+    let id = entity::create(pool, "unavailability", &name, "Unavailability").await?;
+    entity::set_properties(pool, id, props).await?;
+    relation::create(pool, "for_user", id, user_id).await?;
+    relation::create(pool, "scoped_to_tor", id, tor_id).await?;
+    Ok(id)
+}
+
+/// Record that a member cannot attend on a given weekday, every week.
+pub async fn add_recurring(
+    pool: &PgPool,
+    tor_id: i64,
+    user_id: i64,
+    weekday: Weekday,
+    note: &str,
+) -> Result<i64, sqlx::Error> {
+    let weekday = weekday_name(weekday);
+    create_entry(pool, tor_id, user_id, &[("kind", "recurring"), ("weekday", weekday), ("note", note)]).await
+}
+
+/// Record a one-off absence covering `start`..=`end`.
+pub async fn add_absence(
+    pool: &PgPool,
+    tor_id: i64,
+    user_id: i64,
+    start: NaiveDate,
+    end: NaiveDate,
+    note: &str,
+) -> Result<i64, sqlx::Error> {
+    let start = start.format("%Y-%m-%d").to_string();
+    let end = end.format("%Y-%m-%d").to_string();
+    create_entry(pool, tor_id, user_id, &[("kind", "absence"), ("start_date", &start), ("end_date", &end), ("note", note)]).await
+}
+
+pub fn weekday_name(weekday: Weekday) -> &'static str {
+    match weekday {
+        Weekday::Mon => "Monday",
+        Weekday::Tue => "Tuesday",
+        Weekday::Wed => "Wednesday",
+        Weekday::Thu => "Thursday",
+        Weekday::Fri => "Friday",
+        Weekday::Sat => "Saturday",
+        Weekday::Sun => "Sunday",
+    }
+}
+
+/// Projected attendance of a ToR's position holders on one date.
+///
+/// Vacant positions are not counted; a user holding several positions
+/// counts once per position.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProjectedAttendance {
+    pub date: String,
+    pub weekday: String,
+    pub mandatory_total: usize,
+    pub mandatory_available: usize,
+    pub optional_total: usize,
+    pub optional_available: usize,
+    /// "Label (Position)" for each unavailable holder, mandatory first.
+    pub unavailable: Vec<String>,
+}
+
+impl ProjectedAttendance {
+    fn rank(&self) -> (usize, usize) {
+        (self.mandatory_available, self.mandatory_available + self.optional_available)
+    }
+}
+
+/// Project who can attend on `date` given the recorded unavailability.
+pub fn project_attendance(members: &[TorMember], entries: &[Unavailability], date: NaiveDate) -> ProjectedAttendance {
+    let mut projection = ProjectedAttendance {
+        date: date.format("%Y-%m-%d").to_string(),
+        weekday: weekday_name(date.weekday()).to_string(),
+        mandatory_total: 0,
+        mandatory_available: 0,
+        optional_total: 0,
+        optional_available: 0,
+        unavailable: Vec::new(),
+    };
+    for m in members {
+        let Some(holder_id) = m.holder_id else { continue };
+        let mandatory = m.membership_type == "mandatory";
+        let away = entries.iter().any(|e| e.user_id == holder_id && e.covers(date));
+        match (mandatory, away) {
+            (true, false) => projection.mandatory_available += 1,
+            (false, false) => projection.optional_available += 1,
+            _ => projection.unavailable.push(format!(
+                "{} ({})",
+                m.holder_label.as_deref().unwrap_or(""),
+                m.position_label
+            )),
+        }
+        if mandatory {
+            projection.mandatory_total += 1;
+        } else {
+            projection.optional_total += 1;
+        }
+    }
+    projection
+}
+
+/// Weekdays within two weeks of `requested` (and after `today`) with better
+/// projected attendance, best first: most mandatory members available, then
+/// most members overall, then closest to the requested date.
+///
+/// Empty when everyone can attend on the requested date.
+pub fn suggest_dates(
+    members: &[TorMember],
+    entries: &[Unavailability],
+    requested: NaiveDate,
+    today: NaiveDate,
+    limit: usize,
+) -> Vec<ProjectedAttendance> {
+    let baseline = project_attendance(members, entries, requested);
+    if baseline.unavailable.is_empty() {
+        return Vec::new();
+    }
+
+    let mut candidates: Vec<(i64, ProjectedAttendance)> = (-SUGGESTION_WINDOW_DAYS..=SUGGESTION_WINDOW_DAYS)
+        .filter(|&offset| offset != 0)
+        .map(|offset| (offset, requested + chrono::Duration::days(offset)))
+        .filter(|(_, date)| *date > today)
+        .filter(|(_, date)| !matches!(date.weekday(), Weekday::Sat | Weekday::Sun))
+        .map(|(offset, date)| (offset, project_attendance(members, entries, date)))
+        .filter(|(_, p)| p.rank() > baseline.rank())
+        .collect();
+
+    candidates.sort_by(|(oa, a), (ob, b)| {
+        b.rank().cmp(&a.rank()).then(oa.abs().cmp(&ob.abs())).then(oa.cmp(ob))
+    });
+    candidates.into_iter().take(limit).map(|(_, p)| p).collect()
+}
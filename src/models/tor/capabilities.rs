@@ -0,0 +1,176 @@
+//! Position capabilities within a ToR.
+//!
+//! Each ToR stores default capabilities per membership type as
+//! `capability_defaults` (JSON `{"mandatory": [..], "optional": [..]}`).
+//! A position inherits the defaults for its membership type unless it has
+//! an explicit grant or deny in `capability_overrides` (JSON `{cap: bool}`).
+//!
+//! The effective result is written back to the position's `can_*`
+//! properties, which is what `auth::abac` checks.
+
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+
+use crate::models::entity;
+
+use super::queries::find_members;
+
+/// Capability keys with their display labels, in matrix column order.
+pub const CAPABILITIES: &[(&str, &str)] = &[
+    ("can_call_meetings", "Call meetings"),
+    ("can_manage_agenda", "Manage agenda"),
+    ("can_record_decisions", "Record decisions"),
+    ("can_review_suggestions", "Review suggestions"),
+    ("can_create_proposals", "Create proposals"),
+    ("can_approve_proposals", "Approve proposals"),
+];
+
+/// Membership types that carry their own defaults.
+pub const MEMBERSHIP_TYPES: &[(&str, &str)] = &[
+    ("mandatory", "Mandatory members"),
+    ("optional", "Optional members"),
+];
+
+pub fn is_capability(key: &str) -> bool {
+    CAPABILITIES.iter().any(|(k, _)| *k == key)
+}
+
+/// ToR-level capabilities granted to every position of a membership type.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CapabilityDefaults {
+    #[serde(default)]
+    pub mandatory: Vec<String>,
+    #[serde(default)]
+    pub optional: Vec<String>,
+}
+
+impl CapabilityDefaults {
+    pub fn grants(&self, membership_type: &str, capability: &str) -> bool {
+        let list = if membership_type == "mandatory" { &self.mandatory } else { &self.optional };
+        list.iter().any(|c| c == capability)
+    }
+}
+
+/// A position's explicit overrides and what it inherits from the ToR.
+#[derive(Debug, Clone)]
+pub struct PositionCapabilities {
+    pub position_id: i64,
+    pub position_label: String,
+    pub membership_type: String,
+    pub holder_label: Option<String>,
+    /// Explicit grant (`true`) or deny (`false`); absent keys inherit.
+    pub overrides: BTreeMap<String, bool>,
+    /// Capabilities granted by the defaults for this membership type.
+    pub inherited: Vec<String>,
+}
+
+impl PositionCapabilities {
+    /// `inherit`, `grant` or `deny`, for the matrix selects.
+    pub fn setting(&self, capability: &str) -> &'static str {
+        match self.overrides.get(capability) {
+            Some(true) => "grant",
+            Some(false) => "deny",
+            None => "inherit",
+        }
+    }
+
+    pub fn inherits(&self, capability: &str) -> bool {
+        self.inherited.iter().any(|c| c == capability)
+    }
+
+    /// The effective capability after overrides.
+    pub fn has(&self, capability: &str) -> bool {
+        self.overrides.get(capability).copied().unwrap_or_else(|| self.inherits(capability))
+    }
+}
+
+/// Every position in a ToR against every capability.
+#[derive(Debug, Clone)]
+pub struct CapabilityMatrix {
+    pub defaults: CapabilityDefaults,
+    pub positions: Vec<PositionCapabilities>,
+}
+
+/// Load the ToR's defaults and each position's overrides.
+///
+/// Positions configured before the editor existed have no
+/// `capability_overrides`; their raw `can_*` properties are read as explicit
+/// overrides so saving the matrix leaves them unchanged.
+pub async fn find_capability_matrix(pool: &PgPool, tor_id: i64) -> Result<CapabilityMatrix, sqlx::Error> {
+    let defaults: CapabilityDefaults = entity::get_properties(pool, tor_id).await?
+        .get("capability_defaults")
+        .and_then(|json| serde_json::from_str(json).ok())
+        .unwrap_or_default();
+
+    let mut positions: Vec<PositionCapabilities> = Vec::new();
+    for m in find_members(pool, tor_id).await? {
+        // A position with several holders appears once per holder.
+        if positions.iter().any(|p| p.position_id == m.position_id) {
+            continue;
+        }
+        let props = entity::get_properties(pool, m.position_id).await?;
+        let overrides = match props.get("capability_overrides") {
+            Some(json) => serde_json::from_str::<BTreeMap<String, bool>>(json)
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|(k, _)| is_capability(k))
+                .collect(),
+            None => props.iter()
+                .filter(|(k, _)| is_capability(k))
+                .map(|(k, v)| (k.clone(), v == "true"))
+                .collect(),
+        };
+        let inherited = CAPABILITIES.iter()
+            .map(|(k, _)| *k)
+            .filter(|k| defaults.grants(&m.membership_type, k))
+            .map(String::from)
+            .collect();
+        positions.push(PositionCapabilities {
+            position_id: m.position_id,
+            position_label: m.position_label,
+            membership_type: m.membership_type,
+            holder_label: m.holder_label,
+            overrides,
+            inherited,
+        });
+    }
+
+    Ok(CapabilityMatrix { defaults, positions })
+}
+
+/// Store the ToR defaults and per-position overrides, then apply them.
+/// Positions missing from `overrides` keep their current overrides.
+pub async fn save_capabilities(
+    pool: &PgPool,
+    tor_id: i64,
+    defaults: &CapabilityDefaults,
+    overrides: &HashMap<i64, BTreeMap<String, bool>>,
+) -> Result<(), sqlx::Error> {
+    let json = serde_json::to_string(defaults).unwrap_or_else(|_| "{}".to_string());
+    entity::set_property(pool, tor_id, "capability_defaults", &json).await?;
+
+    for (position_id, caps) in overrides {
+        let json = serde_json::to_string(caps).unwrap_or_else(|_| "{}".to_string());
+        entity::set_property(pool, *position_id, "capability_overrides", &json).await?;
+    }
+    apply_capabilities(pool, tor_id).await
+}
+
+/// Write each position's effective capabilities to its `can_*` properties.
+/// Called after the matrix is saved and whenever a position's membership
+/// type changes. Overrides are stored alongside so the written `can_*`
+/// values are not mistaken for explicit ones on the next load.
+pub async fn apply_capabilities(pool: &PgPool, tor_id: i64) -> Result<(), sqlx::Error> {
+    let matrix = find_capability_matrix(pool, tor_id).await?;
+    for p in &matrix.positions {
+        let json = serde_json::to_string(&p.overrides).unwrap_or_else(|_| "{}".to_string());
+        entity::set_property(pool, p.position_id, "capability_overrides", &json).await?;
+        for (key, _) in CAPABILITIES {
+            let value = if p.has(key) { "true" } else { "false" };
+            entity::set_property(pool, p.position_id, key, value).await?;
+        }
+    }
+    Ok(())
+}
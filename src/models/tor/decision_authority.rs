@@ -0,0 +1,127 @@
+use sqlx::PgPool;
+
+use crate::auth::session::Permissions;
+use crate::models::entity;
+use crate::models::opinion::OpinionListItem;
+
+use super::types::TorMember;
+
+/// Who may record decisions on a ToR's agenda points.
+///
+/// Stored on the ToR entity as `decision_authority` (`permission`,
+/// `positions` or `vote`) plus `decision_positions` (JSON array of
+/// position ids) for the position-based mode.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DecisionAuthority {
+    /// Anyone with the `agenda.decide` permission (the default).
+    Permission,
+    /// Holders of the listed positions.
+    Positions(Vec<i64>),
+    /// The course of action with a majority of member opinions; recorded by
+    /// anyone with `agenda.decide`.
+    Vote,
+}
+
+impl DecisionAuthority {
+    pub fn mode(&self) -> &'static str {
+        match self {
+            DecisionAuthority::Permission => "permission",
+            DecisionAuthority::Positions(_) => "positions",
+            DecisionAuthority::Vote => "vote",
+        }
+    }
+
+    pub fn includes_position(&self, position_id: i64) -> bool {
+        matches!(self, DecisionAuthority::Positions(ids) if ids.contains(&position_id))
+    }
+
+    /// Human-readable description for the agenda detail page.
+    pub fn describe(&self, members: &[TorMember]) -> String {
+        match self {
+            DecisionAuthority::Permission => "Members with the agenda.decide permission".to_string(),
+            DecisionAuthority::Positions(ids) => {
+                let labels: Vec<&str> = members.iter()
+                    .filter(|m| ids.contains(&m.position_id))
+                    .map(|m| m.position_label.as_str())
+                    .collect();
+                if labels.is_empty() {
+                    "No position currently holds decision authority".to_string()
+                } else {
+                    format!("Holders of: {}", labels.join(", "))
+                }
+            }
+            DecisionAuthority::Vote => "Majority of member opinions".to_string(),
+        }
+    }
+}
+
+/// Load the decision authority configured for a ToR.
+pub async fn find_decision_authority(pool: &PgPool, tor_id: i64) -> Result<DecisionAuthority, sqlx::Error> {
+    let props = entity::get_properties(pool, tor_id).await?;
+    Ok(match props.get("decision_authority").map(|s| s.as_str()) {
+        Some("positions") => {
+            let ids = props.get("decision_positions")
+                .and_then(|json| serde_json::from_str::<Vec<i64>>(json).ok())
+                .unwrap_or_default();
+            DecisionAuthority::Positions(ids)
+        }
+        Some("vote") => DecisionAuthority::Vote,
+        _ => DecisionAuthority::Permission,
+    })
+}
+
+/// Store the decision authority for a ToR.
+pub async fn save_decision_authority(
+    pool: &PgPool,
+    tor_id: i64,
+    authority: &DecisionAuthority,
+) -> Result<(), sqlx::Error> {
+    let positions = match authority {
+        DecisionAuthority::Positions(ids) => serde_json::to_string(ids).unwrap_or_else(|_| "[]".to_string()),
+        _ => "[]".to_string(),
+    };
+    entity::set_properties(pool, tor_id, &[
+        ("decision_authority", authority.mode()),
+        ("decision_positions", &positions),
+    ]).await
+}
+
+/// Whether a user may record decisions under the given authority.
+pub async fn can_decide(
+    pool: &PgPool,
+    authority: &DecisionAuthority,
+    user_id: i64,
+    permissions: &Permissions,
+) -> Result<bool, sqlx::Error> {
+    match authority {
+        DecisionAuthority::Permission | DecisionAuthority::Vote => Ok(permissions.has("agenda.decide")),
+        DecisionAuthority::Positions(ids) => {
+            if ids.is_empty() {
+                return Ok(false);
+            }
+            let (held,): (bool,) = sqlx::query_as(
+                "SELECT EXISTS( \
+                     SELECT 1 FROM relations r \
+                     WHERE r.source_id = $1 AND r.target_id = ANY($2) \
+                       AND r.relation_type_id = ( \
+                           SELECT id FROM entities WHERE entity_type = 'relation_type' AND name = 'fills_position'))",
+            )
+            .bind(user_id)
+            .bind(ids)
+            .fetch_one(pool)
+            .await?;
+            Ok(held)
+        }
+    }
+}
+
+/// The course of action preferred by more than half of the opinions, if any.
+pub fn vote_winner(opinions: &[OpinionListItem]) -> Option<i64> {
+    let mut counts: std::collections::HashMap<i64, usize> = std::collections::HashMap::new();
+    for op in opinions {
+        *counts.entry(op.preferred_coa_id).or_default() += 1;
+    }
+    counts.into_iter()
+        .find(|(_, n)| n * 2 > opinions.len())
+        .map(|(coa_id, _)| coa_id)
+}
@@ -0,0 +1,218 @@
+//! Position delegation while a member is on leave.
+//!
+//! ```text
+//! tor_function --(delegates_to)--> user
+//!              relation_properties: delegator_id, start_date, end_date, note
+//! ```
+//!
+//! Between `start_date` and `end_date` (inclusive, `YYYY-MM-DD`) the delegate
+//! holds the position's capabilities (see `auth::abac`) and may cast the
+//! delegator's ballot votes as a proxy. A delegation only counts while the
+//! delegator still fills the position. Expired delegations are removed by
+//! the scheduler; the audit log keeps the trail.
+
+use chrono::NaiveDate;
+use sqlx::PgPool;
+
+/// One position delegated to a user.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct PositionDelegation {
+    /// The `delegates_to` relation.
+    pub id: i64,
+    pub position_id: i64,
+    pub position_label: String,
+    pub tor_id: i64,
+    pub delegator_id: i64,
+    pub delegator_name: String,
+    pub delegate_id: i64,
+    pub delegate_name: String,
+    pub start_date: String,
+    pub end_date: String,
+    pub note: String,
+    /// Whether the delegator still fills the position.
+    pub delegator_holds: bool,
+}
+
+impl PositionDelegation {
+    /// Whether the delegate acts for the position on the given day.
+    pub fn is_active_on(&self, day: NaiveDate) -> bool {
+        let day = day.format("%Y-%m-%d").to_string();
+        self.delegator_holds && self.start_date <= day && day <= self.end_date
+    }
+
+    /// "Active", "Upcoming", "Ended" or "Lapsed" (the delegator left the position).
+    pub fn status_on(&self, day: NaiveDate) -> &'static str {
+        let day = day.format("%Y-%m-%d").to_string();
+        if !self.delegator_holds {
+            "Lapsed"
+        } else if day < self.start_date {
+            "Upcoming"
+        } else if day > self.end_date {
+            "Ended"
+        } else {
+            "Active"
+        }
+    }
+}
+
+const SELECT_DELEGATION: &str =
+    "SELECT r.id, f.id AS position_id, f.label AS position_label, r_tor.target_id AS tor_id, \
+            COALESCE(NULLIF(p_from.value, '')::BIGINT, 0) AS delegator_id, \
+            COALESCE(uf.label, '') AS delegator_name, \
+            u.id AS delegate_id, u.label AS delegate_name, \
+            COALESCE(p_start.value, '') AS start_date, \
+            COALESCE(p_end.value, '') AS end_date, \
+            COALESCE(p_note.value, '') AS note, \
+            EXISTS ( \
+                SELECT 1 FROM relations r_fills \
+                WHERE r_fills.target_id = f.id AND r_fills.source_id::TEXT = p_from.value \
+                  AND r_fills.relation_type_id = ( \
+                      SELECT id FROM entities WHERE entity_type = 'relation_type' AND name = 'fills_position') \
+            ) AS delegator_holds \
+     FROM relations r \
+     JOIN entities f ON f.id = r.source_id AND f.entity_type = 'tor_function' \
+     JOIN entities u ON u.id = r.target_id AND u.entity_type = 'user' \
+     JOIN relations r_tor ON r_tor.source_id = f.id \
+         AND r_tor.relation_type_id = ( \
+             SELECT id FROM entities WHERE entity_type = 'relation_type' AND name = 'belongs_to_tor') \
+     LEFT JOIN relation_properties p_from ON p_from.relation_id = r.id AND p_from.key = 'delegator_id' \
+     LEFT JOIN entities uf ON uf.id::TEXT = p_from.value \
+     LEFT JOIN relation_properties p_start ON p_start.relation_id = r.id AND p_start.key = 'start_date' \
+     LEFT JOIN relation_properties p_end ON p_end.relation_id = r.id AND p_end.key = 'end_date' \
+     LEFT JOIN relation_properties p_note ON p_note.relation_id = r.id AND p_note.key = 'note' \
+     WHERE r.relation_type_id = ( \
+         SELECT id FROM entities WHERE entity_type = 'relation_type' AND name = 'delegates_to')";
+
+/// Check a delegation before saving it.
+pub fn validate_delegation(delegator_id: i64, delegate_id: i64, start: &str, end: &str) -> Vec<String> {
+    let mut errors = Vec::new();
+    if delegator_id <= 0 {
+        errors.push("Only a filled position can be delegated".to_string());
+    }
+    if delegate_id <= 0 {
+        errors.push("Choose a delegate".to_string());
+    } else if delegate_id == delegator_id {
+        errors.push("The position holder cannot be their own delegate".to_string());
+    }
+    let start = NaiveDate::parse_from_str(start.trim(), "%Y-%m-%d");
+    let end = NaiveDate::parse_from_str(end.trim(), "%Y-%m-%d");
+    match (start, end) {
+        (Ok(start), Ok(end)) if end < start => errors.push("The end date must not be before the start date".to_string()),
+        (Ok(_), Ok(_)) => {}
+        _ => errors.push("Enter a start and end date".to_string()),
+    }
+    errors
+}
+
+/// Delegate a position to a user for a window, replacing any earlier
+/// delegation of the position to the same user. Returns the relation id.
+pub async fn delegate_position(
+    pool: &PgPool,
+    position_id: i64,
+    delegator_id: i64,
+    delegate_id: i64,
+    start: &str,
+    end: &str,
+    note: &str,
+) -> Result<i64, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let relation_id: i64 = sqlx::query_scalar(
+        "INSERT INTO relations (relation_type_id, source_id, target_id) \
+         VALUES ((SELECT id FROM entities WHERE entity_type = 'relation_type' AND name = 'delegates_to'), $1, $2) \
+         ON CONFLICT (relation_type_id, source_id, target_id) DO UPDATE SET updated_at = NOW() \
+         RETURNING id",
+    )
+    .bind(position_id)
+    .bind(delegate_id)
+    .fetch_one(&mut *tx)
+    .await?;
+    sqlx::query("DELETE FROM relation_properties WHERE relation_id = $1")
+        .bind(relation_id)
+        .execute(&mut *tx)
+        .await?;
+    let delegator = delegator_id.to_string();
+    for (key, value) in [
+        ("delegator_id", delegator.as_str()),
+        ("start_date", start.trim()),
+        ("end_date", end.trim()),
+        ("note", note.trim()),
+    ] {
+        sqlx::query("INSERT INTO relation_properties (relation_id, key, value) VALUES ($1, $2, $3)")
+            .bind(relation_id)
+            .bind(key)
+            .bind(value)
+            .execute(&mut *tx)
+            .await?;
+    }
+    tx.commit().await?;
+    Ok(relation_id)
+}
+
+pub async fn find_delegation(pool: &PgPool, id: i64) -> Result<Option<PositionDelegation>, sqlx::Error> {
+    sqlx::query_as::<_, PositionDelegation>(&format!("{} AND r.id = $1", SELECT_DELEGATION))
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+}
+
+/// Delegations in a ToR, by start date.
+pub async fn find_delegations(pool: &PgPool, tor_id: i64) -> Result<Vec<PositionDelegation>, sqlx::Error> {
+    sqlx::query_as::<_, PositionDelegation>(&format!(
+        "{} AND r_tor.target_id = $1 ORDER BY p_start.value, f.label, u.label",
+        SELECT_DELEGATION,
+    ))
+    .bind(tor_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// Delegations to a user across all ToRs, by start date.
+pub async fn find_delegations_to(pool: &PgPool, delegate_id: i64) -> Result<Vec<PositionDelegation>, sqlx::Error> {
+    sqlx::query_as::<_, PositionDelegation>(&format!(
+        "{} AND u.id = $1 ORDER BY p_start.value, f.label",
+        SELECT_DELEGATION,
+    ))
+    .bind(delegate_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// Delegations in a ToR the user acts under on `day`.
+pub async fn find_active_delegations(
+    pool: &PgPool,
+    delegate_id: i64,
+    tor_id: i64,
+    day: NaiveDate,
+) -> Result<Vec<PositionDelegation>, sqlx::Error> {
+    Ok(find_delegations(pool, tor_id).await?
+        .into_iter()
+        .filter(|d| d.delegate_id == delegate_id && d.is_active_on(day))
+        .collect())
+}
+
+/// End a delegation early. Returns false if it did not exist.
+pub async fn revoke_delegation(pool: &PgPool, id: i64) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "DELETE FROM relations WHERE id = $1 AND relation_type_id = ( \
+             SELECT id FROM entities WHERE entity_type = 'relation_type' AND name = 'delegates_to')",
+    )
+    .bind(id)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Remove delegations that ended before `day`. Returns how many were removed.
+pub async fn cleanup_expired_delegations(pool: &PgPool, day: NaiveDate) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        "DELETE FROM relations r \
+         USING relation_properties p_end \
+         WHERE p_end.relation_id = r.id AND p_end.key = 'end_date' AND p_end.value < $1 \
+           AND r.relation_type_id = ( \
+               SELECT id FROM entities WHERE entity_type = 'relation_type' AND name = 'delegates_to')",
+    )
+    .bind(day.format("%Y-%m-%d").to_string())
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
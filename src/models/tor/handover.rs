@@ -0,0 +1,347 @@
+//! Secretariat handover package.
+//!
+//! Everything an incoming ToR secretary needs in one archive: the active
+//! members, open work, upcoming meetings, the standing agenda (protocol
+//! steps), minutes still awaiting approval, and the ToR's configuration.
+//! The archive is a zip with a README, the full package as JSON, one CSV
+//! per list for opening in a spreadsheet, and a SHA-256 manifest.
+
+use serde_json::json;
+use sqlx::PgPool;
+
+use crate::errors::AppError;
+use crate::models::meeting::{self, MeetingListItem};
+use crate::models::protocol::{self, ProtocolStep};
+use crate::models::{export_record, minutes, proposal, setting, suggestion};
+
+use super::capabilities::find_capability_matrix;
+use super::decision_authority::find_decision_authority;
+use super::impact::{CLOSED_AGENDA_STATUSES, OPEN_PROPOSAL_STATUSES};
+use super::queries::{find_detail_by_id, find_members};
+use super::types::{TorDetail, TorMember};
+
+/// Meeting statuses that will not take place (again).
+const PAST_MEETING_STATUSES: &[&str] = &["completed", "cancelled"];
+
+/// Work on the ToR that still awaits action.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HandoverItem {
+    /// `proposal`, `suggestion`, `agenda_point` or `action_item`.
+    pub kind: &'static str,
+    pub title: String,
+    pub status: String,
+    /// Submitted, scheduled or due date.
+    pub date: String,
+    /// Who raised it, or who is responsible for an action item.
+    pub owner: String,
+}
+
+/// Minutes not yet approved, with their meeting.
+#[derive(Debug, Clone)]
+pub struct HandoverMinutes {
+    pub minutes_id: i64,
+    pub meeting_id: i64,
+    pub meeting_label: String,
+    pub meeting_date: String,
+    pub status: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct HandoverPackage {
+    pub tor: TorDetail,
+    pub generated_at: String,
+    /// Positions with a holder.
+    pub members: Vec<TorMember>,
+    pub vacancies: Vec<TorMember>,
+    pub open_items: Vec<HandoverItem>,
+    pub upcoming_meetings: Vec<MeetingListItem>,
+    pub standing_items: Vec<ProtocolStep>,
+    pub pending_minutes: Vec<HandoverMinutes>,
+    /// Meeting schedule, decision authority, capability defaults, proposal
+    /// intake form and setting overrides.
+    pub configuration: serde_json::Value,
+}
+
+/// Collect the handover package for a ToR.
+pub async fn build_handover(pool: &PgPool, tor_id: i64) -> Result<HandoverPackage, AppError> {
+    let tor = find_detail_by_id(pool, tor_id).await?.ok_or(AppError::NotFound)?;
+    let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+
+    let (members, vacancies): (Vec<_>, Vec<_>) = find_members(pool, tor_id).await?
+        .into_iter()
+        .partition(|m| m.holder_id.is_some());
+
+    let mut open_items: Vec<HandoverItem> = Vec::new();
+    for p in proposal::find_all_for_tor(pool, tor_id).await? {
+        if OPEN_PROPOSAL_STATUSES.contains(&p.status.as_str()) {
+            open_items.push(HandoverItem {
+                kind: "proposal",
+                title: p.title,
+                status: p.status,
+                date: p.submitted_date,
+                owner: p.submitted_by_name,
+            });
+        }
+    }
+    for s in suggestion::find_all_for_tor(pool, tor_id).await? {
+        if s.status == "open" {
+            open_items.push(HandoverItem {
+                kind: "suggestion",
+                title: s.description_preview,
+                status: s.status,
+                date: s.submitted_date,
+                owner: s.submitted_by_name,
+            });
+        }
+    }
+    for a in crate::models::agenda_point::find_all_for_tor(pool, tor_id).await? {
+        if !CLOSED_AGENDA_STATUSES.contains(&a.status.as_str()) {
+            open_items.push(HandoverItem {
+                kind: "agenda_point",
+                title: a.title,
+                status: a.status,
+                date: a.scheduled_date,
+                owner: String::new(),
+            });
+        }
+    }
+
+    let meetings = meeting::find_by_tor(pool, tor_id).await?;
+    let mut pending_minutes = Vec::new();
+    for m in meetings.iter().filter(|m| m.has_minutes) {
+        let Some(min) = minutes::find_by_meeting(pool, m.id).await? else { continue };
+        for item in min.action_items_list() {
+            if item.status != "done" {
+                open_items.push(HandoverItem {
+                    kind: "action_item",
+                    title: item.description,
+                    status: item.status,
+                    date: item.due_date,
+                    owner: item.responsible,
+                });
+            }
+        }
+        if min.status != "approved" {
+            pending_minutes.push(HandoverMinutes {
+                minutes_id: min.id,
+                meeting_id: m.id,
+                meeting_label: m.label.clone(),
+                meeting_date: m.meeting_date.clone(),
+                status: min.status,
+            });
+        }
+    }
+
+    let mut upcoming_meetings: Vec<MeetingListItem> = meetings.into_iter()
+        .filter(|m| m.meeting_date.as_str() >= today.as_str())
+        .filter(|m| !PAST_MEETING_STATUSES.contains(&m.status.as_str()))
+        .collect();
+    upcoming_meetings.sort_by(|a, b| a.meeting_date.cmp(&b.meeting_date));
+
+    let standing_items = protocol::find_steps_for_tor(pool, tor_id).await?;
+
+    let authority = find_decision_authority(pool, tor_id).await?;
+    let capabilities = find_capability_matrix(pool, tor_id).await?;
+    let intake = proposal::find_intake(pool, tor_id).await?;
+    let proposal_templates = proposal::find_templates(pool, tor_id).await?;
+    let overrides: serde_json::Map<String, serde_json::Value> = setting::find_for_tor(pool, tor_id).await?
+        .into_iter()
+        .filter_map(|s| s.override_value.map(|v| (s.name, json!(v))))
+        .collect();
+    let configuration = json!({
+        "schedule": {
+            "meeting_cadence": tor.meeting_cadence,
+            "cadence_day": tor.cadence_day,
+            "cadence_time": tor.cadence_time,
+            "cadence_duration_minutes": tor.cadence_duration_minutes,
+            "default_location": tor.default_location,
+            "remote_url": tor.remote_url,
+        },
+        "decision_authority": {
+            "mode": authority.mode(),
+            "description": authority.describe(&members),
+        },
+        "capability_defaults": capabilities.defaults,
+        "proposal_intake": intake,
+        "proposal_templates": proposal_templates,
+        "setting_overrides": overrides,
+    });
+
+    Ok(HandoverPackage {
+        tor,
+        generated_at: chrono::Local::now().format("%Y-%m-%d %H:%M").to_string(),
+        members,
+        vacancies,
+        open_items,
+        upcoming_meetings,
+        standing_items,
+        pending_minutes,
+        configuration,
+    })
+}
+
+fn escape_csv(s: &str) -> String {
+    if s.contains(',') || s.contains('"') || s.contains('\n') {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+fn csv(header: &str, rows: Vec<Vec<String>>) -> String {
+    let mut out = format!("{header}\n");
+    for row in rows {
+        let cells: Vec<String> = row.iter().map(|c| escape_csv(c)).collect();
+        out.push_str(&cells.join(","));
+        out.push('\n');
+    }
+    out
+}
+
+impl HandoverPackage {
+    /// Archive file name, e.g. `handover-finance_board-2026-10-16.zip`.
+    pub fn file_name(&self) -> String {
+        format!("handover-{}-{}.zip", self.tor.name, &self.generated_at[..10])
+    }
+
+    fn member_rows(members: &[TorMember]) -> Vec<Vec<String>> {
+        members.iter()
+            .map(|m| vec![
+                m.position_label.clone(),
+                m.membership_type.clone(),
+                m.holder_label.clone().unwrap_or_default(),
+                m.holder_name.clone().unwrap_or_default(),
+            ])
+            .collect()
+    }
+
+    /// The whole package as one JSON document.
+    pub fn to_json(&self) -> serde_json::Value {
+        let members = |list: &[TorMember]| -> Vec<serde_json::Value> {
+            list.iter()
+                .map(|m| json!({
+                    "position": m.position_label,
+                    "membership_type": m.membership_type,
+                    "holder": m.holder_label,
+                    "holder_username": m.holder_name,
+                }))
+                .collect()
+        };
+        json!({
+            "tor": {
+                "id": self.tor.id,
+                "name": self.tor.name,
+                "label": self.tor.label,
+                "status": self.tor.status,
+                "description": self.tor.description,
+            },
+            "generated_at": self.generated_at,
+            "members": members(&self.members),
+            "vacancies": members(&self.vacancies),
+            "open_items": self.open_items.iter().map(|i| json!({
+                "kind": i.kind,
+                "title": i.title,
+                "status": i.status,
+                "date": i.date,
+                "owner": i.owner,
+            })).collect::<Vec<_>>(),
+            "upcoming_meetings": self.upcoming_meetings.iter().map(|m| json!({
+                "id": m.id,
+                "label": m.label,
+                "date": m.meeting_date,
+                "status": m.status,
+                "agenda_items": m.agenda_count,
+            })).collect::<Vec<_>>(),
+            "standing_items": self.standing_items.iter().map(|s| json!({
+                "order": s.sequence_order,
+                "label": s.label,
+                "type": s.step_type,
+                "duration_minutes": s.default_duration_minutes,
+                "required": s.is_required,
+                "responsible": s.responsible,
+            })).collect::<Vec<_>>(),
+            "pending_minutes": self.pending_minutes.iter().map(|m| json!({
+                "minutes_id": m.minutes_id,
+                "meeting": m.meeting_label,
+                "meeting_date": m.meeting_date,
+                "status": m.status,
+            })).collect::<Vec<_>>(),
+            "configuration": self.configuration,
+        })
+    }
+
+    fn readme(&self) -> String {
+        let mut out = format!(
+            "# Handover package: {}\n\nGenerated {}.\n\n",
+            self.tor.label, self.generated_at,
+        );
+        out.push_str(&format!("- Members: {} ({} vacant positions)\n", self.members.len(), self.vacancies.len()));
+        out.push_str(&format!("- Open items: {}\n", self.open_items.len()));
+        out.push_str(&format!("- Upcoming meetings: {}\n", self.upcoming_meetings.len()));
+        out.push_str(&format!("- Standing items: {}\n", self.standing_items.len()));
+        out.push_str(&format!("- Minutes awaiting approval: {}\n", self.pending_minutes.len()));
+        if let Some(next) = self.upcoming_meetings.first() {
+            out.push_str(&format!("\nNext meeting: {} on {} ({}).\n", next.label, next.meeting_date, next.status));
+        }
+        out.push_str(
+            "\n## Files\n\n\
+             - `package.json`: everything below in one document\n\
+             - `members.csv`: positions and their holders, vacancies included\n\
+             - `open_items.csv`: proposals, suggestions, agenda points and action items awaiting action\n\
+             - `upcoming_meetings.csv`: meetings not yet held\n\
+             - `standing_items.csv`: the standing agenda, in order\n\
+             - `pending_minutes.csv`: minutes not yet approved\n\
+             - `configuration.json`: schedule, decision authority, capabilities, proposal form and setting overrides\n\
+             - `SHA256SUMS`: checksums of the files above; check with `sha256sum -c SHA256SUMS`\n",
+        );
+        out
+    }
+
+    /// Write the package as a zip archive.
+    pub fn to_archive(&self) -> Result<Vec<u8>, zip::result::ZipError> {
+        let mut members = Self::member_rows(&self.members);
+        members.extend(Self::member_rows(&self.vacancies));
+        let files: Vec<(&str, String)> = vec![
+            ("README.md", self.readme()),
+            ("package.json", serde_json::to_string_pretty(&self.to_json()).unwrap_or_default()),
+            ("members.csv", csv("position,membership_type,holder,holder_username", members)),
+            ("open_items.csv", csv(
+                "kind,title,status,date,owner",
+                self.open_items.iter()
+                    .map(|i| vec![i.kind.to_string(), i.title.clone(), i.status.clone(), i.date.clone(), i.owner.clone()])
+                    .collect(),
+            )),
+            ("upcoming_meetings.csv", csv(
+                "id,label,date,status,agenda_items",
+                self.upcoming_meetings.iter()
+                    .map(|m| vec![m.id.to_string(), m.label.clone(), m.meeting_date.clone(), m.status.clone(), m.agenda_count.to_string()])
+                    .collect(),
+            )),
+            ("standing_items.csv", csv(
+                "order,label,type,duration_minutes,required,responsible",
+                self.standing_items.iter()
+                    .map(|s| vec![
+                        s.sequence_order.to_string(),
+                        s.label.clone(),
+                        s.step_type.clone(),
+                        s.default_duration_minutes.map(|d| d.to_string()).unwrap_or_default(),
+                        s.is_required.to_string(),
+                        s.responsible.clone(),
+                    ])
+                    .collect(),
+            )),
+            ("pending_minutes.csv", csv(
+                "minutes_id,meeting,meeting_date,status",
+                self.pending_minutes.iter()
+                    .map(|m| vec![m.minutes_id.to_string(), m.meeting_label.clone(), m.meeting_date.clone(), m.status.clone()])
+                    .collect(),
+            )),
+            ("configuration.json", serde_json::to_string_pretty(&self.configuration).unwrap_or_default()),
+        ];
+
+        let files: Vec<(String, Vec<u8>)> = files.into_iter()
+            .map(|(name, contents)| (name.to_string(), contents.into_bytes()))
+            .collect();
+        export_record::zip_with_manifest(&format!("handover-{}", self.tor.name), &files)
+    }
+}
@@ -0,0 +1,165 @@
+//! Downstream impact of losing a ToR's output.
+//!
+//! Follows `feeds_into` and `escalates_to` relations outward from a ToR to
+//! find every ToR that depends on it, directly or transitively, with the open
+//! work each of them holds. Used before rejecting a proposal or suspending a
+//! ToR to see what stalls downstream.
+
+use std::collections::{HashMap, VecDeque};
+
+use serde::Serialize;
+use sqlx::PgPool;
+
+use super::dependencies::{find_graph_data, GraphEdge, GraphNode};
+
+/// Proposal statuses that still await a decision.
+pub(super) const OPEN_PROPOSAL_STATUSES: &[&str] = &["submitted", "under_review"];
+
+/// Agenda point statuses with no further work pending.
+pub(super) const CLOSED_AGENDA_STATUSES: &[&str] = &["decided", "voted"];
+
+/// A ToR in the impact subgraph with its open work.
+#[derive(Debug, Clone, Serialize)]
+pub struct ImpactNode {
+    #[serde(flatten)]
+    pub node: GraphNode,
+    /// Hops from the analysed ToR; 0 for the ToR itself.
+    pub depth: usize,
+    /// Reached through at least one blocking dependency.
+    pub blocked: bool,
+    pub open_proposals: i64,
+    pub open_agenda_points: i64,
+}
+
+impl ImpactNode {
+    pub fn open_items(&self) -> i64 {
+        self.open_proposals + self.open_agenda_points
+    }
+}
+
+/// The analysed ToR, everything downstream of it, and the edges between them.
+#[derive(Debug, Clone, Serialize)]
+pub struct ImpactAnalysis {
+    pub tor_id: i64,
+    /// The analysed ToR first, then affected ToRs by depth and label.
+    pub nodes: Vec<ImpactNode>,
+    pub edges: Vec<GraphEdge>,
+}
+
+impl ImpactAnalysis {
+    /// Downstream ToRs, excluding the analysed one.
+    pub fn affected(&self) -> &[ImpactNode] {
+        self.nodes.get(1..).unwrap_or(&[])
+    }
+
+    pub fn blocked_count(&self) -> usize {
+        self.affected().iter().filter(|n| n.blocked).count()
+    }
+
+    pub fn affected_open_items(&self) -> i64 {
+        self.affected().iter().map(|n| n.open_items()).sum()
+    }
+}
+
+/// Everything downstream of `tor_id`. `None` if the ToR is not active.
+pub async fn analyse_impact(pool: &PgPool, tor_id: i64) -> Result<Option<ImpactAnalysis>, sqlx::Error> {
+    let graph = find_graph_data(pool).await?;
+    if !graph.nodes.iter().any(|n| n.id == tor_id) {
+        return Ok(None);
+    }
+    let active: HashMap<i64, &GraphNode> = graph.nodes.iter().map(|n| (n.id, n)).collect();
+
+    // Breadth-first so each ToR gets its shortest distance.
+    let mut depth: HashMap<i64, usize> = HashMap::from([(tor_id, 0)]);
+    let mut queue = VecDeque::from([tor_id]);
+    let mut edges = Vec::new();
+    while let Some(current) = queue.pop_front() {
+        for edge in graph.edges.iter().filter(|e| e.source == current && active.contains_key(&e.target)) {
+            edges.push(edge.clone());
+            if !depth.contains_key(&edge.target) {
+                depth.insert(edge.target, depth[&current] + 1);
+                queue.push_back(edge.target);
+            }
+        }
+    }
+
+    let ids: Vec<i64> = depth.keys().copied().collect();
+    let (proposals, agenda_points) = count_open_items(pool, &ids).await?;
+    let mut nodes: Vec<ImpactNode> = depth.iter()
+        .map(|(id, d)| ImpactNode {
+            node: active[id].clone(),
+            depth: *d,
+            blocked: *id != tor_id && edges.iter().any(|e| e.target == *id && e.is_blocking),
+            open_proposals: proposals.get(id).copied().unwrap_or(0),
+            open_agenda_points: agenda_points.get(id).copied().unwrap_or(0),
+        })
+        .collect();
+    nodes.sort_by(|a, b| a.depth.cmp(&b.depth).then_with(|| a.node.label.cmp(&b.node.label)));
+
+    Ok(Some(ImpactAnalysis { tor_id, nodes, edges }))
+}
+
+/// Open proposals and open agenda points per ToR.
+async fn count_open_items(
+    pool: &PgPool,
+    tor_ids: &[i64],
+) -> Result<(HashMap<i64, i64>, HashMap<i64, i64>), sqlx::Error> {
+    let proposals: Vec<(i64, i64)> = sqlx::query_as(
+        "SELECT r.target_id, COUNT(*) \
+         FROM entities e \
+         JOIN relations r ON r.source_id = e.id \
+             AND r.relation_type_id = ( \
+                 SELECT id FROM entities WHERE entity_type = 'relation_type' AND name = 'submitted_to') \
+         LEFT JOIN entity_properties p_status ON e.id = p_status.entity_id AND p_status.key = 'status' \
+         WHERE e.entity_type = 'proposal' \
+           AND r.target_id = ANY($1) \
+           AND COALESCE(p_status.value, 'draft') = ANY($2) \
+         GROUP BY r.target_id",
+    )
+    .bind(tor_ids)
+    .bind(OPEN_PROPOSAL_STATUSES)
+    .fetch_all(pool)
+    .await?;
+
+    let agenda_points: Vec<(i64, i64)> = sqlx::query_as(
+        "SELECT r.target_id, COUNT(*) \
+         FROM entities e \
+         JOIN relations r ON r.source_id = e.id \
+             AND r.relation_type_id = ( \
+                 SELECT id FROM entities WHERE entity_type = 'relation_type' AND name = 'belongs_to_tor') \
+         LEFT JOIN entity_properties p_status ON e.id = p_status.entity_id AND p_status.key = 'status' \
+         WHERE e.entity_type = 'agenda_point' \
+           AND r.target_id = ANY($1) \
+           AND NOT (COALESCE(p_status.value, 'scheduled') = ANY($2)) \
+         GROUP BY r.target_id",
+    )
+    .bind(tor_ids)
+    .bind(CLOSED_AGENDA_STATUSES)
+    .fetch_all(pool)
+    .await?;
+
+    Ok((proposals.into_iter().collect(), agenda_points.into_iter().collect()))
+}
+
+/// Open agenda points spawned from a proposal, as (id, title, status).
+pub async fn find_open_agenda_points_for_proposal(
+    pool: &PgPool,
+    proposal_id: i64,
+) -> Result<Vec<(i64, String, String)>, sqlx::Error> {
+    sqlx::query_as(
+        "SELECT ap.id, COALESCE(p_title.value, ap.label), COALESCE(p_status.value, 'scheduled') AS status \
+         FROM relations r \
+         JOIN entities ap ON ap.id = r.target_id AND ap.entity_type = 'agenda_point' \
+         LEFT JOIN entity_properties p_title ON ap.id = p_title.entity_id AND p_title.key = 'title' \
+         LEFT JOIN entity_properties p_status ON ap.id = p_status.entity_id AND p_status.key = 'status' \
+         WHERE r.source_id = $1 \
+           AND r.relation_type_id = ( \
+               SELECT id FROM entities WHERE entity_type = 'relation_type' AND name = 'spawns_agenda_point') \
+           AND NOT (COALESCE(p_status.value, 'scheduled') = ANY($2)) \
+         ORDER BY ap.id",
+    )
+    .bind(proposal_id)
+    .bind(CLOSED_AGENDA_STATUSES)
+    .fetch_all(pool)
+    .await
+}
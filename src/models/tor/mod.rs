@@ -2,8 +2,22 @@ pub mod types;
 pub mod queries;
 pub mod dependencies;
 pub mod calendar;
+pub mod decision_authority;
+pub mod availability;
+pub mod impact;
+pub mod capabilities;
+pub mod terms;
+pub mod handover;
+pub mod delegations;
+pub mod standing_items;
 
 pub use types::*;
 pub use queries::*;
 pub use dependencies::*;
 pub use calendar::*;
+pub use decision_authority::*;
+pub use impact::*;
+pub use capabilities::*;
+pub use terms::*;
+pub use handover::*;
+pub use delegations::*;
@@ -1,5 +1,6 @@
-use sqlx::PgPool;
+use sqlx::{PgPool, Postgres, Transaction};
 use crate::errors::AppError;
+use crate::models::relation;
 use super::types::*;
 
 pub async fn find_all_list_items(pool: &PgPool) -> Result<Vec<TorListItem>, sqlx::Error> {
@@ -180,13 +181,12 @@ pub async fn update(
     Ok(())
 }
 
+/// Delete a ToR. ToRs go to the trash unless `trash.entity_types` leaves
+/// them out.
 pub async fn delete(pool: &PgPool, id: i64) -> Result<(), sqlx::Error> {
-    sqlx::query(
-        "DELETE FROM entities WHERE id = $1 AND entity_type = 'tor'",
-    )
-    .bind(id)
-    .execute(pool)
-    .await?;
+    if crate::models::entity::find_by_id(pool, id).await?.is_some_and(|e| e.entity_type == "tor") {
+        crate::models::entity::delete(pool, id).await?;
+    }
     Ok(())
 }
 
@@ -217,12 +217,102 @@ pub async fn find_members(pool: &PgPool, tor_id: i64) -> Result<Vec<TorMember>,
     Ok(members)
 }
 
-/// Assign a user to a position (creates fills_position relation).
+/// Assign a user to a position (creates fills_position relation), archiving
+/// the ToR's membership as it stood before the change.
 pub async fn assign_to_position(
     pool: &PgPool,
     user_id: i64,
     position_id: i64,
     membership_type: &str,
+) -> Result<(), sqlx::Error> {
+    record_position_change(pool, position_id).await?;
+    fill_position(pool, user_id, position_id, membership_type).await
+}
+
+/// Remove the current holder from a position, archiving the ToR's membership
+/// as it stood before the change.
+pub async fn vacate_position(pool: &PgPool, position_id: i64) -> Result<(), sqlx::Error> {
+    record_position_change(pool, position_id).await?;
+    clear_position(pool, position_id).await
+}
+
+/// Remove one user from a position, leaving any other holders in place, and
+/// archive the ToR's membership as it stood before the change.
+pub async fn remove_from_position(pool: &PgPool, user_id: i64, position_id: i64) -> Result<(), sqlx::Error> {
+    record_position_change(pool, position_id).await?;
+    relation::delete(pool, "fills_position", user_id, position_id).await
+}
+
+/// Archive today's membership of the ToR the position belongs to. The
+/// `_in_tx` position helpers leave this to the caller, before the transaction.
+pub async fn record_position_change(pool: &PgPool, position_id: i64) -> Result<(), sqlx::Error> {
+    let tor_id: Option<i64> = sqlx::query_scalar(
+        "SELECT target_id FROM relations WHERE source_id = $1 \
+         AND relation_type_id = ( \
+             SELECT id FROM entities WHERE entity_type = 'relation_type' AND name = 'belongs_to_tor')",
+    )
+    .bind(position_id)
+    .fetch_optional(pool)
+    .await?;
+    if let Some(tor_id) = tor_id {
+        super::terms::record_change(pool, tor_id, chrono::Local::now().date_naive()).await?;
+    }
+    Ok(())
+}
+
+/// Remove the current holder from a position within a transaction, without
+/// archiving; call `record_position_change` first.
+pub async fn vacate_position_in_tx(tx: &mut Transaction<'_, Postgres>, position_id: i64) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "DELETE FROM relations WHERE target_id = $1 \
+         AND relation_type_id = ( \
+             SELECT id FROM entities WHERE entity_type = 'relation_type' AND name = 'fills_position')",
+    )
+    .bind(position_id)
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+/// Assign a user to a position within a transaction, without archiving; call
+/// `record_position_change` first.
+pub async fn assign_to_position_in_tx(
+    tx: &mut Transaction<'_, Postgres>,
+    user_id: i64,
+    position_id: i64,
+    membership_type: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO entity_properties (entity_id, key, value) VALUES ($1, 'membership_type', $2) \
+         ON CONFLICT(entity_id, key) DO UPDATE SET value = excluded.value",
+    )
+    .bind(position_id)
+    .bind(membership_type)
+    .execute(&mut **tx)
+    .await?;
+
+    sqlx::query(
+        "INSERT INTO relations (relation_type_id, source_id, target_id) \
+         VALUES ( \
+             (SELECT id FROM entities WHERE entity_type = 'relation_type' AND name = 'fills_position'), \
+             $1, $2) \
+         ON CONFLICT DO NOTHING",
+    )
+    .bind(user_id)
+    .bind(position_id)
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+/// Create the fills_position relation without archiving; rotations archive
+/// the whole term themselves.
+pub(super) async fn fill_position(
+    pool: &PgPool,
+    user_id: i64,
+    position_id: i64,
+    membership_type: &str,
 ) -> Result<(), sqlx::Error> {
     // Set the membership_type property on the position
     sqlx::query(
@@ -250,8 +340,8 @@ pub async fn assign_to_position(
     Ok(())
 }
 
-/// Remove the current holder from a position.
-pub async fn vacate_position(pool: &PgPool, position_id: i64) -> Result<(), sqlx::Error> {
+/// Delete every fills_position relation of a position without archiving.
+pub(super) async fn clear_position(pool: &PgPool, position_id: i64) -> Result<(), sqlx::Error> {
     sqlx::query(
         "DELETE FROM relations WHERE target_id = $1 \
          AND relation_type_id = ( \
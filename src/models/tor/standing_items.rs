@@ -0,0 +1,171 @@
+//! Standing agenda items.
+//!
+//! A `standing_item` is an agenda point a ToR discusses regularly, such as
+//! "Approve previous minutes" or "Safety moment", linked to the ToR with
+//! `scoped_to_tor`. Its recurrence rule says which meetings it is due for,
+//! and an optional protocol step places it in the meeting order. Confirming
+//! a meeting creates an agenda point for each item due, in protocol order,
+//! and schedules it for the meeting.
+
+use chrono::{Datelike, NaiveDate};
+use sqlx::PgPool;
+
+use crate::errors::AppError;
+use crate::models::{agenda_point, entity, meeting, relation};
+
+/// Recurrence rules as (key, label).
+pub const RECURRENCES: &[(&str, &str)] = &[
+    ("every", "Every meeting"),
+    ("monthly", "First meeting each month"),
+    ("quarterly", "First meeting each quarter"),
+    ("annually", "First meeting each year"),
+];
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct StandingItem {
+    pub id: i64,
+    pub title: String,
+    pub description: String,
+    /// `informative` or `decision`, as for agenda points.
+    pub item_type: String,
+    pub time_allocation_minutes: i32,
+    pub presenter: String,
+    pub recurrence: String,
+    /// Protocol step the item is discussed under; 0 for none.
+    pub protocol_step_id: i64,
+    pub protocol_step_label: String,
+    /// Sequence order of that step; items without a step come last.
+    pub protocol_order: Option<i64>,
+    /// Date of the last meeting the item was added to, or empty.
+    pub last_meeting_date: String,
+}
+
+impl StandingItem {
+    pub fn recurrence_label(&self) -> &'static str {
+        RECURRENCES.iter()
+            .find(|(key, _)| *key == self.recurrence)
+            .map_or("", |(_, label)| *label)
+    }
+
+    /// Whether the item belongs on the agenda of a meeting on `date`.
+    pub fn is_due(&self, date: NaiveDate) -> bool {
+        let last = NaiveDate::parse_from_str(&self.last_meeting_date, "%Y-%m-%d").ok();
+        is_due(&self.recurrence, last, date)
+    }
+}
+
+/// Whether an item recurring by `recurrence`, last added to a meeting on
+/// `last`, is due for a meeting on `date`.
+pub fn is_due(recurrence: &str, last: Option<NaiveDate>, date: NaiveDate) -> bool {
+    let Some(last) = last else { return true };
+    match recurrence {
+        "monthly" => (last.year(), last.month()) != (date.year(), date.month()),
+        "quarterly" => (last.year(), last.month0() / 3) != (date.year(), date.month0() / 3),
+        "annually" => last.year() != date.year(),
+        _ => true,
+    }
+}
+
+/// A ToR's standing items in protocol order.
+pub async fn find_for_tor(pool: &PgPool, tor_id: i64) -> Result<Vec<StandingItem>, sqlx::Error> {
+    sqlx::query_as::<_, StandingItem>(
+        "SELECT e.id, e.label AS title, COALESCE(p_desc.value, '') AS description, \
+                COALESCE(p_type.value, 'informative') AS item_type, \
+                COALESCE(p_time.value, '0')::INT AS time_allocation_minutes, \
+                COALESCE(p_pres.value, '') AS presenter, \
+                COALESCE(p_rec.value, 'every') AS recurrence, \
+                COALESCE(step.id, 0) AS protocol_step_id, COALESCE(step.label, '') AS protocol_step_label, \
+                CAST(p_seq.value AS BIGINT) AS protocol_order, \
+                COALESCE(p_last.value, '') AS last_meeting_date \
+         FROM entities e \
+         JOIN relations r_tor ON r_tor.source_id = e.id AND r_tor.target_id = $1 \
+             AND r_tor.relation_type_id = ( \
+                 SELECT id FROM entities WHERE entity_type = 'relation_type' AND name = 'scoped_to_tor') \
+         LEFT JOIN entity_properties p_desc ON e.id = p_desc.entity_id AND p_desc.key = 'description' \
+         LEFT JOIN entity_properties p_type ON e.id = p_type.entity_id AND p_type.key = 'item_type' \
+         LEFT JOIN entity_properties p_time ON e.id = p_time.entity_id AND p_time.key = 'time_allocation_minutes' \
+         LEFT JOIN entity_properties p_pres ON e.id = p_pres.entity_id AND p_pres.key = 'presenter' \
+         LEFT JOIN entity_properties p_rec ON e.id = p_rec.entity_id AND p_rec.key = 'recurrence' \
+         LEFT JOIN entity_properties p_step ON e.id = p_step.entity_id AND p_step.key = 'protocol_step_id' \
+         LEFT JOIN entities step ON step.id = CAST(p_step.value AS BIGINT) AND step.entity_type = 'protocol_step' \
+         LEFT JOIN entity_properties p_seq ON step.id = p_seq.entity_id AND p_seq.key = 'sequence_order' \
+         LEFT JOIN entity_properties p_last ON e.id = p_last.entity_id AND p_last.key = 'last_meeting_date' \
+         WHERE e.entity_type = 'standing_item' \
+         ORDER BY CAST(p_seq.value AS BIGINT) NULLS LAST, e.id",
+    )
+    .bind(tor_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// Add a standing item to a ToR.
+#[allow(clippy::too_many_arguments)]
+pub async fn create(
+    pool: &PgPool,
+    tor_id: i64,
+    title: &str,
+    description: &str,
+    item_type: &str,
+    time_allocation_minutes: i32,
+    presenter: &str,
+    recurrence: &str,
+    protocol_step_id: Option<i64>,
+) -> Result<i64, sqlx::Error> {
+    let name = entity::available_name(pool, "standing_item", &format!("standing_{tor_id}")).await?;
+    let id = entity::create(pool, "standing_item", &name, title.trim()).await?;
+    entity::set_properties(pool, id, &[
+        ("description", description.trim()),
+        ("item_type", item_type),
+        ("time_allocation_minutes", &time_allocation_minutes.to_string()),
+        ("presenter", presenter.trim()),
+        ("recurrence", recurrence),
+        ("protocol_step_id", &protocol_step_id.unwrap_or(0).to_string()),
+    ]).await?;
+    relation::create(pool, "scoped_to_tor", id, tor_id).await?;
+    Ok(id)
+}
+
+/// Remove a ToR's standing item. Agenda points already created from it stay.
+/// Returns false when the item is not the ToR's.
+pub async fn delete(pool: &PgPool, tor_id: i64, id: i64) -> Result<bool, sqlx::Error> {
+    let deleted = sqlx::query(
+        "DELETE FROM entities e WHERE e.id = $1 AND e.entity_type = 'standing_item' \
+         AND EXISTS (SELECT 1 FROM relations r WHERE r.source_id = e.id AND r.target_id = $2 \
+             AND r.relation_type_id = ( \
+                 SELECT id FROM entities WHERE entity_type = 'relation_type' AND name = 'scoped_to_tor'))",
+    )
+    .bind(id)
+    .bind(tor_id)
+    .execute(pool)
+    .await?;
+    Ok(deleted.rows_affected() > 0)
+}
+
+/// Create agenda points on a meeting for the ToR's standing items due on
+/// `meeting_date`, in protocol order. Returns the new agenda point ids.
+pub async fn instantiate(
+    pool: &PgPool,
+    tor_id: i64,
+    meeting_id: i64,
+    meeting_date: &str,
+    user_id: i64,
+) -> Result<Vec<i64>, AppError> {
+    let Ok(date) = NaiveDate::parse_from_str(meeting_date, "%Y-%m-%d") else {
+        return Ok(Vec::new());
+    };
+    let mut created = Vec::new();
+    for item in find_for_tor(pool, tor_id).await?.into_iter().filter(|i| i.is_due(date)) {
+        let agenda_point_id = agenda_point::create(
+            pool, tor_id, &item.title, &item.description, &item.item_type, meeting_date,
+            item.time_allocation_minutes, user_id, &item.presenter, "", "",
+        ).await?;
+        entity::set_property(pool, agenda_point_id, "standing_item_id", &item.id.to_string()).await?;
+        if let Some(order) = item.protocol_order {
+            entity::set_property(pool, agenda_point_id, "protocol_order", &order.to_string()).await?;
+        }
+        meeting::assign_agenda(pool, meeting_id, agenda_point_id).await?;
+        entity::set_property(pool, item.id, "last_meeting_date", meeting_date).await?;
+        created.push(agenda_point_id);
+    }
+    Ok(created)
+}
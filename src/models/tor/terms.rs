@@ -0,0 +1,310 @@
+//! Membership terms and bulk rotation between them.
+//!
+//! Starting a new term ends every position assignment on a date, records
+//! who held what as an archived `membership_term` entity (properties
+//! `tor_id`, `start_date`, `end_date` and `members` as JSON), then applies
+//! the new holders. Archived terms keep minutes and decisions attributable
+//! after the people in the positions have changed: [`MembershipHistory`]
+//! resolves who held each position on a given date. Assigning, vacating or
+//! removing a holder from a single position archives the membership before
+//! the change the same way (see [`record_change`]).
+
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+
+use crate::models::entity;
+
+use super::capabilities::apply_capabilities;
+use super::queries::{clear_position, fill_position, find_members};
+
+/// A position and its holder as recorded in an archived term.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TermMember {
+    pub position_id: i64,
+    pub position_label: String,
+    pub membership_type: String,
+    pub holder_id: Option<i64>,
+    pub holder_label: Option<String>,
+}
+
+/// An archived membership term, covering `start_date` up to but not
+/// including `end_date` (the day the next term started). Dates are
+/// `YYYY-MM-DD`; `start_date` is empty for the first recorded term.
+#[derive(Debug, Clone)]
+pub struct MembershipTerm {
+    pub id: i64,
+    pub tor_id: i64,
+    pub start_date: String,
+    pub end_date: String,
+    pub members: Vec<TermMember>,
+}
+
+impl MembershipTerm {
+    pub fn filled_count(&self) -> usize {
+        self.members.iter().filter(|m| m.holder_id.is_some()).count()
+    }
+}
+
+/// The holder a position gets in the new term.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NewHolder {
+    Keep,
+    Vacant,
+    User(i64),
+}
+
+#[derive(Debug, Clone)]
+pub struct PositionRotation {
+    pub position_id: i64,
+    pub holder: NewHolder,
+    pub membership_type: String,
+}
+
+/// A position whose holder changes with the new term.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Handover {
+    pub position_id: i64,
+    pub position_label: String,
+    pub outgoing: Option<(i64, String)>,
+    pub incoming: Option<(i64, String)>,
+}
+
+/// Check a rotation before applying it. Returns a list of problems.
+pub fn validate_rotation(
+    term_date: Option<NaiveDate>,
+    today: NaiveDate,
+    last_term: Option<&MembershipTerm>,
+    rotations: &[PositionRotation],
+) -> Vec<String> {
+    let mut errors = Vec::new();
+    match term_date {
+        None => errors.push("Enter the date the new term starts".to_string()),
+        Some(date) if date > today => {
+            errors.push("The new term cannot start in the future; run the rotation on or after its start date".to_string())
+        }
+        Some(date) => {
+            let last_end = last_term
+                .and_then(|t| NaiveDate::parse_from_str(&t.end_date, "%Y-%m-%d").ok());
+            if let Some(last_end) = last_end.filter(|end| *end >= date) {
+                errors.push(format!("The new term must start after {}, when the current term started", last_end));
+            }
+        }
+    }
+    if rotations.iter().any(|r| r.membership_type != "mandatory" && r.membership_type != "optional") {
+        errors.push("Choose a membership type for every position".to_string());
+    }
+    let mut seen = HashMap::new();
+    for r in rotations {
+        if let NewHolder::User(user_id) = r.holder {
+            *seen.entry(user_id).or_insert(0) += 1;
+        }
+    }
+    if seen.values().any(|n| *n > 1) {
+        errors.push("A person can hold only one position in the new term".to_string());
+    }
+    errors
+}
+
+/// Positions whose holder changes with the rotation.
+pub async fn plan_handovers(
+    pool: &PgPool,
+    tor_id: i64,
+    rotations: &[PositionRotation],
+) -> Result<Vec<Handover>, sqlx::Error> {
+    let members = find_members(pool, tor_id).await?;
+    let mut handovers = Vec::new();
+    for r in rotations {
+        let Some(current) = members.iter().find(|m| m.position_id == r.position_id) else {
+            continue;
+        };
+        let outgoing = current.holder_id.zip(current.holder_label.clone());
+        let incoming = match r.holder {
+            NewHolder::Keep => continue,
+            NewHolder::Vacant => None,
+            NewHolder::User(user_id) if current.holder_id == Some(user_id) => continue,
+            NewHolder::User(user_id) => entity::find_by_id(pool, user_id).await?
+                .filter(|u| u.entity_type == "user")
+                .map(|u| (u.id, u.label)),
+        };
+        if outgoing.is_none() && incoming.is_none() {
+            continue;
+        }
+        handovers.push(Handover {
+            position_id: r.position_id,
+            position_label: current.position_label.clone(),
+            outgoing,
+            incoming,
+        });
+    }
+    Ok(handovers)
+}
+
+/// Current positions and holders, one entry per position.
+pub async fn find_current_members(pool: &PgPool, tor_id: i64) -> Result<Vec<TermMember>, sqlx::Error> {
+    let mut members: Vec<TermMember> = Vec::new();
+    for m in find_members(pool, tor_id).await? {
+        if members.iter().any(|t| t.position_id == m.position_id) {
+            continue;
+        }
+        members.push(TermMember {
+            position_id: m.position_id,
+            position_label: m.position_label,
+            membership_type: m.membership_type,
+            holder_id: m.holder_id,
+            holder_label: m.holder_label,
+        });
+    }
+    Ok(members)
+}
+
+/// Archive `members` as the term ending on `end` and starting where the last
+/// archived term ended. Returns the archived term's id.
+async fn archive_term(
+    pool: &PgPool,
+    tor_id: i64,
+    end: &str,
+    label: &str,
+    members: &[TermMember],
+) -> Result<i64, sqlx::Error> {
+    let start = find_terms(pool, tor_id).await?
+        .first()
+        .map(|t| t.end_date.clone())
+        .unwrap_or_default();
+    let name = entity::available_name(pool, "membership_term", &format!("tor{}_{}", tor_id, end)).await?;
+    let term_id = entity::create(pool, "membership_term", &name, label).await?;
+    let json = serde_json::to_string(members).unwrap_or_else(|_| "[]".to_string());
+    entity::set_properties(pool, term_id, &[
+        ("tor_id", &tor_id.to_string()),
+        ("start_date", &start),
+        ("end_date", end),
+        ("members", &json),
+    ]).await?;
+    Ok(term_id)
+}
+
+/// Archive the current membership as a term ending when the new one starts
+/// on `end_date`, then apply the rotations. Returns the archived term's id.
+pub async fn start_new_term(
+    pool: &PgPool,
+    tor_id: i64,
+    end_date: NaiveDate,
+    rotations: &[PositionRotation],
+) -> Result<i64, sqlx::Error> {
+    let end = end_date.format("%Y-%m-%d").to_string();
+    let members = find_current_members(pool, tor_id).await?;
+    let term_id = archive_term(pool, tor_id, &end, &format!("Term ending {}", end), &members).await?;
+
+    for r in rotations {
+        if !members.iter().any(|m| m.position_id == r.position_id) {
+            continue;
+        }
+        match r.holder {
+            NewHolder::Keep => {
+                entity::set_property(pool, r.position_id, "membership_type", &r.membership_type).await?;
+            }
+            NewHolder::Vacant => {
+                clear_position(pool, r.position_id).await?;
+                entity::set_property(pool, r.position_id, "membership_type", &r.membership_type).await?;
+            }
+            NewHolder::User(user_id) => {
+                clear_position(pool, r.position_id).await?;
+                fill_position(pool, user_id, r.position_id, &r.membership_type).await?;
+            }
+        }
+    }
+    apply_capabilities(pool, tor_id).await?;
+    Ok(term_id)
+}
+
+/// Archive the membership as it stands before a single position is assigned
+/// or vacated on `date`, outside a rotation; the position helpers in
+/// `queries` call this before every change, so earlier dates keep resolving
+/// to the previous holders. History resolves to the day, so only the first
+/// change of a day is archived; nothing is archived for a ToR that has no
+/// history and no holders yet. Returns the archived term's id, if any.
+pub async fn record_change(pool: &PgPool, tor_id: i64, date: NaiveDate) -> Result<Option<i64>, sqlx::Error> {
+    let end = date.format("%Y-%m-%d").to_string();
+    let terms = find_terms(pool, tor_id).await?;
+    if terms.first().is_some_and(|t| t.end_date >= end) {
+        return Ok(None);
+    }
+    let members = find_current_members(pool, tor_id).await?;
+    if terms.is_empty() && members.iter().all(|m| m.holder_id.is_none()) {
+        return Ok(None);
+    }
+    let label = format!("Membership before changes on {}", end);
+    Ok(Some(archive_term(pool, tor_id, &end, &label, &members).await?))
+}
+
+/// Archived terms of a ToR, most recent first.
+pub async fn find_terms(pool: &PgPool, tor_id: i64) -> Result<Vec<MembershipTerm>, sqlx::Error> {
+    let rows: Vec<(i64, String, String, String)> = sqlx::query_as(
+        "SELECT e.id, COALESCE(p_start.value, ''), COALESCE(p_end.value, ''), COALESCE(p_members.value, '[]') \
+         FROM entities e \
+         JOIN entity_properties p_tor ON e.id = p_tor.entity_id AND p_tor.key = 'tor_id' \
+         LEFT JOIN entity_properties p_start ON e.id = p_start.entity_id AND p_start.key = 'start_date' \
+         LEFT JOIN entity_properties p_end ON e.id = p_end.entity_id AND p_end.key = 'end_date' \
+         LEFT JOIN entity_properties p_members ON e.id = p_members.entity_id AND p_members.key = 'members' \
+         WHERE e.entity_type = 'membership_term' AND p_tor.value = $1 \
+         ORDER BY p_end.value DESC, e.id DESC",
+    )
+    .bind(tor_id.to_string())
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter()
+        .map(|(id, start_date, end_date, members)| MembershipTerm {
+            id,
+            tor_id,
+            start_date,
+            end_date,
+            members: serde_json::from_str(&members).unwrap_or_default(),
+        })
+        .collect())
+}
+
+/// Archived terms plus the current membership, for resolving who held
+/// which position on a given date.
+#[derive(Debug, Clone)]
+pub struct MembershipHistory {
+    /// Most recent first, as returned by [`find_terms`].
+    pub terms: Vec<MembershipTerm>,
+    pub current: Vec<TermMember>,
+}
+
+impl MembershipHistory {
+    /// Positions and holders on `date` (`YYYY-MM-DD`, longer timestamps are
+    /// cut to the day). An empty date resolves to the current membership.
+    pub fn at(&self, date: &str) -> &[TermMember] {
+        let day = date.get(..10).unwrap_or(date);
+        if !day.is_empty()
+            && let Some(term) = self.terms.iter().rev().find(|t| day < t.end_date.as_str())
+        {
+            return &term.members;
+        }
+        &self.current
+    }
+
+    /// Labels of the positions `user_id` held on `date`.
+    pub fn positions_of(&self, user_id: i64, date: &str) -> Vec<String> {
+        self.at(date).iter()
+            .filter(|m| m.holder_id == Some(user_id))
+            .map(|m| m.position_label.clone())
+            .collect()
+    }
+}
+
+pub async fn find_membership_history(pool: &PgPool, tor_id: i64) -> Result<MembershipHistory, sqlx::Error> {
+    Ok(MembershipHistory {
+        terms: find_terms(pool, tor_id).await?,
+        current: find_current_members(pool, tor_id).await?,
+    })
+}
+
+/// Positions and holders of a ToR as they were on `date`.
+pub async fn find_members_at(pool: &PgPool, tor_id: i64, date: &str) -> Result<Vec<TermMember>, sqlx::Error> {
+    Ok(find_membership_history(pool, tor_id).await?.at(date).to_vec())
+}
@@ -0,0 +1,277 @@
+//! Soft deletion: the trash.
+//!
+//! `entity::delete` does not remove entities of the types listed in
+//! `trash.entity_types` (users, proposals and ToRs by default). The row, its
+//! properties and every relation touching it are copied into `trash` as one
+//! JSON snapshot and then removed, so list queries leave trashed entities out
+//! without having to filter them. From `/admin/trash` an entity is restored
+//! under its original id or purged for good; the scheduler purges entries
+//! older than `trash.retention_days`.
+//!
+//! Only the entity's own rows are kept. Relations whose other end is gone by
+//! the time of a restore are dropped, as are rows in side tables such as form
+//! drafts and the access log.
+
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+
+use super::setting;
+
+/// One trashed entity, for listing.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct TrashEntry {
+    pub id: i64,
+    pub entity_id: i64,
+    pub entity_type: String,
+    pub name: String,
+    pub label: String,
+    pub property_count: i64,
+    pub relation_count: i64,
+    pub deleted_at: String,
+}
+
+/// What a restore brought back.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Restored {
+    pub entity_id: i64,
+    pub relations: usize,
+    /// Relations left out because their other end no longer exists.
+    pub dropped: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+struct SnapshotEntity {
+    entity_type: String,
+    name: String,
+    label: String,
+    sort_order: i32,
+    is_active: bool,
+    created_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+struct SnapshotRelation {
+    id: i64,
+    relation_type_id: i64,
+    source_id: i64,
+    target_id: i64,
+    created_at: String,
+    #[sqlx(skip)]
+    properties: Vec<(String, String)>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Snapshot {
+    entity: SnapshotEntity,
+    properties: Vec<(String, String)>,
+    relations: Vec<SnapshotRelation>,
+}
+
+/// Entity types listed in a `trash.entity_types` value.
+pub fn parse_types(value: &str) -> Vec<String> {
+    value.split(',')
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+        .collect()
+}
+
+/// Whether deleting an entity of this type sends it to the trash.
+pub async fn is_trashable(pool: &PgPool, entity_type: &str) -> bool {
+    let types = setting::get_value(pool, "trash.entity_types", "user,proposal,tor").await;
+    parse_types(&types).iter().any(|t| t == entity_type)
+}
+
+/// Move an entity to the trash if its type is trashable. Returns false,
+/// leaving it alone, when it is not (or does not exist).
+pub(crate) async fn move_to_trash(pool: &PgPool, id: i64) -> Result<bool, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let entity: Option<SnapshotEntity> = sqlx::query_as(
+        "SELECT entity_type, name, label, sort_order, is_active, created_at::TEXT AS created_at \
+         FROM entities WHERE id = $1 FOR UPDATE",
+    )
+    .bind(id)
+    .fetch_optional(&mut *tx)
+    .await?;
+    let Some(entity) = entity else { return Ok(false) };
+    if !is_trashable(pool, &entity.entity_type).await {
+        return Ok(false);
+    }
+
+    let properties: Vec<(String, String)> = sqlx::query_as(
+        "SELECT key, value FROM entity_properties WHERE entity_id = $1 ORDER BY key",
+    )
+    .bind(id)
+    .fetch_all(&mut *tx)
+    .await?;
+    let mut relations: Vec<SnapshotRelation> = sqlx::query_as(
+        "SELECT id, relation_type_id, source_id, target_id, created_at::TEXT AS created_at \
+         FROM relations WHERE source_id = $1 OR target_id = $1 ORDER BY id",
+    )
+    .bind(id)
+    .fetch_all(&mut *tx)
+    .await?;
+    for relation in &mut relations {
+        relation.properties = sqlx::query_as(
+            "SELECT key, value FROM relation_properties WHERE relation_id = $1 ORDER BY key",
+        )
+        .bind(relation.id)
+        .fetch_all(&mut *tx)
+        .await?;
+    }
+
+    let (entity_type, name, label) = (entity.entity_type.clone(), entity.name.clone(), entity.label.clone());
+    let snapshot = serde_json::to_value(Snapshot { entity, properties, relations }).unwrap_or_default();
+    sqlx::query(
+        "INSERT INTO trash (entity_id, entity_type, name, label, snapshot) VALUES ($1, $2, $3, $4, $5)",
+    )
+    .bind(id)
+    .bind(&entity_type)
+    .bind(&name)
+    .bind(&label)
+    .bind(&snapshot)
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query("DELETE FROM entities WHERE id = $1")
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+    Ok(true)
+}
+
+const SELECT_ENTRY: &str =
+    "SELECT id, entity_id, entity_type, name, label, \
+            jsonb_array_length(snapshot->'properties')::BIGINT AS property_count, \
+            jsonb_array_length(snapshot->'relations')::BIGINT AS relation_count, \
+            TO_CHAR(deleted_at, 'YYYY-MM-DD HH24:MI') AS deleted_at \
+     FROM trash";
+
+/// Everything in the trash, most recently deleted first.
+pub async fn find_all(pool: &PgPool) -> Result<Vec<TrashEntry>, sqlx::Error> {
+    sqlx::query_as(&format!("{SELECT_ENTRY} ORDER BY trash.deleted_at DESC, id DESC"))
+        .fetch_all(pool)
+        .await
+}
+
+pub async fn find_by_id(pool: &PgPool, id: i64) -> Result<Option<TrashEntry>, sqlx::Error> {
+    sqlx::query_as(&format!("{SELECT_ENTRY} WHERE id = $1"))
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+}
+
+/// Whether another entity has taken the trashed one's type and name since,
+/// which blocks a restore.
+pub async fn name_taken(pool: &PgPool, entry: &TrashEntry) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM entities WHERE entity_type = $1 AND name = $2)")
+        .bind(&entry.entity_type)
+        .bind(&entry.name)
+        .fetch_one(pool)
+        .await
+}
+
+/// Put a trashed entity back under its original id with its properties and
+/// the relations whose other end still exists. Returns `None` when the
+/// trash entry does not exist.
+pub async fn restore(pool: &PgPool, trash_id: i64) -> Result<Option<Restored>, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let row: Option<(i64, serde_json::Value)> = sqlx::query_as(
+        "DELETE FROM trash WHERE id = $1 RETURNING entity_id, snapshot",
+    )
+    .bind(trash_id)
+    .fetch_optional(&mut *tx)
+    .await?;
+    let Some((entity_id, snapshot)) = row else { return Ok(None) };
+    let snapshot: Snapshot = serde_json::from_value(snapshot)
+        .map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
+
+    let entity = &snapshot.entity;
+    sqlx::query(
+        "INSERT INTO entities (id, entity_type, name, label, sort_order, is_active, created_at) \
+         OVERRIDING SYSTEM VALUE VALUES ($1, $2, $3, $4, $5, $6, $7::TIMESTAMPTZ)",
+    )
+    .bind(entity_id)
+    .bind(&entity.entity_type)
+    .bind(&entity.name)
+    .bind(&entity.label)
+    .bind(entity.sort_order)
+    .bind(entity.is_active)
+    .bind(&entity.created_at)
+    .execute(&mut *tx)
+    .await?;
+    for (key, value) in &snapshot.properties {
+        sqlx::query("INSERT INTO entity_properties (entity_id, key, value) VALUES ($1, $2, $3)")
+            .bind(entity_id)
+            .bind(key)
+            .bind(value)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    let mut restored = Restored { entity_id, relations: 0, dropped: 0 };
+    let mut relation_ids = Vec::new();
+    for relation in &snapshot.relations {
+        let inserted = sqlx::query(
+            "INSERT INTO relations (id, relation_type_id, source_id, target_id, created_at) \
+             OVERRIDING SYSTEM VALUE \
+             SELECT $1, $2, $3, $4, $5::TIMESTAMPTZ \
+             WHERE EXISTS (SELECT 1 FROM entities WHERE id = $2) \
+               AND EXISTS (SELECT 1 FROM entities WHERE id = $3) \
+               AND EXISTS (SELECT 1 FROM entities WHERE id = $4) \
+             ON CONFLICT DO NOTHING",
+        )
+        .bind(relation.id)
+        .bind(relation.relation_type_id)
+        .bind(relation.source_id)
+        .bind(relation.target_id)
+        .bind(&relation.created_at)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected() > 0;
+        if !inserted {
+            restored.dropped += 1;
+            continue;
+        }
+        for (key, value) in &relation.properties {
+            sqlx::query("INSERT INTO relation_properties (relation_id, key, value) VALUES ($1, $2, $3)")
+                .bind(relation.id)
+                .bind(key)
+                .bind(value)
+                .execute(&mut *tx)
+                .await?;
+        }
+        restored.relations += 1;
+        relation_ids.push(relation.id);
+    }
+
+    // The entity is back: its tombstones would tell delta-export consumers
+    // to delete it again.
+    sqlx::query(
+        "DELETE FROM deletions WHERE (record_kind = 'entity' AND record_id = $1) \
+            OR (record_kind = 'relation' AND record_id = ANY($2))",
+    )
+    .bind(entity_id)
+    .bind(&relation_ids)
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+    Ok(Some(restored))
+}
+
+/// Remove a trash entry for good. Returns false when it did not exist.
+pub async fn purge(pool: &PgPool, trash_id: i64) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM trash WHERE id = $1")
+        .bind(trash_id)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Purge entries deleted before the cutoff. Returns how many went.
+pub async fn purge_expired(pool: &PgPool, before: chrono::DateTime<chrono::Utc>) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM trash WHERE deleted_at < $1::TIMESTAMPTZ")
+        .bind(before.to_rfc3339())
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected())
+}
@@ -51,7 +51,7 @@ pub fn default_columns() -> Vec<crate::models::table_filter::ColumnDef> {
 /// role_options: Vec<(name, label)> fetched from DB.
 pub fn fields_json(role_options: &[(String, String)]) -> String {
     let roles_json: String = role_options.iter()
-        .map(|(name, label)| format!(r#"{{"value":"{name}","label":"{label}"}}"#))
+        .map(|(name, label)| serde_json::json!({ "value": name, "label": label }).to_string())
         .collect::<Vec<_>>()
         .join(",");
 
@@ -203,8 +203,9 @@ pub async fn update(
     .execute(pool)
     .await?;
 
-    // Update password if provided
+    // Update password if provided, keeping the one it replaces
     if let Some(pw) = password {
+        crate::models::password_history::push_current(pool, id).await?;
         sqlx::query(
             "INSERT INTO entity_properties (entity_id, key, value) VALUES ($1, 'password', $2) \
              ON CONFLICT(entity_id, key) DO UPDATE SET value = excluded.value"
@@ -230,13 +231,19 @@ pub async fn update(
 
 /// Assign the default "viewer" role to a user. No-op if viewer role doesn't exist.
 pub async fn assign_default_role(pool: &PgPool, user_id: i64) -> Result<(), sqlx::Error> {
-    let viewer_id: Option<(i64,)> = sqlx::query_as::<_, (i64,)>(
-        "SELECT id FROM entities WHERE entity_type = 'role' AND name = 'viewer'"
+    assign_role_by_name(pool, user_id, "viewer").await.map(|_| ())
+}
+
+/// Assign a role by name. Returns false if no such role exists.
+pub async fn assign_role_by_name(pool: &PgPool, user_id: i64, role_name: &str) -> Result<bool, sqlx::Error> {
+    let role: Option<(i64,)> = sqlx::query_as::<_, (i64,)>(
+        "SELECT id FROM entities WHERE entity_type = 'role' AND name = $1"
     )
+    .bind(role_name)
     .fetch_optional(pool)
     .await?;
 
-    if let Some((role_id,)) = viewer_id {
+    if let Some((role_id,)) = role {
         sqlx::query(
             "INSERT INTO relations (relation_type_id, source_id, target_id) \
              VALUES ((SELECT id FROM entities WHERE entity_type = 'relation_type' AND name = 'has_role'), $1, $2) \
@@ -247,18 +254,105 @@ pub async fn assign_default_role(pool: &PgPool, user_id: i64) -> Result<(), sqlx
         .execute(pool)
         .await?;
     }
-    Ok(())
+    Ok(role.is_some())
+}
+
+/// Find the user linked to a single sign-on identity (`issuer|subject`).
+pub async fn find_by_oidc_subject(pool: &PgPool, subject: &str) -> Result<Option<User>, sqlx::Error> {
+    let username: Option<String> = sqlx::query_scalar(
+        "SELECT e.name FROM entities e \
+         JOIN entity_properties p ON e.id = p.entity_id AND p.key = 'oidc_subject' \
+         WHERE e.entity_type = 'user' AND p.value = $1"
+    )
+    .bind(subject)
+    .fetch_optional(pool)
+    .await?;
+    match username {
+        Some(name) => find_by_username(pool, &name).await,
+        None => Ok(None),
+    }
 }
 
-/// Delete a user entity (cascades to properties and relations via FK).
+/// Id of a user with this e-mail address, compared case-insensitively.
+pub async fn find_id_by_email(pool: &PgPool, email: &str) -> Result<Option<i64>, sqlx::Error> {
+    sqlx::query_scalar(
+        "SELECT e.id FROM entities e \
+         JOIN entity_properties p ON e.id = p.entity_id AND p.key = 'email' \
+         WHERE e.entity_type = 'user' AND LOWER(p.value) = LOWER($1) \
+         ORDER BY e.id LIMIT 1"
+    )
+    .bind(email.trim())
+    .fetch_optional(pool)
+    .await
+}
+
+/// Delete a user entity. Users go to the trash unless `trash.entity_types`
+/// leaves them out.
 pub async fn delete(pool: &PgPool, id: i64) -> Result<(), sqlx::Error> {
-    sqlx::query("DELETE FROM entities WHERE id = $1 AND entity_type = 'user'")
+    if crate::models::entity::find_by_id(pool, id).await?.is_some_and(|e| e.entity_type == "user") {
+        crate::models::entity::delete(pool, id).await?;
+    }
+    Ok(())
+}
+
+/// Lock an account: it can no longer sign in and its sessions end on their
+/// next request. The reason is shown to administrators. With `until` the
+/// lock lifts by itself at that time; without, only [`unlock`] lifts it.
+pub async fn lock(
+    pool: &PgPool,
+    id: i64,
+    reason: &str,
+    until: Option<chrono::DateTime<chrono::Utc>>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE entities SET is_active = false, updated_at = NOW() WHERE id = $1 AND entity_type = 'user'")
+        .bind(id)
+        .execute(pool)
+        .await?;
+    let locked_at = crate::warnings::clock::now().format("%Y-%m-%d %H:%M UTC").to_string();
+    crate::models::entity::set_properties(pool, id, &[
+        ("locked_reason", reason),
+        ("locked_at", &locked_at),
+    ]).await?;
+    if let Some(until) = until {
+        crate::models::entity::set_property(pool, id, "locked_until", &until.to_rfc3339()).await?;
+    }
+    Ok(())
+}
+
+/// Let a locked account sign in again.
+pub async fn unlock(pool: &PgPool, id: i64) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE entities SET is_active = true, updated_at = NOW() WHERE id = $1 AND entity_type = 'user'")
+        .bind(id)
+        .execute(pool)
+        .await?;
+    sqlx::query("DELETE FROM entity_properties WHERE entity_id = $1 AND key IN ('locked_reason', 'locked_at', 'locked_until')")
         .bind(id)
         .execute(pool)
         .await?;
     Ok(())
 }
 
+/// Whether the account is locked. A lock whose time is up is lifted here.
+pub async fn is_locked(pool: &PgPool, id: i64) -> Result<bool, sqlx::Error> {
+    let row: Option<(bool, Option<String>)> = sqlx::query_as(
+        "SELECT e.is_active, p.value FROM entities e \
+         LEFT JOIN entity_properties p ON p.entity_id = e.id AND p.key = 'locked_until' \
+         WHERE e.id = $1 AND e.entity_type = 'user'",
+    )
+    .bind(id)
+    .fetch_optional(pool)
+    .await?;
+    let Some((false, until)) = row else { return Ok(false) };
+    let expired = until
+        .and_then(|u| chrono::DateTime::parse_from_rfc3339(&u).ok())
+        .is_some_and(|u| u <= crate::warnings::clock::now());
+    if expired {
+        unlock(pool, id).await?;
+        return Ok(false);
+    }
+    Ok(true)
+}
+
 /// Count users that have a specific role via has_role relation.
 pub async fn count_by_role_id(pool: &PgPool, role_id: i64) -> Result<i64, sqlx::Error> {
     let (count,) = sqlx::query_as::<_, (i64,)>(
@@ -319,6 +413,7 @@ pub async fn find_all_with_roles(pool: &PgPool) -> Result<Vec<UserWithRoles>, sq
 
 /// Update only the password property for a user.
 pub async fn update_password(pool: &PgPool, id: i64, password_hash: &str) -> Result<(), sqlx::Error> {
+    crate::models::password_history::push_current(pool, id).await?;
     sqlx::query(
         "INSERT INTO entity_properties (entity_id, key, value) VALUES ($1, 'password', $2) \
          ON CONFLICT(entity_id, key) DO UPDATE SET value = excluded.value"
@@ -0,0 +1,304 @@
+//! Inbound webhooks: external systems POST JSON to `/hooks/{slug}` and each
+//! delivery becomes a suggestion or proposal on the webhook's ToR.
+//!
+//! A webhook is a `webhook` entity (name = slug) with a shared secret and a
+//! field mapping. Field paths are dot-separated (`issue.fields.summary`);
+//! array elements are addressed by index (`labels.0`).
+//!
+//! Rotating the secret keeps the previous one valid for a grace period
+//! (`security.signing_key_grace_days`) so the sender can be updated without
+//! dropping deliveries.
+
+use hmac::{Hmac, Mac};
+use rand::Rng;
+use sha2::Sha256;
+use sqlx::PgPool;
+
+use crate::errors::AppError;
+use super::{entity, proposal, relation, suggestion};
+
+/// Header carrying `sha256=<hex HMAC of the raw body>`.
+pub const SIGNATURE_HEADER: &str = "X-Ahlt-Signature";
+
+/// What a delivery creates.
+pub const TARGETS: &[&str] = &["suggestion", "proposal"];
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct Webhook {
+    pub id: i64,
+    pub slug: String,
+    pub label: String,
+    pub is_active: bool,
+    pub secret: String,
+    /// Secret replaced by the last rotation, valid until `previous_secret_until`.
+    pub previous_secret: String,
+    pub previous_secret_until: String,
+    pub target: String,
+    pub tor_id: i64,
+    pub tor_label: String,
+    pub submitted_by_id: i64,
+    pub title_field: String,
+    pub description_field: String,
+    pub rationale_field: String,
+    pub external_id_field: String,
+}
+
+/// Fields submitted from the webhook admin form.
+pub struct WebhookInput<'a> {
+    pub slug: &'a str,
+    pub label: &'a str,
+    pub target: &'a str,
+    pub tor_id: i64,
+    pub title_field: &'a str,
+    pub description_field: &'a str,
+    pub rationale_field: &'a str,
+    pub external_id_field: &'a str,
+}
+
+/// Result of processing one delivery.
+#[derive(Debug, PartialEq)]
+pub enum Delivery {
+    Created { entity_type: String, id: i64 },
+    /// The external id was already delivered; nothing was created.
+    Duplicate { id: i64 },
+    /// A mapped field the target requires was missing from the payload.
+    MissingField(String),
+}
+
+const SELECT_WEBHOOK: &str =
+    "SELECT e.id, e.name AS slug, e.label, e.is_active, \
+            COALESCE(p_secret.value, '') AS secret, \
+            COALESCE(p_prev.value, '') AS previous_secret, \
+            COALESCE(p_prev_until.value, '') AS previous_secret_until, \
+            COALESCE(p_target.value, 'suggestion') AS target, \
+            t.id AS tor_id, t.label AS tor_label, \
+            COALESCE(p_by.value, '0')::BIGINT AS submitted_by_id, \
+            COALESCE(p_title.value, '') AS title_field, \
+            COALESCE(p_desc.value, '') AS description_field, \
+            COALESCE(p_rat.value, '') AS rationale_field, \
+            COALESCE(p_ext.value, '') AS external_id_field \
+     FROM entities e \
+     JOIN relations r ON r.source_id = e.id \
+     JOIN entities rt ON rt.id = r.relation_type_id AND rt.name = 'scoped_to_tor' \
+     JOIN entities t ON t.id = r.target_id \
+     LEFT JOIN entity_properties p_secret ON e.id = p_secret.entity_id AND p_secret.key = 'secret' \
+     LEFT JOIN entity_properties p_prev ON e.id = p_prev.entity_id AND p_prev.key = 'previous_secret' \
+     LEFT JOIN entity_properties p_prev_until ON e.id = p_prev_until.entity_id AND p_prev_until.key = 'previous_secret_until' \
+     LEFT JOIN entity_properties p_target ON e.id = p_target.entity_id AND p_target.key = 'target' \
+     LEFT JOIN entity_properties p_by ON e.id = p_by.entity_id AND p_by.key = 'submitted_by_id' \
+     LEFT JOIN entity_properties p_title ON e.id = p_title.entity_id AND p_title.key = 'title_field' \
+     LEFT JOIN entity_properties p_desc ON e.id = p_desc.entity_id AND p_desc.key = 'description_field' \
+     LEFT JOIN entity_properties p_rat ON e.id = p_rat.entity_id AND p_rat.key = 'rationale_field' \
+     LEFT JOIN entity_properties p_ext ON e.id = p_ext.entity_id AND p_ext.key = 'external_id_field' \
+     WHERE e.entity_type = 'webhook'";
+
+/// All configured webhooks, by label.
+pub async fn find_all(pool: &PgPool) -> Result<Vec<Webhook>, sqlx::Error> {
+    sqlx::query_as::<_, Webhook>(&format!("{} ORDER BY e.label", SELECT_WEBHOOK))
+        .fetch_all(pool)
+        .await
+}
+
+/// Look up a webhook by its URL slug.
+pub async fn find_by_slug(pool: &PgPool, slug: &str) -> Result<Option<Webhook>, sqlx::Error> {
+    sqlx::query_as::<_, Webhook>(&format!("{} AND e.name = $1", SELECT_WEBHOOK))
+        .bind(slug)
+        .fetch_optional(pool)
+        .await
+}
+
+/// Validate admin input. Returns a list of problems (empty when valid).
+pub fn validate(input: &WebhookInput<'_>) -> Vec<String> {
+    let mut errors = Vec::new();
+    if input.slug.is_empty()
+        || !input.slug.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+    {
+        errors.push("Slug must be lowercase letters, digits and dashes".to_string());
+    }
+    if input.label.trim().is_empty() {
+        errors.push("Name is required".to_string());
+    }
+    if !TARGETS.contains(&input.target) {
+        errors.push("Target must be suggestion or proposal".to_string());
+    }
+    if input.description_field.trim().is_empty() {
+        errors.push("Description field is required".to_string());
+    }
+    if input.target == "proposal" && input.title_field.trim().is_empty() {
+        errors.push("Title field is required for proposals".to_string());
+    }
+    errors
+}
+
+/// Create a webhook with a fresh secret. Returns its id.
+pub async fn create(pool: &PgPool, input: &WebhookInput<'_>, created_by: i64) -> Result<i64, sqlx::Error> {
+    let id = entity::create(pool, "webhook", input.slug, input.label.trim()).await?;
+    entity::set_properties(pool, id, &[
+        ("secret", &generate_secret()),
+        ("target", input.target),
+        ("submitted_by_id", &created_by.to_string()),
+        ("title_field", input.title_field.trim()),
+        ("description_field", input.description_field.trim()),
+        ("rationale_field", input.rationale_field.trim()),
+        ("external_id_field", input.external_id_field.trim()),
+    ]).await?;
+    relation::create(pool, "scoped_to_tor", id, input.tor_id).await?;
+    Ok(id)
+}
+
+/// Replace the shared secret. The old one keeps verifying for `grace` so
+/// the sender can be updated; a zero grace revokes it at once.
+pub async fn rotate_secret(pool: &PgPool, id: i64, grace: chrono::Duration) -> Result<(), sqlx::Error> {
+    let props = entity::get_properties(pool, id).await?;
+    let previous = props.get("secret").cloned().unwrap_or_default();
+    let until = (chrono::Utc::now() + grace).to_rfc3339();
+    entity::set_properties(pool, id, &[
+        ("secret", &generate_secret()),
+        ("previous_secret", &previous),
+        ("previous_secret_until", &until),
+    ]).await
+}
+
+/// Pause or resume deliveries. Returns false if no such webhook.
+pub async fn set_active(pool: &PgPool, id: i64, active: bool) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "UPDATE entities SET is_active = $2, updated_at = NOW() WHERE id = $1 AND entity_type = 'webhook'"
+    )
+    .bind(id)
+    .bind(active)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Delete a webhook. Items it already created are kept.
+pub async fn delete(pool: &PgPool, id: i64) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM entities WHERE id = $1 AND entity_type = 'webhook'")
+        .bind(id)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+pub(crate) fn generate_secret() -> String {
+    let mut rng = rand::rng();
+    let bytes: Vec<u8> = (0..32).map(|_| rng.random()).collect();
+    hex::encode(bytes)
+}
+
+/// Check a `sha256=<hex>` signature header against the raw body in constant time.
+pub fn verify_signature(secret: &str, body: &[u8], header: &str) -> bool {
+    let Some(hex_sig) = header.trim().strip_prefix("sha256=") else {
+        return false;
+    };
+    let Ok(expected) = hex::decode(hex_sig) else {
+        return false;
+    };
+    let Ok(mut mac) = Hmac::<Sha256>::new_from_slice(secret.as_bytes()) else {
+        return false;
+    };
+    mac.update(body);
+    mac.verify_slice(&expected).is_ok()
+}
+
+impl Webhook {
+    /// Whether a delivery is signed with the current secret, or with the
+    /// previous one while its grace period lasts.
+    pub fn accepts(&self, body: &[u8], header: &str, now: chrono::DateTime<chrono::Utc>) -> bool {
+        if verify_signature(&self.secret, body, header) {
+            return true;
+        }
+        let in_grace = chrono::DateTime::parse_from_rfc3339(&self.previous_secret_until)
+            .is_ok_and(|until| now < until);
+        in_grace && !self.previous_secret.is_empty() && verify_signature(&self.previous_secret, body, header)
+    }
+
+    /// When the previous secret stops being accepted, if it still is.
+    pub fn previous_secret_expiry(&self) -> Option<String> {
+        chrono::DateTime::parse_from_rfc3339(&self.previous_secret_until)
+            .ok()
+            .filter(|until| *until > chrono::Utc::now())
+            .map(|until| until.format("%Y-%m-%d %H:%M").to_string())
+    }
+}
+
+/// Signature header value for a body, as a sender would compute it.
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .expect("HMAC accepts keys of any length");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Read a dot-separated path from a JSON payload. Scalars are stringified;
+/// empty paths, objects, arrays and nulls yield `None`.
+pub fn extract(payload: &serde_json::Value, path: &str) -> Option<String> {
+    if path.is_empty() {
+        return None;
+    }
+    let mut current = payload;
+    for part in path.split('.') {
+        current = match current {
+            serde_json::Value::Array(items) => items.get(part.parse::<usize>().ok()?)?,
+            other => other.get(part)?,
+        };
+    }
+    match current {
+        serde_json::Value::String(s) if !s.trim().is_empty() => Some(s.trim().to_string()),
+        serde_json::Value::Number(n) => Some(n.to_string()),
+        serde_json::Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+/// Find an item already created by this webhook for an external id.
+async fn find_delivered(pool: &PgPool, webhook_slug: &str, external_id: &str) -> Result<Option<i64>, sqlx::Error> {
+    sqlx::query_scalar(
+        "SELECT e.id FROM entities e \
+         JOIN entity_properties p_src ON e.id = p_src.entity_id AND p_src.key = 'source_webhook' \
+         JOIN entity_properties p_ext ON e.id = p_ext.entity_id AND p_ext.key = 'external_id' \
+         WHERE p_src.value = $1 AND p_ext.value = $2 \
+         LIMIT 1"
+    )
+    .bind(webhook_slug)
+    .bind(external_id)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Turn a verified payload into a suggestion or proposal on the webhook's ToR.
+pub async fn deliver(pool: &PgPool, hook: &Webhook, payload: &serde_json::Value) -> Result<Delivery, AppError> {
+    let external_id = extract(payload, &hook.external_id_field);
+    let delivered = match &external_id {
+        Some(ext) => find_delivered(pool, &hook.slug, ext).await?,
+        None => None,
+    };
+    if let Some(id) = delivered {
+        return Ok(Delivery::Duplicate { id });
+    }
+
+    let Some(description) = extract(payload, &hook.description_field) else {
+        return Ok(Delivery::MissingField(hook.description_field.clone()));
+    };
+    let today: String = sqlx::query_scalar("SELECT CURRENT_DATE::text").fetch_one(pool).await?;
+
+    let id = if hook.target == "proposal" {
+        let Some(title) = extract(payload, &hook.title_field) else {
+            return Ok(Delivery::MissingField(hook.title_field.clone()));
+        };
+        let rationale = extract(payload, &hook.rationale_field).unwrap_or_default();
+        proposal::create(
+            pool, hook.tor_id, &title, &description, &rationale,
+            hook.submitted_by_id, &today, None,
+        ).await?
+    } else {
+        suggestion::create(pool, hook.tor_id, &description, hook.submitted_by_id, &today).await?
+    };
+
+    entity::set_property(pool, id, "source_webhook", &hook.slug).await?;
+    if let Some(ext) = &external_id {
+        entity::set_property(pool, id, "external_id", ext).await?;
+    }
+
+    Ok(Delivery::Created { entity_type: hook.target.clone(), id })
+}
@@ -0,0 +1,85 @@
+//! Timed auto-transitions.
+//!
+//! A transition with `auto_after_days` set is also taken by the scheduler:
+//! once an entity has been in the transition's from-status for that many
+//! days and the transition's guards hold, it moves to the to-status without
+//! anyone acting. Time in a status is read from `status_changed_at`, which a
+//! database trigger keeps next to every `status` property.
+
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+
+use crate::errors::AppError;
+
+use super::{guards, hooks};
+
+/// An entity that has waited long enough for an auto-transition.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct DueTransition {
+    pub entity_id: i64,
+    pub entity_label: String,
+    pub scope: String,
+    pub transition_id: i64,
+    pub transition_label: String,
+    pub from_status_code: String,
+    pub to_status_code: String,
+    pub days: i64,
+    pub condition: Option<String>,
+}
+
+/// Entities past an auto-transition's wait as of `now`, each entity's
+/// shortest wait first.
+pub async fn find_due(pool: &PgPool, now: DateTime<Utc>) -> Result<Vec<DueTransition>, sqlx::Error> {
+    sqlx::query_as::<_, DueTransition>(
+        "SELECT e.id AS entity_id, e.label AS entity_label, p_scope.value AS scope, \
+                t.id AS transition_id, t.label AS transition_label, \
+                p_from_code.value AS from_status_code, p_to_code.value AS to_status_code, \
+                CAST(p_auto.value AS BIGINT) AS days, p_cond.value AS condition \
+         FROM entities t \
+         JOIN entity_properties p_auto ON t.id = p_auto.entity_id AND p_auto.key = 'auto_after_days' \
+         JOIN entity_properties p_scope ON t.id = p_scope.entity_id AND p_scope.key = 'entity_type_scope' \
+         JOIN relations r_from ON t.id = r_from.source_id \
+         JOIN entities rt_from ON r_from.relation_type_id = rt_from.id AND rt_from.name = 'transition_from' \
+         JOIN entity_properties p_from_code ON r_from.target_id = p_from_code.entity_id AND p_from_code.key = 'status_code' \
+         JOIN relations r_to ON t.id = r_to.source_id \
+         JOIN entities rt_to ON r_to.relation_type_id = rt_to.id AND rt_to.name = 'transition_to' \
+         JOIN entity_properties p_to_code ON r_to.target_id = p_to_code.entity_id AND p_to_code.key = 'status_code' \
+         JOIN entities e ON e.entity_type = p_scope.value \
+         JOIN entity_properties p_status ON e.id = p_status.entity_id AND p_status.key = 'status' \
+             AND p_status.value = p_from_code.value \
+         JOIN entity_properties p_since ON e.id = p_since.entity_id AND p_since.key = 'status_changed_at' \
+         LEFT JOIN entity_properties p_cond ON t.id = p_cond.entity_id AND p_cond.key = 'condition' \
+         WHERE t.entity_type = 'workflow_transition' \
+           AND CAST(p_auto.value AS BIGINT) > 0 \
+           AND p_since.value::TIMESTAMPTZ <= $1::TIMESTAMPTZ - make_interval(days => CAST(p_auto.value AS INT)) \
+         ORDER BY e.id, CAST(p_auto.value AS BIGINT), t.id",
+    )
+    .bind(now.to_rfc3339())
+    .fetch_all(pool)
+    .await
+}
+
+/// Whether the transition's guards hold for the entity now.
+pub async fn guards_hold(pool: &PgPool, due: &DueTransition) -> Result<bool, sqlx::Error> {
+    let Some(condition) = due.condition.as_deref().filter(|c| !c.trim().is_empty()) else {
+        return Ok(true);
+    };
+    let facts = guards::load_facts(pool, due.entity_id).await?;
+    Ok(guards::allows(condition, &facts))
+}
+
+/// Take the transition as the system and run its hooks. Proposals and
+/// meetings go through their own transitions so domain events and emails
+/// follow as usual.
+pub async fn apply(pool: &PgPool, due: &DueTransition) -> Result<(), AppError> {
+    super::queries::write_status(pool, &due.scope, due.entity_id, &due.to_status_code, 0).await?;
+    hooks::run(pool, &hooks::Firing {
+        transition_id: due.transition_id,
+        scope: &due.scope,
+        entity_id: due.entity_id,
+        from_status: &due.from_status_code,
+        to_status: &due.to_status_code,
+        actor_id: 0,
+    }).await;
+    Ok(())
+}
@@ -0,0 +1,153 @@
+//! Guard conditions on workflow transitions.
+//!
+//! A transition's `condition` property lists guards separated by `;`. The
+//! transition is only available when every guard holds for the entity:
+//!
+//! - `key=value`, `key!=value`: a property equals (or differs from) the text
+//! - `key>n`, `key>=n`, `key<n`, `key<=n`: a property compared as a number;
+//!   a missing or non-numeric property fails the guard
+//! - `has:relation_type`, `!has:relation_type`: the entity takes part in (or
+//!   has no) relation of that type, in either direction
+//!
+//! A missing property reads as empty text. A stored condition that no longer
+//! parses blocks its transition rather than being ignored.
+
+use std::collections::HashMap;
+
+use sqlx::PgPool;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+impl Op {
+    fn is_numeric(self) -> bool {
+        !matches!(self, Op::Eq | Op::Ne)
+    }
+}
+
+/// Comparison operators, longest first so `>=` is not read as `>`.
+const OPS: &[(&str, Op)] = &[
+    ("!=", Op::Ne),
+    (">=", Op::Ge),
+    ("<=", Op::Le),
+    ("=", Op::Eq),
+    (">", Op::Gt),
+    ("<", Op::Lt),
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Guard {
+    Property { key: String, op: Op, value: String },
+    Relation { relation_type: String, exists: bool },
+}
+
+/// What guards are checked against: an entity's properties and the types
+/// of the relations it takes part in.
+#[derive(Debug, Clone, Default)]
+pub struct EntityFacts {
+    pub properties: HashMap<String, String>,
+    pub relation_types: Vec<String>,
+}
+
+fn is_name(s: &str) -> bool {
+    !s.is_empty() && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+}
+
+/// Parse a condition into its guards. An empty condition has none.
+pub fn parse(condition: &str) -> Result<Vec<Guard>, String> {
+    condition.split(';')
+        .map(str::trim)
+        .filter(|clause| !clause.is_empty())
+        .map(parse_guard)
+        .collect()
+}
+
+fn parse_guard(clause: &str) -> Result<Guard, String> {
+    let (exists, rest) = match clause.strip_prefix('!') {
+        Some(rest) => (false, rest.trim_start()),
+        None => (true, clause),
+    };
+    if let Some(relation_type) = rest.strip_prefix("has:") {
+        let relation_type = relation_type.trim();
+        if !is_name(relation_type) {
+            return Err(format!("'{clause}' needs a relation type after has:"));
+        }
+        return Ok(Guard::Relation { relation_type: relation_type.to_string(), exists });
+    }
+
+    let Some(at) = clause.find(['=', '!', '<', '>']) else {
+        return Err(format!("'{clause}' is not a comparison; use key=value, key>n or has:relation_type"));
+    };
+    let key = clause[..at].trim();
+    if !is_name(key) {
+        return Err(format!("'{clause}' does not start with a property name"));
+    }
+    let rest = &clause[at..];
+    let Some((symbol, op)) = OPS.iter().find(|(symbol, _)| rest.starts_with(symbol)) else {
+        return Err(format!("'{clause}' has an unknown operator"));
+    };
+    let value = rest[symbol.len()..].trim();
+    if op.is_numeric() && value.parse::<f64>().is_err() {
+        return Err(format!("'{clause}' compares with '{value}', which is not a number"));
+    }
+    Ok(Guard::Property { key: key.to_string(), op: *op, value: value.to_string() })
+}
+
+impl Guard {
+    pub fn holds(&self, facts: &EntityFacts) -> bool {
+        match self {
+            Guard::Relation { relation_type, exists } => {
+                facts.relation_types.iter().any(|t| t == relation_type) == *exists
+            }
+            Guard::Property { key, op, value } => {
+                let actual = facts.properties.get(key).map_or("", |v| v.as_str());
+                match op {
+                    Op::Eq => actual == value,
+                    Op::Ne => actual != value,
+                    _ => {
+                        let (Ok(actual), Ok(value)) = (actual.trim().parse::<f64>(), value.parse::<f64>()) else {
+                            return false;
+                        };
+                        match op {
+                            Op::Gt => actual > value,
+                            Op::Ge => actual >= value,
+                            Op::Lt => actual < value,
+                            _ => actual <= value,
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Whether every guard of `condition` holds for the entity.
+pub fn allows(condition: &str, facts: &EntityFacts) -> bool {
+    parse(condition).is_ok_and(|guards| guards.iter().all(|g| g.holds(facts)))
+}
+
+/// Load the properties and relation types guards are checked against.
+pub async fn load_facts(pool: &PgPool, entity_id: i64) -> Result<EntityFacts, sqlx::Error> {
+    let properties: Vec<(String, String)> = sqlx::query_as(
+        "SELECT key, value FROM entity_properties WHERE entity_id = $1"
+    )
+    .bind(entity_id)
+    .fetch_all(pool)
+    .await?;
+    let relation_types: Vec<String> = sqlx::query_scalar(
+        "SELECT DISTINCT rt.name FROM relations r \
+         JOIN entities rt ON rt.id = r.relation_type_id \
+         WHERE r.source_id = $1 OR r.target_id = $1"
+    )
+    .bind(entity_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(EntityFacts { properties: properties.into_iter().collect(), relation_types })
+}
@@ -0,0 +1,224 @@
+//! Transition hooks: side effects a transition declares.
+//!
+//! A transition's `hooks` property holds a JSON array of actions, run in
+//! order each time the transition fires through [`super::execute_transition`]
+//! or as a timed auto-transition:
+//!
+//! ```text
+//! [{"action": "create_warning", "severity": "high", "message": "{label} escalated", "permission": "proposal.approve"},
+//!  {"action": "send_webhook", "event": "escalated"},
+//!  {"action": "set_property", "key": "approved_on", "value": "{today}"},
+//!  {"action": "spawn_entity", "entity_type": "action_item", "label": "Follow up {label}", "relation_type": "arising_from"}]
+//! ```
+//!
+//! - `create_warning` warns holders of `permission` (default `workflow.manage`)
+//! - `send_webhook` records a `{scope}.{event}` domain event, which outbound
+//!   webhooks subscribed to it deliver
+//! - `set_property` sets a property on the entity; `status` is off limits
+//! - `spawn_entity` creates an entity, linked to this one by `relation_type`
+//!   (new entity → this one) when given
+//!
+//! Text may use `{id}`, `{label}`, `{from}`, `{to}` and `{today}`. Hooks run
+//! after the status has changed, so a failing hook is logged and the rest
+//! still run.
+
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+
+use crate::models::{domain_event, entity, relation};
+use crate::warnings;
+
+/// Permission whose holders are warned when a hook names none.
+const DEFAULT_WARNING_PERMISSION: &str = "workflow.manage";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum Hook {
+    CreateWarning {
+        severity: String,
+        message: String,
+        #[serde(default)]
+        permission: String,
+    },
+    SendWebhook {
+        event: String,
+    },
+    SetProperty {
+        key: String,
+        value: String,
+    },
+    SpawnEntity {
+        entity_type: String,
+        label: String,
+        #[serde(default)]
+        relation_type: String,
+    },
+}
+
+/// The transition being taken, for placeholders and event payloads.
+#[derive(Debug, Clone)]
+pub struct Firing<'a> {
+    pub transition_id: i64,
+    pub scope: &'a str,
+    pub entity_id: i64,
+    pub from_status: &'a str,
+    pub to_status: &'a str,
+    pub actor_id: i64,
+}
+
+fn is_identifier(s: &str) -> bool {
+    !s.is_empty() && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+}
+
+/// Parse and check a hooks definition. Blank means no hooks.
+pub fn parse(json: &str) -> Result<Vec<Hook>, String> {
+    if json.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    let hooks: Vec<Hook> = serde_json::from_str(json)
+        .map_err(|e| format!("Hooks must be a JSON array of actions: {}", e))?;
+    for (i, hook) in hooks.iter().enumerate() {
+        let n = i + 1;
+        match hook {
+            Hook::CreateWarning { severity, message, permission } => {
+                if !warnings::broadcast::SEVERITIES.contains(&severity.as_str()) {
+                    return Err(format!("Hook {}: unknown severity '{}'", n, severity));
+                }
+                if message.trim().is_empty() {
+                    return Err(format!("Hook {}: a warning needs a message", n));
+                }
+                if !permission.is_empty() && !is_identifier(permission) {
+                    return Err(format!("Hook {}: invalid permission '{}'", n, permission));
+                }
+            }
+            Hook::SendWebhook { event } => {
+                if !is_identifier(event) {
+                    return Err(format!("Hook {}: invalid event name '{}'", n, event));
+                }
+            }
+            Hook::SetProperty { key, .. } => {
+                if !is_identifier(key) {
+                    return Err(format!("Hook {}: invalid property key '{}'", n, key));
+                }
+                if key == "status" || key == "status_changed_at" {
+                    return Err(format!("Hook {}: status is changed by transitions, not hooks", n));
+                }
+            }
+            Hook::SpawnEntity { entity_type, label, relation_type } => {
+                if !is_identifier(entity_type) {
+                    return Err(format!("Hook {}: invalid entity type '{}'", n, entity_type));
+                }
+                if label.trim().is_empty() {
+                    return Err(format!("Hook {}: a spawned entity needs a label", n));
+                }
+                if !relation_type.is_empty() && !is_identifier(relation_type) {
+                    return Err(format!("Hook {}: invalid relation type '{}'", n, relation_type));
+                }
+            }
+        }
+    }
+    Ok(hooks)
+}
+
+/// Relation types the hooks link spawned entities with.
+pub fn relation_types(hooks: &[Hook]) -> Vec<&str> {
+    hooks.iter()
+        .filter_map(|h| match h {
+            Hook::SpawnEntity { relation_type, .. } if !relation_type.is_empty() => Some(relation_type.as_str()),
+            _ => None,
+        })
+        .collect()
+}
+
+fn fill(text: &str, firing: &Firing<'_>, label: &str) -> String {
+    text.replace("{id}", &firing.entity_id.to_string())
+        .replace("{label}", label)
+        .replace("{from}", firing.from_status)
+        .replace("{to}", firing.to_status)
+        .replace("{today}", &warnings::clock::now().format("%Y-%m-%d").to_string())
+}
+
+async fn run_one(pool: &PgPool, hook: &Hook, firing: &Firing<'_>, label: &str) -> Result<(), sqlx::Error> {
+    match hook {
+        Hook::CreateWarning { severity, message, permission } => {
+            let permission = if permission.is_empty() { DEFAULT_WARNING_PERMISSION } else { permission };
+            let recipients = warnings::get_users_with_permission(pool, permission).await?;
+            if recipients.is_empty() {
+                return Ok(());
+            }
+            let message = fill(message, firing, label);
+            let details = serde_json::json!({
+                "entity_type": firing.scope,
+                "entity_id": firing.entity_id,
+                "from_status": firing.from_status,
+                "to_status": firing.to_status,
+                "transition_id": firing.transition_id,
+            });
+            let wid = warnings::create_warning(
+                pool, severity, "workflow", "event.workflow.transition_hook", &message, &details.to_string(), "system",
+            ).await?;
+            warnings::create_receipts(pool, wid, &recipients).await?;
+        }
+        Hook::SendWebhook { event } => {
+            let payload = serde_json::json!({
+                "label": label,
+                "from_status": firing.from_status,
+                "to_status": firing.to_status,
+                "transition_id": firing.transition_id,
+            });
+            let mut tx = pool.begin().await?;
+            domain_event::append(
+                &mut tx, &format!("{}.{}", firing.scope, event), firing.scope, firing.entity_id, firing.actor_id, payload,
+            ).await?;
+            tx.commit().await?;
+            warnings::outbox::wake();
+        }
+        Hook::SetProperty { key, value } => {
+            entity::set_property(pool, firing.entity_id, key, &fill(value, firing, label)).await?;
+        }
+        Hook::SpawnEntity { entity_type, label: new_label, relation_type } => {
+            let base = format!("{}_{}_{}", entity_type, firing.scope, firing.entity_id);
+            let name = entity::available_name(pool, entity_type, &base).await?;
+            let id = entity::create(pool, entity_type, &name, &fill(new_label, firing, label)).await?;
+            if !relation_type.is_empty() {
+                relation::create(pool, relation_type, id, firing.entity_id).await?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Run the transition's hooks for the entity. Returns how many succeeded.
+pub async fn run(pool: &PgPool, firing: &Firing<'_>) -> usize {
+    let definition = match entity::get_property(pool, firing.transition_id, "hooks").await {
+        Ok(Some(d)) => d,
+        Ok(None) => return 0,
+        Err(e) => {
+            log::error!("Loading hooks of transition {} failed: {}", firing.transition_id, e);
+            return 0;
+        }
+    };
+    let hooks = match parse(&definition) {
+        Ok(h) => h,
+        Err(e) => {
+            log::error!("Transition {} has invalid hooks: {}", firing.transition_id, e);
+            return 0;
+        }
+    };
+    let label = match entity::find_by_id(pool, firing.entity_id).await {
+        Ok(Some(e)) => e.label,
+        _ => String::new(),
+    };
+
+    let mut succeeded = 0;
+    for hook in &hooks {
+        match run_one(pool, hook, firing, &label).await {
+            Ok(()) => succeeded += 1,
+            Err(e) => log::error!(
+                "Hook {:?} of transition {} failed for {} {}: {}",
+                hook, firing.transition_id, firing.scope, firing.entity_id, e
+            ),
+        }
+    }
+    succeeded
+}
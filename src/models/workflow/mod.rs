@@ -1,5 +1,8 @@
 pub mod types;
 pub mod queries;
+pub mod guards;
+pub mod auto;
+pub mod hooks;
 
 pub use types::*;
 pub use queries::*;
@@ -1,7 +1,8 @@
 use sqlx::PgPool;
 use crate::errors::AppError;
 use crate::auth::session::Permissions;
-use crate::models::{entity, relation};
+use crate::models::{entity, meeting, proposal, relation};
+use super::{guards, hooks};
 use super::types::*;
 
 // =====================================================================
@@ -9,17 +10,19 @@ use super::types::*;
 // =====================================================================
 
 /// Find all available transitions from the current status,
-/// filtered by user permissions and entity properties (conditions).
+/// filtered by user permissions and the transitions' guard conditions
+/// (see [`guards`]) checked against the entity.
 pub async fn find_available_transitions(
     pool: &PgPool,
     entity_type_scope: &str,
     current_status: &str,
     user_permissions: &Permissions,
-    entity_properties: &std::collections::HashMap<String, String>,
+    entity_id: i64,
 ) -> Result<Vec<AvailableTransition>, AppError> {
     // Find all transitions where transition_from matches current_status and entity_type_scope
     #[derive(sqlx::FromRow)]
     struct TransitionRow {
+        transition_id: i64,
         required_permission: String,
         condition: Option<String>,
         requires_outcome: String,
@@ -28,7 +31,8 @@ pub async fn find_available_transitions(
     }
 
     let all_rows: Vec<TransitionRow> = sqlx::query_as(
-        "SELECT COALESCE(p_perm.value, '') AS required_permission, \
+        "SELECT t.id AS transition_id, \
+                COALESCE(p_perm.value, '') AS required_permission, \
                 p_cond.value AS condition, \
                 COALESCE(p_outcome.value, 'false') AS requires_outcome, \
                 COALESCE(p_to_code.value, '') AS to_status_code, \
@@ -61,6 +65,7 @@ pub async fn find_available_transitions(
             r.required_permission,
             r.condition,
             AvailableTransition {
+                transition_id: r.transition_id,
                 to_status_code: r.to_status_code,
                 transition_label: r.transition_label,
                 requires_outcome: r.requires_outcome == "true",
@@ -69,6 +74,7 @@ pub async fn find_available_transitions(
     }).collect();
 
     // Filter by permission and condition
+    let mut facts: Option<guards::EntityFacts> = None;
     let mut available = Vec::new();
     for (required_perm, condition, transition) in all_transitions {
         // Check permission
@@ -76,13 +82,13 @@ pub async fn find_available_transitions(
             continue;
         }
 
-        // Check condition (format: "key=value")
-        if let Some(cond) = &condition {
-            if let Some((key, value)) = cond.split_once('=') {
-                let actual = entity_properties.get(key).map(|s| s.as_str()).unwrap_or("");
-                if actual != value {
-                    continue;
-                }
+        // Check guards, loading the entity's facts on first need
+        if let Some(cond) = condition.as_deref().filter(|c| !c.trim().is_empty()) {
+            if facts.is_none() {
+                facts = Some(guards::load_facts(pool, entity_id).await.map_err(AppError::Db)?);
+            }
+            if !facts.as_ref().is_some_and(|f| guards::allows(cond, f)) {
+                continue;
             }
         }
 
@@ -100,10 +106,10 @@ pub async fn validate_transition(
     current_status: &str,
     new_status: &str,
     user_permissions: &Permissions,
-    entity_properties: &std::collections::HashMap<String, String>,
+    entity_id: i64,
 ) -> Result<AvailableTransition, AppError> {
     let available = find_available_transitions(
-        pool, entity_type_scope, current_status, user_permissions, entity_properties,
+        pool, entity_type_scope, current_status, user_permissions, entity_id,
     ).await?;
 
     available.into_iter()
@@ -113,6 +119,65 @@ pub async fn validate_transition(
         ))
 }
 
+/// Take a transition: validate it as [`validate_transition`] does, write the
+/// new status and run the transition's [`hooks`]. Proposals and meetings
+/// change status through their own models so domain events and emails
+/// follow as usual.
+#[allow(clippy::too_many_arguments)]
+pub async fn execute_transition(
+    pool: &PgPool,
+    entity_type_scope: &str,
+    entity_id: i64,
+    current_status: &str,
+    new_status: &str,
+    user_permissions: &Permissions,
+    actor_id: i64,
+) -> Result<AvailableTransition, AppError> {
+    let transition = validate_transition(
+        pool, entity_type_scope, current_status, new_status, user_permissions, entity_id,
+    ).await?;
+    write_status(pool, entity_type_scope, entity_id, new_status, actor_id).await?;
+    run_transition_hooks(pool, entity_type_scope, entity_id, current_status, &transition, actor_id).await;
+    Ok(transition)
+}
+
+/// Set an entity's status the way its model expects.
+pub(super) async fn write_status(
+    pool: &PgPool,
+    entity_type_scope: &str,
+    entity_id: i64,
+    new_status: &str,
+    actor_id: i64,
+) -> Result<(), AppError> {
+    match entity_type_scope {
+        "proposal" => proposal::transition(pool, entity_id, new_status, None, actor_id).await?,
+        "meeting" => meeting::transition(pool, entity_id, new_status, actor_id).await?,
+        _ => entity::set_property(pool, entity_id, "status", new_status).await?,
+    }
+    Ok(())
+}
+
+/// Run the hooks of a transition the caller validated and wrote itself, for
+/// status changes that carry more than the status (e.g. a rejection reason).
+/// Returns how many hooks succeeded.
+pub async fn run_transition_hooks(
+    pool: &PgPool,
+    entity_type_scope: &str,
+    entity_id: i64,
+    from_status: &str,
+    transition: &AvailableTransition,
+    actor_id: i64,
+) -> usize {
+    hooks::run(pool, &hooks::Firing {
+        transition_id: transition.transition_id,
+        scope: entity_type_scope,
+        entity_id,
+        from_status,
+        to_status: &transition.to_status_code,
+        actor_id,
+    }).await
+}
+
 // =====================================================================
 // Builder queries (used by workflow builder UI)
 // =====================================================================
@@ -209,6 +274,8 @@ pub async fn list_transitions_for_scope(pool: &PgPool, scope: &str) -> Result<Ve
         required_permission: String,
         condition: Option<String>,
         requires_outcome: String,
+        auto_after_days: String,
+        hooks: Option<String>,
     }
 
     let rows: Vec<TransitionRow> = sqlx::query_as(
@@ -219,7 +286,9 @@ pub async fn list_transitions_for_scope(pool: &PgPool, scope: &str) -> Result<Ve
                 COALESCE(p_to_code.value, '') AS to_status_code, \
                 COALESCE(p_perm.value, '') AS required_permission, \
                 p_cond.value AS condition, \
-                COALESCE(p_outcome.value, 'false') AS requires_outcome \
+                COALESCE(p_outcome.value, 'false') AS requires_outcome, \
+                COALESCE(p_auto.value, '0') AS auto_after_days, \
+                p_hooks.value AS hooks \
          FROM entities t \
          JOIN entity_properties p_scope ON t.id = p_scope.entity_id AND p_scope.key = 'entity_type_scope' \
          JOIN relations r_from ON t.id = r_from.source_id \
@@ -233,6 +302,8 @@ pub async fn list_transitions_for_scope(pool: &PgPool, scope: &str) -> Result<Ve
          LEFT JOIN entity_properties p_perm ON t.id = p_perm.entity_id AND p_perm.key = 'required_permission' \
          LEFT JOIN entity_properties p_cond ON t.id = p_cond.entity_id AND p_cond.key = 'condition' \
          LEFT JOIN entity_properties p_outcome ON t.id = p_outcome.entity_id AND p_outcome.key = 'requires_outcome' \
+         LEFT JOIN entity_properties p_auto ON t.id = p_auto.entity_id AND p_auto.key = 'auto_after_days' \
+         LEFT JOIN entity_properties p_hooks ON t.id = p_hooks.entity_id AND p_hooks.key = 'hooks' \
          WHERE t.entity_type = 'workflow_transition' \
            AND p_scope.value = $1 \
          ORDER BY from_status_code, to_status_code"
@@ -254,6 +325,8 @@ pub async fn list_transitions_for_scope(pool: &PgPool, scope: &str) -> Result<Ve
             required_permission: r.required_permission,
             condition: r.condition,
             requires_outcome: r.requires_outcome == "true",
+            auto_after_days: r.auto_after_days.parse::<i64>().unwrap_or(0),
+            hooks: r.hooks,
             transition_label: r.transition_label,
         }
     }).collect();
@@ -353,15 +426,14 @@ pub async fn delete_status(pool: &PgPool, id: i64) -> Result<(), AppError> {
 }
 
 /// Create a new workflow transition entity with properties and relations.
+/// A positive `auto_after_days` makes the scheduler take the transition after
+/// that many days in the from-status; `hooks` run when it fires.
 pub async fn create_transition(
     pool: &PgPool,
     scope: &str,
     from_status_id: i64,
     to_status_id: i64,
-    label: &str,
-    required_permission: &str,
-    requires_outcome: bool,
-    condition: &str,
+    input: &TransitionInput<'_>,
 ) -> Result<i64, AppError> {
     // Read from/to status codes for the entity name and denormalized properties
     let from_code = entity::get_property(pool, from_status_id, "status_code")
@@ -374,22 +446,29 @@ pub async fn create_transition(
         .unwrap_or_default();
 
     let name = format!("{}.{}_to_{}", scope, from_code, to_code);
-    let id = entity::create(pool, "workflow_transition", &name, label)
+    let id = entity::create(pool, "workflow_transition", &name, input.label)
         .await
         .map_err(AppError::Db)?;
 
     // Set properties (both canonical and denormalized)
-    let requires_outcome_str = if requires_outcome { "true" } else { "false" };
+    let requires_outcome_str = if input.requires_outcome { "true" } else { "false" };
     let mut props: Vec<(&str, &str)> = vec![
         ("entity_type_scope", scope),
         ("from_status_code", &from_code),
         ("to_status_code", &to_code),
-        ("transition_label", label),
-        ("required_permission", required_permission),
+        ("transition_label", input.label),
+        ("required_permission", input.required_permission),
         ("requires_outcome", requires_outcome_str),
     ];
-    if !condition.is_empty() {
-        props.push(("condition", condition));
+    if !input.condition.is_empty() {
+        props.push(("condition", input.condition));
+    }
+    let auto_after_days_str = input.auto_after_days.to_string();
+    if input.auto_after_days > 0 {
+        props.push(("auto_after_days", &auto_after_days_str));
+    }
+    if !input.hooks.is_empty() {
+        props.push(("hooks", input.hooks));
     }
     entity::set_properties(pool, id, &props).await.map_err(AppError::Db)?;
 
@@ -402,29 +481,32 @@ pub async fn create_transition(
 
 /// Update an existing workflow transition's properties.
 /// Does NOT change from/to status -- delete and recreate for that.
-pub async fn update_transition(
-    pool: &PgPool,
-    id: i64,
-    label: &str,
-    required_permission: &str,
-    requires_outcome: bool,
-    condition: &str,
-) -> Result<(), AppError> {
+pub async fn update_transition(pool: &PgPool, id: i64, input: &TransitionInput<'_>) -> Result<(), AppError> {
     // Update entity label
     let ent = entity::find_by_id(pool, id).await.map_err(AppError::Db)?
         .ok_or(AppError::NotFound)?;
-    entity::update(pool, id, &ent.name, label).await.map_err(AppError::Db)?;
+    entity::update(pool, id, &ent.name, input.label).await.map_err(AppError::Db)?;
 
     // Update properties
-    entity::set_property(pool, id, "transition_label", label).await.map_err(AppError::Db)?;
-    entity::set_property(pool, id, "required_permission", required_permission).await.map_err(AppError::Db)?;
-    let requires_outcome_str = if requires_outcome { "true" } else { "false" };
+    entity::set_property(pool, id, "transition_label", input.label).await.map_err(AppError::Db)?;
+    entity::set_property(pool, id, "required_permission", input.required_permission).await.map_err(AppError::Db)?;
+    let requires_outcome_str = if input.requires_outcome { "true" } else { "false" };
     entity::set_property(pool, id, "requires_outcome", requires_outcome_str).await.map_err(AppError::Db)?;
 
-    if condition.is_empty() {
+    if input.condition.is_empty() {
         entity::delete_property(pool, id, "condition").await.map_err(AppError::Db)?;
     } else {
-        entity::set_property(pool, id, "condition", condition).await.map_err(AppError::Db)?;
+        entity::set_property(pool, id, "condition", input.condition).await.map_err(AppError::Db)?;
+    }
+    if input.auto_after_days > 0 {
+        entity::set_property(pool, id, "auto_after_days", &input.auto_after_days.to_string()).await.map_err(AppError::Db)?;
+    } else {
+        entity::delete_property(pool, id, "auto_after_days").await.map_err(AppError::Db)?;
+    }
+    if input.hooks.is_empty() {
+        entity::delete_property(pool, id, "hooks").await.map_err(AppError::Db)?;
+    } else {
+        entity::set_property(pool, id, "hooks", input.hooks).await.map_err(AppError::Db)?;
     }
 
     Ok(())
@@ -34,12 +34,43 @@ pub struct WorkflowTransition {
     pub required_permission: String,
     pub condition: Option<String>,
     pub requires_outcome: bool,
+    /// Days in the from-status after which the scheduler takes the
+    /// transition; 0 when it is only taken by hand.
+    pub auto_after_days: i64,
+    /// JSON array of side effects run when the transition fires.
+    pub hooks: Option<String>,
     pub transition_label: String,
 }
 
+impl WorkflowTransition {
+    /// Number of hooks declared, for the builder's badge.
+    pub fn hook_count(&self) -> usize {
+        self.hooks.as_deref()
+            .and_then(|h| super::hooks::parse(h).ok())
+            .map_or(0, |h| h.len())
+    }
+}
+
+/// Editable fields of a transition, from the builder form.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TransitionInput<'a> {
+    pub label: &'a str,
+    /// Empty when anyone who may act on the entity can take it.
+    pub required_permission: &'a str,
+    pub requires_outcome: bool,
+    /// Guard condition; empty for none.
+    pub condition: &'a str,
+    /// Days in the from-status after which the scheduler takes the
+    /// transition; 0 when it is only taken by hand.
+    pub auto_after_days: i64,
+    /// JSON array of [`super::hooks::Hook`]s; empty for none.
+    pub hooks: &'a str,
+}
+
 /// Information about an available transition for UI rendering.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AvailableTransition {
+    pub transition_id: i64,
     pub to_status_code: String,
     pub transition_label: String,
     pub requires_outcome: bool,
@@ -25,5 +25,32 @@ pub struct AgendaPointDetailTemplate {
     pub agenda_point: AgendaPointDetail,
     pub coas: Vec<CoaDetail>,
     pub opinions: Vec<OpinionSummary>,
+    /// Earlier versions of the opinions, for the revision history.
+    pub opinion_versions: Vec<crate::models::opinion::OpinionVersion>,
+    /// Description of who records decisions in this ToR.
+    pub decision_authority: String,
+    pub can_decide: bool,
     pub available_transitions: Vec<AvailableTransition>,
+    /// Member reactions on the opinions.
+    pub sentiment: crate::models::opinion::Sentiment,
+    /// The viewing user, who cannot react to their own opinion.
+    pub user_id: i64,
+    /// Whether a ballot is open on the agenda point.
+    pub ballot_open: bool,
+    /// Follow-up actions arising from the agenda point.
+    pub action_items: Vec<crate::models::action_item::ActionItem>,
+}
+
+impl AgendaPointDetailTemplate {
+    pub fn versions_for(&self, opinion_id: i64) -> Vec<&crate::models::opinion::OpinionVersion> {
+        self.opinion_versions.iter().filter(|v| v.opinion_id == opinion_id).collect()
+    }
+
+    pub fn opinions_locked(&self) -> bool {
+        crate::models::opinion::opinions_locked(&self.agenda_point.status)
+    }
+
+    pub fn reactions(&self) -> &'static [(&'static str, &'static str)] {
+        crate::models::opinion::REACTIONS
+    }
 }
@@ -83,3 +83,116 @@ pub struct ApiErrorResponse {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub details: Option<String>,
 }
+
+/// Create or update a ToR via the API. On update, omitted fields are left
+/// unchanged; on create, `status` defaults to `active` and `meeting_cadence`
+/// to `ad-hoc`.
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct ApiTorRequest {
+    pub name: Option<String>,
+    pub label: Option<String>,
+    pub description: Option<String>,
+    pub status: Option<String>,
+    pub meeting_cadence: Option<String>,
+    pub cadence_day: Option<String>,
+    pub cadence_time: Option<String>,
+    pub cadence_duration_minutes: Option<String>,
+    pub default_location: Option<String>,
+    pub remote_url: Option<String>,
+    pub background_repo_url: Option<String>,
+    pub tor_number: Option<String>,
+    pub classification: Option<String>,
+    pub version: Option<String>,
+    pub organization: Option<String>,
+    pub focus_scope: Option<String>,
+    pub objectives: Option<Vec<String>>,
+    pub inputs_required: Option<Vec<String>>,
+    pub outputs_expected: Option<Vec<String>>,
+    pub poc_contact: Option<String>,
+    pub phase_scheduling: Option<String>,
+    pub info_platform: Option<String>,
+    pub invite_policy: Option<String>,
+}
+
+impl ApiTorRequest {
+    pub const STATUSES: &'static [&'static str] = &["active", "archived"];
+    pub const CADENCES: &'static [&'static str] = &["ad-hoc", "weekly", "biweekly", "monthly", "daily", "working_days"];
+
+    /// Validation errors; `creating` makes name and label required.
+    pub fn validate(&self, creating: bool) -> Vec<String> {
+        use crate::auth::validate;
+        let mut errors = Vec::new();
+        match (&self.name, creating) {
+            (Some(name), _) => errors.extend(validate::validate_required(name, "Name", 50)),
+            (None, true) => errors.push("Name is required".to_string()),
+            (None, false) => {}
+        }
+        match (&self.label, creating) {
+            (Some(label), _) => errors.extend(validate::validate_required(label, "Label", 100)),
+            (None, true) => errors.push("Label is required".to_string()),
+            (None, false) => {}
+        }
+        if let Some(description) = &self.description {
+            errors.extend(validate::validate_optional(description, "Description", 500));
+        }
+        if let Some(status) = self.status.as_deref().filter(|s| !Self::STATUSES.contains(s)) {
+            errors.push(format!("Status must be one of {} (got '{}')", Self::STATUSES.join(", "), status));
+        }
+        if let Some(cadence) = self.meeting_cadence.as_deref().filter(|c| !Self::CADENCES.contains(c)) {
+            errors.push(format!("Meeting cadence must be one of {} (got '{}')", Self::CADENCES.join(", "), cadence));
+        }
+        errors
+    }
+
+    /// Properties given in the request, as stored on the ToR entity. Lists
+    /// are stored as JSON arrays, like the ToR form does.
+    pub fn props(&self) -> Vec<(&'static str, String)> {
+        let list = |items: &Vec<String>| {
+            let items: Vec<&str> = items.iter().map(|i| i.trim()).filter(|i| !i.is_empty()).collect();
+            serde_json::to_string(&items).unwrap_or_else(|_| "[]".to_string())
+        };
+        let text = [
+            ("description", &self.description),
+            ("status", &self.status),
+            ("meeting_cadence", &self.meeting_cadence),
+            ("cadence_day", &self.cadence_day),
+            ("cadence_time", &self.cadence_time),
+            ("cadence_duration_minutes", &self.cadence_duration_minutes),
+            ("default_location", &self.default_location),
+            ("remote_url", &self.remote_url),
+            ("background_repo_url", &self.background_repo_url),
+            ("tor_number", &self.tor_number),
+            ("classification", &self.classification),
+            ("version", &self.version),
+            ("organization", &self.organization),
+            ("focus_scope", &self.focus_scope),
+            ("poc_contact", &self.poc_contact),
+            ("phase_scheduling", &self.phase_scheduling),
+            ("info_platform", &self.info_platform),
+            ("invite_policy", &self.invite_policy),
+        ];
+        let lists = [
+            ("objectives", &self.objectives),
+            ("inputs_required", &self.inputs_required),
+            ("outputs_expected", &self.outputs_expected),
+        ];
+        text.into_iter()
+            .filter_map(|(key, value)| value.as_ref().map(|v| (key, v.trim().to_string())))
+            .chain(lists.into_iter().filter_map(|(key, value)| value.as_ref().map(|v| (key, list(v)))))
+            .collect()
+    }
+}
+
+/// Assign a user to a position in a ToR via the API.
+#[derive(Deserialize, Debug)]
+pub struct ApiTorMemberRequest {
+    pub user_id: i64,
+    pub position_id: i64,
+    #[serde(default = "default_membership_type")]
+    pub membership_type: String,
+}
+
+fn default_membership_type() -> String {
+    "optional".to_string()
+}
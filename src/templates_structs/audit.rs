@@ -1,6 +1,9 @@
 use askama::Template;
 
-use crate::models::audit::AuditEntryPage;
+use crate::audit::chain::ChainReport;
+use crate::models::access_review::{AccessEntry, AccessReview};
+use crate::models::audit::{AuditEntryPage, AuditFilter};
+use crate::models::recertification::{Campaign, Item};
 use super::PageContext;
 
 #[derive(Template)]
@@ -8,7 +11,66 @@ use super::PageContext;
 pub struct AuditListTemplate {
     pub ctx: PageContext,
     pub audit_page: AuditEntryPage,
-    pub search_query: Option<String>,
-    pub action_filter: Option<String>,
-    pub target_type_filter: Option<String>,
+    pub filter: AuditFilter,
+}
+
+/// Result of re-walking both audit hash chains.
+#[derive(Template)]
+#[template(path = "audit/verify.html")]
+pub struct AuditVerifyTemplate {
+    pub ctx: PageContext,
+    pub checked_at: String,
+    pub database: ChainReport,
+    pub files: ChainReport,
+}
+
+impl AuditVerifyTemplate {
+    pub fn reports(&self) -> [(&'static str, &ChainReport); 2] {
+        [("Database entries", &self.database), ("Log files", &self.files)]
+    }
+}
+
+/// Generated access reviews and their sign-off status.
+#[derive(Template)]
+#[template(path = "audit/access_reviews.html")]
+pub struct AccessReviewsTemplate {
+    pub ctx: PageContext,
+    pub reviews: Vec<AccessReview>,
+}
+
+/// One access review: the captured access and the sign-off form.
+#[derive(Template)]
+#[template(path = "audit/access_review.html")]
+pub struct AccessReviewTemplate {
+    pub ctx: PageContext,
+    pub review: AccessReview,
+    pub entries: Vec<AccessEntry>,
+}
+
+/// Recertification campaigns with their progress, and the launch form.
+#[derive(Template)]
+#[template(path = "admin/recertifications.html")]
+pub struct RecertificationCampaignsTemplate {
+    pub ctx: PageContext,
+    pub campaigns: Vec<Campaign>,
+    /// Suggested deadline for a new campaign, two weeks out.
+    pub default_deadline: String,
+    pub errors: Vec<String>,
+}
+
+/// One campaign: its progress and every item's decision.
+#[derive(Template)]
+#[template(path = "admin/recertification.html")]
+pub struct RecertificationCampaignTemplate {
+    pub ctx: PageContext,
+    pub campaign: Campaign,
+    pub items: Vec<Item>,
+}
+
+/// The current user's pending recertification items.
+#[derive(Template)]
+#[template(path = "governance/recertification_tasks.html")]
+pub struct RecertificationTasksTemplate {
+    pub ctx: PageContext,
+    pub items: Vec<Item>,
 }
@@ -1,6 +1,7 @@
 use askama::Template;
 
 use crate::models::coa::CoaDetail;
+use crate::models::revision::RevisionDiff;
 use super::PageContext;
 
 #[derive(Template)]
@@ -12,4 +13,6 @@ pub struct CoaFormTemplate {
     pub form_title: String,
     pub coa: Option<CoaDetail>,
     pub errors: Vec<String>,
+    /// Earlier versions of the title and description, when editing.
+    pub history: Vec<RevisionDiff>,
 }
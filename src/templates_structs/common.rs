@@ -1,7 +1,33 @@
 use askama::Template;
 use sqlx::FromRow;
 
-use super::PageContext;
+use super::{PageContext, PrintMeta};
+use crate::models::api_usage::ApiUsageSummary;
+use crate::models::branding::Branding;
+use crate::models::changelog::ChangelogEntry;
+use crate::models::email_template::{BuiltIn, EmailTemplate, RenderedEmail};
+use crate::models::export_record::{ExportCheck, ExportRecord};
+use crate::models::glossary::GlossaryTerm;
+use crate::models::minutes::PublishedMinutes;
+use crate::models::data_manager::spreadsheet::{SheetImportReport, SheetKind, SHEET_KINDS};
+use crate::models::opinion::DecisionRegisterEntry;
+use crate::models::outbound_webhook::{self, OutboundWebhook, WebhookDelivery};
+use crate::models::ownership_transfer::{OwnershipTransfer, TransferItem};
+use crate::models::quota::{self, RoleQuota, TorQuotaUsage, UserQuotaUsage};
+use crate::models::rejection_reason::{RejectionReason, RejectionSummary};
+use crate::models::scheduler_job::JobState;
+use crate::models::report_definition::{self, ReportDefinition};
+use crate::models::report_subscription::{self, ReportDelivery, ReportSubscription, ReportTable};
+use crate::models::survey::{HealthScore, SurveyTemplate};
+use crate::models::share_token::{ShareToken, VIEWS};
+use crate::models::signing_key::{self, SigningKey};
+use crate::models::sla_pack::{self, SlaCompliance, SlaPack};
+use crate::models::tor::{CalendarEvent, GovernanceMapEntry};
+use crate::models::trash::TrashEntry;
+use crate::models::webhook::Webhook;
+use crate::models::role::RoleDisplay;
+use crate::warnings::broadcast::{self, Broadcast};
+use crate::warnings::outbox::OutboxEntry;
 
 #[derive(Template)]
 #[template(path = "login.html")]
@@ -9,6 +35,19 @@ pub struct LoginTemplate {
     pub error: Option<String>,
     pub app_name: String,
     pub csrf_token: String,
+    /// Page to return to after login (already validated as a local path).
+    pub next: String,
+    pub branding: Branding,
+    /// Single sign-on button label; `None` when SSO is off.
+    pub sso_label: Option<String>,
+}
+
+/// Shown by the service worker for pages not available offline.
+#[derive(Template)]
+#[template(path = "offline.html")]
+pub struct OfflineTemplate {
+    pub app_name: String,
+    pub branding: Branding,
 }
 
 #[derive(Template)]
@@ -16,6 +55,42 @@ pub struct LoginTemplate {
 pub struct AccountTemplate {
     pub ctx: PageContext,
     pub errors: Vec<String>,
+    pub devices: Vec<crate::models::remember_token::RememberTokenDisplay>,
+    /// Devices and addresses the account was signed in from.
+    pub sign_ins: Vec<crate::models::sign_in_device::SignInDevice>,
+    pub out_of_office: Option<crate::models::delegation::OutOfOffice>,
+    /// Members currently away who delegated to this user.
+    pub acting_for: Vec<crate::models::delegation::OutOfOffice>,
+    /// Candidate delegates (everyone but the user).
+    pub delegates: Vec<crate::models::entity::Entity>,
+    pub email_preferences: Vec<crate::warnings::notifications::email::EmailPreference>,
+    /// Whether the server sends e-mail at all (`email.enabled`).
+    pub email_enabled: bool,
+    pub has_email: bool,
+    pub api_tokens: Vec<crate::models::api_token::ApiToken>,
+    pub token_errors: Vec<String>,
+    /// Raw value of a token just created; shown once.
+    pub new_token: Option<String>,
+    /// Whether single sign-on is configured.
+    pub sso_enabled: bool,
+    pub sso_linked: bool,
+    /// False for accounts created through single sign-on.
+    pub has_password: bool,
+    /// The password policy in a sentence.
+    pub password_rules: String,
+    /// The password expired; the page stays on the Security tab until it is changed.
+    pub password_expired: bool,
+    /// VAPID public key browsers subscribe with; `None` when push is off.
+    pub push_public_key: Option<String>,
+    pub push_devices: Vec<crate::warnings::notifications::push::PushSubscription>,
+    pub today: String,
+    pub max_token_expiry: String,
+}
+
+impl AccountTemplate {
+    pub fn token_scopes(&self) -> &'static [(&'static str, &'static str, &'static str)] {
+        crate::models::api_token::SCOPES
+    }
 }
 
 #[derive(Template)]
@@ -23,6 +98,8 @@ pub struct AccountTemplate {
 pub struct SettingsTemplate {
     pub ctx: PageContext,
     pub settings: Vec<crate::models::setting::SettingDisplay>,
+    pub history: Vec<crate::models::setting::SettingChange>,
+    pub errors: Vec<String>,
 }
 
 #[derive(Template)]
@@ -30,6 +107,458 @@ pub struct SettingsTemplate {
 pub struct DataManagerTemplate {
     pub ctx: PageContext,
     pub entity_types: Vec<String>,
+    /// Fingerprint of the configured export public key, if one is set.
+    pub key_fingerprint: Option<String>,
+}
+
+/// Release notes: recorded upgrades and enabled features.
+#[derive(Template)]
+#[template(path = "admin/changelog.html")]
+pub struct ChangelogTemplate {
+    pub ctx: PageContext,
+    pub entries: Vec<ChangelogEntry>,
+}
+
+/// Questionnaire templates for ToR self-assessments.
+#[derive(Template)]
+#[template(path = "admin/surveys.html")]
+pub struct SurveyTemplatesTemplate {
+    pub ctx: PageContext,
+    pub templates: Vec<SurveyTemplate>,
+    pub errors: Vec<String>,
+}
+
+/// Outgoing emails with their built-in texts and locale overrides.
+#[derive(Template)]
+#[template(path = "admin/email_templates.html")]
+pub struct EmailTemplatesTemplate {
+    pub ctx: PageContext,
+    pub built_ins: &'static [BuiltIn],
+    pub overrides: Vec<EmailTemplate>,
+}
+
+impl EmailTemplatesTemplate {
+    /// Locales overriding an email, e.g. "en, nb-NO".
+    pub fn locales_of(&self, kind: &str) -> Vec<String> {
+        self.overrides.iter()
+            .filter(|t| t.kind == kind)
+            .map(|t| t.locale.clone())
+            .collect()
+    }
+}
+
+/// Editor for one email in one locale, with a preview using sample data.
+#[derive(Template)]
+#[template(path = "admin/email_template.html")]
+pub struct EmailTemplateFormTemplate {
+    pub ctx: PageContext,
+    pub built_in: &'static BuiltIn,
+    pub locale: String,
+    pub subject: String,
+    pub body: String,
+    /// Whether an override is stored for this exact locale.
+    pub is_override: bool,
+    pub overrides: Vec<EmailTemplate>,
+    pub preview: Option<RenderedEmail>,
+    pub errors: Vec<String>,
+}
+
+/// Recently generated exports with their checksums, and a verifier.
+#[derive(Template)]
+#[template(path = "admin/exports.html")]
+pub struct ExportsTemplate {
+    pub ctx: PageContext,
+    pub exports: Vec<ExportRecord>,
+    /// Digest or manifest last submitted for checking.
+    pub checksums: String,
+    pub checks: Option<Vec<ExportCheck>>,
+}
+
+/// Excel templates and upload forms for initial population.
+#[derive(Template)]
+#[template(path = "admin/spreadsheet_import.html")]
+pub struct SpreadsheetImportTemplate {
+    pub ctx: PageContext,
+    /// The last upload and what it was for.
+    pub report: Option<(SheetKind, SheetImportReport)>,
+}
+
+impl SpreadsheetImportTemplate {
+    pub fn kinds(&self) -> &'static [SheetKind] {
+        &SHEET_KINDS
+    }
+}
+
+#[derive(Template)]
+#[template(path = "admin/api_usage.html")]
+pub struct ApiUsageTemplate {
+    pub ctx: PageContext,
+    pub usage: Vec<ApiUsageSummary>,
+    pub hours: i64,
+}
+
+#[derive(Template)]
+#[template(path = "admin/outbox.html")]
+pub struct OutboxTemplate {
+    pub ctx: PageContext,
+    pub entries: Vec<OutboxEntry>,
+    /// (status, row count) across the whole outbox.
+    pub counts: Vec<(String, i64)>,
+    /// Status filter; empty for all.
+    pub status: String,
+}
+
+#[derive(Template)]
+#[template(path = "admin/scheduler.html")]
+pub struct SchedulerTemplate {
+    pub ctx: PageContext,
+    pub jobs: Vec<JobState>,
+    pub tick_secs: u64,
+}
+
+#[derive(Template)]
+#[template(path = "admin/trash.html")]
+pub struct TrashTemplate {
+    pub ctx: PageContext,
+    pub entries: Vec<TrashEntry>,
+    pub retention_days: i64,
+}
+
+#[derive(Template)]
+#[template(path = "admin/broadcasts.html")]
+pub struct BroadcastsTemplate {
+    pub ctx: PageContext,
+    pub broadcasts: Vec<Broadcast>,
+    pub roles: Vec<RoleDisplay>,
+    /// (id, name, label) of active ToRs.
+    pub tors: Vec<(i64, String, String)>,
+    pub errors: Vec<String>,
+}
+
+impl BroadcastsTemplate {
+    pub fn audiences(&self) -> &'static [(&'static str, &'static str)] {
+        broadcast::AUDIENCES
+    }
+
+    pub fn severities(&self) -> &'static [&'static str] {
+        broadcast::SEVERITIES
+    }
+}
+
+#[derive(Template)]
+#[template(path = "admin/quotas.html")]
+pub struct QuotasTemplate {
+    pub ctx: PageContext,
+    /// Global limits as (label, value); 0 = unlimited.
+    pub limits: Vec<(String, i64)>,
+    pub roles: Vec<RoleQuota>,
+    pub users: Vec<UserQuotaUsage>,
+    pub tors: Vec<TorQuotaUsage>,
+    pub errors: Vec<String>,
+}
+
+impl QuotasTemplate {
+    pub fn user_quotas(&self) -> &'static [(&'static str, &'static str)] {
+        quota::USER_QUOTAS
+    }
+}
+
+#[derive(Template)]
+#[template(path = "admin/sla_packs.html")]
+pub struct SlaPacksTemplate {
+    pub ctx: PageContext,
+    pub packs: Vec<SlaPack>,
+    /// Active ToRs as (id, label, assigned pack id).
+    pub assignments: Vec<(i64, String, Option<i64>)>,
+    /// Day of the last compliance run, if any.
+    pub computed_on: Option<String>,
+    pub errors: Vec<String>,
+}
+
+impl SlaPacksTemplate {
+    pub fn metrics(&self) -> &'static [(&'static str, &'static str, &'static str)] {
+        sla_pack::METRICS
+    }
+}
+
+#[derive(Template)]
+#[template(path = "admin/rejection_reasons.html")]
+pub struct RejectionReasonsTemplate {
+    pub ctx: PageContext,
+    pub reasons: Vec<RejectionReason>,
+    pub errors: Vec<String>,
+}
+
+/// The global glossary (`tor_id` None) or a ToR's glossary.
+#[derive(Template)]
+#[template(path = "glossary/list.html")]
+pub struct GlossaryTemplate {
+    pub ctx: PageContext,
+    pub tor_id: Option<i64>,
+    pub tor_label: String,
+    pub terms: Vec<GlossaryTerm>,
+    /// Global terms the ToR uses without redefining them.
+    pub inherited: Vec<GlossaryTerm>,
+    pub errors: Vec<String>,
+}
+
+impl GlossaryTemplate {
+    pub fn base_url(&self) -> String {
+        match self.tor_id {
+            Some(tor_id) => format!("/tor/{tor_id}/glossary"),
+            None => "/glossary".to_string(),
+        }
+    }
+}
+
+#[derive(Template)]
+#[template(path = "admin/default_coas.html")]
+pub struct DefaultCoasTemplate {
+    pub ctx: PageContext,
+    pub coas: Vec<crate::models::coa::defaults::DefaultCoa>,
+    pub errors: Vec<String>,
+}
+
+#[derive(Template)]
+#[template(path = "admin/webhooks.html")]
+pub struct WebhooksTemplate {
+    pub ctx: PageContext,
+    pub webhooks: Vec<Webhook>,
+    /// (id, name, label) of active ToRs a webhook can feed.
+    pub tors: Vec<(i64, String, String)>,
+    pub errors: Vec<String>,
+}
+
+#[derive(Template)]
+#[template(path = "admin/outbound_webhooks.html")]
+pub struct OutboundWebhooksTemplate {
+    pub ctx: PageContext,
+    pub webhooks: Vec<OutboundWebhook>,
+    pub errors: Vec<String>,
+}
+
+impl OutboundWebhooksTemplate {
+    pub fn event_suggestions(&self) -> &'static [(&'static str, &'static str)] {
+        outbound_webhook::EVENT_SUGGESTIONS
+    }
+}
+
+#[derive(Template)]
+#[template(path = "admin/webhook_deliveries.html")]
+pub struct WebhookDeliveriesTemplate {
+    pub ctx: PageContext,
+    pub deliveries: Vec<WebhookDelivery>,
+    /// All outbound webhooks, for the filter.
+    pub webhooks: Vec<OutboundWebhook>,
+    /// Webhook filter; 0 for all.
+    pub webhook_id: i64,
+    /// Status filter; empty for all.
+    pub status: String,
+}
+
+#[derive(Template)]
+#[template(path = "admin/share_tokens.html")]
+pub struct ShareTokensTemplate {
+    pub ctx: PageContext,
+    pub tokens: Vec<ShareToken>,
+    /// (id, name, label) of active ToRs a token can be limited to.
+    pub tors: Vec<(i64, String, String)>,
+    pub errors: Vec<String>,
+    /// Raw token just issued; shown once.
+    pub new_token: Option<String>,
+    pub today: String,
+}
+
+impl ShareTokensTemplate {
+    pub fn views(&self) -> &'static [(&'static str, &'static str)] {
+        VIEWS
+    }
+}
+
+#[derive(Template)]
+#[template(path = "admin/signing_keys.html")]
+pub struct SigningKeysTemplate {
+    pub ctx: PageContext,
+    pub keys: Vec<SigningKey>,
+    pub grace_days: i64,
+    /// Keys in `SESSION_KEY_PREVIOUS` still accepted for session cookies.
+    pub previous_session_keys: usize,
+}
+
+impl SigningKeysTemplate {
+    pub fn purposes(&self) -> &'static [(&'static str, &'static str, &'static str)] {
+        signing_key::PURPOSES
+    }
+
+    /// Keys of one purpose that still verify, newest first.
+    pub fn live_keys(&self, purpose: &str) -> Vec<&SigningKey> {
+        self.keys.iter().filter(|k| k.purpose == purpose && k.status != "retired").collect()
+    }
+}
+
+/// Standalone read-only view served to share token holders (no session).
+#[derive(Template)]
+#[template(path = "share/embed.html")]
+pub struct ShareEmbedTemplate {
+    pub branding: Branding,
+    pub title: String,
+    pub view: String,
+    pub tor_label: Option<String>,
+    pub tors: Vec<(i64, String, String)>,
+    pub dependencies: Vec<GovernanceMapEntry>,
+    pub events: Vec<CalendarEvent>,
+    pub decisions: Vec<DecisionRegisterEntry>,
+    pub minutes: Vec<PublishedMinutes>,
+}
+
+impl ShareEmbedTemplate {
+    /// Dependencies leaving the given ToR.
+    pub fn outgoing(&self, tor_id: i64) -> Vec<&GovernanceMapEntry> {
+        self.dependencies.iter().filter(|d| d.source_tor_id == tor_id).collect()
+    }
+}
+
+/// A user's report subscriptions and the reports delivered to them.
+#[derive(Template)]
+#[template(path = "reports/subscriptions.html")]
+pub struct ReportSubscriptionsTemplate {
+    pub ctx: PageContext,
+    pub subscriptions: Vec<ReportSubscription>,
+    pub deliveries: Vec<ReportDelivery>,
+    pub errors: Vec<String>,
+    /// Governance health of the user's ToRs, as (ToR id, label, score).
+    pub health: Vec<(i64, String, Option<HealthScore>)>,
+    /// SLA compliance of the user's ToRs that have a pack, per metric.
+    pub sla: Vec<SlaCompliance>,
+    /// Why the user's ToRs rejected proposals.
+    pub rejections: Vec<RejectionSummary>,
+}
+
+impl ReportSubscriptionsTemplate {
+    pub fn reports(&self) -> &'static [(&'static str, &'static str)] {
+        report_subscription::REPORTS
+    }
+
+    pub fn formats(&self) -> &'static [(&'static str, &'static str)] {
+        report_subscription::FORMATS
+    }
+
+    pub fn frequencies(&self) -> &'static [(&'static str, &'static str)] {
+        report_subscription::FREQUENCIES
+    }
+}
+
+/// Ownership transfers offered to and by the signed-in user.
+#[derive(Template)]
+#[template(path = "transfers/list.html")]
+pub struct OwnershipTransfersTemplate {
+    pub ctx: PageContext,
+    pub user_id: i64,
+    pub incoming: Vec<OwnershipTransfer>,
+    pub outgoing: Vec<OwnershipTransfer>,
+}
+
+/// Offer a proposal, ToR chair or document to another user.
+#[derive(Template)]
+#[template(path = "transfers/form.html")]
+pub struct TransferFormTemplate {
+    pub ctx: PageContext,
+    pub user_id: i64,
+    pub item: TransferItem,
+    /// Transfer of the item already waiting for acceptance.
+    pub pending: Option<OwnershipTransfer>,
+    pub users: Vec<UserOption>,
+    pub to_user_id: i64,
+    pub note: String,
+    pub errors: Vec<String>,
+}
+
+/// Admin overview of every active report subscription.
+#[derive(Template)]
+#[template(path = "admin/report_subscriptions.html")]
+pub struct ReportSubscriptionsAdminTemplate {
+    pub ctx: PageContext,
+    pub subscriptions: Vec<ReportSubscription>,
+}
+
+/// Scheduled reports sent to distribution groups, with the create/edit form.
+#[derive(Template)]
+#[template(path = "admin/report_definitions.html")]
+pub struct ReportDefinitionsTemplate {
+    pub ctx: PageContext,
+    pub definitions: Vec<ReportDefinition>,
+    /// (key, label) of the distribution groups a report can go to.
+    pub groups: Vec<(String, String)>,
+    /// (id, label) of the ToRs a report can be limited to.
+    pub tors: Vec<(i64, String)>,
+    /// The definition being edited; None for the new-definition form.
+    pub editing: Option<ReportDefinition>,
+    pub errors: Vec<String>,
+}
+
+impl ReportDefinitionsTemplate {
+    pub fn group_label(&self, key: &str) -> String {
+        self.groups.iter()
+            .find(|(k, _)| k == key)
+            .map_or_else(|| "(deleted group)".to_string(), |(_, label)| label.clone())
+    }
+
+    /// A form field's value: the edited definition's, or the default.
+    pub fn field(&self, name: &str) -> String {
+        match (&self.editing, name) {
+            (Some(d), "label") => d.label.clone(),
+            (Some(d), "report") => d.report.clone(),
+            (Some(d), "parameter") => d.parameter.to_string(),
+            (Some(d), "tor_id") => d.tor_id.map(|id| id.to_string()).unwrap_or_default(),
+            (Some(d), "schedule") => d.schedule.clone(),
+            (Some(d), "format") => d.format.clone(),
+            (Some(d), "group_key") => d.group_key.clone(),
+            (Some(d), "delivery") => d.delivery.clone(),
+            (None, "report") => report_definition::REPORTS[0].key.to_string(),
+            (None, "parameter") => report_definition::REPORTS[0].default.to_string(),
+            (None, "schedule") => "0 7 * * 1".to_string(),
+            (None, "format") => "csv".to_string(),
+            (None, "delivery") => "inbox".to_string(),
+            _ => String::new(),
+        }
+    }
+
+    pub fn is_selected(&self, name: &str, value: &str) -> bool {
+        self.field(name) == value
+    }
+}
+
+/// Printable HTML delivery of a subscribed report.
+#[derive(Template)]
+#[template(path = "print/report.html")]
+pub struct ReportPrintTemplate {
+    pub meta: PrintMeta,
+    pub report: ReportTable,
+}
+
+impl ReportPrintTemplate {
+    /// Reports are informational and carry no signature lines.
+    pub fn signatories(&self) -> Vec<(&'static str, String)> {
+        Vec::new()
+    }
+}
+
+#[derive(Template)]
+#[template(path = "admin/branding.html")]
+pub struct BrandingTemplate {
+    pub ctx: PageContext,
+    /// (key, label, values being edited) per surface.
+    pub surfaces: Vec<(&'static str, &'static str, Branding)>,
+    /// Whether the values shown are an unpublished draft.
+    pub has_draft: bool,
+    pub errors: Vec<String>,
+}
+
+/// Sample application page rendered with draft branding.
+#[derive(Template)]
+#[template(path = "admin/branding_preview.html")]
+pub struct BrandingPreviewTemplate {
+    pub ctx: PageContext,
 }
 
 /// UserOption for the "add member" dropdown.
@@ -19,4 +19,10 @@ pub struct DashboardTemplate {
     pub user_tors: Vec<crate::models::dashboard::UserTorMembership>,
     pub upcoming_meetings: Vec<crate::models::dashboard::UpcomingMeeting>,
     pub pending_items: crate::models::dashboard::PendingItems,
+    /// Headline counters; kept current over the WebSocket.
+    pub counts: crate::models::dashboard::DashboardCounts,
+    /// Release notes the admin has not dismissed; empty for other users.
+    pub changes: Vec<crate::models::changelog::ChangelogEntry>,
+    /// Forms the user left with unsaved edits, e.g. when their session expired.
+    pub drafts: Vec<crate::models::form_draft::FormDraft>,
 }
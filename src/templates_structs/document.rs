@@ -26,4 +26,6 @@ pub struct DocumentFormTemplate {
 pub struct DocumentDetailTemplate {
     pub ctx: PageContext,
     pub document: crate::models::document::DocumentDetail,
+    /// The user may offer the document to a new owner.
+    pub can_transfer: bool,
 }
@@ -1,11 +1,13 @@
 use askama::Template;
 
+use crate::models::branding::Branding;
 use crate::models::meeting::{MeetingListItem, MeetingDetail, MeetingAgendaPoint};
-use crate::models::minutes::Minutes;
+use crate::models::minutes::{Minutes, MinutesSection};
+use crate::models::revision::RevisionDiff;
 use crate::models::protocol::ProtocolStep;
 use crate::models::workflow::AvailableTransition;
 use crate::auth::session::Permissions;
-use super::PageContext;
+use super::{PageContext, PrintMeta};
 
 #[derive(Template)]
 #[template(path = "meetings/list.html")]
@@ -31,9 +33,13 @@ pub struct MeetingDetailTemplate {
     pub meeting: MeetingDetail,
     pub agenda_points: Vec<MeetingAgendaPoint>,
     pub unassigned_points: Vec<MeetingAgendaPoint>,
+    pub undecided_points: Vec<MeetingAgendaPoint>,
+    pub carry_over_targets: Vec<MeetingListItem>,
     pub protocol_steps: Vec<ProtocolStep>,
     pub transitions: Vec<AvailableTransition>,
     pub minutes: Option<Minutes>,
+    /// Documents attached to the proposals on the agenda.
+    pub pack: Vec<crate::models::meeting::MeetingPackDocument>,
     pub tor_id: i64,
     pub tor_capabilities: Permissions,
 }
@@ -44,4 +50,184 @@ pub struct MinutesViewTemplate {
     pub ctx: PageContext,
     pub minutes: Minutes,
     pub sections: Vec<crate::models::minutes::MinutesSection>,
+    pub revisions: Vec<crate::models::revision::Revision>,
+    /// Sections edited since the minutes were circulated.
+    pub changed_sections: usize,
+    /// The ToR's glossary, for marking defined terms.
+    pub glossary: Vec<crate::models::glossary::GlossaryTerm>,
+}
+
+impl MinutesViewTemplate {
+    /// Escaped HTML with defined terms marked.
+    pub fn glossed(&self, text: &str) -> String {
+        crate::models::glossary::annotate(text, &self.glossary)
+    }
+
+    /// A section's earlier revisions with what each edit changed.
+    pub fn revisions_for(&self, section: &MinutesSection) -> Vec<RevisionDiff> {
+        let revisions = self.revisions.iter().filter(|r| r.target_id == section.id).cloned().collect();
+        crate::models::revision::diffs(revisions, &[("content", &section.content)])
+    }
+}
+
+/// Standalone print-friendly export of approved minutes (no session chrome).
+#[derive(Template)]
+#[template(path = "minutes/export.html")]
+pub struct MinutesExportTemplate {
+    pub branding: Branding,
+    /// "auto" follows the reader's colour scheme; "light" or "dark" forces one.
+    pub theme: String,
+    pub title: String,
+    pub meeting_name: String,
+    pub generated_date: String,
+    pub sections: Vec<MinutesSection>,
+}
+
+impl MinutesExportTemplate {
+    pub fn icon(&self, section_type: &str) -> &'static str {
+        match section_type {
+            "attendance" => "👥",
+            "protocol" => "📋",
+            "agenda_items" => "📝",
+            "decisions" => "✅",
+            "action_items" => "🎯",
+            _ => "📄",
+        }
+    }
+}
+
+/// `?print=1` variant of the meeting detail page: the agenda for circulation.
+#[derive(Template)]
+#[template(path = "print/agenda.html")]
+pub struct AgendaPrintTemplate {
+    pub meta: PrintMeta,
+    pub meeting: MeetingDetail,
+    pub agenda_points: Vec<MeetingAgendaPoint>,
+}
+
+impl AgendaPrintTemplate {
+    /// Signature lines as (role, name); the name is blank when not recorded.
+    pub fn signatories(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("Chair", self.meeting.chair_user_id.clone()),
+            ("Secretary", self.meeting.secretary_user_id.clone()),
+        ]
+    }
+}
+
+/// `?print=1` variant of the minutes page, with approval signatures.
+#[derive(Template)]
+#[template(path = "print/minutes.html")]
+pub struct MinutesPrintTemplate {
+    pub meta: PrintMeta,
+    pub minutes: Minutes,
+    pub meeting: Option<MeetingDetail>,
+    pub sections: Vec<MinutesSection>,
+}
+
+impl MinutesPrintTemplate {
+    /// Signature lines as (role, name); the name is blank when not recorded.
+    pub fn signatories(&self) -> Vec<(&'static str, String)> {
+        let (chair, secretary) = self.meeting.as_ref()
+            .map(|m| (m.chair_user_id.clone(), m.secretary_user_id.clone()))
+            .unwrap_or_default();
+        vec![("Chair", chair), ("Secretary", secretary)]
+    }
+}
+
+/// `word/document.xml` of the minutes Word export (see `export::docx`).
+#[derive(Template)]
+#[template(path = "docx/minutes.xml")]
+pub struct MinutesDocxTemplate {
+    pub minutes: Minutes,
+    pub meeting: Option<MeetingDetail>,
+    pub sections: Vec<MinutesSection>,
+}
+
+impl MinutesDocxTemplate {
+    /// Signature lines as (role, name); the name is blank when not recorded.
+    pub fn signatories(&self) -> Vec<(&'static str, String)> {
+        let (chair, secretary) = self.meeting.as_ref()
+            .map(|m| (m.chair_user_id.clone(), m.secretary_user_id.clone()))
+            .unwrap_or_default();
+        vec![("Chair", chair), ("Secretary", secretary)]
+    }
+}
+
+/// `word/document.xml` of the agenda pack Word export (see `export::docx`).
+#[derive(Template)]
+#[template(path = "docx/agenda.xml")]
+pub struct AgendaDocxTemplate {
+    pub meeting: MeetingDetail,
+    pub items: Vec<crate::export::docx::AgendaPackItem>,
+}
+
+/// Meeting details shown under an agenda title, as (label, value); empty ones are left out.
+fn meeting_details(m: &MeetingDetail) -> Vec<(&'static str, &str)> {
+    [
+        ("Date", m.meeting_date.as_str()),
+        ("Location", m.location.as_str()),
+        ("Meeting number", m.meeting_number.as_str()),
+        ("Classification", m.classification.as_str()),
+        ("VTC", m.vtc_details.as_str()),
+        ("Chair", m.chair_user_id.as_str()),
+        ("Secretary", m.secretary_user_id.as_str()),
+    ]
+    .into_iter()
+    .filter(|(_, v)| !v.is_empty())
+    .collect()
+}
+
+impl AgendaDocxTemplate {
+    pub fn details(&self) -> Vec<(&'static str, &str)> {
+        meeting_details(&self.meeting)
+    }
+}
+
+/// The offline meeting pack viewer (see `export::offline`). Items are
+/// rendered fragments; the manifest is pre-serialised JSON.
+#[derive(Template)]
+#[template(path = "offline/pack.html")]
+pub struct OfflinePackTemplate {
+    pub branding: Branding,
+    pub meeting: MeetingDetail,
+    pub items: Vec<crate::export::offline::OfflineItem>,
+    pub manifest_json: String,
+    pub generated_at: String,
+}
+
+impl OfflinePackTemplate {
+    /// The print stylesheet, inlined so the file needs nothing else.
+    pub fn stylesheet(&self) -> &'static str {
+        include_str!("../../static/css/print.css")
+    }
+}
+
+/// Agenda item of the offline viewer.
+#[derive(Template)]
+#[template(path = "offline/agenda.html")]
+pub struct OfflineAgendaTemplate {
+    pub meeting: MeetingDetail,
+    pub items: Vec<crate::export::docx::AgendaPackItem>,
+}
+
+impl OfflineAgendaTemplate {
+    pub fn details(&self) -> Vec<(&'static str, &str)> {
+        meeting_details(&self.meeting)
+    }
+}
+
+/// Agenda point item of the offline viewer, linking to its documents.
+#[derive(Template)]
+#[template(path = "offline/point.html")]
+pub struct OfflinePointTemplate {
+    pub item: crate::export::docx::AgendaPackItem,
+    pub documents: Vec<crate::models::meeting::MeetingPackDocument>,
+}
+
+/// Document item of the offline viewer.
+#[derive(Template)]
+#[template(path = "offline/document.html")]
+pub struct OfflineDocumentTemplate {
+    pub document: crate::export::offline::OfflineDocument,
 }
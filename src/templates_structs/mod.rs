@@ -24,12 +24,35 @@ pub struct PageContext {
     pub warning_count: i64,
     pub tor_context: Option<TorContext>,
     pub theme: String,
+    pub branding: crate::models::branding::Branding,
+    /// Accessibility debug mode: pages carry landmark and contrast diagnostics.
+    pub a11y_debug: bool,
+}
+
+/// Header and footer data of the `?print=1` layouts: export branding, the
+/// application name and when the document was printed.
+pub struct PrintMeta {
+    pub branding: crate::models::branding::Branding,
+    pub app_name: String,
+    pub printed_at: String,
+}
+
+impl PrintMeta {
+    pub async fn build(pool: &PgPool) -> Self {
+        PrintMeta {
+            branding: crate::models::branding::load(pool, "export").await,
+            app_name: setting::get_value(pool, "app.name", "Ahlt").await,
+            printed_at: chrono::Local::now().format("%Y-%m-%d %H:%M").to_string(),
+        }
+    }
 }
 
 pub struct TorContext {
     pub tor_id: i64,
     pub tor_name: String,
     pub active_section: String,
+    /// Item whose access log the Access log tab opens; `None` hides the tab.
+    pub access_log_item: Option<i64>,
 }
 
 impl PageContext {
@@ -47,7 +70,9 @@ impl PageContext {
         let theme = crate::models::user::get_user_theme(pool, user_id).await
             .unwrap_or_else(|_| "auto".to_string());
         let warning_count = crate::warnings::queries::count_unread(pool, user_id).await;
-        Ok(Self { username, avatar_initial, permissions, flash, nav_modules, sidebar_items, app_name, csrf_token, warning_count, tor_context: None, theme })
+        let branding = crate::models::branding::load_for_page(pool, "app").await;
+        let a11y_debug = setting::get_bool(pool, "a11y.debug").await;
+        Ok(Self { username, avatar_initial, permissions, flash, nav_modules, sidebar_items, app_name, csrf_token, warning_count, tor_context: None, theme, branding, a11y_debug })
     }
 
     /// Attach ToR context for pages nested under /tor/{id}/...
@@ -56,9 +81,18 @@ impl PageContext {
             tor_id,
             tor_name: name.to_string(),
             active_section: section.to_string(),
+            access_log_item: None,
         });
         self
     }
+
+    /// Show the Access log tab for an item of the ToR (see `models::access_log`).
+    pub fn with_access_log(mut self, entity_id: i64) -> Self {
+        if let Some(tc) = self.tor_context.as_mut() {
+            tc.access_log_item = Some(entity_id);
+        }
+        self
+    }
 }
 
 mod common;
@@ -80,18 +114,23 @@ mod document;
 mod api;
 
 // Re-export all types for seamless imports
-pub use self::common::{LoginTemplate, AccountTemplate, SettingsTemplate, DataManagerTemplate, UserOption};
-pub use self::user::{UserListTemplate, UserFormTemplate};
+pub use self::common::{LoginTemplate, OfflineTemplate, AccountTemplate, SettingsTemplate, DataManagerTemplate, SpreadsheetImportTemplate, ChangelogTemplate, SurveyTemplatesTemplate, EmailTemplatesTemplate, EmailTemplateFormTemplate, ExportsTemplate, ApiUsageTemplate, OutboxTemplate, SchedulerTemplate, TrashTemplate, BroadcastsTemplate, QuotasTemplate, SlaPacksTemplate, RejectionReasonsTemplate, GlossaryTemplate, DefaultCoasTemplate, WebhooksTemplate, OutboundWebhooksTemplate, WebhookDeliveriesTemplate, ShareTokensTemplate, SigningKeysTemplate, ShareEmbedTemplate, ReportSubscriptionsTemplate, ReportSubscriptionsAdminTemplate, ReportDefinitionsTemplate, OwnershipTransfersTemplate, TransferFormTemplate, ReportPrintTemplate, BrandingTemplate, BrandingPreviewTemplate, UserOption};
+pub use self::user::{UserListTemplate, UserFormTemplate, UserReassignTemplate};
 pub use self::role::{
     RoleAssignmentTemplate, MatrixCell, PermissionRow, PageGroup, RoleColumn, MenuBuilderTemplate,
     PermissionGroup, RoleBuilderTemplate, PreviewRequest, PreviewResponse, RoleBuilderForm,
 };
 pub use self::dashboard::DashboardTemplate;
-pub use self::audit::AuditListTemplate;
+pub use self::audit::{
+    AuditListTemplate, AuditVerifyTemplate, AccessReviewsTemplate, AccessReviewTemplate,
+    RecertificationCampaignsTemplate, RecertificationCampaignTemplate, RecertificationTasksTemplate,
+};
 pub use self::ontology::{OntologyConceptsTemplate, OntologyGraphTemplate, OntologyDataTemplate, OntologyDetailTemplate};
 pub use self::tor::{
-    TorListTemplate, TorFormTemplate, TorDetailTemplate, GovernanceMapTemplate,
-    TorOutlookTemplate, PresentationTemplatesTemplate,
+    TorListTemplate, TorFormTemplate, TorDetailTemplate, GovernanceMapTemplate, TorImpactTemplate,
+    TorOutlookTemplate, PresentationTemplatesTemplate, TorSettingsTemplate, TorAvailabilityTemplate, TorAttendanceTemplate,
+    DistributionGroupsTemplate, DistributionGroupTemplate, TorNewTermTemplate, NewTermRow,
+    TorAssessmentsTemplate, TorAssessmentTemplate, TorAccessLogTemplate,
 };
 pub use self::workflow::{
     WorkflowTemplate, WorkflowIndexTemplate, WorkflowBuilderListTemplate,
@@ -101,12 +140,17 @@ pub use self::suggestion::SuggestionFormTemplate;
 pub use self::proposal::{ProposalFormTemplate, ProposalDetailTemplate};
 pub use self::agenda::{AgendaPointFormTemplate, AgendaPointDetailTemplate};
 pub use self::coa::CoaFormTemplate;
-pub use self::opinion::{OpinionFormTemplate, DecisionFormTemplate};
+pub use self::opinion::{
+    OpinionFormTemplate, DecisionFormTemplate, DecisionRegisterTemplate, DecisionRegisterPrintTemplate,
+    BallotTemplate, ActionItemsTemplate, ActionItemFormTemplate, MyActionItemsTemplate,
+};
 pub use self::meeting::{
     MeetingsListTemplate, TorMeetingsListTemplate, MeetingDetailTemplate, MinutesViewTemplate,
+    MinutesExportTemplate, AgendaPrintTemplate, MinutesPrintTemplate, MinutesDocxTemplate, AgendaDocxTemplate,
+    OfflinePackTemplate, OfflineAgendaTemplate, OfflinePointTemplate, OfflineDocumentTemplate,
 };
-pub use self::warning::{WarningListTemplate, WarningDetailTemplate};
+pub use self::warning::{WarningListTemplate, WarningDetailTemplate, AssigneeGroup, NotificationsTemplate};
 pub use self::document::{DocumentListTemplate, DocumentFormTemplate, DocumentDetailTemplate};
 pub use self::api::{
-    PaginatedResponse, ApiUserResponse, ApiUserRequest, ApiEntityProperty, ApiEntityResponse, ApiEntityRequest, ApiErrorResponse,
+    PaginatedResponse, ApiUserResponse, ApiUserRequest, ApiEntityProperty, ApiEntityResponse, ApiEntityRequest, ApiErrorResponse, ApiTorRequest, ApiTorMemberRequest,
 };
@@ -23,5 +23,194 @@ pub struct DecisionFormTemplate {
     pub agenda_point: crate::models::agenda_point::AgendaPointDetail,
     pub coas: Vec<crate::models::coa::CoaDetail>,
     pub opinions: Vec<crate::models::opinion::OpinionSummary>,
+    /// Version stamp of the opinions shown (see `opinion::version_stamp`).
+    pub opinion_versions: String,
+    /// Member reactions on the opinions.
+    pub sentiment: crate::models::opinion::Sentiment,
+    /// The absent member the current user is deciding for, if any.
+    pub on_behalf_of: Option<crate::models::delegation::OutOfOffice>,
+    /// Who can own follow-up actions, as (user id, label).
+    pub owners: Vec<(i64, String)>,
+    pub follow_up_actions: String,
+    pub follow_up_owner_id: String,
+    pub follow_up_due: String,
     pub errors: Vec<String>,
 }
+
+impl DecisionFormTemplate {
+    pub fn reactions(&self) -> &'static [(&'static str, &'static str)] {
+        crate::models::opinion::REACTIONS
+    }
+}
+
+#[derive(Template)]
+#[template(path = "opinion/decision_register.html")]
+pub struct DecisionRegisterTemplate {
+    pub ctx: PageContext,
+    pub tor_id: i64,
+    pub tor_name: String,
+    pub decisions: Vec<crate::models::opinion::DecisionRegisterEntry>,
+    /// The ToR publishes `/feeds/tor/{id}/…` without login.
+    pub public_feeds: bool,
+}
+
+/// `?print=1` variant of the decision register.
+#[derive(Template)]
+#[template(path = "print/decision_register.html")]
+pub struct DecisionRegisterPrintTemplate {
+    pub meta: super::PrintMeta,
+    pub tor_id: i64,
+    pub tor_label: String,
+    pub decisions: Vec<crate::models::opinion::DecisionRegisterEntry>,
+}
+
+impl DecisionRegisterPrintTemplate {
+    /// Signature lines as (role, name): the secretary certifies the register.
+    pub fn signatories(&self) -> Vec<(&'static str, String)> {
+        vec![("Certified by the Secretary", String::new())]
+    }
+}
+
+#[derive(Template)]
+#[template(path = "agenda/ballot.html")]
+pub struct BallotTemplate {
+    pub ctx: PageContext,
+    pub tor_id: i64,
+    pub agenda_point: crate::models::agenda_point::AgendaPointDetail,
+    pub coas: Vec<crate::models::coa::CoaDetail>,
+    /// The open ballot, if any.
+    pub current: Option<crate::models::ballot::Ballot>,
+    /// Earlier ballots, newest first.
+    pub closed: Vec<crate::models::ballot::Ballot>,
+    pub votes_cast: usize,
+    /// The current user's stored choice on the open ballot.
+    pub my_choice: Option<String>,
+    /// Members the current user may vote for as proxy (user id, name).
+    pub proxy_for: Vec<(i64, String)>,
+    pub user_id: i64,
+    /// Whether the current user may open and close ballots.
+    pub can_manage: bool,
+    pub default_quorum_pct: i64,
+    pub errors: Vec<String>,
+}
+
+impl BallotTemplate {
+    pub fn ballot_types(&self) -> &'static [(&'static str, &'static str, &'static str)] {
+        crate::models::ballot::BALLOT_TYPES
+    }
+
+    pub fn weighted_points(&self) -> i64 {
+        crate::models::ballot::WEIGHTED_POINTS
+    }
+
+    pub fn coa_title(&self, coa_id: &i64) -> String {
+        self.coas.iter().find(|c| c.id == *coa_id).map(|c| c.title.clone()).unwrap_or_default()
+    }
+
+    /// Whether the stored choice is this yes/no option or an abstention.
+    pub fn chose(&self, choice: &str) -> bool {
+        self.my_choice.as_deref() == Some(choice)
+    }
+
+    /// The rank the current user gave a course of action, or "".
+    pub fn my_rank(&self, coa_id: &i64) -> String {
+        self.my_choice.as_deref().unwrap_or("")
+            .split(',')
+            .position(|id| id == coa_id.to_string())
+            .map(|i| (i + 1).to_string())
+            .unwrap_or_default()
+    }
+
+    /// The points the current user gave a course of action, or "".
+    pub fn my_points(&self, coa_id: &i64) -> String {
+        self.my_choice.as_deref().unwrap_or("")
+            .split(',')
+            .filter_map(|pair| pair.split_once(':'))
+            .find(|(id, _)| *id == coa_id.to_string())
+            .map(|(_, p)| p.to_string())
+            .unwrap_or_default()
+    }
+}
+
+/// Form values are keyed by field name so a rejected submission re-renders
+/// with what was typed.
+fn form_value(values: &std::collections::HashMap<String, String>, key: &str) -> String {
+    values.get(key).cloned().unwrap_or_default()
+}
+
+#[derive(Template)]
+#[template(path = "actions/list.html")]
+pub struct ActionItemsTemplate {
+    pub ctx: PageContext,
+    pub tor_id: i64,
+    pub tor_name: String,
+    pub items: Vec<crate::models::action_item::ActionItem>,
+    /// Position holders as (user id, label).
+    pub owners: Vec<(i64, String)>,
+    /// The ToR's agenda points as (id, title).
+    pub agenda_points: Vec<(i64, String)>,
+    pub values: std::collections::HashMap<String, String>,
+    pub errors: Vec<String>,
+    pub today: String,
+    pub user_id: i64,
+    /// Whether the current user may add, edit and delete items.
+    pub can_manage: bool,
+}
+
+impl ActionItemsTemplate {
+    pub fn value(&self, key: &str) -> String {
+        form_value(&self.values, key)
+    }
+
+    pub fn statuses(&self) -> &'static [(&'static str, &'static str)] {
+        crate::models::action_item::STATUSES
+    }
+
+    pub fn open_count(&self) -> usize {
+        self.items.iter().filter(|i| i.is_open()).count()
+    }
+}
+
+#[derive(Template)]
+#[template(path = "actions/form.html")]
+pub struct ActionItemFormTemplate {
+    pub ctx: PageContext,
+    pub item: crate::models::action_item::ActionItem,
+    pub owners: Vec<(i64, String)>,
+    pub values: std::collections::HashMap<String, String>,
+    pub errors: Vec<String>,
+}
+
+impl ActionItemFormTemplate {
+    pub fn value(&self, key: &str) -> String {
+        form_value(&self.values, key)
+    }
+
+    pub fn statuses(&self) -> &'static [(&'static str, &'static str)] {
+        crate::models::action_item::STATUSES
+    }
+
+    /// The owner list, keeping the current owner if they have left the ToR.
+    pub fn owner_options(&self) -> Vec<(i64, String)> {
+        let mut owners = self.owners.clone();
+        if !owners.iter().any(|(id, _)| *id == self.item.owner_id) {
+            owners.push((self.item.owner_id, self.item.owner_name.clone()));
+        }
+        owners
+    }
+}
+
+/// "My action items": the current user's items across all ToRs.
+#[derive(Template)]
+#[template(path = "actions/mine.html")]
+pub struct MyActionItemsTemplate {
+    pub ctx: PageContext,
+    pub items: Vec<crate::models::action_item::ActionItem>,
+    pub today: String,
+}
+
+impl MyActionItemsTemplate {
+    pub fn statuses(&self) -> &'static [(&'static str, &'static str)] {
+        crate::models::action_item::STATUSES
+    }
+}
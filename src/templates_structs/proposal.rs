@@ -1,6 +1,13 @@
+use std::collections::HashMap;
+
 use askama::Template;
 
-use crate::models::proposal::{ProposalDetail};
+use crate::models::attachment::Attachment;
+use crate::models::document::DocumentListItem;
+use crate::models::revision::RevisionDiff;
+use crate::models::proposal::{
+    ProposalAttachment, ProposalDetail, ProposalForm, ProposalIntake, ProposalTemplate, RelatedProposal, SimilarProposal,
+};
 use super::PageContext;
 
 #[derive(Template)]
@@ -12,7 +19,52 @@ pub struct ProposalFormTemplate {
     pub form_action: String,
     pub form_title: String,
     pub proposal: Option<ProposalDetail>,
+    /// Submitted values, shown again when the form is re-rendered.
+    pub draft: Option<ProposalForm>,
     pub errors: Vec<String>,
+    /// Likely duplicates of the submitted proposal, awaiting confirmation.
+    pub similar: Vec<SimilarProposal>,
+    /// The ToR's extra sections and guidance.
+    pub intake: ProposalIntake,
+    /// Saved section answers, keyed by section key.
+    pub answers: HashMap<String, String>,
+    /// The ToR's proposal templates, offered on new proposals.
+    pub templates: Vec<ProposalTemplate>,
+    /// Key of the template the new proposal starts from, or "".
+    pub template_key: String,
+}
+
+impl ProposalFormTemplate {
+    /// Value for a form field: the submitted draft, else the saved proposal.
+    pub fn value(&self, field: &str) -> &str {
+        if let Some(d) = &self.draft {
+            return match field {
+                "title" => &d.title,
+                "description" => &d.description,
+                _ => &d.rationale,
+            };
+        }
+        match (&self.proposal, field) {
+            (Some(p), "title") => &p.title,
+            (Some(p), "description") => &p.description,
+            (Some(p), _) => &p.rationale,
+            (None, _) => "",
+        }
+    }
+
+    /// The template the new proposal starts from.
+    pub fn template(&self) -> Option<&ProposalTemplate> {
+        self.templates.iter().find(|t| t.key == self.template_key)
+    }
+
+    /// Answer for an intake section: the submitted draft, else the saved one.
+    pub fn section_value(&self, key: &str) -> &str {
+        let saved = match &self.draft {
+            Some(d) => d.fields.get(&format!("section_{key}")),
+            None => self.answers.get(key),
+        };
+        saved.map(|v| v.as_str()).unwrap_or("")
+    }
 }
 
 #[derive(Template)]
@@ -21,4 +73,44 @@ pub struct ProposalDetailTemplate {
     pub ctx: PageContext,
     pub tor_id: i64,
     pub proposal: ProposalDetail,
+    pub related: Vec<RelatedProposal>,
+    pub intake: ProposalIntake,
+    pub answers: HashMap<String, String>,
+    pub attachments: Vec<ProposalAttachment>,
+    /// ToR documents that can still be attached.
+    pub documents: Vec<DocumentListItem>,
+    /// Files uploaded to the proposal.
+    pub files: Vec<Attachment>,
+    /// Upload size limit, in megabytes.
+    pub max_upload_mb: i64,
+    /// What blocks submitting, for draft and rejected proposals.
+    pub missing: Vec<String>,
+    /// The user follows this proposal's status changes.
+    pub watching: bool,
+    /// The user may offer the proposal to a new submitter.
+    pub can_transfer: bool,
+    /// The ToR's glossary, for marking defined terms.
+    pub glossary: Vec<crate::models::glossary::GlossaryTerm>,
+    /// Earlier versions of the title, description and rationale.
+    pub history: Vec<RevisionDiff>,
+}
+
+impl ProposalDetailTemplate {
+    /// Escaped HTML with defined terms marked.
+    pub fn glossed(&self, text: &str) -> String {
+        crate::models::glossary::annotate(text, &self.glossary)
+    }
+
+    pub fn answer(&self, key: &str) -> &str {
+        self.answers.get(key).map(|v| v.as_str()).unwrap_or("")
+    }
+
+    pub fn editable(&self) -> bool {
+        self.proposal.status == "draft" || self.proposal.status == "rejected"
+    }
+
+    /// File extensions the upload form accepts.
+    pub fn upload_accept(&self) -> String {
+        crate::models::attachment::accept_list()
+    }
 }
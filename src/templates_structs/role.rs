@@ -83,6 +83,8 @@ pub struct RoleBuilderForm {
     pub name: String,
     pub label: String,
     pub description: String,
+    #[serde(default)]
+    pub landing_page: String,
     pub permission_ids: String, // JSON array
     pub csrf_token: String,
     pub role_id: Option<String>,
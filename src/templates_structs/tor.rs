@@ -1,9 +1,15 @@
+use std::collections::HashMap;
+
 use askama::Template;
 
-use crate::models::tor::{TorListItem, TorDetail, TorMember, TorFunctionListItem, TorDependency, GovernanceMapEntry};
+use crate::models::tor::{TorListItem, TorDetail, TorMember, TorFunctionListItem, TorDependency, GovernanceMapEntry, ImpactAnalysis, PositionDelegation};
+use crate::models::tor::standing_items::StandingItem;
 use crate::models::meeting::MeetingListItem;
 use crate::models::protocol::ProtocolStep;
 use crate::models::presentation_template::{PresentationTemplate, TemplateSlide};
+use crate::models::setting::TorSettingDisplay;
+use crate::models::sla_pack::SlaCompliance;
+use crate::models::survey::{self, Assessment, HealthScore, SurveyQuestion, SurveyResults, SurveyTemplate};
 use super::PageContext;
 use super::common::UserOption;
 
@@ -32,11 +38,25 @@ pub struct TorDetailTemplate {
     pub members: Vec<TorMember>,
     pub functions: Vec<TorFunctionListItem>,
     pub protocol_steps: Vec<ProtocolStep>,
+    pub standing_items: Vec<StandingItem>,
     pub available_users: Vec<UserOption>,
     pub upstream_deps: Vec<TorDependency>,
     pub downstream_deps: Vec<TorDependency>,
     pub other_tors: Vec<(i64, String, String)>,
     pub meetings: Vec<MeetingListItem>,
+    pub health: Option<HealthScore>,
+    /// Compliance with the ToR's SLA pack, per metric; empty without a pack.
+    pub sla: Vec<SlaCompliance>,
+    /// The user may hand the chair position to someone else.
+    pub can_transfer: bool,
+    /// Current and upcoming position delegations.
+    pub delegations: Vec<PositionDelegation>,
+    /// Filled positions the user may delegate.
+    pub delegable_positions: Vec<TorMember>,
+    /// Users a position can be delegated to.
+    pub delegate_users: Vec<UserOption>,
+    pub user_id: i64,
+    pub today: chrono::NaiveDate,
 }
 
 #[derive(Template)]
@@ -47,6 +67,22 @@ pub struct GovernanceMapTemplate {
     pub dependencies: Vec<GovernanceMapEntry>,
 }
 
+/// Downstream impact of suspending a ToR or rejecting one of its proposals.
+#[derive(Template)]
+#[template(path = "tor/impact.html")]
+pub struct TorImpactTemplate {
+    pub ctx: PageContext,
+    pub tor_id: i64,
+    pub tor_name: String,
+    /// What is being analysed, e.g. "Suspending Ops Board".
+    pub subject: String,
+    /// (id, title) when analysing a proposal rejection.
+    pub proposal: Option<(i64, String)>,
+    /// Open agenda points spawned from the proposal, as (id, title, status).
+    pub spawned_items: Vec<(i64, String, String)>,
+    pub analysis: ImpactAnalysis,
+}
+
 #[derive(Template)]
 #[template(path = "tor/outlook.html")]
 pub struct TorOutlookTemplate {
@@ -66,3 +102,174 @@ pub struct PresentationTemplatesTemplate {
     pub selected_template: Option<PresentationTemplate>,
     pub slides: Vec<TemplateSlide>,
 }
+
+#[derive(Template)]
+#[template(path = "tor/settings.html")]
+pub struct TorSettingsTemplate {
+    pub ctx: PageContext,
+    pub tor_id: i64,
+    pub tor_label: String,
+    pub settings: Vec<TorSettingDisplay>,
+    /// Problems with submitted override values.
+    pub errors: Vec<String>,
+    pub decision_authority: crate::models::tor::DecisionAuthority,
+    pub positions: Vec<crate::models::tor::TorMember>,
+    pub capabilities: crate::models::tor::CapabilityMatrix,
+    pub intake: crate::models::proposal::ProposalIntake,
+    pub proposal_templates: Vec<crate::models::proposal::ProposalTemplate>,
+}
+
+impl TorSettingsTemplate {
+    pub fn title_placeholders(&self) -> &'static [(&'static str, &'static str)] {
+        crate::models::proposal::TITLE_PLACEHOLDERS
+    }
+
+    pub fn capability_columns(&self) -> &'static [(&'static str, &'static str)] {
+        crate::models::tor::CAPABILITIES
+    }
+
+    pub fn membership_types(&self) -> &'static [(&'static str, &'static str)] {
+        crate::models::tor::MEMBERSHIP_TYPES
+    }
+}
+
+/// Views of one item of a classified ToR.
+#[derive(Template)]
+#[template(path = "tor/access_log.html")]
+pub struct TorAccessLogTemplate {
+    pub ctx: PageContext,
+    pub tor_id: i64,
+    pub classification: String,
+    pub item_label: String,
+    /// `tor`, `meeting` or `proposal`.
+    pub item_type: String,
+    pub item_path: String,
+    pub entries: Vec<crate::models::access_log::AccessLogEntry>,
+    /// Whether views are currently recorded (`audit.read_mode`).
+    pub read_mode: bool,
+}
+
+#[derive(Template)]
+#[template(path = "governance/distribution_groups.html")]
+pub struct DistributionGroupsTemplate {
+    pub ctx: PageContext,
+    /// Each group with its current member count.
+    pub groups: Vec<(crate::models::distribution_group::DistributionGroup, usize)>,
+    pub errors: Vec<String>,
+}
+
+impl DistributionGroupsTemplate {
+    pub fn membership_filters(&self) -> &'static [(&'static str, &'static str)] {
+        crate::models::distribution_group::MEMBERSHIP_FILTERS
+    }
+}
+
+#[derive(Template)]
+#[template(path = "governance/distribution_group.html")]
+pub struct DistributionGroupTemplate {
+    pub ctx: PageContext,
+    pub group: crate::models::distribution_group::DistributionGroup,
+    pub members: Vec<crate::models::distribution_group::GroupMember>,
+}
+
+impl DistributionGroupTemplate {
+    pub fn without_email(&self) -> usize {
+        self.members.iter().filter(|m| m.email.is_empty()).count()
+    }
+}
+
+/// One position in the new-term wizard with the chosen holder.
+#[derive(Debug, Clone)]
+pub struct NewTermRow {
+    pub member: crate::models::tor::TermMember,
+    /// Select value: `keep`, `vacant` or a user id.
+    pub holder: String,
+    pub membership_type: String,
+}
+
+impl NewTermRow {
+    pub fn is_holder(&self, user_id: i64) -> bool {
+        self.member.holder_id == Some(user_id)
+    }
+}
+
+#[derive(Template)]
+#[template(path = "tor/new_term.html")]
+pub struct TorNewTermTemplate {
+    pub ctx: PageContext,
+    pub tor_id: i64,
+    pub tor_label: String,
+    /// `edit` to choose holders, `review` to confirm the handovers.
+    pub step: String,
+    pub term_date: String,
+    pub note: String,
+    pub rows: Vec<NewTermRow>,
+    pub users: Vec<UserOption>,
+    pub handovers: Vec<crate::models::tor::Handover>,
+    pub terms: Vec<crate::models::tor::MembershipTerm>,
+    pub errors: Vec<String>,
+}
+
+#[derive(Template)]
+#[template(path = "tor/availability.html")]
+pub struct TorAvailabilityTemplate {
+    pub ctx: PageContext,
+    pub tor_id: i64,
+    pub tor_label: String,
+    pub entries: Vec<crate::models::tor::availability::Unavailability>,
+    pub user_id: i64,
+    pub is_member: bool,
+}
+
+/// Quorum history and member attendance rates from roll calls.
+#[derive(Template)]
+#[template(path = "tor/attendance.html")]
+pub struct TorAttendanceTemplate {
+    pub ctx: PageContext,
+    pub tor_id: i64,
+    pub tor_label: String,
+    pub stats: crate::models::meeting::attendance::TorAttendance,
+}
+
+#[derive(Template)]
+#[template(path = "tor/assessments.html")]
+pub struct TorAssessmentsTemplate {
+    pub ctx: PageContext,
+    pub tor_id: i64,
+    pub tor_label: String,
+    pub assessments: Vec<Assessment>,
+    pub templates: Vec<SurveyTemplate>,
+    pub health: Option<HealthScore>,
+}
+
+#[derive(Template)]
+#[template(path = "tor/assessment.html")]
+pub struct TorAssessmentTemplate {
+    pub ctx: PageContext,
+    pub tor_id: i64,
+    pub tor_label: String,
+    pub assessment: Assessment,
+    pub results: SurveyResults,
+    /// The viewer's answers by question key; empty before they respond.
+    pub answers: HashMap<String, String>,
+    pub is_member: bool,
+    pub errors: Vec<String>,
+}
+
+impl TorAssessmentTemplate {
+    pub fn questions(&self) -> Vec<SurveyQuestion> {
+        self.assessment.questions_list()
+    }
+
+    pub fn ratings(&self) -> Vec<i64> {
+        (1..=survey::RATING_MAX).collect()
+    }
+
+    pub fn answer(&self, key: &str) -> &str {
+        self.answers.get(key).map(|s| s.as_str()).unwrap_or("")
+    }
+
+    pub fn is_answer(&self, key: &str, rating: &i64) -> bool {
+        self.answer(key) == rating.to_string()
+    }
+}
@@ -1,7 +1,8 @@
 use askama::Template;
 
+use crate::models::reassignment::ReassignItem;
 use crate::models::user::UserDisplay;
-use super::PageContext;
+use super::{PageContext, UserOption};
 
 #[derive(Template)]
 #[template(path = "users/list.html")]
@@ -10,8 +11,7 @@ pub struct UserListTemplate {
     pub user_page: crate::models::user::types::UserPage,
     pub filter_json: String,
     pub filter_active: bool,
-    pub sort_column: String,
-    pub sort_dir: String,
+    pub table: crate::models::table_filter::TableView,
     pub columns: Vec<crate::models::table_filter::ColumnDef>,
     pub available_roles: Vec<(String, String)>,
     pub fields_json: String,
@@ -25,4 +25,33 @@ pub struct UserFormTemplate {
     pub form_title: String,
     pub user: Option<UserDisplay>,
     pub errors: Vec<String>,
+    /// Why the account is locked; `None` when it can sign in.
+    pub locked_reason: Option<String>,
+}
+
+/// Move a user's pending work to another user, with a preview.
+#[derive(Template)]
+#[template(path = "users/reassign.html")]
+pub struct UserReassignTemplate {
+    pub ctx: PageContext,
+    pub from_user_id: i64,
+    pub from_label: String,
+    pub users: Vec<UserOption>,
+    pub to_user_id: i64,
+    pub to_label: String,
+    /// Whether a valid receiving user was chosen and `items` is the plan.
+    pub previewed: bool,
+    pub kinds: &'static [(&'static str, &'static str)],
+    pub items: Vec<ReassignItem>,
+    pub errors: Vec<String>,
+}
+
+impl UserReassignTemplate {
+    pub fn group(&self, kind: &str) -> Vec<&ReassignItem> {
+        self.items.iter().filter(|i| i.kind == kind).collect()
+    }
+
+    pub fn moving(&self) -> usize {
+        self.items.iter().filter(|i| i.moves()).count()
+    }
 }
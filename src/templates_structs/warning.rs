@@ -1,5 +1,7 @@
 use askama::Template;
 
+use crate::models::action_item::ActionItem;
+use crate::warnings::notifications::Notification;
 use crate::warnings::queries::{WarningPage, WarningDetail, WarningRecipient, WarningTimelineEvent};
 use super::PageContext;
 use super::common::UserOption;
@@ -24,4 +26,31 @@ pub struct WarningDetailTemplate {
     pub timeline: Vec<WarningTimelineEvent>,
     pub user_receipt_id: i64,
     pub users: Vec<UserOption>,
+    /// Action items converted from the warning.
+    pub action_items: Vec<ActionItem>,
+    /// Who a new action item can be assigned to, grouped by ToR.
+    pub assignees: Vec<AssigneeGroup>,
+    pub can_convert: bool,
+}
+
+/// A ToR and the position holders an action item in it can go to.
+pub struct AssigneeGroup {
+    pub tor_id: i64,
+    pub tor_label: String,
+    /// (user id, label)
+    pub holders: Vec<(i64, String)>,
+}
+
+#[derive(Template)]
+#[template(path = "notifications.html")]
+pub struct NotificationsTemplate {
+    pub ctx: PageContext,
+    pub items: Vec<Notification>,
+    /// Channel tabs as (key, label, unread count).
+    pub channels: Vec<(String, String, usize)>,
+    /// Selected channel key; empty for all channels.
+    pub channel: String,
+    pub show_read: bool,
+    /// Unread notifications across all channels.
+    pub unread: usize,
 }
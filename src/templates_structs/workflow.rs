@@ -1,8 +1,11 @@
+use std::collections::HashMap;
+
 use askama::Template;
 
-use crate::models::suggestion::SuggestionListItem;
+use crate::models::suggestion::{SuggestionCluster, SuggestionListItem};
 use crate::models::proposal::ProposalListItem;
 use crate::models::agenda_point::AgendaPointListItem;
+use crate::models::meeting::capacity::{MeetingCapacity, SchedulePlan};
 use super::PageContext;
 
 #[derive(Template)]
@@ -15,6 +18,27 @@ pub struct WorkflowTemplate {
     pub suggestions: Vec<SuggestionListItem>,
     pub proposals: Vec<ProposalListItem>,
     pub agenda_points: Vec<AgendaPointListItem>,
+    /// Reasons a proposal may be rejected for.
+    pub rejection_reasons: Vec<crate::models::rejection_reason::RejectionReason>,
+    /// Absent members of this ToR the user is standing in for.
+    pub acting_for: Vec<crate::models::delegation::OutOfOffice>,
+    /// Permissions those members delegated to the user.
+    pub delegated: Vec<String>,
+    /// Auto-closed suggestions the user may still reopen.
+    pub reopenable: Vec<i64>,
+    /// Groups of similar open suggestions, for reviewers.
+    pub clusters: Vec<SuggestionCluster>,
+}
+
+impl WorkflowTemplate {
+    /// Whether the user may act with `code`, directly or for an absent member.
+    pub fn can(&self, code: &str) -> bool {
+        self.ctx.permissions.has(code) || self.delegated.iter().any(|c| c == code)
+    }
+
+    pub fn can_reopen(&self, suggestion_id: &i64) -> bool {
+        self.reopenable.contains(suggestion_id)
+    }
 }
 
 #[derive(Template)]
@@ -52,4 +76,33 @@ pub struct QueueTemplate {
     pub tor_id: i64,
     pub tor_name: String,
     pub queued_proposals: Vec<ProposalListItem>,
+    pub errors: Vec<String>,
+    /// Upcoming meetings with their remaining agenda time.
+    pub upcoming: Vec<MeetingCapacity>,
+    /// Distribution preview, shown before it is committed.
+    pub plan: Option<SchedulePlan>,
+    /// `date` (all on one date) or `distribute` (across upcoming meetings).
+    pub mode: String,
+    pub scheduled_date: String,
+    pub default_minutes: i32,
+    pub selected: Vec<i64>,
+    /// Per-item time allocations that differ from `default_minutes`.
+    pub item_minutes: HashMap<i64, i32>,
+    /// Proposals marked to become decision items.
+    pub decisions: Vec<i64>,
+}
+
+impl QueueTemplate {
+    pub fn is_selected(&self, proposal_id: i64) -> bool {
+        self.selected.contains(&proposal_id)
+    }
+
+    pub fn is_decision(&self, proposal_id: i64) -> bool {
+        self.decisions.contains(&proposal_id)
+    }
+
+    /// Value for an item's time input; empty when it uses the default.
+    pub fn minutes_for(&self, proposal_id: i64) -> String {
+        self.item_minutes.get(&proposal_id).map(|m| m.to_string()).unwrap_or_default()
+    }
 }
@@ -0,0 +1,184 @@
+//! Minimal HTTP/1.1 client for JSON calls to the ticketing system.
+//!
+//! One request per connection (`Connection: close`), HTTPS via rustls with the
+//! Mozilla root set. Handles fixed-length, chunked and read-to-close bodies.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio_rustls::rustls::{ClientConfig, RootCertStore};
+use tokio_rustls::rustls::pki_types::ServerName;
+use tokio_rustls::TlsConnector;
+
+const TIMEOUT: Duration = Duration::from_secs(20);
+
+#[derive(Debug)]
+pub struct Response {
+    pub status: u16,
+    pub body: String,
+}
+
+impl Response {
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+}
+
+/// Split `http(s)://host[:port]/path` into (tls, host, port, path).
+fn parse_url(url: &str) -> Result<(bool, String, u16, String), String> {
+    let (tls, rest) = if let Some(r) = url.strip_prefix("https://") {
+        (true, r)
+    } else if let Some(r) = url.strip_prefix("http://") {
+        (false, r)
+    } else {
+        return Err(format!("Unsupported URL: {}", url));
+    };
+    let (authority, path) = match rest.find('/') {
+        Some(i) => (&rest[..i], &rest[i..]),
+        None => (rest, "/"),
+    };
+    let (host, port) = match authority.rsplit_once(':') {
+        Some((h, p)) => (h, p.parse::<u16>().map_err(|_| format!("Bad port in {}", url))?),
+        None => (authority, if tls { 443 } else { 80 }),
+    };
+    if host.is_empty() {
+        return Err(format!("Missing host in {}", url));
+    }
+    Ok((tls, host.to_string(), port, path.to_string()))
+}
+
+/// Send a request with an optional body, JSON unless a `Content-Type` header says otherwise.
+pub async fn send(
+    method: &str,
+    url: &str,
+    headers: &[(&str, &str)],
+    body: Option<&str>,
+) -> Result<Response, String> {
+    send_bytes(method, url, headers, body.map(str::as_bytes)).await
+}
+
+/// Send a request with an optional binary body, e.g. an encrypted push message.
+pub async fn send_bytes(
+    method: &str,
+    url: &str,
+    headers: &[(&str, &str)],
+    body: Option<&[u8]>,
+) -> Result<Response, String> {
+    tokio::time::timeout(TIMEOUT, send_inner(method, url, headers, body))
+        .await
+        .map_err(|_| format!("{} {} timed out", method, url))?
+}
+
+async fn send_inner(
+    method: &str,
+    url: &str,
+    headers: &[(&str, &str)],
+    body: Option<&[u8]>,
+) -> Result<Response, String> {
+    let (tls, host, port, path) = parse_url(url)?;
+
+    let mut request = format!(
+        "{} {} HTTP/1.1\r\nHost: {}\r\nAccept: application/json\r\nConnection: close\r\n",
+        method, path, host
+    );
+    for (k, v) in headers {
+        request.push_str(&format!("{}: {}\r\n", k, v));
+    }
+    if let Some(b) = body {
+        // JSON unless the caller names another type, e.g. a form-encoded token request
+        if !headers.iter().any(|(k, _)| k.eq_ignore_ascii_case("Content-Type")) {
+            request.push_str("Content-Type: application/json\r\n");
+        }
+        request.push_str(&format!("Content-Length: {}\r\n", b.len()));
+    }
+    request.push_str("\r\n");
+    let mut request = request.into_bytes();
+    request.extend_from_slice(body.unwrap_or(&[]));
+
+    let tcp = TcpStream::connect((host.as_str(), port)).await.map_err(|e| e.to_string())?;
+    if tls {
+        let mut roots = RootCertStore::empty();
+        roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+        let config = ClientConfig::builder().with_root_certificates(roots).with_no_client_auth();
+        let name = ServerName::try_from(host.clone()).map_err(|e| e.to_string())?;
+        let stream = TlsConnector::from(Arc::new(config))
+            .connect(name, tcp)
+            .await
+            .map_err(|e| e.to_string())?;
+        exchange(stream, &request).await
+    } else {
+        exchange(tcp, &request).await
+    }
+}
+
+async fn exchange<S: AsyncRead + AsyncWrite + Unpin>(mut stream: S, request: &[u8]) -> Result<Response, String> {
+    stream.write_all(request).await.map_err(|e| e.to_string())?;
+    stream.flush().await.map_err(|e| e.to_string())?;
+
+    let mut raw = Vec::new();
+    // Servers may close TLS without close_notify; keep whatever arrived
+    if let Err(e) = stream.read_to_end(&mut raw).await
+        && raw.is_empty()
+    {
+        return Err(e.to_string());
+    }
+    parse_response(&raw)
+}
+
+/// Parse a complete HTTP/1.1 response.
+pub fn parse_response(raw: &[u8]) -> Result<Response, String> {
+    let split = raw.windows(4).position(|w| w == b"\r\n\r\n")
+        .ok_or_else(|| "Malformed HTTP response".to_string())?;
+    let head = String::from_utf8_lossy(&raw[..split]);
+    let payload = &raw[split + 4..];
+
+    let mut lines = head.lines();
+    let status = lines.next()
+        .and_then(|l| l.split_whitespace().nth(1))
+        .and_then(|s| s.parse::<u16>().ok())
+        .ok_or_else(|| "Malformed HTTP status line".to_string())?;
+
+    let mut chunked = false;
+    let mut length: Option<usize> = None;
+    for line in lines {
+        let Some((k, v)) = line.split_once(':') else { continue };
+        let (k, v) = (k.trim().to_ascii_lowercase(), v.trim());
+        if k == "transfer-encoding" && v.eq_ignore_ascii_case("chunked") {
+            chunked = true;
+        } else if k == "content-length" {
+            length = v.parse().ok();
+        }
+    }
+
+    let body = if chunked {
+        decode_chunked(payload)?
+    } else {
+        match length {
+            Some(n) => payload[..n.min(payload.len())].to_vec(),
+            None => payload.to_vec(),
+        }
+    };
+    Ok(Response { status, body: String::from_utf8_lossy(&body).into_owned() })
+}
+
+fn decode_chunked(mut data: &[u8]) -> Result<Vec<u8>, String> {
+    let mut out = Vec::new();
+    loop {
+        let line_end = data.windows(2).position(|w| w == b"\r\n")
+            .ok_or_else(|| "Truncated chunked body".to_string())?;
+        let size_str = String::from_utf8_lossy(&data[..line_end]);
+        let size = usize::from_str_radix(size_str.split(';').next().unwrap_or("").trim(), 16)
+            .map_err(|_| "Bad chunk size".to_string())?;
+        data = &data[line_end + 2..];
+        if size == 0 {
+            return Ok(out);
+        }
+        if data.len() < size {
+            return Err("Truncated chunk".to_string());
+        }
+        out.extend_from_slice(&data[..size]);
+        data = data.get(size + 2..).unwrap_or(&[]);
+    }
+}
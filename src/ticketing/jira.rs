@@ -0,0 +1,71 @@
+//! Jira REST API v2 calls used by the action item sync.
+
+use serde_json::{json, Value};
+
+use super::{http, TicketingConfig};
+
+/// What an action item contributes to its ticket.
+pub struct TicketFields<'a> {
+    pub summary: &'a str,
+    pub description: String,
+    pub due_date: &'a str,
+}
+
+fn fields_json(cfg: &TicketingConfig, fields: &TicketFields<'_>, create: bool) -> Value {
+    // Jira limits summaries to 255 characters
+    let summary: String = fields.summary.chars().take(250).collect();
+    let mut body = json!({ "summary": summary, "description": fields.description });
+    if create {
+        body["project"] = json!({ "key": cfg.project_key });
+        body["issuetype"] = json!({ "name": cfg.issue_type });
+    }
+    if !fields.due_date.is_empty() {
+        body["duedate"] = json!(fields.due_date);
+    }
+    json!({ "fields": body })
+}
+
+async fn call(cfg: &TicketingConfig, method: &str, path: &str, body: Option<Value>) -> Result<http::Response, String> {
+    let url = format!("{}{}", cfg.base_url, path);
+    let body = body.map(|b| b.to_string());
+    let res = http::send(method, &url, &[("Authorization", &cfg.auth_header)], body.as_deref()).await?;
+    if !res.is_success() {
+        return Err(format!("{} {} returned {}: {}", method, path, res.status, res.body.chars().take(200).collect::<String>()));
+    }
+    Ok(res)
+}
+
+/// Create an issue, returning its key (e.g. `OPS-42`).
+pub async fn create_issue(cfg: &TicketingConfig, fields: &TicketFields<'_>) -> Result<String, String> {
+    let res = call(cfg, "POST", "/rest/api/2/issue", Some(fields_json(cfg, fields, true))).await?;
+    let created: Value = serde_json::from_str(&res.body).map_err(|e| e.to_string())?;
+    created.get("key")
+        .and_then(|k| k.as_str())
+        .map(|k| k.to_string())
+        .ok_or_else(|| "Create response has no issue key".to_string())
+}
+
+/// Push edited fields to an existing issue.
+pub async fn update_issue(cfg: &TicketingConfig, key: &str, fields: &TicketFields<'_>) -> Result<(), String> {
+    call(cfg, "PUT", &format!("/rest/api/2/issue/{}", key), Some(fields_json(cfg, fields, false))).await?;
+    Ok(())
+}
+
+/// Current status of an issue as (status name, local action item status).
+pub async fn fetch_status(cfg: &TicketingConfig, key: &str) -> Result<(String, &'static str), String> {
+    let res = call(cfg, "GET", &format!("/rest/api/2/issue/{}?fields=status", key), None).await?;
+    let issue: Value = serde_json::from_str(&res.body).map_err(|e| e.to_string())?;
+    let status = &issue["fields"]["status"];
+    let name = status["name"].as_str().unwrap_or("").to_string();
+    let category = status["statusCategory"]["key"].as_str().unwrap_or("");
+    Ok((name, local_status(category)))
+}
+
+/// Map a Jira status category to an action item status.
+pub fn local_status(category: &str) -> &'static str {
+    match category {
+        "done" => "done",
+        "indeterminate" => "in_progress",
+        _ => "open",
+    }
+}
@@ -0,0 +1,178 @@
+//! Ticketing integration: minutes action items become Jira issues.
+//!
+//! New and edited items are marked `sync: "pending"` when saved. The sync
+//! creates or updates the linked issue and polls its status back onto the
+//! item; once linked, an item's status is owned by the ticket.
+
+pub mod http;
+pub mod jira;
+
+use std::time::Duration;
+
+use base64::Engine;
+use serde_json::Value;
+use sqlx::PgPool;
+
+use crate::models::minutes;
+use crate::models::minutes::action_items::{self, TicketUpdate};
+use crate::models::setting;
+
+/// How often linked tickets are polled.
+const POLL_INTERVAL_SECS: u64 = 300;
+
+/// Connection details for the ticketing system.
+#[derive(Debug, Clone)]
+pub struct TicketingConfig {
+    /// Base URL without trailing slash, e.g. `https://example.atlassian.net`.
+    pub base_url: String,
+    pub project_key: String,
+    pub issue_type: String,
+    /// Full `Authorization` header value.
+    pub auth_header: String,
+}
+
+impl TicketingConfig {
+    /// Load from settings plus the `TICKETING_USER` / `TICKETING_API_TOKEN`
+    /// environment variables. `None` when disabled or incomplete.
+    pub async fn load(pool: &PgPool) -> Option<Self> {
+        if !setting::get_bool(pool, "ticketing.enabled").await {
+            return None;
+        }
+        let base_url = setting::get_value(pool, "ticketing.base_url", "").await;
+        let project_key = setting::get_value(pool, "ticketing.project_key", "").await;
+        let issue_type = setting::get_value(pool, "ticketing.issue_type", "Task").await;
+        let user = std::env::var("TICKETING_USER").ok()?;
+        let token = std::env::var("TICKETING_API_TOKEN").ok()?;
+        if base_url.is_empty() || project_key.is_empty() {
+            log::warn!("Ticketing is enabled but base URL or project key is not set");
+            return None;
+        }
+        let credentials = base64::engine::general_purpose::STANDARD.encode(format!("{}:{}", user, token));
+        Some(Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            project_key,
+            issue_type: if issue_type.is_empty() { "Task".to_string() } else { issue_type },
+            auth_header: format!("Basic {}", credentials),
+        })
+    }
+
+    pub fn browse_url(&self, key: &str) -> String {
+        format!("{}/browse/{}", self.base_url, key)
+    }
+}
+
+fn str_field<'a>(item: &'a Value, key: &str) -> &'a str {
+    item.get(key).and_then(|v| v.as_str()).unwrap_or("")
+}
+
+fn ticket_fields<'a>(item: &'a Value, minutes_label: &str) -> jira::TicketFields<'a> {
+    let mut description = format!("Action item from minutes: {}", minutes_label);
+    let responsible = str_field(item, "responsible");
+    if !responsible.is_empty() {
+        description.push_str(&format!("\nResponsible: {}", responsible));
+    }
+    jira::TicketFields {
+        summary: str_field(item, "description"),
+        description,
+        due_date: str_field(item, "due_date"),
+    }
+}
+
+/// Sync the action items of one minutes document. Returns the number of tickets created.
+pub async fn sync_minutes(
+    pool: &PgPool,
+    cfg: &TicketingConfig,
+    minutes_id: i64,
+    minutes_label: &str,
+) -> Result<usize, sqlx::Error> {
+    let items: Vec<Value> = serde_json::from_str(&minutes::find_structured_action_items(pool, minutes_id).await?)
+        .unwrap_or_default();
+
+    let mut updates = Vec::new();
+    let mut created = 0;
+    for item in &items {
+        let ref_id = str_field(item, "ref");
+        if ref_id.is_empty() {
+            continue;
+        }
+        let key = str_field(item, "external_key");
+        let pending = str_field(item, "sync") == "pending";
+        let mut update = TicketUpdate { ref_id: ref_id.to_string(), ..Default::default() };
+
+        if key.is_empty() {
+            if !pending {
+                continue;
+            }
+            match jira::create_issue(cfg, &ticket_fields(item, minutes_label)).await {
+                Ok(new_key) => {
+                    let details = serde_json::json!({
+                        "ticket": new_key,
+                        "summary": format!("Created ticket {} for action item", new_key)
+                    });
+                    let _ = crate::audit::log(pool, 0, "minutes.ticket_created", "minutes", minutes_id, details).await;
+                    update.external_url = Some(cfg.browse_url(&new_key));
+                    update.external_key = Some(new_key);
+                    update.pushed = Some(action_items::fingerprint(item));
+                    created += 1;
+                }
+                Err(e) => log::warn!("Ticket creation for minutes {} failed: {}", minutes_id, e),
+            }
+        } else {
+            if pending {
+                match jira::update_issue(cfg, key, &ticket_fields(item, minutes_label)).await {
+                    Ok(()) => update.pushed = Some(action_items::fingerprint(item)),
+                    Err(e) => log::warn!("Ticket update for {} failed: {}", key, e),
+                }
+            }
+            if pending || str_field(item, "status") != "done" {
+                match jira::fetch_status(cfg, key).await {
+                    Ok((name, status)) => {
+                        update.external_status = Some(name);
+                        update.status = Some(status.to_string());
+                    }
+                    Err(e) => log::warn!("Ticket status poll for {} failed: {}", key, e),
+                }
+            }
+        }
+        updates.push(update);
+    }
+
+    if !updates.is_empty() {
+        // Re-read: the minutes may have been edited while the calls were in flight
+        let current = minutes::find_structured_action_items(pool, minutes_id).await?;
+        let merged = action_items::apply_ticket_updates(&current, &updates);
+        if merged != current {
+            minutes::update_structured_action_items(pool, minutes_id, &merged).await?;
+        }
+    }
+    Ok(created)
+}
+
+/// Sync every minutes document with linked or pending action items.
+pub async fn sync_all(pool: &PgPool, cfg: &TicketingConfig) {
+    let rows = match minutes::find_with_ticketed_action_items(pool).await {
+        Ok(rows) => rows,
+        Err(e) => {
+            log::error!("Ticket sync query failed: {}", e);
+            return;
+        }
+    };
+    for (minutes_id, label) in rows {
+        if let Err(e) = sync_minutes(pool, cfg, minutes_id, &label).await {
+            log::error!("Ticket sync for minutes {} failed: {}", minutes_id, e);
+        }
+    }
+}
+
+/// Poll linked tickets in the background while the integration is enabled.
+pub fn spawn_sync(pool: PgPool) {
+    actix_web::rt::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(POLL_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+            if let Some(cfg) = TicketingConfig::load(&pool).await {
+                sync_all(&pool, &cfg).await;
+            }
+        }
+    });
+}
@@ -0,0 +1,305 @@
+//! Admin broadcasts: notices for planned maintenance, policy changes and the
+//! like, sent as an `announcement.broadcast` warning to everyone, a role's
+//! users or a ToR's members.
+//!
+//! A broadcast is a `warning_broadcast` entity. It is sent at its send time
+//! (immediately when none is given) by [`run_due`], which the warning
+//! scheduler calls; the audience is resolved at that moment, so users who
+//! joined the role or ToR in the meantime are included. Once its expiry
+//! passes the warning is resolved, which takes it out of inboxes.
+
+use serde::Serialize;
+use sqlx::PgPool;
+
+use crate::models::{entity, role, tor};
+use super::clock;
+
+/// Who a broadcast goes to, as (key, label).
+pub const AUDIENCES: &[(&str, &str)] = &[
+    ("all", "All users"),
+    ("role", "Users with a role"),
+    ("tor", "Members of a ToR"),
+];
+
+/// Severities a broadcast can carry, mildest first.
+pub const SEVERITIES: &[&str] = &["info", "low", "medium", "high", "critical"];
+
+/// Longest message accepted.
+pub const MAX_MESSAGE_LEN: usize = 500;
+
+/// `source_action` of broadcast warnings; shown under Announcements.
+pub const SOURCE_ACTION: &str = "announcement.broadcast";
+
+/// Timestamps are stored as UTC in this format, so they sort as text.
+const TIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%SZ";
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct Broadcast {
+    pub id: i64,
+    pub severity: String,
+    pub message: String,
+    pub details: String,
+    pub audience: String,
+    pub audience_id: Option<i64>,
+    pub audience_label: String,
+    /// UTC, `YYYY-MM-DDTHH:MM:SSZ`.
+    pub send_at: String,
+    /// UTC, `YYYY-MM-DDTHH:MM:SSZ`; empty for no expiry.
+    pub expires_at: String,
+    /// `scheduled`, `sent`, `expired` or `cancelled`.
+    pub status: String,
+    pub warning_id: Option<i64>,
+    pub recipient_count: i64,
+    pub created_by_name: String,
+}
+
+impl Broadcast {
+    pub fn audience_text(&self) -> String {
+        match self.audience.as_str() {
+            "all" => "All users".to_string(),
+            "role" => format!("Role: {}", self.audience_label),
+            _ => format!("ToR: {}", self.audience_label),
+        }
+    }
+
+    /// Send time in server-local time for display.
+    pub fn send_at_local(&self) -> String {
+        local_display(&self.send_at)
+    }
+
+    /// Expiry in server-local time for display; empty for none.
+    pub fn expires_at_local(&self) -> String {
+        local_display(&self.expires_at)
+    }
+}
+
+fn local_display(utc: &str) -> String {
+    chrono::DateTime::parse_from_rfc3339(utc)
+        .map(|t| t.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_default()
+}
+
+/// Fields submitted from the broadcast form.
+pub struct BroadcastInput<'a> {
+    pub severity: &'a str,
+    pub message: &'a str,
+    pub details: &'a str,
+    pub audience: &'a str,
+    /// Role or ToR id, for those audiences.
+    pub audience_id: Option<i64>,
+    /// `None` to send now.
+    pub send_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Parse a `datetime-local` form value (server-local time). Empty is `None`.
+pub fn parse_local(value: &str) -> Result<Option<chrono::DateTime<chrono::Utc>>, String> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Ok(None);
+    }
+    chrono::NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M")
+        .ok()
+        .and_then(|t| t.and_local_timezone(chrono::Local).earliest())
+        .map(|t| Some(t.with_timezone(&chrono::Utc)))
+        .ok_or_else(|| format!("'{}' is not a valid date and time", value))
+}
+
+/// Check a broadcast before it is scheduled.
+pub fn validate(input: &BroadcastInput<'_>, now: chrono::DateTime<chrono::Utc>) -> Vec<String> {
+    let mut errors = Vec::new();
+    if !SEVERITIES.contains(&input.severity) {
+        errors.push("Unknown severity".to_string());
+    }
+    let message = input.message.trim();
+    if message.is_empty() {
+        errors.push("Message is required".to_string());
+    } else if message.chars().count() > MAX_MESSAGE_LEN {
+        errors.push(format!("Message must be at most {} characters", MAX_MESSAGE_LEN));
+    }
+    match input.audience {
+        "all" => {}
+        "role" | "tor" if input.audience_id.is_none() => {
+            errors.push(format!("Choose a {}", if input.audience == "role" { "role" } else { "ToR" }));
+        }
+        "role" | "tor" => {}
+        _ => errors.push("Unknown audience".to_string()),
+    }
+    let send_at = input.send_at.unwrap_or(now);
+    if let Some(expires_at) = input.expires_at {
+        if expires_at <= send_at {
+            errors.push("Expiry must be after the send time".to_string());
+        }
+        if expires_at <= now {
+            errors.push("Expiry cannot be in the past".to_string());
+        }
+    }
+    errors
+}
+
+/// Schedule a broadcast. The scheduler (or [`run_due`]) sends it when due.
+pub async fn create(pool: &PgPool, input: &BroadcastInput<'_>, created_by: i64) -> Result<i64, sqlx::Error> {
+    let now = clock::now();
+    let audience_label = match (input.audience, input.audience_id) {
+        ("role", Some(id)) => role::find_by_id(pool, id).await?.map(|r| r.label).unwrap_or_default(),
+        ("tor", Some(id)) => tor::find_detail_by_id(pool, id).await?.map(|t| t.label).unwrap_or_default(),
+        _ => String::new(),
+    };
+    let name = format!("broadcast.{}.{}", created_by, now.timestamp_micros());
+    let id = entity::create(pool, "warning_broadcast", &name, input.message.trim()).await?;
+    let send_at = input.send_at.unwrap_or(now).format(TIME_FORMAT).to_string();
+    let expires_at = input.expires_at.map(|t| t.format(TIME_FORMAT).to_string()).unwrap_or_default();
+    let audience_id = input.audience_id.map(|id| id.to_string()).unwrap_or_default();
+    entity::set_properties(pool, id, &[
+        ("severity", input.severity),
+        ("message", input.message.trim()),
+        ("details", input.details.trim()),
+        ("audience", input.audience),
+        ("audience_id", &audience_id),
+        ("audience_label", &audience_label),
+        ("send_at", &send_at),
+        ("expires_at", &expires_at),
+        ("status", "scheduled"),
+        ("created_by_id", &created_by.to_string()),
+    ]).await?;
+    Ok(id)
+}
+
+/// Active users a broadcast audience currently covers.
+pub async fn audience_users(pool: &PgPool, audience: &str, audience_id: Option<i64>) -> Result<Vec<i64>, sqlx::Error> {
+    let ids = match (audience, audience_id) {
+        ("all", _) => {
+            sqlx::query_scalar::<_, i64>(
+                "SELECT id FROM entities WHERE entity_type = 'user' AND is_active = true ORDER BY id",
+            )
+            .fetch_all(pool)
+            .await?
+        }
+        ("role", Some(role_id)) => {
+            sqlx::query_scalar::<_, i64>(
+                "SELECT u.id FROM entities u \
+                 JOIN relations r ON r.source_id = u.id AND r.target_id = $1 \
+                   AND r.relation_type_id = (SELECT id FROM entities WHERE entity_type = 'relation_type' AND name = 'has_role') \
+                 WHERE u.entity_type = 'user' AND u.is_active = true \
+                 ORDER BY u.id",
+            )
+            .bind(role_id)
+            .fetch_all(pool)
+            .await?
+        }
+        ("tor", Some(tor_id)) => {
+            let mut ids: Vec<i64> = tor::find_members(pool, tor_id).await?
+                .into_iter()
+                .filter_map(|m| m.holder_id)
+                .collect();
+            ids.sort_unstable();
+            ids.dedup();
+            ids
+        }
+        _ => Vec::new(),
+    };
+    Ok(ids)
+}
+
+const SELECT_BROADCAST: &str =
+    "SELECT e.id, \
+            COALESCE(p_sev.value, 'info') AS severity, \
+            COALESCE(p_msg.value, e.label) AS message, \
+            COALESCE(p_det.value, '') AS details, \
+            COALESCE(p_aud.value, 'all') AS audience, \
+            NULLIF(p_aud_id.value, '')::BIGINT AS audience_id, \
+            COALESCE(p_aud_label.value, '') AS audience_label, \
+            COALESCE(p_send.value, '') AS send_at, \
+            COALESCE(p_exp.value, '') AS expires_at, \
+            COALESCE(p_status.value, 'scheduled') AS status, \
+            NULLIF(p_warn.value, '')::BIGINT AS warning_id, \
+            COALESCE(NULLIF(p_count.value, '')::BIGINT, 0) AS recipient_count, \
+            COALESCE(u.label, '') AS created_by_name \
+     FROM entities e \
+     LEFT JOIN entity_properties p_sev ON e.id = p_sev.entity_id AND p_sev.key = 'severity' \
+     LEFT JOIN entity_properties p_msg ON e.id = p_msg.entity_id AND p_msg.key = 'message' \
+     LEFT JOIN entity_properties p_det ON e.id = p_det.entity_id AND p_det.key = 'details' \
+     LEFT JOIN entity_properties p_aud ON e.id = p_aud.entity_id AND p_aud.key = 'audience' \
+     LEFT JOIN entity_properties p_aud_id ON e.id = p_aud_id.entity_id AND p_aud_id.key = 'audience_id' \
+     LEFT JOIN entity_properties p_aud_label ON e.id = p_aud_label.entity_id AND p_aud_label.key = 'audience_label' \
+     LEFT JOIN entity_properties p_send ON e.id = p_send.entity_id AND p_send.key = 'send_at' \
+     LEFT JOIN entity_properties p_exp ON e.id = p_exp.entity_id AND p_exp.key = 'expires_at' \
+     LEFT JOIN entity_properties p_status ON e.id = p_status.entity_id AND p_status.key = 'status' \
+     LEFT JOIN entity_properties p_warn ON e.id = p_warn.entity_id AND p_warn.key = 'warning_id' \
+     LEFT JOIN entity_properties p_count ON e.id = p_count.entity_id AND p_count.key = 'recipient_count' \
+     LEFT JOIN entity_properties p_by ON e.id = p_by.entity_id AND p_by.key = 'created_by_id' \
+     LEFT JOIN entities u ON u.id::TEXT = p_by.value \
+     WHERE e.entity_type = 'warning_broadcast'";
+
+/// All broadcasts, latest send time first.
+pub async fn find_all(pool: &PgPool) -> Result<Vec<Broadcast>, sqlx::Error> {
+    sqlx::query_as::<_, Broadcast>(&format!("{} ORDER BY send_at DESC, e.id DESC", SELECT_BROADCAST))
+        .fetch_all(pool)
+        .await
+}
+
+pub async fn find_by_id(pool: &PgPool, id: i64) -> Result<Option<Broadcast>, sqlx::Error> {
+    sqlx::query_as::<_, Broadcast>(&format!("{} AND e.id = $1", SELECT_BROADCAST))
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+}
+
+/// Send a scheduled broadcast now. Returns the warning id.
+async fn send(pool: &PgPool, b: &Broadcast) -> Result<i64, sqlx::Error> {
+    let warning_id = super::create_warning(pool, &b.severity, "broadcast", SOURCE_ACTION, &b.message, &b.details, "system").await?;
+    let user_ids = audience_users(pool, &b.audience, b.audience_id).await?;
+    super::create_receipts(pool, warning_id, &user_ids).await?;
+    entity::set_properties(pool, b.id, &[
+        ("status", "sent"),
+        ("warning_id", &warning_id.to_string()),
+        ("recipient_count", &user_ids.len().to_string()),
+    ]).await?;
+    Ok(warning_id)
+}
+
+/// Send broadcasts whose time has come and expire those past their expiry.
+/// Returns (sent, expired).
+pub async fn run_due(pool: &PgPool) -> Result<(usize, usize), sqlx::Error> {
+    let now = clock::now().format(TIME_FORMAT).to_string();
+    let mut sent = 0;
+    let mut expired = 0;
+    for b in find_all(pool).await? {
+        let past_expiry = !b.expires_at.is_empty() && b.expires_at <= now;
+        match b.status.as_str() {
+            // Scheduled but already past its expiry (the server was down): never sent
+            "scheduled" if past_expiry => {
+                entity::set_property(pool, b.id, "status", "expired").await?;
+                expired += 1;
+            }
+            "scheduled" if b.send_at <= now => {
+                send(pool, &b).await?;
+                sent += 1;
+            }
+            "sent" if past_expiry => {
+                if let Some(warning_id) = b.warning_id {
+                    super::resolve_warning(pool, warning_id, 0).await?;
+                }
+                entity::set_property(pool, b.id, "status", "expired").await?;
+                expired += 1;
+            }
+            _ => {}
+        }
+    }
+    Ok((sent, expired))
+}
+
+/// Cancel a scheduled broadcast, or withdraw a sent one (its warning is
+/// resolved). Returns false if it had already ended.
+pub async fn cancel(pool: &PgPool, id: i64, actor_id: i64) -> Result<bool, sqlx::Error> {
+    let Some(b) = find_by_id(pool, id).await? else {
+        return Ok(false);
+    };
+    match (b.status.as_str(), b.warning_id) {
+        ("scheduled", _) => {}
+        ("sent", Some(warning_id)) => super::resolve_warning(pool, warning_id, actor_id).await?,
+        _ => return Ok(false),
+    }
+    entity::set_property(pool, id, "status", "cancelled").await?;
+    Ok(true)
+}
@@ -0,0 +1,24 @@
+//! Injectable clock for the warning system.
+//!
+//! Generators, receipts, and retention cleanup read the current time through
+//! [`now`] instead of calling `Utc::now()` directly. In production this is the
+//! system clock; tests wrap a future in [`with_fixed_now`] to replay generator
+//! runs at a pinned instant, so dedup, escalation, and retention behaviour is
+//! deterministic. The override is task-local, so parallel tests don't interfere.
+
+use chrono::{DateTime, Utc};
+use std::future::Future;
+
+tokio::task_local! {
+    static FIXED_NOW: DateTime<Utc>;
+}
+
+/// Current time — the pinned instant inside [`with_fixed_now`], otherwise the system clock.
+pub fn now() -> DateTime<Utc> {
+    FIXED_NOW.try_with(|t| *t).unwrap_or_else(|_| Utc::now())
+}
+
+/// Run `fut` with [`now`] pinned to `at`.
+pub async fn with_fixed_now<F: Future>(at: DateTime<Utc>, fut: F) -> F::Output {
+    FIXED_NOW.scope(at, fut).await
+}
@@ -1,6 +1,11 @@
+use askama::Template;
 use sqlx::PgPool;
 
 use crate::handlers::warning_handlers::ws::ConnectionMap;
+use crate::models::fiscal::FiscalCalendar;
+use crate::models::meeting::attendance;
+use crate::models::{action_item, recertification, report_definition, report_subscription, setting, suggestion, workflow};
+use crate::templates_structs::{PrintMeta, ReportPrintTemplate};
 
 /// Check for users without a role assignment.
 pub async fn check_users_without_role(pool: &PgPool, conn_map: &ConnectionMap) {
@@ -54,16 +59,22 @@ pub async fn check_users_without_role(pool: &PgPool, conn_map: &ConnectionMap) {
         return;
     }
 
-    if let Ok(receipt_ids) = super::create_receipts(pool, warning_id, &admin_ids).await {
-        let _ = receipt_ids; // receipts created
-        crate::handlers::warning_handlers::ws::notify_users(
-            conn_map, pool, &admin_ids, warning_id, "medium", &message,
-        ).await;
+    if super::create_receipts(pool, warning_id, &admin_ids).await.is_ok() {
+        super::outbox::drain(pool, conn_map).await;
     }
 }
 
+/// Database size that raises a warning; more than double raises it as high severity.
+pub const DATABASE_SIZE_THRESHOLD_MB: i64 = 500;
+
 /// Check database size against threshold using pg_database_size().
 pub async fn check_database_size(pool: &PgPool, conn_map: &ConnectionMap) {
+    check_database_size_against(pool, conn_map, DATABASE_SIZE_THRESHOLD_MB).await;
+}
+
+/// Check database size against an explicit threshold, so replay tests can
+/// drive the escalation without growing the database.
+pub async fn check_database_size_against(pool: &PgPool, conn_map: &ConnectionMap, threshold_mb: i64) {
     let size_bytes: i64 = match sqlx::query_as::<_, (i64,)>(
         "SELECT pg_database_size(current_database())",
     )
@@ -77,7 +88,6 @@ pub async fn check_database_size(pool: &PgPool, conn_map: &ConnectionMap) {
         }
     };
 
-    let threshold_mb: i64 = 500; // 500 MB
     let size_mb = size_bytes / (1024 * 1024);
 
     if size_mb < threshold_mb {
@@ -112,9 +122,7 @@ pub async fn check_database_size(pool: &PgPool, conn_map: &ConnectionMap) {
     }
 
     if super::create_receipts(pool, warning_id, &admin_ids).await.is_ok() {
-        crate::handlers::warning_handlers::ws::notify_users(
-            conn_map, pool, &admin_ids, warning_id, severity, &message,
-        ).await;
+        super::outbox::drain(pool, conn_map).await;
     }
 }
 
@@ -152,9 +160,9 @@ pub async fn check_tor_vacancies(pool: &PgPool, conn_map: &ConnectionMap) {
         }
     };
 
-    // Build per-ToR vacancy map
-    let mut tor_vacancies: std::collections::HashMap<i64, (String, Vec<(i64, String)>)> =
-        std::collections::HashMap::new();
+    // Build per-ToR vacancy map (ordered by ToR id so replayed runs are deterministic)
+    let mut tor_vacancies: std::collections::BTreeMap<i64, (String, Vec<(i64, String)>)> =
+        std::collections::BTreeMap::new();
     for (tor_id, tor_label, pos_id, pos_label) in &rows {
         tor_vacancies
             .entry(*tor_id)
@@ -209,9 +217,7 @@ pub async fn check_tor_vacancies(pool: &PgPool, conn_map: &ConnectionMap) {
         }
 
         if super::create_receipts(pool, warning_id, &target_ids).await.is_ok() {
-            crate::handlers::warning_handlers::ws::notify_users(
-                conn_map, pool, &target_ids, warning_id, "medium", &message,
-            ).await;
+            super::outbox::drain(pool, conn_map).await;
         }
     }
 
@@ -222,7 +228,7 @@ pub async fn check_tor_vacancies(pool: &PgPool, conn_map: &ConnectionMap) {
 /// Resolve vacancy warnings for ToRs that no longer have unfilled mandatory positions.
 async fn auto_resolve_tor_vacancies(
     pool: &PgPool,
-    current_vacancies: &std::collections::HashMap<i64, (String, Vec<(i64, String)>)>,
+    current_vacancies: &std::collections::BTreeMap<i64, (String, Vec<(i64, String)>)>,
 ) {
     let source_action = "scheduled.tor_vacancy";
 
@@ -262,19 +268,521 @@ async fn auto_resolve_tor_vacancies(
     }
 }
 
+/// Warn the owner of each open action item past its due date.
+/// One warning per item; resolved once the item is done or its due date moves.
+pub async fn check_overdue_action_items(pool: &PgPool, conn_map: &ConnectionMap) {
+    let today = super::clock::now().date_naive().format("%Y-%m-%d").to_string();
+    let overdue = match action_item::find_overdue(pool, &today).await {
+        Ok(items) => items,
+        Err(e) => {
+            log::error!("Generator check_overdue_action_items query failed: {}", e);
+            return;
+        }
+    };
+
+    let source_action = "scheduled.action_item_overdue";
+    for item in &overdue {
+        let dedup_key = format!("action_item_overdue_{}", item.id);
+        if item.owner_id == 0 || super::warning_exists(pool, source_action, &dedup_key).await {
+            continue;
+        }
+
+        let message = format!("Action item '{}' ({}) was due {}", item.title, item.tor_label, item.due_date);
+        let details = serde_json::json!({
+            "dedup": dedup_key,
+            "action_item_id": item.id,
+            "tor_id": item.tor_id,
+            "due_date": item.due_date,
+            "owner_id": item.owner_id,
+        })
+        .to_string();
+
+        let warning_id = match super::create_warning(
+            pool, "medium", "governance", source_action,
+            &message, &details, "system",
+        ).await {
+            Ok(id) => id,
+            Err(e) => {
+                log::error!("Failed to create overdue warning for action item {}: {}", item.id, e);
+                continue;
+            }
+        };
+
+        if super::create_receipts(pool, warning_id, &[item.owner_id]).await.is_ok() {
+            super::outbox::drain(pool, conn_map).await;
+        }
+    }
+
+    let still_overdue: std::collections::BTreeSet<i64> = overdue.iter().map(|i| i.id).collect();
+    auto_resolve_overdue_action_items(pool, &still_overdue).await;
+}
+
+/// Resolve overdue warnings for action items that are no longer overdue.
+async fn auto_resolve_overdue_action_items(pool: &PgPool, still_overdue: &std::collections::BTreeSet<i64>) {
+    let warnings: Vec<(i64, String)> = match sqlx::query_as::<_, (i64, String)>(
+        "SELECT e.id, det.value AS details
+         FROM entities e
+         JOIN entity_properties st ON st.entity_id = e.id AND st.key = 'status' AND st.value = 'active'
+         JOIN entity_properties sa ON sa.entity_id = e.id AND sa.key = 'source_action' AND sa.value = $1
+         JOIN entity_properties det ON det.entity_id = e.id AND det.key = 'details'
+         WHERE e.entity_type = 'warning'"
+    )
+    .bind("scheduled.action_item_overdue")
+    .fetch_all(pool)
+    .await
+    {
+        Ok(r) => r,
+        Err(_) => return,
+    };
+
+    for (warning_id, details_str) in warnings {
+        let item_id = serde_json::from_str::<serde_json::Value>(&details_str).ok()
+            .and_then(|v| v.get("action_item_id").and_then(|t| t.as_i64()));
+        if let Some(id) = item_id
+            && !still_overdue.contains(&id)
+        {
+            if let Err(e) = super::resolve_warning(pool, warning_id, 0).await {
+                log::error!("Failed to auto-resolve overdue warning {}: {}", warning_id, e);
+            }
+            log::info!("Auto-resolved overdue warning {} for action item {}", warning_id, id);
+        }
+    }
+}
+
+/// Warn ToR managers when a ToR's latest meetings missed quorum
+/// `warnings.quorum_miss_threshold` times in a row (0 disables).
+/// One warning per ToR; resolved once a meeting is quorate again.
+pub async fn check_quorum_misses(pool: &PgPool, conn_map: &ConnectionMap) {
+    let threshold = setting::get_i64(pool, attendance::MISS_THRESHOLD_SETTING).await;
+    let missing = if threshold > 0 {
+        match attendance::find_repeated_misses(pool, threshold as usize).await {
+            Ok(rows) => rows,
+            Err(e) => {
+                log::error!("Generator check_quorum_misses query failed: {}", e);
+                return;
+            }
+        }
+    } else {
+        Vec::new()
+    };
+
+    let source_action = "scheduled.quorum_missed";
+    let target_ids = super::get_users_with_permission(pool, "tor.manage_members")
+        .await
+        .unwrap_or_default();
+
+    for (tor_id, tor_label, misses) in &missing {
+        let dedup_key = format!("quorum_missed_{}", tor_id);
+        if super::warning_exists(pool, source_action, &dedup_key).await {
+            continue;
+        }
+
+        let message = format!("{} missed quorum at its last {} meetings", tor_label, misses);
+        let details = serde_json::json!({
+            "dedup": dedup_key,
+            "tor_id": tor_id,
+            "tor_label": tor_label,
+            "missed_in_a_row": misses,
+        })
+        .to_string();
+
+        let warning_id = match super::create_warning(
+            pool, "medium", "governance", source_action,
+            &message, &details, "system",
+        ).await {
+            Ok(id) => id,
+            Err(e) => {
+                log::error!("Failed to create quorum warning for ToR {}: {}", tor_id, e);
+                continue;
+            }
+        };
+
+        if target_ids.is_empty() {
+            continue;
+        }
+
+        if super::create_receipts(pool, warning_id, &target_ids).await.is_ok() {
+            super::outbox::drain(pool, conn_map).await;
+        }
+    }
+
+    let still_missing: std::collections::BTreeSet<i64> = missing.iter().map(|(id, _, _)| *id).collect();
+    auto_resolve_quorum_misses(pool, &still_missing).await;
+}
+
+/// Resolve quorum warnings for ToRs that met quorum again.
+async fn auto_resolve_quorum_misses(pool: &PgPool, still_missing: &std::collections::BTreeSet<i64>) {
+    let warnings: Vec<(i64, String)> = match sqlx::query_as::<_, (i64, String)>(
+        "SELECT e.id, det.value AS details
+         FROM entities e
+         JOIN entity_properties st ON st.entity_id = e.id AND st.key = 'status' AND st.value = 'active'
+         JOIN entity_properties sa ON sa.entity_id = e.id AND sa.key = 'source_action' AND sa.value = $1
+         JOIN entity_properties det ON det.entity_id = e.id AND det.key = 'details'
+         WHERE e.entity_type = 'warning'"
+    )
+    .bind("scheduled.quorum_missed")
+    .fetch_all(pool)
+    .await
+    {
+        Ok(r) => r,
+        Err(_) => return,
+    };
+
+    for (warning_id, details_str) in warnings {
+        let tor_id = serde_json::from_str::<serde_json::Value>(&details_str).ok()
+            .and_then(|v| v.get("tor_id").and_then(|t| t.as_i64()));
+        if let Some(id) = tor_id
+            && !still_missing.contains(&id)
+        {
+            if let Err(e) = super::resolve_warning(pool, warning_id, 0).await {
+                log::error!("Failed to auto-resolve quorum warning {}: {}", warning_id, e);
+            }
+            log::info!("Auto-resolved quorum warning {} for ToR {}", warning_id, id);
+        }
+    }
+}
+
+/// A generated report as stored: CSV, or a printable HTML page.
+pub async fn render_report(
+    pool: &PgPool,
+    report: &report_subscription::ReportTable,
+    format: &str,
+) -> Result<String, askama::Error> {
+    if format == "csv" {
+        return Ok(report.to_csv());
+    }
+    let tmpl = ReportPrintTemplate { meta: PrintMeta::build(pool).await, report: report.clone() };
+    tmpl.render()
+}
+
+/// Generate due report subscriptions and notify each owner that their report
+/// is ready to download.
+pub async fn deliver_report_subscriptions(pool: &PgPool, conn_map: &ConnectionMap) {
+    let today = super::clock::now().date_naive();
+    let due = match report_subscription::find_due(pool, &today.format("%Y-%m-%d").to_string()).await {
+        Ok(subs) => subs,
+        Err(e) => {
+            log::error!("Generator deliver_report_subscriptions query failed: {}", e);
+            return;
+        }
+    };
+
+    let fiscal = FiscalCalendar::load(pool).await;
+    for sub in due {
+        let period = report_subscription::period_before(&sub.frequency, today, &fiscal);
+        let report = match report_subscription::build_report(pool, &sub, period).await {
+            Ok(report) => report,
+            Err(e) => {
+                log::error!("Failed to build report for subscription {}: {}", sub.id, e);
+                continue;
+            }
+        };
+        let content = match render_report(pool, &report, &sub.format).await {
+            Ok(content) => content,
+            Err(e) => {
+                log::error!("Failed to render report for subscription {}: {}", sub.id, e);
+                continue;
+            }
+        };
+        let delivery_id = match report_subscription::record_delivery(pool, &sub, period, &report.title, &content, today).await {
+            Ok(id) => id,
+            Err(e) => {
+                log::error!("Failed to store report for subscription {}: {}", sub.id, e);
+                continue;
+            }
+        };
+
+        let message = format!("Your {} report is ready: {}", sub.frequency, report.title);
+        let details = serde_json::json!({
+            "subscription_id": sub.id,
+            "delivery_id": delivery_id,
+            "url": format!("/reports/deliveries/{}", delivery_id),
+        })
+        .to_string();
+        let warning_id = match super::create_warning(
+            pool, "info", "system", "scheduled.report_subscription",
+            &message, &details, "system",
+        ).await {
+            Ok(id) => id,
+            Err(e) => {
+                log::error!("Failed to create report notification: {}", e);
+                continue;
+            }
+        };
+        let targets = [sub.user_id];
+        if super::create_receipts(pool, warning_id, &targets).await.is_ok() {
+            super::outbox::drain(pool, conn_map).await;
+        }
+    }
+}
+
+/// Run due scheduled report definitions: build each report once, store it
+/// in the inbox of every member of the definition's group, notify them and,
+/// for e-mail delivery, queue the report e-mail. The next run follows the
+/// definition's schedule.
+pub async fn deliver_report_definitions(pool: &PgPool, conn_map: &ConnectionMap) {
+    let now = super::clock::now();
+    let due = match report_definition::find_due(pool, now).await {
+        Ok(defs) => defs,
+        Err(e) => {
+            log::error!("Generator deliver_report_definitions query failed: {}", e);
+            return;
+        }
+    };
+
+    let today = now.with_timezone(&chrono::Local).date_naive();
+    for def in due {
+        // Advance first, so a failing report is not retried every tick
+        if let Err(e) = report_definition::mark_run(pool, &def, now).await {
+            log::error!("Failed to schedule report definition {}: {}", def.id, e);
+            continue;
+        }
+        let (report, period) = match report_definition::build_report(pool, &def, today).await {
+            Ok(built) => built,
+            Err(e) => {
+                log::error!("Failed to build report for definition {}: {}", def.id, e);
+                continue;
+            }
+        };
+        let content = match render_report(pool, &report, &def.format).await {
+            Ok(content) => content,
+            Err(e) => {
+                log::error!("Failed to render report for definition {}: {}", def.id, e);
+                continue;
+            }
+        };
+        let recipients = match report_definition::find_recipients(pool, &def).await {
+            Ok(ids) => ids,
+            Err(e) => {
+                log::error!("Failed to find recipients for report definition {}: {}", def.id, e);
+                continue;
+            }
+        };
+
+        let mut delivery_ids = Vec::with_capacity(recipients.len());
+        for &user_id in &recipients {
+            match report_definition::record_delivery(pool, &def, user_id, period, &report.title, &content).await {
+                Ok(id) => delivery_ids.push((user_id, id)),
+                Err(e) => log::error!("Failed to store report for definition {} and user {}: {}", def.id, user_id, e),
+            }
+        }
+        if delivery_ids.is_empty() {
+            continue;
+        }
+
+        if def.emails() {
+            let queued = async {
+                let mut tx = pool.begin().await?;
+                for &(_, delivery_id) in &delivery_ids {
+                    super::notifications::email::queue_report_delivery(pool, &mut tx, delivery_id, "scheduled").await?;
+                }
+                tx.commit().await
+            };
+            if let Err(e) = queued.await {
+                log::error!("Failed to queue report e-mails for definition {}: {}", def.id, e);
+            }
+        }
+
+        let message = format!("A scheduled report is ready: {}", report.title);
+        let details = serde_json::json!({
+            "definition_id": def.id,
+            "url": "/reports/subscriptions",
+        })
+        .to_string();
+        let warning_id = match super::create_warning(
+            pool, "info", "system", "scheduled.report_definition",
+            &message, &details, "system",
+        ).await {
+            Ok(id) => id,
+            Err(e) => {
+                log::error!("Failed to create report notification: {}", e);
+                continue;
+            }
+        };
+        let targets: Vec<i64> = delivery_ids.iter().map(|(user_id, _)| *user_id).collect();
+        if super::create_receipts(pool, warning_id, &targets).await.is_ok() {
+            super::outbox::drain(pool, conn_map).await;
+        }
+    }
+}
+
+/// Close stale suggestions whose warning period has passed, then warn the
+/// submitters of newly stale ones (see `models::suggestion::sunset`).
+pub async fn sunset_stale_suggestions(pool: &PgPool, conn_map: &ConnectionMap) {
+    let now = super::clock::now();
+    let mut notified = false;
+
+    let to_close = match suggestion::find_to_close(pool, now).await {
+        Ok(rows) => rows,
+        Err(e) => {
+            log::error!("Generator sunset_stale_suggestions query failed: {}", e);
+            return;
+        }
+    };
+    for s in to_close {
+        let reopen_until = match suggestion::auto_close(pool, &s, now).await {
+            Ok(date) => date,
+            Err(e) => {
+                log::error!("Failed to close stale suggestion {}: {}", s.id, e);
+                continue;
+            }
+        };
+        let details = serde_json::json!({
+            "tor_id": s.tor_id,
+            "inactive_days": s.stale_days + s.warning_days,
+            "summary": format!("Closed suggestion #{} after {} days without activity", s.id, s.stale_days + s.warning_days)
+        });
+        let _ = crate::audit::log(pool, 0, "suggestion.auto_closed", "suggestion", s.id, details).await;
+        let message = format!(
+            "Your suggestion '{}' in {} was closed after {} days without activity. You can reopen it until {}.",
+            s.label, s.tor_name, s.stale_days + s.warning_days, reopen_until,
+        );
+        match super::notifications::send(pool, "scheduled.suggestion_closed", &message, &s.link(), &[s.submitted_by_id]).await {
+            Ok(sent) => notified |= sent.is_some(),
+            Err(e) => log::error!("Failed to notify closing of suggestion {}: {}", s.id, e),
+        }
+    }
+
+    let to_warn = match suggestion::find_to_warn(pool, now).await {
+        Ok(rows) => rows,
+        Err(e) => {
+            log::error!("Generator sunset_stale_suggestions query failed: {}", e);
+            return;
+        }
+    };
+    for s in to_warn {
+        let closes_on = match suggestion::mark_warned(pool, &s, now).await {
+            Ok(date) => date,
+            Err(e) => {
+                log::error!("Failed to mark stale suggestion {}: {}", s.id, e);
+                continue;
+            }
+        };
+        let message = format!(
+            "Your suggestion '{}' in {} has had no activity for {} days and will be closed on {} unless someone acts on it.",
+            s.label, s.tor_name, s.stale_days, closes_on,
+        );
+        match super::notifications::send(pool, "scheduled.suggestion_stale", &message, &s.link(), &[s.submitted_by_id]).await {
+            Ok(sent) => notified |= sent.is_some(),
+            Err(e) => log::error!("Failed to warn about stale suggestion {}: {}", s.id, e),
+        }
+    }
+
+    if notified {
+        super::outbox::drain(pool, conn_map).await;
+    }
+}
+
+/// Close recertification campaigns past their deadline. Unconfirmed access
+/// is flagged or revoked as the campaign chose; campaign managers (holders
+/// of `roles.assign`) are told the outcome.
+pub async fn close_recertification_campaigns(pool: &PgPool, conn_map: &ConnectionMap) {
+    let today = super::clock::now().date_naive().format("%Y-%m-%d").to_string();
+    let closed = match recertification::close_overdue(pool, &today).await {
+        Ok(closed) => closed,
+        Err(e) => {
+            log::error!("Generator close_recertification_campaigns failed: {}", e);
+            return;
+        }
+    };
+    if closed.is_empty() {
+        return;
+    }
+
+    let managers = super::get_users_with_permission(pool, "roles.assign").await.unwrap_or_default();
+    let mut notified = false;
+    for (campaign, affected) in closed {
+        if campaign.on_deadline == "revoke" {
+            for item in &affected {
+                let details = serde_json::json!({
+                    "campaign_id": campaign.id,
+                    "user_id": item.user_id,
+                    "position_id": item.position_id,
+                    "summary": format!("Revoked unconfirmed access of {} as {} in {}", item.user_label, item.position_label, item.tor_label)
+                });
+                let _ = crate::audit::log(pool, 0, "recertification.auto_revoked", "tor", item.tor_id, details).await;
+            }
+        }
+        let details = serde_json::json!({
+            "on_deadline": campaign.on_deadline,
+            "unconfirmed": affected.len(),
+            "summary": format!("Closed access recertification '{}' at its deadline with {} unconfirmed items", campaign.label, affected.len())
+        });
+        let _ = crate::audit::log(pool, 0, "recertification.closed", "recert_campaign", campaign.id, details).await;
+
+        let message = format!(
+            "Access recertification '{}' closed at its deadline: {} confirmed, {} unconfirmed {}",
+            campaign.label, campaign.confirmed, affected.len(),
+            if campaign.on_deadline == "revoke" { "and revoked" } else { "and flagged for follow-up" },
+        );
+        let link = format!("/recertifications/{}", campaign.id);
+        match super::notifications::send(pool, "scheduled.recertification_closed", &message, &link, &managers).await {
+            Ok(sent) => notified |= sent.is_some(),
+            Err(e) => log::error!("Failed to notify closing of recertification {}: {}", campaign.id, e),
+        }
+    }
+
+    if notified {
+        super::outbox::drain(pool, conn_map).await;
+    }
+}
+
+/// Take timed workflow auto-transitions whose wait has passed and whose
+/// guards hold. An entity moves at most once per run.
+pub async fn apply_auto_transitions(pool: &PgPool) {
+    let due = match workflow::auto::find_due(pool, super::clock::now()).await {
+        Ok(rows) => rows,
+        Err(e) => {
+            log::error!("Generator apply_auto_transitions query failed: {}", e);
+            return;
+        }
+    };
+    let mut moved: Vec<i64> = Vec::new();
+    for d in due {
+        if moved.contains(&d.entity_id) {
+            continue;
+        }
+        match workflow::auto::guards_hold(pool, &d).await {
+            Ok(true) => {}
+            Ok(false) => continue,
+            Err(e) => {
+                log::error!("Failed to check guards of transition {} for {} {}: {}", d.transition_id, d.scope, d.entity_id, e);
+                continue;
+            }
+        }
+        if let Err(e) = workflow::auto::apply(pool, &d).await {
+            log::error!("Failed to auto-transition {} {}: {}", d.scope, d.entity_id, e);
+            continue;
+        }
+        moved.push(d.entity_id);
+        let details = serde_json::json!({
+            "transition_id": d.transition_id,
+            "from": d.from_status_code,
+            "to": d.to_status_code,
+            "days": d.days,
+            "summary": format!("'{}' moved from {} to {} after {} days ({})",
+                d.entity_label, d.from_status_code, d.to_status_code, d.days, d.transition_label)
+        });
+        let _ = crate::audit::log(pool, 0, "workflow.auto_transition", &d.scope, d.entity_id, details).await;
+    }
+    if !moved.is_empty() {
+        log::info!("Auto-transitioned {} workflow item(s)", moved.len());
+    }
+}
+
 /// Clean up old warnings based on retention settings.
 pub async fn cleanup_old_warnings(pool: &PgPool) -> Result<(), sqlx::Error> {
-    let resolved_days = get_setting_days(pool, "warnings.retention_resolved_days", 30).await;
-    let deleted_days = get_setting_days(pool, "warnings.retention_deleted_days", 7).await;
+    let resolved_retention = setting::get_duration(pool, "warnings.retention_resolved_days").await;
+    let deleted_retention = setting::get_duration(pool, "warnings.retention_deleted_days").await;
 
     // Delete receipts that have been resolved for longer than retention
-    let resolved_cutoff = chrono::Utc::now()
-        .checked_sub_signed(chrono::Duration::days(resolved_days))
+    let resolved_cutoff = super::clock::now()
+        .checked_sub_signed(resolved_retention)
         .map(|dt| dt.format("%Y-%m-%dT%H:%M:%S").to_string())
         .unwrap_or_default();
 
-    let deleted_cutoff = chrono::Utc::now()
-        .checked_sub_signed(chrono::Duration::days(deleted_days))
+    let deleted_cutoff = super::clock::now()
+        .checked_sub_signed(deleted_retention)
         .map(|dt| dt.format("%Y-%m-%dT%H:%M:%S").to_string())
         .unwrap_or_default();
 
@@ -331,20 +839,3 @@ pub async fn cleanup_old_warnings(pool: &PgPool) -> Result<(), sqlx::Error> {
 
     Ok(())
 }
-
-async fn get_setting_days(pool: &PgPool, setting_name: &str, default: i64) -> i64 {
-    let result: Option<(String,)> = sqlx::query_as(
-        "SELECT ep.value FROM entities e
-         JOIN entity_properties ep ON ep.entity_id = e.id AND ep.key = 'value'
-         WHERE e.entity_type = 'setting' AND e.name = $1",
-    )
-    .bind(setting_name)
-    .fetch_optional(pool)
-    .await
-    .ok()
-    .flatten();
-
-    result
-        .and_then(|r| r.0.parse().ok())
-        .unwrap_or(default)
-}
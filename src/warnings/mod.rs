@@ -1,9 +1,12 @@
+pub mod broadcast;
+pub mod clock;
 pub mod generators;
+pub mod notifications;
+pub mod outbox;
 pub mod queries;
 pub mod scheduler;
 
 use sqlx::PgPool;
-use chrono::Utc;
 
 use crate::models::{entity, relation};
 
@@ -17,9 +20,9 @@ pub async fn create_warning(
     details: &str,
     scope: &str,
 ) -> Result<i64, sqlx::Error> {
-    let timestamp = Utc::now().timestamp();
-    let name = format!("{}.{}.{}", source_action, category, timestamp);
-    let warning_id = entity::create(pool, "warning", &name, message).await?;
+    let timestamp = clock::now().timestamp();
+    let base = format!("{}.{}.{}", source_action, category, timestamp);
+    let warning_id = create_uniquely_named(pool, "warning", &base, message).await?;
 
     entity::set_properties(pool, warning_id, &[
         ("severity", severity),
@@ -34,37 +37,72 @@ pub async fn create_warning(
     Ok(warning_id)
 }
 
+/// Create an entity named `base`, appending `.1`, `.2`, … when several
+/// warnings or events land in the same second. Each attempt is a single
+/// conflict-free insert, so concurrent generator runs cannot collide.
+async fn create_uniquely_named(pool: &PgPool, entity_type: &str, base: &str, label: &str) -> Result<i64, sqlx::Error> {
+    let mut name = base.to_string();
+    let mut n = 1;
+    loop {
+        if let Some(id) = entity::create_if_absent(pool, entity_type, &name, label).await? {
+            return Ok(id);
+        }
+        name = format!("{}.{}", base, n);
+        n += 1;
+    }
+}
+
 /// Create receipt entities for each target user. Returns receipt IDs.
+///
+/// The receipts and their live-push delivery are committed together through
+/// the [`outbox`], so recipients are notified even if no dispatcher is
+/// reachable at the moment the warning is raised.
 pub async fn create_receipts(
     pool: &PgPool,
     warning_id: i64,
     target_user_ids: &[i64],
 ) -> Result<Vec<i64>, sqlx::Error> {
-    let now = Utc::now().format("%Y-%m-%dT%H:%M:%S").to_string();
+    if target_user_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+    let now = clock::now().format("%Y-%m-%dT%H:%M:%S").to_string();
+    let timestamp = clock::now().timestamp();
     let mut receipt_ids = Vec::new();
+    let mut tx = pool.begin().await?;
 
     for &user_id in target_user_ids {
         let receipt_name = format!("wr.{}.{}", warning_id, user_id);
-        let receipt_id = entity::create(pool, "warning_receipt", &receipt_name, "Warning Receipt").await?;
+        let receipt_id = entity::create_in_tx(&mut tx, "warning_receipt", &receipt_name, "Warning Receipt").await?;
 
-        entity::set_properties(pool, receipt_id, &[
-            ("status", "unread"),
-            ("status_at", &now),
-        ]).await?;
+        entity::set_property_in_tx(&mut tx, receipt_id, "status", "unread").await?;
+        entity::set_property_in_tx(&mut tx, receipt_id, "status_at", &now).await?;
 
         // Link receipt to warning and user
-        relation::create(pool, "for_warning", receipt_id, warning_id).await?;
-        relation::create(pool, "for_user", receipt_id, user_id).await?;
+        relation::create_in_tx(&mut tx, "for_warning", receipt_id, warning_id).await?;
+        relation::create_in_tx(&mut tx, "for_user", receipt_id, user_id).await?;
 
         // Link warning to target user
-        relation::create(pool, "targets_user", warning_id, user_id).await?;
+        relation::create_in_tx(&mut tx, "targets_user", warning_id, user_id).await?;
 
-        // Create "created" event
-        create_event(pool, receipt_id, "created", user_id, None).await?;
+        // Create "created" event; the receipt is new, so its name is free
+        let event_name = format!("we.{}.created.{}", receipt_id, timestamp);
+        let event_id = entity::create_in_tx(&mut tx, "warning_event", &event_name, "created").await?;
+        entity::set_property_in_tx(&mut tx, event_id, "action", "created").await?;
+        entity::set_property_in_tx(&mut tx, event_id, "actor_user_id", &user_id.to_string()).await?;
+        relation::create_in_tx(&mut tx, "on_receipt", event_id, receipt_id).await?;
 
         receipt_ids.push(receipt_id);
     }
 
+    outbox::enqueue(&mut tx, outbox::CHANNEL_WS, serde_json::json!({
+        "warning_id": warning_id,
+        "user_ids": target_user_ids,
+    })).await?;
+    notifications::email::queue_warning(pool, &mut tx, warning_id, target_user_ids).await?;
+    notifications::push::queue_warning(pool, &mut tx, warning_id, target_user_ids).await?;
+    tx.commit().await?;
+    outbox::wake();
+
     Ok(receipt_ids)
 }
 
@@ -76,9 +114,9 @@ pub async fn create_event(
     actor_user_id: i64,
     note: Option<&str>,
 ) -> Result<i64, sqlx::Error> {
-    let timestamp = Utc::now().timestamp();
-    let event_name = format!("we.{}.{}.{}", receipt_id, action, timestamp);
-    let event_id = entity::create(pool, "warning_event", &event_name, action).await?;
+    let timestamp = clock::now().timestamp();
+    let base = format!("we.{}.{}.{}", receipt_id, action, timestamp);
+    let event_id = create_uniquely_named(pool, "warning_event", &base, action).await?;
 
     entity::set_properties(pool, event_id, &[
         ("action", action),
@@ -138,7 +176,7 @@ pub async fn update_receipt_status(
     new_status: &str,
     actor_user_id: i64,
 ) -> Result<(), sqlx::Error> {
-    let now = Utc::now().format("%Y-%m-%dT%H:%M:%S").to_string();
+    let now = clock::now().format("%Y-%m-%dT%H:%M:%S").to_string();
     entity::set_properties(pool, receipt_id, &[
         ("status", new_status),
         ("status_at", &now),
@@ -153,7 +191,7 @@ pub async fn resolve_warning(pool: &PgPool, warning_id: i64, actor_user_id: i64)
 
     let receipt_ids = get_receipt_ids_for_warning(pool, warning_id).await?;
     for receipt_id in receipt_ids {
-        let now = Utc::now().format("%Y-%m-%dT%H:%M:%S").to_string();
+        let now = clock::now().format("%Y-%m-%dT%H:%M:%S").to_string();
         entity::set_properties(pool, receipt_id, &[
             ("status", "resolved"),
             ("status_at", &now),
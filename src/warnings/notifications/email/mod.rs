@@ -0,0 +1,346 @@
+//! E-mail notifications.
+//!
+//! Warnings and notifications, meeting invitations, proposal status
+//! changes and scheduled reports are rendered through the email templates
+//! (`models::email_template`) and sent over SMTP to users who opted in on
+//! their account page. Each
+//! message is an `email` row in the notification outbox, queued in the
+//! transaction that makes the change; the sender started by
+//! [`spawn_sender`] delivers them and retries with backoff while the mail
+//! server is unreachable.
+//!
+//! Nothing is queued unless the `email.enabled` setting is on.
+
+pub mod smtp;
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Postgres, Transaction};
+
+use crate::models::{email_template, entity, meeting, proposal, relation, setting, tor};
+use crate::warnings::outbox;
+use smtp::SmtpConfig;
+
+/// How often the sender looks for due retries when nothing wakes it.
+const POLL_SECS: u64 = 30;
+
+/// Kinds of e-mail a user can opt in to, as (key, label, description).
+/// The choice is stored on the user as `email_notify_{key}`.
+pub const PREFERENCES: &[(&str, &str, &str)] = &[
+    ("warnings", "Warnings and notifications", "Everything that arrives in your notification center"),
+    ("meetings", "Meeting invitations", "When a meeting of one of your ToRs is confirmed"),
+    ("proposals", "Proposal status", "When someone else moves one of your proposals to a new status"),
+    ("reports", "Scheduled reports", "When a scheduled report sent to one of your groups is ready"),
+];
+
+/// A user's choice for one kind of e-mail, for the account page.
+#[derive(Debug, Clone)]
+pub struct EmailPreference {
+    pub key: &'static str,
+    pub label: &'static str,
+    pub description: &'static str,
+    pub enabled: bool,
+}
+
+fn preference_property(key: &str) -> String {
+    format!("email_notify_{}", key)
+}
+
+/// The user's e-mail preferences, all kinds in display order.
+pub async fn find_preferences(pool: &PgPool, user_id: i64) -> Result<Vec<EmailPreference>, sqlx::Error> {
+    let props = entity::get_properties(pool, user_id).await?;
+    Ok(PREFERENCES.iter()
+        .map(|(key, label, description)| EmailPreference {
+            key,
+            label,
+            description,
+            enabled: props.get(&preference_property(key)).is_some_and(|v| v == "true"),
+        })
+        .collect())
+}
+
+/// Opt the user in to exactly the kinds in `enabled`; unknown keys are ignored.
+pub async fn set_preferences(pool: &PgPool, user_id: i64, enabled: &[String]) -> Result<(), sqlx::Error> {
+    for (key, _, _) in PREFERENCES {
+        let on = enabled.iter().any(|k| k == key);
+        entity::set_property(pool, user_id, &preference_property(key), if on { "true" } else { "false" }).await?;
+    }
+    Ok(())
+}
+
+/// A user who receives a kind of e-mail.
+#[derive(Debug, Clone, PartialEq, sqlx::FromRow)]
+pub struct Recipient {
+    pub user_id: i64,
+    pub name: String,
+    pub email: String,
+}
+
+/// Of `user_ids`, the active users with an address who opted in to `preference`.
+pub async fn find_recipients(pool: &PgPool, user_ids: &[i64], preference: &str) -> Result<Vec<Recipient>, sqlx::Error> {
+    if user_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+    let recipients = sqlx::query_as::<_, Recipient>(
+        "SELECT u.id AS user_id, u.label AS name, p_email.value AS email \
+         FROM entities u \
+         JOIN entity_properties p_email ON p_email.entity_id = u.id AND p_email.key = 'email' \
+         JOIN entity_properties p_pref ON p_pref.entity_id = u.id AND p_pref.key = $2 \
+         WHERE u.entity_type = 'user' AND u.is_active = true AND u.id = ANY($1) \
+           AND p_pref.value = 'true' \
+         ORDER BY u.id",
+    )
+    .bind(user_ids)
+    .bind(preference_property(preference))
+    .fetch_all(pool)
+    .await?;
+    Ok(recipients.into_iter().filter(|r| smtp::is_address(r.email.trim())).collect())
+}
+
+/// A queued message: the template to render and what to fill it with.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedEmail {
+    pub kind: String,
+    pub to: String,
+    pub to_name: String,
+    pub vars: HashMap<String, String>,
+}
+
+/// Values every template may use, plus a helper for absolute links.
+struct Context {
+    app_name: String,
+    base_url: String,
+}
+
+impl Context {
+    async fn load(pool: &PgPool) -> Self {
+        Context {
+            app_name: setting::get_value(pool, "app.name", "Ahlt").await,
+            base_url: setting::get_value(pool, "email.base_url", "").await.trim().trim_end_matches('/').to_string(),
+        }
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+}
+
+/// Queue one message per recipient within the caller's transaction.
+/// Returns how many were queued; wake the outbox after the commit.
+async fn queue(
+    tx: &mut Transaction<'_, Postgres>,
+    ctx: &Context,
+    kind: &str,
+    recipients: &[Recipient],
+    vars: &[(&str, String)],
+) -> Result<usize, sqlx::Error> {
+    for r in recipients {
+        let mut all: HashMap<String, String> = vars.iter().map(|(k, v)| (k.to_string(), v.clone())).collect();
+        all.insert("recipient.name".to_string(), r.name.clone());
+        all.insert("app.name".to_string(), ctx.app_name.clone());
+        let queued = QueuedEmail {
+            kind: kind.to_string(),
+            to: r.email.trim().to_string(),
+            to_name: r.name.clone(),
+            vars: all,
+        };
+        let payload = serde_json::to_value(&queued).unwrap_or_default();
+        outbox::enqueue(tx, outbox::CHANNEL_EMAIL, payload).await?;
+    }
+    Ok(recipients.len())
+}
+
+/// Queue the `notification` e-mail for a warning's recipients.
+pub async fn queue_warning(
+    pool: &PgPool,
+    tx: &mut Transaction<'_, Postgres>,
+    warning_id: i64,
+    user_ids: &[i64],
+) -> Result<usize, sqlx::Error> {
+    if !setting::get_bool(pool, "email.enabled").await {
+        return Ok(0);
+    }
+    let recipients = find_recipients(pool, user_ids, "warnings").await?;
+    if recipients.is_empty() {
+        return Ok(0);
+    }
+    let props = entity::get_properties(pool, warning_id).await?;
+    let prop = |key: &str| props.get(key).cloned().unwrap_or_default();
+    let link = match prop("link") {
+        link if link.is_empty() => format!("/warnings/{}", warning_id),
+        link => link,
+    };
+    let ctx = Context::load(pool).await;
+    let channel = super::channel_label(super::channel_of(&prop("source_action")));
+    let vars = [
+        ("notification.channel", channel.to_string()),
+        ("notification.message", prop("message")),
+        ("notification.url", ctx.url(&link)),
+    ];
+    queue(tx, &ctx, "notification", &recipients, &vars).await
+}
+
+/// Queue the `meeting_invitation` e-mail for the members of a meeting's ToR.
+pub async fn queue_meeting_invitation(
+    pool: &PgPool,
+    tx: &mut Transaction<'_, Postgres>,
+    meeting_id: i64,
+) -> Result<usize, sqlx::Error> {
+    if !setting::get_bool(pool, "email.enabled").await {
+        return Ok(0);
+    }
+    let Some(m) = meeting::find_by_id(pool, meeting_id).await? else {
+        return Ok(0);
+    };
+    let members: Vec<i64> = tor::find_members(pool, m.tor_id).await?
+        .into_iter()
+        .filter_map(|member| member.holder_id)
+        .collect();
+    let recipients = find_recipients(pool, &members, "meetings").await?;
+    if recipients.is_empty() {
+        return Ok(0);
+    }
+    let detail = tor::find_detail_by_id(pool, m.tor_id).await?;
+    let location = if m.location.is_empty() {
+        detail.as_ref().map(|d| d.default_location.clone()).unwrap_or_default()
+    } else {
+        m.location.clone()
+    };
+    let ctx = Context::load(pool).await;
+    let vars = [
+        ("tor.name", m.tor_label.clone()),
+        ("meeting.date", m.meeting_date.clone()),
+        ("meeting.time", detail.map(|d| d.cadence_time).unwrap_or_default()),
+        ("meeting.location", location),
+        ("meeting.url", ctx.url(&format!("/meetings/{}", meeting_id))),
+    ];
+    queue(tx, &ctx, "meeting_invitation", &recipients, &vars).await
+}
+
+/// Queue the `proposal_status` e-mail for a proposal's author, unless they
+/// made the change themselves.
+pub async fn queue_proposal_status(
+    pool: &PgPool,
+    tx: &mut Transaction<'_, Postgres>,
+    proposal_id: i64,
+    status: &str,
+    actor_id: i64,
+) -> Result<usize, sqlx::Error> {
+    if !setting::get_bool(pool, "email.enabled").await {
+        return Ok(0);
+    }
+    let Some(p) = proposal::find_by_id(pool, proposal_id).await.ok().flatten() else {
+        return Ok(0);
+    };
+    if p.submitted_by_id == actor_id {
+        return Ok(0);
+    }
+    let recipients = find_recipients(pool, &[p.submitted_by_id], "proposals").await?;
+    if recipients.is_empty() {
+        return Ok(0);
+    }
+    let tor = relation::find_targets(pool, proposal_id, "submitted_to").await?.into_iter().next();
+    let path = match &tor {
+        Some(t) => format!("/tor/{}/proposals/{}", t.id, proposal_id),
+        None => "/workflow".to_string(),
+    };
+    let ctx = Context::load(pool).await;
+    let vars = [
+        ("proposal.title", p.title.clone()),
+        ("proposal.status", status.replace('_', " ")),
+        ("tor.name", tor.map(|t| t.label).unwrap_or_default()),
+        ("proposal.url", ctx.url(&path)),
+    ];
+    queue(tx, &ctx, "proposal_status", &recipients, &vars).await
+}
+
+/// Queue the `report_delivery` e-mail for one stored report delivery, if its
+/// owner opted in to report e-mails.
+pub async fn queue_report_delivery(
+    pool: &PgPool,
+    tx: &mut Transaction<'_, Postgres>,
+    delivery_id: i64,
+    frequency: &str,
+) -> Result<usize, sqlx::Error> {
+    if !setting::get_bool(pool, "email.enabled").await {
+        return Ok(0);
+    }
+    let Some(d) = crate::models::report_subscription::find_delivery(pool, delivery_id).await? else {
+        return Ok(0);
+    };
+    let recipients = find_recipients(pool, &[d.user_id], "reports").await?;
+    if recipients.is_empty() {
+        return Ok(0);
+    }
+    let ctx = Context::load(pool).await;
+    let vars = [
+        ("report.title", d.title.clone()),
+        ("report.period", format!("{} to {}", d.period_start, d.period_end)),
+        ("report.frequency", frequency.to_string()),
+        ("report.url", ctx.url(&format!("/reports/deliveries/{}", d.id))),
+    ];
+    queue(tx, &ctx, "report_delivery", &recipients, &vars).await
+}
+
+/// Render and send one queued message.
+async fn deliver(pool: &PgPool, config: Option<&SmtpConfig>, payload: &str) -> Result<(), String> {
+    let config = config.ok_or("E-mail is disabled or SMTP is not configured")?;
+    let queued: QueuedEmail = serde_json::from_str(payload).map_err(|e| e.to_string())?;
+    let rendered = email_template::render(pool, &queued.kind, email_template::DEFAULT_LOCALE, &queued.vars).await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Unknown email kind '{}'", queued.kind))?;
+    let message = smtp::Message {
+        to: queued.to,
+        to_name: queued.to_name,
+        subject: rendered.subject,
+        body: rendered.body,
+    };
+    smtp::send(config, &message).await
+}
+
+/// Send every due e-mail. Returns how many were sent.
+pub async fn drain(pool: &PgPool) -> usize {
+    let mut sent = 0;
+    let mut config = None;
+    loop {
+        let rows = match outbox::claim(pool, &[outbox::CHANNEL_EMAIL], true).await {
+            Ok(rows) => rows,
+            Err(e) => {
+                log::error!("Failed to claim queued e-mail: {}", e);
+                return sent;
+            }
+        };
+        if rows.is_empty() {
+            return sent;
+        }
+        if config.is_none() {
+            config = Some(SmtpConfig::load(pool).await);
+        }
+        for row in &rows {
+            let outcome = deliver(pool, config.as_ref().and_then(|c| c.as_ref()), &row.payload).await;
+            if outcome.is_ok() {
+                sent += 1;
+            }
+            if let Err(e) = outbox::record_outcome(pool, row, outcome).await {
+                log::error!("Failed to record e-mail delivery {}: {}", row.id, e);
+            }
+        }
+    }
+}
+
+/// Start the sender alongside the warning scheduler: it sends whenever mail
+/// is queued and polls for retries that have come due.
+pub fn spawn_sender(pool: PgPool) {
+    actix_web::rt::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(POLL_SECS));
+        loop {
+            tokio::select! {
+                _ = outbox::MAIL_WAKE.notified() => {}
+                _ = interval.tick() => {}
+            }
+            drain(&pool).await;
+        }
+    });
+}
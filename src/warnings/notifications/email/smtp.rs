@@ -0,0 +1,286 @@
+//! Minimal SMTP client for outgoing e-mail.
+//!
+//! One message per connection. Supports implicit TLS (usually port 465),
+//! STARTTLS (usually 587) and plain connections to a local relay, with
+//! `AUTH PLAIN` when credentials are configured. TLS uses rustls with the
+//! Mozilla root set. Messages are plain text, UTF-8, base64-encoded.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use sqlx::PgPool;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio_rustls::rustls::{ClientConfig, RootCertStore};
+use tokio_rustls::rustls::pki_types::ServerName;
+use tokio_rustls::TlsConnector;
+
+use crate::models::setting;
+
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How the connection to the server is secured.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Security {
+    /// TLS from the first byte.
+    Tls,
+    /// Plain connection upgraded with `STARTTLS`; refused if the server
+    /// does not offer it.
+    StartTls,
+    /// No encryption, for a relay on the same host or network.
+    None,
+}
+
+impl Security {
+    pub fn from_key(key: &str) -> Option<Self> {
+        match key {
+            "tls" => Some(Security::Tls),
+            "starttls" => Some(Security::StartTls),
+            "none" => Some(Security::None),
+            _ => None,
+        }
+    }
+}
+
+/// Connection details for the SMTP server.
+#[derive(Debug, Clone)]
+pub struct SmtpConfig {
+    pub host: String,
+    pub port: u16,
+    pub security: Security,
+    /// User name and password for `AUTH PLAIN`; none to send unauthenticated.
+    pub credentials: Option<(String, String)>,
+    /// Sender address, used for the envelope and the `From` header.
+    pub from: String,
+}
+
+impl SmtpConfig {
+    /// Load from settings plus the `SMTP_USER` / `SMTP_PASSWORD` environment
+    /// variables. `None` when e-mail is disabled or incompletely configured.
+    pub async fn load(pool: &PgPool) -> Option<Self> {
+        if !setting::get_bool(pool, "email.enabled").await {
+            return None;
+        }
+        let host = setting::get_value(pool, "email.smtp_host", "").await.trim().to_string();
+        let from = setting::get_value(pool, "email.from_address", "").await.trim().to_string();
+        let security = setting::get_value(pool, "email.smtp_security", "starttls").await;
+        let port = setting::get_i64(pool, "email.smtp_port").await;
+        if host.is_empty() || !is_address(&from) {
+            log::warn!("E-mail is enabled but the SMTP host or sender address is not set");
+            return None;
+        }
+        let Some(security) = Security::from_key(security.trim()) else {
+            log::warn!("Unknown SMTP security mode '{}'; use tls, starttls or none", security.trim());
+            return None;
+        };
+        let credentials = match (std::env::var("SMTP_USER"), std::env::var("SMTP_PASSWORD")) {
+            (Ok(user), Ok(password)) if !user.is_empty() => Some((user, password)),
+            _ => None,
+        };
+        Some(Self {
+            host,
+            port: u16::try_from(port).ok()?,
+            security,
+            credentials,
+            from,
+        })
+    }
+}
+
+/// A plain-text message to one recipient.
+#[derive(Debug, Clone)]
+pub struct Message {
+    pub to: String,
+    pub to_name: String,
+    pub subject: String,
+    pub body: String,
+}
+
+/// Whether an address can be put on the envelope as is.
+pub fn is_address(address: &str) -> bool {
+    let Some((local, domain)) = address.rsplit_once('@') else {
+        return false;
+    };
+    !local.is_empty()
+        && domain.contains('.')
+        && address.len() <= 254
+        && !address.chars().any(|c| c.is_whitespace() || c.is_control() || matches!(c, '<' | '>' | ',' | ';'))
+}
+
+/// Header text: control characters become spaces; anything but printable
+/// ASCII is sent as RFC 2047 encoded words.
+fn encode_header(text: &str) -> String {
+    let text: String = text.chars().map(|c| if c.is_control() { ' ' } else { c }).collect();
+    if text.is_ascii() {
+        return text;
+    }
+    // Keep each encoded word within the 75-character limit
+    let mut words = Vec::new();
+    let mut chunk = String::new();
+    for c in text.chars() {
+        if chunk.len() + c.len_utf8() > 45 {
+            words.push(format!("=?utf-8?B?{}?=", STANDARD.encode(&chunk)));
+            chunk.clear();
+        }
+        chunk.push(c);
+    }
+    if !chunk.is_empty() {
+        words.push(format!("=?utf-8?B?{}?=", STANDARD.encode(&chunk)));
+    }
+    words.join("\r\n ")
+}
+
+/// `Name <address>`, quoting or encoding the name as needed.
+fn mailbox(name: &str, address: &str) -> String {
+    let name = name.trim();
+    if name.is_empty() {
+        return format!("<{}>", address);
+    }
+    let display = if name.is_ascii() && name.chars().any(|c| "()<>[]:;@\\,.\"".contains(c)) {
+        format!("\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        encode_header(name)
+    };
+    format!("{} <{}>", display, address)
+}
+
+/// The message as sent after `DATA`, without the terminating dot.
+pub fn format_message(from: &str, message: &Message, date: chrono::DateTime<chrono::Utc>, message_id: &str) -> String {
+    let domain = from.rsplit_once('@').map(|(_, d)| d).unwrap_or("localhost");
+    let body = message.body.replace("\r\n", "\n").replace('\n', "\r\n");
+    let encoded = STANDARD.encode(body.as_bytes());
+    let mut out = String::new();
+    out.push_str(&format!("From: <{}>\r\n", from));
+    out.push_str(&format!("To: {}\r\n", mailbox(&message.to_name, &message.to)));
+    out.push_str(&format!("Subject: {}\r\n", encode_header(&message.subject)));
+    out.push_str(&format!("Date: {}\r\n", date.to_rfc2822()));
+    out.push_str(&format!("Message-ID: <{}@{}>\r\n", message_id, domain));
+    out.push_str("MIME-Version: 1.0\r\n");
+    out.push_str("Content-Type: text/plain; charset=utf-8\r\n");
+    out.push_str("Content-Transfer-Encoding: base64\r\n\r\n");
+    for line in encoded.as_bytes().chunks(76) {
+        out.push_str(std::str::from_utf8(line).unwrap_or(""));
+        out.push_str("\r\n");
+    }
+    out
+}
+
+/// Deliver a message.
+pub async fn send(config: &SmtpConfig, message: &Message) -> Result<(), String> {
+    if !is_address(&message.to) {
+        return Err(format!("Invalid recipient address '{}'", message.to));
+    }
+    tokio::time::timeout(TIMEOUT, send_inner(config, message))
+        .await
+        .map_err(|_| format!("SMTP session with {}:{} timed out", config.host, config.port))?
+}
+
+async fn send_inner(config: &SmtpConfig, message: &Message) -> Result<(), String> {
+    let tcp = TcpStream::connect((config.host.as_str(), config.port)).await
+        .map_err(|e| format!("Cannot connect to {}:{}: {}", config.host, config.port, e))?;
+    match config.security {
+        Security::Tls => {
+            let mut conn = Conn::new(tls_connect(&config.host, tcp).await?);
+            conn.expect(&[220]).await?;
+            conn.command(&ehlo(config), &[250]).await?;
+            conn.transact(config, message).await
+        }
+        Security::StartTls => {
+            let mut conn = Conn::new(tcp);
+            conn.expect(&[220]).await?;
+            let capabilities = conn.command(&ehlo(config), &[250]).await?;
+            if !capabilities.to_ascii_uppercase().contains("STARTTLS") {
+                return Err("SMTP server does not offer STARTTLS".to_string());
+            }
+            conn.command("STARTTLS", &[220]).await?;
+            let mut conn = Conn::new(tls_connect(&config.host, conn.stream.into_inner()).await?);
+            conn.command(&ehlo(config), &[250]).await?;
+            conn.transact(config, message).await
+        }
+        Security::None => {
+            let mut conn = Conn::new(tcp);
+            conn.expect(&[220]).await?;
+            conn.command(&ehlo(config), &[250]).await?;
+            conn.transact(config, message).await
+        }
+    }
+}
+
+fn ehlo(config: &SmtpConfig) -> String {
+    let domain = config.from.rsplit_once('@').map(|(_, d)| d).unwrap_or("localhost");
+    format!("EHLO {}", domain)
+}
+
+async fn tls_connect(
+    host: &str,
+    tcp: TcpStream,
+) -> Result<tokio_rustls::client::TlsStream<TcpStream>, String> {
+    let mut roots = RootCertStore::empty();
+    roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+    let config = ClientConfig::builder().with_root_certificates(roots).with_no_client_auth();
+    let name = ServerName::try_from(host.to_string()).map_err(|e| e.to_string())?;
+    TlsConnector::from(Arc::new(config))
+        .connect(name, tcp)
+        .await
+        .map_err(|e| format!("TLS handshake with {} failed: {}", host, e))
+}
+
+struct Conn<S> {
+    stream: BufReader<S>,
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> Conn<S> {
+    fn new(stream: S) -> Self {
+        Conn { stream: BufReader::new(stream) }
+    }
+
+    /// Read a (possibly multi-line) reply and check its code.
+    async fn expect(&mut self, codes: &[u16]) -> Result<String, String> {
+        let mut text = String::new();
+        loop {
+            let mut line = String::new();
+            let read = self.stream.read_line(&mut line).await.map_err(|e| e.to_string())?;
+            if read == 0 {
+                return Err("SMTP server closed the connection".to_string());
+            }
+            let code = line.get(..3).and_then(|c| c.parse::<u16>().ok())
+                .ok_or_else(|| format!("Malformed SMTP reply: {}", line.trim_end()))?;
+            text.push_str(line.get(4..).unwrap_or("").trim_end());
+            text.push('\n');
+            if line.as_bytes().get(3) != Some(&b'-') {
+                if codes.contains(&code) {
+                    return Ok(text);
+                }
+                return Err(format!("SMTP {}: {}", code, text.trim_end().replace('\n', " ")));
+            }
+        }
+    }
+
+    async fn command(&mut self, line: &str, codes: &[u16]) -> Result<String, String> {
+        self.stream.write_all(format!("{}\r\n", line).as_bytes()).await.map_err(|e| e.to_string())?;
+        self.stream.flush().await.map_err(|e| e.to_string())?;
+        self.expect(codes).await
+    }
+
+    async fn transact(&mut self, config: &SmtpConfig, message: &Message) -> Result<(), String> {
+        if let Some((user, password)) = &config.credentials {
+            let token = STANDARD.encode(format!("\0{}\0{}", user, password));
+            self.command(&format!("AUTH PLAIN {}", token), &[235]).await?;
+        }
+        self.command(&format!("MAIL FROM:<{}>", config.from), &[250]).await?;
+        self.command(&format!("RCPT TO:<{}>", message.to), &[250, 251]).await?;
+        self.command("DATA", &[354]).await?;
+
+        let id = format!("{}.{}", chrono::Utc::now().timestamp_millis(), rand::random::<u32>());
+        let data = format_message(&config.from, message, chrono::Utc::now(), &id);
+        // Dot-stuffing: a line starting with '.' gets another one
+        let data = format!("\r\n{}", data).replace("\r\n.", "\r\n..");
+        self.stream.write_all(&data.as_bytes()[2..]).await.map_err(|e| e.to_string())?;
+        self.command(".", &[250]).await?;
+        // The message is accepted; a failed goodbye does not matter
+        let _ = self.command("QUIT", &[221]).await;
+        Ok(())
+    }
+}
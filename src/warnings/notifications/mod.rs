@@ -0,0 +1,271 @@
+//! Notification center: one inbox per user across channels.
+//!
+//! Every notification is delivered as a warning with a receipt per
+//! recipient, so read/unread state, the unread badge and live updates work
+//! the same for all of them. The channel is derived from the warning's
+//! `source_action` prefix:
+//!
+//! - `mention.*` — someone wrote `@username` in an opinion or suggestion
+//! - `watch.*` — a watched proposal changed status
+//! - `transfer.*` — an ownership transfer was offered, accepted or declined
+//! - `announcement.*` and `event.system.*` — system announcements
+//! - anything else — warnings raised by the warning generators
+//!
+//! Users who opt in also receive notifications by e-mail (see [`email`]),
+//! and high and critical warnings on devices that allow it (see [`push`]).
+
+pub mod email;
+pub mod push;
+
+use sqlx::PgPool;
+
+use crate::models::{entity, relation};
+
+/// Channels as (key, label), in the order the tabs are shown.
+pub const CHANNELS: &[(&str, &str)] = &[
+    ("warning", "Warnings"),
+    ("mention", "Mentions"),
+    ("watch", "Watch updates"),
+    ("transfer", "Transfers"),
+    ("announcement", "Announcements"),
+];
+
+/// Most notifications listed on the page.
+const LIMIT: i64 = 200;
+
+/// The channel a warning is shown under, from its `source_action`.
+pub fn channel_of(source_action: &str) -> &'static str {
+    match source_action.split('.').next().unwrap_or("") {
+        "mention" => "mention",
+        "watch" => "watch",
+        "transfer" => "transfer",
+        "announcement" => "announcement",
+        _ if source_action.starts_with("event.system.") => "announcement",
+        _ => "warning",
+    }
+}
+
+/// Label of a channel key.
+pub fn channel_label(channel: &str) -> &'static str {
+    CHANNELS.iter()
+        .find(|(key, _)| *key == channel)
+        .map(|(_, label)| *label)
+        .unwrap_or("Warnings")
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct Notification {
+    pub receipt_id: i64,
+    pub warning_id: i64,
+    pub source_action: String,
+    pub severity: String,
+    pub message: String,
+    /// Where the notification points; empty for plain warnings.
+    pub link: String,
+    pub status: String,
+    pub created_at: String,
+}
+
+impl Notification {
+    pub fn channel(&self) -> &'static str {
+        channel_of(&self.source_action)
+    }
+
+    pub fn channel_label(&self) -> &'static str {
+        channel_label(self.channel())
+    }
+
+    pub fn is_unread(&self) -> bool {
+        self.status == "unread"
+    }
+
+    /// Page the notification opens: its link, or the warning itself.
+    pub fn href(&self) -> String {
+        if self.link.is_empty() {
+            format!("/warnings/{}", self.warning_id)
+        } else {
+            self.link.clone()
+        }
+    }
+}
+
+/// Deliver a notification to users. `source_action` selects the channel
+/// (e.g. `mention.opinion`); nothing is created without recipients.
+pub async fn send(
+    pool: &PgPool,
+    source_action: &str,
+    message: &str,
+    link: &str,
+    user_ids: &[i64],
+) -> Result<Option<i64>, sqlx::Error> {
+    if user_ids.is_empty() {
+        return Ok(None);
+    }
+    let warning_id = super::create_warning(pool, "info", "notification", source_action, message, "", "user").await?;
+    if !link.is_empty() {
+        entity::set_property(pool, warning_id, "link", link).await?;
+    }
+    super::create_receipts(pool, warning_id, user_ids).await?;
+    Ok(Some(warning_id))
+}
+
+/// The user's notifications, unread first then newest first. Deleted and
+/// resolved ones are left out, and read ones unless `show_read`.
+pub async fn find_for_user(
+    pool: &PgPool,
+    user_id: i64,
+    show_read: bool,
+) -> Result<Vec<Notification>, sqlx::Error> {
+    sqlx::query_as::<_, Notification>(
+        "SELECT receipt.id AS receipt_id, w.id AS warning_id, \
+                COALESCE(wsa.value, '') AS source_action, \
+                COALESCE(wsev.value, 'info') AS severity, \
+                COALESCE(wmsg.value, w.label) AS message, \
+                COALESCE(wlink.value, '') AS link, \
+                rst.value AS status, w.created_at::TEXT AS created_at \
+         FROM entities receipt \
+         JOIN entity_properties rst ON rst.entity_id = receipt.id AND rst.key = 'status' \
+         JOIN relations r_user ON r_user.source_id = receipt.id \
+         JOIN entities rt_user ON rt_user.id = r_user.relation_type_id AND rt_user.name = 'for_user' \
+         JOIN relations r_warn ON r_warn.source_id = receipt.id \
+         JOIN entities rt_warn ON rt_warn.id = r_warn.relation_type_id AND rt_warn.name = 'for_warning' \
+         JOIN entities w ON w.id = r_warn.target_id \
+         LEFT JOIN entity_properties wsa ON wsa.entity_id = w.id AND wsa.key = 'source_action' \
+         LEFT JOIN entity_properties wsev ON wsev.entity_id = w.id AND wsev.key = 'severity' \
+         LEFT JOIN entity_properties wmsg ON wmsg.entity_id = w.id AND wmsg.key = 'message' \
+         LEFT JOIN entity_properties wlink ON wlink.entity_id = w.id AND wlink.key = 'link' \
+         WHERE receipt.entity_type = 'warning_receipt' \
+           AND r_user.target_id = $1 \
+           AND rst.value NOT IN ('deleted', 'resolved') \
+           AND ($2 OR rst.value = 'unread') \
+         ORDER BY CASE rst.value WHEN 'unread' THEN 0 ELSE 1 END, w.created_at DESC, receipt.id DESC \
+         LIMIT $3",
+    )
+    .bind(user_id)
+    .bind(show_read)
+    .bind(LIMIT)
+    .fetch_all(pool)
+    .await
+}
+
+/// Mark one of the user's notifications read. Returns it, or None when the
+/// receipt is not theirs.
+pub async fn mark_read(
+    pool: &PgPool,
+    user_id: i64,
+    receipt_id: i64,
+) -> Result<Option<Notification>, sqlx::Error> {
+    let Some(notification) = find_for_user(pool, user_id, true).await?
+        .into_iter()
+        .find(|n| n.receipt_id == receipt_id)
+    else {
+        return Ok(None);
+    };
+    if notification.is_unread() {
+        super::update_receipt_status(pool, receipt_id, "read", user_id).await?;
+    }
+    Ok(Some(notification))
+}
+
+/// Mark the user's unread notifications read, in one channel or all.
+/// Returns how many were marked.
+pub async fn mark_all_read(
+    pool: &PgPool,
+    user_id: i64,
+    channel: Option<&str>,
+) -> Result<usize, sqlx::Error> {
+    let unread: Vec<Notification> = find_for_user(pool, user_id, false).await?
+        .into_iter()
+        .filter(|n| channel.is_none_or(|c| n.channel() == c))
+        .collect();
+    for n in &unread {
+        super::update_receipt_status(pool, n.receipt_id, "read", user_id).await?;
+    }
+    Ok(unread.len())
+}
+
+/// Usernames written as `@username` in a text, lowercased and deduplicated.
+pub fn mentioned_usernames(text: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for (i, _) in text.match_indices('@') {
+        // An @ inside a word is an e-mail address, not a mention
+        if text[..i].chars().next_back().is_some_and(|c| c.is_alphanumeric()) {
+            continue;
+        }
+        let name: String = text[i + 1..].chars()
+            .take_while(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.'))
+            .collect();
+        let name = name.trim_end_matches('.').to_lowercase();
+        if !name.is_empty() && !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    names
+}
+
+/// Notify users mentioned in `text`, except the author. Returns the users
+/// notified.
+pub async fn notify_mentions(
+    pool: &PgPool,
+    author_id: i64,
+    author_name: &str,
+    text: &str,
+    context: &str,
+    link: &str,
+) -> Result<Vec<i64>, sqlx::Error> {
+    let names = mentioned_usernames(text);
+    if names.is_empty() {
+        return Ok(Vec::new());
+    }
+    let rows: Vec<(i64,)> = sqlx::query_as(
+        "SELECT id FROM entities \
+         WHERE entity_type = 'user' AND is_active = true AND LOWER(name) = ANY($1) AND id != $2 \
+         ORDER BY id",
+    )
+    .bind(&names)
+    .bind(author_id)
+    .fetch_all(pool)
+    .await?;
+    let user_ids: Vec<i64> = rows.into_iter().map(|(id,)| id).collect();
+    let message = format!("{author_name} mentioned you in {context}");
+    send(pool, "mention.text", &message, link, &user_ids).await?;
+    Ok(user_ids)
+}
+
+/// Start following changes to an entity (currently proposals).
+pub async fn watch(pool: &PgPool, user_id: i64, entity_id: i64) -> Result<(), sqlx::Error> {
+    relation::create(pool, "watches", user_id, entity_id).await
+}
+
+pub async fn unwatch(pool: &PgPool, user_id: i64, entity_id: i64) -> Result<(), sqlx::Error> {
+    relation::delete(pool, "watches", user_id, entity_id).await
+}
+
+/// Users watching an entity.
+pub async fn find_watchers(pool: &PgPool, entity_id: i64) -> Result<Vec<i64>, sqlx::Error> {
+    Ok(relation::find_sources(pool, entity_id, "watches").await?
+        .into_iter()
+        .map(|u| u.id)
+        .collect())
+}
+
+pub async fn is_watching(pool: &PgPool, user_id: i64, entity_id: i64) -> Result<bool, sqlx::Error> {
+    Ok(find_watchers(pool, entity_id).await?.contains(&user_id))
+}
+
+/// Tell an entity's watchers, other than the user who made the change,
+/// that it changed. Returns the users notified.
+pub async fn notify_watchers(
+    pool: &PgPool,
+    entity_id: i64,
+    actor_id: i64,
+    message: &str,
+    link: &str,
+) -> Result<Vec<i64>, sqlx::Error> {
+    let watchers: Vec<i64> = find_watchers(pool, entity_id).await?
+        .into_iter()
+        .filter(|&id| id != actor_id)
+        .collect();
+    send(pool, "watch.update", message, link, &watchers).await?;
+    Ok(watchers)
+}
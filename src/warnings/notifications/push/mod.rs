@@ -0,0 +1,351 @@
+//! Web Push notifications.
+//!
+//! A browser that allows notifications registers a subscription from the
+//! account page; from then on high and critical warnings, and a reminder the
+//! day before each confirmed meeting of the user's ToRs, reach that device
+//! through its push service even when no tab is open. Each message is a
+//! `push` row in the notification outbox, one per subscription, so a device
+//! that is offline or a push service that is down only delays its own
+//! messages. Subscriptions the push service reports as gone are removed.
+//!
+//! Nothing is queued unless the `push.enabled` setting is on. The VAPID
+//! public key and contact are settings; the private key is read from the
+//! `VAPID_PRIVATE_KEY` environment variable.
+
+pub mod webpush;
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::{PgPool, Postgres, Transaction};
+
+use crate::models::{entity, meeting, setting, tor};
+use crate::warnings::outbox;
+use webpush::{Outcome, Target, Vapid};
+
+/// How often the sender looks for due retries when nothing wakes it.
+const POLL_SECS: u64 = 30;
+/// Warning severities pushed to devices; the rest stay in the notification center.
+pub const PUSH_SEVERITIES: &[&str] = &["high", "critical"];
+/// How long a push service may hold a message for an offline device.
+const TTL_SECS: u32 = 24 * 3600;
+/// Meeting property recording that its reminder was queued.
+const REMINDER_PROPERTY: &str = "push_reminder_sent";
+
+/// Load the VAPID identity. `None` when push is disabled or the keys are
+/// missing or do not match.
+pub async fn load_vapid(pool: &PgPool) -> Option<Vapid> {
+    if !setting::get_bool(pool, "push.enabled").await {
+        return None;
+    }
+    let subject = setting::get_value(pool, "push.vapid_subject", "").await.trim().to_string();
+    let public_key = setting::get_value(pool, "push.vapid_public_key", "").await;
+    let private_key = std::env::var("VAPID_PRIVATE_KEY").unwrap_or_default();
+    if subject.is_empty() || public_key.trim().is_empty() || private_key.is_empty() {
+        log::warn!("Push is enabled but the VAPID subject or keys are not set");
+        return None;
+    }
+    match Vapid::new(&subject, &public_key, &private_key) {
+        Ok(vapid) => Some(vapid),
+        Err(e) => {
+            log::warn!("Push is enabled but the VAPID keys are unusable: {}", e);
+            None
+        }
+    }
+}
+
+/// A browser registered to receive a user's push messages.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct PushSubscription {
+    pub id: i64,
+    pub user_id: i64,
+    pub endpoint: String,
+    pub p256dh: String,
+    pub auth: String,
+    /// Browser description shown on the account page.
+    pub device: String,
+    pub created_at: String,
+}
+
+impl PushSubscription {
+    fn target(&self) -> Target<'_> {
+        Target { endpoint: &self.endpoint, p256dh: &self.p256dh, auth: &self.auth }
+    }
+}
+
+const SUBSCRIPTION_SELECT: &str =
+    "SELECT e.id, CAST(p_user.value AS BIGINT) AS user_id, p_end.value AS endpoint, \
+            COALESCE(p_key.value, '') AS p256dh, COALESCE(p_auth.value, '') AS auth, \
+            COALESCE(p_dev.value, '') AS device, e.created_at::TEXT AS created_at \
+     FROM entities e \
+     JOIN entity_properties p_user ON p_user.entity_id = e.id AND p_user.key = 'user_id' \
+     JOIN entity_properties p_end ON p_end.entity_id = e.id AND p_end.key = 'endpoint' \
+     LEFT JOIN entity_properties p_key ON p_key.entity_id = e.id AND p_key.key = 'p256dh' \
+     LEFT JOIN entity_properties p_auth ON p_auth.entity_id = e.id AND p_auth.key = 'auth' \
+     LEFT JOIN entity_properties p_dev ON p_dev.entity_id = e.id AND p_dev.key = 'device' \
+     WHERE e.entity_type = 'push_subscription'";
+
+/// Entity name for an endpoint; endpoints are long, so hash them.
+fn subscription_name(endpoint: &str) -> String {
+    format!("push.{}", &hex::encode(Sha256::digest(endpoint.as_bytes()))[..32])
+}
+
+/// Register a browser for the user, replacing any earlier registration of
+/// the same endpoint (including another user's on a shared device).
+pub async fn subscribe(
+    pool: &PgPool,
+    user_id: i64,
+    endpoint: &str,
+    p256dh: &str,
+    auth: &str,
+    device: &str,
+) -> Result<i64, String> {
+    if webpush::audience(endpoint).is_none() {
+        return Err("Push endpoint must be an http(s) URL".to_string());
+    }
+    if webpush::decode(p256dh).map(|k| k.len()) != Ok(65) {
+        return Err("Subscription key must be an uncompressed P-256 public key".to_string());
+    }
+    if webpush::decode(auth).map(|a| a.len()) != Ok(16) {
+        return Err("Subscription secret must be 16 bytes".to_string());
+    }
+    let name = subscription_name(endpoint);
+    let id = match entity::find_by_type_and_name(pool, "push_subscription", &name).await.map_err(|e| e.to_string())? {
+        Some(existing) => existing.id,
+        None => entity::create(pool, "push_subscription", &name, "Push subscription").await.map_err(|e| e.to_string())?,
+    };
+    let user = user_id.to_string();
+    let device: String = device.chars().take(120).collect();
+    entity::set_properties(pool, id, &[
+        ("user_id", &user),
+        ("endpoint", endpoint),
+        ("p256dh", p256dh.trim()),
+        ("auth", auth.trim()),
+        ("device", &device),
+    ])
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(id)
+}
+
+/// Remove the user's registration of an endpoint. Returns whether one existed.
+pub async fn unsubscribe(pool: &PgPool, user_id: i64, endpoint: &str) -> Result<bool, sqlx::Error> {
+    let Some(existing) = entity::find_by_type_and_name(pool, "push_subscription", &subscription_name(endpoint)).await? else {
+        return Ok(false);
+    };
+    if entity::get_property(pool, existing.id, "user_id").await?.as_deref() != Some(&user_id.to_string()) {
+        return Ok(false);
+    }
+    entity::delete(pool, existing.id).await?;
+    Ok(true)
+}
+
+/// The user's registered browsers, newest first.
+pub async fn find_for_user(pool: &PgPool, user_id: i64) -> Result<Vec<PushSubscription>, sqlx::Error> {
+    find_for_users(pool, &[user_id]).await
+}
+
+/// Registered browsers of the active users among `user_ids`.
+pub async fn find_for_users(pool: &PgPool, user_ids: &[i64]) -> Result<Vec<PushSubscription>, sqlx::Error> {
+    if user_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+    let sql = format!(
+        "{} AND CAST(p_user.value AS BIGINT) = ANY($1) \
+           AND EXISTS (SELECT 1 FROM entities u WHERE u.id = CAST(p_user.value AS BIGINT) AND u.is_active = true) \
+         ORDER BY e.id DESC",
+        SUBSCRIPTION_SELECT
+    );
+    sqlx::query_as::<_, PushSubscription>(&sql).bind(user_ids).fetch_all(pool).await
+}
+
+async fn find_by_id(pool: &PgPool, id: i64) -> Result<Option<PushSubscription>, sqlx::Error> {
+    let sql = format!("{} AND e.id = $1", SUBSCRIPTION_SELECT);
+    sqlx::query_as::<_, PushSubscription>(&sql).bind(id).fetch_optional(pool).await
+}
+
+/// A queued message, as the service worker receives it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedPush {
+    pub subscription_id: i64,
+    pub title: String,
+    pub body: String,
+    pub url: String,
+    /// Messages with the same tag replace each other on the device.
+    pub tag: String,
+    pub urgency: String,
+}
+
+/// Queue one message per subscription of `user_ids` within the caller's
+/// transaction. Returns how many were queued; wake the outbox after the commit.
+async fn queue(
+    pool: &PgPool,
+    tx: &mut Transaction<'_, Postgres>,
+    user_ids: &[i64],
+    message: QueuedPush,
+) -> Result<usize, sqlx::Error> {
+    let subscriptions = find_for_users(pool, user_ids).await?;
+    for s in &subscriptions {
+        let queued = QueuedPush { subscription_id: s.id, ..message.clone() };
+        let payload = serde_json::to_value(&queued).unwrap_or_default();
+        outbox::enqueue(tx, outbox::CHANNEL_PUSH, payload).await?;
+    }
+    Ok(subscriptions.len())
+}
+
+/// Queue a push for a warning's recipients when it is high or critical.
+pub async fn queue_warning(
+    pool: &PgPool,
+    tx: &mut Transaction<'_, Postgres>,
+    warning_id: i64,
+    user_ids: &[i64],
+) -> Result<usize, sqlx::Error> {
+    if !setting::get_bool(pool, "push.enabled").await {
+        return Ok(0);
+    }
+    let props = entity::get_properties(pool, warning_id).await?;
+    let prop = |key: &str| props.get(key).cloned().unwrap_or_default();
+    let severity = prop("severity");
+    if !PUSH_SEVERITIES.contains(&severity.as_str()) {
+        return Ok(0);
+    }
+    let url = match prop("link") {
+        link if link.is_empty() => format!("/warnings/{}", warning_id),
+        link => link,
+    };
+    let message = QueuedPush {
+        subscription_id: 0,
+        title: format!("{} warning", capitalize(&severity)),
+        body: prop("message"),
+        url,
+        tag: format!("warning-{}", warning_id),
+        urgency: "high".to_string(),
+    };
+    queue(pool, tx, user_ids, message).await
+}
+
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Queue reminders for confirmed meetings held the day after `today` to the
+/// members of their ToRs. Each meeting is reminded once. Returns how many
+/// messages were queued.
+pub async fn queue_meeting_reminders(pool: &PgPool, today: chrono::NaiveDate) -> Result<usize, sqlx::Error> {
+    if !setting::get_bool(pool, "push.enabled").await {
+        return Ok(0);
+    }
+    let tomorrow = (today + chrono::Duration::days(1)).format("%Y-%m-%d").to_string();
+    let meetings: Vec<meeting::MeetingListItem> = meeting::find_upcoming_all(pool, &tomorrow).await?
+        .into_iter()
+        .filter(|m| m.meeting_date == tomorrow && m.status == "confirmed")
+        .collect();
+    let mut queued = 0;
+    for m in meetings {
+        if entity::get_property(pool, m.id, REMINDER_PROPERTY).await?.is_some() {
+            continue;
+        }
+        let members: Vec<i64> = tor::find_members(pool, m.tor_id).await?
+            .into_iter()
+            .filter_map(|member| member.holder_id)
+            .collect();
+        let time = tor::find_detail_by_id(pool, m.tor_id).await?
+            .map(|d| d.cadence_time)
+            .unwrap_or_default();
+        let message = QueuedPush {
+            subscription_id: 0,
+            title: format!("{} meets tomorrow", m.tor_label),
+            body: if time.is_empty() { m.label.clone() } else { format!("{} at {}", m.label, time) },
+            url: format!("/meetings/{}", m.id),
+            tag: format!("meeting-{}", m.id),
+            urgency: "normal".to_string(),
+        };
+        let mut tx = pool.begin().await?;
+        queued += queue(pool, &mut tx, &members, message).await?;
+        entity::set_property_in_tx(&mut tx, m.id, REMINDER_PROPERTY, &today.to_string()).await?;
+        tx.commit().await?;
+    }
+    if queued > 0 {
+        outbox::wake();
+    }
+    Ok(queued)
+}
+
+/// Send one queued message.
+async fn deliver(pool: &PgPool, vapid: Option<&Vapid>, payload: &str) -> Result<(), String> {
+    let vapid = vapid.ok_or("Push is disabled or VAPID is not configured")?;
+    let queued: QueuedPush = serde_json::from_str(payload).map_err(|e| e.to_string())?;
+    // A browser unsubscribed since the message was queued has nothing to receive it
+    let Some(subscription) = find_by_id(pool, queued.subscription_id).await.map_err(|e| e.to_string())? else {
+        return Ok(());
+    };
+    let mut body = queued.body.clone();
+    let message = loop {
+        let message = serde_json::json!({
+            "title": queued.title, "body": body, "url": queued.url, "tag": queued.tag,
+        })
+        .to_string();
+        if message.len() <= webpush::MAX_PAYLOAD || body.is_empty() {
+            break message;
+        }
+        let keep = body.chars().count() * 3 / 4;
+        body = body.chars().take(keep).collect::<String>() + "…";
+    };
+    match webpush::send(vapid, &subscription.target(), message.as_bytes(), TTL_SECS, &queued.urgency).await? {
+        Outcome::Delivered => Ok(()),
+        Outcome::Gone => {
+            log::info!("Push subscription {} is gone; removing it", subscription.id);
+            entity::delete(pool, subscription.id).await.map_err(|e| e.to_string())
+        }
+    }
+}
+
+/// Send every due push message. Returns how many were sent.
+pub async fn drain(pool: &PgPool) -> usize {
+    let mut sent = 0;
+    let mut vapid = None;
+    loop {
+        let rows = match outbox::claim(pool, &[outbox::CHANNEL_PUSH], true).await {
+            Ok(rows) => rows,
+            Err(e) => {
+                log::error!("Failed to claim queued push messages: {}", e);
+                return sent;
+            }
+        };
+        if rows.is_empty() {
+            return sent;
+        }
+        if vapid.is_none() {
+            vapid = Some(load_vapid(pool).await);
+        }
+        for row in &rows {
+            let outcome = deliver(pool, vapid.as_ref().and_then(|v| v.as_ref()), &row.payload).await;
+            if outcome.is_ok() {
+                sent += 1;
+            }
+            if let Err(e) = outbox::record_outcome(pool, row, outcome).await {
+                log::error!("Failed to record push delivery {}: {}", row.id, e);
+            }
+        }
+    }
+}
+
+/// Start the sender alongside the warning scheduler: it sends whenever a
+/// push is queued and polls for retries that have come due.
+pub fn spawn_sender(pool: PgPool) {
+    actix_web::rt::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(POLL_SECS));
+        loop {
+            tokio::select! {
+                _ = outbox::PUSH_WAKE.notified() => {}
+                _ = interval.tick() => {}
+            }
+            drain(&pool).await;
+        }
+    });
+}
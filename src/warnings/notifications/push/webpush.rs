@@ -0,0 +1,194 @@
+//! Web Push transport.
+//!
+//! Messages are encrypted for the subscribing browser with `aes128gcm`
+//! (RFC 8291) and the request is signed with the application server's VAPID
+//! key (RFC 8292), so any standards-compliant push service — Mozilla,
+//! Google, Apple — accepts it without a vendor account. Each message is
+//! one record; the HTTP request goes through the ticketing client.
+
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes128Gcm, Nonce};
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use hkdf::Hkdf;
+use ring::agreement::{self, EphemeralPrivateKey, UnparsedPublicKey, ECDH_P256};
+use ring::rand::{SecureRandom, SystemRandom};
+use ring::signature::{EcdsaKeyPair, ECDSA_P256_SHA256_FIXED_SIGNING};
+use sha2::Sha256;
+
+use crate::ticketing::http;
+
+/// Record size announced in the header; a message is always one record.
+const RECORD_SIZE: u32 = 4096;
+/// Largest plaintext that fits one record: the size less the padding
+/// delimiter and the 16-byte tag.
+pub const MAX_PAYLOAD: usize = RECORD_SIZE as usize - 17;
+/// How long a VAPID token is valid; push services refuse more than 24 hours.
+const TOKEN_LIFETIME_SECS: i64 = 12 * 3600;
+
+/// The application server's VAPID identity.
+#[derive(Debug, Clone)]
+pub struct Vapid {
+    /// `mailto:` or `https:` contact for the push service operator.
+    pub subject: String,
+    /// Uncompressed P-256 public key (65 bytes).
+    pub public_key: Vec<u8>,
+    /// P-256 private scalar (32 bytes).
+    private_key: Vec<u8>,
+}
+
+impl Vapid {
+    /// Build from base64url keys, checking that they form a P-256 pair.
+    pub fn new(subject: &str, public_key: &str, private_key: &str) -> Result<Self, String> {
+        let public_key = decode(public_key).map_err(|_| "VAPID public key is not base64url")?;
+        let private_key = decode(private_key).map_err(|_| "VAPID private key is not base64url")?;
+        EcdsaKeyPair::from_private_key_and_public_key(
+            &ECDSA_P256_SHA256_FIXED_SIGNING,
+            &private_key,
+            &public_key,
+            &SystemRandom::new(),
+        )
+        .map_err(|_| "VAPID keys are not a P-256 key pair".to_string())?;
+        Ok(Self { subject: subject.to_string(), public_key, private_key })
+    }
+
+    /// The public key as browsers expect it for `applicationServerKey`.
+    pub fn public_key_b64(&self) -> String {
+        URL_SAFE_NO_PAD.encode(&self.public_key)
+    }
+
+    /// Signed JWT for the push service behind `endpoint`, valid from `now`.
+    pub fn token(&self, endpoint: &str, now: i64) -> Result<String, String> {
+        let audience = audience(endpoint).ok_or("Push endpoint is not an http(s) URL")?;
+        let header = URL_SAFE_NO_PAD.encode(br#"{"typ":"JWT","alg":"ES256"}"#);
+        let claims = URL_SAFE_NO_PAD.encode(
+            serde_json::json!({ "aud": audience, "exp": now + TOKEN_LIFETIME_SECS, "sub": self.subject }).to_string(),
+        );
+        let signing_input = format!("{}.{}", header, claims);
+        let rng = SystemRandom::new();
+        let key = EcdsaKeyPair::from_private_key_and_public_key(
+            &ECDSA_P256_SHA256_FIXED_SIGNING,
+            &self.private_key,
+            &self.public_key,
+            &rng,
+        )
+        .map_err(|_| "VAPID keys are not a P-256 key pair")?;
+        let signature = key.sign(&rng, signing_input.as_bytes()).map_err(|_| "Could not sign VAPID token")?;
+        Ok(format!("{}.{}", signing_input, URL_SAFE_NO_PAD.encode(signature.as_ref())))
+    }
+}
+
+/// Decode base64url with or without padding.
+pub fn decode(value: &str) -> Result<Vec<u8>, base64::DecodeError> {
+    URL_SAFE_NO_PAD.decode(value.trim().trim_end_matches('='))
+}
+
+/// The origin of a push endpoint, which VAPID tokens are scoped to.
+pub fn audience(endpoint: &str) -> Option<String> {
+    let (scheme, rest) = endpoint.split_once("://")?;
+    if scheme != "https" && scheme != "http" {
+        return None;
+    }
+    let host = rest.split(['/', '?', '#']).next().unwrap_or("");
+    if host.is_empty() {
+        return None;
+    }
+    Some(format!("{}://{}", scheme, host))
+}
+
+/// Content-encryption key and nonce for one message (RFC 8291 section 3.4).
+pub fn derive_keys(
+    shared_secret: &[u8],
+    auth_secret: &[u8],
+    ua_public: &[u8],
+    as_public: &[u8],
+    salt: &[u8],
+) -> Result<([u8; 16], [u8; 12]), String> {
+    let mut key_info = b"WebPush: info\0".to_vec();
+    key_info.extend_from_slice(ua_public);
+    key_info.extend_from_slice(as_public);
+    let mut ikm = [0u8; 32];
+    Hkdf::<Sha256>::new(Some(auth_secret), shared_secret)
+        .expand(&key_info, &mut ikm)
+        .map_err(|e| e.to_string())?;
+
+    let prk = Hkdf::<Sha256>::new(Some(salt), &ikm);
+    let mut cek = [0u8; 16];
+    let mut nonce = [0u8; 12];
+    prk.expand(b"Content-Encoding: aes128gcm\0", &mut cek).map_err(|e| e.to_string())?;
+    prk.expand(b"Content-Encoding: nonce\0", &mut nonce).map_err(|e| e.to_string())?;
+    Ok((cek, nonce))
+}
+
+/// Encrypt `plaintext` for a subscription's `p256dh` key and `auth` secret.
+/// Returns the request body: the `aes128gcm` header followed by one record.
+pub fn encrypt(p256dh: &[u8], auth_secret: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, String> {
+    if plaintext.len() > MAX_PAYLOAD {
+        return Err(format!("Push payload is {} bytes; the limit is {}", plaintext.len(), MAX_PAYLOAD));
+    }
+    let rng = SystemRandom::new();
+    let mut salt = [0u8; 16];
+    rng.fill(&mut salt).map_err(|_| "No randomness available")?;
+    let private = EphemeralPrivateKey::generate(&ECDH_P256, &rng).map_err(|_| "Could not generate a key")?;
+    let as_public = private.compute_public_key().map_err(|_| "Could not compute the public key")?;
+    let as_public = as_public.as_ref().to_vec();
+    let shared = agreement::agree_ephemeral(private, &UnparsedPublicKey::new(&ECDH_P256, p256dh), |s| s.to_vec())
+        .map_err(|_| "Subscription key is not a P-256 public key")?;
+    let (cek, nonce) = derive_keys(&shared, auth_secret, p256dh, &as_public, &salt)?;
+
+    let mut record = plaintext.to_vec();
+    // Delimiter for the last (and only) record, no padding
+    record.push(0x02);
+    let ciphertext = Aes128Gcm::new_from_slice(&cek)
+        .map_err(|e| e.to_string())?
+        .encrypt(Nonce::from_slice(&nonce), record.as_slice())
+        .map_err(|_| "Encryption failed")?;
+
+    let mut body = Vec::with_capacity(21 + as_public.len() + ciphertext.len());
+    body.extend_from_slice(&salt);
+    body.extend_from_slice(&RECORD_SIZE.to_be_bytes());
+    body.push(as_public.len() as u8);
+    body.extend_from_slice(&as_public);
+    body.extend_from_slice(&ciphertext);
+    Ok(body)
+}
+
+/// Where a subscription stands after a send.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Outcome {
+    Delivered,
+    /// The push service no longer knows the subscription; forget it.
+    Gone,
+}
+
+/// A browser's push subscription.
+#[derive(Debug, Clone)]
+pub struct Target<'a> {
+    pub endpoint: &'a str,
+    pub p256dh: &'a str,
+    pub auth: &'a str,
+}
+
+/// Encrypt and send one message. `ttl` is how long the push service may
+/// hold it for an offline device; `urgency` is `low`, `normal` or `high`.
+pub async fn send(vapid: &Vapid, target: &Target<'_>, payload: &[u8], ttl: u32, urgency: &str) -> Result<Outcome, String> {
+    let p256dh = decode(target.p256dh).map_err(|_| "Subscription key is not base64url")?;
+    let auth = decode(target.auth).map_err(|_| "Subscription secret is not base64url")?;
+    let body = encrypt(&p256dh, &auth, payload)?;
+    let token = vapid.token(target.endpoint, chrono::Utc::now().timestamp())?;
+    let authorization = format!("vapid t={}, k={}", token, vapid.public_key_b64());
+    let ttl = ttl.to_string();
+    let headers = [
+        ("Authorization", authorization.as_str()),
+        ("Content-Type", "application/octet-stream"),
+        ("Content-Encoding", "aes128gcm"),
+        ("TTL", ttl.as_str()),
+        ("Urgency", urgency),
+    ];
+    let response = http::send_bytes("POST", target.endpoint, &headers, Some(&body)).await?;
+    match response.status {
+        404 | 410 => Ok(Outcome::Gone),
+        _ if response.is_success() => Ok(Outcome::Delivered),
+        status => Err(format!("HTTP {}: {}", status, response.body.chars().take(200).collect::<String>())),
+    }
+}
@@ -0,0 +1,302 @@
+//! Notification outbox.
+//!
+//! Deliveries are written to `notification_outbox` in the same transaction
+//! as the receipts they announce, then a dispatcher drains the table and
+//! hands each row to its channel's transport. A row that cannot be delivered
+//! stays pending and is retried with backoff, so a notification is never
+//! lost because a transport was down when the data changed. Rows that keep
+//! failing are parked as `failed` and can be replayed from `/outbox`.
+//!
+//! Channels: `ws` pushes a new warning to connected users, `dashboard`
+//! refreshes their dashboard counters, `email` sends one message over
+//! SMTP and `push` sends one Web Push message to a browser. E-mail and Web
+//! Push have their own senders (see `notifications::email` and
+//! `notifications::push`) so a slow mail or push service never holds up
+//! live updates. Outbound webhooks are not an
+//! outbox channel: they read the domain event stream directly (see
+//! `models::outbound_webhook`).
+
+use std::sync::LazyLock;
+use std::time::Duration;
+
+use serde::Serialize;
+use sqlx::{PgPool, Postgres, Transaction};
+use tokio::sync::Notify;
+
+use crate::handlers::warning_handlers::ws::{self, ConnectionMap};
+
+/// Push a warning to its recipients' open sockets.
+pub const CHANNEL_WS: &str = "ws";
+/// Push fresh dashboard counters to every open socket after a domain event.
+pub const CHANNEL_DASHBOARD: &str = "dashboard";
+/// Send one e-mail; drained by the e-mail sender, not the dispatcher.
+pub const CHANNEL_EMAIL: &str = "email";
+/// Send one Web Push message; drained by the push sender, not the dispatcher.
+pub const CHANNEL_PUSH: &str = "push";
+
+/// Attempts before a row is parked as failed.
+pub const MAX_ATTEMPTS: i32 = 10;
+/// Rows claimed per round.
+const BATCH_SIZE: i64 = 50;
+/// A row left `sending` this long belongs to a dispatcher that died; reclaim it.
+const STALE_CLAIM_SECS: i64 = 300;
+/// Longest wait between retries.
+const MAX_BACKOFF_SECS: i64 = 300;
+/// How often the dispatcher looks for due retries when nothing wakes it.
+const POLL_SECS: u64 = 15;
+/// How long delivered rows are kept for inspection.
+pub const DELIVERED_RETENTION_DAYS: i64 = 7;
+
+static WAKE: LazyLock<Notify> = LazyLock::new(Notify::new);
+/// Wakes the e-mail sender.
+pub(crate) static MAIL_WAKE: LazyLock<Notify> = LazyLock::new(Notify::new);
+/// Wakes the Web Push sender.
+pub(crate) static PUSH_WAKE: LazyLock<Notify> = LazyLock::new(Notify::new);
+
+/// An outbox row, for the admin page.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct OutboxEntry {
+    pub id: i64,
+    pub channel: String,
+    pub payload: String,
+    pub status: String,
+    pub attempts: i32,
+    pub last_error: String,
+    pub available_at: String,
+    pub created_at: String,
+    pub delivered_at: Option<String>,
+}
+
+impl OutboxEntry {
+    /// Delivered and in-flight rows are not replayed.
+    pub fn can_replay(&self) -> bool {
+        self.status == "failed" || self.status == "pending"
+    }
+}
+
+#[derive(sqlx::FromRow)]
+pub(crate) struct ClaimedRow {
+    pub id: i64,
+    pub channel: String,
+    pub payload: String,
+    pub attempts: i32,
+}
+
+/// Queue a delivery within the transaction making the change it announces.
+/// Call [`wake`] after the transaction commits.
+pub async fn enqueue(
+    tx: &mut Transaction<'_, Postgres>,
+    channel: &str,
+    payload: serde_json::Value,
+) -> Result<i64, sqlx::Error> {
+    let (id,): (i64,) = sqlx::query_as(
+        "INSERT INTO notification_outbox (channel, payload) VALUES ($1, $2::JSONB) RETURNING id",
+    )
+    .bind(channel)
+    .bind(payload.to_string())
+    .fetch_one(&mut **tx)
+    .await?;
+    Ok(id)
+}
+
+/// Tell the dispatcher and the e-mail and push senders there is new work.
+pub fn wake() {
+    WAKE.notify_one();
+    MAIL_WAKE.notify_one();
+    PUSH_WAKE.notify_one();
+}
+
+/// Seconds to wait before the next attempt after `attempts` failures.
+pub fn backoff_secs(attempts: i32) -> i64 {
+    2_i64.saturating_pow(attempts.clamp(0, 16) as u32).min(MAX_BACKOFF_SECS)
+}
+
+/// Claim due rows so that concurrent dispatchers never deliver one twice:
+/// rows of `channels` when `include`, all others otherwise.
+pub(crate) async fn claim(pool: &PgPool, channels: &[&str], include: bool) -> Result<Vec<ClaimedRow>, sqlx::Error> {
+    sqlx::query_as::<_, ClaimedRow>(
+        "UPDATE notification_outbox \
+         SET status = 'sending', attempts = attempts + 1, claimed_at = NOW() \
+         WHERE id IN ( \
+             SELECT id FROM notification_outbox \
+             WHERE ((status = 'pending' AND available_at <= NOW()) \
+                OR (status = 'sending' AND claimed_at < NOW() - make_interval(secs => $1))) \
+               AND (channel = ANY($3)) = $4 \
+             ORDER BY id LIMIT $2 \
+             FOR UPDATE SKIP LOCKED) \
+         RETURNING id, channel, payload::TEXT AS payload, attempts",
+    )
+    .bind(STALE_CLAIM_SECS as f64)
+    .bind(BATCH_SIZE)
+    .bind(channels)
+    .bind(include)
+    .fetch_all(pool)
+    .await
+}
+
+async fn deliver(pool: &PgPool, conn_map: &ConnectionMap, row: &ClaimedRow) -> Result<(), String> {
+    let payload: serde_json::Value = serde_json::from_str(&row.payload).map_err(|e| e.to_string())?;
+    match row.channel.as_str() {
+        CHANNEL_WS => {
+            if conn_map.read().is_err() {
+                return Err("Connection map unavailable".to_string());
+            }
+            let warning_id = payload["warning_id"].as_i64().ok_or("Payload has no warning_id")?;
+            let user_ids: Vec<i64> = payload["user_ids"].as_array()
+                .map(|ids| ids.iter().filter_map(|v| v.as_i64()).collect())
+                .unwrap_or_default();
+            // A warning deleted since it was queued has nothing left to announce
+            let Some(warning) = super::queries::get_warning_detail(pool, warning_id).await
+                .map_err(|e| e.to_string())? else {
+                return Ok(());
+            };
+            ws::notify_users(conn_map, pool, &user_ids, warning_id, &warning.severity, &warning.message).await;
+            Ok(())
+        }
+        CHANNEL_DASHBOARD => {
+            let user_ids: Vec<i64> = conn_map.read()
+                .map_err(|_| "Connection map unavailable".to_string())?
+                .keys()
+                .copied()
+                .collect();
+            for user_id in user_ids {
+                ws::send_dashboard_counts(conn_map, pool, user_id).await;
+            }
+            Ok(())
+        }
+        other => Err(format!("No transport for channel '{}'", other)),
+    }
+}
+
+pub(crate) async fn record_outcome(pool: &PgPool, row: &ClaimedRow, outcome: Result<(), String>) -> Result<(), sqlx::Error> {
+    match outcome {
+        Ok(()) => {
+            sqlx::query(
+                "UPDATE notification_outbox SET status = 'delivered', delivered_at = NOW(), last_error = '' \
+                 WHERE id = $1",
+            )
+            .bind(row.id)
+            .execute(pool)
+            .await?;
+        }
+        Err(error) => {
+            let status = if row.attempts >= MAX_ATTEMPTS { "failed" } else { "pending" };
+            sqlx::query(
+                "UPDATE notification_outbox \
+                 SET status = $2, last_error = $3, available_at = NOW() + make_interval(secs => $4) \
+                 WHERE id = $1",
+            )
+            .bind(row.id)
+            .bind(status)
+            .bind(&error)
+            .bind(backoff_secs(row.attempts) as f64)
+            .execute(pool)
+            .await?;
+            log::warn!("Outbox {} delivery {} failed (attempt {}): {}", row.channel, row.id, row.attempts, error);
+        }
+    }
+    Ok(())
+}
+
+/// Deliver every due row except e-mail and push. Returns how many were delivered.
+pub async fn drain(pool: &PgPool, conn_map: &ConnectionMap) -> usize {
+    let mut delivered = 0;
+    loop {
+        let rows = match claim(pool, &[CHANNEL_EMAIL, CHANNEL_PUSH], false).await {
+            Ok(rows) => rows,
+            Err(e) => {
+                log::error!("Failed to claim outbox rows: {}", e);
+                return delivered;
+            }
+        };
+        if rows.is_empty() {
+            return delivered;
+        }
+        for row in &rows {
+            let outcome = deliver(pool, conn_map, row).await;
+            if outcome.is_ok() {
+                delivered += 1;
+            }
+            if let Err(e) = record_outcome(pool, row, outcome).await {
+                log::error!("Failed to record outbox delivery {}: {}", row.id, e);
+            }
+        }
+    }
+}
+
+/// Requeue a pending or failed row for immediate delivery.
+pub async fn replay(pool: &PgPool, id: i64) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "UPDATE notification_outbox \
+         SET status = 'pending', attempts = 0, available_at = NOW(), last_error = '' \
+         WHERE id = $1 AND status IN ('pending', 'failed')",
+    )
+    .bind(id)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Requeue every failed row. Returns how many were requeued.
+pub async fn replay_failed(pool: &PgPool) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        "UPDATE notification_outbox \
+         SET status = 'pending', attempts = 0, available_at = NOW(), last_error = '' \
+         WHERE status = 'failed'",
+    )
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+/// Delete delivered rows older than `before`. Returns how many were removed.
+pub async fn cleanup(pool: &PgPool, before: chrono::DateTime<chrono::Utc>) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        "DELETE FROM notification_outbox WHERE status = 'delivered' AND delivered_at < $1::TIMESTAMPTZ",
+    )
+    .bind(before.to_rfc3339())
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+/// Most recent rows, newest first, optionally only those with `status`.
+pub async fn find_recent(pool: &PgPool, status: Option<&str>, limit: i64) -> Result<Vec<OutboxEntry>, sqlx::Error> {
+    sqlx::query_as::<_, OutboxEntry>(
+        "SELECT id, channel, payload::TEXT AS payload, status, attempts, last_error, \
+                to_char(available_at, 'YYYY-MM-DD HH24:MI:SS') AS available_at, \
+                to_char(created_at, 'YYYY-MM-DD HH24:MI:SS') AS created_at, \
+                to_char(delivered_at, 'YYYY-MM-DD HH24:MI:SS') AS delivered_at \
+         FROM notification_outbox \
+         WHERE ($1::TEXT IS NULL OR status = $1) \
+         ORDER BY id DESC LIMIT $2",
+    )
+    .bind(status)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+}
+
+/// Row counts per status.
+pub async fn count_by_status(pool: &PgPool) -> Result<Vec<(String, i64)>, sqlx::Error> {
+    sqlx::query_as(
+        "SELECT status, COUNT(*) FROM notification_outbox GROUP BY status ORDER BY status",
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Start the dispatcher: it drains the outbox whenever a delivery is queued,
+/// and polls for retries that have come due.
+pub fn spawn_dispatcher(pool: PgPool, conn_map: ConnectionMap) {
+    actix_web::rt::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(POLL_SECS));
+        loop {
+            tokio::select! {
+                _ = WAKE.notified() => {}
+                _ = interval.tick() => {}
+            }
+            drain(&pool, &conn_map).await;
+        }
+    });
+}
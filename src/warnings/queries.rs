@@ -316,3 +316,14 @@ pub async fn find_receipt_for_user(pool: &PgPool, warning_id: i64, user_id: i64)
     .await?;
     Ok(row.map(|r| r.0))
 }
+
+/// Current status of a receipt ("unread" when never set).
+pub async fn get_receipt_status(pool: &PgPool, receipt_id: i64) -> Result<String, sqlx::Error> {
+    let row: Option<(String,)> = sqlx::query_as(
+        "SELECT value FROM entity_properties WHERE entity_id = $1 AND key = 'status'",
+    )
+    .bind(receipt_id)
+    .fetch_optional(pool)
+    .await?;
+    Ok(row.map(|r| r.0).unwrap_or_else(|| "unread".to_string()))
+}
@@ -1,21 +1,273 @@
+//! Background job scheduler.
+//!
+//! The jobs are listed in [`JOBS`] with how often each runs. Their state is
+//! kept in `scheduler_job` entities (see [`crate::models::scheduler_job`]),
+//! so admins can see next run times, durations and failures on /scheduler,
+//! pause and resume jobs, and ask for a run now. The loop checks for due
+//! jobs every [`TICK_SECS`] seconds, and at once after [`wake`].
+//!
+//! Each run is its own task: a job that panics is recorded as failed, with
+//! the panic location and backtrace as context, and the other jobs go on.
+
+use std::cell::RefCell;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::LazyLock;
 use std::time::Duration;
+
+use chrono::Utc;
 use sqlx::PgPool;
+use tokio::sync::Notify;
+
 use crate::handlers::warning_handlers::ws::ConnectionMap;
+use crate::models::scheduler_job::{self, RunOutcome};
+
+use super::generators;
+
+/// Seconds between checks for due jobs.
+pub const TICK_SECS: u64 = 30;
+
+static WAKE: LazyLock<Notify> = LazyLock::new(Notify::new);
+
+thread_local! {
+    /// Where the last panic on this thread happened, with its backtrace.
+    static LAST_PANIC: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Why a job run failed: the error, and the context it was raised in.
+pub struct JobFailure {
+    pub error: String,
+    pub context: String,
+}
+
+impl JobFailure {
+    /// Wrap an error raised at `step`; the context is the error's debug
+    /// form, which keeps the source chain (e.g. the database error code).
+    fn at<E: std::fmt::Display + std::fmt::Debug>(step: &str, e: E) -> Self {
+        JobFailure { error: format!("{}: {}", step, e), context: format!("{:?}", e) }
+    }
+}
+
+type JobResult = Result<String, JobFailure>;
+type JobFuture = Pin<Box<dyn Future<Output = JobResult>>>;
+
+/// A background job: a stable key, a label for the admin page, how often
+/// it runs, and the work it does. `run` returns a summary of what it did.
+pub struct Job {
+    pub key: &'static str,
+    pub label: &'static str,
+    pub every_secs: u64,
+    run: fn(PgPool, ConnectionMap) -> JobFuture,
+}
+
+/// Count-style summary, empty when nothing happened.
+fn summary(n: u64, what: &str) -> String {
+    if n > 0 { format!("{} {}", n, what) } else { String::new() }
+}
+
+pub const JOBS: &[Job] = &[
+    Job { key: "users_without_role", label: "Users without a role", every_secs: 300, run: |pool, conn_map| Box::pin(async move {
+        generators::check_users_without_role(&pool, &conn_map).await;
+        Ok(String::new())
+    }) },
+    Job { key: "database_size", label: "Database size", every_secs: 300, run: |pool, conn_map| Box::pin(async move {
+        generators::check_database_size(&pool, &conn_map).await;
+        Ok(String::new())
+    }) },
+    Job { key: "tor_vacancies", label: "ToR vacancies", every_secs: 300, run: |pool, conn_map| Box::pin(async move {
+        generators::check_tor_vacancies(&pool, &conn_map).await;
+        Ok(String::new())
+    }) },
+    Job { key: "overdue_action_items", label: "Overdue action items", every_secs: 300, run: |pool, conn_map| Box::pin(async move {
+        generators::check_overdue_action_items(&pool, &conn_map).await;
+        Ok(String::new())
+    }) },
+    Job { key: "quorum_misses", label: "Missed quorums", every_secs: 300, run: |pool, conn_map| Box::pin(async move {
+        generators::check_quorum_misses(&pool, &conn_map).await;
+        Ok(String::new())
+    }) },
+    Job { key: "recertification_campaigns", label: "Close recertification campaigns", every_secs: 300, run: |pool, conn_map| Box::pin(async move {
+        generators::close_recertification_campaigns(&pool, &conn_map).await;
+        Ok(String::new())
+    }) },
+    Job { key: "report_subscriptions", label: "Report subscriptions", every_secs: 300, run: |pool, conn_map| Box::pin(async move {
+        generators::deliver_report_subscriptions(&pool, &conn_map).await;
+        Ok(String::new())
+    }) },
+    Job { key: "report_definitions", label: "Scheduled report definitions", every_secs: 300, run: |pool, conn_map| Box::pin(async move {
+        generators::deliver_report_definitions(&pool, &conn_map).await;
+        Ok(String::new())
+    }) },
+    Job { key: "suggestion_sunset", label: "Sunset stale suggestions", every_secs: 300, run: |pool, conn_map| Box::pin(async move {
+        generators::sunset_stale_suggestions(&pool, &conn_map).await;
+        Ok(String::new())
+    }) },
+    Job { key: "workflow_auto_transitions", label: "Workflow auto-transitions", every_secs: 300, run: |pool, _| Box::pin(async move {
+        generators::apply_auto_transitions(&pool).await;
+        Ok(String::new())
+    }) },
+    Job { key: "suggestion_clusters", label: "Suggestion clustering", every_secs: 300, run: |pool, _| Box::pin(async move {
+        let n = crate::models::suggestion::refresh_clusters(&pool).await
+            .map_err(|e| JobFailure::at("Suggestion clustering", e))?;
+        Ok(summary(n as u64, "new suggestion cluster(s)"))
+    }) },
+    Job { key: "broadcasts", label: "Broadcasts", every_secs: 300, run: |pool, _| Box::pin(async move {
+        let (sent, expired) = super::broadcast::run_due(&pool).await
+            .map_err(|e| JobFailure::at("Broadcast run", e))?;
+        Ok(if sent + expired > 0 { format!("{} sent, {} expired", sent, expired) } else { String::new() })
+    }) },
+    Job { key: "outbound_webhooks", label: "Outbound webhooks", every_secs: 300, run: |pool, _| Box::pin(async move {
+        let (delivered, failed) = crate::models::outbound_webhook::run_due(&pool).await
+            .map_err(|e| JobFailure::at("Outbound webhook run", e))?;
+        Ok(if delivered + failed > 0 { format!("{} delivered, {} failed", delivered, failed) } else { String::new() })
+    }) },
+    Job { key: "meeting_reminders", label: "Meeting reminders", every_secs: 300, run: |pool, _| Box::pin(async move {
+        let n = super::notifications::push::queue_meeting_reminders(&pool, super::clock::now().date_naive()).await
+            .map_err(|e| JobFailure::at("Meeting reminder run", e))?;
+        Ok(summary(n as u64, "meeting reminder push message(s) queued"))
+    }) },
+    Job { key: "sla_compliance", label: "SLA compliance", every_secs: 300, run: |pool, _| Box::pin(async move {
+        let measured = crate::models::sla_pack::run_nightly(&pool, super::clock::now().date_naive()).await
+            .map_err(|e| JobFailure::at("SLA compliance run", e))?;
+        Ok(measured.map(|n| format!("Measured SLA compliance for {} ToR(s)", n)).unwrap_or_default())
+    }) },
+    Job { key: "warning_cleanup", label: "Warning cleanup", every_secs: 300, run: |pool, _| Box::pin(async move {
+        generators::cleanup_old_warnings(&pool).await
+            .map_err(|e| JobFailure::at("Warning cleanup", e))?;
+        Ok(String::new())
+    }) },
+    Job { key: "outbox_cleanup", label: "Outbox cleanup", every_secs: 300, run: |pool, _| Box::pin(async move {
+        let cutoff = Utc::now() - chrono::Duration::days(super::outbox::DELIVERED_RETENTION_DAYS);
+        let n = super::outbox::cleanup(&pool, cutoff).await
+            .map_err(|e| JobFailure::at("Outbox cleanup", e))?;
+        Ok(summary(n, "delivered outbox rows cleaned up"))
+    }) },
+    Job { key: "form_draft_cleanup", label: "Form draft cleanup", every_secs: 300, run: |pool, _| Box::pin(async move {
+        let cutoff = Utc::now() - chrono::Duration::days(crate::models::form_draft::RETENTION_DAYS);
+        let n = crate::models::form_draft::cleanup(&pool, cutoff).await
+            .map_err(|e| JobFailure::at("Form draft cleanup", e))?;
+        Ok(summary(n, "abandoned form drafts cleaned up"))
+    }) },
+    Job { key: "api_usage_cleanup", label: "API usage cleanup", every_secs: 300, run: |pool, _| Box::pin(async move {
+        let retention = crate::models::setting::get_duration(&pool, "api.usage_retention_days").await;
+        let n = crate::models::api_usage::cleanup(&pool, Utc::now() - retention).await
+            .map_err(|e| JobFailure::at("API usage cleanup", e))?;
+        Ok(summary(n, "API usage buckets cleaned up"))
+    }) },
+    Job { key: "access_log_cleanup", label: "Access log cleanup", every_secs: 300, run: |pool, _| Box::pin(async move {
+        let retention = crate::models::setting::get_duration(&pool, "audit.read_retention_days").await;
+        let n = crate::models::access_log::cleanup(&pool, Utc::now() - retention).await
+            .map_err(|e| JobFailure::at("Access log cleanup", e))?;
+        Ok(summary(n, "access log entries cleaned up"))
+    }) },
+    Job { key: "trash_purge", label: "Trash purge", every_secs: 300, run: |pool, _| Box::pin(async move {
+        let retention = crate::models::setting::get_duration(&pool, "trash.retention_days").await;
+        let n = crate::models::trash::purge_expired(&pool, Utc::now() - retention).await
+            .map_err(|e| JobFailure::at("Trash purge", e))?;
+        Ok(summary(n, "expired trash entries purged"))
+    }) },
+    Job { key: "signing_key_retirement", label: "Signing key retirement", every_secs: 300, run: |pool, _| Box::pin(async move {
+        let grace = crate::models::setting::get_duration(&pool, "security.signing_key_grace_days").await;
+        let n = crate::models::signing_key::retire_expired(&pool, Utc::now() - grace).await
+            .map_err(|e| JobFailure::at("Signing key retirement", e))?;
+        Ok(summary(n, "rotated signing keys retired"))
+    }) },
+    Job { key: "delegation_cleanup", label: "Expired delegations", every_secs: 300, run: |pool, _| Box::pin(async move {
+        let n = crate::models::tor::cleanup_expired_delegations(&pool, chrono::Local::now().date_naive()).await
+            .map_err(|e| JobFailure::at("Position delegation cleanup", e))?;
+        Ok(summary(n, "expired position delegations removed"))
+    }) },
+];
+
+/// The job with `key`.
+pub fn find_job(key: &str) -> Option<&'static Job> {
+    JOBS.iter().find(|job| job.key == key)
+}
+
+/// Tell the scheduler to check for due jobs now, e.g. after a run was requested.
+pub fn wake() {
+    WAKE.notify_one();
+}
+
+/// Record each panic's location and backtrace for the thread it happened on,
+/// then defer to the previous hook.
+fn install_panic_hook() {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let location = info.location().map(|l| l.to_string()).unwrap_or_default();
+        let backtrace = std::backtrace::Backtrace::force_capture();
+        LAST_PANIC.with(|p| *p.borrow_mut() = Some(format!("panicked at {}\n{}", location, backtrace)));
+        previous(info);
+    }));
+}
+
+fn panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+    payload.downcast_ref::<&str>().map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string())
+}
+
+/// Run one job in its own task and record the outcome.
+pub async fn run_job(pool: &PgPool, conn_map: &ConnectionMap, job: &Job, id: i64) {
+    let started_at = Utc::now();
+    if let Err(e) = scheduler_job::record_start(pool, id, started_at).await {
+        log::error!("Failed to record start of job {}: {}", job.key, e);
+        return;
+    }
+    let timer = std::time::Instant::now();
+    let outcome = match actix_web::rt::spawn((job.run)(pool.clone(), conn_map.clone())).await {
+        Ok(Ok(message)) => RunOutcome::Succeeded(message),
+        Ok(Err(failure)) => RunOutcome::Failed { error: failure.error, context: failure.context },
+        Err(e) if e.is_panic() => RunOutcome::Failed {
+            error: format!("Panicked: {}", panic_message(e.into_panic())),
+            context: LAST_PANIC.with(|p| p.borrow_mut().take()).unwrap_or_default(),
+        },
+        Err(e) => RunOutcome::Failed { error: e.to_string(), context: String::new() },
+    };
+    let duration_ms = timer.elapsed().as_millis() as i64;
+    match &outcome {
+        RunOutcome::Succeeded(message) if !message.is_empty() => log::info!("{}: {}", job.label, message),
+        RunOutcome::Succeeded(_) => {}
+        RunOutcome::Failed { error, .. } => log::error!("Job {} failed: {}", job.key, error),
+    }
+
+    let next_run_at = started_at + chrono::Duration::seconds(job.every_secs as i64);
+    if let Err(e) = scheduler_job::record_finish(pool, id, duration_ms, &outcome, Utc::now(), next_run_at).await {
+        log::error!("Failed to record run of job {}: {}", job.key, e);
+    }
+}
+
+/// Run every job that is due, in [`JOBS`] order.
+pub async fn run_due_jobs(pool: &PgPool, conn_map: &ConnectionMap) {
+    let states = match scheduler_job::find_all(pool).await {
+        Ok(states) => states,
+        Err(e) => {
+            log::error!("Failed to load scheduler job state: {}", e);
+            return;
+        }
+    };
+    let now = Utc::now();
+    for job in JOBS {
+        if let Some(state) = states.iter().find(|s| s.key == job.key && s.is_due(now)) {
+            run_job(pool, conn_map, job, state.id).await;
+        }
+    }
+}
 
 pub fn spawn_scheduler(pool: PgPool, conn_map: ConnectionMap) {
+    install_panic_hook();
     actix_web::rt::spawn(async move {
-        let mut interval = tokio::time::interval(Duration::from_secs(300)); // 5 minutes
+        let jobs: Vec<(&str, &str, i64)> = JOBS.iter().map(|j| (j.key, j.label, j.every_secs as i64)).collect();
+        if let Err(e) = scheduler_job::register(&pool, &jobs, Utc::now()).await {
+            log::error!("Failed to register scheduler jobs: {}", e);
+        }
+        let mut interval = tokio::time::interval(Duration::from_secs(TICK_SECS));
         loop {
-            interval.tick().await;
-            log::info!("Running warning scheduler");
-            // Run generators
-            super::generators::check_users_without_role(&pool, &conn_map).await;
-            super::generators::check_database_size(&pool, &conn_map).await;
-            super::generators::check_tor_vacancies(&pool, &conn_map).await;
-            // Run cleanup
-            if let Err(e) = super::generators::cleanup_old_warnings(&pool).await {
-                log::error!("Warning cleanup failed: {}", e);
+            tokio::select! {
+                _ = WAKE.notified() => {}
+                _ = interval.tick() => {}
             }
+            run_due_jobs(&pool, &conn_map).await;
         }
     });
 }
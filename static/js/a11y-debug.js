@@ -0,0 +1,178 @@
+// Accessibility debug mode: annotate the rendered page with landmark,
+// labelling and contrast problems and list them in the diagnostics panel.
+(function() {
+    var panel = document.getElementById('a11y-debug-panel');
+    var results = document.getElementById('a11y-debug-results');
+    var summary = document.getElementById('a11y-debug-summary');
+    if (!panel || !results) return;
+
+    var MIN_CONTRAST = 4.5;
+    var MIN_CONTRAST_LARGE = 3;
+    var MAX_TEXT_CHECKS = 800;
+    var issues = [];
+
+    function outside(el) { return !panel.contains(el); }
+
+    function visible(el) {
+        return !!(el.offsetWidth || el.offsetHeight || el.getClientRects().length);
+    }
+
+    function flag(el, kind, message) {
+        if (el) {
+            el.classList.add('a11y-flag');
+            var existing = el.getAttribute('data-a11y-issue');
+            el.setAttribute('data-a11y-issue', existing ? existing + '; ' + message : message);
+        }
+        issues.push({ el: el, kind: kind, message: message });
+    }
+
+    function describe(el) {
+        var text = el.id ? '#' + el.id : el.tagName.toLowerCase();
+        if (!el.id && el.classList.length) text += '.' + el.classList[0];
+        return text;
+    }
+
+    function accessibleName(el) {
+        var name = el.getAttribute('aria-label') || el.getAttribute('title') || '';
+        var labelledBy = el.getAttribute('aria-labelledby');
+        if (labelledBy) {
+            labelledBy.split(/\s+/).forEach(function(id) {
+                var ref = document.getElementById(id);
+                if (ref) name += ' ' + ref.textContent;
+            });
+        }
+        if (el.labels && el.labels.length) {
+            Array.from(el.labels).forEach(function(l) { name += ' ' + l.textContent; });
+        }
+        if (!name.trim() && (el.tagName === 'A' || el.tagName === 'BUTTON')) {
+            name = el.textContent;
+            el.querySelectorAll('img[alt]').forEach(function(img) { name += img.alt; });
+        }
+        if (!name.trim() && el.placeholder) name = el.placeholder;
+        return name.trim();
+    }
+
+    // Landmarks and headings
+    var mains = Array.from(document.querySelectorAll('main, [role="main"]'));
+    if (mains.length !== 1) flag(mains[1] || null, 'Landmarks', 'Page should have exactly one main landmark (found ' + mains.length + ')');
+    var navs = Array.from(document.querySelectorAll('nav, [role="navigation"]'));
+    if (navs.length > 1) {
+        navs.filter(function(n) { return !accessibleName(n); }).forEach(function(n) {
+            flag(n, 'Landmarks', 'Several navigation landmarks: give each an aria-label');
+        });
+    }
+    var h1s = Array.from(document.querySelectorAll('h1')).filter(outside);
+    if (h1s.length !== 1) flag(h1s[1] || null, 'Headings', 'Page should have exactly one h1 (found ' + h1s.length + ')');
+    var previous = 0;
+    Array.from(document.querySelectorAll('h1, h2, h3, h4, h5, h6')).filter(outside).forEach(function(h) {
+        var level = parseInt(h.tagName.substring(1), 10);
+        if (previous && level > previous + 1) {
+            flag(h, 'Headings', 'Heading level jumps from h' + previous + ' to h' + level);
+        }
+        previous = level;
+    });
+
+    // Images, controls, links and buttons
+    document.querySelectorAll('img:not([alt])').forEach(function(img) {
+        if (outside(img)) flag(img, 'Images', 'Image has no alt attribute');
+    });
+    document.querySelectorAll('input:not([type="hidden"]), select, textarea').forEach(function(el) {
+        if (outside(el) && visible(el) && !accessibleName(el)) flag(el, 'Labels', 'Form control has no label');
+    });
+    document.querySelectorAll('a[href], button').forEach(function(el) {
+        if (outside(el) && visible(el) && !accessibleName(el)) flag(el, 'Labels', describe(el) + ' has no accessible name');
+    });
+
+    // Tables
+    document.querySelectorAll('table').forEach(function(table) {
+        if (!outside(table)) return;
+        table.querySelectorAll('thead th:not([scope])').forEach(function(th) {
+            flag(th, 'Tables', 'Column header has no scope');
+        });
+        table.querySelectorAll('thead th[aria-sort]').forEach(function(th) {
+            if (!th.querySelector('a, button')) flag(th, 'Tables', 'Sortable header has no keyboard control');
+        });
+    });
+
+    // Text contrast
+    function parseRgb(value) {
+        var m = value.match(/rgba?\(([\d.]+),\s*([\d.]+),\s*([\d.]+)(?:,\s*([\d.]+))?\)/);
+        if (!m) return null;
+        return { r: +m[1], g: +m[2], b: +m[3], a: m[4] === undefined ? 1 : +m[4] };
+    }
+
+    function luminance(c) {
+        function channel(v) {
+            v /= 255;
+            return v <= 0.03928 ? v / 12.92 : Math.pow((v + 0.055) / 1.055, 2.4);
+        }
+        return 0.2126 * channel(c.r) + 0.7152 * channel(c.g) + 0.0722 * channel(c.b);
+    }
+
+    function background(el) {
+        for (var node = el; node && node.nodeType === 1; node = node.parentElement) {
+            var style = getComputedStyle(node);
+            if (style.backgroundImage !== 'none') return null;
+            var bg = parseRgb(style.backgroundColor);
+            if (bg && bg.a === 1) return bg;
+        }
+        return { r: 255, g: 255, b: 255, a: 1 };
+    }
+
+    var checked = 0;
+    var walker = document.createTreeWalker(document.querySelector('main') || document.body, NodeFilter.SHOW_TEXT);
+    var seen = new Set();
+    while (walker.nextNode() && checked < MAX_TEXT_CHECKS) {
+        var el = walker.currentNode.parentElement;
+        if (!walker.currentNode.textContent.trim() || !el || seen.has(el) || !outside(el) || !visible(el)) continue;
+        seen.add(el);
+        checked++;
+        var style = getComputedStyle(el);
+        var fg = parseRgb(style.color);
+        var bg = background(el);
+        if (!fg || !bg || fg.a < 1) continue;
+        var a = luminance(fg), b = luminance(bg);
+        var ratio = (Math.max(a, b) + 0.05) / (Math.min(a, b) + 0.05);
+        var size = parseFloat(style.fontSize);
+        var large = size >= 24 || (size >= 18.66 && parseInt(style.fontWeight, 10) >= 700);
+        if (ratio < (large ? MIN_CONTRAST_LARGE : MIN_CONTRAST)) {
+            flag(el, 'Contrast', 'Text contrast ' + ratio.toFixed(2) + ':1 is below ' + (large ? '3' : '4.5') + ':1');
+        }
+    }
+
+    // Report
+    summary.textContent = issues.length
+        ? issues.length + ' issue' + (issues.length === 1 ? '' : 's') + ' found'
+        : 'No issues found';
+    issues.forEach(function(issue) {
+        var li = document.createElement('li');
+        li.className = 'a11y-debug-panel__item a11y-debug-panel__item--fail';
+        var kind = document.createElement('strong');
+        kind.textContent = issue.kind + ': ';
+        li.appendChild(kind);
+        if (issue.el) {
+            var link = document.createElement('button');
+            link.type = 'button';
+            link.className = 'a11y-debug-panel__link';
+            link.textContent = issue.message;
+            link.addEventListener('click', function() {
+                issue.el.scrollIntoView({ block: 'center' });
+                issue.el.classList.add('a11y-flag--active');
+                setTimeout(function() { issue.el.classList.remove('a11y-flag--active'); }, 1500);
+            });
+            li.appendChild(link);
+        } else {
+            li.appendChild(document.createTextNode(issue.message));
+        }
+        results.appendChild(li);
+    });
+
+    var toggle = document.getElementById('a11y-debug-toggle');
+    var body = document.getElementById('a11y-debug-body');
+    toggle.addEventListener('click', function() {
+        var open = body.hidden;
+        body.hidden = !open;
+        toggle.setAttribute('aria-expanded', open ? 'true' : 'false');
+        toggle.textContent = open ? 'Hide' : 'Show';
+    });
+})();
@@ -16,6 +16,14 @@
         });
     });
 
+    // Open the tab named in the URL hash (e.g. /account#sessions), or the one
+    // the server asks for when re-rendering a form
+    const openTab = location.hash.slice(1) || document.querySelector('.tabs')?.dataset.openTab;
+    if (openTab) {
+        const target = document.querySelector('[role="tab"][aria-controls="' + openTab + '"]');
+        if (target) target.click();
+    }
+
     // Theme selection
     const currentTheme = localStorage.getItem('theme-preference') || 'auto';
     document.querySelectorAll('.theme-btn').forEach(btn => {
@@ -0,0 +1,66 @@
+// Scheduling assistant: shows projected attendance for the date entered in an
+// <input type="date" data-availability-url="/api/tor/{id}/availability"
+//        data-availability-target="panel-id"> and offers better dates.
+(function() {
+    var inputs = document.querySelectorAll('input[data-availability-url]');
+    for (var i = 0; i < inputs.length; i++) {
+        bind(inputs[i]);
+    }
+
+    function bind(input) {
+        var panel = document.getElementById(input.getAttribute('data-availability-target'));
+        if (!panel) return;
+        input.addEventListener('change', function() {
+            panel.textContent = '';
+            if (!input.value) return;
+            var url = input.getAttribute('data-availability-url') + '?date=' + encodeURIComponent(input.value);
+            fetch(url, { credentials: 'same-origin' })
+                .then(function(res) { return res.ok ? res.json() : null; })
+                .then(function(data) { if (data) render(panel, input, data); })
+                .catch(function() { panel.textContent = ''; });
+        });
+    }
+
+    function counts(a) {
+        var text = a.mandatory_available + '/' + a.mandatory_total + ' mandatory';
+        if (a.optional_total > 0) {
+            text += ', ' + a.optional_available + '/' + a.optional_total + ' optional';
+        }
+        return text;
+    }
+
+    function render(panel, input, data) {
+        var a = data.attendance;
+        var summary = document.createElement('div');
+        var full = a.mandatory_available === a.mandatory_total;
+        summary.className = full ? 'availability-summary--ok' : 'availability-summary--short';
+        summary.textContent = 'Projected attendance on ' + a.weekday + ': ' + counts(a) + ' members available';
+        panel.appendChild(summary);
+
+        if (a.unavailable.length > 0) {
+            var away = document.createElement('div');
+            away.textContent = 'Unavailable: ' + a.unavailable.join(', ');
+            panel.appendChild(away);
+        }
+
+        if (data.suggestions.length > 0) {
+            var list = document.createElement('div');
+            list.className = 'availability-suggestions';
+            var label = document.createElement('span');
+            label.textContent = 'Better dates:';
+            list.appendChild(label);
+            data.suggestions.forEach(function(s) {
+                var btn = document.createElement('button');
+                btn.type = 'button';
+                btn.className = 'btn btn-sm';
+                btn.textContent = s.weekday.slice(0, 3) + ' ' + s.date + ' (' + counts(s) + ')';
+                btn.addEventListener('click', function() {
+                    input.value = s.date;
+                    input.dispatchEvent(new Event('change'));
+                });
+                list.appendChild(btn);
+            });
+            panel.appendChild(list);
+        }
+    }
+})();
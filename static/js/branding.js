@@ -0,0 +1,42 @@
+// Branding page: reads a chosen logo file into the hidden data-URI field named
+// by data-logo-target and shows it in the image named by data-logo-preview;
+// a <select data-preview-frame> switches the preview iframe between pages.
+(function() {
+    var MAX_BYTES = 256 * 1024;
+    var inputs = document.querySelectorAll('input[type="file"][data-logo-target]');
+    for (var i = 0; i < inputs.length; i++) {
+        bind(inputs[i]);
+    }
+
+    var select = document.querySelector('select[data-preview-frame]');
+    if (select) {
+        var frame = document.getElementById(select.getAttribute('data-preview-frame'));
+        select.addEventListener('change', function() {
+            frame.src = select.value;
+        });
+    }
+
+    function bind(input) {
+        var target = document.getElementById(input.getAttribute('data-logo-target'));
+        var preview = document.getElementById(input.getAttribute('data-logo-preview'));
+        input.addEventListener('change', function() {
+            target.value = '';
+            var file = input.files[0];
+            if (!file) return;
+            if (file.size > MAX_BYTES) {
+                alert('Logo must be at most 256 KB');
+                input.value = '';
+                return;
+            }
+            var reader = new FileReader();
+            reader.onload = function() {
+                target.value = reader.result;
+                if (preview) {
+                    preview.src = reader.result;
+                    preview.hidden = false;
+                }
+            };
+            reader.readAsDataURL(file);
+        });
+    }
+})();
@@ -4,13 +4,28 @@
     var list = document.getElementById('col-picker-list');
     if (!picker || !btn || !list) return;
 
+    function setOpen(open) {
+        picker.hidden = !open;
+        btn.setAttribute('aria-expanded', open ? 'true' : 'false');
+        if (open) {
+            var first = list.querySelector('.col-picker__check:not(:disabled)');
+            if (first) first.focus();
+        }
+    }
+
     btn.addEventListener('click', function(e) {
         e.stopPropagation();
-        picker.hidden = !picker.hidden;
+        setOpen(picker.hidden);
     });
     document.addEventListener('click', function(e) {
-        if (!picker.contains(e.target) && !btn.contains(e.target)) {
-            picker.hidden = true;
+        if (!picker.hidden && !picker.contains(e.target) && !btn.contains(e.target)) {
+            setOpen(false);
+        }
+    });
+    picker.addEventListener('keydown', function(e) {
+        if (e.key === 'Escape') {
+            setOpen(false);
+            btn.focus();
         }
     });
 
@@ -28,7 +43,13 @@
         var visibleKeys = cols.filter(function(c) { return c.visible; }).map(function(c) { return c.key; }).join(',');
         document.getElementById('col-picker-columns-input').value = visibleKeys;
         document.getElementById('col-picker-set-global-input').value = setGlobal ? 'true' : 'false';
-        document.getElementById('col-picker-redirect-input').value = window.location.href;
+        // Saved columns apply once the URL no longer names its own
+        var back = new URL(window.location.href);
+        back.searchParams.delete('columns');
+        document.getElementById('col-picker-redirect-input').value = back.toString();
+        // Reopen the picker on the same column once the page reloads
+        var active = document.activeElement && document.activeElement.closest('.col-picker__item');
+        if (active) sessionStorage.setItem('col-picker-focus', active.dataset.key);
         document.getElementById('col-picker-form').submit();
     }
 
@@ -43,6 +64,30 @@
         globalBtn.addEventListener('click', function() { saveColumns(true); });
     }
 
+    var refocus = sessionStorage.getItem('col-picker-focus');
+    if (refocus) {
+        sessionStorage.removeItem('col-picker-focus');
+        setOpen(true);
+        var check = list.querySelector('.col-picker__item[data-key="' + refocus + '"] .col-picker__check');
+        if (check) check.focus();
+    }
+
+    // Keyboard reordering: Alt+ArrowUp / Alt+ArrowDown moves the focused column
+    list.addEventListener('keydown', function(e) {
+        if (!e.altKey || (e.key !== 'ArrowUp' && e.key !== 'ArrowDown')) return;
+        var item = e.target.closest('.col-picker__item');
+        if (!item) return;
+        e.preventDefault();
+        if (e.key === 'ArrowUp' && item.previousElementSibling) {
+            list.insertBefore(item, item.previousElementSibling);
+        } else if (e.key === 'ArrowDown' && item.nextElementSibling) {
+            list.insertBefore(item.nextElementSibling, item);
+        } else {
+            return;
+        }
+        saveColumns(false);
+    });
+
     // Drag-and-drop reordering
     var dragSrc = null;
     list.addEventListener('dragstart', function(e) {
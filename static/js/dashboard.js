@@ -0,0 +1,24 @@
+// Live dashboard counters: nav.js relays WebSocket pushes as `ahlt:ws`
+// events; dashboard_counts carries all counters, warning pushes carry the
+// unread count.
+(function() {
+    function setCount(key, value) {
+        var el = document.querySelector('[data-dash-count="' + key + '"]');
+        if (!el || typeof value !== 'number' || String(value) === el.textContent) return;
+        el.textContent = value;
+        el.classList.remove('dash-counters__value--changed');
+        void el.offsetWidth; // restart the highlight animation
+        el.classList.add('dash-counters__value--changed');
+    }
+
+    document.addEventListener('ahlt:ws', function(e) {
+        var data = e.detail || {};
+        if (data.type === 'dashboard_counts' && data.counts) {
+            Object.keys(data.counts).forEach(function(key) {
+                setCount(key, data.counts[key]);
+            });
+        } else if (data.type === 'count_update' || data.type === 'new_warning') {
+            setCount('unread_warnings', data.unread_count);
+        }
+    });
+})();
@@ -5,6 +5,9 @@
  *   deps.csrfToken, deps.fetchWithTimeout, deps.chunkArray, deps.FETCH_TIMEOUT_MS, deps.CHUNK_SIZE
  *   deps.showLoading, deps.updateLoadingStatus, deps.displayResult
  *   deps.dropZone, deps.fileInput, deps.fileNameEl, deps.btnImport, deps.conflictMode
+ *   deps.secretInput (passphrase or private key for encrypted exports)
+ * ZIP archives (format=archive exports) go whole to /api/data/import-archive.
+ *   deps.btnDryRun, deps.dryRunSection, deps.dryRunSummary, deps.dryRunTbody
  *
  * Returns { reset() }
  */
@@ -41,9 +44,74 @@ function dataManagerImport(deps) {
         }
         deps.fileNameEl.hidden = false;
         deps.btnImport.disabled = false;
+        deps.btnDryRun.disabled = false;
+        deps.dryRunSection.hidden = true;
     }
 
     deps.btnImport.addEventListener('click', handleImport);
+    deps.btnDryRun.addEventListener('click', handleDryRun);
+
+    // Dry run: validate each whole file and list what the import would do,
+    // leaving the files selected so they can be imported afterwards
+    function handleDryRun() {
+        if (fileQueue.length === 0) return;
+        deps.showLoading(true);
+        while (deps.dryRunTbody.firstChild) deps.dryRunTbody.removeChild(deps.dryRunTbody.firstChild);
+        var totals = { create: 0, update: 0, skip: 0, error: 0 };
+        var mode = deps.conflictMode.value;
+        var files = fileQueue.slice();
+
+        files.reduce(function(chain, file) {
+            return chain.then(function() {
+                deps.updateLoadingStatus('Checking ' + file.name + '\u2026');
+                return readImportBuffer(file).then(function(buffer) {
+                    if (isArchive(buffer)) return postArchive(buffer, mode, true);
+                    var parsed = JSON.parse(new TextDecoder().decode(buffer));
+                    if (parsed['@context'] || parsed['@graph']) parsed['ahlt:conflict_mode'] = mode;
+                    else parsed.conflict_mode = mode;
+                    parsed.csrf_token = deps.csrfToken;
+                    parsed.dry_run = true;
+                    return deps.fetchWithTimeout('/api/data/import', {
+                        method: 'POST',
+                        headers: { 'Content-Type': 'application/json' },
+                        body: JSON.stringify(parsed),
+                    }, deps.FETCH_TIMEOUT_MS);
+                }).then(function(resp) {
+                    if (!resp.ok) return resp.text().then(function(t) { throw new Error(file.name + ': ' + t); });
+                    return resp.json().then(function(result) { appendReport(result.items || [], totals); });
+                });
+            });
+        }, Promise.resolve()).then(function() {
+            deps.dryRunSummary.textContent = totals.create + ' to create, ' + totals.update + ' to update, ' +
+                totals.skip + ' to skip, ' + totals.error + ' rejected';
+            deps.dryRunSection.hidden = false;
+        }).catch(function(e) {
+            alert(e.isTimeout ? e.message : 'Dry run error: ' + e.message);
+        }).finally(function() {
+            deps.showLoading(false);
+            deps.updateLoadingStatus('');
+        });
+    }
+
+    function appendReport(items, totals) {
+        items.forEach(function(item) {
+            totals[item.action] = (totals[item.action] || 0) + 1;
+            var tr = document.createElement('tr');
+            tr.className = 'dm-plan-' + item.action;
+            [item.kind, item.reference, item.action, item.reason || ''].forEach(function(value, idx) {
+                var td = document.createElement('td');
+                if (idx === 1) {
+                    var code = document.createElement('code');
+                    code.textContent = value;
+                    td.appendChild(code);
+                } else {
+                    td.textContent = value;
+                }
+                tr.appendChild(td);
+            });
+            deps.dryRunTbody.appendChild(tr);
+        });
+    }
 
     function handleImport() {
         if (fileQueue.length === 0) return;
@@ -57,6 +125,8 @@ function dataManagerImport(deps) {
             fileQueue = [];
             deps.fileNameEl.hidden = true;
             deps.btnImport.disabled = true;
+            deps.btnDryRun.disabled = true;
+            deps.dryRunSection.hidden = true;
             deps.fileInput.value = '';
         }).catch(function(e) {
             if (e.isTimeout) alert(e.message);
@@ -67,6 +137,52 @@ function dataManagerImport(deps) {
         });
     }
 
+    var ENCRYPTED_MAGIC = 'AHLTENC1';
+    var ZIP_MAGIC = 'PK\x03\x04';
+    var PRIVATE_KEY_LENGTH = 44; // base64 of a 32-byte key
+
+    function startsWith(buffer, magic) {
+        return String.fromCharCode.apply(null, new Uint8Array(buffer.slice(0, magic.length))) === magic;
+    }
+
+    function isArchive(buffer) {
+        return startsWith(buffer, ZIP_MAGIC);
+    }
+
+    // Send a whole archive; it is imported in one transaction on the server
+    function postArchive(buffer, mode, dryRun) {
+        var params = new URLSearchParams({ conflict_mode: mode });
+        if (dryRun) params.set('dry_run', 'true');
+        return deps.fetchWithTimeout('/api/data/import-archive?' + params.toString(), {
+            method: 'POST',
+            headers: { 'Content-Type': 'application/zip', 'X-CSRF-Token': deps.csrfToken },
+            body: buffer,
+        }, deps.FETCH_TIMEOUT_MS);
+    }
+
+    // Read a file, decrypting it first if it is an encrypted export
+    function readImportBuffer(file) {
+        return file.arrayBuffer().then(function(buffer) {
+            if (!startsWith(buffer, ENCRYPTED_MAGIC)) return buffer;
+
+            // A private key is a 44-character base64 string; anything else is a passphrase
+            var secret = deps.secretInput.value;
+            if (!secret) throw new Error(file.name + ' is encrypted: enter its passphrase or private key');
+            var isKey = secret.trim().length === PRIVATE_KEY_LENGTH && /^[A-Za-z0-9+\/]+=$/.test(secret.trim());
+            var headers = { 'Content-Type': 'application/octet-stream', 'X-CSRF-Token': deps.csrfToken };
+            if (isKey) headers['X-Export-Private-Key'] = secret.trim();
+            else headers['X-Export-Passphrase'] = secret;
+
+            deps.updateLoadingStatus('Decrypting ' + file.name + '\u2026');
+            return deps.fetchWithTimeout('/api/data/decrypt', {
+                method: 'POST', headers: headers, body: buffer,
+            }, deps.FETCH_TIMEOUT_MS).then(function(resp) {
+                if (!resp.ok) return resp.text().then(function(t) { throw new Error(file.name + ': ' + t); });
+                return resp.arrayBuffer();
+            });
+        });
+    }
+
     function importFiles(files, mode) {
         var fileIdx = 0;
 
@@ -76,9 +192,17 @@ function dataManagerImport(deps) {
             var fileNum = fileIdx + 1;
             fileIdx++;
 
-            return file.text().then(function(text) {
+            return readImportBuffer(file).then(function(buffer) {
+                if (isArchive(buffer)) {
+                    deps.updateLoadingStatus('File ' + fileNum + ' of ' + files.length + ' (archive)\u2026');
+                    return postArchive(buffer, mode, false).then(function(resp) {
+                        if (!resp.ok) return resp.text().then(function(t) { alert('Import failed (' + file.name + '): ' + t); });
+                        return resp.json().then(function(result) { deps.displayResult(result); });
+                    }).then(processNextFile);
+                }
+
                 var parsed;
-                try { parsed = JSON.parse(text); }
+                try { parsed = JSON.parse(new TextDecoder().decode(buffer)); }
                 catch (e) { alert('Invalid JSON in ' + file.name + ': ' + e.message); return processNextFile(); }
 
                 // JSON-LD format: send as single request
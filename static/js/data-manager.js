@@ -62,7 +62,12 @@
         fileInput: document.getElementById('import-file'),
         fileNameEl: document.getElementById('file-name'),
         btnImport: document.getElementById('btn-import'),
-        conflictMode: document.getElementById('conflict-mode')
+        conflictMode: document.getElementById('conflict-mode'),
+        secretInput: document.getElementById('import-secret'),
+        btnDryRun: document.getElementById('btn-dry-run'),
+        dryRunSection: document.getElementById('dry-run-section'),
+        dryRunSummary: document.getElementById('dry-run-summary'),
+        dryRunTbody: document.getElementById('dry-run-tbody')
     });
 
     // Export pipeline
@@ -81,7 +86,21 @@
         });
     }
 
+    var passphraseGroup = document.getElementById('export-passphrase-group');
+    var passphraseInput = document.getElementById('export-passphrase');
+    var encryptRadios = document.querySelectorAll('input[name="export-encrypt"]');
+    for (var j = 0; j < encryptRadios.length; j++) {
+        encryptRadios[j].addEventListener('change', function() {
+            passphraseGroup.hidden = this.value !== 'passphrase';
+        });
+    }
+
     btnExport.addEventListener('click', function() {
+        var encrypt = document.querySelector('input[name="export-encrypt"]:checked').value;
+        if (encrypt === 'passphrase' && passphraseInput.value.length < 12) {
+            alert('The passphrase must be at least 12 characters.');
+            return;
+        }
         showLoading(true);
         var format = document.querySelector('input[name="export-format"]:checked').value;
         var checkedTypes = [];
@@ -90,11 +109,15 @@
 
         var params = new URLSearchParams();
         params.set('format', format);
-        if (checkedTypes.length > 0) params.set('types', checkedTypes.join(','));
+        if (checkedTypes.length > 0 && format !== 'archive') params.set('types', checkedTypes.join(','));
+        var headers = {};
+        if (encrypt) params.set('encrypt', encrypt);
+        if (encrypt === 'passphrase') headers['X-Export-Passphrase'] = passphraseInput.value;
 
-        fetchWithTimeout('/api/data/export?' + params.toString(), {}, FETCH_TIMEOUT_MS).then(function(resp) {
+        fetchWithTimeout('/api/data/export?' + params.toString(), { headers: headers }, FETCH_TIMEOUT_MS).then(function(resp) {
             if (!resp.ok) return resp.text().then(function(t) { alert('Export failed: ' + t); });
-            var ext = format === 'jsonld' ? 'jsonld' : format === 'sql' ? 'sql' : 'json';
+            var ext = format === 'jsonld' ? 'jsonld' : format === 'sql' ? 'sql' : format === 'archive' ? 'zip' : 'json';
+            if (encrypt) ext += '.enc';
             return resp.blob().then(function(blob) {
                 var url = URL.createObjectURL(blob);
                 var a = document.createElement('a');
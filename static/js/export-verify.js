@@ -0,0 +1,17 @@
+// Export verifier: hash a chosen file in the browser (SHA-256) and put the
+// digest and file name in the checksum field, so nothing is uploaded.
+(function() {
+    const input = document.querySelector('[data-checksum-target]');
+    if (!input || !window.crypto || !crypto.subtle) return;
+    const target = document.getElementById(input.dataset.checksumTarget);
+
+    input.addEventListener('change', async () => {
+        const file = input.files[0];
+        if (!file) return;
+        const digest = await crypto.subtle.digest('SHA-256', await file.arrayBuffer());
+        const hex = Array.from(new Uint8Array(digest))
+            .map((b) => b.toString(16).padStart(2, '0'))
+            .join('');
+        target.value = hex + '  ' + file.name;
+    });
+})();
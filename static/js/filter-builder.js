@@ -20,6 +20,7 @@
 
     function makeFieldSelect(selected) {
         var s = el('select', 'filter-field-select');
+        s.setAttribute('aria-label', 'Field');
         FIELDS.forEach(function(f) {
             var o = el('option', '', f.label);
             o.value = f.key;
@@ -32,6 +33,7 @@
     function makeOpSelect(fieldKey, selectedOp) {
         var field = FIELDS.find(function(f) { return f.key === fieldKey; }) || FIELDS[0];
         var s = el('select', 'filter-op-select');
+        s.setAttribute('aria-label', 'Condition');
         (field.ops || []).forEach(function(op) {
             var o = el('option', '', OP_LABELS[op] || op);
             o.value = op;
@@ -45,6 +47,7 @@
         var field = FIELDS.find(function(f) { return f.key === fieldKey; }) || FIELDS[0];
         if (field.type === 'select') {
             var s = el('select', 'filter-value-select');
+            s.setAttribute('aria-label', 'Value');
             (field.options || []).forEach(function(opt) {
                 var o = el('option', '', opt.label);
                 o.value = opt.value;
@@ -55,19 +58,32 @@
         } else if (field.type === 'date') {
             var inp = el('input', 'filter-value-input');
             inp.type = 'date';
+            inp.setAttribute('aria-label', 'Value');
             inp.value = value || '';
             return inp;
         } else {
             var inp = el('input', 'filter-value-input');
             inp.type = 'text';
             inp.placeholder = 'Value...';
+            inp.setAttribute('aria-label', 'Value');
             inp.value = value || '';
             return inp;
         }
     }
 
-    function makeConditionRow(container, cond) {
+    // Move focus somewhere sensible after removing an element: the next or
+    // previous sibling's first control, else the fallback button.
+    function focusAfterRemove(node, fallback) {
+        var sibling = node.nextElementSibling || node.previousElementSibling;
+        node.remove();
+        var target = sibling && sibling.querySelector('select, input, button');
+        (target || fallback).focus();
+    }
+
+    function makeConditionRow(container, cond, addBtn) {
         var row = el('div', 'filter-condition-row');
+        row.setAttribute('role', 'group');
+        row.setAttribute('aria-label', 'Condition');
         var fieldSel = makeFieldSelect(cond.field || (FIELDS[0] && FIELDS[0].key));
         var opSel = makeOpSelect(fieldSel.value, cond.op);
         var valInp = makeValueInput(fieldSel.value, cond.value);
@@ -84,20 +100,29 @@
             valInp = newVal;
         });
 
-        removeBtn.addEventListener('click', function() { row.remove(); });
+        removeBtn.addEventListener('click', function() { focusAfterRemove(row, addBtn); });
 
         row.appendChild(fieldSel);
         row.appendChild(opSel);
         row.appendChild(valInp);
         row.appendChild(removeBtn);
         container.appendChild(row);
+        return row;
+    }
+
+    function addCondition(container, addBtn) {
+        var row = makeConditionRow(container, { field: FIELDS[0] && FIELDS[0].key, op: '', value: '' }, addBtn);
+        row.querySelector('select').focus();
     }
 
     function makeGroup(groupState) {
         var wrapper = el('div', 'filter-group');
+        wrapper.setAttribute('role', 'group');
+        wrapper.setAttribute('aria-label', 'Condition group');
         var header = el('div', 'filter-group__header');
         var logicLabel = el('span', 'filter-group__label', 'Match ');
         var logicSel = el('select', 'filter-logic-select');
+        logicSel.setAttribute('aria-label', 'Group match');
         [['and','ALL'],['or','ANY']].forEach(function(pair) {
             var o = el('option', '', pair[1]);
             o.value = pair[0];
@@ -109,6 +134,7 @@
         addCondBtn.type = 'button';
         var removeGroupBtn = el('button', 'filter-remove-btn', '\u2715 Group');
         removeGroupBtn.type = 'button';
+        removeGroupBtn.setAttribute('aria-label', 'Remove group');
 
         header.appendChild(logicLabel);
         header.appendChild(logicSel);
@@ -117,12 +143,12 @@
         header.appendChild(removeGroupBtn);
 
         var condContainer = el('div', 'filter-group__conditions');
-        (groupState.conditions || []).forEach(function(c) { makeConditionRow(condContainer, c); });
+        (groupState.conditions || []).forEach(function(c) { makeConditionRow(condContainer, c, addCondBtn); });
 
-        addCondBtn.addEventListener('click', function() {
-            makeConditionRow(condContainer, { field: FIELDS[0] && FIELDS[0].key, op: '', value: '' });
+        addCondBtn.addEventListener('click', function() { addCondition(condContainer, addCondBtn); });
+        removeGroupBtn.addEventListener('click', function() {
+            focusAfterRemove(wrapper, document.getElementById('add-group'));
         });
-        removeGroupBtn.addEventListener('click', function() { wrapper.remove(); });
 
         wrapper.appendChild(header);
         wrapper.appendChild(condContainer);
@@ -156,18 +182,17 @@
     var rootLogicSel = document.getElementById('root-logic-select');
     if (rootLogicSel && state.logic) rootLogicSel.value = state.logic;
     var rootConds = document.getElementById('root-conditions');
-    (state.conditions || []).forEach(function(c) { makeConditionRow(rootConds, c); });
+    var addRootBtn = document.getElementById('add-root-condition');
+    (state.conditions || []).forEach(function(c) { makeConditionRow(rootConds, c, addRootBtn); });
     var rootGroups = document.getElementById('root-groups');
     (state.groups || []).forEach(function(g) { rootGroups.appendChild(makeGroup(g)); });
 
-    document.getElementById('add-root-condition').addEventListener('click', function() {
-        makeConditionRow(rootConds, { field: FIELDS[0] && FIELDS[0].key, op: '', value: '' });
-    });
+    addRootBtn.addEventListener('click', function() { addCondition(rootConds, addRootBtn); });
 
     document.getElementById('add-group').addEventListener('click', function() {
         var newGroup = makeGroup({ logic: 'or', conditions: [] });
         rootGroups.appendChild(newGroup);
-        makeConditionRow(newGroup.querySelector('.filter-group__conditions'), { field: FIELDS[0] && FIELDS[0].key, op: '', value: '' });
+        addCondition(newGroup.querySelector('.filter-group__conditions'), newGroup.querySelector('.btn'));
     });
 
     document.getElementById('apply-filter').addEventListener('click', function() {
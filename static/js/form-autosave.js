@@ -0,0 +1,203 @@
+// Unsaved-changes protection for long forms marked <form data-autosave="Label">.
+// While the user edits, the form's values are snapshotted to /form-drafts
+// (keyed by the path the form posts to). Leaving the page with changes that
+// are not yet snapshotted asks for confirmation. When a snapshot exists — say
+// the session expired mid-edit and the user has signed back in — the form
+// offers to restore it. A successful submit clears the snapshot server-side.
+(function() {
+    var SAVE_DELAY_MS = 3000;
+    var SAVE_INTERVAL_MS = 30000;
+
+    var forms = document.querySelectorAll('form[data-autosave]');
+    for (var i = 0; i < forms.length; i++) {
+        bind(forms[i]);
+    }
+
+    function formKey(form) {
+        return new URL(form.getAttribute('action') || location.pathname, location.href).pathname;
+    }
+
+    function csrfToken(form) {
+        var input = form.querySelector('input[name="csrf_token"]');
+        return input ? input.value : '';
+    }
+
+    // Field values as { name: value }; names that occur more than once, and
+    // multi-selects, collect into lists.
+    function collect(form) {
+        var values = {};
+        var multi = {};
+        for (var i = 0; i < form.elements.length; i++) {
+            var el = form.elements[i];
+            if (!el.name || el.name === 'csrf_token' || el.disabled) continue;
+            if (el.type === 'password' || el.type === 'file' || el.type === 'submit' || el.type === 'button') continue;
+            var value;
+            if (el.type === 'checkbox' || el.type === 'radio') {
+                if (!el.checked) continue;
+                value = el.value;
+            } else if (el.type === 'select-multiple') {
+                value = [];
+                for (var j = 0; j < el.options.length; j++) {
+                    if (el.options[j].selected) value.push(el.options[j].value);
+                }
+                multi[el.name] = true;
+            } else {
+                value = el.value;
+            }
+            if (Object.prototype.hasOwnProperty.call(values, el.name)) {
+                if (!multi[el.name]) {
+                    values[el.name] = [values[el.name]];
+                    multi[el.name] = true;
+                }
+                values[el.name] = values[el.name].concat(value);
+            } else {
+                values[el.name] = value;
+            }
+        }
+        return values;
+    }
+
+    // Key order is not preserved by the server, so compare canonically.
+    function canonical(values) {
+        var keys = Object.keys(values).sort();
+        return JSON.stringify(keys.map(function(k) { return [k, values[k]]; }));
+    }
+
+    function apply(form, values) {
+        var seen = {};
+        for (var i = 0; i < form.elements.length; i++) {
+            var el = form.elements[i];
+            if (!el.name || el.name === 'csrf_token') continue;
+            if (el.type === 'password' || el.type === 'file' || el.type === 'submit' || el.type === 'button') continue;
+            var has = Object.prototype.hasOwnProperty.call(values, el.name);
+            var value = has ? values[el.name] : null;
+            var list = Array.isArray(value) ? value : (value === null ? [] : [value]);
+            if (el.type === 'checkbox' || el.type === 'radio') {
+                el.checked = list.indexOf(el.value) !== -1;
+            } else if (el.type === 'select-multiple') {
+                for (var j = 0; j < el.options.length; j++) {
+                    el.options[j].selected = list.indexOf(el.options[j].value) !== -1;
+                }
+            } else if (has) {
+                var index = seen[el.name] || 0;
+                seen[el.name] = index + 1;
+                el.value = Array.isArray(value) ? (value[index] !== undefined ? value[index] : '') : value;
+            } else {
+                continue;
+            }
+            el.dispatchEvent(new Event('input', { bubbles: true }));
+            el.dispatchEvent(new Event('change', { bubbles: true }));
+        }
+    }
+
+    function banner(form, text, actions) {
+        var existing = form.previousElementSibling;
+        if (existing && existing.classList.contains('form-draft-banner')) existing.remove();
+        var box = document.createElement('div');
+        box.className = 'alert alert-warning form-draft-banner';
+        box.setAttribute('role', 'status');
+        var span = document.createElement('span');
+        span.className = 'form-draft-banner__text';
+        span.textContent = text;
+        box.appendChild(span);
+        (actions || []).forEach(function(action) {
+            var btn = document.createElement('button');
+            btn.type = 'button';
+            btn.className = 'btn btn-sm';
+            btn.textContent = action.label;
+            btn.addEventListener('click', function() {
+                box.remove();
+                action.run();
+            });
+            box.appendChild(btn);
+        });
+        form.parentNode.insertBefore(box, form);
+        return box;
+    }
+
+    function bind(form) {
+        var key = formKey(form);
+        var title = form.getAttribute('data-autosave') || document.title;
+        var baseline = canonical(collect(form));
+        var saved = baseline;
+        var submitting = false;
+        var expired = false;
+        var timer = null;
+
+        function save() {
+            clearTimeout(timer);
+            var values = collect(form);
+            var snapshot = canonical(values);
+            if (expired || snapshot === saved || snapshot === baseline) return;
+            fetch('/form-drafts', {
+                method: 'POST',
+                credentials: 'same-origin',
+                keepalive: true,
+                headers: { 'Content-Type': 'application/json' },
+                body: JSON.stringify({
+                    csrf_token: csrfToken(form),
+                    form_key: key,
+                    page_path: location.pathname + location.search,
+                    title: title,
+                    fields: values
+                })
+            }).then(function(res) {
+                if (res.redirected) {
+                    // Signed out: what was snapshotted before is kept
+                    expired = true;
+                    banner(form, 'Your session has expired. Sign in again to continue — your earlier changes to this form were saved.', [
+                        { label: 'Sign in', run: function() { location.reload(); } }
+                    ]);
+                } else if (res.ok) {
+                    saved = snapshot;
+                }
+            }).catch(function() {});
+        }
+
+        function schedule() {
+            clearTimeout(timer);
+            timer = setTimeout(save, SAVE_DELAY_MS);
+        }
+
+        form.addEventListener('input', schedule);
+        form.addEventListener('change', schedule);
+        form.addEventListener('submit', function() {
+            submitting = true;
+            clearTimeout(timer);
+        });
+        setInterval(save, SAVE_INTERVAL_MS);
+        document.addEventListener('ahlt:session-expiring', save);
+
+        window.addEventListener('beforeunload', function(e) {
+            if (submitting || expired) return;
+            var current = canonical(collect(form));
+            if (current === baseline || current === saved) return;
+            save();
+            e.preventDefault();
+            e.returnValue = '';
+        });
+
+        fetch('/form-drafts?key=' + encodeURIComponent(key), { credentials: 'same-origin' })
+            .then(function(res) { return res.ok && !res.redirected ? res.json() : null; })
+            .then(function(draft) {
+                if (!draft || !draft.fields) return;
+                var snapshot = canonical(draft.fields);
+                if (snapshot === canonical(collect(form))) return;
+                banner(form, 'You have unsaved changes to this form from ' + draft.updated_at + '.', [
+                    { label: 'Restore', run: function() {
+                        apply(form, draft.fields);
+                        saved = snapshot;
+                    } },
+                    { label: 'Discard', run: function() {
+                        fetch('/form-drafts/discard', {
+                            method: 'POST',
+                            credentials: 'same-origin',
+                            headers: { 'Content-Type': 'application/json' },
+                            body: JSON.stringify({ csrf_token: csrfToken(form), form_key: key })
+                        });
+                    } }
+                ]);
+            })
+            .catch(function() {});
+    }
+})();
@@ -6,6 +6,10 @@
  *   deps.toolkit — graphToolkit instance
  *   deps.width, deps.height — viewport dimensions
  *
+ * Impact subgraphs add depth, blocked, open_proposals and open_agenda_points
+ * to each node; the analysed ToR (depth 0) is outlined and blocked ToRs are
+ * drawn in the blocking colour.
+ *
  * Returns { render(data) }
  */
 function governanceMapRender(deps) {
@@ -23,12 +27,24 @@ function governanceMapRender(deps) {
     };
     var STATUS_COLORS = { active: '#059669', draft: '#d97706', archived: '#78716c' };
 
+    function nodeStroke(d) {
+        if (d.depth === 0) return NODE_HOVER_STROKE;
+        return d.blocked ? BLOCKING_COLOR : NODE_STROKE;
+    }
+
+    function nodeStrokeWidth(d) {
+        return d.depth === 0 || d.blocked ? 2 : 1;
+    }
+
     function render(data) {
         var g = deps.g;
         var nodes = data.nodes;
         var edges = data.edges;
 
-        deps.statEl.textContent = nodes.length + ' ToRs \u00b7 ' + edges.length + ' dependencies';
+        var isImpact = nodes.length > 0 && nodes[0].depth !== undefined;
+        deps.statEl.textContent = isImpact
+            ? (nodes.length - 1) + ' affected ToRs \u00b7 ' + edges.length + ' dependencies'
+            : nodes.length + ' ToRs \u00b7 ' + edges.length + ' dependencies';
 
         if (!nodes.length) {
             g.append('text')
@@ -94,8 +110,8 @@ function governanceMapRender(deps) {
                 d3.select(this).select('rect').attr('stroke', NODE_HOVER_STROKE).attr('stroke-width', 2);
                 highlightConnected(d.id);
             })
-            .on('mouseout', function() {
-                d3.select(this).select('rect').attr('stroke', NODE_STROKE).attr('stroke-width', 1);
+            .on('mouseout', function(ev, d) {
+                d3.select(this).select('rect').attr('stroke', nodeStroke(d)).attr('stroke-width', nodeStrokeWidth(d));
                 unhighlight();
             });
 
@@ -103,7 +119,7 @@ function governanceMapRender(deps) {
             .attr('x', -nodeW / 2).attr('y', -nodeH / 2)
             .attr('width', nodeW).attr('height', nodeH)
             .attr('rx', 8).attr('ry', 8)
-            .attr('fill', NODE_FILL).attr('stroke', NODE_STROKE).attr('stroke-width', 1);
+            .attr('fill', NODE_FILL).attr('stroke', nodeStroke).attr('stroke-width', nodeStrokeWidth);
         nodeGroup.append('circle')
             .attr('cx', -nodeW / 2 + 14).attr('cy', -nodeH / 2 + 16).attr('r', 4)
             .attr('fill', function(d) { return STATUS_COLORS[d.status] || '#78716c'; });
@@ -121,6 +137,11 @@ function governanceMapRender(deps) {
             .attr('font-size', 10).attr('fill', 'var(--text-muted)')
             .attr('font-family', 'var(--font-mono)')
             .text(function(d) {
+                if (isImpact) {
+                    if (d.depth === 0) return 'analysed';
+                    var open = d.open_proposals + d.open_agenda_points;
+                    return open + ' open item' + (open === 1 ? '' : 's');
+                }
                 var parts = [];
                 if (d.cadence) parts.push(CADENCE_LABELS[d.cadence] || d.cadence);
                 if (d.cadence_day) parts.push(d.cadence_day.charAt(0).toUpperCase() + d.cadence_day.slice(1, 3));
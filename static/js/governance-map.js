@@ -59,7 +59,9 @@
     });
 
     var FETCH_TIMEOUT_MS = 30000;
-    fetch('/api/governance/graph', { signal: AbortSignal.timeout(FETCH_TIMEOUT_MS) })
+    // Pages showing a subgraph (e.g. impact analysis) point the canvas at their own endpoint
+    var graphUrl = canvas.dataset.graphUrl || '/api/governance/graph';
+    fetch(graphUrl, { signal: AbortSignal.timeout(FETCH_TIMEOUT_MS) })
         .then(function(r) { return r.json(); })
         .then(function(data) {
             loading.style.display = 'none';
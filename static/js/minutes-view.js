@@ -33,6 +33,23 @@
         statusTd.appendChild(statusSel);
         tr.appendChild(statusTd);
 
+        var ticketTd = document.createElement('td');
+        if (linked) {
+            var link = document.createElement('a');
+            link.href = item.external_url || '#';
+            link.target = '_blank';
+            link.rel = 'noopener';
+            link.textContent = item.external_key;
+            ticketTd.appendChild(link);
+            if (item.external_status) {
+                ticketTd.appendChild(document.createTextNode(' (' + item.external_status + ')'));
+            }
+        } else if (item.sync === 'pending') {
+            ticketTd.textContent = 'pending';
+            ticketTd.className = 'hint';
+        }
+        tr.appendChild(ticketTd);
+
         var delegTd = document.createElement('td');
         var delegInput = document.createElement('input');
         delegInput.type = 'text';
@@ -84,8 +101,10 @@
         var responsible = item.responsible || '';
         var dueDate = item.due_date || '';
         var status = item.status || 'open';
+        var linked = !!item.external_key;
 
         var tr = document.createElement('tr');
+        tr.dataset.ref = item.ref || '';
 
         var descTd = document.createElement('td');
         var descInput = document.createElement('input');
@@ -119,7 +138,9 @@
         var statusTd = document.createElement('td');
         var statusSel = document.createElement('select');
         statusSel.className = 'input input--sm';
-        statusSel.disabled = !canEdit;
+        // A linked item's status follows its ticket
+        statusSel.disabled = !canEdit || linked;
+        if (linked) { statusSel.title = 'Synced from ' + item.external_key; }
         ACTION_STATUS.forEach(function(opt) {
             var o = document.createElement('option');
             o.value = opt;
@@ -130,6 +151,23 @@
         statusTd.appendChild(statusSel);
         tr.appendChild(statusTd);
 
+        var ticketTd = document.createElement('td');
+        if (linked) {
+            var link = document.createElement('a');
+            link.href = item.external_url || '#';
+            link.target = '_blank';
+            link.rel = 'noopener';
+            link.textContent = item.external_key;
+            ticketTd.appendChild(link);
+            if (item.external_status) {
+                ticketTd.appendChild(document.createTextNode(' (' + item.external_status + ')'));
+            }
+        } else if (item.sync === 'pending') {
+            ticketTd.textContent = 'pending';
+            ticketTd.className = 'hint';
+        }
+        tr.appendChild(ticketTd);
+
         if (canEdit) {
             var actionTd = document.createElement('td');
             var removeBtn = document.createElement('button');
@@ -156,6 +194,7 @@
             var description = inputs[0].value.trim();
             if (!description) return null;
             return {
+                ref: tr.dataset.ref,
                 description: description,
                 responsible: inputs[1].value.trim(),
                 due_date: inputs[2].value.trim(),
@@ -20,12 +20,20 @@ document.addEventListener('click', function(e) {
         ws.onmessage = function(evt) {
             try {
                 var data = JSON.parse(evt.data);
+                // Let page scripts (e.g. the dashboard) react to pushes too
+                document.dispatchEvent(new CustomEvent('ahlt:ws', { detail: data }));
                 if (data.type === 'count_update' || data.type === 'new_warning') {
                     updateBadge(data.unread_count);
                 }
                 if (data.type === 'new_warning') {
                     showToast(data.severity, data.title);
                 }
+                if (data.type === 'session_expiring') {
+                    showExpiryCountdown(data.seconds_left);
+                }
+                if (data.type === 'nav_updated') {
+                    refreshNav();
+                }
             } catch(e) {}
         };
 
@@ -77,5 +85,83 @@ document.addEventListener('click', function(e) {
         }, 5000);
     }
 
+    // Menus changed (Menu Builder, roles): rebuild the links in place. A
+    // sidebar the page was rendered without appears on the next navigation.
+    function refreshNav() {
+        fetch('/api/nav?path=' + encodeURIComponent(location.pathname), { credentials: 'same-origin' })
+            .then(function(resp) { return resp.ok ? resp.json() : null; })
+            .then(function(nav) {
+                if (!nav) return;
+                fillLinks(document.querySelector('.navbar-center'), nav.modules);
+                fillLinks(document.querySelector('.sidebar-nav'), nav.sidebar);
+            });
+    }
+
+    function fillLinks(container, links) {
+        if (!container) return;
+        container.textContent = '';
+        links.forEach(function(link) {
+            var a = document.createElement('a');
+            a.href = link.url;
+            a.textContent = link.label;
+            if (link.is_active) a.className = 'active';
+            container.appendChild(a);
+        });
+    }
+
+    var expiryTimer = null;
+
+    function showExpiryCountdown(secondsLeft) {
+        var existing = document.getElementById('session-expiry-toast');
+        if (existing) existing.remove();
+        clearInterval(expiryTimer);
+
+        var toast = document.createElement('div');
+        toast.id = 'session-expiry-toast';
+        toast.className = 'toast toast-high show';
+        var text = document.createElement('span');
+        var btn = document.createElement('button');
+        btn.type = 'button';
+        btn.className = 'btn btn-sm';
+        btn.textContent = 'Stay signed in';
+        toast.appendChild(text);
+        toast.appendChild(document.createTextNode(' '));
+        toast.appendChild(btn);
+
+        var container = document.getElementById('toast-container');
+        if (!container) {
+            container = document.createElement('div');
+            container.id = 'toast-container';
+            document.body.appendChild(container);
+        }
+        container.appendChild(toast);
+
+        // Let forms with unsaved edits snapshot them before the session ends
+        document.dispatchEvent(new CustomEvent('ahlt:session-expiring'));
+
+        var deadline = Date.now() + secondsLeft * 1000;
+        function tick() {
+            var left = Math.max(0, Math.round((deadline - Date.now()) / 1000));
+            text.textContent = 'Your session expires in ' + left + 's.';
+            if (left === 0) {
+                clearInterval(expiryTimer);
+                location.reload();
+            }
+        }
+        tick();
+        expiryTimer = setInterval(tick, 1000);
+
+        btn.addEventListener('click', function() {
+            fetch('/session/keepalive', { credentials: 'same-origin' }).then(function(resp) {
+                if (resp.redirected) {
+                    location.reload();
+                    return;
+                }
+                clearInterval(expiryTimer);
+                toast.remove();
+            });
+        });
+    }
+
     connect();
 })();
@@ -0,0 +1,41 @@
+// Notification center keyboard navigation:
+// j / ArrowDown and k / ArrowUp move between notifications,
+// Enter opens the focused one, r marks it read.
+(function() {
+    const list = document.getElementById('notification-list');
+    if (!list) return;
+    const items = Array.from(list.querySelectorAll('.notification-item'));
+
+    function focusAt(index) {
+        const i = Math.max(0, Math.min(items.length - 1, index));
+        items[i].focus();
+    }
+
+    document.addEventListener('keydown', (e) => {
+        if (e.ctrlKey || e.metaKey || e.altKey) return;
+        const tag = (e.target.tagName || '').toLowerCase();
+        if (tag === 'input' || tag === 'textarea' || tag === 'select') return;
+
+        const current = items.indexOf(document.activeElement);
+        if (e.key === 'j' || e.key === 'ArrowDown') {
+            e.preventDefault();
+            focusAt(current + 1);
+        } else if (e.key === 'k' || e.key === 'ArrowUp') {
+            e.preventDefault();
+            focusAt(current < 0 ? 0 : current - 1);
+        } else if (e.key === 'Enter' && current >= 0) {
+            e.preventDefault();
+            window.location.href = items[current].dataset.href;
+        } else if (e.key === 'r' && current >= 0) {
+            const form = items[current].querySelector('.notification-read-form');
+            if (form) form.submit();
+        }
+    });
+
+    items.forEach((item) => {
+        item.addEventListener('click', (e) => {
+            if (e.target.closest('form, a')) return;
+            window.location.href = item.dataset.href;
+        });
+    });
+})();
@@ -0,0 +1,92 @@
+// Push notification toggle on the account page. Subscribes this browser
+// with the server's VAPID public key and registers the subscription at
+// /account/push/subscribe, or removes it again.
+(function() {
+    var card = document.getElementById('push-card');
+    if (!card || !card.dataset.publicKey) {
+        return;
+    }
+    var status = document.getElementById('push-status');
+    var enableBtn = document.getElementById('push-enable');
+    var disableBtn = document.getElementById('push-disable');
+
+    if (!('serviceWorker' in navigator) || !('PushManager' in window) || !('Notification' in window)) {
+        status.textContent = 'This browser does not support push notifications.';
+        return;
+    }
+
+    function keyBytes(base64url) {
+        var base64 = base64url.replace(/-/g, '+').replace(/_/g, '/');
+        var raw = atob(base64 + '='.repeat((4 - base64.length % 4) % 4));
+        var bytes = new Uint8Array(raw.length);
+        for (var i = 0; i < raw.length; i++) {
+            bytes[i] = raw.charCodeAt(i);
+        }
+        return bytes;
+    }
+
+    function post(url, body) {
+        body.csrf_token = card.dataset.csrf;
+        return fetch(url, {
+            method: 'POST',
+            headers: { 'Content-Type': 'application/json' },
+            credentials: 'same-origin',
+            body: JSON.stringify(body)
+        }).then(function(response) {
+            if (!response.ok) {
+                return response.json().catch(function() { return {}; }).then(function(data) {
+                    throw new Error(data.error || 'Request failed');
+                });
+            }
+        });
+    }
+
+    function show(subscribed) {
+        enableBtn.hidden = subscribed;
+        disableBtn.hidden = !subscribed;
+        status.textContent = subscribed
+            ? 'This device receives push notifications.'
+            : Notification.permission === 'denied'
+                ? 'Notifications are blocked for this site in your browser settings.'
+                : 'This device does not receive push notifications.';
+    }
+
+    navigator.serviceWorker.ready.then(function(registration) {
+        registration.pushManager.getSubscription().then(function(subscription) {
+            show(!!subscription);
+        });
+
+        enableBtn.addEventListener('click', function() {
+            registration.pushManager.subscribe({
+                userVisibleOnly: true,
+                applicationServerKey: keyBytes(card.dataset.publicKey)
+            }).then(function(subscription) {
+                var json = subscription.toJSON();
+                return post('/account/push/subscribe', {
+                    endpoint: json.endpoint,
+                    keys: json.keys,
+                    device: navigator.userAgent
+                });
+            }).then(function() {
+                location.reload();
+            }).catch(function(err) {
+                show(false);
+                status.textContent = 'Could not enable push notifications: ' + err.message;
+            });
+        });
+
+        disableBtn.addEventListener('click', function() {
+            registration.pushManager.getSubscription().then(function(subscription) {
+                if (!subscription) {
+                    return;
+                }
+                return post('/account/push/unsubscribe', { endpoint: subscription.endpoint })
+                    .then(function() { return subscription.unsubscribe(); });
+            }).then(function() {
+                location.reload();
+            }).catch(function(err) {
+                status.textContent = 'Could not disable push notifications: ' + err.message;
+            });
+        });
+    });
+})();
@@ -0,0 +1,12 @@
+// Registers the service worker that makes the app installable, keeps
+// read-only views for offline use and receives push messages.
+(function() {
+    if (!('serviceWorker' in navigator)) {
+        return;
+    }
+    window.addEventListener('load', function() {
+        navigator.serviceWorker.register('/sw.js').catch(function(err) {
+            console.warn('Service worker registration failed:', err);
+        });
+    });
+})();
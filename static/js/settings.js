@@ -0,0 +1,11 @@
+    // History drawer
+    const drawer = document.getElementById('settings-history');
+    document.getElementById('settings-history-toggle').addEventListener('click', () => {
+        drawer.classList.toggle('open');
+    });
+    document.getElementById('settings-history-close').addEventListener('click', () => {
+        drawer.classList.remove('open');
+    });
+    document.addEventListener('keydown', (e) => {
+        if (e.key === 'Escape') drawer.classList.remove('open');
+    });
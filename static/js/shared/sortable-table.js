@@ -0,0 +1,78 @@
+/**
+ * Client-side sorting for small, unpaged tables.
+ *
+ * Usage: <table data-sortable> with <th scope="col"> headers. Headers marked
+ * data-sort="none" (e.g. action columns) stay unsortable. Each sortable header
+ * gets a button, so sorting works from the keyboard; aria-sort reflects the
+ * current order and the change is announced through a polite live region.
+ */
+(function() {
+    var status = null;
+
+    function announce(text) {
+        if (!status) {
+            status = document.createElement('div');
+            status.className = 'sr-only';
+            status.setAttribute('role', 'status');
+            status.setAttribute('aria-live', 'polite');
+            document.body.appendChild(status);
+        }
+        status.textContent = text;
+    }
+
+    function cellValue(row, index) {
+        var cell = row.cells[index];
+        return cell ? (cell.dataset.sortValue || cell.textContent).trim() : '';
+    }
+
+    function compare(a, b) {
+        var na = parseFloat(a), nb = parseFloat(b);
+        if (!isNaN(na) && !isNaN(nb) && String(na) === a && String(nb) === b) return na - nb;
+        return a.localeCompare(b, undefined, { numeric: true, sensitivity: 'base' });
+    }
+
+    function sortBy(table, header, index) {
+        var ascending = header.getAttribute('aria-sort') !== 'ascending';
+        var tbody = table.tBodies[0];
+        var rows = Array.from(tbody.rows);
+        // An empty-state row spanning the table has nothing to sort
+        if (rows.some(function(r) { return r.querySelector('td[colspan]'); })) return;
+
+        rows.sort(function(a, b) {
+            var result = compare(cellValue(a, index), cellValue(b, index));
+            return ascending ? result : -result;
+        });
+        rows.forEach(function(r) { tbody.appendChild(r); });
+
+        table.querySelectorAll('thead th[aria-sort]').forEach(function(th) {
+            th.setAttribute('aria-sort', 'none');
+            th.querySelector('.sort-indicator').textContent = '';
+        });
+        header.setAttribute('aria-sort', ascending ? 'ascending' : 'descending');
+        header.querySelector('.sort-indicator').textContent = ascending ? '▲' : '▼';
+        announce('Sorted by ' + header.dataset.label + ', ' + (ascending ? 'ascending' : 'descending'));
+    }
+
+    document.querySelectorAll('table[data-sortable]').forEach(function(table) {
+        var headers = table.querySelectorAll('thead th');
+        headers.forEach(function(th, index) {
+            if (th.dataset.sort === 'none' || !th.textContent.trim()) return;
+            var label = th.textContent.trim();
+            th.dataset.label = label;
+            th.setAttribute('aria-sort', 'none');
+
+            var btn = document.createElement('button');
+            btn.type = 'button';
+            btn.className = 'sort-button';
+            btn.textContent = label;
+            var indicator = document.createElement('span');
+            indicator.className = 'sort-indicator';
+            indicator.setAttribute('aria-hidden', 'true');
+            btn.appendChild(indicator);
+            btn.addEventListener('click', function() { sortBy(table, th, index); });
+
+            th.textContent = '';
+            th.appendChild(btn);
+        });
+    });
+})();
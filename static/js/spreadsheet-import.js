@@ -0,0 +1,28 @@
+// Spreadsheet import page: reads a chosen workbook into the hidden data-URI
+// field named by data-workbook-target, so the form posts it with the rest.
+(function() {
+    var MAX_BYTES = 2 * 1024 * 1024;
+    var inputs = document.querySelectorAll('input[type="file"][data-workbook-target]');
+    for (var i = 0; i < inputs.length; i++) {
+        bind(inputs[i]);
+    }
+
+    function bind(input) {
+        var target = document.getElementById(input.getAttribute('data-workbook-target'));
+        input.addEventListener('change', function() {
+            target.value = '';
+            var file = input.files[0];
+            if (!file) return;
+            if (file.size > MAX_BYTES) {
+                alert('Workbook must be at most 2 MB');
+                input.value = '';
+                return;
+            }
+            var reader = new FileReader();
+            reader.onload = function() {
+                target.value = reader.result;
+            };
+            reader.readAsDataURL(file);
+        });
+    }
+})();
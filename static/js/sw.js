@@ -0,0 +1,127 @@
+// Service worker: installable app shell, offline fallback and Web Push.
+//
+// Served from /sw.js so it controls the whole site. Static assets are
+// cached on first use. Page navigations go to the network first; read-only
+// views (dashboard, meetings, ToRs, minutes, ...) are kept so they can be
+// reopened offline, and anything else falls back to the offline page.
+// Signing out clears the kept pages. Push messages carry
+// { title, body, url, tag } and open `url` when clicked.
+var VERSION = 'v1';
+var STATIC_CACHE = 'ahlt-static-' + VERSION;
+var PAGE_CACHE = 'ahlt-pages-' + VERSION;
+var OFFLINE_URL = '/offline';
+var PRECACHE = [OFFLINE_URL, '/static/css/style.css', '/static/js/theme.js', '/static/img/icon.svg'];
+
+// Views that only display data; forms and actions are never kept
+var READ_ONLY_PREFIXES = [
+    '/dashboard', '/meetings', '/tor', '/minutes', '/documents',
+    '/notifications', '/warnings', '/workflow', '/governance'
+];
+var EDIT_SEGMENTS = ['new', 'edit'];
+
+self.addEventListener('install', function(event) {
+    event.waitUntil(
+        caches.open(STATIC_CACHE)
+            .then(function(cache) { return cache.addAll(PRECACHE); })
+            .then(function() { return self.skipWaiting(); })
+    );
+});
+
+self.addEventListener('activate', function(event) {
+    event.waitUntil(
+        caches.keys().then(function(keys) {
+            return Promise.all(keys
+                .filter(function(key) { return key !== STATIC_CACHE && key !== PAGE_CACHE; })
+                .map(function(key) { return caches.delete(key); }));
+        }).then(function() { return self.clients.claim(); })
+    );
+});
+
+function isReadOnlyView(url) {
+    var path = url.pathname;
+    var segments = path.split('/');
+    var listed = READ_ONLY_PREFIXES.some(function(prefix) {
+        return path === prefix || path.indexOf(prefix + '/') === 0;
+    });
+    return listed && !segments.some(function(s) { return EDIT_SEGMENTS.indexOf(s) !== -1; });
+}
+
+self.addEventListener('fetch', function(event) {
+    var request = event.request;
+    var url = new URL(request.url);
+    if (url.origin !== self.location.origin) {
+        return;
+    }
+
+    if (request.method === 'POST' && url.pathname === '/logout') {
+        event.waitUntil(caches.delete(PAGE_CACHE));
+        return;
+    }
+    if (request.method !== 'GET') {
+        return;
+    }
+
+    if (url.pathname.indexOf('/static/') === 0) {
+        event.respondWith(
+            caches.match(request).then(function(cached) {
+                return cached || fetch(request).then(function(response) {
+                    if (response.ok) {
+                        var copy = response.clone();
+                        caches.open(STATIC_CACHE).then(function(cache) { cache.put(request, copy); });
+                    }
+                    return response;
+                });
+            })
+        );
+        return;
+    }
+
+    if (request.mode === 'navigate') {
+        event.respondWith(
+            fetch(request).then(function(response) {
+                // Redirects (e.g. to /login) are not the page that was asked for
+                if (response.ok && !response.redirected && isReadOnlyView(url)) {
+                    var copy = response.clone();
+                    caches.open(PAGE_CACHE).then(function(cache) { cache.put(request, copy); });
+                }
+                return response;
+            }).catch(function() {
+                return caches.match(request, { cacheName: PAGE_CACHE }).then(function(cached) {
+                    return cached || caches.match(OFFLINE_URL);
+                });
+            })
+        );
+    }
+});
+
+self.addEventListener('push', function(event) {
+    var data = {};
+    if (event.data) {
+        try {
+            data = event.data.json();
+        } catch (e) {
+            data = { body: event.data.text() };
+        }
+    }
+    event.waitUntil(self.registration.showNotification(data.title || 'Ahlt', {
+        body: data.body || '',
+        tag: data.tag || undefined,
+        icon: '/static/img/icon.svg',
+        data: { url: data.url || '/dashboard' }
+    }));
+});
+
+self.addEventListener('notificationclick', function(event) {
+    event.notification.close();
+    var target = (event.notification.data && event.notification.data.url) || '/dashboard';
+    event.waitUntil(
+        self.clients.matchAll({ type: 'window', includeUncontrolled: true }).then(function(windows) {
+            for (var i = 0; i < windows.length; i++) {
+                if (new URL(windows[i].url).pathname === target && 'focus' in windows[i]) {
+                    return windows[i].focus();
+                }
+            }
+            return self.clients.openWindow(target);
+        })
+    );
+});
@@ -1,6 +1,36 @@
 function changePerPage(n) {
+    sessionStorage.setItem('table-focus', '#per-page-select');
     const url = new URL(window.location.href);
     url.searchParams.set('per_page', n);
     url.searchParams.set('page', '1');
     window.location.href = url.toString();
 }
+
+// Sorting and paging reload the page; put focus back on the control that was
+// used so keyboard and screen reader users keep their place.
+(function() {
+    document.addEventListener('click', function(e) {
+        const sortLink = e.target.closest('.sort-link[data-sort-key]');
+        if (sortLink) {
+            sessionStorage.setItem('table-focus', '.sort-link[data-sort-key="' + sortLink.dataset.sortKey + '"]');
+            return;
+        }
+        const pageLink = e.target.closest('[data-page-link]');
+        if (pageLink) {
+            sessionStorage.setItem('table-focus', '[data-page-link][rel="' + pageLink.getAttribute('rel') + '"]');
+        }
+    });
+
+    // This script loads above the table, so wait for the rest of the page
+    document.addEventListener('DOMContentLoaded', function() {
+        const selector = sessionStorage.getItem('table-focus');
+        if (!selector) return;
+        sessionStorage.removeItem('table-focus');
+        const target = document.querySelector(selector)
+            || document.querySelector('.pagination-v2__btn--current');
+        if (target) {
+            if (!target.matches('a, select, button')) target.setAttribute('tabindex', '-1');
+            target.focus();
+        }
+    });
+})();
@@ -0,0 +1,93 @@
+//! Access log tests — covers recording and reading views of classified items.
+//!
+//! - Only items of classified ToRs are recorded, and only while read auditing is on
+//! - Repeated views within the window are recorded once
+//! - Chairs and auditors may read the log; other members may not
+//! - Entries are listed newest first and removed by the retention cleanup
+
+mod common;
+
+use ahlt::models::{access_log, relation, tor};
+use common::*;
+use sqlx::PgPool;
+
+async fn set(pool: &PgPool, name: &str, value: &str) {
+    let id = insert_entity(pool, "setting", name, name).await;
+    insert_prop(pool, id, "value", value).await;
+}
+
+async fn position(pool: &PgPool, tor_id: i64, name: &str, label: &str, holder: i64) {
+    let id = insert_entity(pool, "tor_function", name, label).await;
+    relation::create(pool, "belongs_to_tor", id, tor_id).await.unwrap();
+    tor::assign_to_position(pool, holder, id, "mandatory").await.unwrap();
+}
+
+#[tokio::test]
+async fn test_views_of_classified_tors_are_recorded() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let secret = insert_entity(pool, "tor", "secret", "Secret Board").await;
+    insert_prop(pool, secret, "classification", "RESTRICTED").await;
+    let open = insert_entity(pool, "tor", "open", "Open Forum").await;
+    let meeting = insert_entity(pool, "meeting", "m1", "Kick-off").await;
+    let alice = insert_entity(pool, "user", "alice", "Alice").await;
+    let bob = insert_entity(pool, "user", "bob", "Bob").await;
+
+    assert!(access_log::is_classified(pool, secret).await.unwrap());
+    assert!(!access_log::is_classified(pool, open).await.unwrap());
+
+    // Unclassified ToRs are never recorded and show no tab, even to auditors
+    assert!(!access_log::track(pool, open, open, alice, "alice", true).await.unwrap());
+    assert!(access_log::find_for_entity(pool, open, open).await.unwrap().is_empty());
+
+    // Read auditing is on by default; reloads within the window count once
+    assert!(!access_log::track(pool, meeting, secret, alice, "alice", false).await.unwrap());
+    assert!(!access_log::record(pool, meeting, secret, alice, "alice").await.unwrap());
+    assert!(access_log::record(pool, meeting, secret, bob, "bob").await.unwrap());
+    let entries = access_log::find_for_entity(pool, secret, meeting).await.unwrap();
+    assert_eq!(entries.iter().map(|e| e.user_id).collect::<Vec<_>>(), vec![bob, alice]);
+    assert_eq!(entries[0].user_label, "Bob");
+    assert!(access_log::find_for_entity(pool, secret, secret).await.unwrap().is_empty());
+
+    // Deleted accounts keep their username but lose the label
+    sqlx::query("DELETE FROM entities WHERE id = $1").bind(bob).execute(pool).await.unwrap();
+    let entries = access_log::find_for_entity(pool, secret, meeting).await.unwrap();
+    assert_eq!(entries[0].username, "bob");
+    assert_eq!(entries[0].user_label, "");
+
+    // Switching read auditing off stops recording
+    set(pool, "audit.read_mode", "false").await;
+    assert!(access_log::track(pool, secret, secret, alice, "alice", true).await.unwrap());
+    assert!(access_log::find_for_entity(pool, secret, secret).await.unwrap().is_empty());
+
+    let future = chrono::Utc::now() + chrono::Duration::minutes(1);
+    assert_eq!(access_log::cleanup(pool, future).await.unwrap(), 2);
+    assert!(access_log::find_for_entity(pool, secret, meeting).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_chairs_and_auditors_can_view() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let board = insert_entity(pool, "tor", "board", "Board").await;
+    insert_prop(pool, board, "classification", "CONFIDENTIAL").await;
+    let other = insert_entity(pool, "tor", "other", "Other").await;
+    let chair = insert_entity(pool, "user", "chair", "Chair Person").await;
+    let member = insert_entity(pool, "user", "member", "Member").await;
+    let outsider = insert_entity(pool, "user", "outsider", "Outsider").await;
+    position(pool, board, "board_chair", "Chair", chair).await;
+    position(pool, board, "board_member", "Member", member).await;
+    position(pool, other, "other_chair", "Chair", outsider).await;
+
+    assert!(access_log::is_chair(pool, chair, board).await.unwrap());
+    assert!(!access_log::is_chair(pool, member, board).await.unwrap());
+    assert!(!access_log::is_chair(pool, outsider, board).await.unwrap());
+
+    assert!(access_log::track(pool, board, board, chair, "chair", false).await.unwrap());
+    assert!(!access_log::track(pool, board, board, member, "member", false).await.unwrap());
+    assert!(access_log::track(pool, board, board, outsider, "outsider", true).await.unwrap());
+    assert!(access_log::can_view(pool, member, true, board).await.unwrap());
+
+    // Every viewer is recorded, whether or not they may read the log
+    assert_eq!(access_log::find_for_entity(pool, board, board).await.unwrap().len(), 3);
+}
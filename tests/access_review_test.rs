@@ -0,0 +1,64 @@
+//! Access review tests — covers the periodic role/permission report.
+//!
+//! - A review captures roles, permissions, ToR positions and capabilities
+//! - The snapshot exports as CSV and PDF and ignores later changes
+//! - A review is signed off once, recording who reviewed it
+
+mod common;
+
+use ahlt::export::pdf;
+use ahlt::models::{access_review, relation};
+use common::*;
+
+#[tokio::test]
+async fn test_review_snapshot_and_sign_off() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+
+    let alice = insert_entity(pool, "user", "alice", "Alice").await;
+    let bob = insert_entity(pool, "user", "bob", "Bob").await;
+    let admin = insert_entity(pool, "role", "admin", "Administrator").await;
+    let audit = insert_entity(pool, "permission", "audit.view", "View Audit").await;
+    relation::create(pool, "has_permission", admin, audit).await.unwrap();
+    relation::create(pool, "has_role", alice, admin).await.unwrap();
+    let board = insert_entity(pool, "tor", "board", "Board").await;
+    let chair = insert_entity(pool, "tor_function", "board_chair", "Chair").await;
+    insert_prop(pool, chair, "can_call_meetings", "true").await;
+    insert_prop(pool, chair, "can_approve_proposals", "false").await;
+    relation::create(pool, "belongs_to_tor", chair, board).await.unwrap();
+    relation::create(pool, "fills_position", bob, chair).await.unwrap();
+
+    let id = access_review::generate(pool, alice).await.unwrap();
+    // Later changes do not alter the captured access
+    relation::create(pool, "has_role", bob, admin).await.unwrap();
+
+    let review = access_review::find_by_id(pool, id).await.unwrap().unwrap();
+    assert_eq!(review.user_count, 2);
+    assert_eq!(review.generated_by_name, "Alice");
+    assert!(!review.is_signed_off());
+
+    let entries = access_review::find_entries(pool, id).await.unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].roles, vec!["Administrator"]);
+    assert_eq!(entries[0].permissions, vec!["audit.view"]);
+    assert!(entries[1].roles.is_empty());
+    assert_eq!(entries[1].positions, vec!["Board — Chair"]);
+    assert_eq!(entries[1].capabilities, vec!["Board: Call meetings"]);
+
+    let csv = access_review::to_table(&review, &entries).to_csv();
+    assert_eq!(csv.lines().next(), Some("User,Username,Active,Roles,Permissions,ToR positions,Capabilities"));
+    assert!(csv.contains("Bob,bob,yes,,,Board — Chair,Board: Call meetings"));
+
+    assert!(access_review::sign_off(pool, id, bob, "  No changes needed ").await.unwrap());
+    assert!(!access_review::sign_off(pool, id, alice, "again").await.unwrap());
+    let review = access_review::find_by_id(pool, id).await.unwrap().unwrap();
+    assert!(review.is_signed_off());
+    assert_eq!(review.reviewed_by_name, "Bob");
+    assert_eq!(review.notes, "No changes needed");
+    assert!(!review.reviewed_at.is_empty());
+
+    let bytes = pdf::render_access_review(&review, &entries, None);
+    assert!(bytes.starts_with(b"%PDF-"));
+    assert!(String::from_utf8_lossy(&bytes).contains("<ahlt:reviewedBy>Bob</ahlt:reviewedBy>"));
+    assert_eq!(access_review::find_all(pool).await.unwrap().len(), 1);
+}
@@ -0,0 +1,168 @@
+//! Action item tests — covers follow-up work tracked against a ToR's
+//! decisions and the overdue warning generator.
+//!
+//! - Items link to their agenda point and the decision taken on it
+//! - Validation of owner, due date and status; open items sort first
+//! - Closing an item stamps its completion date
+//! - Overdue items warn their owner once and resolve when done
+//! - Items converted from a warning resolve it when done
+
+mod common;
+
+use ahlt::models::action_item::{self, ActionItemInput};
+use ahlt::models::{agenda_point, coa, opinion, relation, tor};
+use ahlt::warnings::clock;
+use chrono::TimeZone;
+use common::*;
+
+/// A ToR with `alice` in its chair position. Returns (tor, alice).
+async fn board_with_member(pool: &sqlx::PgPool) -> (i64, i64) {
+    let alice = insert_entity(pool, "user", "alice", "Alice").await;
+    let board = insert_entity(pool, "tor", "board", "Board").await;
+    let chair = insert_entity(pool, "tor_function", "board_chair", "Chair").await;
+    relation::create(pool, "belongs_to_tor", chair, board).await.unwrap();
+    tor::assign_to_position(pool, alice, chair, "mandatory").await.unwrap();
+    (board, alice)
+}
+
+fn input<'a>(title: &'a str, owner_id: i64, due_date: &'a str) -> ActionItemInput<'a> {
+    ActionItemInput { title, description: "", owner_id, due_date, status: "open" }
+}
+
+#[tokio::test]
+async fn test_action_items_from_decision() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let (board, alice) = board_with_member(pool).await;
+    let outsider = insert_entity(pool, "user", "mallory", "Mallory").await;
+
+    let owners = action_item::find_owners(pool, board).await.unwrap();
+    assert_eq!(owners, vec![(alice, "Alice".to_string())]);
+    let owner_ids: Vec<i64> = owners.iter().map(|(id, _)| *id).collect();
+
+    assert!(action_item::validate(&input("Publish budget", alice, "2026-11-01"), &owner_ids).is_empty());
+    let errors = action_item::validate(&ActionItemInput { status: "someday", ..input(" ", outsider, "next week") }, &owner_ids);
+    assert_eq!(errors.len(), 4, "{errors:?}");
+    assert_eq!(action_item::follow_up_titles("Publish budget\n\n  Brief staff  \n"), vec!["Publish budget", "Brief staff"]);
+
+    let budget = agenda_point::create(pool, board, "Budget 2027", "", "decision", "2026-10-01", 30, alice, "", "", "")
+        .await.unwrap();
+    let approve = coa::create(pool, "Approve", "", "simple", alice).await.unwrap();
+    relation::create(pool, "considers_coa", budget, approve).await.unwrap();
+    let decision_id = opinion::record_decision(pool, budget, alice, approve, "Agreed").await.unwrap();
+
+    let publish = action_item::create(pool, board, &input("Publish budget", alice, "2026-11-01"), Some(budget), alice)
+        .await.unwrap();
+    let brief = action_item::create(pool, board, &input("Brief staff", alice, ""), None, alice).await.unwrap();
+    let file = action_item::create(pool, board, &input("File report", alice, "2026-10-20"), None, alice).await.unwrap();
+
+    let item = action_item::find_by_id(pool, publish).await.unwrap().unwrap();
+    assert_eq!((item.tor_id, item.owner_name.as_str()), (board, "Alice"));
+    assert_eq!((item.agenda_point_id, item.decision_id), (Some(budget), Some(decision_id)));
+    assert_eq!(item.agenda_point_title, "Budget 2027");
+    assert!(item.is_overdue("2026-11-02") && !item.is_overdue("2026-11-01"));
+
+    let file_item = action_item::find_by_id(pool, file).await.unwrap().unwrap();
+    action_item::set_status(pool, file, action_item::DONE, &file_item.status).await.unwrap();
+    let file_item = action_item::find_by_id(pool, file).await.unwrap().unwrap();
+    assert!(!file_item.is_open() && !file_item.completed_date.is_empty());
+    assert!(!file_item.is_overdue("2027-01-01"));
+
+    // Open items by due date (undated last), then closed ones
+    let ids: Vec<i64> = action_item::find_for_tor(pool, board).await.unwrap().iter().map(|i| i.id).collect();
+    assert_eq!(ids, vec![publish, brief, file]);
+    assert_eq!(action_item::find_for_owner(pool, alice).await.unwrap().len(), 3);
+    assert!(action_item::find_for_owner(pool, outsider).await.unwrap().is_empty());
+    assert_eq!(action_item::find_for_agenda_point(pool, budget).await.unwrap().len(), 1);
+
+    let reopened = ActionItemInput { status: "in_progress", ..input("File the report", alice, "2026-10-20") };
+    action_item::update(pool, &file_item, &reopened).await.unwrap();
+    let file_item = action_item::find_by_id(pool, file).await.unwrap().unwrap();
+    assert_eq!((file_item.title.as_str(), file_item.status_label()), ("File the report", "In progress"));
+    assert!(file_item.completed_date.is_empty());
+
+    action_item::delete(pool, brief).await.unwrap();
+    assert!(action_item::find_by_id(pool, brief).await.unwrap().is_none());
+}
+
+async fn overdue_warning(pool: &sqlx::PgPool) -> Vec<(i64, String)> {
+    sqlx::query_as(
+        "SELECT e.id, st.value FROM entities e
+         JOIN entity_properties sa ON sa.entity_id = e.id AND sa.key = 'source_action'
+         JOIN entity_properties st ON st.entity_id = e.id AND st.key = 'status'
+         WHERE e.entity_type = 'warning' AND sa.value = 'scheduled.action_item_overdue'",
+    ).fetch_all(pool).await.unwrap()
+}
+
+#[tokio::test]
+async fn test_overdue_action_item_warning() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let (board, alice) = board_with_member(pool).await;
+    let conn_map = ahlt::handlers::warning_handlers::ws::new_connection_map();
+
+    let late = action_item::create(pool, board, &input("Publish budget", alice, "2026-10-10"), None, alice).await.unwrap();
+    action_item::create(pool, board, &input("Brief staff", alice, "2026-10-30"), None, alice).await.unwrap();
+    action_item::create(pool, board, &input("Someday", alice, ""), None, alice).await.unwrap();
+
+    let at = chrono::Utc.with_ymd_and_hms(2026, 10, 16, 6, 0, 0).unwrap();
+    clock::with_fixed_now(at, ahlt::warnings::generators::check_overdue_action_items(pool, &conn_map)).await;
+    let warnings = overdue_warning(pool).await;
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].1, "active");
+
+    let (message, receipt_user): (String, i64) = sqlx::query_as(
+        "SELECT m.value, r.target_id FROM entities e
+         JOIN entity_properties m ON m.entity_id = e.id AND m.key = 'message'
+         JOIN relations fw ON fw.target_id = e.id
+         JOIN relations r ON r.source_id = fw.source_id
+         WHERE e.id = $1
+           AND fw.relation_type_id = (SELECT id FROM entities WHERE entity_type = 'relation_type' AND name = 'for_warning')
+           AND r.relation_type_id = (SELECT id FROM entities WHERE entity_type = 'relation_type' AND name = 'for_user')",
+    ).bind(warnings[0].0).fetch_one(pool).await.unwrap();
+    assert!(message.contains("Publish budget") && message.contains("Board"), "{message}");
+    assert_eq!(receipt_user, alice);
+
+    // Deduplicated while the item stays overdue
+    clock::with_fixed_now(at, ahlt::warnings::generators::check_overdue_action_items(pool, &conn_map)).await;
+    assert_eq!(overdue_warning(pool).await.len(), 1);
+
+    action_item::set_status(pool, late, action_item::DONE, "open").await.unwrap();
+    clock::with_fixed_now(at, ahlt::warnings::generators::check_overdue_action_items(pool, &conn_map)).await;
+    let warnings = overdue_warning(pool).await;
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].1, "resolved");
+}
+
+#[tokio::test]
+async fn test_warning_converted_to_action_item() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let (board, alice) = board_with_member(pool).await;
+
+    let warning = ahlt::warnings::create_warning(
+        pool, "high", "security", "event.login.failed", "Repeated failed logins", "", "system",
+    ).await.unwrap();
+    ahlt::warnings::create_receipts(pool, warning, &[alice]).await.unwrap();
+
+    let id = action_item::create(pool, board, &input("Review login policy", alice, "2026-11-01"), None, alice)
+        .await.unwrap();
+    action_item::link_warning(pool, id, warning).await.unwrap();
+    let items = action_item::find_for_warning(pool, warning).await.unwrap();
+    assert_eq!(items.iter().map(|i| i.id).collect::<Vec<_>>(), vec![id]);
+    let item = &items[0];
+    assert_eq!((item.warning_id, item.agenda_point_id), (Some(warning), None));
+
+    // Only finishing the item resolves the warning
+    assert_eq!(action_item::resolve_linked_warning(pool, item, "in_progress", alice).await.unwrap(), None);
+    assert_eq!(action_item::resolve_linked_warning(pool, item, action_item::DONE, alice).await.unwrap(), Some(warning));
+
+    let statuses: Vec<(String,)> = sqlx::query_as(
+        "SELECT p.value FROM entity_properties p WHERE p.key = 'status' AND (p.entity_id = $1 OR p.entity_id IN (
+             SELECT r.source_id FROM relations r
+             WHERE r.target_id = $1
+               AND r.relation_type_id = (SELECT id FROM entities WHERE entity_type = 'relation_type' AND name = 'for_warning')))",
+    ).bind(warning).fetch_all(pool).await.unwrap();
+    assert_eq!(statuses.len(), 2, "the warning and alice's receipt");
+    assert!(statuses.iter().all(|(s,)| s == "resolved"), "{statuses:?}");
+}
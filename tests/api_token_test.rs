@@ -0,0 +1,104 @@
+//! API token tests — covers validation, scoped permissions and the token
+//! lifecycle behind `Authorization: Bearer`.
+//!
+//! - Tokens need a name, known scopes and an expiry within a year
+//! - A token only carries its owner's permissions within its scopes
+//! - Wrong secrets, revoked and expired tokens and inactive owners don't resolve
+
+mod common;
+
+use ahlt::models::api_token::{self, ApiTokenInput};
+use ahlt::models::{relation, signing_key};
+use chrono::NaiveDate;
+use common::*;
+
+#[test]
+fn test_token_validation_and_scopes() {
+    let today = NaiveDate::from_ymd_opt(2026, 10, 16).unwrap();
+    let scopes = vec!["tors".to_string()];
+    let valid = ApiTokenInput { label: "Sync", scopes: &scopes, read_only: true, expires_at: "2027-01-01" };
+    assert!(api_token::validate(&valid, today).is_empty());
+
+    let none: Vec<String> = Vec::new();
+    let errors = api_token::validate(&ApiTokenInput { label: " ", scopes: &none, read_only: false, expires_at: "" }, today);
+    assert_eq!(errors, vec!["Name is required", "Choose at least one scope", "Expiry date is required"]);
+
+    let bogus = vec!["admin".to_string()];
+    let errors = api_token::validate(&ApiTokenInput { label: "x", scopes: &bogus, read_only: false, expires_at: "2026-10-15" }, today);
+    assert_eq!(errors, vec!["Unknown scope 'admin'", "Expiry date cannot be in the past"]);
+    let errors = api_token::validate(&ApiTokenInput { label: "x", scopes: &scopes, read_only: false, expires_at: "2027-10-17" }, today);
+    assert_eq!(errors, vec!["API tokens can be valid for at most 365 days"]);
+
+    let owner: Vec<String> = ["audit.view", "tor.edit", "tor.list", "users.list"].iter().map(|s| s.to_string()).collect();
+    assert_eq!(api_token::scoped_permissions(&owner, &["tors"]), vec!["tor.edit", "tor.list"]);
+    assert_eq!(api_token::scoped_permissions(&owner, &["events", "users"]), vec!["audit.view", "users.list"]);
+    assert!(api_token::scoped_permissions(&owner, &["proposals"]).is_empty());
+}
+
+#[tokio::test]
+async fn test_token_lifecycle() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let alice = insert_entity(pool, "user", "alice", "Alice").await;
+    let bob = insert_entity(pool, "user", "bob", "Bob").await;
+    let role = insert_entity(pool, "role", "secretary", "Secretary").await;
+    for code in ["tor.list", "tor.edit", "users.list"] {
+        let perm = insert_entity(pool, "permission", code, code).await;
+        relation::create(pool, "has_permission", role, perm).await.unwrap();
+    }
+    relation::create(pool, "has_role", alice, role).await.unwrap();
+
+    let scopes = vec!["tors".to_string()];
+    let input = ApiTokenInput { label: "Nightly sync", scopes: &scopes, read_only: true, expires_at: "2026-12-31" };
+    let (id, raw) = api_token::create(pool, alice, &input).await.unwrap();
+    let (selector, secret) = raw.split_once('.').unwrap();
+    assert_eq!((selector.len(), secret.len()), (24, 64));
+
+    // Identifying the token leaves no trace of use
+    let identified = api_token::identify(pool, &raw, "2026-10-16").await.unwrap().unwrap();
+    assert_eq!(identified.token_id, id);
+    assert!(api_token::find_for_user(pool, alice).await.unwrap()[0].last_used_at.is_empty());
+
+    // Resolves to the owner with scoped permissions, and records the use
+    let identity = api_token::resolve(pool, &raw, "2026-10-16").await.unwrap().unwrap();
+    assert_eq!((identity.token_id, identity.user_id, identity.username.as_str()), (id, alice, "alice"));
+    assert_eq!(identity.permissions, vec!["tor.edit", "tor.list"]);
+    assert!(identity.read_only);
+    let tokens = api_token::find_for_user(pool, alice).await.unwrap();
+    assert_eq!(tokens.len(), 1);
+    assert_eq!(tokens[0].scope_labels(), "Terms of Reference");
+    assert!(!tokens[0].last_used_at.is_empty());
+    assert!(!tokens[0].token_hash.contains(secret));
+
+    // Wrong secret, unknown selector, malformed and expired tokens don't resolve
+    let forged = format!("{}.{}", selector, "0".repeat(64));
+    assert!(api_token::resolve(pool, &forged, "2026-10-16").await.unwrap().is_none());
+    assert!(api_token::resolve(pool, "nope.nope", "2026-10-16").await.unwrap().is_none());
+    assert!(api_token::resolve(pool, "garbage", "2026-10-16").await.unwrap().is_none());
+    assert!(api_token::resolve(pool, &raw, "2026-12-31").await.unwrap().is_some());
+    assert!(api_token::resolve(pool, &raw, "2027-01-01").await.unwrap().is_none());
+
+    // Permissions follow the owner's roles
+    sqlx::query("DELETE FROM relations WHERE source_id = $1").bind(alice).execute(pool).await.unwrap();
+    let identity = api_token::resolve(pool, &raw, "2026-10-16").await.unwrap().unwrap();
+    assert!(identity.permissions.is_empty());
+
+    // Only the owner can revoke, once
+    assert!(!api_token::revoke(pool, bob, id).await.unwrap());
+    assert!(api_token::revoke(pool, alice, id).await.unwrap());
+    assert!(!api_token::revoke(pool, alice, id).await.unwrap());
+    assert!(api_token::resolve(pool, &raw, "2026-10-16").await.unwrap().is_none());
+    assert!(api_token::find_for_user(pool, alice).await.unwrap()[0].is_revoked());
+
+    // Deactivated owners and rotated-out keys stop tokens too
+    let (_, second) = api_token::create(pool, alice, &input).await.unwrap();
+    sqlx::query("UPDATE entities SET is_active = false WHERE id = $1").bind(alice).execute(pool).await.unwrap();
+    assert!(api_token::resolve(pool, &second, "2026-10-16").await.unwrap().is_none());
+    sqlx::query("UPDATE entities SET is_active = true WHERE id = $1").bind(alice).execute(pool).await.unwrap();
+    assert!(api_token::resolve(pool, &second, "2026-10-16").await.unwrap().is_some());
+    let key = signing_key::find_all(pool).await.unwrap().into_iter().find(|k| k.purpose == "api_key").unwrap();
+    signing_key::rotate(pool, "api_key").await.unwrap();
+    assert!(api_token::resolve(pool, &second, "2026-10-16").await.unwrap().is_some());
+    assert!(signing_key::retire(pool, key.id).await.unwrap());
+    assert!(api_token::resolve(pool, &second, "2026-10-16").await.unwrap().is_none());
+}
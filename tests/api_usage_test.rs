@@ -0,0 +1,72 @@
+//! API usage tests — covers the hourly per-caller, per-endpoint aggregation.
+//!
+//! - Requests in the same hour land in one bucket with summed counters
+//! - Summaries group buckets across hours and honour the time window
+//! - Old buckets are cleaned up
+
+mod common;
+
+use ahlt::models::api_usage;
+use chrono::{Duration, TimeZone, Utc};
+use common::setup_test_db;
+
+#[tokio::test]
+async fn test_record_aggregates_into_hourly_bucket() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let at = Utc.with_ymd_and_hms(2026, 3, 1, 14, 5, 0).unwrap();
+
+    api_usage::record(pool, "alice", "GET /api/v1/users", at, false, 10).await.unwrap();
+    api_usage::record(pool, "alice", "GET /api/v1/users", at + Duration::minutes(30), true, 50).await.unwrap();
+    api_usage::record(pool, "alice", "GET /api/v1/users", at, false, 30).await.unwrap();
+
+    let (buckets,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM entities WHERE entity_type = 'api_usage'")
+        .fetch_one(pool).await.unwrap();
+    assert_eq!(buckets, 1);
+
+    let usage = api_usage::summarize(pool, at - Duration::hours(1)).await.unwrap();
+    assert_eq!(usage.len(), 1);
+    let u = &usage[0];
+    assert_eq!(u.request_count, 3);
+    assert_eq!(u.error_count, 1);
+    assert_eq!(u.avg_latency_ms, 30);
+    assert_eq!(u.max_latency_ms, 50);
+    assert_eq!(u.error_rate_pct(), 33);
+    assert!(!u.is_failing(), "too few requests to flag");
+}
+
+#[tokio::test]
+async fn test_summarize_groups_across_hours_and_respects_window() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let at = Utc.with_ymd_and_hms(2026, 3, 1, 14, 0, 0).unwrap();
+
+    for h in 0..3 {
+        api_usage::record(pool, "bot", "POST /api/v1/entities", at + Duration::hours(h), true, 5).await.unwrap();
+    }
+    api_usage::record(pool, "alice", "GET /api/v1/tors", at + Duration::hours(2), false, 5).await.unwrap();
+
+    let all = api_usage::summarize(pool, at).await.unwrap();
+    assert_eq!(all.len(), 2);
+    assert_eq!(all[0].caller, "bot");
+    assert_eq!(all[0].request_count, 3);
+
+    let recent = api_usage::summarize(pool, at + Duration::hours(2)).await.unwrap();
+    let bot = recent.iter().find(|u| u.caller == "bot").unwrap();
+    assert_eq!(bot.request_count, 1);
+}
+
+#[tokio::test]
+async fn test_cleanup_removes_old_buckets() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let at = Utc.with_ymd_and_hms(2026, 3, 1, 14, 0, 0).unwrap();
+
+    api_usage::record(pool, "alice", "GET /api/v1/tors", at - Duration::days(40), false, 5).await.unwrap();
+    api_usage::record(pool, "alice", "GET /api/v1/tors", at, false, 5).await.unwrap();
+
+    let removed = api_usage::cleanup(pool, at - Duration::days(30)).await.unwrap();
+    assert_eq!(removed, 1);
+    let left = api_usage::summarize(pool, at - Duration::days(60)).await.unwrap();
+    assert_eq!(left[0].request_count, 1);
+}
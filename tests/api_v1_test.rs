@@ -15,6 +15,7 @@ use ahlt::models::{entity, relation, user, tor, proposal};
 use ahlt::models::user::NewUser;
 use ahlt::models::table_filter::{FilterTree, SortSpec};
 use ahlt::auth::password;
+use ahlt::templates_structs::{ApiTorMemberRequest, ApiTorRequest};
 use ahlt::warnings;
 
 mod common;
@@ -485,6 +486,77 @@ async fn test_api_tor_detail_not_found() {
     assert!(result.is_none());
 }
 
+#[test]
+fn test_api_tor_request_validation() {
+    let empty: ApiTorRequest = serde_json::from_str("{}").expect("parse");
+    assert_eq!(empty.validate(true), vec!["Name is required", "Label is required"]);
+    assert!(empty.validate(false).is_empty(), "Update may leave everything unchanged");
+    assert!(empty.props().is_empty());
+
+    let bad: ApiTorRequest = serde_json::from_str(
+        r#"{"name": "ops", "label": "Ops", "status": "paused", "meeting_cadence": "yearly"}"#,
+    ).expect("parse");
+    let errors = bad.validate(true);
+    assert_eq!(errors.len(), 2);
+    assert!(errors[0].starts_with("Status must be one of active, archived"));
+
+    let good: ApiTorRequest = serde_json::from_str(
+        r#"{"name": "ops", "label": "Ops", "description": " Runs ops ", "objectives": ["Keep uptime", " "]}"#,
+    ).expect("parse");
+    assert!(good.validate(true).is_empty());
+    let props = good.props();
+    assert!(props.contains(&("description", "Runs ops".to_string())));
+    assert!(props.contains(&("objectives", r#"["Keep uptime"]"#.to_string())));
+    assert!(!props.iter().any(|(k, _)| *k == "status"), "Omitted fields are not written");
+}
+
+#[tokio::test]
+async fn test_api_tor_create_update_and_members() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+
+    let create: ApiTorRequest = serde_json::from_str(
+        r#"{"name": "api_tor_ops", "label": "Ops Board", "status": "active", "meeting_cadence": "monthly"}"#,
+    ).expect("parse");
+    let props = create.props();
+    let props: Vec<(&str, &str)> = props.iter().map(|(k, v)| (*k, v.as_str())).collect();
+    let tor_id = tor::create(pool, "api_tor_ops", "Ops Board", &props).await.expect("create");
+    let duplicate = tor::create(pool, "api_tor_ops", "Again", &[]).await.expect_err("duplicate");
+    assert!(duplicate.as_database_error().is_some_and(|d| d.is_unique_violation()));
+
+    // Partial update: only the given fields change
+    let update: ApiTorRequest = serde_json::from_str(r#"{"meeting_cadence": "weekly"}"#).expect("parse");
+    let props = update.props();
+    let props: Vec<(&str, &str)> = props.iter().map(|(k, v)| (*k, v.as_str())).collect();
+    tor::update(pool, tor_id, "api_tor_ops", "Ops Board", &props).await.expect("update");
+    let detail = tor::find_detail_by_id(pool, tor_id).await.expect("query").expect("found");
+    assert_eq!((detail.status.as_str(), detail.meeting_cadence.as_str()), ("active", "weekly"));
+
+    // Member management: assign to a position, then vacate it
+    let chair = entity::create(pool, "tor_function", "api_ops_chair", "Chair").await.expect("position");
+    relation::create(pool, "belongs_to_tor", chair, tor_id).await.expect("link");
+    let hash = password::hash_password("Password1!").expect("hash");
+    let alice = user::create(pool, &NewUser {
+        username: "api_tor_alice".to_string(),
+        password: hash,
+        email: "api_tor_alice@test.com".to_string(),
+        display_name: "Alice".to_string(),
+    }).await.expect("user");
+
+    let assign: ApiTorMemberRequest = serde_json::from_str(
+        &format!(r#"{{"user_id": {alice}, "position_id": {chair}}}"#),
+    ).expect("parse");
+    assert_eq!(assign.membership_type, "optional");
+    tor::assign_to_position(pool, assign.user_id, assign.position_id, &assign.membership_type).await.expect("assign");
+    let members = tor::find_members(pool, tor_id).await.expect("members");
+    assert_eq!(members[0].holder_id, Some(alice));
+    assert_eq!(tor::count_members(pool, tor_id).await.expect("count"), 1);
+
+    tor::vacate_position(pool, chair).await.expect("vacate");
+    let members = tor::find_members(pool, tor_id).await.expect("members");
+    assert_eq!(members[0].holder_id, None);
+}
+
 // ---------------------------------------------------------------------------
 // Proposal API (mirrors /api/v1/proposals handler)
 // ---------------------------------------------------------------------------
@@ -0,0 +1,92 @@
+//! Proposal file upload tests — covers multipart parsing, upload validation
+//! and storing files against a proposal.
+//!
+//! - Only accepted types whose content matches their extension are stored,
+//!   up to the size limit
+//! - Uploads are listed with the proposal, count towards its intake minimum
+//!   and the ToR's storage, and deleting one removes its file
+
+mod common;
+
+use ahlt::handlers::multipart;
+use ahlt::models::{attachment, proposal, quota};
+use common::*;
+
+const PNG: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+
+#[test]
+fn test_multipart_parse_and_validate() {
+    let body = b"--XyZ\r\n\
+        Content-Disposition: form-data; name=\"csrf_token\"\r\n\r\n\
+        abc123\r\n\
+        --XyZ\r\n\
+        Content-Disposition: form-data; name=\"file\"; filename=\"C:\\\\Users\\\\me\\\\notes.txt\"\r\n\
+        Content-Type: text/plain\r\n\r\n\
+        line one\r\nline two\r\n\
+        --XyZ--\r\n";
+    let parts = multipart::parse("multipart/form-data; boundary=XyZ", body).unwrap();
+    assert_eq!(parts.len(), 2);
+    assert_eq!((parts[0].name.as_str(), parts[0].text().as_str(), parts[0].filename.as_deref()), ("csrf_token", "abc123", None));
+    assert_eq!(parts[1].filename.as_deref(), Some("notes.txt"));
+    assert_eq!(parts[1].content_type, "text/plain");
+    assert_eq!(parts[1].data, b"line one\r\nline two");
+    assert!(multipart::parse("application/x-www-form-urlencoded", body).is_err());
+    assert!(multipart::parse("multipart/form-data", body).is_err());
+    assert!(multipart::parse("multipart/form-data; boundary=XyZ", b"--XyZ\r\nbroken").is_err());
+
+    let mb = 1024 * 1024;
+    assert_eq!(attachment::validate("scan.PNG", "image/png", PNG, mb), Ok("image/png"));
+    assert_eq!(attachment::validate("plan.pdf", "application/octet-stream", b"%PDF-1.7", mb), Ok("application/pdf"));
+    assert_eq!(attachment::validate("data.csv", "application/vnd.ms-excel", b"a,b\n1,2", mb), Ok("text/csv"));
+    assert_eq!(attachment::validate("", "", b"", mb).unwrap_err(), "Choose a file to upload");
+    assert_eq!(attachment::validate("a.txt", "text/plain", b"", mb).unwrap_err(), "'a.txt' is empty");
+    assert!(attachment::validate("tool.exe", "application/octet-stream", b"MZ", mb).unwrap_err().contains("not an accepted file type"));
+    assert_eq!(
+        attachment::validate("fake.pdf", "application/pdf", b"MZ\x90\0", mb).unwrap_err(),
+        "The content of 'fake.pdf' does not match its file type"
+    );
+    assert!(attachment::validate("scan.png", "text/html", PNG, mb).is_err());
+    assert!(attachment::validate("notes.txt", "text/plain", b"\xff\xfe", mb).is_err());
+    assert_eq!(
+        attachment::validate("big.txt", "text/plain", &vec![b'a'; mb + 1], mb).unwrap_err(),
+        "'big.txt' is 1.0 MB; uploads may be at most 1 MB"
+    );
+}
+
+#[tokio::test]
+async fn test_store_list_and_delete_uploads() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let alice = insert_entity(pool, "user", "alice", "Alice").await;
+    let board = insert_entity(pool, "tor", "board", "Board").await;
+    let pid = proposal::create(pool, board, "Budget", "d", "", alice, "2026-10-01", None).await.unwrap();
+    let dir = std::env::temp_dir().join(format!("ahlt-uploads-{}-{pid}", std::process::id()));
+
+    let intake = proposal::ProposalIntake { min_attachments: 1, ..Default::default() };
+    proposal::save_intake(pool, board, &intake).await.unwrap();
+    assert_eq!(proposal::missing_for_submit(pool, board, pid).await.unwrap().len(), 1);
+
+    let key = attachment::save_file(&dir, PNG).unwrap();
+    let id = attachment::create(pool, pid, &key, "scan.png", "image/png", PNG, alice).await.unwrap();
+
+    let files = attachment::find_for_proposal(pool, pid).await.unwrap();
+    assert_eq!(files.len(), 1);
+    let file = &files[0];
+    assert_eq!((file.id, file.proposal_id, file.file_name.as_str()), (id, pid, "scan.png"));
+    assert_eq!((file.content_type.as_str(), file.size_bytes, file.uploaded_by_name.as_str()), ("image/png", PNG.len() as i64, "Alice"));
+    assert_eq!(file.size_display(), "16 B");
+    assert_eq!(file.sha256.len(), 64);
+    assert_eq!(attachment::read_file(&dir, file).unwrap(), PNG);
+
+    // Uploads satisfy the intake minimum and use the ToR's storage, but are
+    // not library documents
+    assert!(proposal::missing_for_submit(pool, board, pid).await.unwrap().is_empty());
+    assert!(proposal::find_attachments(pool, pid).await.unwrap().is_empty());
+    assert_eq!(quota::tor_storage_bytes(pool, board).await.unwrap(), PNG.len() as i64);
+
+    attachment::delete(pool, &dir, file).await.unwrap();
+    assert!(attachment::find_by_id(pool, id).await.unwrap().is_none());
+    assert!(!dir.join(&key).exists());
+    assert_eq!(quota::tor_storage_bytes(pool, board).await.unwrap(), 0);
+    let _ = std::fs::remove_dir_all(&dir);
+}
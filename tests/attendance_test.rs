@@ -0,0 +1,86 @@
+//! Meeting attendance tests — covers statistics from roll calls.
+//!
+//! - Quorum history per meeting and attendance rates per member
+//! - Cancelled meetings and empty roll calls are left out
+//! - Repeatedly missed quorum warns once and resolves when a meeting is quorate
+
+mod common;
+
+use ahlt::models::meeting::{self, attendance};
+use ahlt::models::relation;
+use ahlt::warnings::generators;
+use common::*;
+use sqlx::PgPool;
+
+async fn meeting(pool: &PgPool, tor: i64, name: &str, date: &str, status: &str, present: &[&str], absent: &[&str]) -> i64 {
+    let id = insert_entity(pool, "meeting", name, name).await;
+    insert_prop(pool, id, "meeting_date", date).await;
+    insert_prop(pool, id, "status", status).await;
+    relation::create(pool, "belongs_to_tor", id, tor).await.unwrap();
+    let roll_call: Vec<_> = present.iter().map(|u| serde_json::json!({ "username": u, "status": "present" }))
+        .chain(absent.iter().map(|u| serde_json::json!({ "username": u, "status": "absent" })))
+        .collect();
+    meeting::update_roll_call(pool, id, &serde_json::Value::from(roll_call).to_string()).await.unwrap();
+    id
+}
+
+async fn quorum_warnings(pool: &PgPool) -> Vec<String> {
+    let rows: Vec<(String,)> = sqlx::query_as(
+        "SELECT st.value FROM entities e
+         JOIN entity_properties sa ON sa.entity_id = e.id AND sa.key = 'source_action'
+         JOIN entity_properties st ON st.entity_id = e.id AND st.key = 'status'
+         WHERE e.entity_type = 'warning' AND sa.value = 'scheduled.quorum_missed'",
+    ).fetch_all(pool).await.unwrap();
+    rows.into_iter().map(|(s,)| s).collect()
+}
+
+#[tokio::test]
+async fn test_attendance_statistics() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    insert_entity(pool, "user", "alice", "Alice").await;
+    let board = insert_entity(pool, "tor", "board", "Board").await;
+
+    meeting(pool, board, "m1", "2026-01-10", "completed", &["alice", "bob"], &["carol"]).await;
+    meeting(pool, board, "m2", "2026-02-10", "completed", &["alice"], &["bob", "carol"]).await;
+    meeting(pool, board, "m3", "2026-03-10", "cancelled", &[], &["alice", "bob", "carol"]).await;
+    meeting(pool, board, "m4", "2026-04-10", "confirmed", &[], &[]).await;
+
+    let stats = attendance::find_for_tor(pool, board).await.unwrap();
+    assert_eq!(stats.quorum_pct, 50);
+    let history: Vec<_> = stats.meetings.iter().map(|m| (m.label.as_str(), m.present(), m.quorum, m.quorum_met())).collect();
+    assert_eq!(history, vec![("m1", 2, 2, true), ("m2", 1, 2, false)]);
+    assert_eq!(stats.quorate_count(), 1);
+    assert_eq!(stats.average_pct(), 50);
+    assert_eq!(stats.trailing_misses(), 1);
+
+    let rates: Vec<_> = stats.members.iter().map(|m| (m.display_name.as_str(), m.expected(), m.rate_pct())).collect();
+    assert_eq!(rates, vec![("Alice", 2, 100), ("bob", 2, 50), ("carol", 2, 0)]);
+
+    let chart: serde_json::Value = serde_json::from_str(&stats.chart_json()).unwrap();
+    assert_eq!(chart["meetings"][1]["quorum_met"], false);
+    assert_eq!(chart["members"][0]["rate_pct"], 100);
+}
+
+#[tokio::test]
+async fn test_repeated_quorum_miss_warning() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let board = insert_entity(pool, "tor", "board", "Board").await;
+    let conn_map = ahlt::handlers::warning_handlers::ws::new_connection_map();
+
+    meeting(pool, board, "m1", "2026-01-10", "completed", &[], &["alice", "bob"]).await;
+    meeting(pool, board, "m2", "2026-02-10", "completed", &["alice"], &["bob", "carol"]).await;
+    generators::check_quorum_misses(pool, &conn_map).await;
+    assert!(quorum_warnings(pool).await.is_empty());
+
+    meeting(pool, board, "m3", "2026-03-10", "completed", &[], &["alice"]).await;
+    generators::check_quorum_misses(pool, &conn_map).await;
+    generators::check_quorum_misses(pool, &conn_map).await;
+    assert_eq!(quorum_warnings(pool).await, vec!["active"]);
+    assert_eq!(attendance::find_repeated_misses(pool, 3).await.unwrap(), vec![(board, "Board".to_string(), 3)]);
+
+    meeting(pool, board, "m4", "2026-04-10", "completed", &["alice", "bob"], &[]).await;
+    generators::check_quorum_misses(pool, &conn_map).await;
+    assert_eq!(quorum_warnings(pool).await, vec!["resolved"]);
+}
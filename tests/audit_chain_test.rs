@@ -0,0 +1,69 @@
+//! Audit hash chain tests — covers tamper evidence in both audit stores.
+//!
+//! - New database entries and log lines link to their predecessor
+//! - Verification reports the first edited, removed or unhashed entry
+//! - Entries from before the chain are counted, not flagged
+
+mod common;
+
+use ahlt::audit::chain;
+use common::*;
+use serde_json::json;
+
+#[tokio::test]
+async fn test_database_and_file_chains() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let dir = std::env::temp_dir().join(format!("ahlt-audit-chain-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let enabled = insert_entity(pool, "setting", "audit.enabled", "audit.enabled").await;
+    insert_prop(pool, enabled, "value", "true").await;
+    let path = insert_entity(pool, "setting", "audit.log_path", "audit.log_path").await;
+    insert_prop(pool, path, "value", dir.to_str().unwrap()).await;
+
+    // An entry from before the chain existed, then three chained ones
+    let legacy = insert_entity(pool, "audit_entry", "user.created", "legacy").await;
+    insert_prop(pool, legacy, "action", "user.created").await;
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("audit-2020-01-01.jsonl"), "{\"action\":\"legacy\"}\n").unwrap();
+
+    let alice = insert_entity(pool, "user", "alice", "Alice").await;
+    for (action, id) in [("role.created", 1), ("proposal.submitted", 2), ("user.deleted", 3)] {
+        ahlt::audit::log(pool, alice, action, "role", id, json!({ "summary": format!("Entry {id}") })).await.unwrap();
+    }
+
+    let report = chain::verify_database(pool).await.unwrap();
+    assert!(report.is_intact(), "{:?}", report.broken);
+    assert_eq!((report.checked, report.unchained), (2, 1));
+    let report = chain::verify_files(pool).await.unwrap();
+    assert!(report.is_intact(), "{:?}", report.broken);
+    assert_eq!((report.checked, report.unchained), (3, 1));
+
+    // Log lines carry their links; the first links to the genesis hash
+    let today = chrono::Utc::now().format("%Y-%m-%d").to_string();
+    let log = std::fs::read_to_string(dir.join(format!("audit-{today}.jsonl"))).unwrap();
+    let lines: Vec<serde_json::Value> = log.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+    assert_eq!(lines[0]["prev_hash"], chain::GENESIS);
+    assert_eq!(lines[1]["prev_hash"], lines[0]["hash"]);
+    assert_eq!(lines[2]["hash"].as_str().unwrap(), chain::entry_hash(&lines[2]));
+
+    // Editing a database summary breaks that entry
+    let ids: Vec<i64> = sqlx::query_scalar("SELECT id FROM entities WHERE entity_type = 'audit_entry' ORDER BY id")
+        .fetch_all(pool).await.unwrap();
+    sqlx::query("UPDATE entity_properties SET value = 'Edited' WHERE entity_id = $1 AND key = 'summary'")
+        .bind(ids[2]).execute(pool).await.unwrap();
+    let report = chain::verify_database(pool).await.unwrap();
+    let broken = report.broken.unwrap();
+    assert_eq!(broken.location, format!("Database entry #{}", ids[2]));
+    assert!(broken.reason.contains("content was changed"));
+    assert_eq!(report.checked, 1);
+
+    // Removing a log line breaks the next one
+    let without_middle: Vec<&str> = log.lines().enumerate().filter(|(i, _)| *i != 1).map(|(_, l)| l).collect();
+    std::fs::write(dir.join(format!("audit-{today}.jsonl")), without_middle.join("\n") + "\n").unwrap();
+    let broken = chain::verify_files(pool).await.unwrap().broken.unwrap();
+    assert_eq!(broken.location, format!("audit-{today}.jsonl line 2"));
+    assert!(broken.reason.contains("removed or changed"));
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,78 @@
+//! Audit export tests — covers filtering and exports from both stores.
+//!
+//! - Database entries filter by user, action prefix, target type and date
+//! - Exports merge database entries with the daily log files
+//! - Export periods default to 30 days and are bounded
+
+mod common;
+
+use ahlt::audit::export;
+use ahlt::models::audit::{self, AuditFilter};
+use chrono::NaiveDate;
+use common::*;
+use serde_json::json;
+
+fn filter(pairs: &[(&str, &str)]) -> AuditFilter {
+    let query: String = pairs.iter().map(|(k, v)| format!("{k}={v}&")).collect();
+    serde_urlencoded::from_str(&query).unwrap()
+}
+
+#[tokio::test]
+async fn test_filters_and_export() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let dir = std::env::temp_dir().join(format!("ahlt-audit-export-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let enabled = insert_entity(pool, "setting", "audit.enabled", "audit.enabled").await;
+    insert_prop(pool, enabled, "value", "true").await;
+    let path = insert_entity(pool, "setting", "audit.log_path", "audit.log_path").await;
+    insert_prop(pool, path, "value", dir.to_str().unwrap()).await;
+
+    let alice = insert_entity(pool, "user", "alice", "Alice").await;
+    let bob = insert_entity(pool, "user", "bob", "Bob").await;
+    ahlt::audit::log(pool, alice, "role.created", "role", 7, json!({ "summary": "Created role Clerk" })).await.unwrap();
+    ahlt::audit::log(pool, bob, "user.created", "user", 9, json!({ "summary": "Created user carol" })).await.unwrap();
+    ahlt::audit::log(pool, alice, "proposal.submitted", "proposal", 3, json!({ "summary": "Submitted, \"Budget\"" })).await.unwrap();
+
+    // The database keeps the high-value actions only
+    let page = audit::find_paginated(pool, 1, 25, &AuditFilter::default()).await.unwrap();
+    assert_eq!(page.total_count, 2);
+    let page = audit::find_paginated(pool, 1, 25, &filter(&[("user", "alice"), ("action", "role.")])).await.unwrap();
+    assert_eq!(page.entries.iter().map(|e| e.target_id).collect::<Vec<_>>(), vec![7]);
+    let page = audit::find_paginated(pool, 1, 25, &filter(&[("target_type", "user"), ("action", "all")])).await.unwrap();
+    assert_eq!(page.total_count, 1);
+    let page = audit::find_paginated(pool, 1, 25, &filter(&[("to", "2020-01-01")])).await.unwrap();
+    assert_eq!(page.total_count, 0);
+    assert_eq!(filter(&[("user", "alice"), ("q", "a b"), ("from", "bad")]).query_string(), "&q=a%20b&user=alice");
+
+    // Exports add the log files, which have every action
+    let today = chrono::Utc::now().date_naive();
+    let (from, to) = export::period(&AuditFilter::default(), today).unwrap();
+    assert_eq!((to - from).num_days(), 29);
+    assert!(export::period(&filter(&[("from", "2026-02-01"), ("to", "2026-01-01")]), today).is_err());
+    assert!(export::period(&filter(&[("from", "2024-01-01"), ("to", "2026-01-01")]), today).is_err());
+
+    let all = export::collect(pool, &AuditFilter::default(), from, to).await.unwrap();
+    assert_eq!(all.iter().filter(|r| r.source == "database").count(), 2);
+    assert_eq!(all.iter().filter(|r| r.source == "file").count(), 3);
+
+    let alice_only = export::collect(pool, &filter(&[("user", "alice")]), from, to).await.unwrap();
+    let mut actions: Vec<_> = alice_only.iter().map(|r| (r.source, r.action.as_str())).collect();
+    actions.sort();
+    assert_eq!(actions, vec![("database", "role.created"), ("file", "proposal.submitted"), ("file", "role.created")]);
+
+    let proposals = export::collect(pool, &filter(&[("action", "proposal.")]), from, to).await.unwrap();
+    let csv = export::to_csv(&proposals);
+    let mut lines = csv.lines();
+    assert_eq!(lines.next().unwrap(), "timestamp,source,user_id,username,action,target_type,target_id,summary,details");
+    let row = lines.next().unwrap();
+    assert!(row.contains(&format!(",file,{},alice,proposal.submitted,proposal,3,\"Submitted, \"\"Budget\"\"\",", alice)));
+    assert!(lines.next().is_none());
+
+    let jsonl = export::to_jsonl(&proposals).unwrap();
+    let parsed: serde_json::Value = serde_json::from_str(jsonl.trim()).unwrap();
+    assert_eq!(parsed["details"]["summary"], "Submitted, \"Budget\"");
+    assert_eq!(export::file_name(NaiveDate::from_ymd_opt(2026, 1, 1).unwrap(), today, "csv"), format!("audit-2026-01-01-to-{today}.csv"));
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -5,12 +5,14 @@
 //! - Password verification (correct and incorrect)
 //! - User creation with hashed passwords
 //! - Password updates and re-verification
+//! - Post-login redirect targets (deep links and role landing pages)
+//! - Session idle timeout and absolute lifetime
 
 mod common;
 
 use ahlt::models::user::NewUser;
-use ahlt::auth::password;
-use ahlt::models::user;
+use ahlt::auth::{password, redirect, timeout};
+use ahlt::models::{role, user};
 use common::*;
 
 const TEST_USERNAME: &str = "testuser";
@@ -159,3 +161,97 @@ async fn test_update_password_and_verify() {
     assert!(!password::verify_password(old_password, &updated.password)
         .expect("Old password verification failed"));
 }
+
+#[test]
+fn test_redirect_safe_path() {
+    assert_eq!(redirect::safe_path("/tor/3/meetings?tab=open"), Some("/tor/3/meetings?tab=open"));
+    assert_eq!(redirect::safe_path("https://evil.example/"), None);
+    assert_eq!(redirect::safe_path("//evil.example/"), None);
+    assert_eq!(redirect::safe_path("/\\evil.example/"), None);
+    assert_eq!(redirect::safe_path("/login?next=/x"), None);
+    assert_eq!(redirect::safe_path("/logout"), None);
+    assert_eq!(redirect::safe_path("dashboard"), None);
+}
+
+#[test]
+fn test_redirect_login_url_encodes_next() {
+    assert_eq!(redirect::login_url("/tor/3?tab=a b"), "/login?next=/tor/3%3Ftab%3Da%20b");
+    assert_eq!(redirect::login_url("/"), "/login");
+    assert_eq!(redirect::login_url("//evil.example"), "/login");
+}
+
+#[tokio::test]
+async fn test_role_landing_page_for_user() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let user_id = insert_entity(pool, "user", "lander", "Lander").await;
+
+    // No roles — no landing page
+    assert_eq!(role::find_landing_page_for_user(pool, user_id).await.unwrap(), None);
+
+    let first: i64 = sqlx::query_scalar(
+        "INSERT INTO entities (entity_type, name, label, sort_order) VALUES ('role', 'secretary', 'Secretary', 1) RETURNING id",
+    ).fetch_one(pool).await.unwrap();
+    let second: i64 = sqlx::query_scalar(
+        "INSERT INTO entities (entity_type, name, label, sort_order) VALUES ('role', 'member', 'Member', 2) RETURNING id",
+    ).fetch_one(pool).await.unwrap();
+    ahlt::models::relation::create(pool, "has_role", user_id, first).await.unwrap();
+    ahlt::models::relation::create(pool, "has_role", user_id, second).await.unwrap();
+
+    // Only the second role configures one
+    role::set_landing_page(pool, second, "/proposals").await.unwrap();
+    assert_eq!(role::find_landing_page_for_user(pool, user_id).await.unwrap().as_deref(), Some("/proposals"));
+
+    // Lower sort_order wins
+    role::set_landing_page(pool, first, "/tor").await.unwrap();
+    assert_eq!(role::find_landing_page_for_user(pool, user_id).await.unwrap().as_deref(), Some("/tor"));
+
+    // Clearing falls through to the next role
+    role::set_landing_page(pool, first, "").await.unwrap();
+    assert_eq!(role::find_landing_page_for_user(pool, user_id).await.unwrap().as_deref(), Some("/proposals"));
+    let detail = role::find_detail_by_id(pool, first).await.unwrap().unwrap();
+    assert_eq!(detail.landing_page, "");
+}
+
+#[test]
+fn test_session_limits_idle_and_absolute() {
+    let limits = timeout::SessionLimits { idle_secs: 1800, absolute_secs: 12 * 3600 };
+    let login = 1_000_000;
+
+    // Idle: expires 30 minutes after the last request
+    assert!(limits.is_active(login, login, login + 1799));
+    assert!(!limits.is_active(login, login, login + 1800));
+
+    // Activity keeps it alive, but never past the absolute lifetime
+    let late = login + 12 * 3600 - 60;
+    assert_eq!(limits.expires_at(login, late), login + 12 * 3600);
+    assert!(!limits.is_active(login, late, login + 12 * 3600));
+}
+
+#[tokio::test]
+async fn test_keep_signed_in_extends_only_idle_timeout() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+
+    let normal = timeout::SessionLimits::load(pool, false).await;
+    let kept = timeout::SessionLimits::load(pool, true).await;
+    assert_eq!(normal.idle_secs, 30 * 60);
+    assert_eq!(kept.idle_secs, 1440 * 60);
+    assert_eq!(normal.absolute_secs, kept.absolute_secs);
+
+    // Settings override the declared defaults
+    let id = insert_entity(pool, "setting", "session.idle_timeout_minutes", "Idle").await;
+    insert_prop(pool, id, "value", "5").await;
+    assert_eq!(timeout::SessionLimits::load(pool, false).await.idle_secs, 300);
+}
+
+#[test]
+fn test_session_tracker_keeps_latest_expiry() {
+    let tracker = timeout::SessionTracker::new();
+    assert_eq!(tracker.expires_at(1), None);
+    tracker.record(1, 100);
+    tracker.record(1, 250);
+    assert_eq!(tracker.expires_at(1), Some(250));
+    tracker.forget(1);
+    assert_eq!(tracker.expires_at(1), None);
+}
@@ -0,0 +1,153 @@
+//! Ballot tests — covers vote parsing and tallies, and the ballot flow from
+//! opening to the recorded decision.
+//!
+//! - Quorum counts abstentions; ranked ballots run off, weighted ones sum
+//! - Closing a carried ballot records the decision with its tally; a ballot
+//!   without quorum closes without one
+
+mod common;
+
+use std::collections::HashMap;
+
+use ahlt::models::ballot::{self, Ballot};
+use ahlt::models::{agenda_point, coa, entity, opinion, relation, tor};
+use common::*;
+use sqlx::PgPool;
+
+fn ballot_of(ballot_type: &str, motion_coa_id: i64, eligible: usize, quorum_pct: i64) -> Ballot {
+    Ballot {
+        id: 1,
+        agenda_point_id: 1,
+        ballot_type: ballot_type.to_string(),
+        status: "open".to_string(),
+        motion_coa_id,
+        quorum_pct,
+        eligible: (1..=eligible as i64).collect(),
+        opened_by_id: 1,
+        opened_at: String::new(),
+        closed_at: String::new(),
+        tally: None,
+    }
+}
+
+fn form(fields: &[(&str, &str)]) -> HashMap<String, String> {
+    fields.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+}
+
+async fn member(pool: &PgPool, tor_id: i64, name: &str) -> i64 {
+    let user = insert_entity(pool, "user", name, name).await;
+    let position = insert_entity(pool, "tor_function", &format!("{name}_seat"), "Member").await;
+    relation::create(pool, "belongs_to_tor", position, tor_id).await.unwrap();
+    tor::assign_to_position(pool, user, position, "mandatory").await.unwrap();
+    user
+}
+
+#[tokio::test]
+async fn test_parse_and_tally() {
+    assert_eq!(ballot::quorum(10, 50), 5);
+    assert_eq!(ballot::quorum(3, 50), 2);
+    assert_eq!(ballot::quorum(0, 50), 0);
+
+    // Parsing form input
+    let coas = [11, 12, 13];
+    assert_eq!(ballot::parse_choice("yes_no", &form(&[("choice", "no")]), &coas).unwrap(), "no");
+    assert!(ballot::parse_choice("yes_no", &form(&[("choice", "maybe")]), &coas).is_err());
+    let ranked = form(&[("rank_11", "2"), ("rank_12", ""), ("rank_13", "1")]);
+    assert_eq!(ballot::parse_choice("ranked", &ranked, &coas).unwrap(), "13,11");
+    let clash = form(&[("rank_11", "1"), ("rank_13", "1")]);
+    assert_eq!(ballot::parse_choice("ranked", &clash, &coas).unwrap_err(), "Give each course of action a different rank");
+    let points = form(&[("points_11", "7"), ("points_12", "3"), ("points_13", "0")]);
+    assert_eq!(ballot::parse_choice("weighted", &points, &coas).unwrap(), "11:7,12:3");
+    let short = form(&[("points_11", "4")]);
+    assert_eq!(ballot::parse_choice("weighted", &short, &coas).unwrap_err(), "Spread exactly 10 points (4 given)");
+    let abstain = form(&[("abstain", "1"), ("points_11", "4")]);
+    assert_eq!(ballot::parse_choice("weighted", &abstain, &coas).unwrap(), "abstain");
+
+    let titles: Vec<(i64, String)> = vec![(11, "A".into()), (12, "B".into()), (13, "C".into())];
+    let votes = |v: &[&str]| v.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+    // Yes/no: abstentions count toward quorum but not the result
+    let t = ballot::tally(&ballot_of("yes_no", 11, 6, 50), &votes(&["yes", "yes", "no", "abstain"]), &titles);
+    assert_eq!((t.cast, t.abstained, t.quorum, t.winner), (4, 1, 3, Some(11)));
+    assert_eq!(t.counts, vec![("Yes".into(), 2), ("No".into(), 1), ("Abstain".into(), 1)]);
+    assert_eq!(t.summary, "Yes / No / Abstain ballot on 'A': 2 yes, 1 no, 1 abstain (4 of 6 eligible voted, quorum 3). Carried: 'A'.");
+    let t = ballot::tally(&ballot_of("yes_no", 11, 6, 50), &votes(&["yes", "no"]), &titles);
+    assert!(!t.quorum_met() && t.winner.is_none());
+    let t = ballot::tally(&ballot_of("yes_no", 11, 2, 50), &votes(&["yes", "no"]), &titles);
+    assert!(t.quorum_met() && t.winner.is_none() && t.summary.ends_with("Not carried; no decision."));
+
+    // Ranked: C is eliminated and its voter's second choice decides
+    let t = ballot::tally(&ballot_of("ranked", 0, 5, 50), &votes(&["11", "11,12", "12", "12,11", "13,11"]), &titles);
+    assert_eq!(t.rounds.len(), 2);
+    assert_eq!(t.rounds[0], vec![("A".into(), 2), ("B".into(), 2), ("C".into(), 1)]);
+    assert_eq!((t.counts.clone(), t.winner), (vec![("A".into(), 3), ("B".into(), 2)], Some(11)));
+    let (winner, _) = ballot::instant_runoff(&[vec![11], vec![12]], &[11, 12]);
+    assert_eq!(winner, None);
+
+    // Weighted: most points wins, a tie carries nothing
+    let t = ballot::tally(&ballot_of("weighted", 0, 3, 50), &votes(&["11:4,12:6", "12:10", "abstain"]), &titles);
+    assert_eq!((t.counts.clone(), t.winner), (vec![("A".into(), 4), ("B".into(), 16), ("C".into(), 0)], Some(12)));
+    let t = ballot::tally(&ballot_of("weighted", 0, 2, 50), &votes(&["11:10", "12:10"]), &titles);
+    assert_eq!(t.winner, None);
+}
+
+#[tokio::test]
+async fn test_ballot_flow_records_decision() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let board = insert_entity(pool, "tor", "board", "Board").await;
+    let chair = member(pool, board, "chair").await;
+    let alice = member(pool, board, "alice").await;
+    let bob = member(pool, board, "bob").await;
+    let outsider = insert_entity(pool, "user", "outsider", "Outsider").await;
+
+    let ap = agenda_point::create(pool, board, "Budget", "", "decision", "2026-10-20", 30, chair, "", "", "").await.unwrap();
+    let alpha = coa::create(pool, "Alpha", "", "simple", chair).await.unwrap();
+    let beta = coa::create(pool, "Beta", "", "simple", chair).await.unwrap();
+    relation::create(pool, "considers_coa", ap, alpha).await.unwrap();
+    relation::create(pool, "considers_coa", ap, beta).await.unwrap();
+    let titles = vec![(alpha, "Alpha".to_string()), (beta, "Beta".to_string())];
+
+    let eligible = ballot::eligible_voters(pool, board).await.unwrap();
+    assert_eq!(eligible.len(), 3);
+    assert!(!eligible.contains(&outsider));
+    assert_eq!(ballot::default_quorum_pct(pool).await, 50);
+    assert_eq!(ballot::validate_open("ranked", 0, 50, &[alpha], 3), vec!["Ranked and weighted ballots need at least two courses of action"]);
+    assert_eq!(ballot::validate_open("yes_no", 0, 0, &[alpha, beta], 3).len(), 2);
+
+    // A ranked ballot that misses quorum closes without a decision
+    let first = ballot::open(pool, ap, "ranked", 0, 100, &eligible, chair).await.unwrap();
+    ballot::cast(pool, first, alice, &format!("{beta},{alpha}")).await.unwrap();
+    ballot::cast(pool, first, outsider, &alpha.to_string()).await.unwrap();
+    let b = ballot::find_by_id(pool, first).await.unwrap().unwrap();
+    assert_eq!(ballot::find_votes(pool, &b).await.unwrap().len(), 1, "only eligible voters count");
+    let (tally, decision) = ballot::close(pool, &b, &titles, chair).await.unwrap();
+    assert_eq!((tally.quorum, decision), (3, None));
+    let b = ballot::find_by_id(pool, first).await.unwrap().unwrap();
+    assert!(!b.is_open() && b.tally == Some(tally));
+    assert!(ballot::close(pool, &b, &titles, chair).await.is_err(), "a closed ballot is not closed again");
+
+    // A yes/no ballot where a vote is changed before closing
+    let second = ballot::open(pool, ap, "yes_no", beta, 50, &eligible, chair).await.unwrap();
+    let (a, b) = tokio::join!(ballot::cast(pool, second, alice, "no"), ballot::cast(pool, second, alice, "no"));
+    a.unwrap();
+    b.unwrap();
+    ballot::cast(pool, second, alice, "yes").await.unwrap();
+    ballot::cast(pool, second, bob, "abstain").await.unwrap();
+    let ballots = ballot::find_for_agenda_point(pool, ap).await.unwrap();
+    assert_eq!(ballots.iter().map(|b| (b.id, b.is_open())).collect::<Vec<_>>(), vec![(second, true), (first, false)]);
+
+    let (tally, decision) = ballot::close(pool, &ballots[0], &titles, chair).await.unwrap();
+    assert_eq!((tally.cast, tally.winner), (2, Some(beta)));
+    let decision = decision.expect("carried ballot records a decision");
+    let props = entity::get_properties(pool, decision).await.unwrap();
+    assert_eq!(props.get("selected_coa_id"), Some(&beta.to_string()));
+    assert_eq!(props.get("decision_rationale"), Some(&tally.summary));
+    assert_eq!(props.get("ballot_id"), Some(&second.to_string()));
+    assert!(props.get("ballot_tally").unwrap().contains("\"winner\":"));
+
+    let point = agenda_point::find_by_id(pool, ap).await.unwrap().unwrap();
+    assert!(opinion::opinions_locked(&point.status));
+    let ap_props = entity::get_properties(pool, ap).await.unwrap();
+    assert_eq!(ap_props.get("selected_coa_id"), Some(&beta.to_string()));
+}
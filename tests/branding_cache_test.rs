@@ -0,0 +1,36 @@
+//! Branding cache tests — kept in their own binary, since the cache is
+//! process-wide and would otherwise be shared with other tests' schemas.
+//!
+//! - Cached values are served until the generation in the database moves
+//! - A write made through another replica is picked up on the next read
+
+mod common;
+
+use ahlt::models::branding::{self, Branding};
+use ahlt::models::cache_generation;
+use common::*;
+
+#[tokio::test]
+async fn test_cache_follows_database_generation() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    branding::enable_cache();
+
+    let live = Branding { footer_text: "Live".to_string(), ..Branding::default() };
+    branding::save(pool, "app", &live).await.unwrap();
+    assert_eq!(branding::load(pool, "app").await.footer_text, "Live");
+
+    // Another replica writes the setting; this one still serves its cache
+    sqlx::query(
+        "UPDATE entity_properties SET value = 'Elsewhere' WHERE key = 'value' \
+         AND entity_id = (SELECT id FROM entities WHERE entity_type = 'setting' AND name = 'branding.app.footer_text')",
+    )
+    .execute(pool)
+    .await
+    .unwrap();
+    assert_eq!(branding::load(pool, "app").await.footer_text, "Live");
+
+    // ...until that replica bumps the generation
+    cache_generation::bump(pool, "branding").await.unwrap();
+    assert_eq!(branding::load(pool, "app").await.footer_text, "Elsewhere");
+}
@@ -0,0 +1,200 @@
+//! Branding tests — covers validation, storage and per-surface fallback.
+//!
+//! - Colours must be hex; logos must be small raster data URIs
+//! - Portal and export branding fall back to the application's field by field
+//! - Branding settings stay off the generic settings page
+//! - Drafts are previewed without affecting the live branding until published
+//! - Dark-mode variants are derived from the brand palette
+//! - Brand colours are checked for text contrast in accessibility debug mode
+//! - Pages link the published logo by a URL that changes with the image
+
+mod common;
+
+use ahlt::models::branding::{self, Branding};
+use ahlt::models::setting;
+use common::*;
+
+fn brand(logo: &str, primary: &str, accent: &str, footer: &str) -> Branding {
+    Branding {
+        logo: logo.to_string(),
+        primary_color: primary.to_string(),
+        accent_color: accent.to_string(),
+        footer_text: footer.to_string(),
+    }
+}
+
+#[test]
+fn test_validate() {
+    assert!(branding::validate("app", &Branding::default()).is_empty());
+    assert!(branding::validate("app", &brand("data:image/png;base64,iVBORw0KGgo=", "#123", "#A1b2C3", "Acme")).is_empty());
+
+    assert_eq!(branding::validate("app", &brand("", "red", "#12", "")).len(), 2);
+    assert!(!branding::validate("app", &brand("data:image/svg+xml;base64,PHN2Zz4=", "", "", "")).is_empty());
+    assert!(!branding::validate("app", &brand("https://example.com/logo.png", "", "", "")).is_empty());
+
+    let huge = format!("data:image/png;base64,{}", "A".repeat(branding::MAX_LOGO_BYTES * 2));
+    assert!(!branding::validate("portal", &brand(&huge, "", "", "")).is_empty());
+    assert!(!branding::validate("export", &brand("", "", "", &"x".repeat(501))).is_empty());
+}
+
+#[test]
+fn test_style_tag() {
+    assert_eq!(Branding::default().style_tag(), "");
+
+    let style = brand("", "#0a3d62", "#f60", "").style_tag();
+    assert!(style.starts_with("<style>:root:not(.dark)"));
+    assert!(style.contains("--primary: #0a3d62;"));
+    assert!(style.contains("--nav-bg: #0a3d62;"));
+    assert!(style.contains("--accent: #f60;"));
+    assert!(style.contains("--accent-subtle: rgba(255, 102, 0, 0.08);"));
+    // Dark mode gets a lightened accent and keeps its own primary
+    let (_, dark) = style.split_once(":root.dark").unwrap();
+    assert!(dark.contains("--accent: #ffab73;"));
+    assert!(!dark.contains("--primary"));
+
+    let primary_only = brand("", "#0a3d62", "", "").style_tag();
+    assert!(!primary_only.contains(":root.dark"));
+}
+
+#[test]
+fn test_document_color() {
+    let b = brand("", "#000000", "", "");
+    assert_eq!(b.document_color(false), "#000000");
+    assert_eq!(b.document_color(true), "#737373");
+    assert_eq!(Branding::default().document_color(false), "#1c1917");
+    assert_eq!(Branding::default().document_color(true), "#e7e5e4");
+}
+
+#[test]
+fn test_contrast_checks() {
+    assert!((branding::contrast_ratio("#fff", "#000000").unwrap() - 21.0).abs() < 0.01);
+    assert!((branding::contrast_ratio("#777777", "#ffffff").unwrap() - 4.48).abs() < 0.01);
+    assert!(branding::contrast_ratio("white", "#000").is_none());
+
+    assert!(Branding::default().contrast_checks().is_empty());
+    let checks = brand("", "#0a3d62", "#f60", "").contrast_checks();
+    assert_eq!(checks.len(), 4);
+    assert!(checks.iter().find(|c| c.label == "Button text on primary").unwrap().passes());
+    let link = checks.iter().find(|c| c.label == "Links on page surface").unwrap();
+    assert_eq!(link.foreground, "#ff6600");
+    assert!(!link.passes());
+    assert_eq!(link.ratio_text(), "2.94:1");
+}
+
+#[test]
+fn test_export_template_uses_palette_and_escapes() {
+    use ahlt::models::minutes::MinutesSection;
+    use ahlt::templates_structs::MinutesExportTemplate;
+    use askama::Template;
+
+    let html = MinutesExportTemplate {
+        branding: brand("", "#0a3d62", "", "Acme <Board>"),
+        theme: "dark".to_string(),
+        title: "Minutes".to_string(),
+        meeting_name: "Board".to_string(),
+        generated_date: "2026-03-01".to_string(),
+        sections: vec![MinutesSection {
+            id: 1,
+            name: "decisions".to_string(),
+            label: "Decisions".to_string(),
+            section_type: "decisions".to_string(),
+            sequence_order: 0,
+            content: "Approved\n<script>x</script>".to_string(),
+            is_auto_generated: true,
+            circulated_content: None,
+        }],
+    }
+    .render()
+    .unwrap();
+
+    assert!(html.contains(r#"data-theme="dark""#));
+    assert!(html.contains("--doc-brand: #0a3d62;"));
+    assert!(html.contains("--doc-brand: #7894a9;"));
+    assert!(html.contains("Acme &#60;Board&#62;") || html.contains("Acme &lt;Board&gt;"));
+    assert!(!html.contains("<script>x"));
+    assert!(html.contains("Approved<br"));
+}
+
+#[tokio::test]
+async fn test_save_and_fallback() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+
+    assert_eq!(branding::load(pool, "portal").await, Branding::default());
+
+    branding::save(pool, "app", &brand("data:image/png;base64,AAAA", "#0a3d62", "", "Acme Corp")).await.unwrap();
+    branding::save(pool, "portal", &brand("", "", "#f60", "Public site")).await.unwrap();
+
+    assert_eq!(
+        branding::load(pool, "portal").await,
+        brand("data:image/png;base64,AAAA", "#0a3d62", "#f60", "Public site")
+    );
+    assert_eq!(branding::load(pool, "export").await, branding::load(pool, "app").await);
+
+    let all = branding::load_all(pool).await;
+    assert_eq!(all.iter().map(|(key, _, _)| *key).collect::<Vec<_>>(), vec!["app", "portal", "export"]);
+    assert_eq!(all[1].2.logo, "");
+
+    // Saving again updates in place
+    branding::save(pool, "app", &brand("", "#0a3d62", "", "Acme Corp")).await.unwrap();
+    assert_eq!(branding::load(pool, "app").await.logo, "");
+
+    let settings = setting::find_all(pool).await.unwrap();
+    assert!(settings.iter().all(|s| !s.name.starts_with("branding.")));
+}
+
+#[tokio::test]
+async fn test_draft_publish_and_discard() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+
+    branding::save(pool, "app", &brand("", "#0a3d62", "", "Live")).await.unwrap();
+    assert!(!branding::has_draft(pool).await);
+    assert_eq!(branding::load_draft(pool, "app").await.footer_text, "Live");
+
+    for (key, _) in branding::SURFACES {
+        let footer = if *key == "app" { "Draft" } else { "" };
+        branding::save_draft(pool, key, &brand("", "#f60", "", footer)).await.unwrap();
+    }
+    assert!(branding::has_draft(pool).await);
+    assert_eq!(branding::load(pool, "app").await.footer_text, "Live");
+    assert_eq!(branding::load_draft(pool, "export").await, brand("", "#f60", "", "Draft"));
+    assert_eq!(branding::load_all(pool).await[0].2.footer_text, "Draft");
+
+    branding::discard_draft(pool).await.unwrap();
+    assert!(!branding::has_draft(pool).await);
+    assert_eq!(branding::load_draft(pool, "app").await.primary_color, "#0a3d62");
+    assert!(!branding::publish(pool).await.unwrap());
+
+    branding::save_draft(pool, "app", &brand("", "#f60", "", "Draft")).await.unwrap();
+    assert!(branding::publish(pool).await.unwrap());
+    assert!(!branding::has_draft(pool).await);
+    assert_eq!(branding::load(pool, "app").await, brand("", "#f60", "", "Draft"));
+}
+
+#[tokio::test]
+async fn test_pages_link_the_published_logo() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+
+    assert_eq!(branding::load_for_page(pool, "app").await.logo, "");
+    assert!(branding::load_logo(pool, "app").await.is_none());
+
+    branding::save(pool, "app", &brand("data:image/png;base64,iVBORw0KGgo=", "#0a3d62", "", "")).await.unwrap();
+    let logo = branding::load_logo(pool, "portal").await.unwrap();
+    assert_eq!(logo.media_type, "image/png");
+    assert_eq!(logo.bytes, b"\x89PNG\r\n\x1a\n");
+    let page = branding::load_for_page(pool, "portal").await;
+    assert_eq!(page.logo, format!("/branding/logo/portal?v={}", logo.tag));
+    assert_eq!(page.primary_color, "#0a3d62");
+    assert!(branding::load(pool, "app").await.logo.starts_with("data:"));
+    assert!(branding::load_logo(pool, "elsewhere").await.is_none());
+
+    // A new logo gets a new URL once published, not while a draft
+    branding::save_draft(pool, "app", &brand("data:image/gif;base64,R0lGODlh", "", "", "")).await.unwrap();
+    assert_eq!(branding::load_logo(pool, "app").await.unwrap().tag, logo.tag);
+    branding::publish(pool).await.unwrap();
+    let replaced = branding::load_logo(pool, "app").await.unwrap();
+    assert_eq!(replaced.media_type, "image/gif");
+    assert_ne!(replaced.tag, logo.tag);
+}
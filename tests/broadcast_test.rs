@@ -0,0 +1,111 @@
+//! Broadcast tests — covers admin notices to all users, a role or a ToR.
+//!
+//! - Input is checked: audience target, message length, expiry after send
+//! - Broadcasts go out at their send time to the audience of that moment,
+//!   are resolved at expiry, and can be cancelled or withdrawn
+
+mod common;
+
+use ahlt::models::relation;
+use ahlt::warnings::broadcast::{self, BroadcastInput};
+use ahlt::warnings::clock::with_fixed_now;
+use ahlt::warnings::queries;
+use chrono::{Duration, TimeZone, Utc};
+use common::*;
+
+fn input<'a>(audience: &'a str, audience_id: Option<i64>) -> BroadcastInput<'a> {
+    BroadcastInput {
+        severity: "info",
+        message: "Planned maintenance Saturday 22:00",
+        details: "",
+        audience,
+        audience_id,
+        send_at: None,
+        expires_at: None,
+    }
+}
+
+#[test]
+fn test_validate_broadcast() {
+    let now = Utc.with_ymd_and_hms(2026, 10, 16, 12, 0, 0).unwrap();
+    assert!(broadcast::validate(&input("all", None), now).is_empty());
+    assert_eq!(broadcast::validate(&input("role", None), now), vec!["Choose a role"]);
+    assert_eq!(broadcast::validate(&input("everyone", None), now), vec!["Unknown audience"]);
+
+    let long = "x".repeat(broadcast::MAX_MESSAGE_LEN + 1);
+    assert!(!broadcast::validate(&BroadcastInput { message: &long, ..input("all", None) }, now).is_empty());
+    assert!(!broadcast::validate(&BroadcastInput { severity: "urgent", ..input("all", None) }, now).is_empty());
+
+    let later = BroadcastInput {
+        send_at: Some(now + Duration::hours(2)),
+        expires_at: Some(now + Duration::hours(1)),
+        ..input("all", None)
+    };
+    assert_eq!(broadcast::validate(&later, now), vec!["Expiry must be after the send time"]);
+
+    assert_eq!(broadcast::parse_local("").unwrap(), None);
+    assert!(broadcast::parse_local("2026-10-17T22:00").unwrap().is_some());
+    assert!(broadcast::parse_local("tomorrow").is_err());
+}
+
+#[tokio::test]
+async fn test_send_expire_and_cancel() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let admin = insert_entity(pool, "user", "admin", "Admin").await;
+    let alice = insert_entity(pool, "user", "alice", "Alice").await;
+    let bob = insert_entity(pool, "user", "bob", "Bob").await;
+    let gone = insert_entity(pool, "user", "gone", "Gone").await;
+    sqlx::query("UPDATE entities SET is_active = false WHERE id = $1").bind(gone).execute(pool).await.unwrap();
+    let ops = insert_entity(pool, "role", "ops", "Operations").await;
+    relation::create(pool, "has_role", alice, ops).await.unwrap();
+    let board = insert_entity(pool, "tor", "board", "Board").await;
+    let chair = insert_entity(pool, "tor_function", "chair", "Chair").await;
+    relation::create(pool, "belongs_to_tor", chair, board).await.unwrap();
+    relation::create(pool, "fills_position", bob, chair).await.unwrap();
+
+    assert_eq!(broadcast::audience_users(pool, "all", None).await.unwrap(), vec![admin, alice, bob]);
+    assert_eq!(broadcast::audience_users(pool, "role", Some(ops)).await.unwrap(), vec![alice]);
+    assert_eq!(broadcast::audience_users(pool, "tor", Some(board)).await.unwrap(), vec![bob]);
+
+    // Scheduled for later: nothing goes out until the send time
+    let t0 = Utc.with_ymd_and_hms(2026, 10, 16, 12, 0, 0).unwrap();
+    let scheduled = BroadcastInput {
+        send_at: Some(t0 + Duration::hours(1)),
+        expires_at: Some(t0 + Duration::hours(3)),
+        ..input("role", Some(ops))
+    };
+    let id = with_fixed_now(t0, broadcast::create(pool, &scheduled, admin)).await.unwrap();
+    assert_eq!(with_fixed_now(t0, broadcast::run_due(pool)).await.unwrap(), (0, 0));
+    assert_eq!(queries::count_unread(pool, alice).await, 0);
+
+    // The audience is taken at send time
+    relation::create(pool, "has_role", bob, ops).await.unwrap();
+    assert_eq!(with_fixed_now(t0 + Duration::hours(1), broadcast::run_due(pool)).await.unwrap(), (1, 0));
+    let b = broadcast::find_by_id(pool, id).await.unwrap().unwrap();
+    assert_eq!((b.status.as_str(), b.recipient_count, b.audience_label.as_str()), ("sent", 2, "Operations"));
+    assert_eq!(queries::count_unread(pool, alice).await, 1);
+    assert_eq!(queries::count_unread(pool, admin).await, 0);
+    let warning = queries::get_warning_detail(pool, b.warning_id.unwrap()).await.unwrap().unwrap();
+    assert_eq!(warning.source_action, broadcast::SOURCE_ACTION);
+
+    // At expiry the notice is resolved
+    assert_eq!(with_fixed_now(t0 + Duration::hours(3), broadcast::run_due(pool)).await.unwrap(), (0, 1));
+    let warning = queries::get_warning_detail(pool, b.warning_id.unwrap()).await.unwrap().unwrap();
+    assert_eq!(warning.status, "resolved");
+    assert!(!broadcast::cancel(pool, id, admin).await.unwrap());
+
+    // Cancelled before sending: never sent; withdrawn after: resolved
+    let later = BroadcastInput { send_at: Some(Utc::now() + Duration::days(1)), ..input("all", None) };
+    let cancelled = broadcast::create(pool, &later, admin).await.unwrap();
+    assert!(broadcast::cancel(pool, cancelled, admin).await.unwrap());
+    let now_id = broadcast::create(pool, &input("all", None), admin).await.unwrap();
+    assert_eq!(broadcast::run_due(pool).await.unwrap(), (1, 0));
+    assert_eq!(queries::count_unread(pool, admin).await, 1);
+    assert!(broadcast::cancel(pool, now_id, admin).await.unwrap());
+    let withdrawn = broadcast::find_by_id(pool, now_id).await.unwrap().unwrap();
+    assert_eq!(withdrawn.status, "cancelled");
+    let warning = queries::get_warning_detail(pool, withdrawn.warning_id.unwrap()).await.unwrap().unwrap();
+    assert_eq!(warning.status, "resolved");
+    assert_eq!(broadcast::find_all(pool).await.unwrap().len(), 3);
+}
@@ -0,0 +1,101 @@
+//! Position capability tests — covers defaults, overrides and materialization.
+//!
+//! - Positions inherit the ToR defaults for their membership type
+//! - Explicit grants and denies win over the defaults
+//! - Raw can_* properties set before the editor are kept as overrides
+//! - Effective capabilities are what ABAC checks
+
+mod common;
+
+use std::collections::{BTreeMap, HashMap};
+
+use ahlt::auth::abac;
+use ahlt::models::{relation, tor};
+use common::*;
+use sqlx::PgPool;
+
+async fn position(pool: &PgPool, tor_id: i64, name: &str, membership_type: &str) -> i64 {
+    let id = insert_entity(pool, "tor_function", name, name).await;
+    insert_prop(pool, id, "membership_type", membership_type).await;
+    relation::create(pool, "belongs_to_tor", id, tor_id).await.unwrap();
+    id
+}
+
+async fn can(pool: &PgPool, user_id: i64, tor_id: i64, capability: &str) -> bool {
+    abac::has_resource_capability(pool, user_id, tor_id, "belongs_to_tor", capability).await.unwrap()
+}
+
+#[tokio::test]
+async fn test_defaults_overrides_and_abac() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let tor_id = insert_entity(pool, "tor", "board", "Board").await;
+    let chair = position(pool, tor_id, "chair", "mandatory").await;
+    let observer = position(pool, tor_id, "observer", "optional").await;
+    let secretary = position(pool, tor_id, "secretary", "mandatory").await;
+    // Configured by hand before the editor existed
+    insert_prop(pool, secretary, "can_record_decisions", "true").await;
+
+    let alice = insert_entity(pool, "user", "alice", "Alice").await;
+    let bob = insert_entity(pool, "user", "bob", "Bob").await;
+    relation::create(pool, "fills_position", alice, chair).await.unwrap();
+    relation::create(pool, "fills_position", bob, observer).await.unwrap();
+
+    let matrix = tor::find_capability_matrix(pool, tor_id).await.unwrap();
+    let sec = matrix.positions.iter().find(|p| p.position_id == secretary).unwrap();
+    assert_eq!(sec.setting("can_record_decisions"), "grant");
+    assert_eq!(sec.setting("can_call_meetings"), "inherit");
+
+    let defaults = tor::CapabilityDefaults {
+        mandatory: vec!["can_call_meetings".to_string(), "can_manage_agenda".to_string()],
+        optional: vec!["can_create_proposals".to_string()],
+    };
+    // The chair may not manage the agenda despite the mandatory default
+    let overrides = HashMap::from([
+        (chair, BTreeMap::from([("can_manage_agenda".to_string(), false)])),
+        (observer, BTreeMap::new()),
+    ]);
+    tor::save_capabilities(pool, tor_id, &defaults, &overrides).await.unwrap();
+
+    let matrix = tor::find_capability_matrix(pool, tor_id).await.unwrap();
+    assert_eq!(matrix.defaults, defaults);
+    let get = |id: i64| matrix.positions.iter().find(|p| p.position_id == id).unwrap();
+    assert!(get(chair).has("can_call_meetings"));
+    assert!(!get(chair).has("can_manage_agenda"));
+    assert!(get(chair).inherits("can_manage_agenda"));
+    assert!(get(observer).has("can_create_proposals"));
+    assert!(!get(observer).has("can_call_meetings"));
+    // Not part of the submitted overrides: the legacy grant is kept
+    assert!(get(secretary).has("can_record_decisions"));
+    assert_eq!(get(secretary).setting("can_call_meetings"), "inherit");
+    assert!(get(secretary).has("can_call_meetings"));
+
+    assert!(can(pool, alice, tor_id, "can_call_meetings").await);
+    assert!(!can(pool, alice, tor_id, "can_manage_agenda").await);
+    assert!(can(pool, bob, tor_id, "can_create_proposals").await);
+    assert!(!can(pool, bob, tor_id, "can_call_meetings").await);
+}
+
+#[tokio::test]
+async fn test_membership_change_reapplies_defaults() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let tor_id = insert_entity(pool, "tor", "board", "Board").await;
+    let member = position(pool, tor_id, "member", "optional").await;
+    let carol = insert_entity(pool, "user", "carol", "Carol").await;
+
+    let defaults = tor::CapabilityDefaults {
+        mandatory: vec!["can_call_meetings".to_string()],
+        optional: Vec::new(),
+    };
+    tor::save_capabilities(pool, tor_id, &defaults, &HashMap::new()).await.unwrap();
+
+    tor::assign_to_position(pool, carol, member, "mandatory").await.unwrap();
+    assert!(!can(pool, carol, tor_id, "can_call_meetings").await);
+    tor::apply_capabilities(pool, tor_id).await.unwrap();
+    assert!(can(pool, carol, tor_id, "can_call_meetings").await);
+
+    // Written can_* values are not read back as explicit overrides
+    let matrix = tor::find_capability_matrix(pool, tor_id).await.unwrap();
+    assert_eq!(matrix.positions[0].setting("can_call_meetings"), "inherit");
+}
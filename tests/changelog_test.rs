@@ -0,0 +1,101 @@
+//! Changelog tests — covers release notes for migrations and feature settings.
+//!
+//! - Migrations present when the changelog starts are recorded as baseline
+//! - Later migrations are recorded once and shown until dismissed
+//! - Governance-relevant migrations warn admins
+//! - Only boolean settings switched on are recorded as features
+
+mod common;
+
+use ahlt::models::{changelog::{self, ReleaseNote}, relation, setting};
+use common::*;
+use sqlx::PgPool;
+
+async fn admin(pool: &PgPool) -> i64 {
+    let user = insert_entity(pool, "user", "admin", "Admin").await;
+    let role = insert_entity(pool, "role", "administrator", "Administrator").await;
+    let permission = insert_entity(pool, "permission", "settings.manage", "Manage settings").await;
+    relation::create(pool, "has_role", user, role).await.unwrap();
+    relation::create(pool, "has_permission", role, permission).await.unwrap();
+    user
+}
+
+async fn apply_migration(pool: &PgPool, version: i64, description: &str) {
+    sqlx::query(
+        "INSERT INTO _sqlx_migrations (version, description, success, checksum, execution_time) \
+         VALUES ($1, $2, true, '\\x00', 0)",
+    )
+    .bind(version)
+    .bind(description)
+    .execute(pool)
+    .await
+    .unwrap();
+}
+
+async fn migration_warnings(pool: &PgPool) -> i64 {
+    sqlx::query_scalar(
+        "SELECT COUNT(*) FROM entity_properties WHERE key = 'source_action' AND value = 'event.system.migration'",
+    )
+    .fetch_one(pool)
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_migrations_recorded_once() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let admin = admin(pool).await;
+
+    let baseline = changelog::record_migrations(pool, changelog::MIGRATION_NOTES).await.unwrap();
+    assert!(!baseline.is_empty());
+    assert!(baseline.iter().all(|e| e.baseline));
+    assert!(changelog::find_unseen(pool, admin).await.unwrap().is_empty());
+    assert!(changelog::record_migrations(pool, changelog::MIGRATION_NOTES).await.unwrap().is_empty());
+
+    let notes = [ReleaseNote {
+        version: 29990101,
+        title: "Quorum counts delegates",
+        description: "Delegated attendance now counts towards quorum.",
+        governance: true,
+    }];
+    apply_migration(pool, 29990101, "quorum delegates").await;
+    apply_migration(pool, 29990102, "index_agenda_points").await;
+    let upgrade = changelog::record_migrations(pool, &notes).await.unwrap();
+    assert_eq!(upgrade.len(), 2);
+    assert_eq!((upgrade[0].title.as_str(), upgrade[0].governance), ("Quorum counts delegates", true));
+    assert_eq!((upgrade[1].title.as_str(), upgrade[1].governance), ("index agenda points", false));
+    assert_eq!(migration_warnings(pool).await, 1);
+
+    let unseen = changelog::find_unseen(pool, admin).await.unwrap();
+    assert_eq!(unseen.iter().map(|e| e.reference.as_str()).collect::<Vec<_>>(), vec!["29990102", "29990101"]);
+    changelog::mark_seen(pool, admin).await.unwrap();
+    assert!(changelog::find_unseen(pool, admin).await.unwrap().is_empty());
+    assert_eq!(changelog::find_all(pool).await.unwrap().len(), baseline.len() + 2);
+}
+
+#[tokio::test]
+async fn test_feature_enables_recorded() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let flag = insert_entity(pool, "setting", "ticketing.enabled", "Ticketing sync").await;
+    insert_prop(pool, flag, "value", "false").await;
+    let text = insert_entity(pool, "setting", "app.name", "Application name").await;
+    insert_prop(pool, text, "value", "Ahlt").await;
+
+    let renamed = setting::change_value(pool, text, "Board Portal", 0, "update").await.unwrap().unwrap();
+    assert!(changelog::record_setting_change(pool, &renamed).await.unwrap().is_none());
+
+    let enabled = setting::change_value(pool, flag, "true", 0, "update").await.unwrap().unwrap();
+    let entry = changelog::record_setting_change(pool, &enabled).await.unwrap().unwrap();
+    assert_eq!((entry.kind.as_str(), entry.title.as_str()), ("feature", "Ticketing sync"));
+    assert!(entry.description.contains("ticketing"));
+
+    let disabled = setting::change_value(pool, flag, "false", 0, "update").await.unwrap().unwrap();
+    assert!(changelog::record_setting_change(pool, &disabled).await.unwrap().is_none());
+
+    // Enabling again is a new entry
+    let enabled = setting::change_value(pool, flag, "true", 0, "update").await.unwrap().unwrap();
+    assert!(changelog::record_setting_change(pool, &enabled).await.unwrap().is_some());
+    assert_eq!(changelog::find_all(pool).await.unwrap().len(), 2);
+}
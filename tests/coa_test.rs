@@ -51,7 +51,7 @@ async fn test_update_coa() {
     let coa_id = coa::create(pool, "Original Title", "Original description", "simple", user_id).await.unwrap();
 
     // Update title and description
-    coa::update(pool, coa_id, "Revised Title", "Revised description").await.unwrap();
+    coa::update(pool, coa_id, "Revised Title", "Revised description", user_id).await.unwrap();
 
     // Verify changes
     let detail = coa::find_by_id(pool, coa_id).await.unwrap();
@@ -137,6 +137,7 @@ async fn seed_base_entities(pool: &PgPool) -> Result<(), sqlx::Error> {
         "requires_permission",
         "belongs_to_tor",
         "fills_position",
+        "delegates_to",
         "participates_in",
         "is_blocking",
         // Workflow
@@ -145,6 +146,7 @@ async fn seed_base_entities(pool: &PgPool) -> Result<(), sqlx::Error> {
         // Minutes & documents
         "minutes_of",
         "section_of",
+        "revision_of",
         "has_section",
         "has_subsection",
         "protocol_of",
@@ -153,11 +155,20 @@ async fn seed_base_entities(pool: &PgPool) -> Result<(), sqlx::Error> {
         // Governance pipeline
         "submitted_to",
         "spawns_proposal",
+        "consolidated_into",
+        "clusters_suggestion",
+        "related_to_proposal",
+        "attached_to_proposal",
         "suggested_to",
         "scoped_to_tor",
+        "change_of",
         "spawns_agenda_point",
         "considers_coa",
         "scheduled_for_meeting",
+        // ToR dependencies
+        "feeds_into",
+        "escalates_to",
+        "uses_sla_pack",
         // Opinions
         "opinion_by",
         "opinion_on",
@@ -168,6 +179,7 @@ async fn seed_base_entities(pool: &PgPool) -> Result<(), sqlx::Error> {
         "targets_user",
         "on_receipt",
         "forwarded_to_user",
+        "watches",
     ];
 
     for rt in relation_types {
@@ -238,3 +250,20 @@ pub async fn insert_relation(pool: &PgPool, relation_type_id: i64, source_id: i6
     .expect("Failed to insert relation");
     row.0
 }
+
+// ============================================================================
+// FIXTURE SEEDING
+// ============================================================================
+
+/// Seed the database from a JSON fixture in the data manager import format
+/// (`tests/fixtures/**/*.json`). Relations may reference base entities created
+/// by `setup_test_db()`, such as `role:default`. Panics on any import error.
+#[allow(dead_code)]
+pub async fn seed_fixture(pool: &PgPool, json: &str) {
+    let payload: ahlt::models::data_manager::types::ImportPayload =
+        serde_json::from_str(json).expect("Failed to parse fixture");
+    let result = ahlt::models::data_manager::import::import_data(pool, &payload)
+        .await
+        .expect("Failed to import fixture");
+    assert!(result.errors.is_empty(), "Fixture import errors: {:?}", result.errors);
+}
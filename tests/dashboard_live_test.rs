@@ -0,0 +1,82 @@
+//! Live dashboard tests — covers the counters pushed over the WebSocket.
+//!
+//! - Counters count every pending review and unread warning, not just the
+//!   five listed under "Needs attention"
+//! - Domain events queue a dashboard refresh that reaches connected users
+
+mod common;
+
+use ahlt::handlers::warning_handlers::ws::new_connection_map;
+use ahlt::models::{dashboard, proposal, relation};
+use ahlt::warnings::{self, outbox};
+use common::*;
+use tokio::sync::mpsc;
+
+/// A user filling a position on a ToR, with `proposals` submitted to it.
+async fn member_with_proposals(pool: &sqlx::PgPool, proposals: usize) -> (i64, Vec<i64>) {
+    let alice = insert_entity(pool, "user", "alice", "Alice").await;
+    let tor = insert_entity(pool, "tor", "board", "Board").await;
+    let chair = insert_entity(pool, "tor_function", "chair", "Chair").await;
+    relation::create(pool, "belongs_to_tor", chair, tor).await.unwrap();
+    relation::create(pool, "fills_position", alice, chair).await.unwrap();
+
+    let mut ids = Vec::new();
+    for i in 0..proposals {
+        let id = insert_entity(pool, "proposal", &format!("p{i}"), &format!("Proposal {i}")).await;
+        insert_prop(pool, id, "status", "submitted").await;
+        relation::create(pool, "submitted_to", id, tor).await.unwrap();
+        ids.push(id);
+    }
+    (alice, ids)
+}
+
+#[tokio::test]
+async fn test_counts_are_not_capped() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let (alice, proposals) = member_with_proposals(pool, 7).await;
+    proposal::update_status(pool, proposals[0], "draft", None).await.unwrap();
+
+    for i in 0..6 {
+        let wid = warnings::create_warning(pool, "info", "system", "test", &format!("Notice {i}"), "", "system").await.unwrap();
+        warnings::create_receipts(pool, wid, &[alice]).await.unwrap();
+    }
+
+    let counts = dashboard::find_counts(pool, alice).await;
+    assert_eq!(counts.pending_reviews, 6);
+    assert_eq!(counts.unread_warnings, 6);
+    assert_eq!(counts.upcoming_meetings, 0);
+    let listed = dashboard::find_pending_items(pool, alice).await;
+    assert_eq!((listed.pending_proposals.len(), listed.unread_warnings.len()), (5, 5));
+
+    // Someone outside the ToR has nothing to review
+    let bob = insert_entity(pool, "user", "bob", "Bob").await;
+    assert_eq!(dashboard::find_counts(pool, bob).await, dashboard::DashboardCounts::default());
+}
+
+#[tokio::test]
+async fn test_domain_events_push_counts() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let (alice, proposals) = member_with_proposals(pool, 2).await;
+
+    let conn_map = new_connection_map();
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    conn_map.write().unwrap().insert(alice, vec![tx]);
+
+    proposal::transition(pool, proposals[0], "approved", None, alice).await.unwrap();
+    let queued = outbox::find_recent(pool, Some("pending"), 10).await.unwrap();
+    assert_eq!(queued.len(), 1);
+    assert_eq!(queued[0].channel, outbox::CHANNEL_DASHBOARD);
+
+    assert_eq!(outbox::drain(pool, &conn_map).await, 1);
+    let msg: serde_json::Value = serde_json::from_str(&rx.try_recv().unwrap()).unwrap();
+    assert_eq!(msg["type"], "dashboard_counts");
+    assert_eq!(msg["counts"]["pending_reviews"].as_i64(), Some(1));
+    assert_eq!(msg["counts"]["unread_warnings"].as_i64(), Some(0));
+    assert!(rx.try_recv().is_err());
+
+    // Plain status updates are not domain events and push nothing
+    proposal::update_status(pool, proposals[1], "draft", None).await.unwrap();
+    assert_eq!(outbox::drain(pool, &conn_map).await, 0);
+}
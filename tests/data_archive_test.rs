@@ -0,0 +1,133 @@
+//! Archive export/import tests — covers moving a whole environment.
+//!
+//! - An archive imported elsewhere gets new IDs, and relations, ID-valued
+//!   properties and ToR-scoped setting names follow them
+//! - Uploaded files travel with the archive
+//! - A dry run commits nothing; archives from newer versions are refused
+
+mod common;
+
+use std::io::Write;
+
+use ahlt::models::data_manager::archive;
+use ahlt::models::data_manager::types::ConflictMode;
+use ahlt::models::{entity, relation, setting};
+use common::*;
+
+fn uploads(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("ahlt-archive-{}-{name}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+async fn count(pool: &sqlx::PgPool, entity_type: &str) -> i64 {
+    sqlx::query_scalar("SELECT COUNT(*) FROM entities WHERE entity_type = $1")
+        .bind(entity_type)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_archive_round_trip_remaps_ids() {
+    let source = setup_test_db().await;
+    let src = source.pool();
+    let src_uploads = uploads("src");
+    std::fs::create_dir_all(&src_uploads).unwrap();
+
+    let alice = insert_entity(src, "user", "alice", "Alice").await;
+    let board = insert_entity(src, "tor", "board", "Board").await;
+    let idea = insert_entity(src, "suggestion", "idea", "Idea").await;
+    insert_prop(src, idea, "submitted_by_id", &alice.to_string()).await;
+    insert_prop(src, idea, "description", "Meet monthly").await;
+    relation::create(src, "suggested_to", idea, board).await.unwrap();
+    setting::set_tor_override(src, board, "suggestions.stale_after_days", "10").await.unwrap();
+    let file = insert_entity(src, "document_attachment", "0a1b2c", "minutes.txt").await;
+    insert_prop(src, file, "uploaded_by", &alice.to_string()).await;
+    std::fs::write(src_uploads.join("0a1b2c"), b"Minutes").unwrap();
+
+    let bytes = archive::export_archive(src, &src_uploads).await.unwrap();
+
+    // The target already has rows, so every ID shifts
+    let target = setup_test_db().await;
+    let dst = target.pool();
+    let dst_uploads = uploads("dst");
+    for i in 0..5 {
+        insert_entity(dst, "user", &format!("local{i}"), "Local").await;
+    }
+
+    let contents = archive::read_archive(dst, &bytes).await.unwrap();
+    assert_eq!(contents.manifest.archive_version, archive::ARCHIVE_VERSION);
+    assert_eq!(contents.manifest.files, 1);
+
+    // A dry run reports but writes nothing
+    let preview = archive::import_archive(dst, &contents, &ConflictMode::Skip, &dst_uploads, true).await.unwrap();
+    assert!(preview.result.dry_run);
+    assert!(preview.result.created >= 4);
+    assert_eq!(count(dst, "suggestion").await, 0);
+    assert!(!dst_uploads.exists());
+
+    let result = archive::import_archive(dst, &contents, &ConflictMode::Skip, &dst_uploads, false).await.unwrap();
+    assert!(result.result.errors.is_empty(), "{:?}", result.result.errors);
+    assert_eq!(result.files, 1);
+    assert!(result.remapped_ids > 0);
+
+    let new_alice = entity::find_by_type_and_name(dst, "user", "alice").await.unwrap().unwrap().id;
+    let new_board = entity::find_by_type_and_name(dst, "tor", "board").await.unwrap().unwrap().id;
+    let new_idea = entity::find_by_type_and_name(dst, "suggestion", "idea").await.unwrap().unwrap().id;
+    assert_ne!(new_alice, alice);
+    assert_eq!(entity::get_property(dst, new_idea, "submitted_by_id").await.unwrap(), Some(new_alice.to_string()));
+    assert_eq!(entity::get_property(dst, new_idea, "description").await.unwrap().as_deref(), Some("Meet monthly"));
+
+    let linked: bool = sqlx::query_scalar(
+        "SELECT EXISTS (SELECT 1 FROM relations r JOIN entities t ON t.id = r.relation_type_id \
+         WHERE t.name = 'suggested_to' AND r.source_id = $1 AND r.target_id = $2)",
+    )
+    .bind(new_idea)
+    .bind(new_board)
+    .fetch_one(dst)
+    .await
+    .unwrap();
+    assert!(linked);
+
+    let scoped = format!("suggestions.stale_after_days@tor:{new_board}");
+    assert!(entity::find_by_type_and_name(dst, "setting", &scoped).await.unwrap().is_some());
+    assert_eq!(std::fs::read(dst_uploads.join("0a1b2c")).unwrap(), b"Minutes");
+
+    // Importing again skips everything already there
+    let again = archive::import_archive(dst, &contents, &ConflictMode::Skip, &dst_uploads, false).await.unwrap();
+    assert_eq!(again.result.created, 0);
+    assert_eq!(again.files, 0);
+    assert_eq!(count(dst, "suggestion").await, 1);
+
+    // In fail mode a conflict imports nothing
+    let failed = archive::import_archive(dst, &contents, &ConflictMode::Fail, &dst_uploads, false).await.unwrap();
+    assert_eq!(failed.result.errors.len(), 1);
+
+    let _ = std::fs::remove_dir_all(&src_uploads);
+    let _ = std::fs::remove_dir_all(&dst_uploads);
+}
+
+#[tokio::test]
+async fn test_archive_from_newer_version_is_refused() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+
+    let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    zip.start_file("manifest.json", zip::write::SimpleFileOptions::default()).unwrap();
+    let manifest = serde_json::json!({
+        "archive_version": archive::ARCHIVE_VERSION + 1,
+        "schema_version": 1,
+        "migration": 0,
+        "created_at": "2026-01-01T00:00:00Z",
+        "entities": 0,
+        "relations": 0,
+        "files": 0,
+    });
+    zip.write_all(manifest.to_string().as_bytes()).unwrap();
+    let bytes = zip.finish().unwrap().into_inner();
+
+    let err = archive::read_archive(pool, &bytes).await.err().unwrap();
+    assert!(err.contains("unsupported archive_version"), "{err}");
+    assert!(archive::read_archive(pool, b"not a zip").await.is_err());
+}
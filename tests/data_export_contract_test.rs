@@ -0,0 +1,181 @@
+//! Contract tests for the data manager import/export format.
+//!
+//! External integrations consume the export payload directly, so its shape is
+//! pinned by golden fixtures in `tests/fixtures/data_manager/`:
+//! - Golden fixture equals a fresh export of the same data
+//! - Export → import → export round-trip is lossless (native and JSON-LD)
+//! - `schema_version` is embedded and newer versions are rejected on import
+//! - Legacy payloads without `schema_version` still import
+
+mod common;
+
+use ahlt::models::data_manager::{
+    export, import, jsonld,
+    types::{ExportPayload, ImportPayload, SCHEMA_VERSION},
+};
+use common::setup_test_db;
+use serde_json::Value;
+use sqlx::PgPool;
+
+const GOLDEN_V1: &str = include_str!("fixtures/data_manager/export_v1.json");
+const LEGACY_UNVERSIONED: &str = include_str!("fixtures/data_manager/legacy_unversioned.json");
+
+/// Entity types present in the golden fixture — used as the export filter so
+/// seeded base entities don't leak into the comparison.
+fn fixture_types() -> Vec<String> {
+    vec!["contract_tor".to_string(), "contract_position".to_string()]
+}
+
+/// Drop database-assigned IDs, which legitimately differ between databases.
+fn normalize(mut value: Value) -> Value {
+    for key in ["entities", "relations"] {
+        if let Some(items) = value.get_mut(key).and_then(|v| v.as_array_mut()) {
+            for item in items {
+                if let Some(obj) = item.as_object_mut() {
+                    obj.remove("id");
+                }
+            }
+        }
+    }
+    value
+}
+
+fn normalized_export(payload: &ExportPayload) -> Value {
+    normalize(serde_json::to_value(payload).expect("serialize export"))
+}
+
+/// Import a JSON document into the pool, asserting it applied cleanly.
+async fn import_json(pool: &PgPool, json: &str) {
+    let payload: ImportPayload = serde_json::from_str(json).expect("parse import payload");
+    let result = import::import_data(pool, &payload).await.expect("import failed");
+    assert!(result.errors.is_empty(), "import errors: {:?}", result.errors);
+}
+
+async fn export_fixture_types(pool: &PgPool) -> ExportPayload {
+    let types = fixture_types();
+    export::export_entities(pool, Some(&types))
+        .await
+        .expect("export failed")
+}
+
+// ────────────────────────────────────────────────────────────────────
+// 1. Golden fixture matches a fresh export
+// ────────────────────────────────────────────────────────────────────
+
+#[tokio::test]
+async fn test_export_matches_golden_fixture() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+
+    import_json(pool, GOLDEN_V1).await;
+    let exported = export_fixture_types(pool).await;
+
+    let golden: Value = serde_json::from_str(GOLDEN_V1).expect("parse golden fixture");
+    assert_eq!(
+        normalized_export(&exported),
+        normalize(golden),
+        "export format drifted from tests/fixtures/data_manager/export_v1.json — \
+         bump SCHEMA_VERSION and add a new fixture if this change is intentional"
+    );
+}
+
+// ────────────────────────────────────────────────────────────────────
+// 2. Round-trip: export → import → export
+// ────────────────────────────────────────────────────────────────────
+
+#[tokio::test]
+async fn test_native_round_trip_is_lossless() {
+    let source_db = setup_test_db().await;
+    import_json(source_db.pool(), GOLDEN_V1).await;
+    let first = export_fixture_types(source_db.pool()).await;
+
+    let target_db = setup_test_db().await;
+    let serialized = serde_json::to_string(&first).expect("serialize export");
+    import_json(target_db.pool(), &serialized).await;
+    let second = export_fixture_types(target_db.pool()).await;
+
+    assert_eq!(normalized_export(&first), normalized_export(&second));
+}
+
+#[tokio::test]
+async fn test_jsonld_round_trip_is_lossless() {
+    let source_db = setup_test_db().await;
+    import_json(source_db.pool(), GOLDEN_V1).await;
+    let types = fixture_types();
+    let doc = jsonld::export_jsonld(source_db.pool(), Some(&types))
+        .await
+        .expect("jsonld export failed");
+
+    let payload = jsonld::parse_jsonld(&doc).expect("parse jsonld");
+    assert_eq!(payload.schema_version, Some(SCHEMA_VERSION));
+
+    let target_db = setup_test_db().await;
+    let result = import::import_data(target_db.pool(), &payload)
+        .await
+        .expect("import failed");
+    assert!(result.errors.is_empty(), "import errors: {:?}", result.errors);
+
+    // JSON-LD does not carry relation properties, so compare entities only
+    let first = normalized_export(&export_fixture_types(source_db.pool()).await);
+    let second = normalized_export(&export_fixture_types(target_db.pool()).await);
+    assert_eq!(first["entities"], second["entities"]);
+}
+
+// ────────────────────────────────────────────────────────────────────
+// 3. Versioning
+// ────────────────────────────────────────────────────────────────────
+
+#[tokio::test]
+async fn test_export_embeds_current_schema_version() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+
+    let exported = export_fixture_types(pool).await;
+    assert_eq!(exported.schema_version, SCHEMA_VERSION);
+
+    let golden: Value = serde_json::from_str(GOLDEN_V1).expect("parse golden fixture");
+    assert_eq!(
+        golden["schema_version"].as_u64(),
+        Some(SCHEMA_VERSION as u64),
+        "SCHEMA_VERSION changed — add a golden fixture for the new version"
+    );
+
+    let sql = export::export_sql(pool, None).await.expect("sql export failed");
+    assert!(sql.contains(&format!("-- schema_version: {}", SCHEMA_VERSION)));
+}
+
+#[tokio::test]
+async fn test_import_rejects_newer_schema_version() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+
+    let mut doc: Value = serde_json::from_str(GOLDEN_V1).expect("parse golden fixture");
+    doc["schema_version"] = Value::from(SCHEMA_VERSION + 1);
+    let payload: ImportPayload = serde_json::from_value(doc).expect("parse payload");
+
+    let err = import::import_data(pool, &payload)
+        .await
+        .expect_err("newer schema_version must be rejected");
+    assert!(err.contains("unsupported schema_version"), "unexpected error: {err}");
+
+    let count: (i64,) = sqlx::query_as(
+        "SELECT COUNT(*) FROM entities WHERE entity_type = 'contract_tor'",
+    )
+    .fetch_one(pool)
+    .await
+    .expect("count query failed");
+    assert_eq!(count.0, 0, "rejected payload must not write anything");
+}
+
+#[tokio::test]
+async fn test_legacy_unversioned_payload_imports() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+
+    import_json(pool, LEGACY_UNVERSIONED).await;
+
+    let exported = export_fixture_types(pool).await;
+    assert_eq!(exported.entities.len(), 1);
+    assert_eq!(exported.entities[0].name, "legacy_board");
+    assert_eq!(exported.schema_version, SCHEMA_VERSION);
+}
@@ -0,0 +1,115 @@
+//! Delta export tests — covers the change feed behind `?since=`.
+//!
+//! - Property writes, relation changes and deletions all advance the feed
+//! - Each export's `until` continues the feed without repeats
+//! - Writes committed after an export by a transaction begun before it are
+//!   not skipped
+//! - `since` accepts RFC 3339 timestamps and dates; type filters apply
+
+mod common;
+
+use std::time::Duration;
+
+use ahlt::models::data_manager::export;
+use ahlt::models::{entity, relation};
+use common::*;
+
+/// Let the clock move past the previous cursor.
+async fn tick() {
+    tokio::time::sleep(Duration::from_millis(5)).await;
+}
+
+fn entity_names(payload: &ahlt::models::data_manager::types::ExportPayload) -> Vec<&str> {
+    payload.entities.iter().map(|e| e.name.as_str()).collect()
+}
+
+#[tokio::test]
+async fn test_change_feed_follows_writes() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let alice = insert_entity(pool, "user", "alice", "Alice").await;
+    let bob = insert_entity(pool, "user", "bob", "Bob").await;
+    let tor = insert_entity(pool, "tor", "board", "Board").await;
+
+    let first = export::export_changes(pool, None, "2000-01-01T00:00:00Z").await.unwrap();
+    assert!(entity_names(&first).contains(&"alice"));
+    let cursor = first.until.clone().unwrap();
+
+    // Nothing changed: an empty delta
+    tick().await;
+    let empty = export::export_changes(pool, None, &cursor).await.unwrap();
+    assert!(empty.entities.is_empty() && empty.relations.is_empty() && empty.deleted.is_empty());
+
+    // A property write touches only its entity; a new relation shows up
+    tick().await;
+    entity::set_property(pool, alice, "email", "alice@example.org").await.unwrap();
+    relation::create(pool, "belongs_to_tor", alice, tor).await.unwrap();
+    let delta = export::export_changes(pool, None, &cursor).await.unwrap();
+    assert_eq!(entity_names(&delta), vec!["alice"]);
+    assert_eq!(delta.entities[0].properties.get("email").map(String::as_str), Some("alice@example.org"));
+    assert_eq!(delta.relations.len(), 1);
+    assert_eq!((delta.relations[0].source.as_str(), delta.relations[0].target.as_str()), ("user:alice", "tor:board"));
+    let cursor = delta.until.clone().unwrap();
+
+    // Deleting an entity leaves tombstones for it and its relations
+    tick().await;
+    relation::create(pool, "belongs_to_tor", bob, tor).await.unwrap();
+    entity::delete(pool, bob).await.unwrap();
+    let delta = export::export_changes(pool, None, &cursor).await.unwrap();
+    assert!(delta.entities.is_empty());
+    let deleted: Vec<(&str, &str)> = delta.deleted.iter().map(|d| (d.kind.as_str(), d.reference.as_str())).collect();
+    assert!(deleted.contains(&("entity", "user:bob")));
+    assert!(deleted.iter().any(|(kind, _)| *kind == "relation"));
+    assert_eq!(delta.since.as_deref(), Some(cursor.as_str()));
+}
+
+#[tokio::test]
+async fn test_open_transaction_holds_back_cursor() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let dave = insert_entity(pool, "user", "dave", "Dave").await;
+    let cursor = export::export_changes(pool, None, "2000-01-01").await.unwrap().until.unwrap();
+
+    // A write begun before the export but committed after it
+    tick().await;
+    let mut tx = pool.begin().await.unwrap();
+    entity::set_property_in_tx(&mut tx, dave, "email", "dave@example.org").await.unwrap();
+    tick().await;
+    let during = export::export_changes(pool, None, &cursor).await.unwrap();
+    assert!(during.entities.is_empty(), "uncommitted writes are not visible");
+    tx.commit().await.unwrap();
+
+    let after = export::export_changes(pool, None, &during.until.unwrap()).await.unwrap();
+    assert_eq!(entity_names(&after), vec!["dave"]);
+}
+
+#[tokio::test]
+async fn test_since_parsing_and_type_filter() {
+    assert_eq!(export::parse_since("2026-03-01").as_deref(), Some("2026-03-01T00:00:00.000000Z"));
+    assert_eq!(export::parse_since("2026-03-01 10:30:00").as_deref(), Some("2026-03-01T10:30:00.000000Z"));
+    assert_eq!(
+        export::parse_since("2026-03-01T12:30:00.25+02:00").as_deref(),
+        Some("2026-03-01T10:30:00.250000Z")
+    );
+    assert!(export::parse_since("yesterday").is_none());
+
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let start = export::export_changes(pool, None, "2000-01-01").await.unwrap().until.unwrap();
+    tick().await;
+    let carol = insert_entity(pool, "user", "carol", "Carol").await;
+    let tor = insert_entity(pool, "tor", "audit", "Audit").await;
+    relation::create(pool, "belongs_to_tor", carol, tor).await.unwrap();
+    entity::delete(pool, insert_entity(pool, "tor", "old", "Old").await).await.unwrap();
+
+    let users = vec!["user".to_string()];
+    let delta = export::export_changes(pool, Some(&users), &start).await.unwrap();
+    assert_eq!(entity_names(&delta), vec!["carol"]);
+    // The relation crosses into an unfiltered type, and the deleted ToR is filtered out
+    assert!(delta.relations.is_empty());
+    assert!(!delta.deleted.iter().any(|d| d.kind == "entity"));
+
+    // Full exports are unchanged by the delta fields
+    let full = serde_json::to_value(export::export_entities(pool, Some(&users)).await.unwrap()).unwrap();
+    assert!(full.get("since").is_none() && full.get("deleted").is_none());
+}
@@ -7,6 +7,7 @@
 //! - Relation import using seeded relation types
 //! - Full entity export round-trip
 //! - Type-filtered export
+//! - Payload validation and dry-run reports
 
 mod common;
 
@@ -14,7 +15,7 @@ use std::collections::HashMap;
 
 use ahlt::models::data_manager::{
     export,
-    import,
+    import::{self, PayloadIssue},
     types::{ConflictMode, EntityImport, ImportPayload, RelationImport},
 };
 use common::setup_test_db;
@@ -62,6 +63,7 @@ async fn test_import_entities() {
     let pool = db.pool();
 
     let payload = ImportPayload {
+        schema_version: None,
         conflict_mode: ConflictMode::Skip,
         entities: vec![
             make_entity(
@@ -130,6 +132,7 @@ async fn test_import_skip_mode() {
 
     // First import — entity is created
     let payload1 = ImportPayload {
+        schema_version: None,
         conflict_mode: ConflictMode::Skip,
         entities: vec![entity.clone()],
         relations: vec![],
@@ -146,6 +149,7 @@ async fn test_import_skip_mode() {
         vec![("version", "2")],
     );
     let payload2 = ImportPayload {
+        schema_version: None,
         conflict_mode: ConflictMode::Skip,
         entities: vec![modified],
         relations: vec![],
@@ -175,6 +179,7 @@ async fn test_import_with_relations() {
     let pool = db.pool();
 
     let payload = ImportPayload {
+        schema_version: None,
         conflict_mode: ConflictMode::Skip,
         entities: vec![
             make_entity("dm_rel_user", "alice", "Alice", vec![]),
@@ -215,6 +220,7 @@ async fn test_export_entities() {
 
     // Import some test entities
     let payload = ImportPayload {
+        schema_version: None,
         conflict_mode: ConflictMode::Skip,
         entities: vec![
             make_entity(
@@ -266,6 +272,7 @@ async fn test_export_filtered_by_type() {
 
     // Import entities of two different types
     let payload = ImportPayload {
+        schema_version: None,
         conflict_mode: ConflictMode::Skip,
         entities: vec![
             make_entity("dm_type_a", "a_item", "A Item", vec![]),
@@ -297,3 +304,108 @@ async fn test_export_filtered_by_type() {
     assert_eq!(type_a[0].name, "a_item");
     assert!(type_b.is_empty(), "should NOT include dm_type_b entities");
 }
+
+// ────────────────────────────────────────────────────────────────────
+// 6. Payload validation — duplicates, unknown types, dangling references
+// ────────────────────────────────────────────────────────────────────
+
+#[tokio::test]
+async fn test_validate_payload() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+
+    let payload = ImportPayload {
+        schema_version: None,
+        conflict_mode: ConflictMode::Skip,
+        entities: vec![
+            make_entity("dm_val", "one", "One", vec![]),
+            make_entity("dm_val", "one", "One again", vec![]),
+            make_entity("dm_val", "", "Nameless", vec![]),
+            make_entity("dm_val_role", "reader", "Reader", vec![]),
+        ],
+        relations: vec![
+            make_relation("has_role", "dm_val:one", "dm_val_role:reader"),
+            make_relation("no_such_relation", "dm_val:one", "dm_val_role:reader"),
+            make_relation("has_role", "dm_val:one", "dm_val_role:missing"),
+            make_relation("has_role", "not-a-reference", "dm_val_role:reader"),
+        ],
+    };
+
+    let issues = import::validate_payload(pool, &payload).await.expect("validation failed");
+    let positions: Vec<_> = issues.iter().map(|i| match i {
+        PayloadIssue::Entity(n, _) => ("entity", *n),
+        PayloadIssue::Relation(n, _) => ("relation", *n),
+    }).collect();
+    assert_eq!(positions, vec![("entity", 1), ("entity", 2), ("relation", 1), ("relation", 2), ("relation", 3)]);
+    assert!(matches!(&issues[2], PayloadIssue::Relation(_, r) if r.contains("unknown relation type")));
+    assert!(matches!(&issues[3], PayloadIssue::Relation(_, r) if r.contains("dangling reference: dm_val_role:missing")));
+}
+
+// ────────────────────────────────────────────────────────────────────
+// 7. Dry run reports the plan and commits nothing
+// ────────────────────────────────────────────────────────────────────
+
+#[tokio::test]
+async fn test_preview_import_commits_nothing() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+
+    let existing = ImportPayload {
+        schema_version: None,
+        conflict_mode: ConflictMode::Skip,
+        entities: vec![make_entity("dm_dry", "kept", "Kept", vec![])],
+        relations: vec![],
+    };
+    import::import_data(pool, &existing).await.expect("import failed");
+
+    let payload = ImportPayload {
+        schema_version: None,
+        conflict_mode: ConflictMode::Upsert,
+        entities: vec![
+            make_entity("dm_dry", "kept", "Kept, renamed", vec![]),
+            make_entity("dm_dry", "new", "New", vec![]),
+            make_entity("dm_dry", "new", "New twice", vec![]),
+        ],
+        relations: vec![
+            make_relation("has_role", "dm_dry:new", "dm_dry:kept"),
+            make_relation("has_role", "dm_dry:new", "dm_dry:gone"),
+        ],
+    };
+
+    let report = import::preview_import(pool, &payload).await.expect("dry run failed");
+    assert!(report.dry_run);
+    assert_eq!((report.created, report.updated, report.skipped), (1, 1, 0));
+    assert_eq!(report.errors.len(), 2);
+    let plan: Vec<_> = report.items.iter().map(|i| (i.reference.as_str(), i.action.as_str())).collect();
+    assert_eq!(plan, vec![
+        ("dm_dry:kept", "update"),
+        ("dm_dry:new", "create"),
+        ("dm_dry:new", "error"),
+        ("has_role dm_dry:new -> dm_dry:kept", "create"),
+        ("has_role dm_dry:new -> dm_dry:gone", "error"),
+    ]);
+
+    // Nothing was written
+    let exported = export::export_entities(pool, Some(&["dm_dry".to_string()])).await.expect("export failed");
+    assert_eq!(exported.entities.len(), 1);
+    assert_eq!(exported.entities[0].label, "Kept");
+
+    // The real import does what the dry run reported
+    let result = import::import_data(pool, &payload).await.expect("import failed");
+    assert!(!result.dry_run);
+    assert_eq!((result.created, result.updated), (1, 1));
+    assert_eq!(result.items.len(), report.items.len());
+
+    // In fail mode an invalid payload imports nothing at all
+    let strict = ImportPayload {
+        schema_version: None,
+        conflict_mode: ConflictMode::Fail,
+        entities: vec![make_entity("dm_dry", "strict", "Strict", vec![])],
+        relations: vec![make_relation("has_role", "dm_dry:strict", "dm_dry:gone")],
+    };
+    let result = import::import_data(pool, &strict).await.expect("import failed");
+    assert_eq!(result.created, 0);
+    assert_eq!(result.errors.len(), 1);
+    let exported = export::export_entities(pool, Some(&["dm_dry".to_string()])).await.expect("export failed");
+    assert!(exported.entities.iter().all(|e| e.name != "strict"));
+}
@@ -0,0 +1,85 @@
+//! Decision citation tests — covers citation IDs, content hashes and resolution.
+//!
+//! - Decisions get sequential per-ToR, per-year citation IDs when recorded
+//! - The content hash is the SHA-256 of the canonical decision and detects edits
+//! - Citation IDs (any case) and hashes resolve to the decision; JSON-LD output
+//! - Decisions recorded before citations existed are backfilled
+
+mod common;
+
+use ahlt::models::{decision_citation, entity, opinion, relation};
+use common::*;
+
+#[test]
+fn test_prefix_and_canonical_form() {
+    assert_eq!(decision_citation::prefix("ops_board"), "OPS-BOARD");
+    assert_eq!(decision_citation::prefix("--audit 2--"), "AUDIT-2");
+    assert_eq!(decision_citation::prefix("__"), "TOR");
+
+    let canonical = decision_citation::canonical("OPS-2026-001", 4, &[("decided_date", "2026-03-05".to_string())]);
+    assert_eq!(canonical, r#"{"citation_id":"OPS-2026-001","decided_date":"2026-03-05","tor_id":4}"#);
+    assert_eq!(decision_citation::content_hash("abc"), "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+}
+
+#[tokio::test]
+async fn test_citations_are_assigned_and_resolve() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let alice = insert_entity(pool, "user", "alice", "Alice").await;
+    let ops = insert_entity(pool, "tor", "ops_board", "Ops Board").await;
+    let fin = insert_entity(pool, "tor", "fin", "Finance").await;
+    let coa = insert_entity(pool, "coa", "coa1", "Option A").await;
+    let mut agenda_points = Vec::new();
+    for (name, tor) in [("ap1", ops), ("ap2", ops), ("ap3", fin)] {
+        let ap = insert_entity(pool, "agenda_point", name, name).await;
+        insert_prop(pool, ap, "title", &format!("Budget {name}")).await;
+        relation::create(pool, "belongs_to_tor", ap, tor).await.unwrap();
+        agenda_points.push(ap);
+    }
+
+    let first = opinion::record_decision(pool, agenda_points[0], alice, coa, "Cheapest").await.unwrap();
+    let second = opinion::record_decision(pool, agenda_points[1], alice, coa, "Fastest").await.unwrap();
+    let other = opinion::record_decision(pool, agenda_points[2], alice, coa, "Only option").await.unwrap();
+    let year = chrono::Local::now().format("%Y").to_string();
+
+    let register = opinion::find_decision_register(pool, None).await.unwrap();
+    let citation = |id: i64| register.iter().find(|d| d.id == id).unwrap().clone();
+    assert_eq!(citation(first).citation_id, format!("OPS-BOARD-{year}-001"));
+    assert_eq!(citation(second).citation_id, format!("OPS-BOARD-{year}-002"));
+    assert_eq!(citation(other).citation_id, format!("FIN-{year}-001"));
+    let entry = citation(first);
+    assert_eq!(entry.content_hash.len(), 64);
+    assert_ne!(entry.content_hash, citation(second).content_hash);
+    assert_eq!(entry.permalink(), format!("/decisions/sha256/{}", entry.content_hash));
+    assert!(decision_citation::verify(pool, &entry).await.unwrap());
+
+    // Both forms resolve; unknown references do not
+    let by_id = decision_citation::resolve(pool, &entry.citation_id.to_lowercase()).await.unwrap().unwrap();
+    assert_eq!(by_id.id, first);
+    let by_hash = decision_citation::resolve(pool, &entry.content_hash.to_uppercase()).await.unwrap().unwrap();
+    assert_eq!(by_hash.id, first);
+    assert!(decision_citation::resolve(pool, "OPS-BOARD-1999-001").await.unwrap().is_none());
+
+    let doc = decision_citation::to_json_ld(&entry, "https://gov.example");
+    assert_eq!(doc["@type"], "ChooseAction");
+    assert_eq!(doc["@id"], format!("https://gov.example/decisions/sha256/{}", entry.content_hash));
+    assert_eq!(doc["url"], format!("https://gov.example/cite/OPS-BOARD-{year}-001"));
+    assert_eq!(doc["identifier"][1]["value"], entry.content_hash);
+    assert_eq!(doc["actionOption"], "Option A");
+    assert_eq!(doc["participant"]["name"], "Ops Board");
+
+    // Editing the rationale breaks the content address
+    entity::set_property(pool, first, "decision_rationale", "Rewritten").await.unwrap();
+    assert!(!decision_citation::verify(pool, &entry).await.unwrap());
+
+    // Decisions from before citations get the next free numbers, oldest first
+    let legacy = insert_entity(pool, "decision", "legacy", "legacy").await;
+    insert_prop(pool, legacy, "agenda_point_id", &agenda_points[0].to_string()).await;
+    insert_prop(pool, legacy, "decided_date", &format!("{year}-01-02 09:00:00")).await;
+    assert_eq!(decision_citation::backfill(pool).await.unwrap(), 1);
+    assert_eq!(decision_citation::backfill(pool).await.unwrap(), 0);
+    assert_eq!(
+        entity::get_property(pool, legacy, "citation_id").await.unwrap().as_deref(),
+        Some(format!("OPS-BOARD-{year}-003").as_str())
+    );
+}
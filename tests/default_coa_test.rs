@@ -0,0 +1,80 @@
+//! Default COA set tests — covers managing the set and generating COAs for
+//! decision agenda points.
+//!
+//! - Titles are unique within an item type's set; sections need titles
+//! - Generated COAs copy the boilerplate sections and can be edited apart
+
+mod common;
+
+use ahlt::models::agenda_point;
+use ahlt::models::coa::{self, defaults::{self, BoilerplateSection, DefaultCoaInput}};
+use common::*;
+
+fn section(title: &str, content: &str) -> BoilerplateSection {
+    BoilerplateSection { title: title.to_string(), content: content.to_string() }
+}
+
+fn input<'a>(title: &'a str, sections: Vec<BoilerplateSection>) -> DefaultCoaInput<'a> {
+    DefaultCoaInput { title, description: "", sections }
+}
+
+#[tokio::test]
+async fn test_manage_default_set() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+
+    let approve = defaults::create(pool, "decision", &input("Approve", vec![])).await.unwrap();
+    let conditions = defaults::create(pool, "decision", &input("Approve with conditions", vec![
+        section("Conditions", "The conditions that must be met."),
+        section("Follow-up", ""),
+    ])).await.unwrap();
+    defaults::create(pool, "other", &input("Approve", vec![])).await.unwrap();
+
+    let set = defaults::find_set(pool, "decision").await.unwrap();
+    assert_eq!(set.iter().map(|c| c.id).collect::<Vec<_>>(), vec![approve, conditions]);
+    assert_eq!(set[1].sections[0], section("Conditions", "The conditions that must be met."));
+
+    assert_eq!(defaults::validate(&input(" approve ", vec![]), &set, None), vec!["The set already has a course of action titled 'approve'"]);
+    assert!(defaults::validate(&input("Approve", vec![]), &set, Some(approve)).is_empty());
+    assert_eq!(defaults::validate(&input("", vec![section(" ", "text")]), &set, None), vec![
+        "Title is required",
+        "Every boilerplate section needs a title",
+    ]);
+
+    let edit = DefaultCoaInput { title: "Approve as submitted", description: "No changes", sections: vec![section("Implementation", "")] };
+    defaults::update(pool, &set[0], &edit).await.unwrap();
+    let updated = defaults::find_by_id(pool, approve).await.unwrap().unwrap();
+    assert_eq!((updated.title.as_str(), updated.description.as_str(), updated.sections.len()), ("Approve as submitted", "No changes", 1));
+
+    defaults::delete(pool, conditions).await.unwrap();
+    assert_eq!(defaults::find_set(pool, "decision").await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_generate_for_agenda_point() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let alice = insert_entity(pool, "user", "alice", "Alice").await;
+    let board = insert_entity(pool, "tor", "board", "Board").await;
+    defaults::create(pool, "decision", &input("Approve", vec![])).await.unwrap();
+    defaults::create(pool, "decision", &input("Defer", vec![
+        section("Information needed", "What must be provided."),
+        section("Return date", "When the matter returns."),
+    ])).await.unwrap();
+
+    let ap = agenda_point::create(pool, board, "Budget", "", "decision", "2026-10-20", 30, alice, "", "", "").await.unwrap();
+    let ids = defaults::generate(pool, ap, "decision", alice).await.unwrap();
+    assert_eq!(ids.len(), 2);
+    let linked: Vec<i64> = coa::find_all_for_agenda_point(pool, ap).await.unwrap().iter().map(|c| c.id).collect();
+    assert!(ids.iter().all(|id| linked.contains(id)));
+
+    let approve = coa::find_by_id(pool, ids[0]).await.unwrap();
+    assert_eq!((approve.title.as_str(), approve.coa_type.as_str()), ("Approve", "simple"));
+    let defer = coa::find_by_id(pool, ids[1]).await.unwrap();
+    assert_eq!(defer.coa_type, "complex");
+    let sections: Vec<(&str, &str)> = defer.sections.iter().map(|s| (s.title.as_str(), s.content.as_str())).collect();
+    assert_eq!(sections, vec![("Information needed", "What must be provided."), ("Return date", "When the matter returns.")]);
+
+    // Nothing is generated for item types without a set
+    assert!(defaults::generate(pool, ap, "informative", alice).await.unwrap().is_empty());
+}
@@ -0,0 +1,98 @@
+//! Out-of-office delegation tests — covers the window and attribution.
+//!
+//! - A window needs a delegate other than the user and end >= start
+//! - Only windows covering today route work to the delegate
+//! - A delegate acts only in ToRs where the absent member sits
+//! - Delegated decisions read "Bob on behalf of Alice" in the register and minutes
+
+mod common;
+
+use ahlt::models::{delegation, meeting, minutes, opinion, relation, tor};
+use chrono::NaiveDate;
+use common::*;
+use sqlx::PgPool;
+
+fn day(s: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+}
+
+async fn member(pool: &PgPool, tor_id: i64, name: &str, label: &str) -> i64 {
+    let user = insert_entity(pool, "user", name, label).await;
+    let position = insert_entity(pool, "tor_function", &format!("{name}_seat"), "Member").await;
+    relation::create(pool, "belongs_to_tor", position, tor_id).await.unwrap();
+    tor::assign_to_position(pool, user, position, "mandatory").await.unwrap();
+    user
+}
+
+#[tokio::test]
+async fn test_out_of_office_window() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let board = insert_entity(pool, "tor", "board", "Board").await;
+    let audit = insert_entity(pool, "tor", "audit", "Audit").await;
+    let alice = member(pool, board, "alice", "Alice").await;
+    let bob = insert_entity(pool, "user", "bob", "Bob").await;
+
+    assert_eq!(delegation::validate(alice, alice, "2026-05-01", "2026-05-10"), vec!["You cannot delegate to yourself"]);
+    assert_eq!(delegation::validate(alice, bob, "2026-05-10", "2026-05-01").len(), 1);
+    assert_eq!(delegation::validate(alice, 0, "", "2026-05-01").len(), 2);
+    assert!(delegation::validate(alice, bob, "2026-05-01", "2026-05-01").is_empty());
+
+    assert!(delegation::find_for_user(pool, alice).await.unwrap().is_none());
+    delegation::set_out_of_office(pool, alice, bob, "2026-05-01", "2026-05-10", " Annual leave ").await.unwrap();
+    let ooo = delegation::find_for_user(pool, alice).await.unwrap().unwrap();
+    assert_eq!((ooo.delegate_name.as_str(), ooo.note.as_str()), ("Bob", "Annual leave"));
+    assert!(ooo.is_active_on(day("2026-05-10")));
+    assert!(!ooo.is_active_on(day("2026-04-30")));
+    assert!(ooo.is_current_on(day("2026-04-30")));
+    assert_eq!(ooo.attribution(), "Bob on behalf of Alice");
+
+    assert_eq!(delegation::find_active_delegators(pool, bob, day("2026-05-05")).await.unwrap(), vec![ooo]);
+    assert!(delegation::find_active_delegators(pool, bob, day("2026-05-11")).await.unwrap().is_empty());
+
+    // Bob may act for Alice only in the ToR she sits on
+    let principals = delegation::find_principals_in_tor(pool, bob, board, day("2026-05-05")).await.unwrap();
+    assert_eq!(principals.len(), 1);
+    assert_eq!(principals[0].0.user_id, alice);
+    assert!(delegation::find_principals_in_tor(pool, bob, audit, day("2026-05-05")).await.unwrap().is_empty());
+
+    delegation::clear_out_of_office(pool, alice).await.unwrap();
+    assert!(delegation::find_for_user(pool, alice).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_delegated_decision_attribution() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let board = insert_entity(pool, "tor", "board", "Board").await;
+    let alice = member(pool, board, "alice", "Alice").await;
+    let bob = insert_entity(pool, "user", "bob", "Bob").await;
+    let meeting_id = insert_entity(pool, "meeting", "may", "May Meeting").await;
+
+    let mut decisions = Vec::new();
+    for (name, on_behalf) in [("budget", true), ("audit", false)] {
+        let ap = insert_entity(pool, "agenda_point", name, name).await;
+        relation::create(pool, "belongs_to_tor", ap, board).await.unwrap();
+        meeting::assign_agenda(pool, meeting_id, ap).await.unwrap();
+        let coa = insert_entity(pool, "coa", &format!("{name}_coa"), "Approve").await;
+        let decided_by = if on_behalf { bob } else { alice };
+        let id = opinion::record_decision(pool, ap, decided_by, coa, "").await.unwrap();
+        if on_behalf {
+            opinion::record_on_behalf_of(pool, id, ap, alice).await.unwrap();
+        }
+        decisions.push(id);
+    }
+
+    let register = opinion::find_decision_register(pool, Some(board)).await.unwrap();
+    let entry = |id: i64| register.iter().find(|d| d.id == id).unwrap();
+    assert_eq!(entry(decisions[0]).decided_by(), "Bob on behalf of Alice");
+    assert_eq!(entry(decisions[1]).decided_by(), "Alice (Member)");
+
+    let minutes_id = minutes::generate_scaffold(pool, meeting_id, board, "May Meeting").await.unwrap();
+    let sections = minutes::find_sections(pool, minutes_id).await.unwrap();
+    let decided = sections.iter().find(|s| s.section_type == "decisions").unwrap();
+    assert_eq!(
+        decided.content,
+        "- budget: Approve (decided by Bob on behalf of Alice)\n- audit: Approve (decided by Alice)"
+    );
+}
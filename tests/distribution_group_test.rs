@@ -0,0 +1,100 @@
+//! Distribution group tests — covers position rules and address exports.
+//!
+//! - Every active ToR has a built-in members group
+//! - Stored rules match position text and membership type across ToRs
+//! - Members follow the current position holders
+//! - Address lists skip members without email and quote awkward names
+
+mod common;
+
+use ahlt::models::{distribution_group::{self, GroupRule}, relation, tor};
+use common::*;
+use sqlx::PgPool;
+
+async fn user(pool: &PgPool, name: &str, label: &str, email: &str) -> i64 {
+    let id = insert_entity(pool, "user", name, label).await;
+    if !email.is_empty() {
+        insert_prop(pool, id, "email", email).await;
+    }
+    id
+}
+
+async fn position(pool: &PgPool, tor_id: i64, name: &str, label: &str, holder: i64, membership_type: &str) -> i64 {
+    let id = insert_entity(pool, "tor_function", name, label).await;
+    relation::create(pool, "belongs_to_tor", id, tor_id).await.unwrap();
+    tor::assign_to_position(pool, holder, id, membership_type).await.unwrap();
+    id
+}
+
+#[tokio::test]
+async fn test_groups_follow_position_holders() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let board = insert_entity(pool, "tor", "board", "Board").await;
+    let audit = insert_entity(pool, "tor", "audit", "Audit Committee").await;
+    let alice = user(pool, "alice", "Alice", "alice@example.org").await;
+    let bob = user(pool, "bob", "Bob", "bob@example.org").await;
+    let carol = user(pool, "carol", "Carol", "").await;
+
+    position(pool, board, "board_chair", "Chair", alice, "mandatory").await;
+    position(pool, board, "board_member", "Member", bob, "optional").await;
+    let audit_chair = position(pool, audit, "audit_chair", "Vice Chair", carol, "mandatory").await;
+    position(pool, audit, "audit_member", "Member", alice, "mandatory").await;
+
+    let groups = distribution_group::find_all(pool).await.unwrap();
+    assert_eq!(
+        groups.iter().map(|g| g.key.clone()).collect::<Vec<_>>(),
+        vec![format!("tor-{}", audit), format!("tor-{}", board)]
+    );
+    let board_group = distribution_group::find_by_key(pool, &format!("tor-{}", board)).await.unwrap().unwrap();
+    assert_eq!(board_group.label, "Members of Board");
+    let members = distribution_group::find_members(pool, &board_group.rule).await.unwrap();
+    assert_eq!(members.iter().map(|m| m.user_id).collect::<Vec<_>>(), vec![alice, bob]);
+
+    let rule = GroupRule { tor_id: None, position_match: "CHAIR".to_string(), membership_type: String::new() };
+    assert!(distribution_group::validate("Chairs", &rule).is_empty());
+    assert_eq!(distribution_group::validate("", &GroupRule::default()).len(), 2);
+    let id = distribution_group::create(pool, "Chairs", "", &rule).await.unwrap();
+    let chairs = distribution_group::find_by_key(pool, &id.to_string()).await.unwrap().unwrap();
+    assert_eq!(chairs.describe(), "Positions matching \u{201c}CHAIR\u{201d} in every ToR");
+
+    let members = distribution_group::find_members(pool, &chairs.rule).await.unwrap();
+    assert_eq!(members.len(), 2);
+    assert_eq!(members[0].positions, vec!["Chair (Board)".to_string()]);
+    assert_eq!(members[1].user_id, carol);
+
+    // Mandatory members across ToRs; Alice appears once with both positions
+    let mandatory = GroupRule { membership_type: "mandatory".to_string(), ..GroupRule::default() };
+    let members = distribution_group::find_members(pool, &mandatory).await.unwrap();
+    assert_eq!(members[0].positions, vec!["Member (Audit Committee)".to_string(), "Chair (Board)".to_string()]);
+    assert_eq!(members.len(), 2);
+
+    // A new holder replaces the old one
+    tor::vacate_position(pool, audit_chair).await.unwrap();
+    tor::assign_to_position(pool, bob, audit_chair, "mandatory").await.unwrap();
+    let members = distribution_group::find_members(pool, &chairs.rule).await.unwrap();
+    assert_eq!(members.iter().map(|m| m.user_id).collect::<Vec<_>>(), vec![alice, bob]);
+
+    assert!(distribution_group::delete(pool, id).await.unwrap());
+    assert!(distribution_group::find_by_key(pool, &id.to_string()).await.unwrap().is_none());
+    assert!(distribution_group::find_by_key(pool, "tor-x").await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_address_exports() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let board = insert_entity(pool, "tor", "board", "Board").await;
+    let dana = user(pool, "dana", "Doe, Dana", "dana@example.org").await;
+    let erik = user(pool, "erik", "Erik", "").await;
+    position(pool, board, "chair", "Chair", dana, "mandatory").await;
+    position(pool, board, "secretary", "Secretary", erik, "optional").await;
+
+    let rule = GroupRule { tor_id: Some(board), ..GroupRule::default() };
+    let members = distribution_group::find_members(pool, &rule).await.unwrap();
+    assert_eq!(distribution_group::to_address_list(&members), "\"Doe, Dana\" <dana@example.org>;\n");
+    assert_eq!(
+        distribution_group::to_csv(&members),
+        "Name,Email,Positions\n\"Doe, Dana\",dana@example.org,Chair (Board)\nErik,,Secretary (Board)\n"
+    );
+}
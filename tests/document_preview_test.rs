@@ -0,0 +1,75 @@
+//! Attachment preview tests — covers thumbnails and the meeting pack.
+//!
+//! - Image and PDF data URIs are recognised; anything else previews as text
+//! - Thumbnails are cached on disk until the document changes
+//! - The meeting pack lists attachments of the proposals on the agenda
+
+mod common;
+
+use ahlt::models::document::{self, preview::{self, PreviewKind}};
+use ahlt::models::{meeting, relation};
+use common::*;
+
+/// 1x1 transparent PNG.
+const PNG: &str = "iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAQAAAC1HAwCAAAAC0lEQVR42mNkYAAAAAYAAjCB0C8AAAAASUVORK5CYII=";
+
+#[tokio::test]
+async fn test_thumbnail_kinds_and_cache() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+
+    assert_eq!(
+        preview::detect(&format!("data:image/png;base64,{PNG}")),
+        PreviewKind::Image { mime: "image/png".into(), data: PNG.into() }
+    );
+    assert_eq!(preview::detect("data:image/svg+xml;base64,PHN2Zz4="), PreviewKind::Text);
+    let pdf = b"%PDF-1.4\n1 0 obj << /Type /Pages /Count 2 >>\n2 0 obj << /Type /Page >>\n3 0 obj << /Type/Page >>";
+    let pdf_uri = format!("data:application/pdf;base64,{}", base64_encode(pdf));
+    assert_eq!(preview::detect(&pdf_uri), PreviewKind::Pdf { pages: 2 });
+
+    let id = document::create(pool, "Budget <draft>", "ad_hoc", "Line one & two\n\nSecond paragraph", 0, None).await.unwrap();
+    let doc = document::find_by_id(pool, id).await.unwrap().unwrap();
+    let svg = preview::render_svg(&doc);
+    assert!(svg.starts_with("<svg") && svg.contains("Budget &lt;draft&gt;") && svg.contains("Line one &amp; two"));
+
+    let dir = std::env::temp_dir().join(format!("ahlt-previews-{}-{id}", std::process::id()));
+    assert_eq!(preview::cached_thumbnail(&dir, &doc).unwrap(), svg);
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+
+    // An edited document gets a new thumbnail and the old one is dropped
+    document::update(pool, id, "Budget", "ad_hoc", &pdf_uri).await.unwrap();
+    let edited = document::find_by_id(pool, id).await.unwrap().unwrap();
+    assert_ne!(preview::fingerprint(&edited), preview::fingerprint(&doc));
+    assert!(preview::cached_thumbnail(&dir, &edited).unwrap().contains("2 pages"));
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_meeting_pack() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let meeting_id = insert_entity(pool, "meeting", "board_2026_03", "Board March").await;
+    let proposal = insert_entity(pool, "proposal", "budget", "Budget").await;
+    let point = insert_entity(pool, "agenda_point", "budget_point", "Budget 2027").await;
+    let unscheduled = insert_entity(pool, "proposal", "hiring", "Hiring").await;
+    let budget_doc = document::create(pool, "Budget paper", "ad_hoc", "Numbers", 0, None).await.unwrap();
+    let hiring_doc = document::create(pool, "Hiring plan", "ad_hoc", "People", 0, None).await.unwrap();
+
+    relation::create(pool, "spawns_agenda_point", proposal, point).await.unwrap();
+    meeting::assign_agenda(pool, meeting_id, point).await.unwrap();
+    relation::create(pool, "attached_to_proposal", budget_doc, proposal).await.unwrap();
+    relation::create(pool, "attached_to_proposal", hiring_doc, unscheduled).await.unwrap();
+
+    let pack = meeting::find_pack_documents(pool, meeting_id).await.unwrap();
+    assert_eq!(pack.len(), 1);
+    assert_eq!(
+        (pack[0].document_id, pack[0].title.as_str(), pack[0].agenda_label.as_str()),
+        (budget_doc, "Budget paper", "Budget 2027")
+    );
+}
+
+fn base64_encode(bytes: &[u8]) -> String {
+    use base64::Engine;
+    base64::engine::general_purpose::STANDARD.encode(bytes)
+}
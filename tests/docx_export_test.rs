@@ -0,0 +1,146 @@
+//! Word export tests — covers the .docx packages for minutes and meeting
+//! agenda packs.
+//!
+//! - The package holds the parts Word needs and every style the layout uses
+//! - Text is escaped and multi-line fields become separate paragraphs
+//! - The agenda pack lists each point with its proposal and COAs
+//! - Unchanged content exports to identical bytes
+
+mod common;
+
+use std::io::Read;
+
+use ahlt::export::docx;
+use ahlt::models::meeting::MeetingDetail;
+use ahlt::models::minutes::{Minutes, MinutesSection};
+use ahlt::models::{agenda_point, coa, meeting, proposal, relation};
+use ahlt::templates_structs::MinutesDocxTemplate;
+use askama::Template;
+use common::*;
+
+fn part(bytes: &[u8], name: &str) -> String {
+    let mut archive = zip::ZipArchive::new(std::io::Cursor::new(bytes)).unwrap();
+    let mut contents = String::new();
+    archive.by_name(name).unwrap().read_to_string(&mut contents).unwrap();
+    contents
+}
+
+fn minutes() -> Minutes {
+    Minutes {
+        id: 11,
+        name: "minutes_7".to_string(),
+        label: "Minutes — Board & Co".to_string(),
+        status: "approved".to_string(),
+        generated_date: "2026-03-05".to_string(),
+        meeting_id: 7,
+        meeting_name: "board_2026_03".to_string(),
+        approved_by: "alice".to_string(),
+        approved_date: "2026-03-10".to_string(),
+        distribution_list: r#"["all-staff"]"#.to_string(),
+        structured_attendance: r#"[{"name":"Alice","status":"present"}]"#.to_string(),
+        structured_action_items: r#"[{"description":"Send <budget>","responsible":"Bob","status":"open"}]"#.to_string(),
+        circulated_at: String::new(),
+    }
+}
+
+fn section(label: &str, content: &str) -> MinutesSection {
+    MinutesSection {
+        id: 1,
+        name: "decisions_11".to_string(),
+        label: label.to_string(),
+        section_type: "decisions".to_string(),
+        sequence_order: 1,
+        content: content.to_string(),
+        is_auto_generated: false,
+        circulated_content: None,
+    }
+}
+
+#[test]
+fn test_minutes_docx_package() {
+    let xml = MinutesDocxTemplate {
+        minutes: minutes(),
+        meeting: None::<MeetingDetail>,
+        sections: vec![section("Decisions", "Budget approved.\nReview in Q3.")],
+    }
+    .render()
+    .unwrap();
+    let bytes = docx::package("Minutes — Board & Co", &xml).unwrap();
+
+    let archive = zip::ZipArchive::new(std::io::Cursor::new(&bytes)).unwrap();
+    let mut names: Vec<&str> = archive.file_names().collect();
+    names.sort();
+    assert_eq!(names, vec![
+        "[Content_Types].xml",
+        "_rels/.rels",
+        "docProps/core.xml",
+        "word/_rels/document.xml.rels",
+        "word/document.xml",
+        "word/styles.xml",
+    ]);
+
+    let document = part(&bytes, "word/document.xml");
+    assert!(document.starts_with("<?xml"));
+    assert!(document.contains(">Minutes — Board &#38;") || document.contains(">Minutes — Board &amp;"));
+    assert!(document.contains("Send &#60;budget&#62;") || document.contains("Send &lt;budget&gt;"));
+    assert!(document.contains(">Budget approved.</w:t>"));
+    assert!(document.contains(">Review in Q3.</w:t>"));
+    assert!(document.contains(">all-staff</w:t>"));
+    assert!(document.contains(">Approved by: </w:t>"));
+
+    let styles = part(&bytes, "word/styles.xml");
+    for style in ["Title", "Subtitle", "Heading1", "Heading2", "Heading3", "Meta", "TableGrid"] {
+        assert!(styles.contains(&format!(r#"w:styleId="{style}""#)), "missing style {style}");
+    }
+    assert!(part(&bytes, "docProps/core.xml").contains("<dc:title>Minutes — Board &amp; Co</dc:title>"));
+
+    assert_eq!(docx::package("Minutes — Board & Co", &xml).unwrap(), bytes);
+}
+
+#[tokio::test]
+async fn test_agenda_pack_docx() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let alice = insert_entity(pool, "user", "alice", "Alice").await;
+    let board = insert_entity(pool, "tor", "board", "Board").await;
+
+    let meeting_id = meeting::create(pool, board, "2026-10-01", "Board", "Room 1", "Bring laptops", "", "", "", "alice", "")
+        .await.unwrap();
+    let budget = agenda_point::create(pool, board, "Budget 2027", "Line one\nLine two", "decision", "2026-10-01", 30, alice, "Alice", "", "")
+        .await.unwrap();
+    let update = agenda_point::create(pool, board, "Status update", "", "informative", "2026-10-02", 10, alice, "", "", "")
+        .await.unwrap();
+    meeting::assign_agenda(pool, meeting_id, budget).await.unwrap();
+    meeting::assign_agenda(pool, meeting_id, update).await.unwrap();
+
+    let proposal_id = proposal::create(pool, board, "Adopt the 2027 budget", "Spend wisely", "Costs are rising", alice, "2026-09-01", None)
+        .await.unwrap();
+    relation::create(pool, "spawns_agenda_point", proposal_id, budget).await.unwrap();
+    let approve = coa::create(pool, "Approve", "As submitted", "complex", alice).await.unwrap();
+    coa::add_section(pool, approve, "Conditions", "Quarterly review", 1).await.unwrap();
+    relation::create(pool, "considers_coa", budget, approve).await.unwrap();
+    let reject = coa::create(pool, "Reject", "", "simple", alice).await.unwrap();
+    relation::create(pool, "considers_coa", budget, reject).await.unwrap();
+
+    let items = docx::agenda_pack_items(pool, meeting_id).await.unwrap();
+    assert_eq!(items.len(), 2);
+    let budget_item = items.iter().find(|i| i.point.id == budget).unwrap();
+    assert!(budget_item.is_decision());
+    assert_eq!(budget_item.proposal.as_ref().map(|p| p.id), Some(proposal_id));
+    assert_eq!(budget_item.coas.iter().map(|c| c.title.as_str()).collect::<Vec<_>>(), vec!["Approve", "Reject"]);
+    assert!(items.iter().find(|i| i.point.id == update).unwrap().proposal.is_none());
+
+    let meeting = meeting::find_by_id(pool, meeting_id).await.unwrap().unwrap();
+    let bytes = docx::agenda_pack(pool, &meeting).await.unwrap();
+    assert_eq!(docx::agenda_file_name(&meeting), format!("agenda-{}.docx", meeting.name));
+
+    let document = part(&bytes, "word/document.xml");
+    for text in [
+        "Room 1", "Bring laptops", "For decision", "For information", "30 min",
+        "Line one", "Line two", "Proposal: Adopt the 2027 budget", "Costs are rising",
+        "COA 1: Approve", "COA 2: Reject", "Conditions", "Quarterly review",
+    ] {
+        assert!(document.contains(text), "missing {text}");
+    }
+    assert_eq!(docx::agenda_pack(pool, &meeting).await.unwrap(), bytes);
+}
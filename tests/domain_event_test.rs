@@ -0,0 +1,83 @@
+//! Domain event tests — covers the append-only event stream.
+//!
+//! - Proposal and meeting transitions and recorded decisions append events
+//! - Readers page by id, filter by type, and never see uncommitted events
+//! - The stream rejects updates and deletes
+
+mod common;
+
+use ahlt::models::{domain_event, meeting, opinion, proposal};
+use common::*;
+
+/// Readers hold back events while any transaction in the database is open,
+/// so these tests must not overlap.
+static SERIAL: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+fn types(events: &[domain_event::DomainEvent]) -> Vec<&str> {
+    events.iter().map(|e| e.event_type.as_str()).collect()
+}
+
+#[tokio::test]
+async fn test_mutations_append_events() {
+    let _serial = SERIAL.lock().await;
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let alice = insert_entity(pool, "user", "alice", "Alice").await;
+    let tor = insert_entity(pool, "tor", "board", "Board").await;
+    let prop = insert_entity(pool, "proposal", "budget", "Budget").await;
+
+    proposal::transition(pool, prop, "submitted", None, alice).await.unwrap();
+    proposal::transition(pool, prop, "rejected", Some("No budget"), alice).await.unwrap();
+    let mid = meeting::create(pool, tor, "2026-05-01", "Board", "", "", "", "", "", "", "").await.unwrap();
+    meeting::transition(pool, mid, "confirmed", alice).await.unwrap();
+    let ap = insert_entity(pool, "agenda_point", "ap1", "Budget").await;
+    let coa = insert_entity(pool, "coa", "coa1", "Option A").await;
+    let decision = opinion::record_decision(pool, ap, alice, coa, "Cheapest").await.unwrap();
+    // Plain status updates are not events
+    proposal::update_status(pool, prop, "draft", None).await.unwrap();
+
+    let all = domain_event::find_after(pool, 0, None, 100).await.unwrap();
+    assert_eq!(types(&all), vec!["proposal.submitted", "proposal.rejected", "meeting.confirmed", "decision.recorded"]);
+    assert_eq!(all[1].payload["rejection_reason"], "No budget");
+    assert_eq!((all[2].aggregate_type.as_str(), all[2].aggregate_id, all[2].actor_id), ("meeting", mid, alice));
+    assert_eq!((all[3].aggregate_id, all[3].payload["selected_coa_id"].as_i64()), (decision, Some(coa)));
+
+    // Cursor paging and type filters
+    let page = domain_event::find_after(pool, 0, None, 2).await.unwrap();
+    assert_eq!(page.len(), 2);
+    let rest = domain_event::find_after(pool, page[1].id, None, 100).await.unwrap();
+    assert_eq!(types(&rest), vec!["meeting.confirmed", "decision.recorded"]);
+    let proposals = domain_event::find_after(pool, 0, Some("proposal."), 100).await.unwrap();
+    assert_eq!(proposals.len(), 2);
+    let history = domain_event::find_for_aggregate(pool, "proposal", prop).await.unwrap();
+    assert_eq!(types(&history), vec!["proposal.submitted", "proposal.rejected"]);
+}
+
+#[tokio::test]
+async fn test_stream_is_transactional_and_append_only() {
+    let _serial = SERIAL.lock().await;
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let alice = insert_entity(pool, "user", "alice", "Alice").await;
+    let prop = insert_entity(pool, "proposal", "budget", "Budget").await;
+
+    // A rolled-back change leaves no event
+    let mut tx = pool.begin().await.unwrap();
+    domain_event::append(&mut tx, "proposal.submitted", "proposal", prop, alice, serde_json::json!({})).await.unwrap();
+    tx.rollback().await.unwrap();
+    assert!(domain_event::find_after(pool, 0, None, 100).await.unwrap().is_empty());
+
+    // While an earlier transaction is open, later commits are held back so
+    // a reader's cursor cannot pass the pending event
+    let mut pending = pool.begin().await.unwrap();
+    domain_event::append(&mut pending, "proposal.under_review", "proposal", prop, alice, serde_json::json!({})).await.unwrap();
+    proposal::transition(pool, prop, "approved", None, alice).await.unwrap();
+    assert!(domain_event::find_after(pool, 0, None, 100).await.unwrap().is_empty());
+    pending.commit().await.unwrap();
+    let events = domain_event::find_after(pool, 0, None, 100).await.unwrap();
+    assert_eq!(types(&events), vec!["proposal.under_review", "proposal.approved"]);
+
+    assert!(sqlx::query("UPDATE domain_events SET event_type = 'x'").execute(pool).await.is_err());
+    assert!(sqlx::query("DELETE FROM domain_events").execute(pool).await.is_err());
+    assert_eq!(domain_event::find_after(pool, 0, None, 100).await.unwrap().len(), 2);
+}
@@ -0,0 +1,186 @@
+//! E-mail notification tests — covers SMTP delivery of opted-in events.
+//!
+//! - The SMTP client authenticates, encodes headers and body, and reports
+//!   refusals
+//! - Only opted-in users with an address are queued, only while e-mail is
+//!   enabled, and authors are not told about their own changes
+//! - Queued mail is sent by the e-mail sender and retried while the server
+//!   is unreachable
+
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use ahlt::handlers::warning_handlers::ws::new_connection_map;
+use ahlt::models::{proposal, setting};
+use ahlt::warnings::outbox;
+use ahlt::warnings::notifications::{self, email};
+use ahlt::warnings::notifications::email::smtp::{self, Security, SmtpConfig};
+use base64::Engine;
+use common::*;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+
+/// A fake SMTP server; returns its port and the commands and messages it received.
+async fn fake_server(refuse_rcpt: bool) -> (u16, Arc<Mutex<Vec<String>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let received = log.clone();
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let (read, mut write) = stream.into_split();
+            let mut lines = BufReader::new(read).lines();
+            write.write_all(b"220 fake ESMTP\r\n").await.unwrap();
+            let mut data: Option<String> = None;
+            while let Ok(Some(line)) = lines.next_line().await {
+                if let Some(body) = data.as_mut() {
+                    if line == "." {
+                        received.lock().unwrap().push(data.take().unwrap());
+                        write.write_all(b"250 queued\r\n").await.unwrap();
+                    } else {
+                        body.push_str(&line);
+                        body.push('\n');
+                    }
+                    continue;
+                }
+                received.lock().unwrap().push(line.clone());
+                let reply: &[u8] = match line.split(' ').next().unwrap() {
+                    "EHLO" => b"250-fake\r\n250 AUTH PLAIN\r\n",
+                    "AUTH" => b"235 ok\r\n",
+                    "RCPT" if refuse_rcpt => b"550 no such user\r\n",
+                    "DATA" => {
+                        data = Some(String::new());
+                        b"354 go ahead\r\n"
+                    }
+                    "QUIT" => b"221 bye\r\n",
+                    _ => b"250 ok\r\n",
+                };
+                write.write_all(reply).await.unwrap();
+            }
+        }
+    });
+    (port, log)
+}
+
+fn decode_body(message: &str) -> String {
+    let encoded: String = message.split("\n\n").nth(1).unwrap().lines().collect();
+    String::from_utf8(base64::engine::general_purpose::STANDARD.decode(encoded).unwrap()).unwrap()
+}
+
+async fn set(pool: &sqlx::PgPool, name: &str, value: &str) -> i64 {
+    let id = insert_entity(pool, "setting", name, name).await;
+    insert_prop(pool, id, "value", value).await;
+    id
+}
+
+#[tokio::test]
+async fn test_smtp_session() {
+    let (port, log) = fake_server(false).await;
+    let config = SmtpConfig {
+        host: "127.0.0.1".to_string(),
+        port,
+        security: Security::None,
+        credentials: Some(("mailer".to_string(), "s3cret".to_string())),
+        from: "governance@example.org".to_string(),
+    };
+    let message = smtp::Message {
+        to: "alice@example.org".to_string(),
+        to_name: "Andersen, Alice".to_string(),
+        subject: "Møte i styret\r\nBcc: x@evil.example".to_string(),
+        body: "Line one\n.starts with a dot".to_string(),
+    };
+    smtp::send(&config, &message).await.unwrap();
+
+    let log = log.lock().unwrap().clone();
+    let auth = base64::engine::general_purpose::STANDARD.encode("\0mailer\0s3cret");
+    assert_eq!(log[..5], [
+        "EHLO example.org".to_string(),
+        format!("AUTH PLAIN {auth}"),
+        "MAIL FROM:<governance@example.org>".to_string(),
+        "RCPT TO:<alice@example.org>".to_string(),
+        "DATA".to_string(),
+    ]);
+    let sent = &log[5];
+    assert!(sent.contains("To: \"Andersen, Alice\" <alice@example.org>\n"));
+    // Non-ASCII subjects are encoded, and line breaks cannot inject headers
+    assert!(sent.contains("Subject: =?utf-8?B?"));
+    assert!(!sent.contains("\nBcc:"));
+    assert_eq!(decode_body(sent), "Line one\r\n.starts with a dot");
+    assert_eq!(log[6], "QUIT");
+
+    // A refused recipient is an error
+    let (port, _) = fake_server(true).await;
+    let err = smtp::send(&SmtpConfig { port, credentials: None, ..config.clone() }, &message).await.unwrap_err();
+    assert_eq!(err, "SMTP 550: no such user");
+    assert!(smtp::send(&config, &smtp::Message { to: "a@b.example>\r\nRCPT".to_string(), ..message }).await.is_err());
+    assert!(smtp::is_address("bob@example.org"));
+    assert!(!smtp::is_address("bob@localhost"));
+}
+
+#[tokio::test]
+async fn test_opted_in_users_are_mailed() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let alice = insert_entity(pool, "user", "alice", "Alice").await;
+    insert_prop(pool, alice, "email", "alice@example.org").await;
+    let bob = insert_entity(pool, "user", "bob", "Bob").await;
+    insert_prop(pool, bob, "email", "bob@example.org").await;
+    let carol = insert_entity(pool, "user", "carol", "Carol").await;
+    email::set_preferences(pool, alice, &["warnings".to_string(), "proposals".to_string()]).await.unwrap();
+    email::set_preferences(pool, carol, &["warnings".to_string()]).await.unwrap();
+    let prefs = email::find_preferences(pool, alice).await.unwrap();
+    assert_eq!(prefs.iter().map(|p| (p.key, p.enabled)).collect::<Vec<_>>(),
+        vec![("warnings", true), ("meetings", false), ("proposals", true), ("reports", false)]);
+
+    let email_rows = |status: &'static str| async move {
+        outbox::find_recent(pool, Some(status), 100).await.unwrap()
+            .into_iter()
+            .filter(|e| e.channel == outbox::CHANNEL_EMAIL)
+            .collect::<Vec<_>>()
+    };
+
+    // Disabled: nothing is queued
+    notifications::send(pool, "mention.text", "bob mentioned you", "/tor/1", &[alice, bob, carol]).await.unwrap();
+    assert!(email_rows("pending").await.is_empty());
+
+    let (port, log) = fake_server(false).await;
+    set(pool, "email.enabled", "true").await;
+    set(pool, "email.smtp_host", "127.0.0.1").await;
+    let port_id = set(pool, "email.smtp_port", &port.to_string()).await;
+    set(pool, "email.smtp_security", "none").await;
+    set(pool, "email.from_address", "governance@example.org").await;
+    set(pool, "email.base_url", "https://gov.example/").await;
+
+    // Only Alice opted in and has an address
+    notifications::send(pool, "mention.text", "bob mentioned you", "/tor/1", &[alice, bob, carol]).await.unwrap();
+    assert_eq!(email_rows("pending").await.len(), 1);
+    assert_eq!(email::drain(pool).await, 1);
+    let sent = log.lock().unwrap().iter().find(|l| l.contains("Subject:")).cloned().unwrap();
+    assert!(sent.contains("Subject: [Ahlt] Mentions: bob mentioned you"));
+    let body = decode_body(&sent);
+    assert!(body.starts_with("Dear Alice,"));
+    assert!(body.contains("https://gov.example/tor/1"));
+    assert_eq!(email_rows("delivered").await.len(), 1);
+
+    // Authors hear about others' changes to their proposals, not their own
+    let prop = insert_entity(pool, "proposal", "budget", "Budget").await;
+    insert_prop(pool, prop, "submitted_by_id", &alice.to_string()).await;
+    proposal::transition(pool, prop, "submitted", None, alice).await.unwrap();
+    assert!(email_rows("pending").await.is_empty());
+    proposal::transition(pool, prop, "under_review", None, bob).await.unwrap();
+    let queued = email_rows("pending").await;
+    assert_eq!(queued.len(), 1);
+    assert!(queued[0].payload.contains("under review"));
+
+    // The mail server is down: the message stays queued for a retry
+    setting::update_value(pool, port_id, "1").await.unwrap();
+    assert_eq!(email::drain(pool).await, 0);
+    let retry = &email_rows("pending").await[0];
+    assert_eq!(retry.attempts, 1);
+    assert!(retry.last_error.starts_with("Cannot connect"), "{}", retry.last_error);
+
+    // Live pushes never pick up e-mail
+    assert!(outbox::drain(pool, &new_connection_map()).await >= 1);
+    assert_eq!(email_rows("pending").await.len(), 1);
+}
@@ -0,0 +1,72 @@
+//! Email template tests — covers placeholders, validation and locale fallback.
+//!
+//! - `{{name}}` placeholders are substituted; unknown ones are left visible
+//! - Overrides must use a valid locale and only the email's placeholders
+//! - Rendering falls back from region to language to `en` to the built-in
+
+mod common;
+
+use std::collections::HashMap;
+
+use ahlt::models::email_template;
+use common::*;
+
+#[test]
+fn test_substitution_and_validation() {
+    let vars: HashMap<String, String> = [("meeting.date", "2026-03-12"), ("tor.name", "Board")]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+    assert_eq!(
+        email_template::substitute("{{tor.name}} on {{ meeting.date }}, {{unknown}} {{", &vars),
+        "Board on 2026-03-12, {{unknown}} {{"
+    );
+    assert_eq!(email_template::placeholders("{{a}} {{ b }} {{a}}"), vec!["a", "b"]);
+
+    assert!(email_template::is_valid_locale("nb-NO"));
+    assert!(!email_template::is_valid_locale("NB"));
+    assert!(!email_template::is_valid_locale("nb-no-x"));
+
+    let invitation = email_template::find_built_in("meeting_invitation").unwrap();
+    assert!(email_template::validate(invitation, "en", invitation.subject, invitation.body).is_empty());
+    let errors = email_template::validate(invitation, "norsk", " ", "Hi {{report.title}}");
+    assert_eq!(errors, vec![
+        "Locale must be a language code such as 'en' or 'nb-NO'",
+        "Subject is required",
+        "Unknown placeholder {{report.title}}",
+    ]);
+
+    // Every built-in only uses its own placeholders
+    for b in email_template::BUILT_INS {
+        assert!(email_template::validate(b, "en", b.subject, b.body).is_empty(), "{}", b.kind);
+    }
+}
+
+#[tokio::test]
+async fn test_locale_fallback_and_reset() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let reminder = email_template::find_built_in("meeting_reminder").unwrap();
+    let vars = reminder.sample();
+
+    let built_in = email_template::render(pool, "meeting_reminder", "nb-NO", &vars).await.unwrap().unwrap();
+    assert_eq!(built_in.subject, "Reminder: Finance Board meets on 2026-03-12");
+    assert_eq!(built_in.locale, "");
+    assert!(email_template::render(pool, "no_such_email", "en", &vars).await.unwrap().is_none());
+
+    email_template::save(pool, "meeting_reminder", "en", "Soon: {{tor.name}}", "Body").await.unwrap();
+    email_template::save(pool, "meeting_reminder", "nb", "Påminnelse: {{tor.name}}", "Hei").await.unwrap();
+    let rendered = email_template::render(pool, "meeting_reminder", "nb-NO", &vars).await.unwrap().unwrap();
+    assert_eq!((rendered.subject.as_str(), rendered.locale.as_str()), ("Påminnelse: Finance Board", "nb"));
+    let rendered = email_template::render(pool, "meeting_reminder", "sv", &vars).await.unwrap().unwrap();
+    assert_eq!((rendered.subject.as_str(), rendered.locale.as_str()), ("Soon: Finance Board", "en"));
+
+    // Saving again replaces the text rather than adding a variant
+    email_template::save(pool, "meeting_reminder", "nb", "Møte: {{tor.name}}", "Hei").await.unwrap();
+    assert_eq!(email_template::find_all(pool).await.unwrap().len(), 2);
+
+    assert!(email_template::reset(pool, "meeting_reminder", "nb").await.unwrap());
+    assert!(!email_template::reset(pool, "meeting_reminder", "nb").await.unwrap());
+    let rendered = email_template::render(pool, "meeting_reminder", "nb-NO", &vars).await.unwrap().unwrap();
+    assert_eq!(rendered.locale, "en");
+}
@@ -0,0 +1,74 @@
+//! Export checksum tests — covers manifests, records and archives.
+//!
+//! - Manifests use the `sha256sum` format and parse back
+//! - The same file is recorded once and can be looked up by digest
+//! - Meeting packs and audit archives ship a SHA256SUMS of their files
+
+mod common;
+
+use std::io::Read;
+
+use ahlt::models::export_record;
+use ahlt::models::{document, meeting, relation};
+use common::*;
+
+#[tokio::test]
+async fn test_manifest_and_records() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let alice = insert_entity(pool, "user", "alice", "Alice").await;
+
+    let sha = export_record::sha256_hex(b"abc");
+    assert_eq!(sha, "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+    let manifest = export_record::manifest(&[("a.csv", &sha)]);
+    assert_eq!(manifest, format!("{sha}  a.csv\n"));
+    assert_eq!(
+        export_record::parse_manifest(&format!("{}\n{} *b.zip\nnot a line\n", manifest.trim(), sha.to_uppercase())),
+        vec![("a.csv".to_string(), sha.clone()), ("b.zip".to_string(), sha.clone())]
+    );
+
+    let first = export_record::record(pool, "user_list", "users.csv", b"abc", alice).await.unwrap();
+    let again = export_record::record(pool, "user_list", "users.csv", b"abc", alice).await.unwrap();
+    assert_eq!(first.id, again.id);
+    assert_eq!((first.sha256.as_str(), first.size_bytes, first.generated_by_name.as_str()), (sha.as_str(), 3, "Alice"));
+    assert_eq!(first.digest_header(), "sha-256=ungWv48Bz+pBQUDeXa4iI7ADYaOWF3qctBD/YfIAFa0=");
+    assert_eq!(first.manifest(), manifest.replace("a.csv", "users.csv"));
+
+    export_record::record(pool, "user_list", "copy.csv", b"abc", alice).await.unwrap();
+    let matches = export_record::find_by_sha256(pool, &sha.to_uppercase()).await.unwrap();
+    assert_eq!(matches.iter().map(|r| r.file_name.as_str()).collect::<Vec<_>>(), vec!["users.csv", "copy.csv"]);
+    assert!(export_record::find_by_sha256(pool, &export_record::sha256_hex(b"abd")).await.unwrap().is_empty());
+    assert_eq!(export_record::find_recent(pool, 10).await.unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_meeting_pack_manifest() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let board = insert_entity(pool, "tor", "board", "Board").await;
+    let meeting_id = meeting::create(pool, board, "2026-03-12", "Board", "Room 4", "", "", "", "", "", "").await.unwrap();
+    let proposal = insert_entity(pool, "proposal", "budget", "Budget").await;
+    let point = insert_entity(pool, "agenda_point", "budget_point", "Budget 2027").await;
+    let doc = document::create(pool, "Budget paper", "ad_hoc", "Numbers", 0, None).await.unwrap();
+    relation::create(pool, "spawns_agenda_point", proposal, point).await.unwrap();
+    meeting::assign_agenda(pool, meeting_id, point).await.unwrap();
+    relation::create(pool, "attached_to_proposal", doc, proposal).await.unwrap();
+
+    let detail = meeting::find_by_id(pool, meeting_id).await.unwrap().unwrap();
+    let bytes = meeting::pack::build_pack(pool, &detail).await.unwrap();
+    let folder = meeting::pack::file_name(&detail).trim_end_matches(".zip").to_string();
+    let mut archive = zip::ZipArchive::new(std::io::Cursor::new(bytes)).unwrap();
+    let mut names: Vec<String> = archive.file_names().map(|n| n.trim_start_matches(&format!("{folder}/")).to_string()).collect();
+    names.sort();
+    assert_eq!(names, vec!["SHA256SUMS", "agenda.txt", "documents/01-budget-paper.txt"]);
+
+    let mut sums = String::new();
+    archive.by_name(&format!("{folder}/SHA256SUMS")).unwrap().read_to_string(&mut sums).unwrap();
+    let sums = export_record::parse_manifest(&sums);
+    assert_eq!(sums.len(), 2);
+    for (name, sha) in sums {
+        let mut contents = Vec::new();
+        archive.by_name(&format!("{folder}/{name}")).unwrap().read_to_end(&mut contents).unwrap();
+        assert_eq!(export_record::sha256_hex(&contents), sha, "{name}");
+    }
+}
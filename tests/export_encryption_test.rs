@@ -0,0 +1,85 @@
+//! Export encryption tests — covers passphrase and public-key encryption.
+//!
+//! - A passphrase-encrypted export round-trips and rejects a wrong passphrase
+//! - Tampered or truncated files fail to decrypt
+//! - A key-encrypted export opens only with the matching private key
+
+mod common;
+
+use ahlt::models::data_manager::{encryption, export};
+use ahlt::models::data_manager::encryption::{EncryptionError, Recipient, Secret};
+use common::*;
+
+#[tokio::test]
+async fn test_passphrase_round_trip() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    insert_entity(pool, "user", "alice", "Alice").await;
+
+    let data = export::export_entities(pool, Some(&["user".to_string()])).await.unwrap();
+    let plaintext = serde_json::to_vec(&data).unwrap();
+
+    assert_eq!(
+        encryption::encrypt(&plaintext, Recipient::Passphrase("too short")).unwrap_err(),
+        EncryptionError::WeakPassphrase
+    );
+    let sealed = encryption::encrypt(&plaintext, Recipient::Passphrase("correct horse battery")).unwrap();
+    assert!(encryption::is_encrypted(&sealed));
+    assert!(!encryption::is_encrypted(&plaintext));
+    assert!(!sealed.windows(5).any(|w| w == b"alice"));
+    // Fresh salt and nonce every time
+    assert_ne!(sealed, encryption::encrypt(&plaintext, Recipient::Passphrase("correct horse battery")).unwrap());
+
+    let opened = encryption::decrypt(&sealed, Secret::Passphrase("correct horse battery")).unwrap();
+    assert_eq!(opened, plaintext);
+
+    assert_eq!(
+        encryption::decrypt(&sealed, Secret::Passphrase("wrong horse battery")).unwrap_err(),
+        EncryptionError::Failed
+    );
+    let mut tampered = sealed.clone();
+    *tampered.last_mut().unwrap() ^= 1;
+    assert_eq!(
+        encryption::decrypt(&tampered, Secret::Passphrase("correct horse battery")).unwrap_err(),
+        EncryptionError::Failed
+    );
+    assert_eq!(
+        encryption::decrypt(&sealed[..20], Secret::Passphrase("correct horse battery")).unwrap_err(),
+        EncryptionError::Malformed
+    );
+    assert_eq!(
+        encryption::decrypt(&plaintext, Secret::Passphrase("correct horse battery")).unwrap_err(),
+        EncryptionError::Malformed
+    );
+}
+
+#[tokio::test]
+async fn test_public_key_round_trip() {
+    let (private_key, public_key) = encryption::generate_key_pair();
+    let (other_private, _) = encryption::generate_key_pair();
+    assert_eq!(private_key.len(), 44);
+    assert_eq!(encryption::fingerprint(&public_key).unwrap().len(), 16);
+    assert!(encryption::fingerprint("not a key").is_none());
+
+    let sealed = encryption::encrypt(b"INSERT INTO entities ...", Recipient::PublicKey(&public_key)).unwrap();
+    assert_eq!(
+        encryption::decrypt(&sealed, Secret::PrivateKey(&private_key)).unwrap(),
+        b"INSERT INTO entities ..."
+    );
+    assert_eq!(
+        encryption::decrypt(&sealed, Secret::PrivateKey(&other_private)).unwrap_err(),
+        EncryptionError::Failed
+    );
+    assert_eq!(
+        encryption::decrypt(&sealed, Secret::Passphrase("correct horse battery")).unwrap_err(),
+        EncryptionError::WrongMode
+    );
+    assert_eq!(
+        encryption::decrypt(&sealed, Secret::PrivateKey("short")).unwrap_err(),
+        EncryptionError::InvalidKey
+    );
+    assert_eq!(
+        encryption::encrypt(b"x", Recipient::PublicKey("")).unwrap_err(),
+        EncryptionError::InvalidKey
+    );
+}
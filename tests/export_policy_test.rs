@@ -0,0 +1,91 @@
+//! Export policy tests — covers role restrictions and watermarks on exports.
+//!
+//! - An empty allow-list lets everyone export; a set one needs a listed role
+//! - Watermarks name the user, time and deployment, and are off by default
+//! - CSVs get a final watermark row; PDFs carry it on the page and in XMP
+
+mod common;
+
+use ahlt::export::pdf;
+use ahlt::models::export_policy::{self, Watermark};
+use ahlt::models::{minutes::Minutes, relation};
+use ahlt::warnings::clock;
+use chrono::{TimeZone, Utc};
+use common::*;
+use sqlx::PgPool;
+
+async fn set(pool: &PgPool, name: &str, value: &str) {
+    let id = insert_entity(pool, "setting", name, name).await;
+    insert_prop(pool, id, "value", value).await;
+}
+
+#[test]
+fn test_role_allow_list() {
+    let allowed = export_policy::parse_roles(" Admin, records_officer ,,");
+    assert_eq!(allowed, vec!["admin", "records_officer"]);
+    assert!(export_policy::role_allowed(&allowed, "viewer,admin"));
+    assert!(!export_policy::role_allowed(&allowed, "viewer"));
+    assert!(!export_policy::role_allowed(&allowed, ""));
+    assert!(export_policy::role_allowed(&[], "viewer"));
+}
+
+#[test]
+fn test_csv_stamp() {
+    let mark = Watermark {
+        deployment: "Board \"Prod\"".to_string(),
+        exported_by: "Alice (alice)".to_string(),
+        exported_at: "2026-03-11 09:00 UTC".to_string(),
+    };
+    let csv = mark.stamp_csv("id,name\n1,Alpha".to_string());
+    assert_eq!(csv, "id,name\n1,Alpha\n\"# Board \"\"Prod\"\" — exported by Alice (alice) on 2026-03-11 09:00 UTC\"\n");
+}
+
+#[tokio::test]
+async fn test_restrictions_and_watermark() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let alice = insert_entity(pool, "user", "alice", "Alice").await;
+    let bob = insert_entity(pool, "user", "bob", "Bob").await;
+    let officer = insert_entity(pool, "role", "records_officer", "Records Officer").await;
+    relation::create(pool, "has_role", alice, officer).await.unwrap();
+
+    assert!(export_policy::may_export(pool, bob).await.unwrap());
+    assert_eq!(export_policy::watermark(pool, alice).await.unwrap(), None);
+
+    set(pool, "exports.allowed_roles", "records_officer").await;
+    assert!(export_policy::may_export(pool, alice).await.unwrap());
+    assert!(!export_policy::may_export(pool, bob).await.unwrap());
+
+    set(pool, "exports.watermark", "true").await;
+    set(pool, "app.name", "Ahlt Test").await;
+    let at = Utc.with_ymd_and_hms(2026, 3, 11, 9, 0, 0).unwrap();
+    let mark = clock::with_fixed_now(at, export_policy::watermark(pool, alice)).await.unwrap().unwrap();
+    assert_eq!(mark.line(), "Ahlt Test — exported by Alice (alice) on 2026-03-11 09:00 UTC");
+
+    set(pool, "exports.watermark_text", "  Board secretariat — internal ").await;
+    let mark = export_policy::watermark(pool, alice).await.unwrap().unwrap();
+    assert_eq!(mark.deployment, "Board secretariat — internal");
+
+    // The PDF differs only by the stamp and records it in its metadata
+    let minutes = Minutes {
+        id: 1,
+        name: "minutes_1".to_string(),
+        label: "Minutes".to_string(),
+        status: "approved".to_string(),
+        generated_date: "2026-03-05".to_string(),
+        meeting_id: 1,
+        meeting_name: "board_2026_03".to_string(),
+        approved_by: String::new(),
+        approved_date: String::new(),
+        distribution_list: String::new(),
+        structured_attendance: String::new(),
+        structured_action_items: String::new(),
+        circulated_at: String::new(),
+    };
+    let plain = clock::with_fixed_now(at, async { pdf::render_minutes(&minutes, None, &[], None) }).await;
+    let stamped = clock::with_fixed_now(at, async { pdf::render_minutes(&minutes, None, &[], Some(&mark)) }).await;
+    assert_ne!(plain, stamped);
+    let doc = String::from_utf8_lossy(&stamped);
+    assert!(doc.contains("<ahlt:watermark>Board secretariat — internal — exported by Alice (alice) on"));
+    assert!(!String::from_utf8_lossy(&plain).contains("ahlt:watermark"));
+}
@@ -0,0 +1,96 @@
+//! Feed tests — covers Atom/RSS syndication of governance output.
+//!
+//! - The decision register renders as valid, escaped Atom and RSS entries
+//!   linking back into the application
+//! - Only approved minutes are published, scoped to their ToR
+//! - Public feeds are opt-in per ToR
+
+mod common;
+
+use ahlt::handlers::feed_handlers;
+use ahlt::models::{feed, meeting, minutes, opinion, relation, setting};
+use ahlt::models::feed::{FeedFormat, FeedKind};
+use common::*;
+
+const BASE: &str = "https://gov.example";
+
+/// Minutes for a new meeting of `tor`, with one written section.
+async fn minutes_for(pool: &sqlx::PgPool, tor: i64, date: &str, status: &str) -> i64 {
+    let mtg = meeting::create(pool, tor, date, "Board", "", "", "", "", "", "", "").await.unwrap();
+    let id = insert_entity(pool, "minutes", &format!("minutes-{date}"), &format!("Minutes {date}")).await;
+    insert_prop(pool, id, "status", status).await;
+    relation::create(pool, "minutes_of", mtg, id).await.unwrap();
+    let section = insert_entity(pool, "minutes_section", &format!("summary-{date}"), "Summary").await;
+    insert_prop(pool, section, "content", "Budget approved").await;
+    relation::create(pool, "section_of", section, id).await.unwrap();
+    id
+}
+
+#[tokio::test]
+async fn test_decision_feed_renders_atom_and_rss() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let alice = insert_entity(pool, "user", "alice", "Alice").await;
+    let tor = insert_entity(pool, "tor", "board", "Board").await;
+    let ap = insert_entity(pool, "agenda_point", "ap1", "Budget").await;
+    insert_prop(pool, ap, "title", "Budget <2027> & beyond").await;
+    relation::create(pool, "belongs_to_tor", ap, tor).await.unwrap();
+    let coa = insert_entity(pool, "coa", "coa1", "Option A").await;
+    let decision = opinion::record_decision(pool, ap, alice, coa, "Cheapest \"by far\"").await.unwrap();
+
+    let path = format!("/feeds/tor/{tor}/decisions.atom");
+    let built = feed::build(pool, FeedKind::Decisions, Some((tor, "Board")), BASE, &path).await.unwrap();
+    assert_eq!(built.title, "Decisions — Board");
+    assert_eq!(built.entries.len(), 1);
+    assert_eq!(built.updated, built.entries[0].updated);
+
+    let atom = built.render(FeedFormat::Atom);
+    assert!(atom.starts_with("<?xml"));
+    assert!(atom.contains(&format!("<link rel=\"self\" href=\"{BASE}{path}\"/>")));
+    assert!(atom.contains("<title>Board: Budget &lt;2027&gt; &amp; beyond</title>"));
+    assert!(atom.contains(&format!("<id>{BASE}/decisions/{decision}</id>")));
+    assert!(atom.contains(&format!("href=\"{BASE}/tor/{tor}/workflow/agenda/{ap}\"")));
+    assert!(atom.contains("Rationale: Cheapest &quot;by far&quot;"));
+    assert!(!atom.contains("<2027>"));
+
+    let rss = built.render(FeedFormat::Rss);
+    assert!(rss.contains("<rss version=\"2.0\""));
+    assert!(rss.contains(&format!("<guid isPermaLink=\"false\">{BASE}/decisions/{decision}</guid>")));
+    // RFC 2822 dates, e.g. "Fri, 16 Oct 2026 10:00:00 +0000"
+    let pub_date = rss.split("<pubDate>").nth(1).unwrap().split("</pubDate>").next().unwrap();
+    assert!(pub_date.ends_with("+0000"), "{pub_date}");
+
+    assert_eq!(FeedKind::from_key("minutes"), Some(FeedKind::Minutes));
+    assert_eq!(FeedFormat::from_extension("json"), None);
+    assert_eq!(feed::escape("a\u{0}b'"), "ab&apos;");
+}
+
+#[tokio::test]
+async fn test_minutes_feed_is_scoped_and_public_feeds_opt_in() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let board = insert_entity(pool, "tor", "board", "Board").await;
+    let audit = insert_entity(pool, "tor", "audit", "Audit").await;
+    let approved = minutes_for(pool, board, "2026-09-01", "approved").await;
+    minutes_for(pool, board, "2026-10-01", "draft").await;
+    let other = minutes_for(pool, audit, "2026-09-15", "approved").await;
+
+    let board_minutes = minutes::find_published(pool, Some(board), 10).await.unwrap();
+    assert_eq!(board_minutes.iter().map(|m| m.id).collect::<Vec<_>>(), vec![approved]);
+    assert_eq!(board_minutes[0].meeting_date, "2026-09-01");
+    assert!(board_minutes[0].updated_at.ends_with('Z'));
+    assert_eq!(minutes::find_published(pool, None, 10).await.unwrap().len(), 2);
+    assert_eq!(minutes::find_published(pool, None, 1).await.unwrap().len(), 1);
+
+    let built = feed::build(pool, FeedKind::Minutes, Some((board, "Board")), BASE, "/share/x/feed.rss").await.unwrap();
+    assert_eq!(built.entries.len(), 1);
+    assert_eq!(built.entries[0].link, format!("{BASE}/minutes/{approved}"));
+    assert_eq!(built.entries[0].summary, "Summary\nBudget approved");
+    assert!(!built.render(FeedFormat::Rss).contains(&format!("/minutes/{other}")));
+
+    // Off unless the ToR opts in
+    assert!(!feed_handlers::is_public(pool, board).await);
+    setting::set_tor_override(pool, board, "feeds.public", "true").await.unwrap();
+    assert!(feed_handlers::is_public(pool, board).await);
+    assert!(!feed_handlers::is_public(pool, audit).await);
+}
@@ -0,0 +1,117 @@
+//! Fiscal calendar tests — covers reporting periods that follow the fiscal year.
+//!
+//! - Fiscal years, quarters and their labels follow the configured start month
+//! - Settings load into the calendar; labels without `{q}` are refused
+//! - Quarterly subscriptions, the attendance report and proposal titles use it
+
+mod common;
+
+use ahlt::models::fiscal::{self, FiscalCalendar};
+use ahlt::models::report_definition::{self, ReportDefinition};
+use ahlt::models::{proposal, relation, report_subscription, setting};
+use chrono::NaiveDate;
+use common::*;
+use sqlx::PgPool;
+
+fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(y, m, d).unwrap()
+}
+
+fn july() -> FiscalCalendar {
+    FiscalCalendar { start_month: 7, quarter_label: "FY{fy} Q{q}".to_string() }
+}
+
+async fn set(pool: &PgPool, name: &str, value: &str) {
+    let id = insert_entity(pool, "setting", name, name).await;
+    insert_prop(pool, id, "value", value).await;
+}
+
+#[test]
+fn test_quarters_follow_start_month() {
+    let calendar = FiscalCalendar::default();
+    assert_eq!(calendar.quarter(date(2026, 8, 3)), 3);
+    assert_eq!(calendar.label(date(2026, 8, 3)), "2026 Q3");
+    assert_eq!(calendar.quarter_start(date(2026, 2, 14), 1), date(2025, 10, 1));
+
+    let fy = july();
+    assert_eq!(fy.year_start(date(2026, 6, 30)), date(2025, 7, 1));
+    assert_eq!(fy.year_start(date(2026, 7, 1)), date(2026, 7, 1));
+    assert_eq!(fy.fiscal_year(date(2026, 7, 1)), 2027);
+    assert_eq!(fy.quarter(date(2026, 7, 1)), 1);
+    assert_eq!(fy.quarter(date(2026, 6, 30)), 4);
+    assert_eq!(fy.label(date(2027, 1, 15)), "FY2027 Q3");
+    assert_eq!(fy.quarter_start(date(2026, 8, 3), 0), date(2026, 7, 1));
+    assert_eq!(fy.quarter_start(date(2026, 8, 3), 2), date(2026, 1, 1));
+    assert_eq!(fy.quarter_start(date(2026, 8, 3), -1), date(2026, 10, 1));
+
+    assert!(setting::validate("fiscal.quarter_label", "text", "{start_year}/{fy} quarter {q}").is_ok());
+    assert!(setting::validate("fiscal.quarter_label", "text", "FY{fy}").is_err());
+    assert!(setting::validate("fiscal.year_start_month", "number", "13").is_err());
+}
+
+#[tokio::test]
+async fn test_load_and_schedule() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    assert_eq!(FiscalCalendar::load(pool).await, FiscalCalendar::default());
+
+    set(pool, "fiscal.year_start_month", "7").await;
+    set(pool, "fiscal.quarter_label", "FY{fy} Q{q}").await;
+    assert_eq!(FiscalCalendar::load(pool).await, july());
+    sqlx::query("UPDATE entity_properties SET value = 'FY{fy}' WHERE key = 'value' AND entity_id = \
+                 (SELECT id FROM entities WHERE name = 'fiscal.quarter_label')")
+        .execute(pool)
+        .await
+        .unwrap();
+    assert!(fiscal::validate_label(&FiscalCalendar::load(pool).await.quarter_label).is_ok());
+
+    let fy = july();
+    assert_eq!(report_subscription::next_after("quarterly", date(2026, 8, 3), &fy), date(2026, 10, 1));
+    assert_eq!(
+        report_subscription::period_before("quarterly", date(2026, 10, 1), &fy),
+        (date(2026, 7, 1), date(2026, 10, 1))
+    );
+    assert_eq!(
+        proposal::expand_title("Budget {fiscal_year} {quarter}", "Board", date(2026, 8, 3), &fy),
+        "Budget 2027 Q1"
+    );
+}
+
+#[tokio::test]
+async fn test_attendance_report_groups_by_fiscal_quarter() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    set(pool, "fiscal.year_start_month", "7").await;
+    set(pool, "fiscal.quarter_label", "FY{fy} Q{q}").await;
+    let board = insert_entity(pool, "tor", "board", "Board").await;
+    for (name, day, status) in [("m1", "2026-06-20", "present"), ("m2", "2026-07-10", "absent"), ("m3", "2026-09-01", "present")] {
+        let meeting = insert_entity(pool, "meeting", name, name).await;
+        insert_prop(pool, meeting, "meeting_date", day).await;
+        relation::create(pool, "belongs_to_tor", meeting, board).await.unwrap();
+        let minutes = insert_entity(pool, "minutes", &format!("{name}_minutes"), name).await;
+        insert_prop(pool, minutes, "structured_attendance", &format!(r#"[{{"name":"Alice","status":"{status}"}}]"#)).await;
+        relation::create(pool, "minutes_of", meeting, minutes).await.unwrap();
+    }
+
+    let def = ReportDefinition {
+        id: 0,
+        label: "Attendance".to_string(),
+        is_active: true,
+        report: "meeting_attendance".to_string(),
+        parameter: 2,
+        tor_id: Some(board),
+        tor_label: "Board".to_string(),
+        schedule: String::new(),
+        format: "csv".to_string(),
+        group_key: String::new(),
+        delivery: "inbox".to_string(),
+        next_run_at: String::new(),
+        last_run_at: String::new(),
+    };
+    let (table, period) = report_definition::build_report(pool, &def, date(2026, 9, 15)).await.unwrap();
+    assert_eq!(period, (date(2026, 4, 1), date(2026, 9, 16)));
+    let quarters: Vec<(&str, &str, &str)> = table.rows.iter()
+        .map(|r| (r[1].as_str(), r[2].as_str(), r[6].as_str()))
+        .collect();
+    assert_eq!(quarters, vec![("FY2026 Q4", "1", "100%"), ("FY2027 Q1", "2", "50%")]);
+}
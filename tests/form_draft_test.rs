@@ -0,0 +1,73 @@
+//! Form draft tests — covers server-side snapshots of forms being edited.
+//!
+//! - Snapshots hold only text fields, never the CSRF token, and link back to
+//!   a local page
+//! - One snapshot per user and form, replaced on each save, removed on
+//!   discard, cleanup, or when the user is deleted
+
+mod common;
+
+use ahlt::models::form_draft;
+use common::*;
+use serde_json::json;
+
+fn fields(value: serde_json::Value) -> serde_json::Map<String, serde_json::Value> {
+    value.as_object().unwrap().clone()
+}
+
+#[test]
+fn test_validate_snapshot() {
+    let kept = form_draft::validate(
+        "/tor/1/proposals/new",
+        "/tor/1/proposals/new",
+        &fields(json!({ "csrf_token": "secret", "title": "Budget", "tags": ["a", "b"] })),
+    )
+    .unwrap();
+    assert_eq!(kept, json!({ "title": "Budget", "tags": ["a", "b"] }));
+
+    let ok = fields(json!({ "title": "Budget" }));
+    assert!(form_draft::validate("https://evil.example/x", "/tor/1", &ok).is_err());
+    assert!(form_draft::validate("/tor/1", "//evil.example", &ok).is_err());
+    assert!(form_draft::validate("/tor/1", "/login", &ok).is_err());
+    assert_eq!(
+        form_draft::validate("/tor/1", "/tor/1", &fields(json!({ "count": 3 }))).unwrap_err(),
+        "Field 'count' must be text"
+    );
+    let huge = fields(json!({ "description": "x".repeat(form_draft::MAX_FIELDS_BYTES) }));
+    assert!(form_draft::validate("/tor/1", "/tor/1", &huge).is_err());
+}
+
+#[tokio::test]
+async fn test_save_find_and_discard() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let alice = insert_entity(pool, "user", "alice", "Alice").await;
+    let bob = insert_entity(pool, "user", "bob", "Bob").await;
+    let key = "/minutes/7/sections/9";
+
+    form_draft::save(pool, alice, key, "/minutes/7", "Minutes — Summary", &json!({ "content": "First" })).await.unwrap();
+    form_draft::save(pool, alice, key, "/minutes/7", "Minutes — Summary", &json!({ "content": "Second" })).await.unwrap();
+    form_draft::save(pool, alice, "/tor/1/proposals/new", "/tor/1/proposals/new", "", &json!({})).await.unwrap();
+
+    let draft = form_draft::find(pool, alice, key).await.unwrap().unwrap();
+    assert_eq!(serde_json::from_str::<serde_json::Value>(&draft.fields).unwrap(), json!({ "content": "Second" }));
+    assert_eq!((draft.page_path.as_str(), draft.title.as_str()), ("/minutes/7", "Minutes — Summary"));
+    assert_eq!(form_draft::find_for_user(pool, alice).await.unwrap().len(), 2);
+    // Snapshots are private to their user
+    assert!(form_draft::find(pool, bob, key).await.unwrap().is_none());
+    assert!(!form_draft::discard(pool, bob, key).await.unwrap());
+
+    assert!(form_draft::discard(pool, alice, key).await.unwrap());
+    assert!(form_draft::find(pool, alice, key).await.unwrap().is_none());
+
+    // Abandoned snapshots are cleaned up
+    let future = chrono::Utc::now() + chrono::Duration::minutes(1);
+    form_draft::save(pool, bob, key, "/minutes/7", "", &json!({ "content": "Bob" })).await.unwrap();
+    assert_eq!(form_draft::cleanup(pool, chrono::Utc::now() - chrono::Duration::days(1)).await.unwrap(), 0);
+    assert_eq!(form_draft::cleanup(pool, future).await.unwrap(), 2);
+
+    // Deleting the user removes their snapshots
+    form_draft::save(pool, alice, key, "/minutes/7", "", &json!({})).await.unwrap();
+    sqlx::query("DELETE FROM entities WHERE id = $1").bind(alice).execute(pool).await.unwrap();
+    assert!(form_draft::find_for_user(pool, alice).await.unwrap().is_empty());
+}
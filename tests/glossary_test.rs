@@ -0,0 +1,97 @@
+//! Glossary tests — covers global and per-ToR terms.
+//!
+//! - A ToR's own definition replaces the global one; other ToRs keep it
+//! - Terms are marked as whole words, case-insensitively, in escaped HTML
+//! - Meeting packs carry the ToR's glossary
+
+mod common;
+
+use std::io::Read;
+
+use ahlt::models::{glossary, meeting};
+use common::*;
+
+fn term(term: &str, definition: &str) -> glossary::GlossaryTerm {
+    glossary::GlossaryTerm {
+        id: 0,
+        name: String::new(),
+        term: term.to_string(),
+        definition: definition.to_string(),
+        tor_id: None,
+    }
+}
+
+#[tokio::test]
+async fn test_tor_terms_replace_global_terms() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let board = insert_entity(pool, "tor", "board", "Board").await;
+    let audit = insert_entity(pool, "tor", "audit", "Audit").await;
+
+    glossary::create(pool, None, "Quorum", "Half the members plus one").await.unwrap();
+    glossary::create(pool, None, "CAPEX", "Capital expenditure").await.unwrap();
+    glossary::create(pool, Some(board), "quorum", "Two thirds of the voting members").await.unwrap();
+    glossary::create(pool, Some(board), "RFC", "Request for comment").await.unwrap();
+
+    let board_terms = glossary::find_for_tor(pool, board).await.unwrap();
+    let summary: Vec<(&str, &str)> = board_terms.iter().map(|t| (t.term.as_str(), t.definition.as_str())).collect();
+    assert_eq!(summary, vec![
+        ("CAPEX", "Capital expenditure"),
+        ("quorum", "Two thirds of the voting members"),
+        ("RFC", "Request for comment"),
+    ]);
+    let audit_terms = glossary::find_for_tor(pool, audit).await.unwrap();
+    assert_eq!(audit_terms.len(), 2);
+    assert!(audit_terms.iter().all(|t| t.is_global()));
+
+    // Duplicates are refused within one glossary only
+    let errors = glossary::validate(pool, Some(board), " RFC ", "Again", None).await.unwrap();
+    assert_eq!(errors, vec!["'RFC' is already defined in this glossary"]);
+    assert!(glossary::validate(pool, Some(audit), "RFC", "Review", None).await.unwrap().is_empty());
+    let rfc = board_terms.iter().find(|t| t.term == "RFC").unwrap();
+    assert!(glossary::validate(pool, Some(board), "rfc", "Renamed", Some(rfc.id)).await.unwrap().is_empty());
+    assert_eq!(glossary::validate(pool, None, "", "", None).await.unwrap().len(), 2);
+
+    glossary::update(pool, rfc, "RfC", "Request for change").await.unwrap();
+    let updated = glossary::find_by_id(pool, rfc.id).await.unwrap().unwrap();
+    assert_eq!((updated.term.as_str(), updated.definition.as_str(), updated.tor_id), ("RfC", "Request for change", Some(board)));
+}
+
+#[tokio::test]
+async fn test_annotate() {
+    let terms = vec![
+        term("Quorum", "Enough members <present>"),
+        term("voting member", "A member with a \"vote\""),
+        term("member", "Anyone on the ToR"),
+    ];
+    let html = glossary::annotate("No quorum & no voting members, one member. Quorums? <b>", &terms);
+    assert_eq!(
+        html,
+        "No <abbr class=\"glossary-term\" title=\"Enough members &lt;present&gt;\">quorum</abbr> &amp; no voting members, \
+         one <abbr class=\"glossary-term\" title=\"Anyone on the ToR\">member</abbr>. Quorums? &lt;b&gt;"
+    );
+    assert!(glossary::annotate("A voting member.", &terms).contains(">voting member</abbr>"));
+    assert_eq!(glossary::annotate("Plain text", &[]), "Plain text");
+    assert_eq!(glossary::annotate("Café quorum", &terms).matches("<abbr").count(), 1);
+}
+
+#[tokio::test]
+async fn test_meeting_pack_includes_glossary() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let board = insert_entity(pool, "tor", "board", "Board").await;
+    let meeting_id = meeting::create(pool, board, "2026-03-12", "Board", "Room 4", "", "", "", "", "", "").await.unwrap();
+    glossary::create(pool, Some(board), "Quorum", "Two thirds of the voting members").await.unwrap();
+
+    let detail = meeting::find_by_id(pool, meeting_id).await.unwrap().unwrap();
+    let bytes = meeting::pack::build_pack(pool, &detail).await.unwrap();
+    let folder = meeting::pack::file_name(&detail).trim_end_matches(".zip").to_string();
+    let mut archive = zip::ZipArchive::new(std::io::Cursor::new(bytes)).unwrap();
+
+    let mut text = String::new();
+    archive.by_name(&format!("{folder}/glossary.txt")).unwrap().read_to_string(&mut text).unwrap();
+    assert!(text.contains("Quorum\n    Two thirds of the voting members"), "{text}");
+    let mut agenda = String::new();
+    archive.by_name(&format!("{folder}/agenda.txt")).unwrap().read_to_string(&mut agenda).unwrap();
+    assert!(agenda.contains("glossary.txt"));
+}
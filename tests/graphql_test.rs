@@ -0,0 +1,137 @@
+//! GraphQL API tests — covers queries over the entity/relation graph.
+//!
+//! - Nested relation traversal: ToR → positions → users
+//! - Entity types follow the REST permissions; secrets are never returned
+//! - ToR-owned entities are limited to members, warnings to their recipients
+//! - Query depth is capped
+
+mod common;
+
+use ahlt::auth::session::Permissions;
+use ahlt::graphql;
+use ahlt::models::{entity, relation};
+use ahlt::warnings;
+use common::*;
+use serde_json::{json, Value};
+use sqlx::PgPool;
+
+async fn run(pool: &PgPool, permissions: &[&str], query: &str) -> (Value, usize) {
+    run_as(pool, 0, permissions, query).await
+}
+
+async fn run_as(pool: &PgPool, user_id: i64, permissions: &[&str], query: &str) -> (Value, usize) {
+    let permissions = Permissions(permissions.iter().map(|p| p.to_string()).collect());
+    let response = graphql::execute(pool, user_id, permissions, async_graphql::Request::new(query)).await;
+    (response.data.into_json().unwrap(), response.errors.len())
+}
+
+async fn board(pool: &PgPool) -> i64 {
+    let alice = insert_entity(pool, "user", "alice", "Alice").await;
+    insert_prop(pool, alice, "password", "$argon2id$secret").await;
+    insert_prop(pool, alice, "email", "alice@example.com").await;
+    let board = insert_entity(pool, "tor", "board", "Board").await;
+    let chair = insert_entity(pool, "tor_function", "board_chair", "Chair").await;
+    relation::create(pool, "belongs_to_tor", chair, board).await.unwrap();
+    relation::create(pool, "fills_position", alice, chair).await.unwrap();
+    board
+}
+
+const TRAVERSAL: &str = r#"{
+    entities(entityType: "tor") {
+        label
+        incoming(relation: "belongs_to_tor") {
+            label
+            incoming(relation: "fills_position") { name properties { key value } password: property(key: "password") }
+        }
+    }
+}"#;
+
+#[tokio::test]
+async fn test_nested_traversal() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let board = board(pool).await;
+
+    let (data, errors) = run(pool, &["entities.list"], TRAVERSAL).await;
+    assert_eq!(errors, 0);
+    assert_eq!(data, json!({ "entities": [{
+        "label": "Board",
+        "incoming": [{
+            "label": "Chair",
+            "incoming": [{
+                "name": "alice",
+                "properties": [{ "key": "email", "value": "alice@example.com" }],
+                "password": null
+            }]
+        }]
+    }]}));
+
+    let (data, _) = run(pool, &["entities.list"], &format!("{{ entity(id: {board}) {{ entityType name }} }}")).await;
+    assert_eq!(data, json!({ "entity": { "entityType": "tor", "name": "board" } }));
+}
+
+#[tokio::test]
+async fn test_permissions_and_limits() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let board = board(pool).await;
+
+    // ToR access alone stops at the users
+    let (data, _) = run(pool, &["tor.list"], TRAVERSAL).await;
+    assert_eq!(data["entities"][0]["incoming"][0]["incoming"], json!([]));
+    let (data, _) = run(pool, &["users.list"], TRAVERSAL).await;
+    assert_eq!(data["entities"], json!([]));
+    let (data, _) = run(pool, &["users.list"], &format!("{{ entity(id: {board}) {{ name }} }}")).await;
+    assert_eq!(data["entity"], Value::Null);
+    let (data, _) = run(pool, &["tor.list"], r#"{ entities(entityType: "relation_type") { name } }"#).await;
+    assert_eq!(data["entities"], json!([]));
+
+    let deep = format!(
+        "{{ entity(id: {board}) {{ {} name {} }} }}",
+        "incoming(relation: \"belongs_to_tor\") { outgoing(relation: \"belongs_to_tor\") {".repeat(6),
+        "} }".repeat(6),
+    );
+    let (_, errors) = run(pool, &["entities.list"], &deep).await;
+    assert_eq!(errors, 1);
+}
+
+#[tokio::test]
+async fn test_tor_and_recipient_scope() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let board = board(pool).await;
+    let alice = entity::find_by_type_and_name(pool, "user", "alice").await.unwrap().unwrap().id;
+    let bob = insert_entity(pool, "user", "bob", "Bob").await;
+    let meeting = insert_entity(pool, "meeting", "board_meeting", "Board Meeting").await;
+    relation::create(pool, "belongs_to_tor", meeting, board).await.unwrap();
+    let proposal = insert_entity(pool, "proposal", "board_proposal", "Board Proposal").await;
+    relation::create(pool, "submitted_to", proposal, board).await.unwrap();
+    let warning = warnings::create_warning(pool, "high", "security", "login.lockout", "Locked", "ip=10.0.0.1", "user")
+        .await
+        .unwrap();
+    warnings::create_receipts(pool, warning, &[alice]).await.unwrap();
+
+    let perms = ["meetings.view", "proposal.view", "warnings.view", "tor.list"];
+    let list = r#"{ meetings: entities(entityType: "meeting") { name }
+                    proposals: entities(entityType: "proposal") { name }
+                    warnings: entities(entityType: "warning") { label } }"#;
+
+    let (data, _) = run_as(pool, alice, &perms, list).await;
+    assert_eq!(data, json!({
+        "meetings": [{ "name": "board_meeting" }],
+        "proposals": [{ "name": "board_proposal" }],
+        "warnings": [{ "label": "Locked" }],
+    }));
+
+    // A non-member, non-recipient sees none of it, by list, id or relation
+    let (data, _) = run_as(pool, bob, &perms, list).await;
+    assert_eq!(data, json!({ "meetings": [], "proposals": [], "warnings": [] }));
+    for id in [meeting, proposal, warning] {
+        let (data, _) = run_as(pool, bob, &perms, &format!("{{ entity(id: {id}) {{ name }} }}")).await;
+        assert_eq!(data["entity"], Value::Null);
+    }
+    let (data, _) = run_as(pool, bob, &perms, &format!(
+        "{{ entity(id: {board}) {{ incoming(relation: \"belongs_to_tor\", entityType: \"meeting\") {{ name }} }} }}"
+    )).await;
+    assert_eq!(data["entity"]["incoming"], json!([]));
+}
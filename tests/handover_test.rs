@@ -0,0 +1,108 @@
+//! Secretariat handover tests — covers the package contents and archive.
+//!
+//! - Members are split from vacancies; only open work is listed
+//! - Only meetings still to be held are upcoming; unapproved minutes are pending
+//! - The archive holds a README, the JSON package and one CSV per list
+
+mod common;
+
+use std::io::Read;
+
+use ahlt::models::{agenda_point, meeting, minutes, proposal, protocol, relation, suggestion, tor};
+use common::*;
+
+async fn board(pool: &sqlx::PgPool) -> (i64, i64) {
+    let alice = insert_entity(pool, "user", "alice", "Alice").await;
+    let board = insert_entity(pool, "tor", "board", "Board").await;
+    insert_prop(pool, board, "meeting_cadence", "monthly").await;
+    let chair = insert_entity(pool, "tor_function", "board_chair", "Chair").await;
+    relation::create(pool, "belongs_to_tor", chair, board).await.unwrap();
+    tor::assign_to_position(pool, alice, chair, "mandatory").await.unwrap();
+    let secretary = insert_entity(pool, "tor_function", "board_secretary", "Secretary").await;
+    relation::create(pool, "belongs_to_tor", secretary, board).await.unwrap();
+    (board, alice)
+}
+
+#[tokio::test]
+async fn test_handover_package_contents() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let (board, alice) = board(pool).await;
+
+    let open = proposal::create(pool, board, "Fund the archive", "", "", alice, "2026-03-01", None).await.unwrap();
+    proposal::update_status(pool, open, "submitted", None).await.unwrap();
+    let closed = proposal::create(pool, board, "Old idea", "", "", alice, "2026-01-01", None).await.unwrap();
+    proposal::update_status(pool, closed, "rejected", Some("No budget")).await.unwrap();
+    suggestion::create(pool, board, "Meet online", alice, "2026-03-02").await.unwrap();
+    let decided = agenda_point::create(pool, board, "Budget", "", "decision", "2026-02-01", 10, alice, "", "normal", "").await.unwrap();
+    sqlx::query("UPDATE entity_properties SET value = 'decided' WHERE entity_id = $1 AND key = 'status'")
+        .bind(decided)
+        .execute(pool)
+        .await
+        .unwrap();
+    protocol::create_step(pool, board, "board_opening", "Opening", "procedural", 1, Some(5), "", true, "Chair").await.unwrap();
+
+    let past = meeting::create(pool, board, "2000-01-10", "board", "", "", "", "", "", "", "").await.unwrap();
+    meeting::update_status(pool, past, "completed").await.unwrap();
+    let held = minutes::generate_scaffold(pool, past, board, "Board January").await.unwrap();
+    minutes::update_structured_action_items(pool, held, r#"[
+        {"description": "Circulate budget", "responsible": "Alice", "due_date": "2000-02-01", "status": "open"},
+        {"description": "Book room", "responsible": "Alice", "due_date": "2000-01-20", "status": "done"}
+    ]"#).await.unwrap();
+    let later = meeting::create(pool, board, "2099-02-10", "board", "", "", "", "", "", "", "").await.unwrap();
+    let next = meeting::create(pool, board, "2099-01-10", "board", "Room 4", "", "", "", "", "", "").await.unwrap();
+    meeting::update_status(pool, later, "cancelled").await.unwrap();
+
+    let package = tor::build_handover(pool, board).await.unwrap();
+    assert_eq!(package.members.iter().map(|m| m.position_label.as_str()).collect::<Vec<_>>(), vec!["Chair"]);
+    assert_eq!(package.vacancies.iter().map(|m| m.position_label.as_str()).collect::<Vec<_>>(), vec!["Secretary"]);
+    assert_eq!(
+        package.open_items.iter().map(|i| (i.kind, i.title.as_str())).collect::<Vec<_>>(),
+        vec![("proposal", "Fund the archive"), ("suggestion", "Meet online"), ("action_item", "Circulate budget")]
+    );
+    assert_eq!(package.upcoming_meetings.iter().map(|m| m.id).collect::<Vec<_>>(), vec![next]);
+    assert_eq!(package.standing_items.len(), 1);
+    assert_eq!(package.pending_minutes.len(), 1);
+    assert_eq!(package.pending_minutes[0].meeting_id, past);
+    assert_eq!(package.configuration["schedule"]["meeting_cadence"], "monthly");
+    assert_eq!(package.configuration["decision_authority"]["mode"], "permission");
+
+    minutes::update_status(pool, held, "approved").await.unwrap();
+    assert!(tor::build_handover(pool, board).await.unwrap().pending_minutes.is_empty());
+}
+
+#[tokio::test]
+async fn test_handover_archive() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let (board, _) = board(pool).await;
+    assert!(tor::build_handover(pool, board + 1000).await.is_err());
+
+    let package = tor::build_handover(pool, board).await.unwrap();
+    assert!(package.file_name().starts_with("handover-board-"));
+    let bytes = package.to_archive().unwrap();
+    let mut archive = zip::ZipArchive::new(std::io::Cursor::new(bytes)).unwrap();
+    let mut names: Vec<String> = archive.file_names().map(|n| n.to_string()).collect();
+    names.sort();
+    assert_eq!(names, vec![
+        "handover-board/README.md",
+        "handover-board/SHA256SUMS",
+        "handover-board/configuration.json",
+        "handover-board/members.csv",
+        "handover-board/open_items.csv",
+        "handover-board/package.json",
+        "handover-board/pending_minutes.csv",
+        "handover-board/standing_items.csv",
+        "handover-board/upcoming_meetings.csv",
+    ]);
+
+    let mut members = String::new();
+    archive.by_name("handover-board/members.csv").unwrap().read_to_string(&mut members).unwrap();
+    assert_eq!(members, "position,membership_type,holder,holder_username\nChair,mandatory,Alice,alice\nSecretary,optional,,\n");
+
+    let mut json = String::new();
+    archive.by_name("handover-board/package.json").unwrap().read_to_string(&mut json).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(json["tor"]["label"], "Board");
+    assert_eq!(json["vacancies"][0]["position"], "Secretary");
+}
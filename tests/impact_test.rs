@@ -0,0 +1,77 @@
+//! Dependency impact tests — covers the downstream subgraph of a ToR.
+//!
+//! - Only ToRs reachable through feeds_into / escalates_to are affected
+//! - Each affected ToR gets its shortest distance and a blocked flag
+//! - Open proposals and agenda points are counted per ToR
+//! - Agenda points spawned from a proposal are listed until decided
+
+mod common;
+
+use ahlt::models::{relation, tor};
+use common::*;
+use sqlx::PgPool;
+
+async fn item(pool: &PgPool, entity_type: &str, name: &str, status: &str, rel: &str, tor_id: i64) -> i64 {
+    let id = insert_entity(pool, entity_type, name, name).await;
+    insert_prop(pool, id, "title", name).await;
+    insert_prop(pool, id, "status", status).await;
+    relation::create(pool, rel, id, tor_id).await.unwrap();
+    id
+}
+
+#[tokio::test]
+async fn test_downstream_subgraph_with_counts() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let mut ids = Vec::new();
+    for name in ["a", "b", "c", "d", "e"] {
+        ids.push(insert_entity(pool, "tor", name, &name.to_uppercase()).await);
+    }
+    let (a, b, c, d, e) = (ids[0], ids[1], ids[2], ids[3], ids[4]);
+
+    tor::add_dependency(pool, a, b, "feeds_into", "", "", true).await.unwrap();
+    tor::add_dependency(pool, b, c, "escalates_to", "", "", false).await.unwrap();
+    tor::add_dependency(pool, a, c, "feeds_into", "", "", false).await.unwrap();
+    tor::add_dependency(pool, d, a, "feeds_into", "", "", false).await.unwrap();
+    tor::add_dependency(pool, c, a, "escalates_to", "", "", false).await.unwrap();
+
+    item(pool, "proposal", "p1", "submitted", "submitted_to", b).await;
+    item(pool, "proposal", "p2", "under_review", "submitted_to", b).await;
+    item(pool, "proposal", "p3", "approved", "submitted_to", b).await;
+    item(pool, "agenda_point", "ap1", "scheduled", "belongs_to_tor", c).await;
+    item(pool, "agenda_point", "ap2", "decided", "belongs_to_tor", c).await;
+    item(pool, "proposal", "p4", "submitted", "submitted_to", e).await;
+
+    let analysis = tor::analyse_impact(pool, a).await.unwrap().unwrap();
+    assert_eq!(analysis.nodes[0].node.id, a);
+    assert_eq!(analysis.nodes[0].depth, 0);
+
+    let affected: Vec<(&str, usize, bool, i64, i64)> = analysis.affected().iter()
+        .map(|n| (n.node.label.as_str(), n.depth, n.blocked, n.open_proposals, n.open_agenda_points))
+        .collect();
+    assert_eq!(affected, vec![("B", 1, true, 2, 0), ("C", 1, false, 0, 1)]);
+    assert_eq!(analysis.blocked_count(), 1);
+    assert_eq!(analysis.affected_open_items(), 3);
+    // a→b, a→c, b→c, c→a; the upstream d→a is left out
+    assert_eq!(analysis.edges.len(), 4);
+    assert!(analysis.edges.iter().all(|edge| edge.source != d));
+
+    let leaf = tor::analyse_impact(pool, e).await.unwrap().unwrap();
+    assert!(leaf.affected().is_empty());
+    assert!(tor::analyse_impact(pool, 999_999).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_open_agenda_points_for_proposal() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let tor_id = insert_entity(pool, "tor", "ops", "Ops").await;
+    let proposal_id = item(pool, "proposal", "p1", "approved", "submitted_to", tor_id).await;
+    let open = item(pool, "agenda_point", "ap1", "scheduled", "belongs_to_tor", tor_id).await;
+    let done = item(pool, "agenda_point", "ap2", "voted", "belongs_to_tor", tor_id).await;
+    relation::create(pool, "spawns_agenda_point", proposal_id, open).await.unwrap();
+    relation::create(pool, "spawns_agenda_point", proposal_id, done).await.unwrap();
+
+    let items = tor::find_open_agenda_points_for_proposal(pool, proposal_id).await.unwrap();
+    assert_eq!(items, vec![(open, "ap1".to_string(), "scheduled".to_string())]);
+}
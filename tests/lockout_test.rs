@@ -0,0 +1,94 @@
+//! Account lockout tests — covers per-account counting of failed sign-ins.
+//!
+//! - Failures from any address count toward the threshold within the window
+//! - Reaching it locks the account and warns the user administrators once
+//! - A timed lockout ends by itself; one without a duration needs an unlock
+//! - A threshold of 0 turns lockout off
+
+mod common;
+
+use std::net::IpAddr;
+
+use ahlt::auth::lockout;
+use ahlt::models::{entity, relation, user};
+use common::*;
+use sqlx::PgPool;
+
+async fn set(pool: &PgPool, name: &str, value: &str) {
+    let id = insert_entity(pool, "setting", name, name).await;
+    insert_prop(pool, id, "value", value).await;
+}
+
+fn ip(s: &str) -> IpAddr {
+    s.parse().unwrap()
+}
+
+async fn lockout_warnings(pool: &PgPool) -> Vec<String> {
+    sqlx::query_scalar(
+        "SELECT p.value FROM entities e JOIN entity_properties p ON p.entity_id = e.id AND p.key = 'message' \
+         WHERE e.entity_type = 'warning' AND e.name LIKE 'event.security.account_lockout%' ORDER BY e.id",
+    )
+    .fetch_all(pool)
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_distributed_failures_lock_the_account() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let admin = insert_entity(pool, "user", "admin", "Admin").await;
+    let role = insert_entity(pool, "role", "admin", "Administrator").await;
+    let perm = insert_entity(pool, "permission", "users.edit", "Edit Users").await;
+    relation::create(pool, "has_role", admin, role).await.unwrap();
+    relation::create(pool, "has_permission", role, perm).await.unwrap();
+    let alice = insert_entity(pool, "user", "alice", "Alice").await;
+    set(pool, "login.lockout_threshold", "3").await;
+
+    assert!(!lockout::record_failure(pool, alice, "alice", ip("203.0.113.1")).await.unwrap());
+    assert!(!lockout::record_failure(pool, alice, "alice", ip("203.0.113.2")).await.unwrap());
+    assert!(!user::is_locked(pool, alice).await.unwrap());
+    assert!(lockout::record_failure(pool, alice, "alice", ip("198.51.100.7")).await.unwrap());
+    assert!(user::is_locked(pool, alice).await.unwrap());
+
+    // Failures while locked neither count nor warn again
+    assert!(!lockout::record_failure(pool, alice, "alice", ip("198.51.100.7")).await.unwrap());
+    assert_eq!(lockout_warnings(pool).await, vec![
+        "Account 'alice' locked for 30 minutes after 3 failed sign-ins from 3 address(es) within 15 minutes",
+    ]);
+    let reason = entity::get_property(pool, alice, "locked_reason").await.unwrap().unwrap();
+    assert_eq!(reason, "3 failed sign-ins from 3 address(es) within 15 minutes");
+
+    // The lockout ends by itself once its time is up
+    entity::set_property(pool, alice, "locked_until", "2020-01-01T00:00:00+00:00").await.unwrap();
+    assert!(!user::is_locked(pool, alice).await.unwrap());
+    assert!(entity::get_property(pool, alice, "locked_reason").await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_lock_until_unlocked_and_off() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let bob = insert_entity(pool, "user", "bob", "Bob").await;
+    set(pool, "login.lockout_threshold", "2").await;
+    set(pool, "login.lockout_minutes", "0").await;
+
+    lockout::record_failure(pool, bob, "bob", ip("10.0.0.1")).await.unwrap();
+    lockout::clear(pool, bob).await.unwrap();
+    assert!(!lockout::record_failure(pool, bob, "bob", ip("10.0.0.1")).await.unwrap());
+    assert!(lockout::record_failure(pool, bob, "bob", ip("10.0.0.1")).await.unwrap());
+    assert!(entity::get_property(pool, bob, "locked_until").await.unwrap().is_none());
+    assert!(user::is_locked(pool, bob).await.unwrap());
+    user::unlock(pool, bob).await.unwrap();
+    assert!(!user::is_locked(pool, bob).await.unwrap());
+
+    sqlx::query("UPDATE entity_properties SET value = '0' WHERE key = 'value' AND entity_id = \
+                 (SELECT id FROM entities WHERE name = 'login.lockout_threshold')")
+        .execute(pool)
+        .await
+        .unwrap();
+    for _ in 0..5 {
+        assert!(!lockout::record_failure(pool, bob, "bob", ip("10.0.0.1")).await.unwrap());
+    }
+    assert!(!user::is_locked(pool, bob).await.unwrap());
+}
@@ -0,0 +1,85 @@
+//! Meeting archive tests — covers the schema-versioned JSON record served
+//! at `/api/v1/meetings/{id}/archive`.
+//!
+//! - Agenda points carry their proposal, COAs, opinions and decision
+//! - Minutes sections, action items and attendance are included
+//! - Attendance falls back to the roll call when the minutes have none
+
+mod common;
+
+use ahlt::models::meeting::archive::{self, ARCHIVE_SCHEMA, ARCHIVE_SCHEMA_VERSION};
+use ahlt::models::{agenda_point, coa, meeting, minutes, opinion, proposal, relation};
+use ahlt::warnings::clock;
+use chrono::TimeZone;
+use common::*;
+
+#[tokio::test]
+async fn test_archive_record() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let alice = insert_entity(pool, "user", "alice", "Alice").await;
+    let board = insert_entity(pool, "tor", "board", "Board").await;
+
+    let meeting_id = meeting::create(pool, board, "2026-10-01", "Board", "Room 1", "", "", "", "", "alice", "")
+        .await.unwrap();
+    let budget = agenda_point::create(pool, board, "Budget 2027", "Annual budget", "decision", "2026-10-01", 30, alice, "", "", "")
+        .await.unwrap();
+    meeting::assign_agenda(pool, meeting_id, budget).await.unwrap();
+    let proposal_id = proposal::create(pool, board, "Adopt the budget", "d", "r", alice, "2026-09-01", None).await.unwrap();
+    relation::create(pool, "spawns_agenda_point", proposal_id, budget).await.unwrap();
+    let approve = coa::create(pool, "Approve", "", "complex", alice).await.unwrap();
+    coa::add_section(pool, approve, "Conditions", "Quarterly review", 1).await.unwrap();
+    relation::create(pool, "considers_coa", budget, approve).await.unwrap();
+    opinion::record_opinion(pool, budget, alice, approve, "Looks sound").await.unwrap();
+    let decision_id = opinion::record_decision(pool, budget, alice, approve, "Agreed by all").await.unwrap();
+
+    let minutes_id = minutes::generate_scaffold(pool, meeting_id, board, "Board October").await.unwrap();
+    minutes::update_structured_attendance(pool, minutes_id, r#"[{"name":"Alice","status":"present"}]"#).await.unwrap();
+    minutes::update_structured_action_items(pool, minutes_id,
+        r#"[{"description":"Publish budget","responsible":"Alice","due_date":"2026-11-01","status":"open"}]"#).await.unwrap();
+
+    let m = meeting::find_by_id(pool, meeting_id).await.unwrap().unwrap();
+    let at = chrono::Utc.with_ymd_and_hms(2026, 10, 16, 12, 0, 0).unwrap();
+    let record = clock::with_fixed_now(at, archive::build(pool, &m)).await.unwrap();
+
+    assert_eq!((record.schema, record.schema_version), (ARCHIVE_SCHEMA, ARCHIVE_SCHEMA_VERSION));
+    assert_eq!(record.generated_at, "2026-10-16T12:00:00Z");
+    assert_eq!((record.meeting.tor.id, record.meeting.location.as_str()), (board, "Room 1"));
+
+    assert_eq!(record.agenda.len(), 1);
+    let point = &record.agenda[0];
+    assert_eq!(point.proposal.as_ref().map(|p| p.id), Some(proposal_id));
+    assert_eq!(point.coas[0].sections[0].content, "Quarterly review");
+    assert_eq!((point.opinions[0].preferred_coa_id, point.opinions[0].commentary.as_str()), (approve, "Looks sound"));
+    let decision = point.decision.as_ref().unwrap();
+    assert_eq!((decision.id, decision.rationale.as_str()), (decision_id, "Agreed by all"));
+
+    assert_eq!(record.attendance.len(), 1);
+    assert_eq!(record.attendance[0].name, "Alice");
+    let min = record.minutes.as_ref().unwrap();
+    assert_eq!(min.id, minutes_id);
+    assert!(min.sections.iter().any(|s| s.section_type == "decisions"));
+    assert_eq!(min.action_items[0].description, "Publish budget");
+}
+
+#[tokio::test]
+async fn test_archive_without_minutes() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let board = insert_entity(pool, "tor", "board", "Board").await;
+    let meeting_id = meeting::create(pool, board, "2026-10-01", "Board", "", "", "", "", "", "", "").await.unwrap();
+    meeting::update_roll_call(pool, meeting_id, r#"[{"username":"bob","status":"excused"}]"#).await.unwrap();
+
+    let m = meeting::find_by_id(pool, meeting_id).await.unwrap().unwrap();
+    let record = archive::build(pool, &m).await.unwrap();
+    assert!(record.agenda.is_empty());
+    assert!(record.minutes.is_none());
+    assert_eq!((record.attendance[0].name.as_str(), record.attendance[0].status.as_str()), ("bob", "excused"));
+    assert_eq!(archive::file_name(&m), format!("meeting-archive-{}.json", m.name));
+
+    let json = serde_json::to_value(&record).unwrap();
+    let mut keys: Vec<&str> = json.as_object().unwrap().keys().map(String::as_str).collect();
+    keys.sort();
+    assert_eq!(keys, vec!["agenda", "attendance", "generated_at", "meeting", "minutes", "schema", "schema_version"]);
+    assert!(json["minutes"].is_null());
+}
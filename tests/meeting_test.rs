@@ -223,6 +223,131 @@ async fn test_find_unassigned_agenda_points() {
     assert_eq!(unassigned[0].id, agenda2);
 }
 
+#[tokio::test]
+async fn test_carry_over_undecided_agenda_points() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let (tor_id, _, _) = setup_tor_with_relation_types(pool).await;
+    let past = ahlt::models::meeting::create(pool, tor_id, "2026-04-01", "Test ToR", "", "", "", "", "", "", "").await.unwrap();
+    let next = ahlt::models::meeting::create(pool, tor_id, "2026-04-08", "Test ToR", "", "", "", "", "", "", "").await.unwrap();
+    ahlt::models::meeting::update_status(pool, past, "completed").await.unwrap();
+
+    let open = insert_entity(pool, "agenda_point", "agenda-open", "Open Decision").await;
+    insert_prop(pool, open, "item_type", "decision").await;
+    insert_prop(pool, open, "status", "presented").await;
+    let decided = insert_entity(pool, "agenda_point", "agenda-decided", "Decided Point").await;
+    insert_prop(pool, decided, "item_type", "decision").await;
+    insert_prop(pool, decided, "status", "decided").await;
+    let info = insert_entity(pool, "agenda_point", "agenda-info", "Briefing").await;
+    insert_prop(pool, info, "item_type", "informative").await;
+    for id in [open, decided, info] {
+        ahlt::models::meeting::assign_agenda(pool, past, id).await.unwrap();
+    }
+    let user_id = insert_entity(pool, "user", "member", "Member").await;
+    let coa_id = insert_entity(pool, "coa", "coa-a", "Option A").await;
+    ahlt::models::opinion::record_opinion(pool, open, user_id, coa_id, "Needs more data").await.unwrap();
+
+    let undecided: Vec<_> = ahlt::models::meeting::find_agenda_points(pool, past).await.unwrap()
+        .into_iter()
+        .filter(|p| p.is_undecided())
+        .collect();
+    assert_eq!(undecided.len(), 1);
+    assert_eq!(undecided[0].id, open);
+
+    let from = ahlt::models::meeting::find_by_id(pool, past).await.unwrap().unwrap();
+    let targets = ahlt::models::meeting::find_carry_over_targets(pool, tor_id, &from.meeting_date).await.unwrap();
+    assert_eq!(targets.len(), 1);
+    assert_eq!(targets[0].id, next);
+
+    let carried = ahlt::models::meeting::carry_over(pool, &from, &targets[0], &undecided).await.unwrap();
+    assert_eq!(carried.len(), 1);
+
+    // Scheduled for the next meeting, still listed on the original agenda
+    let next_points = ahlt::models::meeting::find_agenda_points(pool, next).await.unwrap();
+    assert_eq!(next_points.len(), 1);
+    assert_eq!(next_points[0].status, "scheduled");
+    assert_eq!(ahlt::models::meeting::find_agenda_points(pool, past).await.unwrap().len(), 3);
+
+    // Opinions stay with the point
+    let opinions = ahlt::models::opinion::find_opinions_for_agenda_point(pool, open).await.unwrap();
+    assert_eq!(opinions.len(), 1);
+
+    // Deferral recorded on the original meeting; carrying over again is a no-op
+    let from = ahlt::models::meeting::find_by_id(pool, past).await.unwrap().unwrap();
+    assert!(from.is_carried_over(open));
+    assert_eq!(from.carried_over_list()[0].to_meeting_id, next);
+    let again = ahlt::models::meeting::carry_over(pool, &from, &targets[0], &undecided).await.unwrap();
+    assert!(again.is_empty());
+}
+
+#[tokio::test]
+async fn test_upcoming_capacity_and_distribution_plan() {
+    use ahlt::models::meeting::capacity::{apply_assignments, find_upcoming_capacity, plan_schedule, QueueItem};
+
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let (tor_id, _, _) = setup_tor_with_relation_types(pool).await;
+    insert_prop(pool, tor_id, "cadence_duration_minutes", "60").await;
+    let held = ahlt::models::meeting::create(pool, tor_id, "2026-05-01", "Test ToR", "", "", "", "", "", "", "").await.unwrap();
+    ahlt::models::meeting::update_status(pool, held, "completed").await.unwrap();
+    let first = ahlt::models::meeting::create(pool, tor_id, "2026-05-08", "Test ToR", "", "", "", "", "", "", "").await.unwrap();
+    let second = ahlt::models::meeting::create(pool, tor_id, "2026-05-15", "Test ToR", "", "", "", "", "", "", "").await.unwrap();
+    insert_prop(pool, second, "duration_minutes", "90").await;
+
+    // 40 of the first meeting's 60 minutes are already booked
+    let booked = insert_entity(pool, "agenda_point", "agenda-booked", "Booked").await;
+    insert_prop(pool, booked, "time_allocation_minutes", "40").await;
+    ahlt::models::meeting::assign_agenda(pool, first, booked).await.unwrap();
+
+    let upcoming = find_upcoming_capacity(pool, tor_id, "2026-05-01").await.unwrap();
+    let ids: Vec<i64> = upcoming.iter().map(|m| m.meeting_id).collect();
+    assert_eq!(ids, vec![first, second]);
+    assert_eq!((upcoming[0].capacity_minutes, upcoming[0].allocated_minutes, upcoming[0].remaining()), (60, 40, 20));
+    assert_eq!((upcoming[1].capacity_minutes, upcoming[1].remaining()), (90, 90));
+
+    let item = |id: i64, minutes: i32| QueueItem { proposal_id: id, title: format!("P{}", id), minutes };
+    let plan = plan_schedule(upcoming.clone(), vec![item(1, 30), item(2, 15), item(3, 60), item(4, 45)]);
+    let placed = |i: usize| plan.meetings[i].items.iter().map(|it| it.proposal_id).collect::<Vec<_>>();
+    // 30 does not fit the 20 free minutes; 15 does; 60 and 45 compete for the second meeting
+    assert_eq!(placed(0), vec![2]);
+    assert_eq!(placed(1), vec![1, 3]);
+    assert_eq!(plan.unplaced.len(), 1);
+    assert_eq!(plan.unplaced[0].proposal_id, 4);
+    assert_eq!(plan.placed_count(), 3);
+
+    // Committing the preview works while it still fits, not once overbooked
+    assert!(apply_assignments(upcoming.clone(), vec![(item(2, 15), first), (item(1, 30), second)]).is_some());
+    assert!(apply_assignments(upcoming.clone(), vec![(item(1, 30), first)]).is_none());
+    assert!(apply_assignments(upcoming, vec![(item(1, 30), held)]).is_none());
+}
+
+#[tokio::test]
+async fn test_carry_over_annotates_minutes() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let (tor_id, _, _) = setup_tor_with_relation_types(pool).await;
+    let past = ahlt::models::meeting::create(pool, tor_id, "2026-04-01", "Test ToR", "", "", "", "", "", "", "").await.unwrap();
+    let next = ahlt::models::meeting::create(pool, tor_id, "2026-04-08", "Test ToR", "", "", "", "", "", "", "").await.unwrap();
+    let open = insert_entity(pool, "agenda_point", "agenda-open", "Open Decision").await;
+    insert_prop(pool, open, "item_type", "decision").await;
+    ahlt::models::meeting::assign_agenda(pool, past, open).await.unwrap();
+
+    let minutes_id = ahlt::models::minutes::generate_scaffold(pool, past, tor_id, "Test ToR 2026-04-01").await.unwrap();
+
+    let from = ahlt::models::meeting::find_by_id(pool, past).await.unwrap().unwrap();
+    let targets = ahlt::models::meeting::find_carry_over_targets(pool, tor_id, &from.meeting_date).await.unwrap();
+    assert_eq!(targets[0].id, next);
+    let points = ahlt::models::meeting::find_agenda_points(pool, past).await.unwrap();
+    let carried = ahlt::models::meeting::carry_over(pool, &from, &targets[0], &points).await.unwrap();
+    ahlt::models::minutes::annotate_deferrals(pool, minutes_id, &carried, 0).await.unwrap();
+
+    let sections = ahlt::models::minutes::find_sections(pool, minutes_id).await.unwrap();
+    let decisions = sections.iter().find(|s| s.section_type == "decisions").unwrap();
+    assert!(decisions.content.contains("Open Decision"));
+    assert!(decisions.content.contains(&targets[0].label));
+    assert!(!decisions.content.contains("No decisions recorded."));
+}
+
 
 // --- Calendar Confirm Handler Tests ---
 
@@ -0,0 +1,151 @@
+//! Membership history tests — covers point-in-time membership resolution.
+//!
+//! - Dates resolve to the archived term covering them, later dates to the current membership
+//! - Minutes attendance lists the holders on the meeting date
+//! - The decision register names the position held when the decision was made
+//! - Assigning, vacating or revoking a single position archives the membership before the change
+
+mod common;
+
+use ahlt::models::{entity, minutes, opinion, recertification, relation, tor::{self, NewHolder, PositionRotation}};
+use chrono::NaiveDate;
+use common::*;
+use sqlx::PgPool;
+
+struct Board {
+    tor_id: i64,
+    alice: i64,
+    bob: i64,
+    chair: i64,
+}
+
+/// A board whose Chair passed from Alice to Bob on 2026-03-01.
+async fn rotated_board(pool: &PgPool) -> Board {
+    let tor_id = insert_entity(pool, "tor", "board", "Board").await;
+    let alice = insert_entity(pool, "user", "alice", "Alice").await;
+    let bob = insert_entity(pool, "user", "bob", "Bob").await;
+    let chair = insert_entity(pool, "tor_function", "chair", "Chair").await;
+    relation::create(pool, "belongs_to_tor", chair, tor_id).await.unwrap();
+    tor::assign_to_position(pool, alice, chair, "mandatory").await.unwrap();
+
+    let rotation = PositionRotation { position_id: chair, holder: NewHolder::User(bob), membership_type: "mandatory".to_string() };
+    let start = NaiveDate::from_ymd_opt(2026, 3, 1).unwrap();
+    tor::start_new_term(pool, tor_id, start, &[rotation]).await.unwrap();
+    Board { tor_id, alice, bob, chair }
+}
+
+#[tokio::test]
+async fn test_members_at_date() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let board = rotated_board(pool).await;
+
+    let history = tor::find_membership_history(pool, board.tor_id).await.unwrap();
+    assert_eq!(history.at("2026-02-28")[0].holder_id, Some(board.alice));
+    assert_eq!(history.at("2026-03-01")[0].holder_id, Some(board.bob));
+    assert_eq!(history.at("2026-02-28 17:30:00")[0].holder_id, Some(board.alice));
+    assert_eq!(history.at("")[0].holder_id, Some(board.bob));
+    assert_eq!(history.positions_of(board.alice, "2026-01-15"), vec!["Chair".to_string()]);
+    assert!(history.positions_of(board.alice, "2026-04-01").is_empty());
+
+    let members = tor::find_members_at(pool, board.tor_id, "2026-01-15").await.unwrap();
+    assert_eq!(members[0].position_id, board.chair);
+    assert_eq!(members[0].holder_label.as_deref(), Some("Alice"));
+}
+
+#[tokio::test]
+async fn test_minutes_attendance_uses_meeting_date() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let board = rotated_board(pool).await;
+    let meeting = insert_entity(pool, "meeting", "february", "February Meeting").await;
+    insert_prop(pool, meeting, "meeting_date", "2026-02-10").await;
+
+    let minutes_id = minutes::generate_scaffold(pool, meeting, board.tor_id, "February Meeting").await.unwrap();
+    let sections = minutes::find_sections(pool, minutes_id).await.unwrap();
+    let attendance = sections.iter().find(|s| s.section_type == "attendance").unwrap();
+    assert!(attendance.content.contains("Chair \u{2014} **Alice**"));
+    assert!(!attendance.content.contains("Bob"));
+
+    let record = minutes::find_by_id(pool, minutes_id).await.unwrap().unwrap();
+    let expected = record.attendance_list();
+    assert_eq!(expected.len(), 1);
+    assert_eq!(expected[0].name, "Alice (Chair)");
+    assert_eq!(expected[0].status, "present");
+}
+
+#[tokio::test]
+async fn test_decision_register_uses_position_at_decision() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let board = rotated_board(pool).await;
+
+    let mut decisions = Vec::new();
+    for (name, decided_by, date) in [("budget", board.alice, "2026-02-20 10:00:00"), ("audit", board.alice, "2026-03-05 10:00:00")] {
+        let ap = insert_entity(pool, "agenda_point", name, name).await;
+        relation::create(pool, "belongs_to_tor", ap, board.tor_id).await.unwrap();
+        let coa = insert_entity(pool, "coa", &format!("{}_coa", name), "Approve").await;
+        let id = opinion::record_decision(pool, ap, decided_by, coa, "").await.unwrap();
+        entity::set_property(pool, id, "decided_date", date).await.unwrap();
+        decisions.push(id);
+    }
+
+    let register = opinion::find_decision_register(pool, Some(board.tor_id)).await.unwrap();
+    let entry = |id: i64| register.iter().find(|d| d.id == id).unwrap();
+    // Alice chaired the board until 2026-03-01 and held no position after
+    assert_eq!(entry(decisions[0]).decided_by(), "Alice (Chair)");
+    assert_eq!(entry(decisions[1]).decided_by(), "Alice");
+}
+
+#[tokio::test]
+async fn test_single_changes_are_archived() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let board = rotated_board(pool).await;
+    let carol = insert_entity(pool, "user", "carol", "Carol").await;
+    let today = chrono::Local::now().date_naive();
+    let yesterday = (today - chrono::Duration::days(1)).format("%Y-%m-%d").to_string();
+    let today = today.format("%Y-%m-%d").to_string();
+
+    // Bob steps down and Carol takes over the same day: one archive, of Bob
+    tor::vacate_position(pool, board.chair).await.unwrap();
+    tor::assign_to_position(pool, carol, board.chair, "mandatory").await.unwrap();
+
+    let history = tor::find_membership_history(pool, board.tor_id).await.unwrap();
+    assert_eq!(history.terms.len(), 2);
+    assert_eq!(history.terms[0].start_date, "2026-03-01");
+    assert_eq!(history.terms[0].end_date, today);
+    assert_eq!(history.at("2026-02-01")[0].holder_id, Some(board.alice));
+    assert_eq!(history.at(&yesterday)[0].holder_id, Some(board.bob));
+    assert_eq!(history.at(&today)[0].holder_id, Some(carol));
+
+    // Archiving an earlier date after today's change is a no-op
+    let april = NaiveDate::from_ymd_opt(2026, 4, 4).unwrap();
+    assert!(tor::record_change(pool, board.tor_id, april).await.unwrap().is_none());
+
+    // A ToR with no history and nobody in post has nothing to archive
+    let fresh = insert_entity(pool, "tor", "fresh", "Fresh").await;
+    let seat = insert_entity(pool, "tor_function", "seat", "Seat").await;
+    relation::create(pool, "belongs_to_tor", seat, fresh).await.unwrap();
+    assert!(tor::record_change(pool, fresh, april).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_recertification_revoke_is_archived() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let board = rotated_board(pool).await;
+    let admin = insert_entity(pool, "user", "admin", "Admin").await;
+    let yesterday = (chrono::Local::now().date_naive() - chrono::Duration::days(1))
+        .format("%Y-%m-%d").to_string();
+
+    recertification::launch(pool, "Q4", "2026-12-31", "flag", admin).await.unwrap();
+    let items = recertification::find_tasks(pool, admin, true).await.unwrap();
+    let item = items.iter().find(|i| i.position_id == board.chair).unwrap();
+    assert!(recertification::decide(pool, item, recertification::REVOKED, admin).await.unwrap());
+
+    let history = tor::find_membership_history(pool, board.tor_id).await.unwrap();
+    assert_eq!(history.terms.len(), 2);
+    assert_eq!(history.at(&yesterday)[0].holder_id, Some(board.bob));
+    assert_eq!(history.at("")[0].holder_id, None);
+}
@@ -169,7 +169,7 @@ async fn test_update_section_content() {
         .id;
 
     let new_content = "1. Financial Review\n2. Strategic Planning\n3. Q&A";
-    let _ = update_section_content(pool, agenda_section_id, new_content)
+    let _ = update_section_content(pool, agenda_section_id, new_content, 0)
         .await
         .expect("Failed to update section content");
 
@@ -236,3 +236,69 @@ async fn test_auto_generated_attendance_section() {
     assert!(attendance.content.contains("Attendance"));
     assert!(attendance.is_auto_generated);
 }
+
+#[test]
+fn test_diff_lines_marks_added_and_removed() {
+    use ahlt::models::minutes::diff::{diff_lines, DiffKind};
+
+    let lines = diff_lines("a\nb\nc", "a\nc\nd");
+    let kinds: Vec<(DiffKind, &str)> = lines.iter().map(|l| (l.kind, l.text.as_str())).collect();
+    assert_eq!(kinds, vec![
+        (DiffKind::Same, "a"),
+        (DiffKind::Removed, "b"),
+        (DiffKind::Same, "c"),
+        (DiffKind::Added, "d"),
+    ]);
+}
+
+#[tokio::test]
+async fn test_changes_since_circulation_and_revisions() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+
+    let tor_id = tor::create(pool, TEST_TOR_NAME, TEST_TOR_LABEL, &[])
+        .await
+        .expect("Failed to create ToR");
+    let meeting_id = create_test_meeting(pool).await;
+    let minutes_id = generate_scaffold(pool, meeting_id, tor_id, TEST_MEETING_NAME)
+        .await
+        .expect("Failed to generate scaffold");
+    let user_id = insert_entity(pool, "user", "editor", "Editor").await;
+
+    let section_id = find_sections(pool, minutes_id).await.expect("Failed to find sections")
+        .iter()
+        .find(|s| s.section_type == "agenda_items")
+        .expect("Agenda section not found")
+        .id;
+
+    // Edits before circulation are kept as revisions but not flagged
+    update_section_content(pool, section_id, "1. Budget", user_id).await.expect("Failed to update section");
+    let section = find_sections(pool, minutes_id).await.expect("Failed to find sections")
+        .into_iter().find(|s| s.id == section_id).unwrap();
+    assert!(section.circulated_content.is_none());
+    assert!(section.changes_since_circulation().is_none());
+
+    update_status(pool, minutes_id, "pending_approval").await.expect("Failed to update status");
+    mark_circulated(pool, minutes_id).await.expect("Failed to mark circulated");
+    let minutes = find_by_id(pool, minutes_id).await.expect("Query failed").expect("Minutes not found");
+    assert!(!minutes.circulated_at.is_empty());
+
+    // Saving identical content records nothing
+    let recorded = record_revision(pool, section_id, "1. Budget", user_id).await.expect("Failed to record revision");
+    assert!(!recorded);
+
+    update_section_content(pool, section_id, "1. Budget\n2. Hiring", user_id).await.expect("Failed to update section");
+
+    let section = find_sections(pool, minutes_id).await.expect("Failed to find sections")
+        .into_iter().find(|s| s.id == section_id).unwrap();
+    let changes = section.changes_since_circulation().expect("Section should show changes");
+    assert_eq!(changes.len(), 2);
+    assert_eq!(changes[1].text, "2. Hiring");
+    assert_eq!(changes[1].marker(), "+");
+
+    let revisions = find_revisions(pool, minutes_id).await.expect("Failed to find revisions");
+    assert_eq!(revisions.len(), 2);
+    assert_eq!(revisions[0].field("content"), "1. Budget");
+    assert_eq!(revisions[0].edited_by, "Editor");
+    assert!(revisions.iter().all(|r| r.target_id == section_id));
+}
@@ -0,0 +1,88 @@
+//! Mobile quick action tests — covers the queries behind `/api/v1/mobile`.
+//!
+//! - Pending opinions: open decision items in the user's ToRs with COAs to
+//!   choose from and no opinion from the user yet
+//! - Warning acknowledgement reads and moves the receipt status
+//! - API tokens can be scoped to meetings and agenda work
+
+mod common;
+
+use ahlt::models::{agenda_point, api_token, coa, opinion, relation, tor};
+use ahlt::warnings::{self, queries};
+use common::*;
+
+#[tokio::test]
+async fn test_pending_opinions_for_user() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let alice = insert_entity(pool, "user", "alice", "Alice").await;
+    let board = insert_entity(pool, "tor", "board", "Board").await;
+    let chair = insert_entity(pool, "tor_function", "board_chair", "Chair").await;
+    relation::create(pool, "belongs_to_tor", chair, board).await.unwrap();
+    tor::assign_to_position(pool, alice, chair, "mandatory").await.unwrap();
+    let other = insert_entity(pool, "tor", "audit", "Audit").await;
+
+    let point = |tor_id: i64, title: &'static str, item_type: &'static str, date: &'static str| async move {
+        agenda_point::create(pool, tor_id, title, "", item_type, date, 15, alice, "", "", "").await.unwrap()
+    };
+    let with_coas = |point_id: i64| async move {
+        let approve = coa::create(pool, &format!("Approve {point_id}"), "", "simple", alice).await.unwrap();
+        let reject = coa::create(pool, &format!("Reject {point_id}"), "", "simple", alice).await.unwrap();
+        relation::create(pool, "considers_coa", point_id, approve).await.unwrap();
+        relation::create(pool, "considers_coa", point_id, reject).await.unwrap();
+        approve
+    };
+
+    let later = point(board, "Budget 2027", "decision", "2026-11-02").await;
+    with_coas(later).await;
+    let sooner = point(board, "Hiring plan", "decision", "2026-10-05").await;
+    with_coas(sooner).await;
+    let answered = point(board, "Office move", "decision", "2026-10-06").await;
+    let answered_coa = with_coas(answered).await;
+    opinion::record_opinion(pool, answered, alice, answered_coa, "").await.unwrap();
+    let decided = point(board, "Travel policy", "decision", "2026-10-07").await;
+    let decided_coa = with_coas(decided).await;
+    opinion::record_decision(pool, decided, alice, decided_coa, "Agreed").await.unwrap();
+    point(board, "Status update", "informative", "2026-10-08").await;
+    point(board, "No options yet", "decision", "2026-10-09").await;
+    let elsewhere = point(other, "Audit scope", "decision", "2026-10-10").await;
+    with_coas(elsewhere).await;
+
+    let tor_ids = tor::find_tor_ids_for_user(pool, alice).await;
+    assert_eq!(tor_ids, vec![board]);
+    let pending = opinion::find_pending_for_user(pool, alice, &tor_ids).await.unwrap();
+    let titles: Vec<&str> = pending.iter().map(|p| p.title.as_str()).collect();
+    assert_eq!(titles, vec!["Hiring plan", "Budget 2027"]);
+    assert_eq!((pending[0].tor_id, pending[0].tor_label.as_str()), (board, "Board"));
+    assert_eq!(pending[0].scheduled_date, "2026-10-05");
+
+    let hiring_coa = coa::find_all_for_agenda_point(pool, sooner).await.unwrap()[0].id;
+    opinion::record_opinion(pool, sooner, alice, hiring_coa, "").await.unwrap();
+    let pending = opinion::find_pending_for_user(pool, alice, &tor_ids).await.unwrap();
+    assert_eq!(pending.iter().map(|p| p.agenda_point_id).collect::<Vec<_>>(), vec![later]);
+}
+
+#[tokio::test]
+async fn test_warning_acknowledgement_and_scopes() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let alice = insert_entity(pool, "user", "alice", "Alice").await;
+
+    let warning_id = warnings::create_warning(pool, "medium", "governance", "test", "Quorum at risk", "{}", "user")
+        .await.unwrap();
+    warnings::create_receipts(pool, warning_id, &[alice]).await.unwrap();
+    let receipt_id = queries::find_receipt_for_user(pool, warning_id, alice).await.unwrap().unwrap();
+    assert_eq!(queries::get_receipt_status(pool, receipt_id).await.unwrap(), "unread");
+
+    warnings::update_receipt_status(pool, receipt_id, "read", alice).await.unwrap();
+    assert_eq!(queries::get_receipt_status(pool, receipt_id).await.unwrap(), "read");
+    let open = queries::find_for_user(pool, alice, 1, 20, None, None, false, false).await.unwrap();
+    assert!(open.items.is_empty(), "acknowledged warnings leave the mobile list");
+
+    let owner: Vec<String> = ["agenda.participate", "meetings.view", "warnings.view", "users.list"]
+        .iter().map(|s| s.to_string()).collect();
+    assert_eq!(
+        api_token::scoped_permissions(&owner, &["meetings", "agenda", "warnings"]),
+        vec!["agenda.participate", "meetings.view", "warnings.view"],
+    );
+}
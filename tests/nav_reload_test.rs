@@ -0,0 +1,69 @@
+//! Navigation hot-reload tests — covers the nav cache and its version.
+//!
+//! - Navigation is served from a cache until the version is bumped
+//! - The version lives in the database, so a bump from any replica counts
+//! - A bump pushes `nav_updated` to every connected user
+
+mod common;
+
+use ahlt::auth::session::Permissions;
+use ahlt::handlers::warning_handlers::ws::{self, new_connection_map};
+use ahlt::models::{nav_item, relation};
+use common::*;
+use sqlx::PgPool;
+use tokio::sync::mpsc;
+
+async fn nav(pool: &PgPool, name: &str, label: &str, url: &str, permission: i64) {
+    let id = insert_entity(pool, "nav_item", name, label).await;
+    insert_prop(pool, id, "url", url).await;
+    relation::create(pool, "requires_permission", id, permission).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_cache_and_version_bump() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let users = insert_entity(pool, "permission", "users.list", "List Users").await;
+    let audit = insert_entity(pool, "permission", "audit.view", "View Audit").await;
+    let permissions = Permissions(vec!["users.list".to_string(), "audit.view".to_string()]);
+    let labels = |modules: Vec<nav_item::NavModule>| modules.into_iter().map(|m| m.label).collect::<Vec<_>>();
+
+    nav(pool, "users", "Users", "/users", users).await;
+    nav_item::bump(pool).await.unwrap();
+    let (modules, _) = nav_item::find_navigation(pool, &permissions, "/users").await;
+    assert_eq!(labels(modules), vec!["Users"]);
+
+    // Served from the cache until the next bump
+    nav(pool, "audit", "Audit", "/audit", audit).await;
+    let (modules, _) = nav_item::find_navigation(pool, &permissions, "/users").await;
+    assert_eq!(labels(modules), vec!["Users"]);
+
+    let conn_map = new_connection_map();
+    let (alice_tx, mut alice_rx) = mpsc::unbounded_channel();
+    let (bob_tx, mut bob_rx) = mpsc::unbounded_channel();
+    conn_map.write().unwrap().insert(1, vec![alice_tx]);
+    conn_map.write().unwrap().insert(2, vec![bob_tx]);
+    let before = nav_item::version(pool).await.unwrap();
+    ws::notify_nav_updated(pool, &conn_map).await.unwrap();
+    assert_eq!(nav_item::version(pool).await.unwrap(), before + 1);
+
+    let pushed: serde_json::Value = serde_json::from_str(&alice_rx.try_recv().unwrap()).unwrap();
+    assert_eq!(pushed, serde_json::json!({ "type": "nav_updated", "version": before + 1 }));
+    assert!(bob_rx.try_recv().is_ok());
+
+    let (modules, _) = nav_item::find_navigation(pool, &permissions, "/users").await;
+    assert_eq!(labels(modules), vec!["Users", "Audit"]);
+
+    // Another replica removes an item and bumps the shared version row
+    sqlx::query("UPDATE entities SET is_active = false WHERE entity_type = 'nav_item' AND name = 'audit'")
+        .execute(pool)
+        .await
+        .unwrap();
+    sqlx::query("UPDATE cache_generations SET generation = generation + 1 WHERE name = 'nav'")
+        .execute(pool)
+        .await
+        .unwrap();
+    assert_eq!(nav_item::version(pool).await.unwrap(), before + 2);
+    let (modules, _) = nav_item::find_navigation(pool, &permissions, "/users").await;
+    assert_eq!(labels(modules), vec!["Users"]);
+}
@@ -0,0 +1,106 @@
+//! Network allowlist tests — covers CIDR matching, role bypass and warnings.
+//!
+//! - Ranges match IPv4, IPv6 and IPv4-mapped addresses; bad entries are rejected
+//! - An empty allowlist or an unrestricted scope lets every address through
+//! - Bypass roles get in from anywhere
+//! - A blocked address raises one warning until that warning is resolved
+
+mod common;
+
+use std::net::IpAddr;
+
+use ahlt::auth::network::{self, Cidr, Scope};
+use ahlt::models::{relation, setting};
+use common::*;
+use sqlx::PgPool;
+
+async fn set(pool: &PgPool, name: &str, value: &str) {
+    let id = insert_entity(pool, "setting", name, name).await;
+    insert_prop(pool, id, "value", value).await;
+}
+
+fn ip(s: &str) -> IpAddr {
+    s.parse().unwrap()
+}
+
+#[test]
+fn test_cidr_matching() {
+    let office = Cidr::parse("10.20.0.0/16").unwrap();
+    assert!(office.contains(ip("10.20.255.1")));
+    assert!(office.contains(ip("::ffff:10.20.0.7")));
+    assert!(!office.contains(ip("10.21.0.1")));
+    assert!(!office.contains(ip("2001:db8::1")));
+
+    let v6 = Cidr::parse(" 2001:db8::/32 ").unwrap();
+    assert!(v6.contains(ip("2001:db8:ffff::1")));
+    assert!(!v6.contains(ip("2001:db9::1")));
+
+    assert!(Cidr::parse("192.0.2.5").unwrap().contains(ip("192.0.2.5")));
+    assert!(!Cidr::parse("192.0.2.5").unwrap().contains(ip("192.0.2.6")));
+    assert!(Cidr::parse("0.0.0.0/0").unwrap().contains(ip("203.0.113.9")));
+
+    let list = network::parse_allowlist("10.0.0.0/8, 192.0.2.0/24\n2001:db8::/32").unwrap();
+    assert_eq!(list.iter().map(|c| c.to_string()).collect::<Vec<_>>(), vec!["10.0.0.0/8", "192.0.2.0/24", "2001:db8::/32"]);
+    assert!(network::parse_allowlist("").unwrap().is_empty());
+    assert!(network::parse_allowlist("10.0.0.0/33").is_err());
+    assert!(network::parse_allowlist("office-lan").is_err());
+    assert!(setting::validate("network.allowlist", "text", "10.0.0.0/8").is_ok());
+    assert!(setting::validate("network.allowlist", "text", "10.0.0/8").is_err());
+}
+
+#[tokio::test]
+async fn test_check_and_bypass() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let alice = insert_entity(pool, "user", "alice", "Alice").await;
+    let bob = insert_entity(pool, "user", "bob", "Bob").await;
+    let ops = insert_entity(pool, "role", "ops", "Operations").await;
+    relation::create(pool, "has_role", bob, ops).await.unwrap();
+    let outside = ip("203.0.113.9");
+
+    // No allowlist: everything goes
+    assert!(network::check(pool, outside, alice, Scope::Login).await.unwrap());
+
+    set(pool, "network.allowlist", "10.0.0.0/8").await;
+    assert!(!network::check(pool, outside, alice, Scope::Login).await.unwrap());
+    assert!(!network::check(pool, outside, alice, Scope::Api).await.unwrap());
+    assert!(network::check(pool, ip("10.1.2.3"), alice, Scope::Login).await.unwrap());
+
+    set(pool, "network.restrict_api", "false").await;
+    assert!(network::check(pool, outside, alice, Scope::Api).await.unwrap());
+
+    set(pool, "network.bypass_roles", " OPS ").await;
+    assert!(network::check(pool, outside, bob, Scope::Login).await.unwrap());
+    assert!(!network::check(pool, outside, alice, Scope::Login).await.unwrap());
+}
+
+#[tokio::test]
+async fn test_blocked_attempts_warn_once_per_address() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let admin = insert_entity(pool, "user", "admin", "Admin").await;
+    let role = insert_entity(pool, "role", "admin", "Administrator").await;
+    let perm = insert_entity(pool, "permission", "settings.manage", "Manage Settings").await;
+    relation::create(pool, "has_role", admin, role).await.unwrap();
+    relation::create(pool, "has_permission", role, perm).await.unwrap();
+    let alice = insert_entity(pool, "user", "alice", "Alice").await;
+
+    network::report_blocked(pool, ip("203.0.113.9"), alice, "alice", Scope::Login).await;
+    network::report_blocked(pool, ip("203.0.113.9"), alice, "alice", Scope::Api).await;
+    network::report_blocked(pool, ip("198.51.100.4"), alice, "alice", Scope::Login).await;
+
+    let messages: Vec<String> = sqlx::query_scalar(
+        "SELECT p.value FROM entities e JOIN entity_properties p ON p.entity_id = e.id AND p.key = 'message' \
+         WHERE e.entity_type = 'warning' ORDER BY e.id",
+    )
+    .fetch_all(pool)
+    .await
+    .unwrap();
+    assert_eq!(messages, vec![
+        "Sign-in by 'alice' blocked from 203.0.113.9, outside the network allowlist",
+        "Sign-in by 'alice' blocked from 198.51.100.4, outside the network allowlist",
+    ]);
+    let receipts: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM entities WHERE entity_type = 'warning_receipt'")
+        .fetch_one(pool).await.unwrap();
+    assert_eq!(receipts, 2);
+}
@@ -0,0 +1,106 @@
+//! New-device sign-in tests — covers sign-in recording, alerts and account locks.
+//!
+//! - The first sign-in sets the baseline; a new device or address is reported
+//! - A new sign-in warns only the user it belongs to, unless alerts are off
+//! - "This wasn't me" locks the account and alerts the user administrators
+//! - Unlocking lets the account sign in again
+
+mod common;
+
+use std::net::IpAddr;
+
+use ahlt::auth::new_device;
+use ahlt::models::sign_in_device::{self, Sighting};
+use ahlt::models::{relation, user};
+use common::*;
+use sqlx::PgPool;
+
+fn ip(s: &str) -> IpAddr {
+    s.parse().unwrap()
+}
+
+/// Receipt names (`wr.<warning>.<user>`) of warnings raised by `source_action`.
+async fn receipts_for(pool: &PgPool, source_action: &str) -> Vec<String> {
+    sqlx::query_scalar(
+        "SELECT r.name FROM entities r \
+         JOIN relations rel ON rel.source_id = r.id \
+         JOIN entities rt ON rt.id = rel.relation_type_id AND rt.name = 'for_warning' \
+         JOIN entity_properties p ON p.entity_id = rel.target_id AND p.key = 'source_action' \
+         WHERE r.entity_type = 'warning_receipt' AND p.value = $1 ORDER BY r.id",
+    )
+    .bind(source_action)
+    .fetch_all(pool)
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_sightings() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let alice = insert_entity(pool, "user", "alice", "Alice").await;
+
+    assert_eq!(sign_in_device::record(pool, alice, "Firefox on Linux", "10.0.0.1").await.unwrap(), Sighting::First);
+    assert_eq!(sign_in_device::record(pool, alice, "Firefox on Linux", "10.0.0.1").await.unwrap(), Sighting::Known);
+    let Sighting::New(id) = sign_in_device::record(pool, alice, "Firefox on Linux", "203.0.113.9").await.unwrap() else {
+        panic!("a new address should be reported");
+    };
+    assert!(matches!(sign_in_device::record(pool, alice, "Safari on iOS", "10.0.0.1").await.unwrap(), Sighting::New(_)));
+
+    let sign_ins = sign_in_device::find_for_user(pool, alice).await.unwrap();
+    assert_eq!(sign_ins.len(), 3);
+    let found = sign_in_device::find_by_id(pool, id).await.unwrap().unwrap();
+    assert_eq!((found.user_id, found.ip.as_str()), (alice, "203.0.113.9"));
+}
+
+#[tokio::test]
+async fn test_new_sign_in_warns_the_user() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let alice = insert_entity(pool, "user", "alice", "Alice").await;
+    let bob = insert_entity(pool, "user", "bob", "Bob").await;
+
+    new_device::notice(pool, alice, "alice", "Firefox on Linux", ip("10.0.0.1")).await;
+    new_device::notice(pool, alice, "alice", "Firefox on Linux", ip("10.0.0.1")).await;
+    assert!(receipts_for(pool, "event.security.new_sign_in").await.is_empty());
+
+    new_device::notice(pool, alice, "alice", "Chrome on Windows", ip("10.0.0.1")).await;
+    new_device::notice(pool, bob, "bob", "Chrome on Windows", ip("10.0.0.1")).await;
+    let receipts = receipts_for(pool, "event.security.new_sign_in").await;
+    assert_eq!(receipts.len(), 1);
+    assert!(receipts[0].ends_with(&format!(".{alice}")));
+
+    let setting = insert_entity(pool, "setting", "login.new_device_alerts", "login.new_device_alerts").await;
+    insert_prop(pool, setting, "value", "false").await;
+    new_device::notice(pool, alice, "alice", "Edge on Windows", ip("10.0.0.1")).await;
+    assert_eq!(receipts_for(pool, "event.security.new_sign_in").await.len(), 1);
+    assert_eq!(sign_in_device::find_for_user(pool, alice).await.unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn test_not_me_locks_and_unlock() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let admin = insert_entity(pool, "user", "admin", "Admin").await;
+    let role = insert_entity(pool, "role", "admin", "Administrator").await;
+    let perm = insert_entity(pool, "permission", "users.edit", "Edit Users").await;
+    relation::create(pool, "has_role", admin, role).await.unwrap();
+    relation::create(pool, "has_permission", role, perm).await.unwrap();
+    let alice = insert_entity(pool, "user", "alice", "Alice").await;
+
+    sign_in_device::record(pool, alice, "Firefox on Linux", "10.0.0.1").await.unwrap();
+    let Sighting::New(id) = sign_in_device::record(pool, alice, "Chrome on Windows", "203.0.113.9").await.unwrap() else {
+        panic!("a new device should be reported");
+    };
+    let sign_in = sign_in_device::find_by_id(pool, id).await.unwrap().unwrap();
+    assert!(!user::is_locked(pool, alice).await.unwrap());
+
+    new_device::report_not_me(pool, alice, "alice", &sign_in).await.unwrap();
+    assert!(user::is_locked(pool, alice).await.unwrap());
+    let receipts = receipts_for(pool, "event.security.account_locked").await;
+    assert_eq!(receipts.len(), 1);
+    assert!(receipts[0].ends_with(&format!(".{admin}")));
+
+    user::unlock(pool, alice).await.unwrap();
+    assert!(!user::is_locked(pool, alice).await.unwrap());
+}
@@ -0,0 +1,117 @@
+//! Notification outbox tests — covers queued warning delivery.
+//!
+//! - Receipts and their live-push delivery are committed together
+//! - The dispatcher delivers to connected users and marks rows delivered
+//! - Failed deliveries stay queued with backoff, park after too many
+//!   attempts, and can be replayed
+
+mod common;
+
+use ahlt::handlers::warning_handlers::ws::{new_connection_map, ConnectionMap};
+use ahlt::warnings::{self, outbox};
+use common::*;
+use tokio::sync::mpsc;
+
+async fn statuses(pool: &sqlx::PgPool) -> Vec<(String, i32)> {
+    outbox::find_recent(pool, None, 100).await.unwrap()
+        .into_iter()
+        .map(|e| (e.status, e.attempts))
+        .collect()
+}
+
+fn poisoned_map() -> ConnectionMap {
+    let map = new_connection_map();
+    let held = map.clone();
+    let _ = std::thread::spawn(move || {
+        let _guard = held.write().unwrap();
+        panic!("poison the connection map");
+    })
+    .join();
+    map
+}
+
+#[tokio::test]
+async fn test_receipts_queue_and_deliver() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let alice = insert_entity(pool, "user", "alice", "Alice").await;
+    let bob = insert_entity(pool, "user", "bob", "Bob").await;
+
+    let conn_map = new_connection_map();
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    conn_map.write().unwrap().insert(alice, vec![tx]);
+
+    let wid = warnings::create_warning(pool, "high", "system", "test", "Disk full", "", "system").await.unwrap();
+    let receipts = warnings::create_receipts(pool, wid, &[alice, bob]).await.unwrap();
+    assert_eq!(receipts.len(), 2);
+
+    // Queued with the receipts, nothing pushed yet
+    assert_eq!(statuses(pool).await, vec![("pending".to_string(), 0)]);
+    assert!(rx.try_recv().is_err());
+
+    assert_eq!(outbox::drain(pool, &conn_map).await, 1);
+    let msg: serde_json::Value = serde_json::from_str(&rx.try_recv().unwrap()).unwrap();
+    assert_eq!(msg["type"], "new_warning");
+    assert_eq!(msg["warning_id"].as_i64(), Some(wid));
+    assert_eq!(msg["severity"], "high");
+    assert_eq!(msg["title"], "Disk full");
+    assert_eq!(msg["unread_count"].as_i64(), Some(1));
+
+    let entry = &outbox::find_recent(pool, None, 10).await.unwrap()[0];
+    assert_eq!((entry.status.as_str(), entry.attempts), ("delivered", 1));
+    assert!(entry.delivered_at.is_some());
+    assert!(!entry.can_replay());
+    // Nothing left to deliver
+    assert_eq!(outbox::drain(pool, &conn_map).await, 0);
+
+    // No recipients, nothing queued
+    assert!(warnings::create_receipts(pool, wid, &[]).await.unwrap().is_empty());
+    assert_eq!(statuses(pool).await.len(), 1);
+}
+
+#[tokio::test]
+async fn test_failed_delivery_is_retried_and_replayed() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let alice = insert_entity(pool, "user", "alice", "Alice").await;
+    let wid = warnings::create_warning(pool, "medium", "system", "test", "Check backups", "", "system").await.unwrap();
+    warnings::create_receipts(pool, wid, &[alice]).await.unwrap();
+
+    // The socket hub is down: the row stays queued with a backoff
+    assert_eq!(outbox::drain(pool, &poisoned_map()).await, 0);
+    let entry = &outbox::find_recent(pool, None, 10).await.unwrap()[0];
+    assert_eq!((entry.status.as_str(), entry.attempts), ("pending", 1));
+    assert_eq!(entry.last_error, "Connection map unavailable");
+    // Not due again until the backoff passes
+    let conn_map = new_connection_map();
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    conn_map.write().unwrap().insert(alice, vec![tx]);
+    assert_eq!(outbox::drain(pool, &conn_map).await, 0);
+
+    // A replay makes it due immediately
+    assert!(outbox::replay(pool, entry.id).await.unwrap());
+    assert_eq!(outbox::drain(pool, &conn_map).await, 1);
+    assert!(rx.try_recv().is_ok());
+    assert!(!outbox::replay(pool, entry.id).await.unwrap());
+
+    // A channel without a transport is parked after the last attempt
+    let mut db_tx = pool.begin().await.unwrap();
+    let id = outbox::enqueue(&mut db_tx, "carrier_pigeon", serde_json::json!({})).await.unwrap();
+    db_tx.commit().await.unwrap();
+    sqlx::query("UPDATE notification_outbox SET attempts = $2 WHERE id = $1")
+        .bind(id)
+        .bind(outbox::MAX_ATTEMPTS - 1)
+        .execute(pool)
+        .await
+        .unwrap();
+    assert_eq!(outbox::drain(pool, &conn_map).await, 0);
+    let failed = outbox::find_recent(pool, Some("failed"), 10).await.unwrap();
+    assert_eq!(failed.len(), 1);
+    assert!(failed[0].last_error.contains("carrier_pigeon"));
+    assert_eq!(outbox::replay_failed(pool).await.unwrap(), 1);
+    assert_eq!(outbox::find_recent(pool, Some("pending"), 10).await.unwrap()[0].attempts, 0);
+
+    assert_eq!(outbox::backoff_secs(1), 2);
+    assert_eq!(outbox::backoff_secs(5), 32);
+    assert_eq!(outbox::backoff_secs(outbox::MAX_ATTEMPTS), 300);
+}
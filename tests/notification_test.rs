@@ -0,0 +1,79 @@
+//! Notification center tests — covers channels, mentions and watches.
+//!
+//! - The channel comes from the warning's source action
+//! - `@username` mentions notify the named users, never the author
+//! - Mark-all-read can be limited to one channel
+//! - Watchers hear about changes made by others
+
+mod common;
+
+use ahlt::models::relation;
+use ahlt::warnings::{self, notifications};
+use common::*;
+
+#[tokio::test]
+async fn test_mentions_and_read_state() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let alice = insert_entity(pool, "user", "alice", "Alice").await;
+    let bob = insert_entity(pool, "user", "bob.smith", "Bob").await;
+
+    assert_eq!(notifications::channel_of("mention.text"), "mention");
+    assert_eq!(notifications::channel_of("event.system.migration"), "announcement");
+    assert_eq!(notifications::channel_of("event.user.created"), "warning");
+    assert_eq!(
+        notifications::mentioned_usernames("Agree with @Bob.Smith. Mail me at x@example.org, @bob.smith and @carol"),
+        vec!["bob.smith", "carol"]
+    );
+
+    let notified = notifications::notify_mentions(
+        pool, alice, "alice", "@alice @bob.smith please check", "a suggestion", "/tor/1/workflow",
+    ).await.unwrap();
+    assert_eq!(notified, vec![bob]);
+    let wid = warnings::create_warning(pool, "high", "security", "event.setting.critical_changed", "Setting changed", "", "system").await.unwrap();
+    warnings::create_receipts(pool, wid, &[bob]).await.unwrap();
+
+    let inbox = notifications::find_for_user(pool, bob, false).await.unwrap();
+    assert_eq!(inbox.len(), 2);
+    let mention = inbox.iter().find(|n| n.channel() == "mention").unwrap();
+    assert_eq!(mention.message, "alice mentioned you in a suggestion");
+    assert_eq!(mention.href(), "/tor/1/workflow");
+    assert!(notifications::find_for_user(pool, alice, false).await.unwrap().is_empty());
+
+    // Someone else's receipt cannot be marked
+    assert!(notifications::mark_read(pool, alice, mention.receipt_id).await.unwrap().is_none());
+    assert_eq!(notifications::mark_all_read(pool, bob, Some("mention")).await.unwrap(), 1);
+    let unread = notifications::find_for_user(pool, bob, false).await.unwrap();
+    assert_eq!(unread.iter().map(|n| n.channel()).collect::<Vec<_>>(), vec!["warning"]);
+    assert_eq!(unread[0].href(), format!("/warnings/{wid}"));
+    assert_eq!(notifications::find_for_user(pool, bob, true).await.unwrap().len(), 2);
+
+    notifications::mark_read(pool, bob, unread[0].receipt_id).await.unwrap().unwrap();
+    assert_eq!(warnings::queries::count_unread(pool, bob).await, 0);
+}
+
+#[tokio::test]
+async fn test_watch_updates() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let alice = insert_entity(pool, "user", "alice", "Alice").await;
+    let bob = insert_entity(pool, "user", "bob", "Bob").await;
+    let board = insert_entity(pool, "tor", "board", "Board").await;
+    let proposal = insert_entity(pool, "proposal", "budget", "Budget").await;
+    relation::create(pool, "submitted_to", proposal, board).await.unwrap();
+
+    notifications::watch(pool, alice, proposal).await.unwrap();
+    notifications::watch(pool, bob, proposal).await.unwrap();
+    notifications::watch(pool, bob, proposal).await.unwrap();
+    assert!(notifications::is_watching(pool, bob, proposal).await.unwrap());
+
+    let notified = notifications::notify_watchers(pool, proposal, alice, "Proposal 'Budget' was approved", "/tor/1/proposals/2").await.unwrap();
+    assert_eq!(notified, vec![bob]);
+    let inbox = notifications::find_for_user(pool, bob, false).await.unwrap();
+    assert_eq!((inbox.len(), inbox[0].channel(), inbox[0].channel_label()), (1, "watch", "Watch updates"));
+
+    notifications::unwatch(pool, bob, proposal).await.unwrap();
+    assert!(!notifications::is_watching(pool, bob, proposal).await.unwrap());
+    assert!(notifications::notify_watchers(pool, proposal, alice, "Proposal 'Budget' was rejected", "").await.unwrap().is_empty());
+    assert_eq!(notifications::find_for_user(pool, bob, false).await.unwrap().len(), 1);
+}
@@ -0,0 +1,106 @@
+//! Offline meeting pack tests — covers the self-contained HTML viewer and
+//! the manifest-driven refresh.
+//!
+//! - The bundle inlines its stylesheet and documents and embeds the manifest
+//! - Documents attached through several points appear once
+//! - A refresh returns only the items whose hash the client does not have
+
+mod common;
+
+use ahlt::export::offline;
+use ahlt::models::{agenda_point, document, meeting, proposal, relation};
+use common::*;
+
+const PIXEL: &str = "data:image/png;base64,iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR42mNk+M9QDwADhgGAWjR9awAAAABJRU5ErkJggg==";
+
+/// A meeting with two points from one proposal, which has a text and an image document.
+async fn setup(pool: &sqlx::PgPool) -> (i64, i64, i64, i64) {
+    let alice = insert_entity(pool, "user", "alice", "Alice").await;
+    let board = insert_entity(pool, "tor", "board", "Board").await;
+    let meeting_id = meeting::create(pool, board, "2026-10-01", "Board", "Room 1", "Bring <laptops>", "", "", "", "alice", "")
+        .await.unwrap();
+    let budget = agenda_point::create(pool, board, "Budget 2027", "Line one\nLine two", "decision", "2026-10-01", 30, alice, "Alice", "", "")
+        .await.unwrap();
+    let review = agenda_point::create(pool, board, "Budget review", "", "informative", "2026-10-02", 10, alice, "", "", "")
+        .await.unwrap();
+    meeting::assign_agenda(pool, meeting_id, budget).await.unwrap();
+    meeting::assign_agenda(pool, meeting_id, review).await.unwrap();
+
+    let proposal_id = proposal::create(pool, board, "Adopt the 2027 budget", "Spend wisely", "Costs are rising", alice, "2026-09-01", None)
+        .await.unwrap();
+    relation::create(pool, "spawns_agenda_point", proposal_id, budget).await.unwrap();
+    relation::create(pool, "spawns_agenda_point", proposal_id, review).await.unwrap();
+    let paper = document::create(pool, "Budget paper", "ad_hoc", "Numbers & notes", alice, None).await.unwrap();
+    let chart = document::create(pool, "Budget chart", "ad_hoc", PIXEL, alice, None).await.unwrap();
+    relation::create(pool, "attached_to_proposal", paper, proposal_id).await.unwrap();
+    relation::create(pool, "attached_to_proposal", chart, proposal_id).await.unwrap();
+    (meeting_id, budget, paper, chart)
+}
+
+#[tokio::test]
+async fn test_offline_bundle_is_self_contained() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let (meeting_id, budget, paper, chart) = setup(pool).await;
+    let m = meeting::find_by_id(pool, meeting_id).await.unwrap().unwrap();
+
+    let items = offline::items(pool, &m).await.unwrap();
+    let keys: Vec<&str> = items.iter().map(|i| i.key.as_str()).collect();
+    assert_eq!(keys[0], "agenda");
+    assert_eq!(keys.len(), 5, "agenda, two points, two documents once each: {keys:?}");
+    assert!(keys.contains(&offline::point_key(budget).as_str()));
+    assert_eq!(keys.iter().filter(|k| **k == offline::document_key(paper)).count(), 1);
+
+    let html = String::from_utf8(offline::bundle(pool, &m).await.unwrap()).unwrap();
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(!html.contains("/static/"), "bundle must not load app assets");
+    assert!(html.contains(".print-page"), "print stylesheet inlined");
+    assert!(html.contains(&format!(r#"src="{PIXEL}""#)), "image embedded");
+    assert!(html.contains("Numbers &amp; notes") || html.contains("Numbers &#38; notes"));
+    assert!(html.contains("Bring &lt;laptops&gt;") || html.contains("Bring &#60;laptops&#62;"));
+    assert!(html.contains(&format!(r##"href="#{}""##, offline::document_key(chart))));
+    for item in &items {
+        assert!(html.contains(&format!(r#"id="{}" data-kind="{}" data-sha256="{}""#, item.key, item.kind, item.sha256)));
+    }
+
+    let start = html.find(r#"<script type="application/json" id="offline-manifest">"#).unwrap();
+    let json = &html[start..];
+    let json = &json[json.find('>').unwrap() + 1..json.find("</script>").unwrap()];
+    let manifest: serde_json::Value = serde_json::from_str(json).unwrap();
+    assert_eq!(manifest["format"], offline::OFFLINE_FORMAT);
+    assert_eq!(manifest["meeting_id"], meeting_id);
+    assert_eq!(manifest["items"].as_array().unwrap().len(), items.len());
+    assert_eq!(manifest["items"][0]["sha256"], items[0].sha256.as_str());
+    assert_eq!(offline::file_name(&m), format!("meeting-offline-{}.html", m.name));
+}
+
+#[tokio::test]
+async fn test_offline_refresh_returns_changed_items() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let (meeting_id, budget, paper, _) = setup(pool).await;
+    let m = meeting::find_by_id(pool, meeting_id).await.unwrap().unwrap();
+
+    let first = offline::items(pool, &m).await.unwrap();
+    let have_all = first.iter().map(|i| i.sha256.clone()).collect::<Vec<_>>().join(",");
+    let have = offline::parse_have(&format!("{have_all},not-a-digest"));
+    assert_eq!(have.len(), first.len());
+
+    let same = offline::update(&m, offline::items(pool, &m).await.unwrap(), &have);
+    assert!(same.items.is_empty());
+    assert_eq!(same.unchanged, first.len());
+
+    document::update(pool, paper, "Budget paper", "ad_hoc", "Revised numbers").await.unwrap();
+    agenda_point::update(pool, budget, "Budget 2027", "Line one only", "decision", "2026-10-01", 30, "Alice", "", "")
+        .await.unwrap();
+
+    let update = offline::update(&m, offline::items(pool, &m).await.unwrap(), &have);
+    let changed: Vec<&str> = update.items.iter().map(|i| i.key.as_str()).collect();
+    assert_eq!(changed, vec![offline::point_key(budget), offline::document_key(paper)]);
+    assert!(update.items[1].html.contains("Revised numbers"));
+    assert_eq!(update.unchanged, first.len() - 2);
+    assert_eq!(update.manifest.items.len(), first.len());
+
+    let fresh = offline::update(&m, offline::items(pool, &m).await.unwrap(), &offline::parse_have(""));
+    assert_eq!(fresh.items.len(), first.len());
+}
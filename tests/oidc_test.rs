@@ -0,0 +1,155 @@
+//! Single sign-on tests — covers PKCE, ID token checks and account resolution.
+//!
+//! - The PKCE challenge matches the RFC 7636 example
+//! - ID tokens are rejected for the wrong issuer, audience, nonce or expiry
+//! - Providers are only trusted over https
+//! - Unknown identities are provisioned with the default role, linked ones
+//!   sign in, and e-mail collisions with local accounts are refused
+
+mod common;
+
+use ahlt::auth::oidc::{self, IdClaims, OidcConfig, Resolution};
+use ahlt::models::user;
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use common::*;
+use serde_json::json;
+
+const ISSUER: &str = "https://login.example.org";
+
+fn config(auto_provision: bool) -> OidcConfig {
+    OidcConfig {
+        issuer: ISSUER.to_string(),
+        client_id: "ahlt".to_string(),
+        client_secret: "secret".to_string(),
+        redirect_url: String::new(),
+        button_label: "Sign in with SSO".to_string(),
+        auto_provision,
+        default_role: "default".to_string(),
+    }
+}
+
+fn token(claims: serde_json::Value) -> String {
+    format!(
+        "{}.{}.sig",
+        URL_SAFE_NO_PAD.encode(br#"{"alg":"RS256"}"#),
+        URL_SAFE_NO_PAD.encode(claims.to_string())
+    )
+}
+
+fn claims(sub: &str, email: &str, username: &str) -> IdClaims {
+    serde_json::from_value(json!({
+        "iss": ISSUER, "sub": sub, "aud": "ahlt", "exp": 0,
+        "email": email, "email_verified": true,
+        "preferred_username": username, "name": "Kari Nordmann"
+    }))
+    .unwrap()
+}
+
+#[test]
+fn test_verified_email_requires_the_claim() {
+    let mut c = claims("u1", "kari@example.com", "kari");
+    assert_eq!(c.verified_email(), Some("kari@example.com"));
+    c.email_verified = Some(false);
+    assert_eq!(c.verified_email(), None);
+    c.email_verified = None;
+    assert_eq!(c.verified_email(), None);
+}
+
+#[test]
+fn test_pkce_challenge() {
+    assert_eq!(
+        oidc::pkce_challenge("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk"),
+        "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM"
+    );
+}
+
+#[test]
+fn test_validate_id_token() {
+    let now = 1_800_000_000;
+    let valid = json!({ "iss": ISSUER, "sub": "u1", "aud": "ahlt", "exp": now + 300, "nonce": "n1" });
+    let claims = oidc::validate_id_token(&token(valid.clone()), ISSUER, "ahlt", "n1", now).unwrap();
+    assert_eq!(claims.sub, "u1");
+
+    let mut listed = valid.clone();
+    listed["aud"] = json!(["other", "ahlt"]);
+    assert!(oidc::validate_id_token(&token(listed), ISSUER, "ahlt", "n1", now).is_ok());
+
+    assert!(oidc::validate_id_token(&token(valid.clone()), ISSUER, "ahlt", "n2", now).is_err());
+    assert!(oidc::validate_id_token(&token(valid.clone()), ISSUER, "other", "n1", now).is_err());
+    assert!(oidc::validate_id_token(&token(valid.clone()), "https://evil.example", "ahlt", "n1", now).is_err());
+    assert!(oidc::validate_id_token(&token(valid), ISSUER, "ahlt", "n1", now + 3600).is_err());
+    assert!(oidc::validate_id_token("not-a-jwt", ISSUER, "ahlt", "n1", now).is_err());
+}
+
+#[test]
+fn test_check_metadata_requires_https() {
+    let metadata = |issuer: &str, token_endpoint: &str| oidc::ProviderMetadata {
+        issuer: issuer.to_string(),
+        authorization_endpoint: format!("{}/authorize", issuer),
+        token_endpoint: token_endpoint.to_string(),
+    };
+    assert!(oidc::check_metadata(ISSUER, &metadata(ISSUER, "https://login.example.org/token")).is_ok());
+    assert!(oidc::check_metadata(ISSUER, &metadata(ISSUER, "http://login.example.org/token")).is_err());
+    assert!(oidc::check_metadata(ISSUER, &metadata("https://other.example.org", "https://other.example.org/token")).is_err());
+    let plain = "http://login.example.org";
+    assert!(oidc::check_metadata(plain, &metadata(plain, "https://login.example.org/token")).is_err());
+    assert!(oidc::is_https("HTTPS://login.example.org"));
+    assert!(!oidc::is_https("https:/login"));
+}
+
+#[test]
+fn test_username_base() {
+    assert_eq!(oidc::username_base(&claims("s", "kari@example.org", "Kari.N")), "kari_n");
+    assert_eq!(oidc::username_base(&claims("s", "kari@example.org", "")), "kari");
+    assert_eq!(oidc::username_base(&claims("s", "", "")), "sso_user");
+}
+
+#[tokio::test]
+async fn test_resolve_user_provisions_then_signs_in() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let cfg = config(true);
+
+    let first = oidc::resolve_user(pool, &cfg, &claims("sub-1", "kari@example.org", "kari")).await.unwrap();
+    let Resolution::Provisioned(id, username) = first else { panic!("expected provisioning") };
+    assert_eq!(username, "kari");
+    let created = user::find_by_username(pool, "kari").await.unwrap().unwrap();
+    assert_eq!(created.id, id);
+    assert_eq!(created.email, "kari@example.org");
+    assert!(created.password.is_empty());
+    assert!(created.role_id > 0);
+
+    let again = oidc::resolve_user(pool, &cfg, &claims("sub-1", "kari@example.org", "kari")).await.unwrap();
+    assert_eq!(again, Resolution::Linked(id, "kari".to_string()));
+
+    // Same preferred username, different person: a distinct account
+    let other = oidc::resolve_user(pool, &cfg, &claims("sub-2", "kari2@example.org", "kari")).await.unwrap();
+    assert!(matches!(other, Resolution::Provisioned(_, ref name) if name == "kari_2"));
+}
+
+#[tokio::test]
+async fn test_resolve_user_refuses_email_collisions_and_links_explicitly() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let local = insert_entity(pool, "user", "ola", "Ola").await;
+    insert_prop(pool, local, "email", "Ola@Example.org").await;
+
+    let ola = claims("sub-ola", "ola@example.org", "ola");
+    assert_eq!(oidc::resolve_user(pool, &config(true), &ola).await.unwrap(), Resolution::EmailInUse);
+    assert_eq!(
+        oidc::resolve_user(pool, &config(false), &claims("sub-x", "x@example.org", "x")).await.unwrap(),
+        Resolution::Unknown
+    );
+
+    // The local user links the identity from their account page
+    assert!(oidc::link(pool, &config(true), local, "sub-ola").await.unwrap());
+    assert_eq!(
+        oidc::resolve_user(pool, &config(false), &ola).await.unwrap(),
+        Resolution::Linked(local, "ola".to_string())
+    );
+
+    // Nobody else can claim it
+    let other = insert_entity(pool, "user", "kari", "Kari").await;
+    assert!(!oidc::link(pool, &config(true), other, "sub-ola").await.unwrap());
+}
@@ -0,0 +1,65 @@
+//! Opinion reaction tests — covers reactions and the sentiment summary.
+//!
+//! - One reaction per member per opinion; reacting again changes or withdraws it
+//! - Reactions are tallied per opinion and per agenda point
+
+mod common;
+
+use ahlt::models::opinion::{self, ReactionCounts};
+use common::*;
+
+#[tokio::test]
+async fn test_react_changes_and_withdraws() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let alice = insert_entity(pool, "user", "alice", "Alice").await;
+    let bob = insert_entity(pool, "user", "bob", "Bob").await;
+    let point = insert_entity(pool, "agenda_point", "budget", "Budget").await;
+    let coa = insert_entity(pool, "coa", "approve", "Approve").await;
+    let op = opinion::record_opinion(pool, point, alice, coa, "Approve it").await.unwrap();
+
+    assert_eq!(opinion::react(pool, op, bob, "support").await.unwrap().as_deref(), Some("support"));
+    assert_eq!(opinion::react(pool, op, bob, "oppose").await.unwrap().as_deref(), Some("oppose"));
+    let sentiment = opinion::find_sentiment(pool, point, bob).await.unwrap();
+    assert_eq!(sentiment.overall, ReactionCounts { support: 0, oppose: 1, neutral: 0 });
+    assert!(sentiment.is_mine(op, "oppose") && !sentiment.is_mine(op, "support"));
+
+    assert_eq!(opinion::react(pool, op, bob, "oppose").await.unwrap(), None);
+    let sentiment = opinion::find_sentiment(pool, point, bob).await.unwrap();
+    assert_eq!(sentiment.overall.total(), 0);
+    assert!(sentiment.mine.is_empty());
+
+    assert!(opinion::is_reaction("neutral") && !opinion::is_reaction("love"));
+}
+
+#[tokio::test]
+async fn test_sentiment_summary() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let alice = insert_entity(pool, "user", "alice", "Alice").await;
+    let bob = insert_entity(pool, "user", "bob", "Bob").await;
+    let carol = insert_entity(pool, "user", "carol", "Carol").await;
+    let point = insert_entity(pool, "agenda_point", "budget", "Budget").await;
+    let other_point = insert_entity(pool, "agenda_point", "travel", "Travel").await;
+    let approve = insert_entity(pool, "coa", "approve", "Approve").await;
+    let defer = insert_entity(pool, "coa", "defer", "Defer").await;
+
+    let by_alice = opinion::record_opinion(pool, point, alice, approve, "").await.unwrap();
+    let by_bob = opinion::record_opinion(pool, point, bob, defer, "").await.unwrap();
+    let elsewhere = opinion::record_opinion(pool, other_point, carol, approve, "").await.unwrap();
+
+    opinion::react(pool, by_alice, bob, "oppose").await.unwrap();
+    opinion::react(pool, by_alice, carol, "support").await.unwrap();
+    opinion::react(pool, by_bob, carol, "neutral").await.unwrap();
+    opinion::react(pool, by_bob, alice, "oppose").await.unwrap();
+    opinion::react(pool, elsewhere, alice, "support").await.unwrap();
+
+    let sentiment = opinion::find_sentiment(pool, point, carol).await.unwrap();
+    assert_eq!(sentiment.overall, ReactionCounts { support: 1, oppose: 2, neutral: 1 });
+    assert_eq!(sentiment.members, 3);
+    assert_eq!(sentiment.overall.percent("oppose"), 50);
+    assert_eq!(sentiment.for_opinion(by_alice), ReactionCounts { support: 1, oppose: 1, neutral: 0 });
+    assert_eq!(sentiment.for_opinion(by_bob).get("neutral"), 1);
+    assert_eq!(sentiment.mine.len(), 2);
+    assert_eq!(sentiment.for_opinion(elsewhere).total(), 0);
+}
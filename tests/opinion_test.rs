@@ -1,7 +1,8 @@
 //! Integration tests for the opinion model layer.
 //!
 //! Tests cover: record_opinion, find_opinions_for_agenda_point,
-//! find_opinion_by_user_and_agenda_point, update_opinion, record_decision.
+//! find_opinion_by_user_and_agenda_point, update_opinion, record_decision,
+//! opinion versions and the lock at decision time.
 
 mod common;
 
@@ -288,3 +289,65 @@ async fn test_record_decision() {
 
     println!("[PASS] test_record_decision");
 }
+
+#[tokio::test]
+async fn test_update_opinion_keeps_prior_versions() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+
+    let user_id = create_test_user(pool, "ver").await;
+    let (_tor_id, ap_id, coa1_id, coa2_id) =
+        create_ap_with_coas(pool, "ver", user_id).await;
+
+    let opinion_id = opinion::record_opinion(pool, ap_id, user_id, coa1_id, "First take")
+        .await
+        .unwrap();
+
+    // Resubmitting unchanged content is not a revision
+    opinion::update_opinion(pool, opinion_id, coa1_id, "First take").await.unwrap();
+    let unchanged = opinion::find_opinion_by_id(pool, opinion_id).await.unwrap().unwrap();
+    assert_eq!(unchanged.version, 1);
+    assert!(opinion::find_versions_for_agenda_point(pool, ap_id).await.unwrap().is_empty());
+
+    opinion::update_opinion(pool, opinion_id, coa2_id, "Second take").await.unwrap();
+    opinion::update_opinion(pool, opinion_id, coa2_id, "Third take").await.unwrap();
+
+    let current = opinion::find_opinion_by_id(pool, opinion_id).await.unwrap().unwrap();
+    assert_eq!(current.version, 3);
+    assert!(!current.revised_date.is_empty());
+
+    let versions = opinion::find_versions_for_agenda_point(pool, ap_id).await.unwrap();
+    assert_eq!(versions.len(), 2);
+    assert_eq!(versions[0].version, 2);
+    assert_eq!(versions[0].commentary, "Second take");
+    assert_eq!(versions[1].version, 1);
+    assert_eq!(versions[1].preferred_coa_id, coa1_id);
+    assert_eq!(versions[1].coa_title, "ver COA Alpha");
+}
+
+#[tokio::test]
+async fn test_record_decision_freezes_considered_versions() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+
+    let user_id = create_test_user(pool, "frz").await;
+    let (_tor_id, ap_id, coa1_id, coa2_id) =
+        create_ap_with_coas(pool, "frz", user_id).await;
+
+    let opinion_id = opinion::record_opinion(pool, ap_id, user_id, coa1_id, "Initial")
+        .await
+        .unwrap();
+    opinion::update_opinion(pool, opinion_id, coa2_id, "Revised").await.unwrap();
+
+    let before = opinion::find_opinions_for_agenda_point(pool, ap_id).await.unwrap();
+    assert_eq!(opinion::version_stamp(&before), format!("{}:2", opinion_id));
+    assert_eq!(before[0].considered_version, None);
+
+    opinion::record_decision(pool, ap_id, user_id, coa2_id, "Go with Beta").await.unwrap();
+
+    let ap = agenda_point::find_by_id(pool, ap_id).await.unwrap().unwrap();
+    assert!(opinion::opinions_locked(&ap.status));
+
+    let after = opinion::find_opinions_for_agenda_point(pool, ap_id).await.unwrap();
+    assert_eq!(after[0].considered_version, Some(2));
+}
@@ -0,0 +1,177 @@
+//! Outbound webhook tests — covers event filters, backoff and signed delivery.
+//!
+//! - Filters match exact event types and `prefix.*` patterns
+//! - Backoff doubles per failure up to the cap
+//! - A run queues matching events, signs the body and retries failures
+
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use ahlt::models::{domain_event, outbound_webhook::{self, OutboundWebhookInput}, webhook};
+use common::*;
+use serde_json::json;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+fn input<'a>(slug: &'a str, url: &'a str, events: &'a str) -> OutboundWebhookInput<'a> {
+    OutboundWebhookInput { slug, label: "Archive", url, events }
+}
+
+#[test]
+fn test_matches_filter() {
+    assert!(outbound_webhook::matches_filter("", "proposal.approved"));
+    assert!(outbound_webhook::matches_filter("proposal.approved", "proposal.approved"));
+    assert!(!outbound_webhook::matches_filter("proposal.approved", "proposal.rejected"));
+    assert!(outbound_webhook::matches_filter("meeting.confirmed, proposal.*", "proposal.rejected"));
+    assert!(!outbound_webhook::matches_filter("proposal.*", "meeting.confirmed"));
+    assert_eq!(
+        outbound_webhook::normalize_filter(" proposal.*,,meeting.confirmed, proposal.* "),
+        "proposal.*, meeting.confirmed"
+    );
+}
+
+#[test]
+fn test_validate_input() {
+    assert!(outbound_webhook::validate(&input("archive", "https://example.org/hook", "proposal.*")).is_empty());
+    assert!(!outbound_webhook::validate(&input("Archive", "https://example.org/hook", "")).is_empty());
+    assert!(!outbound_webhook::validate(&input("archive", "ftp://example.org", "")).is_empty());
+    assert!(!outbound_webhook::validate(&input("archive", "https:///path", "")).is_empty());
+    assert!(!outbound_webhook::validate(&input("archive", "https://example.org", "Proposal approved")).is_empty());
+    assert!(!outbound_webhook::validate(&input("archive", "https://example.org", ".*")).is_empty());
+}
+
+#[test]
+fn test_backoff_doubles_up_to_cap() {
+    assert_eq!(outbound_webhook::backoff_secs(1), 60);
+    assert_eq!(outbound_webhook::backoff_secs(2), 120);
+    assert_eq!(outbound_webhook::backoff_secs(4), 480);
+    assert_eq!(outbound_webhook::backoff_secs(30), 6 * 3600);
+}
+
+/// Stub receiver: records (signature header, body) and answers with the
+/// next queued status, or 200 once the queue is empty.
+async fn stub_receiver(received: Arc<Mutex<Vec<(String, String)>>>, statuses: Vec<u16>) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let statuses = Arc::new(Mutex::new(statuses));
+    tokio::spawn(async move {
+        loop {
+            let Ok((mut socket, _)) = listener.accept().await else { return };
+            let mut buf = vec![0u8; 16384];
+            let mut len = 0;
+            let (head, body) = loop {
+                let n = socket.read(&mut buf[len..]).await.unwrap();
+                len += n;
+                let text = String::from_utf8_lossy(&buf[..len]).to_string();
+                if let Some(end) = text.find("\r\n\r\n") {
+                    let body_len = text.lines()
+                        .find_map(|l| l.strip_prefix("Content-Length: "))
+                        .and_then(|v| v.trim().parse::<usize>().ok())
+                        .unwrap_or(0);
+                    if len >= end + 4 + body_len || n == 0 {
+                        break (text[..end].to_string(), text[end + 4..].to_string());
+                    }
+                }
+            };
+            let signature = head.lines()
+                .find_map(|l| l.strip_prefix("X-Ahlt-Signature: "))
+                .unwrap_or("")
+                .to_string();
+            received.lock().unwrap().push((signature, body));
+
+            let status = {
+                let mut queue = statuses.lock().unwrap();
+                if queue.is_empty() { 200 } else { queue.remove(0) }
+            };
+            let response = format!("HTTP/1.1 {} X\r\nContent-Length: 2\r\n\r\nok", status);
+            socket.write_all(response.as_bytes()).await.unwrap();
+            socket.shutdown().await.ok();
+        }
+    });
+    format!("http://{}/hook", addr)
+}
+
+async fn append_event(pool: &sqlx::PgPool, event_type: &str, aggregate_id: i64) -> i64 {
+    let mut tx = pool.begin().await.unwrap();
+    let id = domain_event::append(&mut tx, event_type, "proposal", aggregate_id, 0, json!({ "n": aggregate_id }))
+        .await
+        .unwrap();
+    tx.commit().await.unwrap();
+    id
+}
+
+#[tokio::test]
+async fn test_run_due_signs_filters_and_retries() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let admin = insert_entity(pool, "user", "admin", "Admin").await;
+
+    // Events before the webhook exists are not delivered
+    append_event(pool, "proposal.approved", 1).await;
+
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let url = stub_receiver(received.clone(), vec![503]).await;
+    let id = outbound_webhook::create(pool, &input("archive", &url, "proposal.approved"), admin).await.unwrap();
+    let hook = outbound_webhook::find_by_id(pool, id).await.unwrap().unwrap();
+    assert_eq!(hook.secret.len(), 64);
+
+    let approved = append_event(pool, "proposal.approved", 2).await;
+    append_event(pool, "proposal.rejected", 3).await;
+
+    // First send is refused and rescheduled
+    assert_eq!(outbound_webhook::run_due(pool).await.unwrap(), (0, 1));
+    let deliveries = outbound_webhook::find_deliveries(pool, Some(id), None, 10).await.unwrap();
+    assert_eq!(deliveries.len(), 1);
+    let delivery = &deliveries[0];
+    assert_eq!(delivery.event_id, approved);
+    assert_eq!(delivery.status, "pending");
+    assert_eq!(delivery.attempts, 1);
+    assert_eq!(delivery.response_status, 503);
+    assert!(delivery.last_error.starts_with("HTTP 503"));
+
+    // Not yet due again
+    assert_eq!(outbound_webhook::run_due(pool).await.unwrap(), (0, 0));
+
+    // A manual retry sends it now
+    assert!(outbound_webhook::requeue(pool, delivery.id).await.unwrap());
+    assert_eq!(outbound_webhook::run_due(pool).await.unwrap(), (1, 0));
+    let delivered = outbound_webhook::find_delivery(pool, delivery.id).await.unwrap().unwrap();
+    assert_eq!(delivered.status, "delivered");
+    assert_eq!(delivered.response_status, 200);
+    assert!(!delivered.can_retry());
+    assert!(!outbound_webhook::requeue(pool, delivery.id).await.unwrap());
+
+    // Both sends carried the same body, signed with the webhook's secret
+    let received = received.lock().unwrap().clone();
+    assert_eq!(received.len(), 2);
+    for (signature, body) in &received {
+        assert_eq!(body, &delivered.body);
+        assert!(webhook::verify_signature(&hook.secret, body.as_bytes(), signature));
+    }
+    let sent: serde_json::Value = serde_json::from_str(&received[0].1).unwrap();
+    assert_eq!(sent["event_type"], "proposal.approved");
+}
+
+#[tokio::test]
+async fn test_paused_webhook_holds_deliveries_and_delete_clears_log() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let admin = insert_entity(pool, "user", "admin", "Admin").await;
+
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let url = stub_receiver(received.clone(), Vec::new()).await;
+    let id = outbound_webhook::create(pool, &input("all", &url, ""), admin).await.unwrap();
+    assert!(outbound_webhook::set_active(pool, id, false).await.unwrap());
+
+    append_event(pool, "meeting.confirmed", 1).await;
+    assert_eq!(outbound_webhook::run_due(pool).await.unwrap(), (0, 0));
+    assert!(received.lock().unwrap().is_empty());
+
+    // Resuming delivers what was recorded while paused
+    outbound_webhook::set_active(pool, id, true).await.unwrap();
+    assert_eq!(outbound_webhook::run_due(pool).await.unwrap(), (1, 0));
+    assert_eq!(received.lock().unwrap().len(), 1);
+
+    assert!(outbound_webhook::delete(pool, id).await.unwrap());
+    assert!(outbound_webhook::find_deliveries(pool, None, None, 10).await.unwrap().is_empty());
+}
@@ -0,0 +1,128 @@
+//! Ownership transfer tests — covers offering, accepting and declining.
+//!
+//! - Owners are found per kind: submitter, chair holder, creator
+//! - Requests are validated (new owner, vacant chair position, one pending)
+//! - Accepting moves ownership; declining and cancelling leave it alone
+//! - Incoming and outgoing lists show the right transfers
+
+mod common;
+
+use ahlt::models::{entity, ownership_transfer, relation, tor};
+use common::*;
+use sqlx::PgPool;
+
+async fn position(pool: &PgPool, tor_id: i64, name: &str, label: &str, holder: Option<i64>) -> i64 {
+    let id = insert_entity(pool, "tor_function", name, label).await;
+    relation::create(pool, "belongs_to_tor", id, tor_id).await.unwrap();
+    match holder {
+        Some(user) => tor::assign_to_position(pool, user, id, "mandatory").await.unwrap(),
+        None => insert_prop(pool, id, "membership_type", "mandatory").await,
+    }
+    id
+}
+
+#[tokio::test]
+async fn test_proposal_and_document_transfers() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let board = insert_entity(pool, "tor", "board", "Board").await;
+    let alice = insert_entity(pool, "user", "alice", "Alice").await;
+    let bob = insert_entity(pool, "user", "bob", "Bob").await;
+    let admin = insert_entity(pool, "user", "admin_user", "Admin").await;
+    let proposal = insert_entity(pool, "proposal", "budget", "Budget 2027").await;
+    insert_prop(pool, proposal, "submitted_by_id", &alice.to_string()).await;
+    relation::create(pool, "submitted_to", proposal, board).await.unwrap();
+    let document = insert_entity(pool, "document", "charter", "Charter").await;
+    insert_prop(pool, document, "created_by_id", &alice.to_string()).await;
+
+    let item = ownership_transfer::find_item(pool, proposal).await.unwrap().unwrap();
+    assert_eq!((item.kind.as_str(), item.owner_id, item.tor_id), ("proposal", alice, board));
+    assert_eq!(item.owner_name, "Alice");
+    assert_eq!(item.path(), format!("/tor/{}/proposals/{}", board, proposal));
+    assert!(ownership_transfer::find_item(pool, alice).await.unwrap().is_none());
+
+    let errors = ownership_transfer::validate(pool, &item, 0).await.unwrap();
+    assert_eq!(errors, vec!["Choose the new owner".to_string()]);
+    let errors = ownership_transfer::validate(pool, &item, alice).await.unwrap();
+    assert_eq!(errors, vec!["Alice already owns this proposal".to_string()]);
+    assert!(ownership_transfer::validate(pool, &item, bob).await.unwrap().is_empty());
+
+    // Nothing changes until the new owner accepts
+    let id = ownership_transfer::request(pool, &item, bob, admin, " please take over ").await.unwrap();
+    let transfer = ownership_transfer::find_by_id(pool, id).await.unwrap().unwrap();
+    assert!(transfer.is_pending());
+    assert_eq!((transfer.from_name.as_str(), transfer.to_name.as_str(), transfer.requested_by_name.as_str()), ("Alice", "Bob", "Admin"));
+    assert_eq!(transfer.note, "please take over");
+    assert!(transfer.can_cancel(admin) && transfer.can_cancel(alice) && !transfer.can_cancel(bob));
+    assert_eq!(entity::get_property(pool, proposal, "submitted_by_id").await.unwrap(), Some(alice.to_string()));
+    assert_eq!(
+        ownership_transfer::validate(pool, &item, bob).await.unwrap(),
+        vec!["A transfer of this proposal is already waiting for acceptance".to_string()]
+    );
+
+    assert_eq!(ownership_transfer::find_incoming(pool, bob).await.unwrap().len(), 1);
+    assert_eq!(ownership_transfer::find_outgoing(pool, alice).await.unwrap().len(), 1);
+    assert_eq!(ownership_transfer::find_outgoing(pool, admin).await.unwrap().len(), 1);
+    assert!(ownership_transfer::find_outgoing(pool, bob).await.unwrap().is_empty());
+
+    assert!(ownership_transfer::accept(pool, &transfer).await.unwrap());
+    assert_eq!(entity::get_property(pool, proposal, "submitted_by_id").await.unwrap(), Some(bob.to_string()));
+    let transfer = ownership_transfer::find_by_id(pool, id).await.unwrap().unwrap();
+    assert_eq!(transfer.status, ownership_transfer::STATUS_ACCEPTED);
+    assert!(!transfer.decided_at.is_empty());
+    assert!(ownership_transfer::find_pending_for_item(pool, proposal).await.unwrap().is_none());
+
+    // A declined document transfer keeps its creator
+    let item = ownership_transfer::find_item(pool, document).await.unwrap().unwrap();
+    assert_eq!((item.owner_id, item.tor_id), (alice, 0));
+    let id = ownership_transfer::request(pool, &item, bob, alice, "").await.unwrap();
+    ownership_transfer::decline(pool, id).await.unwrap();
+    let transfer = ownership_transfer::find_by_id(pool, id).await.unwrap().unwrap();
+    assert_eq!(transfer.status, ownership_transfer::STATUS_DECLINED);
+    assert!(!transfer.can_cancel(alice));
+    assert_eq!(entity::get_property(pool, document, "created_by_id").await.unwrap(), Some(alice.to_string()));
+    assert!(ownership_transfer::validate(pool, &item, bob).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_tor_chair_transfer() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let board = insert_entity(pool, "tor", "board", "Board").await;
+    let empty = insert_entity(pool, "tor", "empty", "Empty").await;
+    let alice = insert_entity(pool, "user", "alice", "Alice").await;
+    let bob = insert_entity(pool, "user", "bob", "Bob").await;
+    let carol = insert_entity(pool, "user", "carol", "Carol").await;
+    position(pool, board, "board_vice_chair", "Vice Chair", Some(carol)).await;
+    let chair = position(pool, board, "board_chair", "Chair", Some(alice)).await;
+    position(pool, board, "board_member", "Member", Some(bob)).await;
+
+    let item = ownership_transfer::find_item(pool, board).await.unwrap().unwrap();
+    assert_eq!((item.owner_id, item.position_id), (alice, chair));
+    assert_eq!(item.describe(), "the chair of ToR \u{2018}Board\u{2019}");
+
+    let no_chair = ownership_transfer::find_item(pool, empty).await.unwrap().unwrap();
+    assert_eq!(no_chair.owner_id, 0);
+    assert_eq!(
+        ownership_transfer::validate(pool, &no_chair, bob).await.unwrap(),
+        vec!["This ToR has no chair position to transfer".to_string()]
+    );
+
+    let id = ownership_transfer::request(pool, &item, bob, alice, "").await.unwrap();
+    let transfer = ownership_transfer::find_by_id(pool, id).await.unwrap().unwrap();
+    assert!(ownership_transfer::accept(pool, &transfer).await.unwrap());
+
+    let members = tor::find_members(pool, board).await.unwrap();
+    let holder = members.iter().find(|m| m.position_id == chair).unwrap();
+    assert_eq!(holder.holder_id, Some(bob));
+    assert_eq!(holder.membership_type, "mandatory");
+    assert_eq!(members.iter().filter(|m| m.holder_id == Some(alice)).count(), 0);
+
+    // A withdrawn offer frees the item for a new one
+    let item = ownership_transfer::find_item(pool, board).await.unwrap().unwrap();
+    let id = ownership_transfer::request(pool, &item, carol, bob, "").await.unwrap();
+    ownership_transfer::cancel(pool, id).await.unwrap();
+    assert!(ownership_transfer::find_pending_for_item(pool, board).await.unwrap().is_none());
+    let item = ownership_transfer::find_item(pool, board).await.unwrap().unwrap();
+    assert_eq!(item.owner_id, bob);
+}
@@ -0,0 +1,143 @@
+//! Password policy tests — covers the configurable rules for new passwords.
+//!
+//! - Length, character classes, banned passwords and the username are checked
+//! - Settings load into the policy, never below the built-in minimum
+//! - Recent passwords cannot be reused; replaced hashes are kept per user
+//! - Passwords older than the maximum age expire
+//! - An expired password keeps the user on the account page, but not from signing out
+
+mod common;
+
+use ahlt::auth::password;
+use ahlt::auth::password_policy::{self, PasswordPolicy};
+use ahlt::models::{password_history, user};
+use common::*;
+use sqlx::PgPool;
+
+async fn set(pool: &PgPool, name: &str, value: &str) {
+    let id = insert_entity(pool, "setting", name, name).await;
+    insert_prop(pool, id, "value", value).await;
+}
+
+#[test]
+fn test_check_rules() {
+    let policy = PasswordPolicy {
+        min_length: 10,
+        min_classes: 3,
+        banned: password_policy::parse_banned("Password123!,\n qwerty12345 "),
+        ..Default::default()
+    };
+    assert!(policy.check("Correct-Horse9", "alice").is_empty());
+    assert_eq!(policy.check("", "alice"), vec!["Password is required"]);
+    assert_eq!(policy.check("Sh0rt!", "alice"), vec!["Password must be at least 8 characters"]);
+    assert_eq!(policy.check("Abcdef12!", "alice"), vec!["Password must be at least 10 characters"]);
+    assert_eq!(policy.check("correcthorse9", "alice").len(), 1);
+    assert_eq!(policy.check("PASSWORD123!", "alice"), vec!["This password is too common; choose another"]);
+    assert_eq!(policy.check("Alice-2026-spring", "ALICE"), vec!["Password must not contain the username"]);
+
+    assert_eq!(password_policy::classes("abc"), 1);
+    assert_eq!(password_policy::classes("aB3 "), 4);
+    assert_eq!(PasswordPolicy::default().describe(), "At least 8 characters.");
+}
+
+#[tokio::test]
+async fn test_load_from_settings() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    assert_eq!(PasswordPolicy::load(pool).await, PasswordPolicy::default());
+
+    set(pool, "auth.password_min_length", "4").await;
+    set(pool, "auth.password_min_classes", "2").await;
+    set(pool, "auth.password_banned", "letmein123").await;
+    set(pool, "auth.password_history", "3").await;
+    set(pool, "auth.password_max_age_days", "90").await;
+    let policy = PasswordPolicy::load(pool).await;
+    assert_eq!(policy, PasswordPolicy {
+        min_length: 8,
+        min_classes: 2,
+        banned: vec!["letmein123".to_string()],
+        history: 3,
+        max_age_days: 90,
+    });
+}
+
+#[tokio::test]
+async fn test_reuse_and_expiry() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let hash = |p: &str| password::hash_password(p).unwrap();
+    let alice = user::create(pool, &user::NewUser {
+        username: "alice".to_string(),
+        password: hash("first-pass-1"),
+        email: "alice@example.com".to_string(),
+        display_name: "Alice".to_string(),
+    }).await.unwrap();
+    user::update_password(pool, alice, &hash("second-pass-2")).await.unwrap();
+    user::update_password(pool, alice, &hash("third-pass-3")).await.unwrap();
+    assert_eq!(password_history::recent(pool, alice, 10).await.unwrap().len(), 2);
+
+    let policy = PasswordPolicy { history: 2, ..Default::default() };
+    assert!(policy.is_reused(pool, alice, "third-pass-3").await.unwrap());
+    assert!(policy.is_reused(pool, alice, "second-pass-2").await.unwrap());
+    assert!(!policy.is_reused(pool, alice, "first-pass-1").await.unwrap());
+    assert_eq!(
+        policy.validate(pool, "second-pass-2", "alice", Some(alice)).await.unwrap(),
+        vec!["Password must not be one of the last 2 passwords"]
+    );
+    assert!(policy.validate(pool, "second-pass-2", "bob", None).await.unwrap().is_empty());
+    assert!(!PasswordPolicy::default().is_reused(pool, alice, "third-pass-3").await.unwrap());
+
+    // Only the newest hashes are kept
+    for i in 0..password_history::KEPT {
+        user::update_password(pool, alice, &format!("not-a-real-hash-{i}")).await.unwrap();
+    }
+    assert_eq!(password_history::recent(pool, alice, 100).await.unwrap().len() as i64, password_history::KEPT);
+
+    let policy = PasswordPolicy { max_age_days: 30, ..Default::default() };
+    assert!(!policy.is_expired(pool, alice).await.unwrap());
+    sqlx::query("UPDATE password_history SET created_at = NOW() - INTERVAL '31 days'")
+        .execute(pool)
+        .await
+        .unwrap();
+    assert!(policy.is_expired(pool, alice).await.unwrap());
+    assert!(!PasswordPolicy::default().is_expired(pool, alice).await.unwrap());
+}
+
+#[actix_web::test]
+async fn test_logout_while_expired() {
+    use actix_session::{Session, SessionMiddleware, storage::CookieSessionStore};
+    use actix_web::{App, HttpResponse, cookie::Key, http::StatusCode, middleware, test, web};
+    use ahlt::auth::middleware::require_auth;
+
+    assert!(password_policy::allowed_while_expired("/logout"));
+    assert!(password_policy::allowed_while_expired("/static/css/style.css"));
+    assert!(!password_policy::allowed_while_expired("/dashboard"));
+
+    let app = test::init_service(
+        App::new()
+            .wrap(SessionMiddleware::new(CookieSessionStore::default(), Key::generate()))
+            .route("/signin", web::get().to(|session: Session| async move {
+                session.insert("user_id", 1_i64).unwrap();
+                session.insert(password_policy::EXPIRED_KEY, true).unwrap();
+                HttpResponse::Ok().finish()
+            }))
+            .service(
+                web::scope("")
+                    .wrap(middleware::from_fn(require_auth))
+                    .route("/logout", web::post().to(|| async { HttpResponse::Ok().finish() }))
+                    .route("/dashboard", web::get().to(|| async { HttpResponse::Ok().finish() })),
+            ),
+    )
+    .await;
+
+    let res = test::call_service(&app, test::TestRequest::get().uri("/signin").to_request()).await;
+    let cookie = res.response().cookies().next().unwrap().into_owned();
+
+    let req = test::TestRequest::get().uri("/dashboard").cookie(cookie.clone()).to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::SEE_OTHER);
+    assert_eq!(res.headers().get("Location").unwrap(), "/account");
+
+    let req = test::TestRequest::post().uri("/logout").cookie(cookie).to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+}
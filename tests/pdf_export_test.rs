@@ -0,0 +1,130 @@
+//! PDF/A export tests — covers the archival PDF of approved minutes.
+//!
+//! - The file structure is sound: header, cross-reference offsets, trailer ID
+//! - PDF/A-2b identification, output intent and embedded font subsets
+//! - ToR, meeting number and approval date travel in the XMP metadata
+//! - Long minutes flow onto several pages
+//! - Unchanged minutes export to identical bytes
+
+mod common;
+
+use ahlt::export::pdf;
+use ahlt::models::minutes::{self, Minutes, MinutesSection};
+use ahlt::models::meeting;
+use ahlt::warnings::clock;
+use chrono::{TimeZone, Utc};
+use common::*;
+
+fn minutes() -> Minutes {
+    Minutes {
+        id: 11,
+        name: "minutes_7".to_string(),
+        label: "Minutes — Board & Co".to_string(),
+        status: "approved".to_string(),
+        generated_date: "2026-03-05".to_string(),
+        meeting_id: 7,
+        meeting_name: "board_2026_03".to_string(),
+        approved_by: "alice".to_string(),
+        approved_date: "2026-03-10".to_string(),
+        distribution_list: r#"["all-staff"]"#.to_string(),
+        structured_attendance: r#"[{"name":"Alice","status":"present"}]"#.to_string(),
+        structured_action_items: r#"[{"description":"Send (budget)","responsible":"Bob","status":"open"}]"#.to_string(),
+        circulated_at: String::new(),
+    }
+}
+
+fn section(label: &str, content: &str) -> MinutesSection {
+    MinutesSection {
+        id: 1,
+        name: "decisions_11".to_string(),
+        label: label.to_string(),
+        section_type: "decisions".to_string(),
+        sequence_order: 1,
+        content: content.to_string(),
+        is_auto_generated: false,
+        circulated_content: None,
+    }
+}
+
+fn text(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+/// Every cross-reference entry points at the object it names.
+fn assert_xref(bytes: &[u8]) {
+    let at = bytes.windows(9).rposition(|w| w == b"startxref").unwrap();
+    let startxref: usize = text(&bytes[at + 9..]).split_whitespace().next().unwrap().parse().unwrap();
+    assert!(bytes[startxref..].starts_with(b"xref"));
+    let table = text(&bytes[startxref..]);
+    let count: usize = table.lines().nth(1).unwrap().split_whitespace().nth(1).unwrap().parse().unwrap();
+    for (n, entry) in table.lines().skip(3).take(count - 1).enumerate() {
+        let offset: usize = entry[..10].parse().unwrap();
+        assert!(bytes[offset..].starts_with(format!("{} 0 obj", n + 1).as_bytes()), "object {} misplaced", n + 1);
+    }
+}
+
+#[tokio::test]
+async fn test_minutes_pdf_structure() {
+    let at = Utc.with_ymd_and_hms(2026, 3, 11, 9, 0, 0).unwrap();
+    let long = (1..=120).map(|n| format!("Item {n}: the board discussed the matter at length.")).collect::<Vec<_>>().join("\n");
+    let sections = vec![section("Decisions", "Budget approved.\nReview in Q3."), section("Discussion", &long)];
+
+    let bytes = clock::with_fixed_now(at, async { pdf::render_minutes(&minutes(), None, &sections, None) }).await;
+    assert!(bytes.starts_with(b"%PDF-1.7\n"));
+    assert!(bytes.ends_with(b"%%EOF\n"));
+    assert_xref(&bytes);
+
+    let doc = text(&bytes);
+    assert!(doc.contains("/ID [<"));
+    assert!(doc.contains("/S /GTS_PDFA1"));
+    assert!(doc.contains("/FontFile2"));
+    assert!(doc.contains("+DejaVuSans /"), "regular font subset missing");
+    assert!(doc.contains("+DejaVuSans-Bold /"), "bold font subset missing");
+    let pages: usize = doc.split("/Type /Pages").nth(1).unwrap()
+        .split("/Count ").nth(1).unwrap()
+        .split(|c: char| !c.is_ascii_digit()).next().unwrap().parse().unwrap();
+    assert!(pages > 1, "long minutes fit on {pages} page");
+
+    assert!(doc.contains("<pdfaid:part>2</pdfaid:part>"));
+    assert!(doc.contains("<pdfaid:conformance>B</pdfaid:conformance>"));
+    assert!(doc.contains("<xmp:CreateDate>2026-03-11T09:00:00Z</xmp:CreateDate>"));
+    assert!(doc.contains("<ahlt:approvalDate>2026-03-10</ahlt:approvalDate>"));
+    assert!(doc.contains("<dc:title><rdf:Alt><rdf:li xml:lang=\"x-default\">Minutes — Board &amp; Co</rdf:li></rdf:Alt></dc:title>"));
+    assert!(!doc.contains("<dc:creator>"), "no ToR to credit");
+    assert!(doc.contains("/CreationDate (D:20260311090000+00'00')"));
+
+    let again = clock::with_fixed_now(at, async { pdf::render_minutes(&minutes(), None, &sections, None) }).await;
+    assert_eq!(again, bytes);
+}
+
+#[tokio::test]
+async fn test_minutes_pdf_metadata_from_meeting() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let board = insert_entity(pool, "tor", "board", "Board").await;
+    let meeting_id = meeting::create(pool, board, "2026-10-01", "Board", "Room 1", "", "2026/14", "", "", "alice", "bob")
+        .await.unwrap();
+    let minutes_id = minutes::generate_scaffold(pool, meeting_id, board, "Board 2026-10-01").await.unwrap();
+    minutes::mark_approved(pool, minutes_id, "alice", "2026-10-08").await.unwrap();
+    let m = minutes::find_by_id(pool, minutes_id).await.unwrap().unwrap();
+
+    let meeting = meeting::find_by_id(pool, meeting_id).await.unwrap().unwrap();
+    let info = pdf::minutes_info(&m, Some(&meeting));
+    assert_eq!(info.author, "Board");
+    let property = |key: &str| info.properties.iter().find(|p| p.0 == key).map(|p| p.2.clone());
+    assert_eq!(property("tor").as_deref(), Some("Board"));
+    assert_eq!(property("meetingNumber").as_deref(), Some("2026/14"));
+    assert_eq!(property("meetingDate").as_deref(), Some("2026-10-01"));
+    assert_eq!(property("approvalDate").as_deref(), Some("2026-10-08"));
+
+    let at = Utc.with_ymd_and_hms(2026, 10, 9, 12, 0, 0).unwrap();
+    let bytes = clock::with_fixed_now(at, pdf::minutes(pool, &m, None)).await.unwrap();
+    assert_xref(&bytes);
+    let doc = text(&bytes);
+    assert!(doc.contains("<ahlt:tor>Board</ahlt:tor>"));
+    assert!(doc.contains("<ahlt:meetingNumber>2026/14</ahlt:meetingNumber>"));
+    assert!(doc.contains("<ahlt:approvalDate>2026-10-08</ahlt:approvalDate>"));
+    assert!(doc.contains("<rdf:li>Board</rdf:li>"));
+    assert!(doc.contains(pdf::XMP_NAMESPACE));
+    assert_eq!(pdf::minutes_file_name(&m), format!("minutes-{}.pdf", m.meeting_name));
+}
@@ -0,0 +1,157 @@
+//! Print layout tests — covers the `?print=1` variants of the agenda, minutes
+//! and decision register.
+//!
+//! - Print pages carry no navigation and load the print stylesheet
+//! - Signature blocks name the chair and secretary when recorded
+//! - Unapproved minutes are marked as draft
+
+use ahlt::handlers::meeting_handlers::PrintQuery;
+use ahlt::models::branding::Branding;
+use ahlt::models::meeting::{MeetingAgendaPoint, MeetingDetail};
+use ahlt::models::minutes::{Minutes, MinutesSection};
+use ahlt::models::opinion::DecisionRegisterEntry;
+use ahlt::templates_structs::{
+    AgendaPrintTemplate, DecisionRegisterPrintTemplate, MinutesPrintTemplate, PrintMeta,
+};
+use askama::Template;
+
+fn meta() -> PrintMeta {
+    PrintMeta {
+        branding: Branding { footer_text: "Acme Corp".to_string(), ..Branding::default() },
+        app_name: "Ahlt".to_string(),
+        printed_at: "2026-03-01 09:00".to_string(),
+    }
+}
+
+fn meeting() -> MeetingDetail {
+    MeetingDetail {
+        id: 7,
+        name: "board_2026_03".to_string(),
+        label: "Board — March".to_string(),
+        meeting_date: "2026-03-05".to_string(),
+        status: "confirmed".to_string(),
+        location: "Room 1".to_string(),
+        notes: String::new(),
+        tor_id: 3,
+        tor_name: "board".to_string(),
+        tor_label: "Board".to_string(),
+        meeting_number: String::new(),
+        classification: String::new(),
+        vtc_details: String::new(),
+        chair_user_id: "alice".to_string(),
+        secretary_user_id: String::new(),
+        roll_call_data: "[]".to_string(),
+        carried_over_data: "[]".to_string(),
+    }
+}
+
+#[test]
+fn test_print_query() {
+    let query = |print: Option<&str>| PrintQuery { print: print.map(str::to_string) };
+    assert!(query(Some("1")).is_print());
+    assert!(query(Some("true")).is_print());
+    assert!(!query(Some("0")).is_print());
+    assert!(!query(None).is_print());
+}
+
+#[test]
+fn test_agenda_print_layout() {
+    let html = AgendaPrintTemplate {
+        meta: meta(),
+        meeting: meeting(),
+        agenda_points: vec![MeetingAgendaPoint {
+            id: 1,
+            name: "budget".to_string(),
+            label: "Budget <2027>".to_string(),
+            item_type: "decision".to_string(),
+            status: "scheduled".to_string(),
+        }],
+    }
+    .render()
+    .unwrap();
+
+    assert!(html.contains("/static/css/print.css"));
+    assert!(!html.contains("navbar"));
+    assert!(!html.contains("/static/css/style.css"));
+    assert!(html.contains("Budget &#60;2027&#62;") || html.contains("Budget &lt;2027&gt;"));
+    assert!(html.contains("For decision"));
+    assert!(html.contains("Chair: alice"));
+    assert!(html.contains(r#"<div class="print-signature__role">Secretary</div>"#));
+    assert!(html.contains("Acme Corp · Printed 2026-03-01 09:00"));
+}
+
+#[test]
+fn test_minutes_and_register_print_layout() {
+    let minutes = Minutes {
+        id: 9,
+        name: "minutes_7".to_string(),
+        label: "Minutes — Board March".to_string(),
+        status: "draft".to_string(),
+        generated_date: "2026-03-05".to_string(),
+        meeting_id: 7,
+        meeting_name: "Board — March".to_string(),
+        approved_by: String::new(),
+        approved_date: String::new(),
+        distribution_list: "[]".to_string(),
+        structured_attendance: "[]".to_string(),
+        structured_action_items: "[]".to_string(),
+        circulated_at: String::new(),
+    };
+    let section = MinutesSection {
+        id: 1,
+        name: "decisions".to_string(),
+        label: "Decisions".to_string(),
+        section_type: "decisions".to_string(),
+        sequence_order: 0,
+        content: "Approved\nbudget".to_string(),
+        is_auto_generated: true,
+        circulated_content: None,
+    };
+    let mut tmpl = MinutesPrintTemplate {
+        meta: meta(),
+        minutes,
+        meeting: Some(meeting()),
+        sections: vec![section],
+    };
+    let html = tmpl.render().unwrap();
+    assert!(html.contains("Draft — not yet approved"));
+    assert!(html.contains("Approved<br"));
+    assert!(html.contains("Chair: alice"));
+
+    tmpl.minutes.status = "approved".to_string();
+    tmpl.minutes.approved_date = "2026-03-10".to_string();
+    tmpl.meeting = None;
+    let html = tmpl.render().unwrap();
+    assert!(!html.contains("not yet approved"));
+    assert!(html.contains("2026-03-10"));
+    assert!(html.contains(r#"<div class="print-signature__role">Chair</div>"#));
+
+    let html = DecisionRegisterPrintTemplate {
+        meta: meta(),
+        tor_id: 3,
+        tor_label: "Board".to_string(),
+        decisions: vec![DecisionRegisterEntry {
+            id: 1,
+            agenda_point_id: 2,
+            agenda_point_title: "Budget".to_string(),
+            tor_id: 3,
+            tor_label: "Board".to_string(),
+            selected_coa_title: "Approve".to_string(),
+            decision_rationale: "Within limits".to_string(),
+            decided_by_id: 9,
+            decided_by_name: "Alice".to_string(),
+            decided_by_position: "Chair".to_string(),
+            on_behalf_of_name: String::new(),
+            decided_date: "2026-03-05".to_string(),
+            citation_id: "BOARD-2026-001".to_string(),
+            content_hash: "ab".repeat(32),
+        }],
+    }
+    .render()
+    .unwrap();
+    assert!(html.contains("<thead>"));
+    assert!(html.contains("Within limits"));
+    assert!(html.contains("Alice (Chair)"));
+    assert!(html.contains("BOARD-2026-001"));
+    assert!(html.contains("Certified by the Secretary"));
+}
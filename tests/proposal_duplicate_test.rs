@@ -0,0 +1,63 @@
+//! Proposal duplicate detection tests.
+//!
+//! - Proposals sharing title and description terms in the same ToR are found
+//! - Stemming matches different word forms; unrelated proposals are not found
+//! - Proposals in other ToRs are ignored
+//! - Linked proposals show up on both sides
+
+mod common;
+
+use ahlt::models::proposal;
+use common::*;
+
+async fn create(pool: &sqlx::PgPool, tor_id: i64, user: i64, title: &str, description: &str) -> i64 {
+    proposal::create(pool, tor_id, title, description, "", user, "2026-03-01", None).await.unwrap()
+}
+
+#[tokio::test]
+async fn test_find_similar_in_tor() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let user = insert_entity(pool, "user", "alice", "Alice").await;
+    let board = insert_entity(pool, "tor", "board", "Board").await;
+    let audit = insert_entity(pool, "tor", "audit", "Audit").await;
+
+    let projector = create(pool, board, user, "Replace the projector in the board room",
+        "The projector in the board room is failing and should be replaced.").await;
+    create(pool, board, user, "Annual budget review", "Review the spending plan for next year.").await;
+    create(pool, audit, user, "Replace projector in board room", "Projector replacement.").await;
+
+    let similar = proposal::find_similar(pool, board, "Replacing board room projectors",
+        "Buy a new projector for the board room.").await.unwrap();
+    assert_eq!(similar.iter().map(|s| s.id).collect::<Vec<_>>(), vec![projector]);
+    assert!(similar[0].score >= proposal::SIMILARITY_THRESHOLD && similar[0].score <= 1.0);
+    assert_eq!(similar[0].status, "draft");
+
+    let none = proposal::find_similar(pool, board, "Catering for the summer meeting",
+        "Order lunch for attendees.").await.unwrap();
+    assert!(none.is_empty());
+    assert!(proposal::find_similar(pool, board, "the", "and of").await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_link_related_proposals() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let user = insert_entity(pool, "user", "alice", "Alice").await;
+    let board = insert_entity(pool, "tor", "board", "Board").await;
+    let audit = insert_entity(pool, "tor", "audit", "Audit").await;
+
+    let original = create(pool, board, user, "Adopt remote voting", "Allow votes by video call.").await;
+    let follow_up = create(pool, board, user, "Adopt remote voting for committees", "Extend to committees.").await;
+    let elsewhere = create(pool, audit, user, "Adopt remote voting", "Audit committee version.").await;
+
+    assert!(proposal::belongs_to_tor(pool, original, board).await.unwrap());
+    assert!(!proposal::belongs_to_tor(pool, elsewhere, board).await.unwrap());
+
+    proposal::link_related(pool, follow_up, original).await.unwrap();
+    let from_new = proposal::find_related(pool, follow_up).await.unwrap();
+    assert_eq!(from_new.iter().map(|r| (r.id, r.title.as_str())).collect::<Vec<_>>(), vec![(original, "Adopt remote voting")]);
+    let from_original = proposal::find_related(pool, original).await.unwrap();
+    assert_eq!(from_original.iter().map(|r| r.id).collect::<Vec<_>>(), vec![follow_up]);
+    assert!(proposal::find_related(pool, elsewhere).await.unwrap().is_empty());
+}
@@ -0,0 +1,99 @@
+//! Proposal intake form tests — covers per-ToR sections and attachments.
+//!
+//! - The configuration round-trips and defaults to the standard form
+//! - Section answers arrive as `section_<key>` form fields
+//! - Required sections and attachments block submitting, not saving drafts
+
+mod common;
+
+use std::collections::HashMap;
+
+use ahlt::models::{document, proposal};
+use ahlt::models::proposal::{IntakeSection, ProposalForm, ProposalIntake};
+use common::*;
+
+fn intake() -> ProposalIntake {
+    ProposalIntake {
+        guidance: "Proposals need a costing.".to_string(),
+        sections: vec![
+            IntakeSection {
+                key: proposal::section_key("Financial impact"),
+                label: "Financial impact".to_string(),
+                help: "Costs and savings".to_string(),
+                required: true,
+            },
+            IntakeSection {
+                key: proposal::section_key("Legal review (if any)"),
+                label: "Legal review (if any)".to_string(),
+                help: String::new(),
+                required: false,
+            },
+        ],
+        min_attachments: 1,
+        attachment_help: "Attach the cost estimate.".to_string(),
+    }
+}
+
+#[tokio::test]
+async fn test_intake_configuration_and_answers() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let user = insert_entity(pool, "user", "alice", "Alice").await;
+    let board = insert_entity(pool, "tor", "board", "Board").await;
+
+    assert!(proposal::find_intake(pool, board).await.unwrap().is_standard());
+    let intake = intake();
+    assert_eq!(intake.sections[1].key, "legal_review_if_any");
+    proposal::save_intake(pool, board, &intake).await.unwrap();
+    assert_eq!(proposal::find_intake(pool, board).await.unwrap(), intake);
+
+    let form: ProposalForm = serde_urlencoded::from_str(
+        "title=Fund&description=Grant&rationale=Need&csrf_token=t\
+         &section_financial_impact=%E2%82%AC10k&section_legal_review_if_any=&section_unknown=x",
+    ).unwrap();
+    let answers = form.section_answers();
+    assert_eq!(answers.get("financial_impact").map(|s| s.as_str()), Some("€10k"));
+
+    let id = proposal::create(pool, board, "Fund", "Grant", "Need", user, "2026-03-01", None).await.unwrap();
+    proposal::save_section_answers(pool, id, &intake, &answers).await.unwrap();
+    let saved = proposal::find_section_answers(pool, id).await.unwrap();
+    assert_eq!(saved, HashMap::from([("financial_impact".to_string(), "€10k".to_string())]));
+
+    // Clearing an answer removes it
+    proposal::save_section_answers(pool, id, &intake, &HashMap::new()).await.unwrap();
+    assert!(proposal::find_section_answers(pool, id).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_submit_requirements() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let user = insert_entity(pool, "user", "alice", "Alice").await;
+    let board = insert_entity(pool, "tor", "board", "Board").await;
+    let id = proposal::create(pool, board, "Fund", "Grant", "Need", user, "2026-03-01", None).await.unwrap();
+
+    // Standard form: nothing extra to provide
+    assert!(proposal::missing_for_submit(pool, board, id).await.unwrap().is_empty());
+
+    let intake = intake();
+    proposal::save_intake(pool, board, &intake).await.unwrap();
+    assert_eq!(proposal::missing_for_submit(pool, board, id).await.unwrap(), vec![
+        "Financial impact is required".to_string(),
+        "At least 1 attached document(s) required (0 attached)".to_string(),
+    ]);
+
+    let answers = HashMap::from([("financial_impact".to_string(), "  ".to_string())]);
+    assert_eq!(intake.missing(&answers, 1), vec!["Financial impact is required".to_string()]);
+
+    let answers = HashMap::from([("financial_impact".to_string(), "€10k".to_string())]);
+    proposal::save_section_answers(pool, id, &intake, &answers).await.unwrap();
+    let estimate = document::create(pool, "Cost estimate", "meeting_paper", "€10k", user, Some(board)).await.unwrap();
+    proposal::attach_document(pool, id, estimate).await.unwrap();
+    let attachments = proposal::find_attachments(pool, id).await.unwrap();
+    assert_eq!(attachments.iter().map(|a| (a.title.as_str(), a.doc_type.as_str())).collect::<Vec<_>>(),
+        vec![("Cost estimate", "meeting_paper")]);
+    assert!(proposal::missing_for_submit(pool, board, id).await.unwrap().is_empty());
+
+    proposal::detach_document(pool, id, estimate).await.unwrap();
+    assert_eq!(proposal::missing_for_submit(pool, board, id).await.unwrap().len(), 1);
+}
@@ -0,0 +1,86 @@
+//! Proposal template tests — covers title patterns and template validation,
+//! and storing templates and their default COAs.
+//!
+//! - Title patterns expand date and ToR placeholders
+//! - A proposal from a template carries its default COAs onto its agenda point
+
+mod common;
+
+use ahlt::models::proposal::{self, ProposalTemplate};
+use ahlt::models::fiscal::FiscalCalendar;
+use ahlt::models::{agenda_point, coa, entity};
+use chrono::NaiveDate;
+use common::*;
+
+fn template(name: &str, coas: &[&str]) -> ProposalTemplate {
+    ProposalTemplate {
+        key: proposal::section_key(name),
+        name: name.to_string(),
+        title_pattern: "Budget reallocation {quarter} {year}".to_string(),
+        default_coas: coas.iter().map(|c| c.to_string()).collect(),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_title_patterns_and_validation() {
+    let day = NaiveDate::from_ymd_opt(2026, 8, 3).unwrap();
+    let fiscal = FiscalCalendar::default();
+    assert_eq!(proposal::expand_title("Budget reallocation {quarter} {year}", "Board", day, &fiscal), "Budget reallocation Q3 2026");
+    assert_eq!(proposal::expand_title("{tor}: {month} report ({date})", "Board", day, &fiscal), "Board: August report (2026-08-03)");
+    assert_eq!(proposal::expand_title("Plain", "Board", day, &fiscal), "Plain");
+
+    assert_eq!(proposal::parse_coa_lines(" Approve \n\n Reject\r\nDefer "), vec!["Approve", "Reject", "Defer"]);
+
+    let existing = vec![template("Budget reallocation", &[])];
+    assert!(proposal::validate_template(&template("Membership change", &["Admit", "Decline"]), &existing, false).is_empty());
+    assert_eq!(
+        proposal::validate_template(&template("Budget Reallocation", &[]), &existing, false),
+        vec!["A template named 'Budget Reallocation' already exists"]
+    );
+    assert!(proposal::validate_template(&template("Budget reallocation", &[]), &existing, true).is_empty());
+    assert_eq!(proposal::validate_template(&template("", &["A", "A"]), &existing, false), vec![
+        "Template name is required",
+        "Each default course of action needs a different title",
+    ]);
+    assert_eq!(proposal::validate_template(&template("!!", &[]), &existing, false), vec!["Template name needs letters or digits"]);
+}
+
+#[tokio::test]
+async fn test_templates_and_default_coas() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let alice = insert_entity(pool, "user", "alice", "Alice").await;
+    let board = insert_entity(pool, "tor", "board", "Board").await;
+    assert!(proposal::find_templates(pool, board).await.unwrap().is_empty());
+
+    let mut budget = template("Budget reallocation", &["Approve", "Approve in part", "Reject"]);
+    budget.sections.insert("financial_impact".to_string(), "Amount moved: ".to_string());
+    let plain = template("Information item", &[]);
+    proposal::save_templates(pool, board, &[budget.clone(), plain.clone()]).await.unwrap();
+    assert_eq!(proposal::find_templates(pool, board).await.unwrap(), vec![budget.clone(), plain.clone()]);
+    let found = proposal::find_template(pool, board, "budget_reallocation").await.unwrap().unwrap();
+    assert_eq!(found.section("financial_impact"), "Amount moved: ");
+
+    let from_budget = proposal::create(pool, board, "Budget reallocation Q4", "d", "", alice, "2026-10-01", None).await.unwrap();
+    proposal::apply_to_proposal(pool, from_budget, &budget).await.unwrap();
+    let from_plain = proposal::create(pool, board, "For information", "d", "", alice, "2026-10-01", None).await.unwrap();
+    proposal::apply_to_proposal(pool, from_plain, &plain).await.unwrap();
+    assert_eq!(entity::get_property(pool, from_budget, "template_key").await.unwrap().as_deref(), Some("budget_reallocation"));
+    assert!(proposal::find_default_coas(pool, from_plain).await.unwrap().is_empty());
+
+    // Deleting the template keeps the COAs the proposal was created with
+    proposal::save_templates(pool, board, &[plain]).await.unwrap();
+    let titles = proposal::find_default_coas(pool, from_budget).await.unwrap();
+    assert_eq!(titles, vec!["Approve", "Approve in part", "Reject"]);
+
+    let ap = agenda_point::create(pool, board, "Budget reallocation Q4", "", "decision", "2026-10-20", 30, alice, "", "", "").await.unwrap();
+    let ids = proposal::create_default_coas(pool, ap, &titles, alice).await.unwrap();
+    let linked: Vec<i64> = coa::find_all_for_agenda_point(pool, ap).await.unwrap().iter().map(|c| c.id).collect();
+    assert_eq!(linked.len(), 3);
+    assert!(ids.iter().all(|id| linked.contains(id)));
+    assert_eq!(coa::find_by_id(pool, ids[1]).await.unwrap().title, "Approve in part");
+
+    proposal::save_templates(pool, board, &[]).await.unwrap();
+    assert!(entity::get_property(pool, board, "proposal_templates").await.unwrap().is_none());
+}
@@ -206,6 +206,7 @@ async fn test_update_proposal() {
         "Updated Title",
         "Updated description",
         "Updated rationale",
+        user_id,
     ).await.unwrap();
 
     // Verify update
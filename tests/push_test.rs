@@ -0,0 +1,244 @@
+//! Web Push tests — covers message encryption, VAPID and delivery.
+//!
+//! - Messages decrypt with the subscription's key and auth secret (RFC 8291)
+//! - VAPID tokens are ES256-signed for the endpoint's origin (RFC 8292)
+//! - Subscriptions are validated and re-registered by endpoint
+//! - Only high and critical warnings and tomorrow's confirmed meetings are
+//!   queued, and only while push is enabled
+//! - The sender signs each request and forgets subscriptions that are gone
+
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes128Gcm, Nonce};
+use ahlt::models::relation;
+use ahlt::warnings::{self, outbox};
+use ahlt::warnings::notifications::push::{self, webpush::{self, Vapid}};
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use common::*;
+use ring::agreement::{self, EphemeralPrivateKey, UnparsedPublicKey, ECDH_P256};
+use ring::rand::SystemRandom;
+use ring::signature::{self, ECDSA_P256_SHA256_FIXED};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+/// Application server key pair from RFC 8291 Appendix A.
+const VAPID_PUBLIC: &str = "BP4z9KsN6nGRTbVYI_c7VJSPQTBtkgcy27mlmlMoZIIgDll6e3vCYLocInmYWAmS6TlzAC8wEqKK6PBru3jl7A8";
+const VAPID_PRIVATE: &str = "yfWPiYE-n46HLnH0KqZOF1fJJU3MYrct3AELtAQ-oRw";
+/// A valid subscription key and secret (the RFC's user agent keys).
+const UA_PUBLIC: &str = "BCVxsr7N_eNgVRqvHtD0zTZsEc6-VV-JvLexhqUzORcxaOzi6-AYWXvTBHm4bjyPjs7Vd8pZGH6SRpkNtoIAiw4";
+const UA_AUTH: &str = "BTBZMqHH6r4Tts7J_aSIgg";
+
+#[test]
+fn test_encrypt_round_trip() {
+    let rng = SystemRandom::new();
+    let ua_private = EphemeralPrivateKey::generate(&ECDH_P256, &rng).unwrap();
+    let ua_public = ua_private.compute_public_key().unwrap().as_ref().to_vec();
+    let auth = [7u8; 16];
+
+    let body = webpush::encrypt(&ua_public, &auth, b"{\"title\":\"Hello\"}").unwrap();
+    let salt = &body[..16];
+    assert_eq!(u32::from_be_bytes(body[16..20].try_into().unwrap()), 4096);
+    assert_eq!(body[20], 65);
+    let as_public = &body[21..86];
+
+    // The browser's side: agree on the secret, derive the keys, decrypt
+    let shared = agreement::agree_ephemeral(ua_private, &UnparsedPublicKey::new(&ECDH_P256, as_public), |s| s.to_vec())
+        .unwrap();
+    let (cek, nonce) = webpush::derive_keys(&shared, &auth, &ua_public, as_public, salt).unwrap();
+    let plaintext = Aes128Gcm::new_from_slice(&cek).unwrap()
+        .decrypt(Nonce::from_slice(&nonce), &body[86..])
+        .unwrap();
+    assert_eq!(plaintext, b"{\"title\":\"Hello\"}\x02");
+
+    assert!(webpush::encrypt(&ua_public, &auth, &vec![b'x'; webpush::MAX_PAYLOAD + 1]).is_err());
+    assert!(webpush::encrypt(b"not a key", &auth, b"x").is_err());
+}
+
+#[test]
+fn test_vapid_token_is_signed_for_the_origin() {
+    let vapid = Vapid::new("mailto:ops@example.org", VAPID_PUBLIC, VAPID_PRIVATE).unwrap();
+    assert_eq!(vapid.public_key_b64(), VAPID_PUBLIC);
+    assert!(Vapid::new("mailto:ops@example.org", UA_PUBLIC, VAPID_PRIVATE).is_err());
+
+    let token = vapid.token("https://push.example.net:8443/send/abc?x=1", 1_800_000_000).unwrap();
+    let parts: Vec<&str> = token.split('.').collect();
+    assert_eq!(parts.len(), 3);
+    let claims: serde_json::Value = serde_json::from_slice(&URL_SAFE_NO_PAD.decode(parts[1]).unwrap()).unwrap();
+    assert_eq!(claims["aud"], "https://push.example.net:8443");
+    assert_eq!(claims["sub"], "mailto:ops@example.org");
+    assert_eq!(claims["exp"], 1_800_000_000 + 12 * 3600);
+
+    let public = URL_SAFE_NO_PAD.decode(VAPID_PUBLIC).unwrap();
+    let signed = format!("{}.{}", parts[0], parts[1]);
+    signature::UnparsedPublicKey::new(&ECDSA_P256_SHA256_FIXED, &public)
+        .verify(signed.as_bytes(), &URL_SAFE_NO_PAD.decode(parts[2]).unwrap())
+        .unwrap();
+
+    assert!(vapid.token("ftp://push.example.net/x", 0).is_err());
+    assert_eq!(webpush::audience("https:///x"), None);
+}
+
+async fn set(pool: &sqlx::PgPool, name: &str, value: &str) {
+    let id = insert_entity(pool, "setting", name, name).await;
+    insert_prop(pool, id, "value", value).await;
+}
+
+async fn push_rows(pool: &sqlx::PgPool, status: &str) -> Vec<serde_json::Value> {
+    outbox::find_recent(pool, Some(status), 100).await.unwrap()
+        .into_iter()
+        .filter(|e| e.channel == outbox::CHANNEL_PUSH)
+        .map(|e| serde_json::from_str(&e.payload).unwrap())
+        .collect()
+}
+
+async fn warn(pool: &sqlx::PgPool, severity: &str, users: &[i64]) -> i64 {
+    let id = warnings::create_warning(pool, severity, "system", "test.warning", "Disk almost full", "", "user")
+        .await
+        .unwrap();
+    warnings::create_receipts(pool, id, users).await.unwrap();
+    id
+}
+
+#[tokio::test]
+async fn test_subscriptions_and_queueing() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let alice = insert_entity(pool, "user", "alice", "Alice").await;
+    let bob = insert_entity(pool, "user", "bob", "Bob").await;
+
+    let phone = "https://push.example.net/send/phone";
+    let laptop = "https://push.example.net/send/laptop";
+    assert!(push::subscribe(pool, alice, "not-a-url", UA_PUBLIC, UA_AUTH, "").await.is_err());
+    assert!(push::subscribe(pool, alice, phone, "AAAA", UA_AUTH, "").await.is_err());
+    assert!(push::subscribe(pool, alice, phone, UA_PUBLIC, "AAAA", "").await.is_err());
+    let first = push::subscribe(pool, alice, phone, UA_PUBLIC, UA_AUTH, "Phone").await.unwrap();
+    push::subscribe(pool, alice, laptop, UA_PUBLIC, UA_AUTH, "Laptop").await.unwrap();
+
+    // Registering the same endpoint again replaces the registration
+    assert_eq!(push::subscribe(pool, alice, phone, UA_PUBLIC, UA_AUTH, "Phone (new)").await.unwrap(), first);
+    let devices = push::find_for_user(pool, alice).await.unwrap();
+    assert_eq!(devices.iter().map(|d| d.device.as_str()).collect::<Vec<_>>(), vec!["Laptop", "Phone (new)"]);
+
+    // Disabled: nothing is queued
+    warn(pool, "critical", &[alice, bob]).await;
+    assert!(push_rows(pool, "pending").await.is_empty());
+
+    set(pool, "push.enabled", "true").await;
+    warn(pool, "medium", &[alice, bob]).await;
+    assert!(push_rows(pool, "pending").await.is_empty());
+    let critical = warn(pool, "critical", &[alice, bob]).await;
+    let queued = push_rows(pool, "pending").await;
+    assert_eq!(queued.len(), 2);
+    assert_eq!(queued[0]["title"], "Critical warning");
+    assert_eq!(queued[0]["url"], format!("/warnings/{}", critical));
+
+    // Only the owner can remove a registration
+    assert!(!push::unsubscribe(pool, bob, laptop).await.unwrap());
+    assert!(push::unsubscribe(pool, alice, laptop).await.unwrap());
+    assert_eq!(push::find_for_user(pool, alice).await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_meeting_reminders_are_sent_once() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    set(pool, "push.enabled", "true").await;
+    let bob = insert_entity(pool, "user", "bob", "Bob").await;
+    push::subscribe(pool, bob, "https://push.example.net/send/bob", UA_PUBLIC, UA_AUTH, "").await.unwrap();
+    let board = insert_entity(pool, "tor", "board", "Board").await;
+    let chair = insert_entity(pool, "tor_function", "chair", "Chair").await;
+    relation::create(pool, "belongs_to_tor", chair, board).await.unwrap();
+    relation::create(pool, "fills_position", bob, chair).await.unwrap();
+
+    let meeting = |name: &'static str, date: &'static str, status: &'static str| async move {
+        let id = insert_entity(pool, "meeting", name, name).await;
+        insert_prop(pool, id, "meeting_date", date).await;
+        insert_prop(pool, id, "status", status).await;
+        relation::create(pool, "belongs_to_tor", id, board).await.unwrap();
+        id
+    };
+    let tomorrow = meeting("board-1", "2026-10-17", "confirmed").await;
+    meeting("board-2", "2026-10-17", "projected").await;
+    meeting("board-3", "2026-10-18", "confirmed").await;
+
+    let today = chrono::NaiveDate::from_ymd_opt(2026, 10, 16).unwrap();
+    assert_eq!(push::queue_meeting_reminders(pool, today).await.unwrap(), 1);
+    let queued = push_rows(pool, "pending").await;
+    assert_eq!(queued[0]["title"], "Board meets tomorrow");
+    assert_eq!(queued[0]["url"], format!("/meetings/{}", tomorrow));
+
+    assert_eq!(push::queue_meeting_reminders(pool, today).await.unwrap(), 0);
+}
+
+/// Stub push service: records (headers, body length) and answers with the
+/// next queued status.
+async fn stub_push_service(received: Arc<Mutex<Vec<(String, usize)>>>, statuses: Vec<u16>) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let statuses = Arc::new(Mutex::new(statuses));
+    tokio::spawn(async move {
+        loop {
+            let Ok((mut socket, _)) = listener.accept().await else { return };
+            let mut buf = vec![0u8; 16384];
+            let mut len = 0;
+            let (head, body_len) = loop {
+                let n = socket.read(&mut buf[len..]).await.unwrap();
+                len += n;
+                let Some(end) = buf[..len].windows(4).position(|w| w == b"\r\n\r\n") else { continue };
+                let head = String::from_utf8_lossy(&buf[..end]).to_string();
+                let body_len = head.lines()
+                    .find_map(|l| l.strip_prefix("Content-Length: "))
+                    .and_then(|v| v.trim().parse::<usize>().ok())
+                    .unwrap_or(0);
+                if len >= end + 4 + body_len || n == 0 {
+                    break (head, body_len);
+                }
+            };
+            received.lock().unwrap().push((head, body_len));
+            let status = {
+                let mut queue = statuses.lock().unwrap();
+                if queue.is_empty() { 201 } else { queue.remove(0) }
+            };
+            let response = format!("HTTP/1.1 {} X\r\nContent-Length: 0\r\n\r\n", status);
+            socket.write_all(response.as_bytes()).await.unwrap();
+            socket.shutdown().await.ok();
+        }
+    });
+    format!("http://{}/send", addr)
+}
+
+#[tokio::test]
+async fn test_drain_signs_requests_and_drops_gone_subscriptions() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    set(pool, "push.enabled", "true").await;
+    set(pool, "push.vapid_subject", "mailto:ops@example.org").await;
+    set(pool, "push.vapid_public_key", VAPID_PUBLIC).await;
+    // SAFETY: no other test in this binary reads the environment
+    unsafe { std::env::set_var("VAPID_PRIVATE_KEY", VAPID_PRIVATE) };
+
+    let alice = insert_entity(pool, "user", "alice", "Alice").await;
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let base = stub_push_service(received.clone(), vec![201, 410]).await;
+    push::subscribe(pool, alice, &format!("{}/a", base), UA_PUBLIC, UA_AUTH, "").await.unwrap();
+    push::subscribe(pool, alice, &format!("{}/b", base), UA_PUBLIC, UA_AUTH, "").await.unwrap();
+    warn(pool, "high", &[alice]).await;
+
+    assert_eq!(push::drain(pool).await, 2);
+    assert_eq!(push_rows(pool, "delivered").await.len(), 2);
+    // The push service said one subscription is gone
+    assert_eq!(push::find_for_user(pool, alice).await.unwrap().len(), 1);
+
+    let received = received.lock().unwrap().clone();
+    assert_eq!(received.len(), 2);
+    for (head, body_len) in &received {
+        assert!(head.contains("Authorization: vapid t="), "{}", head);
+        assert!(head.contains(&format!(", k={}", VAPID_PUBLIC)));
+        assert!(head.contains("Content-Encoding: aes128gcm"));
+        assert!(head.contains("Urgency: high"));
+        assert!(*body_len > 86 + 16);
+    }
+}
@@ -0,0 +1,109 @@
+//! Quota tests — covers open proposal, ToR storage and daily API limits.
+//!
+//! - Limits of 0 are unlimited; the most generous role override wins
+//! - Checks explain what limit was hit; closed proposals and shrinking
+//!   documents don't count against a quota
+//! - The usage report pairs each user and ToR with their limits
+
+mod common;
+
+use ahlt::models::quota::{self, Usage};
+use ahlt::models::{api_usage, document, proposal, relation, setting};
+use chrono::{Duration, TimeZone, Utc};
+use common::*;
+
+async fn set(pool: &sqlx::PgPool, name: &str, value: &str) {
+    let id = insert_entity(pool, "setting", name, name).await;
+    insert_prop(pool, id, "value", value).await;
+}
+
+#[test]
+fn test_limits_and_usage() {
+    assert_eq!(quota::most_generous(&[]), None);
+    assert_eq!(quota::most_generous(&[3, 10]), Some(10));
+    assert_eq!(quota::most_generous(&[3, 0, 10]), Some(0));
+
+    let unlimited = Usage { used: 500, limit: 0 };
+    assert!(unlimited.is_unlimited() && !unlimited.is_exceeded());
+    assert_eq!(unlimited.pct(), 0);
+    let full = Usage { used: 12, limit: 10 };
+    assert!(full.is_exceeded());
+    assert_eq!(full.pct(), 100);
+    assert_eq!(Usage { used: 3, limit: 10 }.pct(), 30);
+
+    assert_eq!(quota::format_mb(5 * 1024 * 1024 / 2), "2.5 MB");
+}
+
+#[tokio::test]
+async fn test_quotas_enforced_and_reported() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let alice = insert_entity(pool, "user", "alice", "Alice").await;
+    let bob = insert_entity(pool, "user", "bob", "Bob").await;
+    let tor_id = insert_entity(pool, "tor", "board", "Board").await;
+
+    // Unlimited by default
+    proposal::create(pool, tor_id, "One", "d", "", alice, "2026-10-16", None).await.unwrap();
+    assert_eq!(quota::check_open_proposals(pool, alice).await.unwrap(), None);
+
+    // Global limit; approved proposals no longer count
+    set(pool, quota::OPEN_PROPOSALS, "2").await;
+    let second = proposal::create(pool, tor_id, "Two", "d", "", alice, "2026-10-16", None).await.unwrap();
+    let message = quota::check_open_proposals(pool, alice).await.unwrap().unwrap();
+    assert!(message.contains("2 open proposals"), "{}", message);
+    assert_eq!(quota::check_open_proposals(pool, bob).await.unwrap(), None);
+    proposal::update_status(pool, second, "approved", None).await.unwrap();
+    assert_eq!(quota::open_proposals(pool, alice).await.unwrap(), 1);
+    assert_eq!(quota::check_open_proposals(pool, alice).await.unwrap(), None);
+
+    // Role overrides: the most generous applies, empty clears
+    let reviewers = insert_entity(pool, "role", "reviewer", "Reviewer").await;
+    let chairs = insert_entity(pool, "role", "chair", "Chair").await;
+    relation::create(pool, "has_role", bob, reviewers).await.unwrap();
+    relation::create(pool, "has_role", bob, chairs).await.unwrap();
+    quota::set_role_limit(pool, reviewers, quota::OPEN_PROPOSALS, Some(1)).await.unwrap();
+    assert_eq!(quota::user_limit(pool, bob, quota::OPEN_PROPOSALS).await.unwrap(), 1);
+    quota::set_role_limit(pool, chairs, quota::OPEN_PROPOSALS, Some(5)).await.unwrap();
+    assert_eq!(quota::user_limit(pool, bob, quota::OPEN_PROPOSALS).await.unwrap(), 5);
+    quota::set_role_limit(pool, chairs, quota::OPEN_PROPOSALS, None).await.unwrap();
+    quota::set_role_limit(pool, reviewers, quota::OPEN_PROPOSALS, Some(0)).await.unwrap();
+    assert_eq!(quota::user_limit(pool, bob, quota::OPEN_PROPOSALS).await.unwrap(), 0);
+    let roles = quota::find_role_quotas(pool).await.unwrap();
+    let chair_row = roles.iter().find(|r| r.id == chairs).unwrap();
+    assert_eq!(chair_row.overrides, vec!["".to_string(), "".to_string()]);
+
+    // ToR storage, with a ToR override; shrinking a document is always allowed
+    set(pool, quota::TOR_STORAGE_MB, "1").await;
+    let body = "x".repeat(600 * 1024);
+    document::create(pool, "Annex", "ad_hoc", &body, alice, Some(tor_id)).await.unwrap();
+    assert_eq!(quota::tor_storage_bytes(pool, tor_id).await.unwrap(), 600 * 1024);
+    let message = quota::check_tor_storage(pool, tor_id, 600 * 1024, 0).await.unwrap().unwrap();
+    assert!(message.contains("1.0 MB"), "{}", message);
+    assert_eq!(quota::check_tor_storage(pool, tor_id, 700 * 1024, 600 * 1024).await.unwrap(), None);
+    setting::set_tor_override(pool, tor_id, quota::TOR_STORAGE_MB, "2").await.unwrap();
+    assert_eq!(quota::check_tor_storage(pool, tor_id, 600 * 1024, 0).await.unwrap(), None);
+
+    // Daily API requests count the UTC day only
+    set(pool, quota::API_REQUESTS_PER_DAY, "3").await;
+    let today = Utc.with_ymd_and_hms(2026, 10, 16, 9, 0, 0).unwrap();
+    api_usage::record(pool, "alice", "GET /api/v1/tors", today - Duration::days(1), false, 5).await.unwrap();
+    api_usage::record(pool, "alice", "GET /api/v1/tors", today, false, 5).await.unwrap();
+    api_usage::record(pool, "alice", "GET /api/v1/users", today + Duration::hours(3), false, 5).await.unwrap();
+    assert_eq!(quota::api_requests_on(pool, "alice", today).await.unwrap(), 2);
+    assert_eq!(quota::check_api_requests(pool, alice, "alice", today).await.unwrap(), None);
+    api_usage::record(pool, "alice", "GET /api/v1/users", today, false, 5).await.unwrap();
+    let message = quota::check_api_requests(pool, alice, "alice", today).await.unwrap().unwrap();
+    assert!(message.contains("Daily API quota of 3 requests"), "{}", message);
+    assert_eq!(quota::check_api_requests(pool, bob, "bob", today).await.unwrap(), None);
+
+    // Report
+    let users = quota::user_usage(pool, today).await.unwrap();
+    let a = users.iter().find(|u| u.id == alice).unwrap();
+    assert_eq!((a.open_proposals.used, a.open_proposals.limit), (1, 2));
+    assert!(a.api_requests_today.is_exceeded());
+    assert_eq!(users[0].id, alice);
+    let b = users.iter().find(|u| u.id == bob).unwrap();
+    assert!(b.open_proposals.is_unlimited());
+    let tors = quota::tor_usage(pool).await.unwrap();
+    assert_eq!((tors[0].storage.used, tors[0].storage.limit), (600 * 1024, 2 * 1024 * 1024));
+}
@@ -0,0 +1,110 @@
+//! Bulk reassignment tests — covers moving a user's pending work.
+//!
+//! - The plan lists cover, delegations, unfinished minutes, open action
+//!   items and positions, and says why anything stays
+//! - Applying it moves everything not skipped and leaves finished work alone
+
+mod common;
+
+use ahlt::models::{action_item, delegation, entity, relation, reassignment, tor};
+use chrono::NaiveDate;
+use common::*;
+use sqlx::PgPool;
+
+async fn position(pool: &PgPool, tor_id: i64, name: &str, holder: i64) -> i64 {
+    let id = insert_entity(pool, "tor_function", name, name).await;
+    relation::create(pool, "belongs_to_tor", id, tor_id).await.unwrap();
+    tor::assign_to_position(pool, holder, id, "mandatory").await.unwrap();
+    id
+}
+
+async fn minutes(pool: &PgPool, tor_id: i64, name: &str, secretary: &str, status: &str) -> i64 {
+    let meeting = insert_entity(pool, "meeting", name, name).await;
+    insert_prop(pool, meeting, "secretary_user_id", secretary).await;
+    relation::create(pool, "belongs_to_tor", meeting, tor_id).await.unwrap();
+    let id = insert_entity(pool, "minutes", &format!("{name}_minutes"), &format!("Minutes {name}")).await;
+    insert_prop(pool, id, "status", status).await;
+    relation::create(pool, "minutes_of", meeting, id).await.unwrap();
+    meeting
+}
+
+async fn action(pool: &PgPool, tor_id: i64, title: &str, owner: i64, status: &str) -> i64 {
+    let input = action_item::ActionItemInput { title, description: "", owner_id: owner, due_date: "", status };
+    action_item::create(pool, tor_id, &input, None, owner).await.unwrap()
+}
+
+#[tokio::test]
+async fn test_plan_and_apply_reassignment() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let today = NaiveDate::from_ymd_opt(2026, 3, 10).unwrap();
+    let alice = insert_entity(pool, "user", "alice", "Alice").await;
+    let bob = insert_entity(pool, "user", "bob", "Bob").await;
+    let carol = insert_entity(pool, "user", "carol", "Carol").await;
+    let ops = insert_entity(pool, "tor", "ops", "Ops Board").await;
+
+    let chair = position(pool, ops, "chair", alice).await;
+    let shared = position(pool, ops, "member", alice).await;
+    tor::assign_to_position(pool, bob, shared, "optional").await.unwrap();
+    let carols = position(pool, ops, "secretary", carol).await;
+
+    // Alice covers for Carol and Bob while they are away, and for Carol's position
+    delegation::set_out_of_office(pool, carol, alice, "2026-03-01", "2026-03-20", "").await.unwrap();
+    delegation::set_out_of_office(pool, bob, alice, "2026-03-01", "2026-03-20", "").await.unwrap();
+    tor::delegate_position(pool, carols, carol, alice, "2026-03-05", "2026-03-31", "Leave").await.unwrap();
+
+    let by_id = minutes(pool, ops, "m1", &alice.to_string(), "draft").await;
+    let by_name = minutes(pool, ops, "m2", "alice", "pending_approval").await;
+    let approved = minutes(pool, ops, "m3", "alice", "approved").await;
+
+    let open = action(pool, ops, "Draft budget", alice, "open").await;
+    let done = action(pool, ops, "Old task", alice, "done").await;
+
+    assert_eq!(reassignment::validate(pool, alice, alice).await.unwrap().len(), 1);
+    assert!(reassignment::validate(pool, alice, bob).await.unwrap().is_empty());
+
+    let plan = reassignment::plan(pool, alice, bob, today).await.unwrap();
+    let summary: Vec<(&str, &str, bool)> = plan.iter().map(|i| (i.kind, i.label.as_str(), i.moves())).collect();
+    assert_eq!(summary, vec![
+        ("cover", "Out-of-office cover for Bob", false),
+        ("cover", "Out-of-office cover for Carol", true),
+        ("delegation", "secretary for Carol", true),
+        ("minutes", "Minutes m1", true),
+        ("minutes", "Minutes m2", true),
+        ("action_item", "Draft budget", true),
+        ("position", "chair", true),
+        ("position", "member", false),
+    ]);
+
+    let applied = reassignment::apply(pool, alice, bob, today).await.unwrap();
+    assert_eq!(applied.iter().filter(|i| i.moves()).count(), 6);
+
+    assert_eq!(delegation::find_for_user(pool, carol).await.unwrap().unwrap().delegate_id, bob);
+    assert_eq!(delegation::find_for_user(pool, bob).await.unwrap().unwrap().delegate_id, alice);
+    let delegates: Vec<i64> = tor::find_delegations(pool, ops).await.unwrap().iter()
+        .filter(|d| d.end_date == "2026-03-31")
+        .map(|d| d.delegate_id)
+        .collect();
+    assert_eq!(delegates, vec![bob]);
+
+    let secretary = |id| async move { entity::get_property(pool, id, "secretary_user_id").await.unwrap().unwrap() };
+    assert_eq!(secretary(by_id).await, bob.to_string());
+    assert_eq!(secretary(by_name).await, "bob");
+    assert_eq!(secretary(approved).await, "alice");
+
+    assert_eq!(action_item::find_by_id(pool, open).await.unwrap().unwrap().owner_id, bob);
+    assert_eq!(action_item::find_by_id(pool, done).await.unwrap().unwrap().owner_id, alice);
+
+    let holders = |position_id| async move {
+        let mut ids: Vec<i64> = relation::find_sources(pool, position_id, "fills_position").await.unwrap()
+            .iter().map(|u| u.id).collect();
+        ids.sort();
+        ids
+    };
+    assert_eq!(holders(chair).await, vec![bob]);
+    assert_eq!(holders(shared).await, vec![alice, bob]);
+
+    // Only the skipped items are left
+    let rest = reassignment::plan(pool, alice, bob, today).await.unwrap();
+    assert!(rest.iter().all(|i| !i.moves()));
+}
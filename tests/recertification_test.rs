@@ -0,0 +1,93 @@
+//! Access recertification tests — covers campaigns of chair-reviewed access.
+//!
+//! - Launching routes each filled position to its ToR's chair, or to the
+//!   fallback reviewers for the chair's own position and chairless ToRs
+//! - Revoking removes the member from the position; decisions are final
+//! - At the deadline pending items are flagged, or revoked when configured
+
+mod common;
+
+use ahlt::models::{recertification, relation};
+use common::*;
+
+async fn position(pool: &sqlx::PgPool, tor_id: i64, name: &str, label: &str, holder: i64) -> i64 {
+    let id = insert_entity(pool, "tor_function", name, label).await;
+    relation::create(pool, "belongs_to_tor", id, tor_id).await.unwrap();
+    relation::create(pool, "fills_position", holder, id).await.unwrap();
+    id
+}
+
+#[tokio::test]
+async fn test_campaign_routes_items_and_applies_decisions() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let admin = insert_entity(pool, "user", "recert_admin", "Recert Admin").await;
+    let chair = insert_entity(pool, "user", "recert_chair", "Recert Chair").await;
+    let member = insert_entity(pool, "user", "recert_member", "Recert Member").await;
+    let other = insert_entity(pool, "user", "recert_other", "Recert Other").await;
+
+    let tor_id = insert_entity(pool, "tor", "recert_tor", "Recert Board").await;
+    let chair_pos = position(pool, tor_id, "recert_chair_pos", "Chair", chair).await;
+    let member_pos = position(pool, tor_id, "recert_member_pos", "Secretary", member).await;
+    let other_pos = position(pool, tor_id, "recert_other_pos", "Adviser", other).await;
+
+    let today = chrono::NaiveDate::from_ymd_opt(2026, 10, 17).unwrap();
+    assert_eq!(recertification::validate_launch("", "2026-10-01", "delete", today).len(), 3);
+    assert!(recertification::validate_launch("Q4", "2026-10-31", "flag", today).is_empty());
+
+    let (campaign_id, reviewers) = recertification::launch(pool, "Q4", "2026-10-31", "flag", admin).await.unwrap();
+    assert!(reviewers.contains(&(chair, 2)));
+    assert!(reviewers.contains(&(0, 1)), "the chair's own position goes to the fallback reviewers");
+
+    let fallback = recertification::find_tasks(pool, admin, true).await.unwrap();
+    assert_eq!(fallback.iter().map(|i| i.position_id).collect::<Vec<_>>(), vec![chair_pos]);
+
+    let tasks = recertification::find_tasks(pool, chair, false).await.unwrap();
+    assert_eq!(tasks.len(), 2);
+    let secretary = tasks.iter().find(|i| i.position_id == member_pos).unwrap();
+    assert!(secretary.can_decide(chair, false));
+    assert!(!secretary.can_decide(member, true));
+
+    assert!(recertification::decide(pool, secretary, recertification::REVOKED, chair).await.unwrap());
+    assert!(!recertification::decide(pool, secretary, recertification::CONFIRMED, chair).await.unwrap());
+    assert!(relation::find_targets(pool, member, "fills_position").await.unwrap().is_empty());
+
+    let campaign = recertification::find_by_id(pool, campaign_id).await.unwrap().unwrap();
+    assert_eq!((campaign.total, campaign.pending, campaign.revoked), (3, 2, 1));
+    assert_eq!(campaign.progress_pct(), 33);
+
+    // Not yet due, then overdue: pending items are flagged and access kept.
+    assert!(recertification::close_overdue(pool, "2026-10-31").await.unwrap().is_empty());
+    let closed = recertification::close_overdue(pool, "2026-11-01").await.unwrap();
+    assert_eq!(closed.len(), 1);
+    let (campaign, flagged) = &closed[0];
+    assert!(!campaign.is_open());
+    assert_eq!((campaign.flagged, campaign.pending), (2, 0));
+    assert!(flagged.iter().any(|i| i.position_id == other_pos));
+    assert!(!relation::find_targets(pool, other, "fills_position").await.unwrap().is_empty());
+    assert!(recertification::find_tasks(pool, chair, true).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_revoke_at_deadline_removes_unconfirmed_access() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let admin = insert_entity(pool, "user", "recert_admin2", "Recert Admin2").await;
+    let member = insert_entity(pool, "user", "recert_member2", "Recert Member2").await;
+
+    let tor_id = insert_entity(pool, "tor", "recert_tor2", "Chairless Board").await;
+    position(pool, tor_id, "recert_pos2", "Member", member).await;
+
+    let (campaign_id, reviewers) = recertification::launch(pool, "Sweep", "2026-10-20", "revoke", admin).await.unwrap();
+    assert_eq!(reviewers, vec![(0, 1)]);
+    let items = recertification::find_tasks(pool, admin, true).await.unwrap();
+    assert_eq!(items.len(), 1);
+    assert!(recertification::find_tasks(pool, admin, false).await.unwrap().is_empty());
+
+    let campaign = recertification::find_by_id(pool, campaign_id).await.unwrap().unwrap();
+    let (campaign, revoked) = recertification::close(pool, &campaign).await.unwrap();
+    assert_eq!((campaign.revoked, revoked.len()), (1, 1));
+    assert!(relation::find_targets(pool, member, "fills_position").await.unwrap().is_empty());
+    let item = recertification::find_item(pool, items[0].id).await.unwrap().unwrap();
+    assert_eq!((item.decision.as_str(), item.decided_by_name.as_str()), (recertification::AUTO_REVOKED, "system"));
+}
@@ -0,0 +1,101 @@
+//! Rejection reason taxonomy tests — covers managing reasons and the per-ToR
+//! rejection analytics.
+//!
+//! - Reason names are unique and fixed; retired reasons are not offered
+//! - Rejections are counted per ToR and reason, older uncategorised ones as
+//!   Unclassified
+
+mod common;
+
+use ahlt::models::proposal;
+use ahlt::models::rejection_reason::{self, RejectionReasonInput};
+use common::*;
+
+fn input<'a>(name: &'a str, label: &'a str) -> RejectionReasonInput<'a> {
+    RejectionReasonInput { name, label, description: "" }
+}
+
+#[tokio::test]
+async fn test_manage_reasons() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+
+    let errors = rejection_reason::validate(pool, &input("Bad Name", ""), None).await.unwrap();
+    assert_eq!(errors, vec!["Name may only contain lowercase letters, digits, '-' and '_'", "Label is required"]);
+    let errors = rejection_reason::validate(pool, &input("other", "Unclassified"), None).await.unwrap();
+    assert_eq!(errors, vec!["'Unclassified' is reserved for rejections without a reason"]);
+
+    let budget = rejection_reason::create(pool, &input("budget", "Budget")).await.unwrap();
+    let scope = rejection_reason::create(pool, &input("scope", "Out of scope")).await.unwrap();
+    let errors = rejection_reason::validate(pool, &input("budget", "Again"), None).await.unwrap();
+    assert_eq!(errors, vec!["A rejection reason named 'budget' already exists"]);
+
+    // Editing keeps the name whatever the form sends
+    let reason = rejection_reason::find_by_id(pool, budget).await.unwrap().unwrap();
+    let edit = RejectionReasonInput { name: "ignored", label: "Insufficient budget detail", description: "Costs missing" };
+    assert!(rejection_reason::validate(pool, &edit, Some(budget)).await.unwrap().is_empty());
+    rejection_reason::update(pool, &reason, &edit).await.unwrap();
+    let reason = rejection_reason::find_by_id(pool, budget).await.unwrap().unwrap();
+    assert_eq!((reason.name.as_str(), reason.label.as_str(), reason.description.as_str()), ("budget", "Insufficient budget detail", "Costs missing"));
+    assert_eq!(reason.usage, 0);
+
+    rejection_reason::set_active(pool, scope, false).await.unwrap();
+    let active: Vec<i64> = rejection_reason::find_active(pool).await.unwrap().iter().map(|r| r.id).collect();
+    assert_eq!(active, vec![budget]);
+    let all: Vec<(i64, bool)> = rejection_reason::find_all(pool).await.unwrap().iter().map(|r| (r.id, r.is_active)).collect();
+    assert_eq!(all, vec![(budget, true), (scope, false)]);
+
+    assert_eq!(rejection_reason::reason_text("Budget", "  "), "Budget");
+    assert_eq!(rejection_reason::reason_text("Budget", "No cost table"), "Budget — No cost table");
+
+    rejection_reason::delete(pool, scope).await.unwrap();
+    assert!(rejection_reason::find_by_id(pool, scope).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_rejection_analytics() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let alice = insert_entity(pool, "user", "alice", "Alice").await;
+    let board = insert_entity(pool, "tor", "board", "Board").await;
+    let audit = insert_entity(pool, "tor", "audit", "Audit").await;
+    let quiet = insert_entity(pool, "tor", "quiet", "Quiet").await;
+    rejection_reason::create(pool, &input("budget", "Insufficient budget detail")).await.unwrap();
+    rejection_reason::create(pool, &input("scope", "Out of scope")).await.unwrap();
+
+    let mut board_ids = Vec::new();
+    for title in ["One", "Two", "Three", "Four"] {
+        board_ids.push(proposal::create(pool, board, title, "d", "", alice, "2026-10-01", None).await.unwrap());
+    }
+    let audit_id = proposal::create(pool, audit, "Five", "d", "", alice, "2026-10-01", None).await.unwrap();
+
+    proposal::reject(pool, board_ids[0], "budget", "Insufficient budget detail — No cost table", alice).await.unwrap();
+    proposal::reject(pool, board_ids[1], "budget", "Insufficient budget detail", alice).await.unwrap();
+    proposal::reject(pool, board_ids[2], "scope", "Out of scope", alice).await.unwrap();
+    proposal::transition(pool, board_ids[3], "rejected", Some("Free text"), alice).await.unwrap();
+    proposal::reject(pool, audit_id, "scope", "Out of scope", alice).await.unwrap();
+
+    let p = proposal::find_by_id(pool, board_ids[0]).await.unwrap().unwrap();
+    assert_eq!(p.rejection_reason.as_deref(), Some("Insufficient budget detail — No cost table"));
+
+    // Resubmitting clears the category; the earlier rejection still counts
+    proposal::transition(pool, board_ids[0], "submitted", None, alice).await.unwrap();
+    let props = ahlt::models::entity::get_properties(pool, board_ids[0]).await.unwrap();
+    assert!(!props.contains_key("rejection_category") && !props.contains_key("rejection_reason"));
+
+    let summaries = rejection_reason::find_summaries(pool, &[board, audit, quiet]).await.unwrap();
+    assert_eq!(summaries.len(), 2);
+    let (a, b) = (&summaries[0], &summaries[1]);
+    assert_eq!((a.tor_label.as_str(), a.total, a.top_reason()), ("Audit", 1, Some("Out of scope")));
+    assert_eq!((b.tor_label.as_str(), b.total, b.top_reason()), ("Board", 4, Some("Insufficient budget detail")));
+    assert_eq!(b.reasons, vec![
+        ("Insufficient budget detail".to_string(), 2),
+        ("Out of scope".to_string(), 1),
+        ("Unclassified".to_string(), 1),
+    ]);
+    assert_eq!(b.pct(&2), 50);
+
+    let usage: Vec<(String, i64)> = rejection_reason::find_all(pool).await.unwrap().into_iter().map(|r| (r.name, r.usage)).collect();
+    assert_eq!(usage, vec![("budget".to_string(), 2), ("scope".to_string(), 2)]);
+    assert!(rejection_reason::find_summaries(pool, &[quiet]).await.unwrap().is_empty());
+}
@@ -0,0 +1,99 @@
+//! Remember-me token tests — covers the rotating persistent login tokens.
+//!
+//! Tests the remember_token model layer:
+//! - A token is single-use and is replaced on redemption
+//! - Replaying a consumed token revokes all of the user's tokens
+//! - Expired tokens and inactive users are rejected
+//! - Revocation is scoped to the owning user
+
+mod common;
+
+use ahlt::models::remember_token;
+use chrono::Duration;
+use common::*;
+
+async fn seed_user(pool: &sqlx::PgPool, name: &str) -> i64 {
+    insert_entity(pool, "user", name, name).await
+}
+
+#[tokio::test]
+async fn test_token_rotates_on_use() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let user_id = seed_user(pool, "alice").await;
+
+    let first = remember_token::issue(pool, user_id, "Firefox on Linux", Duration::days(30), None).await.unwrap();
+    let (who, second) = remember_token::consume(pool, &first, Duration::days(30)).await.unwrap().expect("valid token");
+    assert_eq!(who, user_id);
+    assert_ne!(first, second);
+
+    // Still one device, keeping its label
+    let devices = remember_token::find_for_user(pool, user_id).await.unwrap();
+    assert_eq!(devices.len(), 1);
+    assert_eq!(devices[0].label, "Firefox on Linux");
+
+    // The rotated token works once more
+    assert!(remember_token::consume(pool, &second, Duration::days(30)).await.unwrap().is_some());
+}
+
+#[tokio::test]
+async fn test_replayed_token_revokes_all() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let user_id = seed_user(pool, "bob").await;
+
+    let laptop = remember_token::issue(pool, user_id, "Laptop", Duration::days(30), None).await.unwrap();
+    remember_token::issue(pool, user_id, "Phone", Duration::days(30), None).await.unwrap();
+
+    // Known selector with a forged validator looks like a stolen token
+    let selector = laptop.split_once('.').unwrap().0;
+    let forged = format!("{}.{}", selector, "00".repeat(32));
+    assert!(remember_token::consume(pool, &forged, Duration::days(30)).await.unwrap().is_none());
+    assert!(remember_token::find_for_user(pool, user_id).await.unwrap().is_empty());
+
+    // A consumed token cannot be replayed
+    let token = remember_token::issue(pool, user_id, "Laptop", Duration::days(30), None).await.unwrap();
+    assert!(remember_token::consume(pool, &token, Duration::days(30)).await.unwrap().is_some());
+    assert!(remember_token::consume(pool, &token, Duration::days(30)).await.unwrap().is_none());
+    assert!(remember_token::consume(pool, "garbage", Duration::days(30)).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_expired_token_and_inactive_user_rejected() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let user_id = seed_user(pool, "carol").await;
+
+    let expired = remember_token::issue(pool, user_id, "Old", Duration::seconds(-1), None).await.unwrap();
+    assert!(remember_token::consume(pool, &expired, Duration::days(30)).await.unwrap().is_none());
+    assert!(remember_token::find_for_user(pool, user_id).await.unwrap().is_empty(), "expired token is removed");
+
+    let token = remember_token::issue(pool, user_id, "Laptop", Duration::days(30), None).await.unwrap();
+    sqlx::query("UPDATE entities SET is_active = false WHERE id = $1")
+        .bind(user_id)
+        .execute(pool)
+        .await
+        .unwrap();
+    assert!(remember_token::consume(pool, &token, Duration::days(30)).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_revoke_is_scoped_to_owner() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let alice = seed_user(pool, "alice").await;
+    let mallory = seed_user(pool, "mallory").await;
+
+    let token = remember_token::issue(pool, alice, "Laptop", Duration::days(30), None).await.unwrap();
+    let device = remember_token::find_for_user(pool, alice).await.unwrap()[0].id;
+
+    assert!(!remember_token::revoke(pool, mallory, device).await.unwrap());
+    assert!(remember_token::revoke(pool, alice, device).await.unwrap());
+    assert!(remember_token::consume(pool, &token, Duration::days(30)).await.unwrap().is_none());
+
+    remember_token::issue(pool, alice, "A", Duration::days(30), None).await.unwrap();
+    remember_token::issue(pool, alice, "B", Duration::days(30), None).await.unwrap();
+    remember_token::issue(pool, mallory, "C", Duration::days(30), None).await.unwrap();
+    assert_eq!(remember_token::revoke_all(pool, alice).await.unwrap(), 2);
+    assert_eq!(remember_token::find_for_user(pool, mallory).await.unwrap().len(), 1);
+}
@@ -0,0 +1,165 @@
+//! Scheduled report definition tests — covers cron schedules and delivery.
+//!
+//! - Cron fields, steps and the day-of-month/day-of-week rule
+//! - Due definitions run once and reach every member of their group
+//! - Paused definitions are skipped; deleting one removes its deliveries
+//! - E-mail delivery links each recipient to their own copy
+
+mod common;
+
+use ahlt::handlers::warning_handlers::ws::new_connection_map;
+use ahlt::models::report_definition::{self, cron::Schedule, ReportDefinitionInput};
+use ahlt::models::{relation, report_subscription};
+use ahlt::warnings::{clock::with_fixed_now, generators, outbox};
+use chrono::{TimeZone, Utc};
+use common::*;
+use sqlx::PgPool;
+
+#[test]
+fn test_cron_schedules() {
+    let at = |d: u32, h: u32, m: u32| Utc.with_ymd_and_hms(2026, 3, d, h, m, 0).unwrap();
+    let next = |expr: &str, after| Schedule::parse(expr).unwrap().next_after(&after);
+
+    // Wednesday 4 March -> Monday 9 March
+    assert_eq!(next("0 7 * * 1", at(4, 10, 0)), Some(at(9, 7, 0)));
+    assert_eq!(next("*/15 * * * *", at(4, 10, 15)), Some(at(4, 10, 30)));
+    assert_eq!(next("@daily", at(4, 10, 0)), Some(at(5, 0, 0)));
+    // Either the 10th or a Sunday (the 8th)
+    assert_eq!(next("0 0 10 * 7", at(4, 10, 0)), Some(at(8, 0, 0)));
+    assert_eq!(next("0 0 31 2 *", at(4, 10, 0)), None);
+
+    assert!(Schedule::parse("0 7 * *").is_err());
+    assert!(Schedule::parse("60 * * * *").is_err());
+    assert!(Schedule::parse("*/0 * * * *").is_err());
+}
+
+/// A ToR with one position filled by `user_id`.
+async fn tor_with_member(pool: &PgPool, name: &str, label: &str, user_id: i64) -> i64 {
+    let tor_id = insert_entity(pool, "tor", name, label).await;
+    let position = insert_entity(pool, "tor_function", &format!("{}_chair", name), "Chair").await;
+    relation::create(pool, "belongs_to_tor", position, tor_id).await.unwrap();
+    relation::create(pool, "fills_position", user_id, position).await.unwrap();
+    tor_id
+}
+
+async fn proposal(pool: &PgPool, tor_id: i64, name: &str, submitted: &str, status: &str) {
+    let id = insert_entity(pool, "proposal", name, name).await;
+    insert_prop(pool, id, "title", name).await;
+    insert_prop(pool, id, "submitted_date", submitted).await;
+    insert_prop(pool, id, "status", status).await;
+    relation::create(pool, "submitted_to", id, tor_id).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_due_definition_delivers_to_group_once() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let conn_map = new_connection_map();
+    let alice = insert_entity(pool, "user", "alice", "Alice").await;
+    let bob = insert_entity(pool, "user", "bob", "Bob").await;
+    let carol = insert_entity(pool, "user", "carol", "Carol").await;
+    let ops = tor_with_member(pool, "ops", "Ops Board", alice).await;
+    let position = insert_entity(pool, "tor_function", "ops_secretary", "Secretary").await;
+    relation::create(pool, "belongs_to_tor", position, ops).await.unwrap();
+    relation::create(pool, "fills_position", bob, position).await.unwrap();
+    tor_with_member(pool, "fin", "Finance Board", carol).await;
+
+    proposal(pool, ops, "stuck", "2026-02-01", "under_review").await;
+    proposal(pool, ops, "recent", "2026-03-01", "submitted").await;
+    proposal(pool, ops, "done", "2026-01-01", "approved").await;
+
+    let group_key = format!("tor-{}", ops);
+    let input = ReportDefinitionInput {
+        label: "Stuck proposals",
+        report: "pending_proposals",
+        parameter: "14",
+        tor_id: "",
+        schedule: "0 * * * *",
+        format: "csv",
+        group_key: &group_key,
+        delivery: "inbox",
+    };
+    assert!(report_definition::validate(pool, &input).await.unwrap().is_empty());
+    let bad = ReportDefinitionInput { schedule: "daily", group_key: "tor-0", ..input };
+    assert_eq!(report_definition::validate(pool, &bad).await.unwrap().len(), 2);
+
+    let created = Utc.with_ymd_and_hms(2026, 3, 9, 8, 30, 0).unwrap();
+    let id = report_definition::create(pool, &input, created).await.unwrap();
+    let def = report_definition::find_by_id(pool, id).await.unwrap().unwrap();
+    assert_eq!(def.next_run_at, "2026-03-09T09:00:00+00:00");
+
+    let run_at = |h: u32| Utc.with_ymd_and_hms(2026, 3, 9, h, 0, 0).unwrap();
+    with_fixed_now(Utc.with_ymd_and_hms(2026, 3, 9, 8, 59, 0).unwrap(), generators::deliver_report_definitions(pool, &conn_map)).await;
+    assert!(report_subscription::find_deliveries_for_user(pool, alice).await.unwrap().is_empty());
+
+    with_fixed_now(run_at(9), generators::deliver_report_definitions(pool, &conn_map)).await;
+    with_fixed_now(run_at(9), generators::deliver_report_definitions(pool, &conn_map)).await;
+    for user in [alice, bob] {
+        let deliveries = report_subscription::find_deliveries_for_user(pool, user).await.unwrap();
+        assert_eq!(deliveries.len(), 1);
+        let delivery = report_subscription::find_delivery(pool, deliveries[0].id).await.unwrap().unwrap();
+        assert_eq!(delivery.title, "Stuck proposals (as of 2026-03-09)");
+        assert_eq!(
+            delivery.content,
+            "ToR,Proposal,Status,Submitted,Days pending\nOps Board,stuck,under review,2026-02-01,36\n"
+        );
+    }
+    assert!(report_subscription::find_deliveries_for_user(pool, carol).await.unwrap().is_empty());
+
+    let def = report_definition::find_by_id(pool, id).await.unwrap().unwrap();
+    assert_eq!(def.last_run_at, "2026-03-09T09:00:00+00:00");
+    assert_eq!(def.next_run_at, "2026-03-09T10:00:00+00:00");
+
+    // Paused definitions are skipped; resuming does not make up missed runs
+    report_definition::set_active(pool, &def, false, run_at(9)).await.unwrap();
+    with_fixed_now(run_at(12), generators::deliver_report_definitions(pool, &conn_map)).await;
+    assert_eq!(report_subscription::find_deliveries_for_user(pool, alice).await.unwrap().len(), 1);
+    report_definition::set_active(pool, &def, true, run_at(12)).await.unwrap();
+    let def = report_definition::find_by_id(pool, id).await.unwrap().unwrap();
+    assert_eq!(def.next_run_at, "2026-03-09T13:00:00+00:00");
+
+    report_definition::delete(pool, id).await.unwrap();
+    assert!(report_subscription::find_deliveries_for_user(pool, alice).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_email_delivery_links_each_recipients_copy() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let alice = insert_entity(pool, "user", "alice", "Alice").await;
+    insert_prop(pool, alice, "email", "alice@example.org").await;
+    insert_prop(pool, alice, "email_notify_reports", "true").await;
+    let ops = tor_with_member(pool, "ops", "Ops Board", alice).await;
+    let setting = insert_entity(pool, "setting", "email.enabled", "email.enabled").await;
+    insert_prop(pool, setting, "value", "true").await;
+
+    let group_key = format!("tor-{}", ops);
+    let input = ReportDefinitionInput {
+        label: "Weekly backlog",
+        report: "pending_proposals",
+        parameter: "7",
+        tor_id: &ops.to_string(),
+        schedule: "@weekly",
+        format: "html",
+        group_key: &group_key,
+        delivery: "email",
+    };
+    let id = report_definition::create(pool, &input, Utc.with_ymd_and_hms(2026, 3, 4, 12, 0, 0).unwrap()).await.unwrap();
+    with_fixed_now(
+        Utc.with_ymd_and_hms(2026, 3, 8, 0, 5, 0).unwrap(),
+        generators::deliver_report_definitions(pool, &new_connection_map()),
+    ).await;
+
+    let deliveries = report_subscription::find_deliveries_for_user(pool, alice).await.unwrap();
+    assert_eq!(deliveries.len(), 1);
+    let delivery = report_subscription::find_delivery(pool, deliveries[0].id).await.unwrap().unwrap();
+    assert_eq!(delivery.filename(), format!("scheduled-{}-2026-03-08.html", id));
+    assert!(delivery.content.contains("/static/css/print.css"));
+
+    let emails: Vec<_> = outbox::find_recent(pool, None, 100).await.unwrap()
+        .into_iter()
+        .filter(|e| e.channel == outbox::CHANNEL_EMAIL && e.payload.contains("report_delivery"))
+        .collect();
+    assert_eq!(emails.len(), 1);
+    assert!(emails[0].payload.contains(&format!("/reports/deliveries/{}", delivery.id)));
+}
@@ -0,0 +1,133 @@
+//! Report subscription tests — covers scheduling, generation and delivery.
+//!
+//! - Input is checked against the known reports, formats and frequencies
+//! - Schedules advance by day, week or calendar month
+//! - Due subscriptions are generated once, limited to the owner's ToRs
+//! - Each delivery notifies its owner; paused subscriptions are skipped
+
+mod common;
+
+use ahlt::handlers::warning_handlers::ws::new_connection_map;
+use ahlt::models::fiscal::FiscalCalendar;
+use ahlt::models::{relation, report_subscription};
+use ahlt::warnings::{clock::with_fixed_now, generators};
+use chrono::{NaiveDate, TimeZone, Utc};
+use common::*;
+use sqlx::PgPool;
+
+fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(y, m, d).unwrap()
+}
+
+#[test]
+fn test_validate_and_schedule() {
+    assert!(report_subscription::validate("proposal_throughput", "csv", "weekly").is_empty());
+    assert_eq!(report_subscription::validate("users", "pdf", "hourly").len(), 3);
+
+    let fiscal = FiscalCalendar::default();
+
+    assert_eq!(report_subscription::next_after("daily", date(2026, 2, 28), &fiscal), date(2026, 3, 1));
+    assert_eq!(report_subscription::next_after("weekly", date(2026, 2, 28), &fiscal), date(2026, 3, 7));
+    assert_eq!(report_subscription::next_after("monthly", date(2026, 1, 31), &fiscal), date(2026, 2, 28));
+    assert_eq!(
+        report_subscription::period_before("monthly", date(2026, 3, 1), &fiscal),
+        (date(2026, 2, 1), date(2026, 3, 1))
+    );
+}
+
+/// A ToR with one position filled by `user_id`.
+async fn tor_with_member(pool: &PgPool, name: &str, label: &str, user_id: i64) -> i64 {
+    let tor_id = insert_entity(pool, "tor", name, label).await;
+    let position = insert_entity(pool, "tor_function", &format!("{}_chair", name), "Chair").await;
+    relation::create(pool, "belongs_to_tor", position, tor_id).await.unwrap();
+    relation::create(pool, "fills_position", user_id, position).await.unwrap();
+    tor_id
+}
+
+async fn proposal(pool: &PgPool, tor_id: i64, name: &str, submitted: &str, status: &str) {
+    let id = insert_entity(pool, "proposal", name, name).await;
+    insert_prop(pool, id, "title", name).await;
+    insert_prop(pool, id, "submitted_date", submitted).await;
+    insert_prop(pool, id, "status", status).await;
+    relation::create(pool, "submitted_to", id, tor_id).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_due_subscription_delivers_csv_once() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let conn_map = new_connection_map();
+    let alice = insert_entity(pool, "user", "alice", "Alice").await;
+    let ops = tor_with_member(pool, "ops", "Ops, Board", alice).await;
+    let other = insert_entity(pool, "tor", "fin", "Finance Board").await;
+
+    proposal(pool, ops, "p1", "2026-03-02", "approved").await;
+    proposal(pool, ops, "p2", "2026-03-05", "submitted").await;
+    proposal(pool, ops, "p3", "2026-02-20", "rejected").await;
+    proposal(pool, ops, "p4", "2026-03-03", "draft").await;
+    proposal(pool, other, "p5", "2026-03-03", "approved").await;
+
+    let id = report_subscription::create(pool, alice, "proposal_throughput", "csv", "weekly", date(2026, 3, 2))
+        .await.unwrap();
+    let sub = report_subscription::find_by_id(pool, id).await.unwrap().unwrap();
+    assert_eq!(sub.next_run, "2026-03-09");
+
+    // Not yet due
+    let run_at = |d: u32| Utc.with_ymd_and_hms(2026, 3, d, 9, 0, 0).unwrap();
+    with_fixed_now(run_at(8), generators::deliver_report_subscriptions(pool, &conn_map)).await;
+    assert!(report_subscription::find_deliveries_for_user(pool, alice).await.unwrap().is_empty());
+
+    with_fixed_now(run_at(9), generators::deliver_report_subscriptions(pool, &conn_map)).await;
+    with_fixed_now(run_at(9), generators::deliver_report_subscriptions(pool, &conn_map)).await;
+    let deliveries = report_subscription::find_deliveries_for_user(pool, alice).await.unwrap();
+    assert_eq!(deliveries.len(), 1);
+    assert_eq!(deliveries[0].period_start, "2026-03-02");
+    assert_eq!(deliveries[0].title, "Proposal throughput for my ToRs (2026-03-02 to 2026-03-08)");
+
+    let delivery = report_subscription::find_delivery(pool, deliveries[0].id).await.unwrap().unwrap();
+    assert_eq!(delivery.content, "ToR,Submitted,Approved,Rejected,Open\n\"Ops, Board\",2,1,0,1\n");
+    assert_eq!(delivery.filename(), format!("report-{}-2026-03-02.csv", id));
+
+    let sub = report_subscription::find_by_id(pool, id).await.unwrap().unwrap();
+    assert_eq!(sub.next_run, "2026-03-16");
+    assert_eq!(sub.last_run, "2026-03-09");
+
+    let (notified,): (i64,) = sqlx::query_as(
+        "SELECT COUNT(*) FROM entity_properties WHERE key = 'source_action' AND value = 'scheduled.report_subscription'",
+    )
+    .fetch_one(pool).await.unwrap();
+    assert_eq!(notified, 1);
+
+    // Paused subscriptions are skipped, and leave the admin view
+    assert!(report_subscription::set_active(pool, id, false).await.unwrap());
+    assert!(report_subscription::find_all_active(pool).await.unwrap().is_empty());
+    with_fixed_now(run_at(20), generators::deliver_report_subscriptions(pool, &conn_map)).await;
+    assert_eq!(report_subscription::find_deliveries_for_user(pool, alice).await.unwrap().len(), 1);
+
+    assert!(report_subscription::delete(pool, id).await.unwrap());
+    assert!(report_subscription::find_delivery(pool, delivery.id).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_html_delivery_is_printable_and_escaped() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let bob = insert_entity(pool, "user", "bob", "Bob").await;
+    let ops = tor_with_member(pool, "ops", "Ops <Board>", bob).await;
+    proposal(pool, ops, "p1", "2026-03-31", "submitted").await;
+
+    report_subscription::create(pool, bob, "proposal_throughput", "html", "monthly", date(2026, 3, 1))
+        .await.unwrap();
+    with_fixed_now(
+        Utc.with_ymd_and_hms(2026, 4, 1, 6, 0, 0).unwrap(),
+        generators::deliver_report_subscriptions(pool, &new_connection_map()),
+    ).await;
+
+    let deliveries = report_subscription::find_deliveries_for_user(pool, bob).await.unwrap();
+    let delivery = report_subscription::find_delivery(pool, deliveries[0].id).await.unwrap().unwrap();
+    assert_eq!(delivery.content_type(), "text/html; charset=utf-8");
+    assert!(delivery.content.contains("/static/css/print.css"));
+    assert!(delivery.content.contains("(2026-03-01 to 2026-03-31)"));
+    assert!(!delivery.content.contains("Ops <Board>"));
+    assert!(!delivery.content.contains("print-signatures"));
+}
@@ -0,0 +1,70 @@
+//! Revision tests — covers the history kept when proposals and COAs are edited.
+//!
+//! - Every edit that changes a tracked field keeps the previous values
+//! - Saving unchanged text records nothing
+//! - Each revision diffs against the next newer one, the newest against now
+//! - Restoring is an edit, so the version it replaces is kept too
+
+mod common;
+
+use ahlt::models::{coa, proposal, revision};
+use common::*;
+
+#[tokio::test]
+async fn test_proposal_revisions_and_restore() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let tor_id = insert_entity(pool, "tor", "board", "Board").await;
+    let alice = insert_entity(pool, "user", "alice", "Alice").await;
+    let id = proposal::create(pool, tor_id, "Budget", "Line one", "Because", alice, "2026-03-01", None).await.unwrap();
+
+    proposal::update(pool, id, "Budget", "Line one", "Because", alice).await.unwrap();
+    assert!(revision::find_for(pool, &[id]).await.unwrap().is_empty());
+
+    proposal::update(pool, id, "Budget 2027", "Line one\nLine two", "Because", alice).await.unwrap();
+    proposal::update(pool, id, "Budget 2027", "Line one\nLine two", "Needed", alice).await.unwrap();
+    let revisions = revision::find_for(pool, &[id]).await.unwrap();
+    assert_eq!(revisions.len(), 2);
+    let first = revisions[1].clone();
+    assert_eq!((first.field("title"), first.field("description"), first.edited_by.as_str()), ("Budget", "Line one", "Alice"));
+
+    let history = revision::diffs(revisions, &[
+        ("title", "Budget 2027"),
+        ("description", "Line one\nLine two"),
+        ("rationale", "Needed"),
+    ]);
+    let fields = |i: usize| history[i].changes.iter().map(|c| c.field.clone()).collect::<Vec<_>>();
+    assert_eq!(fields(0), vec!["rationale"]);
+    assert_eq!(fields(1), vec!["title", "description"]);
+    let description = &history[1].changes[1];
+    assert_eq!(description.label(), "Description");
+    assert_eq!(description.lines.iter().map(|l| l.marker()).collect::<Vec<_>>(), vec![" ", "+"]);
+
+    // Back to the first version; the version replaced is kept
+    proposal::update(pool, id, first.field("title"), first.field("description"), first.field("rationale"), alice).await.unwrap();
+    let p = proposal::find_by_id(pool, id).await.unwrap().unwrap();
+    assert_eq!((p.title.as_str(), p.description.as_str(), p.rationale.as_str()), ("Budget", "Line one", "Because"));
+    let revisions = revision::find_for(pool, &[id]).await.unwrap();
+    assert_eq!(revisions.len(), 3);
+    assert_eq!(revisions[0].field("rationale"), "Needed");
+    assert_eq!(revision::find_by_id(pool, revisions[0].id).await.unwrap().unwrap().target_id, id);
+}
+
+#[tokio::test]
+async fn test_coa_revisions() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let bob = insert_entity(pool, "user", "bob", "Bob").await;
+    let id = coa::create(pool, "Option A", "Do nothing", "simple", bob).await.unwrap();
+
+    coa::update(pool, id, "Option A", "Do something", bob).await.unwrap();
+    let revisions = revision::find_for(pool, &[id]).await.unwrap();
+    assert_eq!(revisions.len(), 1);
+    assert_eq!(revisions[0].fields, vec![
+        ("title".to_string(), "Option A".to_string()),
+        ("description".to_string(), "Do nothing".to_string()),
+    ]);
+    let history = revision::diffs(revisions, &[("title", "Option A"), ("description", "Do something")]);
+    assert_eq!(history[0].changes.len(), 1);
+    assert_eq!(history[0].changes[0].field, "description");
+}
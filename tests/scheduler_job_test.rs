@@ -0,0 +1,96 @@
+//! Scheduler job state: registration, pause/resume and run-now controls,
+//! and how runs and failures are recorded.
+
+mod common;
+
+use ahlt::handlers::warning_handlers::ws::new_connection_map;
+use ahlt::models::scheduler_job::{self, RunOutcome};
+use ahlt::warnings::scheduler;
+use chrono::{Duration, Utc};
+use common::*;
+
+#[tokio::test]
+async fn test_job_controls_and_run_records() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let now = Utc::now();
+
+    scheduler_job::register(pool, &[("alpha", "Alpha", 300), ("beta", "Beta", 3600)], now).await.unwrap();
+    let jobs = scheduler_job::find_all(pool).await.unwrap();
+    assert_eq!(jobs.iter().map(|j| j.key.as_str()).collect::<Vec<_>>(), ["alpha", "beta"]);
+    assert_eq!(jobs[1].every_label(), "1 h");
+    assert!(jobs.iter().all(|j| j.is_due(now)), "new jobs are due at once");
+
+    let alpha = jobs[0].id;
+    scheduler_job::set_paused(pool, alpha, true).await.unwrap();
+    let state = scheduler_job::find_by_key(pool, "alpha").await.unwrap().unwrap();
+    assert!(state.paused && !state.is_due(now));
+
+    // Run now works while paused
+    scheduler_job::request_run(pool, alpha).await.unwrap();
+    let state = scheduler_job::find_by_key(pool, "alpha").await.unwrap().unwrap();
+    assert!(state.is_due(now));
+
+    scheduler_job::record_start(pool, alpha, now).await.unwrap();
+    let state = scheduler_job::find_by_key(pool, "alpha").await.unwrap().unwrap();
+    assert!(state.running && !state.run_requested && !state.is_due(now));
+
+    let failure = RunOutcome::Failed { error: "boom".to_string(), context: "at step 2".to_string() };
+    scheduler_job::record_finish(pool, alpha, 42, &failure, now, now + Duration::seconds(300)).await.unwrap();
+    let state = scheduler_job::find_by_key(pool, "alpha").await.unwrap().unwrap();
+    assert!(!state.running);
+    assert_eq!((state.run_count, state.failure_count), (1, 1));
+    assert_eq!(state.last_outcome, "failed");
+    assert_eq!(state.last_error, "boom");
+    assert_eq!(state.last_error_context, "at step 2");
+    assert_eq!(state.last_duration_ms, Some(42));
+
+    scheduler_job::set_paused(pool, alpha, false).await.unwrap();
+    let ok = RunOutcome::Succeeded("3 things done".to_string());
+    scheduler_job::record_finish(pool, alpha, 7, &ok, now, now + Duration::seconds(300)).await.unwrap();
+    let state = scheduler_job::find_by_key(pool, "alpha").await.unwrap().unwrap();
+    assert_eq!((state.run_count, state.failure_count), (2, 1));
+    assert_eq!(state.last_outcome, "ok");
+    assert_eq!(state.last_message, "3 things done");
+    assert_eq!(state.last_error, "boom", "the last failure stays visible");
+    assert!(!state.is_due(now) && state.is_due(now + Duration::seconds(300)));
+}
+
+#[tokio::test]
+async fn test_register_records_interrupted_run() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let now = Utc::now();
+
+    scheduler_job::register(pool, &[("alpha", "Alpha", 300)], now).await.unwrap();
+    let alpha = scheduler_job::find_by_key(pool, "alpha").await.unwrap().unwrap();
+    scheduler_job::record_start(pool, alpha.id, now).await.unwrap();
+
+    // The process restarts mid-run
+    scheduler_job::register(pool, &[("alpha", "Alpha renamed", 600)], now).await.unwrap();
+    let state = scheduler_job::find_by_key(pool, "alpha").await.unwrap().unwrap();
+    assert_eq!(state.label, "Alpha renamed");
+    assert_eq!(state.every_secs, 600);
+    assert!(!state.running);
+    assert_eq!(state.last_outcome, "failed");
+    assert!(state.last_error.starts_with("Interrupted"));
+    assert!(state.is_due(now));
+}
+
+#[actix_rt::test]
+async fn test_run_job_records_outcome() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let conn_map = new_connection_map();
+
+    let job = scheduler::find_job("delegation_cleanup").expect("job is registered");
+    scheduler_job::register(pool, &[(job.key, job.label, job.every_secs as i64)], Utc::now()).await.unwrap();
+    let state = scheduler_job::find_by_key(pool, job.key).await.unwrap().unwrap();
+
+    scheduler::run_job(pool, &conn_map, job, state.id).await;
+    let state = scheduler_job::find_by_key(pool, job.key).await.unwrap().unwrap();
+    assert_eq!(state.last_outcome, "ok", "error: {}", state.last_error);
+    assert_eq!(state.run_count, 1);
+    assert!(state.last_duration_ms.is_some());
+    assert!(!state.is_due(Utc::now()));
+}
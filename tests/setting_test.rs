@@ -0,0 +1,197 @@
+//! Settings tests — covers global lookup and per-ToR override resolution.
+//!
+//! Tests the setting model layer:
+//! - ToR → global → default resolution order
+//! - Overrides are isolated per ToR and hidden from the global settings list
+//! - Only `tor_overridable` settings are offered for a ToR
+//! - Clearing an override falls back to the global value
+//! - Value changes are recorded with old/new values and can be rolled back
+//! - Values are validated against declared definitions; typed getters parse them
+
+mod common;
+
+use ahlt::models::setting;
+use common::*;
+
+/// Create a global setting entity with a value, optionally ToR-overridable.
+async fn seed_setting(pool: &sqlx::PgPool, name: &str, value: &str, overridable: bool) -> i64 {
+    let id = insert_entity(pool, "setting", name, name).await;
+    insert_prop(pool, id, "value", value).await;
+    insert_prop(pool, id, "setting_type", "number").await;
+    if overridable {
+        insert_prop(pool, id, "tor_overridable", "true").await;
+    }
+    id
+}
+
+#[tokio::test]
+async fn test_tor_value_resolution_order() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    seed_setting(pool, "suggestions.stale_after_days", "14", true).await;
+    let tor_a = insert_entity(pool, "tor", "board_a", "Board A").await;
+    let tor_b = insert_entity(pool, "tor", "board_b", "Board B").await;
+
+    // No override — global value
+    assert_eq!(setting::get_value_for_tor(pool, tor_a, "suggestions.stale_after_days", "30").await, "14");
+
+    // Override applies to its own ToR only
+    setting::set_tor_override(pool, tor_a, "suggestions.stale_after_days", "5").await.unwrap();
+    assert_eq!(setting::get_value_for_tor(pool, tor_a, "suggestions.stale_after_days", "30").await, "5");
+    assert_eq!(setting::get_value_for_tor(pool, tor_b, "suggestions.stale_after_days", "30").await, "14");
+    assert_eq!(setting::get_value(pool, "suggestions.stale_after_days", "30").await, "14");
+
+    // Replacing the override updates in place
+    setting::set_tor_override(pool, tor_a, "suggestions.stale_after_days", "9").await.unwrap();
+    assert_eq!(setting::get_value_for_tor(pool, tor_a, "suggestions.stale_after_days", "30").await, "9");
+
+    // Unknown setting — default
+    assert_eq!(setting::get_value_for_tor(pool, tor_a, "governance.missing", "30").await, "30");
+}
+
+#[tokio::test]
+async fn test_overrides_hidden_from_global_list() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    seed_setting(pool, "suggestions.stale_after_days", "14", true).await;
+    let tor = insert_entity(pool, "tor", "board", "Board").await;
+
+    setting::set_tor_override(pool, tor, "suggestions.stale_after_days", "5").await.unwrap();
+
+    let all = setting::find_all(pool).await.unwrap();
+    assert_eq!(all.len(), 1, "override must not appear on /settings");
+    assert_eq!(all[0].name, "suggestions.stale_after_days");
+    assert_eq!(all[0].value, "14");
+}
+
+#[tokio::test]
+async fn test_find_for_tor_lists_overridable_settings() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    seed_setting(pool, "suggestions.stale_after_days", "14", true).await;
+    seed_setting(pool, "suggestions.stale_warning_days", "7", true).await;
+    seed_setting(pool, "audit.retention_days", "90", false).await;
+    let tor = insert_entity(pool, "tor", "board", "Board").await;
+
+    setting::set_tor_override(pool, tor, "suggestions.stale_warning_days", "3").await.unwrap();
+
+    let settings = setting::find_for_tor(pool, tor).await.unwrap();
+    let names: Vec<&str> = settings.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(names, vec!["suggestions.stale_after_days", "suggestions.stale_warning_days"]);
+
+    let stale = &settings[0];
+    assert_eq!(stale.override_value, None);
+    assert_eq!(stale.effective_value(), "14");
+
+    let warning = &settings[1];
+    assert_eq!(warning.global_value, "7");
+    assert_eq!(warning.override_value.as_deref(), Some("3"));
+    assert_eq!(warning.effective_value(), "3");
+}
+
+#[tokio::test]
+async fn test_clear_override_falls_back_to_global() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    seed_setting(pool, "suggestions.stale_after_days", "14", true).await;
+    let tor = insert_entity(pool, "tor", "board", "Board").await;
+
+    setting::set_tor_override(pool, tor, "suggestions.stale_after_days", "5").await.unwrap();
+    setting::clear_tor_override(pool, tor, "suggestions.stale_after_days").await.unwrap();
+
+    assert_eq!(setting::get_value_for_tor(pool, tor, "suggestions.stale_after_days", "30").await, "14");
+    let (count,): (i64,) = sqlx::query_as(
+        "SELECT COUNT(*) FROM entities WHERE entity_type = 'setting'",
+    )
+    .fetch_one(pool)
+    .await
+    .unwrap();
+    assert_eq!(count, 1, "override entity should be removed");
+
+    // Clearing again is a no-op
+    setting::clear_tor_override(pool, tor, "suggestions.stale_after_days").await.unwrap();
+}
+
+#[tokio::test]
+async fn test_change_history_and_rollback() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let id = seed_setting(pool, "audit.retention_days", "90", false).await;
+    insert_prop(pool, id, "critical", "true").await;
+    let admin = insert_entity(pool, "user", "admin", "Admin").await;
+
+    // Unchanged values are not recorded
+    assert!(setting::change_value(pool, id, "90", admin, "update").await.unwrap().is_none());
+
+    let first = setting::change_value(pool, id, "30", admin, "update").await.unwrap().expect("recorded");
+    assert_eq!((first.old_value.as_str(), first.new_value.as_str()), ("90", "30"));
+    assert!(first.critical);
+    setting::change_value(pool, id, "7", admin, "update").await.unwrap().expect("recorded");
+    assert_eq!(setting::get_value(pool, "audit.retention_days", "0").await, "7");
+
+    // Newest first, attributed to the user
+    let history = setting::find_history(pool, 10).await.unwrap();
+    assert_eq!(history.len(), 2);
+    assert_eq!((history[0].old_value.as_str(), history[0].new_value.as_str()), ("30", "7"));
+    assert_eq!(history[0].changed_by, "admin");
+
+    // Rolling back the first change restores its old value and is itself recorded
+    let rolled = setting::rollback(pool, first.change_id, admin).await.unwrap().expect("rolled back");
+    assert_eq!((rolled.old_value.as_str(), rolled.new_value.as_str()), ("7", "90"));
+    assert_eq!(setting::get_value(pool, "audit.retention_days", "0").await, "90");
+
+    let history = setting::find_history(pool, 10).await.unwrap();
+    assert_eq!(history.len(), 3);
+    assert_eq!(history[0].kind, "rollback");
+
+    // Rolling back again to the same value is a no-op
+    assert!(setting::rollback(pool, first.change_id, admin).await.unwrap().is_none());
+    assert!(setting::rollback(pool, -1, admin).await.unwrap().is_none());
+}
+
+#[test]
+fn test_validate_against_definitions() {
+    assert!(setting::validate("audit.enabled", "boolean", "true").is_ok());
+    assert!(setting::validate("audit.enabled", "boolean", "yes").is_err());
+    assert!(setting::validate("audit.retention_days", "number", "0").is_ok());
+    assert!(setting::validate("audit.retention_days", "number", "-1").is_err());
+    assert!(setting::validate("audit.retention_days", "number", "ten").is_err());
+    assert!(setting::validate("security.signing_key_grace_days", "number", "366").is_err());
+    assert!(setting::validate("app.name", "text", "").is_ok());
+
+    // Undeclared settings fall back to their stored type
+    assert!(setting::validate("custom.limit", "number", "12").is_ok());
+    assert!(setting::validate("custom.limit", "number", "12x").is_err());
+    assert!(setting::validate("custom.label", "text", "anything").is_ok());
+
+    for def in setting::DEFINITIONS {
+        assert!(
+            setting::validate(def.name, "text", def.default).is_ok(),
+            "default for {} must satisfy its own definition", def.name
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_typed_getters() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+
+    // Missing — declared defaults
+    assert_eq!(setting::get_i64(pool, "email.smtp_port").await, 587);
+    assert!(!setting::get_bool(pool, "audit.enabled").await);
+    assert_eq!(setting::get_duration(pool, "security.signing_key_grace_days").await, chrono::Duration::days(7));
+
+    // Stored values are parsed
+    seed_setting(pool, "email.smtp_port", "2525", false).await;
+    let enabled = insert_entity(pool, "setting", "audit.enabled", "Audit").await;
+    insert_prop(pool, enabled, "value", "true").await;
+    seed_setting(pool, "security.signing_key_grace_days", "3", false).await;
+    assert_eq!(setting::get_i64(pool, "email.smtp_port").await, 2525);
+    assert!(setting::get_bool(pool, "audit.enabled").await);
+    assert_eq!(setting::get_duration(pool, "security.signing_key_grace_days").await, chrono::Duration::days(3));
+
+    // Garbage falls back to the declared default
+    seed_setting(pool, "warnings.retention_deleted_days", "soon", false).await;
+    assert_eq!(setting::get_i64(pool, "warnings.retention_deleted_days").await, 7);
+}
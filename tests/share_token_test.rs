@@ -0,0 +1,100 @@
+//! Share token tests — covers issuing, resolving and scoping read-only embeds.
+//!
+//! - Only a hash of the secret is stored; the raw token resolves once issued
+//! - Expired, suspended, deleted or tampered tokens do not resolve
+//! - The decision register can be limited to one ToR
+
+mod common;
+
+use ahlt::models::{opinion, relation, share_token};
+use ahlt::models::share_token::ShareTokenInput;
+use chrono::NaiveDate;
+use common::*;
+
+fn input<'a>(view: &'a str, tor_id: Option<i64>, expires_at: &'a str) -> ShareTokenInput<'a> {
+    ShareTokenInput { label: "Intranet", view, tor_id, expires_at }
+}
+
+#[test]
+fn test_validate_input() {
+    let today = NaiveDate::from_ymd_opt(2026, 3, 1).unwrap();
+    assert!(share_token::validate(&input("tor_calendar", Some(1), "2026-06-01"), today).is_empty());
+    assert!(share_token::validate(&input("governance_map", None, "2026-03-01"), today).is_empty());
+
+    assert!(!share_token::validate(&input("users", None, "2026-06-01"), today).is_empty());
+    assert!(!share_token::validate(&input("governance_map", Some(1), "2026-06-01"), today).is_empty());
+    assert!(!share_token::validate(&input("tor_calendar", None, "2026-02-28"), today).is_empty());
+    assert!(!share_token::validate(&input("tor_calendar", None, "2027-06-01"), today).is_empty());
+    assert!(!share_token::validate(&input("tor_calendar", None, ""), today).is_empty());
+
+    let mut unnamed = input("tor_calendar", None, "2026-06-01");
+    unnamed.label = "  ";
+    assert!(!share_token::validate(&unnamed, today).is_empty());
+}
+
+#[tokio::test]
+async fn test_resolve_checks_secret_expiry_and_status() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let admin = insert_entity(pool, "user", "admin", "Admin").await;
+    let tor_id = insert_entity(pool, "tor", "ops", "Ops Board").await;
+
+    let (id, raw) = share_token::create(pool, &input("tor_calendar", Some(tor_id), "2026-06-01"), admin)
+        .await.unwrap();
+    let (selector, secret) = raw.split_once('.').unwrap();
+
+    let (stored,): (String,) = sqlx::query_as(
+        "SELECT value FROM entity_properties WHERE entity_id = $1 AND key = 'token_hash'",
+    )
+    .bind(id).fetch_one(pool).await.unwrap();
+    assert_ne!(stored, secret);
+
+    let token = share_token::resolve(pool, &raw, "2026-06-01").await.unwrap().unwrap();
+    assert_eq!(token.id, id);
+    assert_eq!(token.tor_id, Some(tor_id));
+    assert_eq!(token.tor_label.as_deref(), Some("Ops Board"));
+    assert_eq!(token.created_by_name, "Admin");
+    assert!(!token.last_used_at.is_empty());
+
+    assert!(share_token::resolve(pool, &raw, "2026-06-02").await.unwrap().is_none());
+    assert!(share_token::resolve(pool, &format!("{}.{}", selector, "0".repeat(64)), "2026-05-01").await.unwrap().is_none());
+    assert!(share_token::resolve(pool, selector, "2026-05-01").await.unwrap().is_none());
+
+    assert!(share_token::set_active(pool, id, false).await.unwrap());
+    assert!(share_token::resolve(pool, &raw, "2026-05-01").await.unwrap().is_none());
+    assert!(share_token::set_active(pool, id, true).await.unwrap());
+    assert!(share_token::resolve(pool, &raw, "2026-05-01").await.unwrap().is_some());
+
+    assert!(share_token::delete(pool, id).await.unwrap());
+    assert!(share_token::resolve(pool, &raw, "2026-05-01").await.unwrap().is_none());
+    assert!(share_token::find_all(pool).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_decision_register_scoped_to_tor() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let admin = insert_entity(pool, "user", "admin", "Admin").await;
+
+    let mut tors = Vec::new();
+    for (name, label) in [("ops", "Ops Board"), ("fin", "Finance Board")] {
+        let tor_id = insert_entity(pool, "tor", name, label).await;
+        let ap = insert_entity(pool, "agenda_point", &format!("{}_ap", name), "Point").await;
+        insert_prop(pool, ap, "title", &format!("{} budget", label)).await;
+        relation::create(pool, "belongs_to_tor", ap, tor_id).await.unwrap();
+        let coa = insert_entity(pool, "coa", &format!("{}_coa", name), "Approve").await;
+        opinion::record_decision(pool, ap, admin, coa, "Within limits").await.unwrap();
+        tors.push(tor_id);
+    }
+
+    let all = opinion::find_decision_register(pool, None).await.unwrap();
+    assert_eq!(all.len(), 2);
+
+    let ops = opinion::find_decision_register(pool, Some(tors[0])).await.unwrap();
+    assert_eq!(ops.len(), 1);
+    assert_eq!(ops[0].tor_label, "Ops Board");
+    assert_eq!(ops[0].agenda_point_title, "Ops Board budget");
+    assert_eq!(ops[0].selected_coa_title, "Approve");
+    assert_eq!(ops[0].decision_rationale, "Within limits");
+    assert_eq!(ops[0].decided_by_name, "Admin");
+}
@@ -0,0 +1,110 @@
+//! Secrets rotation tests — covers the session key ring and signing keys.
+//!
+//! - Session cookies sealed with a previous `SESSION_KEY` are re-sealed with
+//!   the current one; unknown or current cookies are left alone
+//! - Rotated signing keys keep verifying until retired, by hand or after the
+//!   grace period; share tokens follow their key
+//! - A rotated webhook secret is accepted until its grace period ends
+
+mod common;
+
+use actix_web::cookie::{Cookie, CookieJar, Key};
+use ahlt::auth::session_keys::{COOKIE_NAME, SessionKeys};
+use ahlt::models::share_token::{self, ShareTokenInput};
+use ahlt::models::{signing_key, webhook};
+use common::*;
+
+fn open(key: &Key, sealed: &str) -> Option<String> {
+    CookieJar::new().private(key)
+        .decrypt(Cookie::new(COOKIE_NAME, sealed.to_string()))
+        .map(|c| c.value().to_string())
+}
+
+#[test]
+fn test_session_key_ring() {
+    let old = "o".repeat(64);
+    let new = "n".repeat(64);
+    let before = SessionKeys::from_values(Some(&old), None);
+    let sealed = before.seal(r#"{"user_id":"7"}"#);
+
+    // Rotation: the old key moves to SESSION_KEY_PREVIOUS
+    let after = SessionKeys::from_values(Some(&new), Some(&format!(" {} , short", old)));
+    assert_eq!(after.previous.len(), 1);
+    let resealed = after.reseal(&sealed).unwrap();
+    assert_eq!(open(&after.current, &resealed).as_deref(), Some(r#"{"user_id":"7"}"#));
+    assert!(open(&before.current, &resealed).is_none());
+
+    // Current cookies and cookies from unknown keys are not touched
+    assert!(after.reseal(&resealed).is_none());
+    let stranger = SessionKeys::from_values(Some(&"x".repeat(64)), None).seal("{}");
+    assert!(after.reseal(&stranger).is_none());
+    assert!(after.reseal("not-a-cookie").is_none());
+
+    // Dropping the old key ends the grace
+    let later = SessionKeys::from_values(Some(&new), None);
+    assert!(later.reseal(&sealed).is_none());
+    assert!(open(&later.current, &sealed).is_none());
+}
+
+#[tokio::test]
+async fn test_rotate_and_retire_signing_keys() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let admin = insert_entity(pool, "user", "admin", "Admin").await;
+    let tor_id = insert_entity(pool, "tor", "ops", "Ops Board").await;
+
+    signing_key::ensure_all(pool).await.unwrap();
+    signing_key::ensure_all(pool).await.unwrap();
+    assert_eq!(signing_key::find_all(pool).await.unwrap().len(), signing_key::PURPOSES.len());
+
+    let first = signing_key::sign(pool, "api_key", b"payload").await.unwrap();
+    assert!(signing_key::verify(pool, "api_key", b"payload", &first).await.unwrap());
+    assert!(!signing_key::verify(pool, "api_key", b"other", &first).await.unwrap());
+    assert!(!signing_key::verify(pool, "webhook", b"payload", &first).await.unwrap());
+
+    let input = ShareTokenInput { label: "Intranet", view: "tor_calendar", tor_id: Some(tor_id), expires_at: "2099-01-01" };
+    let (_, raw) = share_token::create(pool, &input, admin).await.unwrap();
+
+    // Rotated out: old signatures still verify, new ones use the new key
+    let new_kid = signing_key::rotate(pool, "api_key").await.unwrap().unwrap();
+    assert!(signing_key::rotate(pool, "carrier_pigeon").await.unwrap().is_none());
+    let second = signing_key::sign(pool, "api_key", b"payload").await.unwrap();
+    assert!(second.starts_with(&format!("{}.", new_kid)));
+    assert!(signing_key::verify(pool, "api_key", b"payload", &first).await.unwrap());
+    signing_key::rotate(pool, "share_token").await.unwrap();
+    assert!(share_token::resolve(pool, &raw, "2026-01-01").await.unwrap().is_some());
+
+    // Retired by hand, or once the grace period has passed
+    let keys = signing_key::find_all(pool).await.unwrap();
+    let old_api = keys.iter().find(|k| k.purpose == "api_key" && k.status == "retiring").unwrap();
+    let active_api = keys.iter().find(|k| k.purpose == "api_key" && k.status == "active").unwrap();
+    assert!(!signing_key::retire(pool, active_api.id).await.unwrap());
+    assert!(signing_key::retire(pool, old_api.id).await.unwrap());
+    assert!(!signing_key::verify(pool, "api_key", b"payload", &first).await.unwrap());
+    assert!(signing_key::verify(pool, "api_key", b"payload", &second).await.unwrap());
+
+    let past = chrono::Utc::now() - chrono::Duration::days(7);
+    assert_eq!(signing_key::retire_expired(pool, past).await.unwrap(), 0);
+    assert_eq!(signing_key::retire_expired(pool, chrono::Utc::now() + chrono::Duration::minutes(1)).await.unwrap(), 1);
+    assert!(share_token::resolve(pool, &raw, "2026-01-01").await.unwrap().is_none());
+
+    // Webhook secrets: the previous one is accepted during the grace period
+    let hook_input = webhook::WebhookInput {
+        slug: "jira", label: "Jira", target: "suggestion", tor_id,
+        title_field: "", description_field: "issue.summary", rationale_field: "", external_id_field: "",
+    };
+    webhook::create(pool, &hook_input, admin).await.unwrap();
+    let hook = webhook::find_by_slug(pool, "jira").await.unwrap().unwrap();
+    let body = br#"{"issue":{"summary":"x"}}"#;
+    let old_signature = webhook::sign(&hook.secret, body);
+    webhook::rotate_secret(pool, hook.id, chrono::Duration::days(7)).await.unwrap();
+    let rotated = webhook::find_by_slug(pool, "jira").await.unwrap().unwrap();
+    let now = chrono::Utc::now();
+    assert!(rotated.accepts(body, &webhook::sign(&rotated.secret, body), now));
+    assert!(rotated.accepts(body, &old_signature, now));
+    assert!(rotated.previous_secret_expiry().is_some());
+    assert!(!rotated.accepts(body, &old_signature, now + chrono::Duration::days(8)));
+    webhook::rotate_secret(pool, hook.id, chrono::Duration::zero()).await.unwrap();
+    let revoked = webhook::find_by_slug(pool, "jira").await.unwrap().unwrap();
+    assert!(!revoked.accepts(body, &webhook::sign(&rotated.secret, body), chrono::Utc::now()));
+}
@@ -0,0 +1,129 @@
+//! SLA policy pack tests — covers classification, pack validation and the
+//! nightly compliance run.
+//!
+//! - Items finished within the limit are met; unfinished items only count
+//!   once they are overdue
+//! - Compliance is measured per ToR against the pack assigned to it, and
+//!   runs once per day
+
+mod common;
+
+use ahlt::models::sla_pack::{self, Outcome, SlaCompliance, SlaPackInput};
+use ahlt::models::{proposal, relation};
+use chrono::NaiveDate;
+use common::*;
+
+fn date(s: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+}
+
+async fn event(pool: &sqlx::PgPool, event_type: &str, proposal_id: i64, at: &str) {
+    sqlx::query(
+        "INSERT INTO domain_events (event_type, aggregate_type, aggregate_id, occurred_at) \
+         VALUES ($1, 'proposal', $2, $3::TIMESTAMPTZ)",
+    )
+    .bind(event_type)
+    .bind(proposal_id)
+    .bind(at)
+    .execute(pool)
+    .await
+    .unwrap();
+}
+
+#[test]
+fn test_classify_and_compliance() {
+    let today = date("2026-10-16");
+    let start = date("2026-10-01");
+    assert_eq!(sla_pack::classify(start, Some(date("2026-10-08")), 7, today), Outcome::Met);
+    assert_eq!(sla_pack::classify(start, Some(date("2026-10-09")), 7, today), Outcome::Breached);
+    assert_eq!(sla_pack::classify(start, None, 7, today), Outcome::Breached);
+    assert_eq!(sla_pack::classify(start, None, 30, today), Outcome::Pending);
+
+    let row = |met, breached| SlaCompliance {
+        tor_id: 1, tor_label: "Board".into(), pack_label: "Standard".into(),
+        metric: "review_days".into(), limit_days: 7, met, breached, pending: 0,
+        computed_on: "2026-10-16".into(),
+    };
+    assert_eq!(row(9, 1).pct(), Some(90));
+    assert_eq!(row(9, 1).badge_class(), "badge-success");
+    assert_eq!(row(3, 1).badge_class(), "badge-warning");
+    assert_eq!(row(1, 1).badge_class(), "badge-danger");
+    assert_eq!(row(0, 0).pct(), None);
+    assert_eq!(row(0, 0).metric_label(), "Review");
+}
+
+#[tokio::test]
+async fn test_nightly_compliance() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let alice = insert_entity(pool, "user", "alice", "Alice").await;
+    let board = insert_entity(pool, "tor", "board", "Board").await;
+    let audit = insert_entity(pool, "tor", "audit", "Audit").await;
+
+    // Validation
+    let input = |name, review, minutes| SlaPackInput {
+        name, label: "Standard", description: "",
+        limits: vec![("review_days", review), ("decision_days", ""), ("minutes_days", minutes)],
+    };
+    let errors = sla_pack::validate(pool, &input("Bad Name", "", ""), None).await.unwrap();
+    assert_eq!(errors, vec!["Name may only contain lowercase letters, digits, '-' and '_'", "Set a limit for at least one metric"]);
+    let errors = sla_pack::validate(pool, &input("standard", "400", "5"), None).await.unwrap();
+    assert_eq!(errors, vec!["Review limit must be between 0 and 365 days"]);
+    let pack_input = input("standard", "7", "10");
+    assert!(sla_pack::validate(pool, &pack_input, None).await.unwrap().is_empty());
+    let pack = sla_pack::create(pool, &pack_input).await.unwrap();
+    let errors = sla_pack::validate(pool, &pack_input, None).await.unwrap();
+    assert_eq!(errors, vec!["An SLA pack named 'standard' already exists"]);
+    assert!(sla_pack::validate(pool, &pack_input, Some(pack)).await.unwrap().is_empty());
+
+    sla_pack::assign(pool, board, Some(pack)).await.unwrap();
+    assert_eq!(sla_pack::find_for_tor(pool, board).await.unwrap().unwrap().review_days, 7);
+    assert!(sla_pack::find_for_tor(pool, audit).await.unwrap().is_none());
+
+    // Reviewed in time, reviewed late, never reviewed and overdue, still pending,
+    // and one submitted before the window
+    let mut ids = Vec::new();
+    for title in ["On time", "Late", "Overdue", "Pending", "Old"] {
+        ids.push(proposal::create(pool, board, title, "d", "", alice, "2026-10-01", None).await.unwrap());
+    }
+    event(pool, "proposal.submitted", ids[0], "2026-10-01T09:00:00Z").await;
+    event(pool, "proposal.under_review", ids[0], "2026-10-05T09:00:00Z").await;
+    event(pool, "proposal.submitted", ids[1], "2026-10-01T09:00:00Z").await;
+    event(pool, "proposal.under_review", ids[1], "2026-10-12T09:00:00Z").await;
+    event(pool, "proposal.submitted", ids[2], "2026-09-20T09:00:00Z").await;
+    event(pool, "proposal.submitted", ids[3], "2026-10-14T09:00:00Z").await;
+    event(pool, "proposal.submitted", ids[4], "2026-05-01T09:00:00Z").await;
+
+    // A completed meeting whose minutes were approved late
+    let meeting = insert_entity(pool, "meeting", "board-2026-10-01", "Board meeting").await;
+    insert_prop(pool, meeting, "meeting_date", "2026-10-01").await;
+    insert_prop(pool, meeting, "status", "completed").await;
+    relation::create(pool, "belongs_to_tor", meeting, board).await.unwrap();
+    let minutes = insert_entity(pool, "minutes", "minutes-board", "Minutes").await;
+    insert_prop(pool, minutes, "status", "approved").await;
+    insert_prop(pool, minutes, "approved_date", "2026-10-15").await;
+    relation::create(pool, "minutes_of", meeting, minutes).await.unwrap();
+
+    let today = date("2026-10-16");
+    assert_eq!(sla_pack::run_nightly(pool, today).await.unwrap(), Some(1));
+    assert_eq!(sla_pack::run_nightly(pool, today).await.unwrap(), None);
+
+    let rows = sla_pack::find_compliance(pool, board).await.unwrap();
+    let metrics: Vec<&str> = rows.iter().map(|c| c.metric.as_str()).collect();
+    assert_eq!(metrics, vec!["review_days", "minutes_days"]);
+    assert_eq!((rows[0].met, rows[0].breached, rows[0].pending), (1, 2, 1));
+    assert_eq!(rows[0].pct(), Some(33));
+    assert_eq!((rows[1].met, rows[1].breached, rows[1].pending), (0, 1, 0));
+    assert_eq!(rows[0].pack_label, "Standard");
+    assert!(sla_pack::find_compliance(pool, audit).await.unwrap().is_empty());
+
+    // Reassigning drops the rows until the next run; deleting the pack clears it
+    sla_pack::assign(pool, board, None).await.unwrap();
+    assert!(sla_pack::find_compliance(pool, board).await.unwrap().is_empty());
+    sla_pack::assign(pool, audit, Some(pack)).await.unwrap();
+    assert_eq!(sla_pack::recompute(pool, date("2026-10-17")).await.unwrap(), 1);
+    assert_eq!(sla_pack::find_compliance(pool, audit).await.unwrap()[0].met, 0);
+    sla_pack::delete(pool, pack).await.unwrap();
+    assert!(sla_pack::find_compliance(pool, audit).await.unwrap().is_empty());
+    assert!(sla_pack::find_for_tor(pool, audit).await.unwrap().is_none());
+}
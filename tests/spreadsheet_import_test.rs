@@ -0,0 +1,122 @@
+//! Spreadsheet import tests — covers the Excel templates and importer.
+//!
+//! - Templates read back with the expected header row
+//! - Rows are checked on their own: required cells, choices, numbers, repeats
+//! - A valid workbook creates ToRs, positions with holders and agenda items
+//! - A workbook with any row error imports nothing
+
+mod common;
+
+use ahlt::models::data_manager::spreadsheet::{self, RowError, SheetKind};
+use ahlt::models::{protocol, tor};
+use common::*;
+use rust_xlsxwriter::Workbook;
+
+fn workbook(rows: &[&[&str]]) -> Vec<u8> {
+    let mut workbook = Workbook::new();
+    let sheet = workbook.add_worksheet();
+    for (r, cells) in rows.iter().enumerate() {
+        for (c, value) in cells.iter().enumerate() {
+            sheet.write_string(r as u32, c as u16, *value).unwrap();
+        }
+    }
+    workbook.save_to_buffer().unwrap()
+}
+
+fn cells(rows: &[&[&str]]) -> Vec<Vec<String>> {
+    rows.iter().map(|r| r.iter().map(|c| c.to_string()).collect()).collect()
+}
+
+#[test]
+fn test_template_and_row_checks() {
+    let bytes = spreadsheet::build_template(SheetKind::Positions).unwrap();
+    let header = &spreadsheet::read_workbook(&bytes).unwrap()[0];
+    assert_eq!(header, &["ToR *", "Name *", "Label *", "Membership type", "Category", "Holder"]);
+    assert_eq!(SheetKind::from_slug("agenda-items"), Some(SheetKind::AgendaItems));
+
+    let (rows, errors) = spreadsheet::parse_rows(SheetKind::AgendaItems, &cells(&[
+        &["tor", "NAME", "Label *", "Type", "Order"],
+        &["board", "approve", "Approve minutes", "Fixed", "1"],
+        &[],
+        &["board", "", "Any other business", "vote", "last"],
+        &["board", "approve", "Approve again", "", ""],
+    ]));
+    assert_eq!(rows.len(), 3);
+    assert_eq!(rows[0].get("step_type"), "fixed");
+    assert_eq!(errors, vec![
+        RowError { row: 4, column: "Name".to_string(), message: "Name is required".to_string() },
+        RowError { row: 4, column: "Type".to_string(), message: "Use one of: procedural, agenda_slot, fixed".to_string() },
+        RowError { row: 4, column: "Order".to_string(), message: "Enter a whole number".to_string() },
+        RowError { row: 5, column: "Name".to_string(), message: "Name 'approve' is already used on row 2".to_string() },
+    ]);
+
+    let (_, errors) = spreadsheet::parse_rows(SheetKind::Tors, &cells(&[&["Name", "Colour"]]));
+    assert_eq!(errors.iter().map(|e| e.column.as_str()).collect::<Vec<_>>(), vec!["Colour", "Label"]);
+}
+
+#[tokio::test]
+async fn test_import_workbooks() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let alice = insert_entity(pool, "user", "alice", "Alice").await;
+
+    let tors = workbook(&[
+        &["Name", "Label", "Meeting cadence", "Cadence time"],
+        &["finance_board", "Finance Board", "Monthly", "09:30"],
+    ]);
+    let report = spreadsheet::import_workbook(pool, SheetKind::Tors, &tors).await.unwrap();
+    assert!(report.errors.is_empty(), "{:?}", report.errors);
+    assert_eq!(report.created, 1);
+    let board = tor::find_all_list_items(pool).await.unwrap()
+        .into_iter()
+        .find(|t| t.name == "finance_board")
+        .unwrap();
+    let detail = tor::find_detail_by_id(pool, board.id).await.unwrap().unwrap();
+    assert_eq!((detail.meeting_cadence.as_str(), detail.status.as_str()), ("monthly", "active"));
+
+    // Unknown ToR and holder, and a holder given two positions: nothing is imported
+    let bad = workbook(&[
+        &["ToR", "Name", "Label", "Holder"],
+        &["finance_board", "fb_chair", "Chair", "alice"],
+        &["audit_board", "fb_member", "Member", "zed"],
+        &["finance_board", "fb_deputy", "Deputy", "alice"],
+    ]);
+    let report = spreadsheet::import_workbook(pool, SheetKind::Positions, &bad).await.unwrap();
+    assert_eq!(report.created, 0);
+    assert_eq!(report.errors.iter().map(|e| (e.row, e.column.as_str())).collect::<Vec<_>>(), vec![
+        (3, "ToR"),
+        (3, "Holder"),
+        (4, "Holder"),
+    ]);
+    assert!(tor::find_members(pool, board.id).await.unwrap().is_empty());
+
+    let positions = workbook(&[
+        &["ToR", "Name", "Label", "Membership type", "Holder"],
+        &["finance_board", "fb_chair", "Chair", "mandatory", "alice"],
+        &["finance_board", "fb_secretary", "Secretary", "", ""],
+    ]);
+    let report = spreadsheet::import_workbook(pool, SheetKind::Positions, &positions).await.unwrap();
+    assert!(report.errors.is_empty(), "{:?}", report.errors);
+    let members = tor::find_members(pool, board.id).await.unwrap();
+    assert_eq!(members.len(), 2);
+    assert_eq!((members[0].position_label.as_str(), members[0].holder_id), ("Chair", Some(alice)));
+    assert_eq!((members[1].membership_type.as_str(), members[1].holder_id), ("optional", None));
+
+    let again = spreadsheet::import_workbook(pool, SheetKind::Positions, &positions).await.unwrap();
+    assert_eq!(again.errors[0].message, "'fb_chair' already exists");
+
+    let items = workbook(&[
+        &["ToR", "Name", "Label", "Duration (minutes)", "Required"],
+        &["finance_board", "fb_opening", "Opening", "5", "Yes"],
+        &["finance_board", "fb_aob", "Any other business", "", "no"],
+    ]);
+    let report = spreadsheet::import_workbook(pool, SheetKind::AgendaItems, &items).await.unwrap();
+    assert!(report.errors.is_empty(), "{:?}", report.errors);
+    let steps = protocol::find_steps_for_tor(pool, board.id).await.unwrap();
+    assert_eq!(steps.iter().map(|s| s.label.as_str()).collect::<Vec<_>>(), vec!["Opening", "Any other business"]);
+    assert_eq!(steps[0].default_duration_minutes, Some(5));
+    assert!(steps[0].is_required && !steps[1].is_required);
+
+    let report = spreadsheet::import_workbook(pool, SheetKind::Tors, b"not a workbook").await.unwrap();
+    assert_eq!(report.errors[0].row, 0);
+}
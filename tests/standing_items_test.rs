@@ -0,0 +1,60 @@
+//! Standing agenda item tests — covers recurring agenda points per ToR.
+//!
+//! - Recurrence rules decide which meetings an item is due for
+//! - Confirming a meeting adds the due items as agenda points in protocol order
+
+mod common;
+
+use ahlt::models::{meeting, protocol};
+use ahlt::models::tor::standing_items;
+use chrono::NaiveDate;
+use common::*;
+
+fn date(s: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+}
+
+#[test]
+fn test_recurrence_rules() {
+    let last = Some(date("2026-10-05"));
+    assert!(standing_items::is_due("monthly", None, date("2026-10-20")));
+    assert!(standing_items::is_due("every", last, date("2026-10-20")));
+    assert!(!standing_items::is_due("monthly", last, date("2026-10-20")));
+    assert!(standing_items::is_due("monthly", last, date("2026-11-02")));
+    assert!(!standing_items::is_due("quarterly", last, date("2026-12-31")));
+    assert!(standing_items::is_due("quarterly", last, date("2027-01-04")));
+    assert!(!standing_items::is_due("annually", last, date("2026-12-31")));
+}
+
+#[tokio::test]
+async fn test_confirmed_meeting_gets_standing_items_in_protocol_order() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+
+    let user = insert_entity(pool, "user", "sec", "Secretary").await;
+    let tor_id = insert_entity(pool, "tor", "safety_board", "Safety Board").await;
+    let opening = protocol::create_step(pool, tor_id, "opening", "Opening", "procedural", 1, None, "", true, "").await.unwrap();
+    let business = protocol::create_step(pool, tor_id, "business", "Business", "agenda_slot", 2, None, "", true, "").await.unwrap();
+
+    standing_items::create(pool, tor_id, "Zz any other business", "", "informative", 10, "", "every", None).await.unwrap();
+    standing_items::create(pool, tor_id, "Budget review", "", "decision", 20, "Treasurer", "quarterly", Some(business)).await.unwrap();
+    standing_items::create(pool, tor_id, "Safety moment", "", "informative", 5, "", "every", Some(opening)).await.unwrap();
+
+    let items = standing_items::find_for_tor(pool, tor_id).await.unwrap();
+    let titles: Vec<&str> = items.iter().map(|i| i.title.as_str()).collect();
+    assert_eq!(titles, vec!["Safety moment", "Budget review", "Zz any other business"]);
+
+    let first = meeting::create(pool, tor_id, "2026-10-20", "Safety Board", "", "", "", "", "", "", "").await.unwrap();
+    let created = standing_items::instantiate(pool, tor_id, first, "2026-10-20", user).await.unwrap();
+    assert_eq!(created.len(), 3);
+    let agenda: Vec<String> = meeting::find_agenda_points(pool, first).await.unwrap()
+        .into_iter().map(|p| p.label).collect();
+    assert_eq!(agenda, vec!["Safety moment", "Budget review", "Zz any other business"]);
+
+    // The quarterly item was already added this quarter.
+    let second = meeting::create(pool, tor_id, "2026-11-17", "Safety Board", "", "", "", "", "", "", "").await.unwrap();
+    standing_items::instantiate(pool, tor_id, second, "2026-11-17", user).await.unwrap();
+    let agenda: Vec<String> = meeting::find_agenda_points(pool, second).await.unwrap()
+        .into_iter().map(|p| p.label).collect();
+    assert_eq!(agenda, vec!["Safety moment", "Zz any other business"]);
+}
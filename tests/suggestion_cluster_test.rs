@@ -0,0 +1,109 @@
+//! Suggestion clustering tests — covers grouping similar open suggestions.
+//!
+//! - Suggestions sharing terms are grouped; unrelated ones are left out
+//! - Refreshing keeps dismissed groups dismissed and drops outdated proposals
+//! - Converting a cluster creates one proposal linked to every open member
+
+mod common;
+
+use ahlt::auth::password;
+use ahlt::models::user::NewUser;
+use ahlt::models::{entity, proposal, suggestion, tor, user};
+use common::setup_test_db;
+
+async fn create_user(pool: &sqlx::PgPool) -> i64 {
+    user::create(
+        pool,
+        &NewUser {
+            username: "clusterer".to_string(),
+            password: password::hash_password("pass").unwrap(),
+            email: "clusterer@test.com".to_string(),
+            display_name: "Cluster Tester".to_string(),
+        },
+    )
+    .await
+    .unwrap()
+}
+
+fn terms(words: &str) -> Vec<String> {
+    words.split_whitespace().map(str::to_string).collect()
+}
+
+#[tokio::test]
+async fn test_group_similar() {
+    let items = vec![
+        (1, terms("parking car space staff")),
+        (2, terms("parking space visitor")),
+        (3, terms("canteen lunch menu")),
+        (4, terms("visitor parking permit")),
+        (5, terms("lunch menu vegetarian")),
+        (6, terms("printer toner")),
+    ];
+    let groups = suggestion::group_similar(&items, suggestion::CLUSTER_THRESHOLD);
+    assert_eq!(groups.len(), 2);
+    assert_eq!(groups[0].members, vec![1, 2, 4]);
+    assert_eq!(groups[0].themes[0], "parking");
+    assert_eq!(groups[1].members, vec![3, 5]);
+    assert_eq!(groups[1].themes, vec!["lunch", "menu"]);
+    assert!(groups[1].score > 0.6);
+
+    assert!(suggestion::group_similar(&items[..1], 0.3).is_empty());
+}
+
+#[tokio::test]
+async fn test_refresh_dismiss_and_convert() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let user_id = create_user(pool).await;
+    let tor_id = tor::create(pool, "cluster_board", "Cluster Board", &[("status", "active")]).await.unwrap();
+
+    let a = suggestion::create(pool, tor_id, "More bicycle parking near the main entrance", user_id, "2026-03-01").await.unwrap();
+    let b = suggestion::create(pool, tor_id, "Covered bicycle parking at the entrance", user_id, "2026-03-02").await.unwrap();
+    suggestion::create(pool, tor_id, "Publish the meeting minutes within a week", user_id, "2026-03-03").await.unwrap();
+
+    assert_eq!(suggestion::refresh_clusters(pool).await.unwrap(), 1);
+    assert_eq!(suggestion::refresh_clusters(pool).await.unwrap(), 0);
+    let clusters = suggestion::find_proposed_clusters(pool, tor_id).await.unwrap();
+    assert_eq!(clusters.len(), 1);
+    let cluster = &clusters[0];
+    assert_eq!(cluster.members.iter().map(|m| m.id).collect::<Vec<_>>(), vec![a, b]);
+    assert!(cluster.themes.contains("bicycl"), "{}", cluster.themes);
+
+    // Dismissed groups stay dismissed
+    suggestion::dismiss_cluster(pool, cluster.id).await.unwrap();
+    assert_eq!(suggestion::refresh_clusters(pool).await.unwrap(), 0);
+    assert!(suggestion::find_proposed_clusters(pool, tor_id).await.unwrap().is_empty());
+
+    // A new member makes a new group, and the old proposal is dropped once it no longer matches
+    let c = suggestion::create(pool, tor_id, "Bicycle parking racks by the entrance", user_id, "2026-03-04").await.unwrap();
+    assert_eq!(suggestion::refresh_clusters(pool).await.unwrap(), 1);
+    let cluster = suggestion::find_proposed_clusters(pool, tor_id).await.unwrap().remove(0);
+    assert_eq!(cluster.members.len(), 3);
+    suggestion::update_status(pool, c, "rejected", Some("Duplicate")).await.unwrap();
+    suggestion::refresh_clusters(pool).await.unwrap();
+    assert!(suggestion::find_cluster(pool, cluster.id).await.unwrap().is_none());
+    suggestion::update_status(pool, c, "open", None).await.unwrap();
+    suggestion::refresh_clusters(pool).await.unwrap();
+    let cluster = suggestion::find_proposed_clusters(pool, tor_id).await.unwrap().remove(0);
+
+    let proposal_id = suggestion::convert_cluster(pool, &cluster, "Bicycle parking", user_id, "2026-03-05").await.unwrap();
+    let detail = proposal::find_by_id(pool, proposal_id).await.unwrap().unwrap();
+    assert_eq!(detail.title, "Bicycle parking");
+    assert!(detail.description.contains(&format!("#{a}")) && detail.description.contains(&format!("#{c}")));
+
+    let listed = suggestion::find_all_for_tor(pool, tor_id).await.unwrap();
+    for id in [a, b, c] {
+        let s = listed.iter().find(|s| s.id == id).unwrap();
+        assert_eq!(s.status, "accepted");
+        assert_eq!(s.spawned_proposal_id, Some(proposal_id));
+    }
+    // Each proposal is listed once even with several source suggestions
+    let proposals = proposal::find_all_for_tor(pool, tor_id).await.unwrap();
+    assert_eq!(proposals.iter().filter(|p| p.id == proposal_id).count(), 1);
+
+    let converted = suggestion::find_cluster(pool, cluster.id).await.unwrap().unwrap();
+    assert_eq!(converted.status, "converted");
+    assert_eq!(converted.proposal_id, Some(proposal_id));
+    assert_eq!(entity::get_property(pool, cluster.id, "status").await.unwrap().as_deref(), Some("converted"));
+    assert!(suggestion::find_proposed_clusters(pool, tor_id).await.unwrap().is_empty());
+}
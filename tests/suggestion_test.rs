@@ -4,8 +4,9 @@ mod common;
 
 use ahlt::auth::password;
 use ahlt::models::user::NewUser;
-use ahlt::models::{suggestion, tor, user};
-use common::setup_test_db;
+use ahlt::models::{setting, suggestion, tor, user};
+use chrono::{Duration, Utc};
+use common::{insert_entity, insert_prop, setup_test_db};
 
 /// Helper: create a test user, returning user id.
 async fn create_test_user(pool: &sqlx::PgPool, suffix: &str) -> i64 {
@@ -185,3 +186,102 @@ async fn test_find_by_id_nonexistent() {
     let result = suggestion::find_by_id(pool, 999_999).await.unwrap();
     assert!(result.is_none());
 }
+
+/// Helper: set a global setting value.
+async fn set(pool: &sqlx::PgPool, name: &str, value: &str) {
+    let id = insert_entity(pool, "setting", name, name).await;
+    insert_prop(pool, id, "value", value).await;
+}
+
+#[tokio::test]
+async fn test_stale_suggestion_warn_close_reopen() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+
+    let user_id = create_test_user(pool, "stale").await;
+    let tor_id = create_test_tor(pool, "tor_stale_sug").await;
+    let sug_id = suggestion::create(pool, tor_id, "Rotate the meeting venue", user_id, "2025-06-01")
+        .await
+        .unwrap();
+    let later = |days: i64| Utc::now() + Duration::days(days);
+
+    // Off by default
+    assert!(suggestion::find_to_warn(pool, later(1000)).await.unwrap().is_empty());
+
+    set(pool, suggestion::STALE_AFTER_DAYS, "30").await;
+    set(pool, suggestion::STALE_WARNING_DAYS, "7").await;
+    set(pool, suggestion::REOPEN_GRACE_DAYS, "14").await;
+    assert!(suggestion::find_to_warn(pool, later(29)).await.unwrap().is_empty());
+
+    let due = suggestion::find_to_warn(pool, later(31)).await.unwrap();
+    assert_eq!(due.len(), 1);
+    assert_eq!(due[0].id, sug_id);
+    assert_eq!(due[0].submitted_by_id, user_id);
+    assert_eq!(due[0].tor_name, "tor_stale_sug");
+    suggestion::mark_warned(pool, &due[0], later(31)).await.unwrap();
+
+    // Warned once; not closable until the warning period has passed
+    assert!(suggestion::find_to_warn(pool, later(31)).await.unwrap().is_empty());
+    assert!(suggestion::find_to_close(pool, later(5)).await.unwrap().is_empty());
+    let due = suggestion::find_to_close(pool, later(8)).await.unwrap();
+    assert_eq!(due.len(), 1);
+
+    let now = later(8);
+    suggestion::auto_close(pool, &due[0], now).await.unwrap();
+    let detail = suggestion::find_by_id(pool, sug_id).await.unwrap().unwrap();
+    assert_eq!(detail.status, "closed");
+    let listed = suggestion::find_all_for_tor(pool, tor_id).await.unwrap();
+    assert!(listed[0].close_reason.as_deref().unwrap().contains("37 days"));
+    assert!(suggestion::find_to_warn(pool, later(1000)).await.unwrap().is_empty());
+
+    // Reopenable within the grace period only
+    assert!(suggestion::can_reopen(pool, sug_id, now + Duration::days(14)).await.unwrap());
+    assert!(!suggestion::can_reopen(pool, sug_id, now + Duration::days(15)).await.unwrap());
+    suggestion::reopen(pool, sug_id).await.unwrap();
+    let detail = suggestion::find_by_id(pool, sug_id).await.unwrap().unwrap();
+    assert_eq!(detail.status, "open");
+    assert!(!suggestion::can_reopen(pool, sug_id, now).await.unwrap());
+    assert!(suggestion::find_to_warn(pool, later(29)).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_stale_suggestion_activity_and_tor_override() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+
+    let user_id = create_test_user(pool, "stale_tor").await;
+    let quiet = create_test_tor(pool, "tor_quiet").await;
+    let busy = create_test_tor(pool, "tor_busy").await;
+    let quiet_sug = suggestion::create(pool, quiet, "Archive old minutes", user_id, "2025-06-01")
+        .await
+        .unwrap();
+    let busy_sug = suggestion::create(pool, busy, "Add a second secretary", user_id, "2025-06-01")
+        .await
+        .unwrap();
+    let later = |days: i64| Utc::now() + Duration::days(days);
+
+    set(pool, suggestion::STALE_AFTER_DAYS, "30").await;
+    set(pool, suggestion::STALE_WARNING_DAYS, "7").await;
+    setting::set_tor_override(pool, busy, suggestion::STALE_AFTER_DAYS, "10").await.unwrap();
+    setting::set_tor_override(pool, quiet, suggestion::STALE_AFTER_DAYS, "0").await.unwrap();
+
+    // The quiet ToR opted out; the busy ToR sunsets sooner
+    let due = suggestion::find_to_warn(pool, later(11)).await.unwrap();
+    assert_eq!(due.iter().map(|s| s.id).collect::<Vec<_>>(), vec![busy_sug]);
+    assert_eq!(due[0].stale_days, 10);
+    assert!(!suggestion::find_to_warn(pool, later(100)).await.unwrap().iter().any(|s| s.id == quiet_sug));
+    suggestion::mark_warned(pool, &due[0], later(11)).await.unwrap();
+
+    // Any activity after the warning keeps it open
+    ahlt::models::entity::set_property(pool, busy_sug, "description", "Add a second secretary, part time")
+        .await
+        .unwrap();
+    assert!(suggestion::find_to_close(pool, later(30)).await.unwrap().is_empty());
+    // ...and a fresh warning follows once it goes quiet again
+    assert_eq!(suggestion::find_to_warn(pool, later(11)).await.unwrap().len(), 1);
+
+    // A malformed override falls back to the global period instead of failing the job
+    setting::set_tor_override(pool, quiet, suggestion::STALE_AFTER_DAYS, "soon").await.unwrap();
+    let due = suggestion::find_to_warn(pool, later(31)).await.unwrap();
+    assert!(due.iter().any(|s| s.id == quiet_sug && s.stale_days == 30));
+}
@@ -0,0 +1,114 @@
+//! Self-assessment survey tests — covers templates, answers and health scores.
+//!
+//! - Questions are parsed one per line; a template needs a rating question
+//! - Ratings are required and must be 1-5; open answers are optional
+//! - Results aggregate every response; answering again replaces the old answers
+//! - The health score comes from the latest answered assessment
+
+mod common;
+
+use std::collections::HashMap;
+
+use ahlt::models::{relation, report_subscription, survey, tor};
+use chrono::NaiveDate;
+use common::*;
+use sqlx::PgPool;
+
+fn form(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+    pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+}
+
+async fn member(pool: &PgPool, tor_id: i64, name: &str) -> i64 {
+    let user = insert_entity(pool, "user", name, name).await;
+    let position = insert_entity(pool, "tor_function", &format!("{name}_seat"), "Member").await;
+    relation::create(pool, "belongs_to_tor", position, tor_id).await.unwrap();
+    tor::assign_to_position(pool, user, position, "mandatory").await.unwrap();
+    user
+}
+
+#[tokio::test]
+async fn test_templates_and_answer_checks() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+
+    let questions = survey::parse_questions("Meetings run on time\n\n  Papers arrive early \n", "What should change?");
+    assert_eq!(
+        questions.iter().map(|q| (q.key.as_str(), q.kind.as_str())).collect::<Vec<_>>(),
+        vec![("q1", "rating"), ("q2", "rating"), ("q3", "text")]
+    );
+    assert_eq!(questions[1].text, "Papers arrive early");
+    assert_eq!(survey::validate_template("", &survey::parse_questions("", "Why?")).len(), 2);
+    assert!(survey::validate_template("Annual review", &questions).is_empty());
+
+    let id = survey::create_template(pool, "Annual review", "Once a year", &questions).await.unwrap();
+    let template = survey::find_template(pool, id).await.unwrap().unwrap();
+    assert_eq!(template.questions_list(), questions);
+
+    let board = insert_entity(pool, "tor", "board", "Board").await;
+    let aid = survey::start_assessment(pool, board, &template, "2026", "").await.unwrap();
+    let assessment = survey::find_assessment(pool, aid).await.unwrap().unwrap();
+    assert!(assessment.is_open());
+    assert_eq!(assessment.label, "Annual review \u{2014} 2026");
+
+    let errors = assessment.check_answers(&form(&[("answer_q1", "6"), ("answer_q3", "Shorter")])).unwrap_err();
+    assert_eq!(errors.len(), 2);
+    let answers = assessment.check_answers(&form(&[("answer_q1", "4"), ("answer_q2", " 2 "), ("answer_q3", "")])).unwrap();
+    assert_eq!(answers, form(&[("q1", "4"), ("q2", "2")]));
+
+    // Deleting the template leaves the assessment's questions in place
+    survey::delete_template(pool, id).await.unwrap();
+    assert!(survey::find_templates(pool).await.unwrap().is_empty());
+    assert_eq!(survey::find_assessment(pool, aid).await.unwrap().unwrap().questions_list().len(), 3);
+
+    survey::close_assessment(pool, aid).await.unwrap();
+    assert!(!survey::find_assessment(pool, aid).await.unwrap().unwrap().is_open());
+}
+
+#[tokio::test]
+async fn test_results_and_health_score() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let board = insert_entity(pool, "tor", "board", "Board").await;
+    let alice = member(pool, board, "alice").await;
+    let bob = member(pool, board, "bob").await;
+    member(pool, board, "carol").await;
+
+    let questions = survey::parse_questions("Meetings run on time\nPapers arrive early", "What should change?");
+    let id = survey::create_template(pool, "Review", "", &questions).await.unwrap();
+    let template = survey::find_template(pool, id).await.unwrap().unwrap();
+    assert!(survey::find_health_score(pool, board).await.unwrap().is_none());
+
+    let first = survey::start_assessment(pool, board, &template, "2026 Q1", "").await.unwrap();
+    survey::submit_response(pool, first, alice, &form(&[("q1", "5"), ("q2", "5")])).await.unwrap();
+    let second = survey::start_assessment(pool, board, &template, "2026 Q2", "").await.unwrap();
+    // An assessment without responses does not replace the last score
+    assert_eq!(survey::find_health_score(pool, board).await.unwrap().unwrap().period, "2026 Q1");
+
+    survey::submit_response(pool, second, alice, &form(&[("q1", "1"), ("q2", "1")])).await.unwrap();
+    survey::submit_response(pool, second, alice, &form(&[("q1", "4"), ("q2", "3"), ("q3", "Fewer items")])).await.unwrap();
+    survey::submit_response(pool, second, bob, &form(&[("q1", "2"), ("q2", "3")])).await.unwrap();
+    assert_eq!(survey::find_response(pool, second, alice).await.unwrap().unwrap()["q3"], "Fewer items");
+    assert!(survey::find_response(pool, second, 0).await.unwrap().is_none());
+
+    let assessment = survey::find_assessment(pool, second).await.unwrap().unwrap();
+    assert_eq!(assessment.responses, 2);
+    let results = survey::find_results(pool, &assessment).await.unwrap();
+    assert_eq!(results.questions[0].distribution, vec![0, 1, 0, 1, 0]);
+    assert_eq!(results.questions[0].average_label(), "3.0");
+    assert_eq!(results.questions[1].percent(&3), 100);
+    assert_eq!(results.questions[2].comments, vec!["Fewer items".to_string()]);
+    // Mean rating 3.0 of 5
+    assert_eq!(results.score, Some(50));
+
+    let health = survey::find_health_score(pool, board).await.unwrap().unwrap();
+    assert_eq!((health.period.as_str(), health.score, health.band()), ("2026 Q2", 50, "Needs attention"));
+    assert_eq!((health.responses, health.members, health.participation()), (2, 3, 66));
+
+    let sub_id = report_subscription::create(
+        pool, alice, "governance_health", "csv", "monthly", NaiveDate::from_ymd_opt(2026, 3, 1).unwrap(),
+    ).await.unwrap();
+    let sub = report_subscription::find_by_id(pool, sub_id).await.unwrap().unwrap();
+    let period = (NaiveDate::from_ymd_opt(2026, 3, 1).unwrap(), NaiveDate::from_ymd_opt(2026, 4, 1).unwrap());
+    let report = report_subscription::build_report(pool, &sub, period).await.unwrap();
+    assert_eq!(report.to_csv(), "ToR,Assessment,Score,Health,Responses\nBoard,2026 Q2,50,Needs attention,2 of 3\n");
+}
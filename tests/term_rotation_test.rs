@@ -0,0 +1,109 @@
+//! Term rotation tests — covers the new-term wizard's model.
+//!
+//! - Start dates must be past the current term and not in the future
+//! - Handovers list only positions whose holder changes
+//! - Starting a term archives the outgoing membership, then applies holders
+//! - Archived terms chain their start and end dates
+
+mod common;
+
+use ahlt::models::{relation, tor::{self, NewHolder, PositionRotation}};
+use chrono::NaiveDate;
+use common::*;
+use sqlx::PgPool;
+
+fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(y, m, d).unwrap()
+}
+
+fn rotation(position_id: i64, holder: NewHolder, membership_type: &str) -> PositionRotation {
+    PositionRotation { position_id, holder, membership_type: membership_type.to_string() }
+}
+
+async fn position(pool: &PgPool, tor_id: i64, name: &str, holder: Option<i64>) -> i64 {
+    let id = insert_entity(pool, "tor_function", name, name).await;
+    insert_prop(pool, id, "membership_type", "mandatory").await;
+    relation::create(pool, "belongs_to_tor", id, tor_id).await.unwrap();
+    if let Some(user_id) = holder {
+        relation::create(pool, "fills_position", user_id, id).await.unwrap();
+    }
+    id
+}
+
+#[test]
+fn test_validate_rotation() {
+    let today = date(2026, 6, 1);
+    let ok = [rotation(1, NewHolder::User(7), "mandatory"), rotation(2, NewHolder::Keep, "optional")];
+    assert!(tor::validate_rotation(Some(today), today, None, &ok).is_empty());
+    assert_eq!(tor::validate_rotation(None, today, None, &ok).len(), 1);
+    assert_eq!(tor::validate_rotation(Some(date(2026, 6, 2)), today, None, &ok).len(), 1);
+
+    let last = tor::MembershipTerm {
+        id: 1,
+        tor_id: 1,
+        start_date: String::new(),
+        end_date: "2026-05-01".to_string(),
+        members: Vec::new(),
+    };
+    let errors = tor::validate_rotation(Some(date(2026, 5, 1)), today, Some(&last), &ok);
+    assert_eq!(errors, vec!["The new term must start after 2026-05-01, when the current term started".to_string()]);
+
+    let twice = [rotation(1, NewHolder::User(7), "mandatory"), rotation(2, NewHolder::User(7), "other")];
+    assert_eq!(tor::validate_rotation(Some(today), today, None, &twice).len(), 2);
+}
+
+#[tokio::test]
+async fn test_new_term_archives_and_rotates() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let tor_id = insert_entity(pool, "tor", "board", "Board").await;
+    let alice = insert_entity(pool, "user", "alice", "Alice").await;
+    let bob = insert_entity(pool, "user", "bob", "Bob").await;
+    let carol = insert_entity(pool, "user", "carol", "Carol").await;
+    let chair = position(pool, tor_id, "Chair", Some(alice)).await;
+    let secretary = position(pool, tor_id, "Secretary", Some(bob)).await;
+    let treasurer = position(pool, tor_id, "Treasurer", None).await;
+
+    let rotations = [
+        rotation(chair, NewHolder::User(carol), "mandatory"),
+        rotation(secretary, NewHolder::Vacant, "optional"),
+        rotation(treasurer, NewHolder::Keep, "mandatory"),
+    ];
+    let handovers = tor::plan_handovers(pool, tor_id, &rotations).await.unwrap();
+    assert_eq!(handovers, vec![
+        tor::Handover {
+            position_id: chair,
+            position_label: "Chair".to_string(),
+            outgoing: Some((alice, "Alice".to_string())),
+            incoming: Some((carol, "Carol".to_string())),
+        },
+        tor::Handover {
+            position_id: secretary,
+            position_label: "Secretary".to_string(),
+            outgoing: Some((bob, "Bob".to_string())),
+            incoming: None,
+        },
+    ]);
+
+    let first = tor::start_new_term(pool, tor_id, date(2026, 1, 1), &rotations).await.unwrap();
+    let members = tor::find_current_members(pool, tor_id).await.unwrap();
+    let holder = |id: i64| members.iter().find(|m| m.position_id == id).unwrap().clone();
+    assert_eq!(holder(chair).holder_id, Some(carol));
+    assert_eq!(holder(secretary).holder_id, None);
+    assert_eq!(holder(secretary).membership_type, "optional");
+
+    let terms = tor::find_terms(pool, tor_id).await.unwrap();
+    assert_eq!(terms.len(), 1);
+    assert_eq!(terms[0].id, first);
+    assert_eq!((terms[0].start_date.as_str(), terms[0].end_date.as_str()), ("", "2026-01-01"));
+    assert_eq!(terms[0].filled_count(), 2);
+    let archived_chair = terms[0].members.iter().find(|m| m.position_id == chair).unwrap();
+    assert_eq!(archived_chair.holder_label.as_deref(), Some("Alice"));
+
+    // The next term starts where the archived one ended
+    tor::start_new_term(pool, tor_id, date(2027, 1, 1), &[]).await.unwrap();
+    let terms = tor::find_terms(pool, tor_id).await.unwrap();
+    assert_eq!((terms[0].start_date.as_str(), terms[0].end_date.as_str()), ("2026-01-01", "2027-01-01"));
+    let archived_chair = terms[0].members.iter().find(|m| m.position_id == chair).unwrap();
+    assert_eq!(archived_chair.holder_id, Some(carol));
+}
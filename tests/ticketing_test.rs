@@ -0,0 +1,178 @@
+//! Ticketing integration tests — covers action item ↔ Jira issue sync.
+//!
+//! - Saving assigns stable refs, keeps ticket fields and marks edits for sync
+//! - Sync results merge into the stored items by ref
+//! - The HTTP client parses fixed-length and chunked responses
+//! - A full sync against a stub Jira creates, links and polls tickets
+
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use ahlt::models::minutes::action_items::{self, TicketUpdate};
+use ahlt::ticketing::{self, http, TicketingConfig};
+use common::*;
+use serde_json::Value;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+fn items(json: &str) -> Vec<Value> {
+    serde_json::from_str(json).unwrap()
+}
+
+#[test]
+fn test_prepare_assigns_refs_and_marks_pending() {
+    let submitted = r#"[{"description":"Draft plan","responsible":"Ann","due_date":"","status":"open"}]"#;
+    let untracked = items(&action_items::prepare_for_save(submitted, "[]", false));
+    assert!(untracked[0]["ref"].as_str().unwrap().starts_with("ai-"));
+    assert!(untracked[0].get("sync").is_none());
+
+    let tracked = items(&action_items::prepare_for_save(submitted, "[]", true));
+    assert_eq!(tracked[0]["sync"], "pending");
+}
+
+#[test]
+fn test_prepare_keeps_ticket_fields_and_ticket_status() {
+    let stored = r#"[{"ref":"ai-1","description":"Draft plan","responsible":"Ann","due_date":"","status":"in_progress",
+                      "external_key":"OPS-1","external_url":"u","external_status":"In Progress"}]"#;
+
+    // Client cannot overwrite ticket fields or the ticket-owned status
+    let same = r#"[{"ref":"ai-1","description":"Draft plan","responsible":"Ann","due_date":"","status":"done","external_key":"X"}]"#;
+    let saved = items(&action_items::prepare_for_save(same, stored, true));
+    assert_eq!(saved[0]["external_key"], "OPS-1");
+    assert_eq!(saved[0]["status"], "in_progress");
+    assert!(saved[0].get("sync").is_none(), "unchanged content needs no push");
+
+    let edited = r#"[{"ref":"ai-1","description":"Final plan","responsible":"Ann","due_date":"","status":"open"}]"#;
+    let saved = items(&action_items::prepare_for_save(edited, stored, true));
+    assert_eq!(saved[0]["sync"], "pending");
+    assert_eq!(saved[0]["external_key"], "OPS-1");
+
+    // Unknown refs are treated as new items
+    let forged = r#"[{"ref":"ai-999","description":"Other","responsible":"","due_date":"","status":"open"}]"#;
+    let saved = items(&action_items::prepare_for_save(forged, stored, false));
+    assert_ne!(saved[0]["ref"], "ai-999");
+}
+
+#[test]
+fn test_apply_updates_clears_pending_only_if_unchanged() {
+    let stored = r#"[{"ref":"a","description":"One","sync":"pending"},{"ref":"b","description":"Two (edited)","sync":"pending"}]"#;
+    let updates = vec![
+        TicketUpdate {
+            ref_id: "a".into(),
+            external_key: Some("OPS-1".into()),
+            pushed: Some(action_items::fingerprint(&serde_json::json!({"description":"One"}))),
+            ..Default::default()
+        },
+        TicketUpdate {
+            ref_id: "b".into(),
+            external_key: Some("OPS-2".into()),
+            pushed: Some(action_items::fingerprint(&serde_json::json!({"description":"Two"}))),
+            ..Default::default()
+        },
+        TicketUpdate { ref_id: "gone".into(), external_key: Some("OPS-3".into()), ..Default::default() },
+    ];
+    let merged = items(&action_items::apply_ticket_updates(stored, &updates));
+    assert_eq!(merged.len(), 2);
+    assert_eq!(merged[0]["external_key"], "OPS-1");
+    assert!(merged[0].get("sync").is_none());
+    assert_eq!(merged[1]["external_key"], "OPS-2");
+    assert_eq!(merged[1]["sync"], "pending", "edited during push stays pending");
+}
+
+#[test]
+fn test_parse_responses() {
+    let fixed = http::parse_response(b"HTTP/1.1 201 Created\r\nContent-Length: 2\r\n\r\n{}extra").unwrap();
+    assert_eq!(fixed.status, 201);
+    assert_eq!(fixed.body, "{}");
+
+    let chunked = http::parse_response(
+        b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n4\r\n{\"a\"\r\n3\r\n:1}\r\n0\r\n\r\n",
+    ).unwrap();
+    assert_eq!(chunked.body, "{\"a\":1}");
+
+    assert!(http::parse_response(b"garbage").is_err());
+}
+
+/// Stub Jira: records request lines and answers create, update and status calls.
+async fn stub_jira(requests: Arc<Mutex<Vec<String>>>) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        loop {
+            let Ok((mut socket, _)) = listener.accept().await else { return };
+            let mut buf = vec![0u8; 8192];
+            let mut len = 0;
+            // Read headers plus the announced body
+            loop {
+                let n = socket.read(&mut buf[len..]).await.unwrap();
+                len += n;
+                let text = String::from_utf8_lossy(&buf[..len]).to_string();
+                if let Some(end) = text.find("\r\n\r\n") {
+                    let body_len = text.lines()
+                        .find_map(|l| l.strip_prefix("Content-Length: "))
+                        .and_then(|v| v.trim().parse::<usize>().ok())
+                        .unwrap_or(0);
+                    if len >= end + 4 + body_len || n == 0 {
+                        break;
+                    }
+                }
+            }
+            let request_line = String::from_utf8_lossy(&buf[..len]).lines().next().unwrap_or("").to_string();
+            requests.lock().unwrap().push(request_line.clone());
+
+            let response = if request_line.starts_with("POST /rest/api/2/issue ") {
+                let body = r#"{"id":"1","key":"OPS-1"}"#;
+                format!("HTTP/1.1 201 Created\r\nTransfer-Encoding: chunked\r\n\r\n{:x}\r\n{}\r\n0\r\n\r\n", body.len(), body)
+            } else if request_line.starts_with("GET /rest/api/2/issue/OPS-1") {
+                let body = r#"{"fields":{"status":{"name":"In Progress","statusCategory":{"key":"indeterminate"}}}}"#;
+                format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n{}", body.len(), body)
+            } else if request_line.starts_with("PUT /rest/api/2/issue/OPS-1") {
+                "HTTP/1.1 204 No Content\r\n\r\n".to_string()
+            } else {
+                "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n".to_string()
+            };
+            let _ = socket.write_all(response.as_bytes()).await;
+            let _ = socket.shutdown().await;
+        }
+    });
+    format!("http://{}", addr)
+}
+
+#[tokio::test]
+async fn test_sync_creates_links_and_polls_tickets() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let cfg = TicketingConfig {
+        base_url: stub_jira(requests.clone()).await,
+        project_key: "OPS".into(),
+        issue_type: "Task".into(),
+        auth_header: "Basic dGVzdDp0ZXN0".into(),
+    };
+
+    let minutes_id = insert_entity(pool, "minutes", "minutes_1", "Board minutes").await;
+    let saved = action_items::prepare_for_save(
+        r#"[{"description":"Draft plan","responsible":"Ann","due_date":"2026-05-01","status":"open"}]"#, "[]", true,
+    );
+    insert_prop(pool, minutes_id, "structured_action_items", &saved).await;
+
+    // First run creates the issue and links it
+    assert_eq!(ticketing::sync_minutes(pool, &cfg, minutes_id, "Board minutes").await.unwrap(), 1);
+    let stored = items(&ahlt::models::minutes::find_structured_action_items(pool, minutes_id).await.unwrap());
+    assert_eq!(stored[0]["external_key"], "OPS-1");
+    assert_eq!(stored[0]["external_url"], format!("{}/browse/OPS-1", cfg.base_url));
+    assert!(stored[0].get("sync").is_none());
+
+    // Second run polls the status back onto the item
+    assert_eq!(ticketing::sync_minutes(pool, &cfg, minutes_id, "Board minutes").await.unwrap(), 0);
+    let stored = items(&ahlt::models::minutes::find_structured_action_items(pool, minutes_id).await.unwrap());
+    assert_eq!(stored[0]["external_status"], "In Progress");
+    assert_eq!(stored[0]["status"], "in_progress");
+
+    let seen = requests.lock().unwrap().clone();
+    assert!(seen[0].starts_with("POST /rest/api/2/issue "));
+    assert!(seen[1].starts_with("GET /rest/api/2/issue/OPS-1?fields=status"));
+
+    let found = ahlt::models::minutes::find_with_ticketed_action_items(pool).await.unwrap();
+    assert_eq!(found, vec![(minutes_id, "Board minutes".to_string())]);
+}
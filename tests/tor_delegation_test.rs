@@ -0,0 +1,113 @@
+//! ToR position delegation tests — covers delegating a position for a window.
+//!
+//! - Delegations are validated and re-delegating to the same user replaces the window
+//! - The delegate holds the position's capabilities only inside the window
+//! - A delegation lapses as soon as the delegator leaves the position
+//! - Revoking and the scheduler cleanup remove delegations
+
+mod common;
+
+use ahlt::auth::abac;
+use ahlt::models::{relation, tor};
+use chrono::NaiveDate;
+use common::*;
+use sqlx::PgPool;
+
+fn day(s: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+}
+
+async fn position(pool: &PgPool, tor_id: i64, name: &str, label: &str, holder: i64) -> i64 {
+    let id = insert_entity(pool, "tor_function", name, label).await;
+    insert_prop(pool, id, "can_call_meetings", "true").await;
+    relation::create(pool, "belongs_to_tor", id, tor_id).await.unwrap();
+    tor::assign_to_position(pool, holder, id, "mandatory").await.unwrap();
+    id
+}
+
+#[tokio::test]
+async fn test_validate_delegation() {
+    assert!(tor::validate_delegation(1, 2, "2026-01-01", "2026-01-31").is_empty());
+    assert!(tor::validate_delegation(1, 2, "2026-01-05", "2026-01-05").is_empty());
+
+    assert_eq!(tor::validate_delegation(0, 2, "2026-01-01", "2026-01-31").len(), 1);
+    assert_eq!(tor::validate_delegation(1, 0, "2026-01-01", "2026-01-31").len(), 1);
+    assert_eq!(tor::validate_delegation(1, 1, "2026-01-01", "2026-01-31").len(), 1);
+    assert_eq!(tor::validate_delegation(1, 2, "2026-02-01", "2026-01-31").len(), 1);
+    assert_eq!(tor::validate_delegation(1, 2, "", "2026-01-31").len(), 1);
+    assert_eq!(tor::validate_delegation(1, 2, "2026-01-01", "31/01/2026").len(), 1);
+}
+
+#[tokio::test]
+async fn test_delegate_inherits_capabilities_during_window() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let board = insert_entity(pool, "tor", "board", "Board").await;
+    let alice = insert_entity(pool, "user", "alice", "Alice").await;
+    let bob = insert_entity(pool, "user", "bob", "Bob").await;
+    let chair = position(pool, board, "chair", "Chair", alice).await;
+
+    let today = chrono::Local::now().date_naive();
+    let fmt = |d: NaiveDate| d.format("%Y-%m-%d").to_string();
+    let yesterday = fmt(today - chrono::Duration::days(1));
+    let next_week = fmt(today + chrono::Duration::days(7));
+    let later = fmt(today + chrono::Duration::days(14));
+
+    assert!(!abac::has_resource_capability(pool, bob, board, "belongs_to_tor", "can_call_meetings").await.unwrap());
+
+    // An upcoming delegation grants nothing yet
+    let id = tor::delegate_position(pool, chair, alice, bob, &next_week, &later, "Leave").await.unwrap();
+    assert!(!abac::has_resource_capability(pool, bob, board, "belongs_to_tor", "can_call_meetings").await.unwrap());
+    let delegations = tor::find_delegations(pool, board).await.unwrap();
+    assert_eq!(delegations.len(), 1);
+    assert_eq!(delegations[0].status_on(today), "Upcoming");
+    assert_eq!(delegations[0].delegator_name, "Alice");
+    assert_eq!(delegations[0].note, "Leave");
+
+    // Delegating again to the same user replaces the window
+    let again = tor::delegate_position(pool, chair, alice, bob, &yesterday, &next_week, "").await.unwrap();
+    assert_eq!(again, id);
+    let delegation = tor::find_delegation(pool, id).await.unwrap().unwrap();
+    assert_eq!(delegation.start_date, yesterday);
+    assert_eq!(delegation.note, "");
+    assert_eq!(delegation.status_on(today), "Active");
+
+    assert!(abac::has_resource_capability(pool, bob, board, "belongs_to_tor", "can_call_meetings").await.unwrap());
+    assert!(abac::load_tor_capabilities(pool, bob, board).await.unwrap().has("can_call_meetings"));
+    assert_eq!(tor::find_active_delegations(pool, bob, board, today).await.unwrap().len(), 1);
+    assert!(tor::find_active_delegations(pool, bob, board, day("2000-01-01")).await.unwrap().is_empty());
+    // The delegator keeps their own capabilities
+    assert!(abac::has_resource_capability(pool, alice, board, "belongs_to_tor", "can_call_meetings").await.unwrap());
+
+    // Once the delegator leaves the position the delegation lapses
+    tor::vacate_position(pool, chair).await.unwrap();
+    assert!(!abac::has_resource_capability(pool, bob, board, "belongs_to_tor", "can_call_meetings").await.unwrap());
+    assert_eq!(tor::find_delegation(pool, id).await.unwrap().unwrap().status_on(today), "Lapsed");
+    assert!(tor::find_active_delegations(pool, bob, board, today).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_revoke_and_cleanup() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let board = insert_entity(pool, "tor", "board", "Board").await;
+    let alice = insert_entity(pool, "user", "alice", "Alice").await;
+    let bob = insert_entity(pool, "user", "bob", "Bob").await;
+    let carol = insert_entity(pool, "user", "carol", "Carol").await;
+    let chair = position(pool, board, "chair", "Chair", alice).await;
+
+    let old = tor::delegate_position(pool, chair, alice, bob, "2026-01-01", "2026-01-31", "").await.unwrap();
+    let current = tor::delegate_position(pool, chair, alice, carol, "2026-02-01", "2026-02-28", "").await.unwrap();
+    let delegations = tor::find_delegations(pool, board).await.unwrap();
+    assert_eq!(delegations.iter().map(|d| d.id).collect::<Vec<_>>(), vec![old, current]);
+    assert_eq!(delegations[0].status_on(day("2026-02-10")), "Ended");
+
+    // Delegations that ended before the day are removed
+    assert_eq!(tor::cleanup_expired_delegations(pool, day("2026-02-10")).await.unwrap(), 1);
+    assert!(tor::find_delegation(pool, old).await.unwrap().is_none());
+    assert!(tor::find_delegation(pool, current).await.unwrap().is_some());
+
+    assert!(tor::revoke_delegation(pool, current).await.unwrap());
+    assert!(!tor::revoke_delegation(pool, current).await.unwrap());
+    assert!(tor::find_delegations(pool, board).await.unwrap().is_empty());
+}
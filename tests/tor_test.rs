@@ -227,3 +227,124 @@ async fn test_find_non_members() {
 
     assert!(!non_members.is_empty());
 }
+
+#[tokio::test]
+async fn test_decision_authority_defaults_and_positions() {
+    use ahlt::auth::session::Permissions;
+    use ahlt::models::tor::{assign_to_position, can_decide, find_decision_authority, save_decision_authority, DecisionAuthority};
+
+    let db = setup_test_db().await;
+    let pool = db.pool();
+
+    let tor_id = create(pool, TEST_TOR_NAME, TEST_TOR_LABEL, &[]).await.expect("Failed to create ToR");
+    let chair = insert_entity(pool, "tor_function", "chair", "Chair").await;
+    let member = insert_entity(pool, "tor_function", "member", "Member").await;
+    for pos in [chair, member] {
+        ahlt::models::relation::create(pool, "belongs_to_tor", pos, tor_id).await.unwrap();
+    }
+    let chair_user = insert_entity(pool, "user", "chair_user", "Chair User").await;
+    let member_user = insert_entity(pool, "user", "member_user", "Member User").await;
+    assign_to_position(pool, chair_user, chair, "mandatory").await.unwrap();
+    assign_to_position(pool, member_user, member, "optional").await.unwrap();
+
+    let decide = Permissions(vec!["agenda.decide".to_string()]);
+    let none = Permissions(vec![]);
+
+    // Default: the agenda.decide permission
+    let authority = find_decision_authority(pool, tor_id).await.unwrap();
+    assert_eq!(authority, DecisionAuthority::Permission);
+    assert!(can_decide(pool, &authority, member_user, &decide).await.unwrap());
+    assert!(!can_decide(pool, &authority, chair_user, &none).await.unwrap());
+
+    // Position-based: only the chair, regardless of permission
+    save_decision_authority(pool, tor_id, &DecisionAuthority::Positions(vec![chair])).await.unwrap();
+    let authority = find_decision_authority(pool, tor_id).await.unwrap();
+    assert_eq!(authority, DecisionAuthority::Positions(vec![chair]));
+    assert!(can_decide(pool, &authority, chair_user, &none).await.unwrap());
+    assert!(!can_decide(pool, &authority, member_user, &decide).await.unwrap());
+
+    let members = find_members(pool, tor_id).await.unwrap();
+    assert_eq!(authority.describe(&members), "Holders of: Chair");
+
+    save_decision_authority(pool, tor_id, &DecisionAuthority::Vote).await.unwrap();
+    assert_eq!(find_decision_authority(pool, tor_id).await.unwrap(), DecisionAuthority::Vote);
+}
+
+#[test]
+fn test_vote_winner_requires_majority() {
+    use ahlt::models::opinion::OpinionListItem;
+    use ahlt::models::tor::vote_winner;
+
+    let op = |id: i64, coa: i64| OpinionListItem {
+        id,
+        recorded_by: id,
+        recorded_by_name: String::new(),
+        preferred_coa_id: coa,
+        commentary: String::new(),
+        created_date: String::new(),
+        version: 1,
+        revised_date: String::new(),
+        considered_version: None,
+    };
+
+    assert_eq!(vote_winner(&[]), None);
+    assert_eq!(vote_winner(&[op(1, 10), op(2, 10), op(3, 20)]), Some(10));
+    // A tie is not a majority
+    assert_eq!(vote_winner(&[op(1, 10), op(2, 20)]), None);
+}
+
+#[tokio::test]
+async fn test_availability_projection_and_suggestions() {
+    use ahlt::models::tor::assign_to_position;
+    use ahlt::models::tor::availability::{add_absence, add_recurring, find_for_tor, project_attendance, suggest_dates};
+    use chrono::{NaiveDate, Weekday};
+
+    let db = setup_test_db().await;
+    let pool = db.pool();
+
+    let tor_id = create(pool, TEST_TOR_NAME, TEST_TOR_LABEL, &[]).await.expect("Failed to create ToR");
+    let chair = insert_entity(pool, "tor_function", "chair", "Chair").await;
+    let secretary = insert_entity(pool, "tor_function", "secretary", "Secretary").await;
+    let observer = insert_entity(pool, "tor_function", "observer", "Observer").await;
+    for pos in [chair, secretary, observer] {
+        ahlt::models::relation::create(pool, "belongs_to_tor", pos, tor_id).await.unwrap();
+    }
+    let chair_user = insert_entity(pool, "user", "chair_user", "Chair User").await;
+    let secretary_user = insert_entity(pool, "user", "secretary_user", "Secretary User").await;
+    let observer_user = insert_entity(pool, "user", "observer_user", "Observer User").await;
+    assign_to_position(pool, chair_user, chair, "mandatory").await.unwrap();
+    assign_to_position(pool, secretary_user, secretary, "mandatory").await.unwrap();
+    assign_to_position(pool, observer_user, observer, "optional").await.unwrap();
+
+    let date = |s: &str| NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap();
+    // The chair never attends on Mondays; the secretary is away 2026-03-10..12
+    add_recurring(pool, tor_id, chair_user, Weekday::Mon, "Part-time").await.unwrap();
+    add_absence(pool, tor_id, secretary_user, date("2026-03-10"), date("2026-03-12"), "Leave").await.unwrap();
+
+    let members = find_members(pool, tor_id).await.unwrap();
+    let entries = find_for_tor(pool, tor_id).await.unwrap();
+    assert_eq!(entries.len(), 2);
+
+    // Monday 2026-03-09: chair away
+    let monday = project_attendance(&members, &entries, date("2026-03-09"));
+    assert_eq!((monday.mandatory_available, monday.mandatory_total), (1, 2));
+    assert_eq!((monday.optional_available, monday.optional_total), (1, 1));
+    assert_eq!(monday.unavailable, vec!["Chair User (Chair)".to_string()]);
+
+    // Wednesday 2026-03-11: secretary away
+    let wednesday = project_attendance(&members, &entries, date("2026-03-11"));
+    assert_eq!(wednesday.mandatory_available, 1);
+
+    // Alternatives to the Monday have everyone available, closest first
+    let suggestions = suggest_dates(&members, &entries, date("2026-03-09"), date("2026-03-01"), 3);
+    let dates: Vec<&str> = suggestions.iter().map(|s| s.date.as_str()).collect();
+    assert_eq!(dates, vec!["2026-03-06", "2026-03-05", "2026-03-13"]);
+    assert!(suggestions.iter().all(|s| s.mandatory_available == 2));
+
+    // Dates in the past are never suggested
+    let suggestions = suggest_dates(&members, &entries, date("2026-03-09"), date("2026-03-08"), 3);
+    assert_eq!(suggestions[0].date, "2026-03-13");
+
+    // Nothing to suggest when everyone can attend
+    assert!(suggest_dates(&members, &entries, date("2026-03-05"), date("2026-03-01"), 3).is_empty());
+}
@@ -0,0 +1,76 @@
+//! Trash tests — covers soft deletion, restore and purge.
+//!
+//! - Deleting a trashable entity hides it from queries and keeps a snapshot
+//! - Other types are still deleted for good
+//! - Restoring brings back id, properties and the links that can be restored
+//! - A name taken since blocks a restore; expired entries are purged
+
+mod common;
+
+use ahlt::models::{entity, relation, trash, user};
+use common::*;
+
+#[tokio::test]
+async fn test_delete_restore_and_purge() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let alice = insert_entity(pool, "user", "alice", "Alice").await;
+    insert_prop(pool, alice, "email", "alice@example.com").await;
+    let editor = insert_entity(pool, "role", "editor", "Editor").await;
+    let board = insert_entity(pool, "tor", "board", "Board").await;
+    let chair = insert_entity(pool, "tor_function", "chair", "Chair").await;
+    relation::create(pool, "has_role", alice, editor).await.unwrap();
+    relation::create(pool, "fills_position", alice, chair).await.unwrap();
+
+    user::delete(pool, alice).await.unwrap();
+    assert!(entity::find_by_id(pool, alice).await.unwrap().is_none());
+    assert!(user::find_display_by_id(pool, alice).await.unwrap().is_none());
+    let entries = trash::find_all(pool).await.unwrap();
+    assert_eq!(entries.len(), 1);
+    let entry = &entries[0];
+    assert_eq!((entry.entity_id, entry.entity_type.as_str(), entry.label.as_str()), (alice, "user", "Alice"));
+    assert_eq!((entry.property_count, entry.relation_count), (1, 2));
+
+    // Types not listed are removed for good
+    let term = insert_entity(pool, "glossary_term", "quorum", "Quorum").await;
+    entity::delete(pool, term).await.unwrap();
+    assert!(entity::find_by_id(pool, term).await.unwrap().is_none());
+    assert_eq!(trash::find_all(pool).await.unwrap().len(), 1);
+
+    // The position is purged meanwhile, so that link cannot come back
+    entity::delete(pool, chair).await.unwrap();
+    let restored = trash::restore(pool, entry.id).await.unwrap().unwrap();
+    assert_eq!((restored.entity_id, restored.relations, restored.dropped), (alice, 1, 1));
+    let back = user::find_display_by_id(pool, alice).await.unwrap().unwrap();
+    assert_eq!((back.email.as_str(), back.role_names.as_str()), ("alice@example.com", "editor"));
+    assert!(trash::find_all(pool).await.unwrap().is_empty());
+    let tombstones: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM deletions WHERE record_kind = 'entity' AND record_id = $1")
+        .bind(alice).fetch_one(pool).await.unwrap();
+    assert_eq!(tombstones, 0);
+
+    // A ToR recreated under the same name blocks restoring the old one
+    entity::delete(pool, board).await.unwrap();
+    insert_entity(pool, "tor", "board", "New Board").await;
+    let entry = trash::find_all(pool).await.unwrap().remove(0);
+    assert!(trash::name_taken(pool, &entry).await.unwrap());
+
+    assert_eq!(trash::purge_expired(pool, chrono::Utc::now() - chrono::Duration::days(1)).await.unwrap(), 0);
+    assert_eq!(trash::purge_expired(pool, chrono::Utc::now() + chrono::Duration::minutes(1)).await.unwrap(), 1);
+    assert!(!trash::purge(pool, entry.id).await.unwrap());
+}
+
+#[tokio::test]
+async fn test_trashed_types_setting() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    assert_eq!(trash::parse_types(" user, ,tor "), vec!["user", "tor"]);
+    let setting = insert_entity(pool, "setting", "trash.entity_types", "trash.entity_types").await;
+    insert_prop(pool, setting, "value", "proposal").await;
+
+    let bob = insert_entity(pool, "user", "bob", "Bob").await;
+    entity::delete(pool, bob).await.unwrap();
+    let proposal = insert_entity(pool, "proposal", "p1", "Budget").await;
+    entity::delete(pool, proposal).await.unwrap();
+    let kept: Vec<String> = trash::find_all(pool).await.unwrap().into_iter().map(|e| e.entity_type).collect();
+    assert_eq!(kept, vec!["proposal"]);
+}
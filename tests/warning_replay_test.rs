@@ -0,0 +1,280 @@
+//! Record-and-replay tests for the warning generators.
+//!
+//! Generator inputs are seeded from fixtures in `tests/fixtures/warnings/` and
+//! every run is pinned with `warnings::clock::with_fixed_now`, so the produced
+//! warnings and receipts can be compared against recorded expectations:
+//! - ToR vacancy snapshot matches the recorded fixture
+//! - Dedup suppresses repeat warnings across runs
+//! - Auto-resolve, then re-raise when a vacancy reopens
+//! - Database size severity escalation against an explicit threshold
+//! - Retention cleanup of resolved and deleted receipts
+
+mod common;
+
+use ahlt::handlers::warning_handlers::ws::new_connection_map;
+use ahlt::models::{entity, relation};
+use ahlt::warnings::{self, clock::with_fixed_now, generators};
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use common::*;
+use serde_json::{json, Value};
+use sqlx::PgPool;
+
+const TOR_VACANCIES: &str = include_str!("fixtures/warnings/tor_vacancies.json");
+const TOR_VACANCIES_EXPECTED: &str = include_str!("fixtures/warnings/tor_vacancies.expected.json");
+
+fn t0() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2026, 1, 5, 9, 0, 0).unwrap()
+}
+
+/// Record every warning with its receipts, ordered by creation, as JSON.
+async fn snapshot(pool: &PgPool) -> Value {
+    let warnings: Vec<(i64, String)> = sqlx::query_as(
+        "SELECT id, name FROM entities WHERE entity_type = 'warning' ORDER BY id",
+    )
+    .fetch_all(pool)
+    .await
+    .unwrap();
+
+    let mut out = Vec::new();
+    for (warning_id, name) in warnings {
+        let props = entity::get_properties(pool, warning_id).await.unwrap();
+        let receipts: Vec<(String, String, String)> = sqlx::query_as(
+            "SELECT u.name, st.value, sa.value
+             FROM relations rw
+             JOIN entities rt_w ON rt_w.id = rw.relation_type_id AND rt_w.name = 'for_warning'
+             JOIN relations ru ON ru.source_id = rw.source_id
+             JOIN entities rt_u ON rt_u.id = ru.relation_type_id AND rt_u.name = 'for_user'
+             JOIN entities u ON u.id = ru.target_id
+             JOIN entity_properties st ON st.entity_id = rw.source_id AND st.key = 'status'
+             JOIN entity_properties sa ON sa.entity_id = rw.source_id AND sa.key = 'status_at'
+             WHERE rw.target_id = $1
+             ORDER BY u.name",
+        )
+        .bind(warning_id)
+        .fetch_all(pool)
+        .await
+        .unwrap();
+
+        out.push(json!({
+            "name": name,
+            "severity": props["severity"],
+            "category": props["category"],
+            "source_action": props["source_action"],
+            "message": props["message"],
+            "status": props["status"],
+            "receipts": receipts.iter().map(|(user, status, status_at)| json!({
+                "user": user, "status": status, "status_at": status_at,
+            })).collect::<Vec<_>>(),
+        }));
+    }
+    Value::Array(out)
+}
+
+async fn count_warnings(pool: &PgPool, source_action: &str, status: &str) -> i64 {
+    let (count,): (i64,) = sqlx::query_as(
+        "SELECT COUNT(*) FROM entities e
+         JOIN entity_properties sa ON sa.entity_id = e.id AND sa.key = 'source_action'
+         JOIN entity_properties st ON st.entity_id = e.id AND st.key = 'status'
+         WHERE e.entity_type = 'warning' AND sa.value = $1 AND st.value = $2",
+    )
+    .bind(source_action)
+    .bind(status)
+    .fetch_one(pool)
+    .await
+    .unwrap();
+    count
+}
+
+async fn entity_id(pool: &PgPool, entity_type: &str, name: &str) -> i64 {
+    entity::find_by_type_and_name(pool, entity_type, name)
+        .await
+        .unwrap()
+        .unwrap_or_else(|| panic!("missing {}:{}", entity_type, name))
+        .id
+}
+
+// ────────────────────────────────────────────────────────────────────
+// 1. Snapshot of a replayed vacancy run
+// ────────────────────────────────────────────────────────────────────
+
+#[tokio::test]
+async fn test_replay_tor_vacancies_matches_recording() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    seed_fixture(pool, TOR_VACANCIES).await;
+    let conn_map = new_connection_map();
+
+    with_fixed_now(t0(), generators::check_tor_vacancies(pool, &conn_map)).await;
+
+    let expected: Value = serde_json::from_str(TOR_VACANCIES_EXPECTED).unwrap();
+    assert_eq!(snapshot(pool).await, expected);
+}
+
+// ────────────────────────────────────────────────────────────────────
+// 2. Dedup across runs
+// ────────────────────────────────────────────────────────────────────
+
+#[tokio::test]
+async fn test_replay_dedup_across_runs() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    seed_fixture(pool, TOR_VACANCIES).await;
+    let conn_map = new_connection_map();
+
+    with_fixed_now(t0(), generators::check_tor_vacancies(pool, &conn_map)).await;
+    let first = snapshot(pool).await;
+
+    // Scheduler ticks every 5 minutes — replay a day's worth of runs
+    for tick in 1..=288 {
+        let at = t0() + Duration::minutes(5 * tick);
+        with_fixed_now(at, generators::check_tor_vacancies(pool, &conn_map)).await;
+    }
+
+    assert_eq!(snapshot(pool).await, first, "repeat runs must not add warnings or receipts");
+}
+
+// ────────────────────────────────────────────────────────────────────
+// 3. Auto-resolve, then re-raise
+// ────────────────────────────────────────────────────────────────────
+
+#[tokio::test]
+async fn test_replay_resolve_and_reraise() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    seed_fixture(pool, TOR_VACANCIES).await;
+    let conn_map = new_connection_map();
+
+    with_fixed_now(t0(), generators::check_tor_vacancies(pool, &conn_map)).await;
+    assert_eq!(count_warnings(pool, "scheduled.tor_vacancy", "active").await, 2);
+
+    // Fill the Safety Council chair — its warning resolves on the next run
+    let ola = entity_id(pool, "user", "member_ola").await;
+    let chair = entity_id(pool, "tor_function", "safety_chair").await;
+    relation::create(pool, "fills_position", ola, chair).await.unwrap();
+
+    let t1 = t0() + Duration::hours(1);
+    with_fixed_now(t1, generators::check_tor_vacancies(pool, &conn_map)).await;
+    assert_eq!(count_warnings(pool, "scheduled.tor_vacancy", "active").await, 1);
+    assert_eq!(count_warnings(pool, "scheduled.tor_vacancy", "resolved").await, 1);
+
+    let resolved = snapshot(pool).await;
+    let safety = resolved
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|w| w["message"].as_str().unwrap().starts_with("Safety Council"))
+        .unwrap();
+    assert_eq!(safety["status"], "resolved");
+    assert_eq!(safety["receipts"][0]["status_at"], "2026-01-05T10:00:00");
+
+    // Vacate the chair again — a fresh warning is raised, the resolved one stays
+    relation::delete(pool, "fills_position", ola, chair).await.unwrap();
+    let t2 = t0() + Duration::hours(2);
+    with_fixed_now(t2, generators::check_tor_vacancies(pool, &conn_map)).await;
+    assert_eq!(count_warnings(pool, "scheduled.tor_vacancy", "active").await, 2);
+    assert_eq!(count_warnings(pool, "scheduled.tor_vacancy", "resolved").await, 1);
+}
+
+// ────────────────────────────────────────────────────────────────────
+// 4. Severity escalation for database size
+// ────────────────────────────────────────────────────────────────────
+
+async fn database_size_severity(pool: &PgPool) -> Option<String> {
+    let row: Option<(String,)> = sqlx::query_as(
+        "SELECT sv.value FROM entities e
+         JOIN entity_properties sa ON sa.entity_id = e.id AND sa.key = 'source_action'
+         JOIN entity_properties st ON st.entity_id = e.id AND st.key = 'status'
+         JOIN entity_properties sv ON sv.entity_id = e.id AND sv.key = 'severity'
+         WHERE e.entity_type = 'warning' AND sa.value = 'scheduled.database_size' AND st.value = 'active'",
+    )
+    .fetch_optional(pool)
+    .await
+    .unwrap();
+    row.map(|r| r.0)
+}
+
+#[tokio::test]
+async fn test_replay_database_size_escalation() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    seed_fixture(pool, TOR_VACANCIES).await;
+    let conn_map = new_connection_map();
+
+    let (size_bytes,): (i64,) = sqlx::query_as("SELECT pg_database_size(current_database())")
+        .fetch_one(pool)
+        .await
+        .unwrap();
+    let size_mb = size_bytes / (1024 * 1024);
+    assert!(size_mb > 2, "test database unexpectedly small: {} MB", size_mb);
+
+    // Below threshold — nothing raised
+    with_fixed_now(t0(), generators::check_database_size_against(pool, &conn_map, size_mb * 10)).await;
+    assert_eq!(database_size_severity(pool).await, None);
+
+    // At threshold — medium
+    with_fixed_now(t0(), generators::check_database_size_against(pool, &conn_map, size_mb)).await;
+    assert_eq!(database_size_severity(pool).await.as_deref(), Some("medium"));
+
+    // Resolve, then replay with more than double the threshold — high
+    let (warning_id,): (i64,) = sqlx::query_as(
+        "SELECT e.id FROM entities e
+         JOIN entity_properties sa ON sa.entity_id = e.id AND sa.key = 'source_action'
+         WHERE e.entity_type = 'warning' AND sa.value = 'scheduled.database_size'",
+    )
+    .fetch_one(pool)
+    .await
+    .unwrap();
+    warnings::resolve_warning(pool, warning_id, 0).await.unwrap();
+
+    let t1 = t0() + Duration::minutes(5);
+    with_fixed_now(t1, generators::check_database_size_against(pool, &conn_map, 1)).await;
+    assert_eq!(database_size_severity(pool).await.as_deref(), Some("high"));
+}
+
+// ────────────────────────────────────────────────────────────────────
+// 5. Retention cleanup
+// ────────────────────────────────────────────────────────────────────
+
+#[tokio::test]
+async fn test_replay_retention_cleanup() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    seed_fixture(pool, TOR_VACANCIES).await;
+    let conn_map = new_connection_map();
+
+    with_fixed_now(t0(), generators::check_tor_vacancies(pool, &conn_map)).await;
+
+    // Resolve one warning and dismiss the other's receipt at t0
+    let anna = entity_id(pool, "user", "sec_anna").await;
+    let ids: Vec<(i64,)> = sqlx::query_as(
+        "SELECT id FROM entities WHERE entity_type = 'warning' ORDER BY id",
+    )
+    .fetch_all(pool)
+    .await
+    .unwrap();
+    let (resolved_id, dismissed_id) = (ids[0].0, ids[1].0);
+    with_fixed_now(t0(), async {
+        warnings::resolve_warning(pool, resolved_id, anna).await.unwrap();
+        let receipt = warnings::queries::find_receipt_for_user(pool, dismissed_id, anna)
+            .await
+            .unwrap()
+            .unwrap();
+        warnings::update_receipt_status(pool, receipt, "deleted", anna).await.unwrap();
+    })
+    .await;
+
+    // Day 6 — both within retention (deleted: 7 days, resolved: 30 days)
+    with_fixed_now(t0() + Duration::days(6), generators::cleanup_old_warnings(pool)).await.unwrap();
+    assert_eq!(entity::count_by_type(pool, "warning_receipt").await.unwrap(), 2);
+
+    // Day 8 — dismissed receipt and its orphaned warning are purged
+    with_fixed_now(t0() + Duration::days(8), generators::cleanup_old_warnings(pool)).await.unwrap();
+    assert_eq!(entity::count_by_type(pool, "warning_receipt").await.unwrap(), 1);
+    assert!(entity::find_by_id(pool, dismissed_id).await.unwrap().is_none());
+    assert!(entity::find_by_id(pool, resolved_id).await.unwrap().is_some());
+
+    // Day 31 — resolved receipt past retention, warning purged too
+    with_fixed_now(t0() + Duration::days(31), generators::cleanup_old_warnings(pool)).await.unwrap();
+    assert_eq!(entity::count_by_type(pool, "warning_receipt").await.unwrap(), 0);
+    assert_eq!(entity::count_by_type(pool, "warning").await.unwrap(), 0);
+}
@@ -36,6 +36,22 @@ async fn test_create_warning() {
 }
 
 
+#[tokio::test]
+async fn test_concurrent_warnings_in_same_second_get_distinct_names() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let at = chrono::Utc::now();
+
+    let create = || warnings::clock::with_fixed_now(at, warnings::create_warning(
+        pool, "low", "system", "scheduled.race", "Raced", "", "system",
+    ));
+    let (a, b, c) = tokio::join!(create(), create(), create());
+    let mut ids = vec![a.unwrap(), b.unwrap(), c.unwrap()];
+    ids.sort();
+    ids.dedup();
+    assert_eq!(ids.len(), 3);
+}
+
 #[tokio::test]
 async fn test_warning_dedup_same_source() {
     let db = setup_test_db().await;
@@ -0,0 +1,118 @@
+//! Inbound webhook tests — covers signature checks, field mapping and delivery.
+//!
+//! - HMAC signatures are verified against the raw body
+//! - Dot paths extract nested and indexed payload fields
+//! - Deliveries create suggestions/proposals and de-duplicate by external id
+
+mod common;
+
+use ahlt::models::webhook::{self, Delivery, WebhookInput};
+use common::*;
+use serde_json::json;
+
+fn input<'a>(slug: &'a str, target: &'a str, tor_id: i64) -> WebhookInput<'a> {
+    WebhookInput {
+        slug,
+        label: "Jira",
+        target,
+        tor_id,
+        title_field: "issue.fields.summary",
+        description_field: "issue.fields.description",
+        rationale_field: "",
+        external_id_field: "issue.key",
+    }
+}
+
+#[test]
+fn test_signature_verification() {
+    let body = br#"{"a":1}"#;
+    let header = webhook::sign("s3cret", body);
+    assert!(webhook::verify_signature("s3cret", body, &header));
+    assert!(!webhook::verify_signature("other", body, &header));
+    assert!(!webhook::verify_signature("s3cret", br#"{"a":2}"#, &header));
+    assert!(!webhook::verify_signature("s3cret", body, "sha256=zz"));
+    assert!(!webhook::verify_signature("s3cret", body, ""));
+}
+
+#[test]
+fn test_extract_paths() {
+    let payload = json!({
+        "issue": { "key": "OPS-7", "fields": { "summary": " Fix it ", "labels": ["a", "b"], "n": 3 } },
+        "empty": ""
+    });
+    assert_eq!(webhook::extract(&payload, "issue.key").as_deref(), Some("OPS-7"));
+    assert_eq!(webhook::extract(&payload, "issue.fields.summary").as_deref(), Some("Fix it"));
+    assert_eq!(webhook::extract(&payload, "issue.fields.labels.1").as_deref(), Some("b"));
+    assert_eq!(webhook::extract(&payload, "issue.fields.n").as_deref(), Some("3"));
+    assert_eq!(webhook::extract(&payload, "issue.fields"), None);
+    assert_eq!(webhook::extract(&payload, "empty"), None);
+    assert_eq!(webhook::extract(&payload, "missing.path"), None);
+    assert_eq!(webhook::extract(&payload, ""), None);
+}
+
+#[test]
+fn test_validate_input() {
+    assert!(webhook::validate(&input("jira-ops", "proposal", 1)).is_empty());
+    assert!(!webhook::validate(&input("Jira Ops", "proposal", 1)).is_empty());
+    assert!(!webhook::validate(&input("jira", "ticket", 1)).is_empty());
+
+    let mut no_title = input("jira", "proposal", 1);
+    no_title.title_field = "";
+    assert!(!webhook::validate(&no_title).is_empty());
+    let mut suggestion = input("jira", "suggestion", 1);
+    suggestion.title_field = "";
+    assert!(webhook::validate(&suggestion).is_empty());
+}
+
+#[tokio::test]
+async fn test_deliver_creates_proposal_once_per_external_id() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let admin = insert_entity(pool, "user", "admin", "Admin").await;
+    let tor_id = insert_entity(pool, "tor", "ops", "Ops Board").await;
+
+    webhook::create(pool, &input("jira", "proposal", tor_id), admin).await.unwrap();
+    let hook = webhook::find_by_slug(pool, "jira").await.unwrap().unwrap();
+    assert_eq!(hook.tor_id, tor_id);
+    assert_eq!(hook.secret.len(), 64);
+
+    let payload = json!({ "issue": { "key": "OPS-1", "fields": { "summary": "New rota", "description": "Details" } } });
+    let created = webhook::deliver(pool, &hook, &payload).await.unwrap();
+    let Delivery::Created { entity_type, id } = created else { panic!("expected creation") };
+    assert_eq!(entity_type, "proposal");
+
+    let repeat = webhook::deliver(pool, &hook, &payload).await.unwrap();
+    assert_eq!(repeat, Delivery::Duplicate { id });
+
+    // Same title, different ticket: a second proposal with a distinct name
+    let other = json!({ "issue": { "key": "OPS-2", "fields": { "summary": "New rota", "description": "More" } } });
+    assert!(matches!(webhook::deliver(pool, &hook, &other).await.unwrap(), Delivery::Created { .. }));
+
+    let missing = json!({ "issue": { "key": "OPS-3", "fields": { "description": "No title" } } });
+    assert_eq!(
+        webhook::deliver(pool, &hook, &missing).await.unwrap(),
+        Delivery::MissingField("issue.fields.summary".to_string())
+    );
+}
+
+#[tokio::test]
+async fn test_deliver_creates_suggestions() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+    let admin = insert_entity(pool, "user", "admin", "Admin").await;
+    let tor_id = insert_entity(pool, "tor", "ops", "Ops Board").await;
+
+    let mut cfg = input("tickets", "suggestion", tor_id);
+    cfg.external_id_field = "";
+    webhook::create(pool, &cfg, admin).await.unwrap();
+    let hook = webhook::find_by_slug(pool, "tickets").await.unwrap().unwrap();
+
+    // Without an external id every delivery creates a new suggestion, even on the same day
+    let payload = json!({ "issue": { "fields": { "description": "Please review" } } });
+    for _ in 0..2 {
+        assert!(matches!(webhook::deliver(pool, &hook, &payload).await.unwrap(), Delivery::Created { .. }));
+    }
+    let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM entities WHERE entity_type = 'suggestion'")
+        .fetch_one(pool).await.unwrap();
+    assert_eq!(count, 2);
+}
@@ -5,6 +5,8 @@
 //! - Status creation, retrieval, updates, and deletion
 //! - Transition creation, retrieval, updates, and deletion
 //! - Validation (duplicate statuses, invalid status references, cascade constraints)
+//! - Guard conditions and timed auto-transitions
+//! - Transition hooks run by execute_transition
 
 mod common;
 
@@ -16,6 +18,11 @@ const TEST_STATUS_CODE: &str = "draft";
 const TEST_STATUS_LABEL: &str = "Draft";
 const TEST_TRANSITION_LABEL: &str = "Submit";
 
+/// A manual transition with no permission, guard or hooks.
+fn transition(label: &str) -> TransitionInput<'_> {
+    TransitionInput { label, ..Default::default() }
+}
+
 #[tokio::test]
 async fn test_list_workflow_scopes_empty() {
     let db = setup_test_db().await;
@@ -139,7 +146,7 @@ async fn test_create_transition_success() {
         .await
         .expect("Failed to create active status");
 
-    let transition_id = create_transition(pool, TEST_SCOPE, draft_id, active_id, TEST_TRANSITION_LABEL, "", false, "")
+    let transition_id = create_transition(pool, TEST_SCOPE, draft_id, active_id, &transition(TEST_TRANSITION_LABEL))
         .await
         .expect("Failed to create transition");
 
@@ -165,7 +172,7 @@ async fn test_create_transition_invalid_status() {
         .expect("Failed to create draft status");
 
     // Try to create transition to non-existent status
-    let result = create_transition(pool, TEST_SCOPE, draft_id, 9999, TEST_TRANSITION_LABEL, "", false, "").await;
+    let result = create_transition(pool, TEST_SCOPE, draft_id, 9999, &transition(TEST_TRANSITION_LABEL)).await;
 
     // Should fail when looking up target status properties
     assert!(result.is_err());
@@ -186,10 +193,10 @@ async fn test_list_transitions_for_scope() {
         .await
         .expect("Failed to create done status");
 
-    let _ = create_transition(pool, TEST_SCOPE, draft_id, active_id, "Submit", "", false, "")
+    let _ = create_transition(pool, TEST_SCOPE, draft_id, active_id, &transition("Submit"))
         .await
         .expect("Failed to create draft->active transition");
-    let _ = create_transition(pool, TEST_SCOPE, active_id, done_id, "Complete", "", false, "")
+    let _ = create_transition(pool, TEST_SCOPE, active_id, done_id, &transition("Complete"))
         .await
         .expect("Failed to create active->done transition");
 
@@ -212,12 +219,12 @@ async fn test_update_transition_success() {
         .await
         .expect("Failed to create active status");
 
-    let transition_id = create_transition(pool, TEST_SCOPE, draft_id, active_id, "Submit", "", false, "")
+    let transition_id = create_transition(pool, TEST_SCOPE, draft_id, active_id, &transition("Submit"))
         .await
         .expect("Failed to create transition");
 
     let new_label = "Submit for Review";
-    let _ = update_transition(pool, transition_id, new_label, "permission.workflow.submit", false, "")
+    let _ = update_transition(pool, transition_id, &TransitionInput { required_permission: "permission.workflow.submit", ..transition(new_label) })
         .await
         .expect("Failed to update transition");
 
@@ -241,7 +248,7 @@ async fn test_delete_transition_success() {
         .await
         .expect("Failed to create active status");
 
-    let transition_id = create_transition(pool, TEST_SCOPE, draft_id, active_id, "Submit", "", false, "")
+    let transition_id = create_transition(pool, TEST_SCOPE, draft_id, active_id, &transition("Submit"))
         .await
         .expect("Failed to create transition");
 
@@ -268,7 +275,7 @@ async fn test_delete_status_with_transitions() {
         .await
         .expect("Failed to create active status");
 
-    let _ = create_transition(pool, TEST_SCOPE, draft_id, active_id, "Submit", "", false, "")
+    let _ = create_transition(pool, TEST_SCOPE, draft_id, active_id, &transition("Submit"))
         .await
         .expect("Failed to create transition");
 
@@ -278,3 +285,155 @@ async fn test_delete_status_with_transitions() {
     // Should fail because status is referenced by a transition
     assert!(result.is_err());
 }
+
+#[tokio::test]
+async fn test_transition_guards_filter_available_transitions() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+
+    let draft_id = create_status(pool, TEST_SCOPE, "draft", "Draft", 0, true, false).await.unwrap();
+    let active_id = create_status(pool, TEST_SCOPE, "active", "Active", 1, false, false).await.unwrap();
+    let _ = create_transition(pool, TEST_SCOPE, draft_id, active_id, &TransitionInput { condition: "priority>=2; has:linked_to", ..transition("Submit") })
+        .await
+        .expect("Failed to create transition");
+
+    let linked_to = insert_entity(pool, "relation_type", "linked_to", "Linked To").await;
+    let item = insert_entity(pool, TEST_SCOPE, "item_1", "Item 1").await;
+    let other = insert_entity(pool, TEST_SCOPE, "item_2", "Item 2").await;
+    insert_prop(pool, item, "status", "draft").await;
+    insert_prop(pool, item, "priority", "1").await;
+    let perms = ahlt::auth::session::Permissions(vec![]);
+
+    // Priority too low and no relation yet
+    let available = find_available_transitions(pool, TEST_SCOPE, "draft", &perms, item).await.unwrap();
+    assert!(available.is_empty());
+
+    insert_prop(pool, item, "priority", "3").await;
+    let available = find_available_transitions(pool, TEST_SCOPE, "draft", &perms, item).await.unwrap();
+    assert!(available.is_empty(), "relation guard still fails");
+
+    // The relation counts in either direction
+    insert_relation(pool, linked_to, other, item).await;
+    let available = find_available_transitions(pool, TEST_SCOPE, "draft", &perms, item).await.unwrap();
+    assert_eq!(available.len(), 1);
+    assert_eq!(available[0].to_status_code, "active");
+
+    assert!(guards::parse("priority>high").is_err());
+    assert!(guards::parse("has:").is_err());
+    assert!(guards::parse("status").is_err());
+    assert_eq!(guards::parse(" ; a=b ;").unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_auto_transition_after_days_in_status() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+
+    let draft_id = create_status(pool, TEST_SCOPE, "draft", "Draft", 0, true, false).await.unwrap();
+    let expired_id = create_status(pool, TEST_SCOPE, "expired", "Expired", 1, false, true).await.unwrap();
+    let transition_id = create_transition(pool, TEST_SCOPE, draft_id, expired_id, &TransitionInput { auto_after_days: 3, ..transition("Expire") })
+        .await
+        .expect("Failed to create transition");
+    assert_eq!(list_transitions_for_scope(pool, TEST_SCOPE).await.unwrap()[0].auto_after_days, 3);
+
+    let item = insert_entity(pool, TEST_SCOPE, "item_1", "Item 1").await;
+    insert_prop(pool, item, "status", "draft").await;
+    let changed_at = ahlt::models::entity::get_property(pool, item, "status_changed_at").await.unwrap();
+    assert!(changed_at.is_some(), "trigger records when the status changed");
+
+    let now = chrono::Utc::now();
+    assert!(auto::find_due(pool, now).await.unwrap().is_empty());
+    let due = auto::find_due(pool, now + chrono::Duration::days(4)).await.unwrap();
+    assert_eq!(due.len(), 1);
+    assert_eq!(due[0].entity_id, item);
+    assert_eq!(due[0].to_status_code, "expired");
+
+    // A guard that fails holds the transition back
+    update_transition(pool, transition_id, &TransitionInput { condition: "keep!=yes", auto_after_days: 3, ..transition("Expire") }).await.unwrap();
+    insert_prop(pool, item, "keep", "yes").await;
+    let due = auto::find_due(pool, now + chrono::Duration::days(4)).await.unwrap();
+    assert!(!auto::guards_hold(pool, &due[0]).await.unwrap());
+
+    insert_prop(pool, item, "keep", "no").await;
+    assert!(auto::guards_hold(pool, &due[0]).await.unwrap());
+    auto::apply(pool, &due[0]).await.unwrap();
+    assert_eq!(
+        ahlt::models::entity::get_property(pool, item, "status").await.unwrap().as_deref(),
+        Some("expired")
+    );
+    assert!(auto::find_due(pool, now + chrono::Duration::days(4)).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_execute_transition_runs_hooks() {
+    let db = setup_test_db().await;
+    let pool = db.pool();
+
+    let draft_id = create_status(pool, TEST_SCOPE, "draft", "Draft", 0, true, false).await.unwrap();
+    let active_id = create_status(pool, TEST_SCOPE, "active", "Active", 1, false, false).await.unwrap();
+    let hooks_json = r#"[
+        {"action": "set_property", "key": "activated_from", "value": "{from} ({label})"},
+        {"action": "send_webhook", "event": "activated"},
+        {"action": "spawn_entity", "entity_type": "action_item", "label": "Follow up {label}", "relation_type": "arising_from"}
+    ]"#;
+    let transition_id = create_transition(pool, TEST_SCOPE, draft_id, active_id, &TransitionInput { hooks: hooks_json, ..transition("Activate") })
+        .await
+        .expect("Failed to create transition");
+    assert_eq!(list_transitions_for_scope(pool, TEST_SCOPE).await.unwrap()[0].hook_count(), 3);
+
+    let arising_from = insert_entity(pool, "relation_type", "arising_from", "Arising From").await;
+    let item = insert_entity(pool, TEST_SCOPE, "item_1", "Item 1").await;
+    insert_prop(pool, item, "status", "draft").await;
+    let perms = ahlt::auth::session::Permissions(vec![]);
+
+    // An invalid transition changes nothing
+    assert!(execute_transition(pool, TEST_SCOPE, item, "active", "draft", &perms, 1).await.is_err());
+
+    let taken = execute_transition(pool, TEST_SCOPE, item, "draft", "active", &perms, 1).await.unwrap();
+    assert_eq!(taken.transition_id, transition_id);
+    let props = ahlt::models::entity::get_properties(pool, item).await.unwrap();
+    assert_eq!(props.get("status").map(String::as_str), Some("active"));
+    assert_eq!(props.get("activated_from").map(String::as_str), Some("draft (Item 1)"));
+
+    let (events,): (i64,) = sqlx::query_as(
+        "SELECT COUNT(*) FROM domain_events WHERE event_type = $1 AND aggregate_id = $2",
+    )
+    .bind(format!("{}.activated", TEST_SCOPE))
+    .bind(item)
+    .fetch_one(pool)
+    .await
+    .unwrap();
+    assert_eq!(events, 1);
+
+    let spawned = ahlt::models::entity::find_by_type(pool, "action_item").await.unwrap();
+    assert_eq!(spawned.len(), 1);
+    assert_eq!(spawned[0].label, "Follow up Item 1");
+    let (linked,): (i64,) = sqlx::query_as(
+        "SELECT COUNT(*) FROM relations WHERE relation_type_id = $1 AND source_id = $2 AND target_id = $3",
+    )
+    .bind(arising_from)
+    .bind(spawned[0].id)
+    .bind(item)
+    .fetch_one(pool)
+    .await
+    .unwrap();
+    assert_eq!(linked, 1);
+}
+
+#[test]
+fn test_parse_transition_hooks() {
+    assert!(hooks::parse("  ").unwrap().is_empty());
+    assert_eq!(
+        hooks::parse(r#"[{"action": "create_warning", "severity": "high", "message": "{label} escalated"}]"#).unwrap(),
+        vec![hooks::Hook::CreateWarning {
+            severity: "high".to_string(),
+            message: "{label} escalated".to_string(),
+            permission: String::new(),
+        }]
+    );
+    assert!(hooks::parse(r#"{"action": "send_webhook", "event": "x"}"#).is_err(), "must be an array");
+    assert!(hooks::parse(r#"[{"action": "delete_everything"}]"#).is_err());
+    assert!(hooks::parse(r#"[{"action": "create_warning", "severity": "urgent", "message": "m"}]"#).is_err());
+    assert!(hooks::parse(r#"[{"action": "set_property", "key": "status", "value": "done"}]"#).is_err());
+    assert!(hooks::parse(r#"[{"action": "send_webhook", "event": "bad event"}]"#).is_err());
+}